@@ -0,0 +1,322 @@
+//! AI provider gateway.
+//!
+//! One place for editor features ("summarize note", rewrite, ...) to reach a
+//! language model. Endpoints and models are kept in `.settings.dat`
+//! (`ai_providers`), API keys in secure storage. `ai_complete` streams tokens
+//! to the frontend as `ai-completion-chunk` events and finishes with
+//! `ai-completion-done`; the full text is also returned.
+//!
+//! Connection failures, 429s and 5xx responses are retried with backoff until
+//! the first token arrives. A stream that goes quiet for too long is aborted.
+
+pub mod providers;
+
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use providers::{Event, Prompt, ProviderKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreBuilder;
+
+const SETTINGS_FILE: &str = ".settings.dat";
+const PROVIDERS_KEY: &str = "ai_providers";
+const CHUNK_EVENT: &str = "ai-completion-chunk";
+const DONE_EVENT: &str = "ai-completion-done";
+const MAX_ATTEMPTS: u32 = 3;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest wait for the response headers or between two streamed chunks
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredProvider {
+    endpoint: Option<String>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub kind: ProviderKind,
+    pub name: &'static str,
+    pub endpoint: String,
+    pub model: String,
+    pub has_key: bool,
+    /// Usable for completions: has a key, or doesn't need one
+    pub ready: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompletionOptions {
+    /// Defaults to the first ready provider
+    pub provider: Option<ProviderKind>,
+    pub model: Option<String>,
+    pub system: Option<String>,
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    /// Tags the streamed events and allows `ai_cancel`; generated if missing
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionChunk {
+    pub request_id: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionDone {
+    pub request_id: String,
+    pub text: String,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionResult {
+    pub request_id: String,
+    pub provider: ProviderKind,
+    pub model: String,
+    pub text: String,
+    pub cancelled: bool,
+}
+
+/// Keys read from secure storage, which is slow to open
+static KEYS: Lazy<Mutex<HashMap<ProviderKind, Option<String>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static CANCELLED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn key_name(kind: ProviderKind) -> String {
+    format!("ai-provider-key-{:?}", kind).to_lowercase()
+}
+
+fn load_key(kind: ProviderKind) -> Option<String> {
+    let mut keys = KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    keys.entry(kind)
+        .or_insert_with(|| {
+            crate::secure_storage::SecureStorage::new()
+                .ok()
+                .and_then(|storage| storage.retrieve::<String>(&key_name(kind)).ok().flatten())
+        })
+        .clone()
+}
+
+fn load_providers(app: &AppHandle) -> HashMap<ProviderKind, StoredProvider> {
+    let Ok(store) = StoreBuilder::new(app, PathBuf::from(SETTINGS_FILE)).build() else {
+        return HashMap::new();
+    };
+    let _ = store.reload();
+    store
+        .get(PROVIDERS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_providers(app: &AppHandle, providers: &HashMap<ProviderKind, StoredProvider>) -> Result<(), String> {
+    let store = StoreBuilder::new(app, PathBuf::from(SETTINGS_FILE))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    let value = serde_json::to_value(providers).map_err(|e| e.to_string())?;
+    store.set(PROVIDERS_KEY.to_string(), value);
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+fn provider_info(kind: ProviderKind, stored: Option<&StoredProvider>) -> ProviderInfo {
+    let has_key = load_key(kind).is_some();
+    ProviderInfo {
+        kind,
+        name: kind.name(),
+        endpoint: stored.and_then(|p| p.endpoint.clone()).unwrap_or_else(|| kind.default_endpoint().to_string()),
+        model: stored.and_then(|p| p.model.clone()).unwrap_or_else(|| kind.default_model().to_string()),
+        has_key,
+        ready: has_key || !kind.needs_key(),
+    }
+}
+
+fn is_cancelled(request_id: &str) -> bool {
+    CANCELLED.lock().unwrap_or_else(|e| e.into_inner()).contains(request_id)
+}
+
+/// Send the request, retrying transient failures with exponential backoff
+async fn send(client: &reqwest::Client, request: &providers::Request) -> Result<reqwest::Response, String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut builder = client.post(&request.url).json(&request.body);
+        for (name, value) in &request.headers {
+            builder = builder.header(*name, value);
+        }
+        let retry = match tokio::time::timeout(IDLE_TIMEOUT, builder.send()).await {
+            Ok(Ok(response)) if response.status().is_success() => return Ok(response),
+            Ok(Ok(response)) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                let message = format!("Provider returned {}: {}", status, body.trim());
+                if status.as_u16() != 429 && !status.is_server_error() {
+                    return Err(message);
+                }
+                message
+            }
+            Ok(Err(e)) if e.is_connect() || e.is_timeout() => format!("Could not reach provider: {}", e),
+            Ok(Err(e)) => return Err(format!("AI request failed: {}", e)),
+            Err(_) => "Provider did not respond in time".to_string(),
+        };
+        if attempt >= MAX_ATTEMPTS {
+            return Err(retry);
+        }
+        tracing::warn!(attempt, "AI request failed, retrying: {}", retry);
+        tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+    }
+}
+
+async fn stream_completion(
+    app: &AppHandle,
+    kind: ProviderKind,
+    response: reqwest::Response,
+    request_id: &str,
+) -> Result<(String, bool), String> {
+    let mut stream = response.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        if is_cancelled(request_id) {
+            return Ok((text, true));
+        }
+        let chunk = match tokio::time::timeout(IDLE_TIMEOUT, stream.next()).await {
+            Err(_) => return Err("Provider stopped responding".to_string()),
+            Ok(None) => return Ok((text, false)),
+            Ok(Some(chunk)) => chunk.map_err(|e| format!("Stream read error: {}", e))?,
+        };
+        buffer.extend_from_slice(&chunk);
+
+        // Only decode whole lines so multi-byte characters split across chunks survive
+        while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            match providers::parse_line(kind, &String::from_utf8_lossy(&line)) {
+                Some(Event::Text(delta)) => {
+                    text.push_str(&delta);
+                    let chunk = CompletionChunk { request_id: request_id.to_string(), text: delta };
+                    if let Err(e) = app.emit(CHUNK_EVENT, &chunk) {
+                        tracing::warn!("Failed to emit completion chunk: {}", e);
+                    }
+                }
+                Some(Event::Done) => return Ok((text, false)),
+                Some(Event::Error(message)) => return Err(message),
+                None => {}
+            }
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn ai_list_providers(app: AppHandle) -> Vec<ProviderInfo> {
+    let stored = load_providers(&app);
+    providers::ALL.iter().map(|kind| provider_info(*kind, stored.get(kind))).collect()
+}
+
+/// Set a provider's endpoint, API key and default model. `None` leaves a
+/// value unchanged; an empty string resets it (removing the key).
+#[tauri::command]
+pub fn ai_configure_provider(
+    app: AppHandle,
+    kind: ProviderKind,
+    endpoint: Option<String>,
+    key: Option<String>,
+    model: Option<String>,
+) -> Result<ProviderInfo, String> {
+    if let Some(key) = key {
+        let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+        let key = key.trim().to_string();
+        let saved = if key.is_empty() { storage.delete(&key_name(kind)) } else { storage.store(&key_name(kind), &key) };
+        saved.map_err(|e| format!("Failed to save API key: {}", e))?;
+        KEYS.lock().unwrap_or_else(|e| e.into_inner()).insert(kind, Some(key).filter(|k| !k.is_empty()));
+    }
+
+    let mut stored = load_providers(&app);
+    let entry = stored.entry(kind).or_default();
+    if let Some(endpoint) = endpoint {
+        let endpoint = endpoint.trim().trim_end_matches('/').to_string();
+        if !endpoint.is_empty() && !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            return Err("Endpoint must be an http(s) URL".to_string());
+        }
+        entry.endpoint = Some(endpoint).filter(|e| !e.is_empty());
+    }
+    if let Some(model) = model {
+        entry.model = Some(model.trim().to_string()).filter(|m| !m.is_empty());
+    }
+    save_providers(&app, &stored)?;
+    Ok(provider_info(kind, stored.get(&kind)))
+}
+
+/// Run a completion, streaming tokens as they arrive
+#[tauri::command]
+pub async fn ai_complete(app: AppHandle, prompt: String, options: Option<CompletionOptions>) -> Result<CompletionResult, String> {
+    let options = options.unwrap_or_default();
+    let stored = load_providers(&app);
+    let info = match options.provider {
+        Some(kind) => provider_info(kind, stored.get(&kind)),
+        None => providers::ALL
+            .iter()
+            .map(|kind| provider_info(*kind, stored.get(kind)))
+            .find(|info| info.ready)
+            .ok_or("No AI provider is configured")?,
+    };
+    if !info.ready {
+        return Err(format!("Add an API key for {} first", info.name));
+    }
+
+    let model = options.model.clone().filter(|m| !m.trim().is_empty()).unwrap_or(info.model.clone());
+    let request_id = options.request_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let key = load_key(info.kind);
+    let request = providers::build_request(
+        info.kind,
+        &info.endpoint,
+        key.as_deref(),
+        &Prompt {
+            model: &model,
+            system: options.system.as_deref(),
+            prompt: &prompt,
+            max_tokens: options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            temperature: options.temperature.unwrap_or(0.3),
+        },
+    );
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let outcome = match send(&client, &request).await {
+        Ok(response) => stream_completion(&app, info.kind, response, &request_id).await,
+        Err(e) => Err(e),
+    };
+    CANCELLED.lock().unwrap_or_else(|e| e.into_inner()).remove(&request_id);
+
+    let done = match &outcome {
+        Ok((text, cancelled)) => CompletionDone { request_id: request_id.clone(), text: text.clone(), cancelled: *cancelled, error: None },
+        Err(e) => CompletionDone { request_id: request_id.clone(), text: String::new(), cancelled: false, error: Some(e.clone()) },
+    };
+    if let Err(e) = app.emit(DONE_EVENT, &done) {
+        tracing::warn!("Failed to emit completion end: {}", e);
+    }
+
+    let (text, cancelled) = outcome?;
+    Ok(CompletionResult { request_id, provider: info.kind, model, text, cancelled })
+}
+
+/// Stop a running completion; the text so far is kept
+#[tauri::command]
+pub fn ai_cancel(request_id: String) {
+    CANCELLED.lock().unwrap_or_else(|e| e.into_inner()).insert(request_id);
+}
@@ -0,0 +1,191 @@
+//! Request building and stream parsing for each provider's wire format.
+//!
+//! OpenAI-compatible servers and Anthropic stream server-sent events
+//! (`data: {...}` lines); Ollama streams one JSON object per line.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+pub const ALL: [ProviderKind; 3] = [ProviderKind::OpenAi, ProviderKind::Anthropic, ProviderKind::Ollama];
+
+impl ProviderKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "OpenAI-compatible",
+            ProviderKind::Anthropic => "Anthropic",
+            ProviderKind::Ollama => "Ollama",
+        }
+    }
+
+    pub fn default_endpoint(self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "https://api.openai.com/v1",
+            ProviderKind::Anthropic => "https://api.anthropic.com",
+            ProviderKind::Ollama => "http://localhost:11434",
+        }
+    }
+
+    pub fn default_model(self) -> &'static str {
+        match self {
+            ProviderKind::OpenAi => "gpt-4o-mini",
+            ProviderKind::Anthropic => "claude-3-5-haiku-latest",
+            ProviderKind::Ollama => "llama3.2",
+        }
+    }
+
+    /// Ollama runs locally without a key
+    pub fn needs_key(self) -> bool {
+        self != ProviderKind::Ollama
+    }
+}
+
+pub struct Request {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Value,
+}
+
+pub struct Prompt<'a> {
+    pub model: &'a str,
+    pub system: Option<&'a str>,
+    pub prompt: &'a str,
+    pub max_tokens: u32,
+    pub temperature: f32,
+}
+
+pub fn build_request(kind: ProviderKind, endpoint: &str, key: Option<&str>, prompt: &Prompt) -> Request {
+    let endpoint = endpoint.trim_end_matches('/');
+    let mut headers = Vec::new();
+    match kind {
+        ProviderKind::OpenAi => {
+            if let Some(key) = key {
+                headers.push(("Authorization", format!("Bearer {}", key)));
+            }
+            let mut messages = Vec::new();
+            if let Some(system) = prompt.system {
+                messages.push(json!({ "role": "system", "content": system }));
+            }
+            messages.push(json!({ "role": "user", "content": prompt.prompt }));
+            Request {
+                url: format!("{}/chat/completions", endpoint),
+                headers,
+                body: json!({
+                    "model": prompt.model,
+                    "messages": messages,
+                    "max_tokens": prompt.max_tokens,
+                    "temperature": prompt.temperature,
+                    "stream": true,
+                }),
+            }
+        }
+        ProviderKind::Anthropic => {
+            if let Some(key) = key {
+                headers.push(("x-api-key", key.to_string()));
+            }
+            headers.push(("anthropic-version", "2023-06-01".to_string()));
+            let mut body = json!({
+                "model": prompt.model,
+                "messages": [{ "role": "user", "content": prompt.prompt }],
+                "max_tokens": prompt.max_tokens,
+                "temperature": prompt.temperature,
+                "stream": true,
+            });
+            if let Some(system) = prompt.system {
+                body["system"] = json!(system);
+            }
+            Request { url: format!("{}/v1/messages", endpoint), headers, body }
+        }
+        ProviderKind::Ollama => {
+            let mut messages = Vec::new();
+            if let Some(system) = prompt.system {
+                messages.push(json!({ "role": "system", "content": system }));
+            }
+            messages.push(json!({ "role": "user", "content": prompt.prompt }));
+            Request {
+                url: format!("{}/api/chat", endpoint),
+                headers,
+                body: json!({
+                    "model": prompt.model,
+                    "messages": messages,
+                    "stream": true,
+                    "options": { "temperature": prompt.temperature, "num_predict": prompt.max_tokens },
+                }),
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    Text(String),
+    Done,
+    Error(String),
+}
+
+/// Interpret one line of a streamed response; `None` for lines that carry
+/// nothing (keep-alives, event names, metadata)
+pub fn parse_line(kind: ProviderKind, line: &str) -> Option<Event> {
+    let line = line.trim();
+    let data = match kind {
+        ProviderKind::Ollama => line,
+        _ => line.strip_prefix("data:")?.trim_start(),
+    };
+    if data.is_empty() {
+        return None;
+    }
+    if data == "[DONE]" {
+        return Some(Event::Done);
+    }
+    let json: Value = serde_json::from_str(data).ok()?;
+    if let Some(error) = json.get("error") {
+        let message = error.get("message").and_then(Value::as_str).or_else(|| error.as_str()).unwrap_or("Unknown error");
+        return Some(Event::Error(message.to_string()));
+    }
+
+    let text = match kind {
+        ProviderKind::OpenAi => json.pointer("/choices/0/delta/content").and_then(Value::as_str),
+        ProviderKind::Anthropic => match json.get("type").and_then(Value::as_str) {
+            Some("content_block_delta") => json.pointer("/delta/text").and_then(Value::as_str),
+            Some("message_stop") => return Some(Event::Done),
+            _ => None,
+        },
+        ProviderKind::Ollama => {
+            if json.get("done").and_then(Value::as_bool) == Some(true) {
+                return Some(Event::Done);
+            }
+            json.pointer("/message/content").and_then(Value::as_str)
+        }
+    };
+    text.filter(|t| !t.is_empty()).map(|t| Event::Text(t.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stream_lines() {
+        let openai = r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(parse_line(ProviderKind::OpenAi, openai), Some(Event::Text("Hel".into())));
+        assert_eq!(parse_line(ProviderKind::OpenAi, "data: [DONE]"), Some(Event::Done));
+        assert_eq!(parse_line(ProviderKind::OpenAi, ": keep-alive"), None);
+
+        let anthropic = r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#;
+        assert_eq!(parse_line(ProviderKind::Anthropic, anthropic), Some(Event::Text("lo".into())));
+        assert_eq!(parse_line(ProviderKind::Anthropic, "event: ping"), None);
+        let overloaded = r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert_eq!(parse_line(ProviderKind::Anthropic, overloaded), Some(Event::Error("Overloaded".into())));
+
+        let ollama = r#"{"message":{"role":"assistant","content":"!"},"done":false}"#;
+        assert_eq!(parse_line(ProviderKind::Ollama, ollama), Some(Event::Text("!".into())));
+        assert_eq!(parse_line(ProviderKind::Ollama, r#"{"done":true}"#), Some(Event::Done));
+    }
+}
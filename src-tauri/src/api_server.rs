@@ -1,14 +1,18 @@
 /// HTTP API Server for MCP Integration
 /// Provides REST endpoints for MCP to interact with Lokus
+pub mod publish;
+pub mod rest;
+
 use axum::{
-    extract::State,
+    extract::{Path as UrlPath, State},
     http::StatusCode,
     response::Json,
-    routing::get,
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
 use std::path::PathBuf;
 use tokio::sync::{RwLock, Notify};
 use tokio::time::Duration;
@@ -16,6 +20,16 @@ use tauri::{Emitter, Manager};
 use tauri_plugin_store::StoreBuilder;
 use thiserror::Error;
 
+// Port the server bound to, 0 until it starts
+static SERVER_PORT: AtomicU16 = AtomicU16::new(0);
+
+pub fn server_port() -> Option<u16> {
+    match SERVER_PORT.load(Ordering::Relaxed) {
+        0 => None,
+        port => Some(port),
+    }
+}
+
 #[derive(Clone)]
 pub struct ApiState {
     pub app_handle: tauri::AppHandle,
@@ -173,6 +187,34 @@ pub struct TaskInfo {
     pub created_at: i64,
 }
 
+// List the vault tools MCP clients may use (disabled tools are left out)
+pub async fn list_mcp_tools(
+    State(state): State<ApiState>,
+) -> Json<ApiResponse<Vec<crate::mcp::tools::ToolDefinition>>> {
+    Json(ApiResponse {
+        success: true,
+        data: Some(crate::mcp::tools::enabled_definitions(&state.app_handle)),
+        error: None,
+    })
+}
+
+// Run a vault tool against the current workspace
+pub async fn call_mcp_tool(
+    State(state): State<ApiState>,
+    UrlPath(name): UrlPath<String>,
+    Json(arguments): Json<serde_json::Value>,
+) -> Json<ApiResponse<serde_json::Value>> {
+    let workspace = state.current_workspace.read().await.clone();
+    let result = match workspace {
+        Some(path) => crate::mcp::tools::call(&state.app_handle, std::path::Path::new(&path), &name, arguments).await,
+        None => Err("No workspace open".to_string()),
+    };
+    Json(match result {
+        Ok(data) => ApiResponse { success: true, data: Some(data), error: None },
+        Err(e) => ApiResponse { success: false, data: None, error: Some(e) },
+    })
+}
+
 // Helper functions
 async fn count_notes(workspace: &str) -> usize {
     // Count .md files in workspace
@@ -281,7 +323,13 @@ pub fn create_api_router(state: ApiState) -> Router {
         .route("/api/workspaces/all", get(get_all_workspaces))
         .route("/api/notes", get(list_notes))
         .route("/api/tasks", get(get_tasks))
+        .route("/api/mcp/tools", get(list_mcp_tools))
+        .route("/api/mcp/tools/:name", post(call_mcp_tool))
+        .route("/api/mcp/resources", get(crate::mcp::resources::stream_resource))
+        .route("/api/mcp/resources/read", get(crate::mcp::resources::read_resource_chunk))
         .route("/api/health", get(|| async { "OK" }))
+        .merge(publish::routes())
+        .merge(rest::routes())
         .with_state(state)
 }
 
@@ -345,6 +393,7 @@ async fn try_start_server(app_handle: &tauri::AppHandle) -> Result<u16, ApiServe
         match tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port)).await {
             Ok(listener) => {
                 tracing::info!(port, "Successfully bound to port");
+                SERVER_PORT.store(port, Ordering::Relaxed);
 
                 // Spawn server in background
                 tokio::spawn(async move {
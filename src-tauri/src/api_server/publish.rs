@@ -0,0 +1,465 @@
+//! Publishing notes as static HTML pages served by the API server.
+//!
+//! `publish_note` renders a note with the chosen theme and stores the page,
+//! along with copies of the images it shows, under
+//! `.lokus/published/<token>/`. The token stays the same when a note is
+//! republished, so a shared link keeps working after edits. Pages are served
+//! at `/p/<token>` by the local API server and, when requested, by a second
+//! listener bound to all interfaces so other machines on the LAN can open
+//! them. That listener serves published pages only, never the rest of the API.
+
+use super::{server_port, ApiState};
+use axum::{
+    extract::{Path as UrlPath, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Router,
+};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const PUBLISHED_DIR: &str = ".lokus/published";
+const INDEX_FILE: &str = "index.json";
+const PAGE_FILE: &str = "index.html";
+const ASSETS_DIR: &str = "assets";
+/// Ports tried for the LAN listener, after the API server's 3333-3336
+const LAN_PORTS: std::ops::RangeInclusive<u16> = 3340..=3343;
+
+static LAN_PORT: Lazy<tokio::sync::Mutex<Option<u16>>> = Lazy::new(|| tokio::sync::Mutex::new(None));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishOptions {
+    /// Page title; defaults to the note name
+    pub title: Option<String>,
+    /// Id of an installed theme whose colours should be used
+    pub theme: Option<String>,
+    /// Theme tokens (`--bg`, `--text`, ...) passed directly, e.g. the active theme
+    pub theme_tokens: Option<HashMap<String, String>>,
+    /// Also serve the page to other machines on the local network
+    #[serde(default)]
+    pub lan: bool,
+    /// Public base URL of a tunnel (e.g. `https://abc.trycloudflare.com`)
+    /// forwarding to the local API server
+    pub public_base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishedEntry {
+    /// Note path relative to the workspace root
+    path: String,
+    title: String,
+    published_at: i64,
+    updated_at: i64,
+    #[serde(default)]
+    lan: bool,
+    public_base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedNote {
+    pub token: String,
+    pub path: String,
+    pub title: String,
+    pub published_at: i64,
+    pub updated_at: i64,
+    /// Link on this machine; `None` while the API server isn't running
+    pub local_url: Option<String>,
+    /// Link for other machines on the LAN
+    pub lan_url: Option<String>,
+    /// Link through the configured tunnel
+    pub public_url: Option<String>,
+    /// Best link to hand out: tunnel, then LAN, then local
+    pub url: Option<String>,
+}
+
+// --- Storage ---
+
+fn published_dir(root: &Path) -> PathBuf {
+    root.join(PUBLISHED_DIR)
+}
+
+fn load_index(root: &Path) -> BTreeMap<String, PublishedEntry> {
+    fs::read_to_string(published_dir(root).join(INDEX_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(root: &Path, index: &BTreeMap<String, PublishedEntry>) -> Result<(), String> {
+    let dir = published_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create publish folder: {}", e))?;
+    let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(dir.join(INDEX_FILE), json).map_err(|e| format!("Failed to save published notes: {}", e))
+}
+
+/// Tokens are generated hex strings; anything else never names a page
+fn valid_token(token: &str) -> bool {
+    !token.is_empty() && token.len() <= 64 && token.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn note_context(path: &str) -> Result<(PathBuf, String), String> {
+    let abs = Path::new(path);
+    let root = crate::export::find_workspace_root(abs).ok_or("Note is not inside a workspace")?;
+    let rel = crate::links::relative_path(&root, abs).ok_or("Note is not inside a workspace")?;
+    if !crate::links::is_note(&rel) {
+        return Err(format!("Only markdown notes can be published: {}", rel));
+    }
+    Ok((root, rel))
+}
+
+// --- Rendering ---
+
+/// Theme tokens hold `#rrggbb` or `r g b`; both become valid CSS colours
+fn css_color(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return Some(value.to_string());
+    }
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    (parts.len() == 3 && parts.iter().all(|p| p.parse::<u8>().is_ok())).then(|| format!("rgb({})", parts.join(" ")))
+}
+
+fn theme_css(options: &PublishOptions) -> Result<String, String> {
+    let tokens = match (&options.theme_tokens, &options.theme) {
+        (Some(tokens), _) => tokens.clone(),
+        (None, Some(theme)) => crate::theme::get_theme_tokens(theme.clone())?,
+        (None, None) => HashMap::new(),
+    };
+    let defaults = [
+        ("--bg", "#ffffff"),
+        ("--text", "#1f2328"),
+        ("--accent", "#0969da"),
+        ("--muted", "#6e7781"),
+        ("--panel", "#f6f8fa"),
+        ("--border", "#d0d7de"),
+    ];
+    let vars: String = defaults
+        .iter()
+        .map(|(token, fallback)| {
+            let color = tokens.get(*token).and_then(|v| css_color(v)).unwrap_or_else(|| fallback.to_string());
+            format!("{}:{};", token, color)
+        })
+        .collect();
+    Ok(format!(
+        ":root{{{}}}\
+body{{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;max-width:46rem;margin:2rem auto;padding:0 1rem;line-height:1.6;background:var(--bg);color:var(--text)}}\
+a{{color:var(--accent)}}pre{{background:var(--panel);padding:.75rem;overflow:auto}}code{{font-size:.9em}}\
+img{{max-width:100%}}table{{border-collapse:collapse}}td,th{{border:1px solid var(--border);padding:.25rem .5rem}}\
+blockquote{{border-left:3px solid var(--border);margin-left:0;padding-left:1rem;color:var(--muted)}}\
+footer{{border-top:1px solid var(--border);margin-top:2rem;padding-top:.5rem;font-size:.8em;color:var(--muted)}}",
+        vars
+    ))
+}
+
+/// Render the note body, copying local images into `assets/` next to the
+/// page. Wikilinks become plain text: the notes they point at aren't shared.
+fn render_body(root: &Path, rel: &str, markdown: &str, page_dir: &Path, token: &str) -> Result<String, String> {
+    let note_dir = root.join(crate::links::parent_dir(rel));
+    let markdown = crate::export::flatten_wikilinks(crate::export::strip_frontmatter(markdown), |_, _, _| None);
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    let assets = page_dir.join(ASSETS_DIR);
+    let mut copied: HashMap<PathBuf, String> = HashMap::new();
+    let mut events = Vec::new();
+    for event in Parser::new_ext(&markdown, options) {
+        let event = match event {
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                let dest_url = match crate::export::resolve_local_asset(&note_dir, Some(root), &dest_url) {
+                    Some(source) => {
+                        let name = match copied.get(&source) {
+                            Some(name) => name.clone(),
+                            None => {
+                                let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("bin");
+                                let name = format!("{}.{}", copied.len() + 1, ext.to_lowercase());
+                                fs::create_dir_all(&assets).map_err(|e| format!("Failed to create assets folder: {}", e))?;
+                                fs::copy(&source, assets.join(&name))
+                                    .map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+                                copied.insert(source, name.clone());
+                                name
+                            }
+                        };
+                        CowStr::from(format!("/p/{}/{}/{}", token, ASSETS_DIR, name))
+                    }
+                    None => dest_url,
+                };
+                Event::Start(Tag::Image { link_type, dest_url, title, id })
+            }
+            other => other,
+        };
+        events.push(event);
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    // Raw HTML in the note would run for anyone who opens the link
+    let config = crate::sanitize::PolicyConfig { allow_classes: true, ..Default::default() };
+    Ok(crate::sanitize::clean(&out, &config))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn page_html(title: &str, css: &str, body: &str, updated_at: i64) -> String {
+    let updated = chrono::DateTime::from_timestamp_millis(updated_at)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<meta name=\"robots\" content=\"noindex\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n<h1>{}</h1>\n{}</main>\n<footer>Published with Lokus · updated {}</footer>\n</body>\n</html>\n",
+        escape_html(title),
+        css,
+        escape_html(title),
+        body,
+        updated
+    )
+}
+
+fn publish(root: &Path, rel: &str, options: &PublishOptions) -> Result<(String, PublishedEntry), String> {
+    let content = fs::read_to_string(root.join(rel)).map_err(|e| format!("Failed to read note: {}", e))?;
+    let mut index = load_index(root);
+    let now = chrono::Utc::now().timestamp_millis();
+
+    // Republishing keeps the note's existing link
+    let existing = index.iter().find(|(_, entry)| entry.path == rel).map(|(token, _)| token.clone());
+    let token = existing.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+    let published_at = index.get(&token).map_or(now, |entry| entry.published_at);
+    let title = options
+        .title
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| {
+            let name = rel.rsplit('/').next().unwrap_or(rel);
+            name.strip_suffix(".md").unwrap_or(name).to_string()
+        });
+
+    // Render into a fresh folder so assets removed from the note disappear
+    let page_dir = published_dir(root).join(&token);
+    let staging = published_dir(root).join(format!("{}.tmp", token));
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|e| format!("Failed to create publish folder: {}", e))?;
+    let rendered = render_body(root, rel, &content, &staging, &token)
+        .and_then(|body| Ok(page_html(&title, &theme_css(options)?, &body, now)))
+        .and_then(|page| fs::write(staging.join(PAGE_FILE), page).map_err(|e| format!("Failed to write page: {}", e)));
+    if let Err(e) = rendered {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    let _ = fs::remove_dir_all(&page_dir);
+    fs::rename(&staging, &page_dir).map_err(|e| format!("Failed to publish note: {}", e))?;
+
+    let entry = PublishedEntry {
+        path: rel.to_string(),
+        title,
+        published_at,
+        updated_at: now,
+        lan: options.lan,
+        public_base_url: options
+            .public_base_url
+            .as_deref()
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty()),
+    };
+    index.insert(token.clone(), entry.clone());
+    save_index(root, &index)?;
+    Ok((token, entry))
+}
+
+/// Remove the published page for `rel`; returns whether it was published
+fn unpublish(root: &Path, rel: &str) -> Result<bool, String> {
+    let mut index = load_index(root);
+    let tokens: Vec<String> = index.iter().filter(|(_, e)| e.path == rel).map(|(t, _)| t.clone()).collect();
+    for token in &tokens {
+        index.remove(token);
+        let _ = fs::remove_dir_all(published_dir(root).join(token));
+    }
+    if !tokens.is_empty() {
+        save_index(root, &index)?;
+    }
+    Ok(!tokens.is_empty())
+}
+
+// --- Serving ---
+
+/// Address other machines on the LAN can reach this one at. Connecting a UDP
+/// socket sends nothing; it only picks the outgoing interface.
+fn lan_address() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip()).filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+}
+
+/// Start the LAN listener if it isn't running yet, returning its port
+async fn ensure_lan_listener(app: &AppHandle) -> Result<u16, String> {
+    let mut port = LAN_PORT.lock().await;
+    if let Some(port) = *port {
+        return Ok(port);
+    }
+    let state = app.try_state::<ApiState>().ok_or("API server is not running")?.inner().clone();
+    for candidate in LAN_PORTS {
+        let Ok(listener) = tokio::net::TcpListener::bind(("0.0.0.0", candidate)).await else {
+            continue;
+        };
+        let router = routes().with_state(state);
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::error!(error = %e, "Publish listener error");
+            }
+        });
+        tracing::info!(port = candidate, "Serving published notes on the local network");
+        *port = Some(candidate);
+        return Ok(candidate);
+    }
+    Err(format!("No ports available in range {}-{}", LAN_PORTS.start(), LAN_PORTS.end()))
+}
+
+fn to_published(token: String, entry: PublishedEntry, lan_port: Option<u16>) -> PublishedNote {
+    let page = format!("/p/{}", token);
+    let local_url = server_port().map(|port| format!("http://127.0.0.1:{}{}", port, page));
+    let lan_url = lan_port
+        .filter(|_| entry.lan)
+        .and_then(|port| Some(format!("http://{}:{}{}", lan_address()?, port, page)));
+    let public_url = entry.public_base_url.as_ref().map(|base| format!("{}{}", base, page));
+    PublishedNote {
+        url: public_url.clone().or_else(|| lan_url.clone()).or_else(|| local_url.clone()),
+        token,
+        path: entry.path,
+        title: entry.title,
+        published_at: entry.published_at,
+        updated_at: entry.updated_at,
+        local_url,
+        lan_url,
+        public_url,
+    }
+}
+
+async fn published_file(state: &ApiState, token: &str, file: &str) -> Option<PathBuf> {
+    if !valid_token(token) {
+        return None;
+    }
+    let workspace = state.current_workspace.read().await.clone()?;
+    let path = published_dir(Path::new(&workspace)).join(token).join(file);
+    path.is_file().then_some(path)
+}
+
+async fn serve_page(State(state): State<ApiState>, UrlPath(token): UrlPath<String>) -> Response {
+    match published_file(&state, &token, PAGE_FILE).await {
+        Some(path) => match tokio::fs::read(&path).await {
+            Ok(bytes) => ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], bytes).into_response(),
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        },
+        None => (StatusCode::NOT_FOUND, "This note is not published").into_response(),
+    }
+}
+
+async fn serve_asset(State(state): State<ApiState>, UrlPath((token, file)): UrlPath<(String, String)>) -> Response {
+    if file.contains("..") || file.contains('\\') {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Some(path) = published_file(&state, &token, &format!("{}/{}", ASSETS_DIR, file)).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => ([(header::CONTENT_TYPE, crate::export::workspace::media_type(&file))], bytes).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Routes for published pages, shared by the API server and the LAN listener
+pub fn routes() -> Router<ApiState> {
+    Router::new()
+        .route("/p/:token", get(serve_page))
+        .route("/p/:token/", get(|UrlPath(token): UrlPath<String>| async move { Redirect::permanent(&format!("/p/{}", token)) }))
+        .route("/p/:token/assets/:file", get(serve_asset))
+}
+
+// --- Tauri Commands ---
+
+/// Render a note to HTML and serve it at a stable `/p/<token>` link.
+#[tauri::command]
+pub async fn publish_note(app: AppHandle, path: String, options: Option<PublishOptions>) -> Result<PublishedNote, String> {
+    let options = options.unwrap_or_default();
+    let (root, rel) = note_context(&path)?;
+    let lan_port = if options.lan { Some(ensure_lan_listener(&app).await?) } else { *LAN_PORT.lock().await };
+
+    let (token, entry) = tokio::task::spawn_blocking(move || publish(&root, &rel, &options))
+        .await
+        .map_err(|e| format!("Publish task failed: {}", e))??;
+    Ok(to_published(token, entry, lan_port))
+}
+
+#[tauri::command]
+pub async fn unpublish_note(path: String) -> Result<bool, String> {
+    let (root, rel) = note_context(&path)?;
+    unpublish(&root, &rel)
+}
+
+/// Published notes of the given workspace, or the API server's current one
+#[tauri::command]
+pub async fn list_published(app: AppHandle, workspace_path: Option<String>) -> Result<Vec<PublishedNote>, String> {
+    let workspace = match workspace_path {
+        Some(path) => path,
+        None => {
+            let state = app.try_state::<ApiState>().ok_or("API server is not running")?;
+            let current = state.current_workspace.read().await.clone();
+            current.ok_or("No workspace open")?
+        }
+    };
+    let index = load_index(Path::new(&workspace));
+    // Shared links should keep working after a restart
+    let lan_port = if index.values().any(|e| e.lan) { ensure_lan_listener(&app).await.ok() } else { None };
+    Ok(index.into_iter().map(|(token, entry)| to_published(token, entry, lan_port)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_keeps_token_and_copies_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".lokus")).unwrap();
+        fs::write(root.join("pic.png"), b"png").unwrap();
+        fs::write(
+            root.join("Note.md"),
+            "---\ntags: [a]\n---\nHello ![[pic.png]] and [[Other]]\n\n<img src=x onerror=alert(1)><script>alert(2)</script>\n",
+        )
+        .unwrap();
+
+        let options = PublishOptions { theme_tokens: Some(HashMap::from([("--bg".to_string(), "0 0 0".to_string())])), ..Default::default() };
+        let (token, entry) = publish(root, "Note.md", &options).unwrap();
+        assert!(valid_token(&token));
+        assert_eq!(entry.title, "Note");
+
+        let page = fs::read_to_string(published_dir(root).join(&token).join(PAGE_FILE)).unwrap();
+        assert!(page.contains("--bg:rgb(0 0 0);"));
+        assert!(page.contains(&format!("/p/{}/assets/1.png", token)));
+        assert!(page.contains("and Other"));
+        assert!(!page.contains("tags:"));
+        assert!(!page.contains("onerror") && !page.contains("<script>"));
+        assert!(published_dir(root).join(&token).join("assets/1.png").is_file());
+
+        let (again, _) = publish(root, "Note.md", &PublishOptions::default()).unwrap();
+        assert_eq!(again, token);
+        assert!(unpublish(root, "Note.md").unwrap());
+        assert!(!published_dir(root).join(&token).exists());
+        assert!(load_index(root).is_empty());
+    }
+}
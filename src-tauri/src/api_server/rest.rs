@@ -0,0 +1,436 @@
+//! Authenticated REST API for scripts and external tools.
+//!
+//! Routes live under `/api/v1` and require an API key, sent as
+//! `Authorization: Bearer <key>` or `X-API-Key: <key>`. Keys are created with
+//! `api_keys_create`; only a SHA-256 hash of each key is kept, in
+//! `secure_storage`, and the key itself is shown once at creation.
+//!
+//! Note paths in URLs are relative to the workspace open in the app, e.g.
+//! `GET /api/v1/notes/Projects/Plan.md`. Writes go through the same command
+//! the editor uses, so encryption, indexing and version history still apply.
+//! The browser extension posts pages to `POST /api/v1/clip` (see `clipper`),
+//! and chat bots forward messages to `POST /api/v1/capture/message` (see
+//! `connections::messaging`).
+
+use super::{ApiResponse, ApiState};
+use axum::{
+    extract::{Path as UrlPath, Query, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+const STORAGE_KEY: &str = "api-keys";
+const KEY_PREFIX: &str = "lokus_";
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+/// Clipped pages are posted whole, so allow more than axum's 2 MB default
+const CLIP_BODY_LIMIT: usize = 32 * 1024 * 1024;
+
+/// Keys as stored; `None` until first loaded from secure storage
+static KEYS: Lazy<Mutex<Option<Vec<StoredKey>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredKey {
+    id: String,
+    name: String,
+    /// First characters of the key, so users can tell keys apart
+    prefix: String,
+    /// Hex SHA-256 of the full key
+    hash: String,
+    created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub info: ApiKeyInfo,
+    /// The key itself; it can't be retrieved again
+    pub key: String,
+}
+
+impl From<&StoredKey> for ApiKeyInfo {
+    fn from(key: &StoredKey) -> Self {
+        Self { id: key.id.clone(), name: key.name.clone(), prefix: key.prefix.clone(), created_at: key.created_at }
+    }
+}
+
+// --- Key storage ---
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn storage() -> Result<crate::secure_storage::SecureStorage, String> {
+    crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))
+}
+
+/// Run `f` on the key list, loading it on first use and saving it if `f`
+/// reports a change. Decrypting secure storage is slow, so the list is cached.
+fn with_keys<T>(f: impl FnOnce(&mut Vec<StoredKey>) -> (T, bool)) -> Result<T, String> {
+    let mut cached = KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    if cached.is_none() {
+        let stored: Option<Vec<StoredKey>> =
+            storage()?.retrieve(STORAGE_KEY).map_err(|e| format!("Failed to load API keys: {}", e))?;
+        *cached = Some(stored.unwrap_or_default());
+    }
+    let keys = cached.as_mut().expect("loaded above");
+    let (result, changed) = f(keys);
+    if changed {
+        storage()?.store(STORAGE_KEY, keys).map_err(|e| format!("Failed to save API keys: {}", e))?;
+    }
+    Ok(result)
+}
+
+fn create_key(name: &str) -> Result<CreatedApiKey, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("API key name cannot be empty".to_string());
+    }
+    let key = format!("{}{}", KEY_PREFIX, uuid::Uuid::new_v4().simple());
+    let stored = StoredKey {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        prefix: key[..KEY_PREFIX.len() + 6].to_string(),
+        hash: hash_key(&key),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let info = ApiKeyInfo::from(&stored);
+    with_keys(|keys| {
+        keys.push(stored);
+        ((), true)
+    })?;
+    Ok(CreatedApiKey { info, key })
+}
+
+fn verify_key(key: &str) -> bool {
+    let hash = hash_key(key);
+    with_keys(|keys| (keys.iter().any(|k| k.hash == hash), false)).unwrap_or(false)
+}
+
+// --- Request helpers ---
+
+type ApiError = (StatusCode, Json<ApiResponse<()>>);
+type ApiResult<T> = Result<Json<ApiResponse<T>>, ApiError>;
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(ApiResponse { success: false, data: None, error: Some(message.into()) }))
+}
+
+fn ok<T>(data: T) -> ApiResult<T> {
+    Ok(Json(ApiResponse { success: true, data: Some(data), error: None }))
+}
+
+fn fail<T>(status: StatusCode, message: impl Into<String>) -> ApiResult<T> {
+    Err(error(status, message))
+}
+
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    bearer.or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok())).map(str::trim)
+}
+
+async fn require_api_key(headers: HeaderMap, request: Request, next: Next) -> Response {
+    let Some(key) = presented_key(&headers).map(str::to_string) else {
+        return error(StatusCode::UNAUTHORIZED, "Missing API key").into_response();
+    };
+    let valid = tokio::task::spawn_blocking(move || verify_key(&key)).await.unwrap_or(false);
+    if !valid {
+        return error(StatusCode::UNAUTHORIZED, "Invalid API key").into_response();
+    }
+    next.run(request).await
+}
+
+async fn workspace(state: &ApiState) -> Result<PathBuf, ApiError> {
+    let current = state.current_workspace.read().await;
+    current.as_ref().map(PathBuf::from).ok_or_else(|| error(StatusCode::SERVICE_UNAVAILABLE, "No workspace open"))
+}
+
+/// Map a URL note path into the workspace, refusing anything that could
+/// escape it or touch Lokus' own data
+fn note_path(root: &Path, rel: &str) -> Result<PathBuf, ApiError> {
+    let rel = rel.trim_matches('/');
+    let relative = Path::new(rel);
+    let safe = !rel.is_empty()
+        && relative.components().all(|c| matches!(c, Component::Normal(_)))
+        && !relative.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if !safe {
+        return Err(error(StatusCode::BAD_REQUEST, format!("Invalid note path: {}", rel)));
+    }
+    if !crate::links::is_note(rel) {
+        return Err(error(StatusCode::BAD_REQUEST, "Only markdown notes are available over the API"));
+    }
+    Ok(root.join(relative))
+}
+
+fn modified_ms(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+// --- Handlers ---
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSummary {
+    pub path: String,
+    pub modified: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteContent {
+    pub path: String,
+    pub content: String,
+    pub modified: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NoteBody {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+async fn list_notes(State(state): State<ApiState>) -> ApiResult<Vec<NoteSummary>> {
+    let root = workspace(&state).await?;
+    let notes = tokio::task::spawn_blocking(move || {
+        let mut notes: Vec<NoteSummary> = WalkDir::new(&root)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| {
+                let rel = crate::links::relative_path(&root, e.path())?;
+                crate::links::is_note(&rel).then(|| NoteSummary { path: rel, modified: modified_ms(e.path()) })
+            })
+            .collect();
+        notes.sort_by(|a, b| a.path.cmp(&b.path));
+        notes
+    })
+    .await;
+    match notes {
+        Ok(notes) => ok(notes),
+        Err(e) => fail(StatusCode::INTERNAL_SERVER_ERROR, format!("Listing notes failed: {}", e)),
+    }
+}
+
+async fn get_note(State(state): State<ApiState>, UrlPath(rel): UrlPath<String>) -> ApiResult<NoteContent> {
+    let root = workspace(&state).await?;
+    let path = note_path(&root, &rel)?;
+    if !path.is_file() {
+        return fail(StatusCode::NOT_FOUND, format!("Note not found: {}", rel));
+    }
+    match crate::handlers::files::read_file_content(path.to_string_lossy().to_string()).await {
+        Ok(content) => ok(NoteContent { path: rel, content, modified: modified_ms(&path) }),
+        Err(e) => fail(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn write_note(path: PathBuf, rel: String, content: String) -> ApiResult<NoteContent> {
+    let target = path.clone();
+    let written = tokio::task::spawn_blocking(move || {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+        }
+        crate::handlers::files::write_file_content(target.to_string_lossy().to_string(), content.clone())?;
+        Ok::<_, String>(content)
+    })
+    .await
+    .map_err(|e| format!("Write task failed: {}", e));
+    match written {
+        Ok(Ok(content)) => ok(NoteContent { modified: modified_ms(&path), path: rel, content }),
+        Ok(Err(e)) | Err(e) => fail(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+/// Create a note; fails if it already exists
+async fn create_note(
+    State(state): State<ApiState>,
+    UrlPath(rel): UrlPath<String>,
+    Json(body): Json<NoteBody>,
+) -> ApiResult<NoteContent> {
+    let root = workspace(&state).await?;
+    let path = note_path(&root, &rel)?;
+    if path.exists() {
+        return fail(StatusCode::CONFLICT, format!("Note already exists: {}", rel));
+    }
+    write_note(path, rel, body.content).await
+}
+
+/// Create or replace a note
+async fn put_note(
+    State(state): State<ApiState>,
+    UrlPath(rel): UrlPath<String>,
+    Json(body): Json<NoteBody>,
+) -> ApiResult<NoteContent> {
+    let root = workspace(&state).await?;
+    let path = note_path(&root, &rel)?;
+    write_note(path, rel, body.content).await
+}
+
+/// Move a note to the workspace trash
+async fn delete_note(State(state): State<ApiState>, UrlPath(rel): UrlPath<String>) -> ApiResult<String> {
+    let root = workspace(&state).await?;
+    let path = note_path(&root, &rel)?;
+    if !path.is_file() {
+        return fail(StatusCode::NOT_FOUND, format!("Note not found: {}", rel));
+    }
+    match crate::handlers::files::delete_file(path.to_string_lossy().to_string()) {
+        Ok(()) => ok(rel),
+        Err(e) => fail(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn search(
+    State(state): State<ApiState>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Vec<crate::search::index::RankedResult>> {
+    let root = workspace(&state).await?;
+    if params.q.trim().is_empty() {
+        return ok(Vec::new());
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(200);
+    let results = tokio::task::spawn_blocking(move || {
+        crate::search::index::query(&root.to_string_lossy(), &params.q, limit)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e));
+    match results {
+        Ok(Ok(results)) => ok(results),
+        Ok(Err(e)) | Err(e) => fail(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+async fn tags(State(state): State<ApiState>) -> ApiResult<Vec<crate::tags::TagInfo>> {
+    let root = workspace(&state).await?;
+    let tags = tokio::task::spawn_blocking(move || crate::tags::list(&root))
+        .await
+        .map_err(|e| format!("Tag listing failed: {}", e));
+    match tags {
+        Ok(Ok(tags)) => ok(tags),
+        Ok(Err(e)) | Err(e) => fail(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+/// Save a page sent by the browser extension as a note
+async fn clip(State(state): State<ApiState>, Json(request): Json<crate::clipper::ClipRequest>) -> ApiResult<crate::clipper::ClipResult> {
+    let root = workspace(&state).await?;
+    match crate::clipper::clip_html(&state.app_handle, &root, &request.url, request.html, request.options).await {
+        Ok(result) => ok(result),
+        Err(e) => fail(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+/// Append a message forwarded from Slack, Discord or another chat to the inbox
+async fn capture_message(
+    State(state): State<ApiState>,
+    Json(message): Json<crate::connections::messaging::MessageCapture>,
+) -> ApiResult<crate::capture::CaptureResult> {
+    let root = workspace(&state).await?;
+    let app = state.app_handle.clone();
+    let captured = tokio::task::spawn_blocking(move || crate::connections::messaging::capture(&app, &root, &message))
+        .await
+        .map_err(|e| format!("Capture task failed: {}", e));
+    match captured {
+        Ok(Ok(result)) => ok(result),
+        Ok(Err(e)) | Err(e) => fail(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+/// `/api/v1` routes, all behind the API key check
+pub fn routes() -> Router<ApiState> {
+    Router::new()
+        .route("/api/v1/notes", get(list_notes))
+        .route(
+            "/api/v1/notes/*path",
+            get(get_note).put(put_note).post(create_note).delete(delete_note),
+        )
+        .route("/api/v1/search", get(search))
+        .route("/api/v1/tags", get(tags))
+        .route("/api/v1/clip", post(clip).layer(DefaultBodyLimit::max(CLIP_BODY_LIMIT)))
+        .route("/api/v1/capture/message", post(capture_message))
+        .route_layer(middleware::from_fn(require_api_key))
+}
+
+// --- Tauri Commands ---
+
+/// Create an API key. The returned key is shown once and never stored.
+#[tauri::command]
+pub async fn api_keys_create(name: String) -> Result<CreatedApiKey, String> {
+    tokio::task::spawn_blocking(move || create_key(&name))
+        .await
+        .map_err(|e| format!("API key task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn api_keys_revoke(id: String) -> Result<bool, String> {
+    tokio::task::spawn_blocking(move || {
+        with_keys(|keys| {
+            let before = keys.len();
+            keys.retain(|k| k.id != id);
+            let removed = keys.len() != before;
+            (removed, removed)
+        })
+    })
+    .await
+    .map_err(|e| format!("API key task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn api_keys_list() -> Result<Vec<ApiKeyInfo>, String> {
+    tokio::task::spawn_blocking(|| with_keys(|keys| (keys.iter().map(ApiKeyInfo::from).collect(), false)))
+        .await
+        .map_err(|e| format!("API key task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_note_path_rejects_escapes() {
+        let root = Path::new("/vault");
+        assert_eq!(note_path(root, "a/b.md").unwrap(), root.join("a/b.md"));
+        assert!(note_path(root, "../x.md").is_err());
+        assert!(note_path(root, ".lokus/x.md").is_err());
+        assert!(note_path(root, "image.png").is_err());
+        assert!(note_path(root, "").is_err());
+    }
+
+    #[test]
+    fn test_presented_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(presented_key(&headers), None);
+        headers.insert("x-api-key", HeaderValue::from_static("lokus_abc"));
+        assert_eq!(presented_key(&headers), Some("lokus_abc"));
+        headers.insert("authorization", HeaderValue::from_static("Bearer lokus_def"));
+        assert_eq!(presented_key(&headers), Some("lokus_def"));
+    }
+}
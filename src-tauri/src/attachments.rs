@@ -0,0 +1,503 @@
+//! Attachment management.
+//!
+//! Attachments are the non-note files notes point at: images, PDFs, audio and
+//! so on, referenced by `![[embeds]]`, `[[file.pdf]]` wikilinks or relative
+//! markdown links. This module finds attachments nothing references, moves
+//! them while relinking every note that points at them, and collapses
+//! byte-identical copies into one file. Removed files go to the workspace trash.
+
+use crate::links::{self, LinkKind, MARKDOWN_LINK_RE, WIKILINK_RE};
+use regex::Captures;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+/// Setting holding the folder new attachments are saved to
+const FOLDER_SETTING: &str = "attachments_folder";
+const DEFAULT_FOLDER: &str = "attachments";
+
+/// Extensions treated as attachments wherever they live. Everything inside the
+/// attachments folder counts regardless of extension.
+const ATTACHMENT_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif", "heic", "tiff", "ico", "pdf", "mp3", "wav", "m4a", "ogg",
+    "flac", "webm", "mp4", "mov", "mkv", "avi", "zip", "docx", "xlsx", "pptx", "doc", "xls", "ppt", "odt", "epub",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedAttachment {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveResult {
+    pub path: String,
+    /// Notes whose links were rewritten
+    pub updated_notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub kept: String,
+    pub removed: Vec<String>,
+    pub bytes_saved: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupeReport {
+    pub dry_run: bool,
+    pub groups: Vec<DuplicateGroup>,
+    pub updated_notes: Vec<String>,
+}
+
+pub(crate) fn configured_folder(app: &AppHandle) -> String {
+    use tauri_plugin_store::StoreBuilder;
+
+    StoreBuilder::new(app, PathBuf::from(".settings.dat"))
+        .build()
+        .ok()
+        .and_then(|store| {
+            let _ = store.reload();
+            store.get(FOLDER_SETTING).and_then(|v| v.as_str().map(String::from))
+        })
+        .map(|folder| folder.trim_matches('/').to_string())
+        .filter(|folder| !folder.is_empty())
+        .unwrap_or_else(|| DEFAULT_FOLDER.to_string())
+}
+
+fn is_attachment(rel: &str, folder: &str) -> bool {
+    if links::is_note(rel) {
+        return false;
+    }
+    let in_folder = rel
+        .get(..folder.len())
+        .map_or(false, |prefix| prefix.eq_ignore_ascii_case(folder) && rel[folder.len()..].starts_with('/'));
+    in_folder
+        || rel
+            .rsplit_once('.')
+            .map_or(false, |(_, ext)| ATTACHMENT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn file_name(rel: &str) -> &str {
+    rel.rsplit('/').next().unwrap_or(rel)
+}
+
+/// Notes and attachments of one workspace, and which attachment each link hits
+struct Vault {
+    root: PathBuf,
+    notes: Vec<String>,
+    attachments: Vec<String>,
+    by_path: HashMap<String, String>,
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl Vault {
+    fn scan(root: &Path, folder: &str) -> Self {
+        let mut notes = Vec::new();
+        let mut attachments = Vec::new();
+        for entry in WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| {
+                e.depth() == 0 || !(e.file_type().is_dir() && e.file_name().to_str().map_or(false, |n| links::EXCLUDED_DIRS.contains(&n)))
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let Some(rel) = links::relative_path(root, entry.path()) else { continue };
+            if links::is_note(&rel) {
+                notes.push(rel);
+            } else if is_attachment(&rel, folder) {
+                attachments.push(rel);
+            }
+        }
+        notes.sort();
+        attachments.sort();
+
+        let mut vault = Self {
+            root: root.to_path_buf(),
+            notes,
+            attachments: Vec::new(),
+            by_path: HashMap::new(),
+            by_name: HashMap::new(),
+        };
+        for rel in attachments {
+            vault.add_attachment(rel);
+        }
+        vault
+    }
+
+    fn add_attachment(&mut self, rel: String) {
+        self.by_path.insert(rel.to_lowercase(), rel.clone());
+        self.by_name.entry(file_name(&rel).to_lowercase()).or_default().push(rel.clone());
+        self.attachments.push(rel);
+    }
+
+    fn remove_attachment(&mut self, rel: &str) {
+        self.by_path.remove(&rel.to_lowercase());
+        if let Some(paths) = self.by_name.get_mut(&file_name(rel).to_lowercase()) {
+            paths.retain(|p| p != rel);
+        }
+        self.attachments.retain(|p| p != rel);
+    }
+
+    /// The attachment a link in `note` points at, using the same rules as note links
+    fn resolve(&self, note: &str, kind: LinkKind, target: &str) -> Option<String> {
+        match kind {
+            LinkKind::Markdown => {
+                let target = target.split(['#', '?']).next().unwrap_or("");
+                let decoded = urlencoding::decode(target).map(|s| s.into_owned()).unwrap_or_else(|_| target.to_string());
+                let path = match decoded.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None if links::parent_dir(note).is_empty() => decoded,
+                    None => format!("{}/{}", links::parent_dir(note), decoded),
+                };
+                self.by_path.get(&links::normalize(&path)?.to_lowercase()).cloned()
+            }
+            LinkKind::Wikilink | LinkKind::Embed => {
+                let target = target.trim().trim_start_matches('/');
+                if target.contains('/') {
+                    if let Some(rel) = self.by_path.get(&target.to_lowercase()) {
+                        return Some(rel.clone());
+                    }
+                    let joined = links::normalize(&format!("{}/{}", links::parent_dir(note), target))?;
+                    return self.by_path.get(&joined.to_lowercase()).cloned();
+                }
+                let candidates = self.by_name.get(&target.to_lowercase())?;
+                let dir = links::parent_dir(note);
+                candidates
+                    .iter()
+                    .find(|c| links::parent_dir(c).eq_ignore_ascii_case(dir))
+                    .or_else(|| candidates.iter().min_by_key(|c| (c.matches('/').count(), c.len())))
+                    .cloned()
+            }
+        }
+    }
+
+    /// The text of `note`, decrypted if needed. Errors when the note is
+    /// encrypted and the workspace is locked, since its links can't be seen.
+    fn read_note(&self, note: &str) -> Result<Option<String>, String> {
+        let path = self.root.join(note);
+        let Ok(content) = fs::read_to_string(&path) else { return Ok(None) };
+        crate::encryption::decrypt_for_read(&path.to_string_lossy(), content)
+            .map(Some)
+            .map_err(|e| format!("Can't read links in {}: {}", note, e))
+    }
+
+    /// Number of links pointing at each attachment
+    fn reference_counts(&self) -> Result<HashMap<String, usize>, String> {
+        let mut counts = HashMap::new();
+        for note in &self.notes {
+            let Some(content) = self.read_note(note)? else { continue };
+            for link in links::parse_links(&content) {
+                if let Some(rel) = self.resolve(note, link.kind, &link.target) {
+                    *counts.entry(rel).or_insert(0) += 1;
+                }
+            }
+        }
+        Ok(counts)
+    }
+
+    /// New content for every note with a link that resolves to a key of
+    /// `moves`, pointed at its value. `self` must still reflect the old locations.
+    fn relink(&self, moves: &HashMap<String, String>, after: &Vault) -> Result<Vec<(String, String)>, String> {
+        let mut rewrites = Vec::new();
+        for note in &self.notes {
+            let Some(content) = self.read_note(note)? else { continue };
+            if let Some(rewritten) = rewrite_note(&content, note, self, after, moves) {
+                rewrites.push((note.clone(), rewritten));
+            }
+        }
+        Ok(rewrites)
+    }
+
+    /// Save the output of `relink`. Returns the notes changed.
+    fn save_rewrites(&self, rewrites: Vec<(String, String)>) -> Result<Vec<String>, String> {
+        let mut updated = Vec::new();
+        for (note, content) in rewrites {
+            let path = self.root.join(&note).to_string_lossy().to_string();
+            crate::handlers::files::write_file_content(path, content)?;
+            updated.push(note);
+        }
+        Ok(updated)
+    }
+}
+
+fn rewrite_note(content: &str, note: &str, before: &Vault, after: &Vault, moves: &HashMap<String, String>) -> Option<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        let is_fence = line.trim_start().starts_with("```");
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        if is_fence || in_fence {
+            output.push_str(line);
+            continue;
+        }
+
+        let line = WIKILINK_RE.replace_all(line, |caps: &Captures| {
+            let kind = if &caps[1] == "!" { LinkKind::Embed } else { LinkKind::Wikilink };
+            let target = caps[2].trim();
+            let Some(new_rel) = before.resolve(note, kind, target).and_then(|old| moves.get(&old)) else {
+                return caps[0].to_string();
+            };
+            // Keep the short form when the name alone still finds the file
+            let short = file_name(new_rel);
+            let new_target = if !target.contains('/') && after.resolve(note, kind, short).as_deref() == Some(new_rel.as_str()) {
+                short.to_string()
+            } else {
+                new_rel.clone()
+            };
+            format!(
+                "{}[[{}{}{}]]",
+                &caps[1],
+                new_target,
+                caps.get(3).map_or("", |m| m.as_str()),
+                caps.get(4).map_or("", |m| m.as_str())
+            )
+        });
+
+        let line = MARKDOWN_LINK_RE.replace_all(&line, |caps: &Captures| {
+            let target = &caps[3];
+            if target.contains("://") || target.starts_with("mailto:") || target.starts_with('#') {
+                return caps[0].to_string();
+            }
+            let Some(new_rel) = before.resolve(note, LinkKind::Markdown, target).and_then(|old| moves.get(&old)) else {
+                return caps[0].to_string();
+            };
+            let suffix = target.find(['#', '?']).map_or("", |i| &target[i..]);
+            let new_target = format!("{}{}", links::relative_link(links::parent_dir(note), new_rel).replace(' ', "%20"), suffix);
+            format!("{}[{}]({}{})", &caps[1], &caps[2], new_target, &caps[4])
+        });
+
+        output.push_str(&line);
+    }
+
+    (output != content).then_some(output)
+}
+
+fn workspace_rel(root: &Path, path: &str) -> Result<String, String> {
+    let path = Path::new(path);
+    let absolute = if path.is_absolute() { path.to_path_buf() } else { root.join(path) };
+    links::relative_path(root, &absolute)
+        .and_then(|rel| links::normalize(&rel))
+        .filter(|rel| !rel.is_empty())
+        .ok_or_else(|| format!("Path is outside the workspace: {}", absolute.display()))
+}
+
+pub fn find_orphans(root: &Path, folder: &str) -> Vec<OrphanedAttachment> {
+    let vault = Vault::scan(root, folder);
+    // A locked encrypted note may link to anything, so nothing is provably orphaned
+    let Ok(counts) = vault.reference_counts() else { return Vec::new() };
+    vault
+        .attachments
+        .iter()
+        .filter(|rel| !counts.contains_key(*rel))
+        .map(|rel| OrphanedAttachment {
+            path: rel.clone(),
+            size: fs::metadata(root.join(rel)).map(|m| m.len()).unwrap_or(0),
+        })
+        .collect()
+}
+
+pub fn move_file(root: &Path, folder: &str, src: &str, dest: &str, update_links: bool) -> Result<MoveResult, String> {
+    let src = workspace_rel(root, src)?;
+    let mut dest = workspace_rel(root, dest)?;
+    let src_path = root.join(&src);
+    if !src_path.is_file() {
+        return Err(format!("Attachment not found: {}", src));
+    }
+    // Moving into a folder keeps the file name
+    if root.join(&dest).is_dir() {
+        dest = format!("{}/{}", dest, file_name(&src));
+    }
+    let dest_path = root.join(&dest);
+    if dest_path.exists() {
+        return Err(format!("Destination already exists: {}", dest));
+    }
+
+    let before = Vault::scan(root, folder);
+    let mut after = Vault::scan(root, folder);
+    after.remove_attachment(&src);
+    after.add_attachment(dest.clone());
+    // Work out the rewrites first so a locked note fails before anything moves
+    let rewrites = if update_links {
+        before.relink(&HashMap::from([(src.clone(), dest.clone())]), &after)?
+    } else {
+        Vec::new()
+    };
+
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::rename(&src_path, &dest_path).map_err(|e| format!("Failed to move {}: {}", src, e))?;
+
+    let updated_notes = before.save_rewrites(rewrites)?;
+    Ok(MoveResult { path: dest, updated_notes })
+}
+
+pub fn dedupe(root: &Path, folder: &str, dry_run: bool) -> Result<DedupeReport, String> {
+    let before = Vault::scan(root, folder);
+
+    // Only files sharing a size can be identical, so hash just those
+    let mut by_size: HashMap<u64, Vec<&String>> = HashMap::new();
+    for rel in &before.attachments {
+        if let Ok(meta) = fs::metadata(root.join(rel)) {
+            by_size.entry(meta.len()).or_default().push(rel);
+        }
+    }
+    let mut by_hash: BTreeMap<String, (u64, Vec<String>)> = BTreeMap::new();
+    for (size, paths) in by_size.into_iter().filter(|(size, paths)| *size > 0 && paths.len() > 1) {
+        for rel in paths {
+            let Ok(data) = fs::read(root.join(rel)) else { continue };
+            let entry = by_hash.entry(blake3::hash(&data).to_hex().to_string()).or_insert((size, Vec::new()));
+            entry.1.push(rel.clone());
+        }
+    }
+
+    let counts = before.reference_counts()?;
+    let in_folder = |rel: &str| rel.to_lowercase().starts_with(&format!("{}/", folder.to_lowercase()));
+    let mut report = DedupeReport { dry_run, ..Default::default() };
+    let mut moves = HashMap::new();
+    let mut after = Vault::scan(root, folder);
+
+    for (_, (size, mut paths)) in by_hash.into_iter().filter(|(_, (_, paths))| paths.len() > 1) {
+        // Keep the copy in the attachments folder, then the most linked, then the shortest path
+        paths.sort_by_key(|p| (!in_folder(p), std::cmp::Reverse(counts.get(p).copied().unwrap_or(0)), p.len(), p.clone()));
+        let kept = paths.remove(0);
+        for removed in &paths {
+            moves.insert(removed.clone(), kept.clone());
+            after.remove_attachment(removed);
+        }
+        report.groups.push(DuplicateGroup {
+            bytes_saved: size * paths.len() as u64,
+            kept,
+            removed: paths,
+        });
+    }
+
+    if dry_run || moves.is_empty() {
+        return Ok(report);
+    }
+
+    let rewrites = before.relink(&moves, &after)?;
+    report.updated_notes = before.save_rewrites(rewrites)?;
+    for removed in moves.keys() {
+        crate::trash::move_to_trash(root, &root.join(removed))?;
+    }
+    Ok(report)
+}
+
+// --- Tauri Commands ---
+
+/// Attachments no note links to
+#[tauri::command]
+pub fn find_orphaned_attachments(app: AppHandle, workspace_path: String) -> Result<Vec<OrphanedAttachment>, String> {
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", workspace_path));
+    }
+    Ok(find_orphans(&root, &configured_folder(&app)))
+}
+
+/// Move or rename an attachment, optionally rewriting links to it
+#[tauri::command]
+pub fn move_attachment(
+    app: AppHandle,
+    workspace_path: String,
+    src: String,
+    dest: String,
+    update_links: bool,
+) -> Result<MoveResult, String> {
+    move_file(Path::new(&workspace_path), &configured_folder(&app), &src, &dest, update_links)
+}
+
+/// Merge byte-identical attachments, relinking notes to the copy that is kept
+#[tauri::command]
+pub fn dedupe_attachments(app: AppHandle, workspace_path: String, dry_run: Option<bool>) -> Result<DedupeReport, String> {
+    dedupe(Path::new(&workspace_path), &configured_folder(&app), dry_run.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (rel, content) in files {
+            let path = dir.path().join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_find_orphans() {
+        let dir = workspace(&[
+            ("notes/a.md", "![[diagram.png]] and [spec](../attachments/spec%20v1.pdf)"),
+            ("attachments/diagram.png", "png"),
+            ("attachments/spec v1.pdf", "pdf"),
+            ("attachments/unused.bin", "bin"),
+            ("notes/old.jpg", "jpg"),
+            ("data.csv", "a,b"),
+        ]);
+        let orphans: Vec<String> = find_orphans(dir.path(), "attachments").into_iter().map(|o| o.path).collect();
+        assert_eq!(orphans, vec!["attachments/unused.bin", "notes/old.jpg"]);
+    }
+
+    #[test]
+    fn test_locked_encrypted_note_keeps_attachments() {
+        let dir = workspace(&[
+            ("a.md", "no links"),
+            ("secret.md", "-----BEGIN LOKUS ENCRYPTED NOTE-----\nAAAA\n-----END LOKUS ENCRYPTED NOTE-----\n"),
+            ("attachments/pic.png", "png"),
+        ]);
+        assert!(find_orphans(dir.path(), "attachments").is_empty());
+        assert!(move_file(dir.path(), "attachments", "attachments/pic.png", "pic.png", true).is_err());
+        assert!(dir.path().join("attachments/pic.png").exists());
+    }
+
+    #[test]
+    fn test_move_relinks_references() {
+        let dir = workspace(&[
+            ("notes/a.md", "![[pic.png|200]] ![](../pic.png \"t\")\n```\n![[pic.png]]\n```\n"),
+            ("pic.png", "png"),
+        ]);
+        fs::create_dir_all(dir.path().join("attachments/images")).unwrap();
+        let result = move_file(dir.path(), "attachments", "pic.png", "attachments/images", true).unwrap();
+        assert_eq!(result.path, "attachments/images/pic.png");
+        assert_eq!(result.updated_notes, vec!["notes/a.md"]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("notes/a.md")).unwrap(),
+            "![[pic.png|200]] ![](../attachments/images/pic.png \"t\")\n```\n![[pic.png]]\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_dedupe_keeps_attachments_folder_copy() {
+        let dir = workspace(&[
+            ("a.md", "![](copy.png) ![[attachments/orig.png]]"),
+            ("copy.png", "same"),
+            ("attachments/orig.png", "same"),
+            ("attachments/other.png", "diff"),
+        ]);
+        let report = dedupe(dir.path(), "attachments", true).unwrap();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].kept, "attachments/orig.png");
+        assert_eq!(report.groups[0].removed, vec!["copy.png"]);
+        assert!(dir.path().join("copy.png").exists());
+
+        dedupe(dir.path(), "attachments", false).unwrap();
+        assert!(!dir.path().join("copy.png").exists());
+        assert_eq!(fs::read_to_string(dir.path().join("a.md")).unwrap(), "![](attachments/orig.png) ![[attachments/orig.png]]");
+    }
+}
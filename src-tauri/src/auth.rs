@@ -589,68 +589,8 @@ pub fn get_user_profile() -> Result<Option<UserProfile>, String> {
 
 #[tauri::command]
 pub async fn refresh_auth_token() -> Result<(), String> {
-    let current_token = AuthService::get_token()?
-        .ok_or("No token found")?;
-
-    let refresh_token = current_token.refresh_token
-        .ok_or("No refresh token available")?;
-
-    let auth_base_url = std::env::var("AUTH_BASE_URL")
-        .unwrap_or_else(|_| "https://lokusmd.com".to_string());
-
-    let client = reqwest::Client::new();
-    let refresh_response = client
-        .post(&format!("{}/api/auth/refresh", auth_base_url))
-        .form(&[
-            ("grant_type", "refresh_token"),
-            ("client_id", "lokus-desktop"),
-            ("refresh_token", &refresh_token),
-        ])
-        .send()
-        .await
-        .map_err(|e| format!("Refresh request failed: {}", e))?;
-
-    if !refresh_response.status().is_success() {
-        AuthService::delete_token()?;
-        AuthService::delete_user_profile()?;
-        return Err("Token refresh failed - please sign in again".to_string());
-    }
-
-    let token_data: serde_json::Value = refresh_response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse refresh response: {}", e))?;
-
-    let access_token = token_data["access_token"]
-        .as_str()
-        .ok_or("No access token in refresh response")?;
-
-    let new_refresh_token = token_data["refresh_token"]
-        .as_str()
-        .map(|s| s.to_string())
-        .or(Some(refresh_token));
-
-    let expires_in = token_data["expires_in"].as_u64();
-    let expires_at = expires_in.map(|exp| {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() + exp
-    });
-
-    let new_token = AuthToken {
-        access_token: access_token.to_string(),
-        refresh_token: new_refresh_token,
-        expires_at,
-        user_id: current_token.user_id,
-        token_type: token_data["token_type"]
-            .as_str()
-            .unwrap_or("Bearer")
-            .to_string(),
-    };
-
-    AuthService::store_token(&new_token)?;
-    Ok(())
+    use crate::connections::oauth_manager::{self, Provider};
+    oauth_manager::refresh_now(Provider::Lokus).await.map(|_| ()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
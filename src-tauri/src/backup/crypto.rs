@@ -0,0 +1,166 @@
+//! Password encryption for backup archives.
+//!
+//! The archive is encrypted in 1 MiB chunks with XChaCha20-Poly1305 under a
+//! key derived from the password with Argon2id. Each chunk's nonce carries its
+//! index and a final-chunk flag, so reordered, dropped or truncated chunks
+//! fail authentication instead of restoring a partial workspace.
+//!
+//! Layout: `MAGIC | salt (16) | nonce prefix (19) | { len u32 LE | ciphertext }*`
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::io::{Read, Write};
+
+pub const MAGIC: &[u8; 8] = b"LOKUSBK1";
+const SALT_LEN: usize = 16;
+const PREFIX_LEN: usize = 19;
+const CHUNK_SIZE: usize = 1024 * 1024;
+const TAG_LEN: usize = 16;
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn nonce(prefix: &[u8; PREFIX_LEN], index: u32, last: bool) -> [u8; 24] {
+    let mut nonce = [0u8; 24];
+    nonce[..PREFIX_LEN].copy_from_slice(prefix);
+    nonce[PREFIX_LEN..PREFIX_LEN + 4].copy_from_slice(&index.to_be_bytes());
+    nonce[23] = u8::from(last);
+    nonce
+}
+
+/// Fill `buf` as far as the reader allows; returns the bytes read
+fn read_full(reader: &mut (impl Read + ?Sized), buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Whether a file starts with the encrypted backup header
+pub fn is_encrypted(header: &[u8]) -> bool {
+    header.starts_with(MAGIC)
+}
+
+pub fn encrypt_stream(password: &str, mut input: impl Read, mut output: impl Write) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("Failed to encrypt backup: {}", e);
+    let mut salt = [0u8; SALT_LEN];
+    let mut prefix = [0u8; PREFIX_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut prefix);
+    let key = derive_key(password, &salt)?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+
+    output.write_all(MAGIC).map_err(io_err)?;
+    output.write_all(&salt).map_err(io_err)?;
+    output.write_all(&prefix).map_err(io_err)?;
+
+    // Read one chunk ahead so the last one can be flagged
+    let mut current = vec![0u8; CHUNK_SIZE];
+    let mut next = vec![0u8; CHUNK_SIZE];
+    let mut len = read_full(&mut input, &mut current).map_err(io_err)?;
+    let mut index = 0u32;
+    loop {
+        let next_len = if len == CHUNK_SIZE { read_full(&mut input, &mut next).map_err(io_err)? } else { 0 };
+        let last = next_len == 0;
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce(&prefix, index, last)), &current[..len])
+            .map_err(|e| format!("Failed to encrypt backup: {}", e))?;
+        output.write_all(&(ciphertext.len() as u32).to_le_bytes()).map_err(io_err)?;
+        output.write_all(&ciphertext).map_err(io_err)?;
+        if last {
+            break;
+        }
+        std::mem::swap(&mut current, &mut next);
+        len = next_len;
+        index = index.checked_add(1).ok_or("Backup is too large to encrypt")?;
+    }
+    output.flush().map_err(io_err)
+}
+
+pub fn decrypt_stream(password: &str, mut input: impl Read, mut output: impl Write) -> Result<(), String> {
+    let io_err = |e: std::io::Error| format!("Failed to read backup: {}", e);
+    let corrupt = || "Backup archive is truncated or corrupted".to_string();
+
+    let mut header = [0u8; 8 + SALT_LEN + PREFIX_LEN];
+    if read_full(&mut input, &mut header).map_err(io_err)? < header.len() || !is_encrypted(&header) {
+        return Err("Not an encrypted Lokus backup".to_string());
+    }
+    let salt = &header[8..8 + SALT_LEN];
+    let mut prefix = [0u8; PREFIX_LEN];
+    prefix.copy_from_slice(&header[8 + SALT_LEN..]);
+    let key = derive_key(password, salt)?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+
+    let read_chunk = |input: &mut dyn Read| -> Result<Option<Vec<u8>>, String> {
+        let mut len = [0u8; 4];
+        match read_full(input, &mut len).map_err(io_err)? {
+            0 => return Ok(None),
+            4 => {}
+            _ => return Err(corrupt()),
+        }
+        let len = u32::from_le_bytes(len) as usize;
+        if !(TAG_LEN..=CHUNK_SIZE + TAG_LEN).contains(&len) {
+            return Err(corrupt());
+        }
+        let mut chunk = vec![0u8; len];
+        if read_full(input, &mut chunk).map_err(io_err)? < len {
+            return Err(corrupt());
+        }
+        Ok(Some(chunk))
+    };
+
+    let mut current = read_chunk(&mut input)?.ok_or_else(corrupt)?;
+    let mut index = 0u32;
+    loop {
+        let next = read_chunk(&mut input)?;
+        let last = next.is_none();
+        let plaintext = cipher
+            .decrypt(XNonce::from_slice(&nonce(&prefix, index, last)), current.as_slice())
+            .map_err(|_| {
+                if index == 0 {
+                    "Wrong password, or the backup is corrupted".to_string()
+                } else {
+                    corrupt()
+                }
+            })?;
+        output.write_all(&plaintext).map_err(io_err)?;
+        match next {
+            Some(chunk) => current = chunk,
+            None => break,
+        }
+        index = index.checked_add(1).ok_or_else(corrupt)?;
+    }
+    output.flush().map_err(io_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_tamper_detection() {
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 123).map(|i| (i % 251) as u8).collect();
+        let mut encrypted = Vec::new();
+        encrypt_stream("hunter2", data.as_slice(), &mut encrypted).unwrap();
+        assert!(is_encrypted(&encrypted));
+
+        let mut decrypted = Vec::new();
+        decrypt_stream("hunter2", encrypted.as_slice(), &mut decrypted).unwrap();
+        assert_eq!(decrypted, data);
+
+        assert!(decrypt_stream("wrong", encrypted.as_slice(), &mut Vec::new()).is_err());
+        // Dropping the final chunk must not look like a complete archive
+        let truncated = &encrypted[..encrypted.len() - (123 + TAG_LEN + 4)];
+        assert!(decrypt_stream("hunter2", truncated, &mut Vec::new()).is_err());
+    }
+}
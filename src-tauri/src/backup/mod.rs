@@ -0,0 +1,471 @@
+//! Whole-workspace backups as a single archive.
+//!
+//! A backup is a zip with every workspace file under `files/`, including the
+//! `.lokus` metadata but not the trash, plus a `lokus-backup.json` manifest
+//! recording each file's size and BLAKE3 hash. Restores check every file
+//! against the manifest. With a password, the zip is additionally encrypted
+//! (see `crypto`) and saved as `.lokusbackup`. Progress is emitted as
+//! `backup-progress` events.
+
+pub mod crypto;
+pub mod schedule;
+
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+
+pub const PROGRESS_EVENT: &str = "backup-progress";
+pub const ENCRYPTED_EXTENSION: &str = "lokusbackup";
+const MANIFEST_NAME: &str = "lokus-backup.json";
+const FILES_PREFIX: &str = "files/";
+const FORMAT_VERSION: u32 = 1;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupOptions {
+    /// Encrypt the archive with this password
+    pub password: Option<String>,
+    /// Include version history (`.lokus/backups`)
+    pub include_versions: bool,
+}
+
+impl Default for BackupOptions {
+    fn default() -> Self {
+        BackupOptions { password: None, include_versions: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+    path: String,
+    size: u64,
+    hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    format_version: u32,
+    workspace_name: String,
+    /// Unix ms
+    created_at: i64,
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgress {
+    /// "backup" or "restore"
+    pub operation: &'static str,
+    /// "scanning", "archiving", "encrypting", "decrypting", "extracting" or "done"
+    pub phase: &'static str,
+    pub current: usize,
+    pub total: usize,
+    pub bytes: u64,
+    pub total_bytes: u64,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+    pub archive_size: u64,
+    pub encrypted: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSummary {
+    pub target: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// When the backup was made, Unix ms
+    pub created_at: i64,
+}
+
+/// Rate-limits progress callbacks; phase changes and the last item always go through
+struct Reporter<F: FnMut(BackupProgress)> {
+    callback: F,
+    operation: &'static str,
+    last: Option<(Instant, &'static str)>,
+}
+
+impl<F: FnMut(BackupProgress)> Reporter<F> {
+    fn new(operation: &'static str, callback: F) -> Self {
+        Reporter { callback, operation, last: None }
+    }
+
+    fn report(&mut self, phase: &'static str, current: usize, total: usize, bytes: u64, total_bytes: u64, path: Option<&str>) {
+        let due = match self.last {
+            Some((at, last_phase)) => last_phase != phase || current == total || at.elapsed() >= PROGRESS_INTERVAL,
+            None => true,
+        };
+        if !due {
+            return;
+        }
+        self.last = Some((Instant::now(), phase));
+        (self.callback)(BackupProgress {
+            operation: self.operation,
+            phase,
+            current,
+            total,
+            bytes,
+            total_bytes,
+            path: path.map(str::to_string),
+        });
+    }
+}
+
+pub(crate) fn emit_progress(app: &AppHandle, progress: BackupProgress) {
+    if let Err(e) = app.emit(PROGRESS_EVENT, &progress) {
+        tracing::warn!("Failed to emit {}: {}", PROGRESS_EVENT, e);
+    }
+}
+
+fn workspace_name(root: &Path) -> String {
+    root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "workspace".to_string())
+}
+
+/// File name prefix shared by a workspace's archives
+pub(crate) fn archive_prefix(root: &Path) -> String {
+    format!("{}-backup-", workspace_name(root))
+}
+
+/// Files to back up, workspace-relative, with their sizes
+fn collect_files(root: &Path, options: &BackupOptions, exclude: Option<&Path>) -> Vec<(String, u64)> {
+    let trash = root.join(".lokus").join("trash");
+    let versions = root.join(".lokus").join("backups");
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            let path = entry.path();
+            path != trash
+                && (options.include_versions || path != versions)
+                && Some(path) != exclude
+        })
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = crate::links::relative_path(root, entry.path())?;
+            let size = entry.metadata().ok()?.len();
+            Some((relative, size))
+        })
+        .collect()
+}
+
+/// Copy `reader` to `writer`, returning the BLAKE3 hash and byte count
+fn copy_hashed(reader: &mut impl Read, writer: &mut impl Write) -> std::io::Result<(String, u64)> {
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        writer.write_all(&buf[..n])?;
+        total += n as u64;
+    }
+    Ok((hasher.finalize().to_hex().to_string(), total))
+}
+
+/// `dest` may be a folder (a timestamped name is chosen) or a full file path
+fn archive_path(root: &Path, dest: &Path, encrypted: bool) -> PathBuf {
+    let is_file = dest
+        .extension()
+        .map_or(false, |ext| ext == "zip" || ext == ENCRYPTED_EXTENSION);
+    if is_file {
+        return dest.to_path_buf();
+    }
+    let extension = if encrypted { ENCRYPTED_EXTENSION } else { "zip" };
+    dest.join(format!("{}{}.{}", archive_prefix(root), Local::now().format("%Y-%m-%d-%H%M%S"), extension))
+}
+
+pub fn create_backup(
+    root: &Path,
+    dest: &Path,
+    options: &BackupOptions,
+    progress: impl FnMut(BackupProgress),
+) -> Result<BackupSummary, String> {
+    if !root.is_dir() {
+        return Err(format!("Workspace not found: {}", root.display()));
+    }
+    let password = options.password.as_deref().filter(|p| !p.is_empty());
+    let output = archive_path(root, dest, password.is_some());
+    let output_dir = output.parent().unwrap_or(dest).to_path_buf();
+    fs::create_dir_all(&output_dir).map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+    let mut reporter = Reporter::new("backup", progress);
+
+    reporter.report("scanning", 0, 0, 0, 0, None);
+    let exclude = output_dir.starts_with(root).then_some(output_dir.as_path());
+    let files = collect_files(root, options, exclude);
+    let total_bytes: u64 = files.iter().map(|(_, size)| size).sum();
+
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write backup: {}", e);
+    let io_err = |e: std::io::Error| format!("Failed to write backup: {}", e);
+    let temp = tempfile::NamedTempFile::new_in(&output_dir).map_err(io_err)?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(temp.reopen().map_err(io_err)?));
+    let deflated = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut entries = Vec::with_capacity(files.len());
+    let mut done_bytes = 0u64;
+    for (index, (relative, size)) in files.iter().enumerate() {
+        reporter.report("archiving", index, files.len(), done_bytes, total_bytes, Some(relative));
+        // Files can vanish while we walk; skip rather than fail the whole backup
+        let Ok(file) = File::open(root.join(relative)) else {
+            continue;
+        };
+        zip.start_file(format!("{}{}", FILES_PREFIX, relative), deflated.large_file(*size >= u32::MAX as u64))
+            .map_err(zip_err)?;
+        let (hash, written) = copy_hashed(&mut BufReader::new(file), &mut zip).map_err(io_err)?;
+        entries.push(ManifestEntry { path: relative.clone(), size: written, hash });
+        done_bytes += written;
+    }
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        workspace_name: workspace_name(root),
+        created_at: Utc::now().timestamp_millis(),
+        files: entries,
+    };
+    zip.start_file(MANIFEST_NAME, deflated).map_err(zip_err)?;
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.write_all(&json).map_err(io_err)?;
+    zip.finish().map_err(zip_err)?.flush().map_err(io_err)?;
+
+    let archive = match password {
+        Some(password) => {
+            reporter.report("encrypting", files.len(), files.len(), done_bytes, total_bytes, None);
+            let encrypted = tempfile::NamedTempFile::new_in(&output_dir).map_err(io_err)?;
+            crypto::encrypt_stream(
+                password,
+                BufReader::new(temp.reopen().map_err(io_err)?),
+                BufWriter::new(encrypted.reopen().map_err(io_err)?),
+            )?;
+            encrypted
+        }
+        None => temp,
+    };
+    archive
+        .persist(&output)
+        .map_err(|e| format!("Failed to save backup to {}: {}", output.display(), e.error))?;
+
+    reporter.report("done", files.len(), files.len(), done_bytes, total_bytes, None);
+    Ok(BackupSummary {
+        path: output.to_string_lossy().to_string(),
+        files: manifest.files.len(),
+        bytes: done_bytes,
+        archive_size: fs::metadata(&output).map(|m| m.len()).unwrap_or(0),
+        encrypted: password.is_some(),
+        created_at: manifest.created_at,
+    })
+}
+
+/// Only plain relative paths may come out of an archive
+fn safe_relative(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    path.components().all(|c| matches!(c, Component::Normal(_))).then(|| path.to_path_buf())
+}
+
+fn extract<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    target: &Path,
+    reporter: &mut Reporter<impl FnMut(BackupProgress)>,
+) -> Result<RestoreSummary, String> {
+    let manifest: Manifest = {
+        let entry = archive
+            .by_name(MANIFEST_NAME)
+            .map_err(|_| "Not a Lokus backup: the manifest is missing".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Backup manifest is invalid: {}", e))?
+    };
+    if manifest.format_version > FORMAT_VERSION {
+        return Err("This backup was made by a newer version of Lokus".to_string());
+    }
+
+    let total_bytes: u64 = manifest.files.iter().map(|f| f.size).sum();
+    let mut done_bytes = 0u64;
+    for (index, expected) in manifest.files.iter().enumerate() {
+        reporter.report("extracting", index, manifest.files.len(), done_bytes, total_bytes, Some(&expected.path));
+        let relative = safe_relative(&expected.path).ok_or_else(|| format!("Unsafe path in backup: {}", expected.path))?;
+        let mut entry = archive
+            .by_name(&format!("{}{}", FILES_PREFIX, expected.path))
+            .map_err(|_| format!("Backup is missing {}", expected.path))?;
+
+        let destination = target.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = BufWriter::new(
+            File::create(&destination).map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?,
+        );
+        let (hash, size) = copy_hashed(&mut entry, &mut out).map_err(|e| format!("Failed to restore {}: {}", expected.path, e))?;
+        out.flush().map_err(|e| format!("Failed to restore {}: {}", expected.path, e))?;
+        if hash != expected.hash || size != expected.size {
+            return Err(format!("Integrity check failed for {}", expected.path));
+        }
+        done_bytes += size;
+    }
+
+    reporter.report("done", manifest.files.len(), manifest.files.len(), done_bytes, total_bytes, None);
+    Ok(RestoreSummary {
+        target: target.to_string_lossy().to_string(),
+        files: manifest.files.len(),
+        bytes: done_bytes,
+        created_at: manifest.created_at,
+    })
+}
+
+/// Restore into `target`, which must be missing or empty. Nothing is left
+/// behind if the archive fails verification.
+pub fn restore_backup(
+    archive: &Path,
+    target: &Path,
+    password: Option<&str>,
+    progress: impl FnMut(BackupProgress),
+) -> Result<RestoreSummary, String> {
+    let mut reporter = Reporter::new("restore", progress);
+    let io_err = |e: std::io::Error| format!("Failed to read backup: {}", e);
+    let mut file = File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut header = [0u8; 8];
+    let header_len = file.read(&mut header).map_err(io_err)?;
+    file.rewind().map_err(io_err)?;
+
+    let target_existed = target.exists();
+    if target_existed && fs::read_dir(target).map_err(io_err)?.next().is_some() {
+        return Err(format!("{} is not empty; restore into a new folder", target.display()));
+    }
+
+    // Decrypted archives go to a temp file that disappears with it
+    let decrypted = if crypto::is_encrypted(&header[..header_len]) {
+        let password = password.filter(|p| !p.is_empty()).ok_or("This backup is encrypted; a password is required")?;
+        reporter.report("decrypting", 0, 0, 0, 0, None);
+        let temp = tempfile::NamedTempFile::new().map_err(io_err)?;
+        crypto::decrypt_stream(password, BufReader::new(file), BufWriter::new(temp.reopen().map_err(io_err)?))?;
+        Some(temp)
+    } else {
+        None
+    };
+    let reader = match &decrypted {
+        Some(temp) => temp.reopen().map_err(io_err)?,
+        None => File::open(archive).map_err(io_err)?,
+    };
+    let mut zip = zip::ZipArchive::new(BufReader::new(reader)).map_err(|e| format!("Backup archive is corrupted: {}", e))?;
+
+    fs::create_dir_all(target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let result = extract(&mut zip, target, &mut reporter);
+    if result.is_err() {
+        let _ = if target_existed {
+            fs::read_dir(target).into_iter().flatten().flatten().try_for_each(|entry| {
+                let path = entry.path();
+                if path.is_dir() {
+                    fs::remove_dir_all(path)
+                } else {
+                    fs::remove_file(path)
+                }
+            })
+        } else {
+            fs::remove_dir_all(target)
+        };
+    }
+    result
+}
+
+// --- Tauri Commands ---
+
+/// Write a backup of the workspace to `dest` (a folder or an archive path)
+#[tauri::command]
+pub async fn backup_workspace(
+    app: AppHandle,
+    workspace_path: String,
+    dest: String,
+    options: Option<BackupOptions>,
+) -> Result<BackupSummary, String> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        create_backup(Path::new(&workspace_path), Path::new(&dest), &options, |progress| emit_progress(&app, progress))
+    })
+    .await
+    .map_err(|e| format!("Backup task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn backup_restore(
+    app: AppHandle,
+    archive: String,
+    target_dir: String,
+    password: Option<String>,
+) -> Result<RestoreSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        restore_backup(Path::new(&archive), Path::new(&target_dir), password.as_deref(), |progress| {
+            emit_progress(&app, progress)
+        })
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, content) in [
+            ("Home.md", "# Home\n"),
+            ("Projects/Plan.md", "# Plan\n"),
+            (".lokus/settings.json", "{}"),
+            (".lokus/trash/abc/Old.md", "gone"),
+        ] {
+            let file = root.join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let workspace = sample_workspace();
+        let out = tempfile::tempdir().unwrap();
+        for password in [None, Some("secret".to_string())] {
+            let options = BackupOptions { password: password.clone(), include_versions: true };
+            let summary = create_backup(workspace.path(), out.path(), &options, |_| {}).unwrap();
+            assert_eq!(summary.files, 3);
+            assert_eq!(summary.encrypted, password.is_some());
+
+            let target = out.path().join(format!("restored-{}", password.is_some()));
+            let restored = restore_backup(Path::new(&summary.path), &target, password.as_deref(), |_| {}).unwrap();
+            assert_eq!(restored.files, 3);
+            assert_eq!(fs::read_to_string(target.join("Projects/Plan.md")).unwrap(), "# Plan\n");
+            assert!(target.join(".lokus/settings.json").exists());
+            assert!(!target.join(".lokus/trash").exists());
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_non_empty_target_and_unsafe_paths() {
+        let workspace = sample_workspace();
+        let out = tempfile::tempdir().unwrap();
+        let summary = create_backup(workspace.path(), out.path(), &BackupOptions::default(), |_| {}).unwrap();
+        assert!(restore_backup(Path::new(&summary.path), workspace.path(), None, |_| {}).is_err());
+
+        assert!(safe_relative("notes/a.md").is_some());
+        assert!(safe_relative("../escape.md").is_none());
+        assert!(safe_relative("/etc/passwd").is_none());
+    }
+}
@@ -0,0 +1,339 @@
+//! Periodic backups on a cron-like schedule.
+//!
+//! Schedules use the five classic cron fields (minute, hour, day of month,
+//! month, day of week) with `*`, lists, ranges and `/step`, or one of
+//! `@hourly`, `@daily`, `@weekly`, `@monthly`. Times are local. Like cron, when
+//! both day fields are restricted a day matching either one fires.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::secure_storage::SecureStorage;
+
+const CONFIG_FILE: &str = "backup-schedule.json";
+/// Give up looking for the next run after this long (e.g. `0 0 31 2 *`)
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    pub cron: String,
+    /// Folder the archives are written to
+    pub destination: String,
+    /// Archives kept in the destination; older ones are deleted
+    pub keep: usize,
+    /// Encrypt with the password kept in secure storage
+    pub encrypted: bool,
+    /// Unix ms
+    pub last_run: Option<i64>,
+    pub last_error: Option<String>,
+    pub next_run: Option<i64>,
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+    let value = |s: &str| -> Result<u32, String> {
+        if let Some(i) = names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+            return Ok(i as u32 + min);
+        }
+        s.parse::<u32>().map_err(|_| format!("Invalid cron value '{}'", s))
+    };
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| format!("Invalid cron step '{}'", step))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err("Cron step must be positive".to_string());
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // `5/15` means from 5 to the end in steps of 15
+                None if part.contains('/') => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("Cron field '{}' is out of range {}-{}", part, min, max));
+        }
+        for v in (start..=end).step_by(step as usize) {
+            allowed[v as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err("A schedule needs five fields: minute hour day-of-month month day-of-week".to_string());
+        };
+        const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+        const DAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+        let mut days_of_week = parse_field(dow, 0, 7, DAYS)?;
+        // 7 is Sunday too
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        Ok(CronSchedule {
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])?,
+            days_of_month: parse_field(dom, 1, 31, &[])?,
+            months: parse_field(month, 1, 12, MONTHS)?,
+            days_of_week,
+            dom_restricted: dom != "*",
+            dow_restricted: dow != "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days_of_month[date.day() as usize];
+        let dow = self.days_of_week[date.weekday().num_days_from_sunday() as usize];
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// First matching minute strictly after `after`
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(MAX_SEARCH_DAYS);
+        let mut t: NaiveDateTime = start;
+        while t < limit {
+            if !self.months[t.month() as usize] || !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours[t.hour() as usize] {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes[t.minute() as usize] {
+                t += Duration::minutes(1);
+                continue;
+            }
+            // Skips times that don't exist on DST change days
+            if let Some(local) = Local.from_local_datetime(&t).earliest() {
+                if local > after {
+                    return Some(local);
+                }
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+}
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(CONFIG_FILE)
+}
+
+pub fn load(root: &Path) -> Option<BackupSchedule> {
+    fs::read_to_string(config_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn save(root: &Path, schedule: &BackupSchedule) -> Result<(), String> {
+    let path = config_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(schedule).map_err(|e| format!("Failed to serialize backup schedule: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write backup schedule: {}", e))
+}
+
+fn password_key(root: &Path) -> String {
+    let digest = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+    format!("backup_password_{}", &digest[..16])
+}
+
+fn next_run(cron: &str) -> Option<i64> {
+    CronSchedule::parse(cron).ok()?.next_after(Local::now()).map(|t| t.timestamp_millis())
+}
+
+/// Delete the oldest scheduled archives beyond `keep`
+fn prune(destination: &Path, prefix: &str, keep: usize) {
+    let mut archives: Vec<PathBuf> = fs::read_dir(destination)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.file_name().map_or(false, |n| n.to_string_lossy().starts_with(prefix)))
+        .filter(|p| p.extension().map_or(false, |ext| ext == "zip" || ext == super::ENCRYPTED_EXTENSION))
+        .collect();
+    // Names embed the creation time, so they sort chronologically
+    archives.sort();
+    let excess = archives.len().saturating_sub(keep.max(1));
+    for old in &archives[..excess] {
+        if let Err(e) = fs::remove_file(old) {
+            tracing::warn!("Failed to remove old backup {}: {}", old.display(), e);
+        }
+    }
+}
+
+/// Run the schedule for `root` if it's due
+#[cfg(desktop)]
+fn run_if_due(app: &tauri::AppHandle, root: &Path) {
+    let Some(mut schedule) = load(root) else {
+        return;
+    };
+    let now = Local::now().timestamp_millis();
+    if schedule.next_run.map_or(true, |next| next > now) {
+        return;
+    }
+
+    let password = if schedule.encrypted {
+        SecureStorage::new().ok().and_then(|s| s.retrieve::<String>(&password_key(root)).ok().flatten())
+    } else {
+        None
+    };
+    let options = super::BackupOptions { password, include_versions: true };
+    let result = if schedule.encrypted && options.password.is_none() {
+        Err("The backup password is missing; set the schedule again".to_string())
+    } else {
+        super::create_backup(root, Path::new(&schedule.destination), &options, |progress| {
+            super::emit_progress(app, progress);
+        })
+    };
+    match result {
+        Ok(summary) => {
+            tracing::info!("Scheduled backup written to {}", summary.path);
+            prune(Path::new(&schedule.destination), &super::archive_prefix(root), schedule.keep);
+            schedule.last_error = None;
+        }
+        Err(e) => {
+            tracing::warn!("Scheduled backup of {} failed: {}", root.display(), e);
+            schedule.last_error = Some(e);
+        }
+    }
+    schedule.last_run = Some(now);
+    schedule.next_run = next_run(&schedule.cron);
+    if let Err(e) = save(root, &schedule) {
+        tracing::warn!("Failed to update backup schedule: {}", e);
+    }
+}
+
+/// Check the open workspace's schedule once a minute
+#[cfg(desktop)]
+pub fn start(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            let Some(root) = crate::watcher::active_root() else {
+                continue;
+            };
+            let app = app.clone();
+            let _ = tokio::task::spawn_blocking(move || run_if_due(&app, &root)).await;
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Back the workspace up on a cron-like schedule (see module docs); an empty
+/// `cron` turns scheduled backups off
+#[tauri::command]
+pub fn backup_schedule(
+    workspace_path: String,
+    cron: String,
+    destination: Option<String>,
+    keep: Option<usize>,
+    password: Option<String>,
+) -> Result<Option<BackupSchedule>, String> {
+    let root = PathBuf::from(&workspace_path);
+    if cron.trim().is_empty() {
+        let _ = fs::remove_file(config_path(&root));
+        if let Ok(storage) = SecureStorage::new() {
+            let _ = storage.delete(&password_key(&root));
+        }
+        return Ok(None);
+    }
+
+    CronSchedule::parse(&cron)?;
+    let destination = destination.ok_or("A destination folder is required")?;
+    fs::create_dir_all(&destination).map_err(|e| format!("Failed to create {}: {}", destination, e))?;
+
+    let storage = SecureStorage::new().map_err(|e| e.to_string())?;
+    let encrypted = match password.filter(|p| !p.is_empty()) {
+        Some(password) => {
+            storage
+                .store(&password_key(&root), &password)
+                .map_err(|e| format!("Failed to store backup password: {}", e))?;
+            true
+        }
+        None => {
+            let _ = storage.delete(&password_key(&root));
+            false
+        }
+    };
+
+    let previous = load(&root);
+    let schedule = BackupSchedule {
+        next_run: next_run(&cron),
+        cron: cron.trim().to_string(),
+        destination,
+        keep: keep.unwrap_or(10).max(1),
+        encrypted,
+        last_run: previous.as_ref().and_then(|p| p.last_run),
+        last_error: None,
+    };
+    save(&root, &schedule)?;
+    Ok(Some(schedule))
+}
+
+#[tauri::command]
+pub fn backup_schedule_get(workspace_path: String) -> Result<Option<BackupSchedule>, String> {
+    Ok(load(Path::new(&workspace_path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, mo, d, h, mi, 0).earliest().unwrap()
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        let daily = CronSchedule::parse("30 2 * * *").unwrap();
+        assert_eq!(daily.next_after(local(2024, 5, 10, 1, 0)), Some(local(2024, 5, 10, 2, 30)));
+        assert_eq!(daily.next_after(local(2024, 5, 10, 2, 30)), Some(local(2024, 5, 11, 2, 30)));
+
+        // Every 15 minutes on weekdays; 2024-05-11 is a Saturday
+        let weekdays = CronSchedule::parse("*/15 * * * mon-fri").unwrap();
+        assert_eq!(weekdays.next_after(local(2024, 5, 10, 23, 50)), Some(local(2024, 5, 13, 0, 0)));
+
+        assert_eq!(CronSchedule::parse("@weekly").unwrap().next_after(local(2024, 5, 10, 12, 0)), Some(local(2024, 5, 12, 0, 0)));
+        assert!(CronSchedule::parse("61 * * * *").is_err());
+        assert!(CronSchedule::parse("* * *").is_err());
+    }
+}
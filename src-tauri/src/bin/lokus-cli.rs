@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(lokus_lib::cli::main(std::env::args().skip(1).collect()));
+}
@@ -0,0 +1,344 @@
+//! Workspace-wide find and replace.
+//!
+//! `bulk_replace_preview` finds every match (literal or regex, optionally
+//! whole-word and case-sensitive, limited to a folder or tag) and keeps the
+//! rewritten notes as a job. `bulk_replace_apply` first saves a version
+//! snapshot of every note it will touch, then writes them all, restoring the
+//! originals if any write fails. `bulk_replace_revert` puts the snapshots back.
+//! Matches never span lines. Jobs live in memory until the app quits; the
+//! snapshots stay in each note's version history.
+
+use crate::metadata_cache::{self, MetadataFilter};
+use once_cell::sync::Lazy;
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Matches listed per file in a preview; all of them are replaced
+const PREVIEW_MATCHES_PER_FILE: usize = 50;
+/// Jobs kept for apply/revert, oldest dropped first
+const MAX_JOBS: usize = 20;
+const SNAPSHOT_ACTION: &str = "Before find and replace";
+
+static JOBS: Lazy<Mutex<Vec<ReplaceJob>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReplaceOptions {
+    /// Treat the query as a regular expression; the replacement may use `$1`
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Only notes under this folder (absolute or workspace-relative)
+    pub folder: Option<String>,
+    /// Only notes carrying this tag or one nested below it
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchPreview {
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePreview {
+    pub path: String,
+    pub matches: usize,
+    /// The first matching lines, before and after replacement
+    pub lines: Vec<MatchPreview>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacePreview {
+    pub job_id: String,
+    pub files: Vec<FilePreview>,
+    pub total_matches: usize,
+    /// Encrypted notes in scope, which aren't searched
+    pub skipped_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceResult {
+    pub job_id: String,
+    pub files: Vec<String>,
+    /// Files left alone because they were edited after the preview (apply)
+    /// or after the replace (revert)
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JobState {
+    Previewed,
+    Applied,
+    Reverted,
+}
+
+#[derive(Debug, Clone)]
+struct FileChange {
+    path: String,
+    original: String,
+    replaced: String,
+    /// Version saved before the replace was written
+    snapshot: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct ReplaceJob {
+    id: String,
+    root: PathBuf,
+    state: JobState,
+    changes: Vec<FileChange>,
+}
+
+fn build_regex(query: &str, options: &ReplaceOptions) -> Result<Regex, String> {
+    if query.is_empty() {
+        return Err("Nothing to search for".to_string());
+    }
+    let pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    let pattern = if options.whole_word { format!(r"\b(?:{})\b", pattern) } else { pattern };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+/// Replace line by line; returns the new text, the number of matches and
+/// previews of the first changed lines
+fn replace_lines(content: &str, re: &Regex, replacement: &str, literal: bool) -> (String, usize, Vec<MatchPreview>) {
+    let mut output = String::with_capacity(content.len());
+    let mut count = 0;
+    let mut previews = Vec::new();
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let matches = re.find_iter(text).filter(|m| !m.is_empty()).count();
+        if matches == 0 {
+            output.push_str(line);
+            continue;
+        }
+        let replaced = if literal { re.replace_all(text, NoExpand(replacement)) } else { re.replace_all(text, replacement) };
+        count += matches;
+        if previews.len() < PREVIEW_MATCHES_PER_FILE {
+            previews.push(MatchPreview { line: index + 1, before: text.to_string(), after: replaced.to_string() });
+        }
+        output.push_str(&replaced);
+        output.push_str(&line[text.len()..]);
+    }
+    (output, count, previews)
+}
+
+fn preview(root: &Path, query: &str, replacement: &str, options: &ReplaceOptions) -> Result<ReplacePreview, String> {
+    let re = build_regex(query, options)?;
+    let filter = MetadataFilter { folder: options.folder.clone(), tag: options.tag.clone(), ..Default::default() };
+    let mut files = Vec::new();
+    let mut changes = Vec::new();
+    let mut skipped_files = 0;
+
+    for entry in metadata_cache::query(root, &filter)? {
+        if !crate::links::is_note(&entry.path) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&entry.path) else { continue };
+        if crate::encryption::is_encrypted(&content) {
+            skipped_files += 1;
+            continue;
+        }
+        let (replaced, matches, lines) = replace_lines(&content, &re, replacement, !options.regex);
+        if matches == 0 {
+            continue;
+        }
+        files.push(FilePreview { path: entry.path.clone(), matches, lines });
+        if replaced != content {
+            changes.push(FileChange { path: entry.path, original: content, replaced, snapshot: None });
+        }
+    }
+
+    let job = ReplaceJob { id: uuid::Uuid::new_v4().to_string(), root: root.to_path_buf(), state: JobState::Previewed, changes };
+    let job_id = job.id.clone();
+    let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    if jobs.len() >= MAX_JOBS {
+        jobs.remove(0);
+    }
+    jobs.push(job);
+
+    Ok(ReplacePreview { job_id, total_matches: files.iter().map(|f| f.matches).sum(), files, skipped_files })
+}
+
+fn take_job(job_id: &str, expected: JobState) -> Result<ReplaceJob, String> {
+    let jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    let job = jobs.iter().find(|j| j.id == job_id).ok_or_else(|| format!("Unknown replace job: {}", job_id))?;
+    match (job.state, expected) {
+        (actual, expected) if actual == expected => Ok(job.clone()),
+        (JobState::Applied, _) => Err("This replace has already been applied".to_string()),
+        (JobState::Reverted, _) => Err("This replace has already been reverted".to_string()),
+        (JobState::Previewed, _) => Err("This replace hasn't been applied".to_string()),
+    }
+}
+
+fn store_job(job: ReplaceJob) {
+    let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(slot) = jobs.iter_mut().find(|j| j.id == job.id) {
+        *slot = job;
+    }
+}
+
+fn snapshot(root: &Path, path: &str, content: &str) -> Result<String, String> {
+    let rel = crate::links::relative_path(root, Path::new(path)).ok_or_else(|| format!("{} is outside the workspace", path))?;
+    crate::handlers::version_history::save_version(
+        root.to_string_lossy().to_string(),
+        rel,
+        content.to_string(),
+        Some(SNAPSHOT_ACTION.to_string()),
+    )
+    .map(|version| version.timestamp)
+}
+
+fn apply(job_id: &str) -> Result<ReplaceResult, String> {
+    let mut job = take_job(job_id, JobState::Previewed)?;
+
+    // Files edited since the preview would lose those edits
+    let (mut ready, mut conflicts) = (Vec::new(), Vec::new());
+    for change in job.changes.drain(..) {
+        match fs::read_to_string(&change.path) {
+            Ok(current) if current == change.original => ready.push(change),
+            _ => conflicts.push(change.path),
+        }
+    }
+    for change in ready.iter_mut() {
+        change.snapshot = Some(snapshot(&job.root, &change.path, &change.original)?);
+    }
+
+    for (index, change) in ready.iter().enumerate() {
+        if let Err(e) = crate::handlers::files::write_file_content(change.path.clone(), change.replaced.clone()) {
+            for written in &ready[..index] {
+                let _ = crate::handlers::files::write_file_content(written.path.clone(), written.original.clone());
+            }
+            return Err(format!("Replace rolled back, {} could not be written: {}", change.path, e));
+        }
+    }
+
+    job.state = JobState::Applied;
+    job.changes = ready;
+    let result = ReplaceResult { job_id: job.id.clone(), files: job.changes.iter().map(|c| c.path.clone()).collect(), conflicts };
+    store_job(job);
+    Ok(result)
+}
+
+fn revert(job_id: &str) -> Result<ReplaceResult, String> {
+    let mut job = take_job(job_id, JobState::Applied)?;
+    let (mut files, mut conflicts) = (Vec::new(), Vec::new());
+    let workspace = job.root.to_string_lossy().to_string();
+
+    for change in &job.changes {
+        if fs::read_to_string(&change.path).ok().as_deref() != Some(change.replaced.as_str()) {
+            conflicts.push(change.path.clone());
+            continue;
+        }
+        // The snapshot is the source of truth; the copy in memory is a fallback
+        let original = match (&change.snapshot, crate::links::relative_path(&job.root, Path::new(&change.path))) {
+            (Some(timestamp), Some(rel)) => {
+                crate::handlers::version_history::get_version_content(workspace.clone(), rel, timestamp.clone())
+                    .unwrap_or_else(|_| change.original.clone())
+            }
+            _ => change.original.clone(),
+        };
+        crate::handlers::files::write_file_content(change.path.clone(), original)?;
+        files.push(change.path.clone());
+    }
+
+    job.state = JobState::Reverted;
+    let result = ReplaceResult { job_id: job.id.clone(), files, conflicts };
+    store_job(job);
+    Ok(result)
+}
+
+// --- Tauri Commands ---
+
+/// Find every match of `query` and how each line would read after the
+/// replace, without changing anything
+#[tauri::command]
+pub async fn bulk_replace_preview(
+    workspace_path: String,
+    query: String,
+    replacement: String,
+    options: Option<ReplaceOptions>,
+) -> Result<ReplacePreview, String> {
+    let root = PathBuf::from(workspace_path);
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || preview(&root, &query, &replacement, &options))
+        .await
+        .map_err(|e| format!("Find and replace failed: {}", e))?
+}
+
+/// Write a previewed replace, snapshotting every changed note first
+#[tauri::command]
+pub async fn bulk_replace_apply(job_id: String) -> Result<ReplaceResult, String> {
+    tokio::task::spawn_blocking(move || apply(&job_id))
+        .await
+        .map_err(|e| format!("Find and replace failed: {}", e))?
+}
+
+/// Undo an applied replace on every note not edited since
+#[tauri::command]
+pub async fn bulk_replace_revert(job_id: String) -> Result<ReplaceResult, String> {
+    tokio::task::spawn_blocking(move || revert(&job_id))
+        .await
+        .map_err(|e| format!("Undoing find and replace failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_lines() {
+        let options = ReplaceOptions { whole_word: true, ..Default::default() };
+        let re = build_regex("cat", &options).unwrap();
+        let (out, count, previews) = replace_lines("Cat and catalog\r\ncat$\n", &re, "dog$1", true);
+        assert_eq!(out, "dog$1 and catalog\r\ndog$1$\n");
+        assert_eq!(count, 2);
+        assert_eq!(previews[0].before, "Cat and catalog");
+
+        let options = ReplaceOptions { regex: true, case_sensitive: true, ..Default::default() };
+        let re = build_regex(r"(\d{4})-(\d{2})", &options).unwrap();
+        assert_eq!(replace_lines("2026-03 and 2025-12", &re, "$2/$1", false).0, "03/2026 and 12/2025");
+        assert!(build_regex("(", &options).is_err());
+    }
+
+    #[test]
+    fn test_apply_and_revert() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join(".lokus")).unwrap();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::write(root.join("Projects/a.md"), "Acme ships\nacme again").unwrap();
+        fs::write(root.join("Projects/b.md"), "Acme").unwrap();
+        fs::write(root.join("c.md"), "Acme outside").unwrap();
+
+        let options = ReplaceOptions { folder: Some("Projects".into()), ..Default::default() };
+        let preview = preview(&root, "acme", "Globex", &options).unwrap();
+        assert_eq!((preview.files.len(), preview.total_matches), (2, 3));
+
+        // Edited after the preview, so left alone
+        fs::write(root.join("Projects/b.md"), "Acme, edited").unwrap();
+        let applied = apply(&preview.job_id).unwrap();
+        assert_eq!(applied.files.len(), 1);
+        assert_eq!(applied.conflicts.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("Projects/a.md")).unwrap(), "Globex ships\nGlobex again");
+        assert_eq!(fs::read_to_string(root.join("c.md")).unwrap(), "Acme outside");
+        assert!(apply(&preview.job_id).is_err());
+
+        let reverted = revert(&preview.job_id).unwrap();
+        assert_eq!(reverted.files.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("Projects/a.md")).unwrap(), "Acme ships\nacme again");
+        assert!(revert(&preview.job_id).is_err());
+    }
+}
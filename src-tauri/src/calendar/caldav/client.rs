@@ -1,12 +1,16 @@
 use crate::calendar::models::{
     Calendar, CalendarEvent, CalendarProvider, CalDAVAccount, CalendarError,
-    CreateEventRequest, UpdateEventRequest,
+    CreateEventRequest, UpdateEventRequest, EventChanges,
 };
 use crate::calendar::ical;
+use crate::connections::request_queue;
 use chrono::{DateTime, Utc};
 use reqwest::{Client, Method, StatusCode};
 use uuid::Uuid;
 
+/// Request queue shared by every CalDAV call
+const QUEUE: &str = "caldav";
+
 /// CalDAV client for interacting with CalDAV servers (iCloud, Fastmail, etc.)
 pub struct CalDAVClient {
     client: Client,
@@ -35,16 +39,17 @@ impl CalDAVClient {
         loop {
             println!("[CalDAV] Requesting: {} {}", String::from_utf8_lossy(method), current_url);
 
-            let response = self.client
-                .request(Method::from_bytes(method).unwrap(), &current_url)
-                .header("Authorization", self.auth_header())
-                .header("Content-Type", "application/xml; charset=utf-8")
-                .header("Depth", "0")
-                .header("User-Agent", "Lokus/1.0 (CalDAV Client)")
-                .body(body.clone())
-                .send()
-                .await
-                .map_err(|e| CalendarError::Network(e.to_string()))?;
+            let response = request_queue::send(QUEUE, || {
+                self.client
+                    .request(Method::from_bytes(method).unwrap(), &current_url)
+                    .header("Authorization", self.auth_header())
+                    .header("Content-Type", "application/xml; charset=utf-8")
+                    .header("Depth", "0")
+                    .header("User-Agent", "Lokus/1.0 (CalDAV Client)")
+                    .body(body.clone())
+            })
+            .await
+            .map_err(CalendarError::Network)?;
 
             let status = response.status();
             println!("[CalDAV] Response status: {}", status);
@@ -155,15 +160,16 @@ impl CalDAVClient {
 </D:propfind>"#;
 
         let url = self.resolve_url(&principal_url);
-        let response = self.client
-            .request(Method::from_bytes(b"PROPFIND").unwrap(), &url)
-            .header("Authorization", self.auth_header())
-            .header("Content-Type", "application/xml; charset=utf-8")
-            .header("Depth", "0")
-            .body(body)
-            .send()
-            .await
-            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .request(Method::from_bytes(b"PROPFIND").unwrap(), &url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .header("Depth", "0")
+                .body(body.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !response.status().is_success() && response.status() != StatusCode::MULTI_STATUS {
             return Err(CalendarError::Api(format!(
@@ -205,16 +211,17 @@ impl CalDAVClient {
         let url = self.resolve_url(&home_url);
         println!("[CalDAV] PROPFIND URL: {}", url);
 
-        let response = self.client
-            .request(Method::from_bytes(b"PROPFIND").unwrap(), &url)
-            .header("Authorization", self.auth_header())
-            .header("Content-Type", "application/xml; charset=utf-8")
-            .header("Depth", "1")
-            .header("User-Agent", "Lokus/1.0 (CalDAV Client)")
-            .body(body)
-            .send()
-            .await
-            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .request(Method::from_bytes(b"PROPFIND").unwrap(), &url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .header("Depth", "1")
+                .header("User-Agent", "Lokus/1.0 (CalDAV Client)")
+                .body(body.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         println!("[CalDAV] list_calendars response status: {}", response.status());
 
@@ -271,15 +278,16 @@ impl CalDAVClient {
         let url = self.resolve_url(calendar_url);
         println!("[CalDAV] REPORT URL: {}", url);
 
-        let response = self.client
-            .request(Method::from_bytes(b"REPORT").unwrap(), &url)
-            .header("Authorization", self.auth_header())
-            .header("Content-Type", "application/xml; charset=utf-8")
-            .header("Depth", "1")
-            .body(body)
-            .send()
-            .await
-            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .request(Method::from_bytes(b"REPORT").unwrap(), &url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .header("Depth", "1")
+                .body(body.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         println!("[CalDAV] REPORT response status: {}", response.status());
 
@@ -311,15 +319,16 @@ impl CalDAVClient {
         let event_url = format!("{}/{}.ics", calendar_url.trim_end_matches('/'), uid);
         let url = self.resolve_url(&event_url);
 
-        let response = self.client
-            .put(&url)
-            .header("Authorization", self.auth_header())
-            .header("Content-Type", "text/calendar; charset=utf-8")
-            .header("If-None-Match", "*")
-            .body(ics_content.clone())
-            .send()
-            .await
-            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .put(&url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "text/calendar; charset=utf-8")
+                .header("If-None-Match", "*")
+                .body(ics_content.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !response.status().is_success() {
             return Err(CalendarError::Api(format!(
@@ -336,6 +345,43 @@ impl CalDAVClient {
             .ok_or_else(|| CalendarError::Parse("Failed to parse created event".to_string()))
     }
 
+    /// Get a single event by ID
+    pub async fn get_event(
+        &self,
+        calendar_url: &str,
+        event_id: &str,
+    ) -> Result<CalendarEvent, CalendarError> {
+        let event_url = format!("{}/{}.ics", calendar_url.trim_end_matches('/'), event_id);
+        let url = self.resolve_url(&event_url);
+
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .get(&url)
+                .header("Authorization", self.auth_header())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
+
+        if !response.status().is_success() {
+            return Err(CalendarError::NotFound(format!("Event not found: {}", event_id)));
+        }
+
+        let etag = response.headers()
+            .get("ETag")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let ics = response.text().await
+            .map_err(|e| CalendarError::Network(e.to_string()))?;
+
+        let mut event = ical::parse_ics_content(&ics, calendar_url)
+            .map_err(|e| CalendarError::Parse(e))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| CalendarError::Parse("Failed to parse event".to_string()))?;
+        event.etag = etag.or(event.etag);
+        Ok(event)
+    }
+
     /// Update an existing event
     pub async fn update_event(
         &self,
@@ -349,12 +395,13 @@ impl CalDAVClient {
         let url = self.resolve_url(&event_url);
 
         // Fetch existing event
-        let existing = self.client
-            .get(&url)
-            .header("Authorization", self.auth_header())
-            .send()
-            .await
-            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        let existing = request_queue::send(QUEUE, || {
+            self.client
+                .get(&url)
+                .header("Authorization", self.auth_header())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !existing.status().is_success() {
             return Err(CalendarError::NotFound(format!("Event not found: {}", event_id)));
@@ -367,20 +414,19 @@ impl CalDAVClient {
         let updated_ics = self.update_ics_event(&existing_ics, updates)?;
 
         // PUT the updated event
-        let mut request = self.client
-            .put(&url)
-            .header("Authorization", self.auth_header())
-            .header("Content-Type", "text/calendar; charset=utf-8");
-
-        if let Some(etag) = etag {
-            request = request.header("If-Match", etag);
-        }
-
-        let response = request
-            .body(updated_ics.clone())
-            .send()
-            .await
-            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        let response = request_queue::send(QUEUE, || {
+            let request = self.client
+                .put(&url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "text/calendar; charset=utf-8")
+                .body(updated_ics.clone());
+            match etag {
+                Some(etag) => request.header("If-Match", etag),
+                None => request,
+            }
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !response.status().is_success() {
             return Err(CalendarError::Api(format!(
@@ -407,27 +453,175 @@ impl CalDAVClient {
         let event_url = format!("{}/{}.ics", calendar_url.trim_end_matches('/'), event_id);
         let url = self.resolve_url(&event_url);
 
-        let mut request = self.client
-            .delete(&url)
-            .header("Authorization", self.auth_header());
+        let response = request_queue::send(QUEUE, || {
+            let request = self.client
+                .delete(&url)
+                .header("Authorization", self.auth_header());
+            match etag {
+                Some(etag) => request.header("If-Match", etag),
+                None => request,
+            }
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
-        if let Some(etag) = etag {
-            request = request.header("If-Match", etag);
+        if !response.status().is_success() && response.status() != StatusCode::NO_CONTENT {
+            return Err(CalendarError::Api(format!(
+                "Failed to delete event: {}",
+                response.status()
+            )));
         }
 
-        let response = request
-            .send()
-            .await
+        Ok(())
+    }
+
+    /// Current collection tag of a calendar; it changes whenever any event does
+    pub async fn get_ctag(&self, calendar_url: &str) -> Result<Option<String>, CalendarError> {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:propfind xmlns:D="DAV:" xmlns:CS="http://calendarserver.org/ns/">
+  <D:prop>
+    <CS:getctag/>
+  </D:prop>
+</D:propfind>"#;
+
+        let url = self.resolve_url(calendar_url);
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .request(Method::from_bytes(b"PROPFIND").unwrap(), &url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .header("Depth", "0")
+                .body(body.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
+
+        if !response.status().is_success() && response.status() != StatusCode::MULTI_STATUS {
+            return Err(CalendarError::Api(format!(
+                "Failed to get ctag: {}",
+                response.status()
+            )));
+        }
+
+        let text = response.text().await
             .map_err(|e| CalendarError::Network(e.to_string()))?;
+        Ok(self.extract_xml_text(&text, "getctag"))
+    }
 
-        if !response.status().is_success() && response.status() != StatusCode::NO_CONTENT {
+    /// Changes since `sync_token` using a sync-collection REPORT (RFC 6578);
+    /// without a token every event is listed. Fails with `SyncTokenExpired`
+    /// when the server no longer accepts the token, and with `Api` when it
+    /// doesn't support sync-collection at all.
+    pub async fn sync_events(
+        &self,
+        calendar_url: &str,
+        sync_token: Option<&str>,
+    ) -> Result<EventChanges, CalendarError> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<D:sync-collection xmlns:D="DAV:">
+  <D:sync-token>{}</D:sync-token>
+  <D:sync-level>1</D:sync-level>
+  <D:prop>
+    <D:getetag/>
+  </D:prop>
+</D:sync-collection>"#,
+            xml_escape(sync_token.unwrap_or(""))
+        );
+
+        let url = self.resolve_url(calendar_url);
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .request(Method::from_bytes(b"REPORT").unwrap(), &url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(body.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
+
+        let status = response.status();
+        let text = response.text().await
+            .map_err(|e| CalendarError::Network(e.to_string()))?;
+
+        if sync_token.is_some()
+            && (status == StatusCode::FORBIDDEN || status == StatusCode::CONFLICT)
+            && text.contains("valid-sync-token")
+        {
+            return Err(CalendarError::SyncTokenExpired);
+        }
+
+        if status != StatusCode::MULTI_STATUS {
             return Err(CalendarError::Api(format!(
-                "Failed to delete event: {}",
+                "sync-collection not supported: {}",
+                status
+            )));
+        }
+
+        let mut changes = EventChanges { full: sync_token.is_none(), ..Default::default() };
+        let mut changed_hrefs = Vec::new();
+        let collection = calendar_url.trim_end_matches('/');
+        for response in Self::split_responses(&text) {
+            let Some(href) = self.extract_xml_text(response, "href") else {
+                continue;
+            };
+            // The collection itself may be listed alongside its members
+            if href.trim_end_matches('/').ends_with(collection) {
+                continue;
+            }
+            let status = self.extract_xml_text(response, "status").unwrap_or_default();
+            if status.contains(" 404 ") {
+                changes.deleted.push(Self::event_id_from_href(&href));
+            } else {
+                changed_hrefs.push(href);
+            }
+        }
+        changes.sync_token = self.extract_xml_text(&text, "sync-token");
+
+        for hrefs in changed_hrefs.chunks(50) {
+            changes.changed.extend(self.multiget(calendar_url, hrefs).await?);
+        }
+        Ok(changes)
+    }
+
+    /// Fetch specific events by href with a calendar-multiget REPORT
+    async fn multiget(&self, calendar_url: &str, hrefs: &[String]) -> Result<Vec<CalendarEvent>, CalendarError> {
+        let href_elements: String = hrefs.iter()
+            .map(|href| format!("  <D:href>{}</D:href>\n", xml_escape(href)))
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop>
+    <D:getetag/>
+    <C:calendar-data/>
+  </D:prop>
+{}</C:calendar-multiget>"#,
+            href_elements
+        );
+
+        let url = self.resolve_url(calendar_url);
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .request(Method::from_bytes(b"REPORT").unwrap(), &url)
+                .header("Authorization", self.auth_header())
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .header("Depth", "1")
+                .body(body.clone())
+        })
+        .await
+        .map_err(CalendarError::Network)?;
+
+        if !response.status().is_success() && response.status() != StatusCode::MULTI_STATUS {
+            return Err(CalendarError::Api(format!(
+                "Failed to fetch events: {}",
                 response.status()
             )));
         }
 
-        Ok(())
+        let text = response.text().await
+            .map_err(|e| CalendarError::Network(e.to_string()))?;
+        self.parse_events(&text, calendar_url)
     }
 
     /// Get updated account with discovered URLs
@@ -652,31 +846,14 @@ impl CalDAVClient {
     // Helper: Parse calendars from XML
     fn parse_calendars(&self, xml: &str, home_url: &str) -> Result<Vec<Calendar>, CalendarError> {
         let mut calendars = Vec::new();
-        let xml_lower = xml.to_lowercase();
 
         // Debug: print part of the response to understand format
         println!("[CalDAV] parse_calendars XML sample (1500 chars): {}", &xml[..xml.len().min(1500)]);
 
-        // Split by response elements (case-insensitive search)
-        // Find all <response> or <d:response> or <D:response> tags
-        let mut response_starts: Vec<usize> = Vec::new();
-        let patterns = ["<response>", "<response ", "<d:response>", "<d:response "];
-        for pattern in patterns {
-            let mut start = 0;
-            while let Some(pos) = xml_lower[start..].find(pattern) {
-                response_starts.push(start + pos);
-                start = start + pos + pattern.len();
-            }
-        }
-        response_starts.sort();
-        response_starts.dedup();
-
-        println!("[CalDAV] Found {} response elements", response_starts.len());
+        let responses = Self::split_responses(xml);
+        println!("[CalDAV] Found {} response elements", responses.len());
 
-        for (i, &start) in response_starts.iter().enumerate() {
-            // Find the end of this response (next response or end)
-            let end = response_starts.get(i + 1).copied().unwrap_or(xml.len());
-            let response = &xml[start..end];
+        for (i, response) in responses.into_iter().enumerate() {
             let response_lower = response.to_lowercase();
 
             // Extract href first to check if it's the home URL
@@ -762,6 +939,37 @@ impl CalDAVClient {
         Ok(calendars)
     }
 
+    // Helper: Split a multistatus body into its <response> elements
+    fn split_responses(xml: &str) -> Vec<&str> {
+        let xml_lower = xml.to_lowercase();
+
+        // Find all <response> or <d:response> or <D:response> tags (case-insensitive search)
+        let mut response_starts: Vec<usize> = Vec::new();
+        let patterns = ["<response>", "<response ", "<d:response>", "<d:response "];
+        for pattern in patterns {
+            let mut start = 0;
+            while let Some(pos) = xml_lower[start..].find(pattern) {
+                response_starts.push(start + pos);
+                start = start + pos + pattern.len();
+            }
+        }
+        response_starts.sort();
+        response_starts.dedup();
+
+        // Each response runs to the next one (or the end)
+        response_starts.iter()
+            .enumerate()
+            .map(|(i, &start)| &xml[start..response_starts.get(i + 1).copied().unwrap_or(xml.len())])
+            .collect()
+    }
+
+    // Helper: Event id from a resource href (`.../<id>.ics`)
+    fn event_id_from_href(href: &str) -> String {
+        let name = href.trim_end_matches('/').rsplit('/').next().unwrap_or(href);
+        let name = name.strip_suffix(".ics").unwrap_or(name);
+        urlencoding::decode(name).map(|n| n.into_owned()).unwrap_or_else(|_| name.to_string())
+    }
+
     // Helper: Parse events from XML REPORT response
     fn parse_events(&self, xml: &str, calendar_id: &str) -> Result<Vec<CalendarEvent>, CalendarError> {
         let mut events = Vec::new();
@@ -1039,6 +1247,10 @@ impl CalDAVClient {
     }
 }
 
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
 /// Test connection to CalDAV server
 pub async fn test_connection(server_url: &str, username: &str, password: &str) -> Result<CalDAVAccount, CalendarError> {
     let account = CalDAVAccount {
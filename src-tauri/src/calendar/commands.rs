@@ -2,36 +2,45 @@ use std::sync::Mutex;
 use tauri::{State, AppHandle, Emitter};
 use chrono::{DateTime, Utc};
 use crate::calendar::models::{
-    Calendar, CalendarEvent, CalendarAccount, CalendarProvider,
+    Calendar, CalendarError, CalendarEvent, CalendarAccount, CalendarProvider,
     CreateEventRequest, UpdateEventRequest, SyncStatus, SyncResult, ICalSubscription, CalDAVAccount,
 };
 use crate::calendar::storage::CalendarStorage;
 use crate::calendar::google::{GoogleCalendarAuth, GoogleCalendarApi, PKCEData};
 use crate::calendar::ical;
 use crate::calendar::caldav;
+use crate::calendar::outlook::{OutlookCalendarAuth, OutlookCalendarApi};
+use crate::calendar::offline;
+use crate::connections::oauth_manager::{self, Provider};
+use crate::errors::LokusError;
 
 /// Shared state for calendar authentication
 pub struct CalendarAuthState {
     pub pkce_data: Option<PKCEData>,
+    pub outlook_pkce_data: Option<PKCEData>,
 }
 
 impl Default for CalendarAuthState {
     fn default() -> Self {
         Self {
             pkce_data: None,
+            outlook_pkce_data: None,
         }
     }
 }
 
 pub type SharedCalendarAuthState = Mutex<CalendarAuthState>;
 
+const GOOGLE_PKCE_FILE: &str = "calendar_pkce.json";
+const OUTLOOK_PKCE_FILE: &str = "outlook_calendar_pkce.json";
+
 // File-based PKCE storage for persistence across restarts
-fn get_pkce_file_path() -> Option<std::path::PathBuf> {
-    dirs::home_dir().map(|h| h.join(".lokus").join("temp").join("calendar_pkce.json"))
+fn get_pkce_file_path(file_name: &str) -> Option<std::path::PathBuf> {
+    dirs::home_dir().map(|h| h.join(".lokus").join("temp").join(file_name))
 }
 
-fn save_pkce_to_file(pkce: &PKCEData) -> Result<(), String> {
-    let path = get_pkce_file_path().ok_or("Could not get home directory")?;
+fn save_pkce_to_file(file_name: &str, pkce: &PKCEData) -> Result<(), String> {
+    let path = get_pkce_file_path(file_name).ok_or("Could not get home directory")?;
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
@@ -45,8 +54,8 @@ fn save_pkce_to_file(pkce: &PKCEData) -> Result<(), String> {
     std::fs::write(&path, json_str).map_err(|e| e.to_string())
 }
 
-fn load_pkce_from_file() -> Option<PKCEData> {
-    let path = get_pkce_file_path()?;
+fn load_pkce_from_file(file_name: &str) -> Option<PKCEData> {
+    let path = get_pkce_file_path(file_name)?;
     let content = std::fs::read_to_string(&path).ok()?;
     let json: serde_json::Value = serde_json::from_str(&content).ok()?;
     Some(PKCEData {
@@ -56,8 +65,8 @@ fn load_pkce_from_file() -> Option<PKCEData> {
     })
 }
 
-fn delete_pkce_file() {
-    if let Some(path) = get_pkce_file_path() {
+fn delete_pkce_file(file_name: &str) {
+    if let Some(path) = get_pkce_file_path(file_name) {
         let _ = std::fs::remove_file(path);
     }
 }
@@ -68,9 +77,8 @@ fn delete_pkce_file() {
 #[tauri::command]
 pub async fn google_calendar_auth_start(
     calendar_state: State<'_, SharedCalendarAuthState>,
-) -> Result<String, String> {
-    let auth = GoogleCalendarAuth::new()
-        .map_err(|e| e.to_string())?;
+) -> Result<String, LokusError> {
+    let auth = GoogleCalendarAuth::new()?;
 
     let (code_verifier, code_challenge) = GoogleCalendarAuth::generate_pkce_pair();
     let state = GoogleCalendarAuth::generate_state();
@@ -81,11 +89,10 @@ pub async fn google_calendar_auth_start(
         state,
     };
 
-    let auth_url = auth.generate_auth_url(&pkce_data)
-        .map_err(|e| e.to_string())?;
+    let auth_url = auth.generate_auth_url(&pkce_data)?;
 
     // Store PKCE data for callback verification (both in memory and file for persistence)
-    save_pkce_to_file(&pkce_data).map_err(|e| format!("Failed to save PKCE: {}", e))?;
+    save_pkce_to_file(GOOGLE_PKCE_FILE, &pkce_data).map_err(|e| format!("Failed to save PKCE: {}", e))?;
     {
         let mut calendar_state_guard = calendar_state.lock()
             .map_err(|e| format!("Calendar state lock failed: {}", e))?;
@@ -102,7 +109,7 @@ pub async fn google_calendar_auth_complete(
     state: String,
     calendar_state: State<'_, SharedCalendarAuthState>,
     app_handle: AppHandle,
-) -> Result<CalendarAccount, String> {
+) -> Result<CalendarAccount, LokusError> {
     let pkce_data = {
         let calendar_state_guard = calendar_state.lock()
             .map_err(|e| format!("Calendar state lock failed: {}", e))?;
@@ -114,24 +121,21 @@ pub async fn google_calendar_auth_complete(
 
     // Verify state parameter
     if state != pkce_data.state {
-        return Err("Invalid state parameter".to_string());
+        return Err(LokusError::invalid("Invalid state parameter"));
     }
 
-    let auth = GoogleCalendarAuth::new()
-        .map_err(|e| e.to_string())?;
+    let auth = GoogleCalendarAuth::new()?;
 
     // Exchange code for token
     let token = auth.exchange_code_for_token(&code, &pkce_data.code_verifier)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Fetch and store account info
     let account = auth.fetch_and_store_account(&token)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Clear PKCE data (both memory and file)
-    delete_pkce_file();
+    delete_pkce_file(GOOGLE_PKCE_FILE);
     {
         let mut calendar_state_guard = calendar_state.lock()
             .map_err(|e| format!("Calendar state lock failed: {}", e))?;
@@ -152,13 +156,13 @@ pub async fn google_calendar_auth_complete(
 pub async fn google_calendar_check_auth_callback(
     calendar_state: State<'_, SharedCalendarAuthState>,
     app_handle: AppHandle,
-) -> Result<Option<CalendarAccount>, String> {
+) -> Result<Option<CalendarAccount>, LokusError> {
     // Check if there's a pending auth (try memory first, then file)
     let pkce_data = {
         let calendar_state_guard = calendar_state.lock()
             .map_err(|e| format!("Calendar state lock failed: {}", e))?;
         calendar_state_guard.pkce_data.clone()
-    }.or_else(|| load_pkce_from_file());
+    }.or_else(|| load_pkce_from_file(GOOGLE_PKCE_FILE));
 
     if pkce_data.is_none() {
         return Ok(None);
@@ -194,24 +198,21 @@ pub async fn google_calendar_check_auth_callback(
 
     // Verify state parameter
     if state != pkce_data.state {
-        return Err("Invalid state parameter".to_string());
+        return Err(LokusError::invalid("Invalid state parameter"));
     }
 
-    let auth = GoogleCalendarAuth::new()
-        .map_err(|e| e.to_string())?;
+    let auth = GoogleCalendarAuth::new()?;
 
     // Exchange code for token
     let token = auth.exchange_code_for_token(code, &pkce_data.code_verifier)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Fetch and store account info
     let account = auth.fetch_and_store_account(&token)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Clear PKCE data (both memory and file)
-    delete_pkce_file();
+    delete_pkce_file(GOOGLE_PKCE_FILE);
     {
         let mut calendar_state_guard = calendar_state.lock()
             .map_err(|e| format!("Calendar state lock failed: {}", e))?;
@@ -229,53 +230,144 @@ pub async fn google_calendar_check_auth_callback(
 
 /// Check if Google Calendar is authenticated
 #[tauri::command]
-pub fn google_calendar_auth_status() -> Result<bool, String> {
-    let auth = GoogleCalendarAuth::new()
-        .map_err(|e| e.to_string())?;
+pub fn google_calendar_auth_status() -> Result<bool, LokusError> {
+    let auth = GoogleCalendarAuth::new()?;
 
     auth.is_authenticated()
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Get the connected Google Calendar account
 #[tauri::command]
-pub fn google_calendar_get_account() -> Result<Option<CalendarAccount>, String> {
+pub fn google_calendar_get_account() -> Result<Option<CalendarAccount>, LokusError> {
     CalendarStorage::get_google_account()
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
-/// Disconnect calendar provider (Google or CalDAV)
+// ============== Outlook Authentication Commands ==============
+
+/// Start Outlook / Office 365 OAuth flow
 #[tauri::command]
-pub async fn calendar_disconnect(provider: String, app_handle: AppHandle) -> Result<(), String> {
+pub async fn outlook_calendar_auth_start(
+    calendar_state: State<'_, SharedCalendarAuthState>,
+) -> Result<String, LokusError> {
+    let auth = OutlookCalendarAuth::new()?;
+
+    let (code_verifier, code_challenge) = GoogleCalendarAuth::generate_pkce_pair();
+    let pkce_data = PKCEData {
+        code_verifier,
+        code_challenge,
+        state: GoogleCalendarAuth::generate_state(),
+    };
+
+    let auth_url = auth.generate_auth_url(&pkce_data)?;
+
+    save_pkce_to_file(OUTLOOK_PKCE_FILE, &pkce_data).map_err(|e| format!("Failed to save PKCE: {}", e))?;
+    {
+        let mut calendar_state_guard = calendar_state.lock()
+            .map_err(|e| format!("Calendar state lock failed: {}", e))?;
+        calendar_state_guard.outlook_pkce_data = Some(pkce_data);
+    }
+
+    Ok(auth_url)
+}
+
+/// Check for the Outlook OAuth callback and complete auth if available
+#[tauri::command]
+pub async fn outlook_calendar_check_auth_callback(
+    calendar_state: State<'_, SharedCalendarAuthState>,
+    app_handle: AppHandle,
+) -> Result<Option<CalendarAccount>, LokusError> {
+    let pkce_data = {
+        let calendar_state_guard = calendar_state.lock()
+            .map_err(|e| format!("Calendar state lock failed: {}", e))?;
+        calendar_state_guard.outlook_pkce_data.clone()
+    }.or_else(|| load_pkce_from_file(OUTLOOK_PKCE_FILE));
+
+    let Some(pkce_data) = pkce_data else {
+        return Ok(None);
+    };
+
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| "Could not find home directory".to_string())?;
+    let callback_file = home_dir.join(".lokus").join("temp").join("outlook_auth_callback.json");
+
+    if !callback_file.exists() {
+        return Ok(None);
+    }
+
+    let callback_content = std::fs::read_to_string(&callback_file)
+        .map_err(|e| format!("Failed to read callback file: {}", e))?;
+    let _ = std::fs::remove_file(&callback_file);
+
+    let callback_data: serde_json::Value = serde_json::from_str(&callback_content)
+        .map_err(|e| format!("Failed to parse callback data: {}", e))?;
+
+    let code = callback_data["code"]
+        .as_str()
+        .ok_or_else(|| "Missing code in callback".to_string())?;
+    let state = callback_data["state"]
+        .as_str()
+        .ok_or_else(|| "Missing state in callback".to_string())?;
+
+    if state != pkce_data.state {
+        return Err(LokusError::invalid("Invalid state parameter"));
+    }
+
+    let auth = OutlookCalendarAuth::new()?;
+
+    let token = auth.exchange_code_for_token(code, &pkce_data.code_verifier)
+        .await?;
+
+    let account = auth.fetch_and_store_account(&token)
+        .await?;
+
+    delete_pkce_file(OUTLOOK_PKCE_FILE);
+    {
+        let mut calendar_state_guard = calendar_state.lock()
+            .map_err(|e| format!("Calendar state lock failed: {}", e))?;
+        calendar_state_guard.outlook_pkce_data = None;
+    }
+
+    let _ = app_handle.emit("calendar-auth-success", serde_json::json!({
+        "provider": "outlook",
+        "email": account.email.clone()
+    }));
+
+    Ok(Some(account))
+}
+
+/// Check if Outlook is authenticated
+#[tauri::command]
+pub fn outlook_calendar_auth_status() -> Result<bool, LokusError> {
+    let auth = OutlookCalendarAuth::new()?;
+
+    auth.is_authenticated()
+        .map_err(LokusError::from)
+}
+
+/// Get the connected Outlook account
+#[tauri::command]
+pub fn outlook_calendar_get_account() -> Result<Option<CalendarAccount>, LokusError> {
+    CalendarStorage::get_outlook_account()
+        .map_err(LokusError::from)
+}
+
+/// Disconnect calendar provider (Google, Outlook or CalDAV)
+#[tauri::command]
+pub async fn calendar_disconnect(provider: String, app_handle: AppHandle) -> Result<(), LokusError> {
     println!("[Calendar] Disconnecting provider: {}", provider);
 
     match provider.as_str() {
         "google" => {
-            // IMPORTANT: Get the token BEFORE deleting storage, so we can revoke it
-            let token_to_revoke = CalendarStorage::get_google_token()
-                .ok()
-                .flatten()
-                .map(|t| t.access_token.clone());
-
-            // Clear Google storage FIRST to prevent race conditions with token refresh
-            // This ensures no background task can refresh/restore the token
-            let _ = CalendarStorage::delete_google_token();
-            let _ = CalendarStorage::delete_google_account();
+            // Clears local credentials before revoking, so a refresh can't restore them
+            let _ = oauth_manager::revoke(Provider::GoogleCalendar).await;
 
             // Remove Google calendars from storage (keep CalDAV ones)
             let mut calendars = CalendarStorage::get_calendars().unwrap_or_default();
             calendars.retain(|c| c.provider != CalendarProvider::Google);
             let _ = CalendarStorage::store_calendars(&calendars);
 
-            // Try to revoke the token with Google (optional, can fail without issue)
-            // This is done AFTER local storage is cleared to prevent race conditions
-            if let Some(access_token) = token_to_revoke {
-                if let Ok(auth) = GoogleCalendarAuth::new() {
-                    // Ignore revocation errors - local cleanup is already done
-                    let _ = auth.revoke_token_only(&access_token).await;
-                }
-            }
-
             // Emit disconnect event
             let _ = app_handle.emit("calendar-disconnected", serde_json::json!({
                 "provider": "google"
@@ -283,6 +375,19 @@ pub async fn calendar_disconnect(provider: String, app_handle: AppHandle) -> Res
 
             Ok(())
         }
+        "outlook" => {
+            let _ = oauth_manager::revoke(Provider::Outlook).await;
+
+            let mut calendars = CalendarStorage::get_calendars().unwrap_or_default();
+            calendars.retain(|c| c.provider != CalendarProvider::Outlook);
+            let _ = CalendarStorage::store_calendars(&calendars);
+
+            let _ = app_handle.emit("calendar-disconnected", serde_json::json!({
+                "provider": "outlook"
+            }));
+
+            Ok(())
+        }
         "caldav" => {
             // Remove CalDAV calendars from storage (keep Google ones)
             let mut calendars = CalendarStorage::get_calendars().unwrap_or_default();
@@ -290,8 +395,7 @@ pub async fn calendar_disconnect(provider: String, app_handle: AppHandle) -> Res
             let _ = CalendarStorage::store_calendars(&calendars);
 
             // Delete CalDAV account credentials
-            CalendarStorage::delete_caldav_account()
-                .map_err(|e| e.to_string())?;
+            CalendarStorage::delete_caldav_account()?;
 
             // Emit disconnect event
             let _ = app_handle.emit("calendar-disconnected", serde_json::json!({
@@ -301,20 +405,19 @@ pub async fn calendar_disconnect(provider: String, app_handle: AppHandle) -> Res
             println!("[Calendar] CalDAV disconnected successfully");
             Ok(())
         }
-        _ => Err(format!("Unknown provider: {}", provider)),
+        _ => Err(LokusError::invalid(format!("Unknown provider: {}", provider))),
     }
 }
 
 // ============== Calendar Commands ==============
 
-/// Get all calendars from connected accounts (Google + CalDAV)
+/// Get all calendars from connected accounts (Google + Outlook + CalDAV)
 #[tauri::command]
-pub async fn get_calendars() -> Result<Vec<Calendar>, String> {
+pub async fn get_calendars() -> Result<Vec<Calendar>, LokusError> {
     let mut all_calendars = Vec::new();
 
     // Check if Google Calendar is connected
-    let auth = GoogleCalendarAuth::new()
-        .map_err(|e| e.to_string())?;
+    let auth = GoogleCalendarAuth::new()?;
 
     if auth.is_authenticated().unwrap_or(false) {
         if let Ok(api) = GoogleCalendarApi::new() {
@@ -325,6 +428,18 @@ pub async fn get_calendars() -> Result<Vec<Calendar>, String> {
         }
     }
 
+    // Check if Outlook is connected
+    if let Ok(auth) = OutlookCalendarAuth::new() {
+        if auth.is_authenticated().unwrap_or(false) {
+            if let Ok(api) = OutlookCalendarApi::new() {
+                if let Ok(outlook_calendars) = api.list_calendars().await {
+                    println!("[Calendar] Fetched {} Outlook calendars", outlook_calendars.len());
+                    all_calendars.extend(outlook_calendars);
+                }
+            }
+        }
+    }
+
     // Check if CalDAV is connected
     if let Ok(Some(account)) = CalendarStorage::get_caldav_account() {
         if account.is_connected {
@@ -349,9 +464,9 @@ pub async fn get_calendars() -> Result<Vec<Calendar>, String> {
 
 /// Get cached calendars (doesn't make API call)
 #[tauri::command]
-pub fn get_cached_calendars() -> Result<Vec<Calendar>, String> {
+pub fn get_cached_calendars() -> Result<Vec<Calendar>, LokusError> {
     CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 // ============== Event Commands ==============
@@ -363,73 +478,72 @@ pub async fn get_events(
     start: String,
     end: String,
     max_results: Option<u32>,
-) -> Result<Vec<CalendarEvent>, String> {
+) -> Result<Vec<CalendarEvent>, LokusError> {
     let start_time: DateTime<Utc> = start.parse()
-        .map_err(|e| format!("Invalid start time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid start time: {}", e)))?;
     let end_time: DateTime<Utc> = end.parse()
-        .map_err(|e| format!("Invalid end time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid end time: {}", e)))?;
 
     // Look up calendar to determine provider
-    let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
+    let calendars = CalendarStorage::get_calendars()?;
     let calendar = calendars.iter()
         .find(|c| c.id == calendar_id)
-        .ok_or_else(|| "Calendar not found".to_string())?;
+        .ok_or_else(|| CalendarError::NotFound(format!("Calendar {}", calendar_id)))?;
 
     match calendar.provider {
         CalendarProvider::Google => {
-            let api = GoogleCalendarApi::new()
-                .map_err(|e| e.to_string())?;
+            let api = GoogleCalendarApi::new()?;
             api.get_events(&calendar_id, start_time, end_time, max_results)
                 .await
-                .map_err(|e| e.to_string())
+                .map_err(LokusError::from)
         }
         CalendarProvider::CalDAV => {
-            let account = CalendarStorage::get_caldav_account()
-                .map_err(|e| e.to_string())?
-                .ok_or_else(|| "CalDAV not connected".to_string())?;
-            let client = caldav::CalDAVClient::new(account)
-                .map_err(|e| e.to_string())?;
+            let account = CalendarStorage::get_caldav_account()?
+                .ok_or(CalendarError::NotConnected)?;
+            let client = caldav::CalDAVClient::new(account)?;
             client.get_events(&calendar_id, start_time, end_time)
                 .await
-                .map_err(|e| e.to_string())
+                .map_err(LokusError::from)
         }
         CalendarProvider::ICal => {
             // iCal events are stored locally, filter by time range
-            let events = CalendarStorage::get_ical_events(&calendar_id)
-                .map_err(|e| e.to_string())?;
+            let events = CalendarStorage::get_ical_events(&calendar_id)?;
             Ok(events.into_iter()
                 .filter(|e| e.start <= end_time && e.end >= start_time)
                 .collect())
         }
+        CalendarProvider::Outlook => {
+            let api = OutlookCalendarApi::new()?;
+            api.get_events(&calendar_id, start_time, end_time)
+                .await
+                .map_err(LokusError::from)
+        }
         CalendarProvider::ICloud => {
             // ICloud uses CalDAV protocol
-            let account = CalendarStorage::get_caldav_account()
-                .map_err(|e| e.to_string())?
-                .ok_or_else(|| "iCloud not connected".to_string())?;
-            let client = caldav::CalDAVClient::new(account)
-                .map_err(|e| e.to_string())?;
+            let account = CalendarStorage::get_caldav_account()?
+                .ok_or(CalendarError::NotConnected)?;
+            let client = caldav::CalDAVClient::new(account)?;
             client.get_events(&calendar_id, start_time, end_time)
                 .await
-                .map_err(|e| e.to_string())
+                .map_err(LokusError::from)
         }
     }
 }
 
-/// Get events from all visible calendars (Google + iCal)
+/// Get events from all visible calendars (Google + Outlook + iCal + CalDAV)
 #[tauri::command]
 pub async fn get_all_events(
     start: String,
     end: String,
-) -> Result<Vec<CalendarEvent>, String> {
+) -> Result<Vec<CalendarEvent>, LokusError> {
     println!("[Calendar] get_all_events called: {} to {}", start, end);
 
     let start_time: DateTime<Utc> = start.parse()
-        .map_err(|e| format!("Invalid start time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid start time: {}", e)))?;
     let end_time: DateTime<Utc> = end.parse()
-        .map_err(|e| format!("Invalid end time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid end time: {}", e)))?;
 
-    let calendars = CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())?;
+    let calendars = CalendarStorage::get_calendars()?;
 
     println!("[Calendar] Found {} calendars, {} visible",
         calendars.len(),
@@ -459,6 +573,28 @@ pub async fn get_all_events(
         }
     }
 
+    // Fetch Outlook events
+    let outlook_calendars: Vec<_> = calendars.iter()
+        .filter(|c| c.visible && c.provider == CalendarProvider::Outlook)
+        .collect();
+
+    if !outlook_calendars.is_empty() {
+        if let Ok(api) = OutlookCalendarApi::new() {
+            for calendar in outlook_calendars {
+                println!("[Calendar] Fetching events from Outlook calendar: {} ({})", calendar.name, calendar.id);
+                match api.get_events(&calendar.id, start_time, end_time).await {
+                    Ok(events) => {
+                        println!("[Calendar] Got {} events from {}", events.len(), calendar.name);
+                        all_events.extend(events);
+                    },
+                    Err(e) => {
+                        println!("[Calendar] ERROR fetching from {}: {}", calendar.name, e);
+                    }
+                }
+            }
+        }
+    }
+
     // Fetch iCal events from visible iCal calendars
     let ical_calendars: Vec<_> = calendars.iter()
         .filter(|c| c.visible && c.provider == CalendarProvider::ICal)
@@ -512,40 +648,31 @@ pub async fn get_all_events(
     Ok(all_events)
 }
 
+fn find_writable_calendar(calendar_id: &str) -> Result<Calendar, LokusError> {
+    let calendar = CalendarStorage::get_calendars()?
+        .into_iter()
+        .find(|c| c.id == calendar_id)
+        .ok_or_else(|| CalendarError::NotFound(format!("Calendar {}", calendar_id)))?;
+
+    if calendar.provider == CalendarProvider::ICal {
+        return Err(LokusError::Unsupported("iCal subscriptions are read-only".to_string()));
+    }
+    Ok(calendar)
+}
+
 /// Create a new event
 #[tauri::command]
 pub async fn create_event(
     calendar_id: String,
     event: CreateEventRequest,
-) -> Result<CalendarEvent, String> {
+) -> Result<CalendarEvent, LokusError> {
     // Look up calendar to determine provider
-    let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
-    let calendar = calendars.iter()
-        .find(|c| c.id == calendar_id)
-        .ok_or_else(|| "Calendar not found".to_string())?;
+    let calendar = find_writable_calendar(&calendar_id)?;
 
-    match calendar.provider {
-        CalendarProvider::Google => {
-            let api = GoogleCalendarApi::new()
-                .map_err(|e| e.to_string())?;
-            api.create_event(&calendar_id, &event)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
-            let account = CalendarStorage::get_caldav_account()
-                .map_err(|e| e.to_string())?
-                .ok_or_else(|| "CalDAV not connected".to_string())?;
-            let client = caldav::CalDAVClient::new(account)
-                .map_err(|e| e.to_string())?;
-            client.create_event(&calendar_id, &event)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        CalendarProvider::ICal => {
-            Err("iCal subscriptions are read-only".to_string())
-        }
-    }
+    // Queued and applied locally when the provider can't be reached
+    offline::create_event(&calendar, event)
+        .await
+        .map_err(LokusError::from)
 }
 
 /// Update an existing event
@@ -555,35 +682,13 @@ pub async fn update_event(
     event_id: String,
     updates: UpdateEventRequest,
     etag: Option<String>,
-) -> Result<CalendarEvent, String> {
+) -> Result<CalendarEvent, LokusError> {
     // Look up calendar to determine provider
-    let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
-    let calendar = calendars.iter()
-        .find(|c| c.id == calendar_id)
-        .ok_or_else(|| "Calendar not found".to_string())?;
+    let calendar = find_writable_calendar(&calendar_id)?;
 
-    match calendar.provider {
-        CalendarProvider::Google => {
-            let api = GoogleCalendarApi::new()
-                .map_err(|e| e.to_string())?;
-            api.update_event(&calendar_id, &event_id, &updates)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
-            let account = CalendarStorage::get_caldav_account()
-                .map_err(|e| e.to_string())?
-                .ok_or_else(|| "CalDAV not connected".to_string())?;
-            let client = caldav::CalDAVClient::new(account)
-                .map_err(|e| e.to_string())?;
-            client.update_event(&calendar_id, &event_id, &updates, etag.as_deref())
-                .await
-                .map_err(|e| e.to_string())
-        }
-        CalendarProvider::ICal => {
-            Err("iCal subscriptions are read-only".to_string())
-        }
-    }
+    offline::update_event(&calendar, event_id, updates, etag)
+        .await
+        .map_err(LokusError::from)
 }
 
 /// Delete an event
@@ -592,86 +697,81 @@ pub async fn delete_event(
     calendar_id: String,
     event_id: String,
     etag: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), LokusError> {
     // Look up calendar to determine provider
-    let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
-    let calendar = calendars.iter()
-        .find(|c| c.id == calendar_id)
-        .ok_or_else(|| "Calendar not found".to_string())?;
+    let calendar = find_writable_calendar(&calendar_id)?;
 
-    match calendar.provider {
-        CalendarProvider::Google => {
-            let api = GoogleCalendarApi::new()
-                .map_err(|e| e.to_string())?;
-            api.delete_event(&calendar_id, &event_id)
-                .await
-                .map_err(|e| e.to_string())
-        }
-        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
-            let account = CalendarStorage::get_caldav_account()
-                .map_err(|e| e.to_string())?
-                .ok_or_else(|| "CalDAV not connected".to_string())?;
-            let client = caldav::CalDAVClient::new(account)
-                .map_err(|e| e.to_string())?;
-            client.delete_event(&calendar_id, &event_id, etag.as_deref())
-                .await
-                .map_err(|e| e.to_string())
-        }
-        CalendarProvider::ICal => {
-            Err("iCal subscriptions are read-only".to_string())
-        }
-    }
+    offline::delete_event(&calendar, event_id, etag)
+        .await
+        .map_err(LokusError::from)
 }
 
 // ============== Sync Commands ==============
 
 /// Get sync status
 #[tauri::command]
-pub fn get_sync_status() -> Result<SyncStatus, String> {
-    // For now, return a basic status
-    Ok(SyncStatus::default())
+pub fn get_sync_status() -> Result<SyncStatus, LokusError> {
+    let state = crate::calendar::sync::SyncStorage::get_sync_state()?;
+
+    Ok(SyncStatus {
+        is_syncing: crate::calendar::sync::incremental::is_syncing(),
+        last_sync: state.last_incremental_sync.max(state.last_full_sync),
+        pending_changes: state.pending_changes,
+        error: state.last_error,
+    })
 }
 
 /// Manually trigger a sync
 #[tauri::command]
-pub async fn sync_calendars(app_handle: AppHandle) -> Result<SyncResult, String> {
+pub async fn sync_calendars(app_handle: AppHandle) -> Result<SyncResult, LokusError> {
     let start = Utc::now();
 
-    // Refresh calendars list
-    let calendars = get_calendars().await?;
-
-    let result = SyncResult {
-        success: true,
-        events_added: 0,
-        events_updated: 0,
-        events_deleted: 0,
-        errors: Vec::new(),
-        synced_at: Utc::now(),
-    };
+    let result = crate::calendar::sync::incremental::run_sync(&app_handle).await?;
 
     // Emit sync complete event
     let _ = app_handle.emit("calendar-sync-complete", serde_json::json!({
-        "success": true,
-        "calendars_synced": calendars.len(),
+        "success": result.success,
+        "events_added": result.events_added,
+        "events_updated": result.events_updated,
+        "events_deleted": result.events_deleted,
         "duration_ms": (Utc::now() - start).num_milliseconds()
     }));
 
     Ok(result)
 }
 
+/// Get events from the local cache, for viewing calendars offline
+#[tauri::command]
+pub fn get_cached_events(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<CalendarEvent>, LokusError> {
+    let visible: Vec<String> = CalendarStorage::get_calendars()?
+        .into_iter()
+        .filter(|c| c.visible)
+        .map(|c| c.id)
+        .collect();
+
+    let mut events: Vec<CalendarEvent> = crate::calendar::sync::SyncStorage::get_all_cached_events()?
+        .into_iter()
+        .filter(|e| visible.contains(&e.calendar_id) && e.start <= end && e.end >= start)
+        .collect();
+    events.sort_by(|a, b| a.start.cmp(&b.start));
+
+    Ok(events)
+}
+
 /// Update calendar visibility
 #[tauri::command]
 pub fn update_calendar_visibility(
     calendar_id: String,
     visible: bool,
-) -> Result<(), String> {
-    let mut calendars = CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())?;
+) -> Result<(), LokusError> {
+    let mut calendars = CalendarStorage::get_calendars()?;
 
     if let Some(calendar) = calendars.iter_mut().find(|c| c.id == calendar_id) {
         calendar.visible = visible;
-        CalendarStorage::store_calendars(&calendars)
-            .map_err(|e| e.to_string())?;
+        CalendarStorage::store_calendars(&calendars)?;
     }
 
     Ok(())
@@ -685,11 +785,11 @@ pub async fn ical_add_subscription(
     url: String,
     name: Option<String>,
     color: Option<String>,
-) -> Result<ICalSubscription, String> {
+) -> Result<ICalSubscription, LokusError> {
     // Fetch and validate the ICS content
     let content = ical::fetch_ics_from_url(&url)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(LokusError::Network)?;
 
     // Try to extract calendar name from content if not provided
     let calendar_name = name.or_else(|| ical::extract_calendar_name(&content))
@@ -699,19 +799,15 @@ pub async fn ical_add_subscription(
     let subscription = ical::create_subscription(&url, Some(calendar_name), color);
 
     // Parse events
-    let events = ical::parse_ics_content(&content, &subscription.id)
-        .map_err(|e| e.to_string())?;
+    let events = ical::parse_ics_content(&content, &subscription.id).map_err(LokusError::Parse)?;
 
     // Store subscription
-    let mut subscriptions = CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())?;
+    let mut subscriptions = CalendarStorage::get_ical_subscriptions()?;
     subscriptions.push(subscription.clone());
-    CalendarStorage::store_ical_subscriptions(&subscriptions)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_subscriptions(&subscriptions)?;
 
     // Store events
-    CalendarStorage::store_ical_events(&subscription.id, &events)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_events(&subscription.id, &events)?;
 
     // Also add to calendars list for UI
     let calendar = Calendar {
@@ -727,11 +823,9 @@ pub async fn ical_add_subscription(
         visible: true,
     };
 
-    let mut calendars = CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())?;
+    let mut calendars = CalendarStorage::get_calendars()?;
     calendars.push(calendar);
-    CalendarStorage::store_calendars(&calendars)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_calendars(&calendars)?;
 
     Ok(subscription)
 }
@@ -742,12 +836,12 @@ pub fn ical_import_file(
     path: String,
     name: Option<String>,
     color: Option<String>,
-) -> Result<ICalSubscription, String> {
+) -> Result<ICalSubscription, LokusError> {
     let path = std::path::Path::new(&path);
 
     // Read file content
     let content = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+        .map_err(|e| LokusError::io("Failed to read", path, e))?;
 
     // Try to extract calendar name
     let calendar_name = name.or_else(|| ical::extract_calendar_name(&content))
@@ -759,19 +853,15 @@ pub fn ical_import_file(
     let subscription = ical::create_subscription(&file_url, Some(calendar_name), color);
 
     // Parse events
-    let events = ical::parse_ics_content(&content, &subscription.id)
-        .map_err(|e| e.to_string())?;
+    let events = ical::parse_ics_content(&content, &subscription.id).map_err(LokusError::Parse)?;
 
     // Store subscription
-    let mut subscriptions = CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())?;
+    let mut subscriptions = CalendarStorage::get_ical_subscriptions()?;
     subscriptions.push(subscription.clone());
-    CalendarStorage::store_ical_subscriptions(&subscriptions)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_subscriptions(&subscriptions)?;
 
     // Store events
-    CalendarStorage::store_ical_events(&subscription.id, &events)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_events(&subscription.id, &events)?;
 
     // Add to calendars list
     let calendar = Calendar {
@@ -787,54 +877,47 @@ pub fn ical_import_file(
         visible: true,
     };
 
-    let mut calendars = CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())?;
+    let mut calendars = CalendarStorage::get_calendars()?;
     calendars.push(calendar);
-    CalendarStorage::store_calendars(&calendars)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_calendars(&calendars)?;
 
     Ok(subscription)
 }
 
 /// Remove an iCal subscription
 #[tauri::command]
-pub fn ical_remove_subscription(subscription_id: String) -> Result<(), String> {
+pub fn ical_remove_subscription(subscription_id: String) -> Result<(), LokusError> {
     // Remove from subscriptions
-    let mut subscriptions = CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())?;
+    let mut subscriptions = CalendarStorage::get_ical_subscriptions()?;
     subscriptions.retain(|s| s.id != subscription_id);
-    CalendarStorage::store_ical_subscriptions(&subscriptions)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_subscriptions(&subscriptions)?;
 
     // Remove cached events
     let _ = CalendarStorage::delete_ical_events(&subscription_id);
 
     // Remove from calendars list
-    let mut calendars = CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())?;
+    let mut calendars = CalendarStorage::get_calendars()?;
     calendars.retain(|c| c.id != subscription_id);
-    CalendarStorage::store_calendars(&calendars)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_calendars(&calendars)?;
 
     Ok(())
 }
 
 /// Get all iCal subscriptions
 #[tauri::command]
-pub fn ical_get_subscriptions() -> Result<Vec<ICalSubscription>, String> {
+pub fn ical_get_subscriptions() -> Result<Vec<ICalSubscription>, LokusError> {
     CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Sync a single iCal subscription
 #[tauri::command]
-pub async fn ical_sync_subscription(subscription_id: String) -> Result<ICalSubscription, String> {
-    let mut subscriptions = CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())?;
+pub async fn ical_sync_subscription(subscription_id: String) -> Result<ICalSubscription, LokusError> {
+    let mut subscriptions = CalendarStorage::get_ical_subscriptions()?;
 
     let subscription = subscriptions.iter_mut()
         .find(|s| s.id == subscription_id)
-        .ok_or_else(|| "Subscription not found".to_string())?;
+        .ok_or_else(|| CalendarError::NotFound(format!("Subscription {}", subscription_id)))?;
 
     // Skip file:// URLs (can't sync local files)
     if subscription.url.starts_with("file://") {
@@ -844,21 +927,18 @@ pub async fn ical_sync_subscription(subscription_id: String) -> Result<ICalSubsc
     // Fetch fresh content
     let content = ical::fetch_ics_from_url(&subscription.url)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(LokusError::Network)?;
 
     // Parse events
-    let events = ical::parse_ics_content(&content, &subscription.id)
-        .map_err(|e| e.to_string())?;
+    let events = ical::parse_ics_content(&content, &subscription.id).map_err(LokusError::Parse)?;
 
     // Update stored events
-    CalendarStorage::store_ical_events(&subscription.id, &events)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_events(&subscription.id, &events)?;
 
     // Update last synced time
     subscription.last_synced = Some(Utc::now());
 
-    CalendarStorage::store_ical_subscriptions(&subscriptions)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_subscriptions(&subscriptions)?;
 
     Ok(subscriptions.into_iter()
         .find(|s| s.id == subscription_id)
@@ -867,9 +947,8 @@ pub async fn ical_sync_subscription(subscription_id: String) -> Result<ICalSubsc
 
 /// Sync all iCal subscriptions
 #[tauri::command]
-pub async fn ical_sync_all() -> Result<Vec<ICalSubscription>, String> {
-    let subscriptions = CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())?;
+pub async fn ical_sync_all() -> Result<Vec<ICalSubscription>, LokusError> {
+    let subscriptions = CalendarStorage::get_ical_subscriptions()?;
 
     let mut updated = Vec::new();
 
@@ -898,35 +977,30 @@ pub fn ical_update_subscription(
     color: Option<String>,
     enabled: Option<bool>,
     sync_interval_minutes: Option<u32>,
-) -> Result<ICalSubscription, String> {
-    let mut subscriptions = CalendarStorage::get_ical_subscriptions()
-        .map_err(|e| e.to_string())?;
+) -> Result<ICalSubscription, LokusError> {
+    let mut subscriptions = CalendarStorage::get_ical_subscriptions()?;
 
     let subscription = subscriptions.iter_mut()
         .find(|s| s.id == subscription_id)
-        .ok_or_else(|| "Subscription not found".to_string())?;
+        .ok_or_else(|| CalendarError::NotFound(format!("Subscription {}", subscription_id)))?;
 
     if let Some(n) = name {
         subscription.name = n.clone();
         // Also update in calendars list
-        let mut calendars = CalendarStorage::get_calendars()
-            .map_err(|e| e.to_string())?;
+        let mut calendars = CalendarStorage::get_calendars()?;
         if let Some(cal) = calendars.iter_mut().find(|c| c.id == subscription_id) {
             cal.name = n;
         }
-        CalendarStorage::store_calendars(&calendars)
-            .map_err(|e| e.to_string())?;
+        CalendarStorage::store_calendars(&calendars)?;
     }
     if let Some(c) = color {
         subscription.color = Some(c.clone());
         // Also update in calendars list
-        let mut calendars = CalendarStorage::get_calendars()
-            .map_err(|e| e.to_string())?;
+        let mut calendars = CalendarStorage::get_calendars()?;
         if let Some(cal) = calendars.iter_mut().find(|c| c.id == subscription_id) {
             cal.color = Some(c);
         }
-        CalendarStorage::store_calendars(&calendars)
-            .map_err(|e| e.to_string())?;
+        CalendarStorage::store_calendars(&calendars)?;
     }
     if let Some(e) = enabled {
         subscription.enabled = e;
@@ -935,8 +1009,7 @@ pub fn ical_update_subscription(
         subscription.sync_interval_minutes = i;
     }
 
-    CalendarStorage::store_ical_subscriptions(&subscriptions)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_ical_subscriptions(&subscriptions)?;
 
     Ok(subscriptions.into_iter()
         .find(|s| s.id == subscription_id)
@@ -945,9 +1018,9 @@ pub fn ical_update_subscription(
 
 /// Get iCal events for a subscription
 #[tauri::command]
-pub fn ical_get_events(subscription_id: String) -> Result<Vec<CalendarEvent>, String> {
+pub fn ical_get_events(subscription_id: String) -> Result<Vec<CalendarEvent>, LokusError> {
     CalendarStorage::get_ical_events(&subscription_id)
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 // ============== CalDAV Commands ==============
@@ -959,23 +1032,19 @@ pub async fn caldav_connect(
     username: String,
     password: String,
     app_handle: AppHandle,
-) -> Result<CalDAVAccount, String> {
+) -> Result<CalDAVAccount, LokusError> {
     // Test connection and discover endpoints
     let account = caldav::test_connection(&server_url, &username, &password)
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Store account credentials securely
-    CalendarStorage::store_caldav_account(&account)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_caldav_account(&account)?;
 
     // Fetch and store calendars
-    let client = caldav::CalDAVClient::new(account.clone())
-        .map_err(|e| e.to_string())?;
+    let client = caldav::CalDAVClient::new(account.clone())?;
 
     let calendars = client.list_calendars()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Add CalDAV calendars to the calendars list
     let mut all_calendars = CalendarStorage::get_calendars()
@@ -987,8 +1056,7 @@ pub async fn caldav_connect(
     // Add new CalDAV calendars
     all_calendars.extend(calendars);
 
-    CalendarStorage::store_calendars(&all_calendars)
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::store_calendars(&all_calendars)?;
 
     // Emit success event
     let _ = app_handle.emit("caldav-connected", serde_json::json!({
@@ -1000,7 +1068,7 @@ pub async fn caldav_connect(
 
 /// Check if CalDAV is connected
 #[tauri::command]
-pub fn caldav_is_connected() -> Result<bool, String> {
+pub fn caldav_is_connected() -> Result<bool, LokusError> {
     println!("[CalDAV] caldav_is_connected called");
     match CalendarStorage::get_caldav_account() {
         Ok(Some(account)) => {
@@ -1013,14 +1081,14 @@ pub fn caldav_is_connected() -> Result<bool, String> {
         },
         Err(e) => {
             println!("[CalDAV] Error getting account: {}", e);
-            Err(e.to_string())
+            Err(e.into())
         },
     }
 }
 
 /// Get CalDAV account info
 #[tauri::command]
-pub fn caldav_get_account() -> Result<Option<CalDAVAccount>, String> {
+pub fn caldav_get_account() -> Result<Option<CalDAVAccount>, LokusError> {
     // Return account without password
     match CalendarStorage::get_caldav_account() {
         Ok(Some(mut account)) => {
@@ -1028,13 +1096,13 @@ pub fn caldav_get_account() -> Result<Option<CalDAVAccount>, String> {
             Ok(Some(account))
         }
         Ok(None) => Ok(None),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(e.into()),
     }
 }
 
 /// Disconnect CalDAV
 #[tauri::command]
-pub async fn caldav_disconnect(app_handle: AppHandle) -> Result<(), String> {
+pub async fn caldav_disconnect(app_handle: AppHandle) -> Result<(), LokusError> {
     // Remove CalDAV calendars from storage
     let mut calendars = CalendarStorage::get_calendars()
         .unwrap_or_default();
@@ -1042,8 +1110,7 @@ pub async fn caldav_disconnect(app_handle: AppHandle) -> Result<(), String> {
     let _ = CalendarStorage::store_calendars(&calendars);
 
     // Delete account credentials
-    CalendarStorage::delete_caldav_account()
-        .map_err(|e| e.to_string())?;
+    CalendarStorage::delete_caldav_account()?;
 
     // Emit disconnect event
     let _ = app_handle.emit("caldav-disconnected", serde_json::json!({}));
@@ -1053,21 +1120,18 @@ pub async fn caldav_disconnect(app_handle: AppHandle) -> Result<(), String> {
 
 /// Refresh CalDAV calendars
 #[tauri::command]
-pub async fn caldav_refresh_calendars() -> Result<Vec<Calendar>, String> {
+pub async fn caldav_refresh_calendars() -> Result<Vec<Calendar>, LokusError> {
     println!("[Calendar] caldav_refresh_calendars called");
 
-    let account = CalendarStorage::get_caldav_account()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "CalDAV not connected".to_string())?;
+    let account = CalendarStorage::get_caldav_account()?
+        .ok_or(CalendarError::NotConnected)?;
 
     println!("[Calendar] Got CalDAV account: {}", account.username);
 
-    let client = caldav::CalDAVClient::new(account)
-        .map_err(|e| e.to_string())?;
+    let client = caldav::CalDAVClient::new(account)?;
 
     let caldav_calendars = client.list_calendars()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     println!("[Calendar] Listed {} CalDAV calendars", caldav_calendars.len());
 
@@ -1091,7 +1155,7 @@ pub async fn caldav_refresh_calendars() -> Result<Vec<Calendar>, String> {
         Ok(_) => println!("[Calendar] Successfully stored {} calendars", all_calendars.len()),
         Err(e) => {
             println!("[Calendar] ERROR storing calendars: {}", e);
-            return Err(e.to_string());
+            return Err(e.into());
         }
     }
 
@@ -1104,22 +1168,20 @@ pub async fn caldav_get_events(
     calendar_url: String,
     start: String,
     end: String,
-) -> Result<Vec<CalendarEvent>, String> {
+) -> Result<Vec<CalendarEvent>, LokusError> {
     let start_time: DateTime<Utc> = start.parse()
-        .map_err(|e| format!("Invalid start time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid start time: {}", e)))?;
     let end_time: DateTime<Utc> = end.parse()
-        .map_err(|e| format!("Invalid end time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid end time: {}", e)))?;
 
-    let account = CalendarStorage::get_caldav_account()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "CalDAV not connected".to_string())?;
+    let account = CalendarStorage::get_caldav_account()?
+        .ok_or(CalendarError::NotConnected)?;
 
-    let client = caldav::CalDAVClient::new(account)
-        .map_err(|e| e.to_string())?;
+    let client = caldav::CalDAVClient::new(account)?;
 
     client.get_events(&calendar_url, start_time, end_time)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Create CalDAV event
@@ -1127,17 +1189,15 @@ pub async fn caldav_get_events(
 pub async fn caldav_create_event(
     calendar_url: String,
     event: CreateEventRequest,
-) -> Result<CalendarEvent, String> {
-    let account = CalendarStorage::get_caldav_account()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "CalDAV not connected".to_string())?;
+) -> Result<CalendarEvent, LokusError> {
+    let account = CalendarStorage::get_caldav_account()?
+        .ok_or(CalendarError::NotConnected)?;
 
-    let client = caldav::CalDAVClient::new(account)
-        .map_err(|e| e.to_string())?;
+    let client = caldav::CalDAVClient::new(account)?;
 
     client.create_event(&calendar_url, &event)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Update CalDAV event
@@ -1147,17 +1207,15 @@ pub async fn caldav_update_event(
     event_id: String,
     updates: UpdateEventRequest,
     etag: Option<String>,
-) -> Result<CalendarEvent, String> {
-    let account = CalendarStorage::get_caldav_account()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "CalDAV not connected".to_string())?;
+) -> Result<CalendarEvent, LokusError> {
+    let account = CalendarStorage::get_caldav_account()?
+        .ok_or(CalendarError::NotConnected)?;
 
-    let client = caldav::CalDAVClient::new(account)
-        .map_err(|e| e.to_string())?;
+    let client = caldav::CalDAVClient::new(account)?;
 
     client.update_event(&calendar_url, &event_id, &updates, etag.as_deref())
         .await
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Delete CalDAV event
@@ -1166,17 +1224,15 @@ pub async fn caldav_delete_event(
     calendar_url: String,
     event_id: String,
     etag: Option<String>,
-) -> Result<(), String> {
-    let account = CalendarStorage::get_caldav_account()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "CalDAV not connected".to_string())?;
+) -> Result<(), LokusError> {
+    let account = CalendarStorage::get_caldav_account()?
+        .ok_or(CalendarError::NotConnected)?;
 
-    let client = caldav::CalDAVClient::new(account)
-        .map_err(|e| e.to_string())?;
+    let client = caldav::CalDAVClient::new(account)?;
 
     client.delete_event(&calendar_url, &event_id, etag.as_deref())
         .await
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 // ============== Sync Commands ==============
@@ -1191,20 +1247,20 @@ use crate::calendar::sync::{SyncStorage, SyncEngine, deduplicate_events};
 pub async fn get_all_events_deduplicated(
     start: String,
     end: String,
-) -> Result<Vec<DeduplicatedEvent>, String> {
+) -> Result<Vec<DeduplicatedEvent>, LokusError> {
     println!("[Calendar] get_all_events_deduplicated called: {} to {}", start, end);
 
     let start_time: DateTime<Utc> = start.parse()
-        .map_err(|e| format!("Invalid start time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid start time: {}", e)))?;
     let end_time: DateTime<Utc> = end.parse()
-        .map_err(|e| format!("Invalid end time: {}", e))?;
+        .map_err(|e| LokusError::invalid(format!("Invalid end time: {}", e)))?;
 
     // Check if deduplication is enabled
     let config = SyncStorage::get_sync_config().unwrap_or_default();
     if !config.deduplication_enabled {
         // Return regular events wrapped as DeduplicatedEvent
         let events = get_all_events(start, end).await?;
-        let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
+        let calendars = CalendarStorage::get_calendars()?;
         let calendar_map: std::collections::HashMap<String, &Calendar> = calendars.iter()
             .map(|c| (c.id.clone(), c))
             .collect();
@@ -1227,8 +1283,7 @@ pub async fn get_all_events_deduplicated(
         return Ok(deduplicated);
     }
 
-    let calendars = CalendarStorage::get_calendars()
-        .map_err(|e| e.to_string())?;
+    let calendars = CalendarStorage::get_calendars()?;
 
     println!("[Calendar] Found {} calendars, {} visible",
         calendars.len(),
@@ -1258,6 +1313,28 @@ pub async fn get_all_events_deduplicated(
         }
     }
 
+    // Fetch Outlook events
+    let outlook_calendars: Vec<_> = calendars.iter()
+        .filter(|c| c.visible && c.provider == CalendarProvider::Outlook)
+        .collect();
+
+    if !outlook_calendars.is_empty() {
+        if let Ok(api) = OutlookCalendarApi::new() {
+            for calendar in outlook_calendars {
+                println!("[Calendar] Fetching events from Outlook calendar: {} ({})", calendar.name, calendar.id);
+                match api.get_events(&calendar.id, start_time, end_time).await {
+                    Ok(events) => {
+                        println!("[Calendar] Got {} events from {}", events.len(), calendar.name);
+                        all_events.extend(events);
+                    },
+                    Err(e) => {
+                        println!("[Calendar] ERROR fetching from {}: {}", calendar.name, e);
+                    }
+                }
+            }
+        }
+    }
+
     // Fetch iCal events from visible iCal calendars
     let ical_calendars: Vec<_> = calendars.iter()
         .filter(|c| c.visible && c.provider == CalendarProvider::ICal)
@@ -1313,15 +1390,13 @@ pub async fn get_all_events_deduplicated(
 
 /// Perform a full sync across all configured calendar pairs
 #[tauri::command]
-pub async fn sync_calendars_full(app_handle: AppHandle) -> Result<FullSyncResult, String> {
+pub async fn sync_calendars_full(app_handle: AppHandle) -> Result<FullSyncResult, LokusError> {
     println!("[Calendar] sync_calendars_full called");
 
-    let engine = SyncEngine::new()
-        .map_err(|e| e.to_string())?;
+    let engine = SyncEngine::new()?;
 
     let result = engine.full_sync()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     // Emit sync complete event
     let _ = app_handle.emit("calendar-sync-complete", serde_json::json!({
@@ -1339,21 +1414,21 @@ pub async fn sync_calendars_full(app_handle: AppHandle) -> Result<FullSyncResult
 
 /// Get sync configuration
 #[tauri::command]
-pub fn get_sync_config() -> Result<SyncConfig, String> {
+pub fn get_sync_config() -> Result<SyncConfig, LokusError> {
     SyncStorage::get_sync_config()
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Set sync configuration
 #[tauri::command]
-pub fn set_sync_config(config: SyncConfig) -> Result<(), String> {
+pub fn set_sync_config(config: SyncConfig) -> Result<(), LokusError> {
     SyncStorage::store_sync_config(&config)
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
 
 /// Get sync state
 #[tauri::command]
-pub fn get_sync_state() -> Result<crate::calendar::models::SyncState, String> {
+pub fn get_sync_state() -> Result<crate::calendar::models::SyncState, LokusError> {
     SyncStorage::get_sync_state()
-        .map_err(|e| e.to_string())
+        .map_err(LokusError::from)
 }
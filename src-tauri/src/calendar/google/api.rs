@@ -1,14 +1,17 @@
 use crate::calendar::models::{
     Calendar, CalendarEvent, CalendarProvider, CalendarError,
     EventAttendee, AttendeeResponseStatus, EventStatus,
-    CreateEventRequest, UpdateEventRequest,
+    CreateEventRequest, UpdateEventRequest, EventChanges,
 };
 use crate::calendar::google::auth::GoogleCalendarAuth;
+use crate::connections::request_queue;
 use reqwest::Client;
 use serde_json;
 use chrono::{DateTime, Utc, TimeZone};
 
 const CALENDAR_API_BASE: &str = "https://www.googleapis.com/calendar/v3";
+/// Request queue shared by every Google Calendar call
+const QUEUE: &str = "google-calendar";
 
 pub struct GoogleCalendarApi {
     auth: GoogleCalendarAuth,
@@ -26,11 +29,13 @@ impl GoogleCalendarApi {
     pub async fn list_calendars(&self) -> Result<Vec<Calendar>, CalendarError> {
         let token = self.auth.get_valid_token().await?;
 
-        let response = self.client
-            .get(&format!("{}/users/me/calendarList", CALENDAR_API_BASE))
-            .bearer_auth(&token.access_token)
-            .send()
-            .await?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .get(&format!("{}/users/me/calendarList", CALENDAR_API_BASE))
+                .bearer_auth(&token.access_token)
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -73,11 +78,13 @@ impl GoogleCalendarApi {
 
         println!("[Calendar API] GET {}", url);
 
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&token.access_token)
-            .send()
-            .await?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .get(&url)
+                .bearer_auth(&token.access_token)
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         println!("[Calendar API] Response status: {}", response.status());
 
@@ -100,6 +107,73 @@ impl GoogleCalendarApi {
         Ok(events)
     }
 
+    /// Events changed since `sync_token`, or every event from `time_min` on
+    /// when there is no token. Cancelled events are reported as deleted.
+    /// Returns `SyncTokenExpired` when Google asks for a full sync.
+    pub async fn sync_events(
+        &self,
+        calendar_id: &str,
+        sync_token: Option<&str>,
+        time_min: DateTime<Utc>,
+    ) -> Result<EventChanges, CalendarError> {
+        let token = self.auth.get_valid_token().await?;
+        let mut changes = EventChanges { full: sync_token.is_none(), ..Default::default() };
+        let mut page_token: Option<String> = None;
+
+        loop {
+            // Sync tokens can't be combined with time bounds; recurring events are
+            // expanded so instances match what `get_events` returns
+            let mut url = format!(
+                "{}/calendars/{}/events?singleEvents=true&maxResults=250",
+                CALENDAR_API_BASE,
+                urlencoding::encode(calendar_id)
+            );
+            match sync_token {
+                Some(sync_token) => url.push_str(&format!("&syncToken={}", urlencoding::encode(sync_token))),
+                None => url.push_str(&format!("&timeMin={}", urlencoding::encode(&time_min.to_rfc3339()))),
+            }
+            if let Some(page) = &page_token {
+                url.push_str(&format!("&pageToken={}", urlencoding::encode(page)));
+            }
+
+            let response = request_queue::send(QUEUE, || {
+                self.client
+                    .get(&url)
+                    .bearer_auth(&token.access_token)
+            })
+            .await
+            .map_err(CalendarError::Network)?;
+
+            if response.status() == reqwest::StatusCode::GONE {
+                return Err(CalendarError::SyncTokenExpired);
+            }
+
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(CalendarError::Api(format!("Failed to sync events: {}", error_text)));
+            }
+
+            let data: serde_json::Value = response.json().await?;
+            for item in data["items"].as_array().map(|a| a.as_slice()).unwrap_or_default() {
+                if item["status"].as_str() == Some("cancelled") {
+                    if let Some(id) = item["id"].as_str() {
+                        changes.deleted.push(id.to_string());
+                    }
+                } else if let Ok(event) = self.parse_event(item, calendar_id) {
+                    changes.changed.push(event);
+                }
+            }
+
+            match data["nextPageToken"].as_str() {
+                Some(next) => page_token = Some(next.to_string()),
+                None => {
+                    changes.sync_token = data["nextSyncToken"].as_str().map(String::from);
+                    return Ok(changes);
+                }
+            }
+        }
+    }
+
     /// Get a single event by ID
     pub async fn get_event(
         &self,
@@ -115,11 +189,13 @@ impl GoogleCalendarApi {
             urlencoding::encode(event_id)
         );
 
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&token.access_token)
-            .send()
-            .await?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .get(&url)
+                .bearer_auth(&token.access_token)
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Err(CalendarError::NotFound(format!("Event {} not found", event_id)));
@@ -150,12 +226,14 @@ impl GoogleCalendarApi {
 
         let event_body = self.build_event_body(request);
 
-        let response = self.client
-            .post(&url)
-            .bearer_auth(&token.access_token)
-            .json(&event_body)
-            .send()
-            .await?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .post(&url)
+                .bearer_auth(&token.access_token)
+                .json(&event_body)
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -187,12 +265,14 @@ impl GoogleCalendarApi {
 
         let event_body = self.build_update_body(&existing, request);
 
-        let response = self.client
-            .put(&url)
-            .bearer_auth(&token.access_token)
-            .json(&event_body)
-            .send()
-            .await?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .put(&url)
+                .bearer_auth(&token.access_token)
+                .json(&event_body)
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -218,11 +298,13 @@ impl GoogleCalendarApi {
             urlencoding::encode(event_id)
         );
 
-        let response = self.client
-            .delete(&url)
-            .bearer_auth(&token.access_token)
-            .send()
-            .await?;
+        let response = request_queue::send(QUEUE, || {
+            self.client
+                .delete(&url)
+                .bearer_auth(&token.access_token)
+        })
+        .await
+        .map_err(CalendarError::Network)?;
 
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Err(CalendarError::NotFound(format!("Event {} not found", event_id)));
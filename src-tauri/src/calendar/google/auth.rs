@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use crate::calendar::models::{CalendarToken, CalendarAccount, CalendarProvider, CalendarError};
 use crate::calendar::storage::CalendarStorage;
+use crate::connections::oauth_manager::{self, Provider};
 use reqwest::Client;
 use serde_json;
 use uuid::Uuid;
@@ -159,93 +160,9 @@ impl GoogleCalendarAuth {
         Ok(token)
     }
 
-    pub async fn refresh_token(&self, refresh_token: &str) -> Result<CalendarToken, CalendarError> {
-        let client = Client::new();
-        let mut params = HashMap::new();
-        params.insert("client_id", self.client_id.as_str());
-        params.insert("client_secret", self.client_secret.as_str());
-        params.insert("refresh_token", refresh_token);
-        params.insert("grant_type", "refresh_token");
-
-        let response = client
-            .post("https://oauth2.googleapis.com/token")
-            .form(&params)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            // If refresh fails, delete stored token
-            let _ = CalendarStorage::delete_google_token();
-            let _ = CalendarStorage::delete_google_account();
-
-            return Err(CalendarError::TokenExpired);
-        }
-
-        let token_data: serde_json::Value = response.json().await?;
-
-        let access_token = token_data["access_token"]
-            .as_str()
-            .ok_or_else(|| CalendarError::Auth("No access token in refresh response".to_string()))?;
-
-        // Use the new refresh token if provided, otherwise keep the existing one
-        let new_refresh_token = token_data["refresh_token"]
-            .as_str()
-            .map(|s| s.to_string())
-            .or_else(|| Some(refresh_token.to_string()));
-
-        let expires_in = token_data["expires_in"].as_u64().unwrap_or(3600);
-        let expires_at = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() + expires_in;
-
-        let scope = token_data["scope"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
-
-        let token = CalendarToken {
-            access_token: access_token.to_string(),
-            refresh_token: new_refresh_token,
-            expires_at: Some(expires_at),
-            scope,
-            token_type: token_data["token_type"]
-                .as_str()
-                .unwrap_or("Bearer")
-                .to_string(),
-        };
-
-        // Store the refreshed token
-        CalendarStorage::store_google_token(&token)?;
-
-        Ok(token)
-    }
-
+    /// A token that is good for at least a few more minutes, refreshed if need be
     pub async fn get_valid_token(&self) -> Result<CalendarToken, CalendarError> {
-        let token = match CalendarStorage::get_google_token()? {
-            Some(token) => token,
-            None => {
-                return Err(CalendarError::NotConnected);
-            }
-        };
-
-        // Check if token is expired
-        if CalendarStorage::is_token_expired(&token) {
-            if let Some(refresh_token) = &token.refresh_token {
-                match self.refresh_token(refresh_token).await {
-                    Ok(new_token) => {
-                        return Ok(new_token);
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
-                }
-            } else {
-                return Err(CalendarError::TokenExpired);
-            }
-        }
-
-        Ok(token)
+        Ok(oauth_manager::access_token(Provider::GoogleCalendar).await?.into())
     }
 
     pub async fn fetch_and_store_account(&self, token: &CalendarToken) -> Result<CalendarAccount, CalendarError> {
@@ -305,22 +222,6 @@ impl GoogleCalendarAuth {
 
     /// Revoke token with Google without touching local storage
     /// Used by disconnect flow which handles storage deletion separately
-    pub async fn revoke_token_only(&self, token: &str) -> Result<(), CalendarError> {
-        let client = Client::new();
-        let response = client
-            .post("https://oauth2.googleapis.com/revoke")
-            .form(&[("token", token)])
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            // Log but don't fail - local cleanup is what matters
-            println!("[Calendar] Token revocation returned status: {}", response.status());
-        }
-
-        Ok(())
-    }
-
     pub fn is_authenticated(&self) -> Result<bool, CalendarError> {
         match CalendarStorage::get_google_token()? {
             Some(token) => Ok(!CalendarStorage::is_token_expired(&token)),
@@ -3,7 +3,12 @@ pub mod storage;
 pub mod google;
 pub mod ical;
 pub mod caldav;
+pub mod outlook;
 pub mod sync;
 pub mod commands;
+pub mod note_links;
+pub mod offline;
+pub mod timeblocking;
 
 pub use commands::*;
+pub use note_links::*;
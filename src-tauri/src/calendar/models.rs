@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crate::errors::LokusError;
 
 /// Represents a calendar event from any provider (Google, CalDAV, etc.)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +57,7 @@ pub enum CalendarProvider {
     CalDAV,
     ICloud,
     ICal,
+    Outlook,
 }
 
 impl std::fmt::Display for CalendarProvider {
@@ -65,6 +67,7 @@ impl std::fmt::Display for CalendarProvider {
             CalendarProvider::CalDAV => write!(f, "caldav"),
             CalendarProvider::ICloud => write!(f, "icloud"),
             CalendarProvider::ICal => write!(f, "ical"),
+            CalendarProvider::Outlook => write!(f, "outlook"),
         }
     }
 }
@@ -257,6 +260,10 @@ pub enum CalendarError {
 
     #[error("Calendar not connected")]
     NotConnected,
+
+    /// The provider no longer accepts the stored sync token; a full sync is needed
+    #[error("Sync token expired")]
+    SyncTokenExpired,
 }
 
 impl From<reqwest::Error> for CalendarError {
@@ -283,6 +290,51 @@ impl From<CalendarError> for String {
     }
 }
 
+impl From<CalendarError> for LokusError {
+    fn from(err: CalendarError) -> Self {
+        let message = err.to_string();
+        match err {
+            CalendarError::Auth(_) | CalendarError::TokenExpired | CalendarError::NotConnected => {
+                LokusError::AuthRequired(message)
+            }
+            CalendarError::Network(_) => LokusError::Network(message),
+            CalendarError::RateLimit => LokusError::RateLimited(message),
+            CalendarError::InvalidRequest(_) => LokusError::InvalidInput(message),
+            CalendarError::Storage(_) => LokusError::Io(message),
+            CalendarError::Parse(_) => LokusError::Parse(message),
+            CalendarError::NotFound(_) => LokusError::NotFound { path: String::new(), message },
+            CalendarError::Api(_) | CalendarError::SyncTokenExpired => LokusError::Other(message),
+        }
+    }
+}
+
+/// A note linked to a calendar event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventNoteLink {
+    pub event_id: String,
+    /// Known when the link was made from a writable calendar; needed to
+    /// update the event's description
+    pub calendar_id: Option<String>,
+    /// Absolute path of the note
+    pub note_path: String,
+    pub event_title: Option<String>,
+    pub event_start: Option<DateTime<Utc>>,
+    pub linked_at: DateTime<Utc>,
+}
+
+/// A task time-blocked onto a calendar event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEventLink {
+    pub task_id: String,
+    pub event_id: String,
+    pub calendar_id: String,
+    /// Schedule block mirroring the event's time on the task
+    pub block_id: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub linked_at: DateTime<Utc>,
+}
+
 // ============== Sync-related Types ==============
 
 /// Represents a mapping between events across different calendar providers
@@ -400,6 +452,73 @@ pub struct CalendarProviderInfo {
     pub event_id: String,
 }
 
+/// Locally persisted events of one calendar, kept current by incremental sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventCache {
+    pub calendar_id: String,
+    /// Google `nextSyncToken` or CalDAV `sync-token`
+    pub sync_token: Option<String>,
+    /// CalDAV collection tag, for servers without sync-collection support
+    pub ctag: Option<String>,
+    pub events: Vec<CalendarEvent>,
+    pub last_synced: Option<DateTime<Utc>>,
+}
+
+/// Changes reported by a provider since a sync token
+#[derive(Debug, Clone, Default)]
+pub struct EventChanges {
+    pub changed: Vec<CalendarEvent>,
+    pub deleted: Vec<String>,
+    pub sync_token: Option<String>,
+    /// The changes are a complete listing that replaces the cache
+    pub full: bool,
+}
+
+/// A change to an event waiting to be pushed to its provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PendingChange {
+    Create { event: CreateEventRequest },
+    Update { updates: UpdateEventRequest },
+    Delete,
+}
+
+/// Where a pending operation stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingOperationStatus {
+    Pending,
+    /// The event changed on the server since it was edited offline
+    Conflict,
+    /// The provider kept rejecting the change
+    Failed,
+}
+
+impl Default for PendingOperationStatus {
+    fn default() -> Self {
+        Self::Pending
+    }
+}
+
+/// An event change made while offline, applied to the local cache right
+/// away and replayed against the provider once it is reachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOperation {
+    pub id: String,
+    pub calendar_id: String,
+    /// A `local-` id for events created offline
+    pub event_id: String,
+    pub change: PendingChange,
+    /// Etag of the event the change was made against; `None` overwrites
+    pub base_etag: Option<String>,
+    #[serde(default)]
+    pub status: PendingOperationStatus,
+    #[serde(default)]
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// State of the sync engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncState {
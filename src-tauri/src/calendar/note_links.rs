@@ -0,0 +1,226 @@
+//! Two-way links between calendar events and notes.
+//!
+//! Links are kept in calendar storage. When the event's calendar is known
+//! and writable, its description gets a trailing block listing the linked
+//! notes as `lokus://` deep links, rewritten whenever the links change, so
+//! the note can be opened straight from the calendar app.
+
+use chrono::Utc;
+use std::path::Path;
+use crate::calendar::caldav;
+use crate::calendar::google::GoogleCalendarApi;
+use crate::calendar::outlook::OutlookCalendarApi;
+use crate::calendar::models::{CalendarEvent, CalendarProvider, EventNoteLink, UpdateEventRequest};
+use crate::calendar::storage::CalendarStorage;
+use crate::errors::LokusError;
+
+/// First line of the block appended to event descriptions
+const NOTES_MARKER: &str = "[Lokus notes]";
+
+/// `lokus://` URI that opens the note at `path`
+pub fn note_uri(path: &str) -> String {
+    crate::deep_link::note_link(path)
+}
+
+fn note_title(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// `description` with its notes block replaced by one listing `note_paths`,
+/// or removed when there are none
+pub fn embed_note_links(description: Option<&str>, note_paths: &[String]) -> Option<String> {
+    let existing = description.unwrap_or("");
+    let base = match existing.find(NOTES_MARKER) {
+        Some(index) => &existing[..index],
+        None => existing,
+    }
+    .trim_end();
+
+    let mut updated = base.to_string();
+    if !note_paths.is_empty() {
+        if !updated.is_empty() {
+            updated.push_str("\n\n");
+        }
+        updated.push_str(NOTES_MARKER);
+        for path in note_paths {
+            updated.push_str(&format!("\n{}: {}", note_title(path), note_uri(path)));
+        }
+    }
+    Some(updated).filter(|d| !d.is_empty())
+}
+
+async fn fetch_event(calendar_id: &str, event_id: &str) -> Result<CalendarEvent, String> {
+    let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
+    let calendar = calendars.iter()
+        .find(|c| c.id == calendar_id)
+        .ok_or_else(|| "Calendar not found".to_string())?;
+
+    match calendar.provider {
+        CalendarProvider::Google => {
+            let api = GoogleCalendarApi::new()
+                .map_err(|e| e.to_string())?;
+            api.get_event(calendar_id, event_id)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        CalendarProvider::Outlook => {
+            let api = OutlookCalendarApi::new()
+                .map_err(|e| e.to_string())?;
+            api.get_event(calendar_id, event_id)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
+            let account = CalendarStorage::get_caldav_account()
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| "CalDAV not connected".to_string())?;
+            let client = caldav::CalDAVClient::new(account)
+                .map_err(|e| e.to_string())?;
+            client.get_event(calendar_id, event_id)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        CalendarProvider::ICal => {
+            Err("iCal subscriptions are read-only".to_string())
+        }
+    }
+}
+
+/// Rewrite the notes block of the event's description to match `links`
+async fn sync_description(calendar_id: &str, event: &CalendarEvent, links: &[EventNoteLink]) -> Result<(), String> {
+    let writable = CalendarStorage::get_calendars()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .any(|c| c.id == calendar_id && c.is_writable);
+    if !writable {
+        return Ok(());
+    }
+
+    let paths: Vec<String> = links.iter()
+        .filter(|l| l.event_id == event.id)
+        .map(|l| l.note_path.clone())
+        .collect();
+    let description = embed_note_links(event.description.as_deref(), &paths);
+    if description == event.description {
+        return Ok(());
+    }
+
+    let updates = UpdateEventRequest {
+        title: None,
+        // An empty string clears the description; `None` would leave it as is
+        description: Some(description.unwrap_or_default()),
+        start: None,
+        end: None,
+        all_day: None,
+        location: None,
+        attendees: None,
+        recurrence_rule: None,
+        status: None,
+    };
+    crate::calendar::commands::update_event(calendar_id.to_string(), event.id.clone(), updates, event.etag.clone())
+        .await
+        .map(|_| ())
+        .map_err(String::from)
+}
+
+/// Link a note to an event; with the event's calendar the event's
+/// description is updated to link back to the note
+#[tauri::command]
+pub async fn calendar_link_event_to_note(
+    event_id: String,
+    note_path: String,
+    calendar_id: Option<String>,
+) -> Result<EventNoteLink, LokusError> {
+    let event = match &calendar_id {
+        Some(calendar_id) => Some(fetch_event(calendar_id, &event_id).await?),
+        None => None,
+    };
+
+    let mut links = CalendarStorage::get_note_links()?;
+    links.retain(|l| !(l.event_id == event_id && l.note_path == note_path));
+    let link = EventNoteLink {
+        event_id,
+        calendar_id: calendar_id.clone(),
+        note_path,
+        event_title: event.as_ref().map(|e| e.title.clone()),
+        event_start: event.as_ref().map(|e| e.start),
+        linked_at: Utc::now(),
+    };
+    links.push(link.clone());
+    CalendarStorage::store_note_links(&links)?;
+
+    if let (Some(calendar_id), Some(event)) = (&calendar_id, &event) {
+        // The link itself is saved; a calendar that refuses the edit shouldn't undo it
+        if let Err(e) = sync_description(calendar_id, event, &links).await {
+            tracing::warn!("Failed to add note link to event {}: {}", event.id, e);
+        }
+    }
+    Ok(link)
+}
+
+#[tauri::command]
+pub async fn calendar_unlink_event_from_note(event_id: String, note_path: String) -> Result<bool, LokusError> {
+    let mut links = CalendarStorage::get_note_links()?;
+    let Some(index) = links.iter().position(|l| l.event_id == event_id && l.note_path == note_path) else {
+        return Ok(false);
+    };
+    let removed = links.remove(index);
+    CalendarStorage::store_note_links(&links)?;
+
+    if let Some(calendar_id) = &removed.calendar_id {
+        let updated = match fetch_event(calendar_id, &event_id).await {
+            Ok(event) => sync_description(calendar_id, &event, &links).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = updated {
+            tracing::warn!("Failed to remove note link from event {}: {}", event_id, e);
+        }
+    }
+    Ok(true)
+}
+
+/// Events linked to the note at `path`, soonest first
+#[tauri::command]
+pub fn calendar_get_events_for_note(path: String) -> Result<Vec<EventNoteLink>, LokusError> {
+    let mut links: Vec<EventNoteLink> = CalendarStorage::get_note_links()?
+        .into_iter()
+        .filter(|l| l.note_path == path)
+        .collect();
+    links.sort_by(|a, b| a.event_start.cmp(&b.event_start));
+    Ok(links)
+}
+
+#[tauri::command]
+pub fn calendar_get_notes_for_event(event_id: String) -> Result<Vec<EventNoteLink>, LokusError> {
+    Ok(CalendarStorage::get_note_links()?
+        .into_iter()
+        .filter(|l| l.event_id == event_id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_block_is_rewritten_in_place() {
+        let paths = vec!["/vault/Meetings/Standup 2024-03-01.md".to_string()];
+        let described = embed_note_links(Some("Agenda: roadmap\n"), &paths).unwrap();
+        assert_eq!(
+            described,
+            "Agenda: roadmap\n\n[Lokus notes]\nStandup 2024-03-01: lokus://open?path=%2Fvault%2FMeetings%2FStandup%202024-03-01.md"
+        );
+
+        let two = vec![paths[0].clone(), "/vault/Follow-ups.md".to_string()];
+        let relinked = embed_note_links(Some(&described), &two).unwrap();
+        assert_eq!(relinked.matches(NOTES_MARKER).count(), 1);
+        assert!(relinked.starts_with("Agenda: roadmap\n\n[Lokus notes]\n"));
+        assert!(relinked.ends_with("Follow-ups: lokus://open?path=%2Fvault%2FFollow-ups.md"));
+
+        assert_eq!(embed_note_links(Some(&relinked), &[]).as_deref(), Some("Agenda: roadmap"));
+        assert_eq!(embed_note_links(None, &[]), None);
+    }
+}
@@ -0,0 +1,596 @@
+//! Offline-first event changes.
+//!
+//! Creating, updating and deleting events goes to the provider when it is
+//! reachable. When it isn't — or earlier changes to the calendar are still
+//! waiting — the change is applied to the local event cache immediately and
+//! queued in calendar storage. The queue is replayed in order once the
+//! provider answers again; updates and deletes are checked against the etag
+//! they were made on, and left as conflicts when the event changed remotely.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, Emitter};
+use crate::calendar::caldav::CalDAVClient;
+use crate::calendar::google::GoogleCalendarApi;
+use crate::calendar::outlook::OutlookCalendarApi;
+use crate::calendar::models::{
+    AttendeeResponseStatus, Calendar, CalendarError, CalendarEvent, CalendarProvider, CreateEventRequest,
+    EventAttendee, EventStatus, PendingChange, PendingOperation, PendingOperationStatus, UpdateEventRequest,
+};
+use crate::calendar::storage::CalendarStorage;
+use crate::calendar::sync::SyncStorage;
+use crate::errors::LokusError;
+
+/// Prefix of ids given to events created offline
+const LOCAL_ID_PREFIX: &str = "local-";
+
+/// Rejections after which an operation is marked failed
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Serializes read-modify-write cycles of the queue and the event cache
+static QUEUE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static REPLAYING: AtomicBool = AtomicBool::new(false);
+
+fn is_local(event_id: &str) -> bool {
+    event_id.starts_with(LOCAL_ID_PREFIX)
+}
+
+/// Whether the error means the provider couldn't be reached
+fn is_offline(error: &CalendarError) -> bool {
+    matches!(error, CalendarError::Network(_))
+}
+
+// ============== Provider Calls ==============
+
+async fn push_create(calendar: &Calendar, event: &CreateEventRequest) -> Result<CalendarEvent, CalendarError> {
+    match calendar.provider {
+        CalendarProvider::Google => GoogleCalendarApi::new()?.create_event(&calendar.id, event).await,
+        CalendarProvider::Outlook => OutlookCalendarApi::new()?.create_event(&calendar.id, event).await,
+        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
+            let account = CalendarStorage::get_caldav_account()?.ok_or(CalendarError::NotConnected)?;
+            CalDAVClient::new(account)?.create_event(&calendar.id, event).await
+        }
+        CalendarProvider::ICal => Err(CalendarError::InvalidRequest("iCal subscriptions are read-only".to_string())),
+    }
+}
+
+async fn push_update(
+    calendar: &Calendar,
+    event_id: &str,
+    updates: &UpdateEventRequest,
+    etag: Option<&str>,
+) -> Result<CalendarEvent, CalendarError> {
+    match calendar.provider {
+        CalendarProvider::Google => GoogleCalendarApi::new()?.update_event(&calendar.id, event_id, updates).await,
+        CalendarProvider::Outlook => OutlookCalendarApi::new()?.update_event(&calendar.id, event_id, updates, etag).await,
+        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
+            let account = CalendarStorage::get_caldav_account()?.ok_or(CalendarError::NotConnected)?;
+            CalDAVClient::new(account)?.update_event(&calendar.id, event_id, updates, etag).await
+        }
+        CalendarProvider::ICal => Err(CalendarError::InvalidRequest("iCal subscriptions are read-only".to_string())),
+    }
+}
+
+async fn push_delete(calendar: &Calendar, event_id: &str, etag: Option<&str>) -> Result<(), CalendarError> {
+    match calendar.provider {
+        CalendarProvider::Google => GoogleCalendarApi::new()?.delete_event(&calendar.id, event_id).await,
+        CalendarProvider::Outlook => OutlookCalendarApi::new()?.delete_event(event_id).await,
+        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
+            let account = CalendarStorage::get_caldav_account()?.ok_or(CalendarError::NotConnected)?;
+            CalDAVClient::new(account)?.delete_event(&calendar.id, event_id, etag).await
+        }
+        CalendarProvider::ICal => Err(CalendarError::InvalidRequest("iCal subscriptions are read-only".to_string())),
+    }
+}
+
+async fn fetch_remote(calendar: &Calendar, event_id: &str) -> Result<CalendarEvent, CalendarError> {
+    match calendar.provider {
+        CalendarProvider::Google => GoogleCalendarApi::new()?.get_event(&calendar.id, event_id).await,
+        CalendarProvider::Outlook => OutlookCalendarApi::new()?.get_event(&calendar.id, event_id).await,
+        CalendarProvider::CalDAV | CalendarProvider::ICloud => {
+            let account = CalendarStorage::get_caldav_account()?.ok_or(CalendarError::NotConnected)?;
+            CalDAVClient::new(account)?.get_event(&calendar.id, event_id).await
+        }
+        CalendarProvider::ICal => Err(CalendarError::InvalidRequest("iCal subscriptions are read-only".to_string())),
+    }
+}
+
+// ============== Local Application ==============
+
+fn local_event(calendar_id: &str, provider: CalendarProvider, id: &str, request: &CreateEventRequest) -> CalendarEvent {
+    let now = Utc::now();
+    CalendarEvent {
+        id: id.to_string(),
+        calendar_id: calendar_id.to_string(),
+        provider,
+        title: request.title.clone(),
+        description: request.description.clone(),
+        start: request.start,
+        end: request.end,
+        all_day: request.all_day,
+        location: request.location.clone(),
+        attendees: request.attendees.iter().flatten()
+            .map(|email| EventAttendee {
+                email: email.clone(),
+                name: None,
+                response_status: AttendeeResponseStatus::NeedsAction,
+                is_organizer: false,
+            })
+            .collect(),
+        recurrence_rule: request.recurrence_rule.clone(),
+        status: EventStatus::Confirmed,
+        created_at: Some(now),
+        updated_at: Some(now),
+        etag: None,
+        html_link: None,
+        color_id: None,
+    }
+}
+
+/// Apply the set fields of `updates` to `event`
+fn apply_update(event: &mut CalendarEvent, updates: &UpdateEventRequest) {
+    if let Some(title) = &updates.title {
+        event.title = title.clone();
+    }
+    if let Some(description) = &updates.description {
+        event.description = Some(description.clone()).filter(|d| !d.is_empty());
+    }
+    if let Some(start) = updates.start {
+        event.start = start;
+    }
+    if let Some(end) = updates.end {
+        event.end = end;
+    }
+    if let Some(all_day) = updates.all_day {
+        event.all_day = all_day;
+    }
+    if let Some(location) = &updates.location {
+        event.location = Some(location.clone()).filter(|l| !l.is_empty());
+    }
+    if let Some(rule) = &updates.recurrence_rule {
+        event.recurrence_rule = Some(rule.clone());
+    }
+    if let Some(status) = &updates.status {
+        event.status = status.clone();
+    }
+    event.updated_at = Some(Utc::now());
+}
+
+fn fold_update(request: &mut CreateEventRequest, updates: &UpdateEventRequest) {
+    if let Some(title) = &updates.title {
+        request.title = title.clone();
+    }
+    if let Some(description) = &updates.description {
+        request.description = Some(description.clone()).filter(|d| !d.is_empty());
+    }
+    if let Some(start) = updates.start {
+        request.start = start;
+    }
+    if let Some(end) = updates.end {
+        request.end = end;
+    }
+    if let Some(all_day) = updates.all_day {
+        request.all_day = all_day;
+    }
+    if let Some(location) = &updates.location {
+        request.location = Some(location.clone()).filter(|l| !l.is_empty());
+    }
+    if let Some(attendees) = &updates.attendees {
+        request.attendees = Some(attendees.clone());
+    }
+    if let Some(rule) = &updates.recurrence_rule {
+        request.recurrence_rule = Some(rule.clone());
+    }
+}
+
+/// Apply pending operations on top of events fetched from the provider, so
+/// a sync doesn't undo changes that haven't been pushed yet
+pub fn overlay(events: &mut Vec<CalendarEvent>, operations: &[PendingOperation], provider: CalendarProvider) {
+    for operation in operations {
+        match &operation.change {
+            PendingChange::Create { event } => {
+                if !events.iter().any(|e| e.id == operation.event_id) {
+                    events.push(local_event(&operation.calendar_id, provider, &operation.event_id, event));
+                }
+            }
+            PendingChange::Update { updates } => {
+                if let Some(event) = events.iter_mut().find(|e| e.id == operation.event_id) {
+                    apply_update(event, updates);
+                }
+            }
+            PendingChange::Delete => events.retain(|e| e.id != operation.event_id),
+        }
+    }
+}
+
+/// Queue a change and apply it to the event cache; returns the event as it
+/// now looks locally (`None` once deleted)
+fn enqueue(
+    calendar: &Calendar,
+    event_id: String,
+    change: PendingChange,
+    base_etag: Option<String>,
+) -> Result<Option<CalendarEvent>, CalendarError> {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut operations = CalendarStorage::get_pending_operations()?;
+    let mut cache = SyncStorage::get_event_cache(&calendar.id)?;
+
+    let operation = PendingOperation {
+        id: uuid::Uuid::new_v4().to_string(),
+        calendar_id: calendar.id.clone(),
+        event_id: event_id.clone(),
+        change,
+        base_etag: base_etag.or_else(|| cache.events.iter().find(|e| e.id == event_id).and_then(|e| e.etag.clone())),
+        status: PendingOperationStatus::Pending,
+        attempts: 0,
+        last_error: None,
+        created_at: Utc::now(),
+    };
+    overlay(&mut cache.events, std::slice::from_ref(&operation), calendar.provider);
+
+    // Events that only exist locally are folded into their queued creation
+    let queued_create = operations.iter_mut().find(|o| o.event_id == event_id && matches!(o.change, PendingChange::Create { .. }));
+    match (&operation.change, queued_create) {
+        (PendingChange::Update { updates }, Some(create)) => {
+            if let PendingChange::Create { event } = &mut create.change {
+                fold_update(event, updates);
+            }
+        }
+        (PendingChange::Delete, Some(_)) => operations.retain(|o| o.event_id != event_id),
+        _ => operations.push(operation),
+    }
+
+    SyncStorage::store_event_cache(&cache)?;
+    CalendarStorage::store_pending_operations(&operations)?;
+    update_pending_count(operations.len());
+
+    Ok(cache.events.into_iter().find(|e| e.id == event_id))
+}
+
+fn has_pending(calendar_id: &str) -> bool {
+    CalendarStorage::get_pending_operations()
+        .map(|operations| operations.iter().any(|o| o.calendar_id == calendar_id))
+        .unwrap_or(false)
+}
+
+fn update_pending_count(count: usize) {
+    if let Ok(mut state) = SyncStorage::get_sync_state() {
+        state.pending_changes = count as u32;
+        let _ = SyncStorage::store_sync_state(&state);
+    }
+}
+
+/// Replace or remove an event in the cache after a push succeeded
+fn store_pushed(calendar_id: &str, replaced_id: &str, event: Option<CalendarEvent>) -> Result<(), CalendarError> {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cache = SyncStorage::get_event_cache(calendar_id)?;
+    cache.events.retain(|e| e.id != replaced_id);
+    if let Some(event) = event {
+        cache.events.retain(|e| e.id != event.id);
+        cache.events.push(event);
+    }
+    cache.events.sort_by(|a, b| a.start.cmp(&b.start));
+    SyncStorage::store_event_cache(&cache)
+}
+
+// ============== Offline-first Changes ==============
+
+/// Create an event, queueing it when the provider can't be reached
+pub async fn create_event(calendar: &Calendar, request: CreateEventRequest) -> Result<CalendarEvent, CalendarError> {
+    if !has_pending(&calendar.id) {
+        match push_create(calendar, &request).await {
+            Ok(event) => {
+                store_pushed(&calendar.id, &event.id, Some(event.clone()))?;
+                return Ok(event);
+            }
+            Err(e) if is_offline(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let local_id = format!("{}{}", LOCAL_ID_PREFIX, uuid::Uuid::new_v4());
+    enqueue(calendar, local_id, PendingChange::Create { event: request }, None)?
+        .ok_or_else(|| CalendarError::Storage("Queued event is missing from the cache".to_string()))
+}
+
+/// Update an event, queueing the change when the provider can't be reached
+pub async fn update_event(
+    calendar: &Calendar,
+    event_id: String,
+    updates: UpdateEventRequest,
+    etag: Option<String>,
+) -> Result<CalendarEvent, CalendarError> {
+    if !is_local(&event_id) && !has_pending(&calendar.id) {
+        match push_update(calendar, &event_id, &updates, etag.as_deref()).await {
+            Ok(event) => {
+                store_pushed(&calendar.id, &event_id, Some(event.clone()))?;
+                return Ok(event);
+            }
+            Err(e) if is_offline(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    enqueue(calendar, event_id.clone(), PendingChange::Update { updates }, etag)?
+        .ok_or_else(|| CalendarError::NotFound(format!("Event {} is not cached", event_id)))
+}
+
+/// Delete an event, queueing the deletion when the provider can't be reached
+pub async fn delete_event(calendar: &Calendar, event_id: String, etag: Option<String>) -> Result<(), CalendarError> {
+    if !is_local(&event_id) && !has_pending(&calendar.id) {
+        match push_delete(calendar, &event_id, etag.as_deref()).await {
+            Ok(()) => return store_pushed(&calendar.id, &event_id, None),
+            Err(e) if is_offline(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    enqueue(calendar, event_id, PendingChange::Delete, etag).map(|_| ())
+}
+
+// ============== Replay ==============
+
+/// Why an operation couldn't be pushed
+enum Rejection {
+    Offline,
+    Conflict(String),
+    Error(String),
+}
+
+async fn push_operation(calendar: &Calendar, operation: &PendingOperation) -> Result<Option<CalendarEvent>, Rejection> {
+    let reject = |e: CalendarError| if is_offline(&e) { Rejection::Offline } else { Rejection::Error(e.to_string()) };
+
+    if let PendingChange::Create { event } = &operation.change {
+        return push_create(calendar, event).await.map(Some).map_err(reject);
+    }
+
+    // Check the event is still the version the change was made against
+    let remote_etag = match fetch_remote(calendar, &operation.event_id).await {
+        Ok(remote) => remote.etag,
+        Err(CalendarError::NotFound(_)) => {
+            return match operation.change {
+                PendingChange::Delete => Ok(None),
+                _ => Err(Rejection::Conflict("The event was deleted on the server".to_string())),
+            };
+        }
+        Err(e) => return Err(reject(e)),
+    };
+    if let (Some(base), Some(remote)) = (&operation.base_etag, &remote_etag) {
+        if base != remote {
+            return Err(Rejection::Conflict("The event was changed on the server".to_string()));
+        }
+    }
+
+    match &operation.change {
+        PendingChange::Update { updates } => push_update(calendar, &operation.event_id, updates, remote_etag.as_deref())
+            .await
+            .map(Some)
+            .map_err(reject),
+        _ => push_delete(calendar, &operation.event_id, remote_etag.as_deref())
+            .await
+            .map(|_| None)
+            .map_err(reject),
+    }
+}
+
+/// Push queued operations in order; stops at the first one that can't reach
+/// its provider. Returns how many were pushed.
+pub async fn replay(app_handle: &AppHandle) -> Result<usize, String> {
+    if REPLAYING.swap(true, Ordering::SeqCst) {
+        return Ok(0);
+    }
+    let result = replay_queue(app_handle).await;
+    REPLAYING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn replay_queue(app_handle: &AppHandle) -> Result<usize, String> {
+    let calendars = CalendarStorage::get_calendars().map_err(|e| e.to_string())?;
+    let mut pushed = 0;
+    let mut changed = false;
+    // Events whose earlier operations are stuck; later ones must wait for them
+    let mut blocked: Vec<String> = Vec::new();
+
+    let queued = CalendarStorage::get_pending_operations().map_err(|e| e.to_string())?;
+    for queued_operation in queued {
+        // Re-read the operation; it may have been folded or rewritten meanwhile
+        let Some(operation) = CalendarStorage::get_pending_operations()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|o| o.id == queued_operation.id)
+        else {
+            continue;
+        };
+        if operation.status != PendingOperationStatus::Pending || blocked.contains(&operation.event_id) {
+            blocked.push(operation.event_id.clone());
+            continue;
+        }
+        let Some(calendar) = calendars.iter().find(|c| c.id == operation.calendar_id) else {
+            blocked.push(operation.event_id.clone());
+            continue;
+        };
+
+        let outcome = push_operation(calendar, &operation).await;
+        if matches!(outcome, Err(Rejection::Offline)) {
+            break;
+        }
+
+        let pushed_event = {
+            let _guard = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let mut operations = CalendarStorage::get_pending_operations().map_err(|e| e.to_string())?;
+            let pushed_event = match outcome {
+                Ok(event) => {
+                    // Later operations on an event created offline now target its real id
+                    if let Some(event) = event.as_ref().filter(|e| e.id != operation.event_id) {
+                        for later in operations.iter_mut().filter(|o| o.event_id == operation.event_id) {
+                            later.event_id = event.id.clone();
+                        }
+                        let mut links = CalendarStorage::get_note_links().map_err(|e| e.to_string())?;
+                        if links.iter().any(|l| l.event_id == operation.event_id) {
+                            for link in links.iter_mut().filter(|l| l.event_id == operation.event_id) {
+                                link.event_id = event.id.clone();
+                            }
+                            CalendarStorage::store_note_links(&links).map_err(|e| e.to_string())?;
+                        }
+                        crate::calendar::timeblocking::rename_event(&operation.event_id, &event.id)?;
+                    }
+                    operations.retain(|o| o.id != operation.id);
+                    Some(event)
+                }
+                Err(rejection) => {
+                    if let Some(stored) = operations.iter_mut().find(|o| o.id == operation.id) {
+                        match rejection {
+                            Rejection::Conflict(reason) => {
+                                stored.status = PendingOperationStatus::Conflict;
+                                stored.last_error = Some(reason);
+                            }
+                            Rejection::Error(reason) => {
+                                stored.attempts += 1;
+                                if stored.attempts >= MAX_ATTEMPTS {
+                                    stored.status = PendingOperationStatus::Failed;
+                                }
+                                stored.last_error = Some(reason);
+                            }
+                            Rejection::Offline => {}
+                        }
+                    }
+                    blocked.push(operation.event_id.clone());
+                    None
+                }
+            };
+            CalendarStorage::store_pending_operations(&operations).map_err(|e| e.to_string())?;
+            update_pending_count(operations.len());
+            pushed_event
+        };
+        changed = true;
+
+        if let Some(event) = pushed_event {
+            store_pushed(&operation.calendar_id, &operation.event_id, event).map_err(|e| e.to_string())?;
+            pushed += 1;
+        }
+    }
+
+    if changed {
+        let operations = CalendarStorage::get_pending_operations().map_err(|e| e.to_string())?;
+        let _ = app_handle.emit("calendar-pending-operations", &operations);
+    }
+    Ok(pushed)
+}
+
+// ============== Tauri Commands ==============
+
+/// Event changes waiting to be pushed, oldest first
+#[tauri::command]
+pub fn calendar_pending_operations() -> Result<Vec<PendingOperation>, LokusError> {
+    CalendarStorage::get_pending_operations()
+        .map_err(LokusError::from)
+}
+
+/// Try a conflicted or failed operation again, overwriting the server's version
+#[tauri::command]
+pub fn calendar_retry_pending_operation(operation_id: String) -> Result<(), LokusError> {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut operations = CalendarStorage::get_pending_operations()?;
+    let operation = operations.iter_mut()
+        .find(|o| o.id == operation_id)
+        .ok_or_else(|| "Pending operation not found".to_string())?;
+
+    operation.status = PendingOperationStatus::Pending;
+    operation.base_etag = None;
+    operation.attempts = 0;
+    operation.last_error = None;
+    CalendarStorage::store_pending_operations(&operations).map_err(LokusError::from)
+}
+
+/// Drop a queued change; the calendar is fully re-synced to undo it locally
+#[tauri::command]
+pub fn calendar_discard_pending_operation(operation_id: String) -> Result<(), LokusError> {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut operations = CalendarStorage::get_pending_operations()?;
+    let Some(index) = operations.iter().position(|o| o.id == operation_id) else {
+        return Ok(());
+    };
+    let operation = operations.remove(index);
+    // Without its creation, later changes to an offline event have nothing to apply to
+    if matches!(operation.change, PendingChange::Create { .. }) {
+        operations.retain(|o| o.event_id != operation.event_id);
+    }
+
+    let mut cache = SyncStorage::get_event_cache(&operation.calendar_id)?;
+    if is_local(&operation.event_id) {
+        cache.events.retain(|e| e.id != operation.event_id);
+    }
+    cache.sync_token = None;
+    cache.ctag = None;
+    SyncStorage::store_event_cache(&cache)?;
+
+    CalendarStorage::store_pending_operations(&operations)?;
+    update_pending_count(operations.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn operation(event_id: &str, change: PendingChange) -> PendingOperation {
+        PendingOperation {
+            id: uuid::Uuid::new_v4().to_string(),
+            calendar_id: "primary".to_string(),
+            event_id: event_id.to_string(),
+            change,
+            base_etag: None,
+            status: PendingOperationStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_overlay_keeps_unpushed_changes() {
+        let start = Utc::now();
+        let create = CreateEventRequest {
+            title: "Dentist".to_string(),
+            description: None,
+            start,
+            end: start + Duration::hours(1),
+            all_day: false,
+            location: None,
+            attendees: Some(vec!["ada@example.com".to_string()]),
+            recurrence_rule: None,
+        };
+        let mut events = vec![
+            local_event("primary", CalendarProvider::Google, "a", &create),
+            local_event("primary", CalendarProvider::Google, "b", &create),
+        ];
+        let rename = UpdateEventRequest {
+            title: Some("Dentist (moved)".to_string()),
+            description: None,
+            start: None,
+            end: None,
+            all_day: None,
+            location: Some(String::new()),
+            attendees: None,
+            recurrence_rule: None,
+            status: None,
+        };
+        let operations = vec![
+            operation("a", PendingChange::Update { updates: rename }),
+            operation("b", PendingChange::Delete),
+            operation("local-1", PendingChange::Create { event: create }),
+        ];
+
+        overlay(&mut events, &operations, CalendarProvider::Google);
+        // Applying twice must not duplicate the offline event
+        overlay(&mut events, &operations, CalendarProvider::Google);
+
+        let ids: Vec<&str> = events.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "local-1"]);
+        assert_eq!(events[0].title, "Dentist (moved)");
+        assert_eq!(events[1].attendees[0].email, "ada@example.com");
+    }
+}
@@ -0,0 +1,548 @@
+use crate::calendar::models::{
+    Calendar, CalendarEvent, CalendarProvider, CalendarError,
+    EventAttendee, AttendeeResponseStatus, EventStatus,
+    CreateEventRequest, UpdateEventRequest, EventChanges,
+};
+use crate::calendar::outlook::auth::OutlookCalendarAuth;
+use reqwest::{Client, RequestBuilder};
+use serde_json;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+const GRAPH_API_BASE: &str = "https://graph.microsoft.com/v1.0";
+
+/// Ask Graph for UTC times and plain-text bodies
+const PREFER_HEADER: &str = r#"outlook.timezone="UTC", outlook.body-content-type="text""#;
+
+const PAGE_SIZE: u32 = 250;
+
+/// Microsoft Graph calendar client for Outlook / Office 365
+pub struct OutlookCalendarApi {
+    auth: OutlookCalendarAuth,
+    client: Client,
+}
+
+impl OutlookCalendarApi {
+    pub fn new() -> Result<Self, CalendarError> {
+        let auth = OutlookCalendarAuth::new()?;
+        let client = Client::new();
+        Ok(Self { auth, client })
+    }
+
+    async fn authorized(&self, request: RequestBuilder) -> Result<RequestBuilder, CalendarError> {
+        let token = self.auth.get_valid_token().await?;
+        Ok(request
+            .bearer_auth(&token.access_token)
+            .header("Prefer", PREFER_HEADER))
+    }
+
+    /// List all calendars of the signed-in user
+    pub async fn list_calendars(&self) -> Result<Vec<Calendar>, CalendarError> {
+        let url = format!("{}/me/calendars?$top=100", GRAPH_API_BASE);
+        let items = self.get_all_pages(url, "list calendars").await?;
+
+        Ok(items.iter().map(|item| self.parse_calendar(item)).collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Get events from a calendar within a time range, recurring events
+    /// expanded into their occurrences
+    pub async fn get_events(
+        &self,
+        calendar_id: &str,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, CalendarError> {
+        let url = format!(
+            "{}/me/calendars/{}/calendarView?startDateTime={}&endDateTime={}&$top={}",
+            GRAPH_API_BASE,
+            urlencoding::encode(calendar_id),
+            urlencoding::encode(&time_min.to_rfc3339()),
+            urlencoding::encode(&time_max.to_rfc3339()),
+            PAGE_SIZE
+        );
+        let items = self.get_all_pages(url, "get events").await?;
+
+        items.iter()
+            .map(|item| self.parse_event(item, calendar_id))
+            .collect()
+    }
+
+    async fn get_all_pages(&self, first_url: String, action: &str) -> Result<Vec<serde_json::Value>, CalendarError> {
+        let mut items = Vec::new();
+        let mut next = Some(first_url);
+
+        while let Some(url) = next {
+            let response = self.authorized(self.client.get(&url)).await?
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(CalendarError::Api(format!("Failed to {}: {}", action, error_text)));
+            }
+
+            let data: serde_json::Value = response.json().await?;
+            items.extend(data["value"].as_array().cloned().unwrap_or_default());
+            next = data["@odata.nextLink"].as_str().map(String::from);
+        }
+
+        Ok(items)
+    }
+
+    /// Events changed since `delta_link`, or every event in the window when
+    /// there is none. The returned sync token is the next delta link.
+    /// Returns `SyncTokenExpired` when Graph asks for a full sync.
+    pub async fn sync_events(
+        &self,
+        calendar_id: &str,
+        delta_link: Option<&str>,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<EventChanges, CalendarError> {
+        let mut url = match delta_link {
+            Some(link) => link.to_string(),
+            None => format!(
+                "{}/me/calendars/{}/calendarView/delta?startDateTime={}&endDateTime={}",
+                GRAPH_API_BASE,
+                urlencoding::encode(calendar_id),
+                urlencoding::encode(&time_min.to_rfc3339()),
+                urlencoding::encode(&time_max.to_rfc3339())
+            ),
+        };
+        let mut changes = EventChanges::default();
+
+        loop {
+            let response = self.authorized(self.client.get(&url)).await?
+                .header("Prefer", format!("odata.maxpagesize={}", PAGE_SIZE))
+                .send()
+                .await?;
+
+            if response.status() == reqwest::StatusCode::GONE {
+                return Err(CalendarError::SyncTokenExpired);
+            }
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                // An expired delta token is reported as a 400 with this code
+                if error_text.contains("SyncStateNotFound") || error_text.contains("syncStateNotFound") {
+                    return Err(CalendarError::SyncTokenExpired);
+                }
+                return Err(CalendarError::Api(format!("Failed to sync events: {}", error_text)));
+            }
+
+            let data: serde_json::Value = response.json().await?;
+            for item in data["value"].as_array().into_iter().flatten() {
+                if !item["@removed"].is_null() || item["isCancelled"].as_bool() == Some(true) {
+                    if let Some(id) = item["id"].as_str() {
+                        changes.deleted.push(id.to_string());
+                    }
+                } else {
+                    changes.changed.push(self.parse_event(item, calendar_id)?);
+                }
+            }
+
+            match (data["@odata.nextLink"].as_str(), data["@odata.deltaLink"].as_str()) {
+                (Some(next), _) => url = next.to_string(),
+                (None, delta) => {
+                    changes.sync_token = delta.map(String::from);
+                    return Ok(changes);
+                }
+            }
+        }
+    }
+
+    /// Get a single event by ID
+    pub async fn get_event(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+    ) -> Result<CalendarEvent, CalendarError> {
+        let url = format!("{}/me/events/{}", GRAPH_API_BASE, urlencoding::encode(event_id));
+
+        let response = self.authorized(self.client.get(&url)).await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(CalendarError::NotFound(format!("Event {} not found", event_id)));
+        }
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::Api(format!("Failed to get event: {}", error_text)));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        self.parse_event(&data, calendar_id)
+    }
+
+    /// Create a new event
+    pub async fn create_event(
+        &self,
+        calendar_id: &str,
+        request: &CreateEventRequest,
+    ) -> Result<CalendarEvent, CalendarError> {
+        let url = format!("{}/me/calendars/{}/events", GRAPH_API_BASE, urlencoding::encode(calendar_id));
+
+        let mut body = serde_json::json!({
+            "subject": request.title,
+            "isAllDay": request.all_day,
+            "start": graph_time(request.start, request.all_day),
+            "end": graph_time(request.end, request.all_day),
+        });
+        if let Some(ref description) = request.description {
+            body["body"] = serde_json::json!({ "contentType": "text", "content": description });
+        }
+        if let Some(ref location) = request.location {
+            body["location"] = serde_json::json!({ "displayName": location });
+        }
+        if let Some(ref attendees) = request.attendees {
+            body["attendees"] = graph_attendees(attendees);
+        }
+        if let Some(recurrence) = request.recurrence_rule.as_deref().and_then(|r| rrule_to_graph(r, request.start)) {
+            body["recurrence"] = recurrence;
+        }
+
+        let response = self.authorized(self.client.post(&url)).await?
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::Api(format!("Failed to create event: {}", error_text)));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        self.parse_event(&data, calendar_id)
+    }
+
+    /// Update an existing event; with `etag` the update fails if the event
+    /// changed in the meantime
+    pub async fn update_event(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+        request: &UpdateEventRequest,
+        etag: Option<&str>,
+    ) -> Result<CalendarEvent, CalendarError> {
+        let url = format!("{}/me/events/{}", GRAPH_API_BASE, urlencoding::encode(event_id));
+
+        // PATCH only touches the fields that are sent
+        let mut body = serde_json::json!({});
+        if let Some(ref title) = request.title {
+            body["subject"] = serde_json::json!(title);
+        }
+        if let Some(ref description) = request.description {
+            body["body"] = serde_json::json!({ "contentType": "text", "content": description });
+        }
+        if let Some(ref location) = request.location {
+            body["location"] = serde_json::json!({ "displayName": location });
+        }
+        if let Some(all_day) = request.all_day {
+            body["isAllDay"] = serde_json::json!(all_day);
+        }
+        let all_day = request.all_day.unwrap_or(false);
+        if let Some(start) = request.start {
+            body["start"] = graph_time(start, all_day);
+        }
+        if let Some(end) = request.end {
+            body["end"] = graph_time(end, all_day);
+        }
+        if let Some(ref attendees) = request.attendees {
+            body["attendees"] = graph_attendees(attendees);
+        }
+        if let Some(ref status) = request.status {
+            body["showAs"] = serde_json::json!(match status {
+                EventStatus::Tentative => "tentative",
+                EventStatus::Cancelled => "free",
+                EventStatus::Confirmed => "busy",
+            });
+        }
+        if let (Some(rrule), Some(start)) = (request.recurrence_rule.as_deref(), request.start) {
+            if let Some(recurrence) = rrule_to_graph(rrule, start) {
+                body["recurrence"] = recurrence;
+            }
+        }
+
+        let mut builder = self.authorized(self.client.patch(&url)).await?.json(&body);
+        if let Some(etag) = etag {
+            builder = builder.header("If-Match", etag);
+        }
+        let response = builder.send().await?;
+
+        if response.status() == reqwest::StatusCode::PRECONDITION_FAILED {
+            return Err(CalendarError::Api("Event was modified by another client".to_string()));
+        }
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::Api(format!("Failed to update event: {}", error_text)));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        self.parse_event(&data, calendar_id)
+    }
+
+    /// Delete an event
+    pub async fn delete_event(&self, event_id: &str) -> Result<(), CalendarError> {
+        let url = format!("{}/me/events/{}", GRAPH_API_BASE, urlencoding::encode(event_id));
+
+        let response = self.authorized(self.client.delete(&url)).await?
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(CalendarError::NotFound(format!("Event {} not found", event_id)));
+        }
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::Api(format!("Failed to delete event: {}", error_text)));
+        }
+
+        Ok(())
+    }
+
+    fn parse_calendar(&self, data: &serde_json::Value) -> Result<Calendar, CalendarError> {
+        Ok(Calendar {
+            id: data["id"]
+                .as_str()
+                .ok_or_else(|| CalendarError::Parse("Missing calendar ID".to_string()))?
+                .to_string(),
+            provider: CalendarProvider::Outlook,
+            name: data["name"].as_str().unwrap_or("Untitled").to_string(),
+            description: None,
+            color: data["hexColor"].as_str().filter(|c| !c.is_empty()).map(String::from),
+            is_primary: data["isDefaultCalendar"].as_bool().unwrap_or(false),
+            is_writable: data["canEdit"].as_bool().unwrap_or(false),
+            sync_token: None,
+            last_synced: None,
+            visible: true,
+        })
+    }
+
+    fn parse_event(&self, data: &serde_json::Value, calendar_id: &str) -> Result<CalendarEvent, CalendarError> {
+        let id = data["id"]
+            .as_str()
+            .ok_or_else(|| CalendarError::Parse("Missing event ID".to_string()))?
+            .to_string();
+
+        let start = parse_graph_time(&data["start"])
+            .ok_or_else(|| CalendarError::Parse(format!("Invalid start time for event {}", id)))?;
+        let end = parse_graph_time(&data["end"]).unwrap_or(start);
+
+        let organizer = data["organizer"]["emailAddress"]["address"].as_str().unwrap_or("");
+        let attendees = data["attendees"]
+            .as_array()
+            .map(|list| list.iter().filter_map(|a| {
+                let email = a["emailAddress"]["address"].as_str()?;
+                Some(EventAttendee {
+                    email: email.to_string(),
+                    name: a["emailAddress"]["name"].as_str().map(String::from),
+                    response_status: match a["status"]["response"].as_str() {
+                        Some("accepted") | Some("organizer") => AttendeeResponseStatus::Accepted,
+                        Some("declined") => AttendeeResponseStatus::Declined,
+                        Some("tentativelyAccepted") => AttendeeResponseStatus::Tentative,
+                        _ => AttendeeResponseStatus::NeedsAction,
+                    },
+                    is_organizer: email.eq_ignore_ascii_case(organizer),
+                })
+            }).collect())
+            .unwrap_or_default();
+
+        let status = if data["isCancelled"].as_bool() == Some(true) {
+            EventStatus::Cancelled
+        } else if data["showAs"].as_str() == Some("tentative") {
+            EventStatus::Tentative
+        } else {
+            EventStatus::Confirmed
+        };
+
+        let timestamp = |key: &str| data[key].as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|d| d.with_timezone(&Utc));
+
+        Ok(CalendarEvent {
+            id,
+            calendar_id: calendar_id.to_string(),
+            provider: CalendarProvider::Outlook,
+            title: data["subject"].as_str().unwrap_or("(No title)").to_string(),
+            description: data["body"]["content"].as_str()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty()),
+            start,
+            end,
+            all_day: data["isAllDay"].as_bool().unwrap_or(false),
+            location: data["location"]["displayName"].as_str()
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+            attendees,
+            recurrence_rule: graph_to_rrule(&data["recurrence"]),
+            status,
+            created_at: timestamp("createdDateTime"),
+            updated_at: timestamp("lastModifiedDateTime"),
+            etag: data["@odata.etag"].as_str().map(String::from),
+            html_link: data["webLink"].as_str().map(String::from),
+            color_id: None,
+        })
+    }
+}
+
+/// Graph `dateTimeTimeZone` for a UTC time; all-day events start at midnight
+fn graph_time(time: DateTime<Utc>, all_day: bool) -> serde_json::Value {
+    let formatted = if all_day {
+        time.format("%Y-%m-%dT00:00:00").to_string()
+    } else {
+        time.format("%Y-%m-%dT%H:%M:%S").to_string()
+    };
+    serde_json::json!({ "dateTime": formatted, "timeZone": "UTC" })
+}
+
+/// Parse a `dateTimeTimeZone`; times are requested in UTC via the Prefer header
+fn parse_graph_time(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let date_time = value["dateTime"].as_str()?;
+    NaiveDateTime::parse_from_str(date_time, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+fn graph_attendees(emails: &[String]) -> serde_json::Value {
+    serde_json::json!(
+        emails.iter().map(|email| serde_json::json!({
+            "emailAddress": { "address": email },
+            "type": "required"
+        })).collect::<Vec<_>>()
+    )
+}
+
+const WEEKDAYS: [(&str, &str); 7] = [
+    ("MO", "monday"), ("TU", "tuesday"), ("WE", "wednesday"), ("TH", "thursday"),
+    ("FR", "friday"), ("SA", "saturday"), ("SU", "sunday"),
+];
+
+/// Convert a simple RRULE (FREQ, INTERVAL, BYDAY, COUNT, UNTIL) to a Graph
+/// `patternedRecurrence`; rules Graph can't express return `None`
+fn rrule_to_graph(rrule: &str, start: DateTime<Utc>) -> Option<serde_json::Value> {
+    let rule = rrule.trim().trim_start_matches("RRULE:");
+    let parts: std::collections::HashMap<&str, &str> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect();
+
+    let interval: u32 = parts.get("INTERVAL").and_then(|i| i.parse().ok()).unwrap_or(1);
+    let days: Vec<&str> = parts.get("BYDAY")
+        .map(|days| days.split(',')
+            .filter_map(|d| WEEKDAYS.iter().find(|(short, _)| *short == d.trim()).map(|(_, long)| *long))
+            .collect())
+        .unwrap_or_default();
+
+    let mut pattern = match *parts.get("FREQ")? {
+        "DAILY" => serde_json::json!({ "type": "daily" }),
+        "WEEKLY" => {
+            let days = if days.is_empty() {
+                vec![start.format("%A").to_string().to_lowercase()]
+            } else {
+                days.iter().map(|d| d.to_string()).collect()
+            };
+            serde_json::json!({ "type": "weekly", "daysOfWeek": days, "firstDayOfWeek": "monday" })
+        }
+        "MONTHLY" => serde_json::json!({ "type": "absoluteMonthly", "dayOfMonth": start.format("%-d").to_string().parse::<u32>().ok()? }),
+        "YEARLY" => serde_json::json!({
+            "type": "absoluteYearly",
+            "dayOfMonth": start.format("%-d").to_string().parse::<u32>().ok()?,
+            "month": start.format("%-m").to_string().parse::<u32>().ok()?,
+        }),
+        _ => return None,
+    };
+    pattern["interval"] = serde_json::json!(interval);
+
+    let start_date = start.format("%Y-%m-%d").to_string();
+    let range = if let Some(count) = parts.get("COUNT").and_then(|c| c.parse::<u32>().ok()) {
+        serde_json::json!({ "type": "numbered", "startDate": start_date, "numberOfOccurrences": count })
+    } else if let Some(until) = parts.get("UNTIL") {
+        let end_date = NaiveDate::parse_from_str(until.get(..8)?, "%Y%m%d").ok()?;
+        serde_json::json!({ "type": "endDate", "startDate": start_date, "endDate": end_date.format("%Y-%m-%d").to_string() })
+    } else {
+        serde_json::json!({ "type": "noEnd", "startDate": start_date })
+    };
+
+    Some(serde_json::json!({ "pattern": pattern, "range": range }))
+}
+
+/// Convert a Graph `patternedRecurrence` back to an RRULE
+fn graph_to_rrule(recurrence: &serde_json::Value) -> Option<String> {
+    let pattern = &recurrence["pattern"];
+    let freq = match pattern["type"].as_str()? {
+        "daily" => "DAILY",
+        "weekly" => "WEEKLY",
+        "absoluteMonthly" | "relativeMonthly" => "MONTHLY",
+        "absoluteYearly" | "relativeYearly" => "YEARLY",
+        _ => return None,
+    };
+
+    let mut rule = format!("FREQ={}", freq);
+    let interval = pattern["interval"].as_u64().unwrap_or(1);
+    if interval > 1 {
+        rule.push_str(&format!(";INTERVAL={}", interval));
+    }
+    let days: Vec<&str> = pattern["daysOfWeek"].as_array()
+        .map(|days| days.iter()
+            .filter_map(|d| d.as_str())
+            .filter_map(|d| WEEKDAYS.iter().find(|(_, long)| *long == d).map(|(short, _)| *short))
+            .collect())
+        .unwrap_or_default();
+    if !days.is_empty() {
+        rule.push_str(&format!(";BYDAY={}", days.join(",")));
+    }
+
+    let range = &recurrence["range"];
+    match range["type"].as_str() {
+        Some("numbered") => {
+            if let Some(count) = range["numberOfOccurrences"].as_u64() {
+                rule.push_str(&format!(";COUNT={}", count));
+            }
+        }
+        Some("endDate") => {
+            if let Some(end) = range["endDate"].as_str().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) {
+                rule.push_str(&format!(";UNTIL={}T235959Z", end.format("%Y%m%d")));
+            }
+        }
+        _ => {}
+    }
+
+    Some(rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_recurrence_round_trip() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+
+        let weekly = rrule_to_graph("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10", start).unwrap();
+        assert_eq!(weekly["pattern"]["type"], "weekly");
+        assert_eq!(weekly["pattern"]["interval"], 2);
+        assert_eq!(weekly["pattern"]["daysOfWeek"], serde_json::json!(["monday", "wednesday"]));
+        assert_eq!(weekly["range"]["numberOfOccurrences"], 10);
+        assert_eq!(graph_to_rrule(&weekly).unwrap(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10");
+
+        let monthly = rrule_to_graph("FREQ=MONTHLY;UNTIL=20241231T000000Z", start).unwrap();
+        assert_eq!(monthly["pattern"]["dayOfMonth"], 4);
+        assert_eq!(monthly["range"]["endDate"], "2024-12-31");
+        assert_eq!(graph_to_rrule(&monthly).unwrap(), "FREQ=MONTHLY;UNTIL=20241231T235959Z");
+
+        assert!(rrule_to_graph("FREQ=HOURLY", start).is_none());
+        assert!(graph_to_rrule(&serde_json::Value::Null).is_none());
+    }
+
+    #[test]
+    fn test_parse_graph_time() {
+        let parsed = parse_graph_time(&serde_json::json!({
+            "dateTime": "2024-03-04T09:30:00.0000000",
+            "timeZone": "UTC"
+        }));
+        assert_eq!(parsed, Some(Utc.with_ymd_and_hms(2024, 3, 4, 9, 30, 0).unwrap()));
+    }
+}
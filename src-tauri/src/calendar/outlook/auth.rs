@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::calendar::models::{CalendarToken, CalendarAccount, CalendarProvider, CalendarError};
+use crate::calendar::storage::CalendarStorage;
+use crate::connections::oauth_manager::{self, Provider};
+use crate::calendar::google::PKCEData;
+use reqwest::Client;
+use serde_json;
+use chrono::Utc;
+
+const AUTHORITY: &str = "https://login.microsoftonline.com/common/oauth2/v2.0";
+
+/// Microsoft identity platform OAuth for Outlook / Office 365 calendars.
+/// Registered as a public client, so the secret is optional.
+pub struct OutlookCalendarAuth {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+}
+
+impl OutlookCalendarAuth {
+    pub fn new() -> Result<Self, CalendarError> {
+        let client_id = std::env::var("MICROSOFT_CLIENT_ID")
+            .map_err(|_| CalendarError::Auth("MICROSOFT_CLIENT_ID environment variable not set".to_string()))?;
+
+        let client_secret = std::env::var("MICROSOFT_CLIENT_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        // Use OAuth server port for callback (same as Google)
+        let oauth_port = std::env::var("OAUTH_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(9080);
+
+        Ok(Self {
+            client_id,
+            client_secret,
+            redirect_uri: format!("http://localhost:{}/outlook-callback", oauth_port),
+        })
+    }
+
+    pub fn generate_auth_url(&self, pkce_data: &PKCEData) -> Result<String, CalendarError> {
+        // offline_access is what gets us a refresh token
+        let scopes = [
+            "offline_access",
+            "User.Read",
+            "Calendars.ReadWrite",
+        ].join(" ");
+
+        let mut params = HashMap::new();
+        params.insert("client_id", self.client_id.as_str());
+        params.insert("response_type", "code");
+        params.insert("response_mode", "query");
+        params.insert("scope", &scopes);
+        params.insert("redirect_uri", &self.redirect_uri);
+        params.insert("state", &pkce_data.state);
+        params.insert("code_challenge", &pkce_data.code_challenge);
+        params.insert("code_challenge_method", "S256");
+        params.insert("prompt", "select_account");
+
+        let query_string = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        Ok(format!("{}/authorize?{}", AUTHORITY, query_string))
+    }
+
+    async fn request_token(&self, mut params: HashMap<&str, &str>) -> Result<serde_json::Value, CalendarError> {
+        params.insert("client_id", self.client_id.as_str());
+        if let Some(secret) = &self.client_secret {
+            params.insert("client_secret", secret.as_str());
+        }
+
+        let response = Client::new()
+            .post(format!("{}/token", AUTHORITY))
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::Auth(format!("Token request failed: {}", error_text)));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    fn parse_token(token_data: &serde_json::Value, previous_refresh_token: Option<&str>) -> Result<CalendarToken, CalendarError> {
+        let access_token = token_data["access_token"]
+            .as_str()
+            .ok_or_else(|| CalendarError::Auth("No access token in response".to_string()))?;
+
+        let expires_in = token_data["expires_in"].as_u64().unwrap_or(3600);
+        let expires_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + expires_in;
+
+        Ok(CalendarToken {
+            access_token: access_token.to_string(),
+            // Microsoft rotates refresh tokens; keep the old one if none came back
+            refresh_token: token_data["refresh_token"]
+                .as_str()
+                .or(previous_refresh_token)
+                .map(|s| s.to_string()),
+            expires_at: Some(expires_at),
+            scope: token_data["scope"].as_str().unwrap_or("").to_string(),
+            token_type: token_data["token_type"].as_str().unwrap_or("Bearer").to_string(),
+        })
+    }
+
+    pub async fn exchange_code_for_token(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<CalendarToken, CalendarError> {
+        let mut params = HashMap::new();
+        params.insert("code", code);
+        params.insert("grant_type", "authorization_code");
+        params.insert("redirect_uri", &self.redirect_uri);
+        params.insert("code_verifier", code_verifier);
+
+        let token_data = self.request_token(params).await?;
+        let token = Self::parse_token(&token_data, None)?;
+
+        CalendarStorage::store_outlook_token(&token)?;
+
+        Ok(token)
+    }
+
+    /// A token that is good for at least a few more minutes, refreshed if need be
+    pub async fn get_valid_token(&self) -> Result<CalendarToken, CalendarError> {
+        Ok(oauth_manager::access_token(Provider::Outlook).await?.into())
+    }
+
+    pub async fn fetch_and_store_account(&self, token: &CalendarToken) -> Result<CalendarAccount, CalendarError> {
+        let response = Client::new()
+            .get("https://graph.microsoft.com/v1.0/me")
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(CalendarError::Api(format!("Failed to fetch user info: {}", error_text)));
+        }
+
+        let me: serde_json::Value = response.json().await?;
+
+        // Work accounts without a mailbox address only have a UPN
+        let email = me["mail"]
+            .as_str()
+            .or_else(|| me["userPrincipalName"].as_str())
+            .ok_or_else(|| CalendarError::Api("No email in user info".to_string()))?;
+
+        let account = CalendarAccount {
+            id: me["id"]
+                .as_str()
+                .ok_or_else(|| CalendarError::Api("No user ID in response".to_string()))?
+                .to_string(),
+            provider: CalendarProvider::Outlook,
+            email: email.to_string(),
+            is_connected: true,
+            connected_at: Some(Utc::now()),
+        };
+
+        CalendarStorage::store_outlook_account(&account)?;
+
+        Ok(account)
+    }
+
+    pub fn is_authenticated(&self) -> Result<bool, CalendarError> {
+        // An expired access token is fine as long as it can be refreshed
+        match CalendarStorage::get_outlook_token()? {
+            Some(token) => Ok(token.refresh_token.is_some() || !CalendarStorage::is_token_expired(&token)),
+            None => Ok(false),
+        }
+    }
+}
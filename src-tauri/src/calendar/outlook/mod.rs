@@ -0,0 +1,5 @@
+pub mod auth;
+pub mod api;
+
+pub use auth::*;
+pub use api::*;
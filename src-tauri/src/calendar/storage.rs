@@ -1,10 +1,12 @@
 use std::path::PathBuf;
 use keyring::Entry;
-use crate::calendar::models::{CalendarToken, CalendarAccount, Calendar, CalendarError, ICalSubscription, CalendarEvent, CalDAVAccount};
+use crate::calendar::models::{CalendarToken, CalendarAccount, Calendar, CalendarError, ICalSubscription, CalendarEvent, CalDAVAccount, EventNoteLink, PendingOperation, TaskEventLink};
 use serde_json;
 
 const GOOGLE_TOKEN_KEY: &str = "lokus_google_calendar_token";
 const GOOGLE_ACCOUNT_KEY: &str = "lokus_google_calendar_account";
+const OUTLOOK_TOKEN_KEY: &str = "lokus_outlook_calendar_token";
+const OUTLOOK_ACCOUNT_KEY: &str = "lokus_outlook_calendar_account";
 #[allow(dead_code)]
 const CALENDARS_KEY: &str = "lokus_calendars";
 const SERVICE_NAME: &str = "com.lokus.app.calendar";
@@ -118,6 +120,103 @@ impl CalendarStorage {
         }
     }
 
+    // Outlook token and account storage (same layout as Google)
+    pub fn store_outlook_token(token: &CalendarToken) -> Result<(), CalendarError> {
+        if cfg!(debug_assertions) {
+            return Self::store_token_to_file("outlook", token);
+        }
+
+        let entry = Self::get_keyring_entry(OUTLOOK_TOKEN_KEY)?;
+        let token_json = serde_json::to_string(token)
+            .map_err(|e| CalendarError::Storage(format!("Failed to serialize token: {}", e)))?;
+
+        entry.set_password(&token_json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to store token: {}", e)))
+    }
+
+    pub fn get_outlook_token() -> Result<Option<CalendarToken>, CalendarError> {
+        if cfg!(debug_assertions) {
+            return Self::get_token_from_file("outlook");
+        }
+
+        let entry = Self::get_keyring_entry(OUTLOOK_TOKEN_KEY)?;
+        match entry.get_password() {
+            Ok(token_json) => {
+                let token: CalendarToken = serde_json::from_str(&token_json)
+                    .map_err(|e| CalendarError::Storage(format!("Failed to deserialize token: {}", e)))?;
+                Ok(Some(token))
+            }
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(CalendarError::Storage(format!("Failed to retrieve token: {}", e))),
+        }
+    }
+
+    pub fn delete_outlook_token() -> Result<(), CalendarError> {
+        if cfg!(debug_assertions) {
+            let token_path = Self::get_dev_token_path("outlook")?;
+            if token_path.exists() {
+                std::fs::remove_file(&token_path)
+                    .map_err(|e| CalendarError::Storage(format!("Failed to delete token file: {}", e)))?;
+            }
+            return Ok(());
+        }
+
+        let entry = Self::get_keyring_entry(OUTLOOK_TOKEN_KEY)?;
+        match entry.delete_credential() {
+            Ok(_) => Ok(()),
+            Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(CalendarError::Storage(format!("Failed to delete token: {}", e))),
+        }
+    }
+
+    pub fn store_outlook_account(account: &CalendarAccount) -> Result<(), CalendarError> {
+        if cfg!(debug_assertions) {
+            return Self::store_account_to_file("outlook", account);
+        }
+
+        let entry = Self::get_keyring_entry(OUTLOOK_ACCOUNT_KEY)?;
+        let account_json = serde_json::to_string(account)
+            .map_err(|e| CalendarError::Storage(format!("Failed to serialize account: {}", e)))?;
+
+        entry.set_password(&account_json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to store account: {}", e)))
+    }
+
+    pub fn get_outlook_account() -> Result<Option<CalendarAccount>, CalendarError> {
+        if cfg!(debug_assertions) {
+            return Self::get_account_from_file("outlook");
+        }
+
+        let entry = Self::get_keyring_entry(OUTLOOK_ACCOUNT_KEY)?;
+        match entry.get_password() {
+            Ok(account_json) => {
+                let account: CalendarAccount = serde_json::from_str(&account_json)
+                    .map_err(|e| CalendarError::Storage(format!("Failed to deserialize account: {}", e)))?;
+                Ok(Some(account))
+            }
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(CalendarError::Storage(format!("Failed to retrieve account: {}", e))),
+        }
+    }
+
+    pub fn delete_outlook_account() -> Result<(), CalendarError> {
+        if cfg!(debug_assertions) {
+            let account_path = Self::get_dev_account_path("outlook")?;
+            if account_path.exists() {
+                std::fs::remove_file(&account_path)
+                    .map_err(|e| CalendarError::Storage(format!("Failed to delete account file: {}", e)))?;
+            }
+            return Ok(());
+        }
+
+        let entry = Self::get_keyring_entry(OUTLOOK_ACCOUNT_KEY)?;
+        match entry.delete_credential() {
+            Ok(_) => Ok(()),
+            Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(CalendarError::Storage(format!("Failed to delete account: {}", e))),
+        }
+    }
+
     // Calendars list storage (stored in file for both dev and prod - not sensitive)
     pub fn store_calendars(calendars: &[Calendar]) -> Result<(), CalendarError> {
         let calendars_path = Self::get_calendars_path()?;
@@ -354,6 +453,105 @@ impl CalendarStorage {
         Ok(all_events)
     }
 
+    // Event <-> note links
+    fn get_note_links_path() -> Result<PathBuf, CalendarError> {
+        let base_path = Self::get_dev_base_path()?;
+        Ok(base_path.join("note_links.json"))
+    }
+
+    pub fn store_note_links(links: &[EventNoteLink]) -> Result<(), CalendarError> {
+        let path = Self::get_note_links_path()?;
+        let json = serde_json::to_string_pretty(links)
+            .map_err(|e| CalendarError::Storage(format!("Failed to serialize note links: {}", e)))?;
+
+        std::fs::write(&path, json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to write note links file: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn get_note_links() -> Result<Vec<EventNoteLink>, CalendarError> {
+        let path = Self::get_note_links_path()?;
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| CalendarError::Storage(format!("Failed to read note links file: {}", e)))?;
+
+        let links: Vec<EventNoteLink> = serde_json::from_str(&json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to deserialize note links: {}", e)))?;
+
+        Ok(links)
+    }
+
+    // Task <-> event links
+    fn get_task_links_path() -> Result<PathBuf, CalendarError> {
+        let base_path = Self::get_dev_base_path()?;
+        Ok(base_path.join("task_links.json"))
+    }
+
+    pub fn store_task_links(links: &[TaskEventLink]) -> Result<(), CalendarError> {
+        let path = Self::get_task_links_path()?;
+        let json = serde_json::to_string_pretty(links)
+            .map_err(|e| CalendarError::Storage(format!("Failed to serialize task links: {}", e)))?;
+
+        std::fs::write(&path, json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to write task links file: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn get_task_links() -> Result<Vec<TaskEventLink>, CalendarError> {
+        let path = Self::get_task_links_path()?;
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| CalendarError::Storage(format!("Failed to read task links file: {}", e)))?;
+
+        let links: Vec<TaskEventLink> = serde_json::from_str(&json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to deserialize task links: {}", e)))?;
+
+        Ok(links)
+    }
+
+    // Event changes waiting to be pushed
+    fn get_pending_operations_path() -> Result<PathBuf, CalendarError> {
+        let base_path = Self::get_dev_base_path()?;
+        Ok(base_path.join("pending_operations.json"))
+    }
+
+    pub fn store_pending_operations(operations: &[PendingOperation]) -> Result<(), CalendarError> {
+        let path = Self::get_pending_operations_path()?;
+        let json = serde_json::to_string_pretty(operations)
+            .map_err(|e| CalendarError::Storage(format!("Failed to serialize pending operations: {}", e)))?;
+
+        std::fs::write(&path, json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to write pending operations file: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn get_pending_operations() -> Result<Vec<PendingOperation>, CalendarError> {
+        let path = Self::get_pending_operations_path()?;
+
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| CalendarError::Storage(format!("Failed to read pending operations file: {}", e)))?;
+
+        let operations: Vec<PendingOperation> = serde_json::from_str(&json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to deserialize pending operations: {}", e)))?;
+
+        Ok(operations)
+    }
+
     // CalDAV account storage
     const CALDAV_ACCOUNT_KEY: &'static str = "lokus_caldav_account";
     const CALDAV_SERVICE_NAME: &'static str = "com.lokus.app.caldav";
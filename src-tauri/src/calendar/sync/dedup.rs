@@ -95,6 +95,7 @@ fn select_primary_event(
             match e.provider {
                 CalendarProvider::ICal => {},
                 CalendarProvider::Google => score += 50,
+                CalendarProvider::Outlook => score += 45,
                 CalendarProvider::CalDAV | CalendarProvider::ICloud => score += 40,
             }
 
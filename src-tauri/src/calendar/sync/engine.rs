@@ -16,6 +16,7 @@ use crate::calendar::models::{
 };
 use crate::calendar::storage::CalendarStorage;
 use crate::calendar::google::GoogleCalendarApi;
+use crate::calendar::outlook::OutlookCalendarApi;
 use crate::calendar::caldav::CalDAVClient;
 use super::fingerprint::compute_fingerprint;
 use super::storage::SyncStorage;
@@ -168,6 +169,18 @@ impl SyncEngine {
             }
         }
 
+        // Fetch from Outlook
+        if let Ok(api) = OutlookCalendarApi::new() {
+            for calendar in calendars.iter().filter(|c| c.provider == CalendarProvider::Outlook && c.visible) {
+                match api.get_events(&calendar.id, start, end).await {
+                    Ok(events) => all_events.extend(events),
+                    Err(e) => {
+                        tracing::warn!("Failed to fetch Outlook events from {}: {}", calendar.id, e);
+                    }
+                }
+            }
+        }
+
         // Fetch from CalDAV
         if let Ok(Some(account)) = CalendarStorage::get_caldav_account() {
             if let Ok(client) = CalDAVClient::new(account) {
@@ -431,6 +444,10 @@ impl SyncEngine {
                 let api = GoogleCalendarApi::new()?;
                 api.create_event(target_calendar_id, &request).await
             }
+            CalendarProvider::Outlook => {
+                let api = OutlookCalendarApi::new()?;
+                api.create_event(target_calendar_id, &request).await
+            }
             CalendarProvider::CalDAV | CalendarProvider::ICloud => {
                 let account = CalendarStorage::get_caldav_account()?
                     .ok_or_else(|| CalendarError::NotConnected)?;
@@ -489,6 +506,10 @@ impl SyncEngine {
                 let api = GoogleCalendarApi::new()?;
                 api.update_event(&loser.calendar_id, &loser.id, &updates).await?;
             }
+            CalendarProvider::Outlook => {
+                let api = OutlookCalendarApi::new()?;
+                api.update_event(&loser.calendar_id, &loser.id, &updates, loser.etag.as_deref()).await?;
+            }
             CalendarProvider::CalDAV | CalendarProvider::ICloud => {
                 let account = CalendarStorage::get_caldav_account()?
                     .ok_or_else(|| CalendarError::NotConnected)?;
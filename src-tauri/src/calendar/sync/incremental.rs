@@ -0,0 +1,365 @@
+//! Incremental background sync
+//!
+//! Fetches only the events that changed since the last run — Google sync
+//! tokens, Outlook delta links, CalDAV sync-collection, or a ctag check for
+//! CalDAV servers without it — and keeps them in the local event cache so calendars can be
+//! viewed offline. Each run reports how many events were added, updated and
+//! deleted through the `calendar-events-updated` event.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration as StdDuration;
+use chrono::{Duration, Utc};
+use tauri::{AppHandle, Emitter};
+use crate::calendar::caldav::CalDAVClient;
+use crate::calendar::google::GoogleCalendarApi;
+use crate::calendar::outlook::OutlookCalendarApi;
+use crate::calendar::models::{
+    Calendar, CalendarError, CalendarEvent, CalendarProvider, EventCache, EventChanges, SyncResult,
+};
+use crate::calendar::storage::CalendarStorage;
+use super::fingerprint::compute_fingerprint;
+use super::storage::SyncStorage;
+
+/// How often the scheduler checks whether a sync is due
+const TICK: StdDuration = StdDuration::from_secs(60);
+
+/// How far back a full listing reaches
+pub(crate) const PAST_WINDOW_DAYS: i64 = 30;
+
+/// How far ahead a full CalDAV or Outlook listing reaches
+const FUTURE_WINDOW_DAYS: i64 = 365;
+
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Whether a sync is running right now
+pub fn is_syncing() -> bool {
+    SYNC_RUNNING.load(Ordering::SeqCst)
+}
+
+/// Counts of what a sync changed in the cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    pub added: u32,
+    pub updated: u32,
+    pub deleted: u32,
+}
+
+fn has_changed(old: &CalendarEvent, new: &CalendarEvent) -> bool {
+    if let (Some(a), Some(b)) = (&old.etag, &new.etag) {
+        return a != b;
+    }
+    old.updated_at != new.updated_at || compute_fingerprint(old) != compute_fingerprint(new)
+}
+
+/// Apply provider changes to the cached events and count what changed
+pub fn apply_changes(events: &mut Vec<CalendarEvent>, changes: EventChanges) -> ChangeCounts {
+    let mut counts = ChangeCounts::default();
+
+    if changes.full {
+        // A complete listing: anything missing from it is gone
+        let before = events.len();
+        events.retain(|old| changes.changed.iter().any(|new| new.id == old.id));
+        counts.deleted = (before - events.len()) as u32;
+    } else {
+        for id in &changes.deleted {
+            let before = events.len();
+            events.retain(|e| &e.id != id);
+            counts.deleted += (before - events.len()) as u32;
+        }
+    }
+
+    for new in changes.changed {
+        match events.iter_mut().find(|e| e.id == new.id) {
+            Some(old) => {
+                if has_changed(old, &new) {
+                    counts.updated += 1;
+                }
+                *old = new;
+            }
+            None => {
+                counts.added += 1;
+                events.push(new);
+            }
+        }
+    }
+
+    events.sort_by(|a, b| a.start.cmp(&b.start));
+    counts
+}
+
+async fn fetch_google_changes(calendar: &Calendar, cache: &EventCache) -> Result<EventChanges, CalendarError> {
+    let api = GoogleCalendarApi::new()?;
+    let time_min = Utc::now() - Duration::days(PAST_WINDOW_DAYS);
+
+    let mut changes = match api.sync_events(&calendar.id, cache.sync_token.as_deref(), time_min).await {
+        Err(CalendarError::SyncTokenExpired) => api.sync_events(&calendar.id, None, time_min).await?,
+        other => other?,
+    };
+    if cache.sync_token.is_none() || changes.sync_token.is_none() {
+        changes.full = true;
+    }
+    Ok(changes)
+}
+
+async fn fetch_outlook_changes(calendar: &Calendar, cache: &EventCache) -> Result<EventChanges, CalendarError> {
+    let api = OutlookCalendarApi::new()?;
+    let now = Utc::now();
+    let (time_min, time_max) = (now - Duration::days(PAST_WINDOW_DAYS), now + Duration::days(FUTURE_WINDOW_DAYS));
+
+    let mut changes = match api.sync_events(&calendar.id, cache.sync_token.as_deref(), time_min, time_max).await {
+        Err(CalendarError::SyncTokenExpired) => api.sync_events(&calendar.id, None, time_min, time_max).await?,
+        other => other?,
+    };
+    if cache.sync_token.is_none() || changes.sync_token.is_none() {
+        changes.full = true;
+    }
+    Ok(changes)
+}
+
+async fn fetch_caldav_changes(calendar: &Calendar, cache: &mut EventCache) -> Result<EventChanges, CalendarError> {
+    let account = CalendarStorage::get_caldav_account()?
+        .ok_or(CalendarError::NotConnected)?;
+    let client = CalDAVClient::new(account)?;
+
+    // An unchanged ctag means nothing in the calendar changed
+    let ctag = client.get_ctag(&calendar.id).await.unwrap_or(None);
+    if ctag.is_some() && ctag == cache.ctag && cache.last_synced.is_some() {
+        return Ok(EventChanges::default());
+    }
+
+    let synced = match client.sync_events(&calendar.id, cache.sync_token.as_deref()).await {
+        Err(CalendarError::SyncTokenExpired) => client.sync_events(&calendar.id, None).await,
+        other => other,
+    };
+
+    let changes = match synced {
+        Ok(mut changes) => {
+            if cache.sync_token.is_none() {
+                changes.full = true;
+            }
+            changes
+        }
+        // No sync-collection support; list everything in the window instead
+        Err(CalendarError::Api(_)) => {
+            let now = Utc::now();
+            let events = client
+                .get_events(&calendar.id, now - Duration::days(PAST_WINDOW_DAYS), now + Duration::days(FUTURE_WINDOW_DAYS))
+                .await?;
+            EventChanges { changed: events, full: true, ..Default::default() }
+        }
+        Err(e) => return Err(e),
+    };
+
+    cache.ctag = ctag;
+    Ok(changes)
+}
+
+/// Bring the cache of one calendar up to date
+async fn sync_calendar(calendar: &Calendar) -> Result<ChangeCounts, CalendarError> {
+    let mut cache = SyncStorage::get_event_cache(&calendar.id)?;
+
+    let mut changes = match calendar.provider {
+        CalendarProvider::Google => fetch_google_changes(calendar, &cache).await?,
+        CalendarProvider::Outlook => fetch_outlook_changes(calendar, &cache).await?,
+        CalendarProvider::CalDAV | CalendarProvider::ICloud => fetch_caldav_changes(calendar, &mut cache).await?,
+        // Subscriptions are refreshed and stored by the iCal commands
+        CalendarProvider::ICal => return Ok(ChangeCounts::default()),
+    };
+
+    if changes.sync_token.is_some() {
+        cache.sync_token = changes.sync_token.clone();
+    }
+
+    // Keep changes made offline that haven't reached the provider yet
+    let pending: Vec<_> = CalendarStorage::get_pending_operations()?
+        .into_iter()
+        .filter(|o| o.calendar_id == calendar.id)
+        .collect();
+    crate::calendar::offline::overlay(&mut changes.changed, &pending, calendar.provider);
+    let counts = apply_changes(&mut cache.events, changes);
+    cache.last_synced = Some(Utc::now());
+    SyncStorage::store_event_cache(&cache)?;
+
+    Ok(counts)
+}
+
+/// Sync every visible calendar and emit `calendar-events-updated` when
+/// anything changed
+pub async fn run_sync(app_handle: &AppHandle) -> Result<SyncResult, String> {
+    if SYNC_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("A calendar sync is already in progress".to_string());
+    }
+    let result = sync_all(app_handle).await;
+    SYNC_RUNNING.store(false, Ordering::SeqCst);
+
+    let result = result?;
+    if result.events_added + result.events_updated + result.events_deleted > 0 {
+        let _ = app_handle.emit("calendar-events-updated", &result);
+    }
+    // Scheduled tasks follow their events
+    if let Err(e) = crate::calendar::timeblocking::reconcile_links(app_handle) {
+        tracing::warn!("Failed to update scheduled tasks: {}", e);
+    }
+    Ok(result)
+}
+
+async fn sync_all(app_handle: &AppHandle) -> Result<SyncResult, String> {
+    // Push offline changes first so the fetch below reflects them
+    if let Err(e) = crate::calendar::offline::replay(app_handle).await {
+        tracing::warn!("Failed to replay pending calendar changes: {}", e);
+    }
+
+    let mut state = SyncStorage::get_sync_state().map_err(|e| e.to_string())?;
+    state.sync_in_progress = true;
+    SyncStorage::store_sync_state(&state).map_err(|e| e.to_string())?;
+
+    // Pick up new and removed calendars; offline, sync what we know about
+    let calendars = match crate::calendar::commands::get_calendars().await {
+        Ok(calendars) => calendars,
+        Err(e) => {
+            tracing::warn!("Failed to refresh calendar list: {}", e);
+            CalendarStorage::get_calendars().map_err(|e| e.to_string())?
+        }
+    };
+
+    let mut result = SyncResult {
+        success: true,
+        events_added: 0,
+        events_updated: 0,
+        events_deleted: 0,
+        errors: Vec::new(),
+        synced_at: Utc::now(),
+    };
+
+    for calendar in calendars.iter().filter(|c| c.visible) {
+        match sync_calendar(calendar).await {
+            Ok(counts) => {
+                result.events_added += counts.added;
+                result.events_updated += counts.updated;
+                result.events_deleted += counts.deleted;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to sync calendar {}: {}", calendar.id, e);
+                result.errors.push(format!("{}: {}", calendar.name, e));
+            }
+        }
+    }
+
+    result.success = result.errors.is_empty();
+    result.synced_at = Utc::now();
+
+    state.sync_in_progress = false;
+    state.last_incremental_sync = Some(result.synced_at);
+    state.last_error = if result.errors.is_empty() {
+        None
+    } else {
+        Some(result.errors.join("; "))
+    };
+    SyncStorage::store_sync_state(&state).map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+fn has_pending_operations() -> bool {
+    CalendarStorage::get_pending_operations()
+        .map(|operations| !operations.is_empty())
+        .unwrap_or(false)
+}
+
+fn has_remote_calendars() -> bool {
+    CalendarStorage::get_calendars()
+        .map(|calendars| calendars.iter().any(|c| c.provider != CalendarProvider::ICal))
+        .unwrap_or(false)
+}
+
+/// Run incremental syncs on the configured interval for the lifetime of the app
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            if crate::diagnostics::background_paused() {
+                continue;
+            }
+
+            let Ok(config) = SyncStorage::get_sync_config() else {
+                continue;
+            };
+            if !config.enabled || config.auto_sync_interval_minutes == 0 || !has_remote_calendars() {
+                continue;
+            }
+
+            let interval = Duration::minutes(config.auto_sync_interval_minutes as i64);
+            let due = SyncStorage::get_sync_state()
+                .map(|state| state.last_incremental_sync.map_or(true, |last| Utc::now() - last >= interval))
+                .unwrap_or(true);
+            if is_syncing() {
+                continue;
+            }
+
+            if due {
+                if let Err(e) = run_sync(&app_handle).await {
+                    tracing::warn!("Background calendar sync failed: {}", e);
+                }
+            } else if has_pending_operations() {
+                // Push offline changes as soon as the provider is back
+                if let Err(e) = crate::calendar::offline::replay(&app_handle).await {
+                    tracing::warn!("Failed to replay pending calendar changes: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::models::EventStatus;
+
+    fn event(id: &str, title: &str, etag: &str) -> CalendarEvent {
+        let start = Utc::now();
+        CalendarEvent {
+            id: id.to_string(),
+            calendar_id: "primary".to_string(),
+            provider: CalendarProvider::Google,
+            title: title.to_string(),
+            description: None,
+            start,
+            end: start + Duration::hours(1),
+            all_day: false,
+            location: None,
+            attendees: Vec::new(),
+            recurrence_rule: None,
+            status: EventStatus::Confirmed,
+            created_at: None,
+            updated_at: None,
+            etag: Some(etag.to_string()),
+            html_link: None,
+            color_id: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_changes_counts() {
+        let mut events = vec![event("a", "Standup", "1"), event("b", "Review", "1"), event("c", "Lunch", "1")];
+
+        let incremental = EventChanges {
+            changed: vec![event("a", "Standup (moved)", "2"), event("d", "Retro", "1")],
+            deleted: vec!["b".to_string(), "unknown".to_string()],
+            ..Default::default()
+        };
+        let counts = apply_changes(&mut events, incremental);
+        assert_eq!(counts, ChangeCounts { added: 1, updated: 1, deleted: 1 });
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().any(|e| e.title == "Standup (moved)"));
+
+        // A full listing drops whatever it doesn't mention; unchanged etags aren't updates
+        let full = EventChanges {
+            changed: vec![event("a", "Standup (moved)", "2"), event("d", "Retro", "1")],
+            full: true,
+            ..Default::default()
+        };
+        let counts = apply_changes(&mut events, full);
+        assert_eq!(counts, ChangeCounts { added: 0, updated: 0, deleted: 1 });
+        assert_eq!(events.len(), 2);
+    }
+}
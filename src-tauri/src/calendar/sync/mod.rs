@@ -5,11 +5,13 @@
 //! - Conflict resolution (last-modified wins)
 //! - Deduplication in display
 //! - Read-only handling for iCal subscriptions
+//! - Incremental background sync into a local event cache
 
 pub mod fingerprint;
 pub mod storage;
 pub mod dedup;
 pub mod engine;
+pub mod incremental;
 
 pub use fingerprint::*;
 pub use storage::SyncStorage;
@@ -4,11 +4,12 @@
 //! - Event mappings (fingerprint -> event IDs across providers)
 //! - Sync state (last sync times, pending changes)
 //! - Sync configuration (enabled pairs, conflict resolution)
+//! - Event cache (per-calendar events and sync tokens for offline viewing)
 
 use std::path::PathBuf;
 use std::collections::HashMap;
 use crate::calendar::models::{
-    CalendarError, EventMapping, SyncState, SyncConfig,
+    CalendarError, CalendarEvent, EventCache, EventMapping, SyncState, SyncConfig,
 };
 
 /// Storage path: ~/.lokus/calendar/sync/
@@ -50,6 +51,16 @@ impl SyncStorage {
         Ok(base.join("fingerprint_index.json"))
     }
 
+    fn get_event_cache_path(calendar_id: &str) -> Result<PathBuf, CalendarError> {
+        let dir = Self::get_sync_base_path()?.join("events");
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| CalendarError::Storage(format!("Failed to create events directory: {}", e)))?;
+        }
+        // Calendar ids are emails or URL paths; hash them into file names
+        Ok(dir.join(format!("{}.json", blake3::hash(calendar_id.as_bytes()).to_hex())))
+    }
+
     // ============== Event Mappings ==============
 
     /// Store all event mappings
@@ -223,6 +234,67 @@ impl SyncStorage {
         Ok(config)
     }
 
+    // ============== Event Cache ==============
+
+    /// Store the cached events of one calendar
+    pub fn store_event_cache(cache: &EventCache) -> Result<(), CalendarError> {
+        let path = Self::get_event_cache_path(&cache.calendar_id)?;
+        let json = serde_json::to_string(cache)
+            .map_err(|e| CalendarError::Storage(format!("Failed to serialize event cache: {}", e)))?;
+
+        std::fs::write(&path, json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to write event cache: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Get the cached events of one calendar (empty if never synced)
+    pub fn get_event_cache(calendar_id: &str) -> Result<EventCache, CalendarError> {
+        let path = Self::get_event_cache_path(calendar_id)?;
+
+        if !path.exists() {
+            return Ok(EventCache { calendar_id: calendar_id.to_string(), ..Default::default() });
+        }
+
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| CalendarError::Storage(format!("Failed to read event cache: {}", e)))?;
+
+        let cache: EventCache = serde_json::from_str(&json)
+            .map_err(|e| CalendarError::Storage(format!("Failed to deserialize event cache: {}", e)))?;
+
+        Ok(cache)
+    }
+
+    /// Get every cached event, across calendars
+    pub fn get_all_cached_events() -> Result<Vec<CalendarEvent>, CalendarError> {
+        let dir = Self::get_sync_base_path()?.join("events");
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return Ok(Vec::new());
+        };
+
+        let mut events = Vec::new();
+        for entry in entries.flatten() {
+            let cache = std::fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|json| serde_json::from_str::<EventCache>(&json).ok());
+            if let Some(cache) = cache {
+                events.extend(cache.events);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Delete the cached events of one calendar
+    pub fn delete_event_cache(calendar_id: &str) -> Result<(), CalendarError> {
+        let path = Self::get_event_cache_path(calendar_id)?;
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| CalendarError::Storage(format!("Failed to delete event cache: {}", e)))?;
+        }
+        Ok(())
+    }
+
     // ============== Utility ==============
 
     /// Clear all sync data (mappings, state, but keep config)
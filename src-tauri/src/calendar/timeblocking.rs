@@ -0,0 +1,243 @@
+//! Time-blocking tasks onto calendars.
+//!
+//! Scheduling a task creates a calendar event for it and a schedule block on
+//! the task, remembered as a `TaskEventLink` in calendar storage. After each
+//! sync the links are checked against the event cache: when the event was
+//! moved in the calendar the block follows it, and when it was deleted the
+//! block and link go away, leaving the task unscheduled again.
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use crate::calendar::models::{CreateEventRequest, EventCache, TaskEventLink};
+use crate::calendar::storage::CalendarStorage;
+use crate::calendar::sync::incremental::PAST_WINDOW_DAYS;
+use crate::calendar::sync::storage::SyncStorage;
+use crate::errors::LokusError;
+use crate::schedule_blocks::{get_schedule_block_store, save_schedule_block_store, ScheduleBlock};
+use crate::tasks::{get_task_store, Task};
+
+/// What reconciling a link against the calendar did to it
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LinkChange {
+    Moved { link: TaskEventLink },
+    Removed { link: TaskEventLink },
+}
+
+fn block_time(time: DateTime<Utc>) -> String {
+    // Same shape as the frontend's `toISOString`, so blocks compare as strings
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Follow the linked events in `caches`, updating moved links in place and
+/// dropping the ones whose event is gone
+pub fn reconcile(links: &mut Vec<TaskEventLink>, caches: &[EventCache], now: DateTime<Utc>) -> Vec<LinkChange> {
+    let mut changes = Vec::new();
+    // A full listing doesn't reach further back than this, so older events
+    // missing from the cache may still exist
+    let horizon = now - Duration::days(PAST_WINDOW_DAYS);
+
+    links.retain_mut(|link| {
+        let Some(cache) = caches.iter().find(|c| c.calendar_id == link.calendar_id && c.last_synced.is_some()) else {
+            return true;
+        };
+        match cache.events.iter().find(|e| e.id == link.event_id) {
+            Some(event) => {
+                if event.start != link.start || event.end != link.end {
+                    link.start = event.start;
+                    link.end = event.end;
+                    changes.push(LinkChange::Moved { link: link.clone() });
+                }
+                true
+            }
+            None if link.end > horizon => {
+                changes.push(LinkChange::Removed { link: link.clone() });
+                false
+            }
+            None => true,
+        }
+    });
+    changes
+}
+
+/// Bring task links and their schedule blocks in line with the event cache,
+/// emitting `task-schedule-changed` when anything moved or was removed
+pub fn reconcile_links(app_handle: &AppHandle) -> Result<Vec<LinkChange>, String> {
+    let mut links = CalendarStorage::get_task_links().map_err(|e| e.to_string())?;
+    if links.is_empty() {
+        return Ok(Vec::new());
+    }
+    let caches = SyncStorage::get_all_cached_events().map_err(|e| e.to_string())?;
+    let changes = reconcile(&mut links, &caches, Utc::now());
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+    CalendarStorage::store_task_links(&links).map_err(|e| e.to_string())?;
+
+    let mut blocks = get_schedule_block_store(app_handle)?;
+    for change in &changes {
+        match change {
+            LinkChange::Moved { link } => {
+                let Some(mut block) = link.block_id.as_deref().and_then(|id| blocks.get_block(id)).cloned() else {
+                    continue;
+                };
+                block.start = block_time(link.start);
+                block.end = block_time(link.end);
+                block.updated_at = Utc::now().timestamp_millis();
+                blocks.update_block(&block.id.clone(), block)?;
+            }
+            LinkChange::Removed { link } => {
+                if let Some(block_id) = &link.block_id {
+                    let _ = blocks.delete_block(block_id);
+                }
+            }
+        }
+    }
+    save_schedule_block_store(app_handle, &blocks)?;
+
+    let _ = app_handle.emit("task-schedule-changed", &changes);
+    Ok(changes)
+}
+
+/// Point links at the id an event created offline got from its provider
+pub fn rename_event(local_id: &str, event_id: &str) -> Result<(), String> {
+    let mut links = CalendarStorage::get_task_links().map_err(|e| e.to_string())?;
+    if !links.iter().any(|l| l.event_id == local_id) {
+        return Ok(());
+    }
+    for link in links.iter_mut().filter(|l| l.event_id == local_id) {
+        link.event_id = event_id.to_string();
+    }
+    CalendarStorage::store_task_links(&links).map_err(|e| e.to_string())
+}
+
+/// Block out `duration` minutes for a task: creates an event titled after the
+/// task in `calendar_id` and a schedule block on the task that follows it
+#[tauri::command]
+pub async fn calendar_schedule_task(
+    app: AppHandle,
+    task_id: String,
+    calendar_id: String,
+    start: DateTime<Utc>,
+    duration: u32,
+) -> Result<TaskEventLink, LokusError> {
+    if duration == 0 {
+        return Err(LokusError::invalid("Duration must be at least a minute"));
+    }
+    let task = get_task_store(&app)?
+        .get_task(&task_id)
+        .cloned()
+        .ok_or_else(|| format!("Task with id {} not found", task_id))?;
+    let end = start + Duration::minutes(duration as i64);
+
+    let request = CreateEventRequest {
+        title: task.title.clone(),
+        description: task.description.clone(),
+        start,
+        end,
+        all_day: false,
+        location: None,
+        attendees: None,
+        recurrence_rule: None,
+    };
+    let event = crate::calendar::commands::create_event(calendar_id.clone(), request).await?;
+
+    let block = ScheduleBlock::new(task.id.clone(), block_time(event.start), block_time(event.end));
+    let mut blocks = get_schedule_block_store(&app)?;
+    blocks.add_block(block.clone());
+    save_schedule_block_store(&app, &blocks)?;
+
+    let link = TaskEventLink {
+        task_id: task.id,
+        event_id: event.id,
+        calendar_id,
+        block_id: Some(block.id),
+        start: event.start,
+        end: event.end,
+        linked_at: Utc::now(),
+    };
+    let mut links = CalendarStorage::get_task_links()?;
+    links.push(link.clone());
+    CalendarStorage::store_task_links(&links)?;
+
+    Ok(link)
+}
+
+/// Open tasks due within `start..=end` that have no calendar event yet
+#[tauri::command]
+pub async fn calendar_get_unscheduled_tasks(app: AppHandle, start: String, end: String) -> Result<Vec<Task>, LokusError> {
+    let links = CalendarStorage::get_task_links()?;
+    let upcoming = crate::tasks::tasks_get_upcoming(app, start, end).await?;
+    Ok(upcoming
+        .into_iter()
+        .filter(|task| !links.iter().any(|l| l.task_id == task.id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::models::{CalendarEvent, CalendarProvider, EventStatus};
+
+    fn event(id: &str, start: DateTime<Utc>) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            calendar_id: "work".to_string(),
+            provider: CalendarProvider::Google,
+            title: "Write report".to_string(),
+            description: None,
+            start,
+            end: start + Duration::hours(1),
+            all_day: false,
+            location: None,
+            attendees: Vec::new(),
+            recurrence_rule: None,
+            status: EventStatus::Confirmed,
+            created_at: None,
+            updated_at: None,
+            etag: None,
+            html_link: None,
+            color_id: None,
+        }
+    }
+
+    fn link(event_id: &str, start: DateTime<Utc>) -> TaskEventLink {
+        TaskEventLink {
+            task_id: format!("task-{}", event_id),
+            event_id: event_id.to_string(),
+            calendar_id: "work".to_string(),
+            block_id: None,
+            start,
+            end: start + Duration::hours(1),
+            linked_at: start,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_follows_moved_and_deleted_events() {
+        let now = Utc::now();
+        let moved_to = now + Duration::days(2);
+        let cache = EventCache {
+            calendar_id: "work".to_string(),
+            events: vec![event("kept", now), event("moved", moved_to)],
+            last_synced: Some(now),
+            ..Default::default()
+        };
+        let mut links = vec![
+            link("kept", now),
+            link("moved", now),
+            link("deleted", now),
+            // Too old to be in a full listing
+            link("archived", now - Duration::days(90)),
+        ];
+
+        let changes = reconcile(&mut links, &[cache], now);
+
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(&changes[0], LinkChange::Moved { link } if link.event_id == "moved" && link.start == moved_to));
+        assert!(matches!(&changes[1], LinkChange::Removed { link } if link.event_id == "deleted"));
+        let remaining: Vec<_> = links.iter().map(|l| l.event_id.as_str()).collect();
+        assert_eq!(remaining, ["kept", "moved", "archived"]);
+    }
+}
@@ -0,0 +1,342 @@
+//! Whiteboards stored as Obsidian-compatible `.canvas` files (JSON Canvas 1.0).
+//!
+//! Fields this module doesn't know about are kept through load/save so files
+//! written by Obsidian or plugins round-trip unchanged. File cards that embed
+//! notes count as links in the link graph, so canvases show up in backlinks.
+
+mod render;
+
+pub(crate) use render::svg_to_png;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::links::{LinkKind, RawLink};
+
+pub const CANVAS_EXTENSION: &str = "canvas";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Canvas {
+    #[serde(default)]
+    pub nodes: Vec<CanvasNode>,
+    #[serde(default)]
+    pub edges: Vec<CanvasEdge>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A card. `kind` is "text", "file", "link" or "group".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasNode {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    /// Preset "1"-"6" or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Vault-relative path of an embedded file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Heading or block inside `file`, e.g. `#Summary`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subpath: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Group title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasEdge {
+    pub id: String,
+    pub from_node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_side: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_end: Option<String>,
+    pub to_node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_side: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A card to add; position and size default to a text-card-sized slot to the
+/// right of the existing cards
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCanvasNode {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub x: Option<i64>,
+    pub y: Option<i64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub color: Option<String>,
+    pub text: Option<String>,
+    /// Absolute or workspace-relative path
+    pub file: Option<String>,
+    pub subpath: Option<String>,
+    pub url: Option<String>,
+    pub label: Option<String>,
+}
+
+pub fn is_canvas(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case(CANVAS_EXTENSION))
+}
+
+pub fn parse(content: &str) -> Result<Canvas, String> {
+    if content.trim().is_empty() {
+        return Ok(Canvas::default());
+    }
+    serde_json::from_str(content).map_err(|e| format!("Invalid canvas file: {}", e))
+}
+
+/// Serialize with tab indentation, as Obsidian writes canvases
+pub fn to_json(canvas: &Canvas) -> Result<String, String> {
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"\t");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    canvas
+        .serialize(&mut serializer)
+        .map_err(|e| format!("Failed to serialize canvas: {}", e))?;
+    String::from_utf8(out).map_err(|e| e.to_string())
+}
+
+fn validate(canvas: &Canvas) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for node in &canvas.nodes {
+        if !ids.insert(node.id.as_str()) {
+            return Err(format!("Duplicate canvas node id: {}", node.id));
+        }
+        if node.width <= 0 || node.height <= 0 {
+            return Err(format!("Canvas node {} has no size", node.id));
+        }
+    }
+    for edge in &canvas.edges {
+        if !ids.contains(edge.from_node.as_str()) || !ids.contains(edge.to_node.as_str()) {
+            return Err(format!("Canvas edge {} connects a missing node", edge.id));
+        }
+    }
+    Ok(())
+}
+
+/// 16 hex characters, like the ids Obsidian generates
+fn new_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// Links from a canvas for the link graph: each file card embedding a note,
+/// plus links written inside text cards. `line` is the card's position in
+/// the file.
+pub(crate) fn parse_links(content: &str) -> Vec<RawLink> {
+    let Ok(canvas) = parse(content) else {
+        return Vec::new();
+    };
+    let mut links = Vec::new();
+    for (index, node) in canvas.nodes.iter().enumerate() {
+        match (node.kind.as_str(), &node.file, &node.text) {
+            ("file", Some(file), _) if crate::links::is_note(file) => links.push(RawLink {
+                // Leading slash: file cards always hold vault paths
+                target: format!("/{}", file.trim_start_matches('/')),
+                kind: LinkKind::Embed,
+                line: index + 1,
+                context: format!("Canvas card: {}", file),
+            }),
+            ("text", _, Some(text)) => links.extend(crate::links::parse_links(text).into_iter().map(|link| RawLink {
+                line: index + 1,
+                ..link
+            })),
+            _ => {}
+        }
+    }
+    links
+}
+
+fn workspace_root(path: &Path) -> Result<PathBuf, String> {
+    crate::handlers::files::find_workspace_root(path.parent().unwrap_or(path))
+}
+
+fn read_canvas(path: &Path) -> Result<Canvas, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse(&content)
+}
+
+fn write_canvas(path: &Path, canvas: &Canvas) -> Result<(), String> {
+    crate::vaults::ensure_writable(path)?;
+    validate(canvas)?;
+    let json = to_json(canvas)?;
+    let temp = path.with_extension("canvas.tmp");
+    fs::write(&temp, &json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    crate::links::notify_file_saved(&path.to_string_lossy(), &json);
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+/// Create an empty canvas; `.canvas` is appended if missing
+#[tauri::command]
+pub fn canvas_create(path: String) -> Result<String, String> {
+    let mut path = PathBuf::from(path);
+    if !is_canvas(&path) {
+        let name = format!("{}.{}", path.file_name().map_or("Untitled".into(), |n| n.to_string_lossy()), CANVAS_EXTENSION);
+        path.set_file_name(name);
+    }
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    write_canvas(&path, &Canvas::default())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn canvas_load(path: String) -> Result<Canvas, String> {
+    read_canvas(Path::new(&path))
+}
+
+#[tauri::command]
+pub fn canvas_save(path: String, canvas: Canvas) -> Result<(), String> {
+    write_canvas(Path::new(&path), &canvas)
+}
+
+#[tauri::command]
+pub fn canvas_add_node(path: String, node: NewCanvasNode) -> Result<CanvasNode, String> {
+    let path = PathBuf::from(path);
+    let mut canvas = read_canvas(&path)?;
+
+    let file = match node.file {
+        Some(file) => {
+            let root = workspace_root(&path)?;
+            let file_path = Path::new(&file);
+            let relative = if file_path.is_absolute() {
+                crate::links::relative_path(&root, file_path)
+                    .ok_or_else(|| format!("{} is outside the workspace", file))?
+            } else {
+                file.trim_start_matches('/').replace('\\', "/")
+            };
+            Some(relative)
+        }
+        None if node.kind == "file" => return Err("File cards need a file".to_string()),
+        None => None,
+    };
+
+    let width = node.width.unwrap_or(400);
+    let height = node.height.unwrap_or(if file.is_some() { 400 } else { 200 });
+    // Default to a slot right of everything already on the board
+    let x = node
+        .x
+        .unwrap_or_else(|| canvas.nodes.iter().map(|n| n.x + n.width).max().map_or(0, |right| right + 40));
+    let y = node.y.unwrap_or_else(|| canvas.nodes.iter().map(|n| n.y).min().unwrap_or(0));
+
+    let created = CanvasNode {
+        id: new_id(),
+        kind: node.kind,
+        x,
+        y,
+        width,
+        height,
+        color: node.color,
+        text: node.text,
+        file,
+        subpath: node.subpath,
+        url: node.url,
+        label: node.label,
+        extra: Map::new(),
+    };
+    canvas.nodes.push(created.clone());
+    write_canvas(&path, &canvas)?;
+    Ok(created)
+}
+
+/// Render the canvas to `output_path` as SVG or PNG (chosen by `format`, or
+/// by the output extension). Returns the path written.
+#[tauri::command]
+pub async fn canvas_export_to_image(
+    path: String,
+    output_path: String,
+    format: Option<String>,
+    scale: Option<f32>,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let canvas_path = PathBuf::from(&path);
+        let canvas = read_canvas(&canvas_path)?;
+        let root = workspace_root(&canvas_path).ok();
+        let svg = render::to_svg(&canvas, root.as_deref());
+
+        let output = PathBuf::from(&output_path);
+        let format = format
+            .or_else(|| output.extension().map(|e| e.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "png".to_string());
+        let bytes = match format.as_str() {
+            "svg" => svg.into_bytes(),
+            "png" => render::svg_to_png(&svg, scale.unwrap_or(1.0))?,
+            other => return Err(format!("Unsupported image format: {}", other)),
+        };
+        fs::write(&output, bytes).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        Ok(output.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Canvas export failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r##"{
+	"nodes":[
+		{"id":"a1","type":"file","file":"Projects/Plan.md","x":0,"y":0,"width":400,"height":400},
+		{"id":"b2","type":"text","text":"See [[Ideas]]","x":500,"y":0,"width":250,"height":60,"color":"4","styleAttributes":{}}
+	],
+	"edges":[{"id":"e1","fromNode":"a1","fromSide":"right","toNode":"b2","toSide":"left"}]
+}"##;
+
+    #[test]
+    fn test_round_trip_keeps_unknown_fields() {
+        let canvas = parse(SAMPLE).unwrap();
+        assert_eq!(canvas.nodes.len(), 2);
+        assert_eq!(canvas.edges[0].from_node, "a1");
+        let json = to_json(&canvas).unwrap();
+        assert!(json.contains("\"styleAttributes\""));
+        assert!(json.contains("\n\t\"nodes\""));
+        assert!(validate(&canvas).is_ok());
+
+        let mut broken = canvas.clone();
+        broken.nodes.pop();
+        assert!(validate(&broken).is_err());
+    }
+
+    #[test]
+    fn test_parse_links_includes_file_cards_and_text() {
+        let links = parse_links(SAMPLE);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].target, "/Projects/Plan.md");
+        assert_eq!(links[0].kind, LinkKind::Embed);
+        assert_eq!(links[1].target, "Ideas");
+        assert_eq!(links[1].line, 2);
+    }
+}
@@ -0,0 +1,255 @@
+//! Static rendering of a canvas to SVG, and from there to PNG.
+//!
+//! This is an export, not the editor view: cards are drawn as rounded boxes
+//! with their text wrapped to fit, embedded notes show their opening lines,
+//! and edges are straight arrows between card sides.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use super::{Canvas, CanvasEdge, CanvasNode};
+
+const PADDING: i64 = 40;
+const FONT_SIZE: i64 = 14;
+const LINE_HEIGHT: i64 = 20;
+/// Rough average glyph width at `FONT_SIZE`, for wrapping
+const CHAR_WIDTH: i64 = 8;
+const CARD_INSET: i64 = 12;
+const DEFAULT_STROKE: &str = "#8a8a8a";
+/// Obsidian's preset colors "1" through "6"
+const PRESET_COLORS: [&str; 6] = ["#fb464c", "#e9973f", "#e0de71", "#44cf6e", "#53dfdd", "#a882ff"];
+
+fn resolve_color(color: Option<&str>) -> Option<&str> {
+    match color? {
+        preset @ ("1" | "2" | "3" | "4" | "5" | "6") => {
+            let index: usize = preset.parse().ok()?;
+            Some(PRESET_COLORS[index - 1])
+        }
+        hex if hex.starts_with('#') => Some(hex),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Greedy word wrap to at most `max_lines` lines of `width` characters
+fn wrap(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let width = width.max(4);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+        if lines.len() >= max_lines {
+            break;
+        }
+    }
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+    }
+    lines
+}
+
+/// Title and body text of a card
+fn card_text(node: &CanvasNode, root: Option<&Path>) -> (Option<String>, String) {
+    match node.kind.as_str() {
+        "file" => {
+            let file = node.file.clone().unwrap_or_default();
+            let name = file.rsplit('/').next().unwrap_or(&file).trim_end_matches(".md").to_string();
+            let body = root
+                .filter(|_| crate::links::is_note(&file))
+                .and_then(|root| fs::read_to_string(root.join(&file)).ok())
+                .map(|content| crate::export::strip_frontmatter(&content).to_string())
+                .unwrap_or_default();
+            (Some(name), body)
+        }
+        "link" => (Some("Link".to_string()), node.url.clone().unwrap_or_default()),
+        _ => (None, node.text.clone().unwrap_or_default()),
+    }
+}
+
+/// Point on `side` of the node, or the side facing `toward` if unspecified
+fn anchor(node: &CanvasNode, side: Option<&str>, toward: &CanvasNode) -> (i64, i64) {
+    let (cx, cy) = (node.x + node.width / 2, node.y + node.height / 2);
+    let side = side.unwrap_or_else(|| {
+        let (dx, dy) = (toward.x + toward.width / 2 - cx, toward.y + toward.height / 2 - cy);
+        match (dx.abs() >= dy.abs(), dx >= 0, dy >= 0) {
+            (true, true, _) => "right",
+            (true, false, _) => "left",
+            (false, _, true) => "bottom",
+            (false, _, false) => "top",
+        }
+    });
+    match side {
+        "top" => (cx, node.y),
+        "bottom" => (cx, node.y + node.height),
+        "left" => (node.x, cy),
+        _ => (node.x + node.width, cy),
+    }
+}
+
+fn draw_edge(svg: &mut String, edge: &CanvasEdge, from: &CanvasNode, to: &CanvasNode) {
+    let (x1, y1) = anchor(from, edge.from_side.as_deref(), to);
+    let (x2, y2) = anchor(to, edge.to_side.as_deref(), from);
+    let stroke = resolve_color(edge.color.as_deref()).unwrap_or(DEFAULT_STROKE);
+    // Per JSON Canvas, edges end in an arrow unless told otherwise
+    let marker_end = edge.to_end.as_deref().unwrap_or("arrow") == "arrow";
+    let marker_start = edge.from_end.as_deref() == Some("arrow");
+    let _ = write!(
+        svg,
+        r##"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{stroke}" stroke-width="2"{}{}/>"##,
+        if marker_end { r#" marker-end="url(#arrow)""# } else { "" },
+        if marker_start { r#" marker-start="url(#arrow)""# } else { "" },
+    );
+    if let Some(label) = edge.label.as_deref().filter(|l| !l.is_empty()) {
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{}" text-anchor="middle" font-size="{FONT_SIZE}" fill="#555">{}</text>"##,
+            (x1 + x2) / 2,
+            (y1 + y2) / 2 - 6,
+            escape(label)
+        );
+    }
+}
+
+fn draw_node(svg: &mut String, node: &CanvasNode, root: Option<&Path>) {
+    let color = resolve_color(node.color.as_deref());
+    let stroke = color.unwrap_or(DEFAULT_STROKE);
+    let (x, y, w, h) = (node.x, node.y, node.width, node.height);
+
+    if node.kind == "group" {
+        let _ = write!(
+            svg,
+            r##"<rect x="{x}" y="{y}" width="{w}" height="{h}" rx="12" fill="{}" fill-opacity="0.08" stroke="{stroke}" stroke-width="2"/>"##,
+            color.unwrap_or("#888888")
+        );
+        if let Some(label) = node.label.as_deref() {
+            let _ = write!(
+                svg,
+                r##"<text x="{x}" y="{}" font-size="{}" font-weight="bold" fill="#444">{}</text>"##,
+                y - 8,
+                FONT_SIZE + 2,
+                escape(label)
+            );
+        }
+        return;
+    }
+
+    let fill = color.map_or("#ffffff".to_string(), |c| c.to_string());
+    let _ = write!(
+        svg,
+        r##"<rect x="{x}" y="{y}" width="{w}" height="{h}" rx="8" fill="{fill}" fill-opacity="{}" stroke="{stroke}" stroke-width="2"/>"##,
+        if color.is_some() { "0.15" } else { "1" }
+    );
+
+    let (title, body) = card_text(node, root);
+    let chars_per_line = ((w - 2 * CARD_INSET) / CHAR_WIDTH).max(1) as usize;
+    let mut line_y = y + CARD_INSET + FONT_SIZE;
+    if let Some(title) = title {
+        let title: String = title.chars().take(chars_per_line).collect();
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{line_y}" font-size="{FONT_SIZE}" font-weight="bold" fill="#222">{}</text>"##,
+            x + CARD_INSET,
+            escape(&title)
+        );
+        line_y += LINE_HEIGHT + 4;
+    }
+    let max_lines = ((y + h - CARD_INSET - line_y) / LINE_HEIGHT + 1).max(0) as usize;
+    for line in wrap(&body, chars_per_line, max_lines) {
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{line_y}" font-size="{FONT_SIZE}" fill="#333">{}</text>"##,
+            x + CARD_INSET,
+            escape(&line)
+        );
+        line_y += LINE_HEIGHT;
+    }
+}
+
+pub(super) fn to_svg(canvas: &Canvas, root: Option<&Path>) -> String {
+    let (min_x, min_y, max_x, max_y) = if canvas.nodes.is_empty() {
+        (0, 0, 400, 300)
+    } else {
+        canvas.nodes.iter().fold((i64::MAX, i64::MAX, i64::MIN, i64::MIN), |(a, b, c, d), n| {
+            // Group labels sit above the box
+            let top = if n.kind == "group" { n.y - LINE_HEIGHT - 8 } else { n.y };
+            (a.min(n.x), b.min(top), c.max(n.x + n.width), d.max(n.y + n.height))
+        })
+    };
+    let (width, height) = (max_x - min_x + 2 * PADDING, max_y - min_y + 2 * PADDING);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="{} {} {width} {height}" font-family="Helvetica, Arial, sans-serif">"##,
+        min_x - PADDING,
+        min_y - PADDING
+    );
+    svg.push_str(r##"<defs><marker id="arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse"><path d="M0,0 L10,5 L0,10 z" fill="context-stroke"/></marker></defs>"##);
+    let _ = write!(
+        svg,
+        r##"<rect x="{}" y="{}" width="{width}" height="{height}" fill="#fafafa"/>"##,
+        min_x - PADDING,
+        min_y - PADDING
+    );
+
+    // Groups underneath, then edges, then cards on top
+    for node in canvas.nodes.iter().filter(|n| n.kind == "group") {
+        draw_node(&mut svg, node, root);
+    }
+    for edge in &canvas.edges {
+        let from = canvas.nodes.iter().find(|n| n.id == edge.from_node);
+        let to = canvas.nodes.iter().find(|n| n.id == edge.to_node);
+        if let (Some(from), Some(to)) = (from, to) {
+            draw_edge(&mut svg, edge, from, to);
+        }
+    }
+    for node in canvas.nodes.iter().filter(|n| n.kind != "group") {
+        draw_node(&mut svg, node, root);
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Rasterize an SVG document; also used for diagram previews
+pub(crate) fn svg_to_png(svg: &str, scale: f32) -> Result<Vec<u8>, String> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| format!("Failed to render image: {}", e))?;
+    let scale = scale.clamp(0.1, 8.0);
+    let size = tree.size().to_int_size().scale_by(scale).ok_or("Image is too large to render")?;
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("Image is too large to render")?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| format!("Failed to encode PNG: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_colors() {
+        assert_eq!(wrap("one two three four", 9, 10), vec!["one two", "three", "four"]);
+        assert_eq!(wrap("a\nb\nc", 10, 2), vec!["a", "b"]);
+        assert_eq!(resolve_color(Some("4")), Some("#44cf6e"));
+        assert_eq!(resolve_color(Some("#123456")), Some("#123456"));
+        assert_eq!(resolve_color(Some("9")), None);
+    }
+}
@@ -0,0 +1,269 @@
+//! Quick capture: a global shortcut opens a small capture window from
+//! anywhere, and `capture_append` adds the text to the vault's inbox note or
+//! today's daily note. Appending only needs a vault path, so it works while
+//! no workspace window is open.
+
+use chrono::{Local, NaiveDateTime};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Settings key holding a `CaptureConfig`
+const SETTINGS_KEY: &str = "quick_capture";
+const WINDOW_LABEL: &str = "capture";
+
+/// The shortcut currently registered for capture
+static REGISTERED: Lazy<Mutex<Option<Shortcut>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureTarget {
+    #[default]
+    Inbox,
+    Daily,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CaptureConfig {
+    pub enabled: bool,
+    pub shortcut: String,
+    /// Inbox note, relative to the vault root
+    pub inbox: String,
+    pub default_target: CaptureTarget,
+    /// Prefix each entry with the time it was captured
+    pub timestamp: bool,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shortcut: "CommandOrControl+Shift+Space".to_string(),
+            inbox: "Inbox.md".to_string(),
+            default_target: CaptureTarget::Inbox,
+            timestamp: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureResult {
+    pub path: String,
+    pub target: CaptureTarget,
+    /// The note didn't exist before this capture
+    pub created: bool,
+}
+
+fn config(app: &AppHandle, workspace: Option<&Path>) -> CaptureConfig {
+    crate::settings::effective(app, workspace, SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// `text` as a Markdown list item; continuation lines are indented under it
+fn format_entry(text: &str, now: Option<NaiveDateTime>) -> String {
+    let mut lines = text.trim().lines();
+    let first = lines.next().unwrap_or_default();
+    let mut entry = match now {
+        Some(now) => format!("- {} {}", now.format("%H:%M"), first),
+        None => format!("- {}", first),
+    };
+    for line in lines {
+        entry.push_str("\n  ");
+        entry.push_str(line);
+    }
+    entry.push('\n');
+    entry
+}
+
+/// The note `target` appends to, and what a new one starts with
+fn target_note(app: &AppHandle, root: &Path, config: &CaptureConfig, target: CaptureTarget) -> Result<(PathBuf, String), String> {
+    match target {
+        CaptureTarget::Inbox => {
+            let inbox = config.inbox.trim().trim_start_matches('/');
+            if inbox.is_empty() || Path::new(inbox).components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+                return Err(format!("Invalid inbox note '{}'", config.inbox));
+            }
+            let path = root.join(inbox);
+            let path = if path.extension().is_none() { path.with_extension("md") } else { path };
+            let title = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            Ok((path, format!("# {}\n\n", title)))
+        }
+        CaptureTarget::Daily => {
+            let daily = crate::daily_notes::load_config(app);
+            let today = Local::now().date_naive();
+            let (content, _) = crate::daily_notes::build_content(root, &daily, today, None);
+            Ok((crate::daily_notes::note_path(root, &daily, today), content))
+        }
+    }
+}
+
+/// Append `entry` to the note at `path`, starting it with `initial` if it
+/// doesn't exist yet. Returns whether the note was created.
+fn append_to(path: &Path, initial: &str, entry: &str) -> Result<bool, String> {
+    let path_str = path.to_string_lossy().to_string();
+    let (mut content, created) = match fs::read_to_string(path) {
+        Ok(existing) => (crate::encryption::decrypt_for_read(&path_str, existing)?, false),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (initial.to_string(), true),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(entry);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    crate::handlers::files::write_file_content(path_str, content)?;
+    Ok(created)
+}
+
+fn open_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = win.show();
+        let _ = win.set_focus();
+        return Ok(());
+    }
+    let url = WebviewUrl::App("index.html?view=capture".into());
+    WebviewWindowBuilder::new(app, WINDOW_LABEL, url)
+        .title("Quick Capture")
+        .inner_size(560.0, 200.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Make `shortcut` open the capture window, replacing the previous one
+fn register(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let parsed: Shortcut = shortcut.parse().map_err(|e| format!("Invalid shortcut '{}': {}", shortcut, e))?;
+    let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(previous) = registered.take() {
+        let _ = app.global_shortcut().unregister(previous);
+    }
+    app.global_shortcut()
+        .on_shortcut(parsed, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = open_window(app) {
+                    eprintln!("[Capture] Failed to open capture window: {}", e);
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to register shortcut '{}': {}", shortcut, e))?;
+    *registered = Some(parsed);
+    Ok(())
+}
+
+fn unregister(app: &AppHandle) {
+    if let Some(previous) = REGISTERED.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = app.global_shortcut().unregister(previous);
+    }
+}
+
+/// Register the capture shortcut from settings
+pub fn start(app: AppHandle) {
+    let config = config(&app, None);
+    if !config.enabled {
+        return;
+    }
+    if let Err(e) = register(&app, &config.shortcut) {
+        eprintln!("[Capture] {}", e);
+    }
+}
+
+/// Append `text` as an entry of the vault's inbox or daily note (the
+/// configured default unless `target` is given)
+pub(crate) fn append(app: &AppHandle, root: &Path, text: &str, target: Option<CaptureTarget>) -> Result<CaptureResult, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to capture".to_string());
+    }
+    let config = config(app, Some(root));
+    let target = target.unwrap_or(config.default_target);
+    let (path, initial) = target_note(app, root, &config, target)?;
+    let now = config.timestamp.then(|| Local::now().naive_local());
+    let created = append_to(&path, &initial, &format_entry(text, now))?;
+
+    let result = CaptureResult { path: path.to_string_lossy().to_string(), target, created };
+    let _ = app.emit("capture:appended", &result);
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    crate::notifications::send_notification(app, "Captured", &format!("Added to {}", name));
+    Ok(result)
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn open_capture_window(app: AppHandle) -> Result<(), String> {
+    open_window(&app)
+}
+
+/// Append `text` to the inbox or daily note of `workspace` (the last opened
+/// vault by default), creating the note if needed
+#[tauri::command]
+pub fn capture_append(
+    app: AppHandle,
+    text: String,
+    target: Option<CaptureTarget>,
+    workspace: Option<String>,
+) -> Result<CaptureResult, String> {
+    let root = workspace
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(&app))
+        .filter(|root| root.is_dir())
+        .ok_or_else(|| "No vault to capture into".to_string())?;
+    append(&app, &root, &text, target)
+}
+
+/// Change the capture shortcut; `None` turns the shortcut off
+#[tauri::command]
+pub fn capture_set_shortcut(app: AppHandle, shortcut: Option<String>) -> Result<(), String> {
+    let mut config = config(&app, None);
+    match shortcut.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(shortcut) => {
+            register(&app, shortcut)?;
+            config.enabled = true;
+            config.shortcut = shortcut.to_string();
+        }
+        None => {
+            unregister(&app);
+            config.enabled = false;
+        }
+    }
+    let value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    crate::settings::settings_set(app, crate::settings::SettingsScope::Global, SETTINGS_KEY.to_string(), value, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_entry() {
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 5, 4).unwrap().and_hms_opt(9, 5, 0).unwrap();
+        assert_eq!(format_entry("  call Sam \n", Some(at)), "- 09:05 call Sam\n");
+        assert_eq!(format_entry("idea\nmore detail", None), "- idea\n  more detail\n");
+    }
+
+    #[test]
+    fn test_append_creates_then_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Inbox").join("Inbox.md");
+
+        assert!(append_to(&path, "# Inbox\n\n", "- first\n").unwrap());
+        fs::write(&path, fs::read_to_string(&path).unwrap().trim_end()).unwrap();
+        assert!(!append_to(&path, "# Inbox\n\n", "- second\n").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Inbox\n\n- first\n- second\n");
+    }
+}
@@ -0,0 +1,429 @@
+//! BibTeX and BibLaTeX files.
+//!
+//! Handles the subset reference managers export: `@string` macros, `#`
+//! concatenation, braced and quoted values and the common LaTeX accents and
+//! escapes. `@comment` and `@preamble` are skipped, as is anything that
+//! doesn't parse, so one broken entry doesn't hide the rest.
+
+use super::{Name, Reference};
+use std::collections::HashMap;
+
+/// Accent commands and the letters they combine with, as (from, to) pairs
+const ACCENTS: &[(&str, &str, &str)] = &[
+    ("\"", "aeiouyAEIOUY", "äëïöüÿÄËÏÖÜŸ"),
+    ("'", "aeiouyncszAEIOUYNCSZ", "áéíóúýńćśźÁÉÍÓÚÝŃĆŚŹ"),
+    ("`", "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ("^", "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+    ("~", "anoANO", "ãñõÃÑÕ"),
+    ("=", "aeiouAEIOU", "āēīōūĀĒĪŌŪ"),
+    (".", "zZ", "żŻ"),
+    ("c", "csCS", "çşÇŞ"),
+    ("v", "cszrneCSZRNE", "čšžřňěČŠŽŘŇĚ"),
+    ("u", "agAG", "ăğĂĞ"),
+    ("H", "ouOU", "őűŐŰ"),
+];
+
+/// Letter-like commands, e.g. `{\ss}`
+const SYMBOLS: &[(&str, &str)] = &[
+    ("ss", "ß"),
+    ("o", "ø"),
+    ("O", "Ø"),
+    ("ae", "æ"),
+    ("AE", "Æ"),
+    ("oe", "œ"),
+    ("OE", "Œ"),
+    ("aa", "å"),
+    ("AA", "Å"),
+    ("l", "ł"),
+    ("L", "Ł"),
+    ("i", "i"),
+    ("j", "j"),
+];
+
+const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+fn accented(mark: &str, letter: char) -> char {
+    ACCENTS
+        .iter()
+        .find(|(m, _, _)| *m == mark)
+        .and_then(|(_, from, to)| from.chars().position(|c| c == letter).and_then(|i| to.chars().nth(i)))
+        .unwrap_or(letter)
+}
+
+/// Plain text of a LaTeX value: accents resolved, escapes and braces removed
+pub(crate) fn clean_latex(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '{' | '}' => i += 1,
+            '~' => {
+                out.push(' ');
+                i += 1;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                if chars.get(i + 2) == Some(&'-') {
+                    out.push('—');
+                    i += 3;
+                } else {
+                    out.push('–');
+                    i += 2;
+                }
+            }
+            '\\' => {
+                let Some(&next) = chars.get(i + 1) else { break };
+                if "&%$_#{}".contains(next) {
+                    out.push(next);
+                    i += 2;
+                    continue;
+                }
+                let (name, end) = if next.is_ascii_alphabetic() {
+                    let end = (i + 1..chars.len()).find(|&k| !chars[k].is_ascii_alphabetic()).unwrap_or(chars.len());
+                    (chars[i + 1..end].iter().collect::<String>(), end)
+                } else {
+                    (next.to_string(), i + 2)
+                };
+                // A control word swallows the space after it
+                let mut j = end;
+                if next.is_ascii_alphabetic() {
+                    while chars.get(j).is_some_and(|c| *c == ' ') {
+                        j += 1;
+                    }
+                }
+                if ACCENTS.iter().any(|(mark, _, _)| *mark == name) {
+                    if chars.get(j) == Some(&'{') {
+                        j += 1;
+                    }
+                    if chars.get(j) == Some(&'\\') {
+                        // Dotless \i and \j under an accent
+                        j += 1;
+                    }
+                    if let Some(&letter) = chars.get(j) {
+                        out.push(accented(&name, letter));
+                        j += 1;
+                    }
+                    if chars.get(j) == Some(&'}') {
+                        j += 1;
+                    }
+                } else if let Some((_, symbol)) = SYMBOLS.iter().find(|(command, _)| *command == name) {
+                    out.push_str(symbol);
+                } else if next.is_ascii_alphabetic() && chars.get(j) != Some(&'{') {
+                    // A logo or macro without an argument, e.g. \TeX
+                    out.push_str(&name);
+                    j = end;
+                }
+                // Anything else (\emph, \textit, ...) is formatting; its
+                // argument is kept and its braces dropped
+                i = j;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` split at `separator` where it isn't inside braces
+fn split_top_level<'a>(text: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            _ if depth == 0 && text.get(i..i + separator.len()).is_some_and(|s| s.eq_ignore_ascii_case(separator)) => {
+                parts.push(&text[start..i]);
+                i += separator.len();
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// `First von Last`, `von Last, First` or `von Last, Jr, First`; a name
+/// wrapped in braces (an organisation) is kept whole
+fn parse_name(raw: &str) -> Option<Name> {
+    let raw = raw.trim();
+    if raw.is_empty() || raw == "others" {
+        return None;
+    }
+    if raw.starts_with('{') && raw.ends_with('}') && split_top_level(raw, " ").len() == 1 {
+        return Some(Name { family: clean_latex(raw), given: None });
+    }
+    let parts = split_top_level(raw, ",");
+    if parts.len() >= 2 {
+        let given = clean_latex(parts[parts.len() - 1]);
+        return Some(Name { family: clean_latex(parts[0]), given: Some(given).filter(|g| !g.is_empty()) });
+    }
+    let words: Vec<&str> = split_top_level(raw, " ").into_iter().filter(|w| !w.is_empty()).collect();
+    // The family name starts at the first lowercase particle ("van", "de"),
+    // or is the last word
+    let split = words[..words.len() - 1]
+        .iter()
+        .skip(1)
+        .position(|w| w.starts_with(|c: char| c.is_lowercase()))
+        .map_or(words.len() - 1, |i| i + 1);
+    let given = clean_latex(&words[..split].join(" "));
+    Some(Name { family: clean_latex(&words[split..].join(" ")), given: Some(given).filter(|g| !g.is_empty()) })
+}
+
+pub(crate) fn parse_names(raw: &str) -> Vec<Name> {
+    split_top_level(raw, " and ").into_iter().filter_map(parse_name).collect()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// `@string` definitions seen so far
+    macros: HashMap<String, String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace() && !"=,{}()#\"".contains(c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Contents of a `{...}` or `"..."` group, braces inside kept
+    fn delimited(&mut self, close: char) -> Option<String> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 && close == '}' => break,
+                '}' => depth -= 1,
+                '"' if depth == 0 && close == '"' => break,
+                '\\' => self.pos += 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        let value = self.chars.get(start..self.pos)?.iter().collect();
+        self.pos += 1;
+        Some(value)
+    }
+
+    /// A field value: pieces joined with `#`
+    fn value(&mut self) -> Option<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                '{' => value.push_str(&self.delimited('}')?),
+                '"' => value.push_str(&self.delimited('"')?),
+                _ => {
+                    let word = self.identifier();
+                    if word.is_empty() {
+                        return None;
+                    }
+                    let lower = word.to_lowercase();
+                    match self.macros.get(&lower) {
+                        Some(expansion) => value.push_str(expansion),
+                        None => match MONTHS.iter().position(|m| *m == lower) {
+                            Some(month) => value.push_str(&(month + 1).to_string()),
+                            None => value.push_str(&word),
+                        },
+                    }
+                }
+            }
+            self.skip_whitespace();
+            if self.peek() != Some('#') {
+                return Some(value);
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// `name = value` pairs up to the entry's closing delimiter
+    fn fields(&mut self, close: char) -> Option<HashMap<String, String>> {
+        let mut fields = HashMap::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                c if c == close => {
+                    self.pos += 1;
+                    return Some(fields);
+                }
+                ',' => self.pos += 1,
+                _ => {
+                    let name = self.identifier().to_lowercase();
+                    self.skip_whitespace();
+                    if name.is_empty() || self.peek() != Some('=') {
+                        return None;
+                    }
+                    self.pos += 1;
+                    let value = self.value()?;
+                    fields.entry(name).or_insert(value);
+                }
+            }
+        }
+    }
+
+    /// Skip a group whose contents aren't needed, e.g. `@comment{...}`
+    fn skip_group(&mut self, close: char) {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' | '(' => depth += 1,
+                '}' | ')' if depth == 0 && c == close => return,
+                '}' | ')' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+fn kind_of(entry_type: &str) -> &'static str {
+    match entry_type {
+        "article" => "article-journal",
+        "book" | "mvbook" => "book",
+        "inbook" | "incollection" | "bookinbook" => "chapter",
+        "inproceedings" | "conference" => "paper-conference",
+        "phdthesis" | "mastersthesis" | "thesis" => "thesis",
+        "techreport" | "report" => "report",
+        "online" | "www" | "electronic" => "webpage",
+        _ => "document",
+    }
+}
+
+fn to_reference(entry_type: &str, key: String, mut fields: HashMap<String, String>) -> Reference {
+    let authors = fields.remove("author").map(|a| parse_names(&a)).unwrap_or_default();
+    let editors = fields.remove("editor").map(|e| parse_names(&e)).unwrap_or_default();
+    let mut take = |names: &[&str]| names.iter().find_map(|n| fields.remove(*n)).map(|v| clean_latex(&v)).filter(|v| !v.is_empty());
+    let year = take(&["year", "date"]).and_then(|y| y.get(..4).and_then(|y| y.parse().ok()));
+    Reference {
+        key,
+        kind: kind_of(entry_type).to_string(),
+        title: take(&["title"]),
+        year,
+        container_title: take(&["journal", "journaltitle", "booktitle"]),
+        volume: take(&["volume"]),
+        issue: take(&["number", "issue"]),
+        pages: take(&["pages"]),
+        publisher: take(&["publisher", "institution", "school", "organization"]),
+        publisher_place: take(&["address", "location"]),
+        doi: take(&["doi"]),
+        url: take(&["url"]),
+        isbn: take(&["isbn"]),
+        authors,
+        editors,
+    }
+}
+
+pub fn parse(source: &str) -> Vec<Reference> {
+    let mut parser = Parser { chars: source.chars().collect(), pos: 0, macros: HashMap::new() };
+    let mut references = Vec::new();
+    while let Some(at) = parser.chars.get(parser.pos..).and_then(|rest| rest.iter().position(|c| *c == '@')) {
+        parser.pos += at + 1;
+        let entry_type = parser.identifier().to_lowercase();
+        parser.skip_whitespace();
+        let close = match parser.peek() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => continue,
+        };
+        parser.pos += 1;
+        match entry_type.as_str() {
+            "comment" | "preamble" => parser.skip_group(close),
+            "string" => {
+                if let Some(fields) = parser.fields(close) {
+                    for (name, value) in fields {
+                        parser.macros.insert(name, value);
+                    }
+                }
+            }
+            _ => {
+                let key = parser.identifier();
+                parser.skip_whitespace();
+                if key.is_empty() || parser.peek() != Some(',') {
+                    parser.skip_group(close);
+                    continue;
+                }
+                match parser.fields(close) {
+                    Some(fields) => references.push(to_reference(&entry_type, key, fields)),
+                    None => parser.skip_group(close),
+                }
+            }
+        }
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_latex() {
+        assert_eq!(clean_latex(r#"G{\"o}del, Escher, {B}ach"#), "Gödel, Escher, Bach");
+        assert_eq!(clean_latex(r"Erd\H{o}s and Pu\v{s}kin \& {\ss}"), "Erdős and Puškin & ß");
+        assert_eq!(clean_latex(r"\emph{Deep}   learning, pp.~10--20"), "Deep learning, pp. 10–20");
+        assert_eq!(clean_latex(r"Mart\'{\i}n"), "Martín");
+    }
+
+    #[test]
+    fn test_parse_names() {
+        let names = parse_names("Knuth, Donald E. and Ludwig van Beethoven and {World Health Organization} and others");
+        assert_eq!(names.len(), 3);
+        assert_eq!((names[0].family.as_str(), names[0].given.as_deref()), ("Knuth", Some("Donald E.")));
+        assert_eq!((names[1].family.as_str(), names[1].given.as_deref()), ("van Beethoven", Some("Ludwig")));
+        assert_eq!((names[2].family.as_str(), names[2].given.as_deref()), ("World Health Organization", None));
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        let source = r#"
+            @string{ai = "Artificial Intelligence"}
+            @comment{ anything { goes } here }
+            @article{smith2020,
+              author = {Smith, John and Jones, Kate},
+              title = {{Neural} networks},
+              journal = ai # " Review",
+              year = 2020, month = mar,
+              volume = "12", number = {3}, pages = {45--67},
+              doi = {10.1000/xyz}
+            }
+            @broken{nokey}
+            @book(knuth1984, title = "The {\TeX}book", author = "Donald E. Knuth", publisher = {Addison-Wesley}, year = {1984})
+        "#;
+        let references = parse(source);
+        assert_eq!(references.len(), 2);
+        let article = &references[0];
+        assert_eq!(article.key, "smith2020");
+        assert_eq!(article.kind, "article-journal");
+        assert_eq!(article.title.as_deref(), Some("Neural networks"));
+        assert_eq!(article.container_title.as_deref(), Some("Artificial Intelligence Review"));
+        assert_eq!((article.year, article.pages.as_deref()), (Some(2020), Some("45–67")));
+        assert_eq!(article.authors[1].family, "Jones");
+        let book = &references[1];
+        assert_eq!((book.kind.as_str(), book.title.as_deref()), ("book", Some("The TeXbook")));
+        assert_eq!(book.authors[0].family, "Knuth");
+    }
+}
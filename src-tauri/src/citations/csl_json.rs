@@ -0,0 +1,73 @@
+//! CSL JSON, as exported by Zotero (Better BibTeX), Mendeley and pandoc.
+
+use super::{Name, Reference};
+use serde_json::Value;
+
+/// A string field; numbers are accepted, since exporters disagree on volumes
+fn text(item: &Value, field: &str) -> Option<String> {
+    match item.get(field)? {
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn names(item: &Value, field: &str) -> Vec<Name> {
+    let Some(Value::Array(names)) = item.get(field) else { return Vec::new() };
+    names
+        .iter()
+        .filter_map(|name| match (text(name, "family"), text(name, "literal")) {
+            (Some(family), _) => {
+                // Particles ("van") are kept with the family name
+                let family = match text(name, "non-dropping-particle") {
+                    Some(particle) => format!("{} {}", particle, family),
+                    None => family,
+                };
+                Some(Name { family, given: text(name, "given") })
+            }
+            (None, Some(literal)) => Some(Name { family: literal, given: None }),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+/// `{"date-parts": [[2020, 3]]}`, or `{"raw": "2020-03"}` from older exports
+fn year(item: &Value) -> Option<i32> {
+    let issued = item.get("issued")?;
+    let from_parts = issued
+        .pointer("/date-parts/0/0")
+        .and_then(|y| y.as_i64().or_else(|| y.as_str().and_then(|s| s.parse().ok())));
+    from_parts
+        .map(|y| y as i32)
+        .or_else(|| text(issued, "raw").or_else(|| text(issued, "literal")).and_then(|raw| raw.get(..4)?.parse().ok()))
+}
+
+pub fn parse(source: &str) -> Result<Vec<Reference>, String> {
+    let value: Value = serde_json::from_str(source).map_err(|e| format!("Invalid CSL JSON: {}", e))?;
+    let Value::Array(items) = value else {
+        return Err("Invalid CSL JSON: expected a list of references".to_string());
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let key = text(item, "citation-key").or_else(|| text(item, "id"))?;
+            Some(Reference {
+                key,
+                kind: text(item, "type").unwrap_or_else(|| "document".to_string()),
+                title: text(item, "title"),
+                authors: names(item, "author"),
+                editors: names(item, "editor"),
+                year: year(item),
+                container_title: text(item, "container-title"),
+                volume: text(item, "volume"),
+                issue: text(item, "issue"),
+                pages: text(item, "page").map(|p| p.replace('-', "–")),
+                publisher: text(item, "publisher"),
+                publisher_place: text(item, "publisher-place"),
+                doi: text(item, "DOI"),
+                url: text(item, "URL"),
+                isbn: text(item, "ISBN"),
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,349 @@
+//! Citations and bibliographies.
+//!
+//! The `bibliography` setting names one or more BibTeX (`.bib`) or CSL JSON
+//! files, relative to the workspace. They are parsed on first use and again
+//! whenever they change on disk, so a reference manager's auto-export is
+//! picked up without a restart. Notes cite with pandoc's syntax — `[@key]`,
+//! `[see @a, p. 4; @b]` — and each note's bibliography lists what it cites,
+//! in the style from `citation_style` unless the caller picks one.
+
+pub mod bibtex;
+pub mod csl_json;
+pub mod styles;
+
+pub use styles::CitationStyle;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+/// Settings key holding a path, or a list of paths, to bibliography files
+const SETTINGS_KEY: &str = "bibliography";
+const STYLE_KEY: &str = "citation_style";
+const DEFAULT_LIMIT: usize = 50;
+
+/// Parsed files keyed by path, valid while size and mtime match
+static FILES: Lazy<Mutex<HashMap<PathBuf, (u64, SystemTime, Arc<Vec<Reference>>)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A bracketed citation group: `[@a]`, `[see @a, p. 4; -@b]`
+static GROUP_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\[\]]*@[^\[\]]*)\]").unwrap());
+static KEY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[\s;])-?@([\p{L}\p{N}_][\p{L}\p{N}_:.#$%&+?<>~/-]*)").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Name {
+    pub family: String,
+    /// `None` for organisations and other single-part names
+    pub given: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub key: String,
+    /// CSL item type, e.g. `article-journal`, `book`, `chapter`
+    pub kind: String,
+    pub title: Option<String>,
+    pub authors: Vec<Name>,
+    pub editors: Vec<Name>,
+    pub year: Option<i32>,
+    /// Journal, or the book a chapter or paper appears in
+    pub container_title: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub pages: Option<String>,
+    pub publisher: Option<String>,
+    pub publisher_place: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    pub isbn: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattedCitation {
+    pub key: String,
+    /// What goes in the text, e.g. `(Smith, 2020)` or `[1]`
+    pub in_text: String,
+    /// The bibliography entry, as Markdown
+    pub entry: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteBibliography {
+    pub path: String,
+    pub style: CitationStyle,
+    /// Every key cited, in order of first appearance
+    pub cited: Vec<String>,
+    /// Entries in bibliography order
+    pub entries: Vec<FormattedCitation>,
+    /// Cited keys that aren't in the bibliography
+    pub missing: Vec<String>,
+}
+
+// --- Loading ---
+
+fn parse_file(path: &Path) -> Result<Vec<Reference>, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "bib" | "bibtex" => Ok(bibtex::parse(&source)),
+        "json" => csl_json::parse(&source),
+        _ => Err(format!("Unsupported bibliography format: {} (use .bib or CSL .json)", path.display())),
+    }
+}
+
+fn load_file(path: &Path) -> Result<Arc<Vec<Reference>>, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Bibliography not found: {} ({})", path.display(), e))?;
+    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    if let Some((size, mtime, references)) = FILES.lock().unwrap_or_else(|e| e.into_inner()).get(path) {
+        if *size == meta.len() && *mtime == modified {
+            return Ok(references.clone());
+        }
+    }
+    let references = Arc::new(parse_file(path)?);
+    FILES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path.to_path_buf(), (meta.len(), modified, references.clone()));
+    Ok(references)
+}
+
+/// Drop the parsed bibliographies; they are parsed again when next used
+pub(crate) fn clear_cache() -> usize {
+    FILES.lock().unwrap_or_else(|e| e.into_inner()).drain().count()
+}
+
+/// Every reference in the configured files; the first file wins a duplicate key
+pub fn library(root: &Path, setting: Option<&Value>) -> Result<Vec<Reference>, String> {
+    let files: Vec<&str> = match setting {
+        Some(Value::String(path)) => vec![path.as_str()],
+        Some(Value::Array(paths)) => paths.iter().filter_map(|p| p.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    let files: Vec<&str> = files.into_iter().map(str::trim).filter(|f| !f.is_empty()).collect();
+    if files.is_empty() {
+        return Err("No bibliography configured; set `bibliography` to a .bib or CSL JSON file".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    let mut references = Vec::new();
+    for file in files {
+        for reference in load_file(&root.join(file))?.iter() {
+            if seen.insert(reference.key.clone()) {
+                references.push(reference.clone());
+            }
+        }
+    }
+    Ok(references)
+}
+
+// --- Searching and formatting ---
+
+/// References matching every term of `query` in their key, title, authors,
+/// container or year; best matches first
+pub fn search(references: &[Reference], query: &str, limit: usize) -> Vec<Reference> {
+    let terms: Vec<String> = query.split_whitespace().map(|t| t.trim_start_matches('@').to_lowercase()).collect();
+    let mut scored: Vec<(u32, &Reference)> = references
+        .iter()
+        .filter_map(|reference| {
+            let key = reference.key.to_lowercase();
+            let title = reference.title.as_deref().unwrap_or("").to_lowercase();
+            let families: Vec<String> = reference.authors.iter().chain(&reference.editors).map(|n| n.family.to_lowercase()).collect();
+            let other = format!(
+                "{} {}",
+                reference.container_title.as_deref().unwrap_or("").to_lowercase(),
+                reference.year.map(|y| y.to_string()).unwrap_or_default()
+            );
+            let mut score = 0;
+            for term in &terms {
+                score += if key == *term {
+                    100
+                } else if key.starts_with(term.as_str()) {
+                    50
+                } else if families.iter().any(|f| f.starts_with(term.as_str())) {
+                    20
+                } else if title.contains(term.as_str()) {
+                    10
+                } else if key.contains(term.as_str()) || other.contains(term.as_str()) {
+                    5
+                } else {
+                    return None;
+                };
+            }
+            Some((score, reference))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| styles::sort_key(a.1).cmp(&styles::sort_key(b.1))));
+    scored.into_iter().take(limit).map(|(_, reference)| reference.clone()).collect()
+}
+
+pub fn format(style: CitationStyle, reference: &Reference, number: usize) -> FormattedCitation {
+    FormattedCitation {
+        key: reference.key.clone(),
+        in_text: styles::in_text(style, reference, number),
+        entry: styles::bibliography_entry(style, reference, number),
+    }
+}
+
+/// Keys cited in `content`, in order of first appearance; code fences are skipped
+pub fn cited_keys(content: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        for group in GROUP_RE.captures_iter(line) {
+            for key in KEY_RE.captures_iter(&group[1]) {
+                // Trailing punctuation ends the sentence, not the key
+                let key = key[1].trim_end_matches(['.', ':', '?', '#', '$', '%', '&', '+', '<', '>', '~', '/', '-']);
+                if !keys.iter().any(|k| k == key) {
+                    keys.push(key.to_string());
+                }
+            }
+        }
+    }
+    keys
+}
+
+pub fn note_bibliography(path: &str, content: &str, references: &[Reference], style: CitationStyle) -> NoteBibliography {
+    let by_key: HashMap<&str, &Reference> = references.iter().map(|r| (r.key.as_str(), r)).collect();
+    let cited = cited_keys(content);
+    let (found, missing): (Vec<&String>, Vec<&String>) = cited.iter().partition(|key| by_key.contains_key(key.as_str()));
+    let mut found: Vec<&Reference> = found.into_iter().map(|key| by_key[key.as_str()]).collect();
+    if !style.is_numbered() {
+        found.sort_by_key(|reference| styles::sort_key(reference));
+    }
+    NoteBibliography {
+        path: path.to_string(),
+        style,
+        entries: found.iter().enumerate().map(|(i, reference)| format(style, reference, i + 1)).collect(),
+        missing: missing.into_iter().cloned().collect(),
+        cited,
+    }
+}
+
+/// The workspace's references and its configured style
+fn workspace_library(app: &AppHandle, workspace: Option<String>) -> Result<(Vec<Reference>, CitationStyle), String> {
+    let root = workspace
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(app))
+        .ok_or_else(|| "No workspace is open".to_string())?;
+    let references = library(&root, crate::settings::effective(app, Some(&root), SETTINGS_KEY).as_ref())?;
+    let style = crate::settings::effective(app, Some(&root), STYLE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    Ok((references, style))
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn citations_search(app: AppHandle, query: String, limit: Option<usize>, workspace: Option<String>) -> Result<Vec<Reference>, String> {
+    let (references, _) = workspace_library(&app, workspace)?;
+    Ok(search(&references, &query, limit.unwrap_or(DEFAULT_LIMIT)))
+}
+
+#[tauri::command]
+pub fn citations_get(app: AppHandle, key: String, workspace: Option<String>) -> Result<Reference, String> {
+    let (references, _) = workspace_library(&app, workspace)?;
+    let key = key.trim_start_matches('@');
+    references
+        .into_iter()
+        .find(|r| r.key == key)
+        .ok_or_else(|| format!("No reference with key '{}'", key))
+}
+
+/// In-text citation and bibliography entry for one reference; numbered
+/// styles number it 1
+#[tauri::command]
+pub fn citations_format(app: AppHandle, key: String, style: Option<CitationStyle>, workspace: Option<String>) -> Result<FormattedCitation, String> {
+    let (references, default_style) = workspace_library(&app, workspace)?;
+    let key = key.trim_start_matches('@');
+    let reference = references
+        .iter()
+        .find(|r| r.key == key)
+        .ok_or_else(|| format!("No reference with key '{}'", key))?;
+    Ok(format(style.unwrap_or(default_style), reference, 1))
+}
+
+/// The bibliography of everything a note cites
+#[tauri::command]
+pub fn citations_note_bibliography(
+    app: AppHandle,
+    path: String,
+    style: Option<CitationStyle>,
+    workspace: Option<String>,
+) -> Result<NoteBibliography, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let workspace = workspace.or_else(|| {
+        crate::handlers::files::find_workspace_root(Path::new(&path))
+            .ok()
+            .map(|root| root.to_string_lossy().to_string())
+    });
+    let (references, default_style) = workspace_library(&app, workspace)?;
+    Ok(note_bibliography(&path, &content, &references, style.unwrap_or(default_style)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cited_keys() {
+        let content = "As shown [@smith2020; see @knuth1984, p. 4].\nAgain [-@smith2020] and [@doe:2019.]\n```\n[@ignored]\n```\nMail [me@example.com] or [link](https://x.y/@user)";
+        assert_eq!(cited_keys(content), vec!["smith2020", "knuth1984", "doe:2019"]);
+    }
+
+    #[test]
+    fn test_library_search_and_note_bibliography() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("refs.bib"),
+            "@book{knuth1984, title={The {\\TeX}book}, author={Knuth, Donald E.}, year=1984, publisher={Addison-Wesley}}",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("more.json"),
+            r#"[{"id":"smith2020","type":"article-journal","title":"Neural networks","author":[{"family":"Smith","given":"John"}],
+                "issued":{"date-parts":[[2020]]},"container-title":"AI Review","volume":12},
+               {"id":"knuth1984","type":"book","title":"Duplicate"}]"#,
+        )
+        .unwrap();
+
+        let setting = serde_json::json!(["refs.bib", "more.json"]);
+        let references = library(dir.path(), Some(&setting)).unwrap();
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[1].volume.as_deref(), Some("12"));
+        assert!(library(dir.path(), None).is_err());
+
+        assert_eq!(search(&references, "knuth", 10)[0].key, "knuth1984");
+        assert_eq!(search(&references, "neural 2020", 10)[0].key, "smith2020");
+        assert!(search(&references, "neural 1984", 10).is_empty());
+
+        let note = "Typesetting [@knuth1984] and learning [@smith2020; @missing].";
+        let bibliography = note_bibliography("note.md", note, &references, CitationStyle::Ieee);
+        assert_eq!(bibliography.missing, vec!["missing"]);
+        assert_eq!(bibliography.entries[0].in_text, "[1]");
+        assert!(bibliography.entries[0].entry.starts_with("[1] D. E. Knuth, *The TeXbook*."));
+
+        let bibliography = note_bibliography("note.md", note, &references, CitationStyle::Apa);
+        assert_eq!(bibliography.entries[0].in_text, "(Knuth, 1984)");
+        assert_eq!(bibliography.entries[1].in_text, "(Smith, 2020)");
+    }
+}
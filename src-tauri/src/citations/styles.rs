@@ -0,0 +1,492 @@
+//! Citation styles.
+//!
+//! Each style renders an in-text citation and a bibliography entry as
+//! Markdown (titles in `*italics*`). These follow the common cases of each
+//! style guide — journal articles, books, chapters and conference papers —
+//! rather than a complete CSL style definition.
+
+use super::{Name, Reference};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationStyle {
+    #[default]
+    Apa,
+    /// Chicago author-date
+    Chicago,
+    Mla,
+    Ieee,
+    Harvard,
+}
+
+impl CitationStyle {
+    /// Numbered styles list references in the order they are first cited
+    pub fn is_numbered(self) -> bool {
+        self == CitationStyle::Ieee
+    }
+}
+
+fn initials(given: &str, separator: &str) -> String {
+    given
+        .split_whitespace()
+        .map(|word| {
+            word.split('-')
+                .filter_map(|part| part.chars().next())
+                .map(|c| format!("{}.", c))
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn family_initials(name: &Name, separator: &str) -> String {
+    match &name.given {
+        Some(given) => format!("{}, {}", name.family, initials(given, separator)),
+        None => name.family.clone(),
+    }
+}
+
+fn initials_family(name: &Name) -> String {
+    match &name.given {
+        Some(given) => format!("{} {}", initials(given, " "), name.family),
+        None => name.family.clone(),
+    }
+}
+
+fn family_given(name: &Name) -> String {
+    match &name.given {
+        Some(given) => format!("{}, {}", name.family, given),
+        None => name.family.clone(),
+    }
+}
+
+fn given_family(name: &Name) -> String {
+    match &name.given {
+        Some(given) => format!("{} {}", given, name.family),
+        None => name.family.clone(),
+    }
+}
+
+/// `a`, `a{pair}b`, `a, b{last}c`
+fn join(items: &[String], pair: &str, last: &str) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{}{}{}", first, pair, second),
+        [rest @ .., final_item] => format!("{}{}{}", rest.join(", "), last, final_item),
+    }
+}
+
+/// `text` closed with a period, unless it already ends a sentence
+fn sentence(text: &str) -> String {
+    if text.trim_end_matches(['*', '"', '\u{201d}']).ends_with(['.', '?', '!']) {
+        text.to_string()
+    } else {
+        format!("{}.", text)
+    }
+}
+
+fn italic(text: &str) -> String {
+    format!("*{}*", text)
+}
+
+fn quoted(text: &str, punctuation: &str) -> String {
+    let text = if punctuation == "." { sentence(text) } else { format!("{}{}", text, punctuation) };
+    format!("\u{201c}{}\u{201d}", text)
+}
+
+fn link(reference: &Reference) -> Option<String> {
+    match (&reference.doi, &reference.url) {
+        (Some(doi), _) => Some(format!("https://doi.org/{}", doi.trim_start_matches("https://doi.org/"))),
+        (None, Some(url)) => Some(url.clone()),
+        _ => None,
+    }
+}
+
+/// Authors, or editors for an edited volume
+fn creators(reference: &Reference) -> &[Name] {
+    if reference.authors.is_empty() {
+        &reference.editors
+    } else {
+        &reference.authors
+    }
+}
+
+fn is_part(reference: &Reference) -> bool {
+    matches!(reference.kind.as_str(), "chapter" | "paper-conference")
+}
+
+/// Titled on its own rather than inside a journal or book
+fn is_standalone(reference: &Reference) -> bool {
+    reference.container_title.is_none() || matches!(reference.kind.as_str(), "book" | "thesis" | "report")
+}
+
+fn year(reference: &Reference) -> String {
+    reference.year.map_or_else(|| "n.d.".to_string(), |y| y.to_string())
+}
+
+fn title(reference: &Reference) -> String {
+    reference.title.clone().unwrap_or_else(|| reference.key.clone())
+}
+
+fn place_publisher(reference: &Reference) -> Option<String> {
+    match (&reference.publisher_place, &reference.publisher) {
+        (Some(place), Some(publisher)) => Some(format!("{}: {}", place, publisher)),
+        (None, Some(publisher)) => Some(publisher.clone()),
+        _ => None,
+    }
+}
+
+/// Family names for in-text citations: one, two, or the first with "et al."
+fn short_authors(reference: &Reference, and: &str) -> String {
+    match creators(reference) {
+        [] => title(reference),
+        [only] => only.family.clone(),
+        [first, second] => format!("{}{}{}", first.family, and, second.family),
+        [first, ..] => format!("{} et al.", first.family),
+    }
+}
+
+pub fn in_text(style: CitationStyle, reference: &Reference, number: usize) -> String {
+    match style {
+        CitationStyle::Apa => format!("({}, {})", short_authors(reference, " & "), year(reference)),
+        CitationStyle::Chicago => format!("({} {})", short_authors(reference, " and "), year(reference)),
+        CitationStyle::Mla => format!("({})", short_authors(reference, " and ")),
+        CitationStyle::Ieee => format!("[{}]", number),
+        CitationStyle::Harvard => format!("({}, {})", short_authors(reference, " and "), year(reference)),
+    }
+}
+
+fn apa(reference: &Reference) -> String {
+    let names: Vec<String> = creators(reference).iter().map(|n| family_initials(n, " ")).collect();
+    let heading = if is_standalone(reference) { italic(&title(reference)) } else { title(reference) };
+    let mut entry = if names.is_empty() {
+        format!("{} ({}).", sentence(&heading), year(reference))
+    } else {
+        format!("{} ({}). {}", join(&names, ", & ", ", & "), year(reference), sentence(&heading))
+    };
+    match &reference.container_title {
+        Some(container) if is_part(reference) => {
+            entry.push_str(&format!(" In {}", italic(container)));
+            if let Some(pages) = &reference.pages {
+                entry.push_str(&format!(" (pp. {})", pages));
+            }
+            entry.push('.');
+            if let Some(publisher) = &reference.publisher {
+                entry.push_str(&format!(" {}", sentence(publisher)));
+            }
+        }
+        Some(container) if !is_standalone(reference) => {
+            entry.push_str(&format!(" {}", italic(container)));
+            if let Some(volume) = &reference.volume {
+                entry.push_str(&format!(", {}", italic(volume)));
+                if let Some(issue) = &reference.issue {
+                    entry.push_str(&format!("({})", issue));
+                }
+            }
+            if let Some(pages) = &reference.pages {
+                entry.push_str(&format!(", {}", pages));
+            }
+            entry.push('.');
+        }
+        _ => {
+            if let Some(publisher) = &reference.publisher {
+                entry.push_str(&format!(" {}", sentence(publisher)));
+            }
+        }
+    }
+    if let Some(link) = link(reference) {
+        entry.push_str(&format!(" {}", link));
+    }
+    entry
+}
+
+fn chicago(reference: &Reference) -> String {
+    let names: Vec<String> = creators(reference)
+        .iter()
+        .enumerate()
+        .map(|(i, n)| if i == 0 { family_given(n) } else { given_family(n) })
+        .collect();
+    let mut entry = if names.is_empty() {
+        String::new()
+    } else {
+        format!("{} ", sentence(&join(&names, ", and ", ", and ")))
+    };
+    entry.push_str(&format!("{} ", sentence(&year(reference))));
+    if is_standalone(reference) {
+        entry.push_str(&sentence(&italic(&title(reference))));
+    } else {
+        entry.push_str(&quoted(&title(reference), "."));
+    }
+    match &reference.container_title {
+        Some(container) if is_part(reference) => {
+            entry.push_str(&format!(" In {}", italic(container)));
+            if !reference.editors.is_empty() && !reference.authors.is_empty() {
+                let editors: Vec<String> = reference.editors.iter().map(given_family).collect();
+                entry.push_str(&format!(", edited by {}", join(&editors, " and ", ", and ")));
+            }
+            if let Some(pages) = &reference.pages {
+                entry.push_str(&format!(", {}", pages));
+            }
+            entry.push('.');
+            if let Some(publisher) = place_publisher(reference) {
+                entry.push_str(&format!(" {}", sentence(&publisher)));
+            }
+        }
+        Some(container) if !is_standalone(reference) => {
+            entry.push_str(&format!(" {}", italic(container)));
+            if let Some(volume) = &reference.volume {
+                entry.push_str(&format!(" {}", volume));
+            }
+            if let Some(issue) = &reference.issue {
+                entry.push_str(&format!(" ({})", issue));
+            }
+            if let Some(pages) = &reference.pages {
+                entry.push_str(&format!(": {}", pages));
+            }
+            entry.push('.');
+        }
+        _ => {
+            if let Some(publisher) = place_publisher(reference) {
+                entry.push_str(&format!(" {}", sentence(&publisher)));
+            }
+        }
+    }
+    if let Some(link) = link(reference) {
+        entry.push_str(&format!(" {}.", link));
+    }
+    entry
+}
+
+fn mla(reference: &Reference) -> String {
+    let names = match creators(reference) {
+        [] => String::new(),
+        [only] => family_given(only),
+        [first, second] => format!("{}, and {}", family_given(first), given_family(second)),
+        [first, ..] => format!("{}, et al", family_given(first)),
+    };
+    let mut entry = if names.is_empty() { String::new() } else { format!("{} ", sentence(&names)) };
+    let mut details: Vec<String> = Vec::new();
+    if is_standalone(reference) {
+        entry.push_str(&sentence(&italic(&title(reference))));
+    } else {
+        entry.push_str(&quoted(&title(reference), "."));
+        if let Some(container) = &reference.container_title {
+            details.push(italic(container));
+        }
+        if is_part(reference) && !reference.editors.is_empty() && !reference.authors.is_empty() {
+            let editors: Vec<String> = reference.editors.iter().map(given_family).collect();
+            details.push(format!("edited by {}", join(&editors, " and ", ", and ")));
+        }
+        if let Some(volume) = &reference.volume {
+            details.push(format!("vol. {}", volume));
+        }
+        if let Some(issue) = &reference.issue {
+            details.push(format!("no. {}", issue));
+        }
+    }
+    if let Some(publisher) = &reference.publisher {
+        details.push(publisher.clone());
+    }
+    if let Some(year) = reference.year {
+        details.push(year.to_string());
+    }
+    if let Some(pages) = &reference.pages {
+        details.push(format!("pp. {}", pages));
+    }
+    if !details.is_empty() {
+        entry.push_str(&format!(" {}.", details.join(", ")));
+    }
+    if let Some(link) = link(reference) {
+        entry.push_str(&format!(" {}.", link));
+    }
+    entry
+}
+
+fn ieee(reference: &Reference, number: usize) -> String {
+    let creators = creators(reference);
+    let names = if creators.len() > 6 {
+        format!("{} et al.", initials_family(&creators[0]))
+    } else {
+        join(&creators.iter().map(initials_family).collect::<Vec<_>>(), " and ", ", and ")
+    };
+    let mut entry = format!("[{}] ", number);
+    if !names.is_empty() {
+        entry.push_str(&format!("{}, ", names));
+    }
+    let mut details: Vec<String> = Vec::new();
+    if is_standalone(reference) {
+        entry.push_str(&sentence(&italic(&title(reference))));
+        if let Some(publisher) = place_publisher(reference) {
+            details.push(publisher);
+        }
+        details.push(year(reference));
+        entry.push_str(&format!(" {}.", details.join(", ")));
+    } else {
+        entry.push_str(&quoted(&title(reference), ","));
+        match &reference.container_title {
+            Some(container) if is_part(reference) => details.push(format!("in {}", italic(container))),
+            Some(container) => details.push(italic(container)),
+            None => {}
+        }
+        if let Some(volume) = &reference.volume {
+            details.push(format!("vol. {}", volume));
+        }
+        if let Some(issue) = &reference.issue {
+            details.push(format!("no. {}", issue));
+        }
+        if let Some(pages) = &reference.pages {
+            details.push(format!("pp. {}", pages));
+        }
+        details.push(year(reference));
+        if let Some(doi) = &reference.doi {
+            details.push(format!("doi: {}", doi));
+        }
+        entry.push_str(&format!(" {}.", details.join(", ")));
+    }
+    if reference.doi.is_none() {
+        if let Some(url) = &reference.url {
+            entry.push_str(&format!(" [Online]. Available: {}", url));
+        }
+    }
+    entry
+}
+
+fn harvard(reference: &Reference) -> String {
+    let names: Vec<String> = creators(reference).iter().map(|n| family_initials(n, "")).collect();
+    let mut entry = if names.is_empty() {
+        String::new()
+    } else {
+        format!("{} ", join(&names, " and ", " and "))
+    };
+    entry.push_str(&format!("({}) ", year(reference)));
+    match &reference.container_title {
+        Some(container) if !is_standalone(reference) => {
+            entry.push_str(&format!("\u{2018}{}\u{2019}, ", title(reference)));
+            if is_part(reference) {
+                entry.push_str(&format!("in {}.", italic(container)));
+                if let Some(publisher) = place_publisher(reference) {
+                    entry.push_str(&format!(" {}", publisher));
+                }
+            } else {
+                entry.push_str(&italic(container));
+                if let Some(volume) = &reference.volume {
+                    entry.push_str(&format!(", {}", volume));
+                    if let Some(issue) = &reference.issue {
+                        entry.push_str(&format!("({})", issue));
+                    }
+                }
+            }
+            if let Some(pages) = &reference.pages {
+                entry.push_str(&format!(", pp. {}", pages));
+            }
+            entry.push('.');
+        }
+        _ => {
+            entry.push_str(&sentence(&italic(&title(reference))));
+            if let Some(publisher) = place_publisher(reference) {
+                entry.push_str(&format!(" {}", sentence(&publisher)));
+            }
+        }
+    }
+    match (&reference.doi, &reference.url) {
+        (Some(doi), _) => entry.push_str(&format!(" doi: {}.", doi)),
+        (None, Some(url)) => entry.push_str(&format!(" Available at: {}.", url)),
+        _ => {}
+    }
+    entry
+}
+
+/// The reference as a bibliography entry; `number` is used by numbered styles
+pub fn bibliography_entry(style: CitationStyle, reference: &Reference, number: usize) -> String {
+    match style {
+        CitationStyle::Apa => apa(reference),
+        CitationStyle::Chicago => chicago(reference),
+        CitationStyle::Mla => mla(reference),
+        CitationStyle::Ieee => ieee(reference, number),
+        CitationStyle::Harvard => harvard(reference),
+    }
+}
+
+/// Alphabetical order for author-date and MLA bibliographies
+pub fn sort_key(reference: &Reference) -> (String, Option<i32>, String) {
+    let author = creators(reference).first().map_or_else(|| title(reference), |n| n.family.clone());
+    (author.to_lowercase(), reference.year, title(reference).to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(family: &str, given: &str) -> Name {
+        Name { family: family.to_string(), given: Some(given.to_string()) }
+    }
+
+    fn article() -> Reference {
+        Reference {
+            key: "smith2020".into(),
+            kind: "article-journal".into(),
+            title: Some("Neural networks".into()),
+            authors: vec![name("Smith", "John Robert"), name("Jones", "Kate")],
+            year: Some(2020),
+            container_title: Some("AI Review".into()),
+            volume: Some("12".into()),
+            issue: Some("3".into()),
+            pages: Some("45–67".into()),
+            doi: Some("10.1000/xyz".into()),
+            ..Default::default()
+        }
+    }
+
+    fn book() -> Reference {
+        Reference {
+            key: "knuth1984".into(),
+            kind: "book".into(),
+            title: Some("The TeXbook".into()),
+            authors: vec![name("Knuth", "Donald E.")],
+            year: Some(1984),
+            publisher: Some("Addison-Wesley".into()),
+            publisher_place: Some("Reading, MA".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_in_text_citations() {
+        let article = article();
+        assert_eq!(in_text(CitationStyle::Apa, &article, 1), "(Smith & Jones, 2020)");
+        assert_eq!(in_text(CitationStyle::Chicago, &article, 1), "(Smith and Jones 2020)");
+        assert_eq!(in_text(CitationStyle::Mla, &article, 1), "(Smith and Jones)");
+        assert_eq!(in_text(CitationStyle::Ieee, &article, 3), "[3]");
+    }
+
+    #[test]
+    fn test_bibliography_entries() {
+        assert_eq!(
+            bibliography_entry(CitationStyle::Apa, &article(), 1),
+            "Smith, J. R., & Jones, K. (2020). Neural networks. *AI Review*, *12*(3), 45–67. https://doi.org/10.1000/xyz"
+        );
+        assert_eq!(
+            bibliography_entry(CitationStyle::Apa, &book(), 1),
+            "Knuth, D. E. (1984). *The TeXbook*. Addison-Wesley."
+        );
+        assert_eq!(
+            bibliography_entry(CitationStyle::Chicago, &article(), 1),
+            "Smith, John Robert, and Kate Jones. 2020. \u{201c}Neural networks.\u{201d} *AI Review* 12 (3): 45–67. https://doi.org/10.1000/xyz."
+        );
+        assert_eq!(
+            bibliography_entry(CitationStyle::Mla, &book(), 1),
+            "Knuth, Donald E. *The TeXbook*. Addison-Wesley, 1984."
+        );
+        assert_eq!(
+            bibliography_entry(CitationStyle::Ieee, &article(), 2),
+            "[2] J. R. Smith and K. Jones, \u{201c}Neural networks,\u{201d} *AI Review*, vol. 12, no. 3, pp. 45–67, 2020, doi: 10.1000/xyz."
+        );
+        assert_eq!(
+            bibliography_entry(CitationStyle::Harvard, &book(), 1),
+            "Knuth, D.E. (1984) *The TeXbook*. Reading, MA: Addison-Wesley."
+        );
+    }
+}
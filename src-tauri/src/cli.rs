@@ -0,0 +1,459 @@
+//! `lokus-cli`: vault operations from a terminal.
+//!
+//! Searching and creating notes go through the local API server when the
+//! app is running with the same vault open, so its index and open editors
+//! stay in step; that needs an API key (`--api-key` or `LOKUS_API_KEY`).
+//! Everything else, and everything when the app isn't running, works on
+//! the vault files directly.
+
+use crate::export::workspace::{run_export, ExportFormat, WorkspaceExportOptions};
+use crate::tasks::{Task, TaskStore};
+use chrono::{Duration, Local, NaiveDate};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Tauri's app identifier, which names the app data directory
+const APP_IDENTIFIER: &str = "io.lokus.app";
+const API_PORTS: std::ops::RangeInclusive<u16> = 3333..=3336;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+const USAGE: &str = "Usage: lokus <command> [options]
+
+Commands:
+  search <query> [--limit N]                    Search notes
+  new-note [--template NAME] [--title TITLE]    Create a note; `--template daily` opens today's daily note
+           [--folder DIR]
+  export --format markdown|html|epub [--out PATH]
+  sync git push|pull|status|commit [-m MESSAGE]
+  tasks list [--due today|tomorrow|week|overdue|YYYY-MM-DD] [--all]
+
+Options:
+  --vault PATH      Vault to use (default: $LOKUS_VAULT, then the last one opened in the app)
+  --api-key KEY     Key for the running app's API (default: $LOKUS_API_KEY)
+  --json            Print JSON
+";
+
+/// Options that don't take a value
+const SWITCHES: &[&str] = &["json", "all", "help"];
+
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(raw: Vec<String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut raw = raw.into_iter();
+        while let Some(arg) = raw.next() {
+            let name = match arg.as_str() {
+                "-m" => "message".to_string(),
+                "-h" => "help".to_string(),
+                _ => match arg.strip_prefix("--") {
+                    Some(name) => name.to_string(),
+                    None => {
+                        positional.push(arg);
+                        continue;
+                    }
+                },
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                options.insert(name.to_string(), value.to_string());
+            } else if SWITCHES.contains(&name.as_str()) {
+                options.insert(name, String::new());
+            } else {
+                let value = raw.next().ok_or_else(|| format!("--{} needs a value", name))?;
+                options.insert(name, value);
+            }
+        }
+        Ok(Self { positional, options })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+}
+
+fn app_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// A value from one of the app's stores, which are plain JSON files
+fn store_value(file: &str, key: &str) -> Option<Value> {
+    let content = std::fs::read_to_string(app_data_dir()?.join(file)).ok()?;
+    serde_json::from_str::<Value>(&content).ok()?.get(key).cloned()
+}
+
+fn vault(args: &Args) -> Result<PathBuf, String> {
+    let vault = args
+        .get("vault")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOKUS_VAULT").map(PathBuf::from))
+        .or_else(|| store_value(".settings.dat", "last_workspace_path").and_then(|v| v.as_str().map(PathBuf::from)))
+        .ok_or_else(|| "No vault given; pass --vault or set LOKUS_VAULT".to_string())?;
+    if !vault.is_dir() {
+        return Err(format!("Vault does not exist: {}", vault.display()));
+    }
+    vault.canonicalize().map_err(|e| format!("Vault is not accessible: {}", e))
+}
+
+/// The running app's API, when it has `vault` open and we have a key
+struct Remote {
+    base: String,
+    key: String,
+    client: reqwest::Client,
+}
+
+impl Remote {
+    async fn connect(args: &Args, vault: &Path) -> Option<Remote> {
+        let key = args
+            .get("api-key")
+            .map(String::from)
+            .or_else(|| std::env::var("LOKUS_API_KEY").ok())?;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .connect_timeout(std::time::Duration::from_millis(300))
+            .build()
+            .ok()?;
+        for port in API_PORTS {
+            let base = format!("http://127.0.0.1:{}", port);
+            let Ok(response) = client.get(format!("{}/api/workspace", base)).send().await else {
+                continue;
+            };
+            let info: Value = response.json().await.ok()?;
+            let open = info["data"]["workspace"].as_str().and_then(|w| Path::new(w).canonicalize().ok());
+            // Another vault is open in the app; work on the files instead
+            if open.as_deref() != Some(vault) {
+                return None;
+            }
+            return Some(Remote { base, key, client });
+        }
+        None
+    }
+
+    async fn call(&self, request: reqwest::RequestBuilder) -> Result<Value, String> {
+        let response = request
+            .bearer_auth(&self.key)
+            .send()
+            .await
+            .map_err(|e| format!("Lokus API request failed: {}", e))?;
+        let body: Value = response.json().await.map_err(|e| format!("Invalid Lokus API response: {}", e))?;
+        if body["success"].as_bool() != Some(true) {
+            return Err(body["error"].as_str().unwrap_or("Lokus API request failed").to_string());
+        }
+        Ok(body["data"].clone())
+    }
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+// --- Commands ---
+
+async fn search(args: &Args) -> Result<(), String> {
+    let query = args.positional.get(1..).map(|words| words.join(" ")).unwrap_or_default();
+    if query.trim().is_empty() {
+        return Err("Nothing to search for".to_string());
+    }
+    let limit = match args.get("limit") {
+        Some(limit) => limit.parse().map_err(|_| format!("Invalid limit '{}'", limit))?,
+        None => DEFAULT_SEARCH_LIMIT,
+    };
+    let vault = vault(args)?;
+
+    let results = match Remote::connect(args, &vault).await {
+        Some(remote) => {
+            let request = remote
+                .client
+                .get(format!("{}/api/v1/search", remote.base))
+                .query(&[("q", query.as_str()), ("limit", &limit.to_string())]);
+            remote.call(request).await?
+        }
+        None => serde_json::to_value(crate::search::index::query(&vault.to_string_lossy(), &query, limit)?)
+            .map_err(|e| e.to_string())?,
+    };
+
+    if args.has("json") {
+        return print_json(&results);
+    }
+    for result in results.as_array().into_iter().flatten() {
+        let file = result["file"].as_str().unwrap_or_default();
+        let shown = Path::new(file).strip_prefix(&vault).map(|p| p.display().to_string()).unwrap_or(file.to_string());
+        println!("{}", shown);
+        if let Some(snippet) = result["snippet"].as_str().filter(|s| !s.is_empty()) {
+            println!("    {}", snippet.replace('\n', " "));
+        }
+    }
+    Ok(())
+}
+
+async fn new_note(args: &Args) -> Result<(), String> {
+    let vault = vault(args)?;
+    let today = Local::now().date_naive();
+
+    // Work out the note, then create it through the app or on disk
+    let (path, content) = match args.get("template") {
+        Some("daily") => {
+            let config = app_data_dir().map(|dir| crate::daily_notes::config_in(&dir)).unwrap_or_default();
+            let path = crate::daily_notes::note_path(&vault, &config, today);
+            if path.exists() {
+                println!("{}", path.display());
+                return Ok(());
+            }
+            let (content, _) = crate::daily_notes::build_content(&vault, &config, today, None);
+            (path, content)
+        }
+        // The template decides the file name, so this one is always written
+        // directly; a running app picks it up through its file watcher
+        Some(template) => {
+            let ctx = crate::templates::RenderContext::new(args.get("title").map(String::from), HashMap::new());
+            let folder = vault.join(args.get("folder").unwrap_or(""));
+            let created = crate::templates::create_note(&vault, template, &folder, ctx)?;
+            println!("{}", created.path);
+            return Ok(());
+        }
+        None => {
+            let title = args.get("title").unwrap_or("Untitled");
+            let folder = vault.join(args.get("folder").unwrap_or(""));
+            (crate::templates::unused_note_path(&folder, title), format!("# {}\n\n", title))
+        }
+    };
+
+    let relative = path
+        .strip_prefix(&vault)
+        .map_err(|_| "Note folder must be inside the vault".to_string())?
+        .to_string_lossy()
+        .replace('\\', "/");
+    match Remote::connect(args, &vault).await {
+        Some(remote) => {
+            let url = format!("{}/api/v1/notes/{}", remote.base, relative);
+            remote.call(remote.client.post(url).json(&serde_json::json!({ "content": content }))).await?;
+        }
+        None => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            crate::handlers::files::write_file_content(path.to_string_lossy().to_string(), content)?;
+        }
+    }
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn export(args: &Args) -> Result<(), String> {
+    let vault = vault(args)?;
+    let format: ExportFormat = serde_json::from_value(Value::String(args.get("format").unwrap_or("html").to_lowercase()))
+        .map_err(|_| "Format must be markdown, html or epub".to_string())?;
+    let name = vault.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "vault".to_string());
+    let output = match args.get("out") {
+        Some(out) => PathBuf::from(out),
+        None => {
+            let parent = vault.parent().unwrap_or(&vault);
+            match format {
+                ExportFormat::Epub => parent.join(format!("{}.epub", name)),
+                _ => parent.join(format!("{}-export", name)),
+            }
+        }
+    };
+    let options = WorkspaceExportOptions {
+        output_path: output.to_string_lossy().to_string(),
+        include_attachments: true,
+        keep_frontmatter: true,
+        title: None,
+        author: None,
+    };
+
+    let quiet = args.has("json");
+    let summary = run_export(&vault, format, &options, |progress| {
+        if !quiet && progress.phase != "done" {
+            eprint!("\r{} {}/{}", progress.phase, progress.current, progress.total);
+        }
+    })?;
+    if !quiet {
+        eprintln!();
+    }
+
+    if args.has("json") {
+        return print_json(&summary);
+    }
+    println!(
+        "Exported {} notes and {} attachments to {}",
+        summary.notes, summary.attachments, summary.output_path
+    );
+    if summary.unresolved_links > 0 {
+        println!("{} links could not be resolved", summary.unresolved_links);
+    }
+    Ok(())
+}
+
+fn sync(args: &Args) -> Result<(), String> {
+    use crate::sync::git;
+
+    if args.positional.get(1).map(String::as_str) != Some("git") {
+        return Err("Only `sync git` is supported".to_string());
+    }
+    let vault = vault(args)?;
+    if !git::is_repo(&vault) {
+        return Err(format!("{} is not a git repository", vault.display()));
+    }
+    match args.positional.get(2).map(String::as_str) {
+        Some("push") => {
+            git::push(&vault, None, None).map_err(|e| e.to_string())?;
+            println!("Pushed");
+        }
+        Some("pull") => {
+            git::pull(&vault, None, None).map_err(|e| e.to_string())?;
+            println!("Pulled");
+        }
+        Some("commit") => {
+            let message = args.get("message").unwrap_or("Update notes");
+            match git::commit_all(&vault, message)? {
+                Some(commit) => println!("Committed {}", commit),
+                None => println!("Nothing to commit"),
+            }
+        }
+        Some("status") => {
+            let status = git::status(&vault)?;
+            print_json(&status)?;
+        }
+        _ => return Err("Expected `sync git push|pull|commit|status`".to_string()),
+    }
+    Ok(())
+}
+
+/// The range `--due` asks for; `None` means no due date filter. "overdue" is
+/// handled separately since it shouldn't project recurring tasks backwards.
+fn due_range(due: Option<&str>, today: NaiveDate) -> Result<Option<(NaiveDate, NaiveDate)>, String> {
+    Ok(match due {
+        None => None,
+        Some("today") => Some((today, today)),
+        Some("tomorrow") => Some((today + Duration::days(1), today + Duration::days(1))),
+        Some("week") => Some((today, today + Duration::days(6))),
+        Some(date) => {
+            let date = crate::tasks::parse_day(date)?;
+            Some((date, date))
+        }
+    })
+}
+
+fn tasks(args: &Args) -> Result<(), String> {
+    if args.positional.get(1).map(String::as_str) != Some("list") {
+        return Err("Expected `tasks list`".to_string());
+    }
+    let store: TaskStore = match store_value(".tasks.dat", "tasks") {
+        Some(value) => serde_json::from_value(value).map_err(|e| format!("Failed to read tasks: {}", e))?,
+        None => TaskStore::default(),
+    };
+    let today = Local::now().date_naive();
+
+    let mut tasks: Vec<Task> = match args.get("due") {
+        Some("overdue") => store
+            .get_all_tasks()
+            .into_iter()
+            .filter(|t| crate::tasks::due_local(t).map_or(false, |due| due.date_naive() < today))
+            .cloned()
+            .collect(),
+        due => match due_range(due, today)? {
+            Some((start, end)) => crate::tasks::upcoming(&store, start, end),
+            None => store.get_all_tasks().into_iter().cloned().collect(),
+        },
+    };
+    if !args.has("all") {
+        tasks.retain(|t| !matches!(t.status, crate::tasks::TaskStatus::Completed | crate::tasks::TaskStatus::Cancelled));
+    }
+    if matches!(args.get("due"), None | Some("overdue")) {
+        tasks.sort_by(|a, b| a.due_date.is_none().cmp(&b.due_date.is_none()).then(a.due_date.cmp(&b.due_date)));
+    }
+
+    if args.has("json") {
+        return print_json(&tasks);
+    }
+    for task in &tasks {
+        let status = serde_json::to_value(&task.status).ok().and_then(|s| s.as_str().map(String::from)).unwrap_or_default();
+        let due = task.due_date.as_deref().map(|d| d.get(..10).unwrap_or(d)).unwrap_or("");
+        println!("[{:<11}] {:<10} {}", status, due, task.title);
+    }
+    Ok(())
+}
+
+/// Run the CLI with the arguments after the program name; returns the exit code
+pub fn main(raw: Vec<String>) -> i32 {
+    let args = match Args::parse(raw) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    let Some(command) = args.positional.first().cloned().filter(|_| !args.has("help")) else {
+        print!("{}", USAGE);
+        return if args.has("help") { 0 } else { 2 };
+    };
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start: {}", e);
+            return 1;
+        }
+    };
+    let result = runtime.block_on(async {
+        match command.as_str() {
+            "search" => search(&args).await,
+            "new-note" => new_note(&args).await,
+            "export" => export(&args),
+            "sync" => sync(&args),
+            "tasks" => tasks(&args),
+            other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+        }
+    });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("lokus: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Args {
+        Args::parse(raw.iter().map(|s| s.to_string()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args(&["search", "weekly", "review", "--limit", "5", "--json", "--vault=/tmp/v"]);
+        assert_eq!(parsed.positional, ["search", "weekly", "review"]);
+        assert_eq!(parsed.get("limit"), Some("5"));
+        assert_eq!(parsed.get("vault"), Some("/tmp/v"));
+        assert!(parsed.has("json"));
+
+        assert_eq!(args(&["sync", "git", "commit", "-m", "notes"]).get("message"), Some("notes"));
+        assert!(Args::parse(vec!["--limit".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_due_range() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        assert_eq!(due_range(Some("today"), today).unwrap(), Some((today, today)));
+        assert_eq!(
+            due_range(Some("week"), today).unwrap(),
+            Some((today, NaiveDate::from_ymd_opt(2026, 3, 16).unwrap()))
+        );
+        assert!(due_range(Some("someday"), today).is_err());
+        assert_eq!(due_range(None, today).unwrap(), None);
+    }
+}
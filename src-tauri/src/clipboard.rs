@@ -24,9 +24,13 @@ pub async fn clipboard_write_html(app: AppHandle, html: String) -> Result<(), St
 
 #[tauri::command]
 pub async fn clipboard_read_html(app: AppHandle) -> Result<String, String> {
-    app.clipboard()
-        .read_text()
-        .map_err(|e| e.to_string())
+    let text = app.clipboard().read_text().map_err(|e| e.to_string())?;
+    // The clipboard may hold plain text, which must come back unescaped
+    if crate::sanitize::looks_like_html(&text) {
+        Ok(crate::sanitize::sanitize_html(&text, crate::sanitize::Policy::Clipboard))
+    } else {
+        Ok(text)
+    }
 }
 
 #[tauri::command]
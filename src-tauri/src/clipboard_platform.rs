@@ -4,9 +4,19 @@
 /// capabilities while maintaining the existing Tauri clipboard API for compatibility.
 
 use crate::platform::clipboard::{ClipboardUtils, ClipboardPlatformInfo};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use tauri::AppHandle;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+/// Name given to pasted images when no pattern is passed
+const DEFAULT_IMAGE_PATTERN: &str = "Pasted image {datetime}";
+/// Name given to pasted files when no pattern is passed
+const DEFAULT_FILE_PATTERN: &str = "{name}";
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif", "heic", "tiff"];
+
 /// Enhanced clipboard operations with platform awareness
 pub struct PlatformAwareClipboard;
 
@@ -75,8 +85,8 @@ impl PlatformAwareClipboard {
         Ok(ClipboardContentInfo {
             has_text,
             has_html: platform_info.supports_html && has_text, // Simplified detection
-            has_images: false, // Would require additional detection logic
-            has_files: false,  // Would require additional detection logic
+            has_images: app.clipboard().read_image().is_ok(),
+            has_files: !Self::read_files(app).is_empty(),
             content_size_estimate: if has_text {
                 app.clipboard().read_text().map(|t| t.len()).unwrap_or(0)
             } else {
@@ -85,6 +95,40 @@ impl PlatformAwareClipboard {
         })
     }
     
+    /// Files copied in the OS file manager. Falls back to a `file://` URI
+    /// list or a single existing path copied as text.
+    pub fn read_files(app: &AppHandle) -> Vec<PathBuf> {
+        let files = clipboard_file_list();
+        if !files.is_empty() {
+            return files;
+        }
+        let text = app.clipboard().read_text().unwrap_or_default();
+        let files = parse_uri_list(&text);
+        if !files.is_empty() {
+            return files;
+        }
+        let path = PathBuf::from(text.trim());
+        if !text.contains('\n') && path.is_absolute() && path.is_file() {
+            return vec![path];
+        }
+        Vec::new()
+    }
+
+    /// Clipboard image as PNG bytes
+    pub fn read_image_png(app: &AppHandle) -> Result<Vec<u8>, String> {
+        let image = app
+            .clipboard()
+            .read_image()
+            .map_err(|e| format!("No image on the clipboard: {}", e))?;
+        let rgba = image::RgbaImage::from_raw(image.width(), image.height(), image.rgba().to_vec())
+            .ok_or("Clipboard image has an unexpected size")?;
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(rgba)
+            .write_to(&mut io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode clipboard image: {}", e))?;
+        Ok(png)
+    }
+
     /// Get platform-specific clipboard recommendations
     pub fn get_usage_tips() -> Vec<String> {
         ClipboardUtils::get_usage_recommendations()
@@ -133,6 +177,201 @@ pub struct ClipboardContentInfo {
     pub content_size_estimate: usize,
 }
 
+/// A file written to the workspace by `clipboard_paste_into_workspace`
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PastedFile {
+    pub path: String,
+    /// Relative to the workspace root
+    pub relative_path: String,
+    /// Markdown embed (images) or link (other files) for the note
+    pub embed: String,
+    pub optimized: Option<crate::images::ImageOptimizeResult>,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteResult {
+    pub files: Vec<PastedFile>,
+    /// Every embed, one per line, ready to insert
+    pub embed: String,
+}
+
+#[cfg(target_os = "macos")]
+fn clipboard_file_list() -> Vec<PathBuf> {
+    const SCRIPT: &str = "ObjC.import('AppKit'); \
+        const urls = $.NSPasteboard.generalPasteboard.readObjectsForClassesOptions($([$.NSURL]), $({})); \
+        const paths = []; \
+        for (let i = 0; i < urls.count; i++) { const url = urls.objectAtIndex(i); if (url.isFileURL) paths.push(url.path.js); } \
+        paths.join('\\n')";
+    command_lines(Command::new("osascript").args(["-l", "JavaScript", "-e", SCRIPT]))
+}
+
+#[cfg(windows)]
+fn clipboard_file_list() -> Vec<PathBuf> {
+    command_lines(Command::new("powershell").args([
+        "-NoProfile",
+        "-Command",
+        "Get-Clipboard -Format FileDropList | ForEach-Object { $_.FullName }",
+    ]))
+}
+
+/// Wayland first, then X11; both hand out a `text/uri-list`
+#[cfg(target_os = "linux")]
+fn clipboard_file_list() -> Vec<PathBuf> {
+    let commands: [(&str, &[&str]); 2] = [
+        ("wl-paste", &["--no-newline", "--type", "text/uri-list"]),
+        ("xclip", &["-selection", "clipboard", "-t", "text/uri-list", "-o"]),
+    ];
+    for (program, args) in commands {
+        match Command::new(program).args(args).output() {
+            Ok(output) if output.status.success() => {
+                let files = parse_uri_list(&String::from_utf8_lossy(&output.stdout));
+                if !files.is_empty() {
+                    return files;
+                }
+            }
+            _ => {}
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+fn clipboard_file_list() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn command_lines(command: &mut Command) -> Vec<PathBuf> {
+    match command.output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| PathBuf::from(line.trim()))
+            .filter(|path| path.is_file())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Local files in a `text/uri-list` (comments and remote URLs are skipped)
+fn parse_uri_list(text: &str) -> Vec<PathBuf> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("file://"))
+        .filter_map(|line| url::Url::parse(line).ok()?.to_file_path().ok())
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Expand `{name}`, `{date}`, `{time}` and `{datetime}` and drop characters
+/// that aren't allowed in file names
+fn apply_pattern(pattern: &str, name: &str, now: chrono::DateTime<chrono::Local>) -> String {
+    let expanded = pattern
+        .replace("{name}", name)
+        .replace("{datetime}", &now.format("%Y%m%d%H%M%S").to_string())
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string());
+    let cleaned: String = expanded
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { '-' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.').to_string();
+    if cleaned.is_empty() { "Pasted file".to_string() } else { cleaned }
+}
+
+/// Create `<stem>.<ext>` in `dir`, or `<stem>-1.<ext>` and so on when taken.
+/// Creating rather than checking keeps two pastes from picking the same name.
+fn create_unique(dir: &Path, stem: &str, ext: &str) -> Result<(PathBuf, fs::File), String> {
+    for counter in 0..1000 {
+        let name = match (counter, ext.is_empty()) {
+            (0, true) => stem.to_string(),
+            (0, false) => format!("{}.{}", stem, ext),
+            (n, true) => format!("{}-{}", stem, n),
+            (n, false) => format!("{}-{}.{}", stem, n, ext),
+        };
+        let path = dir.join(name);
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create {}: {}", path.display(), e)),
+        }
+    }
+    Err(format!("No free file name for {} in {}", stem, dir.display()))
+}
+
+fn embed_for(root: &Path, path: &Path, note_path: Option<&Path>) -> Result<(String, String), String> {
+    let rel = crate::links::relative_path(root, path).ok_or("Pasted file is outside the workspace")?;
+    let from_dir = note_path
+        .and_then(|note| crate::links::relative_path(root, note))
+        .map(|note| crate::links::parent_dir(&note).to_string())
+        .unwrap_or_default();
+    let target = crate::links::relative_link(&from_dir, &rel).replace(' ', "%20");
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let is_image = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+    let embed = if is_image { format!("![]({})", target) } else { format!("[{}]({})", name, target) };
+    Ok((rel, embed))
+}
+
+fn paste_into(
+    root: &Path,
+    dir: &Path,
+    files: Vec<PathBuf>,
+    image: Option<Vec<u8>>,
+    naming_pattern: Option<&str>,
+    note_path: Option<&Path>,
+    optimize: Option<&crate::images::ImageOptions>,
+) -> Result<PasteResult, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let now = chrono::Local::now();
+    let mut written = Vec::new();
+
+    for source in &files {
+        let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let ext = source.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+        let name = apply_pattern(naming_pattern.unwrap_or(DEFAULT_FILE_PATTERN), &stem, now);
+        let (path, mut file) = create_unique(dir, &name, &ext)?;
+        let copied = fs::File::open(source).and_then(|mut from| io::copy(&mut from, &mut file));
+        if let Err(e) = copied {
+            let _ = fs::remove_file(&path);
+            return Err(format!("Failed to copy {}: {}", source.display(), e));
+        }
+        written.push(path);
+    }
+    if let Some(png) = image {
+        let name = apply_pattern(naming_pattern.unwrap_or(DEFAULT_IMAGE_PATTERN), "Pasted image", now);
+        let (path, mut file) = create_unique(dir, &name, "png")?;
+        if let Err(e) = io::Write::write_all(&mut file, &png) {
+            let _ = fs::remove_file(&path);
+            return Err(format!("Failed to write {}: {}", path.display(), e));
+        }
+        written.push(path);
+    }
+
+    let mut pasted = Vec::new();
+    for path in written {
+        let optimized = match optimize.filter(|_| crate::images::is_optimizable(&path)) {
+            Some(options) => match crate::images::optimize(&path, options) {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    eprintln!("[Clipboard] Failed to optimize {}: {}", path.display(), e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let path = optimized.as_ref().map_or(path, |o| PathBuf::from(&o.path));
+        let (relative_path, embed) = embed_for(root, &path, note_path)?;
+        pasted.push(PastedFile { path: path.to_string_lossy().to_string(), relative_path, embed, optimized });
+    }
+
+    let embed = pasted.iter().map(|f| f.embed.as_str()).collect::<Vec<_>>().join("\n");
+    Ok(PasteResult { files: pasted, embed })
+}
+
 // --- Enhanced Tauri Commands ---
 
 #[tauri::command]
@@ -175,6 +414,45 @@ pub async fn clipboard_clear_enhanced(app: AppHandle) -> Result<(), String> {
         })
 }
 
+/// Save the files or image on the clipboard into the workspace and return
+/// the markdown to embed them. Files go to `target_folder` (relative to the
+/// workspace, or absolute), defaulting to the attachments folder; links are
+/// relative to `note_path` when given. Images are optimized when `optimize`
+/// is set or the workspace has `auto_optimize_images` on.
+#[tauri::command]
+pub async fn clipboard_paste_into_workspace(
+    app: AppHandle,
+    target_folder: Option<String>,
+    naming_pattern: Option<String>,
+    workspace_path: Option<String>,
+    note_path: Option<String>,
+    optimize: Option<bool>,
+) -> Result<PasteResult, String> {
+    let root = workspace_path
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(&app))
+        .ok_or("No workspace is open")?;
+    let folder = target_folder.unwrap_or_else(|| crate::attachments::configured_folder(&app));
+    let dir = if Path::new(&folder).is_absolute() { PathBuf::from(&folder) } else { root.join(folder.trim_matches('/')) };
+    if !dir.starts_with(&root) {
+        return Err("Target folder must be inside the workspace".to_string());
+    }
+
+    let files = PlatformAwareClipboard::read_files(&app);
+    let image = if files.is_empty() { Some(PlatformAwareClipboard::read_image_png(&app)?) } else { None };
+    let options = match optimize {
+        Some(false) => None,
+        Some(true) => Some(crate::images::import_options(&root).unwrap_or_default()),
+        None => crate::images::import_options(&root),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        paste_into(&root, &dir, files, image, naming_pattern.as_deref(), note_path.as_deref().map(Path::new), options.as_ref())
+    })
+    .await
+    .map_err(|e| format!("Paste failed: {}", e))?
+}
+
 /// Initialize platform-aware clipboard
 pub fn initialize() -> Result<(), String> {
     // Verify clipboard availability
@@ -213,6 +491,38 @@ mod tests {
         // Length check is redundant since Vec::len() is always >= 0
     }
     
+    #[test]
+    fn test_uri_lists_and_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("My Report.pdf");
+        fs::write(&file, "pdf").unwrap();
+        let url = url::Url::from_file_path(&file).unwrap();
+        let list = format!("# copied\r\n{}\r\nhttps://example.com/x.png\r\n", url);
+        assert_eq!(parse_uri_list(&list), vec![file]);
+
+        let now = chrono::Local::now();
+        assert_eq!(apply_pattern("{name}", "a/b:c", now), "a-b-c");
+        assert_eq!(apply_pattern("{date}", "x", now), now.format("%Y-%m-%d").to_string());
+        assert_eq!(apply_pattern("..", "x", now), "Pasted file");
+    }
+
+    #[test]
+    fn test_paste_names_and_embeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("vault");
+        let source = dir.path().join("My Report.pdf");
+        fs::write(&source, "pdf").unwrap();
+        let attachments = root.join("attachments");
+        let note = root.join("notes/Today.md");
+
+        let first = paste_into(&root, &attachments, vec![source.clone()], None, None, Some(&note), None).unwrap();
+        assert_eq!(first.embed, "[My Report.pdf](../attachments/My%20Report.pdf)");
+        let second = paste_into(&root, &attachments, vec![source], Some(vec![1, 2, 3]), None, None, None).unwrap();
+        assert_eq!(second.files[0].relative_path, "attachments/My Report-1.pdf");
+        assert!(second.files[1].embed.starts_with("![](attachments/Pasted%20image%20"));
+        assert_eq!(fs::read(&second.files[1].path).unwrap(), vec![1, 2, 3]);
+    }
+
     #[test]
     fn test_initialization() {
         let result = initialize();
@@ -0,0 +1,206 @@
+//! Readability-style main content extraction.
+//!
+//! Paragraph-like elements score their parent (and, at half weight, their
+//! grandparent) by length and comma count; class and id names nudge the
+//! score, and link-heavy containers are discounted. The best container is
+//! re-serialized without scripts, navigation, forms and other page chrome.
+
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+/// Elements dropped from the extracted content
+const REMOVED: &[&str] = &[
+    "script", "style", "noscript", "nav", "footer", "aside", "form", "button", "input", "select", "textarea", "iframe",
+    "svg", "canvas", "template", "dialog",
+];
+const VOID: &[&str] = &["img", "br", "hr", "source", "col", "wbr", "area", "embed", "track", "meta", "link", "input"];
+const POSITIVE: &[&str] = &["article", "body", "content", "entry", "main", "page", "post", "story", "text", "blog"];
+const NEGATIVE: &[&str] = &[
+    "comment", "sidebar", "footer", "footnote-nav", "nav", "menu", "share", "social", "related", "promo", "banner",
+    "advert", "sponsor", "popup", "cookie", "newsletter", "subscribe", "breadcrumb", "masthead", "widget",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Article {
+    pub title: String,
+    pub byline: Option<String>,
+    pub site_name: Option<String>,
+    pub published: Option<String>,
+    pub excerpt: Option<String>,
+    /// Cleaned HTML of the main content
+    pub html: String,
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("valid selector")
+}
+
+fn meta(document: &Html, css: &str) -> Option<String> {
+    document
+        .select(&selector(css))
+        .filter_map(|el| el.value().attr("content"))
+        .map(|content| content.trim().to_string())
+        .find(|content| !content.is_empty())
+}
+
+fn class_weight(el: &ElementRef) -> f64 {
+    let names = format!("{} {}", el.value().attr("class").unwrap_or(""), el.value().attr("id").unwrap_or("")).to_lowercase();
+    let mut weight = 0.0;
+    if POSITIVE.iter().any(|p| names.contains(p)) {
+        weight += 25.0;
+    }
+    if NEGATIVE.iter().any(|n| names.contains(n)) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+fn text_len(el: &ElementRef) -> usize {
+    el.text().map(|t| t.trim().len()).sum()
+}
+
+fn link_density(el: &ElementRef) -> f64 {
+    let total = text_len(el).max(1);
+    let linked: usize = el.select(&selector("a")).map(|a| text_len(&a)).sum();
+    linked as f64 / total as f64
+}
+
+fn is_removed(el: &ElementRef) -> bool {
+    let name = el.value().name();
+    REMOVED.contains(&name)
+        || el.value().attr("hidden").is_some()
+        || el.value().attr("aria-hidden") == Some("true")
+        || (name != "body" && name != "article" && name != "main" && class_weight(el) < 0.0)
+}
+
+/// Pick the element most likely to hold the article body
+fn best_candidate<'a>(document: &'a Html) -> Option<ElementRef<'a>> {
+    let mut scores: HashMap<_, (ElementRef<'a>, f64)> = HashMap::new();
+    for paragraph in document.select(&selector("p, pre, td, blockquote")) {
+        let len = text_len(&paragraph);
+        if len < 25 {
+            continue;
+        }
+        let text: String = paragraph.text().collect();
+        let score = 1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0);
+
+        let parent = paragraph.parent().and_then(ElementRef::wrap);
+        let grandparent = parent.and_then(|p| p.parent()).and_then(ElementRef::wrap);
+        for (ancestor, share) in [(parent, 1.0), (grandparent, 0.5)] {
+            let Some(ancestor) = ancestor else { continue };
+            let entry = scores.entry(ancestor.id()).or_insert_with(|| (ancestor, class_weight(&ancestor)));
+            entry.1 += score * share;
+        }
+    }
+
+    scores
+        .into_values()
+        .map(|(el, score)| (el, score * (1.0 - link_density(&el))))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(el, _)| el)
+}
+
+fn escape(text: &str, attribute: bool) -> String {
+    let escaped = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    if attribute {
+        escaped.replace('"', "&quot;")
+    } else {
+        escaped
+    }
+}
+
+/// Re-serialize `el` without page chrome, promoting lazy-loaded image sources
+fn serialize(el: ElementRef, out: &mut String) {
+    let name = el.value().name();
+    out.push('<');
+    out.push_str(name);
+    let lazy_src = ["data-src", "data-original", "data-lazy-src"].iter().find_map(|a| el.value().attr(a));
+    for (attr, value) in el.value().attrs() {
+        let value = match (attr, lazy_src) {
+            ("src", Some(lazy)) if name == "img" => lazy,
+            _ => value,
+        };
+        out.push_str(&format!(" {}=\"{}\"", attr, escape(value, true)));
+    }
+    if name == "img" && el.value().attr("src").is_none() {
+        if let Some(lazy) = lazy_src {
+            out.push_str(&format!(" src=\"{}\"", escape(lazy, true)));
+        }
+    }
+    out.push('>');
+    if VOID.contains(&name) {
+        return;
+    }
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(&escape(text, false)),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child).filter(|c| !is_removed(c)) {
+                    serialize(child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+    out.push_str(&format!("</{}>", name));
+}
+
+pub fn extract(html: &str) -> Article {
+    let document = Html::parse_document(html);
+    let heading = document
+        .select(&selector("h1"))
+        .map(|h| h.text().collect::<String>().trim().to_string())
+        .find(|h| !h.is_empty());
+    let title_tag = document
+        .select(&selector("title"))
+        .map(|t| t.text().collect::<String>().trim().to_string())
+        .find(|t| !t.is_empty());
+    let title = meta(&document, r#"meta[property="og:title"]"#)
+        .or(heading)
+        .or(title_tag)
+        .unwrap_or_default();
+
+    let body = document.select(&selector("body")).next().unwrap_or_else(|| document.root_element());
+    let content = best_candidate(&document)
+        .or_else(|| document.select(&selector("article, main, [role=main]")).next())
+        .unwrap_or(body);
+    let mut cleaned = String::new();
+    serialize(content, &mut cleaned);
+
+    Article {
+        title,
+        byline: meta(&document, r#"meta[name="author"], meta[property="article:author"]"#),
+        site_name: meta(&document, r#"meta[property="og:site_name"]"#),
+        published: meta(&document, r#"meta[property="article:published_time"], meta[name="date"]"#),
+        excerpt: meta(&document, r#"meta[name="description"], meta[property="og:description"]"#),
+        html: cleaned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_picks_article_over_chrome() {
+        let paragraph = "<p>Rust makes systems programming safer, and the borrow checker, while strict, catches whole classes of bugs before they ship.</p>";
+        let html = format!(
+            r#"<html><head><title>Post | Blog</title><meta property="og:site_name" content="Blog"><meta name="author" content="Ada"></head>
+            <body><nav><a href="/">Home</a><a href="/about">About</a></nav>
+            <div class="sidebar"><p>Subscribe to our newsletter, it is great, really, trust us on this one.</p></div>
+            <div class="post-content"><h1>Safer systems</h1>{p}{p}<script>track()</script><img data-src="/a.png" src="data:,"><div class="share">Share this</div></div>
+            <footer>© Blog</footer></body></html>"#,
+            p = paragraph
+        );
+        let article = extract(&html);
+        assert_eq!(article.title, "Safer systems");
+        assert_eq!(article.byline.as_deref(), Some("Ada"));
+        assert_eq!(article.site_name.as_deref(), Some("Blog"));
+        assert!(article.html.starts_with("<div class=\"post-content\">"));
+        assert!(article.html.contains("borrow checker"));
+        assert!(article.html.contains(r#" src="/a.png""#) && !article.html.contains("data:,"));
+        for dropped in ["track()", "Share this", "newsletter", "Home", "©"] {
+            assert!(!article.html.contains(dropped), "{} should be removed", dropped);
+        }
+    }
+}
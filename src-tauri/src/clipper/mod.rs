@@ -0,0 +1,360 @@
+//! Web clipper.
+//!
+//! Pages arrive from the browser extension, which posts the page URL and HTML
+//! to `POST /api/v1/clip` on the API server, or through `clip_url`, which
+//! fetches the page itself. The main content is extracted (see `extract`),
+//! converted with the importers' HTML-to-Markdown converter and saved in the
+//! clippings folder, with the source, author and dates in frontmatter.
+//! Images are downloaded into the attachments folder; any that can't be
+//! fetched keep their remote URL.
+
+pub mod extract;
+
+use crate::import::html::{html_to_markdown, Reference};
+use crate::sanitize::{sanitize_html, Policy};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use url::Url;
+
+const DEFAULT_FOLDER: &str = "Clippings";
+const CLIPPED_EVENT: &str = "web-clip-saved";
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; Lokus Web Clipper)";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_PAGE_BYTES: usize = 10 * 1024 * 1024;
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+const PARALLEL_DOWNLOADS: usize = 4;
+/// Stands in for an image link until we know where the image ended up
+const IMAGE_PLACEHOLDER: &str = "lokus-clip-image-";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClipOptions {
+    /// Workspace-relative folder for the note, `Clippings` by default
+    pub folder: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    /// The HTML is a selection: keep all of it rather than extracting the article
+    pub selection: bool,
+    pub download_images: bool,
+}
+
+impl Default for ClipOptions {
+    fn default() -> Self {
+        Self { folder: None, title: None, tags: Vec::new(), selection: false, download_images: true }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipRequest {
+    pub url: String,
+    pub html: String,
+    #[serde(flatten)]
+    pub options: ClipOptions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipResult {
+    pub path: String,
+    pub title: String,
+    pub images: usize,
+    /// Images left as remote links because they couldn't be downloaded
+    pub failed_images: usize,
+}
+
+struct Converted {
+    article: extract::Article,
+    markdown: String,
+    /// Absolute image URLs, in placeholder order
+    images: Vec<String>,
+}
+
+fn convert(url: &Url, html: &str, options: &ClipOptions) -> Converted {
+    let mut article = if options.selection {
+        extract::Article { html: html.to_string(), ..Default::default() }
+    } else {
+        extract::extract(html)
+    };
+    // After extraction, which scores the page's own markup
+    article.html = sanitize_html(&article.html, Policy::WebClip);
+
+    let mut images = Vec::new();
+    let conversion = html_to_markdown(&article.html, &mut |reference| match reference {
+        Reference::Image(src) => {
+            let absolute = url.join(src.trim()).ok()?;
+            if !matches!(absolute.scheme(), "http" | "https" | "data") {
+                return None;
+            }
+            images.push(absolute.to_string());
+            Some(format!("{}{}", IMAGE_PLACEHOLDER, images.len() - 1))
+        }
+        Reference::Link(href) if !href.starts_with('#') => url.join(href.trim()).ok().map(|u| u.to_string()),
+        _ => None,
+    });
+    Converted { article, markdown: conversion.markdown, images }
+}
+
+/// Path from a note in `from_dir` to `to`, both workspace-relative, as a
+/// markdown link target
+pub(crate) fn relative_link(from_dir: &str, to: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|s| !s.is_empty()).collect();
+    let to: Vec<&str> = to.split('/').filter(|s| !s.is_empty()).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<&str> = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/").replace(' ', "%20")
+}
+
+fn safe_folder(folder: &str) -> Result<String, String> {
+    let folder = folder.trim().trim_matches('/');
+    if Path::new(folder).components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid folder: {}", folder));
+    }
+    Ok(folder.to_string())
+}
+
+/// `dir/stem.ext`, or `dir/stem 2.ext` and so on if that's taken
+pub(crate) fn available_path(dir: &Path, stem: &str, ext: &str) -> PathBuf {
+    (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{}.{}", stem, ext)),
+            n => dir.join(format!("{} {}.{}", stem, n, ext)),
+        })
+        .find(|path| !path.exists())
+        .expect("unbounded range")
+}
+
+fn image_extension(url: &str, content_type: Option<&str>) -> Option<&'static str> {
+    const KNOWN: &[(&str, &str)] = &[
+        ("png", "image/png"),
+        ("jpg", "image/jpeg"),
+        ("jpeg", "image/jpeg"),
+        ("gif", "image/gif"),
+        ("webp", "image/webp"),
+        ("svg", "image/svg+xml"),
+        ("avif", "image/avif"),
+        ("bmp", "image/bmp"),
+    ];
+    let mime = content_type.map(|c| c.split(';').next().unwrap_or("").trim().to_ascii_lowercase());
+    if let Some(mime) = mime.filter(|m| m.starts_with("image/")) {
+        return KNOWN.iter().find(|(_, m)| *m == mime).map(|(ext, _)| *ext);
+    }
+    let path = Url::parse(url).ok()?.path().to_ascii_lowercase();
+    let ext = path.rsplit_once('.')?.1.to_string();
+    KNOWN.iter().find(|(known, _)| *known == ext).map(|(known, _)| *known)
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Fetch (or decode) one image, returning its bytes and extension
+async fn fetch_image(client: &reqwest::Client, url: &str) -> Result<(Vec<u8>, &'static str), String> {
+    if let Some(data) = url.strip_prefix("data:") {
+        use base64::Engine as _;
+        let (header, payload) = data.split_once(',').ok_or("Malformed data URL")?;
+        let ext = image_extension("", Some(header.split(';').next().unwrap_or(""))).ok_or("Not an image")?;
+        if !header.ends_with(";base64") {
+            return Err("Only base64 data URLs are supported".to_string());
+        }
+        let bytes = base64::engine::general_purpose::STANDARD.decode(payload).map_err(|e| e.to_string())?;
+        return Ok((bytes, ext));
+    }
+
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    if response.content_length().map_or(false, |len| len as usize > MAX_IMAGE_BYTES) {
+        return Err("Image too large".to_string());
+    }
+    let content_type = response.headers().get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let ext = image_extension(url, content_type.as_deref()).ok_or("Not an image")?;
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err("Image too large".to_string());
+    }
+    Ok((bytes.to_vec(), ext))
+}
+
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn render_note(url: &Url, title: &str, article: &extract::Article, tags: &[String], markdown: &str) -> String {
+    let mut note = String::from("---\n");
+    note.push_str(&format!("title: {}\n", yaml_string(title)));
+    note.push_str(&format!("source: {}\n", yaml_string(url.as_str())));
+    if let Some(author) = &article.byline {
+        note.push_str(&format!("author: {}\n", yaml_string(author)));
+    }
+    if let Some(site) = &article.site_name {
+        note.push_str(&format!("site: {}\n", yaml_string(site)));
+    }
+    if let Some(published) = &article.published {
+        note.push_str(&format!("published: {}\n", yaml_string(published)));
+    }
+    note.push_str(&format!("clipped: {}\n", chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)));
+    let mut all_tags = vec!["clipping".to_string()];
+    all_tags.extend(tags.iter().map(|t| t.trim().trim_start_matches('#').to_string()).filter(|t| !t.is_empty() && t != "clipping"));
+    let tags: Vec<String> = all_tags.iter().map(|t| yaml_string(t)).collect();
+    note.push_str(&format!("tags: [{}]\n---\n\n", tags.join(", ")));
+
+    // Extracted articles usually carry their own heading
+    if !markdown.trim_start().starts_with("# ") {
+        note.push_str(&format!("# {}\n\n", title));
+    }
+    note.push_str(markdown.trim());
+    note.push('\n');
+    note
+}
+
+/// Convert a page and save it as a note in the workspace at `root`
+pub async fn clip_html(app: &AppHandle, root: &Path, url: &str, html: String, options: ClipOptions) -> Result<ClipResult, String> {
+    let url = Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    let folder = safe_folder(options.folder.as_deref().unwrap_or(DEFAULT_FOLDER))?;
+    let (page_url, page_options) = (url.clone(), options.clone());
+    let converted = tokio::task::spawn_blocking(move || convert(&page_url, &html, &page_options))
+        .await
+        .map_err(|e| format!("Conversion failed: {}", e))?;
+
+    let title = options
+        .title
+        .clone()
+        .or_else(|| Some(converted.article.title.clone()))
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "Untitled".to_string());
+    let stem = crate::import::sanitize_file_name(&title);
+    let note_dir = root.join(&folder);
+    std::fs::create_dir_all(&note_dir).map_err(|e| format!("Failed to create folder: {}", e))?;
+    let note_path = available_path(&note_dir, &stem, "md");
+
+    // Resolve every image placeholder to a downloaded file or the remote URL
+    let mut markdown = converted.markdown;
+    let mut failed = 0;
+    let targets: Vec<Option<String>> = if options.download_images && !converted.images.is_empty() {
+        let attachments = crate::attachments::configured_folder(app);
+        let attachments_dir = root.join(&attachments);
+        std::fs::create_dir_all(&attachments_dir).map_err(|e| format!("Failed to create attachments folder: {}", e))?;
+        let client = http_client()?;
+        futures::stream::iter(converted.images.iter().enumerate())
+            .map(|(i, image)| {
+                let (client, dir, stem, attachments, folder) = (&client, &attachments_dir, &stem, &attachments, &folder);
+                async move {
+                    match fetch_image(client, image).await {
+                        Ok((bytes, ext)) => {
+                            let path = available_path(dir, &format!("{}-{}", stem, i + 1), ext);
+                            if let Err(e) = std::fs::write(&path, bytes) {
+                                tracing::warn!("Failed to save clipped image: {}", e);
+                                return None;
+                            }
+                            let name = path.file_name()?.to_string_lossy().to_string();
+                            Some(relative_link(folder, &format!("{}/{}", attachments, name)))
+                        }
+                        Err(e) => {
+                            tracing::debug!("Keeping remote image {}: {}", image, e);
+                            None
+                        }
+                    }
+                }
+            })
+            .buffered(PARALLEL_DOWNLOADS)
+            .collect()
+            .await
+    } else {
+        vec![None; converted.images.len()]
+    };
+    // Highest index first so `-1` doesn't clobber `-10`
+    for (i, target) in targets.iter().enumerate().rev() {
+        let target = match target {
+            Some(local) => local.clone(),
+            None => {
+                failed += usize::from(options.download_images);
+                converted.images[i].replace(' ', "%20")
+            }
+        };
+        markdown = markdown.replace(&format!("({}{})", IMAGE_PLACEHOLDER, i), &format!("({})", target));
+    }
+
+    let note = render_note(&url, &title, &converted.article, &options.tags, &markdown);
+    let path = note_path.to_string_lossy().to_string();
+    crate::handlers::files::write_file_content(path.clone(), note)?;
+
+    let result = ClipResult { path, title, images: converted.images.len(), failed_images: failed };
+    if let Err(e) = app.emit(CLIPPED_EVENT, &result) {
+        tracing::warn!("Failed to emit {}: {}", CLIPPED_EVENT, e);
+    }
+    Ok(result)
+}
+
+async fn fetch_page(url: &str) -> Result<String, String> {
+    let response = http_client()?
+        .get(url)
+        .header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch page: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("The page returned {}", response.status()));
+    }
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map_or(true, |v| v.contains("html"));
+    if !is_html {
+        return Err("That URL isn't a web page".to_string());
+    }
+    if response.content_length().map_or(false, |len| len as usize > MAX_PAGE_BYTES) {
+        return Err("The page is too large to clip".to_string());
+    }
+    let html = response.text().await.map_err(|e| format!("Failed to read page: {}", e))?;
+    if html.len() > MAX_PAGE_BYTES {
+        return Err("The page is too large to clip".to_string());
+    }
+    Ok(html)
+}
+
+// --- Tauri Commands ---
+
+/// Fetch a page and save its main content as a note
+#[tauri::command]
+pub async fn clip_url(app: AppHandle, workspace_path: String, url: String, options: Option<ClipOptions>) -> Result<ClipResult, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Only http and https pages can be clipped".to_string());
+    }
+    let html = fetch_page(parsed.as_str()).await?;
+    clip_html(&app, Path::new(&workspace_path), parsed.as_str(), html, options.unwrap_or_default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_resolves_links_and_images() {
+        let url = Url::parse("https://example.com/blog/post.html").unwrap();
+        let html = r##"<body><p>See <a href="../about">about</a> and <a href="#top">top</a>.</p><p><img src="img/a.png" alt="A"></p></body>"##;
+        let converted = convert(&url, html, &ClipOptions { selection: true, ..Default::default() });
+        assert_eq!(converted.images, vec!["https://example.com/blog/img/a.png".to_string()]);
+        assert!(converted.markdown.contains("[about](https://example.com/about)"));
+        assert!(converted.markdown.contains("[top](#top)"));
+        assert!(converted.markdown.contains("![A](lokus-clip-image-0)"));
+
+        assert_eq!(relative_link("Clippings", "attachments/My Post-1.png"), "../attachments/My%20Post-1.png");
+        assert_eq!(relative_link("", "attachments/a.png"), "attachments/a.png");
+        assert_eq!(image_extension("https://x/y.JPEG?w=1", None), Some("jpeg"));
+        assert_eq!(image_extension("https://x/y", Some("image/jpeg; q=1")), Some("jpg"));
+        assert!(safe_folder("../outside").is_err());
+    }
+}
@@ -1,5 +1,6 @@
-use tauri::{State, Manager};
+use tauri::{AppHandle, State, Manager};
 use crate::connections::manager::ConnectionManager;
+use crate::connections::gmail::outbox::{self, OutboxKind};
 use crate::connections::gmail::models::{
     GmailProfile, EmailMessage, EmailComposer, EmailLabel, 
     EmailSearchOptions, EmailListOptions, EmailAddress
@@ -160,7 +161,7 @@ pub async fn gmail_send_email(
     bodyHtml: Option<String>,  // Changed from body_html to bodyHtml to match JS
     cc: Option<Vec<EmailAddress>>,
     bcc: Option<Vec<EmailAddress>>,
-    connection_manager: State<'_, ConnectionManager>,
+    app: AppHandle,
 ) -> Result<String, String> {
 
     let composer = EmailComposer {
@@ -175,10 +176,7 @@ pub async fn gmail_send_email(
         references: None,
     };
     
-    connection_manager
-        .send_email(composer)
-        .await
-        .map_err(|e| e.to_string())
+    outbox::send_or_queue(&app, OutboxKind::Send, composer).await
 }
 
 #[tauri::command]
@@ -190,7 +188,7 @@ pub async fn gmail_reply_email(
     bodyText: Option<String>,
     bodyHtml: Option<String>,
     cc: Option<Vec<EmailAddress>>,
-    connection_manager: State<'_, ConnectionManager>,
+    app: AppHandle,
 ) -> Result<String, String> {
     let composer = EmailComposer {
         to,
@@ -204,10 +202,7 @@ pub async fn gmail_reply_email(
         references: None,
     };
     
-    connection_manager
-        .reply_to_email(&message_id, composer)
-        .await
-        .map_err(|e| e.to_string())
+    outbox::send_or_queue(&app, OutboxKind::Reply { message_id }, composer).await
 }
 
 #[tauri::command]
@@ -218,7 +213,7 @@ pub async fn gmail_forward_email(
     subject: String,
     bodyText: Option<String>,
     bodyHtml: Option<String>,
-    connection_manager: State<'_, ConnectionManager>,
+    app: AppHandle,
 ) -> Result<String, String> {
     let composer = EmailComposer {
         to,
@@ -232,10 +227,7 @@ pub async fn gmail_forward_email(
         references: None,
     };
     
-    connection_manager
-        .forward_email(&message_id, composer)
-        .await
-        .map_err(|e| e.to_string())
+    outbox::send_or_queue(&app, OutboxKind::Forward { message_id }, composer).await
 }
 
 // Email management commands
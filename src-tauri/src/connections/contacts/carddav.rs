@@ -0,0 +1,181 @@
+//! CardDAV (RFC 6352) address books: iCloud, Fastmail, Nextcloud, Radicale.
+
+use super::vcard::parse_vcards;
+use super::{CardDavAccount, Contact, ContactSource};
+use reqwest::{Client, Method, StatusCode};
+
+const MAX_REDIRECTS: u32 = 10;
+
+fn client() -> Result<Client, String> {
+    // Redirects are followed by hand; reqwest drops the auth header across hosts
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Send a WebDAV request, following redirects; returns the final URL and body
+async fn dav(account: &CardDavAccount, method: &[u8], url: &str, depth: &str, body: &str) -> Result<(String, String), String> {
+    let client = client()?;
+    let mut current = url.to_string();
+    for _ in 0..=MAX_REDIRECTS {
+        let response = client
+            .request(Method::from_bytes(method).unwrap(), &current)
+            .basic_auth(&account.username, Some(&account.password))
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .header("Depth", depth)
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        let status = response.status();
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get("location")
+                .and_then(|l| l.to_str().ok())
+                .ok_or_else(|| "Redirect without a location".to_string())?;
+            current = resolve(&current, location)?;
+            continue;
+        }
+        if status == StatusCode::UNAUTHORIZED {
+            return Err("CardDAV server rejected the username or password".to_string());
+        }
+        if !status.is_success() {
+            return Err(format!("CardDAV request failed: {}", status));
+        }
+        let text = response.text().await.map_err(|e| format!("Network error: {}", e))?;
+        return Ok((current, text));
+    }
+    Err("Too many redirects".to_string())
+}
+
+fn resolve(base: &str, href: &str) -> Result<String, String> {
+    url::Url::parse(base)
+        .and_then(|base| base.join(href))
+        .map(|url| url.to_string())
+        .map_err(|e| format!("Invalid URL '{}': {}", href, e))
+}
+
+/// Start offsets of `<name` / `<prefix:name` elements, whatever the prefix
+fn element_starts(xml: &str, name: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut from = 0;
+    while let Some(pos) = xml[from..].find('<') {
+        let start = from + pos;
+        let tag = &xml[start + 1..];
+        let tag_name: &str = tag.split(|c: char| c.is_whitespace() || c == '>' || c == '/').next().unwrap_or("");
+        if tag_name.rsplit(':').next() == Some(name) {
+            starts.push(start);
+        }
+        from = start + 1;
+    }
+    starts
+}
+
+/// Text content of the first `name` element in `xml`
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    let start = *element_starts(xml, name).first()?;
+    let open_end = start + xml[start..].find('>')?;
+    if xml[..open_end].ends_with('/') {
+        return None;
+    }
+    let content = &xml[open_end + 1..];
+    let close = content.find("</")?;
+    let text = content[..close].trim();
+    let text = text
+        .strip_prefix("<![CDATA[")
+        .and_then(|t| t.strip_suffix("]]>"))
+        .map(|t| t.to_string())
+        .unwrap_or_else(|| unescape_xml(text));
+    Some(text).filter(|t| !t.is_empty())
+}
+
+/// Text of the first `href` inside the first `parent` element
+fn nested_href(xml: &str, parent: &str) -> Option<String> {
+    let start = *element_starts(xml, parent).first()?;
+    element_text(&xml[start..], "href")
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#13;", "\r")
+        .replace("&#xD;", "\r")
+        .replace("&amp;", "&")
+}
+
+/// The `<response>` elements of a multistatus body
+fn responses(xml: &str) -> Vec<&str> {
+    let starts = element_starts(xml, "response");
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| &xml[start..starts.get(i + 1).copied().unwrap_or(xml.len())])
+        .collect()
+}
+
+async fn find_home(account: &CardDavAccount) -> Result<String, String> {
+    let principal_body = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:propfind xmlns:D="DAV:"><D:prop><D:current-user-principal/></D:prop></D:propfind>"#;
+
+    let base = account.server_url.trim_end_matches('/');
+    let well_known = resolve(base, "/.well-known/carddav")?;
+    let (url, xml) = match dav(account, b"PROPFIND", &well_known, "0", principal_body).await {
+        Ok(found) => found,
+        Err(_) => dav(account, b"PROPFIND", base, "0", principal_body).await?,
+    };
+    let principal = match nested_href(&xml, "current-user-principal") {
+        Some(href) => resolve(&url, &href)?,
+        None => url,
+    };
+
+    let home_body = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:propfind xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav"><D:prop><C:addressbook-home-set/></D:prop></D:propfind>"#;
+    let (url, xml) = dav(account, b"PROPFIND", &principal, "0", home_body).await?;
+    let home = nested_href(&xml, "addressbook-home-set")
+        .ok_or_else(|| "Server has no CardDAV address books".to_string())?;
+    resolve(&url, &home)
+}
+
+/// URLs of every address book on the account
+pub async fn address_books(account: &CardDavAccount) -> Result<Vec<String>, String> {
+    let home = find_home(account).await?;
+    let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:propfind xmlns:D="DAV:"><D:prop><D:resourcetype/></D:prop></D:propfind>"#;
+    let (url, xml) = dav(account, b"PROPFIND", &home, "1", body).await?;
+
+    let mut books = Vec::new();
+    for response in responses(&xml) {
+        if element_starts(response, "addressbook").is_empty() {
+            continue;
+        }
+        if let Some(href) = element_text(response, "href") {
+            books.push(resolve(&url, &href)?);
+        }
+    }
+    Ok(books)
+}
+
+/// Every contact in one address book
+pub async fn list_contacts(account: &CardDavAccount, book_url: &str) -> Result<Vec<Contact>, String> {
+    let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<C:addressbook-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">
+  <D:prop><D:getetag/><C:address-data/></D:prop>
+</C:addressbook-query>"#;
+    let (_, xml) = dav(account, b"REPORT", book_url, "1", body).await?;
+
+    let mut contacts = Vec::new();
+    for response in responses(&xml) {
+        let (Some(href), Some(card)) = (element_text(response, "href"), element_text(response, "address-data")) else {
+            continue;
+        };
+        contacts.extend(parse_vcards(&card, ContactSource::CardDav, &href));
+    }
+    Ok(contacts)
+}
+
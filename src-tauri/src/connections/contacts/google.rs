@@ -0,0 +1,101 @@
+//! Google contacts through the People API, on the Gmail connection's token.
+
+use super::{Contact, ContactSource};
+use crate::connections::gmail::GmailAuth;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+const CONNECTIONS_URL: &str = "https://people.googleapis.com/v1/people/me/connections";
+const PERSON_FIELDS: &str = "names,emailAddresses,phoneNumbers,organizations,photos";
+
+/// Whether a Google account is connected to read contacts from
+pub fn is_connected() -> bool {
+    GmailAuth::new()
+        .and_then(|auth| auth.is_authenticated())
+        .unwrap_or(false)
+}
+
+fn strings(person: &Value, field: &str) -> Vec<String> {
+    person[field]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v["value"].as_str())
+                .map(|v| v.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn person_to_contact(person: &Value) -> Option<Contact> {
+    let resource_name = person["resourceName"].as_str()?;
+    let mut contact = Contact::new(ContactSource::Google, format!("{}:{}", ContactSource::Google.prefix(), resource_name));
+
+    let name = &person["names"][0];
+    contact.display_name = name["displayName"].as_str().unwrap_or("").to_string();
+    contact.given_name = name["givenName"].as_str().map(|s| s.to_string());
+    contact.family_name = name["familyName"].as_str().map(|s| s.to_string());
+    contact.emails = strings(person, "emailAddresses");
+    contact.phones = strings(person, "phoneNumbers");
+
+    let organization = &person["organizations"][0];
+    contact.organization = organization["name"].as_str().map(|s| s.to_string());
+    contact.job_title = organization["title"].as_str().map(|s| s.to_string());
+    // The generic silhouette isn't worth showing
+    contact.photo_url = person["photos"]
+        .as_array()
+        .and_then(|photos| photos.iter().find(|p| !p["default"].as_bool().unwrap_or(false)))
+        .and_then(|p| p["url"].as_str())
+        .map(|s| s.to_string());
+
+    if contact.display_name.is_empty() {
+        contact.display_name = contact.fallback_name();
+    }
+    Some(contact)
+}
+
+/// Every contact of the connected Google account
+pub async fn list_contacts() -> Result<Vec<Contact>, String> {
+    let token = GmailAuth::new()
+        .map_err(|e| e.to_string())?
+        .get_valid_token()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let client = Client::new();
+    let mut contacts = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let mut request = client
+            .get(CONNECTIONS_URL)
+            .bearer_auth(&token.access_token)
+            .query(&[("personFields", PERSON_FIELDS), ("pageSize", "1000")]);
+        if let Some(page_token) = &page_token {
+            request = request.query(&[("pageToken", page_token)]);
+        }
+
+        let response = request.send().await.map_err(|e| format!("Network error: {}", e))?;
+        match response.status() {
+            status if status.is_success() => {}
+            // Accounts connected before contacts were added never granted the scope
+            StatusCode::FORBIDDEN => {
+                return Err("Google contacts access not granted; reconnect your Google account".to_string())
+            }
+            status => {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(format!("Failed to list Google contacts ({}): {}", status, error_text));
+            }
+        }
+
+        let page: Value = response.json().await.map_err(|e| format!("Invalid People API response: {}", e))?;
+        if let Some(people) = page["connections"].as_array() {
+            contacts.extend(people.iter().filter_map(person_to_contact));
+        }
+        page_token = page["nextPageToken"].as_str().map(|s| s.to_string());
+        if page_token.is_none() {
+            break;
+        }
+    }
+    Ok(contacts)
+}
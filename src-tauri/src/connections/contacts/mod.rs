@@ -0,0 +1,317 @@
+//! Contacts from Google (People API) and CardDAV address books.
+//!
+//! Both sources are merged into one list cached under `~/.lokus/contacts/`,
+//! so @-mentions and attendee links resolve offline. The cache is refreshed
+//! when it is older than an hour or on request; a source that can't be
+//! reached keeps the contacts it had. The CardDAV account, password
+//! included, is kept in secure storage.
+
+mod carddav;
+mod google;
+mod vcard;
+
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const ACCOUNT_KEY: &str = "carddav-account";
+const SEARCH_LIMIT: usize = 20;
+
+fn cache_ttl() -> Duration {
+    Duration::hours(1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContactSource {
+    Google,
+    #[serde(rename = "carddav")]
+    CardDav,
+}
+
+impl ContactSource {
+    /// Prefix of the ids of contacts from this source
+    fn prefix(self) -> &'static str {
+        match self {
+            ContactSource::Google => "google",
+            ContactSource::CardDav => "carddav",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contact {
+    /// `<source>:<id at the source>`
+    pub id: String,
+    pub source: ContactSource,
+    pub display_name: String,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+    pub organization: Option<String>,
+    pub job_title: Option<String>,
+    pub photo_url: Option<String>,
+}
+
+impl Contact {
+    fn new(source: ContactSource, id: String) -> Self {
+        Self {
+            id,
+            source,
+            display_name: String::new(),
+            given_name: None,
+            family_name: None,
+            emails: Vec::new(),
+            phones: Vec::new(),
+            organization: None,
+            job_title: None,
+            photo_url: None,
+        }
+    }
+
+    /// Something to show for contacts without a formatted name
+    fn fallback_name(&self) -> String {
+        let joined = [self.given_name.as_deref(), self.family_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !joined.is_empty() {
+            return joined;
+        }
+        self.emails
+            .first()
+            .or(self.phones.first())
+            .cloned()
+            .unwrap_or_else(|| "Unnamed contact".to_string())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardDavAccount {
+    pub server_url: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// What the frontend sees of the CardDAV account; never includes the password
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardDavProfile {
+    pub server_url: String,
+    pub username: String,
+    pub address_books: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContactCache {
+    contacts: Vec<Contact>,
+    synced_at: Option<DateTime<Utc>>,
+}
+
+/// Serializes refreshes so two windows don't fetch at once
+static REFRESH_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+/// `None` until loaded from secure storage, which is slow
+static ACCOUNT: Lazy<Mutex<Option<Option<CardDavAccount>>>> = Lazy::new(|| Mutex::new(None));
+
+fn account() -> Option<CardDavAccount> {
+    ACCOUNT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| {
+            crate::secure_storage::SecureStorage::new()
+                .ok()
+                .and_then(|storage| storage.retrieve::<CardDavAccount>(ACCOUNT_KEY).ok().flatten())
+        })
+        .clone()
+}
+
+fn cache_path() -> Result<PathBuf, String> {
+    let dir = dirs::home_dir()
+        .ok_or_else(|| "Could not find home directory".to_string())?
+        .join(".lokus")
+        .join("contacts");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create contacts directory: {}", e))?;
+    Ok(dir.join("contacts.json"))
+}
+
+fn load_cache() -> Result<ContactCache, String> {
+    let path = cache_path()?;
+    if !path.exists() {
+        return Ok(ContactCache::default());
+    }
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read contacts cache: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse contacts cache: {}", e))
+}
+
+fn save_cache(cache: &ContactCache) -> Result<(), String> {
+    let json = serde_json::to_string(cache).map_err(|e| format!("Failed to serialize contacts: {}", e))?;
+    std::fs::write(cache_path()?, json).map_err(|e| format!("Failed to write contacts cache: {}", e))
+}
+
+async fn fetch_carddav(account: &CardDavAccount) -> Result<Vec<Contact>, String> {
+    let mut contacts = Vec::new();
+    for book in carddav::address_books(account).await? {
+        contacts.extend(carddav::list_contacts(account, &book).await?);
+    }
+    Ok(contacts)
+}
+
+/// Refetch every connected source into the cache. Sources that fail keep
+/// their cached contacts; disconnected ones are dropped.
+async fn refresh() -> Result<ContactCache, String> {
+    let _guard = REFRESH_LOCK.lock().await;
+    let mut cache = load_cache()?;
+
+    let google = if google::is_connected() { Some(google::list_contacts().await) } else { None };
+    let carddav = match account() {
+        Some(account) => Some(fetch_carddav(&account).await),
+        None => None,
+    };
+
+    for (source, fetched) in [(ContactSource::Google, google), (ContactSource::CardDav, carddav)] {
+        match fetched {
+            Some(Ok(contacts)) => {
+                cache.contacts.retain(|c| c.source != source);
+                cache.contacts.extend(contacts);
+            }
+            Some(Err(e)) => tracing::warn!("Failed to refresh {} contacts: {}", source.prefix(), e),
+            None => cache.contacts.retain(|c| c.source != source),
+        }
+    }
+
+    cache.contacts.sort_by_key(|c| c.display_name.to_lowercase());
+    cache.synced_at = Some(Utc::now());
+    save_cache(&cache)?;
+    Ok(cache)
+}
+
+async fn contacts(force_refresh: bool) -> Result<Vec<Contact>, String> {
+    let cache = load_cache()?;
+    let stale = cache.synced_at.map_or(true, |synced| Utc::now() - synced > cache_ttl());
+    if !force_refresh && !stale {
+        return Ok(cache.contacts);
+    }
+    Ok(refresh().await?.contacts)
+}
+
+/// Contacts matching `query` on name, email or organization; name prefixes
+/// rank first, then word prefixes, then anything containing the query
+pub fn search(contacts: &[Contact], query: &str) -> Vec<Contact> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranked: Vec<(u8, &Contact)> = contacts
+        .iter()
+        .filter_map(|contact| {
+            let name = contact.display_name.to_lowercase();
+            let rank = if name.starts_with(&query) {
+                0
+            } else if name.split_whitespace().any(|word| word.starts_with(&query)) {
+                1
+            } else if contact.emails.iter().any(|e| e.to_lowercase().starts_with(&query)) {
+                2
+            } else if name.contains(&query)
+                || contact.emails.iter().any(|e| e.to_lowercase().contains(&query))
+                || contact.organization.as_deref().is_some_and(|o| o.to_lowercase().contains(&query))
+            {
+                3
+            } else {
+                return None;
+            };
+            Some((rank, contact))
+        })
+        .collect();
+    // Stable, so equal ranks stay in name order
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().take(SEARCH_LIMIT).map(|(_, c)| c.clone()).collect()
+}
+
+// --- Tauri Commands ---
+
+/// Every contact, from the cache unless it is stale or `refresh` is set
+#[tauri::command]
+pub async fn contacts_list(refresh: Option<bool>) -> Result<Vec<Contact>, String> {
+    contacts(refresh.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn contacts_search(query: String) -> Result<Vec<Contact>, String> {
+    Ok(search(&contacts(false).await?, &query))
+}
+
+#[tauri::command]
+pub async fn contacts_get(id: String) -> Result<Option<Contact>, String> {
+    Ok(contacts(false).await?.into_iter().find(|c| c.id == id))
+}
+
+/// Find the account's address books to check the settings, then remember it
+#[tauri::command]
+pub async fn contacts_connect_carddav(server_url: String, username: String, password: String) -> Result<CardDavProfile, String> {
+    if server_url.trim().is_empty() || username.trim().is_empty() {
+        return Err("A server and username are required".to_string());
+    }
+    let account = CardDavAccount { server_url: server_url.trim().to_string(), username, password };
+    let books = carddav::address_books(&account).await?;
+
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.store(ACCOUNT_KEY, &account).map_err(|e| format!("Failed to save CardDAV account: {}", e))?;
+    let profile = CardDavProfile {
+        server_url: account.server_url.clone(),
+        username: account.username.clone(),
+        address_books: books.len(),
+    };
+    *ACCOUNT.lock().unwrap_or_else(|e| e.into_inner()) = Some(Some(account));
+
+    refresh().await?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn contacts_disconnect_carddav() -> Result<(), String> {
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.delete(ACCOUNT_KEY).map_err(|e| format!("Failed to remove CardDAV account: {}", e))?;
+    *ACCOUNT.lock().unwrap_or_else(|e| e.into_inner()) = Some(None);
+
+    refresh().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, email: &str, organization: Option<&str>) -> Contact {
+        let mut contact = Contact::new(ContactSource::Google, format!("google:people/{}", name));
+        contact.display_name = name.to_string();
+        contact.emails.push(email.to_string());
+        contact.organization = organization.map(|o| o.to_string());
+        contact
+    }
+
+    #[test]
+    fn test_search_ranks_name_prefixes_first() {
+        let contacts = vec![
+            contact("Anna Smith", "anna@example.com", None),
+            contact("Jo Anderson", "jo@example.com", None),
+            contact("Sam Lee", "sam@andromeda.io", None),
+            contact("Kim Park", "kim@example.com", Some("Banana Co")),
+            contact("Max Muster", "max@example.com", None),
+        ];
+
+        let names: Vec<_> = search(&contacts, "an").into_iter().map(|c| c.display_name).collect();
+        assert_eq!(names, ["Anna Smith", "Jo Anderson", "Sam Lee", "Kim Park"]);
+
+        let names: Vec<_> = search(&contacts, "SAM@").into_iter().map(|c| c.display_name).collect();
+        assert_eq!(names, ["Sam Lee"]);
+        assert!(search(&contacts, "  ").is_empty());
+    }
+}
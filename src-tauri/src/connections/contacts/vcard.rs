@@ -0,0 +1,143 @@
+//! Just enough vCard (RFC 6350, and the 3.0 that most servers still serve)
+//! to fill a `Contact`.
+
+use super::{Contact, ContactSource};
+
+/// Undo line folding: a line starting with a space or tab continues the previous one
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest.trim_end_matches('\r')),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Split a structured value on unescaped `;`
+fn components(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ';' {
+            parts.push(unescape(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(unescape(&current));
+    parts
+}
+
+/// Every card in `text`, which may hold several `BEGIN:VCARD` blocks
+pub fn parse_vcards(text: &str, source: ContactSource, fallback_id: &str) -> Vec<Contact> {
+    let mut contacts = Vec::new();
+    let mut current: Option<Contact> = None;
+    let mut uid: Option<String> = None;
+
+    for line in unfold(text) {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Apple groups related properties as `item1.EMAIL`
+        let name = head.split(';').next().unwrap_or("");
+        let name = name.rsplit('.').next().unwrap_or(name).to_ascii_uppercase();
+
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(Contact::new(source, String::new()));
+                uid = None;
+            }
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(mut contact) = current.take() {
+                    let key = uid.take().unwrap_or_else(|| fallback_id.to_string());
+                    contact.id = format!("{}:{}", source.prefix(), key);
+                    if contact.display_name.is_empty() {
+                        contact.display_name = contact.fallback_name();
+                    }
+                    contacts.push(contact);
+                }
+            }
+            _ => {
+                let Some(contact) = current.as_mut() else {
+                    continue;
+                };
+                let value = value.trim();
+                match name.as_str() {
+                    "UID" => uid = Some(unescape(value)).filter(|v| !v.is_empty()),
+                    "FN" => contact.display_name = unescape(value),
+                    "N" => {
+                        let parts = components(value);
+                        contact.family_name = parts.first().cloned().filter(|p| !p.is_empty());
+                        contact.given_name = parts.get(1).cloned().filter(|p| !p.is_empty());
+                    }
+                    "EMAIL" if !value.is_empty() => contact.emails.push(unescape(value)),
+                    "TEL" if !value.is_empty() => {
+                        let tel = unescape(value);
+                        contact.phones.push(tel.strip_prefix("tel:").unwrap_or(&tel).to_string());
+                    }
+                    "ORG" => contact.organization = components(value).into_iter().next().filter(|o| !o.is_empty()),
+                    "TITLE" => contact.job_title = Some(unescape(value)).filter(|t| !t.is_empty()),
+                    // Inline base64 photos are too big to cache; only keep links
+                    "PHOTO" if value.starts_with("http") => contact.photo_url = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+    contacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_folded_grouped_card() {
+        let text = "BEGIN:VCARD\r\nVERSION:3.0\r\nUID:1234-abcd\r\nFN:Ada Lovelace\r\nN:Lovelace;Ada;;;\r\n\
+            item1.EMAIL;type=INTERNET;type=pref:ada@example.\r\n com\r\nTEL;TYPE=CELL:+44 20 7946 0000\r\n\
+            ORG:Analytical Engines\\, Ltd;Research\r\nTITLE:Mathematician\r\nEND:VCARD\r\n\
+            BEGIN:VCARD\r\nVERSION:4.0\r\nEMAIL:charles@example.com\r\nEND:VCARD\r\n";
+
+        let contacts = parse_vcards(text, ContactSource::CardDav, "/cards/second.vcf");
+
+        assert_eq!(contacts.len(), 2);
+        let ada = &contacts[0];
+        assert_eq!(ada.id, "carddav:1234-abcd");
+        assert_eq!(ada.display_name, "Ada Lovelace");
+        assert_eq!(ada.given_name.as_deref(), Some("Ada"));
+        assert_eq!(ada.emails, ["ada@example.com"]);
+        assert_eq!(ada.phones, ["+44 20 7946 0000"]);
+        assert_eq!(ada.organization.as_deref(), Some("Analytical Engines, Ltd"));
+
+        // No UID or name: falls back to the resource and the address
+        assert_eq!(contacts[1].id, "carddav:/cards/second.vcf");
+        assert_eq!(contacts[1].display_name, "charles@example.com");
+    }
+}
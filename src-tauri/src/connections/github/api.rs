@@ -0,0 +1,326 @@
+//! The GitHub REST API.
+
+use super::badges::{split_repo, IssueRef, IssueState};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+const API_URL: &str = "https://api.github.com";
+const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const API_VERSION: &str = "2022-11-28";
+const PAGE_SIZE: usize = 100;
+const DEFAULT_LIMIT: usize = 100;
+const MAX_RETRIES: u32 = 4;
+/// Secondary rate limits ask for a wait of a minute or so; a primary limit
+/// that resets later than this is reported rather than waited out
+const MAX_WAIT: Duration = Duration::from_secs(90);
+
+pub const TOKEN_REJECTED: &str = "GitHub rejected the access token";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestLink {
+    merged_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawIssue {
+    number: u64,
+    #[serde(default)]
+    title: String,
+    body: Option<String>,
+    state: String,
+    /// `completed`, `not_planned` or `reopened`
+    state_reason: Option<String>,
+    html_url: String,
+    user: Option<User>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    comments: u64,
+    /// Present when the issue is a pull request
+    pull_request: Option<PullRequestLink>,
+    created_at: String,
+    updated_at: String,
+    closed_at: Option<String>,
+}
+
+/// An issue or pull request
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    /// `owner/name`
+    pub repo: String,
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub state: IssueState,
+    pub url: String,
+    pub author: Option<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub comments: u64,
+    pub is_pull_request: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    pub closed_at: Option<String>,
+}
+
+impl Issue {
+    fn from_raw(repo: &str, raw: RawIssue) -> Self {
+        let state = match (raw.state.as_str(), &raw.pull_request, raw.state_reason.as_deref()) {
+            ("open", _, _) => IssueState::Open,
+            (_, Some(pr), _) if pr.merged_at.is_some() => IssueState::Merged,
+            (_, Some(_), _) => IssueState::Closed,
+            (_, None, Some("not_planned")) => IssueState::NotPlanned,
+            _ => IssueState::Completed,
+        };
+        Issue {
+            repo: repo.to_string(),
+            number: raw.number,
+            title: raw.title,
+            body: raw.body,
+            state,
+            url: raw.html_url,
+            author: raw.user.map(|u| u.login),
+            labels: raw.labels.into_iter().map(|l| l.name).collect(),
+            assignees: raw.assignees.into_iter().map(|u| u.login).collect(),
+            comments: raw.comments,
+            is_pull_request: raw.pull_request.is_some(),
+            created_at: raw.created_at,
+            updated_at: raw.updated_at,
+            closed_at: raw.closed_at,
+        }
+    }
+
+    pub fn reference(&self) -> Option<IssueRef> {
+        IssueRef::parse(&format!("{}#{}", self.repo, self.number))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IssueFilter {
+    /// `open` (the default), `closed` or `all`
+    pub state: Option<String>,
+    /// Issues must have all of these
+    pub labels: Vec<String>,
+    /// A login, `none` or `*`
+    pub assignee: Option<String>,
+    pub creator: Option<String>,
+    pub mentioned: Option<String>,
+    /// Only issues updated after this ISO 8601 time
+    pub since: Option<String>,
+    /// GitHub lists pull requests as issues too; they are left out unless asked for
+    pub include_pull_requests: bool,
+    pub limit: Option<usize>,
+}
+
+fn header<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+    response.headers().get(name)?.to_str().ok()
+}
+
+/// Whether GitHub refused the request for going over a rate limit; primary
+/// limits come back as 403 with no requests remaining
+fn rate_limited(response: &Response) -> bool {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => {
+            header(response, "retry-after").is_some() || header(response, "x-ratelimit-remaining") == Some("0")
+        }
+        _ => false,
+    }
+}
+
+/// `retry-after` if given, else until the limit resets, else exponential backoff
+fn rate_limit_wait(response: &Response, attempt: u32) -> Duration {
+    if let Some(secs) = header(response, "retry-after").and_then(|v| v.parse().ok()) {
+        return Duration::from_secs(secs);
+    }
+    if let Some(reset) = header(response, "x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok()) {
+        return Duration::from_secs((reset - chrono::Utc::now().timestamp()).max(1) as u64);
+    }
+    Duration::from_secs(2u64.pow(attempt))
+}
+
+/// Send a request (rebuilt for each attempt), waiting out short rate limits
+async fn send(request: impl Fn() -> RequestBuilder) -> Result<Response, String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let response = request().send().await.map_err(|e| format!("Network error: {}", e))?;
+        if rate_limited(&response) {
+            let wait = rate_limit_wait(&response, attempt);
+            if attempt < MAX_RETRIES && wait <= MAX_WAIT {
+                tracing::warn!("GitHub rate limit reached; retrying in {}s", wait.as_secs());
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+            return Err(format!(
+                "GitHub rate limit exceeded; try again in {} minutes",
+                wait.as_secs().div_ceil(60)
+            ));
+        }
+        return match response.status() {
+            status if status.is_success() => Ok(response),
+            StatusCode::UNAUTHORIZED => Err(TOKEN_REJECTED.to_string()),
+            StatusCode::NOT_FOUND => Err("Not found on GitHub, or not visible to this account".to_string()),
+            status => {
+                let text = response.text().await.unwrap_or_default();
+                let message = serde_json::from_str::<serde_json::Value>(&text)
+                    .ok()
+                    .and_then(|v| v.get("message")?.as_str().map(str::to_string))
+                    .unwrap_or(text);
+                Err(format!("GitHub request failed ({}): {}", status, message.trim()))
+            }
+        };
+    }
+}
+
+fn request(client: &Client, method: Method, token: &str, path: &str) -> RequestBuilder {
+    client
+        .request(method, format!("{}{}", API_URL, path))
+        .header(ACCEPT, "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", API_VERSION)
+        .header(USER_AGENT, "Lokus")
+        .bearer_auth(token)
+}
+
+fn repo_path(repo: &str) -> Result<String, String> {
+    let (owner, name) = split_repo(repo).ok_or_else(|| format!("Expected a repository as owner/name, got '{}'", repo))?;
+    Ok(format!("{}/{}", owner, name))
+}
+
+pub async fn current_user(client: &Client, token: &str) -> Result<User, String> {
+    send(|| request(client, Method::GET, token, "/user"))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid GitHub response: {}", e))
+}
+
+pub async fn list_issues(client: &Client, token: &str, repo: &str, filter: &IssueFilter) -> Result<Vec<Issue>, String> {
+    let repo = repo_path(repo)?;
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT);
+    let mut query = vec![
+        ("state", filter.state.clone().unwrap_or_else(|| "open".to_string())),
+        ("sort", "updated".to_string()),
+        ("per_page", PAGE_SIZE.to_string()),
+    ];
+    if !filter.labels.is_empty() {
+        query.push(("labels", filter.labels.join(",")));
+    }
+    for (key, value) in [("assignee", &filter.assignee), ("creator", &filter.creator), ("mentioned", &filter.mentioned), ("since", &filter.since)] {
+        if let Some(value) = value {
+            query.push((key, value.clone()));
+        }
+    }
+
+    let mut issues = Vec::new();
+    let mut page = 1;
+    while issues.len() < limit {
+        let path = format!("/repos/{}/issues", repo);
+        let page_query = [query.as_slice(), &[("page", page.to_string())]].concat();
+        let batch: Vec<RawIssue> = send(|| request(client, Method::GET, token, &path).query(&page_query))
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid GitHub response: {}", e))?;
+        let last_page = batch.len() < PAGE_SIZE;
+        issues.extend(
+            batch
+                .into_iter()
+                .filter(|raw| filter.include_pull_requests || raw.pull_request.is_none())
+                .map(|raw| Issue::from_raw(&repo, raw)),
+        );
+        if last_page {
+            break;
+        }
+        page += 1;
+    }
+    issues.truncate(limit);
+    Ok(issues)
+}
+
+pub async fn get_issue(client: &Client, token: &str, reference: &IssueRef) -> Result<Issue, String> {
+    let repo = reference.repo_path();
+    let path = format!("/repos/{}/issues/{}", repo, reference.number);
+    let raw: RawIssue = send(|| request(client, Method::GET, token, &path))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid GitHub response: {}", e))?;
+    Ok(Issue::from_raw(&repo, raw))
+}
+
+pub async fn create_issue(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    title: &str,
+    body: Option<&str>,
+    labels: &[String],
+) -> Result<Issue, String> {
+    let repo = repo_path(repo)?;
+    let path = format!("/repos/{}/issues", repo);
+    let payload = json!({ "title": title, "body": body, "labels": labels });
+    let raw: RawIssue = send(|| request(client, Method::POST, token, &path).json(&payload))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid GitHub response: {}", e))?;
+    Ok(Issue::from_raw(&repo, raw))
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Exchange an OAuth authorization code for an access token
+pub async fn exchange_code(
+    client: &Client,
+    client_id: &str,
+    client_secret: &str,
+    redirect_uri: &str,
+    code: &str,
+    code_verifier: &str,
+) -> Result<String, String> {
+    let params = [
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+        ("redirect_uri", redirect_uri),
+        ("code", code),
+        ("code_verifier", code_verifier),
+    ];
+    // GitHub reports a bad code with 200 and an `error` field
+    let response: TokenResponse = send(|| client.post(TOKEN_URL).header(ACCEPT, "application/json").form(&params))
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid GitHub response: {}", e))?;
+    match (response.access_token, response.error) {
+        (Some(token), _) => Ok(token),
+        (None, error) => Err(format!(
+            "GitHub authorization failed: {}",
+            response.error_description.or(error).unwrap_or_else(|| "no access token returned".to_string())
+        )),
+    }
+}
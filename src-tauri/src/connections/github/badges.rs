@@ -0,0 +1,254 @@
+//! Issue references and the status badges written into notes.
+//!
+//! A badge is an ordinary Markdown link whose text starts with a status icon,
+//! e.g. `[🟢 open · owner/repo#12 Fix sync](https://github.com/owner/repo/issues/12)`,
+//! so it still works wherever the note is rendered. Badges are recognised by
+//! that icon, which lets the refresher rewrite them in place without touching
+//! other links to the same issue.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+
+const MAX_TITLE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueState {
+    Open,
+    /// A closed issue that was done
+    Completed,
+    NotPlanned,
+    Merged,
+    /// A pull request closed without merging
+    Closed,
+}
+
+impl IssueState {
+    fn icon(self) -> &'static str {
+        match self {
+            IssueState::Open => "🟢",
+            IssueState::Completed | IssueState::Merged => "🟣",
+            IssueState::NotPlanned => "⚪",
+            IssueState::Closed => "🔴",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IssueState::Open => "open",
+            IssueState::Completed => "completed",
+            IssueState::NotPlanned => "not planned",
+            IssueState::Merged => "merged",
+            IssueState::Closed => "closed",
+        }
+    }
+}
+
+const ICONS: [&str; 4] = ["🟢", "🟣", "⚪", "🔴"];
+
+/// An issue or pull request, `owner/repo#12`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IssueRef {
+    pub owner: String,
+    pub repo: String,
+    pub number: u64,
+}
+
+fn valid_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) && name.chars().any(|c| c != '.')
+}
+
+/// `owner/name`, checked so it can go into an API path
+pub fn split_repo(repo: &str) -> Option<(&str, &str)> {
+    let (owner, name) = repo.trim().split_once('/')?;
+    (valid_name(owner) && valid_name(name)).then_some((owner, name))
+}
+
+impl IssueRef {
+    /// `owner/repo#12`, or the URL of an issue or pull request
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let url_path = ["https://github.com/", "http://github.com/", "github.com/"]
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix));
+        let (repo, number) = match url_path {
+            Some(path) => {
+                let path = path.split(['?', '#']).next()?;
+                let mut parts = path.split('/');
+                let (owner, name, kind, number) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+                if kind != "issues" && kind != "pull" {
+                    return None;
+                }
+                (format!("{}/{}", owner, name), number)
+            }
+            None => {
+                let (repo, number) = text.split_once('#')?;
+                (repo.to_string(), number)
+            }
+        };
+        let (owner, name) = split_repo(&repo)?;
+        Some(IssueRef {
+            owner: owner.to_string(),
+            repo: name.to_string(),
+            number: number.parse().ok().filter(|n| *n > 0)?,
+        })
+    }
+
+    /// `owner/name`
+    pub fn repo_path(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+}
+
+impl fmt::Display for IssueRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}#{}", self.owner, self.repo, self.number)
+    }
+}
+
+/// Titles go inside the link text, so brackets and line breaks are replaced
+fn clean_title(title: &str) -> String {
+    let title: String = title
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '[' => '(',
+            ']' => ')',
+            '\n' | '\r' | '\t' => ' ',
+            c => c,
+        })
+        .collect();
+    match title.char_indices().nth(MAX_TITLE_CHARS) {
+        Some((end, _)) => format!("{}…", title[..end].trim_end()),
+        None => title,
+    }
+}
+
+pub fn render(reference: &IssueRef, state: IssueState, title: &str, url: &str) -> String {
+    let title = clean_title(title);
+    let text = format!("{} {} · {}", state.icon(), state.label(), reference);
+    if title.is_empty() {
+        format!("[{}]({})", text, url)
+    } else {
+        format!("[{} {}]({})", text, title, url)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge {
+    /// Byte range of the whole link
+    pub range: Range<usize>,
+    pub reference: IssueRef,
+}
+
+/// Length and issue of the badge at the start of `text`, if there is one
+fn badge_at(text: &str) -> Option<(usize, IssueRef)> {
+    let inner = text.strip_prefix('[')?;
+    if !ICONS.iter().any(|icon| inner.strip_prefix(icon).map_or(false, |rest| rest.starts_with(' '))) {
+        return None;
+    }
+    let close = inner.find("](")?;
+    if inner[..close].contains(['[', ']']) {
+        return None;
+    }
+    let target = &inner[close + 2..];
+    let end = target.find(')')?;
+    let reference = IssueRef::parse(&target[..end])?;
+    Some((1 + close + 2 + end + 1, reference))
+}
+
+/// Badges in a note, outside code blocks
+pub fn find(content: &str) -> Vec<Badge> {
+    let mut badges = Vec::new();
+    let mut offset = 0;
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            let mut from = 0;
+            while let Some(start) = line[from..].find('[').map(|i| from + i) {
+                match badge_at(&line[start..]) {
+                    Some((len, reference)) => {
+                        badges.push(Badge { range: offset + start..offset + start + len, reference });
+                        from = start + len;
+                    }
+                    None => from = start + 1,
+                }
+            }
+        }
+        offset += line.len();
+    }
+    badges
+}
+
+/// Replace each badge with `fresh(issue)`, leaving it as is where that is `None`
+pub fn rewrite(content: &str, mut fresh: impl FnMut(&IssueRef) -> Option<String>) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for badge in find(content) {
+        if let Some(replacement) = fresh(&badge.reference) {
+            out.push_str(&content[last..badge.range.start]);
+            out.push_str(&replacement);
+            last = badge.range.end;
+        }
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(number: u64) -> IssueRef {
+        IssueRef { owner: "lokus-ai".to_string(), repo: "lokus".to_string(), number }
+    }
+
+    #[test]
+    fn parses_references_and_urls() {
+        assert_eq!(IssueRef::parse("lokus-ai/lokus#12"), Some(issue(12)));
+        assert_eq!(IssueRef::parse("https://github.com/lokus-ai/lokus/issues/12"), Some(issue(12)));
+        assert_eq!(IssueRef::parse("https://github.com/lokus-ai/lokus/pull/12/files#diff"), Some(issue(12)));
+        assert_eq!(IssueRef::parse("https://github.com/lokus-ai/lokus/blob/main/README.md"), None);
+        assert_eq!(IssueRef::parse("lokus#12"), None);
+        assert_eq!(IssueRef::parse("lokus-ai/lokus#0"), None);
+        assert_eq!(IssueRef::parse("../etc/passwd#1"), None);
+        assert_eq!(IssueRef::parse("../lokus#1"), None);
+        assert_eq!(issue(12).to_string(), "lokus-ai/lokus#12");
+    }
+
+    #[test]
+    fn renders_badges_with_safe_titles() {
+        let url = "https://github.com/lokus-ai/lokus/issues/12";
+        assert_eq!(
+            render(&issue(12), IssueState::Open, "Crash in [sync]\nmodule", url),
+            "[🟢 open · lokus-ai/lokus#12 Crash in (sync) module](https://github.com/lokus-ai/lokus/issues/12)"
+        );
+        let long = render(&issue(12), IssueState::Merged, &"x".repeat(200), url);
+        assert!(long.contains(&format!("{}…]", "x".repeat(MAX_TITLE_CHARS))));
+    }
+
+    #[test]
+    fn rewrites_badges_only() {
+        let url = "https://github.com/lokus-ai/lokus/issues/12";
+        let badge = render(&issue(12), IssueState::Open, "Fix sync", url);
+        let content = format!(
+            "See {} and [the issue]({}).\n\n```\n{}\n```\n- {}\n",
+            badge, url, badge, render(&issue(7), IssueState::Open, "Other", "https://github.com/lokus-ai/lokus/issues/7")
+        );
+        let found = find(&content);
+        assert_eq!(found.len(), 2);
+        assert_eq!(&content[found[0].range.clone()], badge);
+
+        let closed = render(&issue(12), IssueState::Completed, "Fix sync", url);
+        let rewritten = rewrite(&content, |r| (r.number == 12).then(|| closed.clone()));
+        assert!(rewritten.starts_with(&format!("See {} and [the issue]", closed)));
+        // The code block and the other issue are untouched
+        assert!(rewritten.contains(&format!("```\n{}\n```", badge)));
+        assert!(rewritten.contains("🟢 open · lokus-ai/lokus#7 Other"));
+        assert_eq!(rewrite(&content, |_| None), content);
+    }
+}
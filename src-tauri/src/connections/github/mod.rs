@@ -0,0 +1,410 @@
+//! GitHub issues.
+//!
+//! Connect through OAuth (the browser returns to the local callback server,
+//! like the calendar providers) or with a personal access token; either way
+//! the token is kept in secure storage. Issues can be listed and created, and
+//! linked to a note: the note gets a status badge (see `badges`) and the
+//! issue is added to its `github_issues` frontmatter. While the app runs, the
+//! refresher revisits notes with that field and rewrites badges whose issue
+//! has changed state or title.
+
+mod api;
+mod badges;
+
+use api::{Issue, IssueFilter};
+use badges::IssueRef;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const ACCOUNT_KEY: &str = "github-account";
+const ISSUES_FIELD: &str = "github_issues";
+const AUTHORIZE_URL: &str = "https://github.com/login/oauth/authorize";
+const SCOPES: &str = "repo read:user";
+const CALLBACK_FILE: &str = "github_auth_callback.json";
+const AUTH_EVENT: &str = "github-auth-success";
+const UPDATED_EVENT: &str = "github-badges-updated";
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Issues looked up per refresh, to stay well inside the hourly API limit
+const MAX_ISSUES_PER_REFRESH: usize = 200;
+
+/// `None` until loaded from secure storage, which is slow
+static ACCOUNT: Lazy<Mutex<Option<Option<StoredAccount>>>> = Lazy::new(|| Mutex::new(None));
+/// PKCE verifier and state of the OAuth flow in progress
+static PENDING_AUTH: Lazy<Mutex<Option<(String, String)>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubAccount {
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+    /// `oauth` or `token`
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredAccount {
+    token: String,
+    #[serde(flatten)]
+    account: GitHubAccount,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgesUpdated {
+    pub workspace: String,
+    pub paths: Vec<String>,
+}
+
+fn stored_account() -> Option<StoredAccount> {
+    ACCOUNT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| {
+            crate::secure_storage::SecureStorage::new()
+                .ok()
+                .and_then(|storage| storage.retrieve::<StoredAccount>(ACCOUNT_KEY).ok().flatten())
+        })
+        .clone()
+}
+
+fn token() -> Result<String, String> {
+    stored_account().map(|a| a.token).ok_or_else(|| "GitHub is not connected".to_string())
+}
+
+/// Look up the token's user and save both
+async fn connect(token: String, method: &str) -> Result<GitHubAccount, String> {
+    let user = api::current_user(&Client::new(), &token).await?;
+    let stored = StoredAccount {
+        token,
+        account: GitHubAccount {
+            login: user.login,
+            name: user.name,
+            avatar_url: user.avatar_url,
+            method: method.to_string(),
+        },
+    };
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.store(ACCOUNT_KEY, &stored).map_err(|e| format!("Failed to save GitHub token: {}", e))?;
+    let account = stored.account.clone();
+    *ACCOUNT.lock().unwrap_or_else(|e| e.into_inner()) = Some(Some(stored));
+    Ok(account)
+}
+
+struct OAuthApp {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+fn oauth_app() -> Result<OAuthApp, String> {
+    let client_id = std::env::var("GITHUB_CLIENT_ID").map_err(|_| "GITHUB_CLIENT_ID environment variable not set".to_string())?;
+    let client_secret =
+        std::env::var("GITHUB_CLIENT_SECRET").map_err(|_| "GITHUB_CLIENT_SECRET environment variable not set".to_string())?;
+    let oauth_port = std::env::var("OAUTH_PORT").ok().and_then(|p| p.parse::<u16>().ok()).unwrap_or(9080);
+    Ok(OAuthApp {
+        client_id,
+        client_secret,
+        redirect_uri: format!("http://localhost:{}/github-callback", oauth_port),
+    })
+}
+
+// --- Notes ---
+
+fn badge(issue: &Issue) -> Option<String> {
+    Some(badges::render(&issue.reference()?, issue.state, &issue.title, &issue.url))
+}
+
+/// Add (or refresh) the issue's badge in a note and list the issue in its
+/// `github_issues` frontmatter
+fn link(content: &str, issue: &Issue) -> Result<String, String> {
+    let reference = issue.reference().ok_or_else(|| format!("Invalid issue reference {}#{}", issue.repo, issue.number))?;
+    let badge = badge(issue).unwrap_or_default();
+
+    let linked = if badges::find(content).iter().any(|b| b.reference == reference) {
+        badges::rewrite(content, |r| (*r == reference).then(|| badge.clone()))
+    } else if content.trim().is_empty() {
+        format!("{}\n", badge)
+    } else {
+        format!("{}\n\n{}\n", content.trim_end(), badge)
+    };
+
+    let mut issues: Vec<String> = crate::frontmatter::parse(&linked)
+        .and_then(|fm| match fm.get(ISSUES_FIELD)? {
+            serde_json::Value::Array(items) => Some(items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect()),
+            serde_json::Value::String(single) => Some(vec![single.clone()]),
+            _ => None,
+        })
+        .unwrap_or_default();
+    if issues.iter().any(|i| IssueRef::parse(i).as_ref() == Some(&reference)) {
+        return Ok(linked);
+    }
+    issues.push(reference.to_string());
+    crate::frontmatter::set_field(&linked, ISSUES_FIELD, &json!(issues))
+}
+
+/// Rewrite the badges in notes linked to issues; returns the notes changed
+async fn refresh(root: &Path, token: &str) -> Result<Vec<String>, String> {
+    let query = crate::frontmatter::FrontmatterQuery {
+        conditions: vec![crate::frontmatter::Condition {
+            field: ISSUES_FIELD.to_string(),
+            op: crate::frontmatter::Operator::Exists,
+            value: serde_json::Value::Null,
+        }],
+        ..Default::default()
+    };
+    let notes = crate::frontmatter::query(root, &query)?;
+
+    let mut references = BTreeSet::new();
+    let mut linked = Vec::new();
+    for note in notes {
+        let Ok(content) = crate::handlers::files::read_file_content(note.path.clone()).await else {
+            continue;
+        };
+        let found = badges::find(&content);
+        if !found.is_empty() {
+            references.extend(found.into_iter().map(|b| b.reference));
+            linked.push(note.path);
+        }
+    }
+
+    let client = Client::new();
+    let mut fresh = HashMap::new();
+    for reference in references.into_iter().take(MAX_ISSUES_PER_REFRESH) {
+        match api::get_issue(&client, token, &reference).await {
+            Ok(issue) => {
+                if let Some(badge) = badge(&issue) {
+                    fresh.insert(reference, badge);
+                }
+            }
+            Err(e) if e == api::TOKEN_REJECTED => return Err(e),
+            Err(e) => tracing::warn!("Failed to refresh {}: {}", reference, e),
+        }
+    }
+
+    let mut updated = Vec::new();
+    for path in linked {
+        // Re-read, as the note may have been edited while issues were fetched
+        let Ok(content) = crate::handlers::files::read_file_content(path.clone()).await else {
+            continue;
+        };
+        let rewritten = badges::rewrite(&content, |r| fresh.get(r).cloned());
+        if rewritten != content {
+            crate::handlers::files::write_file_content(path.clone(), rewritten)?;
+            updated.push(path);
+        }
+    }
+    Ok(updated)
+}
+
+fn emit_updated(app: &AppHandle, root: &Path, paths: &[String]) {
+    let payload = BadgesUpdated {
+        workspace: root.to_string_lossy().to_string(),
+        paths: paths.to_vec(),
+    };
+    if let Err(e) = app.emit(UPDATED_EVENT, &payload) {
+        tracing::warn!("Failed to emit {}: {}", UPDATED_EVENT, e);
+    }
+}
+
+/// Refresh issue badges in the open workspace for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+            let (Some(root), Ok(token)) = (crate::watcher::active_root(), token()) else {
+                continue;
+            };
+            match refresh(&root, &token).await {
+                Ok(paths) if !paths.is_empty() => emit_updated(&app, &root, &paths),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("GitHub badge refresh failed: {}", e),
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Begin the OAuth flow; returns the URL to open in the browser
+#[tauri::command]
+pub fn github_auth_start() -> Result<String, String> {
+    let app = oauth_app()?;
+    let (code_verifier, code_challenge) = crate::connections::gmail::GmailAuth::generate_pkce_pair();
+    let state = crate::connections::gmail::GmailAuth::generate_state();
+    let url = url::Url::parse_with_params(
+        AUTHORIZE_URL,
+        &[
+            ("client_id", app.client_id.as_str()),
+            ("redirect_uri", app.redirect_uri.as_str()),
+            ("scope", SCOPES),
+            ("state", state.as_str()),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    *PENDING_AUTH.lock().unwrap_or_else(|e| e.into_inner()) = Some((code_verifier, state));
+    Ok(url.to_string())
+}
+
+/// Finish the OAuth flow once the browser has returned to the callback
+/// server; `None` while that hasn't happened yet
+#[tauri::command]
+pub async fn github_check_auth_callback(app: AppHandle) -> Result<Option<GitHubAccount>, String> {
+    let Some((code_verifier, expected_state)) = PENDING_AUTH.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return Ok(None);
+    };
+    let home_dir = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+    let callback_file = home_dir.join(".lokus").join("temp").join(CALLBACK_FILE);
+    if !callback_file.exists() {
+        return Ok(None);
+    }
+
+    let callback_content =
+        std::fs::read_to_string(&callback_file).map_err(|e| format!("Failed to read callback file: {}", e))?;
+    let _ = std::fs::remove_file(&callback_file);
+    let callback_data: serde_json::Value =
+        serde_json::from_str(&callback_content).map_err(|e| format!("Failed to parse callback data: {}", e))?;
+    let code = callback_data["code"].as_str().ok_or_else(|| "Missing code in callback".to_string())?;
+    let state = callback_data["state"].as_str().ok_or_else(|| "Missing state in callback".to_string())?;
+    if state != expected_state {
+        return Err("Invalid state parameter".to_string());
+    }
+
+    let oauth = oauth_app()?;
+    let token = api::exchange_code(
+        &Client::new(),
+        &oauth.client_id,
+        &oauth.client_secret,
+        &oauth.redirect_uri,
+        code,
+        &code_verifier,
+    )
+    .await?;
+    let account = connect(token, "oauth").await?;
+    *PENDING_AUTH.lock().unwrap_or_else(|e| e.into_inner()) = None;
+
+    if let Err(e) = app.emit(AUTH_EVENT, &account) {
+        tracing::warn!("Failed to emit {}: {}", AUTH_EVENT, e);
+    }
+    Ok(Some(account))
+}
+
+/// Connect with a personal access token instead of OAuth
+#[tauri::command]
+pub async fn github_connect_token(token: String) -> Result<GitHubAccount, String> {
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err("A GitHub access token is required".to_string());
+    }
+    connect(token, "token").await
+}
+
+#[tauri::command]
+pub fn github_get_account() -> Option<GitHubAccount> {
+    stored_account().map(|a| a.account)
+}
+
+/// Forget the token; badges already in notes are kept as they are
+#[tauri::command]
+pub fn github_disconnect() -> Result<(), String> {
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.delete(ACCOUNT_KEY).map_err(|e| format!("Failed to remove GitHub token: {}", e))?;
+    *ACCOUNT.lock().unwrap_or_else(|e| e.into_inner()) = Some(None);
+    Ok(())
+}
+
+/// Issues of `owner/name`, most recently updated first
+#[tauri::command]
+pub async fn github_list_issues(repo: String, filter: Option<IssueFilter>) -> Result<Vec<Issue>, String> {
+    api::list_issues(&Client::new(), &token()?, &repo, &filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn github_create_issue(
+    repo: String,
+    title: String,
+    body: Option<String>,
+    labels: Option<Vec<String>>,
+) -> Result<Issue, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("An issue needs a title".to_string());
+    }
+    let body = body.filter(|b| !b.trim().is_empty());
+    api::create_issue(&Client::new(), &token()?, &repo, title, body.as_deref(), &labels.unwrap_or_default()).await
+}
+
+/// Put a status badge for `issue` (`owner/name#12` or its URL) in a note,
+/// which the refresher then keeps up to date
+#[tauri::command]
+pub async fn github_link_issue_to_note(issue: String, note: String) -> Result<Issue, String> {
+    let reference = IssueRef::parse(&issue).ok_or_else(|| format!("Not a GitHub issue: {}", issue))?;
+    let issue = api::get_issue(&Client::new(), &token()?, &reference).await?;
+    let content = crate::handlers::files::read_file_content(note.clone()).await?;
+    crate::handlers::files::write_file_content(note, link(&content, &issue)?)?;
+    Ok(issue)
+}
+
+/// Refresh the badges of a workspace now rather than waiting for the next round
+#[tauri::command]
+pub async fn github_refresh_badges(app: AppHandle, workspace_path: String) -> Result<Vec<String>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let paths = refresh(&root, &token()?).await?;
+    if !paths.is_empty() {
+        emit_updated(&app, &root, &paths);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use badges::IssueState;
+
+    fn issue(state: IssueState) -> Issue {
+        Issue {
+            repo: "lokus-ai/lokus".to_string(),
+            number: 12,
+            title: "Fix sync".to_string(),
+            body: None,
+            state,
+            url: "https://github.com/lokus-ai/lokus/issues/12".to_string(),
+            author: None,
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            comments: 0,
+            is_pull_request: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+            closed_at: None,
+        }
+    }
+
+    #[test]
+    fn linking_twice_updates_the_badge() {
+        let linked = link("# Sync\n\nNotes.\n", &issue(IssueState::Open)).unwrap();
+        assert!(linked.contains("\n\n[🟢 open · lokus-ai/lokus#12 Fix sync]("));
+        assert_eq!(
+            crate::frontmatter::parse(&linked).unwrap()[ISSUES_FIELD],
+            json!(["lokus-ai/lokus#12"])
+        );
+
+        let relinked = link(&linked, &issue(IssueState::Completed)).unwrap();
+        assert_eq!(badges::find(&relinked).len(), 1);
+        assert!(relinked.contains("[🟣 completed · lokus-ai/lokus#12 Fix sync]("));
+        assert_eq!(
+            crate::frontmatter::parse(&relinked).unwrap()[ISSUES_FIELD],
+            json!(["lokus-ai/lokus#12"])
+        );
+    }
+}
@@ -1,12 +1,11 @@
-use std::sync::Arc;
 use reqwest::Client;
 use crate::connections::gmail::models::{
     GmailToken, GmailProfile, EmailMessage, EmailComposer, 
     EmailLabel, EmailSearchOptions, EmailListOptions, EmailAddress,
-    EmailAttachment, GmailError, OperationType
+    EmailAttachment, GmailError
 };
 use crate::connections::gmail::auth::GmailAuth;
-use crate::connections::gmail::queue::OfflineQueue;
+use crate::sanitize::{sanitize_html, Policy};
 use chrono::{DateTime, Utc};
 use serde_json;
 use base64::{Engine as _, engine::general_purpose};
@@ -14,18 +13,16 @@ use base64::{Engine as _, engine::general_purpose};
 pub struct GmailApi {
     auth: GmailAuth,
     client: Client,
-    queue: Arc<OfflineQueue>,
 }
 
 impl GmailApi {
-    pub fn new(queue: Arc<OfflineQueue>) -> Result<Self, GmailError> {
+    pub fn new() -> Result<Self, GmailError> {
         let auth = GmailAuth::new()?;
         let client = Client::new();
         
         Ok(Self {
             auth,
             client,
-            queue,
         })
     }
 
@@ -46,12 +43,7 @@ impl GmailApi {
     }
 
     pub async fn logout(&self) -> Result<(), GmailError> {
-        
-        if let Ok(Some(token)) = crate::connections::gmail::storage::GmailStorage::get_token() {
-            let _ = self.auth.revoke_token(&token.access_token).await;
-        }
-        
-        Ok(())
+        self.auth.revoke_token().await
     }
 
     pub async fn get_profile(&self) -> Result<Option<GmailProfile>, GmailError> {
@@ -197,22 +189,83 @@ impl GmailApi {
         Self::parse_email_message(&message_data)
     }
 
-    // Email composition and sending
-    pub async fn send_email(&self, composer: EmailComposer) -> Result<String, GmailError> {
-        
-        match self.send_email_internal(&composer).await {
-            Ok(message_id) => {
-                Ok(message_id)
-            }
-            Err(e) => {
-                
-                // Add to offline queue for retry
-                let operation_data = serde_json::to_value(&composer)?;
-                let _queue_id = self.queue.add_operation(OperationType::SendEmail, operation_data)?;
-                
-                Err(e)
+    /// Ids of the newest messages carrying `label_id`, without fetching them
+    pub async fn list_message_ids(&self, label_id: &str, max_results: u32) -> Result<Vec<String>, GmailError> {
+        let token = self.get_valid_token().await?;
+
+        let url = format!(
+            "https://gmail.googleapis.com/gmail/v1/users/me/messages?labelIds={}&maxResults={}",
+            urlencoding::encode(label_id),
+            max_results
+        );
+
+        let response = self.client
+            .get(&url)
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(GmailError::Api(format!("Failed to list emails: {}", error_text)));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        Ok(data["messages"]
+            .as_array()
+            .map(|messages| messages.iter().filter_map(|m| m["id"].as_str()).map(|id| id.to_string()).collect())
+            .unwrap_or_default())
+    }
+
+    /// Download an attachment into `out` as it arrives, giving up once it's
+    /// known to exceed `max_bytes`. Returns the number of bytes written.
+    pub async fn download_attachment(
+        &self,
+        message_id: &str,
+        attachment_id: &str,
+        max_bytes: usize,
+        out: &mut impl std::io::Write,
+    ) -> Result<u64, GmailError> {
+        let token = self.get_valid_token().await?;
+
+        let url = format!(
+            "https://gmail.googleapis.com/gmail/v1/users/me/messages/{}/attachments/{}",
+            message_id, attachment_id
+        );
+
+        let mut response = self.client
+            .get(&url)
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(GmailError::Api(format!("Failed to get attachment: {}", error_text)));
+        }
+
+        // The bytes arrive base64 encoded inside JSON, a third larger than the file
+        let max_body = max_bytes.saturating_mul(4) / 3 + 1024;
+        let too_large = || GmailError::Api(format!("Attachment is larger than {} bytes", max_bytes));
+        if response.content_length().is_some_and(|len| len > max_body as u64) {
+            return Err(too_large());
+        }
+        let mut received = 0;
+        let mut decoder = AttachmentDecoder::default();
+        while let Some(chunk) = response.chunk().await? {
+            received += chunk.len();
+            if received > max_body {
+                return Err(too_large());
             }
+            decoder.feed(&chunk, out)?;
         }
+        decoder.finish(out)
+    }
+
+    // Email composition and sending
+    /// Send now; see `outbox` for sending that survives being offline
+    pub async fn send_email(&self, composer: EmailComposer) -> Result<String, GmailError> {
+        self.send_email_internal(&composer).await
     }
 
     async fn send_email_internal(&self, composer: &EmailComposer) -> Result<String, GmailError> {
@@ -233,8 +286,16 @@ impl GmailApi {
             .send()
             .await?;
 
-        if !response.status().is_success() {
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(GmailError::RateLimit);
+        }
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
+            // Worth retrying later, unlike a rejected message
+            if status.is_server_error() {
+                return Err(GmailError::Network(format!("Gmail is unavailable ({})", status)));
+            }
             return Err(GmailError::Api(format!("Failed to send email: {}", error_text)));
         }
 
@@ -472,6 +533,7 @@ impl GmailApi {
         
         // Parse body
         let (body_text, body_html) = Self::parse_email_body(payload);
+        let body_html = body_html.map(|html| sanitize_html(&html, Policy::Email));
         
         // Parse attachments
         let attachments = Self::parse_email_attachments(payload);
@@ -527,57 +589,176 @@ impl GmailApi {
     }
 
     fn parse_email_body(payload: &serde_json::Value) -> (Option<String>, Option<String>) {
-        // TODO: Implement proper MIME parsing for multi-part messages
-        let body = &payload["body"];
-        
-        if let Some(data) = body["data"].as_str() {
-            // Decode base64 body
-            if let Ok(decoded) = general_purpose::URL_SAFE_NO_PAD.decode(data) {
-                if let Ok(text) = String::from_utf8(decoded) {
-                    return (Some(text), None);
+        let (mut text, mut html) = (None, None);
+        Self::collect_bodies(payload, &mut text, &mut html);
+        (text, html)
+    }
+
+    /// Walk the MIME tree, keeping the first `text/plain` and `text/html` parts
+    /// that aren't attachments
+    fn collect_bodies(part: &serde_json::Value, text: &mut Option<String>, html: &mut Option<String>) {
+        let mime_type = part["mimeType"].as_str().unwrap_or("");
+        let is_attachment = part["filename"].as_str().map_or(false, |f| !f.is_empty());
+        if !is_attachment {
+            let decoded = part["body"]["data"]
+                .as_str()
+                .and_then(|data| general_purpose::URL_SAFE_NO_PAD.decode(data.trim_end_matches('=')).ok())
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            if let Some(decoded) = decoded {
+                match mime_type {
+                    "text/html" if html.is_none() => *html = Some(decoded),
+                    "text/html" => {}
+                    _ if text.is_none() => *text = Some(decoded),
+                    _ => {}
                 }
             }
         }
-        
-        // Check parts for multi-part messages
-        if let Some(parts) = payload["parts"].as_array() {
+
+        if let Some(parts) = part["parts"].as_array() {
             for part in parts {
-                let mime_type = part["mimeType"].as_str().unwrap_or("");
-                if mime_type == "text/plain" {
-                    if let Some(data) = part["body"]["data"].as_str() {
-                        if let Ok(decoded) = general_purpose::URL_SAFE_NO_PAD.decode(data) {
-                            if let Ok(text) = String::from_utf8(decoded) {
-                                return (Some(text), None);
-                            }
-                        }
-                    }
-                }
+                Self::collect_bodies(part, text, html);
             }
         }
-        
-        (None, None)
     }
 
     fn parse_email_attachments(payload: &serde_json::Value) -> Vec<EmailAttachment> {
         let mut attachments = Vec::new();
-        
-        if let Some(parts) = payload["parts"].as_array() {
+        Self::collect_attachments(payload, &mut attachments);
+        attachments
+    }
+
+    fn collect_attachments(part: &serde_json::Value, attachments: &mut Vec<EmailAttachment>) {
+        if let Some(filename) = part["filename"].as_str().filter(|f| !f.is_empty()) {
+            let header = |wanted: &str| {
+                part["headers"].as_array().and_then(|headers| {
+                    headers
+                        .iter()
+                        .find(|h| h["name"].as_str().map_or(false, |n| n.eq_ignore_ascii_case(wanted)))
+                        .and_then(|h| h["value"].as_str())
+                        .map(|v| v.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+                })
+            };
+            attachments.push(EmailAttachment {
+                id: part["body"]["attachmentId"].as_str().unwrap_or("").to_string(),
+                filename: filename.to_string(),
+                mime_type: part["mimeType"].as_str().unwrap_or("").to_string(),
+                size: part["body"]["size"].as_u64().unwrap_or(0),
+                // Small parts come inline; the rest is loaded separately when needed
+                data: part["body"]["data"]
+                    .as_str()
+                    .and_then(|data| general_purpose::URL_SAFE_NO_PAD.decode(data.trim_end_matches('=')).ok()),
+                content_id: header("Content-ID"),
+            });
+        }
+
+        if let Some(parts) = part["parts"].as_array() {
             for part in parts {
-                if let Some(filename) = part["filename"].as_str() {
-                    if !filename.is_empty() {
-                        let attachment = EmailAttachment {
-                            id: part["body"]["attachmentId"].as_str().unwrap_or("").to_string(),
-                            filename: filename.to_string(),
-                            mime_type: part["mimeType"].as_str().unwrap_or("").to_string(),
-                            size: part["body"]["size"].as_u64().unwrap_or(0),
-                            data: None, // Data is loaded separately when needed
-                        };
-                        attachments.push(attachment);
-                    }
+                Self::collect_attachments(part, attachments);
+            }
+        }
+    }
+}
+
+/// Pulls the base64url `data` string out of an attachment response as it
+/// streams in and writes the decoded bytes, so the file is never held whole
+/// in memory. The other fields (`attachmentId`, `size`) are skipped.
+#[derive(Default)]
+struct AttachmentDecoder {
+    /// Undecoded input: the tail of the JSON while looking for `"data"`,
+    /// then the base64 characters that don't yet make a full 4-byte group
+    pending: Vec<u8>,
+    in_data: bool,
+    done: bool,
+    written: u64,
+}
+
+impl AttachmentDecoder {
+    const KEY: &'static [u8] = b"\"data\"";
+
+    fn feed(&mut self, chunk: &[u8], out: &mut impl std::io::Write) -> Result<(), GmailError> {
+        if self.done {
+            return Ok(());
+        }
+        self.pending.extend_from_slice(chunk);
+        if !self.in_data {
+            let Some(start) = self.value_start() else {
+                // Keep enough of the tail to match a key split across chunks
+                let keep = self.pending.len().saturating_sub(64);
+                self.pending.drain(..keep);
+                return Ok(());
+            };
+            self.pending.drain(..start);
+            self.in_data = true;
+        }
+        if let Some(end) = self.pending.iter().position(|&b| b == b'"') {
+            self.pending.truncate(end);
+            self.done = true;
+            return Ok(());
+        }
+        let whole = self.pending.len() - self.pending.len() % 4;
+        let group: Vec<u8> = self.pending.drain(..whole).collect();
+        self.write(&group, out)
+    }
+
+    /// Index just past the opening quote of the `data` value, if it has arrived
+    fn value_start(&self) -> Option<usize> {
+        let mut from = 0;
+        while let Some(at) = self.pending[from..].windows(Self::KEY.len()).position(|w| w == Self::KEY) {
+            let mut i = from + at + Self::KEY.len();
+            while self.pending.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+                i += 1;
+            }
+            if self.pending.get(i) == Some(&b':') {
+                i += 1;
+                while self.pending.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+                    i += 1;
+                }
+                if self.pending.get(i) == Some(&b'"') {
+                    return Some(i + 1);
                 }
             }
+            from += at + 1;
+        }
+        None
+    }
+
+    fn write(&mut self, encoded: &[u8], out: &mut impl std::io::Write) -> Result<(), GmailError> {
+        let decoded = general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| GmailError::Parse(format!("Invalid attachment data: {}", e)))?;
+        out.write_all(&decoded).map_err(|e| GmailError::Storage(format!("Failed to write attachment: {}", e)))?;
+        self.written += decoded.len() as u64;
+        Ok(())
+    }
+
+    fn finish(mut self, out: &mut impl std::io::Write) -> Result<u64, GmailError> {
+        if !self.done {
+            return Err(GmailError::Parse("Attachment has no data".to_string()));
+        }
+        let rest: Vec<u8> = std::mem::take(&mut self.pending).into_iter().filter(|&b| b != b'=').collect();
+        if !rest.is_empty() {
+            self.write(&rest, out)?;
+        }
+        out.flush().map_err(|e| GmailError::Storage(format!("Failed to write attachment: {}", e)))?;
+        Ok(self.written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachment_data_decodes_across_chunks() {
+        let body = br#"{"attachmentId": "ANGjdJ8-data", "size": 11, "data" : "aGVsbG8gd29ybGQ="}"#;
+        for split in 1..body.len() {
+            let mut decoder = AttachmentDecoder::default();
+            let mut out = Vec::new();
+            for chunk in body.chunks(split) {
+                decoder.feed(chunk, &mut out).unwrap();
+            }
+            assert_eq!(decoder.finish(&mut out).unwrap(), 11);
+            assert_eq!(out, b"hello world", "chunk size {}", split);
         }
-        
-        attachments
     }
 }
\ No newline at end of file
@@ -0,0 +1,242 @@
+//! Saving Gmail attachments into the workspace.
+//!
+//! Files go to the configured attachments folder unless another workspace
+//! folder is given. A file whose bytes are already in that folder isn't
+//! written again; the existing copy is returned instead, so saving the same
+//! attachment twice (or the same file sent in two emails) leaves one file.
+//! Returned paths are workspace-relative, with a link form ready to paste into
+//! markdown.
+
+use crate::connections::gmail::models::{EmailAttachment, EmailMessage};
+use crate::connections::manager::ConnectionManager;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tempfile::NamedTempFile;
+
+/// Largest attachment saved; Gmail itself caps messages at 25 MB
+pub(crate) const MAX_BYTES: usize = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedAttachment {
+    pub filename: String,
+    /// Relative to the workspace root
+    pub path: String,
+    /// `path` escaped for a markdown link or embed
+    pub link: String,
+    pub size: u64,
+    /// An identical file was already in the folder and is reused
+    pub deduplicated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedAttachment {
+    pub filename: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedAttachments {
+    pub saved: Vec<SavedAttachment>,
+    pub skipped: Vec<SkippedAttachment>,
+}
+
+fn hash_file(path: &Path) -> std::io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+/// A file in `dir`, other than `staged` itself, with the same bytes as `staged`
+fn find_duplicate(dir: &Path, staged: &Path, size: u64) -> Option<PathBuf> {
+    let hash = hash_file(staged).ok()?;
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.metadata().is_ok_and(|m| m.is_file() && m.len() == size))
+        .map(|entry| entry.path())
+        .filter(|path| path != staged)
+        .find(|path| hash_file(path).is_ok_and(|existing| existing == hash))
+}
+
+/// The extension of an attachment name, without anything that could move the
+/// file out of its folder or break the name
+fn safe_extension(ext: &str) -> String {
+    let ext: String = ext.chars().filter(|c| !matches!(c, '/' | '\\' | ':') && !c.is_control()).collect();
+    if ext.is_empty() {
+        "bin".to_string()
+    } else {
+        ext
+    }
+}
+
+/// An empty hidden file in `folder` to write the attachment into; it's
+/// removed when dropped unless [`keep`] moves it into place
+fn stage(root: &Path, folder: &str) -> Result<(PathBuf, NamedTempFile), String> {
+    let dir = root.join(folder);
+    crate::vaults::ensure_writable(&dir)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachments folder: {}", e))?;
+    let staged = tempfile::Builder::new()
+        .prefix(".lokus-attachment-")
+        .suffix(".part")
+        .tempfile_in(&dir)
+        .map_err(|e| format!("Failed to save attachment: {}", e))?;
+    Ok((dir, staged))
+}
+
+/// Move a fully written staged file to `folder/filename`, or drop it in favour
+/// of an identical file already in the folder
+fn keep(dir: &Path, folder: &str, filename: &str, staged: NamedTempFile) -> Result<SavedAttachment, String> {
+    let size = staged.as_file().metadata().map_err(|e| format!("Failed to save attachment: {}", e))?.len();
+    let (path, deduplicated) = match find_duplicate(dir, staged.path(), size) {
+        Some(existing) => (existing, true),
+        None => {
+            let (name, ext) = match filename.rsplit_once('.') {
+                Some((name, ext)) if !name.is_empty() => (name, safe_extension(ext)),
+                _ => (filename, "bin".to_string()),
+            };
+            let path = crate::clipper::available_path(dir, &crate::import::sanitize_file_name(name), &ext);
+            staged.persist_noclobber(&path).map_err(|e| format!("Failed to save attachment: {}", e.error))?;
+            (path, false)
+        }
+    };
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let relative = format!("{}/{}", folder, file_name);
+    Ok(SavedAttachment {
+        filename: filename.to_string(),
+        link: relative.replace(' ', "%20"),
+        path: relative,
+        size,
+        deduplicated,
+    })
+}
+
+/// Write `data` as `folder/filename` under `root`, reusing an identical file
+pub(crate) fn store(root: &Path, folder: &str, filename: &str, data: &[u8]) -> Result<SavedAttachment, String> {
+    if data.len() > MAX_BYTES {
+        return Err(format!("{} is larger than {} MB", filename, MAX_BYTES / (1024 * 1024)));
+    }
+    let (dir, mut staged) = stage(root, folder)?;
+    staged.write_all(data).map_err(|e| format!("Failed to save attachment: {}", e))?;
+    keep(&dir, folder, filename, staged)
+}
+
+/// Save one attachment of `email` like [`store`], streaming it straight to
+/// disk when Gmail didn't send it inline
+pub(crate) async fn save(
+    manager: &ConnectionManager,
+    root: &Path,
+    folder: &str,
+    email: &EmailMessage,
+    attachment: &EmailAttachment,
+) -> Result<SavedAttachment, String> {
+    if attachment.size > MAX_BYTES as u64 {
+        return Err(format!("{} is larger than {} MB", attachment.filename, MAX_BYTES / (1024 * 1024)));
+    }
+    if let Some(data) = &attachment.data {
+        return store(root, folder, &attachment.filename, data);
+    }
+    let (dir, mut staged) = stage(root, folder)?;
+    let mut out = std::io::BufWriter::new(staged.as_file_mut());
+    manager
+        .download_attachment(&email.id, &attachment.id, MAX_BYTES, &mut out)
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", attachment.filename, e))?;
+    drop(out);
+    keep(&dir, folder, &attachment.filename, staged)
+}
+
+fn target_folder(app: &AppHandle, folder: Option<String>) -> Result<String, String> {
+    match folder {
+        Some(folder) => super::notes::safe_folder(&folder),
+        None => Ok(crate::attachments::configured_folder(app)),
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Save one attachment of a message into the workspace
+#[tauri::command]
+pub async fn gmail_save_attachment(
+    app: AppHandle,
+    workspace_path: String,
+    email_id: String,
+    attachment_id: String,
+    folder: Option<String>,
+) -> Result<SavedAttachment, String> {
+    let root = PathBuf::from(&workspace_path);
+    let folder = target_folder(&app, folder)?;
+    let manager = app.state::<ConnectionManager>();
+    let email = manager.get_email_by_id(&email_id).await.map_err(|e| e.to_string())?;
+    let attachment = email
+        .attachments
+        .iter()
+        .find(|a| a.id == attachment_id)
+        .ok_or_else(|| format!("Email {} has no attachment {}", email_id, attachment_id))?;
+
+    save(&manager, &root, &folder, &email, attachment).await
+}
+
+/// Save every attachment of a message; ones that can't be saved are reported, not fatal
+#[tauri::command]
+pub async fn gmail_save_all_attachments(
+    app: AppHandle,
+    workspace_path: String,
+    email_id: String,
+    folder: Option<String>,
+) -> Result<SavedAttachments, String> {
+    let root = PathBuf::from(&workspace_path);
+    let folder = target_folder(&app, folder)?;
+    let manager = app.state::<ConnectionManager>();
+    let email = manager.get_email_by_id(&email_id).await.map_err(|e| e.to_string())?;
+
+    let mut result = SavedAttachments::default();
+    for attachment in &email.attachments {
+        match save(&manager, &root, &folder, &email, attachment).await {
+            Ok(saved) => result.saved.push(saved),
+            Err(reason) => {
+                tracing::warn!("Failed to save attachment '{}': {}", attachment.filename, reason);
+                result.skipped.push(SkippedAttachment { filename: attachment.filename.clone(), reason });
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_files_are_stored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = store(dir.path(), "attachments", "report.pdf", b"same bytes").unwrap();
+        assert_eq!(first.path, "attachments/report.pdf");
+        assert!(!first.deduplicated);
+
+        let again = store(dir.path(), "attachments", "copy of report.pdf", b"same bytes").unwrap();
+        assert_eq!(again.path, "attachments/report.pdf");
+        assert!(again.deduplicated);
+
+        let other = store(dir.path(), "attachments", "report.pdf", b"other bytes").unwrap();
+        assert_eq!(other.path, "attachments/report 2.pdf");
+        assert_eq!(other.link, "attachments/report%202.pdf");
+        assert_eq!(fs::read_dir(dir.path().join("attachments")).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn extension_cannot_leave_the_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let saved = store(dir.path(), "attachments", "photo.p\\ng\u{7}", b"bytes").unwrap();
+        assert_eq!(saved.path, "attachments/photo.png");
+
+        let saved = store(dir.path(), "attachments", "notes.:", b"other bytes").unwrap();
+        assert_eq!(saved.path, "attachments/notes.bin");
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use crate::connections::gmail::models::{GmailToken, GmailProfile, GmailError};
 use crate::connections::gmail::storage::GmailStorage;
+use crate::connections::oauth_manager::{self, Provider};
 use reqwest::Client;
 use serde_json;
 use uuid::Uuid;
@@ -69,6 +70,8 @@ impl GmailAuth {
             "https://www.googleapis.com/auth/gmail.labels",
             "https://www.googleapis.com/auth/userinfo.email",
             "https://www.googleapis.com/auth/userinfo.profile",
+            // Contacts for @-mentions and attendee links
+            "https://www.googleapis.com/auth/contacts.readonly",
         ].join(" ");
 
         let mut params = HashMap::new();
@@ -157,100 +160,9 @@ impl GmailAuth {
         Ok(token)
     }
 
-    pub async fn refresh_token(&self, refresh_token: &str) -> Result<GmailToken, GmailError> {
-        
-        let client = Client::new();
-        let mut params = HashMap::new();
-        params.insert("client_id", self.client_id.as_str());
-        params.insert("client_secret", self.client_secret.as_str());
-        params.insert("refresh_token", refresh_token);
-        params.insert("grant_type", "refresh_token");
-
-        let response = client
-            .post("https://oauth2.googleapis.com/token")
-            .form(&params)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let _error_text = response.text().await.unwrap_or_default();
-            
-            // If refresh fails, delete stored token
-            let _ = GmailStorage::delete_token();
-            let _ = GmailStorage::delete_profile();
-            
-            return Err(GmailError::TokenExpired);
-        }
-
-        let token_data: serde_json::Value = response.json().await?;
-
-        let access_token = token_data["access_token"]
-            .as_str()
-            .ok_or_else(|| GmailError::Auth("No access token in refresh response".to_string()))?;
-
-        // Use the new refresh token if provided, otherwise keep the existing one
-        let new_refresh_token = token_data["refresh_token"]
-            .as_str()
-            .map(|s| s.to_string())
-            .or_else(|| Some(refresh_token.to_string()));
-
-        let expires_in = token_data["expires_in"].as_u64().unwrap_or(3600);
-        let expires_at = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() + expires_in;
-
-        let scope = token_data["scope"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
-
-        let token = GmailToken {
-            access_token: access_token.to_string(),
-            refresh_token: new_refresh_token,
-            expires_at: Some(expires_at),
-            scope,
-            token_type: token_data["token_type"]
-                .as_str()
-                .unwrap_or("Bearer")
-                .to_string(),
-        };
-
-        // Store the refreshed token
-        GmailStorage::store_token(&token)?;
-
-        Ok(token)
-    }
-
+    /// A token that is good for at least a few more minutes, refreshed if need be
     pub async fn get_valid_token(&self) -> Result<GmailToken, GmailError> {
-        // println!("[GMAIL] 🔍 Getting valid Gmail token");
-        
-        let token = match GmailStorage::get_token()? {
-            Some(token) => token,
-            None => {
-                return Err(GmailError::Auth("No Gmail token found - please authenticate".to_string()));
-            }
-        };
-
-        // Check if token is expired
-        if GmailStorage::is_token_expired(&token) {
-            
-            if let Some(refresh_token) = &token.refresh_token {
-                match self.refresh_token(refresh_token).await {
-                    Ok(new_token) => {
-                        return Ok(new_token);
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
-                }
-            } else {
-                return Err(GmailError::TokenExpired);
-            }
-        }
-
-        // println!("[GMAIL] ✅ Valid Gmail token found");
-        Ok(token)
+        Ok(oauth_manager::access_token(Provider::Gmail).await?.into())
     }
 
     pub async fn fetch_and_store_profile(&self, token: &GmailToken) -> Result<GmailProfile, GmailError> {
@@ -310,24 +222,8 @@ impl GmailAuth {
         Ok(profile)
     }
 
-    pub async fn revoke_token(&self, token: &str) -> Result<(), GmailError> {
-        
-        let client = Client::new();
-        let response = client
-            .post("https://oauth2.googleapis.com/revoke")
-            .form(&[("token", token)])
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-        } else {
-        }
-
-        // Delete local storage regardless of revocation result
-        GmailStorage::delete_token()?;
-        GmailStorage::delete_profile()?;
-        
-        Ok(())
+    pub async fn revoke_token(&self) -> Result<(), GmailError> {
+        oauth_manager::revoke(Provider::Gmail).await.map_err(GmailError::Storage)
     }
 
     pub fn is_authenticated(&self) -> Result<bool, GmailError> {
@@ -3,6 +3,9 @@ pub mod api;
 pub mod models;
 pub mod storage;
 pub mod queue;
+pub mod notes;
+pub mod attachments;
+pub mod outbox;
 
 pub use auth::*;
 pub use api::*;
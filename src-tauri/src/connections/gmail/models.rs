@@ -43,6 +43,9 @@ pub struct EmailAttachment {
     pub mime_type: String,
     pub size: u64,
     pub data: Option<Vec<u8>>,
+    /// `Content-ID` of inline parts, referenced as `cid:` from the HTML body
+    #[serde(default)]
+    pub content_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,7 +148,6 @@ pub enum GmailError {
     Api(String),
     
     #[error("Rate limit exceeded")]
-    #[allow(dead_code)]
     RateLimit,
     
     #[error("Token expired")]
@@ -0,0 +1,451 @@
+//! Saving Gmail messages as notes.
+//!
+//! The HTML body is converted to markdown (falling back to the plain text
+//! part), attachments are saved to the attachments folder and listed at the
+//! end of the note, with inline `cid:` images pointing at the saved files,
+//! and the headers go into the frontmatter.
+//!
+//! Label watches are stored per workspace in `.lokus/gmail-watches.json`. A
+//! background loop imports messages that gained a watched label since the
+//! watch was created, each one once.
+
+use crate::connections::gmail::attachments;
+use crate::connections::gmail::models::{EmailAddress, EmailMessage};
+use crate::connections::manager::ConnectionManager;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const WATCHES_FILE: &str = "gmail-watches.json";
+const IMPORTED_EVENT: &str = "gmail-notes-imported";
+const DEFAULT_FOLDER: &str = "Email";
+const TICK: Duration = Duration::from_secs(60);
+const POLL_INTERVAL_SECS: i64 = 5 * 60;
+/// Newest messages of a label looked at per poll
+const PAGE_SIZE: u32 = 25;
+/// Message ids remembered per watch
+const MAX_IMPORTED: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmailNoteOptions {
+    /// Note title; the subject by default
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub include_attachments: bool,
+}
+
+impl Default for EmailNoteOptions {
+    fn default() -> Self {
+        Self { title: None, tags: Vec::new(), include_attachments: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedEmail {
+    pub path: String,
+    /// Workspace-relative paths of the saved attachments
+    pub attachments: Vec<String>,
+    /// Attachments that couldn't be downloaded
+    pub failed_attachments: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelWatch {
+    pub id: String,
+    pub label_id: String,
+    pub label_name: String,
+    /// Workspace-relative folder imported messages are written to
+    pub folder: String,
+    /// Unix seconds
+    pub last_checked: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WatchStore {
+    watches: Vec<LabelWatch>,
+    /// Watch id to the message ids already imported (or present when the
+    /// watch was created), oldest first
+    imported: HashMap<String, Vec<String>>,
+}
+
+// --- Storage ---
+
+fn store_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(WATCHES_FILE)
+}
+
+fn load(root: &Path) -> WatchStore {
+    fs::read_to_string(store_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(root: &Path, store: &WatchStore) -> Result<(), String> {
+    let path = store_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save label watches: {}", e))
+}
+
+pub(crate) fn safe_folder(folder: &str) -> Result<String, String> {
+    let folder = folder.trim().trim_matches('/');
+    if folder.is_empty() || Path::new(folder).components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid folder: {}", folder));
+    }
+    Ok(folder.to_string())
+}
+
+// --- Conversion ---
+
+fn format_address(address: &EmailAddress) -> String {
+    match &address.name {
+        Some(name) => format!("{} <{}>", name, address.email),
+        None => address.email.clone(),
+    }
+}
+
+fn address_list(addresses: &[EmailAddress]) -> serde_json::Value {
+    let formatted: Vec<String> = addresses.iter().filter(|a| !a.email.is_empty()).map(format_address).collect();
+    if formatted.is_empty() {
+        serde_json::Value::Null
+    } else {
+        json!(formatted)
+    }
+}
+
+/// Markdown body of the message; `cid:` images resolve through `inline`
+fn body_markdown(email: &EmailMessage, inline: &HashMap<String, String>) -> String {
+    use crate::import::html::Reference;
+    if let Some(html) = email.body_html.as_deref().filter(|h| !h.trim().is_empty()) {
+        let conversion = crate::import::html::html_to_markdown(html, &mut |reference| match reference {
+            Reference::Image(src) => src.strip_prefix("cid:").and_then(|cid| inline.get(cid)).cloned(),
+            _ => None,
+        });
+        return conversion.markdown.trim().to_string();
+    }
+    email
+        .body_text
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(&email.snippet)
+        .replace("\r\n", "\n")
+        .trim()
+        .to_string()
+}
+
+/// The note for `email`; `attachments` pairs file names with link targets
+fn render_note(email: &EmailMessage, title: &str, tags: &[String], body: &str, attachments: &[(String, String)]) -> Result<String, String> {
+    let mut note = format!("# {}\n\n{}\n", title, body);
+    if !attachments.is_empty() {
+        note.push_str("\n## Attachments\n\n");
+        for (name, link) in attachments {
+            note.push_str(&format!("- [{}]({})\n", name, link));
+        }
+    }
+
+    let mut all_tags = vec!["email".to_string()];
+    all_tags.extend(tags.iter().map(|t| t.trim().trim_start_matches('#').to_string()).filter(|t| !t.is_empty() && t != "email"));
+    let fields = [
+        ("subject", json!(email.subject)),
+        ("from", address_list(&email.from)),
+        ("to", address_list(&email.to)),
+        ("cc", address_list(email.cc.as_deref().unwrap_or_default())),
+        ("date", json!(email.date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))),
+        ("gmailId", json!(email.id)),
+        ("gmailThreadId", json!(email.thread_id)),
+        ("tags", json!(all_tags)),
+    ];
+    for (key, value) in fields {
+        note = crate::frontmatter::set_field(&note, key, &value)?;
+    }
+    Ok(note)
+}
+
+/// Save `email` as a note in `folder` of the workspace at `root`
+async fn save_email(
+    app: &AppHandle,
+    manager: &ConnectionManager,
+    root: &Path,
+    email: &EmailMessage,
+    folder: &str,
+    options: &EmailNoteOptions,
+) -> Result<SavedEmail, String> {
+    let title = options
+        .title
+        .clone()
+        .or_else(|| Some(email.subject.trim().to_string()))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Untitled email".to_string());
+    let stem = crate::import::sanitize_file_name(&title);
+    let note_dir = root.join(folder);
+    fs::create_dir_all(&note_dir).map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    let mut saved = SavedEmail { path: String::new(), attachments: Vec::new(), failed_attachments: Vec::new() };
+    let mut links = Vec::new();
+    let mut inline = HashMap::new();
+    if options.include_attachments && !email.attachments.is_empty() {
+        let attachments_folder = crate::attachments::configured_folder(app);
+        for attachment in &email.attachments {
+            let relative = match attachments::save(manager, root, &attachments_folder, email, attachment).await {
+                Ok(stored) => stored.path,
+                Err(e) => {
+                    tracing::warn!("Failed to save attachment '{}': {}", attachment.filename, e);
+                    saved.failed_attachments.push(attachment.filename.clone());
+                    continue;
+                }
+            };
+            let link = crate::clipper::relative_link(folder, &relative);
+            if let Some(cid) = &attachment.content_id {
+                inline.insert(cid.clone(), link.clone());
+            }
+            links.push((attachment.filename.clone(), link));
+            saved.attachments.push(relative);
+        }
+    }
+
+    let body = body_markdown(email, &inline);
+    let note = render_note(email, &title, &options.tags, &body, &links)?;
+    let path = crate::clipper::available_path(&note_dir, &stem, "md").to_string_lossy().to_string();
+    crate::handlers::files::write_file_content(path.clone(), note)?;
+    saved.path = path;
+    Ok(saved)
+}
+
+// --- Label watches ---
+
+/// Find a label by id or (case-insensitively) by name
+async fn resolve_label(manager: &ConnectionManager, label: &str) -> Result<(String, String), String> {
+    let label = label.trim();
+    manager
+        .get_labels()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|l| l.id == label || l.name.eq_ignore_ascii_case(label))
+        .map(|l| (l.id, l.name))
+        .ok_or_else(|| format!("No Gmail label named '{}'", label))
+}
+
+/// Import the label's messages not seen before, oldest first
+async fn import_new(
+    app: &AppHandle,
+    manager: &ConnectionManager,
+    root: &Path,
+    watch: &LabelWatch,
+    imported: &mut Vec<String>,
+) -> Result<Vec<String>, String> {
+    let ids = manager.list_message_ids(&watch.label_id, PAGE_SIZE).await.map_err(|e| e.to_string())?;
+    let known: HashSet<String> = imported.iter().cloned().collect();
+    let mut created = Vec::new();
+    for id in ids.into_iter().filter(|id| !known.contains(id)).rev() {
+        let email = manager.get_email_by_id(&id).await.map_err(|e| e.to_string())?;
+        match save_email(app, manager, root, &email, &watch.folder, &EmailNoteOptions::default()).await {
+            Ok(saved) => created.push(saved.path),
+            Err(e) => tracing::warn!("Failed to save email '{}': {}", email.subject, e),
+        }
+        imported.push(id);
+    }
+    let excess = imported.len().saturating_sub(MAX_IMPORTED);
+    imported.drain(..excess);
+    Ok(created)
+}
+
+/// Poll the due watches of the workspace at `root`; returns the notes created
+async fn poll(app: &AppHandle, root: &Path) -> Result<Vec<String>, String> {
+    let mut store = load(root);
+    if store.watches.is_empty() {
+        return Ok(Vec::new());
+    }
+    let manager = app.state::<ConnectionManager>();
+    if !manager.is_gmail_authenticated().await.unwrap_or(false) {
+        return Ok(Vec::new());
+    }
+
+    let now = Utc::now().timestamp();
+    let mut created = Vec::new();
+    let mut polled = false;
+    for index in 0..store.watches.len() {
+        let watch = store.watches[index].clone();
+        if watch.last_checked.map_or(false, |t| now - t < POLL_INTERVAL_SECS) {
+            continue;
+        }
+        polled = true;
+        let imported = store.imported.entry(watch.id.clone()).or_default();
+        let outcome = import_new(app, &manager, root, &watch, imported).await;
+        let stored = &mut store.watches[index];
+        stored.last_checked = Some(now);
+        match outcome {
+            Ok(paths) => {
+                stored.last_error = None;
+                created.extend(paths);
+            }
+            Err(e) => stored.last_error = Some(e),
+        }
+    }
+
+    if polled {
+        // Watches may have changed while we were fetching; only update the ones still there
+        let mut latest = load(root);
+        for watch in &mut latest.watches {
+            if let Some(polled) = store.watches.iter().find(|w| w.id == watch.id) {
+                watch.last_checked = polled.last_checked;
+                watch.last_error = polled.last_error.clone();
+                if let Some(imported) = store.imported.remove(&watch.id) {
+                    latest.imported.insert(watch.id.clone(), imported);
+                }
+            }
+        }
+        save(root, &latest)?;
+    }
+    Ok(created)
+}
+
+/// Import watched labels into the open workspace for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            let Some(root) = crate::watcher::active_root() else {
+                continue;
+            };
+            match poll(&app, &root).await {
+                Ok(created) if !created.is_empty() => {
+                    if let Err(e) = app.emit(IMPORTED_EVENT, &created) {
+                        tracing::warn!("Failed to emit {}: {}", IMPORTED_EVENT, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Gmail label import failed: {}", e),
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn gmail_save_email_as_note(
+    app: AppHandle,
+    workspace_path: String,
+    email_id: String,
+    folder: Option<String>,
+    options: Option<EmailNoteOptions>,
+) -> Result<SavedEmail, String> {
+    let root = PathBuf::from(&workspace_path);
+    let folder = safe_folder(folder.as_deref().unwrap_or(DEFAULT_FOLDER))?;
+    let manager = app.state::<ConnectionManager>();
+    let email = manager.get_email_by_id(&email_id).await.map_err(|e| e.to_string())?;
+    save_email(&app, &manager, &root, &email, &folder, &options.unwrap_or_default()).await
+}
+
+/// Start importing messages that get `label` (a label id or name) from now on
+#[tauri::command]
+pub async fn gmail_watch_label(
+    app: AppHandle,
+    workspace_path: String,
+    label: String,
+    folder: Option<String>,
+) -> Result<LabelWatch, String> {
+    let root = PathBuf::from(&workspace_path);
+    let folder = safe_folder(folder.as_deref().unwrap_or(DEFAULT_FOLDER))?;
+    let manager = app.state::<ConnectionManager>();
+    let (label_id, label_name) = resolve_label(&manager, &label).await?;
+
+    let mut store = load(&root);
+    if store.watches.iter().any(|w| w.label_id == label_id && w.folder == folder) {
+        return Err(format!("'{}' is already imported into {}", label_name, folder));
+    }
+    // Messages already in the label are left alone
+    let existing = manager.list_message_ids(&label_id, PAGE_SIZE).await.map_err(|e| e.to_string())?;
+    let watch = LabelWatch {
+        id: uuid::Uuid::new_v4().to_string(),
+        label_id,
+        label_name,
+        folder,
+        last_checked: Some(Utc::now().timestamp()),
+        last_error: None,
+    };
+    store.imported.insert(watch.id.clone(), existing.into_iter().rev().collect());
+    store.watches.push(watch.clone());
+    save(&root, &store)?;
+    Ok(watch)
+}
+
+#[tauri::command]
+pub fn gmail_list_label_watches(workspace_path: String) -> Vec<LabelWatch> {
+    load(Path::new(&workspace_path)).watches
+}
+
+/// Stop watching; notes already imported are kept
+#[tauri::command]
+pub fn gmail_unwatch_label(workspace_path: String, id: String) -> Result<bool, String> {
+    let root = PathBuf::from(&workspace_path);
+    let mut store = load(&root);
+    let before = store.watches.len();
+    store.watches.retain(|w| w.id != id);
+    store.imported.remove(&id);
+    if store.watches.len() == before {
+        return Ok(false);
+    }
+    save(&root, &store)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email() -> EmailMessage {
+        EmailMessage {
+            id: "msg-1".into(),
+            thread_id: "thread-1".into(),
+            subject: "Quarterly report".into(),
+            from: vec![EmailAddress { email: "ada@example.com".into(), name: Some("Ada".into()) }],
+            to: vec![EmailAddress { email: "team@example.com".into(), name: None }],
+            cc: None,
+            bcc: None,
+            body_text: Some("Plain version".into()),
+            body_html: Some(r#"<p>See the <b>chart</b>:</p><img src="cid:chart@mail">"#.into()),
+            attachments: Vec::new(),
+            labels: vec!["INBOX".into()],
+            snippet: "See the chart".into(),
+            date: chrono::DateTime::parse_from_rfc3339("2024-03-01T09:30:00Z").unwrap().with_timezone(&Utc),
+            is_read: true,
+            is_starred: false,
+            size_estimate: 0,
+        }
+    }
+
+    #[test]
+    fn test_email_renders_with_headers_and_attachments() {
+        let email = email();
+        let inline = HashMap::from([("chart@mail".to_string(), "../attachments/chart.png".to_string())]);
+        let body = body_markdown(&email, &inline);
+        assert!(body.contains("See the **chart**:"));
+        assert!(body.contains("](../attachments/chart.png)"));
+
+        let links = vec![("chart.png".to_string(), "../attachments/chart.png".to_string())];
+        let note = render_note(&email, "Quarterly report", &["work".to_string()], &body, &links).unwrap();
+        let frontmatter = crate::frontmatter::parse(&note).unwrap();
+        assert_eq!(frontmatter["from"][0], "Ada <ada@example.com>");
+        assert_eq!(frontmatter["to"][0], "team@example.com");
+        assert!(frontmatter.get("cc").is_none());
+        assert_eq!(frontmatter["date"], "2024-03-01T09:30:00Z");
+        assert_eq!(frontmatter["gmailId"], "msg-1");
+        assert_eq!(frontmatter["tags"], json!(["email", "work"]));
+        assert!(note.contains("# Quarterly report\n\n"));
+        assert!(note.contains("## Attachments\n\n- [chart.png](../attachments/chart.png)\n"));
+    }
+}
@@ -0,0 +1,313 @@
+//! Outbox for messages that couldn't be sent right away.
+//!
+//! Sending, replying and forwarding try Gmail first. When that fails for a
+//! reason that may pass — no connection, rate limiting, Gmail having a bad
+//! moment — the message is written to `~/.lokus/gmail/outbox.json` instead of
+//! being lost. A background loop retries due messages with exponential
+//! backoff, and retries everything at once when Gmail can be reached again.
+//! Sends are never retried automatically on the spot: a request that timed
+//! out may still have gone through. Delivery and permanent failure are
+//! announced with events; failed messages stay in the outbox until cancelled.
+
+use crate::connections::gmail::models::{EmailComposer, GmailError};
+use crate::connections::manager::ConnectionManager;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const OUTBOX_FILE: &str = "outbox.json";
+const QUEUED_EVENT: &str = "gmail-outbox-queued";
+const DELIVERED_EVENT: &str = "gmail-outbox-delivered";
+const FAILED_EVENT: &str = "gmail-outbox-failed";
+/// Ids of queued messages start with this, so callers can tell them from Gmail ids
+pub const ID_PREFIX: &str = "outbox-";
+const TICK: Duration = Duration::from_secs(30);
+const MAX_ATTEMPTS: u32 = 8;
+const FIRST_RETRY_SECS: i64 = 30;
+const MAX_RETRY_SECS: i64 = 60 * 60;
+const GMAIL_API: &str = "https://gmail.googleapis.com/";
+
+/// Serializes read-modify-write cycles of the outbox file
+static OUTBOX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static FLUSHING: AtomicBool = AtomicBool::new(false);
+/// Set when a send fails for a reason that may pass, cleared when one gets through
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OutboxKind {
+    Send,
+    /// The original is fetched when the reply goes out
+    #[serde(rename_all = "camelCase")]
+    Reply { message_id: String },
+    #[serde(rename_all = "camelCase")]
+    Forward { message_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: OutboxKind,
+    pub composer: EmailComposer,
+    pub created_at: DateTime<Utc>,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    /// Given up on; kept so the user can see what wasn't sent
+    pub failed: bool,
+}
+
+/// Errors that may go away by themselves
+fn is_transient(error: &GmailError) -> bool {
+    matches!(error, GmailError::Network(_) | GmailError::RateLimit)
+}
+
+/// Delay before the given retry (1-based): 30s, 1m, 2m, ... up to an hour
+fn retry_delay(attempts: u32) -> chrono::Duration {
+    let secs = FIRST_RETRY_SECS.saturating_mul(1i64 << attempts.saturating_sub(1).min(20));
+    chrono::Duration::seconds(secs.min(MAX_RETRY_SECS))
+}
+
+fn outbox_path() -> Result<PathBuf, String> {
+    let dir = dirs::home_dir().ok_or("Failed to get home directory")?.join(".lokus").join("gmail");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create Gmail directory: {}", e))?;
+    Ok(dir.join(OUTBOX_FILE))
+}
+
+fn load() -> Result<Vec<OutboxEntry>, String> {
+    let path = outbox_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read outbox: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse outbox: {}", e))
+}
+
+fn save(entries: &[OutboxEntry]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(entries).map_err(|e| format!("Failed to serialize outbox: {}", e))?;
+    std::fs::write(outbox_path()?, json).map_err(|e| format!("Failed to save outbox: {}", e))
+}
+
+/// Apply `change` to the outbox under the lock
+fn update<T>(change: impl FnOnce(&mut Vec<OutboxEntry>) -> T) -> Result<T, String> {
+    let _guard = OUTBOX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = load()?;
+    let result = change(&mut entries);
+    save(&entries)?;
+    Ok(result)
+}
+
+fn emit(app: &AppHandle, event: &str, payload: serde_json::Value) {
+    if let Err(e) = app.emit(event, payload) {
+        tracing::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+async fn deliver(manager: &ConnectionManager, kind: &OutboxKind, composer: &EmailComposer) -> Result<String, GmailError> {
+    let composer = composer.clone();
+    let result = match kind {
+        OutboxKind::Send => manager.send_email(composer).await,
+        OutboxKind::Reply { message_id } => manager.reply_to_email(message_id, composer).await,
+        OutboxKind::Forward { message_id } => manager.forward_email(message_id, composer).await,
+    };
+    match &result {
+        Ok(_) => OFFLINE.store(false, Ordering::SeqCst),
+        Err(e) if is_transient(e) => OFFLINE.store(true, Ordering::SeqCst),
+        Err(_) => {}
+    }
+    result
+}
+
+/// Whether Gmail answers at all; any HTTP response means the connection is back
+async fn gmail_reachable() -> bool {
+    reqwest::Client::new()
+        .head(GMAIL_API)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .is_ok()
+}
+
+/// Send now, or queue the message if Gmail can't be reached. Returns the Gmail
+/// message id, or the outbox id (`outbox-…`) when the message was queued.
+pub async fn send_or_queue(app: &AppHandle, kind: OutboxKind, composer: EmailComposer) -> Result<String, String> {
+    let manager = app.state::<ConnectionManager>();
+    let error = match deliver(&manager, &kind, &composer).await {
+        Ok(message_id) => return Ok(message_id),
+        Err(e) if is_transient(&e) => e,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let now = Utc::now();
+    let entry = OutboxEntry {
+        id: format!("{}{}", ID_PREFIX, uuid::Uuid::new_v4()),
+        kind,
+        composer,
+        created_at: now,
+        attempts: 1,
+        next_attempt_at: now + retry_delay(1),
+        last_error: Some(error.to_string()),
+        failed: false,
+    };
+    update(|entries| entries.push(entry.clone()))?;
+    tracing::info!("Queued message {} in the Gmail outbox: {}", entry.id, error);
+    emit(app, QUEUED_EVENT, json!({ "id": entry.id, "subject": entry.composer.subject, "error": error.to_string() }));
+    Ok(entry.id)
+}
+
+/// Try the messages that are due, or all waiting messages with `all`
+async fn flush(app: &AppHandle, all: bool) -> Result<(), String> {
+    if FLUSHING.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let result = flush_pending(app, all).await;
+    FLUSHING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn flush_pending(app: &AppHandle, all: bool) -> Result<(), String> {
+    let now = Utc::now();
+    let due: Vec<OutboxEntry> = {
+        let _guard = OUTBOX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load()?.into_iter().filter(|e| !e.failed && (all || e.next_attempt_at <= now)).collect()
+    };
+    let manager = app.state::<ConnectionManager>();
+
+    for entry in due {
+        // Cancelled while earlier messages were being sent
+        let still_queued = {
+            let _guard = OUTBOX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            load()?.iter().any(|e| e.id == entry.id)
+        };
+        if !still_queued {
+            continue;
+        }
+
+        match deliver(&manager, &entry.kind, &entry.composer).await {
+            Ok(message_id) => {
+                update(|entries| entries.retain(|e| e.id != entry.id))?;
+                emit(
+                    app,
+                    DELIVERED_EVENT,
+                    json!({ "id": entry.id, "messageId": message_id, "subject": entry.composer.subject }),
+                );
+            }
+            Err(error) => {
+                let transient = is_transient(&error);
+                let gave_up = update(|entries| {
+                    let Some(queued) = entries.iter_mut().find(|e| e.id == entry.id) else {
+                        return false;
+                    };
+                    queued.attempts += 1;
+                    queued.last_error = Some(error.to_string());
+                    queued.next_attempt_at = Utc::now() + retry_delay(queued.attempts);
+                    queued.failed = !transient || queued.attempts >= MAX_ATTEMPTS;
+                    queued.failed
+                })?;
+                if gave_up {
+                    emit(
+                        app,
+                        FAILED_EVENT,
+                        json!({ "id": entry.id, "subject": entry.composer.subject, "error": error.to_string() }),
+                    );
+                }
+                // Still offline; the rest would fail the same way
+                if transient {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Retry queued messages for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            let reconnected = OFFLINE.load(Ordering::SeqCst) && gmail_reachable().await;
+            if let Err(e) = flush(&app, reconnected).await {
+                tracing::warn!("Gmail outbox flush failed: {}", e);
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Messages waiting to be sent or given up on, oldest first
+#[tauri::command]
+pub fn gmail_outbox_list() -> Result<Vec<OutboxEntry>, String> {
+    let _guard = OUTBOX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = load()?;
+    entries.sort_by_key(|e| e.created_at);
+    Ok(entries)
+}
+
+/// Drop a message from the outbox without sending it
+#[tauri::command]
+pub fn gmail_outbox_cancel(id: String) -> Result<(), String> {
+    let removed = update(|entries| {
+        let before = entries.len();
+        entries.retain(|e| e.id != id);
+        before != entries.len()
+    })?;
+    if removed {
+        Ok(())
+    } else {
+        Err(format!("No message {} in the outbox", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_to_an_hour() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(30));
+        assert_eq!(retry_delay(2), chrono::Duration::seconds(60));
+        assert_eq!(retry_delay(4), chrono::Duration::seconds(240));
+        assert_eq!(retry_delay(40), chrono::Duration::seconds(MAX_RETRY_SECS));
+        assert!(is_transient(&GmailError::Network("offline".to_string())));
+        assert!(!is_transient(&GmailError::Api("invalid recipient".to_string())));
+    }
+
+    #[test]
+    fn entries_round_trip_with_their_kind() {
+        let entry = OutboxEntry {
+            id: "outbox-1".to_string(),
+            kind: OutboxKind::Reply { message_id: "m1".to_string() },
+            composer: EmailComposer {
+                to: Vec::new(),
+                cc: None,
+                bcc: None,
+                subject: "Re: hi".to_string(),
+                body_text: Some("hello".to_string()),
+                body_html: None,
+                attachments: Vec::new(),
+                in_reply_to: None,
+                references: None,
+            },
+            created_at: Utc::now(),
+            attempts: 1,
+            next_attempt_at: Utc::now(),
+            last_error: None,
+            failed: false,
+        };
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(value["kind"], "reply");
+        assert_eq!(value["messageId"], "m1");
+        let back: OutboxEntry = serde_json::from_value(value).unwrap();
+        assert_eq!(back.kind, entry.kind);
+    }
+}
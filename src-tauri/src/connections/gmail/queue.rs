@@ -14,7 +14,12 @@ pub struct OfflineQueue {
 
 impl OfflineQueue {
     pub fn new() -> Result<Self, GmailError> {
-        let queue_file_path = Self::get_queue_file_path()?;
+        Self::for_provider("gmail")
+    }
+
+    /// A queue persisted under `~/.lokus/<provider>/`
+    pub fn for_provider(provider: &str) -> Result<Self, GmailError> {
+        let queue_file_path = Self::get_queue_file_path(provider)?;
         let operations = Arc::new(Mutex::new(HashMap::new()));
         
         let queue = Self {
@@ -28,13 +33,13 @@ impl OfflineQueue {
         Ok(queue)
     }
 
-    fn get_queue_file_path() -> Result<PathBuf, GmailError> {
+    fn get_queue_file_path(provider: &str) -> Result<PathBuf, GmailError> {
         let home_dir = dirs::home_dir()
             .ok_or_else(|| GmailError::Storage("Failed to get home directory".to_string()))?;
-        let app_dir = home_dir.join(".lokus").join("gmail");
+        let app_dir = home_dir.join(".lokus").join(provider);
         if !app_dir.exists() {
             std::fs::create_dir_all(&app_dir)
-                .map_err(|e| GmailError::Storage(format!("Failed to create Gmail app directory: {}", e)))?;
+                .map_err(|e| GmailError::Storage(format!("Failed to create {} app directory: {}", provider, e)))?;
         }
         Ok(app_dir.join("offline_queue.json"))
     }
@@ -74,7 +79,6 @@ impl OfflineQueue {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn mark_operation_failed(&self, operation_id: &str, error: &str) -> Result<(), GmailError> {
         {
             let mut operations = self.operations.lock().unwrap();
@@ -0,0 +1,268 @@
+//! Blocking IMAP access and message parsing; callers run these on the
+//! blocking thread pool.
+
+use super::{ImapAccount, Security};
+use crate::connections::gmail::models::{EmailAddress, EmailAttachment, EmailMessage};
+use crate::sanitize::{sanitize_html, Policy};
+use chrono::{DateTime, Utc};
+use imap::types::{Flag, NameAttribute};
+use mail_parser::{Address, MessageParser, MimeHeaders};
+use native_tls::{TlsConnector, TlsStream};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+type Session = imap::Session<TlsStream<TcpStream>>;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+const FETCH_QUERY: &str = "(UID FLAGS RFC822.SIZE BODY.PEEK[])";
+const SNIPPET_CHARS: usize = 200;
+
+fn open_stream(host: &str, port: u16) -> Result<TcpStream, String> {
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("Failed to resolve {}", host))?;
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT).map_err(|e| format!("Failed to connect to {}: {}", host, e))?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(TIMEOUT)).map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+fn connect(account: &ImapAccount) -> Result<Session, String> {
+    let server = &account.server;
+    let tls = TlsConnector::builder()
+        .danger_accept_invalid_certs(server.allow_invalid_certs)
+        .build()
+        .map_err(|e| format!("Failed to set up TLS: {}", e))?;
+    let stream = open_stream(&server.imap_host, server.imap_port)?;
+    let tls_error = |e: &dyn std::fmt::Display| format!("TLS handshake with {} failed: {}", server.imap_host, e);
+
+    let client = match server.imap_security {
+        Security::Tls => {
+            let stream = tls.connect(&server.imap_host, stream).map_err(|e| tls_error(&e))?;
+            let mut client = imap::Client::new(stream);
+            client.read_greeting().map_err(|e| format!("IMAP server error: {}", e))?;
+            client
+        }
+        Security::StartTls => {
+            let mut client = imap::Client::new(stream);
+            client.read_greeting().map_err(|e| format!("IMAP server error: {}", e))?;
+            client.secure(&server.imap_host, &tls).map_err(|e| tls_error(&e))?
+        }
+    };
+    client
+        .login(&account.credentials.username, &account.credentials.password)
+        .map_err(|(e, _)| format!("Login failed: {}", e))
+}
+
+/// Run `f` in a fresh session, logging out afterwards
+fn with_session<T>(account: &ImapAccount, f: impl FnOnce(&mut Session) -> imap::error::Result<T>) -> Result<T, String> {
+    let mut session = connect(account)?;
+    let result = f(&mut session).map_err(|e| format!("IMAP error: {}", e));
+    let _ = session.logout();
+    result
+}
+
+/// Selectable folders of the account
+pub fn list_folders(account: &ImapAccount) -> Result<Vec<String>, String> {
+    with_session(account, |session| {
+        let names = session.list(Some(""), Some("*"))?;
+        Ok(names
+            .iter()
+            .filter(|name| !name.attributes().iter().any(|a| matches!(a, NameAttribute::NoSelect)))
+            .map(|name| name.name().to_string())
+            .collect())
+    })
+}
+
+/// The newest `max` messages of `folder`, newest first
+pub fn list_emails(account: &ImapAccount, folder: &str, max: u32) -> Result<Vec<EmailMessage>, String> {
+    with_session(account, |session| {
+        let mailbox = session.select(folder)?;
+        if mailbox.exists == 0 || max == 0 {
+            return Ok(Vec::new());
+        }
+        let first = mailbox.exists.saturating_sub(max - 1).max(1);
+        let fetches = session.fetch(format!("{}:{}", first, mailbox.exists), FETCH_QUERY)?;
+        let mut messages: Vec<EmailMessage> = fetches.iter().filter_map(|fetch| from_fetch(folder, fetch)).collect();
+        messages.sort_by(|a, b| b.date.cmp(&a.date));
+        Ok(messages)
+    })
+}
+
+pub fn get_email(account: &ImapAccount, folder: &str, uid: u32) -> Result<EmailMessage, String> {
+    with_session(account, |session| {
+        session.select(folder)?;
+        let fetches = session.uid_fetch(uid.to_string(), FETCH_QUERY)?;
+        Ok(fetches.iter().find_map(|fetch| from_fetch(folder, fetch)))
+    })?
+    .ok_or_else(|| format!("Message {} not found in {}", uid, folder))
+}
+
+/// Add or remove a system flag such as `\Seen` on one message
+pub fn set_flag(account: &ImapAccount, folder: &str, uid: u32, flag: &str, on: bool) -> Result<(), String> {
+    with_session(account, |session| {
+        session.select(folder)?;
+        session.uid_store(uid.to_string(), format!("{}FLAGS ({})", if on { "+" } else { "-" }, flag))?;
+        Ok(())
+    })
+}
+
+/// The folder marked `\Archive` (RFC 6154), else one named "Archive"
+fn archive_folder(session: &mut Session) -> imap::error::Result<Option<String>> {
+    let names = session.list(Some(""), Some("*"))?;
+    let special = names
+        .iter()
+        .find(|name| name.attributes().iter().any(|a| matches!(a, NameAttribute::Custom(c) if c.eq_ignore_ascii_case("\\Archive"))));
+    Ok(special
+        .or_else(|| names.iter().find(|name| name.name().eq_ignore_ascii_case("Archive")))
+        .map(|name| name.name().to_string()))
+}
+
+/// Move a message into the archive folder
+pub fn archive(account: &ImapAccount, folder: &str, uid: u32) -> Result<(), String> {
+    with_session(account, |session| {
+        let Some(archive) = archive_folder(session)? else {
+            return Ok(Err("The account has no Archive folder".to_string()));
+        };
+        if archive == folder {
+            return Ok(Ok(()));
+        }
+        session.select(folder)?;
+        session.uid_mv(uid.to_string(), &archive)?;
+        Ok(Ok(()))
+    })?
+}
+
+fn from_fetch(folder: &str, fetch: &imap::types::Fetch) -> Option<EmailMessage> {
+    let flags = fetch.flags();
+    parse_message(
+        fetch.uid?,
+        folder,
+        fetch.body()?,
+        flags.contains(&Flag::Seen),
+        flags.contains(&Flag::Flagged),
+        fetch.size.map_or(0, u64::from),
+    )
+}
+
+fn addresses(address: Option<&Address>) -> Vec<EmailAddress> {
+    let convert = |addr: &mail_parser::Addr| {
+        addr.address.as_ref().map(|email| EmailAddress {
+            email: email.to_string(),
+            name: addr.name.as_ref().map(|n| n.to_string()).filter(|n| !n.is_empty()),
+        })
+    };
+    match address {
+        Some(Address::List(list)) => list.iter().filter_map(convert).collect(),
+        Some(Address::Group(groups)) => groups.iter().flat_map(|g| g.addresses.iter()).filter_map(convert).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn snippet(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(SNIPPET_CHARS).collect()
+}
+
+/// Convert a raw RFC 822 message to the shared email model; the UID becomes
+/// the id and the folder the only label
+pub fn parse_message(uid: u32, folder: &str, raw: &[u8], is_read: bool, is_starred: bool, size: u64) -> Option<EmailMessage> {
+    let message = MessageParser::default().parse(raw)?;
+    let body_text = message.body_text(0).map(|t| t.into_owned());
+    // mail-parser renders text parts as HTML when there is no HTML part; only keep real ones
+    let has_html = message
+        .html_part(0)
+        .and_then(|part| part.content_type())
+        .map_or(false, |ct| ct.subtype().map_or(false, |s| s.eq_ignore_ascii_case("html")));
+    let body_html = if has_html { message.body_html(0).map(|h| sanitize_html(&h, Policy::Email)) } else { None };
+    let attachments = message
+        .attachments()
+        .enumerate()
+        .map(|(index, part)| {
+            let mime_type = part
+                .content_type()
+                .map(|ct| format!("{}/{}", ct.ctype(), ct.subtype().unwrap_or("octet-stream")))
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            EmailAttachment {
+                id: index.to_string(),
+                filename: part.attachment_name().unwrap_or("attachment").to_string(),
+                mime_type,
+                size: part.contents().len() as u64,
+                data: Some(part.contents().to_vec()),
+                content_id: part.content_id().map(|id| id.trim_matches(['<', '>']).to_string()),
+            }
+        })
+        .collect();
+    let cc = addresses(message.cc());
+    let bcc = addresses(message.bcc());
+
+    Some(EmailMessage {
+        id: uid.to_string(),
+        thread_id: message.message_id().unwrap_or_default().to_string(),
+        subject: message.subject().unwrap_or_default().to_string(),
+        from: addresses(message.from()),
+        to: addresses(message.to()),
+        cc: Some(cc).filter(|cc| !cc.is_empty()),
+        bcc: Some(bcc).filter(|bcc| !bcc.is_empty()),
+        snippet: snippet(body_text.as_deref().unwrap_or_default()),
+        body_text,
+        body_html,
+        attachments,
+        labels: vec![folder.to_string()],
+        date: message
+            .date()
+            .and_then(|d| DateTime::<Utc>::from_timestamp(d.to_timestamp(), 0))
+            .unwrap_or_else(Utc::now),
+        is_read,
+        is_starred,
+        size_estimate: size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multipart_message() {
+        let raw = "From: Ada Lovelace <ada@example.com>\r\n\
+            To: team@example.com, \"Bob\" <bob@example.com>\r\n\
+            Subject: Notes from the meeting\r\n\
+            Date: Fri, 01 Mar 2024 09:30:00 +0000\r\n\
+            Message-ID: <abc@example.com>\r\n\
+            MIME-Version: 1.0\r\n\
+            Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+            \r\n\
+            --outer\r\n\
+            Content-Type: multipart/alternative; boundary=\"inner\"\r\n\
+            \r\n\
+            --inner\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\
+            \r\n\
+            Agenda   and\r\n  decisions\r\n\
+            --inner\r\n\
+            Content-Type: text/html; charset=utf-8\r\n\
+            \r\n\
+            <p>Agenda and <b>decisions</b></p>\r\n\
+            --inner--\r\n\
+            --outer\r\n\
+            Content-Type: text/csv; name=\"actions.csv\"\r\n\
+            Content-Disposition: attachment; filename=\"actions.csv\"\r\n\
+            \r\n\
+            owner,task\r\n\
+            --outer--\r\n";
+        let email = parse_message(42, "INBOX", raw.as_bytes(), true, false, raw.len() as u64).unwrap();
+        assert_eq!(email.id, "42");
+        assert_eq!(email.subject, "Notes from the meeting");
+        assert_eq!(email.from[0].name.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(email.to.iter().map(|a| a.email.as_str()).collect::<Vec<_>>(), vec!["team@example.com", "bob@example.com"]);
+        assert_eq!(email.date.to_rfc3339(), "2024-03-01T09:30:00+00:00");
+        assert_eq!(email.snippet, "Agenda and decisions");
+        assert!(email.body_html.unwrap().contains("<b>decisions</b>"));
+        assert_eq!(email.attachments.len(), 1);
+        assert_eq!(email.attachments[0].filename, "actions.csv");
+        assert_eq!(email.attachments[0].mime_type, "text/csv");
+        assert_eq!(email.labels, vec!["INBOX"]);
+    }
+}
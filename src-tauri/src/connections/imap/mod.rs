@@ -0,0 +1,304 @@
+//! Generic IMAP/SMTP email connection.
+//!
+//! Covers providers without a dedicated integration: Fastmail, Proton Mail
+//! Bridge, self-hosted servers. The account, password included, is kept in
+//! secure storage. Messages are read over IMAP into the same model the Gmail
+//! connection uses, and sends that fail go into an offline queue that's
+//! retried with backoff in the background.
+
+mod client;
+mod smtp;
+
+use crate::connections::gmail::models::{EmailAddress, EmailComposer, EmailMessage, OperationType};
+use crate::connections::gmail::OfflineQueue;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const ACCOUNT_KEY: &str = "imap-account";
+const DEFAULT_FOLDER: &str = "INBOX";
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const QUEUE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Security {
+    /// TLS from the first byte (IMAP 993, SMTP 465)
+    #[default]
+    Tls,
+    /// Plain connection upgraded with STARTTLS (IMAP 143, SMTP 587)
+    StartTls,
+}
+
+fn default_imap_port() -> u16 {
+    993
+}
+
+fn default_smtp_port() -> u16 {
+    465
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImapServer {
+    pub imap_host: String,
+    #[serde(default = "default_imap_port")]
+    pub imap_port: u16,
+    #[serde(default)]
+    pub imap_security: Security,
+    /// Defaults to the IMAP host
+    #[serde(default)]
+    pub smtp_host: Option<String>,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub smtp_security: Security,
+    /// For local bridges with self-signed certificates
+    #[serde(default)]
+    pub allow_invalid_certs: bool,
+}
+
+impl ImapServer {
+    fn smtp_host(&self) -> &str {
+        self.smtp_host.as_deref().filter(|h| !h.trim().is_empty()).unwrap_or(&self.imap_host)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImapCredentials {
+    pub username: String,
+    pub password: String,
+    /// Sender address; the username when it looks like one
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImapAccount {
+    server: ImapServer,
+    credentials: ImapCredentials,
+}
+
+impl ImapAccount {
+    fn email(&self) -> &str {
+        self.credentials.email.as_deref().filter(|e| !e.trim().is_empty()).unwrap_or(&self.credentials.username)
+    }
+
+    fn profile(&self, folders: Vec<String>) -> ImapProfile {
+        ImapProfile {
+            email: self.email().to_string(),
+            display_name: self.credentials.display_name.clone(),
+            imap_host: self.server.imap_host.clone(),
+            smtp_host: self.server.smtp_host().to_string(),
+            folders,
+        }
+    }
+}
+
+/// What the frontend sees of the account; never includes the password
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImapProfile {
+    pub email: String,
+    pub display_name: Option<String>,
+    pub imap_host: String,
+    pub smtp_host: String,
+    pub folders: Vec<String>,
+}
+
+/// `None` until loaded from secure storage, which is slow
+static ACCOUNT: Lazy<Mutex<Option<Option<ImapAccount>>>> = Lazy::new(|| Mutex::new(None));
+static QUEUE: Lazy<Result<OfflineQueue, String>> = Lazy::new(|| OfflineQueue::for_provider("imap").map_err(|e| e.to_string()));
+
+fn account() -> Option<ImapAccount> {
+    ACCOUNT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| {
+            crate::secure_storage::SecureStorage::new()
+                .ok()
+                .and_then(|storage| storage.retrieve::<ImapAccount>(ACCOUNT_KEY).ok().flatten())
+        })
+        .clone()
+}
+
+fn require_account() -> Result<ImapAccount, String> {
+    account().ok_or_else(|| "No IMAP account is connected".to_string())
+}
+
+fn queue() -> Result<&'static OfflineQueue, String> {
+    QUEUE.as_ref().map_err(|e| format!("Offline queue unavailable: {}", e))
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| format!("IMAP task failed: {}", e))?
+}
+
+/// Retry queued sends whose backoff has elapsed, for the lifetime of the app
+pub fn start() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(QUEUE_INTERVAL).await;
+            let (Ok(queue), Some(account)) = (queue(), account()) else {
+                continue;
+            };
+            for operation in queue.get_pending_operations() {
+                let result = match operation.operation_type {
+                    OperationType::SendEmail => match serde_json::from_value::<EmailComposer>(operation.data.clone()) {
+                        Ok(composer) => match smtp::prepare(&account, &composer) {
+                            Ok((_, message)) => smtp::deliver(&account, message).await,
+                            Err(e) => Err(e),
+                        },
+                        Err(e) => Err(format!("Unreadable queued message: {}", e)),
+                    },
+                    // Nothing else is ever queued for IMAP
+                    _ => Ok(()),
+                };
+                let marked = match result {
+                    Ok(()) => queue.mark_operation_success(&operation.id),
+                    Err(e) => {
+                        tracing::warn!("Queued IMAP send failed: {}", e);
+                        queue.mark_operation_failed(&operation.id, &e)
+                    }
+                };
+                if let Err(e) = marked {
+                    tracing::warn!("Failed to update IMAP queue: {}", e);
+                }
+            }
+        }
+    });
+}
+
+// --- Unified inbox ---
+
+/// The newest `max` messages of the inbox
+pub(crate) async fn inbox(max: u32) -> Result<Vec<EmailMessage>, String> {
+    let account = require_account()?;
+    blocking(move || client::list_emails(&account, DEFAULT_FOLDER, max)).await
+}
+
+pub(crate) async fn set_seen(folder: String, uid: u32, seen: bool) -> Result<(), String> {
+    let account = require_account()?;
+    blocking(move || client::set_flag(&account, &folder, uid, "\\Seen", seen)).await
+}
+
+pub(crate) async fn set_flagged(folder: String, uid: u32, flagged: bool) -> Result<(), String> {
+    let account = require_account()?;
+    blocking(move || client::set_flag(&account, &folder, uid, "\\Flagged", flagged)).await
+}
+
+pub(crate) async fn archive(folder: String, uid: u32) -> Result<(), String> {
+    let account = require_account()?;
+    blocking(move || client::archive(&account, &folder, uid)).await
+}
+
+// --- Tauri Commands ---
+
+/// Log in to check the settings, then remember the account
+#[tauri::command]
+pub async fn imap_connect(server: ImapServer, creds: ImapCredentials) -> Result<ImapProfile, String> {
+    if server.imap_host.trim().is_empty() || creds.username.trim().is_empty() {
+        return Err("A server and username are required".to_string());
+    }
+    let account = ImapAccount { server, credentials: creds };
+    let probe = account.clone();
+    let folders = blocking(move || client::list_folders(&probe)).await?;
+
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.store(ACCOUNT_KEY, &account).map_err(|e| format!("Failed to save IMAP account: {}", e))?;
+    let profile = account.profile(folders);
+    *ACCOUNT.lock().unwrap_or_else(|e| e.into_inner()) = Some(Some(account));
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn imap_disconnect() -> Result<(), String> {
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.delete(ACCOUNT_KEY).map_err(|e| format!("Failed to remove IMAP account: {}", e))?;
+    *ACCOUNT.lock().unwrap_or_else(|e| e.into_inner()) = Some(None);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn imap_is_connected() -> bool {
+    account().is_some()
+}
+
+#[tauri::command]
+pub async fn imap_get_profile() -> Result<Option<ImapProfile>, String> {
+    let Some(account) = account() else {
+        return Ok(None);
+    };
+    let probe = account.clone();
+    let folders = blocking(move || client::list_folders(&probe)).await?;
+    Ok(Some(account.profile(folders)))
+}
+
+#[tauri::command]
+pub async fn imap_list_folders() -> Result<Vec<String>, String> {
+    let account = require_account()?;
+    blocking(move || client::list_folders(&account)).await
+}
+
+/// The newest messages of a folder (the inbox by default), newest first
+#[tauri::command]
+pub async fn imap_list_emails(folder: Option<String>, max_results: Option<u32>) -> Result<Vec<EmailMessage>, String> {
+    let account = require_account()?;
+    let folder = folder.unwrap_or_else(|| DEFAULT_FOLDER.to_string());
+    blocking(move || client::list_emails(&account, &folder, max_results.unwrap_or(DEFAULT_PAGE_SIZE))).await
+}
+
+#[tauri::command]
+pub async fn imap_get_email(folder: String, uid: u32) -> Result<EmailMessage, String> {
+    let account = require_account()?;
+    blocking(move || client::get_email(&account, &folder, uid)).await
+}
+
+/// Send over SMTP; if that fails the message is queued and retried
+#[tauri::command]
+pub async fn imap_send_email(
+    to: Vec<EmailAddress>,
+    subject: String,
+    body_text: String,
+    body_html: Option<String>,
+    cc: Option<Vec<EmailAddress>>,
+    bcc: Option<Vec<EmailAddress>>,
+) -> Result<String, String> {
+    let account = require_account()?;
+    let composer = EmailComposer {
+        to,
+        cc,
+        bcc,
+        subject,
+        body_text: Some(body_text),
+        body_html,
+        attachments: Vec::new(),
+        in_reply_to: None,
+        references: None,
+    };
+    // Messages that can't be built would never send; only delivery failures are queued
+    let (message_id, message) = smtp::prepare(&account, &composer)?;
+    match smtp::deliver(&account, message).await {
+        Ok(()) => Ok(message_id),
+        Err(e) => {
+            let data = serde_json::to_value(&composer).map_err(|e| e.to_string())?;
+            queue()?.add_operation(OperationType::SendEmail, data).map_err(|e| e.to_string())?;
+            Err(format!("{} (queued for retry)", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub fn imap_get_queue_stats() -> Result<HashMap<String, u32>, String> {
+    Ok(queue()?.get_queue_stats())
+}
+
+#[tauri::command]
+pub fn imap_clear_queue() -> Result<(), String> {
+    queue()?.clear_all_operations().map_err(|e| e.to_string())
+}
@@ -0,0 +1,79 @@
+//! Sending over SMTP.
+
+use super::{ImapAccount, Security};
+use crate::connections::gmail::models::{EmailAddress, EmailComposer};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn mailbox(address: &EmailAddress) -> Result<Mailbox, String> {
+    let email = address.email.trim().parse().map_err(|e| format!("Invalid address '{}': {}", address.email, e))?;
+    Ok(Mailbox::new(address.name.clone().filter(|n| !n.trim().is_empty()), email))
+}
+
+fn build_message(account: &ImapAccount, composer: &EmailComposer) -> Result<(String, Message), String> {
+    let sender = EmailAddress { email: account.email().to_string(), name: account.credentials.display_name.clone() };
+    let domain = sender.email.rsplit_once('@').map_or("localhost", |(_, domain)| domain);
+    let message_id = format!("<{}@{}>", uuid::Uuid::new_v4(), domain);
+
+    let mut builder = Message::builder()
+        .from(mailbox(&sender)?)
+        .subject(composer.subject.clone())
+        .message_id(Some(message_id.clone()));
+    for address in &composer.to {
+        builder = builder.to(mailbox(address)?);
+    }
+    for address in composer.cc.iter().flatten() {
+        builder = builder.cc(mailbox(address)?);
+    }
+    for address in composer.bcc.iter().flatten() {
+        builder = builder.bcc(mailbox(address)?);
+    }
+    if let Some(in_reply_to) = &composer.in_reply_to {
+        builder = builder.in_reply_to(in_reply_to.clone());
+    }
+    if let Some(references) = &composer.references {
+        builder = builder.references(references.clone());
+    }
+
+    let text = composer.body_text.clone().unwrap_or_default();
+    let message = match &composer.body_html {
+        Some(html) => builder.multipart(MultiPart::alternative_plain_html(text, html.clone())),
+        None => builder.singlepart(SinglePart::plain(text)),
+    }
+    .map_err(|e| format!("Failed to build message: {}", e))?;
+    Ok((message_id, message))
+}
+
+/// Validate and build the message; returns its `Message-ID` with it
+pub fn prepare(account: &ImapAccount, composer: &EmailComposer) -> Result<(String, Message), String> {
+    if composer.to.is_empty() {
+        return Err("The message has no recipients".to_string());
+    }
+    build_message(account, composer)
+}
+
+pub async fn deliver(account: &ImapAccount, message: Message) -> Result<(), String> {
+    let server = &account.server;
+    let host = server.smtp_host();
+    let parameters = TlsParameters::builder(host.to_string())
+        .dangerous_accept_invalid_certs(server.allow_invalid_certs)
+        .build_rustls()
+        .map_err(|e| format!("Failed to set up TLS: {}", e))?;
+    let tls = match server.smtp_security {
+        Security::Tls => Tls::Wrapper(parameters),
+        Security::StartTls => Tls::Required(parameters),
+    };
+    let transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        .port(server.smtp_port)
+        .tls(tls)
+        .credentials(Credentials::new(account.credentials.username.clone(), account.credentials.password.clone()))
+        .timeout(Some(TIMEOUT))
+        .build();
+    transport.send(message).await.map_err(|e| format!("Failed to send email: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,396 @@
+//! One inbox across the connected email accounts.
+//!
+//! Gmail and the generic IMAP connection both produce the shared
+//! `EmailMessage` model; this layer merges their inboxes newest first, gives
+//! each message an id that says which backend it came from, and sends
+//! read/star/archive actions back to that backend. Outlook mail isn't
+//! connected yet; it would slot in as another `Provider`.
+//!
+//! The last messages seen from each provider are cached in
+//! `~/.lokus/inbox/cache.json` (bodies included, attachment bytes not), so
+//! the inbox can still be browsed when a provider can't be reached.
+
+use crate::connections::gmail::models::{EmailListOptions, EmailMessage};
+use crate::connections::imap;
+use crate::connections::manager::ConnectionManager;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const PAGE_SIZE: usize = 50;
+/// Messages kept per provider, which also bounds how far back pages go
+const MAX_CACHED: usize = 200;
+/// Gmail label and IMAP folder name
+const INBOX: &str = "INBOX";
+
+/// Serializes read-modify-write cycles of the cache file
+static CACHE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Gmail,
+    Imap,
+}
+
+/// Where a message lives in its backend
+#[derive(Debug, Clone, PartialEq)]
+enum MessageRef {
+    Gmail(String),
+    Imap { folder: String, uid: u32 },
+}
+
+impl MessageRef {
+    /// `gmail:<id>` or `imap:<uid>:<folder>`; the folder goes last as it may contain colons
+    fn parse(id: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid inbox message id: {}", id);
+        match id.split_once(':').ok_or_else(invalid)? {
+            ("gmail", native) if !native.is_empty() => Ok(Self::Gmail(native.to_string())),
+            ("imap", rest) => {
+                let (uid, folder) = rest.split_once(':').ok_or_else(invalid)?;
+                let uid = uid.parse().map_err(|_| invalid())?;
+                Ok(Self::Imap { folder: folder.to_string(), uid })
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn id(&self) -> String {
+        match self {
+            Self::Gmail(id) => format!("gmail:{}", id),
+            Self::Imap { folder, uid } => format!("imap:{}:{}", uid, folder),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxMessage {
+    /// Provider-qualified id accepted by the inbox actions
+    pub id: String,
+    pub provider: Provider,
+    pub message: EmailMessage,
+}
+
+impl InboxMessage {
+    fn new(provider: Provider, mut message: EmailMessage) -> Self {
+        let target = match provider {
+            Provider::Gmail => MessageRef::Gmail(message.id.clone()),
+            Provider::Imap => MessageRef::Imap {
+                folder: message.labels.first().cloned().unwrap_or_else(|| INBOX.to_string()),
+                uid: message.id.parse().unwrap_or_default(),
+            },
+        };
+        // Attachment bytes are fetched on demand; don't keep them around
+        for attachment in &mut message.attachments {
+            attachment.data = None;
+        }
+        Self { id: target.id(), provider, message }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InboxFilter {
+    /// Empty means every connected provider
+    pub providers: Vec<Provider>,
+    pub unread_only: bool,
+    pub starred_only: bool,
+    /// Case-insensitive match on subject, sender and snippet
+    pub query: Option<String>,
+}
+
+impl InboxFilter {
+    fn matches(&self, message: &InboxMessage) -> bool {
+        let email = &message.message;
+        if self.unread_only && email.is_read || self.starred_only && !email.is_starred {
+            return false;
+        }
+        let Some(query) = self.query.as_deref().map(str::trim).filter(|q| !q.is_empty()) else {
+            return true;
+        };
+        let query = query.to_lowercase();
+        email.subject.to_lowercase().contains(&query)
+            || email.snippet.to_lowercase().contains(&query)
+            || email.from.iter().any(|a| {
+                a.email.to_lowercase().contains(&query) || a.name.as_deref().is_some_and(|n| n.to_lowercase().contains(&query))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxPage {
+    pub messages: Vec<InboxMessage>,
+    pub page: u32,
+    pub has_more: bool,
+    /// Providers that couldn't be reached and were read from the cache
+    pub offline: Vec<Provider>,
+}
+
+type Cache = BTreeMap<Provider, Vec<InboxMessage>>;
+
+fn cache_path() -> Result<PathBuf, String> {
+    let dir = dirs::home_dir()
+        .ok_or_else(|| "Could not find home directory".to_string())?
+        .join(".lokus")
+        .join("inbox");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create inbox directory: {}", e))?;
+    Ok(dir.join("cache.json"))
+}
+
+fn load_cache() -> Cache {
+    let Ok(path) = cache_path() else {
+        return Cache::new();
+    };
+    std::fs::read_to_string(path).ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
+}
+
+fn save_cache(cache: &Cache) -> Result<(), String> {
+    let json = serde_json::to_string(cache).map_err(|e| format!("Failed to serialize inbox cache: {}", e))?;
+    std::fs::write(cache_path()?, json).map_err(|e| format!("Failed to write inbox cache: {}", e))
+}
+
+/// Apply `change` to the cache under the lock
+fn update_cache(change: impl FnOnce(&mut Cache)) {
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cache = load_cache();
+    change(&mut cache);
+    if let Err(e) = save_cache(&cache) {
+        tracing::warn!("{}", e);
+    }
+}
+
+/// Cached messages after a fetch of the newest ones. Cached messages within
+/// the fetched span that weren't returned have left the inbox; older ones are
+/// kept, since the fetch didn't reach back that far.
+fn merge_fetched(cached: Vec<InboxMessage>, fetched: Vec<InboxMessage>, complete: bool) -> Vec<InboxMessage> {
+    let oldest = fetched.iter().map(|m| m.message.date).min();
+    let seen: HashSet<String> = fetched.iter().map(|m| m.id.clone()).collect();
+    let mut merged = fetched;
+    if !complete {
+        if let Some(oldest) = oldest {
+            merged.extend(cached.into_iter().filter(|m| m.message.date < oldest && !seen.contains(&m.id)));
+        }
+    }
+    merged.sort_by(|a, b| b.message.date.cmp(&a.message.date));
+    merged.truncate(MAX_CACHED);
+    merged
+}
+
+/// The page of `messages` (already newest first) and whether more follow
+fn paginate(messages: Vec<InboxMessage>, page: usize) -> (Vec<InboxMessage>, bool) {
+    let start = page * PAGE_SIZE;
+    let has_more = messages.len() > start + PAGE_SIZE;
+    (messages.into_iter().skip(start).take(PAGE_SIZE).collect(), has_more)
+}
+
+async fn connected(manager: &ConnectionManager) -> Vec<Provider> {
+    let mut providers = Vec::new();
+    if manager.is_gmail_authenticated().await.unwrap_or(false) {
+        providers.push(Provider::Gmail);
+    }
+    if imap::imap_is_connected() {
+        providers.push(Provider::Imap);
+    }
+    providers
+}
+
+async fn fetch(manager: &ConnectionManager, provider: Provider, max: usize) -> Result<Vec<InboxMessage>, String> {
+    let messages = match provider {
+        Provider::Gmail => manager
+            .list_emails(EmailListOptions {
+                label_ids: Some(vec![INBOX.to_string()]),
+                max_results: Some(max as u32),
+                page_token: None,
+                include_spam_trash: false,
+            })
+            .await
+            .map_err(|e| e.to_string())?,
+        Provider::Imap => imap::inbox(max as u32).await?,
+    };
+    Ok(messages.into_iter().map(|m| InboxMessage::new(provider, m)).collect())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Read(bool),
+    Star(bool),
+    Archive,
+}
+
+/// Run `action` on each message's backend, Gmail in one batch, then mirror it in the cache
+async fn apply(app: &AppHandle, ids: &[String], action: Action) -> Result<(), String> {
+    let targets = ids.iter().map(|id| MessageRef::parse(id)).collect::<Result<Vec<_>, _>>()?;
+    let gmail_ids: Vec<String> = targets
+        .iter()
+        .filter_map(|t| match t {
+            MessageRef::Gmail(id) => Some(id.clone()),
+            MessageRef::Imap { .. } => None,
+        })
+        .collect();
+    if !gmail_ids.is_empty() {
+        let manager = app.state::<ConnectionManager>();
+        match action {
+            Action::Read(true) => manager.mark_as_read(gmail_ids).await,
+            Action::Read(false) => manager.mark_as_unread(gmail_ids).await,
+            Action::Star(true) => manager.star_emails(gmail_ids).await,
+            Action::Star(false) => manager.unstar_emails(gmail_ids).await,
+            Action::Archive => manager.archive_emails(gmail_ids).await,
+        }
+        .map_err(|e| e.to_string())?;
+    }
+    for target in targets {
+        let MessageRef::Imap { folder, uid } = target else {
+            continue;
+        };
+        match action {
+            Action::Read(read) => imap::set_seen(folder, uid, read).await?,
+            Action::Star(starred) => imap::set_flagged(folder, uid, starred).await?,
+            Action::Archive => imap::archive(folder, uid).await?,
+        }
+    }
+
+    let ids: HashSet<&String> = ids.iter().collect();
+    update_cache(|cache| {
+        for messages in cache.values_mut() {
+            messages.retain_mut(|m| {
+                if !ids.contains(&m.id) {
+                    return true;
+                }
+                match action {
+                    Action::Read(read) => m.message.is_read = read,
+                    Action::Star(starred) => m.message.is_starred = starred,
+                    Action::Archive => return false,
+                }
+                true
+            });
+        }
+    });
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+/// A page of the merged inbox, newest first. Providers that can't be reached
+/// are served from the cache.
+#[tauri::command]
+pub async fn inbox_list(app: AppHandle, filter: Option<InboxFilter>, page: Option<u32>) -> Result<InboxPage, String> {
+    let filter = filter.unwrap_or_default();
+    let page = page.unwrap_or(0);
+    let manager = app.state::<ConnectionManager>();
+    let providers: Vec<Provider> = connected(&manager)
+        .await
+        .into_iter()
+        .filter(|p| filter.providers.is_empty() || filter.providers.contains(p))
+        .collect();
+    // One extra so a provider with exactly a window's worth still shows more
+    let window = ((page as usize + 1) * PAGE_SIZE).min(MAX_CACHED) + 1;
+
+    let mut fetched = Vec::new();
+    let mut offline = Vec::new();
+    for &provider in &providers {
+        match fetch(&manager, provider, window).await {
+            Ok(messages) => fetched.push((provider, messages)),
+            Err(e) => {
+                tracing::warn!("Failed to load {:?} inbox, using the cache: {}", provider, e);
+                offline.push(provider);
+            }
+        }
+    }
+
+    let mut merged = Vec::new();
+    update_cache(|cache| {
+        for (provider, messages) in fetched {
+            let complete = messages.len() < window;
+            let cached = cache.remove(&provider).unwrap_or_default();
+            cache.insert(provider, merge_fetched(cached, messages, complete));
+        }
+        // Disconnected providers keep their cache but aren't shown
+        for provider in &providers {
+            let cached = cache.get(provider).map(Vec::as_slice).unwrap_or_default();
+            merged.extend(cached.iter().filter(|m| filter.matches(m)).cloned());
+        }
+    });
+    merged.sort_by(|a, b| b.message.date.cmp(&a.message.date));
+
+    let (messages, has_more) = paginate(merged, page as usize);
+    Ok(InboxPage { messages, page, has_more, offline })
+}
+
+#[tauri::command]
+pub async fn inbox_set_read(app: AppHandle, ids: Vec<String>, read: bool) -> Result<(), String> {
+    apply(&app, &ids, Action::Read(read)).await
+}
+
+#[tauri::command]
+pub async fn inbox_set_starred(app: AppHandle, ids: Vec<String>, starred: bool) -> Result<(), String> {
+    apply(&app, &ids, Action::Star(starred)).await
+}
+
+/// Move messages out of the inbox: Gmail drops the label, IMAP moves them to the Archive folder
+#[tauri::command]
+pub async fn inbox_archive(app: AppHandle, ids: Vec<String>) -> Result<(), String> {
+    apply(&app, &ids, Action::Archive).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn message(provider: Provider, id: &str, minutes_ago: i64) -> InboxMessage {
+        let email = EmailMessage {
+            id: id.to_string(),
+            thread_id: id.to_string(),
+            subject: format!("Subject {}", id),
+            from: Vec::new(),
+            to: Vec::new(),
+            cc: None,
+            bcc: None,
+            body_text: None,
+            body_html: None,
+            attachments: Vec::new(),
+            labels: vec!["INBOX".to_string()],
+            snippet: String::new(),
+            date: Utc::now() - Duration::minutes(minutes_ago),
+            is_read: false,
+            is_starred: false,
+            size_estimate: 0,
+        };
+        InboxMessage::new(provider, email)
+    }
+
+    #[test]
+    fn ids_name_their_backend() {
+        assert_eq!(message(Provider::Gmail, "abc", 0).id, "gmail:abc");
+        assert_eq!(message(Provider::Imap, "42", 0).id, "imap:42:INBOX");
+        assert_eq!(MessageRef::parse("gmail:abc").unwrap(), MessageRef::Gmail("abc".to_string()));
+        assert_eq!(
+            MessageRef::parse("imap:7:Lists:rust").unwrap(),
+            MessageRef::Imap { folder: "Lists:rust".to_string(), uid: 7 }
+        );
+        assert!(MessageRef::parse("outlook:1").is_err());
+        assert!(MessageRef::parse("imap:x:INBOX").is_err());
+    }
+
+    #[test]
+    fn fetches_replace_only_the_span_they_cover() {
+        let cached = vec![message(Provider::Gmail, "archived", 5), message(Provider::Gmail, "old", 60)];
+        let fetched = vec![message(Provider::Gmail, "new", 1), message(Provider::Gmail, "kept", 10)];
+
+        let merged = merge_fetched(cached.clone(), fetched.clone(), false);
+        let ids: Vec<&str> = merged.iter().map(|m| m.message.id.as_str()).collect();
+        assert_eq!(ids, ["new", "kept", "old"]);
+
+        let merged = merge_fetched(cached, fetched, true);
+        assert_eq!(merged.len(), 2);
+
+        let (page, has_more) = paginate((0..PAGE_SIZE as i64 + 1).map(|i| message(Provider::Imap, &i.to_string(), i)).collect(), 0);
+        assert_eq!(page.len(), PAGE_SIZE);
+        assert!(has_more);
+    }
+}
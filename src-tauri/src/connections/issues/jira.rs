@@ -0,0 +1,272 @@
+//! Jira Cloud, through the REST API (v3) with an email and API token.
+
+use super::{send, IssueFilter, Provider, RemoteIssue, StatusCategory};
+use reqwest::header::ACCEPT;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const FIELDS: &str = "summary,description,status,assignee,priority,labels,duedate,updated,resolution";
+const PAGE_SIZE: usize = 100;
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Credentials {
+    /// `https://<team>.atlassian.net`
+    pub site: String,
+    pub email: String,
+    pub api_token: String,
+    /// Display name of the account, for the settings UI
+    pub account: Option<String>,
+}
+
+/// `team.atlassian.net` or a full URL, as an https base URL without a trailing slash
+pub fn normalize_site(site: &str) -> Result<String, String> {
+    let site = site.trim().trim_end_matches('/');
+    let site = if site.contains("://") { site.to_string() } else { format!("https://{}", site) };
+    let parsed = url::Url::parse(&site).map_err(|_| format!("Invalid Jira site '{}'", site))?;
+    if parsed.scheme() != "https" || parsed.host_str().is_none() {
+        return Err("The Jira site must be an https URL".to_string());
+    }
+    Ok(site)
+}
+
+pub struct Jira {
+    client: Client,
+    credentials: Credentials,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPage {
+    #[serde(default)]
+    issues: Vec<RawIssue>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawIssue {
+    key: String,
+    fields: Value,
+}
+
+/// A JQL string literal
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The JQL for a project's issues under `filter`, newest changes first
+fn jql(project: &str, filter: &IssueFilter) -> String {
+    let mut clauses = vec![format!("project = {}", quote(project))];
+    if !filter.include_closed {
+        clauses.push("statusCategory != Done".to_string());
+    }
+    if filter.assigned_to_me {
+        clauses.push("assignee = currentUser()".to_string());
+    }
+    if !filter.labels.is_empty() {
+        let labels: Vec<String> = filter.labels.iter().map(|l| quote(l)).collect();
+        clauses.push(format!("labels in ({})", labels.join(", ")));
+    }
+    if let Some(since) = &filter.updated_since {
+        clauses.push(format!("updated >= {}", quote(since)));
+    }
+    if let Some(text) = filter.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        clauses.push(format!("summary ~ {}", quote(text)));
+    }
+    format!("{} ORDER BY updated DESC", clauses.join(" AND "))
+}
+
+/// Plain text of an Atlassian Document Format value; blocks become lines
+fn adf_text(node: &Value) -> String {
+    fn walk(node: &Value, out: &mut String) {
+        match node.get("type").and_then(Value::as_str) {
+            Some("text") => out.push_str(node.get("text").and_then(Value::as_str).unwrap_or_default()),
+            Some("hardBreak") => out.push('\n'),
+            _ => {}
+        }
+        if let Some(children) = node.get("content").and_then(Value::as_array) {
+            for child in children {
+                walk(child, out);
+            }
+        }
+        if matches!(
+            node.get("type").and_then(Value::as_str),
+            Some("paragraph" | "heading" | "listItem" | "codeBlock" | "blockquote")
+        ) && !out.ends_with('\n')
+        {
+            out.push('\n');
+        }
+    }
+    let mut out = String::new();
+    walk(node, &mut out);
+    out.trim().to_string()
+}
+
+fn category(fields: &Value) -> StatusCategory {
+    let key = fields.pointer("/status/statusCategory/key").and_then(Value::as_str);
+    let resolution = fields.pointer("/resolution/name").and_then(Value::as_str).unwrap_or_default().to_lowercase();
+    match key {
+        Some("done") if resolution.contains("won't") || resolution.contains("cancel") || resolution.contains("duplicate") => {
+            StatusCategory::Cancelled
+        }
+        Some("done") => StatusCategory::Done,
+        Some("indeterminate") => StatusCategory::InProgress,
+        _ => StatusCategory::Todo,
+    }
+}
+
+impl Jira {
+    pub fn new(credentials: Credentials) -> Self {
+        Self { client: Client::new(), credentials }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.credentials.site, path))
+            .header(ACCEPT, "application/json")
+            .basic_auth(&self.credentials.email, Some(&self.credentials.api_token))
+    }
+
+    fn to_issue(&self, raw: RawIssue) -> RemoteIssue {
+        let fields = &raw.fields;
+        let text = |pointer: &str| fields.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+        RemoteIssue {
+            provider: Provider::Jira,
+            url: format!("{}/browse/{}", self.credentials.site, raw.key),
+            key: raw.key,
+            title: text("/summary").unwrap_or_default(),
+            description: fields.get("description").filter(|d| !d.is_null()).map(adf_text).filter(|d| !d.is_empty()),
+            status: text("/status/name").unwrap_or_default(),
+            category: category(fields),
+            assignee: text("/assignee/displayName"),
+            priority: text("/priority/name"),
+            labels: fields
+                .get("labels")
+                .and_then(Value::as_array)
+                .map(|labels| labels.iter().filter_map(|l| l.as_str().map(str::to_string)).collect())
+                .unwrap_or_default(),
+            due_date: text("/duedate"),
+            updated_at: text("/updated"),
+        }
+    }
+
+    /// Display name of the account, which also checks the credentials
+    pub async fn myself(&self) -> Result<String, String> {
+        let me: Value = send("Jira", || self.request(Method::GET, "/rest/api/3/myself"))
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Jira response: {}", e))?;
+        Ok(me.get("displayName").and_then(Value::as_str).unwrap_or(&self.credentials.email).to_string())
+    }
+
+    pub async fn search(&self, project: &str, filter: &IssueFilter) -> Result<Vec<RemoteIssue>, String> {
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let jql = jql(project, filter);
+        let mut issues = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut body = json!({
+                "jql": jql,
+                "fields": FIELDS.split(',').collect::<Vec<_>>(),
+                "maxResults": PAGE_SIZE.min(limit - issues.len()),
+            });
+            if let Some(token) = &token {
+                body["nextPageToken"] = json!(token);
+            }
+            let page: SearchPage = send("Jira", || self.request(Method::POST, "/rest/api/3/search/jql").json(&body))
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Invalid Jira response: {}", e))?;
+            issues.extend(page.issues.into_iter().map(|raw| self.to_issue(raw)));
+            match page.next_page_token {
+                Some(next) if issues.len() < limit => token = Some(next),
+                _ => break,
+            }
+        }
+        Ok(issues)
+    }
+
+    pub async fn get(&self, key: &str) -> Result<RemoteIssue, String> {
+        let path = format!("/rest/api/3/issue/{}", urlencoding::encode(key));
+        let raw: RawIssue = send("Jira", || self.request(Method::GET, &path).query(&[("fields", FIELDS)]))
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Jira response: {}", e))?;
+        Ok(self.to_issue(raw))
+    }
+
+    /// Move an issue to a done status, using the first transition that leads to one
+    pub async fn complete(&self, key: &str) -> Result<(), String> {
+        let path = format!("/rest/api/3/issue/{}/transitions", urlencoding::encode(key));
+        let available: Value = send("Jira", || self.request(Method::GET, &path))
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Jira response: {}", e))?;
+        let transition = available
+            .get("transitions")
+            .and_then(Value::as_array)
+            .and_then(|transitions| {
+                transitions
+                    .iter()
+                    .find(|t| t.pointer("/to/statusCategory/key").and_then(Value::as_str) == Some("done"))
+            })
+            .and_then(|t| t.get("id").cloned())
+            .ok_or_else(|| format!("{} has no transition to a done status", key))?;
+        let body = json!({ "transition": { "id": transition } });
+        send("Jira", || self.request(Method::POST, &path).json(&body)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_jql_from_filters() {
+        let filter = IssueFilter {
+            assigned_to_me: true,
+            labels: vec!["bug".to_string()],
+            text: Some("say \"hi\"".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            jql("ENG", &filter),
+            "project = \"ENG\" AND statusCategory != Done AND assignee = currentUser() AND labels in (\"bug\") \
+             AND summary ~ \"say \\\"hi\\\"\" ORDER BY updated DESC"
+        );
+        let all = IssueFilter { include_closed: true, ..Default::default() };
+        assert_eq!(jql("ENG", &all), "project = \"ENG\" ORDER BY updated DESC");
+    }
+
+    #[test]
+    fn flattens_document_format() {
+        let doc = json!({
+            "type": "doc",
+            "content": [
+                { "type": "paragraph", "content": [{ "type": "text", "text": "First" }, { "type": "hardBreak" }, { "type": "text", "text": "line" }] },
+                { "type": "bulletList", "content": [{ "type": "listItem", "content": [{ "type": "paragraph", "content": [{ "type": "text", "text": "item" }] }] }] }
+            ]
+        });
+        assert_eq!(adf_text(&doc), "First\nline\nitem");
+    }
+
+    #[test]
+    fn maps_status_categories() {
+        let fields = |key: &str, resolution: Option<&str>| {
+            json!({ "status": { "statusCategory": { "key": key } }, "resolution": resolution.map(|r| json!({ "name": r })) })
+        };
+        assert_eq!(category(&fields("new", None)), StatusCategory::Todo);
+        assert_eq!(category(&fields("indeterminate", None)), StatusCategory::InProgress);
+        assert_eq!(category(&fields("done", Some("Done"))), StatusCategory::Done);
+        assert_eq!(category(&fields("done", Some("Won't Do"))), StatusCategory::Cancelled);
+        assert_eq!(normalize_site("team.atlassian.net/").unwrap(), "https://team.atlassian.net");
+        assert!(normalize_site("http://team.atlassian.net").is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Linear, through its GraphQL API with a personal API key.
+
+use super::{send, IssueFilter, Provider, RemoteIssue, StatusCategory};
+use reqwest::header::AUTHORIZATION;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const API_URL: &str = "https://api.linear.app/graphql";
+const PAGE_SIZE: usize = 100;
+const DEFAULT_LIMIT: usize = 100;
+
+const ISSUE_FIELDS: &str = "id identifier title description url priorityLabel dueDate updatedAt \
+    state { name type } assignee { name } labels { nodes { name } }";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Credentials {
+    pub api_key: String,
+    /// Name of the account, for the settings UI
+    pub account: Option<String>,
+}
+
+pub struct Linear {
+    client: Client,
+    credentials: Credentials,
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct State {
+    name: String,
+    /// `triage`, `backlog`, `unstarted`, `started`, `completed` or `canceled`
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Labels {
+    nodes: Vec<Named>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawIssue {
+    identifier: String,
+    title: String,
+    description: Option<String>,
+    url: String,
+    priority_label: Option<String>,
+    due_date: Option<String>,
+    updated_at: Option<String>,
+    state: State,
+    assignee: Option<Named>,
+    labels: Labels,
+}
+
+impl From<RawIssue> for RemoteIssue {
+    fn from(raw: RawIssue) -> Self {
+        RemoteIssue {
+            provider: Provider::Linear,
+            key: raw.identifier,
+            title: raw.title,
+            description: raw.description.filter(|d| !d.trim().is_empty()),
+            category: category(&raw.state.kind),
+            status: raw.state.name,
+            url: raw.url,
+            assignee: raw.assignee.map(|a| a.name),
+            // "No priority" is Linear's way of saying none
+            priority: raw.priority_label.filter(|p| p != "No priority"),
+            labels: raw.labels.nodes.into_iter().map(|l| l.name).collect(),
+            due_date: raw.due_date,
+            updated_at: raw.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssuePage {
+    nodes: Vec<RawIssue>,
+    page_info: PageInfo,
+}
+
+fn category(kind: &str) -> StatusCategory {
+    match kind {
+        "started" => StatusCategory::InProgress,
+        "completed" => StatusCategory::Done,
+        "canceled" => StatusCategory::Cancelled,
+        _ => StatusCategory::Todo,
+    }
+}
+
+/// Linear's `IssueFilter` input for a team's issues under `filter`
+fn issue_filter(team: &str, filter: &IssueFilter) -> Value {
+    let mut conditions = json!({ "team": { "key": { "eqIgnoreCase": team } } });
+    if !filter.include_closed {
+        conditions["state"] = json!({ "type": { "nin": ["completed", "canceled"] } });
+    }
+    if filter.assigned_to_me {
+        conditions["assignee"] = json!({ "isMe": { "eq": true } });
+    }
+    if !filter.labels.is_empty() {
+        conditions["labels"] = json!({ "name": { "in": filter.labels } });
+    }
+    if let Some(since) = &filter.updated_since {
+        conditions["updatedAt"] = json!({ "gte": since });
+    }
+    if let Some(text) = filter.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        conditions["title"] = json!({ "containsIgnoreCase": text });
+    }
+    conditions
+}
+
+impl Linear {
+    pub fn new(credentials: Credentials) -> Self {
+        Self { client: Client::new(), credentials }
+    }
+
+    /// Run a GraphQL operation and return its `data`
+    async fn query<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T, String> {
+        let body = json!({ "query": query, "variables": variables });
+        let response: Value = send("Linear", || {
+            self.client
+                .post(API_URL)
+                // Personal API keys are sent as-is, without `Bearer`
+                .header(AUTHORIZATION, &self.credentials.api_key)
+                .json(&body)
+        })
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Linear response: {}", e))?;
+
+        if let Some(error) = response.pointer("/errors/0") {
+            let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+            return Err(match error.pointer("/extensions/code").and_then(Value::as_str) {
+                Some("RATELIMITED") => "Linear rate limit reached; try again in a few minutes".to_string(),
+                _ => format!("Linear request failed: {}", message),
+            });
+        }
+        let data = response.get("data").cloned().unwrap_or(Value::Null);
+        serde_json::from_value(data).map_err(|e| format!("Invalid Linear response: {}", e))
+    }
+
+    /// Name of the account, which also checks the key
+    pub async fn viewer(&self) -> Result<String, String> {
+        let data: Value = self.query("query { viewer { name email } }", json!({})).await?;
+        data.pointer("/viewer/name")
+            .or_else(|| data.pointer("/viewer/email"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| "Invalid Linear response: no viewer".to_string())
+    }
+
+    pub async fn search(&self, team: &str, filter: &IssueFilter) -> Result<Vec<RemoteIssue>, String> {
+        #[derive(Deserialize)]
+        struct Data {
+            issues: IssuePage,
+        }
+        let query = format!(
+            "query Issues($filter: IssueFilter, $first: Int, $after: String) {{ \
+                issues(filter: $filter, first: $first, after: $after, orderBy: updatedAt) {{ \
+                    nodes {{ {} }} pageInfo {{ hasNextPage endCursor }} }} }}",
+            ISSUE_FIELDS
+        );
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let conditions = issue_filter(team, filter);
+        let mut issues = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let variables = json!({ "filter": conditions, "first": PAGE_SIZE.min(limit - issues.len()), "after": after });
+            let data: Data = self.query(&query, variables).await?;
+            issues.extend(data.issues.nodes.into_iter().map(RemoteIssue::from));
+            match data.issues.page_info {
+                PageInfo { has_next_page: true, end_cursor: Some(cursor) } if issues.len() < limit => after = Some(cursor),
+                _ => break,
+            }
+        }
+        Ok(issues)
+    }
+
+    /// An issue by identifier (`ENG-42`)
+    pub async fn get(&self, key: &str) -> Result<RemoteIssue, String> {
+        #[derive(Deserialize)]
+        struct Data {
+            issue: RawIssue,
+        }
+        let query = format!("query Issue($id: String!) {{ issue(id: $id) {{ {} }} }}", ISSUE_FIELDS);
+        let data: Data = self.query(&query, json!({ "id": key })).await?;
+        Ok(data.issue.into())
+    }
+
+    /// Move an issue to its team's first completed state
+    pub async fn complete(&self, key: &str) -> Result<(), String> {
+        let data: Value = self
+            .query(
+                "query States($id: String!) { issue(id: $id) { id team { \
+                    states(filter: { type: { eq: \"completed\" } }) { nodes { id position } } } } }",
+                json!({ "id": key }),
+            )
+            .await?;
+        let issue_id = data.pointer("/issue/id").and_then(Value::as_str).ok_or_else(|| format!("{} not found", key))?;
+        let state_id = data
+            .pointer("/issue/team/states/nodes")
+            .and_then(Value::as_array)
+            .and_then(|states| {
+                states.iter().min_by(|a, b| {
+                    let position = |s: &Value| s.get("position").and_then(Value::as_f64).unwrap_or_default();
+                    position(a).total_cmp(&position(b))
+                })
+            })
+            .and_then(|state| state.get("id")?.as_str())
+            .ok_or_else(|| format!("The team of {} has no completed state", key))?;
+
+        let result: Value = self
+            .query(
+                "mutation Complete($id: String!, $stateId: String!) { \
+                    issueUpdate(id: $id, input: { stateId: $stateId }) { success } }",
+                json!({ "id": issue_id, "stateId": state_id }),
+            )
+            .await?;
+        match result.pointer("/issueUpdate/success").and_then(Value::as_bool) {
+            Some(true) => Ok(()),
+            _ => Err(format!("Linear did not update {}", key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_issue_filters() {
+        let filter = IssueFilter {
+            assigned_to_me: true,
+            labels: vec!["bug".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            issue_filter("ENG", &filter),
+            json!({
+                "team": { "key": { "eqIgnoreCase": "ENG" } },
+                "state": { "type": { "nin": ["completed", "canceled"] } },
+                "assignee": { "isMe": { "eq": true } },
+                "labels": { "name": { "in": ["bug"] } }
+            })
+        );
+        assert_eq!(category("started"), StatusCategory::InProgress);
+        assert_eq!(category("backlog"), StatusCategory::Todo);
+        assert_eq!(category("canceled"), StatusCategory::Cancelled);
+    }
+}
@@ -0,0 +1,645 @@
+//! Jira and Linear issues as tasks.
+//!
+//! Issues are listed per Jira project or Linear team and can be imported as
+//! tasks, optionally with a card on a kanban board. An imported task keeps a
+//! `RemoteIssueLink` holding the remote status and the task's status at the
+//! last reconcile. The reconciler runs every few minutes: when the remote
+//! status has moved on, the task (and its card) follow it; when only the task
+//! was completed and `issue_sync.pushCompletion` is on, the issue is closed
+//! upstream. Credentials are kept in secure storage.
+
+mod jira;
+mod linear;
+
+use crate::kanban::{self, KanbanBoard, KanbanCard};
+use crate::tasks::{self, RemoteIssueLink, Task, TaskStatus};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use once_cell::sync::Lazy;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const JIRA_KEY: &str = "jira-credentials";
+const LINEAR_KEY: &str = "linear-credentials";
+/// Settings key holding an `IssueSyncConfig`
+const SETTINGS_KEY: &str = "issue_sync";
+const RECONCILED_EVENT: &str = "issues-reconciled";
+const MAX_RETRIES: u32 = 4;
+
+/// `None` until loaded from secure storage, which is slow
+static JIRA: Lazy<Mutex<Option<Option<jira::Credentials>>>> = Lazy::new(|| Mutex::new(None));
+static LINEAR: Lazy<Mutex<Option<Option<linear::Credentials>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Jira,
+    Linear,
+}
+
+impl Provider {
+    fn as_str(self) -> &'static str {
+        match self {
+            Provider::Jira => "jira",
+            Provider::Linear => "linear",
+        }
+    }
+
+    fn from_link(link: &RemoteIssueLink) -> Option<Self> {
+        match link.provider.as_str() {
+            "jira" => Some(Provider::Jira),
+            "linear" => Some(Provider::Linear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StatusCategory {
+    Todo,
+    InProgress,
+    Done,
+    Cancelled,
+}
+
+impl StatusCategory {
+    fn task_status(self) -> TaskStatus {
+        match self {
+            StatusCategory::Todo => TaskStatus::Todo,
+            StatusCategory::InProgress => TaskStatus::InProgress,
+            StatusCategory::Done => TaskStatus::Completed,
+            StatusCategory::Cancelled => TaskStatus::Cancelled,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteIssue {
+    pub provider: Provider,
+    /// `PROJ-12` in Jira, `ENG-12` in Linear
+    pub key: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Status as named in Jira or Linear
+    pub status: String,
+    pub category: StatusCategory,
+    pub url: String,
+    pub assignee: Option<String>,
+    pub priority: Option<String>,
+    pub labels: Vec<String>,
+    /// `YYYY-MM-DD`
+    pub due_date: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IssueFilter {
+    /// Include done and cancelled issues
+    pub include_closed: bool,
+    pub assigned_to_me: bool,
+    /// Issues with any of these labels
+    pub labels: Vec<String>,
+    /// Only issues updated since this date (`YYYY-MM-DD`)
+    pub updated_since: Option<String>,
+    /// Words in the title
+    pub text: Option<String>,
+    /// Defaults to 100
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IssueSyncConfig {
+    /// Close the remote issue when its task is completed in Lokus
+    pub push_completion: bool,
+    pub interval_minutes: u64,
+}
+
+impl Default for IssueSyncConfig {
+    fn default() -> Self {
+        Self { push_completion: false, interval_minutes: 10 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStatus {
+    pub provider: Provider,
+    pub connected: bool,
+    pub account: Option<String>,
+    /// Tasks linked to this provider's issues
+    pub linked_tasks: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub created: Vec<Task>,
+    /// Issues that already had a task
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReport {
+    /// Tasks whose status followed their issue
+    pub updated: Vec<String>,
+    /// Issues completed upstream
+    pub pushed: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+// --- HTTP ---
+
+/// Send a request (rebuilt for each attempt), waiting out rate limits
+pub(super) async fn send(service: &str, request: impl Fn() -> RequestBuilder) -> Result<Response, String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let response = request().send().await.map_err(|e| format!("Network error: {}", e))?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map_or(Duration::from_secs(2u64.pow(attempt)), Duration::from_secs);
+            tracing::warn!("{} rate limit reached; retrying in {}s", service, retry_after.as_secs());
+            tokio::time::sleep(retry_after).await;
+            continue;
+        }
+        return match status {
+            status if status.is_success() => Ok(response),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(format!("{} rejected the credentials", service)),
+            StatusCode::NOT_FOUND => Err(format!("Not found in {}", service)),
+            status => {
+                let text = response.text().await.unwrap_or_default();
+                Err(format!("{} request failed ({}): {}", service, status, text.trim()))
+            }
+        };
+    }
+}
+
+// --- Credentials ---
+
+fn load<T: DeserializeOwned + Clone>(cache: &Mutex<Option<Option<T>>>, key: &str) -> Option<T> {
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| {
+            crate::secure_storage::SecureStorage::new()
+                .ok()
+                .and_then(|storage| storage.retrieve::<T>(key).ok().flatten())
+        })
+        .clone()
+}
+
+/// Save credentials, or with `None` forget them
+fn store<T: Serialize + Clone>(cache: &Mutex<Option<Option<T>>>, key: &str, value: Option<T>) -> Result<(), String> {
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    match &value {
+        Some(value) => storage.store(key, value).map_err(|e| format!("Failed to save credentials: {}", e))?,
+        None => storage.delete(key).map_err(|e| format!("Failed to remove credentials: {}", e))?,
+    }
+    *cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+    Ok(())
+}
+
+enum Connector {
+    Jira(jira::Jira),
+    Linear(linear::Linear),
+}
+
+impl Connector {
+    fn for_provider(provider: Provider) -> Result<Self, String> {
+        match provider {
+            Provider::Jira => load(&JIRA, JIRA_KEY)
+                .map(|c| Connector::Jira(jira::Jira::new(c)))
+                .ok_or_else(|| "Jira is not connected".to_string()),
+            Provider::Linear => load(&LINEAR, LINEAR_KEY)
+                .map(|c| Connector::Linear(linear::Linear::new(c)))
+                .ok_or_else(|| "Linear is not connected".to_string()),
+        }
+    }
+
+    async fn search(&self, project: &str, filter: &IssueFilter) -> Result<Vec<RemoteIssue>, String> {
+        match self {
+            Connector::Jira(jira) => jira.search(project, filter).await,
+            Connector::Linear(linear) => linear.search(project, filter).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<RemoteIssue, String> {
+        match self {
+            Connector::Jira(jira) => jira.get(key).await,
+            Connector::Linear(linear) => linear.get(key).await,
+        }
+    }
+
+    async fn complete(&self, key: &str) -> Result<(), String> {
+        match self {
+            Connector::Jira(jira) => jira.complete(key).await,
+            Connector::Linear(linear) => linear.complete(key).await,
+        }
+    }
+}
+
+fn config(app: &AppHandle) -> IssueSyncConfig {
+    crate::settings::effective(app, None, SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn provider_status(app: &AppHandle, provider: Provider) -> ProviderStatus {
+    let account = match provider {
+        Provider::Jira => load(&JIRA, JIRA_KEY).map(|c| c.account.unwrap_or(c.email)),
+        Provider::Linear => load(&LINEAR, LINEAR_KEY).map(|c| c.account.unwrap_or_default()),
+    };
+    let linked_tasks = tasks::get_task_store(app)
+        .map(|store| {
+            store
+                .get_all_tasks()
+                .iter()
+                .filter(|t| t.remote_issue.as_ref().and_then(Provider::from_link) == Some(provider))
+                .count()
+        })
+        .unwrap_or_default();
+    ProviderStatus { provider, connected: account.is_some(), account, linked_tasks }
+}
+
+// --- Tasks and cards ---
+
+/// The board column a status belongs in: the first (leftmost) whose name
+/// implies it, else the first column for new work
+fn column_for(board: &KanbanBoard, status: &TaskStatus) -> Option<String> {
+    let mut columns: Vec<(&String, &kanban::KanbanColumn)> = board.columns.iter().collect();
+    columns.sort_by_key(|(_, column)| column.order);
+    columns
+        .iter()
+        .find(|(_, column)| crate::task_sync::status_for_column(&column.name).as_ref() == Some(status))
+        .or_else(|| (*status == TaskStatus::Todo).then(|| columns.first()).flatten())
+        .map(|(id, _)| id.to_string())
+}
+
+fn due_date(issue: &RemoteIssue) -> Option<String> {
+    let date = NaiveDate::parse_from_str(issue.due_date.as_deref()?, "%Y-%m-%d").ok()?;
+    Local.from_local_datetime(&date.and_time(NaiveTime::MIN)).earliest().map(|dt| dt.to_rfc3339())
+}
+
+fn new_task(issue: &RemoteIssue) -> Task {
+    let mut task = Task::new(issue.title.clone());
+    task.description = issue.description.clone();
+    task.status = issue.category.task_status();
+    task.tags = issue.labels.clone();
+    task.due_date = due_date(issue);
+    task.due_date_is_all_day = task.due_date.is_some();
+    task.remote_issue = Some(RemoteIssueLink {
+        provider: issue.provider.as_str().to_string(),
+        key: issue.key.clone(),
+        url: issue.url.clone(),
+        remote_status: issue.status.clone(),
+        synced_status: task.status.clone(),
+    });
+    task
+}
+
+fn new_card(issue: &RemoteIssue) -> KanbanCard {
+    let mut card = KanbanCard::new(format!("{} {}", issue.key, issue.title));
+    let link = format!("[{}]({})", issue.key, issue.url);
+    card.description = Some(match &issue.description {
+        Some(description) => format!("{}\n\n{}", link, description),
+        None => link,
+    });
+    card.tags = issue.labels.clone();
+    card.assignee = issue.assignee.clone();
+    card.due_date = issue.due_date.clone();
+    card
+}
+
+/// Move a task's card to the column for `status`, if the board has one
+async fn move_card(board_path: &str, card_id: &str, status: &TaskStatus) -> Result<(), String> {
+    let path = Path::new(board_path);
+    let mut board = kanban::load_board_from_file(path).await?;
+    let Some(to) = column_for(&board, status) else {
+        return Ok(());
+    };
+    let from = board
+        .columns
+        .iter()
+        .find(|(_, column)| column.cards.iter().any(|c| c.id == card_id))
+        .map(|(id, _)| id.clone());
+    match from {
+        Some(from) if from != to => {
+            board.move_card(card_id, &from, &to)?;
+            kanban::save_board_to_file(path, &board).await
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn import(
+    app: &AppHandle,
+    provider: Provider,
+    project: &str,
+    filter: &IssueFilter,
+    board_path: Option<&str>,
+) -> Result<ImportResult, String> {
+    let issues = Connector::for_provider(provider)?.search(project, filter).await?;
+    let mut board = match board_path {
+        Some(path) => Some(kanban::load_board_from_file(Path::new(path)).await?),
+        None => None,
+    };
+
+    let mut store = tasks::get_task_store(app)?;
+    let known: HashSet<String> = store
+        .get_all_tasks()
+        .iter()
+        .filter_map(|t| t.remote_issue.as_ref())
+        .filter(|link| Provider::from_link(link) == Some(provider))
+        .map(|link| link.key.clone())
+        .collect();
+
+    let mut result = ImportResult::default();
+    for issue in &issues {
+        if known.contains(&issue.key) {
+            result.skipped += 1;
+            continue;
+        }
+        let mut task = new_task(issue);
+        if let (Some(board), Some(board_path)) = (board.as_mut(), board_path) {
+            let column = column_for(board, &task.status)
+                .or_else(|| column_for(board, &TaskStatus::Todo))
+                .ok_or_else(|| "The board has no columns".to_string())?;
+            let card = new_card(issue);
+            task.kanban_board = Some(board_path.to_string());
+            task.kanban_column = Some(column.clone());
+            task.kanban_card_id = Some(card.id.clone());
+            board.add_card(&column, card)?;
+        }
+        store.add_task(task.clone());
+        result.created.push(task);
+    }
+
+    if let (Some(board), Some(board_path)) = (&board, board_path) {
+        if !result.created.is_empty() {
+            kanban::save_board_to_file(Path::new(board_path), board).await?;
+        }
+    }
+    tasks::save_task_store(app, &store)?;
+    Ok(result)
+}
+
+// --- Reconciling ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    None,
+    /// The issue moved on; the task follows
+    Pull(TaskStatus),
+    /// Only the task was completed; close the issue
+    Push,
+}
+
+fn decide(link: &RemoteIssueLink, local: &TaskStatus, remote: &RemoteIssue, push_completion: bool) -> Change {
+    if remote.status != link.remote_status {
+        let status = remote.category.task_status();
+        return if status == *local { Change::None } else { Change::Pull(status) };
+    }
+    let completed_locally = *local == TaskStatus::Completed && link.synced_status != TaskStatus::Completed;
+    if push_completion && completed_locally && remote.category != StatusCategory::Done {
+        Change::Push
+    } else {
+        Change::None
+    }
+}
+
+/// Bring linked tasks in line with their issues
+async fn reconcile(app: &AppHandle) -> Result<ReconcileReport, String> {
+    let config = config(app);
+    let linked: Vec<(String, RemoteIssueLink, TaskStatus)> = tasks::get_task_store(app)?
+        .get_all_tasks()
+        .into_iter()
+        .filter_map(|t| Some((t.id.clone(), t.remote_issue.clone()?, t.status.clone())))
+        .collect();
+
+    let mut report = ReconcileReport::default();
+    let mut connectors: HashMap<Provider, Option<Connector>> = HashMap::new();
+    let mut fetched: Vec<(String, RemoteIssue)> = Vec::new();
+    for (task_id, link, status) in linked {
+        let Some(provider) = Provider::from_link(&link) else { continue };
+        let Some(connector) = connectors.entry(provider).or_insert_with(|| Connector::for_provider(provider).ok()) else {
+            continue;
+        };
+        let mut issue = match connector.get(&link.key).await {
+            Ok(issue) => issue,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", link.key, e));
+                continue;
+            }
+        };
+        if decide(&link, &status, &issue, config.push_completion) == Change::Push {
+            match connector.complete(&link.key).await {
+                Ok(()) => {
+                    report.pushed.push(link.key.clone());
+                    // Record the status the issue was moved to, so it isn't read as a remote change
+                    issue = connector.get(&link.key).await.unwrap_or(issue);
+                }
+                Err(e) => report.errors.push(format!("{}: {}", link.key, e)),
+            }
+        }
+        fetched.push((task_id, issue));
+    }
+
+    // Tasks may have changed while issues were fetched, so apply to a fresh copy
+    let mut store = tasks::get_task_store(app)?;
+    let mut moves = Vec::new();
+    for (task_id, issue) in fetched {
+        let Some(mut task) = store.get_task(&task_id).cloned() else { continue };
+        let Some(mut link) = task.remote_issue.clone() else { continue };
+        let pushed = report.pushed.contains(&link.key);
+        if let Change::Pull(status) = decide(&link, &task.status, &issue, false) {
+            if !pushed {
+                task.update_status(status.clone());
+                if let (Some(board), Some(card)) = (&task.kanban_board, &task.kanban_card_id) {
+                    moves.push((board.clone(), card.clone(), status));
+                }
+                report.updated.push(task_id.clone());
+            }
+        }
+        if link.remote_status == issue.status && link.synced_status == task.status && !pushed {
+            continue;
+        }
+        link.remote_status = issue.status.clone();
+        link.synced_status = task.status.clone();
+        task.remote_issue = Some(link);
+        store.update_task(&task_id, task)?;
+        if report.updated.contains(&task_id) {
+            store.spawn_next_occurrence(&task_id);
+            if let Err(e) = crate::task_sync::push_to_note(&mut store, &task_id, false) {
+                tracing::warn!("Failed to sync task {} to its note: {}", task_id, e);
+            }
+        }
+    }
+    tasks::save_task_store(app, &store)?;
+
+    for (board, card, status) in moves {
+        if let Err(e) = move_card(&board, &card, &status).await {
+            tracing::warn!("Failed to move card {} on {}: {}", card, board, e);
+        }
+    }
+    Ok(report)
+}
+
+fn emit_report(app: &AppHandle, report: &ReconcileReport) {
+    if report.updated.is_empty() && report.pushed.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(RECONCILED_EVENT, report) {
+        tracing::warn!("Failed to emit {}: {}", RECONCILED_EVENT, e);
+    }
+}
+
+/// Reconcile linked tasks for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = config(&app).interval_minutes.max(1);
+            tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+            if load(&JIRA, JIRA_KEY).is_none() && load(&LINEAR, LINEAR_KEY).is_none() {
+                continue;
+            }
+            match reconcile(&app).await {
+                Ok(report) => emit_report(&app, &report),
+                Err(e) => tracing::warn!("Issue reconcile failed: {}", e),
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Connect Jira Cloud with an account email and API token
+/// (id.atlassian.com/manage-profile/security/api-tokens)
+#[tauri::command]
+pub async fn issues_connect_jira(app: AppHandle, site: String, email: String, api_token: String) -> Result<ProviderStatus, String> {
+    let mut credentials = jira::Credentials {
+        site: jira::normalize_site(&site)?,
+        email: email.trim().to_string(),
+        api_token: api_token.trim().to_string(),
+        account: None,
+    };
+    credentials.account = Some(jira::Jira::new(credentials.clone()).myself().await?);
+    store(&JIRA, JIRA_KEY, Some(credentials))?;
+    Ok(provider_status(&app, Provider::Jira))
+}
+
+/// Connect Linear with a personal API key
+#[tauri::command]
+pub async fn issues_connect_linear(app: AppHandle, api_key: String) -> Result<ProviderStatus, String> {
+    let mut credentials = linear::Credentials { api_key: api_key.trim().to_string(), account: None };
+    credentials.account = Some(linear::Linear::new(credentials.clone()).viewer().await?);
+    store(&LINEAR, LINEAR_KEY, Some(credentials))?;
+    Ok(provider_status(&app, Provider::Linear))
+}
+
+/// Forget a provider's credentials; imported tasks are kept
+#[tauri::command]
+pub fn issues_disconnect(provider: Provider) -> Result<(), String> {
+    match provider {
+        Provider::Jira => store::<jira::Credentials>(&JIRA, JIRA_KEY, None),
+        Provider::Linear => store::<linear::Credentials>(&LINEAR, LINEAR_KEY, None),
+    }
+}
+
+#[tauri::command]
+pub fn issues_status(app: AppHandle) -> Vec<ProviderStatus> {
+    [Provider::Jira, Provider::Linear].into_iter().map(|p| provider_status(&app, p)).collect()
+}
+
+/// Issues of a Jira project or Linear team (by key), most recently updated first
+#[tauri::command]
+pub async fn issues_list(provider: Provider, project: String, filter: Option<IssueFilter>) -> Result<Vec<RemoteIssue>, String> {
+    Connector::for_provider(provider)?.search(project.trim(), &filter.unwrap_or_default()).await
+}
+
+/// Create a task for each matching issue that doesn't have one yet, and a
+/// card for it on `board` if given
+#[tauri::command]
+pub async fn issues_import_as_tasks(
+    app: AppHandle,
+    provider: Provider,
+    project: String,
+    filter: Option<IssueFilter>,
+    board: Option<String>,
+) -> Result<ImportResult, String> {
+    import(&app, provider, project.trim(), &filter.unwrap_or_default(), board.as_deref()).await
+}
+
+/// Reconcile linked tasks now rather than waiting for the next round
+#[tauri::command]
+pub async fn issues_reconcile(app: AppHandle) -> Result<ReconcileReport, String> {
+    let report = reconcile(&app).await?;
+    emit_report(&app, &report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(status: &str, category: StatusCategory) -> RemoteIssue {
+        RemoteIssue {
+            provider: Provider::Linear,
+            key: "ENG-1".to_string(),
+            title: "Ship it".to_string(),
+            description: None,
+            status: status.to_string(),
+            category,
+            url: "https://linear.app/acme/issue/ENG-1".to_string(),
+            assignee: None,
+            priority: None,
+            labels: Vec::new(),
+            due_date: Some("2026-03-01".to_string()),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn remote_changes_win_and_local_completion_is_pushed() {
+        let task = new_task(&issue("Todo", StatusCategory::Todo));
+        let link = task.remote_issue.clone().unwrap();
+        assert_eq!(link.synced_status, TaskStatus::Todo);
+        assert!(task.due_date_is_all_day);
+
+        let started = issue("In Progress", StatusCategory::InProgress);
+        assert_eq!(decide(&link, &TaskStatus::Todo, &started, true), Change::Pull(TaskStatus::InProgress));
+        // Both sides changed: the issue wins
+        assert_eq!(decide(&link, &TaskStatus::Completed, &started, true), Change::Pull(TaskStatus::InProgress));
+
+        let unchanged = issue("Todo", StatusCategory::Todo);
+        assert_eq!(decide(&link, &TaskStatus::Completed, &unchanged, true), Change::Push);
+        assert_eq!(decide(&link, &TaskStatus::Completed, &unchanged, false), Change::None);
+        assert_eq!(decide(&link, &TaskStatus::Todo, &unchanged, true), Change::None);
+    }
+
+    #[test]
+    fn picks_columns_by_name() {
+        let board = KanbanBoard::new("Work".to_string(), vec!["Backlog".into(), "Doing".into(), "Review".into(), "Done".into()]);
+        assert_eq!(column_for(&board, &TaskStatus::Todo).as_deref(), Some("backlog"));
+        assert_eq!(column_for(&board, &TaskStatus::InProgress).as_deref(), Some("doing"));
+        assert_eq!(column_for(&board, &TaskStatus::Completed).as_deref(), Some("done"));
+        assert_eq!(column_for(&board, &TaskStatus::Cancelled), None);
+
+        let plain = KanbanBoard::new("Plain".to_string(), vec!["Ideas".into(), "Later".into()]);
+        assert_eq!(column_for(&plain, &TaskStatus::Todo).as_deref(), Some("ideas"));
+    }
+}
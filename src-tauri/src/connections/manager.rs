@@ -22,7 +22,7 @@ impl ConnectionManager {
         let gmail_queue = Arc::new(OfflineQueue::new()?);
         
         // Initialize Gmail API
-        let gmail_api = Arc::new(GmailApi::new(gmail_queue.clone())?);
+        let gmail_api = Arc::new(GmailApi::new()?);
         
         // Initialize queue processor
         let queue_processor = Arc::new(QueueProcessor::new(gmail_queue.clone()));
@@ -43,7 +43,7 @@ impl ConnectionManager {
         
         // Initialize minimal components for graceful error handling
         let gmail_queue = Arc::new(OfflineQueue::new()?);
-        let gmail_api = Arc::new(GmailApi::new(gmail_queue.clone())?);
+        let gmail_api = Arc::new(GmailApi::new()?);
         let queue_processor = Arc::new(QueueProcessor::new(gmail_queue.clone()));
         
         let manager = Self {
@@ -128,6 +128,20 @@ impl ConnectionManager {
         self.gmail_api.get_email_by_id(message_id).await
     }
 
+    pub async fn list_message_ids(&self, label_id: &str, max_results: u32) -> Result<Vec<String>, GmailError> {
+        self.gmail_api.list_message_ids(label_id, max_results).await
+    }
+
+    pub async fn download_attachment(
+        &self,
+        message_id: &str,
+        attachment_id: &str,
+        max_bytes: usize,
+        out: &mut impl std::io::Write,
+    ) -> Result<u64, GmailError> {
+        self.gmail_api.download_attachment(message_id, attachment_id, max_bytes, out).await
+    }
+
     pub async fn send_email(&self, composer: EmailComposer) -> Result<String, GmailError> {
         self.gmail_api.send_email(composer).await
     }
@@ -0,0 +1,482 @@
+//! Slack and Discord channels.
+//!
+//! Outgoing: notes or selections are posted to a channel's incoming webhook.
+//! Webhook URLs carry their own credentials, so channels are kept in secure
+//! storage and only their names are shown to the UI. Markdown is adapted to
+//! Slack's mrkdwn, wiki links become plain text, and long notes are split
+//! into several messages at line breaks.
+//!
+//! Incoming: bots, shortcuts and scripts can forward a message to
+//! `POST /api/v1/capture/message` on the local API server (see
+//! `api_server::rest`), which appends it to the capture inbox like quick
+//! capture does.
+
+use once_cell::sync::Lazy;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const STORAGE_KEY: &str = "messaging-channels";
+/// Discord rejects longer messages
+const DISCORD_MAX_CHARS: usize = 2000;
+/// Slack truncates text past 40,000 characters but renders long messages
+/// poorly well before that
+const SLACK_MAX_CHARS: usize = 3900;
+const MAX_RETRIES: u32 = 3;
+
+/// Channels as stored; `None` until first loaded from secure storage
+static CHANNELS: Lazy<Mutex<Option<Vec<StoredChannel>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Slack,
+    Discord,
+}
+
+impl Platform {
+    /// Which service a webhook URL belongs to
+    fn of_webhook(url: &str) -> Option<Self> {
+        let parsed = url::Url::parse(url.trim()).ok()?;
+        if parsed.scheme() != "https" {
+            return None;
+        }
+        match (parsed.host_str()?, parsed.path()) {
+            ("hooks.slack.com", path) if path.starts_with("/services/") => Some(Platform::Slack),
+            ("discord.com" | "discordapp.com" | "canary.discord.com" | "ptb.discord.com", path)
+                if path.starts_with("/api/webhooks/") =>
+            {
+                Some(Platform::Discord)
+            }
+            _ => None,
+        }
+    }
+
+    fn max_chars(self) -> usize {
+        match self {
+            Platform::Slack => SLACK_MAX_CHARS,
+            Platform::Discord => DISCORD_MAX_CHARS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredChannel {
+    id: String,
+    name: String,
+    platform: Platform,
+    webhook_url: String,
+    created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelInfo {
+    pub id: String,
+    pub name: String,
+    pub platform: Platform,
+    pub created_at: i64,
+}
+
+impl From<&StoredChannel> for ChannelInfo {
+    fn from(channel: &StoredChannel) -> Self {
+        Self {
+            id: channel.id.clone(),
+            name: channel.name.clone(),
+            platform: channel.platform,
+            created_at: channel.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendResult {
+    pub channel: ChannelInfo,
+    /// Messages posted; long content is split
+    pub messages: usize,
+}
+
+/// A message forwarded to `/api/v1/capture/message`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCapture {
+    pub text: String,
+    pub author: Option<String>,
+    /// Channel or conversation the message was posted in
+    pub channel: Option<String>,
+    /// Where it came from, e.g. `Slack` or `Discord`
+    pub source: Option<String>,
+    /// Link back to the message
+    pub url: Option<String>,
+    /// Inbox or daily note; the capture default otherwise
+    pub target: Option<crate::capture::CaptureTarget>,
+}
+
+// --- Channel storage ---
+
+fn storage() -> Result<crate::secure_storage::SecureStorage, String> {
+    crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))
+}
+
+/// Run `f` on the channel list, loading it on first use and saving it if `f`
+/// reports a change
+fn with_channels<T>(f: impl FnOnce(&mut Vec<StoredChannel>) -> (T, bool)) -> Result<T, String> {
+    let mut cached = CHANNELS.lock().unwrap_or_else(|e| e.into_inner());
+    if cached.is_none() {
+        let stored: Option<Vec<StoredChannel>> =
+            storage()?.retrieve(STORAGE_KEY).map_err(|e| format!("Failed to load channels: {}", e))?;
+        *cached = Some(stored.unwrap_or_default());
+    }
+    let channels = cached.as_mut().expect("loaded above");
+    let (result, changed) = f(channels);
+    if changed {
+        storage()?.store(STORAGE_KEY, channels).map_err(|e| format!("Failed to save channels: {}", e))?;
+    }
+    Ok(result)
+}
+
+/// A channel by id or name (`#` and case are ignored)
+fn find_channel(target: &str) -> Result<StoredChannel, String> {
+    let target = target.trim();
+    let name = target.trim_start_matches('#');
+    with_channels(|channels| {
+        let found = channels
+            .iter()
+            .find(|c| c.id == target)
+            .or_else(|| channels.iter().find(|c| c.name.eq_ignore_ascii_case(name)))
+            .cloned();
+        (found, false)
+    })?
+    .ok_or_else(|| format!("No channel named '{}'", target))
+}
+
+// --- Formatting ---
+
+/// `[[Note]]` and `[[Note|alias]]` as plain text; chat apps can't follow them
+fn unwrap_wiki_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("[[") {
+        let Some(len) = rest[start + 2..].find("]]") else { break };
+        let inner = &rest[start + 2..start + 2 + len];
+        out.push_str(&rest[..start]);
+        out.push_str(inner.rsplit('|').next().unwrap_or(inner).split('#').next().unwrap_or(inner));
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `[text](url)` as Slack's `<url|text>`
+fn slack_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start + 1..].find("](").and_then(|mid| {
+            let text = &rest[start + 1..start + 1 + mid];
+            let after = &rest[start + 1 + mid + 2..];
+            let end = after.find(')')?;
+            Some((text, &after[..end], start + 1 + mid + 2 + end + 1))
+        });
+        match link {
+            Some((text, url, end)) if !text.contains('[') => {
+                out.push_str(&rest[..start]);
+                out.push_str(&format!("<{}|{}>", url, text));
+                rest = &rest[end..];
+            }
+            _ => {
+                out.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Markdown as Slack mrkdwn: headings and bold become `*bold*`, links
+/// `<url|text>`, and `&`, `<` and `>` are escaped as Slack requires
+fn to_slack(markdown: &str) -> String {
+    let mut in_fence = false;
+    markdown
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            let escaped = line.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+            if in_fence {
+                return escaped;
+            }
+            let trimmed = escaped.trim_start();
+            let line = match trimmed.trim_start_matches('#') {
+                heading if heading.len() < trimmed.len() && heading.starts_with(' ') => {
+                    format!("*{}*", heading.trim().trim_matches('*'))
+                }
+                _ => escaped.replace("**", "*").replace("__", "*").replace("~~", "~"),
+            };
+            let line = line.replacen("- [ ] ", "☐ ", 1).replacen("- [x] ", "☑ ", 1);
+            slack_links(&unwrap_wiki_links(&line))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn to_discord(markdown: &str) -> String {
+    let mut in_fence = false;
+    markdown
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            }
+            if in_fence { line.to_string() } else { unwrap_wiki_links(line) }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split `text` into messages of at most `max` characters, at line breaks
+/// where possible
+fn split_messages(text: &str, max: usize) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for line in text.lines() {
+        let mut line = line;
+        loop {
+            let line_chars = line.chars().count();
+            let needed = if current.is_empty() { line_chars } else { line_chars + 1 };
+            if current_chars + needed <= max {
+                if !current.is_empty() {
+                    current.push('\n');
+                }
+                current.push_str(line);
+                current_chars += needed;
+                break;
+            }
+            if !current.is_empty() {
+                messages.push(std::mem::take(&mut current));
+                current_chars = 0;
+                continue;
+            }
+            // A single line longer than a message
+            let cut = line.char_indices().nth(max).map_or(line.len(), |(i, _)| i);
+            messages.push(line[..cut].to_string());
+            line = &line[cut..];
+            if line.is_empty() {
+                break;
+            }
+        }
+    }
+    if !current.trim().is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+fn payload(platform: Platform, message: &str) -> serde_json::Value {
+    match platform {
+        Platform::Slack => json!({ "text": message }),
+        // Never ping @everyone or anyone else from a note
+        Platform::Discord => json!({ "content": message, "allowed_mentions": { "parse": [] } }),
+    }
+}
+
+async fn post(client: &reqwest::Client, channel: &StoredChannel, message: &str) -> Result<(), String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let response = client
+            .post(&channel.webhook_url)
+            .json(&payload(channel.platform, message))
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<f64>().ok())
+                .map_or(Duration::from_secs(2u64.pow(attempt)), Duration::from_secs_f64);
+            tracing::warn!("Rate limited posting to {}; retrying in {:.1}s", channel.name, retry_after.as_secs_f64());
+            tokio::time::sleep(retry_after).await;
+            continue;
+        }
+        if status.is_success() {
+            return Ok(());
+        }
+        let text = response.text().await.unwrap_or_default();
+        return Err(match status {
+            StatusCode::NOT_FOUND | StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED | StatusCode::GONE => {
+                format!("The webhook for '{}' is no longer valid", channel.name)
+            }
+            status => format!("Posting to '{}' failed ({}): {}", channel.name, status, text.trim()),
+        });
+    }
+}
+
+// --- Capture ---
+
+fn capture_text(message: &MessageCapture) -> String {
+    let present = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    let mut context = Vec::new();
+    if let Some(author) = present(&message.author) {
+        context.push(format!("**{}**", author));
+    }
+    if let Some(channel) = present(&message.channel) {
+        context.push(format!("in #{}", channel.trim_start_matches('#')));
+    }
+    if let Some(source) = present(&message.source) {
+        context.push(format!("via {}", source));
+    }
+
+    let mut text = if context.is_empty() {
+        message.text.trim().to_string()
+    } else {
+        format!("{}: {}", context.join(" "), message.text.trim())
+    };
+    if let Some(url) = present(&message.url) {
+        text.push_str(&format!("\n[View message]({})", url));
+    }
+    text
+}
+
+/// Append a forwarded message to the capture inbox of `root`
+pub(crate) fn capture(app: &AppHandle, root: &Path, message: &MessageCapture) -> Result<crate::capture::CaptureResult, String> {
+    if message.text.trim().is_empty() {
+        return Err("The message has no text".to_string());
+    }
+    crate::capture::append(app, root, &capture_text(message), message.target)
+}
+
+// --- Tauri Commands ---
+
+/// Save a Slack or Discord incoming webhook as a named channel
+#[tauri::command]
+pub async fn messaging_add_channel(name: String, webhook_url: String) -> Result<ChannelInfo, String> {
+    let name = name.trim().trim_start_matches('#').to_string();
+    if name.is_empty() {
+        return Err("A channel needs a name".to_string());
+    }
+    let platform = Platform::of_webhook(&webhook_url)
+        .ok_or_else(|| "Not a Slack or Discord incoming webhook URL".to_string())?;
+    let channel = StoredChannel {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        platform,
+        webhook_url: webhook_url.trim().to_string(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    tokio::task::spawn_blocking(move || {
+        with_channels(|channels| {
+            if channels.iter().any(|c| c.name.eq_ignore_ascii_case(&channel.name)) {
+                return (Err(format!("A channel named '{}' already exists", channel.name)), false);
+            }
+            let info = ChannelInfo::from(&channel);
+            channels.push(channel);
+            (Ok(info), true)
+        })
+    })
+    .await
+    .map_err(|e| format!("Channel task failed: {}", e))??
+}
+
+#[tauri::command]
+pub async fn messaging_list_channels() -> Result<Vec<ChannelInfo>, String> {
+    tokio::task::spawn_blocking(|| with_channels(|channels| (channels.iter().map(ChannelInfo::from).collect(), false)))
+        .await
+        .map_err(|e| format!("Channel task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn messaging_remove_channel(id: String) -> Result<bool, String> {
+    tokio::task::spawn_blocking(move || {
+        with_channels(|channels| {
+            let before = channels.len();
+            channels.retain(|c| c.id != id);
+            let removed = channels.len() != before;
+            (removed, removed)
+        })
+    })
+    .await
+    .map_err(|e| format!("Channel task failed: {}", e))?
+}
+
+/// Post a note or selection to a channel (by id or name)
+#[tauri::command]
+pub async fn send_to_channel(target: String, content: String) -> Result<SendResult, String> {
+    let channel = tokio::task::spawn_blocking(move || find_channel(&target))
+        .await
+        .map_err(|e| format!("Channel task failed: {}", e))??;
+    let body = crate::export::strip_frontmatter(&content).trim();
+    if body.is_empty() {
+        return Err("Nothing to send".to_string());
+    }
+    let text = match channel.platform {
+        Platform::Slack => to_slack(body),
+        Platform::Discord => to_discord(body),
+    };
+
+    let client = reqwest::Client::new();
+    let messages = split_messages(&text, channel.platform.max_chars());
+    for message in &messages {
+        post(&client, &channel, message).await?;
+    }
+    Ok(SendResult { channel: ChannelInfo::from(&channel), messages: messages.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_webhooks() {
+        assert_eq!(Platform::of_webhook("https://hooks.slack.com/services/T0/B0/xyz"), Some(Platform::Slack));
+        assert_eq!(Platform::of_webhook("https://discord.com/api/webhooks/1/abc"), Some(Platform::Discord));
+        assert_eq!(Platform::of_webhook("http://hooks.slack.com/services/T0/B0/xyz"), None);
+        assert_eq!(Platform::of_webhook("https://example.com/api/webhooks/1/abc"), None);
+    }
+
+    #[test]
+    fn converts_markdown_for_slack() {
+        let markdown = "## Plan\nShip **v2** & see [docs](https://x.io) or [[Roadmap|the roadmap]]\n- [ ] test <it>\n```\n**raw**\n```";
+        assert_eq!(
+            to_slack(markdown),
+            "*Plan*\nShip *v2* &amp; see <https://x.io|docs> or the roadmap\n☐ test &lt;it&gt;\n```\n**raw**\n```"
+        );
+        assert_eq!(to_discord("See [[Roadmap#Q3]] and [[A|b]]"), "See Roadmap and b");
+    }
+
+    #[test]
+    fn splits_long_messages_at_lines() {
+        assert_eq!(split_messages("aaa\nbbb\ncc", 7), vec!["aaa\nbbb", "cc"]);
+        assert_eq!(split_messages("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(split_messages("short", 2000), vec!["short"]);
+    }
+
+    #[test]
+    fn formats_captured_messages() {
+        let message = MessageCapture {
+            text: " Ship it Friday ".to_string(),
+            author: Some("sam".to_string()),
+            channel: Some("#releases".to_string()),
+            source: Some("Slack".to_string()),
+            url: Some("https://example.slack.com/archives/C1/p1".to_string()),
+            target: None,
+        };
+        assert_eq!(
+            capture_text(&message),
+            "**sam** in #releases via Slack: Ship it Friday\n[View message](https://example.slack.com/archives/C1/p1)"
+        );
+    }
+}
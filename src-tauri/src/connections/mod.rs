@@ -1,4 +1,13 @@
 pub mod gmail;
+pub mod imap;
+pub mod inbox;
+pub mod contacts;
+pub mod readwise;
+pub mod github;
+pub mod messaging;
+pub mod issues;
+pub mod oauth_manager;
+pub mod request_queue;
 pub mod manager;
 pub mod commands;
 
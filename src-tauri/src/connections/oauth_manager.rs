@@ -0,0 +1,561 @@
+//! One place for OAuth tokens.
+//!
+//! Gmail, Google Calendar, Outlook and the Lokus account each keep their token
+//! where they always have (so existing connections survive), but reading,
+//! refreshing and revoking goes through here. Refreshes are serialized per
+//! provider: whoever takes the lock second re-reads the stored token and finds
+//! it already fresh, instead of spending the refresh token a second time. A
+//! background loop refreshes tokens ahead of expiry, with jitter so providers
+//! don't all refresh at once after the machine wakes.
+//!
+//! Adding a provider means adding a `Provider` variant with its endpoint and
+//! storage; refresh, revocation and health reporting come for free.
+
+use crate::auth::{AuthService, AuthToken};
+use crate::calendar::models::{CalendarError, CalendarToken};
+use crate::calendar::storage::CalendarStorage;
+use crate::connections::gmail::models::{GmailError, GmailToken};
+use crate::connections::gmail::storage::GmailStorage;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// Refresh on use when less than this is left
+const USE_MARGIN_SECS: u64 = 300;
+/// The background loop refreshes this far ahead of expiry...
+const AHEAD_SECS: u64 = 600;
+/// ...plus up to this much at random
+const JITTER_SECS: u64 = 300;
+const CHECK_INTERVAL: Duration = Duration::from_secs(120);
+const STATUS_EVENT: &str = "connections-status-changed";
+
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const GOOGLE_REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
+const MICROSOFT_TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Provider {
+    Lokus,
+    Gmail,
+    GoogleCalendar,
+    Outlook,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 4] = [Provider::Lokus, Provider::Gmail, Provider::GoogleCalendar, Provider::Outlook];
+
+    fn name(self) -> &'static str {
+        match self {
+            Provider::Lokus => "Lokus",
+            Provider::Gmail => "Gmail",
+            Provider::GoogleCalendar => "Google Calendar",
+            Provider::Outlook => "Outlook",
+        }
+    }
+
+    fn endpoint(self) -> Result<Endpoint, String> {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let required = |name: &str| env(name).ok_or_else(|| format!("{} environment variable not set", name));
+        Ok(match self {
+            Provider::Lokus => Endpoint {
+                token_url: format!(
+                    "{}/api/auth/refresh",
+                    env("AUTH_BASE_URL").unwrap_or_else(|| "https://lokusmd.com".to_string())
+                ),
+                revoke_url: None,
+                client_id: "lokus-desktop".to_string(),
+                client_secret: None,
+            },
+            Provider::Gmail | Provider::GoogleCalendar => Endpoint {
+                token_url: GOOGLE_TOKEN_URL.to_string(),
+                revoke_url: Some(GOOGLE_REVOKE_URL),
+                client_id: required("GOOGLE_CLIENT_ID")?,
+                client_secret: Some(required("GOOGLE_CLIENT_SECRET")?),
+            },
+            // A public client, so the secret is optional; Microsoft has no
+            // endpoint to revoke a single token
+            Provider::Outlook => Endpoint {
+                token_url: MICROSOFT_TOKEN_URL.to_string(),
+                revoke_url: None,
+                client_id: required("MICROSOFT_CLIENT_ID")?,
+                client_secret: env("MICROSOFT_CLIENT_SECRET"),
+            },
+        })
+    }
+
+    fn load(self) -> Result<Option<OAuthToken>, String> {
+        match self {
+            Provider::Lokus => Ok(AuthService::get_token()?.map(OAuthToken::from)),
+            Provider::Gmail => GmailStorage::get_token().map(|t| t.map(OAuthToken::from)).map_err(|e| e.to_string()),
+            Provider::GoogleCalendar => {
+                CalendarStorage::get_google_token().map(|t| t.map(OAuthToken::from)).map_err(|e| e.to_string())
+            }
+            Provider::Outlook => {
+                CalendarStorage::get_outlook_token().map(|t| t.map(OAuthToken::from)).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    fn save(self, token: &OAuthToken) -> Result<(), String> {
+        match self {
+            Provider::Lokus => {
+                // The user id only comes with sign-in
+                let user_id = AuthService::get_token()?.and_then(|t| t.user_id);
+                AuthService::store_token(&AuthToken {
+                    access_token: token.access_token.clone(),
+                    refresh_token: token.refresh_token.clone(),
+                    expires_at: token.expires_at,
+                    user_id,
+                    token_type: token.token_type.clone(),
+                })
+            }
+            Provider::Gmail => GmailStorage::store_token(&token.clone().into()).map_err(|e| e.to_string()),
+            Provider::GoogleCalendar => CalendarStorage::store_google_token(&token.clone().into()).map_err(|e| e.to_string()),
+            Provider::Outlook => CalendarStorage::store_outlook_token(&token.clone().into()).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Forget the token and the account it belongs to
+    fn clear(self) {
+        let results = match self {
+            Provider::Lokus => vec![AuthService::delete_token(), AuthService::delete_user_profile()],
+            Provider::Gmail => vec![
+                GmailStorage::delete_token().map_err(|e| e.to_string()),
+                GmailStorage::delete_profile().map_err(|e| e.to_string()),
+            ],
+            Provider::GoogleCalendar => vec![
+                CalendarStorage::delete_google_token().map_err(|e| e.to_string()),
+                CalendarStorage::delete_google_account().map_err(|e| e.to_string()),
+            ],
+            Provider::Outlook => vec![
+                CalendarStorage::delete_outlook_token().map_err(|e| e.to_string()),
+                CalendarStorage::delete_outlook_account().map_err(|e| e.to_string()),
+            ],
+        };
+        for e in results.into_iter().filter_map(Result::err) {
+            tracing::warn!("Failed to clear {} credentials: {}", self.name(), e);
+        }
+    }
+}
+
+struct Endpoint {
+    token_url: String,
+    revoke_url: Option<&'static str>,
+    client_id: String,
+    client_secret: Option<String>,
+}
+
+/// The token shape shared by every provider's storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthToken {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// Unix seconds; `None` for tokens that don't expire
+    pub expires_at: Option<u64>,
+    pub scope: String,
+    pub token_type: String,
+}
+
+impl OAuthToken {
+    fn expires_within(&self, now: u64, secs: u64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now.saturating_add(secs))
+    }
+}
+
+impl From<GmailToken> for OAuthToken {
+    fn from(t: GmailToken) -> Self {
+        Self {
+            access_token: t.access_token,
+            refresh_token: t.refresh_token,
+            expires_at: t.expires_at,
+            scope: t.scope,
+            token_type: t.token_type,
+        }
+    }
+}
+
+impl From<OAuthToken> for GmailToken {
+    fn from(t: OAuthToken) -> Self {
+        Self {
+            access_token: t.access_token,
+            refresh_token: t.refresh_token,
+            expires_at: t.expires_at,
+            scope: t.scope,
+            token_type: t.token_type,
+        }
+    }
+}
+
+impl From<CalendarToken> for OAuthToken {
+    fn from(t: CalendarToken) -> Self {
+        Self {
+            access_token: t.access_token,
+            refresh_token: t.refresh_token,
+            expires_at: t.expires_at,
+            scope: t.scope,
+            token_type: t.token_type,
+        }
+    }
+}
+
+impl From<OAuthToken> for CalendarToken {
+    fn from(t: OAuthToken) -> Self {
+        Self {
+            access_token: t.access_token,
+            refresh_token: t.refresh_token,
+            expires_at: t.expires_at,
+            scope: t.scope,
+            token_type: t.token_type,
+        }
+    }
+}
+
+impl From<AuthToken> for OAuthToken {
+    fn from(t: AuthToken) -> Self {
+        Self {
+            access_token: t.access_token,
+            refresh_token: t.refresh_token,
+            expires_at: t.expires_at,
+            scope: String::new(),
+            token_type: t.token_type,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenError {
+    NotConnected,
+    /// The refresh token was rejected; the user has to connect again
+    Reauthorize,
+    Failed(String),
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenError::NotConnected => write!(f, "Not connected"),
+            TokenError::Reauthorize => write!(f, "The connection has expired; please connect again"),
+            TokenError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<TokenError> for GmailError {
+    fn from(e: TokenError) -> Self {
+        match e {
+            TokenError::NotConnected => GmailError::Auth("No Gmail token found - please authenticate".to_string()),
+            TokenError::Reauthorize => GmailError::TokenExpired,
+            TokenError::Failed(e) => GmailError::Auth(e),
+        }
+    }
+}
+
+impl From<TokenError> for CalendarError {
+    fn from(e: TokenError) -> Self {
+        match e {
+            TokenError::NotConnected => CalendarError::NotConnected,
+            TokenError::Reauthorize => CalendarError::TokenExpired,
+            TokenError::Failed(e) => CalendarError::Auth(e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Health {
+    Disconnected,
+    Healthy,
+    /// Will be refreshed soon
+    Expiring,
+    /// Expired and not yet refreshed, e.g. while offline
+    Expired,
+    /// The provider rejected the refresh token
+    NeedsReauth,
+    /// The last refresh failed but the token may still work
+    Failing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatus {
+    pub provider: Provider,
+    pub name: String,
+    pub connected: bool,
+    pub health: Health,
+    pub expires_at: Option<u64>,
+    pub can_refresh: bool,
+    pub last_refreshed_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct RefreshRecord {
+    last_refreshed_at: Option<String>,
+    last_error: Option<String>,
+    needs_reauth: bool,
+}
+
+static RECORDS: Lazy<Mutex<HashMap<Provider, RefreshRecord>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static REFRESH_LOCKS: Lazy<HashMap<Provider, tokio::sync::Mutex<()>>> =
+    Lazy::new(|| Provider::ALL.into_iter().map(|p| (p, tokio::sync::Mutex::new(()))).collect());
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn record(provider: Provider, update: impl FnOnce(&mut RefreshRecord)) {
+    update(RECORDS.lock().unwrap_or_else(|e| e.into_inner()).entry(provider).or_default());
+}
+
+/// A token response, keeping the previous refresh token when none is returned
+/// (Google only sends one on consent; Microsoft rotates them)
+fn parse_token(data: &serde_json::Value, previous: &OAuthToken, now: u64) -> Result<OAuthToken, String> {
+    let access_token = data["access_token"].as_str().ok_or("No access token in refresh response")?;
+    Ok(OAuthToken {
+        access_token: access_token.to_string(),
+        refresh_token: data["refresh_token"].as_str().map(str::to_string).or_else(|| previous.refresh_token.clone()),
+        expires_at: data["expires_in"].as_u64().map(|secs| now + secs),
+        scope: data["scope"].as_str().map_or_else(|| previous.scope.clone(), str::to_string),
+        token_type: data["token_type"].as_str().unwrap_or("Bearer").to_string(),
+    })
+}
+
+/// Exchange the refresh token. A rejected refresh token clears the connection;
+/// anything else (offline, provider down) leaves it for the next attempt.
+async fn refresh(provider: Provider, token: &OAuthToken) -> Result<OAuthToken, TokenError> {
+    let refresh_token = token.refresh_token.as_deref().unwrap_or_default();
+    let endpoint = provider.endpoint().map_err(TokenError::Failed)?;
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", endpoint.client_id.as_str()),
+    ];
+    if let Some(secret) = &endpoint.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+
+    let response = Client::new()
+        .post(&endpoint.token_url)
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| TokenError::Failed(format!("Network error: {}", e)))?;
+    let status = response.status();
+    if matches!(status, StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED) {
+        let body = response.text().await.unwrap_or_default();
+        tracing::warn!("{} rejected the refresh token: {}", provider.name(), body.trim());
+        provider.clear();
+        return Err(TokenError::Reauthorize);
+    }
+    if !status.is_success() {
+        return Err(TokenError::Failed(format!("{} token refresh failed ({})", provider.name(), status)));
+    }
+    let data: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| TokenError::Failed(format!("Invalid token response: {}", e)))?;
+    let fresh = parse_token(&data, token, now()).map_err(TokenError::Failed)?;
+    provider.save(&fresh).map_err(TokenError::Failed)?;
+    Ok(fresh)
+}
+
+/// The stored token, refreshed first if it expires within `margin` seconds.
+/// A failed refresh still returns the old token while it has time left.
+async fn ensure_fresh(provider: Provider, margin: u64) -> Result<OAuthToken, TokenError> {
+    let _guard = REFRESH_LOCKS[&provider].lock().await;
+    // Read under the lock: a refresh that just finished has already stored its token
+    let token = provider.load().map_err(TokenError::Failed)?.ok_or(TokenError::NotConnected)?;
+    if !token.expires_within(now(), margin) {
+        return Ok(token);
+    }
+    if token.refresh_token.is_none() {
+        // Nothing to refresh with; usable until it expires
+        return if token.expires_within(now(), 0) { Err(TokenError::Reauthorize) } else { Ok(token) };
+    }
+    match refresh(provider, &token).await {
+        Ok(fresh) => {
+            record(provider, |r| {
+                *r = RefreshRecord { last_refreshed_at: Some(chrono::Utc::now().to_rfc3339()), ..Default::default() }
+            });
+            Ok(fresh)
+        }
+        Err(e) => {
+            record(provider, |r| {
+                r.needs_reauth = e == TokenError::Reauthorize;
+                r.last_error = Some(e.to_string());
+            });
+            match e {
+                TokenError::Failed(_) if !token.expires_within(now(), 0) => Ok(token),
+                e => Err(e),
+            }
+        }
+    }
+}
+
+/// A usable token for `provider`, refreshing it if it is about to expire
+pub async fn access_token(provider: Provider) -> Result<OAuthToken, TokenError> {
+    ensure_fresh(provider, USE_MARGIN_SECS).await
+}
+
+/// Refresh now, however long the token has left
+pub async fn refresh_now(provider: Provider) -> Result<OAuthToken, TokenError> {
+    ensure_fresh(provider, u64::MAX).await
+}
+
+/// Revoke the token with the provider (where it supports that) and forget it.
+/// Local credentials go first, under the refresh lock, so a refresh in flight
+/// can't store a new token afterwards.
+pub async fn revoke(provider: Provider) -> Result<(), String> {
+    let token = {
+        let _guard = REFRESH_LOCKS[&provider].lock().await;
+        let token = provider.load()?;
+        provider.clear();
+        token
+    };
+    record(provider, |r| *r = RefreshRecord::default());
+
+    let (Some(token), Ok(Endpoint { revoke_url: Some(url), .. })) = (token, provider.endpoint()) else {
+        return Ok(());
+    };
+    // Revoking the refresh token also revokes its access tokens
+    let secret = token.refresh_token.unwrap_or(token.access_token);
+    match Client::new().post(url).form(&[("token", secret)]).send().await {
+        Ok(response) if !response.status().is_success() => {
+            tracing::warn!("{} token revocation returned {}", provider.name(), response.status())
+        }
+        Err(e) => tracing::warn!("{} token revocation failed: {}", provider.name(), e),
+        Ok(_) => {}
+    }
+    Ok(())
+}
+
+fn health(token: Option<&OAuthToken>, record: &RefreshRecord, now: u64) -> Health {
+    match token {
+        None if record.needs_reauth => Health::NeedsReauth,
+        None => Health::Disconnected,
+        Some(token) if token.expires_within(now, 0) => {
+            if token.refresh_token.is_some() {
+                Health::Expired
+            } else {
+                Health::NeedsReauth
+            }
+        }
+        Some(_) if record.last_error.is_some() => Health::Failing,
+        Some(token) if token.expires_within(now, AHEAD_SECS + JITTER_SECS) => Health::Expiring,
+        Some(_) => Health::Healthy,
+    }
+}
+
+pub fn list_status() -> Vec<ConnectionStatus> {
+    let records = RECORDS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    Provider::ALL
+        .into_iter()
+        .map(|provider| {
+            let token = provider.load().ok().flatten();
+            let record = records.get(&provider).cloned().unwrap_or_default();
+            ConnectionStatus {
+                provider,
+                name: provider.name().to_string(),
+                connected: token.is_some(),
+                health: health(token.as_ref(), &record, now()),
+                expires_at: token.as_ref().and_then(|t| t.expires_at),
+                can_refresh: token.as_ref().is_some_and(|t| t.refresh_token.is_some()),
+                last_refreshed_at: record.last_refreshed_at,
+                last_error: record.last_error,
+            }
+        })
+        .collect()
+}
+
+/// Refresh tokens ahead of expiry for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let mut changed = false;
+            for provider in Provider::ALL {
+                let jitter = rand::thread_rng().gen_range(0..=JITTER_SECS);
+                match ensure_fresh(provider, AHEAD_SECS + jitter).await {
+                    Ok(_) | Err(TokenError::NotConnected) => {}
+                    Err(e) => {
+                        tracing::warn!("Background refresh of {} failed: {}", provider.name(), e);
+                        changed |= e == TokenError::Reauthorize;
+                    }
+                }
+            }
+            if changed {
+                if let Err(e) = app.emit(STATUS_EVENT, list_status()) {
+                    tracing::warn!("Failed to emit {}: {}", STATUS_EVENT, e);
+                }
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Connection health of every OAuth provider, for the settings page
+#[tauri::command]
+pub fn connections_list_status() -> Vec<ConnectionStatus> {
+    list_status()
+}
+
+#[tauri::command]
+pub async fn connections_revoke(provider: Provider) -> Result<Vec<ConnectionStatus>, String> {
+    revoke(provider).await?;
+    Ok(list_status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn token(expires_at: Option<u64>, refresh_token: Option<&str>) -> OAuthToken {
+        OAuthToken {
+            access_token: "old".to_string(),
+            refresh_token: refresh_token.map(str::to_string),
+            expires_at,
+            scope: "calendar".to_string(),
+            token_type: "Bearer".to_string(),
+        }
+    }
+
+    #[test]
+    fn refresh_responses_keep_the_refresh_token() {
+        let previous = token(Some(1_000), Some("r1"));
+        let fresh = parse_token(&json!({ "access_token": "new", "expires_in": 3600 }), &previous, 2_000).unwrap();
+        assert_eq!(fresh.access_token, "new");
+        assert_eq!(fresh.refresh_token.as_deref(), Some("r1"));
+        assert_eq!(fresh.expires_at, Some(5_600));
+        assert_eq!(fresh.scope, "calendar");
+
+        let rotated = parse_token(&json!({ "access_token": "new", "refresh_token": "r2" }), &previous, 2_000).unwrap();
+        assert_eq!(rotated.refresh_token.as_deref(), Some("r2"));
+        assert!(parse_token(&json!({ "error": "invalid_grant" }), &previous, 2_000).is_err());
+    }
+
+    #[test]
+    fn reports_health() {
+        let ok = RefreshRecord::default();
+        let now = 10_000;
+        assert_eq!(health(None, &ok, now), Health::Disconnected);
+        let rejected = RefreshRecord { needs_reauth: true, ..Default::default() };
+        assert_eq!(health(None, &rejected, now), Health::NeedsReauth);
+
+        assert_eq!(health(Some(&token(None, None)), &ok, now), Health::Healthy);
+        assert_eq!(health(Some(&token(Some(now + 7_200), Some("r"))), &ok, now), Health::Healthy);
+        assert_eq!(health(Some(&token(Some(now + 60), Some("r"))), &ok, now), Health::Expiring);
+        assert_eq!(health(Some(&token(Some(now - 1), Some("r"))), &ok, now), Health::Expired);
+        assert_eq!(health(Some(&token(Some(now - 1), None)), &ok, now), Health::NeedsReauth);
+
+        let failing = RefreshRecord { last_error: Some("offline".to_string()), ..Default::default() };
+        assert_eq!(health(Some(&token(Some(now + 60), Some("r"))), &failing, now), Health::Failing);
+    }
+}
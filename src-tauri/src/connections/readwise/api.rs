@@ -0,0 +1,124 @@
+//! The Readwise export API (v2).
+
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+const AUTH_URL: &str = "https://readwise.io/api/v2/auth/";
+const EXPORT_URL: &str = "https://readwise.io/api/v2/export/";
+const MAX_RETRIES: u32 = 4;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tag {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Highlight {
+    pub id: i64,
+    pub text: String,
+    pub note: Option<String>,
+    pub location: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    /// Discarded in Readwise's review; not imported
+    #[serde(default)]
+    pub is_discard: bool,
+}
+
+/// A source: book, article, tweet thread or podcast episode
+#[derive(Debug, Clone, Deserialize)]
+pub struct Book {
+    pub user_book_id: i64,
+    #[serde(default)]
+    pub title: String,
+    pub readable_title: Option<String>,
+    pub author: Option<String>,
+    /// `books`, `articles`, `tweets`, `podcasts` or `supplementals`
+    pub category: Option<String>,
+    pub source_url: Option<String>,
+    pub unique_url: Option<String>,
+    pub cover_image_url: Option<String>,
+    pub document_note: Option<String>,
+    #[serde(default)]
+    pub book_tags: Vec<Tag>,
+    #[serde(default)]
+    pub highlights: Vec<Highlight>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportPage {
+    #[serde(default)]
+    results: Vec<Book>,
+    #[serde(rename = "nextPageCursor")]
+    next_page_cursor: Option<Value>,
+}
+
+/// GET with the token, waiting out rate limits as Readwise asks
+async fn get(client: &Client, token: &str, url: &str, query: &[(&str, String)]) -> Result<reqwest::Response, String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let response = client
+            .get(url)
+            .header(reqwest::header::AUTHORIZATION, format!("Token {}", token))
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map_or(Duration::from_secs(2u64.pow(attempt)), Duration::from_secs);
+            tracing::warn!("Readwise rate limit reached; retrying in {}s", retry_after.as_secs());
+            tokio::time::sleep(retry_after).await;
+            continue;
+        }
+        return match status {
+            status if status.is_success() => Ok(response),
+            StatusCode::UNAUTHORIZED => Err("Readwise rejected the access token".to_string()),
+            status => {
+                let text = response.text().await.unwrap_or_default();
+                Err(format!("Readwise request failed ({}): {}", status, text.trim()))
+            }
+        };
+    }
+}
+
+/// Check a token before it is saved
+pub async fn validate_token(token: &str) -> Result<(), String> {
+    get(&Client::new(), token, AUTH_URL, &[]).await.map(|_| ())
+}
+
+/// One page of sources with their highlights, changed after `updated_after`
+/// (ISO 8601) if given; returns the cursor of the next page
+pub async fn export_page(
+    client: &Client,
+    token: &str,
+    updated_after: Option<&str>,
+    cursor: Option<&str>,
+) -> Result<(Vec<Book>, Option<String>), String> {
+    let mut query = Vec::new();
+    if let Some(updated_after) = updated_after {
+        query.push(("updatedAfter", updated_after.to_string()));
+    }
+    if let Some(cursor) = cursor {
+        query.push(("pageCursor", cursor.to_string()));
+    }
+    let page: ExportPage = get(client, token, EXPORT_URL, &query)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Readwise response: {}", e))?;
+    let next = match page.next_page_cursor {
+        Some(Value::String(cursor)) => Some(cursor),
+        Some(Value::Number(cursor)) => Some(cursor.to_string()),
+        _ => None,
+    };
+    Ok((page.results, next))
+}
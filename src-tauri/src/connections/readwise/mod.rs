@@ -0,0 +1,481 @@
+//! Readwise highlights.
+//!
+//! Each source (book, article, tweet thread, podcast) becomes one note in
+//! `<folder>/<Category>/`, rendered from a workspace template when one is
+//! configured (the source is available as `{{title}}`, `{{author}}`,
+//! `{{category}}`, `{{url}}`, `{{cover}}`, `{{document_note}}` and
+//! `{{highlights}}`) or a built-in layout otherwise. Later syncs append new
+//! highlights to the same note, so edits made around them are kept.
+//!
+//! Syncs are incremental: only sources changed since the last successful sync
+//! are fetched. Every highlight carries a `^rw-<id>` block id, and the ids
+//! already imported are also recorded in `.lokus/readwise.json`, so re-running
+//! a sync (even a full one, or after the state file is lost) never duplicates
+//! a highlight. The access token is kept in secure storage.
+
+mod api;
+
+use api::{Book, Highlight};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+const TOKEN_KEY: &str = "readwise-token";
+const STATE_FILE: &str = "readwise.json";
+const DEFAULT_FOLDER: &str = "Readwise";
+const PROGRESS_EVENT: &str = "readwise-sync-progress";
+const STATUS_EVENT: &str = "readwise-sync-status";
+const MARKER_PREFIX: &str = "^rw-";
+
+const DEFAULT_TEMPLATE: &str = "# {{title}}\n\n## Highlights\n\n{{highlights}}\n";
+
+/// `None` until loaded from secure storage, which is slow
+static TOKEN: Lazy<Mutex<Option<Option<String>>>> = Lazy::new(|| Mutex::new(None));
+/// Workspaces with a sync running
+static SYNCING: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SyncedBook {
+    /// Workspace-relative note path
+    path: String,
+    highlight_ids: Vec<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ReadwiseState {
+    folder: Option<String>,
+    template: Option<String>,
+    /// Start of the last successful sync; the next one asks for changes since
+    last_synced_at: Option<String>,
+    last_error: Option<String>,
+    /// Keyed by Readwise's user_book_id
+    books: HashMap<String, SyncedBook>,
+}
+
+impl ReadwiseState {
+    fn folder(&self) -> &str {
+        self.folder.as_deref().unwrap_or(DEFAULT_FOLDER)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadwiseStatus {
+    pub connected: bool,
+    pub folder: String,
+    pub template: Option<String>,
+    pub last_synced_at: Option<String>,
+    pub last_error: Option<String>,
+    pub books: usize,
+    pub highlights: usize,
+    pub syncing: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    pub workspace: String,
+    /// Pages of sources fetched so far
+    pub pages: usize,
+    pub books: usize,
+    pub highlights_added: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub highlights_added: usize,
+}
+
+fn token() -> Option<String> {
+    TOKEN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| {
+            crate::secure_storage::SecureStorage::new()
+                .ok()
+                .and_then(|storage| storage.retrieve::<String>(TOKEN_KEY).ok().flatten())
+        })
+        .clone()
+}
+
+fn state_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(STATE_FILE)
+}
+
+fn load(root: &Path) -> ReadwiseState {
+    fs::read_to_string(state_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(root: &Path, state: &ReadwiseState) -> Result<(), String> {
+    let path = state_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save Readwise state: {}", e))
+}
+
+fn status(root: &Path) -> ReadwiseStatus {
+    let state = load(root);
+    ReadwiseStatus {
+        connected: token().is_some(),
+        folder: state.folder().to_string(),
+        template: state.template.clone(),
+        last_synced_at: state.last_synced_at.clone(),
+        last_error: state.last_error.clone(),
+        books: state.books.len(),
+        highlights: state.books.values().map(|b| b.highlight_ids.len()).sum(),
+        syncing: SYNCING.lock().unwrap_or_else(|e| e.into_inner()).contains(root),
+    }
+}
+
+// --- Rendering ---
+
+fn marker(id: i64) -> String {
+    format!("{}{}", MARKER_PREFIX, id)
+}
+
+/// Ids of the highlights already in a note, from their block ids
+fn marked_ids(content: &str) -> HashSet<i64> {
+    content
+        .lines()
+        .filter_map(|line| line.split_whitespace().last()?.strip_prefix(MARKER_PREFIX)?.parse().ok())
+        .collect()
+}
+
+/// Highlights of `book` that aren't in the note yet
+fn new_highlights<'a>(book: &'a Book, known: &HashSet<i64>) -> Vec<&'a Highlight> {
+    let mut highlights: Vec<&Highlight> =
+        book.highlights.iter().filter(|h| !h.is_discard && !known.contains(&h.id)).collect();
+    // Reading order, so appended highlights follow the ones before them
+    highlights.sort_by_key(|h| (h.location.unwrap_or(i64::MAX), h.id));
+    highlights
+}
+
+fn tag(name: &str) -> String {
+    format!("#{}", name.trim().replace(' ', "-"))
+}
+
+fn render_highlight(highlight: &Highlight) -> String {
+    let mut out = highlight
+        .text
+        .trim()
+        .lines()
+        .map(|line| format!("> {}", line).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.push_str(&format!(" {}", marker(highlight.id)));
+    if let Some(note) = highlight.note.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        out.push_str(&format!("\n\n**Note:** {}", note));
+    }
+    if !highlight.tags.is_empty() {
+        let tags: Vec<String> = highlight.tags.iter().map(|t| tag(&t.name)).collect();
+        out.push_str(&format!("\n\n{}", tags.join(" ")));
+    }
+    out
+}
+
+fn render_highlights(highlights: &[&Highlight]) -> String {
+    highlights.iter().map(|h| render_highlight(h)).collect::<Vec<_>>().join("\n\n")
+}
+
+fn title(book: &Book) -> String {
+    book.readable_title.clone().filter(|t| !t.trim().is_empty()).unwrap_or_else(|| book.title.clone())
+}
+
+fn url(book: &Book) -> Option<String> {
+    book.source_url.clone().or_else(|| book.unique_url.clone())
+}
+
+fn render_book(root: &Path, template: Option<&str>, book: &Book, highlights: &[&Highlight]) -> Result<String, String> {
+    let vars = HashMap::from([
+        ("author".to_string(), book.author.clone().unwrap_or_default()),
+        ("category".to_string(), book.category.clone().unwrap_or_default()),
+        ("url".to_string(), url(book).unwrap_or_default()),
+        ("cover".to_string(), book.cover_image_url.clone().unwrap_or_default()),
+        ("document_note".to_string(), book.document_note.clone().unwrap_or_default()),
+        ("highlights".to_string(), render_highlights(highlights)),
+    ]);
+    let ctx = crate::templates::RenderContext::new(Some(title(book)), vars);
+    let rendered = match template {
+        Some(name) => crate::templates::render_named(root, name, &ctx)?,
+        None => crate::templates::render(DEFAULT_TEMPLATE, &ctx),
+    };
+
+    let mut note = rendered.content;
+    let tags: Vec<String> = book.book_tags.iter().map(|t| t.name.trim().replace(' ', "-")).collect();
+    let fields = [
+        ("source", json!("readwise")),
+        ("readwise_id", json!(book.user_book_id)),
+        ("author", book.author.as_ref().map_or(serde_json::Value::Null, |a| json!(a))),
+        ("category", book.category.as_ref().map_or(serde_json::Value::Null, |c| json!(c))),
+        ("url", url(book).map_or(serde_json::Value::Null, |u| json!(u))),
+        ("tags", if tags.is_empty() { serde_json::Value::Null } else { json!(tags) }),
+    ];
+    for (key, value) in fields {
+        note = crate::frontmatter::set_field(&note, key, &value)?;
+    }
+    Ok(note)
+}
+
+/// `Books`, `Articles`, ...
+fn category_folder(book: &Book) -> String {
+    let category = book.category.as_deref().unwrap_or("books");
+    let mut chars = category.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+// --- Syncing ---
+
+/// The note a source was imported to: the recorded path, or, if that was
+/// moved or the state lost, whichever note has its `readwise_id`
+fn existing_note(root: &Path, state: &ReadwiseState, book: &Book) -> Option<PathBuf> {
+    if let Some(synced) = state.books.get(&book.user_book_id.to_string()) {
+        let path = root.join(&synced.path);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    let filter = crate::metadata_cache::MetadataFilter {
+        field: Some("readwise_id".to_string()),
+        value: Some(json!(book.user_book_id)),
+        limit: Some(1),
+        ..Default::default()
+    };
+    crate::metadata_cache::query(root, &filter)
+        .ok()?
+        .into_iter()
+        .next()
+        .map(|note| PathBuf::from(note.path))
+}
+
+/// Write a source's new highlights; returns the note and how many were added
+fn import_book(root: &Path, state: &mut ReadwiseState, book: &Book) -> Result<Option<(String, bool, usize)>, String> {
+    let key = book.user_book_id.to_string();
+    let existing = existing_note(root, state, book);
+    let content = existing.as_ref().and_then(|path| fs::read_to_string(path).ok());
+    let mut known: HashSet<i64> = content.as_deref().map(marked_ids).unwrap_or_default();
+    if existing.is_some() {
+        known.extend(state.books.get(&key).map(|b| b.highlight_ids.clone()).unwrap_or_default());
+    }
+    let highlights = new_highlights(book, &known);
+    if highlights.is_empty() {
+        return Ok(None);
+    }
+
+    let (path, created) = match (existing, content) {
+        (Some(path), Some(content)) => {
+            let note = format!("{}\n\n{}\n", content.trim_end(), render_highlights(&highlights));
+            crate::handlers::files::write_file_content(path.to_string_lossy().to_string(), note)?;
+            (path, false)
+        }
+        _ => {
+            let folder = root.join(state.folder()).join(category_folder(book));
+            fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+            let path = crate::templates::unused_note_path(&folder, &title(book));
+            let note = render_book(root, state.template.as_deref(), book, &highlights)?;
+            crate::handlers::files::write_file_content(path.to_string_lossy().to_string(), note)?;
+            (path, true)
+        }
+    };
+
+    let synced = state.books.entry(key).or_default();
+    synced.path = crate::links::relative_path(root, &path).unwrap_or_else(|| path.to_string_lossy().to_string());
+    synced.highlight_ids.extend(highlights.iter().map(|h| h.id));
+    Ok(Some((path.to_string_lossy().to_string(), created, highlights.len())))
+}
+
+async fn sync(app: &AppHandle, root: &Path, token: &str, full: bool) -> Result<SyncResult, String> {
+    let started = Utc::now().to_rfc3339();
+    let mut state = load(root);
+    let updated_after = if full { None } else { state.last_synced_at.clone() };
+    let client = reqwest::Client::new();
+
+    let mut result = SyncResult::default();
+    let mut progress = SyncProgress {
+        workspace: root.to_string_lossy().to_string(),
+        pages: 0,
+        books: 0,
+        highlights_added: 0,
+    };
+    let mut cursor: Option<String> = None;
+    loop {
+        let (books, next) = api::export_page(&client, token, updated_after.as_deref(), cursor.as_deref()).await?;
+        for book in &books {
+            match import_book(root, &mut state, book) {
+                Ok(Some((path, created, added))) => {
+                    result.highlights_added += added;
+                    if created {
+                        result.created.push(path);
+                    } else if !result.updated.contains(&path) {
+                        result.updated.push(path);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to import Readwise source '{}': {}", book.title, e),
+            }
+        }
+        // Kept after every page, so an interrupted sync doesn't re-add what it wrote
+        save(root, &state)?;
+
+        progress.pages += 1;
+        progress.books += books.len();
+        progress.highlights_added = result.highlights_added;
+        if let Err(e) = app.emit(PROGRESS_EVENT, &progress) {
+            tracing::warn!("Failed to emit {}: {}", PROGRESS_EVENT, e);
+        }
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    state.last_synced_at = Some(started);
+    state.last_error = None;
+    save(root, &state)?;
+    Ok(result)
+}
+
+// --- Tauri Commands ---
+
+/// Check and save the access token (from readwise.io/access_token), and
+/// optionally where and how sources are written
+#[tauri::command]
+pub async fn readwise_configure(
+    workspace_path: String,
+    token: String,
+    folder: Option<String>,
+    template: Option<String>,
+) -> Result<ReadwiseStatus, String> {
+    let root = PathBuf::from(&workspace_path);
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err("A Readwise access token is required".to_string());
+    }
+    api::validate_token(&token).await?;
+
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.store(TOKEN_KEY, &token).map_err(|e| format!("Failed to save Readwise token: {}", e))?;
+    *TOKEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(Some(token));
+
+    let mut state = load(&root);
+    if let Some(folder) = folder {
+        state.folder = Some(crate::feeds::safe_folder(&folder)?);
+    }
+    if let Some(template) = template {
+        state.template = Some(template).filter(|t| !t.trim().is_empty());
+    }
+    save(&root, &state)?;
+    Ok(status(&root))
+}
+
+#[tauri::command]
+pub fn readwise_status(workspace_path: String) -> ReadwiseStatus {
+    status(Path::new(&workspace_path))
+}
+
+/// Import highlights changed since the last sync, or everything with `full`.
+/// Emits `readwise-sync-progress` after each page and `readwise-sync-status`
+/// when done.
+#[tauri::command]
+pub async fn readwise_sync(app: AppHandle, workspace_path: String, full: Option<bool>) -> Result<SyncResult, String> {
+    let root = PathBuf::from(&workspace_path);
+    let token = token().ok_or_else(|| "Readwise is not connected".to_string())?;
+    if !SYNCING.lock().unwrap_or_else(|e| e.into_inner()).insert(root.clone()) {
+        return Err("A Readwise sync is already running".to_string());
+    }
+
+    let result = sync(&app, &root, &token, full.unwrap_or(false)).await;
+    SYNCING.lock().unwrap_or_else(|e| e.into_inner()).remove(&root);
+    if let Err(e) = &result {
+        let mut state = load(&root);
+        state.last_error = Some(e.clone());
+        let _ = save(&root, &state);
+    }
+    if let Err(e) = app.emit(STATUS_EVENT, status(&root)) {
+        tracing::warn!("Failed to emit {}: {}", STATUS_EVENT, e);
+    }
+    result
+}
+
+/// Forget the token; imported notes are kept
+#[tauri::command]
+pub fn readwise_disconnect() -> Result<(), String> {
+    let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+    storage.delete(TOKEN_KEY).map_err(|e| format!("Failed to remove Readwise token: {}", e))?;
+    *TOKEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlight(id: i64, text: &str, location: i64) -> Highlight {
+        Highlight {
+            id,
+            text: text.to_string(),
+            note: None,
+            location: Some(location),
+            tags: Vec::new(),
+            is_discard: false,
+        }
+    }
+
+    fn book(highlights: Vec<Highlight>) -> Book {
+        Book {
+            user_book_id: 42,
+            title: "Deep Work".to_string(),
+            readable_title: None,
+            author: Some("Cal Newport".to_string()),
+            category: Some("books".to_string()),
+            source_url: None,
+            unique_url: None,
+            cover_image_url: None,
+            document_note: None,
+            book_tags: vec![api::Tag { name: "focus work".to_string() }],
+            highlights,
+        }
+    }
+
+    #[test]
+    fn test_render_book_with_markers_and_frontmatter() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = highlight(2, "Clarity about what matters\nprovides clarity.", 20);
+        first.note = Some("Use for the talk".to_string());
+        let book = book(vec![first, highlight(1, "Deep work is rare.", 10)]);
+        let highlights = new_highlights(&book, &HashSet::new());
+        let note = render_book(dir.path(), None, &book, &highlights).unwrap();
+
+        let frontmatter = crate::frontmatter::parse(&note).unwrap();
+        assert_eq!(frontmatter["readwise_id"], 42);
+        assert_eq!(frontmatter["tags"][0], "focus-work");
+        assert!(note.contains("# Deep Work\n\n## Highlights\n\n> Deep work is rare. ^rw-1\n\n> Clarity about what matters\n> provides clarity. ^rw-2\n\n**Note:** Use for the talk"));
+        assert_eq!(marked_ids(&note), HashSet::from([1, 2]));
+        assert_eq!(category_folder(&book), "Books");
+    }
+
+    #[test]
+    fn test_only_new_highlights_are_imported() {
+        let mut discarded = highlight(4, "Skip me", 40);
+        discarded.is_discard = true;
+        let book = book(vec![highlight(1, "Old", 10), highlight(3, "New", 30), discarded]);
+        let known = marked_ids("> Old ^rw-1\n");
+        let fresh: Vec<i64> = new_highlights(&book, &known).iter().map(|h| h.id).collect();
+        assert_eq!(fresh, vec![3]);
+    }
+}
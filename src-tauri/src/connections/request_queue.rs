@@ -0,0 +1,380 @@
+//! Rate-limited, retrying HTTP requests shared by the connection clients.
+//!
+//! Every request names its provider (`google-calendar`, `caldav`, ...) and
+//! goes through that provider's queue:
+//!
+//! - a token bucket paces requests and a semaphore caps how many are in
+//!   flight, so a bulk sync can't burst past the provider's quota;
+//! - 429s pause the whole provider for `Retry-After` (or an exponential
+//!   backoff), since the limit is per account, not per request; 5xx responses
+//!   are retried with backoff and jitter;
+//! - after a network error the provider is marked offline. Later requests
+//!   wait for it to come back, letting one request through at a time as a
+//!   probe, and fail with a network error after `OFFLINE_WAIT` so callers with
+//!   persistent queues (calendar changes, Gmail sends) can take over.
+//!
+//! A response that is still 429 or 5xx after the last retry is returned as-is,
+//! like any other status, for the caller to report.
+
+use once_cell::sync::Lazy;
+use rand::Rng;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+const MAX_RETRIES: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// How long a request waits for an offline provider before giving up
+const OFFLINE_WAIT: Duration = Duration::from_secs(20);
+/// Between probes of an offline provider
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+static QUEUES: Lazy<Mutex<HashMap<String, Arc<Queue>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    /// Sustained requests per second
+    rate: f64,
+    /// Requests that may go out back to back
+    burst: f64,
+    max_concurrent: usize,
+}
+
+/// Limits kept well under each provider's documented quota
+fn limits(provider: &str) -> Limits {
+    match provider {
+        // 10 queries/second/user before Google starts answering 403/429
+        "google-calendar" => Limits { rate: 5.0, burst: 10.0, max_concurrent: 4 },
+        // iCloud and Fastmail throttle aggressively and don't say so
+        "caldav" => Limits { rate: 2.0, burst: 4.0, max_concurrent: 2 },
+        _ => Limits { rate: 5.0, burst: 5.0, max_concurrent: 4 },
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limits: &Limits, now: Instant) -> Self {
+        Self { tokens: limits.burst, updated: now }
+    }
+
+    /// Take a token, or say how long until one is available
+    fn take(&mut self, limits: &Limits, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limits.rate).min(limits.burst);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / limits.rate))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStats {
+    pub provider: String,
+    /// Waiting for a rate-limit token, a free slot or the provider to come back
+    pub queued: usize,
+    pub in_flight: usize,
+    pub sent: u64,
+    pub succeeded: u64,
+    /// Requests that ended with an error status or no response at all
+    pub failed: u64,
+    pub retried: u64,
+    pub rate_limited: u64,
+    pub server_errors: u64,
+    pub network_errors: u64,
+    pub average_latency_ms: u64,
+    pub offline: bool,
+    /// When the provider's rate-limit pause ends (RFC 3339)
+    pub paused_until: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    stats: QueueStats,
+    total_latency_ms: u64,
+    paused_until: Option<Instant>,
+    offline_since: Option<Instant>,
+    next_probe: Option<Instant>,
+}
+
+struct Queue {
+    limits: Limits,
+    slots: Semaphore,
+    bucket: Mutex<Bucket>,
+    state: Mutex<State>,
+}
+
+impl Queue {
+    fn new(provider: &str) -> Self {
+        let limits = limits(provider);
+        let state = State { stats: QueueStats { provider: provider.to_string(), ..Default::default() }, ..Default::default() };
+        Self {
+            limits,
+            slots: Semaphore::new(limits.max_concurrent),
+            bucket: Mutex::new(Bucket::new(&limits, Instant::now())),
+            state: Mutex::new(state),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait out pauses, offline spells and the token bucket
+    async fn wait_turn(&self, provider: &str, deadline: Instant) -> Result<(), String> {
+        loop {
+            let now = Instant::now();
+            let (wait, offline) = {
+                let mut state = self.state();
+                let wait = match (state.offline_since, state.next_probe) {
+                    (Some(_), Some(probe)) if probe > now => Some(probe - now),
+                    (Some(_), _) => {
+                        // This request is the probe; the rest keep waiting
+                        state.next_probe = Some(now + PROBE_INTERVAL);
+                        None
+                    }
+                    _ => state.paused_until.filter(|until| *until > now).map(|until| until - now),
+                };
+                (wait, state.offline_since.is_some())
+            };
+            let wait = match wait {
+                Some(_) if offline && now >= deadline => return Err(format!("{} is unreachable", provider)),
+                Some(wait) if offline => wait.min(deadline - now),
+                Some(wait) => wait,
+                None => match self.bucket.lock().unwrap_or_else(|e| e.into_inner()).take(&self.limits, now) {
+                    None => return Ok(()),
+                    Some(wait) => wait,
+                },
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn started(&self) {
+        let mut state = self.state();
+        state.stats.queued -= 1;
+        state.stats.in_flight += 1;
+        state.stats.sent += 1;
+    }
+
+    fn finished(&self) {
+        let mut state = self.state();
+        state.stats.queued += 1;
+        state.stats.in_flight -= 1;
+    }
+
+    /// Mark the provider offline; whether to try again
+    fn network_error(&self, attempt: u32, deadline: Instant) -> bool {
+        let mut state = self.state();
+        let now = Instant::now();
+        state.stats.network_errors += 1;
+        state.stats.offline = true;
+        state.offline_since.get_or_insert(now);
+        if attempt > MAX_RETRIES || now >= deadline {
+            return false;
+        }
+        state.next_probe = Some(now + backoff(attempt, rand::thread_rng().gen()).min(PROBE_INTERVAL));
+        state.stats.retried += 1;
+        true
+    }
+
+    /// Record a response; how long to wait before retrying it, if at all
+    fn response(&self, provider: &str, response: &Response, attempt: u32, latency: Duration) -> Option<Duration> {
+        let mut state = self.state();
+        if state.offline_since.take().is_some() {
+            tracing::info!("{} is reachable again", provider);
+        }
+        state.next_probe = None;
+        state.stats.offline = false;
+        state.total_latency_ms += latency.as_millis() as u64;
+        state.stats.average_latency_ms = state.total_latency_ms / state.stats.sent.max(1);
+
+        let status = response.status();
+        if !retryable(status) {
+            return None;
+        }
+        let wait = retry_after(response).unwrap_or_else(|| backoff(attempt, rand::thread_rng().gen()));
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            state.stats.rate_limited += 1;
+            // The quota is the account's, so every queued request waits
+            let until = Instant::now() + wait;
+            state.paused_until = Some(state.paused_until.map_or(until, |current| current.max(until)));
+            state.stats.paused_until = Some((chrono::Utc::now() + wait).to_rfc3339());
+        } else {
+            state.stats.server_errors += 1;
+        }
+        if attempt > MAX_RETRIES || wait > MAX_BACKOFF {
+            return None;
+        }
+        state.stats.retried += 1;
+        tracing::warn!("{} answered {}; retrying in {}ms", provider, status, wait.as_millis());
+        // A 429 is waited out in `wait_turn` through the pause
+        Some(if status == StatusCode::TOO_MANY_REQUESTS { Duration::ZERO } else { wait })
+    }
+
+    fn done(&self, result: &Result<Response, String>) {
+        let mut state = self.state();
+        state.stats.queued -= 1;
+        match result {
+            Ok(response) if response.status().is_success() || response.status().is_redirection() => state.stats.succeeded += 1,
+            Ok(response) => {
+                state.stats.failed += 1;
+                state.stats.last_error = Some(format!("HTTP {}", response.status()));
+            }
+            Err(e) => {
+                state.stats.failed += 1;
+                state.stats.last_error = Some(e.clone());
+            }
+        }
+    }
+}
+
+fn queue(provider: &str) -> Arc<Queue> {
+    QUEUES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(Queue::new(provider)))
+        .clone()
+}
+
+/// `Retry-After` in seconds (HTTP dates are rare enough to fall back to backoff)
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Exponential backoff for the given retry (1-based), with up to 25% jitter
+fn backoff(attempt: u32, jitter: f64) -> Duration {
+    let base = BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))).min(MAX_BACKOFF);
+    base.mul_f64(1.0 + jitter.clamp(0.0, 1.0) * 0.25)
+}
+
+fn retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Send a request through `provider`'s queue. `request` builds the request
+/// afresh for each attempt. Only failures to get any response are errors.
+pub async fn send(provider: &str, request: impl Fn() -> RequestBuilder) -> Result<Response, String> {
+    let queue = queue(provider);
+    queue.state().stats.queued += 1;
+    let deadline = Instant::now() + OFFLINE_WAIT;
+    let result = send_queued(provider, &queue, request, deadline).await;
+    queue.done(&result);
+    result
+}
+
+async fn send_queued(
+    provider: &str,
+    queue: &Queue,
+    request: impl Fn() -> RequestBuilder,
+    deadline: Instant,
+) -> Result<Response, String> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        queue.wait_turn(provider, deadline).await?;
+        let slot = queue.slots.acquire().await.map_err(|e| e.to_string())?;
+        queue.started();
+        let started = Instant::now();
+        let result = request().send().await;
+        drop(slot);
+        queue.finished();
+
+        match result {
+            Err(e) => {
+                if !queue.network_error(attempt, deadline) {
+                    return Err(format!("Network error: {}", e));
+                }
+            }
+            Ok(response) => match queue.response(provider, &response, attempt, started.elapsed()) {
+                None => return Ok(response),
+                Some(wait) => tokio::time::sleep(wait).await,
+            },
+        }
+    }
+}
+
+pub fn stats(provider: Option<&str>) -> Vec<QueueStats> {
+    let queues: Vec<Arc<Queue>> = match provider {
+        Some(provider) => vec![queue(provider)],
+        None => QUEUES.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect(),
+    };
+    let now = Instant::now();
+    let mut stats: Vec<QueueStats> = queues
+        .iter()
+        .map(|queue| {
+            let state = queue.state();
+            let mut stats = state.stats.clone();
+            if !state.paused_until.is_some_and(|until| until > now) {
+                stats.paused_until = None;
+            }
+            stats
+        })
+        .collect();
+    stats.sort_by(|a, b| a.provider.cmp(&b.provider));
+    stats
+}
+
+// --- Tauri Commands ---
+
+/// Queue metrics for one provider, or every provider used so far
+#[tauri::command]
+pub fn connections_get_queue_stats(provider: Option<String>) -> Vec<QueueStats> {
+    stats(provider.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_paces_after_the_burst() {
+        let limits = Limits { rate: 2.0, burst: 2.0, max_concurrent: 1 };
+        let start = Instant::now();
+        let mut bucket = Bucket::new(&limits, start);
+        assert_eq!(bucket.take(&limits, start), None);
+        assert_eq!(bucket.take(&limits, start), None);
+        let wait = bucket.take(&limits, start).unwrap();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert_eq!(bucket.take(&limits, start + wait), None);
+        // Idle time refills only up to the burst
+        let later = start + Duration::from_secs(60);
+        assert_eq!(bucket.take(&limits, later), None);
+        assert_eq!(bucket.take(&limits, later), None);
+        assert!(bucket.take(&limits, later).is_some());
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(backoff(1, 0.0), Duration::from_millis(500));
+        assert_eq!(backoff(3, 0.0), Duration::from_secs(2));
+        assert_eq!(backoff(3, 1.0), Duration::from_millis(2500));
+        assert_eq!(backoff(20, 0.0), MAX_BACKOFF);
+        assert!(retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(retryable(StatusCode::BAD_GATEWAY));
+        assert!(!retryable(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,282 @@
+//! Daily notes.
+//!
+//! One note per day at `<workspace>/<folder>/<date formatted with format>.md`,
+//! using the `dailyNotes` section of the global config the settings screen
+//! writes. New notes are rendered through the template engine and can have the
+//! day's calendar events embedded, either where the template puts `{{events}}`
+//! or as a section at the end.
+
+#[cfg(desktop)]
+use crate::calendar::models::CalendarEvent;
+use crate::templates::{self, RenderContext};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+#[cfg(desktop)]
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Longest range `daily_note_range` will scan
+const MAX_RANGE_DAYS: i64 = 3660;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DailyNotesConfig {
+    /// Folder relative to the workspace root
+    pub folder: String,
+    /// date-fns style file name format, e.g. `yyyy-MM-dd` or `yyyy/MM/dd`
+    pub format: String,
+    /// Name of a template in `.lokus/templates`, or the template text itself
+    pub template: Option<String>,
+    /// Add the day's calendar events to newly created notes
+    pub embed_events: bool,
+}
+
+impl Default for DailyNotesConfig {
+    fn default() -> Self {
+        Self {
+            folder: "Daily Notes".to_string(),
+            format: "yyyy-MM-dd".to_string(),
+            template: None,
+            embed_events: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyNote {
+    pub date: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedDailyNote {
+    pub date: String,
+    pub path: String,
+    /// False if the note already existed
+    pub created: bool,
+    /// Cursor offset from the template, for newly created notes
+    pub cursor: Option<usize>,
+}
+
+/// Read the `dailyNotes` section of `<app data>/Lokus/config.json`
+pub(crate) fn load_config(app: &AppHandle) -> DailyNotesConfig {
+    match app.path().app_data_dir() {
+        Ok(dir) => config_in(&dir),
+        Err(_) => DailyNotesConfig::default(),
+    }
+}
+
+/// The daily notes config stored under an app data directory
+pub(crate) fn config_in(app_data_dir: &Path) -> DailyNotesConfig {
+    fs::read_to_string(app_data_dir.join("Lokus").join("config.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|config| config.get("dailyNotes").cloned())
+        .and_then(|section| serde_json::from_value(section).ok())
+        .unwrap_or_default()
+}
+
+fn parse_date(date: Option<&str>) -> Result<NaiveDate, String> {
+    match date {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", date, e)),
+        None => Ok(Local::now().date_naive()),
+    }
+}
+
+pub fn note_path(root: &Path, config: &DailyNotesConfig, date: NaiveDate) -> PathBuf {
+    let format = templates::convert_date_format(&config.format, date);
+    let mut name = date.format(&format).to_string();
+    if name.trim().is_empty() {
+        name = date.format("%Y-%m-%d").to_string();
+    }
+    root.join(&config.folder).join(format!("{}.md", name))
+}
+
+/// An event as a markdown list item
+#[cfg(desktop)]
+fn format_event(event: &CalendarEvent) -> String {
+    let when = if event.all_day {
+        "All day".to_string()
+    } else {
+        format!(
+            "{}–{}",
+            event.start.with_timezone(&Local).format("%H:%M"),
+            event.end.with_timezone(&Local).format("%H:%M")
+        )
+    };
+    match &event.location {
+        Some(location) if !location.is_empty() => format!("- {} {} ({})", when, event.title, location),
+        _ => format!("- {} {}", when, event.title),
+    }
+}
+
+/// Events overlapping `date` from every visible calendar, as list items.
+/// Calendar errors only mean the note is created without events.
+#[cfg(desktop)]
+async fn events_for(date: NaiveDate) -> Vec<String> {
+    let start = Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid")));
+    let end: DateTime<Utc> = start + Duration::days(1);
+    match crate::calendar::get_all_events(start.to_rfc3339(), end.to_rfc3339()).await {
+        Ok(events) => events.iter().filter(|e| e.end > start && e.start < end).map(format_event).collect(),
+        Err(e) => {
+            tracing::warn!("Could not load events for daily note {}: {}", date, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Calendars are only available on desktop
+#[cfg(not(desktop))]
+async fn events_for(_date: NaiveDate) -> Vec<String> {
+    Vec::new()
+}
+
+/// Content for a new daily note
+pub fn build_content(root: &Path, config: &DailyNotesConfig, date: NaiveDate, events: Option<&[String]>) -> (String, Option<usize>) {
+    let title = date.format(&templates::convert_date_format(&config.format, date)).to_string();
+    let events_md = events.filter(|e| !e.is_empty()).map(|lines| lines.join("\n"));
+
+    // Variables the settings screen documents for daily note templates
+    let mut vars = HashMap::new();
+    let day_format = |d: NaiveDate, f: &str| d.format(f).to_string();
+    vars.insert("yesterday".to_string(), day_format(date - Duration::days(1), "%Y-%m-%d"));
+    vars.insert("tomorrow".to_string(), day_format(date + Duration::days(1), "%Y-%m-%d"));
+    vars.insert("day_name".to_string(), day_format(date, "%A"));
+    vars.insert("day".to_string(), day_format(date, "%A"));
+    vars.insert("day_short".to_string(), day_format(date, "%a"));
+    vars.insert("month_name".to_string(), day_format(date, "%B"));
+    vars.insert("month".to_string(), day_format(date, "%B"));
+    vars.insert("month_short".to_string(), day_format(date, "%b"));
+    vars.insert("week_number".to_string(), date.iso_week().week().to_string());
+    vars.insert("week".to_string(), date.iso_week().week().to_string());
+    vars.insert("year".to_string(), date.year().to_string());
+    vars.insert("events".to_string(), events_md.clone().unwrap_or_default());
+
+    let ctx = RenderContext::for_date(date, Some(title.clone()), vars);
+    let source = match config.template.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        Some(template) => templates::template_body(root, template).unwrap_or_else(|| template.to_string()),
+        None => format!("# {} - {}\n\n", title, date.format("%A")),
+    };
+    let uses_placeholder = source.contains("{{events}}");
+    let rendered = templates::render(&source, &ctx);
+
+    let mut content = rendered.content;
+    if let (Some(events_md), false) = (events_md, uses_placeholder) {
+        if !content.is_empty() && !content.ends_with("\n\n") {
+            content.push_str(if content.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        content.push_str("## Events\n\n");
+        content.push_str(&events_md);
+        content.push('\n');
+    }
+    (content, rendered.cursor)
+}
+
+// --- Tauri Commands ---
+
+/// Open the daily note for `date` (YYYY-MM-DD, default today), creating it if needed
+#[tauri::command]
+pub async fn daily_note_open(app: AppHandle, workspace_path: String, date: Option<String>) -> Result<OpenedDailyNote, String> {
+    let config = load_config(&app);
+    let root = PathBuf::from(&workspace_path);
+    let date = parse_date(date.as_deref())?;
+    let path = note_path(&root, &config, date);
+    let path_str = path.to_string_lossy().to_string();
+
+    if path.exists() {
+        return Ok(OpenedDailyNote {
+            date: date.to_string(),
+            path: path_str,
+            created: false,
+            cursor: None,
+        });
+    }
+
+    let events = if config.embed_events { Some(events_for(date).await) } else { None };
+    let (content, cursor) = build_content(&root, &config, date, events.as_deref());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    crate::handlers::files::write_file_content(path_str.clone(), content)?;
+
+    Ok(OpenedDailyNote {
+        date: date.to_string(),
+        path: path_str,
+        created: true,
+        cursor,
+    })
+}
+
+#[tauri::command]
+pub fn daily_note_exists(app: AppHandle, workspace_path: String, date: Option<String>) -> Result<bool, String> {
+    let date = parse_date(date.as_deref())?;
+    Ok(note_path(Path::new(&workspace_path), &load_config(&app), date).exists())
+}
+
+/// Daily notes that exist between `start` and `end` (inclusive)
+#[tauri::command]
+pub fn daily_note_range(app: AppHandle, workspace_path: String, start: String, end: String) -> Result<Vec<DailyNote>, String> {
+    let config = load_config(&app);
+    let (start, end) = (parse_date(Some(&start))?, parse_date(Some(&end))?);
+    if end < start {
+        return Err("End date is before start date".to_string());
+    }
+    if (end - start).num_days() > MAX_RANGE_DAYS {
+        return Err(format!("Range is limited to {} days", MAX_RANGE_DAYS));
+    }
+
+    let root = Path::new(&workspace_path);
+    Ok(start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|date| (date, note_path(root, &config, date)))
+        .filter(|(_, path)| path.exists())
+        .map(|(date, path)| DailyNote {
+            date: date.to_string(),
+            path: path.to_string_lossy().to_string(),
+            exists: true,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_path_uses_format_and_folder() {
+        let config = DailyNotesConfig {
+            folder: "Journal".to_string(),
+            format: "yyyy/MM/dd EEE".to_string(),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        assert_eq!(note_path(Path::new("/ws"), &config, date), PathBuf::from("/ws/Journal/2024/03/05 Tue.md"));
+    }
+
+    #[test]
+    fn test_default_content_and_events_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let (content, _) = build_content(dir.path(), &DailyNotesConfig::default(), date, None);
+        assert_eq!(content, "# 2024-03-05 - Tuesday\n\n");
+
+        let config = DailyNotesConfig {
+            template: Some("# {{date}}\nPrev: [[{{yesterday}}]]\n{{events}}\n{{cursor}}".to_string()),
+            ..Default::default()
+        };
+        let (content, cursor) = build_content(dir.path(), &config, date, None);
+        assert_eq!(content, "# 2024-03-05\nPrev: [[2024-03-04]]\n\n");
+        assert_eq!(cursor, Some(content.chars().count()));
+    }
+}
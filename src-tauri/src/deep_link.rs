@@ -0,0 +1,324 @@
+//! `lokus://` links that drive the app from outside (launchers, scripts,
+//! calendar events):
+//!
+//! - `lokus://open?vault=X&file=Y` (or `path=/abs/note.md`)
+//! - `lokus://search?query=...&vault=X`
+//! - `lokus://new?template=...&title=...&folder=...&vault=X`
+//! - `lokus://daily?date=YYYY-MM-DD&vault=X`
+//!
+//! `vault` is a registered vault's id or name; without it the last opened
+//! vault is used. Links can come from any web page, so they never reach
+//! folders outside the registered vaults, and a file that would open in
+//! another application needs the user's confirmation first. The vault's window
+//! is opened or focused and then sent `deep-link:navigate`. A window that is
+//! still loading picks the navigation up with `deep_link_take_pending` instead.
+
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+const MAX_QUERY_LEN: usize = 1000;
+
+/// The last navigation, until a window claims it
+static PENDING: Lazy<Mutex<Option<Navigation>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    Open { vault: Option<String>, file: String },
+    Search { vault: Option<String>, query: String },
+    New { vault: Option<String>, template: Option<String>, title: Option<String>, folder: Option<String> },
+    Daily { vault: Option<String>, date: Option<NaiveDate> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Navigation {
+    /// "open" or "search"
+    pub action: String,
+    pub workspace: String,
+    pub path: Option<String>,
+    pub query: Option<String>,
+}
+
+/// `lokus://` link that opens the note at `path`
+pub fn note_link(path: &str) -> String {
+    format!("lokus://open?path={}", urlencoding::encode(path))
+}
+
+fn non_empty(params: &HashMap<String, String>, key: &str) -> Option<String> {
+    params.get(key).map(|v| v.trim()).filter(|v| !v.is_empty()).map(String::from)
+}
+
+/// Parse a `lokus://` link. Links handled elsewhere (auth callbacks, plugin
+/// development) give `Ok(None)`.
+pub fn parse(link: &str) -> Result<Option<DeepLink>, String> {
+    let url = url::Url::parse(link.trim()).map_err(|e| format!("Invalid link '{}': {}", link, e))?;
+    if url.scheme() != "lokus" {
+        return Err(format!("Not a lokus:// link: {}", link));
+    }
+    let params: HashMap<String, String> = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    let vault = non_empty(&params, "vault");
+
+    // `lokus://open?...` puts the action in the host, `lokus:///open?...` in the path
+    let action = url.host_str().filter(|h| !h.is_empty()).unwrap_or_else(|| url.path().trim_matches('/'));
+    let link = match action {
+        "open" => {
+            let file = non_empty(&params, "file")
+                .or_else(|| non_empty(&params, "path"))
+                .ok_or_else(|| "lokus://open needs a file".to_string())?;
+            DeepLink::Open { vault, file }
+        }
+        "search" => {
+            let query = non_empty(&params, "query")
+                .or_else(|| non_empty(&params, "q"))
+                .ok_or_else(|| "lokus://search needs a query".to_string())?;
+            if query.chars().count() > MAX_QUERY_LEN {
+                return Err("Search query is too long".to_string());
+            }
+            DeepLink::Search { vault, query }
+        }
+        "new" => DeepLink::New {
+            vault,
+            template: non_empty(&params, "template"),
+            title: non_empty(&params, "title"),
+            folder: non_empty(&params, "folder"),
+        },
+        "daily" => {
+            let date = non_empty(&params, "date")
+                .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").map_err(|_| format!("Invalid date '{}'", d)))
+                .transpose()?;
+            DeepLink::Daily { vault, date }
+        }
+        "auth-callback" | "plugin-dev" => return Ok(None),
+        other => return Err(format!("Unknown lokus:// action '{}'", other)),
+    };
+    Ok(Some(link))
+}
+
+/// A path inside the vault, from untrusted input
+fn inside(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative.trim_start_matches(['/', '\\']));
+    if relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("'{}' is outside the vault", relative.display()));
+    }
+    Ok(root.join(relative))
+}
+
+/// The registered vault a link refers to: by id or name, the vault
+/// containing `file`, or the last opened vault
+fn resolve_vault(app: &AppHandle, vault: Option<&str>, file: Option<&Path>) -> Result<PathBuf, String> {
+    let vaults = crate::vaults::list_vaults();
+    let root = match vault {
+        Some(wanted) => vaults
+            .iter()
+            .find(|v| v.id == wanted || v.name.eq_ignore_ascii_case(wanted))
+            .map(|v| PathBuf::from(&v.path))
+            .ok_or_else(|| format!("Unknown vault '{}'", wanted))?,
+        None => match file.filter(|f| f.is_absolute()) {
+            Some(file) => vaults
+                .iter()
+                .map(|v| PathBuf::from(&v.path))
+                .filter(|root| file.starts_with(root))
+                .max_by_key(|root| root.components().count())
+                .ok_or_else(|| format!("{} is not in a known vault", file.display()))?,
+            None => crate::settings::current_workspace(app)
+                .filter(|current| vaults.iter().any(|v| Path::new(&v.path) == current))
+                .ok_or_else(|| "No vault is open".to_string())?,
+        },
+    };
+    if !root.is_dir() {
+        return Err(format!("Vault folder is not accessible: {}", root.display()));
+    }
+    Ok(root)
+}
+
+/// A note to open: absolute inside the vault, or relative to it, with `.md`
+/// implied
+fn resolve_file(root: &Path, file: &str) -> Result<PathBuf, String> {
+    let path = match Path::new(file) {
+        absolute if absolute.is_absolute() => {
+            let relative = absolute
+                .strip_prefix(root)
+                .map_err(|_| format!("{} is outside the vault", absolute.display()))?;
+            inside(root, &relative.to_string_lossy())?
+        }
+        _ => inside(root, file)?,
+    };
+    if path.is_file() {
+        return Ok(path);
+    }
+    let with_md = PathBuf::from(format!("{}.md", path.display()));
+    if with_md.is_file() {
+        return Ok(with_md);
+    }
+    Err(format!("Note not found: {}", file))
+}
+
+/// Ask the user before a link hands `path` to another application
+async fn confirm_external_open(app: &AppHandle, path: &Path) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(format!("A link is asking to open {} in another application.", path.display()))
+        .title("Open file outside Lokus?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Open".to_string(), "Cancel".to_string()))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+fn navigate(app: &AppHandle, navigation: Navigation) -> Result<(), String> {
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some(navigation.clone());
+    crate::window_manager::open_workspace_window(app.clone(), navigation.workspace.clone())?;
+    let _ = app.emit("deep-link:navigate", &navigation);
+    Ok(())
+}
+
+fn open_note(app: &AppHandle, root: &Path, path: String) -> Result<(), String> {
+    navigate(
+        app,
+        Navigation {
+            action: "open".to_string(),
+            workspace: root.to_string_lossy().to_string(),
+            path: Some(path),
+            query: None,
+        },
+    )
+}
+
+async fn run(app: &AppHandle, link: DeepLink) -> Result<(), String> {
+    match link {
+        DeepLink::Open { vault, file } => {
+            let root = resolve_vault(app, vault.as_deref(), Some(Path::new(&file)))?;
+            let path = resolve_file(&root, &file)?;
+            if crate::filetypes::handler_for(app, &path) == crate::filetypes::FileHandler::External {
+                if !confirm_external_open(app, &path).await {
+                    return Err(format!("Opening {} was cancelled", path.display()));
+                }
+                return crate::filetypes::open_externally(app, &path);
+            }
+            open_note(app, &root, path.to_string_lossy().to_string())
+        }
+        DeepLink::Search { vault, query } => {
+            let root = resolve_vault(app, vault.as_deref(), None)?;
+            navigate(
+                app,
+                Navigation {
+                    action: "search".to_string(),
+                    workspace: root.to_string_lossy().to_string(),
+                    path: None,
+                    query: Some(query),
+                },
+            )
+        }
+        DeepLink::New { vault, template, title, folder } => {
+            let root = resolve_vault(app, vault.as_deref(), None)?;
+            let folder = inside(&root, folder.as_deref().unwrap_or(""))?;
+            let path = match template {
+                Some(template) => {
+                    let ctx = crate::templates::RenderContext::new(title, HashMap::new());
+                    crate::templates::create_note(&root, &template, &folder, ctx)?.path
+                }
+                None => {
+                    let title = title.unwrap_or_else(|| "Untitled".to_string());
+                    std::fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+                    let path = crate::templates::unused_note_path(&folder, &title).to_string_lossy().to_string();
+                    crate::handlers::files::write_file_content(path.clone(), format!("# {}\n\n", title))?;
+                    path
+                }
+            };
+            open_note(app, &root, path)
+        }
+        DeepLink::Daily { vault, date } => {
+            let root = resolve_vault(app, vault.as_deref(), None)?;
+            let workspace = root.to_string_lossy().to_string();
+            let opened = crate::daily_notes::daily_note_open(app.clone(), workspace, date.map(|d| d.to_string())).await?;
+            open_note(app, &root, opened.path)
+        }
+    }
+}
+
+/// Handle a link the OS passed to the app
+pub fn handle(app: &AppHandle, link: &str) {
+    let link = link.to_string();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match parse(&link) {
+            Ok(Some(parsed)) => run(&app, parsed).await,
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("[DeepLink] {}: {}", link, e);
+            let _ = app.emit("deep-link:error", serde_json::json!({ "url": link, "error": e }));
+        }
+    });
+}
+
+/// Handle the payload of a `deep-link://new-url` event: a JSON array of URLs
+pub fn handle_event_payload(app: &AppHandle, payload: &str) {
+    let links: Vec<String> = serde_json::from_str(payload).unwrap_or_else(|_| vec![payload.trim_matches('"').to_string()]);
+    for link in links {
+        handle(app, &link);
+    }
+}
+
+// --- Tauri Commands ---
+
+/// The navigation a deep link asked for, if no window has taken it yet
+#[tauri::command]
+pub fn deep_link_take_pending(workspace: String) -> Option<Navigation> {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    match pending.as_ref() {
+        Some(navigation) if Path::new(&navigation.workspace) == Path::new(&workspace) => pending.take(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_actions() {
+        assert_eq!(
+            parse("lokus://open?vault=Work&file=Projects%2FPlan.md").unwrap(),
+            Some(DeepLink::Open { vault: Some("Work".into()), file: "Projects/Plan.md".into() })
+        );
+        assert_eq!(
+            parse("lokus:///search?query=weekly+review").unwrap(),
+            Some(DeepLink::Search { vault: None, query: "weekly review".into() })
+        );
+        assert_eq!(
+            parse("lokus://daily?date=2026-02-03").unwrap(),
+            Some(DeepLink::Daily { vault: None, date: NaiveDate::from_ymd_opt(2026, 2, 3) })
+        );
+        assert!(matches!(parse("lokus://new?title=Idea").unwrap(), Some(DeepLink::New { title: Some(_), template: None, .. })));
+        assert_eq!(parse("lokus://auth-callback?code=abc").unwrap(), None);
+
+        assert!(parse("lokus://open").is_err());
+        assert!(parse("lokus://search?query=%20").is_err());
+        assert!(parse("lokus://daily?date=tomorrow").is_err());
+        assert!(parse("lokus://delete?file=a.md").is_err());
+        assert!(parse("https://open?file=a.md").is_err());
+    }
+
+    #[test]
+    fn test_paths_stay_in_vault() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Plan.md"), "# Plan").unwrap();
+
+        assert_eq!(resolve_file(dir.path(), "Plan").unwrap(), dir.path().join("Plan.md"));
+        assert_eq!(resolve_file(dir.path(), &dir.path().join("Plan.md").to_string_lossy()).unwrap(), dir.path().join("Plan.md"));
+        assert!(resolve_file(dir.path(), "../Plan.md").is_err());
+        assert!(resolve_file(dir.path(), "/etc/passwd").is_err());
+        assert!(inside(dir.path(), "a/../../b").is_err());
+    }
+}
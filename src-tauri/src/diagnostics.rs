@@ -0,0 +1,300 @@
+//! Process resource monitoring.
+//!
+//! Every minute the monitor records resident memory, open file handles and
+//! the size of the in-memory search indexes. Crossing a limit emits
+//! `diagnostics-warning`; with `autoMitigate` on (the default) the in-memory
+//! caches are dropped and background sync (git auto-sync, feeds, calendar)
+//! holds off until usage is comfortably back under the limits. The latest
+//! snapshot is attached to crash reports, so a crash under memory pressure
+//! says so. Limits live in `~/.lokus/diagnostics.json`.
+//!
+//! There is no peer-to-peer (Iroh) store in this build, so there are no
+//! blob caches to measure beyond the ones listed here.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const CONFIG_FILE: &str = "diagnostics.json";
+const WARNING_EVENT: &str = "diagnostics-warning";
+const TICK: Duration = Duration::from_secs(60);
+const MB: u64 = 1024 * 1024;
+/// Paused work resumes once every metric is below this share of its limit
+const RESUME_RATIO: f64 = 0.8;
+
+static LATEST: Lazy<Mutex<Option<ResourceSnapshot>>> = Lazy::new(|| Mutex::new(None));
+static BACKGROUND_PAUSED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSnapshot {
+    pub taken_at: i64,
+    /// Resident set size; `None` where it can't be read
+    pub rss_bytes: Option<u64>,
+    /// Open file descriptors; not available on Windows
+    pub open_files: Option<u64>,
+    pub search_index_bytes: u64,
+    pub background_paused: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticsConfig {
+    pub memory_limit_mb: u64,
+    pub open_files_limit: u64,
+    pub search_index_limit_mb: u64,
+    /// Drop caches and pause background sync when a limit is crossed
+    pub auto_mitigate: bool,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit_mb: 2048,
+            open_files_limit: 2048,
+            search_index_limit_mb: 512,
+            auto_mitigate: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Metric {
+    Memory,
+    OpenFiles,
+    SearchIndex,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceWarning {
+    pub metric: Metric,
+    pub value: u64,
+    pub limit: u64,
+    /// Caches were dropped and background sync paused in response
+    pub mitigated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mitigation {
+    pub freed_search_index_bytes: u64,
+    pub cleared_cache_entries: usize,
+    pub background_paused: bool,
+}
+
+fn config_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join(".lokus").join(CONFIG_FILE))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn load_config() -> DiagnosticsConfig {
+    config_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn rss_bytes() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = sysinfo::System::new();
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.memory())
+}
+
+fn open_files() -> Option<u64> {
+    let fd_dir = if cfg!(target_os = "linux") {
+        "/proc/self/fd"
+    } else if cfg!(target_os = "macos") {
+        "/dev/fd"
+    } else {
+        return None;
+    };
+    // Less the descriptor used to list the directory
+    std::fs::read_dir(fd_dir).ok().map(|entries| entries.count().saturating_sub(1) as u64)
+}
+
+fn take_snapshot() -> ResourceSnapshot {
+    let snapshot = ResourceSnapshot {
+        taken_at: chrono::Utc::now().timestamp_millis(),
+        rss_bytes: rss_bytes(),
+        open_files: open_files(),
+        search_index_bytes: crate::search::index::memory_usage() as u64,
+        background_paused: background_paused(),
+    };
+    sentry::configure_scope(|scope| scope.set_context("resources", sentry::protocol::Context::Other(as_map(&snapshot))));
+    *LATEST.lock().unwrap_or_else(|e| e.into_inner()) = Some(snapshot.clone());
+    snapshot
+}
+
+fn as_map(snapshot: &ResourceSnapshot) -> BTreeMap<String, Value> {
+    match serde_json::to_value(snapshot) {
+        Ok(Value::Object(map)) => map.into_iter().collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Metrics at or above `ratio` of their limit
+fn exceeded(snapshot: &ResourceSnapshot, config: &DiagnosticsConfig, ratio: f64) -> Vec<ResourceWarning> {
+    let measured = [
+        (Metric::Memory, snapshot.rss_bytes, config.memory_limit_mb * MB),
+        (Metric::OpenFiles, snapshot.open_files, config.open_files_limit),
+        (Metric::SearchIndex, Some(snapshot.search_index_bytes), config.search_index_limit_mb * MB),
+    ];
+    measured
+        .into_iter()
+        .filter_map(|(metric, value, limit)| {
+            let value = value?;
+            (limit > 0 && value as f64 >= limit as f64 * ratio).then_some(ResourceWarning { metric, value, limit, mitigated: false })
+        })
+        .collect()
+}
+
+/// Whether background sync should hold off for now
+pub(crate) fn background_paused() -> bool {
+    BACKGROUND_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Drop in-memory caches and pause background sync
+fn mitigate() -> Mitigation {
+    let freed = crate::search::index::unload_all() as u64;
+    let cleared = crate::smart_folders::clear_cache() + crate::citations::clear_cache();
+    BACKGROUND_PAUSED.store(true, Ordering::Relaxed);
+    tracing::warn!("Resource limits exceeded: dropped caches ({} bytes of search index), paused background sync", freed);
+    Mitigation {
+        freed_search_index_bytes: freed,
+        cleared_cache_entries: cleared,
+        background_paused: true,
+    }
+}
+
+/// Leave the latest snapshot as a breadcrumb for a crash report. Called from
+/// the panic hook, so it never waits on a lock.
+pub(crate) fn add_crash_breadcrumb() {
+    let Ok(latest) = LATEST.try_lock() else {
+        return;
+    };
+    if let Some(snapshot) = latest.as_ref() {
+        sentry::add_breadcrumb(sentry::Breadcrumb {
+            category: Some("resources".into()),
+            message: Some("Latest resource snapshot".into()),
+            data: as_map(snapshot),
+            ..Default::default()
+        });
+    }
+}
+
+/// Check resource usage for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut over: HashSet<Metric> = HashSet::new();
+        loop {
+            tokio::time::sleep(TICK).await;
+            let snapshot = match tokio::task::spawn_blocking(take_snapshot).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    tracing::warn!("Resource snapshot failed: {}", e);
+                    continue;
+                }
+            };
+            let config = load_config();
+
+            let exceeding = exceeded(&snapshot, &config, 1.0);
+            let mut crossed: Vec<ResourceWarning> = exceeding.iter().filter(|w| !over.contains(&w.metric)).cloned().collect();
+            if !crossed.is_empty() && config.auto_mitigate {
+                let _ = tokio::task::spawn_blocking(mitigate).await;
+                crossed.iter_mut().for_each(|warning| warning.mitigated = true);
+            }
+            for warning in &crossed {
+                tracing::warn!("{:?} at {} crossed its limit of {}", warning.metric, warning.value, warning.limit);
+                sentry::add_breadcrumb(sentry::Breadcrumb {
+                    category: Some("resources".into()),
+                    message: Some(format!("{:?} limit crossed", warning.metric)),
+                    level: sentry::Level::Warning,
+                    data: as_map(&snapshot),
+                    ..Default::default()
+                });
+                if let Err(e) = app.emit(WARNING_EVENT, warning) {
+                    tracing::warn!("Failed to emit {}: {}", WARNING_EVENT, e);
+                }
+            }
+
+            over = exceeding.into_iter().map(|w| w.metric).collect();
+            if background_paused() && exceeded(&snapshot, &config, RESUME_RATIO).is_empty() {
+                BACKGROUND_PAUSED.store(false, Ordering::Relaxed);
+                tracing::info!("Resource usage back under limits; resuming background sync");
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Current resource usage
+#[tauri::command]
+pub async fn diagnostics_snapshot() -> Result<ResourceSnapshot, String> {
+    tokio::task::spawn_blocking(take_snapshot)
+        .await
+        .map_err(|e| format!("Resource snapshot failed: {}", e))
+}
+
+#[tauri::command]
+pub fn diagnostics_get_config() -> DiagnosticsConfig {
+    load_config()
+}
+
+#[tauri::command]
+pub fn diagnostics_set_config(config: DiagnosticsConfig) -> Result<DiagnosticsConfig, String> {
+    let path = config_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save diagnostics config: {}", e))?;
+    Ok(config)
+}
+
+/// Drop caches and pause background sync now; sync resumes once usage is
+/// under the limits at the next check
+#[tauri::command]
+pub async fn diagnostics_mitigate() -> Result<Mitigation, String> {
+    tokio::task::spawn_blocking(mitigate)
+        .await
+        .map_err(|e| format!("Mitigation failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_checked_against_a_ratio() {
+        let config = DiagnosticsConfig { memory_limit_mb: 100, open_files_limit: 0, search_index_limit_mb: 10, ..Default::default() };
+        let snapshot = ResourceSnapshot {
+            taken_at: 0,
+            rss_bytes: Some(90 * MB),
+            open_files: Some(5000),
+            search_index_bytes: 12 * MB,
+            background_paused: false,
+        };
+
+        let over: Vec<Metric> = exceeded(&snapshot, &config, 1.0).into_iter().map(|w| w.metric).collect();
+        // A zero limit is off
+        assert_eq!(over, vec![Metric::SearchIndex]);
+
+        let near: Vec<Metric> = exceeded(&snapshot, &config, RESUME_RATIO).into_iter().map(|w| w.metric).collect();
+        assert_eq!(near, vec![Metric::Memory, Metric::SearchIndex]);
+
+        let unknown = ResourceSnapshot { rss_bytes: None, search_index_bytes: 0, ..snapshot };
+        assert!(exceeded(&unknown, &config, RESUME_RATIO).is_empty());
+    }
+}
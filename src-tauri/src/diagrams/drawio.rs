@@ -0,0 +1,489 @@
+//! draw.io (diagrams.net) files: an `<mxfile>` of pages, each holding an
+//! `<mxGraphModel>` either inline or deflated and base64-encoded. Only the
+//! first page is rendered.
+
+use base64::{engine::general_purpose, Engine as _};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Read as _;
+
+use super::{arrowhead, escape, Bounds, DiagramInfo, DiagramKind, Rendered};
+
+/// Nesting limit when resolving positions inside groups
+const MAX_DEPTH: usize = 32;
+const DEFAULT_FONT_SIZE: f64 = 12.0;
+
+#[derive(Debug, Default, Clone)]
+struct Geometry {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    relative: bool,
+    source_point: Option<(f64, f64)>,
+    target_point: Option<(f64, f64)>,
+    points: Vec<(f64, f64)>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Cell {
+    id: String,
+    value: String,
+    style: String,
+    vertex: bool,
+    edge: bool,
+    parent: Option<String>,
+    source: Option<String>,
+    target: Option<String>,
+    link: Option<String>,
+    geometry: Geometry,
+}
+
+fn attributes(element: &BytesStart) -> HashMap<String, String> {
+    element
+        .attributes()
+        .flatten()
+        .filter_map(|a| {
+            let key = String::from_utf8_lossy(a.key.as_ref()).to_string();
+            Some((key, a.unescape_value().ok()?.to_string()))
+        })
+        .collect()
+}
+
+fn number(attrs: &HashMap<String, String>, key: &str) -> f64 {
+    attrs.get(key).and_then(|v| v.parse().ok()).unwrap_or(0.0)
+}
+
+fn point(attrs: &HashMap<String, String>) -> (f64, f64) {
+    (number(attrs, "x"), number(attrs, "y"))
+}
+
+/// Compressed pages are raw deflate, then base64, of the URL-encoded model
+fn inflate(encoded: &str) -> Result<String, String> {
+    let compact: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = general_purpose::STANDARD
+        .decode(compact)
+        .map_err(|e| format!("Invalid compressed draw.io page: {}", e))?;
+    let mut inflated = String::new();
+    flate2::read::DeflateDecoder::new(bytes.as_slice())
+        .read_to_string(&mut inflated)
+        .map_err(|e| format!("Invalid compressed draw.io page: {}", e))?;
+    urlencoding::decode(&inflated)
+        .map(|s| s.into_owned())
+        .map_err(|e| format!("Invalid compressed draw.io page: {}", e))
+}
+
+/// A `<diagram>` element: its model inline, or the compressed text
+enum Page {
+    Inline,
+    Compressed(String),
+}
+
+/// The pages of a file as graph model XML
+fn pages(content: &str) -> Result<Vec<String>, String> {
+    let mut reader = Reader::from_str(content);
+    let mut pages = Vec::new();
+    let mut has_root = false;
+    let mut in_diagram = false;
+    let mut inline = false;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                let name = e.name();
+                if !has_root {
+                    match name.as_ref() {
+                        // A bare model is a single page
+                        b"mxGraphModel" => return Ok(vec![content.to_string()]),
+                        b"mxfile" => has_root = true,
+                        other => {
+                            return Err(format!("Not a draw.io file (root element <{}>)", String::from_utf8_lossy(other)))
+                        }
+                    }
+                    continue;
+                }
+                match name.as_ref() {
+                    b"diagram" => {
+                        in_diagram = true;
+                        inline = false;
+                        text.clear();
+                    }
+                    b"mxGraphModel" if in_diagram => inline = true,
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) if in_diagram && !inline => {
+                text.push_str(&e.unescape().map_err(|e| format!("Invalid draw.io file: {}", e))?);
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"diagram" => {
+                in_diagram = false;
+                pages.push(if inline { Page::Inline } else { Page::Compressed(std::mem::take(&mut text)) });
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid draw.io file at byte {}: {}", reader.buffer_position(), e)),
+            _ => {}
+        }
+    }
+    if !has_root {
+        return Err("Not a draw.io file".to_string());
+    }
+
+    // Inline models are cut out of the file as they are, in order
+    let mut rest = content;
+    let mut inline_models = Vec::new();
+    while let Some(start) = rest.find("<mxGraphModel") {
+        let Some(end) = rest[start..].find("</mxGraphModel>") else { break };
+        let end = start + end + "</mxGraphModel>".len();
+        inline_models.push(rest[start..end].to_string());
+        rest = &rest[end..];
+    }
+    let mut inline_models = inline_models.into_iter();
+    pages
+        .into_iter()
+        .map(|page| match page {
+            Page::Inline => inline_models.next().ok_or_else(|| "Invalid draw.io page".to_string()),
+            Page::Compressed(text) => inflate(text.trim()),
+        })
+        .collect()
+}
+
+/// Cells of one graph model. Cells wrapped in `<UserObject>`/`<object>` take
+/// their id, label and link from the wrapper.
+fn cells(model: &str) -> Result<Vec<Cell>, String> {
+    let mut reader = Reader::from_str(model);
+    let mut cells = Vec::new();
+    let mut wrapper: Option<HashMap<String, String>> = None;
+    let mut current: Option<Cell> = None;
+    let mut in_points = false;
+
+    loop {
+        let (element, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(e)) => {
+                match e.name().as_ref() {
+                    b"mxCell" => cells.extend(current.take()),
+                    b"UserObject" | b"object" => wrapper = None,
+                    b"Array" => in_points = false,
+                    b"mxGraphModel" => break,
+                    _ => {}
+                }
+                continue;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid draw.io page at byte {}: {}", reader.buffer_position(), e)),
+            _ => continue,
+        };
+        let attrs = attributes(&element);
+        match element.name().as_ref() {
+            b"UserObject" | b"object" => wrapper = (!empty).then_some(attrs),
+            b"mxCell" => {
+                let from_wrapper = |key: &str| wrapper.as_ref().and_then(|w| w.get(key).cloned());
+                let cell = Cell {
+                    id: attrs.get("id").cloned().or_else(|| from_wrapper("id")).unwrap_or_default(),
+                    value: attrs.get("value").cloned().or_else(|| from_wrapper("label")).unwrap_or_default(),
+                    style: attrs.get("style").cloned().unwrap_or_default(),
+                    vertex: attrs.get("vertex").is_some_and(|v| v == "1"),
+                    edge: attrs.get("edge").is_some_and(|v| v == "1"),
+                    parent: attrs.get("parent").cloned(),
+                    source: attrs.get("source").cloned(),
+                    target: attrs.get("target").cloned(),
+                    link: from_wrapper("link"),
+                    geometry: Geometry::default(),
+                };
+                if empty {
+                    cells.push(cell);
+                } else {
+                    current = Some(cell);
+                }
+            }
+            b"mxGeometry" => {
+                if let Some(cell) = current.as_mut() {
+                    cell.geometry.x = number(&attrs, "x");
+                    cell.geometry.y = number(&attrs, "y");
+                    cell.geometry.width = number(&attrs, "width");
+                    cell.geometry.height = number(&attrs, "height");
+                    cell.geometry.relative = attrs.get("relative").is_some_and(|v| v == "1");
+                }
+            }
+            b"Array" => in_points = !empty && attrs.get("as").is_some_and(|v| v == "points"),
+            b"mxPoint" => {
+                if let Some(cell) = current.as_mut() {
+                    match attrs.get("as").map(String::as_str) {
+                        Some("sourcePoint") => cell.geometry.source_point = Some(point(&attrs)),
+                        Some("targetPoint") => cell.geometry.target_point = Some(point(&attrs)),
+                        _ if in_points => cell.geometry.points.push(point(&attrs)),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(cells)
+}
+
+fn first_page_cells(content: &str) -> Result<(Vec<Cell>, usize), String> {
+    let pages = pages(content)?;
+    let first = pages.first().ok_or("draw.io file has no pages")?;
+    Ok((cells(first)?, pages.len()))
+}
+
+pub(super) fn validate(content: &str) -> Result<DiagramInfo, String> {
+    let (cells, pages) = first_page_cells(content)?;
+    let elements = cells.iter().filter(|c| c.vertex || c.edge).count();
+    Ok(DiagramInfo { kind: DiagramKind::Drawio, elements, pages })
+}
+
+/// (link, label) per cell, across all pages
+pub(super) fn links(content: &str) -> Vec<(Option<String>, Option<String>)> {
+    let Ok(pages) = pages(content) else {
+        return Vec::new();
+    };
+    pages
+        .iter()
+        .filter_map(|page| cells(page).ok())
+        .flatten()
+        .filter(|c| c.vertex || c.edge)
+        .map(|c| (c.link.map(|l| l.trim_start_matches("data:page/id,").to_string()), Some(plain_text(&c.value))))
+        .collect()
+}
+
+/// Labels may be HTML; keep line breaks and drop the markup
+fn plain_text(value: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    let mut tag = String::new();
+    for c in value.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                let name = tag.trim_start_matches('/').split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+                if matches!(name.to_lowercase().as_str(), "br" | "div" | "p" | "li") && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            _ if in_tag => tag.push(c),
+            _ => text.push(c),
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+fn parse_style(style: &str) -> HashMap<&str, &str> {
+    let mut map = HashMap::new();
+    for (i, part) in style.split(';').filter(|p| !p.is_empty()).enumerate() {
+        match part.split_once('=') {
+            Some((key, value)) => {
+                map.insert(key, value);
+            }
+            // A leading bare name like `ellipse` or `text` is the shape
+            None if i == 0 => {
+                map.entry("shape").or_insert(part);
+            }
+            None => {
+                map.insert(part, "1");
+            }
+        }
+    }
+    map
+}
+
+fn style_color(style: &HashMap<&str, &str>, key: &str, default: &str) -> String {
+    match style.get(key) {
+        Some(&"none") => "none".to_string(),
+        Some(color) if color.starts_with('#') => escape(color),
+        _ => default.to_string(),
+    }
+}
+
+/// Absolute top-left of a vertex; children of groups and containers are
+/// positioned relative to their parent
+fn origin(cells: &HashMap<&str, &Cell>, id: Option<&str>, depth: usize) -> (f64, f64) {
+    match id.and_then(|id| cells.get(id)).filter(|c| c.vertex) {
+        Some(parent) if depth < MAX_DEPTH => {
+            let (x, y) = origin(cells, parent.parent.as_deref(), depth + 1);
+            (x + parent.geometry.x, y + parent.geometry.y)
+        }
+        _ => (0.0, 0.0),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+impl Rect {
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.w / 2.0, self.y + self.h / 2.0)
+    }
+
+    /// Where the line from `from` to the center crosses the border
+    fn border_toward(&self, from: (f64, f64)) -> (f64, f64) {
+        let (cx, cy) = self.center();
+        let (dx, dy) = (cx - from.0, cy - from.1);
+        let scale = (dx.abs() / (self.w / 2.0).max(1.0)).max(dy.abs() / (self.h / 2.0).max(1.0));
+        if scale <= 1.0 {
+            return (cx, cy);
+        }
+        (cx - dx / scale, cy - dy / scale)
+    }
+}
+
+fn draw_label(svg: &mut String, text: &str, (cx, cy): (f64, f64), style: &HashMap<&str, &str>) {
+    if text.is_empty() {
+        return;
+    }
+    let font_size = style.get("fontSize").and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_FONT_SIZE);
+    let color = style_color(style, "fontColor", "#000000");
+    let lines: Vec<&str> = text.lines().collect();
+    let line_height = font_size * 1.2;
+    let top = cy - line_height * (lines.len() as f64 - 1.0) / 2.0 + font_size * 0.35;
+    let _ = write!(
+        svg,
+        r#"<text font-family="Helvetica, Arial, sans-serif" font-size="{font_size}" fill="{color}" text-anchor="middle">"#
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let _ = write!(svg, r#"<tspan x="{cx:.1}" y="{:.1}">{}</tspan>"#, top + i as f64 * line_height, escape(line));
+    }
+    svg.push_str("</text>");
+}
+
+pub(super) fn render(content: &str) -> Result<Rendered, String> {
+    let (cells, _) = first_page_cells(content)?;
+    let by_id: HashMap<&str, &Cell> = cells.iter().map(|c| (c.id.as_str(), c)).collect();
+    let mut rects: HashMap<&str, Rect> = HashMap::new();
+    let mut bounds = Bounds::new();
+    let mut body = String::new();
+
+    for cell in cells.iter().filter(|c| c.vertex && !c.geometry.relative) {
+        let (ox, oy) = origin(&by_id, cell.parent.as_deref(), 0);
+        let rect = Rect { x: ox + cell.geometry.x, y: oy + cell.geometry.y, w: cell.geometry.width, h: cell.geometry.height };
+        rects.insert(cell.id.as_str(), rect);
+        bounds.include(rect.x, rect.y);
+        bounds.include(rect.x + rect.w, rect.y + rect.h);
+
+        let style = parse_style(&cell.style);
+        let shape = style.get("shape").copied().unwrap_or("rect");
+        let fill = style_color(&style, "fillColor", "#ffffff");
+        let stroke = style_color(&style, "strokeColor", "#000000");
+        let dash = if style.get("dashed") == Some(&"1") { r#" stroke-dasharray="6 4""# } else { "" };
+        let paint = format!(r#"fill="{fill}" stroke="{stroke}" stroke-width="1"{dash}"#);
+        let Rect { x, y, w, h } = rect;
+        let (cx, cy) = rect.center();
+        match shape {
+            "text" | "label" => {}
+            "ellipse" | "doubleEllipse" => {
+                let _ = write!(body, r#"<ellipse cx="{cx:.1}" cy="{cy:.1}" rx="{:.1}" ry="{:.1}" {paint}/>"#, w / 2.0, h / 2.0);
+            }
+            "rhombus" => {
+                let _ = write!(
+                    body,
+                    r#"<polygon points="{cx:.1},{y:.1} {:.1},{cy:.1} {cx:.1},{:.1} {x:.1},{cy:.1}" {paint}/>"#,
+                    x + w,
+                    y + h
+                );
+            }
+            _ => {
+                let radius = if style.get("rounded") == Some(&"1") { (w.min(h) * 0.15).min(10.0) } else { 0.0 };
+                let _ = write!(body, r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="{radius:.1}" {paint}/>"#);
+            }
+        }
+        // Containers label their header rather than their middle
+        let label_at = if shape == "swimlane" { (cx, y + 13.0) } else { (cx, cy) };
+        draw_label(&mut body, &plain_text(&cell.value), label_at, &style);
+    }
+
+    for cell in cells.iter().filter(|c| c.edge) {
+        let (ox, oy) = origin(&by_id, cell.parent.as_deref(), 0);
+        let waypoints: Vec<(f64, f64)> = cell.geometry.points.iter().map(|(x, y)| (ox + x, oy + y)).collect();
+        let source = cell.source.as_deref().and_then(|id| rects.get(id));
+        let target = cell.target.as_deref().and_then(|id| rects.get(id));
+        let start_hint = source.map(Rect::center).or(cell.geometry.source_point.map(|(x, y)| (ox + x, oy + y)));
+        let end_hint = target.map(Rect::center).or(cell.geometry.target_point.map(|(x, y)| (ox + x, oy + y)));
+        let (Some(start_hint), Some(end_hint)) = (start_hint, end_hint) else {
+            continue;
+        };
+
+        let toward_start = waypoints.first().copied().unwrap_or(end_hint);
+        let toward_end = waypoints.last().copied().unwrap_or(start_hint);
+        let start = source.map_or(start_hint, |r| r.border_toward(toward_start));
+        let end = target.map_or(end_hint, |r| r.border_toward(toward_end));
+        let mut points = vec![start];
+        points.extend(waypoints);
+        points.push(end);
+        for (x, y) in &points {
+            bounds.include(*x, *y);
+        }
+
+        let style = parse_style(&cell.style);
+        let stroke = style_color(&style, "strokeColor", "#000000");
+        let dash = if style.get("dashed") == Some(&"1") { r#" stroke-dasharray="6 4""# } else { "" };
+        let list: Vec<String> = points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        let _ = write!(body, r#"<polyline points="{}" fill="none" stroke="{stroke}" stroke-width="1"{dash}/>"#, list.join(" "));
+        if style.get("endArrow") != Some(&"none") {
+            let before = points[points.len() - 2];
+            let _ = write!(body, r#"<polyline points="{}" fill="none" stroke="{stroke}" stroke-width="1"/>"#, arrowhead(before, end, 8.0));
+        }
+        let middle = points.len() / 2;
+        let (a, b) = (points[middle - 1], points[middle]);
+        draw_label(&mut body, &plain_text(&cell.value), ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0), &style);
+    }
+
+    Ok(bounds.document(&body, Some("#ffffff")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    const MODEL: &str = r##"<mxGraphModel><root><mxCell id="0"/><mxCell id="1" parent="0"/>
+        <mxCell id="a" value="Start" style="ellipse;fillColor=#dae8fc;" vertex="1" parent="1"><mxGeometry x="0" y="0" width="80" height="40" as="geometry"/></mxCell>
+        <UserObject id="b" label="&lt;b&gt;See&lt;/b&gt; [[Plan]]" link="Specs/API.md"><mxCell style="rounded=1;" vertex="1" parent="1"><mxGeometry x="200" y="0" width="80" height="40" as="geometry"/></mxCell></UserObject>
+        <mxCell id="e" edge="1" parent="1" source="a" target="b"><mxGeometry relative="1" as="geometry"/></mxCell>
+        </root></mxGraphModel>"##;
+
+    fn compress(model: &str) -> String {
+        let encoded = urlencoding::encode(model).into_owned();
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(encoded.as_bytes()).unwrap();
+        general_purpose::STANDARD.encode(encoder.finish().unwrap())
+    }
+
+    #[test]
+    fn test_inline_and_compressed_pages_render_alike() {
+        let inline = format!(r#"<mxfile host="app.diagrams.net"><diagram id="p1" name="Page-1">{}</diagram></mxfile>"#, MODEL);
+        let compressed = format!(r#"<mxfile><diagram id="p1" name="Page-1">{}</diagram><diagram name="Page-2">{}</diagram></mxfile>"#, compress(MODEL), compress(MODEL));
+
+        let info = validate(&compressed).unwrap();
+        assert_eq!((info.elements, info.pages), (3, 2));
+        let rendered = render(&inline).unwrap();
+        assert_eq!(rendered.svg, render(&compressed).unwrap().svg);
+        assert_eq!((rendered.width, rendered.height), (320.0, 80.0));
+        assert!(rendered.svg.contains("<ellipse"));
+        // The edge runs between the shapes' borders, not their centers
+        assert!(rendered.svg.contains(r#"points="80.0,20.0 200.0,20.0""#));
+
+        let links = links(&inline);
+        assert_eq!(links[1], (Some("Specs/API.md".to_string()), Some("See [[Plan]]".to_string())));
+        assert!(validate("<html></html>").is_err());
+    }
+}
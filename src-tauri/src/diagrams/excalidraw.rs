@@ -0,0 +1,259 @@
+//! Excalidraw scenes: plain `.excalidraw` JSON, or the Obsidian plugin's
+//! `.excalidraw.md` notes with the scene in a `json` code block.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use super::{arrowhead, escape, Bounds, DiagramInfo, DiagramKind, Rendered};
+
+const DEFAULT_FONT_SIZE: f64 = 20.0;
+const LINE_HEIGHT: f64 = 1.25;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Scene {
+    #[serde(rename = "type")]
+    kind: String,
+    elements: Vec<Element>,
+    #[serde(default)]
+    app_state: Value,
+    #[serde(default)]
+    files: HashMap<String, SceneFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SceneFile {
+    #[serde(rename = "dataURL")]
+    data_url: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Element {
+    #[serde(rename = "type")]
+    kind: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    angle: f64,
+    stroke_color: Option<String>,
+    background_color: Option<String>,
+    stroke_width: Option<f64>,
+    stroke_style: Option<String>,
+    opacity: Option<f64>,
+    roundness: Option<Value>,
+    points: Vec<[f64; 2]>,
+    text: Option<String>,
+    font_size: Option<f64>,
+    font_family: Option<u32>,
+    text_align: Option<String>,
+    end_arrowhead: Option<String>,
+    start_arrowhead: Option<String>,
+    file_id: Option<String>,
+    name: Option<String>,
+    link: Option<String>,
+    is_deleted: bool,
+}
+
+impl Element {
+    fn is_linear(&self) -> bool {
+        matches!(self.kind.as_str(), "line" | "arrow" | "freedraw")
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+/// The scene JSON, pulled out of an `.excalidraw.md` note when needed
+fn scene_json(content: &str) -> Result<&str, String> {
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('{') {
+        return Ok(trimmed);
+    }
+    if content.contains("```compressed-json") {
+        return Err("Compressed Excalidraw drawings can't be read; turn off compression in the Excalidraw plugin".to_string());
+    }
+    let start = content
+        .find("```json")
+        .map(|i| i + "```json".len())
+        .ok_or("No Excalidraw drawing found in note")?;
+    let end = content[start..].find("```").ok_or("Unterminated Excalidraw drawing block")?;
+    Ok(content[start..start + end].trim())
+}
+
+fn parse(content: &str) -> Result<Scene, String> {
+    let scene: Scene =
+        serde_json::from_str(scene_json(content)?).map_err(|e| format!("Invalid Excalidraw drawing: {}", e))?;
+    if scene.kind != "excalidraw" {
+        return Err(format!("Not an Excalidraw drawing (type \"{}\")", scene.kind));
+    }
+    Ok(scene)
+}
+
+fn live(scene: &Scene) -> impl Iterator<Item = &Element> {
+    scene.elements.iter().filter(|e| !e.is_deleted)
+}
+
+pub(super) fn validate(content: &str) -> Result<DiagramInfo, String> {
+    let scene = parse(content)?;
+    Ok(DiagramInfo { kind: DiagramKind::Excalidraw, elements: live(&scene).count(), pages: 1 })
+}
+
+/// (link, text) per element
+pub(super) fn links(content: &str) -> Vec<(Option<String>, Option<String>)> {
+    let Ok(scene) = parse(content) else {
+        return Vec::new();
+    };
+    live(&scene).map(|e| (e.link.clone(), e.text.clone())).collect()
+}
+
+fn color(value: Option<&str>, default: &str) -> String {
+    match value {
+        Some("transparent") | Some("") => "none".to_string(),
+        Some(color) => escape(color),
+        None => default.to_string(),
+    }
+}
+
+fn font(family: Option<u32>) -> &'static str {
+    match family {
+        Some(2) => "Helvetica, Arial, sans-serif",
+        Some(3) => "Cascadia Code, Consolas, monospace",
+        _ => "Virgil, Segoe Print, Comic Sans MS, cursive",
+    }
+}
+
+fn draw(svg: &mut String, element: &Element, files: &HashMap<String, SceneFile>) {
+    let stroke = color(element.stroke_color.as_deref(), "#1e1e1e");
+    let fill = color(element.background_color.as_deref(), "none");
+    let stroke_width = element.stroke_width.unwrap_or(2.0);
+    let dash = match element.stroke_style.as_deref() {
+        Some("dashed") => r#" stroke-dasharray="8 6""#,
+        Some("dotted") => r#" stroke-dasharray="2 5""#,
+        _ => "",
+    };
+    let opacity = element.opacity.unwrap_or(100.0) / 100.0;
+    let (cx, cy) = element.center();
+    let _ = write!(svg, r#"<g opacity="{:.2}""#, opacity);
+    if element.angle != 0.0 {
+        let _ = write!(svg, r#" transform="rotate({:.2} {:.1} {:.1})""#, element.angle.to_degrees(), cx, cy);
+    }
+    svg.push('>');
+
+    let style = format!(r#"fill="{fill}" stroke="{stroke}" stroke-width="{stroke_width}"{dash}"#);
+    let (x, y, w, h) = (element.x, element.y, element.width, element.height);
+    match element.kind.as_str() {
+        "rectangle" | "frame" | "embeddable" | "iframe" => {
+            let radius = if element.roundness.as_ref().is_some_and(|r| !r.is_null()) { (w.min(h) * 0.25).min(32.0) } else { 0.0 };
+            let _ = write!(svg, r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="{radius:.1}" {style}/>"#);
+            if let Some(name) = element.name.as_deref().filter(|_| element.kind == "frame") {
+                let _ = write!(svg, r##"<text x="{x:.1}" y="{:.1}" font-size="14" fill="#868e96" stroke="none">{}</text>"##, y - 6.0, escape(name));
+            }
+        }
+        "ellipse" => {
+            let _ = write!(svg, r#"<ellipse cx="{cx:.1}" cy="{cy:.1}" rx="{:.1}" ry="{:.1}" {style}/>"#, w / 2.0, h / 2.0);
+        }
+        "diamond" => {
+            let _ = write!(
+                svg,
+                r#"<polygon points="{cx:.1},{y:.1} {:.1},{cy:.1} {cx:.1},{:.1} {x:.1},{cy:.1}" {style}/>"#,
+                x + w,
+                y + h
+            );
+        }
+        "line" | "arrow" | "freedraw" => {
+            let points: Vec<(f64, f64)> = element.points.iter().map(|[px, py]| (x + px, y + py)).collect();
+            let closed = points.len() > 2 && points.first() == points.last();
+            let list: Vec<String> = points.iter().map(|(px, py)| format!("{:.1},{:.1}", px, py)).collect();
+            let line_fill = if closed && element.kind != "arrow" { fill.as_str() } else { "none" };
+            let _ = write!(
+                svg,
+                r#"<polyline points="{}" fill="{line_fill}" stroke="{stroke}" stroke-width="{stroke_width}"{dash}/>"#,
+                list.join(" ")
+            );
+            let size = 10.0 + stroke_width * 3.0;
+            if let (Some(_), [.., before, last]) = (&element.end_arrowhead, points.as_slice()) {
+                let _ = write!(svg, r#"<polyline points="{}" fill="none" stroke="{stroke}" stroke-width="{stroke_width}"/>"#, arrowhead(*before, *last, size));
+            }
+            if let (Some(_), [first, after, ..]) = (&element.start_arrowhead, points.as_slice()) {
+                let _ = write!(svg, r#"<polyline points="{}" fill="none" stroke="{stroke}" stroke-width="{stroke_width}"/>"#, arrowhead(*after, *first, size));
+            }
+        }
+        "text" => {
+            let font_size = element.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+            let (anchor, text_x) = match element.text_align.as_deref() {
+                Some("center") => ("middle", cx),
+                Some("right") => ("end", x + w),
+                _ => ("start", x),
+            };
+            let _ = write!(
+                svg,
+                r#"<text font-family="{}" font-size="{font_size}" fill="{stroke}" stroke="none" text-anchor="{anchor}">"#,
+                font(element.font_family)
+            );
+            for (i, line) in element.text.as_deref().unwrap_or("").lines().enumerate() {
+                let line_y = y + font_size + i as f64 * font_size * LINE_HEIGHT;
+                let _ = write!(svg, r#"<tspan x="{text_x:.1}" y="{line_y:.1}">{}</tspan>"#, escape(line));
+            }
+            svg.push_str("</text>");
+        }
+        "image" => match element.file_id.as_ref().and_then(|id| files.get(id)) {
+            Some(file) => {
+                let _ = write!(
+                    svg,
+                    r#"<image x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" preserveAspectRatio="none" href="{}"/>"#,
+                    escape(&file.data_url)
+                );
+            }
+            None => {
+                let _ = write!(svg, r##"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="#f1f3f5" stroke="#adb5bd"/>"##);
+            }
+        },
+        _ => {}
+    }
+    svg.push_str("</g>");
+}
+
+pub(super) fn render(content: &str) -> Result<Rendered, String> {
+    let scene = parse(content)?;
+    let mut bounds = Bounds::new();
+    let mut body = String::new();
+    for element in live(&scene) {
+        if element.is_linear() {
+            for [px, py] in &element.points {
+                bounds.include(element.x + px, element.y + py);
+            }
+        } else {
+            bounds.include(element.x, element.y);
+            bounds.include(element.x + element.width, element.y + element.height);
+        }
+        draw(&mut body, element, &scene.files);
+    }
+    let background = scene.app_state.get("viewBackgroundColor").and_then(|v| v.as_str()).unwrap_or("#ffffff");
+    Ok(bounds.document(&body, Some(background)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_notes_and_rendering() {
+        let note = "---\nexcalidraw-plugin: parsed\n---\n# Excalidraw Data\n## Drawing\n```json\n{\"type\":\"excalidraw\",\"elements\":[\
+            {\"type\":\"arrow\",\"x\":10,\"y\":10,\"points\":[[0,0],[100,40]],\"endArrowhead\":\"arrow\"},\
+            {\"type\":\"text\",\"x\":0,\"y\":60,\"width\":50,\"height\":25,\"text\":\"a < b\"}]}\n```\n%%";
+        assert_eq!(validate(note).unwrap().elements, 2);
+        let rendered = render(note).unwrap();
+        assert_eq!((rendered.width, rendered.height), (150.0, 115.0));
+        assert!(rendered.svg.contains("a &lt; b"));
+        assert_eq!(rendered.svg.matches("<polyline").count(), 2);
+
+        assert!(validate("```compressed-json\nN4Ig\n```").unwrap_err().contains("compression"));
+        assert!(validate(r#"{"type":"canvas","elements":[]}"#).is_err());
+        assert!(validate(r#"{"type":"excalidraw"}"#).is_err());
+    }
+}
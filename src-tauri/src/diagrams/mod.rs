@@ -0,0 +1,355 @@
+//! Excalidraw and draw.io drawings kept in the vault.
+//!
+//! Files are validated before they are saved and rendered to static SVG or
+//! PNG previews for embeds and the file list. Previews are cached under
+//! `.lokus/cache/diagrams` by content hash, so an unchanged drawing is only
+//! rendered once. The renderers draw shapes, connectors, text and embedded
+//! images plainly; hand-drawn strokes and fill patterns are not reproduced.
+//!
+//! Links inside drawings (element links and `[[wikilinks]]` in labels) feed
+//! the link graph, and drawings can be linked to like notes.
+
+mod drawio;
+mod excalidraw;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::links::{LinkKind, RawLink};
+
+const CACHE_DIR: &str = "diagrams";
+/// Previews kept per cache directory before the oldest are removed
+const MAX_CACHED: usize = 200;
+pub(crate) const PADDING: f64 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramKind {
+    Excalidraw,
+    Drawio,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+impl PreviewFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            PreviewFormat::Svg => "svg",
+            PreviewFormat::Png => "png",
+        }
+    }
+}
+
+/// A rendered drawing before it is written anywhere
+pub(crate) struct Rendered {
+    pub svg: String,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramInfo {
+    pub kind: DiagramKind,
+    /// Shapes, connectors and text items in the drawing (first page for draw.io)
+    pub elements: usize,
+    pub pages: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramPreview {
+    /// The cached preview image
+    pub path: String,
+    pub content_hash: String,
+    pub format: PreviewFormat,
+    pub width: u32,
+    pub height: u32,
+    /// Whether the preview was rendered by this call
+    pub rendered: bool,
+}
+
+/// `.excalidraw` and `.excalidraw.md` (the Obsidian plugin format) are
+/// Excalidraw; `.drawio` is draw.io
+pub fn kind_of(path: &Path) -> Option<DiagramKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".excalidraw") || name.ends_with(".excalidraw.md") {
+        Some(DiagramKind::Excalidraw)
+    } else if name.ends_with(".drawio") {
+        Some(DiagramKind::Drawio)
+    } else {
+        None
+    }
+}
+
+/// Drawings that aren't notes; `.excalidraw.md` files are handled as notes
+pub(crate) fn is_diagram(rel: &str) -> bool {
+    kind_of(Path::new(rel)).is_some() && !crate::links::is_note(rel)
+}
+
+pub fn validate(path: &Path, content: &str) -> Result<DiagramInfo, String> {
+    match kind_of(path).ok_or_else(|| format!("Not a diagram: {}", path.display()))? {
+        DiagramKind::Excalidraw => excalidraw::validate(content),
+        DiagramKind::Drawio => drawio::validate(content),
+    }
+}
+
+pub(crate) fn render(path: &Path, content: &str) -> Result<Rendered, String> {
+    match kind_of(path).ok_or_else(|| format!("Not a diagram: {}", path.display()))? {
+        DiagramKind::Excalidraw => excalidraw::render(content),
+        DiagramKind::Drawio => drawio::render(content),
+    }
+}
+
+/// Links drawn into a diagram, for the link graph. `line` is the position of
+/// the element in the drawing.
+pub(crate) fn parse_links(rel: &str, content: &str) -> Vec<RawLink> {
+    let labelled = match kind_of(Path::new(rel)) {
+        Some(DiagramKind::Excalidraw) => excalidraw::links(content),
+        Some(DiagramKind::Drawio) => drawio::links(content),
+        None => return Vec::new(),
+    };
+    let mut links = Vec::new();
+    for (index, (link, label)) in labelled.into_iter().enumerate() {
+        if let Some(link) = link.filter(|l| !l.trim().is_empty()) {
+            let parsed = crate::links::parse_links(&link);
+            if !parsed.is_empty() {
+                links.extend(parsed.into_iter().map(|l| RawLink { line: index + 1, ..l }));
+            } else if !link.contains("://") && !link.starts_with("mailto:") {
+                links.push(RawLink {
+                    target: link.trim().to_string(),
+                    kind: LinkKind::Markdown,
+                    line: index + 1,
+                    context: format!("Diagram link: {}", link.trim()),
+                });
+            }
+        }
+        if let Some(label) = label {
+            links.extend(crate::links::parse_links(&label).into_iter().map(|l| RawLink { line: index + 1, ..l }));
+        }
+    }
+    links
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Axis-aligned extent of everything drawn, grown one point at a time
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Bounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Bounds {
+    pub fn new() -> Self {
+        Self { min_x: f64::INFINITY, min_y: f64::INFINITY, max_x: f64::NEG_INFINITY, max_y: f64::NEG_INFINITY }
+    }
+
+    pub fn include(&mut self, x: f64, y: f64) {
+        if x.is_finite() && y.is_finite() {
+            self.min_x = self.min_x.min(x);
+            self.min_y = self.min_y.min(y);
+            self.max_x = self.max_x.max(x);
+            self.max_y = self.max_y.max(y);
+        }
+    }
+
+    /// Wrap drawn content in an SVG document sized to the bounds plus padding
+    pub fn document(self, body: &str, background: Option<&str>) -> Rendered {
+        let (min_x, min_y, max_x, max_y) = if self.min_x.is_finite() {
+            (self.min_x, self.min_y, self.max_x, self.max_y)
+        } else {
+            (0.0, 0.0, 0.0, 0.0)
+        };
+        let width = (max_x - min_x + 2.0 * PADDING).ceil();
+        let height = (max_y - min_y + 2.0 * PADDING).ceil();
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        if let Some(background) = background {
+            svg.push_str(&format!(r#"<rect width="100%" height="100%" fill="{}"/>"#, escape(background)));
+        }
+        svg.push_str(&format!(
+            r#"<g transform="translate({:.1} {:.1})" stroke-linecap="round" stroke-linejoin="round">"#,
+            PADDING - min_x,
+            PADDING - min_y
+        ));
+        svg.push_str(body);
+        svg.push_str("</g></svg>");
+        Rendered { svg, width, height }
+    }
+}
+
+/// Two strokes at the end of a segment, for arrowheads
+pub(crate) fn arrowhead(from: (f64, f64), to: (f64, f64), size: f64) -> String {
+    let angle = (to.1 - from.1).atan2(to.0 - from.0);
+    let spread = 0.45;
+    let left = (to.0 - size * (angle - spread).cos(), to.1 - size * (angle - spread).sin());
+    let right = (to.0 - size * (angle + spread).cos(), to.1 - size * (angle + spread).sin());
+    format!("{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}", left.0, left.1, to.0, to.1, right.0, right.1)
+}
+
+fn cache_dir(path: &Path) -> PathBuf {
+    match crate::handlers::files::find_workspace_root(path.parent().unwrap_or(path)) {
+        Ok(root) => root.join(".lokus").join("cache").join(CACHE_DIR),
+        Err(_) => std::env::temp_dir().join("lokus-diagrams"),
+    }
+}
+
+/// Drop the least recently written previews once the cache is over its cap
+fn prune_cache(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    if files.len() <= MAX_CACHED {
+        return;
+    }
+    files.sort();
+    for (_, path) in &files[..files.len() - MAX_CACHED] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn render_preview(path: &Path, format: PreviewFormat, scale: f32) -> Result<DiagramPreview, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let content_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+    let key = match format {
+        PreviewFormat::Svg => content_hash[..32].to_string(),
+        PreviewFormat::Png => format!("{}@{}", &content_hash[..32], scale),
+    };
+    let dir = cache_dir(path);
+    let cached = dir.join(format!("{}.{}", key, format.extension()));
+    let size_file = dir.join(format!("{}.size", key));
+
+    if let Some((width, height)) = cached
+        .exists()
+        .then(|| fs::read_to_string(&size_file).ok())
+        .flatten()
+        .and_then(|s| s.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?))))
+    {
+        return Ok(DiagramPreview {
+            path: cached.to_string_lossy().to_string(),
+            content_hash,
+            format,
+            width,
+            height,
+            rendered: false,
+        });
+    }
+
+    let rendered = render(path, &content)?;
+    let (bytes, width, height) = match format {
+        PreviewFormat::Svg => (rendered.svg.into_bytes(), rendered.width as u32, rendered.height as u32),
+        PreviewFormat::Png => (
+            crate::canvas::svg_to_png(&rendered.svg, scale)?,
+            (rendered.width * scale as f64).round() as u32,
+            (rendered.height * scale as f64).round() as u32,
+        ),
+    };
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create preview cache: {}", e))?;
+    fs::write(&cached, bytes).map_err(|e| format!("Failed to write preview: {}", e))?;
+    let _ = fs::write(&size_file, format!("{}x{}", width, height));
+    prune_cache(&dir);
+
+    Ok(DiagramPreview {
+        path: cached.to_string_lossy().to_string(),
+        content_hash,
+        format,
+        width,
+        height,
+        rendered: true,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Render a preview of the diagram at `path` (SVG unless `format` says PNG),
+/// reusing the cached image when the drawing hasn't changed
+#[tauri::command]
+pub async fn diagram_render_preview(
+    path: String,
+    format: Option<PreviewFormat>,
+    scale: Option<f32>,
+) -> Result<DiagramPreview, String> {
+    let scale = scale.unwrap_or(1.0).clamp(0.1, 8.0);
+    tokio::task::spawn_blocking(move || render_preview(Path::new(&path), format.unwrap_or_default(), scale))
+        .await
+        .map_err(|e| format!("Diagram preview failed: {}", e))?
+}
+
+/// Check that the diagram at `path` can be read and rendered
+#[tauri::command]
+pub fn diagram_validate(path: String) -> Result<DiagramInfo, String> {
+    let path = PathBuf::from(path);
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    validate(&path, &content)
+}
+
+/// Save a diagram, refusing content that isn't a valid drawing of its type
+#[tauri::command]
+pub fn diagram_save(path: String, content: String) -> Result<DiagramInfo, String> {
+    let info = validate(Path::new(&path), &content)?;
+    crate::handlers::files::write_file_content(path, content)?;
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinds_and_links() {
+        assert_eq!(kind_of(Path::new("a/Sketch.excalidraw")), Some(DiagramKind::Excalidraw));
+        assert_eq!(kind_of(Path::new("Sketch.excalidraw.md")), Some(DiagramKind::Excalidraw));
+        assert_eq!(kind_of(Path::new("Flow.drawio")), Some(DiagramKind::Drawio));
+        assert_eq!(kind_of(Path::new("Note.md")), None);
+        assert!(is_diagram("Flow.drawio"));
+        assert!(!is_diagram("Sketch.excalidraw.md"));
+
+        let scene = r#"{"type":"excalidraw","version":2,"elements":[
+            {"type":"rectangle","x":0,"y":0,"width":100,"height":50,"link":"[[Plan]]"},
+            {"type":"text","x":10,"y":10,"width":80,"height":20,"text":"see [[Ideas]]"},
+            {"type":"ellipse","x":0,"y":0,"width":10,"height":10,"link":"https://example.com"},
+            {"type":"text","x":0,"y":0,"width":10,"height":10,"text":"[[Gone]]","isDeleted":true}
+        ]}"#;
+        let targets: Vec<String> = parse_links("Sketch.excalidraw", scene).into_iter().map(|l| l.target).collect();
+        assert_eq!(targets, vec!["Plan".to_string(), "Ideas".to_string()]);
+    }
+
+    #[test]
+    fn test_preview_is_cached_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        let path = dir.path().join("Flow.drawio");
+        fs::write(&path, r#"<mxfile><diagram name="Page-1"><mxGraphModel><root><mxCell id="0"/><mxCell id="1" parent="0"/>
+            <mxCell id="a" value="Start" vertex="1" parent="1"><mxGeometry x="0" y="0" width="120" height="60" as="geometry"/></mxCell>
+            </root></mxGraphModel></diagram></mxfile>"#).unwrap();
+
+        let first = render_preview(&path, PreviewFormat::Svg, 1.0).unwrap();
+        assert!(first.rendered);
+        assert!(first.path.starts_with(&*dir.path().join(".lokus/cache/diagrams").to_string_lossy()));
+        assert_eq!((first.width, first.height), (160, 100));
+        let second = render_preview(&path, PreviewFormat::Svg, 1.0).unwrap();
+        assert!(!second.rendered);
+        assert_eq!(second.path, first.path);
+
+        fs::write(&path, "<mxfile><diagram>not base64!</diagram></mxfile>").unwrap();
+        assert!(render_preview(&path, PreviewFormat::Svg, 1.0).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! Embedding backends: a local ONNX sentence-embedding model run with `ort`,
+//! or a remote OpenAI-compatible `/embeddings` endpoint.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest input fed to the local model; longer chunks are truncated
+const MAX_TOKENS: usize = 256;
+const MAX_RETRIES: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum BackendConfig {
+    /// Folder holding `model.onnx` and `tokenizer.json` (e.g. all-MiniLM-L6-v2)
+    #[serde(rename_all = "camelCase")]
+    Local { model_dir: String },
+    /// OpenAI-compatible API; the key is kept in secure storage
+    #[serde(rename_all = "camelCase")]
+    Remote { base_url: String, model: String },
+}
+
+impl BackendConfig {
+    /// Identifies the vectors' space: changing it invalidates the index
+    pub fn model_id(&self) -> String {
+        match self {
+            BackendConfig::Local { model_dir } => format!("local:{}", model_dir),
+            BackendConfig::Remote { base_url, model } => format!("remote:{}#{}", base_url.trim_end_matches('/'), model),
+        }
+    }
+}
+
+pub(super) struct LocalModel {
+    session: Mutex<ort::session::Session>,
+    tokenizer: tokenizers::Tokenizer,
+    uses_token_types: bool,
+}
+
+/// Loaded models, shared between workspaces using the same folder
+static LOCAL_MODELS: Lazy<Mutex<HashMap<PathBuf, Arc<LocalModel>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+impl LocalModel {
+    fn load(dir: &Path) -> Result<Arc<Self>, String> {
+        let mut models = LOCAL_MODELS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(model) = models.get(dir) {
+            return Ok(model.clone());
+        }
+        let err = |e: ort::Error| format!("Failed to load embedding model: {}", e);
+        let session = ort::session::Session::builder()
+            .map_err(err)?
+            .with_optimization_level(ort::session::builder::GraphOptimizationLevel::Level3)
+            .map_err(err)?
+            .commit_from_file(dir.join("model.onnx"))
+            .map_err(err)?;
+        let mut tokenizer = tokenizers::Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| format!("Failed to load tokenizer: {}", e))?;
+        tokenizer
+            .with_truncation(Some(tokenizers::TruncationParams { max_length: MAX_TOKENS, ..Default::default() }))
+            .map_err(|e| format!("Failed to configure tokenizer: {}", e))?;
+        tokenizer.with_padding(Some(tokenizers::PaddingParams::default()));
+
+        let uses_token_types = session.inputs.iter().any(|input| input.name == "token_type_ids");
+        let model = Arc::new(Self { session: Mutex::new(session), tokenizer, uses_token_types });
+        models.insert(dir.to_path_buf(), model.clone());
+        Ok(model)
+    }
+
+    /// Mean-pooled token embeddings, masking out padding
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        use ort::value::Tensor;
+
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| format!("Tokenization failed: {}", e))?;
+        let batch = encodings.len();
+        let seq = encodings.first().map_or(0, |e| e.get_ids().len());
+        if batch == 0 || seq == 0 {
+            return Ok(vec![Vec::new(); batch]);
+        }
+        let flat = |f: fn(&tokenizers::Encoding) -> &[u32]| -> Vec<i64> {
+            encodings.iter().flat_map(|e| f(e).iter().map(|v| *v as i64)).collect()
+        };
+        let ids = flat(|e| e.get_ids());
+        let mask = flat(|e| e.get_attention_mask());
+        let types = flat(|e| e.get_type_ids());
+
+        let err = |e: ort::Error| format!("Embedding failed: {}", e);
+        let mut inputs = ort::inputs![
+            "input_ids" => Tensor::from_array(([batch, seq], ids)).map_err(err)?,
+            "attention_mask" => Tensor::from_array(([batch, seq], mask.clone())).map_err(err)?,
+        ]
+        .map_err(err)?;
+        if self.uses_token_types {
+            inputs.push(("token_type_ids".into(), Tensor::from_array(([batch, seq], types)).map_err(err)?.into()));
+        }
+
+        let session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        let outputs = session.run(inputs).map_err(err)?;
+        let hidden = outputs[0].try_extract_tensor::<f32>().map_err(err)?;
+        let dim = *hidden.shape().last().ok_or("Embedding model returned no output")?;
+        let values = hidden.as_slice().ok_or("Embedding output is not contiguous")?;
+
+        Ok((0..batch)
+            .map(|b| {
+                let mut pooled = vec![0.0f32; dim];
+                let mut count = 0.0f32;
+                for t in 0..seq {
+                    if mask[b * seq + t] == 0 {
+                        continue;
+                    }
+                    let row = &values[(b * seq + t) * dim..(b * seq + t + 1) * dim];
+                    pooled.iter_mut().zip(row).for_each(|(p, v)| *p += v);
+                    count += 1.0;
+                }
+                pooled.iter_mut().for_each(|p| *p /= count.max(1.0));
+                pooled
+            })
+            .collect())
+    }
+}
+
+/// Spaces out remote requests to stay under a requests-per-minute budget
+struct RateLimiter {
+    interval: Duration,
+    next: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep(*next - now).await;
+        }
+        *next = Instant::now() + self.interval;
+    }
+
+    /// Honour a server-requested pause before the next request
+    async fn back_off(&self, delay: Duration) {
+        let mut next = self.next.lock().await;
+        *next = (*next).max(Instant::now() + delay);
+    }
+}
+
+#[derive(Deserialize)]
+struct RemoteResponse {
+    data: Vec<RemoteEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct RemoteEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+pub(super) struct RemoteClient {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    limiter: RateLimiter,
+}
+
+impl RemoteClient {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let body = serde_json::json!({ "model": self.model, "input": texts });
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            self.limiter.wait().await;
+            let mut request = self.client.post(&self.url).json(&body);
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+            let response = request.send().await.map_err(|e| format!("Embedding request failed: {}", e))?;
+            let status = response.status();
+
+            if (status.as_u16() == 429 || status.is_server_error()) && attempt < MAX_RETRIES {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map_or(Duration::from_secs(2u64.pow(attempt)), Duration::from_secs);
+                tracing::warn!(status = status.as_u16(), "Embedding endpoint asked to slow down");
+                self.limiter.back_off(retry_after).await;
+                continue;
+            }
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(format!("Embedding endpoint returned {}: {}", status, text.trim()));
+            }
+
+            let mut parsed: RemoteResponse =
+                response.json().await.map_err(|e| format!("Invalid embedding response: {}", e))?;
+            if parsed.data.len() != texts.len() {
+                return Err(format!("Expected {} embeddings, got {}", texts.len(), parsed.data.len()));
+            }
+            parsed.data.sort_by_key(|d| d.index);
+            return Ok(parsed.data.into_iter().map(|d| d.embedding).collect());
+        }
+    }
+}
+
+pub(super) enum Embedder {
+    Local(Arc<LocalModel>),
+    Remote(RemoteClient),
+}
+
+impl Embedder {
+    pub fn new(config: &BackendConfig, api_key: Option<String>, requests_per_minute: u32) -> Result<Self, String> {
+        match config {
+            BackendConfig::Local { model_dir } => Ok(Embedder::Local(LocalModel::load(Path::new(model_dir))?)),
+            BackendConfig::Remote { base_url, model } => Ok(Embedder::Remote(RemoteClient {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(60))
+                    .build()
+                    .map_err(|e| format!("Failed to create HTTP client: {}", e))?,
+                url: format!("{}/embeddings", base_url.trim_end_matches('/')),
+                model: model.clone(),
+                api_key,
+                limiter: RateLimiter::new(requests_per_minute),
+            })),
+        }
+    }
+
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        match self {
+            Embedder::Local(model) => {
+                let model = model.clone();
+                let texts = texts.to_vec();
+                tokio::task::spawn_blocking(move || model.embed(&texts))
+                    .await
+                    .map_err(|e| format!("Embedding task failed: {}", e))?
+            }
+            Embedder::Remote(client) => client.embed(texts).await,
+        }
+    }
+}
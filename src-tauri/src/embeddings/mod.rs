@@ -0,0 +1,433 @@
+//! Semantic search over note embeddings.
+//!
+//! Notes are split into paragraph-aligned chunks and embedded with the
+//! workspace's configured backend (a local ONNX model or an OpenAI-compatible
+//! endpoint, see `backend`). Vectors live in `.lokus/embeddings` (`store`),
+//! keyed by each note's content hash so unchanged notes are never re-embedded.
+//!
+//! Once a workspace's index is loaded, saved notes are queued and embedded in
+//! the background in batches; remote requests are paced to the configured
+//! requests-per-minute and back off when the server asks.
+
+mod backend;
+mod store;
+
+pub use backend::BackendConfig;
+
+use backend::Embedder;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use store::{chunk_text, VectorIndex};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+const SETTINGS_FILE: &str = "settings.json";
+const API_KEY_STORAGE: &str = "embeddings-api-key";
+const PROGRESS_EVENT: &str = "embeddings-progress";
+/// Quiet period after a save before queued notes are embedded
+const DEBOUNCE: Duration = Duration::from_secs(3);
+const SNIPPET_CHARS: usize = 280;
+const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", "node_modules", ".trash"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmbeddingSettings {
+    pub enabled: bool,
+    pub backend: Option<BackendConfig>,
+    /// Chunks sent per embedding call
+    pub batch_size: usize,
+    /// Remote backends only
+    pub requests_per_minute: u32,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self { enabled: false, backend: None, batch_size: 16, requests_per_minute: 60 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingStatus {
+    pub enabled: bool,
+    pub model: Option<String>,
+    pub indexed_notes: usize,
+    pub pending: usize,
+    pub has_api_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticMatch {
+    pub path: String,
+    pub title: String,
+    pub score: f32,
+    /// Text of the best-matching chunk
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub path: Option<String>,
+}
+
+struct Workspace {
+    settings: EmbeddingSettings,
+    index: VectorIndex,
+    embedder: Option<Arc<Embedder>>,
+    pending: BTreeSet<String>,
+    worker_running: bool,
+}
+
+static WORKSPACES: Lazy<Mutex<HashMap<PathBuf, Workspace>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// --- Settings ---
+
+fn load_settings(root: &Path) -> EmbeddingSettings {
+    fs::read_to_string(store::index_dir(root).join(SETTINGS_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(root: &Path, settings: &EmbeddingSettings) -> Result<(), String> {
+    let dir = store::index_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create embeddings folder: {}", e))?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(dir.join(SETTINGS_FILE), json).map_err(|e| format!("Failed to save embedding settings: {}", e))
+}
+
+fn api_key() -> Option<String> {
+    let storage = crate::secure_storage::SecureStorage::new().ok()?;
+    storage.retrieve::<String>(API_KEY_STORAGE).ok().flatten().filter(|k| !k.is_empty())
+}
+
+fn model_id(settings: &EmbeddingSettings) -> String {
+    settings.backend.as_ref().map(BackendConfig::model_id).unwrap_or_default()
+}
+
+// --- Workspace state ---
+
+fn with_workspace<R>(root: &Path, f: impl FnOnce(&mut Workspace) -> R) -> R {
+    let mut workspaces = WORKSPACES.lock().unwrap_or_else(|e| e.into_inner());
+    let fresh = !workspaces.contains_key(root);
+    let workspace = workspaces.entry(root.to_path_buf()).or_insert_with(|| {
+        let settings = load_settings(root);
+        let index = VectorIndex::load(root, &model_id(&settings));
+        Workspace { settings, index, embedder: None, pending: BTreeSet::new(), worker_running: false }
+    });
+    let result = f(workspace);
+    // Catch up on edits made while the index wasn't loaded
+    if fresh && workspace.settings.enabled && workspace.settings.backend.is_some() {
+        let root = root.to_path_buf();
+        tauri::async_runtime::spawn_blocking(move || queue_stale(&root));
+    }
+    result
+}
+
+fn embedder(root: &Path) -> Result<Arc<Embedder>, String> {
+    let (settings, cached) = with_workspace(root, |ws| (ws.settings.clone(), ws.embedder.clone()));
+    if let Some(embedder) = cached {
+        return Ok(embedder);
+    }
+    let backend = settings.backend.as_ref().ok_or("No embedding backend is configured")?;
+    let embedder = Arc::new(Embedder::new(backend, api_key(), settings.requests_per_minute)?);
+    with_workspace(root, |ws| ws.embedder = Some(embedder.clone()));
+    Ok(embedder)
+}
+
+fn note_paths(root: &Path) -> Vec<String> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !EXCLUDED_DIRS.iter().any(|d| e.file_name() == *d))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| crate::links::relative_path(root, e.path()))
+        .filter(|rel| crate::links::is_note(rel))
+        .collect()
+}
+
+/// Note body to embed, or `None` for notes that shouldn't be in the index
+fn embeddable_text(root: &Path, rel: &str) -> Option<String> {
+    let content = fs::read_to_string(root.join(rel)).ok()?;
+    if crate::encryption::is_encrypted(&content) {
+        return None;
+    }
+    Some(crate::export::strip_frontmatter(&content).to_string())
+}
+
+/// Queue notes whose content changed since they were embedded, and drop
+/// deleted ones
+fn queue_stale(root: &Path) {
+    let hashes: HashMap<String, String> = note_paths(root)
+        .into_iter()
+        .filter_map(|rel| {
+            let text = embeddable_text(root, &rel)?;
+            Some((rel, blake3::hash(text.as_bytes()).to_hex().to_string()))
+        })
+        .collect();
+    let stale: Vec<String> = with_workspace(root, |ws| {
+        ws.index.notes.retain(|rel, _| hashes.contains_key(rel));
+        hashes
+            .iter()
+            .filter(|(rel, hash)| ws.index.notes.get(*rel).map_or(true, |note| note.hash != **hash))
+            .map(|(rel, _)| rel.clone())
+            .collect()
+    });
+    if !stale.is_empty() {
+        enqueue(root, stale);
+    }
+}
+
+fn enqueue(root: &Path, rels: Vec<String>) {
+    let start = with_workspace(root, |ws| {
+        ws.pending.extend(rels);
+        let start = !ws.worker_running;
+        ws.worker_running = true;
+        start
+    });
+    if start {
+        let root = root.to_path_buf();
+        tauri::async_runtime::spawn(async move { run_worker(root).await });
+    }
+}
+
+async fn run_worker(root: PathBuf) {
+    loop {
+        tokio::time::sleep(DEBOUNCE).await;
+        let batch: Vec<String> = with_workspace(&root, |ws| {
+            let batch: Vec<String> = std::mem::take(&mut ws.pending).into_iter().collect();
+            if batch.is_empty() {
+                ws.worker_running = false;
+            }
+            batch
+        });
+        if batch.is_empty() {
+            return;
+        }
+        if let Err(e) = embed_notes(&root, &batch, false, |_, _| {}).await {
+            tracing::warn!("Background embedding failed: {}", e);
+        }
+    }
+}
+
+/// Embed `rels` (skipping unchanged notes unless `force`), saving the index
+/// afterwards. `progress(done, rel)` is called after each note.
+async fn embed_notes(root: &Path, rels: &[String], force: bool, mut progress: impl FnMut(usize, &str)) -> Result<usize, String> {
+    let (enabled, batch_size) = with_workspace(root, |ws| (ws.settings.enabled, ws.settings.batch_size.max(1)));
+    if !enabled {
+        return Ok(0);
+    }
+    let embedder = embedder(root)?;
+    let mut embedded = 0;
+    // Changed notes waiting for vectors; embedded once they add up to a batch
+    let mut jobs: Vec<(String, String, String, Vec<store::Chunk>)> = Vec::new();
+
+    for (done, rel) in rels.iter().enumerate() {
+        match embeddable_text(root, rel) {
+            None => {
+                with_workspace(root, |ws| ws.index.notes.remove(rel));
+            }
+            Some(text) => {
+                let hash = blake3::hash(text.as_bytes()).to_hex().to_string();
+                let unchanged = with_workspace(root, |ws| ws.index.notes.get(rel).map_or(false, |n| n.hash == hash));
+                if force || !unchanged {
+                    let chunks = chunk_text(&text);
+                    jobs.push((rel.clone(), hash, text, chunks));
+                }
+            }
+        }
+        let queued: usize = jobs.iter().map(|(_, _, _, chunks)| chunks.len()).sum();
+        if queued >= batch_size || done + 1 == rels.len() {
+            embedded += embed_batch(root, &embedder, std::mem::take(&mut jobs), batch_size).await?;
+        }
+        progress(done + 1, rel);
+    }
+
+    with_workspace(root, |ws| ws.index.save(root))?;
+    Ok(embedded)
+}
+
+/// Embed the chunks of `jobs` (`rel, hash, text, chunks`) `batch_size` at a
+/// time and store each note's vectors
+async fn embed_batch(
+    root: &Path,
+    embedder: &Embedder,
+    jobs: Vec<(String, String, String, Vec<store::Chunk>)>,
+    batch_size: usize,
+) -> Result<usize, String> {
+    let texts: Vec<String> = jobs
+        .iter()
+        .flat_map(|(_, _, text, chunks)| chunks.iter().map(|c| text[c.start..c.end].to_string()))
+        .collect();
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(batch_size) {
+        vectors.extend(embedder.embed(batch).await?);
+    }
+
+    let mut vectors = vectors.into_iter();
+    let count = jobs.len();
+    with_workspace(root, |ws| {
+        for (rel, hash, _, chunks) in jobs {
+            let note_vectors: Vec<Vec<f32>> = vectors.by_ref().take(chunks.len()).collect();
+            ws.index.upsert(&rel, hash, chunks, note_vectors)?;
+        }
+        Ok::<_, String>(())
+    })?;
+    Ok(count)
+}
+
+fn to_match(root: &Path, rel: String, score: f32, chunk: store::Chunk) -> SemanticMatch {
+    let snippet = embeddable_text(root, &rel)
+        .and_then(|text| text.get(chunk.start..chunk.end).map(|s| s.trim().chars().take(SNIPPET_CHARS).collect()))
+        .unwrap_or_default();
+    let name = rel.rsplit('/').next().unwrap_or(&rel);
+    SemanticMatch {
+        title: name.strip_suffix(".md").unwrap_or(name).to_string(),
+        path: root.join(&rel).to_string_lossy().to_string(),
+        score,
+        snippet,
+    }
+}
+
+// --- Hooks ---
+
+/// Queue a saved note for re-embedding in workspaces whose index is loaded
+pub fn notify_file_changed(file_path: &str) {
+    let path = Path::new(file_path);
+    let roots: Vec<(PathBuf, String)> = {
+        let workspaces = WORKSPACES.lock().unwrap_or_else(|e| e.into_inner());
+        workspaces
+            .iter()
+            .filter(|(_, ws)| ws.settings.enabled && ws.settings.backend.is_some())
+            .filter_map(|(root, _)| Some((root.clone(), crate::links::relative_path(root, path)?)))
+            .filter(|(_, rel)| crate::links::is_note(rel))
+            .collect()
+    };
+    for (root, rel) in roots {
+        enqueue(&root, vec![rel]);
+    }
+}
+
+pub fn notify_file_removed(file_path: &str) {
+    let path = Path::new(file_path);
+    let mut workspaces = WORKSPACES.lock().unwrap_or_else(|e| e.into_inner());
+    for (root, ws) in workspaces.iter_mut() {
+        if let Some(rel) = crate::links::relative_path(root, path) {
+            let prefix = format!("{}/", rel);
+            ws.index.notes.retain(|note, _| *note != rel && !note.starts_with(&prefix));
+            ws.pending.retain(|note| *note != rel && !note.starts_with(&prefix));
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn embeddings_settings_get(workspace_path: String) -> EmbeddingSettings {
+    with_workspace(Path::new(&workspace_path), |ws| ws.settings.clone())
+}
+
+/// Save settings. `api_key` replaces the stored key for remote backends; an
+/// empty string removes it. Switching models starts a fresh index.
+#[tauri::command]
+pub fn embeddings_settings_set(workspace_path: String, settings: EmbeddingSettings, api_key: Option<String>) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    if let Some(key) = api_key {
+        let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+        let saved = if key.is_empty() { storage.delete(API_KEY_STORAGE) } else { storage.store(API_KEY_STORAGE, &key) };
+        saved.map_err(|e| format!("Failed to save API key: {}", e))?;
+    }
+    save_settings(&root, &settings)?;
+    with_workspace(&root, |ws| {
+        if model_id(&ws.settings) != model_id(&settings) {
+            ws.index = VectorIndex::new(&model_id(&settings));
+        }
+        ws.settings = settings;
+        ws.embedder = None;
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn embeddings_status(workspace_path: String) -> EmbeddingStatus {
+    let has_api_key = api_key().is_some();
+    with_workspace(Path::new(&workspace_path), |ws| EmbeddingStatus {
+        enabled: ws.settings.enabled,
+        model: ws.settings.backend.as_ref().map(BackendConfig::model_id),
+        indexed_notes: ws.index.notes.len(),
+        pending: ws.pending.len(),
+        has_api_key,
+    })
+}
+
+/// Embed every note in the workspace; `force` re-embeds unchanged notes too.
+/// Returns the number of notes embedded.
+#[tauri::command]
+pub async fn embeddings_reindex(app: AppHandle, workspace_path: String, force: Option<bool>) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    if !with_workspace(&root, |ws| ws.settings.enabled) {
+        return Err("Semantic search is disabled for this workspace".to_string());
+    }
+    let scan_root = root.clone();
+    let notes = tokio::task::spawn_blocking(move || note_paths(&scan_root))
+        .await
+        .map_err(|e| format!("Scan failed: {}", e))?;
+    let total = notes.len();
+    let existing: BTreeSet<&String> = notes.iter().collect();
+    with_workspace(&root, |ws| ws.index.notes.retain(|rel, _| existing.contains(rel)));
+
+    embed_notes(&root, &notes, force.unwrap_or(false), |processed, rel| {
+        let progress = EmbeddingProgress { processed, total, path: Some(rel.to_string()) };
+        if let Err(e) = app.emit(PROGRESS_EVENT, &progress) {
+            tracing::warn!("Failed to emit embedding progress: {}", e);
+        }
+    })
+    .await
+}
+
+/// The `k` notes closest in meaning to `query`
+#[tauri::command]
+pub async fn semantic_search(workspace_path: String, query: String, k: Option<usize>) -> Result<Vec<SemanticMatch>, String> {
+    let root = PathBuf::from(&workspace_path);
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    if !with_workspace(&root, |ws| ws.settings.enabled) {
+        return Err("Semantic search is disabled for this workspace".to_string());
+    }
+    let mut vector = embedder(&root)?
+        .embed(&[query])
+        .await?
+        .pop()
+        .ok_or("Embedding backend returned no vector")?;
+    store::normalize(&mut vector);
+    let hits = with_workspace(&root, |ws| ws.index.search(&vector, k.unwrap_or(10), None));
+    Ok(hits.into_iter().map(|(rel, score, chunk)| to_match(&root, rel, score, chunk)).collect())
+}
+
+/// The `k` notes most similar to the note at `path`
+#[tauri::command]
+pub async fn find_similar_notes(workspace_path: String, path: String, k: Option<usize>) -> Result<Vec<SemanticMatch>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let rel = crate::links::relative_path(&root, Path::new(&path)).ok_or("Note is not inside the workspace")?;
+    if !with_workspace(&root, |ws| ws.settings.enabled) {
+        return Err("Semantic search is disabled for this workspace".to_string());
+    }
+    // Make sure the note itself is current before comparing against it
+    embed_notes(&root, std::slice::from_ref(&rel), false, |_, _| {}).await?;
+    let hits = with_workspace(&root, |ws| {
+        let vector = ws.index.note_vector(&rel)?;
+        Some(ws.index.search(&vector, k.unwrap_or(10), Some(&rel)))
+    })
+    .ok_or("This note has no text to compare")?;
+    Ok(hits.into_iter().map(|(rel, score, chunk)| to_match(&root, rel, score, chunk)).collect())
+}
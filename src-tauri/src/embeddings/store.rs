@@ -0,0 +1,256 @@
+//! On-disk vector index.
+//!
+//! `.lokus/embeddings/index.json` holds the model id, dimension and, for each
+//! note, its content hash and chunk ranges; `vectors.bin` holds every chunk
+//! vector as little-endian `f32`s in the same order. Vectors are normalized
+//! when stored, so similarity is a dot product.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const INDEX_VERSION: u32 = 1;
+const META_FILE: &str = "index.json";
+const VECTORS_FILE: &str = "vectors.bin";
+/// Chunks are cut at paragraph breaks once they reach this many characters
+const CHUNK_TARGET: usize = 1200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// Byte range of the chunk in the note body
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NoteMeta {
+    hash: String,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexMeta {
+    version: u32,
+    model: String,
+    dimension: usize,
+    notes: BTreeMap<String, NoteMeta>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NoteVectors {
+    pub hash: String,
+    pub chunks: Vec<Chunk>,
+    /// `chunks.len() * dimension` values, one normalized row per chunk
+    pub vectors: Vec<f32>,
+}
+
+#[derive(Debug, Default)]
+pub struct VectorIndex {
+    pub model: String,
+    pub dimension: usize,
+    pub notes: BTreeMap<String, NoteVectors>,
+}
+
+pub fn index_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join("embeddings")
+}
+
+pub fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > f32::EPSILON {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Split a note body into chunks of roughly `CHUNK_TARGET` characters,
+/// breaking between paragraphs where possible
+pub fn chunk_text(text: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut last_break = None;
+    let mut offset = 0;
+    for paragraph in text.split_inclusive("\n\n") {
+        offset += paragraph.len();
+        if offset - start >= CHUNK_TARGET {
+            // Cut before this paragraph unless it alone is oversized
+            let end = last_break.filter(|b| *b > start).unwrap_or(offset);
+            chunks.push(Chunk { start, end });
+            start = end;
+        }
+        last_break = Some(offset);
+    }
+    if start < text.len() {
+        chunks.push(Chunk { start, end: text.len() });
+    }
+    // A long paragraph can still exceed the target; hard-split it on char boundaries
+    chunks
+        .into_iter()
+        .flat_map(|chunk| {
+            let mut pieces = Vec::new();
+            let mut piece_start = chunk.start;
+            while chunk.end - piece_start > CHUNK_TARGET * 2 {
+                let mut cut = piece_start + CHUNK_TARGET;
+                while !text.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                pieces.push(Chunk { start: piece_start, end: cut });
+                piece_start = cut;
+            }
+            pieces.push(Chunk { start: piece_start, end: chunk.end });
+            pieces
+        })
+        .filter(|chunk| !text[chunk.start..chunk.end].trim().is_empty())
+        .collect()
+}
+
+impl VectorIndex {
+    pub fn new(model: &str) -> Self {
+        Self { model: model.to_string(), dimension: 0, notes: BTreeMap::new() }
+    }
+
+    /// Load the index, or start an empty one if it's missing, unreadable or
+    /// was built with another model
+    pub fn load(root: &Path, model: &str) -> Self {
+        let dir = index_dir(root);
+        let meta: Option<IndexMeta> = fs::read_to_string(dir.join(META_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .filter(|meta: &IndexMeta| meta.version == INDEX_VERSION && meta.model == model);
+        let (Some(meta), Ok(bytes)) = (meta, fs::read(dir.join(VECTORS_FILE))) else {
+            return Self::new(model);
+        };
+
+        let values: Vec<f32> = bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        let mut notes = BTreeMap::new();
+        let mut offset = 0;
+        for (rel, note) in meta.notes {
+            let len = note.chunks.len() * meta.dimension;
+            let Some(vectors) = values.get(offset..offset + len) else {
+                return Self::new(model);
+            };
+            notes.insert(rel, NoteVectors { hash: note.hash, chunks: note.chunks, vectors: vectors.to_vec() });
+            offset += len;
+        }
+        Self { model: meta.model, dimension: meta.dimension, notes }
+    }
+
+    pub fn save(&self, root: &Path) -> Result<(), String> {
+        let dir = index_dir(root);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create embeddings folder: {}", e))?;
+
+        let mut bytes = Vec::new();
+        let mut meta = IndexMeta {
+            version: INDEX_VERSION,
+            model: self.model.clone(),
+            dimension: self.dimension,
+            notes: BTreeMap::new(),
+        };
+        for (rel, note) in &self.notes {
+            bytes.extend(note.vectors.iter().flat_map(|v| v.to_le_bytes()));
+            meta.notes.insert(rel.clone(), NoteMeta { hash: note.hash.clone(), chunks: note.chunks.clone() });
+        }
+        let json = serde_json::to_string(&meta).map_err(|e| e.to_string())?;
+
+        // Vectors first: a stale index.json over new vectors fails the length check and rebuilds
+        let write = |name: &str, data: &[u8]| {
+            let tmp = dir.join(format!("{}.tmp", name));
+            fs::write(&tmp, data).and_then(|_| fs::rename(&tmp, dir.join(name)))
+        };
+        write(VECTORS_FILE, &bytes).map_err(|e| format!("Failed to save embeddings: {}", e))?;
+        write(META_FILE, json.as_bytes()).map_err(|e| format!("Failed to save embeddings: {}", e))
+    }
+
+    pub fn upsert(&mut self, rel: &str, hash: String, chunks: Vec<Chunk>, mut vectors: Vec<Vec<f32>>) -> Result<(), String> {
+        if let Some(dimension) = vectors.first().map(Vec::len) {
+            if self.dimension == 0 {
+                self.dimension = dimension;
+            }
+            if vectors.iter().any(|v| v.len() != self.dimension) {
+                return Err(format!("Embedding has {} dimensions, index expects {}", dimension, self.dimension));
+            }
+        }
+        vectors.iter_mut().for_each(|v| normalize(v));
+        self.notes.insert(rel.to_string(), NoteVectors { hash, chunks, vectors: vectors.concat() });
+        Ok(())
+    }
+
+    fn rows<'a>(&'a self, note: &'a NoteVectors) -> impl Iterator<Item = &'a [f32]> + 'a {
+        note.vectors.chunks_exact(self.dimension.max(1))
+    }
+
+    /// Mean of a note's chunk vectors, normalized
+    pub fn note_vector(&self, rel: &str) -> Option<Vec<f32>> {
+        let note = self.notes.get(rel)?;
+        let mut mean = vec![0.0; self.dimension];
+        for row in self.rows(note) {
+            mean.iter_mut().zip(row).for_each(|(m, v)| *m += v);
+        }
+        normalize(&mut mean);
+        (self.dimension > 0 && !note.chunks.is_empty()).then_some(mean)
+    }
+
+    /// Best-matching notes for a normalized query vector: each note scores
+    /// its closest chunk, which is returned alongside
+    pub fn search(&self, query: &[f32], k: usize, exclude: Option<&str>) -> Vec<(String, f32, Chunk)> {
+        if query.len() != self.dimension {
+            return Vec::new();
+        }
+        let mut scored: Vec<(String, f32, Chunk)> = self
+            .notes
+            .iter()
+            .filter(|(rel, _)| Some(rel.as_str()) != exclude)
+            .filter_map(|(rel, note)| {
+                self.rows(note)
+                    .zip(&note.chunks)
+                    .map(|(row, chunk)| (dot(query, row), *chunk))
+                    .max_by(|a, b| a.0.total_cmp(&b.0))
+                    .map(|(score, chunk)| (rel.clone(), score, chunk))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(k);
+        scored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunking_breaks_between_paragraphs() {
+        let paragraph = "word ".repeat(150); // 750 chars
+        let text = format!("{}\n\n{}\n\n{}", paragraph, paragraph, paragraph);
+        let chunks = chunk_text(&text);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.end - c.start <= CHUNK_TARGET));
+        assert_eq!(chunks.last().unwrap().end, text.len());
+        assert!(chunk_text("  \n\n ").is_empty());
+    }
+
+    #[test]
+    fn test_round_trip_and_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = VectorIndex::new("test-model");
+        let chunk = Chunk { start: 0, end: 4 };
+        index.upsert("a.md", "h1".into(), vec![chunk], vec![vec![1.0, 0.0]]).unwrap();
+        index.upsert("b.md", "h2".into(), vec![chunk, chunk], vec![vec![0.0, 2.0], vec![1.0, 1.0]]).unwrap();
+        assert!(index.upsert("c.md", "h3".into(), vec![chunk], vec![vec![1.0, 0.0, 0.0]]).is_err());
+        index.save(dir.path()).unwrap();
+
+        let loaded = VectorIndex::load(dir.path(), "test-model");
+        assert_eq!(loaded.dimension, 2);
+        let hits = loaded.search(&[1.0, 0.0], 5, None);
+        assert_eq!(hits[0].0, "a.md");
+        assert_eq!(hits[1].0, "b.md");
+        assert!((hits[1].1 - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-5);
+        assert_eq!(loaded.search(&[1.0, 0.0], 5, Some("a.md")).len(), 1);
+
+        assert!(VectorIndex::load(dir.path(), "other-model").notes.is_empty());
+    }
+}
@@ -0,0 +1,508 @@
+//! Note encryption at rest.
+//!
+//! A workspace gets one XChaCha20-Poly1305 key derived from the user's
+//! passphrase with Argon2id. The salt and a check value live in
+//! `.lokus/encryption.json`; the derived key itself is kept in the OS keychain
+//! (or `SecureStorage` where no keychain is available), so the passphrase is
+//! only needed once per device.
+//!
+//! Encrypted notes keep their file name and are stored as an ASCII-armored
+//! block, which keeps them text files for sync and version control while the
+//! content itself is ciphertext. A note is encrypted if its content is armored
+//! or if it sits inside a folder flagged with `set_folder_encryption`;
+//! `read_file_content`/`write_file_content` handle both cases transparently.
+
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+const ARMOR_BEGIN: &str = "-----BEGIN LOKUS ENCRYPTED NOTE-----";
+const ARMOR_END: &str = "-----END LOKUS ENCRYPTED NOTE-----";
+const FORMAT_VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+const CHECK_PLAINTEXT: &str = "lokus-encryption-check";
+#[cfg(desktop)]
+const KEYCHAIN_SERVICE: &str = "com.lokus.app.encryption";
+
+type Key = [u8; 32];
+
+/// Keys unlocked in this session, by workspace key id
+static UNLOCKED_KEYS: Lazy<Mutex<HashMap<String, Key>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionConfig {
+    version: u32,
+    /// Identifies the key in the keychain; stable across passphrase changes
+    key_id: String,
+    /// Base64 Argon2 salt
+    salt: String,
+    /// Armored `CHECK_PLAINTEXT`, used to verify a passphrase
+    check: String,
+    /// Workspace-relative folders whose notes are always encrypted
+    #[serde(default)]
+    encrypted_folders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    pub configured: bool,
+    pub unlocked: bool,
+    pub encrypted_folders: Vec<String>,
+}
+
+// --- Crypto ---
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(key: &Key, plaintext: &str) -> Result<String, String> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+
+    let mut payload = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+    payload.push(FORMAT_VERSION);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    let encoded = general_purpose::STANDARD.encode(payload);
+    let mut armored = String::with_capacity(encoded.len() + 100);
+    armored.push_str(ARMOR_BEGIN);
+    armored.push('\n');
+    for chunk in encoded.as_bytes().chunks(76) {
+        armored.push_str(std::str::from_utf8(chunk).expect("base64 is ASCII"));
+        armored.push('\n');
+    }
+    armored.push_str(ARMOR_END);
+    armored.push('\n');
+    Ok(armored)
+}
+
+fn decrypt(key: &Key, armored: &str) -> Result<String, String> {
+    let body: String = armored
+        .trim()
+        .strip_prefix(ARMOR_BEGIN)
+        .and_then(|rest| rest.strip_suffix(ARMOR_END))
+        .ok_or("Note is not in the encrypted format")?
+        .split_whitespace()
+        .collect();
+    let payload = general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| format!("Encrypted note is corrupted: {}", e))?;
+
+    match payload.split_first() {
+        Some((&FORMAT_VERSION, rest)) if rest.len() > NONCE_LEN => {
+            let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+            let plaintext = XChaCha20Poly1305::new(key.into())
+                .decrypt(XNonce::from_slice(nonce), ciphertext)
+                .map_err(|_| "Decryption failed: wrong key or corrupted note".to_string())?;
+            String::from_utf8(plaintext).map_err(|e| format!("Decrypted note is not UTF-8: {}", e))
+        }
+        Some((version, _)) => Err(format!("Unsupported encrypted note version {}", version)),
+        None => Err("Encrypted note is empty".to_string()),
+    }
+}
+
+/// True if `content` is an armored encrypted note
+pub fn is_encrypted(content: &str) -> bool {
+    content.trim_start().starts_with(ARMOR_BEGIN)
+}
+
+// --- Workspace config ---
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join("encryption.json")
+}
+
+fn load_config(root: &Path) -> Option<EncryptionConfig> {
+    let content = fs::read_to_string(config_path(root)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_config(root: &Path, config: &EncryptionConfig) -> Result<(), String> {
+    let path = config_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize encryption config: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write encryption config: {}", e))
+}
+
+/// The workspace an encrypted file belongs to: the nearest ancestor with an
+/// encryption config
+fn find_encrypted_workspace(path: &Path) -> Option<(PathBuf, EncryptionConfig)> {
+    path.ancestors()
+        .skip(1)
+        .find_map(|dir| load_config(dir).map(|config| (dir.to_path_buf(), config)))
+}
+
+fn in_encrypted_folder(config: &EncryptionConfig, rel: &str) -> bool {
+    config
+        .encrypted_folders
+        .iter()
+        .any(|folder| folder.is_empty() || rel.starts_with(&format!("{}/", folder)))
+}
+
+// --- Key storage ---
+
+#[cfg(desktop)]
+fn keychain_load(key_id: &str) -> Option<Key> {
+    let encoded = keyring::Entry::new(KEYCHAIN_SERVICE, key_id).ok()?.get_password().ok()?;
+    general_purpose::STANDARD.decode(encoded).ok()?.try_into().ok()
+}
+
+#[cfg(desktop)]
+fn keychain_store(key_id: &str, key: &Key) -> Result<(), String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, key_id)
+        .and_then(|entry| entry.set_password(&general_purpose::STANDARD.encode(key)))
+        .map_err(|e| format!("Failed to store key in keychain: {}", e))
+}
+
+#[cfg(desktop)]
+fn keychain_delete(key_id: &str) {
+    if let Ok(entry) = keyring::Entry::new(KEYCHAIN_SERVICE, key_id) {
+        let _ = entry.delete_credential();
+    }
+}
+
+#[cfg(not(desktop))]
+fn keychain_load(key_id: &str) -> Option<Key> {
+    let storage = crate::secure_storage::SecureStorage::new().ok()?;
+    let encoded: String = storage.retrieve(&format!("note-key-{}", key_id)).ok()??;
+    general_purpose::STANDARD.decode(encoded).ok()?.try_into().ok()
+}
+
+#[cfg(not(desktop))]
+fn keychain_store(key_id: &str, key: &Key) -> Result<(), String> {
+    crate::secure_storage::SecureStorage::new()
+        .and_then(|storage| storage.store(&format!("note-key-{}", key_id), &general_purpose::STANDARD.encode(key)))
+        .map_err(|e| format!("Failed to store key: {}", e))
+}
+
+#[cfg(not(desktop))]
+fn keychain_delete(key_id: &str) {
+    if let Ok(storage) = crate::secure_storage::SecureStorage::new() {
+        let _ = storage.delete(&format!("note-key-{}", key_id));
+    }
+}
+
+fn unlocked_key(config: &EncryptionConfig) -> Option<Key> {
+    let mut keys = UNLOCKED_KEYS.lock().ok()?;
+    if let Some(key) = keys.get(&config.key_id) {
+        return Some(*key);
+    }
+    let key = keychain_load(&config.key_id)?;
+    // Only trust a stored key that still matches this workspace
+    decrypt(&key, &config.check).ok()?;
+    keys.insert(config.key_id.clone(), key);
+    Some(key)
+}
+
+fn require_key(config: &EncryptionConfig) -> Result<Key, String> {
+    unlocked_key(config).ok_or_else(|| "Encrypted notes are locked. Unlock the workspace first.".to_string())
+}
+
+// --- Hooks for file operations ---
+
+/// Decrypt `content` read from `path` if it is an encrypted note
+pub fn decrypt_for_read(path: &str, content: String) -> Result<String, String> {
+    if !is_encrypted(&content) {
+        return Ok(content);
+    }
+    let (_, config) = find_encrypted_workspace(Path::new(path))
+        .ok_or("Note is encrypted but its workspace has no encryption key")?;
+    decrypt(&require_key(&config)?, &content)
+}
+
+/// The content to store for `path`: ciphertext if the note is already
+/// encrypted on disk or lives in an encrypted folder, otherwise `content`.
+pub fn encrypt_for_write(path: &str, content: &str) -> Result<String, String> {
+    match encrypted_target(Path::new(path)) {
+        Some(config) => encrypt(&require_key(&config)?, content),
+        None => Ok(content.to_string()),
+    }
+}
+
+/// Whether a write to `path` has to be stored encrypted
+pub fn stores_encrypted(path: &Path) -> bool {
+    encrypted_target(path).is_some()
+}
+
+fn encrypted_target(file: &Path) -> Option<EncryptionConfig> {
+    let (root, config) = find_encrypted_workspace(file)?;
+    let flagged_folder = crate::links::relative_path(&root, file)
+        .map_or(false, |rel| in_encrypted_folder(&config, &rel));
+    let already_encrypted = flagged_folder
+        || fs::read_to_string(file).map_or(false, |existing| is_encrypted(&existing));
+    already_encrypted.then_some(config)
+}
+
+fn rewrite_file(path: &Path, transform: impl Fn(&str) -> Result<Option<String>, String>) -> Result<bool, String> {
+    crate::vaults::ensure_writable(path)?;
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let Some(updated) = transform(&content)? else {
+        return Ok(false);
+    };
+    let temp_path = path.with_extension("enc.tmp");
+    fs::write(&temp_path, updated).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+fn is_note_file(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("md"))
+}
+
+// --- Tauri Commands ---
+
+/// Unlock encryption for a workspace, setting it up with this passphrase on
+/// first use. The derived key is remembered in the OS keychain.
+#[tauri::command]
+pub async fn encryption_unlock(workspace_path: String, passphrase: String) -> Result<EncryptionStatus, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", workspace_path));
+    }
+
+    // Argon2 is deliberately slow; keep it off the async runtime
+    let config = tokio::task::spawn_blocking(move || -> Result<EncryptionConfig, String> {
+        match load_config(&root) {
+            Some(config) => {
+                let salt = general_purpose::STANDARD
+                    .decode(&config.salt)
+                    .map_err(|e| format!("Encryption config is corrupted: {}", e))?;
+                let key = derive_key(&passphrase, &salt)?;
+                decrypt(&key, &config.check).map_err(|_| "Incorrect passphrase".to_string())?;
+                keychain_store(&config.key_id, &key)?;
+                remember_key(&config.key_id, key);
+                Ok(config)
+            }
+            None => {
+                let mut salt = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let key = derive_key(&passphrase, &salt)?;
+                let config = EncryptionConfig {
+                    version: 1,
+                    key_id: uuid::Uuid::new_v4().to_string(),
+                    salt: general_purpose::STANDARD.encode(salt),
+                    check: encrypt(&key, CHECK_PLAINTEXT)?,
+                    encrypted_folders: Vec::new(),
+                };
+                save_config(&root, &config)?;
+                keychain_store(&config.key_id, &key)?;
+                remember_key(&config.key_id, key);
+                Ok(config)
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Unlock task failed: {}", e))??;
+
+    Ok(EncryptionStatus {
+        configured: true,
+        unlocked: true,
+        encrypted_folders: config.encrypted_folders,
+    })
+}
+
+fn remember_key(key_id: &str, key: Key) {
+    if let Ok(mut keys) = UNLOCKED_KEYS.lock() {
+        keys.insert(key_id.to_string(), key);
+    }
+}
+
+/// Forget the workspace key on this device; encrypted notes stay encrypted
+#[tauri::command]
+pub fn encryption_lock(workspace_path: String) -> Result<(), String> {
+    let config = load_config(Path::new(&workspace_path)).ok_or("Workspace does not use encryption")?;
+    if let Ok(mut keys) = UNLOCKED_KEYS.lock() {
+        keys.remove(&config.key_id);
+    }
+    keychain_delete(&config.key_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn encryption_status(workspace_path: String) -> Result<EncryptionStatus, String> {
+    Ok(match load_config(Path::new(&workspace_path)) {
+        Some(config) => EncryptionStatus {
+            configured: true,
+            unlocked: unlocked_key(&config).is_some(),
+            encrypted_folders: config.encrypted_folders,
+        },
+        None => EncryptionStatus {
+            configured: false,
+            unlocked: false,
+            encrypted_folders: Vec::new(),
+        },
+    })
+}
+
+/// Encrypt a single note in place
+#[tauri::command]
+pub fn encrypt_note(path: String) -> Result<(), String> {
+    let file = Path::new(&path);
+    let (root, config) = find_encrypted_workspace(file).ok_or("Set up encryption for this workspace first")?;
+    let key = require_key(&config)?;
+    rewrite_file(file, |content| {
+        if is_encrypted(content) {
+            Ok(None)
+        } else {
+            encrypt(&key, content).map(Some)
+        }
+    })?;
+    crate::search::index::notify_file_removed(&path);
+    // Saved versions hold the plaintext
+    crate::handlers::version_history::purge_history(&root, &path)?;
+    Ok(())
+}
+
+/// Decrypt a single note in place, storing it as plain text again
+#[tauri::command]
+pub fn decrypt_note(path: String) -> Result<(), String> {
+    let file = Path::new(&path);
+    let (root, config) = find_encrypted_workspace(file).ok_or("Workspace does not use encryption")?;
+    if crate::links::relative_path(&root, file).map_or(false, |rel| in_encrypted_folder(&config, &rel)) {
+        return Err("Note is inside an encrypted folder; turn off folder encryption instead".to_string());
+    }
+    let key = require_key(&config)?;
+    let changed = rewrite_file(file, |content| {
+        if !is_encrypted(content) {
+            return Ok(None);
+        }
+        decrypt(&key, content).map(Some)
+    })?;
+    if changed {
+        if let Ok(content) = fs::read_to_string(file) {
+            crate::search::index::notify_file_saved(&path, &content);
+            crate::links::notify_file_saved(&path, &content);
+        }
+    }
+    Ok(())
+}
+
+/// Turn encryption on or off for every note in a folder (recursively).
+/// Returns the number of notes that were converted.
+#[tauri::command]
+pub async fn set_folder_encryption(workspace_path: String, folder: String, enabled: bool) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    let mut config = load_config(&root).ok_or("Set up encryption for this workspace first")?;
+    let key = require_key(&config)?;
+    let folder_path = PathBuf::from(&folder);
+    let rel = crate::links::relative_path(&root, &folder_path)
+        .ok_or_else(|| format!("Folder is outside the workspace: {}", folder))?;
+    if !folder_path.is_dir() {
+        return Err(format!("Folder does not exist: {}", folder));
+    }
+
+    let workspace = root.clone();
+    let converted = tokio::task::spawn_blocking(move || -> Result<usize, String> {
+        let mut converted = 0;
+        let notes = WalkDir::new(&folder_path)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != ".lokus" && e.file_name() != ".git")
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && is_note_file(e.path()));
+
+        for entry in notes {
+            let changed = rewrite_file(entry.path(), |content| match (enabled, is_encrypted(content)) {
+                (true, false) => encrypt(&key, content).map(Some),
+                (false, true) => decrypt(&key, content).map(Some),
+                _ => Ok(None),
+            })?;
+            if changed {
+                converted += 1;
+                let path = entry.path().to_string_lossy();
+                if enabled {
+                    crate::search::index::notify_file_removed(&path);
+                    crate::handlers::version_history::purge_history(&workspace, &path)?;
+                } else if let Ok(content) = fs::read_to_string(entry.path()) {
+                    crate::search::index::notify_file_saved(&path, &content);
+                    crate::links::notify_file_saved(&path, &content);
+                }
+            }
+        }
+        Ok(converted)
+    })
+    .await
+    .map_err(|e| format!("Folder encryption task failed: {}", e))??;
+
+    config.encrypted_folders.retain(|f| *f != rel);
+    if enabled {
+        config.encrypted_folders.push(rel);
+        config.encrypted_folders.sort();
+    }
+    save_config(&root, &config)?;
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = derive_key("correct horse", b"0123456789abcdef").unwrap();
+        let armored = encrypt(&key, "# Secret\n\nDiary entry").unwrap();
+
+        assert!(is_encrypted(&armored));
+        assert!(!armored.contains("Diary"));
+        assert!(armored.lines().all(|line| line.len() <= 76 || line.starts_with("-----")));
+        assert_eq!(decrypt(&key, &armored).unwrap(), "# Secret\n\nDiary entry");
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let key = derive_key("one", b"0123456789abcdef").unwrap();
+        let other = derive_key("two", b"0123456789abcdef").unwrap();
+        let armored = encrypt(&key, "text").unwrap();
+        assert!(decrypt(&other, &armored).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_folder_matching() {
+        let config = EncryptionConfig {
+            version: 1,
+            key_id: "k".to_string(),
+            salt: String::new(),
+            check: String::new(),
+            encrypted_folders: vec!["Private".to_string()],
+        };
+        assert!(in_encrypted_folder(&config, "Private/journal.md"));
+        assert!(!in_encrypted_folder(&config, "Private Notes/a.md"));
+        assert!(!in_encrypted_folder(&config, "Public/a.md"));
+    }
+
+    #[test]
+    fn test_plain_files_pass_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("note.md");
+        fs::write(&note, "plain").unwrap();
+        let path = note.to_string_lossy().to_string();
+
+        assert_eq!(encrypt_for_write(&path, "updated").unwrap(), "updated");
+        assert_eq!(decrypt_for_read(&path, "plain".to_string()).unwrap(), "plain");
+    }
+}
@@ -0,0 +1,269 @@
+//! Structured errors for Tauri commands.
+//!
+//! A `LokusError` reaches the frontend as `{ code, message, recoverable,
+//! context }`. `code` is stable and listed in [`CATALOG`] (served by
+//! `error_catalog`), so the UI can map it to a localized message instead of
+//! matching on English text; `recoverable` says whether trying again can
+//! succeed, possibly after the user acts (reconnecting an account, resolving a
+//! conflict), and drives retry affordances; `context` carries details such as
+//! the path involved.
+//!
+//! Helpers that still return `Result<_, String>` convert with `?` in both
+//! directions. A string keeps its code when it starts with one the catalog
+//! knows, as `VAULT_READ_ONLY` errors from the vault guard do.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum LokusError {
+    #[error("{message}")]
+    NotFound { path: String, message: String },
+    #[error("{message}")]
+    PermissionDenied { path: String, message: String },
+    #[error("{message}")]
+    AlreadyExists { path: String, message: String },
+    /// Writing into a vault that was marked read-only
+    #[error("{0}")]
+    ReadOnly(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    /// The disk or filesystem failed; often temporary (full disk, locked file)
+    #[error("{0}")]
+    Io(String),
+    /// Files were changed on both sides and need merging
+    #[error("{message}")]
+    Conflict { files: Vec<String>, message: String },
+    #[error("{0}")]
+    Network(String),
+    #[error("{0}")]
+    RateLimited(String),
+    /// The account needs to be connected again
+    #[error("{0}")]
+    AuthRequired(String),
+    #[error("{0}")]
+    Parse(String),
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("{message}")]
+    Plugin { plugin: String, message: String },
+    #[error("{0}")]
+    Other(String),
+}
+
+/// A code the frontend can expect, with an English description
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub code: &'static str,
+    pub recoverable: bool,
+    pub description: &'static str,
+}
+
+pub const CATALOG: &[CatalogEntry] = &[
+    CatalogEntry { code: "NOT_FOUND", recoverable: false, description: "The file, folder or item doesn't exist" },
+    CatalogEntry { code: "PERMISSION_DENIED", recoverable: false, description: "The system refused access" },
+    CatalogEntry { code: "ALREADY_EXISTS", recoverable: false, description: "Something with that name is already there" },
+    CatalogEntry { code: crate::vaults::READ_ONLY_ERROR, recoverable: false, description: "The vault is read-only" },
+    CatalogEntry { code: "INVALID_INPUT", recoverable: false, description: "The request was malformed or a value is not allowed" },
+    CatalogEntry { code: "IO_ERROR", recoverable: true, description: "Reading or writing the disk failed" },
+    CatalogEntry { code: "CONFLICT", recoverable: true, description: "Changes conflict and have to be merged first" },
+    CatalogEntry { code: "NETWORK", recoverable: true, description: "The server couldn't be reached" },
+    CatalogEntry { code: "RATE_LIMITED", recoverable: true, description: "Too many requests; try again shortly" },
+    CatalogEntry { code: "AUTH_REQUIRED", recoverable: true, description: "The account has to be connected again" },
+    CatalogEntry { code: "PARSE_ERROR", recoverable: false, description: "Data couldn't be read in the expected format" },
+    CatalogEntry { code: "UNSUPPORTED", recoverable: false, description: "Not available on this platform or configuration" },
+    CatalogEntry { code: "PLUGIN_ERROR", recoverable: false, description: "A plugin failed" },
+    CatalogEntry { code: "UNKNOWN", recoverable: false, description: "Anything else" },
+];
+
+impl LokusError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            LokusError::NotFound { .. } => "NOT_FOUND",
+            LokusError::PermissionDenied { .. } => "PERMISSION_DENIED",
+            LokusError::AlreadyExists { .. } => "ALREADY_EXISTS",
+            LokusError::ReadOnly(_) => crate::vaults::READ_ONLY_ERROR,
+            LokusError::InvalidInput(_) => "INVALID_INPUT",
+            LokusError::Io(_) => "IO_ERROR",
+            LokusError::Conflict { .. } => "CONFLICT",
+            LokusError::Network(_) => "NETWORK",
+            LokusError::RateLimited(_) => "RATE_LIMITED",
+            LokusError::AuthRequired(_) => "AUTH_REQUIRED",
+            LokusError::Parse(_) => "PARSE_ERROR",
+            LokusError::Unsupported(_) => "UNSUPPORTED",
+            LokusError::Plugin { .. } => "PLUGIN_ERROR",
+            LokusError::Other(_) => "UNKNOWN",
+        }
+    }
+
+    pub fn recoverable(&self) -> bool {
+        CATALOG.iter().any(|entry| entry.code == self.code() && entry.recoverable)
+    }
+
+    pub fn context(&self) -> BTreeMap<&'static str, Value> {
+        let mut context = BTreeMap::new();
+        match self {
+            LokusError::NotFound { path, .. } | LokusError::PermissionDenied { path, .. } | LokusError::AlreadyExists { path, .. }
+                if !path.is_empty() =>
+            {
+                context.insert("path", Value::from(path.as_str()));
+            }
+            LokusError::Conflict { files, .. } => {
+                context.insert("files", Value::from(files.clone()));
+            }
+            LokusError::Plugin { plugin, .. } => {
+                context.insert("plugin", Value::from(plugin.as_str()));
+            }
+            _ => {}
+        }
+        context
+    }
+
+    pub fn not_found(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().display().to_string();
+        LokusError::NotFound { message: format!("'{}' does not exist", path), path }
+    }
+
+    pub fn already_exists(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().display().to_string();
+        let name = path.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+        LokusError::AlreadyExists { message: format!("A file or folder named '{}' already exists", name), path }
+    }
+
+    pub fn conflict(files: Vec<String>) -> Self {
+        LokusError::Conflict { message: format!("Merge conflict in {}", files.join(", ")), files }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        LokusError::InvalidInput(message.into())
+    }
+
+    pub fn plugin(plugin: impl Into<String>, message: impl Into<String>) -> Self {
+        LokusError::Plugin { plugin: plugin.into(), message: message.into() }
+    }
+
+    /// Classify an I/O error on `path`; `action` leads the message ("Failed to rename")
+    pub fn io(action: &str, path: impl AsRef<Path>, err: std::io::Error) -> Self {
+        let path = path.as_ref().display().to_string();
+        let message = format!("{} '{}': {}", action, path, err);
+        match err.kind() {
+            ErrorKind::NotFound => LokusError::NotFound { path, message },
+            ErrorKind::PermissionDenied => LokusError::PermissionDenied { path, message },
+            ErrorKind::AlreadyExists => LokusError::AlreadyExists { path, message },
+            ErrorKind::InvalidInput | ErrorKind::InvalidData => LokusError::InvalidInput(message),
+            _ => LokusError::Io(message),
+        }
+    }
+}
+
+impl Serialize for LokusError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LokusError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("recoverable", &self.recoverable())?;
+        state.serialize_field("context", &self.context())?;
+        state.end()
+    }
+}
+
+impl From<String> for LokusError {
+    fn from(message: String) -> Self {
+        match message.strip_prefix(crate::vaults::READ_ONLY_ERROR) {
+            Some(rest) if rest.starts_with(':') => LokusError::ReadOnly(message),
+            _ => LokusError::Other(message),
+        }
+    }
+}
+
+impl From<&str> for LokusError {
+    fn from(message: &str) -> Self {
+        LokusError::from(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for LokusError {
+    fn from(err: std::io::Error) -> Self {
+        let message = err.to_string();
+        match err.kind() {
+            ErrorKind::NotFound => LokusError::NotFound { path: String::new(), message },
+            ErrorKind::PermissionDenied => LokusError::PermissionDenied { path: String::new(), message },
+            ErrorKind::AlreadyExists => LokusError::AlreadyExists { path: String::new(), message },
+            _ => LokusError::Io(message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for LokusError {
+    fn from(err: serde_json::Error) -> Self {
+        LokusError::Parse(err.to_string())
+    }
+}
+
+impl From<LokusError> for String {
+    fn from(err: LokusError) -> Self {
+        err.to_string()
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Every error code a command can return
+#[tauri::command]
+pub fn error_catalog() -> &'static [CatalogEntry] {
+    CATALOG
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_code_is_in_the_catalog() {
+        let errors = [
+            LokusError::not_found("/a"),
+            LokusError::PermissionDenied { path: String::new(), message: String::new() },
+            LokusError::already_exists("/a"),
+            LokusError::ReadOnly(String::new()),
+            LokusError::invalid(""),
+            LokusError::Io(String::new()),
+            LokusError::conflict(vec![]),
+            LokusError::Network(String::new()),
+            LokusError::RateLimited(String::new()),
+            LokusError::AuthRequired(String::new()),
+            LokusError::Parse(String::new()),
+            LokusError::Unsupported(String::new()),
+            LokusError::plugin("p", ""),
+            LokusError::Other(String::new()),
+        ];
+        for error in errors {
+            assert!(CATALOG.iter().any(|entry| entry.code == error.code()), "{} missing", error.code());
+        }
+    }
+
+    #[test]
+    fn test_serializes_with_code_and_context() {
+        let error = LokusError::io("Failed to read", "/vault/a.md", std::io::Error::from(ErrorKind::NotFound));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "NOT_FOUND");
+        assert_eq!(json["recoverable"], false);
+        assert_eq!(json["context"]["path"], "/vault/a.md");
+        assert!(json["message"].as_str().unwrap().starts_with("Failed to read '/vault/a.md'"));
+
+        let read_only = LokusError::from(format!("{}: /vault/a.md is in a read-only vault (Vault)", crate::vaults::READ_ONLY_ERROR));
+        assert_eq!(read_only.code(), crate::vaults::READ_ONLY_ERROR);
+        assert_eq!(String::from(read_only.clone()), read_only.to_string());
+        assert!(read_only.to_string().starts_with(crate::vaults::READ_ONLY_ERROR));
+
+        let conflict = serde_json::to_value(LokusError::conflict(vec!["a.md".into()])).unwrap();
+        assert_eq!(conflict["message"], "Merge conflict in a.md");
+        assert_eq!(conflict["recoverable"], true);
+        assert_eq!(conflict["context"]["files"][0], "a.md");
+        assert_eq!(LokusError::from("plain").code(), "UNKNOWN");
+    }
+}
@@ -0,0 +1,265 @@
+//! Syntax highlighting for exported code blocks.
+//!
+//! A small lexer that knows the comment, string and keyword rules of the
+//! languages notes most often contain. It only classifies tokens for
+//! colouring, so unknown languages fall back to strings, numbers and
+//! comments in a C-like syntax.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TokenKind {
+    Plain,
+    Keyword,
+    /// Built-in types and literals such as `true`
+    Type,
+    String,
+    Number,
+    Comment,
+}
+
+struct Language {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Keywords match regardless of case (SQL)
+    case_insensitive: bool,
+    quotes: &'static [char],
+    keywords: &'static [&'static str],
+    types: &'static [&'static str],
+}
+
+const RUST: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    case_insensitive: false,
+    quotes: &['"'],
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "fn", "for", "if",
+        "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+        "struct", "super", "trait", "type", "unsafe", "use", "where", "while",
+    ],
+    types: &[
+        "bool", "char", "str", "String", "Vec", "Option", "Result", "Some", "None", "Ok", "Err", "true", "false", "u8",
+        "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize", "f32", "f64",
+    ],
+};
+
+const JAVASCRIPT: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    case_insensitive: false,
+    quotes: &['"', '\'', '`'],
+    keywords: &[
+        "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete", "do", "else",
+        "export", "extends", "finally", "for", "from", "function", "if", "import", "in", "instanceof", "interface",
+        "let", "new", "of", "return", "static", "switch", "this", "throw", "try", "type", "typeof", "var", "while",
+        "yield",
+    ],
+    types: &["true", "false", "null", "undefined", "string", "number", "boolean", "any", "void", "Promise"],
+};
+
+const PYTHON: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    case_insensitive: false,
+    quotes: &['"', '\''],
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except",
+        "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+        "raise", "return", "try", "while", "with", "yield",
+    ],
+    types: &["True", "False", "None", "self", "int", "str", "float", "bool", "list", "dict", "set", "tuple"],
+};
+
+const GO: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    case_insensitive: false,
+    quotes: &['"', '\'', '`'],
+    keywords: &[
+        "break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough", "for", "func", "go",
+        "goto", "if", "import", "interface", "map", "package", "range", "return", "select", "struct", "switch", "type",
+        "var",
+    ],
+    types: &["bool", "byte", "error", "int", "int64", "float64", "rune", "string", "true", "false", "nil"],
+};
+
+const C_LIKE: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    case_insensitive: false,
+    quotes: &['"', '\''],
+    keywords: &[
+        "break", "case", "catch", "class", "const", "continue", "default", "delete", "do", "else", "enum", "extends",
+        "final", "for", "if", "implements", "import", "include", "namespace", "new", "package", "private", "protected",
+        "public", "return", "sizeof", "static", "struct", "switch", "template", "this", "throw", "try", "typedef",
+        "using", "virtual", "void", "while",
+    ],
+    types: &[
+        "bool", "char", "double", "float", "int", "long", "short", "unsigned", "String", "true", "false", "null",
+        "nullptr", "NULL",
+    ],
+};
+
+const SHELL: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    case_insensitive: false,
+    quotes: &['"', '\''],
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local", "return",
+        "then", "until", "while",
+    ],
+    types: &["echo", "cd", "exit", "set", "source", "true", "false"],
+};
+
+const SQL: Language = Language {
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    case_insensitive: true,
+    quotes: &['\''],
+    keywords: &[
+        "select", "from", "where", "insert", "into", "values", "update", "set", "delete", "create", "table", "drop",
+        "alter", "join", "left", "right", "inner", "outer", "on", "group", "by", "order", "having", "limit", "and",
+        "or", "not", "as", "distinct", "union", "index", "primary", "key",
+    ],
+    types: &["null", "true", "false", "integer", "text", "varchar", "boolean", "real"],
+};
+
+const DATA: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    case_insensitive: false,
+    quotes: &['"', '\''],
+    keywords: &[],
+    types: &["true", "false", "null", "yes", "no"],
+};
+
+fn language(name: &str) -> &'static Language {
+    match name.trim().to_lowercase().as_str() {
+        "rust" | "rs" => &RUST,
+        "js" | "javascript" | "jsx" | "ts" | "typescript" | "tsx" | "mjs" => &JAVASCRIPT,
+        "py" | "python" => &PYTHON,
+        "go" | "golang" => &GO,
+        "sh" | "bash" | "zsh" | "shell" | "console" => &SHELL,
+        "sql" | "sqlite" | "postgres" => &SQL,
+        "yaml" | "yml" | "toml" | "ini" => &DATA,
+        "json" | "jsonc" => &JAVASCRIPT,
+        _ => &C_LIKE,
+    }
+}
+
+/// State carried from one line to the next
+#[derive(Default)]
+struct Carry {
+    block_comment: bool,
+}
+
+fn push(tokens: &mut Vec<(TokenKind, String)>, kind: TokenKind, text: &str) {
+    match tokens.last_mut() {
+        Some((last, existing)) if *last == kind => existing.push_str(text),
+        _ => tokens.push((kind, text.to_string())),
+    }
+}
+
+fn highlight_line(line: &str, lang: &Language, carry: &mut Carry) -> Vec<(TokenKind, String)> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        if carry.block_comment {
+            let (_, end) = lang.block_comment.expect("only set for languages with block comments");
+            match rest.find(end) {
+                Some(i) => {
+                    push(&mut tokens, TokenKind::Comment, &rest[..i + end.len()]);
+                    rest = &rest[i + end.len()..];
+                    carry.block_comment = false;
+                }
+                None => {
+                    push(&mut tokens, TokenKind::Comment, rest);
+                    rest = "";
+                }
+            }
+            continue;
+        }
+        if lang.line_comments.iter().any(|prefix| rest.starts_with(prefix)) {
+            push(&mut tokens, TokenKind::Comment, rest);
+            break;
+        }
+        if let Some((start, _)) = lang.block_comment.filter(|(start, _)| rest.starts_with(start)) {
+            push(&mut tokens, TokenKind::Comment, start);
+            rest = &rest[start.len()..];
+            carry.block_comment = true;
+            continue;
+        }
+
+        let c = rest.chars().next().expect("rest is not empty");
+        let len = if lang.quotes.contains(&c) {
+            // Up to the closing quote, skipping escaped ones; unterminated strings run to the line end
+            let mut escaped = false;
+            rest.char_indices()
+                .skip(1)
+                .find(|&(_, ch)| {
+                    let closes = ch == c && !escaped;
+                    escaped = ch == '\\' && !escaped;
+                    closes
+                })
+                .map_or(rest.len(), |(i, ch)| i + ch.len_utf8())
+        } else if c.is_ascii_digit() {
+            rest.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_')).unwrap_or(rest.len())
+        } else if c.is_alphabetic() || c == '_' {
+            rest.find(|ch: char| !(ch.is_alphanumeric() || ch == '_')).unwrap_or(rest.len())
+        } else {
+            c.len_utf8()
+        };
+
+        let (token, tail) = rest.split_at(len);
+        let kind = if lang.quotes.contains(&c) {
+            TokenKind::String
+        } else if c.is_ascii_digit() {
+            TokenKind::Number
+        } else if is_word(lang, lang.keywords, token) {
+            TokenKind::Keyword
+        } else if is_word(lang, lang.types, token) {
+            TokenKind::Type
+        } else {
+            TokenKind::Plain
+        };
+        push(&mut tokens, kind, token);
+        rest = tail;
+    }
+    tokens
+}
+
+fn is_word(lang: &Language, words: &[&str], token: &str) -> bool {
+    if lang.case_insensitive {
+        words.iter().any(|w| w.eq_ignore_ascii_case(token))
+    } else {
+        words.contains(&token)
+    }
+}
+
+/// Split `code` into lines of classified tokens
+pub(super) fn highlight(code: &str, lang: &str) -> Vec<Vec<(TokenKind, String)>> {
+    let lang = language(lang);
+    let mut carry = Carry::default();
+    code.lines().map(|line| highlight_line(line, lang, &mut carry)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_tokens_and_carries_block_comments() {
+        let lines = highlight("fn main() { let s = \"a \\\" b\"; // done\n/* one\ntwo */ 42", "rust");
+        assert_eq!(lines[0][0], (TokenKind::Keyword, "fn".to_string()));
+        assert!(lines[0].contains(&(TokenKind::String, "\"a \\\" b\"".to_string())));
+        assert_eq!(lines[0].last().unwrap(), &(TokenKind::Comment, "// done".to_string()));
+        assert_eq!(lines[1], vec![(TokenKind::Comment, "/* one".to_string())]);
+        assert_eq!(lines[2][0], (TokenKind::Comment, "two */".to_string()));
+        assert_eq!(lines[2].last().unwrap(), &(TokenKind::Number, "42".to_string()));
+
+        let sql = highlight("SELECT name FROM t", "sql");
+        assert_eq!(sql[0][0], (TokenKind::Keyword, "SELECT".to_string()));
+        assert_eq!(highlight("# heading", "python")[0], vec![(TokenKind::Comment, "# heading".to_string())]);
+    }
+}
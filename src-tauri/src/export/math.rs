@@ -0,0 +1,500 @@
+//! TeX math typesetting for PDF export.
+//!
+//! Covers the subset notes use day to day: Greek letters and common operators
+//! (drawn with the base-14 Symbol font), superscripts and subscripts,
+//! fractions, square roots, `\text{}` and named functions such as `\sin`.
+//! Anything else is shown upright by name so nothing silently disappears.
+//! Layout produces a box of positioned glyphs measured in ems, which the PDF
+//! layout scales to the font size in use.
+
+/// Face a math glyph is drawn in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum MathFont {
+    Upright,
+    Italic,
+    /// Symbol font; the text holds bytes in its built-in encoding
+    Symbol,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct MathGlyph {
+    /// Offset from the box origin, in ems; `y` grows upwards from the baseline
+    pub x: f32,
+    pub y: f32,
+    pub text: String,
+    pub font: MathFont,
+    /// Relative to the surrounding font size
+    pub scale: f32,
+}
+
+/// A horizontal rule, as drawn for fraction bars and radicals
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct MathRule {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct MathBox {
+    pub glyphs: Vec<MathGlyph>,
+    pub rules: Vec<MathRule>,
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+impl MathBox {
+    fn append(&mut self, other: MathBox, dy: f32) {
+        let dx = self.width;
+        self.glyphs.extend(other.glyphs.into_iter().map(|g| MathGlyph { x: g.x + dx, y: g.y + dy, ..g }));
+        self.rules.extend(other.rules.into_iter().map(|r| MathRule { x: r.x + dx, y: r.y + dy, ..r }));
+        self.width += other.width;
+        self.ascent = self.ascent.max(other.ascent + dy);
+        self.descent = self.descent.max(other.descent - dy);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Glyph { text: String, font: MathFont, space: f32 },
+    Group(Vec<Node>),
+    Scripts { base: Box<Node>, sup: Option<Box<Node>>, sub: Option<Box<Node>> },
+    Frac(Box<Node>, Box<Node>),
+    Sqrt(Box<Node>),
+    Space(f32),
+}
+
+/// Symbol font code for a TeX command, and whether it is a relation or
+/// binary operator that gets space on both sides
+fn symbol(name: &str) -> Option<(u8, bool)> {
+    let code = match name {
+        "alpha" => b'a',
+        "beta" => b'b',
+        "gamma" => b'g',
+        "delta" => b'd',
+        "epsilon" | "varepsilon" => b'e',
+        "zeta" => b'z',
+        "eta" => b'h',
+        "theta" => b'q',
+        "vartheta" => b'J',
+        "iota" => b'i',
+        "kappa" => b'k',
+        "lambda" => b'l',
+        "mu" => b'm',
+        "nu" => b'n',
+        "xi" => b'x',
+        "pi" => b'p',
+        "varpi" => b'v',
+        "rho" | "varrho" => b'r',
+        "sigma" => b's',
+        "varsigma" => b'V',
+        "tau" => b't',
+        "upsilon" => b'u',
+        "phi" => b'f',
+        "varphi" => b'j',
+        "chi" => b'c',
+        "psi" => b'y',
+        "omega" => b'w',
+        "Gamma" => b'G',
+        "Delta" => b'D',
+        "Theta" => b'Q',
+        "Lambda" => b'L',
+        "Xi" => b'X',
+        "Pi" => b'P',
+        "Sigma" => b'S',
+        "Upsilon" => b'U',
+        "Phi" => b'F',
+        "Psi" => b'Y',
+        "Omega" => b'W',
+        "infty" => 0xA5,
+        "partial" => 0xB6,
+        "nabla" => 0xD1,
+        "forall" => 0x22,
+        "exists" => 0x24,
+        "emptyset" | "varnothing" => 0xC6,
+        "aleph" => 0xC0,
+        "prime" => 0xA2,
+        "angle" => 0xD0,
+        "ldots" | "cdots" | "dots" => 0xBC,
+        "langle" => 0xE1,
+        "rangle" => 0xF1,
+        "sum" => 0xE5,
+        "prod" => 0xD5,
+        "int" => 0xF2,
+        "neg" | "lnot" => 0xD8,
+        _ => return operator(name).map(|code| (code, true)),
+    };
+    Some((code, false))
+}
+
+fn operator(name: &str) -> Option<u8> {
+    Some(match name {
+        "pm" => 0xB1,
+        "times" => 0xB4,
+        "div" => 0xB8,
+        "cdot" => 0xD7,
+        "ast" => 0x2A,
+        "bullet" => 0xB7,
+        "leq" | "le" => 0xA3,
+        "geq" | "ge" => 0xB3,
+        "neq" | "ne" => 0xB9,
+        "approx" => 0xBB,
+        "equiv" => 0xBA,
+        "sim" => 0x7E,
+        "cong" => 0x40,
+        "propto" => 0xB5,
+        "perp" => 0x5E,
+        "to" | "rightarrow" => 0xAE,
+        "leftarrow" | "gets" => 0xAC,
+        "leftrightarrow" => 0xAB,
+        "Rightarrow" | "implies" => 0xDE,
+        "Leftarrow" => 0xDC,
+        "Leftrightarrow" | "iff" => 0xDB,
+        "in" => 0xCE,
+        "notin" => 0xCF,
+        "subset" => 0xCC,
+        "subseteq" => 0xCD,
+        "supset" => 0xC9,
+        "supseteq" => 0xCA,
+        "cup" => 0xC8,
+        "cap" => 0xC7,
+        "wedge" | "land" => 0xD9,
+        "vee" | "lor" => 0xDA,
+        "oplus" => 0xC5,
+        "otimes" => 0xC4,
+        _ => return None,
+    })
+}
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh", "log", "ln", "lg",
+    "exp", "lim", "max", "min", "sup", "inf", "det", "dim", "gcd", "deg", "arg", "ker", "Pr",
+];
+
+const SCRIPT_SCALE: f32 = 0.7;
+/// Height of the fraction bar above the baseline, in ems
+const AXIS: f32 = 0.25;
+const OP_SPACE: f32 = 0.22;
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tex: &str) -> Self {
+        Self { chars: tex.chars().collect(), pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn command_name(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().map_or(false, |c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            // Single-character commands such as `\{` or `\,`
+            self.pos += 1;
+        }
+        self.chars[start..self.pos.min(self.chars.len())].iter().collect()
+    }
+
+    /// Raw text of a `{...}` argument, for `\text` and environment names
+    fn raw_group(&mut self) -> String {
+        self.skip_spaces();
+        if self.peek() != Some('{') {
+            return String::new();
+        }
+        self.pos += 1;
+        let mut depth = 1;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            text.push(c);
+        }
+        text
+    }
+
+    /// One argument: a braced group or a single token
+    fn argument(&mut self) -> Node {
+        self.skip_spaces();
+        if self.peek() == Some('{') {
+            self.pos += 1;
+            return Node::Group(self.list(true));
+        }
+        self.atom().unwrap_or(Node::Group(Vec::new()))
+    }
+
+    fn list(&mut self, braced: bool) -> Vec<Node> {
+        let mut nodes: Vec<Node> = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '}' => {
+                    self.pos += 1;
+                    if braced {
+                        break;
+                    }
+                }
+                '^' | '_' => {
+                    self.pos += 1;
+                    let script = Box::new(self.argument());
+                    let base = nodes.pop().unwrap_or(Node::Group(Vec::new()));
+                    let (base, mut sup, mut sub) = match base {
+                        Node::Scripts { base, sup, sub } => (base, sup, sub),
+                        other => (Box::new(other), None, None),
+                    };
+                    if c == '^' {
+                        sup = Some(script);
+                    } else {
+                        sub = Some(script);
+                    }
+                    nodes.push(Node::Scripts { base, sup, sub });
+                }
+                _ => {
+                    if let Some(node) = self.atom() {
+                        nodes.push(node);
+                    }
+                }
+            }
+        }
+        nodes
+    }
+
+    fn atom(&mut self) -> Option<Node> {
+        let c = self.peek()?;
+        self.pos += 1;
+        let glyph = |text: String, font: MathFont, space: f32| Some(Node::Glyph { text, font, space });
+        match c {
+            c if c.is_whitespace() => None,
+            '{' => Some(Node::Group(self.list(true))),
+            '\\' => self.command(),
+            '&' => Some(Node::Space(1.0)),
+            '\'' => glyph(char::from(0xA2).to_string(), MathFont::Symbol, 0.0),
+            '-' => glyph("-".to_string(), MathFont::Symbol, OP_SPACE),
+            '+' | '=' | '<' | '>' => glyph(c.to_string(), MathFont::Upright, OP_SPACE),
+            c if c.is_alphabetic() => glyph(c.to_string(), MathFont::Italic, 0.0),
+            c => glyph(c.to_string(), MathFont::Upright, 0.0),
+        }
+    }
+
+    fn command(&mut self) -> Option<Node> {
+        let name = self.command_name();
+        let space = match name.as_str() {
+            "," => Some(0.17),
+            ":" | ">" => Some(0.22),
+            ";" => Some(0.28),
+            " " => Some(0.25),
+            "!" => Some(-0.17),
+            "quad" => Some(1.0),
+            "qquad" => Some(2.0),
+            // Line breaks in aligned environments
+            "\\" | "cr" => Some(1.0),
+            _ => None,
+        };
+        if let Some(space) = space {
+            return Some(Node::Space(space));
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.argument();
+                let denominator = self.argument();
+                Some(Node::Frac(Box::new(numerator), Box::new(denominator)))
+            }
+            "sqrt" => {
+                self.skip_spaces();
+                if self.peek() == Some('[') {
+                    // The index of nth roots is not drawn
+                    while let Some(c) = self.peek() {
+                        self.pos += 1;
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                Some(Node::Sqrt(Box::new(self.argument())))
+            }
+            "text" | "textrm" | "mathrm" | "operatorname" | "mathbf" | "textbf" | "mbox" => {
+                Some(Node::Glyph { text: self.raw_group(), font: MathFont::Upright, space: 0.0 })
+            }
+            "mathit" | "textit" => Some(Node::Glyph { text: self.raw_group(), font: MathFont::Italic, space: 0.0 }),
+            "begin" | "end" => {
+                self.raw_group();
+                None
+            }
+            // Sizing commands; the delimiter that follows is drawn as is
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" | "displaystyle"
+            | "textstyle" | "limits" | "nolimits" => {
+                self.skip_spaces();
+                if self.peek() == Some('.') {
+                    self.pos += 1;
+                }
+                None
+            }
+            "{" | "}" | "%" | "$" | "#" | "&" | "_" | "|" => {
+                Some(Node::Glyph { text: name, font: MathFont::Upright, space: 0.0 })
+            }
+            "mid" | "vert" => Some(Node::Glyph { text: "|".to_string(), font: MathFont::Upright, space: OP_SPACE }),
+            name if FUNCTIONS.contains(&name) => {
+                Some(Node::Glyph { text: name.to_string(), font: MathFont::Upright, space: 0.1 })
+            }
+            name => match symbol(name) {
+                Some((code, spaced)) => Some(Node::Glyph {
+                    text: char::from(code).to_string(),
+                    font: MathFont::Symbol,
+                    space: if spaced { OP_SPACE } else { 0.0 },
+                }),
+                None => Some(Node::Glyph { text: name.to_string(), font: MathFont::Upright, space: 0.0 }),
+            },
+        }
+    }
+}
+
+/// Advance width of `text` in ems
+pub(super) fn glyph_width(text: &str, font: MathFont) -> f32 {
+    text.chars()
+        .map(|c| match (font, c) {
+            (MathFont::Symbol, '\u{E5}' | '\u{D5}') => 0.72,
+            (MathFont::Symbol, '\u{F2}') => 0.28,
+            (MathFont::Symbol, 'A'..='Z') => 0.68,
+            (MathFont::Symbol, 'a'..='z') => 0.55,
+            (MathFont::Symbol, _) => 0.6,
+            (_, 'i' | 'j' | 'l' | '.' | ',' | ';' | ':' | '!' | '|' | '\'') => 0.25,
+            (_, '(' | ')' | '[' | ']' | 'f' | 't' | 'r') => 0.33,
+            (_, 'm' | 'w' | 'M' | 'W') => 0.83,
+            (_, '+' | '=' | '<' | '>') => 0.58,
+            (_, 'A'..='Z') => 0.67,
+            (_, ' ') => 0.28,
+            _ => 0.52,
+        })
+        .sum()
+}
+
+fn layout(node: &Node, scale: f32) -> MathBox {
+    match node {
+        Node::Glyph { text, font, space } => {
+            let pad = space * scale;
+            let width = glyph_width(text, *font) * scale;
+            MathBox {
+                glyphs: vec![MathGlyph { x: pad, y: 0.0, text: text.clone(), font: *font, scale }],
+                rules: Vec::new(),
+                width: width + 2.0 * pad,
+                ascent: 0.72 * scale,
+                descent: 0.22 * scale,
+            }
+        }
+        Node::Space(em) => MathBox { width: em * scale, ..Default::default() },
+        Node::Group(nodes) => {
+            let mut out = MathBox::default();
+            for node in nodes {
+                out.append(layout(node, scale), 0.0);
+            }
+            out
+        }
+        Node::Scripts { base, sup, sub } => {
+            let mut out = layout(base, scale);
+            let script_scale = (scale * SCRIPT_SCALE).max(0.5);
+            let sup = sup.as_ref().map(|n| layout(n, script_scale));
+            let sub = sub.as_ref().map(|n| layout(n, script_scale));
+            let width = sup.iter().chain(sub.iter()).map(|b| b.width).fold(0.0, f32::max);
+            let start = out.width;
+            if let Some(sup) = sup {
+                let dy = (out.ascent - 0.35 * scale).max(0.38 * scale);
+                out.append(sup, dy);
+                out.width = start;
+            }
+            if let Some(sub) = sub {
+                out.append(sub, -0.22 * scale);
+                out.width = start;
+            }
+            out.width = start + width;
+            out
+        }
+        Node::Frac(numerator, denominator) => {
+            let inner = (scale * 0.85).max(0.5);
+            let num = layout(numerator, inner);
+            let den = layout(denominator, inner);
+            let gap = 0.12 * scale;
+            let width = num.width.max(den.width) + 0.2 * scale;
+            let axis = AXIS * scale;
+
+            let (num_dy, den_dy) = (axis + gap + num.descent, axis - gap - den.ascent);
+            let mut out = MathBox { width: (width - num.width) / 2.0, ..Default::default() };
+            out.append(num, num_dy);
+            out.width = (width - den.width) / 2.0;
+            out.append(den, den_dy);
+            out.rules.push(MathRule { x: 0.0, y: axis, width });
+            out.width = width;
+            out
+        }
+        Node::Sqrt(body) => {
+            let radical = char::from(0xD6u8).to_string();
+            let radical_width = glyph_width(&radical, MathFont::Symbol) * scale;
+            let mut out = MathBox {
+                glyphs: vec![MathGlyph { x: 0.0, y: 0.0, text: radical, font: MathFont::Symbol, scale }],
+                rules: Vec::new(),
+                width: radical_width,
+                ascent: 0.8 * scale,
+                descent: 0.22 * scale,
+            };
+            let body = layout(body, scale);
+            let top = body.ascent.max(0.72 * scale) + 0.08 * scale;
+            out.rules.push(MathRule { x: radical_width, y: top, width: body.width });
+            out.append(body, 0.0);
+            out.ascent = out.ascent.max(top + 0.05 * scale);
+            out
+        }
+    }
+}
+
+/// Typeset TeX source into a box of glyphs at scale 1
+pub(super) fn typeset(tex: &str) -> MathBox {
+    let nodes = Parser::new(tex).list(false);
+    layout(&Node::Group(nodes), 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typesets_symbols_scripts_and_fractions() {
+        let math = typeset(r"\alpha^2 + \frac{1}{x_i} \leq \sqrt{y}");
+        let alpha = &math.glyphs[0];
+        assert_eq!((alpha.text.as_str(), alpha.font), ("a", MathFont::Symbol));
+        let two = &math.glyphs[1];
+        assert!(two.y > 0.0 && two.scale < 1.0, "{:?}", two);
+
+        let i = math.glyphs.iter().find(|g| g.text == "i").unwrap();
+        assert!(i.y < 0.0);
+        // Fraction bar and radical overline
+        assert_eq!(math.rules.len(), 2);
+        let leq = math.glyphs.iter().find(|g| g.text == char::from(0xA3u8).to_string()).unwrap();
+        assert_eq!(leq.font, MathFont::Symbol);
+        assert!(math.ascent > 0.72 && math.descent > 0.22);
+
+        let text = typeset(r"\sin x \text{ for all } \unknown");
+        let names: Vec<&str> = text.glyphs.iter().map(|g| g.text.as_str()).collect();
+        assert_eq!(names, vec!["sin", "x", " for all ", "unknown"]);
+    }
+}
@@ -0,0 +1,205 @@
+//! Note export.
+//!
+//! Shared helpers for turning workspace markdown into other formats. Each
+//! output format lives in its own submodule; this module owns the Tauri
+//! commands and the pre-processing every format needs (frontmatter removal,
+//! Lokus-specific wikilink syntax, locating images relative to the note).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use walkdir::WalkDir;
+
+mod highlight;
+mod math;
+pub mod pdf;
+#[cfg(desktop)]
+pub mod print;
+pub mod workspace;
+
+// ![[embed.png]] and [[target#heading|alias]]
+static WIKILINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[\[([^\[\]|#]*)(#[^\[\]|]*)?(?:\|([^\[\]]*))?\]\]").unwrap()
+});
+
+/// Strip a leading YAML frontmatter block, returning the body
+pub(crate) fn strip_frontmatter(content: &str) -> &str {
+    let Some(rest) = content.strip_prefix("---") else {
+        return content;
+    };
+    if !rest.starts_with('\n') && !rest.starts_with("\r\n") {
+        return content;
+    }
+    match rest.find("\n---") {
+        Some(end) => {
+            let after = &rest[end + 4..];
+            after.trim_start_matches(&['\r', '\n'][..])
+        }
+        None => content,
+    }
+}
+
+/// Replace wikilink syntax with plain markdown: embeds become images and
+/// links become their alias (or note name) as text. Formats that can link
+/// between exported notes use `rewrite(target, heading, is_embed)` to produce
+/// a real link (or image source, for embeds) instead.
+pub(crate) fn flatten_wikilinks(
+    markdown: &str,
+    rewrite: impl Fn(&str, Option<&str>, bool) -> Option<String>,
+) -> String {
+    WIKILINK_RE
+        .replace_all(markdown, |caps: &Captures| {
+            let target = caps[2].trim();
+            let heading = caps.get(3).map(|m| &m.as_str()[1..]);
+            if &caps[1] == "!" {
+                let src = rewrite(target, heading, true).unwrap_or_else(|| target.replace(' ', "%20"));
+                return format!("![{}]({})", target, src);
+            }
+            let label = caps
+                .get(4)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_else(|| match heading {
+                    Some(h) if target.is_empty() => h.to_string(),
+                    _ => target.rsplit('/').next().unwrap_or(target).to_string(),
+                });
+            match rewrite(target, heading, false) {
+                Some(href) => format!("[{}]({})", label, href),
+                None => label,
+            }
+        })
+        .into_owned()
+}
+
+/// Resolve an image reference from a note to a local file, if it exists.
+/// Only files inside the workspace (or the note's folder, without one) are
+/// returned, so a note can't pull `/etc/passwd` or `../../secret` into an export.
+pub(crate) fn resolve_local_asset(note_dir: &Path, workspace: Option<&Path>, src: &str) -> Option<PathBuf> {
+    if src.contains("://") || src.starts_with("data:") {
+        return None;
+    }
+    let decoded = urlencoding::decode(src).map(|s| s.into_owned()).unwrap_or_else(|_| src.to_string());
+    let base = workspace.unwrap_or(note_dir).canonicalize().ok()?;
+    let inside = |path: PathBuf| path.canonicalize().ok().filter(|p| p.starts_with(&base) && p.is_file());
+
+    if let Some(found) = inside(note_dir.join(&decoded)) {
+        return Some(found);
+    }
+    // Wiki-style embeds are usually vault-relative or just a file name
+    let workspace = workspace?;
+    if let Some(found) = inside(workspace.join(decoded.trim_start_matches('/'))) {
+        return Some(found);
+    }
+    let file_name = Path::new(&decoded).file_name()?;
+    WalkDir::new(workspace)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".lokus" && e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.file_name() == file_name)
+        .find_map(|e| inside(e.into_path()))
+}
+
+/// Find the enclosing workspace (the nearest ancestor with a `.lokus` folder)
+pub(crate) fn find_workspace_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".lokus").is_dir())
+        .map(|dir| dir.to_path_buf())
+}
+
+// --- Tauri Commands ---
+
+/// Render a single note to PDF. Returns the path of the written file.
+#[tauri::command]
+pub async fn export_note_to_pdf(path: String, options: Option<pdf::PdfExportOptions>) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    let source = PathBuf::from(&path);
+    let output = match &options.output_path {
+        Some(output) => PathBuf::from(output),
+        None => source.with_extension("pdf"),
+    };
+
+    tokio::task::spawn_blocking(move || pdf::export_file(&source, &output, &options).map(|_| output))
+        .await
+        .map_err(|e| format!("PDF export task failed: {}", e))?
+        .map(|output| output.to_string_lossy().to_string())
+}
+
+/// Render every note in a folder (recursively) to PDF, mirroring the folder
+/// structure under `output_dir`. Returns the paths of the written files.
+#[tauri::command]
+pub async fn export_folder_to_pdf(
+    folder: String,
+    output_dir: String,
+    options: Option<pdf::PdfExportOptions>,
+) -> Result<Vec<String>, String> {
+    let options = options.unwrap_or_default();
+    let folder = PathBuf::from(folder);
+    let output_dir = PathBuf::from(output_dir);
+    if !folder.is_dir() {
+        return Err(format!("Folder does not exist: {}", folder.display()));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let mut written = Vec::new();
+        let notes = WalkDir::new(&folder)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != ".lokus" && e.file_name() != ".git")
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.file_type().is_file()
+                    && e.path().extension().map_or(false, |ext| ext.eq_ignore_ascii_case("md"))
+            });
+
+        for entry in notes {
+            let rel = entry.path().strip_prefix(&folder).map_err(|e| e.to_string())?;
+            let output = output_dir.join(rel).with_extension("pdf");
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            pdf::export_file(entry.path(), &output, &options)?;
+            written.push(output.to_string_lossy().to_string());
+        }
+        Ok(written)
+    })
+    .await
+    .map_err(|e| format!("PDF export task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_frontmatter() {
+        assert_eq!(strip_frontmatter("---\ntitle: x\n---\n\n# Body"), "# Body");
+        assert_eq!(strip_frontmatter("# No frontmatter"), "# No frontmatter");
+        assert_eq!(strip_frontmatter("----\nnot yaml"), "----\nnot yaml");
+    }
+
+    #[test]
+    fn test_flatten_wikilinks() {
+        let flat = flatten_wikilinks("See [[Folder/Note|the note]], [[Other#Part]] and ![[pic one.png]]", |_, _, _| None);
+        assert_eq!(flat, "See the note, Other and ![pic one.png](pic%20one.png)");
+
+        let linked = flatten_wikilinks("[[Note]]", |target, _, _| Some(format!("{}.html", target)));
+        assert_eq!(linked, "[Note](Note.html)");
+    }
+
+    #[test]
+    fn test_resolve_local_asset_stays_in_workspace() {
+        let outer = tempfile::tempdir().unwrap();
+        let root = outer.path().join("vault");
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join("pic.png"), "png").unwrap();
+        fs::write(outer.path().join("secret.png"), "secret").unwrap();
+        let notes = root.join("notes");
+
+        let found = resolve_local_asset(&notes, Some(&root), "../pic.png").unwrap();
+        assert_eq!(found, root.join("pic.png").canonicalize().unwrap());
+        assert!(resolve_local_asset(&notes, Some(&root), "/pic.png").is_some());
+        assert!(resolve_local_asset(&notes, Some(&root), "../../secret.png").is_none());
+        let absolute = outer.path().join("secret.png");
+        assert!(resolve_local_asset(&notes, Some(&root), &absolute.to_string_lossy()).is_none());
+        assert!(resolve_local_asset(&notes, None, "../pic.png").is_none());
+    }
+}
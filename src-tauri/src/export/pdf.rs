@@ -0,0 +1,1177 @@
+//! Markdown to PDF rendering.
+//!
+//! Notes are parsed with pulldown-cmark into a small block model, laid out
+//! into pages of draw operations, and only then written with printpdf. Laying
+//! out first means the page count is known when headers and footers are drawn.
+//!
+//! Text uses the PDF base-14 fonts so no font files need to be bundled; those
+//! fonts only cover Latin text, so other characters are transliterated where
+//! possible. Math is typeset by [`super::math`] with Greek letters and
+//! operators from the Symbol font, and fenced code is coloured by
+//! [`super::highlight`] using the theme's status colours.
+
+use super::highlight::{self, TokenKind};
+use super::math::{self, MathBox, MathFont};
+use printpdf::{
+    BuiltinFont, Color, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument,
+    PdfDocumentReference, PdfLayerReference, Point, Rect, Rgb,
+};
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+const PT_TO_MM: f32 = 0.352_778;
+const LINE_SPACING: f32 = 1.4;
+const LIST_INDENT: f32 = 6.0;
+const QUOTE_INDENT: f32 = 5.0;
+const CODE_PADDING: f32 = 2.0;
+/// Pixel density assumed for images that are small enough to print unscaled
+const IMAGE_DPI: f32 = 96.0;
+
+// --- Options ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfMargins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Default for PdfMargins {
+    fn default() -> Self {
+        Self { top: 20.0, right: 20.0, bottom: 20.0, left: 20.0 }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfExportOptions {
+    /// "A4" (default), "A5", "Letter" or "Legal"
+    pub page_size: Option<String>,
+    #[serde(default)]
+    pub landscape: bool,
+    /// Margins in millimetres
+    pub margins: Option<PdfMargins>,
+    /// Body font size in points
+    pub font_size: Option<f32>,
+    /// Supports `{title}`, `{page}`, `{pages}` and `{date}`
+    pub header_template: Option<String>,
+    /// Supports `{title}`, `{page}`, `{pages}` and `{date}`; defaults to `{page} / {pages}`
+    pub footer_template: Option<String>,
+    /// Id of an installed theme whose colours should be used
+    pub theme: Option<String>,
+    /// Theme tokens (`--bg`, `--text`, ...) passed directly, e.g. the active theme
+    pub theme_tokens: Option<HashMap<String, String>>,
+    /// Destination file; defaults to the note path with a `.pdf` extension
+    pub output_path: Option<String>,
+}
+
+fn page_dimensions(options: &PdfExportOptions) -> (f32, f32) {
+    let (w, h) = match options.page_size.as_deref().map(|s| s.to_lowercase()).as_deref() {
+        Some("a5") => (148.0, 210.0),
+        Some("letter") => (215.9, 279.4),
+        Some("legal") => (215.9, 355.6),
+        _ => (210.0, 297.0),
+    };
+    if options.landscape { (h, w) } else { (w, h) }
+}
+
+// --- Theme ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Rgb8(u8, u8, u8);
+
+impl Rgb8 {
+    fn to_color(self) -> Color {
+        Color::Rgb(Rgb::new(
+            self.0 as f32 / 255.0,
+            self.1 as f32 / 255.0,
+            self.2 as f32 / 255.0,
+            None,
+        ))
+    }
+
+    fn is_white(self) -> bool {
+        self.0 > 250 && self.1 > 250 && self.2 > 250
+    }
+
+    pub(super) fn to_css(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Parse the two colour formats theme tokens use: `#rrggbb` and `r g b`
+pub(super) fn parse_color(value: &str) -> Option<Rgb8> {
+    let value = value.trim();
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Rgb8(channel(0)?, channel(2)?, channel(4)?));
+    }
+    let parts: Vec<u8> = value
+        .split_whitespace()
+        .map(|p| p.parse().ok())
+        .collect::<Option<Vec<u8>>>()?;
+    match parts.as_slice() {
+        [r, g, b] => Some(Rgb8(*r, *g, *b)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Palette {
+    text: Rgb8,
+    background: Rgb8,
+    accent: Rgb8,
+    muted: Rgb8,
+    panel: Rgb8,
+    border: Rgb8,
+    /// Code highlighting: keywords, strings, numbers and types
+    keyword: Rgb8,
+    string: Rgb8,
+    number: Rgb8,
+    kind: Rgb8,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            text: Rgb8(0x1f, 0x23, 0x28),
+            background: Rgb8(0xff, 0xff, 0xff),
+            accent: Rgb8(0x09, 0x69, 0xda),
+            muted: Rgb8(0x6e, 0x77, 0x81),
+            panel: Rgb8(0xf6, 0xf8, 0xfa),
+            border: Rgb8(0xd0, 0xd7, 0xde),
+            keyword: Rgb8(0xcf, 0x22, 0x2e),
+            string: Rgb8(0x1a, 0x7f, 0x37),
+            number: Rgb8(0x05, 0x50, 0xae),
+            kind: Rgb8(0x95, 0x38, 0x00),
+        }
+    }
+}
+
+impl Palette {
+    fn from_tokens(tokens: &HashMap<String, String>) -> Self {
+        let mut palette = Self::default();
+        let mut apply = |token: &str, slot: &mut Rgb8| {
+            if let Some(color) = tokens.get(token).and_then(|v| parse_color(v)) {
+                *slot = color;
+            }
+        };
+        apply("--text", &mut palette.text);
+        apply("--bg", &mut palette.background);
+        apply("--accent", &mut palette.accent);
+        apply("--muted", &mut palette.muted);
+        apply("--panel", &mut palette.panel);
+        apply("--border", &mut palette.border);
+        apply("--danger", &mut palette.keyword);
+        apply("--success", &mut palette.string);
+        apply("--info", &mut palette.number);
+        apply("--warning", &mut palette.kind);
+        palette
+    }
+
+    fn token(&self, kind: TokenKind) -> Rgb8 {
+        match kind {
+            TokenKind::Plain => self.text,
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Type => self.kind,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.muted,
+        }
+    }
+
+    fn resolve(options: &PdfExportOptions) -> Result<Self, String> {
+        if let Some(tokens) = &options.theme_tokens {
+            return Ok(Self::from_tokens(tokens));
+        }
+        match &options.theme {
+            Some(theme_id) => crate::theme::get_theme_tokens(theme_id.clone()).map(|t| Self::from_tokens(&t)),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+// --- Block model ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FontKind {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+    Symbol,
+}
+
+impl From<MathFont> for FontKind {
+    fn from(font: MathFont) -> Self {
+        match font {
+            MathFont::Upright => FontKind::Regular,
+            MathFont::Italic => FontKind::Italic,
+            MathFont::Symbol => FontKind::Symbol,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Span {
+    text: String,
+    font: FontKind,
+    link: bool,
+    /// Inline math, laid out as one unbreakable box; `text` keeps the source
+    math: Option<MathBox>,
+}
+
+impl Span {
+    fn new(text: impl Into<String>, font: FontKind) -> Self {
+        Self { text: text.into(), font, link: false, math: None }
+    }
+
+    fn width(&self, size_pt: f32) -> f32 {
+        match &self.math {
+            Some(math) => math.width * size_pt * PT_TO_MM,
+            None => text_width(self.text.trim_end(), self.font, size_pt),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading { level: u8, spans: Vec<Span> },
+    Paragraph { spans: Vec<Span>, indent: f32, prefix: Option<String>, quote: bool },
+    Code { text: String, lang: String, indent: f32 },
+    Math { text: String },
+    Rule,
+    Image { src: String, alt: String, indent: f32 },
+    Table { rows: Vec<Vec<String>> },
+}
+
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<Block>,
+    spans: Vec<Span>,
+    bold: u32,
+    italic: u32,
+    link: u32,
+    heading: Option<u8>,
+    quote_depth: usize,
+    list_stack: Vec<Option<u64>>,
+    pending_prefix: Option<String>,
+    code: Option<(String, String)>,
+    image: Option<(String, String)>,
+    table: Option<Vec<Vec<String>>>,
+    cell: Option<String>,
+    in_metadata: bool,
+}
+
+impl BlockBuilder {
+    fn font(&self) -> FontKind {
+        match (self.bold > 0, self.italic > 0) {
+            (true, true) => FontKind::BoldItalic,
+            (true, false) => FontKind::Bold,
+            (false, true) => FontKind::Italic,
+            (false, false) => FontKind::Regular,
+        }
+    }
+
+    fn indent(&self) -> f32 {
+        self.list_stack.len() as f32 * LIST_INDENT + self.quote_depth as f32 * QUOTE_INDENT
+    }
+
+    fn push_text(&mut self, text: &str, font: FontKind) {
+        if let Some(cell) = &mut self.cell {
+            cell.push_str(text);
+            return;
+        }
+        self.spans.push(Span {
+            text: text.to_string(),
+            font,
+            link: self.link > 0,
+            math: None,
+        });
+    }
+
+    fn flush_paragraph(&mut self) {
+        if self.spans.is_empty() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        self.blocks.push(Block::Paragraph {
+            spans,
+            indent: self.indent(),
+            prefix: self.pending_prefix.take(),
+            quote: self.quote_depth > 0,
+        });
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(_) if self.in_metadata => {}
+            Event::Text(text) => {
+                if let Some((code, _)) = &mut self.code {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = &mut self.image {
+                    alt.push_str(&text);
+                } else {
+                    let font = self.font();
+                    self.push_text(&text, font);
+                }
+            }
+            Event::Code(text) => self.push_text(&text, FontKind::Mono),
+            Event::InlineMath(text) if self.cell.is_some() => self.push_text(&text, FontKind::Mono),
+            Event::InlineMath(text) => self.spans.push(Span {
+                text: text.to_string(),
+                font: FontKind::Regular,
+                link: false,
+                math: Some(math::typeset(&text)),
+            }),
+            Event::DisplayMath(text) => {
+                self.flush_paragraph();
+                self.blocks.push(Block::Math { text: text.trim().to_string() });
+            }
+            Event::SoftBreak => self.push_text(" ", self.font()),
+            Event::HardBreak => self.push_text("\n", self.font()),
+            Event::Rule => {
+                self.flush_paragraph();
+                self.blocks.push(Block::Rule);
+            }
+            Event::TaskListMarker(checked) => {
+                self.pending_prefix = Some(if checked { "[x]" } else { "[ ]" }.to_string());
+            }
+            Event::FootnoteReference(label) => self.push_text(&format!("[{}]", label), FontKind::Regular),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush_paragraph();
+                self.heading = Some(level as u8);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_paragraph();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((String::new(), lang));
+            }
+            Tag::List(start) => {
+                self.flush_paragraph();
+                self.list_stack.push(start);
+            }
+            Tag::Item => {
+                self.flush_paragraph();
+                let prefix = match self.list_stack.last_mut() {
+                    Some(Some(number)) => {
+                        let prefix = format!("{}.", number);
+                        *number += 1;
+                        prefix
+                    }
+                    _ => "•".to_string(),
+                };
+                self.pending_prefix = Some(prefix);
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Link { .. } => self.link += 1,
+            Tag::Image { dest_url, .. } => {
+                self.flush_paragraph();
+                self.image = Some((dest_url.to_string(), String::new()));
+            }
+            Tag::Table(_) => {
+                self.flush_paragraph();
+                self.table = Some(Vec::new());
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(rows) = &mut self.table {
+                    rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => self.cell = Some(String::new()),
+            Tag::MetadataBlock(_) => self.in_metadata = true,
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush_paragraph(),
+            TagEnd::Heading(_) => {
+                let spans = std::mem::take(&mut self.spans);
+                let level = self.heading.take().unwrap_or(1);
+                self.blocks.push(Block::Heading { level, spans });
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                if let Some((text, lang)) = self.code.take() {
+                    let indent = self.indent();
+                    self.blocks.push(Block::Code {
+                        text: text.trim_end_matches('\n').to_string(),
+                        lang,
+                        indent,
+                    });
+                }
+            }
+            TagEnd::List(_) => {
+                self.flush_paragraph();
+                self.list_stack.pop();
+            }
+            // Tight list items have no paragraph wrapper
+            TagEnd::Item => self.flush_paragraph(),
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Link => self.link = self.link.saturating_sub(1),
+            TagEnd::Image => {
+                if let Some((src, alt)) = self.image.take() {
+                    let indent = self.indent();
+                    self.blocks.push(Block::Image { src, alt, indent });
+                }
+            }
+            TagEnd::TableCell => {
+                if let (Some(cell), Some(rows)) = (self.cell.take(), &mut self.table) {
+                    if let Some(row) = rows.last_mut() {
+                        row.push(cell.trim().to_string());
+                    }
+                }
+            }
+            TagEnd::Table => {
+                if let Some(rows) = self.table.take() {
+                    self.blocks.push(Block::Table { rows });
+                }
+            }
+            TagEnd::MetadataBlock(_) => self.in_metadata = false,
+            _ => {}
+        }
+    }
+}
+
+fn parse_blocks(markdown: &str) -> Vec<Block> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_MATH);
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+
+    let mut builder = BlockBuilder::default();
+    for event in Parser::new_ext(markdown, options) {
+        builder.handle(event);
+    }
+    builder.flush_paragraph();
+    builder.blocks
+}
+
+// --- Text measurement ---
+
+/// Helvetica advance widths (1/1000 em) for ASCII 32..=126
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+fn char_width_em(c: char, font: FontKind) -> f32 {
+    match font {
+        FontKind::Mono => return 0.6,
+        FontKind::Symbol => return math::glyph_width(&c.to_string(), MathFont::Symbol),
+        _ => {}
+    }
+    let base = match c as u32 {
+        32..=126 => HELVETICA_WIDTHS[(c as u32 - 32) as usize] as f32 / 1000.0,
+        _ => 0.556,
+    };
+    match font {
+        // Helvetica-Bold runs roughly 5% wider than the regular cut
+        FontKind::Bold | FontKind::BoldItalic => base * 1.05,
+        _ => base,
+    }
+}
+
+fn text_width(text: &str, font: FontKind, size_pt: f32) -> f32 {
+    text.chars().map(|c| char_width_em(c, font)).sum::<f32>() * size_pt * PT_TO_MM
+}
+
+/// Fold text into the Latin range covered by the base-14 fonts
+fn to_pdf_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ' '..='~' => out.push(c),
+            '\t' => out.push_str("    "),
+            '‘' | '’' | '‚' | '′' => out.push('\''),
+            '“' | '”' | '„' | '″' => out.push('"'),
+            '–' | '—' | '−' => out.push('-'),
+            '…' => out.push_str("..."),
+            '•' | '·' => out.push('*'),
+            '→' => out.push_str("->"),
+            '←' => out.push_str("<-"),
+            '\u{a0}' => out.push(' '),
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => out.push('a'),
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' => out.push('A'),
+            'è' | 'é' | 'ê' | 'ë' => out.push('e'),
+            'È' | 'É' | 'Ê' | 'Ë' => out.push('E'),
+            'ì' | 'í' | 'î' | 'ï' => out.push('i'),
+            'Ì' | 'Í' | 'Î' | 'Ï' => out.push('I'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => out.push('o'),
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' => out.push('O'),
+            'ù' | 'ú' | 'û' | 'ü' => out.push('u'),
+            'Ù' | 'Ú' | 'Û' | 'Ü' => out.push('U'),
+            'ç' => out.push('c'),
+            'Ç' => out.push('C'),
+            'ñ' => out.push('n'),
+            'Ñ' => out.push('N'),
+            'ß' => out.push_str("ss"),
+            'ý' | 'ÿ' => out.push('y'),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Greedy word wrap of styled spans into lines no wider than `max_width` mm
+fn wrap_spans(spans: &[Span], max_width: f32, size_pt: f32) -> Vec<Vec<Span>> {
+    let mut lines: Vec<Vec<Span>> = vec![Vec::new()];
+    let mut line_width = 0.0;
+
+    // Split into words that keep their trailing whitespace
+    let mut words: Vec<Span> = Vec::new();
+    for span in spans {
+        if span.math.is_some() {
+            words.push(span.clone());
+            continue;
+        }
+        let text = to_pdf_text(&span.text);
+        let mut current = String::new();
+        for c in text.chars() {
+            if c == '\n' {
+                if !current.is_empty() {
+                    words.push(Span { text: std::mem::take(&mut current), ..span.clone() });
+                }
+                words.push(Span { text: "\n".to_string(), ..span.clone() });
+                continue;
+            }
+            if c != ' ' && current.ends_with(' ') {
+                words.push(Span { text: std::mem::take(&mut current), ..span.clone() });
+            }
+            current.push(c);
+        }
+        if !current.is_empty() {
+            words.push(Span { text: current, ..span.clone() });
+        }
+    }
+
+    for mut word in words {
+        if word.text == "\n" {
+            lines.push(Vec::new());
+            line_width = 0.0;
+            continue;
+        }
+
+        let width = word.width(size_pt);
+        if line_width + width > max_width && line_width > 0.0 {
+            lines.push(Vec::new());
+            line_width = 0.0;
+            if word.math.is_none() {
+                word.text = word.text.trim_start().to_string();
+            }
+        }
+
+        // A single word wider than the line gets broken by characters
+        if width > max_width && word.math.is_none() {
+            let mut chunk = String::new();
+            for c in word.text.chars() {
+                let chunk_width = text_width(&chunk, word.font, size_pt) + char_width_em(c, word.font) * size_pt * PT_TO_MM;
+                if chunk_width > max_width && !chunk.is_empty() {
+                    lines.last_mut().unwrap().push(Span { text: std::mem::take(&mut chunk), ..word.clone() });
+                    lines.push(Vec::new());
+                }
+                chunk.push(c);
+            }
+            line_width = text_width(&chunk, word.font, size_pt);
+            lines.last_mut().unwrap().push(Span { text: chunk, ..word });
+            continue;
+        }
+
+        line_width += match &word.math {
+            Some(_) => width,
+            None => text_width(&word.text, word.font, size_pt),
+        };
+        lines.last_mut().unwrap().push(word);
+    }
+
+    lines.retain(|line| !line.is_empty());
+    lines
+}
+
+// --- Layout ---
+
+#[derive(Debug, Clone)]
+enum DrawOp {
+    Text { x: f32, y: f32, text: String, font: FontKind, size: f32, color: Rgb8 },
+    Rect { x: f32, y: f32, w: f32, h: f32, color: Rgb8 },
+    Line { x1: f32, y1: f32, x2: f32, y2: f32, color: Rgb8, thickness: f32 },
+    Image { path: PathBuf, x: f32, y: f32, w: f32, h: f32 },
+}
+
+/// Lays blocks out top-down; all coordinates are millimetres from the top-left
+struct Layout<'a> {
+    page_width: f32,
+    page_height: f32,
+    margins: PdfMargins,
+    base_size: f32,
+    palette: Palette,
+    note_dir: &'a Path,
+    workspace: Option<&'a Path>,
+    pages: Vec<Vec<DrawOp>>,
+    y: f32,
+}
+
+impl<'a> Layout<'a> {
+    fn content_width(&self) -> f32 {
+        self.page_width - self.margins.left - self.margins.right
+    }
+
+    fn bottom(&self) -> f32 {
+        self.page_height - self.margins.bottom
+    }
+
+    fn line_height(&self, size_pt: f32) -> f32 {
+        size_pt * PT_TO_MM * LINE_SPACING
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = self.margins.top;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y + height > self.bottom() && self.y > self.margins.top {
+            self.new_page();
+        }
+    }
+
+    fn push(&mut self, op: DrawOp) {
+        self.pages.last_mut().expect("layout always has a page").push(op);
+    }
+
+    fn text_lines(&mut self, spans: &[Span], indent: f32, size: f32, color: Rgb8, quote: bool) {
+        let x0 = self.margins.left + indent;
+        let width = self.content_width() - indent;
+        let line_height = self.line_height(size);
+
+        for line in wrap_spans(spans, width, size) {
+            // Fractions and scripts in inline math make the line taller
+            let em = size * PT_TO_MM;
+            let (ascent, descent) = line
+                .iter()
+                .filter_map(|s| s.math.as_ref())
+                .fold((1.0f32, LINE_SPACING - 1.0), |(a, d), m| (a.max(m.ascent + 0.1), d.max(m.descent + 0.1)));
+            let line_height = line_height.max((ascent + descent) * em);
+
+            self.ensure_space(line_height);
+            if quote {
+                let bar_x = self.margins.left + indent - QUOTE_INDENT + 1.0;
+                self.push(DrawOp::Rect { x: bar_x, y: self.y, w: 0.8, h: line_height, color: self.palette.border });
+            }
+            let baseline = self.y + ascent * em;
+            let mut x = x0;
+            for span in line {
+                let span_color = if span.link { self.palette.accent } else { color };
+                let advance = match &span.math {
+                    Some(_) => span.width(size),
+                    None => text_width(&span.text, span.font, size),
+                };
+                match span.math {
+                    Some(math) => self.math(&math, x, baseline, size, span_color),
+                    None => self.push(DrawOp::Text { x, y: baseline, text: span.text, font: span.font, size, color: span_color }),
+                }
+                x += advance;
+            }
+            self.y += line_height;
+        }
+    }
+
+    /// Draw typeset math with its baseline at `baseline`
+    fn math(&mut self, math: &MathBox, x: f32, baseline: f32, size: f32, color: Rgb8) {
+        let em = size * PT_TO_MM;
+        for glyph in &math.glyphs {
+            let font = FontKind::from(glyph.font);
+            let text = if font == FontKind::Symbol { glyph.text.clone() } else { to_pdf_text(&glyph.text) };
+            self.push(DrawOp::Text {
+                x: x + glyph.x * em,
+                y: baseline - glyph.y * em,
+                text,
+                font,
+                size: size * glyph.scale,
+                color,
+            });
+        }
+        for rule in &math.rules {
+            let y = baseline - rule.y * em;
+            self.push(DrawOp::Line { x1: x + rule.x * em, y1: y, x2: x + (rule.x + rule.width) * em, y2: y, color, thickness: 0.4 });
+        }
+    }
+
+    fn block(&mut self, block: Block) {
+        let base = self.base_size;
+        let gap = base * PT_TO_MM * 0.6;
+
+        match block {
+            Block::Heading { level, spans } => {
+                let scale = match level {
+                    1 => 1.9,
+                    2 => 1.6,
+                    3 => 1.3,
+                    4 => 1.15,
+                    _ => 1.0,
+                };
+                let size = base * scale;
+                let spans: Vec<Span> = spans
+                    .into_iter()
+                    .map(|s| Span { font: if s.font == FontKind::Mono { s.font } else { FontKind::Bold }, ..s })
+                    .collect();
+                self.y += gap;
+                // Keep a heading together with at least one line of what follows
+                self.ensure_space(self.line_height(size) + self.line_height(base));
+                self.text_lines(&spans, 0.0, size, self.palette.text, false);
+                if level <= 2 {
+                    let y = self.y + 0.5;
+                    self.push(DrawOp::Line {
+                        x1: self.margins.left,
+                        y1: y,
+                        x2: self.page_width - self.margins.right,
+                        y2: y,
+                        color: self.palette.border,
+                        thickness: 0.5,
+                    });
+                    self.y += 1.5;
+                }
+                self.y += gap * 0.5;
+            }
+            Block::Paragraph { spans, indent, prefix, quote } => {
+                if let Some(prefix) = prefix {
+                    let prefix_width = text_width(&to_pdf_text(&prefix), FontKind::Regular, base) + 1.5;
+                    self.ensure_space(self.line_height(base));
+                    let x = self.margins.left + indent - prefix_width;
+                    let y = self.y + base * PT_TO_MM;
+                    self.push(DrawOp::Text {
+                        x,
+                        y,
+                        text: to_pdf_text(&prefix),
+                        font: FontKind::Regular,
+                        size: base,
+                        color: self.palette.muted,
+                    });
+                }
+                let color = if quote { self.palette.muted } else { self.palette.text };
+                self.text_lines(&spans, indent, base, color, quote);
+                self.y += gap;
+            }
+            Block::Code { text, lang, indent } => {
+                let size = base * 0.85;
+                let line_height = self.line_height(size);
+                let x = self.margins.left + indent;
+                let width = self.content_width() - indent;
+                let max_chars = ((width - 2.0 * CODE_PADDING) / (0.6 * size * PT_TO_MM)).floor().max(1.0) as usize;
+
+                let char_width = 0.6 * size * PT_TO_MM;
+
+                self.y += CODE_PADDING;
+                for tokens in highlight::highlight(&text, &lang) {
+                    let chars: Vec<(char, TokenKind)> = tokens
+                        .iter()
+                        .flat_map(|(kind, token)| to_pdf_text(token).chars().map(move |c| (c, *kind)).collect::<Vec<_>>())
+                        .collect();
+                    let chunks: Vec<&[(char, TokenKind)]> =
+                        if chars.is_empty() { vec![&[]] } else { chars.chunks(max_chars).collect() };
+                    for chunk in chunks {
+                        self.ensure_space(line_height);
+                        self.push(DrawOp::Rect { x, y: self.y - 0.01, w: width, h: line_height + 0.02, color: self.palette.panel });
+                        // One text op per run of the same token kind
+                        let mut column = 0;
+                        for run in chunk.chunk_by(|a, b| a.1 == b.1) {
+                            self.push(DrawOp::Text {
+                                x: x + CODE_PADDING + column as f32 * char_width,
+                                y: self.y + size * PT_TO_MM,
+                                text: run.iter().map(|(c, _)| c).collect(),
+                                font: FontKind::Mono,
+                                size,
+                                color: self.palette.token(run[0].1),
+                            });
+                            column += run.len();
+                        }
+                        self.y += line_height;
+                    }
+                }
+                self.y += CODE_PADDING + gap;
+            }
+            Block::Math { text } => {
+                let math = math::typeset(&text);
+                let em = base * PT_TO_MM;
+                // Shrink equations that are wider than the page
+                let size = base * (self.content_width() / (math.width * em)).min(1.0);
+                let em = size * PT_TO_MM;
+                let height = (math.ascent + math.descent) * em;
+                self.ensure_space(height + gap);
+                let x = self.margins.left + (self.content_width() - math.width * em) / 2.0;
+                let baseline = self.y + gap * 0.5 + math.ascent * em;
+                self.math(&math, x, baseline, size, self.palette.text);
+                self.y += height + gap * 1.5;
+            }
+            Block::Rule => {
+                self.ensure_space(gap * 2.0);
+                self.y += gap;
+                self.push(DrawOp::Line {
+                    x1: self.margins.left,
+                    y1: self.y,
+                    x2: self.page_width - self.margins.right,
+                    y2: self.y,
+                    color: self.palette.border,
+                    thickness: 0.5,
+                });
+                self.y += gap;
+            }
+            Block::Image { src, alt, indent } => self.image(&src, &alt, indent),
+            Block::Table { rows } => self.table(rows),
+        }
+    }
+
+    fn image(&mut self, src: &str, alt: &str, indent: f32) {
+        let resolved = crate::export::resolve_local_asset(self.note_dir, self.workspace, src);
+        let dimensions = resolved
+            .as_ref()
+            .and_then(|p| printpdf::image_crate::image_dimensions(p).ok());
+
+        let (Some(path), Some((px_w, px_h))) = (resolved, dimensions) else {
+            // Remote or missing images are shown by their alt text
+            let label = if alt.is_empty() { src.to_string() } else { alt.to_string() };
+            let spans = [Span::new(format!("[image: {}]", label), FontKind::Italic)];
+            self.text_lines(&spans, indent, self.base_size, self.palette.muted, false);
+            return;
+        };
+
+        let max_w = self.content_width() - indent;
+        let max_h = self.bottom() - self.margins.top;
+        let natural_w = px_w as f32 / IMAGE_DPI * 25.4;
+        let natural_h = px_h as f32 / IMAGE_DPI * 25.4;
+        let scale = (max_w / natural_w).min(max_h / natural_h).min(1.0);
+        let (w, h) = (natural_w * scale, natural_h * scale);
+
+        self.ensure_space(h);
+        self.push(DrawOp::Image { path, x: self.margins.left + indent, y: self.y, w, h });
+        self.y += h + self.base_size * PT_TO_MM * 0.6;
+    }
+
+    fn table(&mut self, rows: Vec<Vec<String>>) {
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let size = self.base_size * 0.9;
+        let line_height = self.line_height(size);
+        let col_width = self.content_width() / columns as f32;
+        let padding = 1.5;
+
+        for (row_index, row) in rows.into_iter().enumerate() {
+            let font = if row_index == 0 { FontKind::Bold } else { FontKind::Regular };
+            let cells: Vec<Vec<Vec<Span>>> = row
+                .iter()
+                .map(|cell| {
+                    let span = Span::new(cell.clone(), font);
+                    wrap_spans(&[span], col_width - 2.0 * padding, size)
+                })
+                .collect();
+            let row_lines = cells.iter().map(|c| c.len()).max().unwrap_or(1).max(1);
+            let row_height = row_lines as f32 * line_height + 2.0 * padding;
+
+            self.ensure_space(row_height);
+            if row_index == 0 {
+                self.push(DrawOp::Rect { x: self.margins.left, y: self.y, w: self.content_width(), h: row_height, color: self.palette.panel });
+            }
+            for (col, lines) in cells.into_iter().enumerate() {
+                let x = self.margins.left + col as f32 * col_width + padding;
+                for (i, line) in lines.into_iter().enumerate() {
+                    let y = self.y + padding + i as f32 * line_height + size * PT_TO_MM;
+                    let text: String = line.into_iter().map(|s| s.text).collect();
+                    self.push(DrawOp::Text { x, y, text, font, size, color: self.palette.text });
+                }
+            }
+            self.y += row_height;
+            self.push(DrawOp::Line {
+                x1: self.margins.left,
+                y1: self.y,
+                x2: self.page_width - self.margins.right,
+                y2: self.y,
+                color: self.palette.border,
+                thickness: 0.3,
+            });
+        }
+        self.y += self.base_size * PT_TO_MM * 0.6;
+    }
+}
+
+fn fill_template(template: &str, title: &str, page: usize, pages: usize) -> String {
+    template
+        .replace("{title}", title)
+        .replace("{page}", &page.to_string())
+        .replace("{pages}", &pages.to_string())
+        .replace("{date}", &chrono::Local::now().format("%Y-%m-%d").to_string())
+}
+
+// --- Rendering ---
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    italic: IndirectFontRef,
+    bold_italic: IndirectFontRef,
+    mono: IndirectFontRef,
+    symbol: IndirectFontRef,
+}
+
+impl Fonts {
+    fn load(doc: &PdfDocumentReference) -> Result<Self, String> {
+        let add = |font| doc.add_builtin_font(font).map_err(|e| format!("Failed to load PDF font: {}", e));
+        Ok(Self {
+            regular: add(BuiltinFont::Helvetica)?,
+            bold: add(BuiltinFont::HelveticaBold)?,
+            italic: add(BuiltinFont::HelveticaOblique)?,
+            bold_italic: add(BuiltinFont::HelveticaBoldOblique)?,
+            mono: add(BuiltinFont::Courier)?,
+            symbol: add(BuiltinFont::Symbol)?,
+        })
+    }
+
+    fn get(&self, kind: FontKind) -> &IndirectFontRef {
+        match kind {
+            FontKind::Regular => &self.regular,
+            FontKind::Bold => &self.bold,
+            FontKind::Italic => &self.italic,
+            FontKind::BoldItalic => &self.bold_italic,
+            FontKind::Mono => &self.mono,
+            FontKind::Symbol => &self.symbol,
+        }
+    }
+}
+
+fn draw(layer: &PdfLayerReference, fonts: &Fonts, page_height: f32, op: DrawOp) {
+    // PDF coordinates grow upwards from the bottom-left corner
+    let flip = |y: f32| Mm(page_height - y);
+    match op {
+        DrawOp::Text { x, y, text, font, size, color } => {
+            layer.set_fill_color(color.to_color());
+            layer.use_text(text, size, Mm(x), flip(y), fonts.get(font));
+        }
+        DrawOp::Rect { x, y, w, h, color } => {
+            layer.set_fill_color(color.to_color());
+            layer.add_rect(Rect::new(Mm(x), flip(y + h), Mm(x + w), flip(y)));
+        }
+        DrawOp::Line { x1, y1, x2, y2, color, thickness } => {
+            layer.set_outline_color(color.to_color());
+            layer.set_outline_thickness(thickness);
+            layer.add_line(Line {
+                points: vec![(Point::new(Mm(x1), flip(y1)), false), (Point::new(Mm(x2), flip(y2)), false)],
+                is_closed: false,
+            });
+        }
+        DrawOp::Image { path, x, y, w, h: _ } => {
+            let Ok(image) = printpdf::image_crate::open(&path) else {
+                tracing::warn!("Failed to load image for PDF export: {}", path.display());
+                return;
+            };
+            // Flatten alpha; printpdf embeds RGB data
+            let image = printpdf::image_crate::DynamicImage::ImageRgb8(image.to_rgb8());
+            let height_px = image.height() as f32;
+            let dpi = image.width() as f32 * 25.4 / w;
+            let h = height_px * 25.4 / dpi;
+            printpdf::Image::from_dynamic_image(&image).add_to_layer(
+                layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(x)),
+                    translate_y: Some(flip(y + h)),
+                    dpi: Some(dpi),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+/// Lay out markdown into pages of draw operations
+fn layout_markdown(markdown: &str, options: &PdfExportOptions, palette: Palette, note_dir: &Path, workspace: Option<&Path>) -> (f32, f32, Vec<Vec<DrawOp>>) {
+    let (page_width, page_height) = page_dimensions(options);
+    let margins = options.margins.clone().unwrap_or_default();
+    let mut layout = Layout {
+        page_width,
+        page_height,
+        y: margins.top,
+        margins,
+        base_size: options.font_size.unwrap_or(11.0).clamp(6.0, 24.0),
+        palette,
+        note_dir,
+        workspace,
+        pages: vec![Vec::new()],
+    };
+
+    for block in parse_blocks(markdown) {
+        layout.block(block);
+    }
+    (page_width, page_height, layout.pages)
+}
+
+/// Render a markdown note to a PDF file
+pub fn export_file(source: &Path, output: &Path, options: &PdfExportOptions) -> Result<(), String> {
+    let content = fs::read_to_string(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    let title = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let note_dir = source.parent().unwrap_or(Path::new("."));
+    let workspace = super::find_workspace_root(source);
+    let markdown = super::flatten_wikilinks(super::strip_frontmatter(&content), |_, _, _| None);
+    let palette = Palette::resolve(options)?;
+
+    let (page_width, page_height, pages) =
+        layout_markdown(&markdown, options, palette, note_dir, workspace.as_deref());
+    let margins = options.margins.clone().unwrap_or_default();
+    let page_count = pages.len();
+
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(&title, Mm(page_width), Mm(page_height), "Content");
+    let fonts = Fonts::load(&doc)?;
+    let footer = options.footer_template.clone().unwrap_or_else(|| "{page} / {pages}".to_string());
+    let chrome_size = 8.0;
+
+    for (index, ops) in pages.into_iter().enumerate() {
+        let layer = if index == 0 {
+            doc.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = doc.add_page(Mm(page_width), Mm(page_height), "Content");
+            doc.get_page(page).get_layer(layer)
+        };
+
+        if !palette.background.is_white() {
+            draw(&layer, &fonts, page_height, DrawOp::Rect { x: 0.0, y: 0.0, w: page_width, h: page_height, color: palette.background });
+        }
+
+        let mut chrome = Vec::new();
+        if let Some(header) = &options.header_template {
+            chrome.push((fill_template(header, &title, index + 1, page_count), margins.top / 2.0));
+        }
+        if !footer.is_empty() {
+            chrome.push((fill_template(&footer, &title, index + 1, page_count), page_height - margins.bottom / 2.0));
+        }
+        for (text, y) in chrome {
+            let text = to_pdf_text(&text);
+            let width = text_width(&text, FontKind::Regular, chrome_size);
+            let x = (page_width - width) / 2.0;
+            draw(&layer, &fonts, page_height, DrawOp::Text { x, y, text, font: FontKind::Regular, size: chrome_size, color: palette.muted });
+        }
+
+        for op in ops {
+            draw(&layer, &fonts, page_height, op);
+        }
+    }
+
+    let file = File::create(output).map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks_structure() {
+        let blocks = parse_blocks("# Title\n\nSome **bold** text.\n\n- one\n- two\n\n```rust\nfn main() {}\n```\n\n$$x^2$$\n");
+        assert!(matches!(&blocks[0], Block::Heading { level: 1, .. }));
+        match &blocks[1] {
+            Block::Paragraph { spans, .. } => {
+                assert_eq!(spans[1], Span::new("bold", FontKind::Bold));
+            }
+            other => panic!("expected paragraph, got {:?}", other),
+        }
+        assert!(matches!(&blocks[2], Block::Paragraph { prefix: Some(p), .. } if p == "•"));
+        assert!(matches!(&blocks[4], Block::Code { text, lang, .. } if text == "fn main() {}" && lang == "rust"));
+        assert!(matches!(&blocks[5], Block::Math { text } if text == "x^2"));
+    }
+
+    #[test]
+    fn test_wrap_respects_width() {
+        let spans = vec![Span::new("word ".repeat(50), FontKind::Regular)];
+        let lines = wrap_spans(&spans, 50.0, 11.0);
+        assert!(lines.len() > 1);
+        for line in &lines {
+            let width: f32 = line.iter().map(|s| text_width(s.text.trim_end(), s.font, 11.0)).sum();
+            assert!(width <= 50.0 + 0.01);
+        }
+    }
+
+    #[test]
+    fn test_math_and_code_are_rendered() {
+        let markdown = "Energy $E = mc^2$ here.\n\n$$\\frac{\\alpha}{2}$$\n\n```rust\nlet x = 1; // one\n```\n";
+        let (_, _, pages) = layout_markdown(markdown, &PdfExportOptions::default(), Palette::default(), Path::new("."), None);
+        let texts: Vec<(&str, FontKind, Rgb8)> = pages[0]
+            .iter()
+            .filter_map(|op| match op {
+                DrawOp::Text { text, font, color, .. } => Some((text.as_str(), *font, *color)),
+                _ => None,
+            })
+            .collect();
+        let palette = Palette::default();
+
+        // No TeX source is printed; the symbol font draws the alpha
+        assert!(!texts.iter().any(|(t, _, _)| t.contains('$') || t.contains("frac")));
+        assert!(texts.contains(&("E", FontKind::Italic, palette.text)));
+        assert!(texts.contains(&("a", FontKind::Symbol, palette.text)));
+        assert!(pages[0].iter().any(|op| matches!(op, DrawOp::Line { thickness, .. } if *thickness == 0.4)));
+
+        assert!(texts.contains(&("let", FontKind::Mono, palette.keyword)));
+        assert!(texts.contains(&("1", FontKind::Mono, palette.number)));
+        assert!(texts.contains(&("// one", FontKind::Mono, palette.muted)));
+    }
+
+    #[test]
+    fn test_parse_color_formats() {
+        assert_eq!(parse_color("#18161f"), Some(Rgb8(0x18, 0x16, 0x1f)));
+        assert_eq!(parse_color("15 23 42"), Some(Rgb8(15, 23, 42)));
+        assert_eq!(parse_color("rgb(1,2,3)"), None);
+    }
+
+    #[test]
+    fn test_fill_template() {
+        assert_eq!(fill_template("{title} - {page}/{pages}", "Note", 2, 5), "Note - 2/5");
+    }
+
+    #[test]
+    fn test_layout_paginates_long_notes() {
+        let markdown = "Paragraph line.\n\n".repeat(200);
+        let (_, _, pages) = layout_markdown(&markdown, &PdfExportOptions::default(), Palette::default(), Path::new("."), None);
+        assert!(pages.len() > 1);
+    }
+}
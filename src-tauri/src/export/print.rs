@@ -0,0 +1,263 @@
+//! Printing through the OS print dialog.
+//!
+//! The notes of a print job are rendered into one standalone HTML document
+//! with a print stylesheet, images inlined as data URIs, and written to a
+//! temporary file. A preview window loads it and opens the platform's print
+//! dialog once it has loaded; the file is removed when that window closes.
+//!
+//! Colours come from theme tokens (the active theme, or an installed theme by
+//! id) unless the built-in print theme is asked for, which is also the
+//! default: dark text on white.
+
+use super::pdf::parse_color;
+use super::workspace::{escape_html, media_type, render_html, MD_LINK_RE};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use once_cell::sync::Lazy;
+use regex::Captures;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+/// Print documents, by the label of the window showing them
+static DOCUMENTS: Lazy<Mutex<HashMap<String, tempfile::TempPath>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_JOB: AtomicU32 = AtomicU32::new(1);
+
+/// Theme tokens the stylesheet uses, with the print theme's values
+const PRINT_THEME: &[(&str, &str)] = &[
+    ("--text", "#1f2328"),
+    ("--bg", "#ffffff"),
+    ("--accent", "#0969da"),
+    ("--muted", "#6e7781"),
+    ("--panel", "#f6f8fa"),
+    ("--border", "#d0d7de"),
+];
+
+const PRINT_CSS: &str = "html{print-color-adjust:exact;-webkit-print-color-adjust:exact}\
+body{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;line-height:1.5;color:var(--text);background:var(--bg);margin:0}\
+a{color:var(--accent);text-decoration:none}h1,h2,h3,h4,h5,h6{break-after:avoid}\
+pre,code{font-family:ui-monospace,SFMono-Regular,Menlo,Consolas,monospace;font-size:.9em}\
+pre{background:var(--panel);border:1px solid var(--border);padding:.6em;white-space:pre-wrap;break-inside:avoid}\
+blockquote{margin-left:0;padding-left:1em;border-left:3px solid var(--border);color:var(--muted)}\
+img{max-width:100%;break-inside:avoid}table{border-collapse:collapse;break-inside:avoid}\
+td,th{border:1px solid var(--border);padding:.25em .5em}hr{border:0;border-top:1px solid var(--border)}\
+article+article{break-before:page}\
+@media screen{body{max-width:46rem;margin:2rem auto;padding:0 1rem}}";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrintOptions {
+    /// Markdown to print instead of the whole note, e.g. the editor selection
+    pub selection: Option<String>,
+    /// More notes printed after this one in the same job, each on a new page
+    pub additional_notes: Vec<String>,
+    /// "A4" (default), "A5", "Letter" or "Legal"
+    pub page_size: Option<String>,
+    pub landscape: bool,
+    /// Page margin in millimetres
+    pub margin: Option<f32>,
+    /// Body font size in points
+    pub font_size: Option<f32>,
+    /// Id of an installed theme whose colours should be used
+    pub theme: Option<String>,
+    /// Theme tokens (`--bg`, `--text`, ...) passed directly, e.g. the active theme
+    pub theme_tokens: Option<HashMap<String, String>>,
+    /// Ignore `theme`/`themeTokens` and print dark text on white
+    pub print_theme: bool,
+}
+
+/// `:root` variables for the stylesheet; tokens that are missing or not
+/// colours keep the print theme's value
+fn theme_variables(options: &PrintOptions) -> Result<String, String> {
+    let tokens = match (&options.theme_tokens, &options.theme) {
+        _ if options.print_theme => HashMap::new(),
+        (Some(tokens), _) => tokens.clone(),
+        (None, Some(theme_id)) => crate::theme::get_theme_tokens(theme_id.clone())?,
+        (None, None) => HashMap::new(),
+    };
+    let variables: Vec<String> = PRINT_THEME
+        .iter()
+        .map(|(token, fallback)| {
+            let value = tokens
+                .get(*token)
+                .and_then(|v| parse_color(v))
+                .map(|c| c.to_css())
+                .unwrap_or_else(|| fallback.to_string());
+            format!("{}:{}", token, value)
+        })
+        .collect();
+    Ok(format!(":root{{{}}}", variables.join(";")))
+}
+
+fn page_rule(options: &PrintOptions) -> String {
+    let size = match options.page_size.as_deref().map(|s| s.to_lowercase()).as_deref() {
+        Some("a5") => "A5",
+        Some("letter") => "letter",
+        Some("legal") => "legal",
+        _ => "A4",
+    };
+    let orientation = if options.landscape { " landscape" } else { "" };
+    let margin = options.margin.unwrap_or(20.0).clamp(0.0, 60.0);
+    let font_size = options.font_size.unwrap_or(11.0).clamp(6.0, 32.0);
+    format!("@page{{size:{}{};margin:{}mm}}body{{font-size:{}pt}}", size, orientation, margin, font_size)
+}
+
+/// Local image as a data URI, so the print document doesn't depend on where
+/// it is loaded from
+fn data_uri(note_dir: &Path, workspace: Option<&Path>, src: &str) -> Option<String> {
+    let path = super::resolve_local_asset(note_dir, workspace, src)?;
+    let bytes = fs::read(&path).ok()?;
+    let mime = media_type(&path.to_string_lossy());
+    mime.starts_with("image/").then(|| format!("data:{};base64,{}", mime, BASE64.encode(bytes)))
+}
+
+/// One note (or a selection from it) as an `<article>`
+fn render_note(path: &Path, selection: Option<&str>) -> Result<String, String> {
+    let markdown = match selection {
+        Some(selection) => selection.to_string(),
+        None => {
+            let path_str = path.to_string_lossy().to_string();
+            let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let content = crate::encryption::decrypt_for_read(&path_str, content)?;
+            super::strip_frontmatter(&content).to_string()
+        }
+    };
+
+    let note_dir = path.parent().unwrap_or(Path::new("."));
+    let workspace = super::find_workspace_root(path);
+    let workspace = workspace.as_deref();
+    let markdown = super::flatten_wikilinks(&markdown, |target, _, is_embed| {
+        if is_embed { data_uri(note_dir, workspace, target) } else { None }
+    });
+    let markdown = MD_LINK_RE.replace_all(&markdown, |caps: &Captures| {
+        match data_uri(note_dir, workspace, &caps[3]).filter(|_| &caps[1] == "!") {
+            Some(uri) => format!("![{}]({}{})", &caps[2], uri, &caps[4]),
+            None => caps[0].to_string(),
+        }
+    });
+    Ok(format!("<article>\n{}</article>\n", render_html(&markdown)))
+}
+
+/// The whole print job as one HTML document
+fn build_document(path: &Path, options: &PrintOptions) -> Result<String, String> {
+    let title = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Untitled".to_string());
+    let selection = options.selection.as_deref().filter(|s| !s.trim().is_empty());
+
+    let mut body = render_note(path, selection)?;
+    for note in &options.additional_notes {
+        body.push_str(&render_note(Path::new(note), None)?);
+    }
+
+    // Notes may contain raw HTML; nothing in the document needs to run
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta http-equiv=\"Content-Security-Policy\" content=\"default-src 'none'; img-src data:; style-src 'unsafe-inline'\">\n<title>{}</title>\n<style>{}{}{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(&title),
+        theme_variables(options)?,
+        page_rule(options),
+        PRINT_CSS,
+        body
+    ))
+}
+
+fn open_print_window(app: &AppHandle, title: &str, document: tempfile::TempPath) -> Result<(), String> {
+    let url = url::Url::from_file_path(&document).map_err(|_| "Invalid print document path".to_string())?;
+    let label = format!("print-{}", NEXT_JOB.fetch_add(1, Ordering::Relaxed));
+
+    let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::External(url))
+        .title(format!("Print — {}", title))
+        .inner_size(820.0, 900.0)
+        .on_page_load(|webview, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                if let Err(e) = webview.print() {
+                    eprintln!("[Print] Failed to open the print dialog: {}", e);
+                }
+            }
+        })
+        .build()
+        .map_err(|e| format!("Failed to open print window: {}", e))?;
+
+    DOCUMENTS.lock().unwrap_or_else(|e| e.into_inner()).insert(label.clone(), document);
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            DOCUMENTS.lock().unwrap_or_else(|e| e.into_inner()).remove(&label);
+        }
+    });
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+/// Print a note, a selection from it, or it followed by more notes, through
+/// the OS print dialog
+#[tauri::command]
+pub async fn print_note(app: AppHandle, path: String, options: Option<PrintOptions>) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    let source = PathBuf::from(&path);
+    if !source.is_file() {
+        return Err(format!("Note not found: {}", path));
+    }
+    let title = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+    let document = tokio::task::spawn_blocking(move || -> Result<tempfile::TempPath, String> {
+        let html = build_document(&source, &options)?;
+        let mut file = tempfile::Builder::new()
+            .prefix("lokus-print-")
+            .suffix(".html")
+            .tempfile()
+            .map_err(|e| format!("Failed to create print document: {}", e))?;
+        std::io::Write::write_all(&mut file, html.as_bytes()).map_err(|e| format!("Failed to write print document: {}", e))?;
+        Ok(file.into_temp_path())
+    })
+    .await
+    .map_err(|e| format!("Print task failed: {}", e))??;
+
+    open_print_window(&app, &title, document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_combines_notes_and_inlines_images() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        fs::write(dir.path().join("pic.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let first = dir.path().join("First.md");
+        let second = dir.path().join("Second.md");
+        fs::write(&first, "---\ntags: [a]\n---\n# First\n\n![[pic.png]] and [[Second]]\n").unwrap();
+        fs::write(&second, "# Second\n\n![alt](pic.png)\n<script>alert(1)</script>\n").unwrap();
+
+        let options = PrintOptions {
+            additional_notes: vec![second.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        let html = build_document(&first, &options).unwrap();
+        assert_eq!(html.matches("<article>").count(), 2);
+        assert_eq!(html.matches("src=\"data:image/png;base64,").count(), 2);
+        assert!(!html.contains("tags: [a]"));
+        assert!(html.contains("default-src 'none'"));
+
+        let selection = PrintOptions { selection: Some("Only *this*".to_string()), ..Default::default() };
+        let html = build_document(&first, &selection).unwrap();
+        assert!(html.contains("Only <em>this</em>") && !html.contains("First</h1>"));
+    }
+
+    #[test]
+    fn test_theme_variables() {
+        let tokens = HashMap::from([("--bg".to_string(), "15 23 42".to_string()), ("--text".to_string(), "oops".to_string())]);
+        let options = PrintOptions { theme_tokens: Some(tokens), ..Default::default() };
+        let css = theme_variables(&options).unwrap();
+        assert!(css.contains("--bg:#0f172a") && css.contains("--text:#1f2328"));
+
+        let plain = PrintOptions { print_theme: true, ..options };
+        assert!(theme_variables(&plain).unwrap().contains("--bg:#ffffff"));
+    }
+}
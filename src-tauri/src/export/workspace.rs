@@ -0,0 +1,694 @@
+//! Whole-workspace export to portable formats.
+//!
+//! Every note is rewritten so that it no longer depends on Lokus: wikilinks
+//! become relative links to the exported files, embeds become images, and
+//! referenced attachments are copied next to the notes at their vault path.
+//! The same rewritten markdown then feeds all three formats — plain Markdown,
+//! a static HTML site with an index and backlinks, or an EPUB book.
+
+use crate::links::{self, LinkKind, Resolver};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+const PROGRESS_EVENT: &str = "workspace-export-progress";
+const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", ".trash", "node_modules"];
+
+// ![alt](src) and [label](href), with an optional title
+pub(super) static MD_LINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(!?)\[([^\]]*)\]\(([^)\s]+)((?:\s+"[^"]*")?)\)"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Epub,
+}
+
+impl ExportFormat {
+    /// Extension given to exported notes; links between notes use it too
+    fn note_extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Epub => "xhtml",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceExportOptions {
+    /// Output folder for Markdown/HTML, or the `.epub` file to write
+    pub output_path: String,
+    /// Copy images and other attachments referenced by notes
+    #[serde(default = "default_true")]
+    pub include_attachments: bool,
+    /// Keep YAML frontmatter in Markdown exports
+    #[serde(default = "default_true")]
+    pub keep_frontmatter: bool,
+    /// Site/book title; defaults to the workspace folder name
+    pub title: Option<String>,
+    pub author: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    pub format: ExportFormat,
+    /// "notes", "attachments" or "done"
+    pub phase: String,
+    pub current: usize,
+    pub total: usize,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceExportSummary {
+    pub output_path: String,
+    pub notes: usize,
+    pub attachments: usize,
+    /// Links that could not be resolved to a note or attachment
+    pub unresolved_links: usize,
+}
+
+// --- Helpers ---
+
+pub(super) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Percent-encode each segment of a '/'-separated path for use in a link
+fn encode_href(path: &str) -> String {
+    path.split('/')
+        .map(|segment| if segment == ".." { segment.to_string() } else { urlencoding::encode(segment).into_owned() })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// GitHub-style heading anchor
+fn slugify(heading: &str) -> String {
+    let mut slug = String::with_capacity(heading.len());
+    for c in heading.trim().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+fn note_title(rel: &str) -> String {
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    name.strip_suffix(".md").unwrap_or(name).to_string()
+}
+
+fn with_extension(rel: &str, ext: &str) -> String {
+    let stem = rel.strip_suffix(".md").unwrap_or(rel);
+    format!("{}.{}", stem, ext)
+}
+
+pub(crate) fn media_type(path: &str) -> &'static str {
+    let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "css" => "text/css",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+fn collect_notes(root: &Path) -> Vec<String> {
+    let mut notes: Vec<String> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| e.file_name().to_str().map_or(true, |n| !EXCLUDED_DIRS.contains(&n)))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| links::relative_path(root, e.path()))
+        .filter(|rel| links::is_note(rel))
+        .collect();
+    notes.sort();
+    notes
+}
+
+// --- Link rewriting ---
+
+/// Rewrites one note's links against the set of exported notes, recording the
+/// attachments it references.
+struct LinkRewriter<'a> {
+    root: &'a Path,
+    resolver: &'a Resolver,
+    ext: &'static str,
+    attachments: RefCell<BTreeSet<String>>,
+    unresolved: Cell<usize>,
+}
+
+impl<'a> LinkRewriter<'a> {
+    fn note_href(&self, source: &str, target_rel: &str, heading: Option<&str>) -> String {
+        let target = with_extension(target_rel, self.ext);
+        let mut href = encode_href(&links::relative_link(links::parent_dir(source), &target));
+        if let Some(heading) = heading.filter(|h| !h.is_empty()) {
+            href.push('#');
+            href.push_str(&slugify(heading));
+        }
+        href
+    }
+
+    fn asset_href(&self, source: &str, src: &str) -> Option<String> {
+        let note_dir = self.root.join(links::parent_dir(source));
+        let path = super::resolve_local_asset(&note_dir, Some(self.root), src)?;
+        let rel = links::relative_path(self.root, &path)?;
+        let href = encode_href(&links::relative_link(links::parent_dir(source), &rel));
+        self.attachments.borrow_mut().insert(rel);
+        Some(href)
+    }
+
+    fn miss(&self) {
+        self.unresolved.set(self.unresolved.get() + 1);
+    }
+
+    fn rewrite_line(&self, source: &str, line: &str) -> String {
+        // Standard markdown links first, so the output of the wikilink pass
+        // below is never rewritten twice
+        let with_markdown_links = MD_LINK_RE.replace_all(line, |caps: &Captures| {
+            let (is_image, label, href, title) = (&caps[1] == "!", &caps[2], &caps[3], &caps[4]);
+            if href.contains("://") || href.starts_with('#') || href.starts_with("mailto:") {
+                return caps[0].to_string();
+            }
+
+            let (path_part, fragment) = match href.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment)),
+                None => (href, None),
+            };
+            let new_href = if is_image {
+                self.asset_href(source, path_part)
+            } else if let Some(rel) = self.resolver.resolve(source, LinkKind::Markdown, href) {
+                let mut note_href = self.note_href(source, &rel, None);
+                if let Some(fragment) = fragment {
+                    note_href.push('#');
+                    note_href.push_str(fragment);
+                }
+                Some(note_href)
+            } else {
+                // Links to non-note files (PDFs, spreadsheets) are attachments too
+                self.asset_href(source, path_part)
+            };
+
+            match new_href {
+                Some(new_href) => format!("{}[{}]({}{})", &caps[1], label, new_href, title),
+                None => {
+                    self.miss();
+                    caps[0].to_string()
+                }
+            }
+        });
+
+        super::flatten_wikilinks(&with_markdown_links, |target, heading, embed| {
+            if embed {
+                return self.asset_href(source, target).or_else(|| {
+                    self.miss();
+                    None
+                });
+            }
+            if target.is_empty() {
+                // [[#Heading]] points into the current note
+                return heading.map(|h| format!("#{}", slugify(h)));
+            }
+            match self.resolver.resolve(source, LinkKind::Wikilink, target) {
+                Some(rel) => Some(self.note_href(source, &rel, heading)),
+                None => {
+                    self.miss();
+                    None
+                }
+            }
+        })
+    }
+
+    /// Rewrite all links outside fenced code blocks
+    fn rewrite(&self, source: &str, markdown: &str) -> String {
+        let mut output = String::with_capacity(markdown.len());
+        let mut in_fence = false;
+        for line in markdown.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                output.push_str(line);
+            } else if in_fence {
+                output.push_str(line);
+            } else {
+                output.push_str(&self.rewrite_line(source, line));
+            }
+        }
+        output
+    }
+}
+
+// --- HTML rendering ---
+
+/// Render markdown to HTML, giving every heading an id so `#heading` links work
+pub(super) fn render_html(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+
+    let mut events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+    let mut i = 0;
+    while i < events.len() {
+        if let Event::Start(Tag::Heading { id: None, .. }) = &events[i] {
+            let mut text = String::new();
+            let mut j = i + 1;
+            while j < events.len() && !matches!(events[j], Event::End(TagEnd::Heading(_))) {
+                if let Event::Text(t) | Event::Code(t) = &events[j] {
+                    text.push_str(t);
+                }
+                j += 1;
+            }
+            if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+                *id = Some(CowStr::from(slugify(&text)));
+            }
+        }
+        i += 1;
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+const SITE_CSS: &str = "body{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;max-width:46rem;margin:2rem auto;padding:0 1rem;line-height:1.6;color:#1f2328}\
+a{color:#0969da}pre{background:#f6f8fa;padding:.75rem;overflow:auto}code{font-size:.9em}\
+img{max-width:100%}table{border-collapse:collapse}td,th{border:1px solid #d0d7de;padding:.25rem .5rem}\
+nav{font-size:.9em;margin-bottom:1.5rem}.backlinks{border-top:1px solid #d0d7de;margin-top:2rem;font-size:.9em}";
+
+fn html_page(title: &str, css_href: &str, nav: &str, body: &str, footer: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{}\">\n</head>\n<body>\n{}<main>\n{}</main>\n{}</body>\n</html>\n",
+        escape_html(title),
+        css_href,
+        nav,
+        body,
+        footer
+    )
+}
+
+fn xhtml_page(title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n<head>\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+/// Nested list of notes grouped by folder, for the site index and EPUB nav
+fn notes_tree_html(notes: &[String], ext: &str) -> String {
+    let mut folders: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+    for rel in notes {
+        folders.entry(links::parent_dir(rel)).or_default().push(rel);
+    }
+
+    let mut out = String::from("<ol>\n");
+    for (folder, notes) in folders {
+        if !folder.is_empty() {
+            out.push_str(&format!("<li><span>{}</span>\n<ol>\n", escape_html(folder)));
+        }
+        for rel in notes {
+            out.push_str(&format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                encode_href(&with_extension(rel, ext)),
+                escape_html(&note_title(rel))
+            ));
+        }
+        if !folder.is_empty() {
+            out.push_str("</ol>\n</li>\n");
+        }
+    }
+    out.push_str("</ol>\n");
+    out
+}
+
+// --- Export ---
+
+struct PreparedNote {
+    rel: String,
+    markdown: String,
+}
+
+/// Sources linking to each note, computed from the original (unrewritten) notes
+fn compute_backlinks(root: &Path, notes: &[String], resolver: &Resolver) -> HashMap<String, BTreeSet<String>> {
+    let mut backlinks: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for source in notes {
+        let Ok(content) = fs::read_to_string(root.join(source)) else { continue };
+        for link in links::parse_links(&content) {
+            if link.kind == LinkKind::Embed {
+                continue;
+            }
+            if let Some(target) = resolver.resolve(source, link.kind, &link.target) {
+                if target != *source {
+                    backlinks.entry(target).or_default().insert(source.clone());
+                }
+            }
+        }
+    }
+    backlinks
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Export the workspace at `root`, reporting progress through `progress`
+pub fn run_export(
+    root: &Path,
+    format: ExportFormat,
+    options: &WorkspaceExportOptions,
+    mut progress: impl FnMut(ExportProgress),
+) -> Result<WorkspaceExportSummary, String> {
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", root.display()));
+    }
+    let output = PathBuf::from(&options.output_path);
+    if output.starts_with(root) && format != ExportFormat::Epub {
+        return Err("Export folder cannot be inside the workspace".to_string());
+    }
+
+    let notes = collect_notes(root);
+    let resolver = Resolver::new(notes.iter());
+    let rewriter = LinkRewriter {
+        root,
+        resolver: &resolver,
+        ext: format.note_extension(),
+        attachments: RefCell::new(BTreeSet::new()),
+        unresolved: Cell::new(0),
+    };
+    let mut report = |phase: &str, current: usize, total: usize, path: Option<&str>| {
+        progress(ExportProgress {
+            format,
+            phase: phase.to_string(),
+            current,
+            total,
+            path: path.map(|p| p.to_string()),
+        })
+    };
+
+    let mut prepared = Vec::with_capacity(notes.len());
+    for (index, rel) in notes.iter().enumerate() {
+        report("notes", index + 1, notes.len(), Some(rel));
+        let content = fs::read_to_string(root.join(rel))
+            .map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+        let (frontmatter, body) = split_frontmatter(&content);
+        let mut markdown = rewriter.rewrite(rel, body);
+        if format == ExportFormat::Markdown && options.keep_frontmatter {
+            markdown.insert_str(0, frontmatter);
+        }
+        prepared.push(PreparedNote { rel: rel.clone(), markdown });
+    }
+
+    let attachments: Vec<String> = if options.include_attachments {
+        rewriter.attachments.borrow().iter().cloned().collect()
+    } else {
+        Vec::new()
+    };
+    let title = options.title.clone().unwrap_or_else(|| {
+        root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "Notes".to_string())
+    });
+
+    match format {
+        ExportFormat::Markdown => {
+            for note in &prepared {
+                write_file(&output.join(&note.rel), note.markdown.as_bytes())?;
+            }
+        }
+        ExportFormat::Html => {
+            let backlinks = compute_backlinks(root, &notes, &resolver);
+            for note in &prepared {
+                let dir = links::parent_dir(&note.rel);
+                let up = |target: &str| encode_href(&links::relative_link(dir, target));
+                let nav = format!("<nav><a href=\"{}\">{}</a></nav>\n", up("index.html"), escape_html(&title));
+                let footer = match backlinks.get(&note.rel) {
+                    Some(sources) if !sources.is_empty() => {
+                        let items: String = sources
+                            .iter()
+                            .map(|s| format!("<li><a href=\"{}\">{}</a></li>\n", up(&with_extension(s, "html")), escape_html(&note_title(s))))
+                            .collect();
+                        format!("<section class=\"backlinks\">\n<h2>Backlinks</h2>\n<ul>\n{}</ul>\n</section>\n", items)
+                    }
+                    _ => String::new(),
+                };
+                let body = format!("<h1>{}</h1>\n{}", escape_html(&note_title(&note.rel)), render_html(&note.markdown));
+                let page = html_page(&note_title(&note.rel), &up("style.css"), &nav, &body, &footer);
+                write_file(&output.join(with_extension(&note.rel, "html")), page.as_bytes())?;
+            }
+            let index_body = format!("<h1>{}</h1>\n{}", escape_html(&title), notes_tree_html(&notes, "html"));
+            write_file(&output.join("index.html"), html_page(&title, "style.css", "", &index_body, "").as_bytes())?;
+            write_file(&output.join("style.css"), SITE_CSS.as_bytes())?;
+        }
+        ExportFormat::Epub => {
+            write_epub(root, &output, &title, options.author.as_deref(), &prepared, &attachments, &mut report)?;
+        }
+    }
+
+    if format != ExportFormat::Epub {
+        for (index, rel) in attachments.iter().enumerate() {
+            report("attachments", index + 1, attachments.len(), Some(rel));
+            let destination = output.join(rel);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            fs::copy(root.join(rel), &destination).map_err(|e| format!("Failed to copy {}: {}", rel, e))?;
+        }
+    }
+
+    report("done", notes.len(), notes.len(), None);
+    Ok(WorkspaceExportSummary {
+        output_path: output.to_string_lossy().to_string(),
+        notes: notes.len(),
+        attachments: attachments.len(),
+        unresolved_links: rewriter.unresolved.get(),
+    })
+}
+
+/// Split content into its frontmatter block (including delimiters) and body
+fn split_frontmatter(content: &str) -> (&str, &str) {
+    let body = super::strip_frontmatter(content);
+    (&content[..content.len() - body.len()], body)
+}
+
+fn write_epub(
+    root: &Path,
+    output: &Path,
+    title: &str,
+    author: Option<&str>,
+    notes: &[PreparedNote],
+    attachments: &[String],
+    report: &mut impl FnMut(&str, usize, usize, Option<&str>),
+) -> Result<(), String> {
+    use zip::write::SimpleFileOptions;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file = File::create(output).map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write EPUB: {}", e);
+    let io_err = |e: std::io::Error| format!("Failed to write EPUB: {}", e);
+    let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    // The mimetype entry must come first and be uncompressed
+    zip.start_file("mimetype", stored).map_err(zip_err)?;
+    zip.write_all(b"application/epub+zip").map_err(io_err)?;
+
+    zip.start_file("META-INF/container.xml", deflated).map_err(zip_err)?;
+    zip.write_all(
+        b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n<rootfiles>\n<rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>\n</rootfiles>\n</container>\n",
+    )
+    .map_err(io_err)?;
+
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    for (index, note) in notes.iter().enumerate() {
+        let href = with_extension(&note.rel, "xhtml");
+        let body = format!("<h1>{}</h1>\n{}", escape_html(&note_title(&note.rel)), render_html(&note.markdown));
+        zip.start_file(format!("OEBPS/{}", href), deflated).map_err(zip_err)?;
+        zip.write_all(xhtml_page(&note_title(&note.rel), &body).as_bytes()).map_err(io_err)?;
+
+        manifest.push_str(&format!(
+            "<item id=\"note{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            index,
+            escape_html(&encode_href(&href))
+        ));
+        spine.push_str(&format!("<itemref idref=\"note{}\"/>\n", index));
+    }
+
+    for (index, rel) in attachments.iter().enumerate() {
+        report("attachments", index + 1, attachments.len(), Some(rel));
+        let data = fs::read(root.join(rel)).map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+        zip.start_file(format!("OEBPS/{}", rel), deflated).map_err(zip_err)?;
+        zip.write_all(&data).map_err(io_err)?;
+        manifest.push_str(&format!(
+            "<item id=\"asset{}\" href=\"{}\" media-type=\"{}\"/>\n",
+            index,
+            escape_html(&encode_href(rel)),
+            media_type(rel)
+        ));
+    }
+
+    let rels: Vec<String> = notes.iter().map(|n| n.rel.clone()).collect();
+    let nav_body = format!("<nav epub:type=\"toc\">\n<h1>{}</h1>\n{}</nav>\n", escape_html(title), notes_tree_html(&rels, "xhtml"));
+    zip.start_file("OEBPS/nav.xhtml", deflated).map_err(zip_err)?;
+    zip.write_all(xhtml_page(title, &nav_body).as_bytes()).map_err(io_err)?;
+
+    let opf = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\">\n<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<dc:identifier id=\"book-id\">urn:uuid:{}</dc:identifier>\n<dc:title>{}</dc:title>\n<dc:creator>{}</dc:creator>\n<dc:language>en</dc:language>\n<meta property=\"dcterms:modified\">{}</meta>\n</metadata>\n<manifest>\n<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n{}</manifest>\n<spine>\n{}</spine>\n</package>\n",
+        uuid::Uuid::new_v4(),
+        escape_html(title),
+        escape_html(author.unwrap_or("Lokus")),
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        manifest,
+        spine
+    );
+    zip.start_file("OEBPS/content.opf", deflated).map_err(zip_err)?;
+    zip.write_all(opf.as_bytes()).map_err(io_err)?;
+
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+/// Export the whole workspace as Markdown, a static HTML site, or an EPUB.
+/// Progress is emitted as `workspace-export-progress` events.
+#[tauri::command]
+pub async fn export_workspace(
+    app: AppHandle,
+    workspace_path: String,
+    format: ExportFormat,
+    options: WorkspaceExportOptions,
+) -> Result<WorkspaceExportSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        run_export(Path::new(&workspace_path), format, &options, |progress| {
+            let _ = app.emit(PROGRESS_EVENT, progress);
+        })
+    })
+    .await
+    .map_err(|e| format!("Workspace export task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".lokus")).unwrap();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::create_dir_all(root.join("assets")).unwrap();
+        fs::write(root.join("assets/diagram one.png"), b"png").unwrap();
+        fs::write(
+            root.join("Home.md"),
+            "---\ntags: [home]\n---\nSee [[Plan|the plan]] and [[Plan#Next Steps]].\n\n![[diagram one.png]]\n\n```\n[[Plan]]\n```\n",
+        )
+        .unwrap();
+        fs::write(root.join("Projects/Plan.md"), "# Plan\n\n## Next Steps\n\nBack to [home](../Home.md) and [[Missing]].\n").unwrap();
+        dir
+    }
+
+    fn options(output: &Path) -> WorkspaceExportOptions {
+        WorkspaceExportOptions {
+            output_path: output.to_string_lossy().to_string(),
+            include_attachments: true,
+            keep_frontmatter: true,
+            title: Some("Vault".to_string()),
+            author: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_export_resolves_links_and_copies_attachments() {
+        let workspace = sample_workspace();
+        let out = tempfile::tempdir().unwrap();
+        let summary = run_export(workspace.path(), ExportFormat::Markdown, &options(out.path()), |_| {}).unwrap();
+
+        assert_eq!(summary.notes, 2);
+        assert_eq!(summary.attachments, 1);
+        assert_eq!(summary.unresolved_links, 1);
+
+        let home = fs::read_to_string(out.path().join("Home.md")).unwrap();
+        assert!(home.starts_with("---\ntags: [home]\n---\n"));
+        assert!(home.contains("[the plan](Projects/Plan.md)"));
+        assert!(home.contains("[Plan](Projects/Plan.md#next-steps)"));
+        assert!(home.contains("![diagram one.png](assets/diagram%20one.png)"));
+        assert!(home.contains("```\n[[Plan]]\n```"));
+        assert!(out.path().join("assets/diagram one.png").is_file());
+    }
+
+    #[test]
+    fn test_html_export_has_index_and_backlinks() {
+        let workspace = sample_workspace();
+        let out = tempfile::tempdir().unwrap();
+        let mut events = Vec::new();
+        run_export(workspace.path(), ExportFormat::Html, &options(out.path()), |p| events.push(p.phase)).unwrap();
+
+        let index = fs::read_to_string(out.path().join("index.html")).unwrap();
+        assert!(index.contains("href=\"Projects/Plan.html\""));
+
+        let plan = fs::read_to_string(out.path().join("Projects/Plan.html")).unwrap();
+        assert!(plan.contains("id=\"next-steps\""));
+        assert!(plan.contains("<h2>Backlinks</h2>"));
+        assert!(plan.contains("href=\"../Home.html\""));
+        assert_eq!(events.last().map(String::as_str), Some("done"));
+    }
+
+    #[test]
+    fn test_epub_export_structure() {
+        let workspace = sample_workspace();
+        let out = tempfile::tempdir().unwrap();
+        let book = out.path().join("vault.epub");
+        run_export(workspace.path(), ExportFormat::Epub, &options(&book), |_| {}).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&book).unwrap()).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        assert!(archive.by_name("OEBPS/content.opf").is_ok());
+        assert!(archive.by_name("OEBPS/Projects/Plan.xhtml").is_ok());
+        assert!(archive.by_name("OEBPS/assets/diagram one.png").is_ok());
+    }
+
+    #[test]
+    fn test_export_into_workspace_is_rejected() {
+        let workspace = sample_workspace();
+        let inside = workspace.path().join("export");
+        assert!(run_export(workspace.path(), ExportFormat::Html, &options(&inside), |_| {}).is_err());
+    }
+}
@@ -0,0 +1,459 @@
+//! RSS and Atom feed subscriptions.
+//!
+//! Subscriptions are stored per workspace in `.lokus/feeds.json`. Each new
+//! item becomes a note in the feed's folder, rendered from a workspace
+//! template when the feed names one (the item is available as `{{title}}`,
+//! `{{link}}`, `{{author}}`, `{{published}}`, `{{feed}}` and `{{content}}`)
+//! or from a built-in layout otherwise; `source`, `link` and `published` are
+//! always set in the frontmatter.
+//!
+//! Items are deduplicated by GUID, and requests send the last `ETag` and
+//! `Last-Modified` so unchanged feeds cost a 304. A background loop refreshes
+//! the open workspace's feeds when their interval has passed.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const FEEDS_FILE: &str = "feeds.json";
+const UPDATED_EVENT: &str = "feeds-updated";
+const DEFAULT_FOLDER: &str = "Feeds";
+const DEFAULT_INTERVAL_MINUTES: u32 = 60;
+const TICK: Duration = Duration::from_secs(60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// New items imported per refresh; a first fetch only brings in the latest ones
+const MAX_NEW_ITEMS: usize = 50;
+/// GUIDs remembered per feed, so items that drop out of the feed and come back stay deduplicated
+const MAX_SEEN: usize = 2000;
+
+const DEFAULT_TEMPLATE: &str = "# {{title}}\n\n{{content}}\n\n[Read the original]({{link}})\n";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Feed {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// Workspace-relative folder new items are written to
+    pub folder: String,
+    /// Workspace template used for items
+    pub template: Option<String>,
+    pub interval_minutes: u32,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Unix seconds
+    pub last_checked: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FeedStore {
+    feeds: Vec<Feed>,
+    /// Feed id to the GUIDs already imported, oldest first
+    seen: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedRefresh {
+    pub feed_id: String,
+    pub title: String,
+    /// Paths of the notes created
+    pub created: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// One feed item, reduced to what notes are made from
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    guid: String,
+    title: String,
+    link: Option<String>,
+    author: Option<String>,
+    published: Option<String>,
+    /// Markdown converted from the item's content or summary
+    content: String,
+}
+
+// --- Storage ---
+
+fn store_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(FEEDS_FILE)
+}
+
+fn load(root: &Path) -> FeedStore {
+    fs::read_to_string(store_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(root: &Path, store: &FeedStore) -> Result<(), String> {
+    let path = store_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save feeds: {}", e))
+}
+
+pub(crate) fn safe_folder(folder: &str) -> Result<String, String> {
+    let folder = folder.trim().trim_matches('/');
+    if folder.is_empty() || Path::new(folder).components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid folder: {}", folder));
+    }
+    Ok(folder.to_string())
+}
+
+// --- Fetching and parsing ---
+
+enum Fetched {
+    NotModified,
+    Feed { body: Vec<u8>, etag: Option<String>, last_modified: Option<String> },
+}
+
+async fn fetch(feed_url: &str, etag: Option<&str>, last_modified: Option<&str>) -> Result<Fetched, String> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("Lokus/", env!("CARGO_PKG_VERSION"), " (feed reader)"))
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut request = client.get(feed_url);
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = last_modified {
+        request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+    }
+    let response = request.send().await.map_err(|e| format!("Failed to fetch feed: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    if !response.status().is_success() {
+        return Err(format!("The feed returned {}", response.status()));
+    }
+    let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let (etag, last_modified) = (header(reqwest::header::ETAG), header(reqwest::header::LAST_MODIFIED));
+    let body = response.bytes().await.map_err(|e| format!("Failed to read feed: {}", e))?;
+    Ok(Fetched::Feed { body: body.to_vec(), etag, last_modified })
+}
+
+fn html_to_markdown(html: &str, base: Option<&url::Url>) -> String {
+    use crate::import::html::Reference;
+    let conversion = crate::import::html::html_to_markdown(html, &mut |reference| match reference {
+        Reference::Link(href) | Reference::Image(href) => base?.join(href.trim()).ok().map(|u| u.to_string()),
+        _ => None,
+    });
+    conversion.markdown.trim().to_string()
+}
+
+/// Parse a feed document into its title and items, newest first
+fn parse(body: &[u8]) -> Result<(Option<String>, Vec<Item>), String> {
+    let feed = feed_rs::parser::parse(body).map_err(|e| format!("Not a valid RSS or Atom feed: {}", e))?;
+    let mut items: Vec<(Option<chrono::DateTime<Utc>>, Item)> = feed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let link = entry.links.first().map(|l| l.href.clone());
+            let base = link.as_deref().and_then(|l| url::Url::parse(l).ok());
+            let html = entry
+                .content
+                .and_then(|c| c.body)
+                .or_else(|| entry.summary.map(|s| s.content))
+                .unwrap_or_default();
+            let date = entry.published.or(entry.updated);
+            let item = Item {
+                guid: entry.id,
+                title: entry
+                    .title
+                    .map(|t| t.content.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .unwrap_or_else(|| "Untitled".to_string()),
+                link,
+                author: entry.authors.first().map(|a| a.name.clone()).filter(|n| !n.trim().is_empty()),
+                published: date.map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+                content: html_to_markdown(&html, base.as_ref()),
+            };
+            (date, item)
+        })
+        .collect();
+    items.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok((feed.title.map(|t| t.content.trim().to_string()).filter(|t| !t.is_empty()), items.into_iter().map(|(_, item)| item).collect()))
+}
+
+/// Items not imported before, newest first, at most `MAX_NEW_ITEMS`
+fn new_items(items: Vec<Item>, seen: &[String]) -> Vec<Item> {
+    let seen: HashSet<&String> = seen.iter().collect();
+    items.into_iter().filter(|item| !seen.contains(&item.guid)).take(MAX_NEW_ITEMS).collect()
+}
+
+fn render_item(root: &Path, feed: &Feed, item: &Item) -> Result<String, String> {
+    let vars = HashMap::from([
+        ("link".to_string(), item.link.clone().unwrap_or_default()),
+        ("author".to_string(), item.author.clone().unwrap_or_default()),
+        ("published".to_string(), item.published.clone().unwrap_or_default()),
+        ("feed".to_string(), feed.title.clone()),
+        ("content".to_string(), item.content.clone()),
+    ]);
+    let ctx = crate::templates::RenderContext::new(Some(item.title.clone()), vars);
+    let rendered = match &feed.template {
+        Some(name) => crate::templates::render_named(root, name, &ctx)?,
+        None => crate::templates::render(DEFAULT_TEMPLATE, &ctx),
+    };
+
+    let mut note = rendered.content;
+    let fields = [
+        ("source", json!(feed.url)),
+        ("link", item.link.as_ref().map_or(serde_json::Value::Null, |l| json!(l))),
+        ("published", item.published.as_ref().map_or(serde_json::Value::Null, |p| json!(p))),
+        ("author", item.author.as_ref().map_or(serde_json::Value::Null, |a| json!(a))),
+    ];
+    for (key, value) in fields {
+        note = crate::frontmatter::set_field(&note, key, &value)?;
+    }
+    Ok(note)
+}
+
+fn write_item(root: &Path, feed: &Feed, item: &Item) -> Result<String, String> {
+    let note = render_item(root, feed, item)?;
+    let folder = root.join(&feed.folder);
+    fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    let stem = crate::import::sanitize_file_name(&item.title);
+    let path = (1..)
+        .map(|n| match n {
+            1 => folder.join(format!("{}.md", stem)),
+            n => folder.join(format!("{} {}.md", stem, n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded candidates");
+    let path = path.to_string_lossy().to_string();
+    crate::handlers::files::write_file_content(path.clone(), note)?;
+    Ok(path)
+}
+
+/// Fetch one feed and write its new items; updates the feed's cache headers
+/// and the seen GUIDs in `store`
+async fn refresh_feed(root: &Path, store: &mut FeedStore, index: usize) -> FeedRefresh {
+    let feed = store.feeds[index].clone();
+    let mut result = FeedRefresh { feed_id: feed.id.clone(), title: feed.title.clone(), created: Vec::new(), error: None };
+
+    let outcome = match fetch(&feed.url, feed.etag.as_deref(), feed.last_modified.as_deref()).await {
+        Ok(Fetched::NotModified) => Ok(()),
+        Ok(Fetched::Feed { body, etag, last_modified }) => parse(&body).map(|(title, items)| {
+            let seen = store.seen.entry(feed.id.clone()).or_default();
+            // Write oldest first so notes are created in publication order
+            for item in new_items(items, seen).into_iter().rev() {
+                match write_item(root, &feed, &item) {
+                    Ok(path) => result.created.push(path),
+                    Err(e) => tracing::warn!("Failed to save feed item '{}': {}", item.title, e),
+                }
+                seen.push(item.guid);
+            }
+            let excess = seen.len().saturating_sub(MAX_SEEN);
+            seen.drain(..excess);
+
+            let stored = &mut store.feeds[index];
+            stored.etag = etag;
+            stored.last_modified = last_modified;
+            if let Some(title) = title.filter(|_| stored.title == stored.url) {
+                stored.title = title;
+            }
+        }),
+        Err(e) => Err(e),
+    };
+
+    let stored = &mut store.feeds[index];
+    stored.last_checked = Some(Utc::now().timestamp());
+    stored.last_error = outcome.err();
+    result.error = stored.last_error.clone();
+    result.title = stored.title.clone();
+    result
+}
+
+async fn refresh(root: &Path, only_due: bool) -> Result<Vec<FeedRefresh>, String> {
+    let mut store = load(root);
+    let now = Utc::now().timestamp();
+    let mut results = Vec::new();
+    for index in 0..store.feeds.len() {
+        let feed = &store.feeds[index];
+        let due = feed.last_checked.map_or(true, |t| now - t >= i64::from(feed.interval_minutes) * 60);
+        if only_due && !due {
+            continue;
+        }
+        results.push(refresh_feed(root, &mut store, index).await);
+    }
+    if !results.is_empty() {
+        // Subscriptions may have changed while we were fetching; only update the ones still there
+        let mut latest = load(root);
+        for feed in &mut latest.feeds {
+            if let Some(refreshed) = store.feeds.iter().find(|f| f.id == feed.id) {
+                let (template, folder, interval) = (feed.template.clone(), feed.folder.clone(), feed.interval_minutes);
+                *feed = Feed { template, folder, interval_minutes: interval, ..refreshed.clone() };
+                if let Some(seen) = store.seen.remove(&feed.id) {
+                    latest.seen.insert(feed.id.clone(), seen);
+                }
+            }
+        }
+        save(root, &latest)?;
+    }
+    Ok(results)
+}
+
+/// Refresh due feeds of the open workspace for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            if crate::diagnostics::background_paused() {
+                continue;
+            }
+            let Some(root) = crate::watcher::active_root() else {
+                continue;
+            };
+            match refresh(&root, true).await {
+                Ok(results) if results.iter().any(|r| !r.created.is_empty()) => {
+                    if let Err(e) = app.emit(UPDATED_EVENT, &results) {
+                        tracing::warn!("Failed to emit {}: {}", UPDATED_EVENT, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Feed refresh failed: {}", e),
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Subscribe to a feed and import its latest items
+#[tauri::command]
+pub async fn feed_add(
+    workspace_path: String,
+    url: String,
+    folder: Option<String>,
+    template: Option<String>,
+    interval_minutes: Option<u32>,
+) -> Result<FeedRefresh, String> {
+    let root = PathBuf::from(&workspace_path);
+    let parsed = url::Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Feeds must be http or https URLs".to_string());
+    }
+    let mut store = load(&root);
+    if store.feeds.iter().any(|f| f.url == parsed.as_str()) {
+        return Err("You're already subscribed to this feed".to_string());
+    }
+    store.feeds.push(Feed {
+        id: uuid::Uuid::new_v4().to_string(),
+        url: parsed.to_string(),
+        title: parsed.to_string(),
+        folder: safe_folder(folder.as_deref().unwrap_or(DEFAULT_FOLDER))?,
+        template: template.filter(|t| !t.trim().is_empty()),
+        interval_minutes: interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES).max(5),
+        etag: None,
+        last_modified: None,
+        last_checked: None,
+        last_error: None,
+    });
+    let index = store.feeds.len() - 1;
+    let result = refresh_feed(&root, &mut store, index).await;
+    if let Some(error) = &result.error {
+        // Don't keep a subscription that never worked
+        return Err(error.clone());
+    }
+    save(&root, &store)?;
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn feed_list(workspace_path: String) -> Vec<Feed> {
+    load(Path::new(&workspace_path)).feeds
+}
+
+/// Unsubscribe; notes already imported are kept
+#[tauri::command]
+pub fn feed_remove(workspace_path: String, id: String) -> Result<bool, String> {
+    let root = PathBuf::from(&workspace_path);
+    let mut store = load(&root);
+    let before = store.feeds.len();
+    store.feeds.retain(|f| f.id != id);
+    store.seen.remove(&id);
+    if store.feeds.len() == before {
+        return Ok(false);
+    }
+    save(&root, &store)?;
+    Ok(true)
+}
+
+/// Refresh every feed now, whether or not it's due
+#[tauri::command]
+pub async fn feed_refresh_all(app: AppHandle, workspace_path: String) -> Result<Vec<FeedRefresh>, String> {
+    let results = refresh(Path::new(&workspace_path), false).await?;
+    if let Err(e) = app.emit(UPDATED_EVENT, &results) {
+        tracing::warn!("Failed to emit {}: {}", UPDATED_EVENT, e);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Example Blog</title><link>https://example.com/</link>
+<item><title>Older</title><link>https://example.com/older</link><guid>post-1</guid>
+<pubDate>Mon, 01 Jan 2024 10:00:00 GMT</pubDate><description>&lt;p&gt;First &lt;a href="/about"&gt;post&lt;/a&gt;&lt;/p&gt;</description></item>
+<item><title>Newer</title><link>https://example.com/newer</link><guid>post-2</guid>
+<pubDate>Tue, 02 Jan 2024 10:00:00 GMT</pubDate><description>Second</description></item>
+</channel></rss>"#;
+
+    #[test]
+    fn test_parse_and_dedupe_by_guid() {
+        let (title, items) = parse(RSS.as_bytes()).unwrap();
+        assert_eq!(title.as_deref(), Some("Example Blog"));
+        assert_eq!(items.iter().map(|i| i.title.as_str()).collect::<Vec<_>>(), vec!["Newer", "Older"]);
+        assert_eq!(items[1].guid, "post-1");
+        assert_eq!(items[1].published.as_deref(), Some("2024-01-01T10:00:00Z"));
+        assert_eq!(items[1].content, "First [post](https://example.com/about)");
+
+        let fresh = new_items(items, &["post-2".to_string()]);
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].guid, "post-1");
+    }
+
+    #[test]
+    fn test_items_render_with_frontmatter() {
+        let dir = tempfile::tempdir().unwrap();
+        let feed = Feed {
+            id: "f".into(),
+            url: "https://example.com/feed.xml".into(),
+            title: "Example Blog".into(),
+            folder: "Feeds".into(),
+            template: None,
+            interval_minutes: 60,
+            etag: None,
+            last_modified: None,
+            last_checked: None,
+            last_error: None,
+        };
+        let item = Item {
+            guid: "post-1".into(),
+            title: "Hello".into(),
+            link: Some("https://example.com/hello".into()),
+            author: None,
+            published: Some("2024-01-01T10:00:00Z".into()),
+            content: "Body text".into(),
+        };
+        let note = render_item(dir.path(), &feed, &item).unwrap();
+        let frontmatter = crate::frontmatter::parse(&note).unwrap();
+        assert_eq!(frontmatter["source"], "https://example.com/feed.xml");
+        assert_eq!(frontmatter["link"], "https://example.com/hello");
+        assert!(frontmatter.get("author").is_none());
+        assert!(note.contains("# Hello\n\nBody text\n\n[Read the original](https://example.com/hello)"));
+    }
+}
@@ -1,7 +1,13 @@
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+/// Locks held longer than this are assumed abandoned and expire
+const STALE_AFTER: Duration = Duration::from_secs(300);
+/// How long a writer waits for another writer of the same file
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
 lazy_static::lazy_static! {
     static ref FILE_LOCK_MANAGER: FileLock = FileLock {
         locks: Arc::new(Mutex::new(HashMap::new())),
@@ -32,7 +38,7 @@ impl FileLock {
     /// Acquire a write lock on a file
     /// Blocks until lock is available or timeout
     pub fn acquire_write_lock(path: &str, operation_id: &str) -> Result<(), String> {
-        let timeout = Duration::from_secs(30);
+        let timeout = WRITE_TIMEOUT;
         let start = Instant::now();
         
         loop {
@@ -42,8 +48,8 @@ impl FileLock {
                 
                 // Check if file is already locked
                 if let Some(lock_state) = locks.get(path) {
-                    // Check for stale locks (> 5 minutes old)
-                    if lock_state.locked_at.elapsed() > Duration::from_secs(300) {
+                    // Check for stale locks
+                    if lock_state.locked_at.elapsed() > STALE_AFTER {
                         locks.remove(path);
                     } else {
                         // File is locked
@@ -102,6 +108,26 @@ impl FileLock {
         Ok(locks.contains_key(path))
     }
     
+    /// Who holds the lock on a file and for how long. A stale lock is
+    /// expired here rather than reported as held.
+    pub fn status(path: &str) -> Result<FileLockStatus, String> {
+        let mut locks = FILE_LOCK_MANAGER.locks.lock()
+            .map_err(|e| format!("Failed to acquire lock mutex: {}", e))?;
+
+        let mut status = FileLockStatus::default();
+        if let Some(lock_state) = locks.get(path) {
+            if lock_state.locked_at.elapsed() > STALE_AFTER {
+                locks.remove(path);
+                status.expired_stale_lock = true;
+            } else {
+                status.locked = true;
+                status.locked_by = Some(lock_state.locked_by.clone());
+                status.held_for_ms = Some(lock_state.locked_at.elapsed().as_millis() as u64);
+            }
+        }
+        Ok(status)
+    }
+
     /// Force release all locks (emergency use only)
     pub fn release_all_locks() -> Result<(), String> {
         let mut locks = FILE_LOCK_MANAGER.locks.lock()
@@ -114,20 +140,46 @@ impl FileLock {
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLockStatus {
+    pub locked: bool,
+    pub locked_by: Option<String>,
+    pub held_for_ms: Option<u64>,
+    /// A lock older than the expiry was found and released
+    pub expired_stale_lock: bool,
+    /// Hash of the file as it is on disk; pass it back when saving so a
+    /// change made elsewhere in the meantime is caught
+    pub content_hash: Option<String>,
+}
+
+/// Hash of a file's bytes as used for conflict checks
+pub fn content_hash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// Hash of the file at `path`, or None if it can't be read
+pub fn disk_hash(path: &str) -> Option<String> {
+    std::fs::read(path).ok().map(|bytes| content_hash(&bytes))
+}
+
 /// RAII-style lock guard that automatically releases lock when dropped
-#[allow(dead_code)]
 pub struct FileLockGuard {
     path: String,
     operation_id: String,
 }
 
-#[allow(dead_code)]
 impl FileLockGuard {
     /// Create a new file lock guard
     pub fn new(path: String, operation_id: String) -> Result<Self, String> {
         FileLock::acquire_write_lock(&path, &operation_id)?;
         Ok(FileLockGuard { path, operation_id })
     }
+
+    /// Write lock for one save of `path`
+    pub fn for_write(path: &str) -> Result<Self, String> {
+        Self::new(path.to_string(), format!("write-{}", uuid::Uuid::new_v4()))
+    }
 }
 
 impl Drop for FileLockGuard {
@@ -213,6 +265,16 @@ where
     operation()
 }
 
+// --- Tauri Commands ---
+
+/// Whether a file is being written and its current hash on disk
+#[tauri::command]
+pub fn file_lock_status(path: String) -> Result<FileLockStatus, String> {
+    let mut status = FileLock::status(&path)?;
+    status.content_hash = disk_hash(&path);
+    Ok(status)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,4 +306,18 @@ mod tests {
         // Lock should be released automatically
         assert!(!FileLock::is_locked(&path).unwrap());
     }
+
+    #[test]
+    fn test_status_reports_holder_and_expires_stale_locks() {
+        let path = "/test/file3.txt";
+        let _guard = FileLockGuard::new(path.to_string(), "test-op-3".to_string()).unwrap();
+        let status = FileLock::status(path).unwrap();
+        assert!(status.locked && !status.expired_stale_lock);
+        assert_eq!(status.locked_by.as_deref(), Some("test-op-3"));
+
+        FILE_LOCK_MANAGER.locks.lock().unwrap().get_mut(path).unwrap().locked_at = Instant::now() - STALE_AFTER - Duration::from_secs(1);
+        let status = FileLock::status(path).unwrap();
+        assert!(!status.locked && status.expired_stale_lock);
+        assert!(!FileLock::is_locked(path).unwrap());
+    }
 }
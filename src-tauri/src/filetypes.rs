@@ -0,0 +1,254 @@
+//! Which viewer opens which kind of file.
+//!
+//! Extensions map to a `FileHandler`: the markdown editor, a read-only
+//! preview, a built-in viewer (table, canvas, ...), a plugin's viewer, or the
+//! OS default app. Built-in defaults can be overridden per extension in the
+//! `file_handlers` setting, globally or per workspace; the workspace map is
+//! merged over the global one like any other setting.
+//!
+//! Compound extensions are matched first, so `drawing.excalidraw.md` can be
+//! routed differently from plain notes.
+
+use crate::settings::SettingsScope;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Settings key holding extension -> handler overrides
+const SETTINGS_KEY: &str = "file_handlers";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FileHandler {
+    /// The markdown editor
+    Editor,
+    /// Read-only preview (images, PDFs, media)
+    Preview,
+    /// A built-in viewer such as `table`, `canvas`, `kanban` or `diagram`
+    #[serde(rename_all = "camelCase")]
+    Viewer { viewer: String },
+    /// A viewer contributed by a plugin
+    #[serde(rename_all = "camelCase")]
+    Plugin { plugin_id: String, viewer: Option<String> },
+    /// The OS default app for the file
+    External,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTypeRegistry {
+    /// Effective handler per extension
+    pub handlers: BTreeMap<String, FileHandler>,
+    /// Extensions whose handler comes from settings rather than the defaults
+    pub overridden: Vec<String>,
+    /// Handler for extensions not in `handlers`
+    pub fallback: FileHandler,
+}
+
+fn viewer(name: &str) -> FileHandler {
+    FileHandler::Viewer { viewer: name.to_string() }
+}
+
+/// Built-in handlers; anything else opens externally
+fn defaults() -> BTreeMap<String, FileHandler> {
+    let mut handlers = BTreeMap::new();
+    for ext in ["md", "markdown", "txt"] {
+        handlers.insert(ext.to_string(), FileHandler::Editor);
+    }
+    for ext in ["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "pdf", "mp3", "m4a", "wav", "ogg", "mp4", "webm", "mov"] {
+        handlers.insert(ext.to_string(), FileHandler::Preview);
+    }
+    for (ext, name) in [
+        ("csv", "table"),
+        ("tsv", "table"),
+        ("canvas", "canvas"),
+        ("kanban", "kanban"),
+        ("excalidraw", "diagram"),
+        ("excalidraw.md", "diagram"),
+    ] {
+        handlers.insert(ext.to_string(), viewer(name));
+    }
+    handlers.insert("drawio".to_string(), FileHandler::External);
+    handlers
+}
+
+fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// Overrides from a `file_handlers` value; `null` entries mean "back to the
+/// default", so a workspace can undo a global override
+fn overrides(value: Option<Value>) -> BTreeMap<String, Option<FileHandler>> {
+    let Some(Value::Object(map)) = value else {
+        return BTreeMap::new();
+    };
+    map.into_iter()
+        .filter_map(|(ext, handler)| {
+            let handler = match handler {
+                Value::Null => None,
+                handler => Some(serde_json::from_value(handler).ok()?),
+            };
+            Some((normalize_extension(&ext), handler))
+        })
+        .collect()
+}
+
+fn build_registry(settings: Option<Value>) -> FileTypeRegistry {
+    let mut handlers = defaults();
+    let mut overridden = Vec::new();
+    for (ext, handler) in overrides(settings) {
+        if let Some(handler) = handler {
+            handlers.insert(ext.clone(), handler);
+            overridden.push(ext);
+        }
+    }
+    FileTypeRegistry { handlers, overridden, fallback: FileHandler::External }
+}
+
+/// Extensions of a file name to try, longest first: `a.excalidraw.md`
+/// gives `excalidraw.md` then `md`
+fn candidate_extensions(path: &Path) -> Vec<String> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let parts: Vec<&str> = name.trim_start_matches('.').split('.').collect();
+    let mut candidates = Vec::new();
+    if parts.len() > 2 {
+        candidates.push(parts[parts.len() - 2..].join("."));
+    }
+    if parts.len() > 1 {
+        candidates.push(parts[parts.len() - 1].to_string());
+    }
+    candidates
+}
+
+impl FileTypeRegistry {
+    pub fn handler_for(&self, path: &Path) -> FileHandler {
+        candidate_extensions(path)
+            .iter()
+            .find_map(|ext| self.handlers.get(ext))
+            .cloned()
+            .unwrap_or_else(|| self.fallback.clone())
+    }
+}
+
+fn workspace_or_current(app: &AppHandle, workspace: Option<String>) -> Option<PathBuf> {
+    workspace.map(PathBuf::from).or_else(|| crate::settings::current_workspace(app))
+}
+
+pub fn registry(app: &AppHandle, workspace: Option<&Path>) -> FileTypeRegistry {
+    build_registry(crate::settings::effective(app, workspace, SETTINGS_KEY))
+}
+
+/// How `path` should be opened, using the settings of the workspace it is in
+pub fn handler_for(app: &AppHandle, path: &Path) -> FileHandler {
+    let workspace = crate::handlers::files::find_workspace_root(path).ok();
+    registry(app, workspace.as_deref()).handler_for(path)
+}
+
+pub fn open_externally(app: &AppHandle, path: &Path) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+// --- Tauri Commands ---
+
+/// Effective handlers for `workspace` (the open workspace when not given)
+#[tauri::command]
+pub fn filetypes_get_registry(app: AppHandle, workspace: Option<String>) -> FileTypeRegistry {
+    registry(&app, workspace_or_current(&app, workspace).as_deref())
+}
+
+/// Set the handler for an extension, or reset it to the default with no
+/// handler. Global unless `scope` says workspace.
+#[tauri::command]
+pub fn filetypes_set_handler(
+    app: AppHandle,
+    ext: String,
+    handler: Option<FileHandler>,
+    scope: Option<SettingsScope>,
+    workspace: Option<String>,
+) -> Result<FileTypeRegistry, String> {
+    let ext = normalize_extension(&ext);
+    if ext.is_empty() || ext.contains('/') {
+        return Err("Invalid file extension".to_string());
+    }
+    let scope = scope.unwrap_or(SettingsScope::Global);
+    let workspace = workspace_or_current(&app, workspace);
+
+    let current = match scope {
+        SettingsScope::Global => crate::settings::effective(&app, None, SETTINGS_KEY),
+        SettingsScope::Workspace => {
+            let workspace = workspace.as_deref().ok_or_else(|| "No workspace is open".to_string())?;
+            crate::settings::workspace_settings(workspace).remove(SETTINGS_KEY)
+        }
+    };
+    let mut map = match current {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    match (handler, scope) {
+        (Some(handler), _) => {
+            map.insert(ext, serde_json::to_value(handler).map_err(|e| e.to_string())?);
+        }
+        (None, SettingsScope::Global) => {
+            map.remove(&ext);
+        }
+        // Masks a global override for this workspace
+        (None, SettingsScope::Workspace) => {
+            map.insert(ext, Value::Null);
+        }
+    }
+
+    let workspace_str = workspace.as_ref().map(|w| w.to_string_lossy().to_string());
+    crate::settings::settings_set(app.clone(), scope, SETTINGS_KEY.to_string(), Value::Object(map), workspace_str)?;
+    Ok(registry(&app, workspace.as_deref()))
+}
+
+/// Open a file the way the registry says. External files are handed to the
+/// OS here; for everything else the handler is returned for the frontend to
+/// route to the right view.
+#[tauri::command]
+pub fn filetypes_open(app: AppHandle, path: String) -> Result<FileHandler, String> {
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    let handler = handler_for(&app, &path);
+    if handler == FileHandler::External {
+        open_externally(&app, &path)?;
+    }
+    Ok(handler)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_defaults_and_overrides() {
+        let registry = build_registry(None);
+        assert_eq!(registry.handler_for(Path::new("/v/Note.md")), FileHandler::Editor);
+        assert_eq!(registry.handler_for(Path::new("/v/data.CSV")), viewer("table"));
+        assert_eq!(registry.handler_for(Path::new("/v/sketch.excalidraw.md")), viewer("diagram"));
+        assert_eq!(registry.handler_for(Path::new("/v/chart.drawio")), FileHandler::External);
+        assert_eq!(registry.handler_for(Path::new("/v/archive.zip")), FileHandler::External);
+
+        let settings = json!({
+            ".drawio": { "type": "plugin", "pluginId": "drawio-viewer", "viewer": null },
+            "csv": null,
+            "txt": { "type": "bogus" }
+        });
+        let registry = build_registry(Some(settings));
+        assert_eq!(
+            registry.handler_for(Path::new("chart.drawio")),
+            FileHandler::Plugin { plugin_id: "drawio-viewer".to_string(), viewer: None }
+        );
+        assert_eq!(registry.handler_for(Path::new("data.csv")), viewer("table"));
+        assert_eq!(registry.handler_for(Path::new("notes.txt")), FileHandler::Editor);
+        assert_eq!(registry.overridden, vec!["drawio".to_string()]);
+    }
+}
@@ -0,0 +1,336 @@
+//! Note frontmatter (YAML properties).
+//!
+//! Parsing lives here and is shared with the metadata cache, which stores each
+//! note's frontmatter as JSON; queries run against the cache so property views
+//! and Dataview-style tables don't need to read every file. Field edits are
+//! made line by line so the rest of the frontmatter keeps its formatting.
+
+use crate::metadata_cache::{self, MetadataFilter};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Operator {
+    Eq,
+    Ne,
+    /// Substring for strings, membership for lists
+    Contains,
+    Exists,
+    Missing,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    /// Field name; nested fields use dots, e.g. `author.name`
+    pub field: String,
+    pub op: Operator,
+    #[serde(default)]
+    pub value: JsonValue,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FrontmatterQuery {
+    /// All conditions must hold
+    pub conditions: Vec<Condition>,
+    pub folder: Option<String>,
+    pub sort_by: Option<String>,
+    pub descending: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteProperties {
+    pub path: String,
+    pub title: Option<String>,
+    pub frontmatter: JsonValue,
+}
+
+// --- Parsing ---
+
+/// The YAML between the `---` fences, or `None` if the note has no frontmatter
+fn yaml_block(content: &str) -> Option<&str> {
+    let body = crate::export::strip_frontmatter(content);
+    if body.len() == content.len() {
+        return None;
+    }
+    let header = &content[..content.len() - body.len()];
+    Some(header.trim_start_matches("---").trim_end().trim_end_matches("---"))
+}
+
+/// Parse a note's YAML frontmatter into a JSON object; `None` if it has none or it is invalid
+pub fn parse(content: &str) -> Option<JsonValue> {
+    let value: serde_yaml::Value = serde_yaml::from_str(yaml_block(content)?).ok()?;
+    serde_json::to_value(value).ok().filter(|v| v.is_object())
+}
+
+/// Why a note's frontmatter can't be used, if it has some
+pub(crate) fn check(content: &str) -> Result<(), String> {
+    let Some(yaml) = yaml_block(content) else {
+        let opened = content.strip_prefix("---").map_or(false, |rest| rest.starts_with('\n') || rest.starts_with("\r\n"));
+        return if opened { Err("Frontmatter is never closed with '---'".to_string()) } else { Ok(()) };
+    };
+    match serde_yaml::from_str::<serde_yaml::Value>(yaml) {
+        Ok(serde_yaml::Value::Mapping(_) | serde_yaml::Value::Null) => Ok(()),
+        Ok(_) => Err("Frontmatter is not a list of key: value pairs".to_string()),
+        Err(e) => Err(format!("Invalid YAML: {}", e)),
+    }
+}
+
+/// Look up `a.b.c` in a JSON object
+pub fn field<'a>(frontmatter: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    path.split('.').try_fold(frontmatter, |value, key| value.get(key))
+}
+
+// --- Editing ---
+
+pub(crate) fn is_key_line(line: &str, key: &str) -> bool {
+    line.strip_prefix(key)
+        .map_or(false, |rest| rest.starts_with(':') && (rest.len() == 1 || rest[1..].starts_with([' ', '\t', '\r'])))
+}
+
+/// YAML for `key: value`, using block style for lists and maps
+fn render_field(key: &str, value: &JsonValue) -> Result<String, String> {
+    let yaml = serde_yaml::to_string(value).map_err(|e| format!("Invalid value for '{}': {}", key, e))?;
+    let yaml = yaml.trim_end();
+    Ok(match value {
+        JsonValue::Array(items) if items.is_empty() => format!("{}: []", key),
+        JsonValue::Object(map) if map.is_empty() => format!("{}: {{}}", key),
+        JsonValue::Array(_) => format!("{}:\n{}", key, yaml),
+        JsonValue::Object(_) => format!("{}:\n{}", key, yaml.lines().map(|l| format!("  {}", l)).collect::<Vec<_>>().join("\n")),
+        _ => format!("{}: {}", key, yaml),
+    })
+}
+
+/// Set (or with `null`, remove) a top-level frontmatter field, creating the
+/// frontmatter if the note has none
+pub fn set_field(content: &str, key: &str, value: &JsonValue) -> Result<String, String> {
+    let key = key.trim();
+    if key.is_empty() || key.contains(['\n', ':']) {
+        return Err(format!("Invalid frontmatter key '{}'", key));
+    }
+    let replacement = if value.is_null() { None } else { Some(render_field(key, value)?) };
+
+    let Some(yaml) = yaml_block(content) else {
+        return Ok(match replacement {
+            Some(field) => format!("---\n{}\n---\n\n{}", field, content),
+            None => content.to_string(),
+        });
+    };
+    let body = crate::export::strip_frontmatter(content);
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    let mut skipping = false;
+    for line in yaml.trim_matches('\n').lines() {
+        if skipping {
+            // Continuation lines of the old value: indented, or list items
+            if line.starts_with([' ', '\t']) || line.starts_with("- ") || line == "-" {
+                continue;
+            }
+            skipping = false;
+        }
+        if is_key_line(line, key) {
+            skipping = true;
+            if let (false, Some(field)) = (replaced, &replacement) {
+                lines.push(field.clone());
+            }
+            replaced = true;
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if let (false, Some(field)) = (replaced, replacement) {
+        lines.push(field);
+    }
+
+    if lines.is_empty() {
+        return Ok(body.to_string());
+    }
+    Ok(format!("---\n{}\n---\n\n{}", lines.join("\n"), body))
+}
+
+// --- Queries ---
+
+fn compare(actual: &JsonValue, wanted: &JsonValue) -> Option<Ordering> {
+    match (actual, wanted) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (JsonValue::Number(a), JsonValue::String(b)) => a.as_f64()?.partial_cmp(&b.trim().parse::<f64>().ok()?),
+        (JsonValue::String(a), JsonValue::Number(b)) => a.trim().parse::<f64>().ok()?.partial_cmp(&b.as_f64()?),
+        // ISO dates compare correctly as strings
+        (JsonValue::String(a), JsonValue::String(b)) => Some(a.to_lowercase().cmp(&b.to_lowercase())),
+        (JsonValue::Bool(a), JsonValue::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn equals(actual: &JsonValue, wanted: &JsonValue) -> bool {
+    compare(actual, wanted).map_or(actual == wanted, |o| o == Ordering::Equal)
+        || matches!((actual, wanted), (JsonValue::Bool(a), JsonValue::String(b)) if a.to_string() == b.to_lowercase())
+}
+
+pub(crate) fn matches(frontmatter: &JsonValue, condition: &Condition) -> bool {
+    let actual = field(frontmatter, &condition.field).filter(|v| !v.is_null());
+    let wanted = &condition.value;
+    match (condition.op, actual) {
+        (Operator::Exists, actual) => actual.is_some(),
+        (Operator::Missing, actual) => actual.is_none(),
+        (Operator::Ne, None) => true,
+        (_, None) => false,
+        (Operator::Eq, Some(JsonValue::Array(items))) if !wanted.is_array() => items.iter().any(|i| equals(i, wanted)),
+        (Operator::Eq, Some(actual)) => equals(actual, wanted),
+        (Operator::Ne, Some(JsonValue::Array(items))) if !wanted.is_array() => !items.iter().any(|i| equals(i, wanted)),
+        (Operator::Ne, Some(actual)) => !equals(actual, wanted),
+        (Operator::Contains, Some(JsonValue::Array(items))) => items.iter().any(|i| equals(i, wanted)),
+        (Operator::Contains, Some(JsonValue::String(s))) => wanted
+            .as_str()
+            .map_or(false, |w| s.to_lowercase().contains(&w.to_lowercase())),
+        (Operator::Contains, Some(_)) => false,
+        (Operator::Gt, Some(actual)) => compare(actual, wanted) == Some(Ordering::Greater),
+        (Operator::Gte, Some(actual)) => matches!(compare(actual, wanted), Some(Ordering::Greater | Ordering::Equal)),
+        (Operator::Lt, Some(actual)) => compare(actual, wanted) == Some(Ordering::Less),
+        (Operator::Lte, Some(actual)) => matches!(compare(actual, wanted), Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+pub fn query(root: &Path, query: &FrontmatterQuery) -> Result<Vec<NoteProperties>, String> {
+    let notes = metadata_cache::query(
+        root,
+        &MetadataFilter {
+            folder: query.folder.clone(),
+            ..Default::default()
+        },
+    )?;
+
+    let mut results: Vec<NoteProperties> = notes
+        .into_iter()
+        .filter(|note| crate::links::is_note(&note.path))
+        .filter_map(|note| {
+            let frontmatter = note.frontmatter?;
+            query.conditions.iter().all(|c| matches(&frontmatter, c)).then(|| NoteProperties {
+                path: note.path,
+                title: note.title,
+                frontmatter,
+            })
+        })
+        .collect();
+
+    if let Some(sort_by) = &query.sort_by {
+        // Notes without the field sort last either way
+        results.sort_by(|a, b| {
+            let ordering = match (field(&a.frontmatter, sort_by), field(&b.frontmatter, sort_by)) {
+                (Some(x), Some(y)) => {
+                    let o = compare(x, y).unwrap_or(Ordering::Equal);
+                    if query.descending { o.reverse() } else { o }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            ordering.then_with(|| a.path.cmp(&b.path))
+        });
+    }
+    if let Some(limit) = query.limit {
+        results.truncate(limit);
+    }
+    Ok(results)
+}
+
+fn read_note(path: &str) -> Result<String, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    crate::encryption::decrypt_for_read(path, content)
+}
+
+// --- Tauri Commands ---
+
+/// A note's frontmatter as JSON (`null` if it has none)
+#[tauri::command]
+pub fn get_frontmatter(path: String) -> Result<JsonValue, String> {
+    Ok(parse(&read_note(&path)?).unwrap_or(JsonValue::Null))
+}
+
+/// Set a top-level frontmatter field; a `null` value removes it
+#[tauri::command]
+pub fn set_frontmatter_field(path: String, key: String, value: JsonValue) -> Result<JsonValue, String> {
+    crate::vaults::ensure_writable(Path::new(&path))?;
+    let updated = set_field(&read_note(&path)?, &key, &value)?;
+    let frontmatter = parse(&updated).unwrap_or(JsonValue::Null);
+    crate::handlers::files::write_file_content(path, updated)?;
+    Ok(frontmatter)
+}
+
+#[tauri::command]
+pub fn query_notes_by_frontmatter(workspace_path: String, filter: FrontmatterQuery) -> Result<Vec<NoteProperties>, String> {
+    query(Path::new(&workspace_path), &filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_nested_fields() {
+        let fm = parse("---\ntitle: Plan\nauthor:\n  name: Ada\ntags: [a, b]\n---\nBody").unwrap();
+        assert_eq!(field(&fm, "author.name"), Some(&json!("Ada")));
+        assert_eq!(fm["tags"], json!(["a", "b"]));
+        assert!(parse("No frontmatter").is_none());
+    }
+
+    #[test]
+    fn test_set_field_preserves_other_lines() {
+        let content = "---\n# keep me\ntitle: Plan\ntags:\n  - a\n  - b\nstatus: draft\n---\n\nBody\n";
+        let updated = set_field(content, "tags", &json!(["x"])).unwrap();
+        assert_eq!(updated, "---\n# keep me\ntitle: Plan\ntags:\n- x\nstatus: draft\n---\n\nBody\n");
+
+        let updated = set_field(&updated, "status", &JsonValue::Null).unwrap();
+        let updated = set_field(&updated, "due", &json!("2024-05-01")).unwrap();
+        assert_eq!(parse(&updated).unwrap(), json!({"title": "Plan", "tags": ["x"], "due": "2024-05-01"}));
+
+        assert_eq!(set_field("Body", "done", &json!(true)).unwrap(), "---\ndone: true\n---\n\nBody");
+    }
+
+    #[test]
+    fn test_conditions() {
+        let fm = json!({"status": "Active", "priority": 3, "tags": ["work"], "due": "2024-05-01"});
+        let c = |field: &str, op, value| Condition { field: field.into(), op, value };
+        assert!(matches(&fm, &c("status", Operator::Eq, json!("active"))));
+        assert!(matches(&fm, &c("priority", Operator::Gte, json!("3"))));
+        assert!(matches(&fm, &c("tags", Operator::Eq, json!("work"))));
+        assert!(matches(&fm, &c("due", Operator::Lt, json!("2024-06-01"))));
+        assert!(matches(&fm, &c("owner", Operator::Missing, JsonValue::Null)));
+        assert!(!matches(&fm, &c("priority", Operator::Gt, json!(5))));
+    }
+
+    #[test]
+    fn test_query_sorts_and_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join("a.md"), "---\npriority: 1\ntype: task\n---\n").unwrap();
+        fs::write(root.join("b.md"), "---\npriority: 5\ntype: task\n---\n").unwrap();
+        fs::write(root.join("c.md"), "---\ntype: note\n---\n").unwrap();
+
+        let results = query(
+            &root,
+            &FrontmatterQuery {
+                conditions: vec![Condition { field: "type".into(), op: Operator::Eq, value: json!("task") }],
+                sort_by: Some("priority".into()),
+                descending: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.path.rsplit('/').next().unwrap()).collect();
+        assert_eq!(names, vec!["b.md", "a.md"]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Autosave of unsaved editor buffers.
+//!
+//! While a note has unsaved edits the editor periodically sends its buffer
+//! here, and it is kept in the workspace's `.lokus/autosave`, a few snapshots
+//! per file. Unlike the write journal, which covers a save that is already
+//! under way, these are edits the user never saved: after a crash or a
+//! force-quit `autosave_recover` lists the buffers that are newer than the
+//! file on disk so the app can offer them back. A successful save calls
+//! `autosave_clear`.
+
+use super::files::{durable_write, find_workspace_root};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::AppHandle;
+
+const AUTOSAVE_DIR: &str = "autosave";
+/// Snapshots kept per file; older ones are removed as new ones arrive
+const MAX_SNAPSHOTS: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    /// Relative to the workspace root
+    path: String,
+    content: String,
+    cursor: Option<usize>,
+    saved_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableBuffer {
+    pub path: String,
+    pub content: String,
+    /// Editor cursor position when the buffer was stored
+    pub cursor: Option<usize>,
+    pub saved_at: i64,
+    /// Last modification of the file on disk; `None` if it no longer exists
+    pub disk_modified_at: Option<i64>,
+}
+
+fn autosave_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join(AUTOSAVE_DIR)
+}
+
+fn buffer_dir(root: &Path, rel: &str) -> PathBuf {
+    let digest = blake3::hash(rel.as_bytes()).to_hex();
+    autosave_dir(root).join(&digest[..16])
+}
+
+/// Snapshot files of one buffer, oldest first
+fn snapshots(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    files.retain(|p| p.extension().and_then(|e| e.to_str()) == Some("json"));
+    files.sort();
+    files
+}
+
+fn read_snapshot(path: &Path) -> Option<Snapshot> {
+    fs::read(path).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+fn modified_millis(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+/// Workspace root and relative path of a note, if it is in a workspace
+fn locate(path: &Path) -> Option<(PathBuf, String)> {
+    let root = find_workspace_root(path).ok()?;
+    let rel = crate::links::relative_path(&root, path)?;
+    Some((root, rel))
+}
+
+/// Keep `content` as the newest snapshot of `path`. Files outside a
+/// workspace have nowhere to go and are skipped, and so are encrypted notes,
+/// whose plaintext must never reach the disk.
+fn store(path: &Path, content: &str, cursor: Option<usize>) -> Result<(), String> {
+    let Some((root, rel)) = locate(path) else {
+        return Ok(());
+    };
+    let dir = buffer_dir(&root, &rel);
+    if crate::encryption::stores_encrypted(path) {
+        // Snapshots taken before the note was encrypted go too
+        let _ = fs::remove_dir_all(&dir);
+        return Ok(());
+    }
+    let existing = snapshots(&dir);
+    if existing
+        .last()
+        .and_then(|newest| read_snapshot(newest))
+        .is_some_and(|newest| newest.content == content && newest.cursor == cursor)
+    {
+        return Ok(());
+    }
+
+    let snapshot = Snapshot {
+        path: rel,
+        content: content.to_string(),
+        cursor,
+        saved_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let json = serde_json::to_vec(&snapshot).map_err(|e| format!("Failed to serialize autosave: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create autosave directory: {}", e))?;
+    // Zero-padded so names sort by time
+    durable_write(&dir.join(format!("{:015}.json", snapshot.saved_at)), &json)?;
+
+    let existing = snapshots(&dir);
+    for old in &existing[..existing.len().saturating_sub(MAX_SNAPSHOTS)] {
+        let _ = fs::remove_file(old);
+    }
+    Ok(())
+}
+
+/// Buffers in `root` newer than their files, newest first. Buffers the file
+/// has caught up with (same content, or saved later) are cleared.
+fn recover(root: &Path) -> Vec<RecoverableBuffer> {
+    let Ok(entries) = fs::read_dir(autosave_dir(root)) else {
+        return vec![];
+    };
+
+    let mut buffers = vec![];
+    for dir in entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()) {
+        // A torn newest snapshot falls back to the one before it
+        let Some(snapshot) = snapshots(&dir).iter().rev().find_map(|p| read_snapshot(p)) else {
+            let _ = fs::remove_dir_all(&dir);
+            continue;
+        };
+        let target = root.join(&snapshot.path);
+        let disk_modified_at = modified_millis(&target);
+        let stale = disk_modified_at.is_some_and(|modified| modified >= snapshot.saved_at)
+            || fs::read_to_string(&target).is_ok_and(|on_disk| on_disk == snapshot.content);
+        if stale {
+            let _ = fs::remove_dir_all(&dir);
+            continue;
+        }
+        buffers.push(RecoverableBuffer {
+            path: target.to_string_lossy().to_string(),
+            content: snapshot.content,
+            cursor: snapshot.cursor,
+            saved_at: snapshot.saved_at,
+            disk_modified_at,
+        });
+    }
+
+    buffers.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    buffers
+}
+
+fn clear(path: &Path) {
+    if let Some((root, rel)) = locate(path) {
+        let _ = fs::remove_dir_all(buffer_dir(&root, &rel));
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Store the unsaved buffer of `path`
+#[tauri::command]
+pub async fn autosave_store(path: String, content: String, cursor: Option<usize>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || store(Path::new(&path), &content, cursor))
+        .await
+        .map_err(|e| format!("Autosave failed: {}", e))?
+}
+
+/// Unsaved buffers in `workspace` (the open workspace when not given) that
+/// are newer than the files on disk. Meant to be called once on startup.
+#[tauri::command]
+pub async fn autosave_recover(app: AppHandle, workspace: Option<String>) -> Result<Vec<RecoverableBuffer>, String> {
+    let root = workspace
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(&app))
+        .filter(|root| root.is_dir())
+        .ok_or_else(|| "No workspace is open".to_string())?;
+    tokio::task::spawn_blocking(move || recover(&root))
+        .await
+        .map_err(|e| format!("Autosave recovery failed: {}", e))
+}
+
+/// Drop the stored buffers of `path`, once it was saved or the recovery declined
+#[tauri::command]
+pub async fn autosave_clear(path: String) -> Result<(), String> {
+    clear(Path::new(&path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_notes_are_not_snapshotted() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("Private")).unwrap();
+        let config = r#"{"version":1,"keyId":"k","salt":"","check":"","encryptedFolders":["Private"]}"#;
+        fs::create_dir(root.join(".lokus")).unwrap();
+        fs::write(root.join(".lokus/encryption.json"), config).unwrap();
+
+        let secret = root.join("Private/diary.md");
+        store(&secret, "dear diary", None).unwrap();
+        assert!(!buffer_dir(root, "Private/diary.md").exists());
+        assert!(recover(root).is_empty());
+
+        let public = root.join("plan.md");
+        store(&public, "draft", None).unwrap();
+        assert_eq!(snapshots(&buffer_dir(root, "plan.md")).len(), 1);
+    }
+
+    #[test]
+    fn test_unsaved_buffers_are_recovered_until_cleared() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".lokus")).unwrap();
+        let note = root.join("note.md");
+        let saved = root.join("saved.md");
+        fs::write(&note, "old").unwrap();
+        fs::write(&saved, "old").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        for i in 0..MAX_SNAPSHOTS + 2 {
+            store(&note, &format!("draft {}", i), Some(i)).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        store(&saved, "same", None).unwrap();
+        fs::write(&saved, "same").unwrap();
+
+        assert_eq!(snapshots(&buffer_dir(root, "note.md")).len(), MAX_SNAPSHOTS);
+        let buffers = recover(root);
+        assert_eq!(buffers.len(), 1);
+        assert_eq!(buffers[0].path, note.to_string_lossy());
+        assert_eq!(buffers[0].content, format!("draft {}", MAX_SNAPSHOTS + 1));
+        assert_eq!(buffers[0].cursor, Some(MAX_SNAPSHOTS + 1));
+        assert!(!buffer_dir(root, "saved.md").exists());
+
+        clear(&note);
+        assert!(recover(root).is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+//! Reading and writing large files in pieces.
+//!
+//! `read_file_range` serves one slice of a file; `read_file_stream_start`
+//! sends a whole file as `file-stream:chunk` events followed by one
+//! `file-stream:end`. Streaming writes go to a temp file next to the target
+//! that replaces it on finish. Chunks travel base64-encoded.
+//!
+//! Whole-file reads and writes are capped per file (`max_file_size_mb` in
+//! the workspace settings, 50 MB by default) so a huge file fails with a
+//! clear error instead of being pulled into memory; ranges and streams are
+//! the way to open those.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+const DEFAULT_MAX_FILE_MB: u64 = 50;
+/// Workspace setting overriding `DEFAULT_MAX_FILE_MB`
+const SETTINGS_KEY: &str = "max_file_size_mb";
+const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+/// Largest range or chunk handled in one call
+const MAX_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+const CHUNK_EVENT: &str = "file-stream:chunk";
+const END_EVENT: &str = "file-stream:end";
+
+/// Read streams the frontend has asked to stop
+static CANCELLED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+/// Open streaming writes, by id
+static WRITES: Lazy<Mutex<HashMap<String, StreamWrite>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct StreamWrite {
+    target: PathBuf,
+    temp: tempfile::NamedTempFile,
+    written: u64,
+    limit: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRange {
+    pub offset: u64,
+    /// Base64 of the bytes read
+    pub data: String,
+    pub bytes_read: usize,
+    pub total_size: u64,
+    pub eof: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStreamStart {
+    pub stream_id: String,
+    pub total_size: u64,
+    pub chunk_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamChunk {
+    stream_id: String,
+    offset: u64,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamEnd {
+    stream_id: String,
+    bytes: u64,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+/// Largest file, in bytes, that may be read or written whole
+pub(crate) fn size_limit(path: &Path) -> u64 {
+    let configured = super::files::find_workspace_root(path)
+        .ok()
+        .and_then(|root| crate::settings::workspace_settings(&root).get(SETTINGS_KEY).and_then(|v| v.as_u64()));
+    configured.unwrap_or(DEFAULT_MAX_FILE_MB).max(1) * 1024 * 1024
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Refuse to handle `size` bytes of `path` in one piece if that's over its limit
+pub(crate) fn check_size(path: &Path, size: u64) -> Result<(), String> {
+    let limit = size_limit(path);
+    if size > limit {
+        return Err(format!(
+            "{} is {}, over the {} limit for whole-file reads and writes; open it in ranges instead",
+            path.display(),
+            megabytes(size),
+            megabytes(limit)
+        ));
+    }
+    Ok(())
+}
+
+fn clamp_chunk_size(requested: Option<usize>) -> usize {
+    requested.unwrap_or(DEFAULT_CHUNK_SIZE).clamp(1, MAX_CHUNK_SIZE)
+}
+
+fn read_range(path: &Path, offset: u64, len: usize) -> Result<FileRange, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let total_size = file.metadata().map_err(|e| e.to_string())?.len();
+    file.seek(SeekFrom::Start(offset)).map_err(|e| format!("Failed to seek: {}", e))?;
+
+    let mut buffer = Vec::with_capacity(len.min(total_size.saturating_sub(offset) as usize));
+    file.take(len as u64).read_to_end(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(FileRange {
+        offset,
+        bytes_read: buffer.len(),
+        eof: offset + buffer.len() as u64 >= total_size,
+        data: BASE64.encode(&buffer),
+        total_size,
+    })
+}
+
+/// Send `path` as chunk events until the end, an error, or cancellation
+fn stream_file(app: &AppHandle, stream_id: &str, path: &Path, chunk_size: usize) -> StreamEnd {
+    let mut end = StreamEnd { stream_id: stream_id.to_string(), bytes: 0, cancelled: false, error: None };
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            end.error = Some(format!("Failed to open {}: {}", path.display(), e));
+            return end;
+        }
+    };
+
+    let mut buffer = vec![0u8; chunk_size];
+    loop {
+        if CANCELLED.lock().is_ok_and(|mut c| c.remove(stream_id)) {
+            end.cancelled = true;
+            break;
+        }
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                end.error = Some(format!("Failed to read {}: {}", path.display(), e));
+                break;
+            }
+        };
+        let _ = app.emit(CHUNK_EVENT, StreamChunk {
+            stream_id: stream_id.to_string(),
+            offset: end.bytes,
+            data: BASE64.encode(&buffer[..read]),
+        });
+        end.bytes += read as u64;
+    }
+    end
+}
+
+fn take_write(stream_id: &str) -> Result<StreamWrite, String> {
+    WRITES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(stream_id)
+        .ok_or_else(|| format!("No open write stream {}", stream_id))
+}
+
+// --- Tauri Commands ---
+
+/// Up to `len` bytes of `path` starting at `offset`
+#[tauri::command]
+pub async fn read_file_range(path: String, offset: u64, len: usize) -> Result<FileRange, String> {
+    let len = len.min(MAX_CHUNK_SIZE);
+    tokio::task::spawn_blocking(move || read_range(Path::new(&path), offset, len))
+        .await
+        .map_err(|e| format!("Read failed: {}", e))?
+}
+
+/// Start sending `path` as `file-stream:chunk` events. The stream id tags
+/// every event of this stream and can be passed to `file_stream_cancel`;
+/// pass one in to listen before the first chunk can arrive.
+#[tauri::command]
+pub async fn read_file_stream_start(
+    app: AppHandle,
+    path: String,
+    chunk_size: Option<usize>,
+    stream_id: Option<String>,
+) -> Result<FileStreamStart, String> {
+    let source = PathBuf::from(&path);
+    let total_size = fs::metadata(&source).map_err(|e| format!("Failed to open {}: {}", path, e))?.len();
+    let start = FileStreamStart {
+        stream_id: stream_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        total_size,
+        chunk_size: clamp_chunk_size(chunk_size),
+    };
+
+    let (stream_id, chunk_size) = (start.stream_id.clone(), start.chunk_size);
+    tokio::task::spawn_blocking(move || {
+        let end = stream_file(&app, &stream_id, &source, chunk_size);
+        if let Some(error) = &end.error {
+            eprintln!("[FileStream] {}", error);
+        }
+        let _ = app.emit(END_EVENT, end);
+    });
+    Ok(start)
+}
+
+/// Stop a read stream before its next chunk
+#[tauri::command]
+pub fn file_stream_cancel(stream_id: String) {
+    if let Ok(mut cancelled) = CANCELLED.lock() {
+        cancelled.insert(stream_id);
+    }
+}
+
+/// Open a streaming write to `path`. Nothing replaces the file until
+/// `write_file_stream_finish`.
+#[tauri::command]
+pub fn write_file_stream_start(path: String) -> Result<String, String> {
+    let target = PathBuf::from(&path);
+    crate::vaults::ensure_writable(&target)?;
+    let parent = target.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let temp = tempfile::Builder::new()
+        .prefix(".lokus-save-")
+        .suffix(".tmp")
+        .tempfile_in(parent)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let limit = size_limit(&target);
+    WRITES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(stream_id.clone(), StreamWrite { target, temp, written: 0, limit });
+    Ok(stream_id)
+}
+
+/// Append a base64 chunk to an open write. Going over the file size limit
+/// aborts the write.
+#[tauri::command]
+pub fn write_file_stream_chunk(stream_id: String, data: String) -> Result<u64, String> {
+    let bytes = BASE64.decode(data.as_bytes()).map_err(|e| format!("Invalid chunk: {}", e))?;
+    if bytes.len() > MAX_CHUNK_SIZE {
+        return Err(format!("Chunks can be at most {}", megabytes(MAX_CHUNK_SIZE as u64)));
+    }
+
+    let mut writes = WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    let write = writes.get_mut(&stream_id).ok_or_else(|| format!("No open write stream {}", stream_id))?;
+    if write.written + bytes.len() as u64 > write.limit {
+        let write = writes.remove(&stream_id).expect("write stream exists");
+        return Err(format!(
+            "{} would be over its {} limit; the write was aborted",
+            write.target.display(),
+            megabytes(write.limit)
+        ));
+    }
+    if let Err(e) = write.temp.write_all(&bytes) {
+        writes.remove(&stream_id);
+        return Err(format!("Failed to write chunk: {}", e));
+    }
+    write.written += bytes.len() as u64;
+    Ok(write.written)
+}
+
+/// Flush an open write and replace the target with it. Returns the number
+/// of bytes written.
+#[tauri::command]
+pub async fn write_file_stream_finish(stream_id: String) -> Result<u64, String> {
+    let write = take_write(&stream_id)?;
+    tokio::task::spawn_blocking(move || {
+        let target = write.target.to_string_lossy().to_string();
+        let _lock = crate::file_locking::FileLockGuard::for_write(&target)?;
+        // Notes are encrypted and indexed like any other save
+        if super::files::is_note_write(&write.target) {
+            let content = fs::read_to_string(write.temp.path())
+                .map_err(|_| format!("{} can only be saved as UTF-8 text", target))?;
+            super::files::write_note(&target, &content)?;
+            return Ok(write.written);
+        }
+        if let Ok(meta) = fs::metadata(&write.target) {
+            let _ = fs::set_permissions(write.temp.path(), meta.permissions());
+        }
+        write.temp.as_file().sync_all().map_err(|e| format!("Failed to flush {}: {}", target, e))?;
+        write.temp.persist(&write.target).map_err(|e| format!("Failed to replace {}: {}", target, e.error))?;
+        #[cfg(unix)]
+        if let Some(dir) = write.target.parent().and_then(|parent| fs::File::open(parent).ok()) {
+            let _ = dir.sync_all();
+        }
+        Ok(write.written)
+    })
+    .await
+    .map_err(|e| format!("Write failed: {}", e))?
+}
+
+/// Drop an open write; the target is left as it was
+#[tauri::command]
+pub fn write_file_stream_abort(stream_id: String) -> Result<(), String> {
+    take_write(&stream_id).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges_and_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.bin");
+        fs::write(&path, b"0123456789").unwrap();
+
+        let range = read_range(&path, 4, 3).unwrap();
+        assert_eq!(BASE64.decode(range.data).unwrap(), b"456");
+        assert_eq!((range.total_size, range.eof), (10, false));
+        let tail = read_range(&path, 8, 100).unwrap();
+        assert_eq!((tail.bytes_read, tail.eof), (2, true));
+
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        fs::write(dir.path().join(".lokus/settings.json"), r#"{"max_file_size_mb": 2}"#).unwrap();
+        assert!(check_size(&path, 2 * 1024 * 1024).is_ok());
+        let error = check_size(&path, 3 * 1024 * 1024).unwrap_err();
+        assert!(error.contains("3.0 MB") && error.contains("2.0 MB limit"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_write_replaces_on_finish() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.md");
+        fs::write(&path, "old").unwrap();
+
+        let id = write_file_stream_start(path.to_string_lossy().to_string()).unwrap();
+        write_file_stream_chunk(id.clone(), BASE64.encode("new ")).unwrap();
+        assert_eq!(write_file_stream_chunk(id.clone(), BASE64.encode("text")).unwrap(), 8);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        let written = write_file_stream_finish(id.clone()).await.unwrap();
+        assert_eq!(written, 8);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new text");
+        assert!(write_file_stream_abort(id).is_err());
+    }
+}
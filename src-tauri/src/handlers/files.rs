@@ -1,6 +1,9 @@
 use serde::{Serialize, Deserialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use crate::errors::LokusError;
+use crate::traversal::{VaultWalk, Visit};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FileEntry {
@@ -11,23 +14,54 @@ pub struct FileEntry {
     created: Option<i64>,
     modified: Option<i64>,
     children: Option<Vec<FileEntry>>,
+    /// A cloud file that isn't downloaded yet
+    #[serde(default)]
+    placeholder: bool,
+}
+
+/// Directories and files to exclude from file tree
+const EXCLUDED_NAMES: &[&str] = &[".lokus", "node_modules", ".git", ".DS_Store"];
+
+/// Page size used when `read_directory_page` isn't given a limit
+const DEFAULT_PAGE_LIMIT: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectorySort {
+    #[default]
+    Name,
+    Modified,
+    Created,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DirectoryPage {
+    entries: Vec<FileEntry>,
+    /// Pass back as `cursor` for the next page; absent on the last page
+    next_cursor: Option<usize>,
+    total: usize,
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct FolderSummary {
+    files: usize,
+    folders: usize,
+    has_subdirs: bool,
 }
 
 // --- Private Helper ---
 fn read_directory_contents(path: &Path) -> futures::future::BoxFuture<'static, Result<Vec<FileEntry>, String>> {
     let path = path.to_path_buf();
     Box::pin(async move {
-        read_directory_contents_with_depth(&path, 0).await
+        let mut walk = VaultWalk::new(&path);
+        read_directory_contents_with_depth(&path, 0, &mut walk).await
     })
 }
 
-async fn read_directory_contents_with_depth(path: &Path, depth: usize) -> Result<Vec<FileEntry>, String> {
+async fn read_directory_contents_with_depth(path: &Path, depth: usize, walk: &mut VaultWalk) -> Result<Vec<FileEntry>, String> {
     // Limit recursion depth to prevent infinite loops
     const MAX_DEPTH: usize = 10;
 
-    // Directories and files to exclude from file tree
-    const EXCLUDED_NAMES: &[&str] = &[".lokus", "node_modules", ".git", ".DS_Store"];
-
     if depth > MAX_DEPTH {
         return Ok(vec![]);
     }
@@ -48,15 +82,28 @@ async fn read_directory_contents_with_depth(path: &Path, depth: usize) -> Result
 
         // Get file type efficiently without full metadata
         let file_type = entry.file_type().await.map_err(|e| e.to_string())?;
-        let is_directory = file_type.is_dir();
-        
-        // Skip symbolic links to prevent infinite loops
-        if file_type.is_symlink() {
-            continue;
+        let mut is_directory = file_type.is_dir();
+        let mut placeholder = false;
+
+        // Follow symlinks unless they lead back into the vault or somewhere
+        // already listed, and apply the cloud placeholder policy to files
+        if !is_directory {
+            if file_type.is_symlink() && !walk.follow_link(&path) {
+                continue;
+            }
+            let Ok(metadata) = tokio::fs::metadata(&path).await else {
+                continue;
+            };
+            is_directory = metadata.is_dir();
+            match walk.visit_path(&path, &metadata) {
+                Visit::Skip => continue,
+                Visit::Stub => placeholder = true,
+                Visit::Read => {}
+            }
         }
 
         let children = if is_directory {
-            Some(Box::pin(read_directory_contents_with_depth(&path, depth + 1)).await?)
+            Some(Box::pin(read_directory_contents_with_depth(&path, depth + 1, walk)).await?)
         } else {
             None
         };
@@ -67,13 +114,14 @@ async fn read_directory_contents_with_depth(path: &Path, depth: usize) -> Result
         let modified = None;
 
         entries.push(FileEntry {
-            name,
+            name: crate::traversal::listed_name(&path, name),
             path: path.to_string_lossy().to_string(),
             is_directory,
             size,
             created,
             modified,
             children,
+            placeholder,
         });
     }
     
@@ -81,33 +129,262 @@ async fn read_directory_contents_with_depth(path: &Path, depth: usize) -> Result
     Ok(entries)
 }
 
+/// Direct children of `path` as tree entries without children, skipping the
+/// same names as the full walk and following symlinks the same way
+fn list_children(path: &Path) -> Result<Vec<FileEntry>, LokusError> {
+    let root = find_workspace_root(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut walk = VaultWalk::new(&root);
+    let mut entries = vec![];
+    for entry in fs::read_dir(path).map_err(|e| LokusError::io("Failed to read", path, e))?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if EXCLUDED_NAMES.contains(&name.as_str()) {
+            continue;
+        }
+        let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if is_symlink && !walk.follow_link(&entry.path()) {
+            continue;
+        }
+        let Ok(meta) = fs::metadata(entry.path()) else { continue };
+        let visit = walk.visit_path(&entry.path(), &meta);
+        if visit == Visit::Skip {
+            continue;
+        }
+        entries.push(FileEntry {
+            name: crate::traversal::listed_name(&entry.path(), name),
+            path: entry.path().to_string_lossy().to_string(),
+            is_directory: meta.is_dir(),
+            size: if meta.is_dir() { 0 } else { meta.len() },
+            created: crate::metadata_cache::to_ms(meta.created()),
+            modified: crate::metadata_cache::to_ms(meta.modified()),
+            children: None,
+            placeholder: visit == Visit::Stub,
+        });
+    }
+    Ok(entries)
+}
+
+/// Folders first, then by `sort`; dates are newest first unless reversed,
+/// with the name breaking ties so pages stay stable
+fn sort_entries(entries: &mut [FileEntry], sort: DirectorySort, descending: bool) {
+    entries.sort_by(|a, b| {
+        let order = match sort {
+            DirectorySort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            DirectorySort::Modified => b.modified.cmp(&a.modified),
+            DirectorySort::Created => b.created.cmp(&a.created),
+        };
+        let order = if descending { order.reverse() } else { order };
+        b.is_directory.cmp(&a.is_directory).then(order).then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+fn directory_page(path: &Path, cursor: usize, limit: usize, sort: DirectorySort, descending: bool) -> Result<DirectoryPage, LokusError> {
+    let mut entries = list_children(path)?;
+    sort_entries(&mut entries, sort, descending);
+    let total = entries.len();
+    let entries: Vec<FileEntry> = entries.into_iter().skip(cursor).take(limit).collect();
+    let next = cursor + entries.len();
+    Ok(DirectoryPage { entries, next_cursor: (next < total).then_some(next), total })
+}
+
+fn folder_summary(path: &Path) -> Result<FolderSummary, LokusError> {
+    let mut summary = FolderSummary::default();
+    for entry in list_children(path)? {
+        if entry.is_directory {
+            summary.folders += 1;
+        } else {
+            summary.files += 1;
+        }
+    }
+    summary.has_subdirs = summary.folders > 0;
+    Ok(summary)
+}
+
 // --- Tauri Commands ---
 
+/// Nest the flat metadata cache listing into a tree, sorted like the disk walk
+fn tree_from_cache(root: &Path, entries: Vec<crate::metadata_cache::FileMetadata>) -> Vec<FileEntry> {
+    let mut by_parent: std::collections::HashMap<PathBuf, Vec<crate::metadata_cache::FileMetadata>> = std::collections::HashMap::new();
+    for entry in entries {
+        let parent = Path::new(&entry.path).parent().map(Path::to_path_buf).unwrap_or_default();
+        by_parent.entry(parent).or_default().push(entry);
+    }
+
+    fn build(dir: &Path, by_parent: &mut std::collections::HashMap<PathBuf, Vec<crate::metadata_cache::FileMetadata>>) -> Vec<FileEntry> {
+        let mut entries: Vec<FileEntry> = by_parent
+            .remove(dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| {
+                let path = PathBuf::from(&entry.path);
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                FileEntry {
+                    name: crate::traversal::listed_name(&path, name),
+                    children: entry.is_directory.then(|| build(&path, by_parent)),
+                    path: entry.path,
+                    is_directory: entry.is_directory,
+                    size: entry.size,
+                    created: entry.created,
+                    modified: entry.modified,
+                    placeholder: entry.placeholder,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.is_directory.cmp(&a.is_directory).then_with(|| a.name.cmp(&b.name)));
+        entries
+    }
+
+    build(root, &mut by_parent)
+}
+
 #[tauri::command]
-pub async fn read_workspace_files(workspace_path: String) -> Result<Vec<FileEntry>, String> {
-    read_directory_contents(Path::new(&workspace_path)).await
+pub async fn read_workspace_files(workspace_path: String) -> Result<Vec<FileEntry>, LokusError> {
+    // Served from the metadata cache, which stays warm across restarts; walk the
+    // disk only if the cache is unavailable
+    let root = PathBuf::from(&workspace_path);
+    let cache_root = root.clone();
+    let cached = tokio::task::spawn_blocking(move || crate::metadata_cache::cached_entries(&cache_root)).await;
+    match cached {
+        Ok(Ok(entries)) => Ok(tree_from_cache(&root, entries)),
+        _ => Ok(read_directory_contents(&root).await?),
+    }
 }
 
+/// One page of a folder's direct children, for expanding the file tree
+/// lazily instead of reading the whole workspace up front
 #[tauri::command]
-pub async fn read_file_content(path: String) -> Result<String, String> {
-    tokio::fs::read_to_string(path).await.map_err(|e| e.to_string())
+pub async fn read_directory_page(
+    path: String,
+    cursor: Option<usize>,
+    limit: Option<usize>,
+    sort: Option<DirectorySort>,
+    descending: Option<bool>,
+) -> Result<DirectoryPage, LokusError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).max(1);
+    tokio::task::spawn_blocking(move || {
+        directory_page(Path::new(&path), cursor.unwrap_or(0), limit, sort.unwrap_or_default(), descending.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Directory read failed: {}", e))?
 }
 
+/// Child counts of a folder, without listing it
 #[tauri::command]
-pub fn read_binary_file(path: String) -> Result<Vec<u8>, String> {
-    fs::read(path).map_err(|e| e.to_string())
+pub async fn get_folder_summary(path: String) -> Result<FolderSummary, LokusError> {
+    tokio::task::spawn_blocking(move || folder_summary(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Directory read failed: {}", e))?
 }
 
 #[tauri::command]
-pub fn write_file_content(path: String, content: String) -> Result<(), String> {
-    atomic_write_file(&path, &content)
+pub async fn read_file_content(path: String) -> Result<String, LokusError> {
+    let size = tokio::fs::metadata(&path).await.map_err(|e| LokusError::io("Failed to read", &path, e))?.len();
+    super::file_stream::check_size(Path::new(&path), size)?;
+    let content = tokio::fs::read_to_string(&path).await.map_err(|e| LokusError::io("Failed to read", &path, e))?;
+    Ok(crate::encryption::decrypt_for_read(&path, content)?)
 }
 
-// Atomic write implementation: write to temp file then rename
-fn atomic_write_file(path: &str, content: &str) -> Result<(), String> {
+#[tauri::command]
+pub fn read_binary_file(path: String) -> Result<Vec<u8>, LokusError> {
+    let size = fs::metadata(&path).map_err(|e| LokusError::io("Failed to read", &path, e))?.len();
+    super::file_stream::check_size(Path::new(&path), size)?;
+    fs::read(&path).map_err(|e| LokusError::io("Failed to read", &path, e))
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalModification {
+    pub path: String,
+    /// Hash the editor's buffer was based on
+    pub expected_hash: String,
+    /// Hash of what is on disk now
+    pub current_hash: String,
+}
+
+#[tauri::command]
+pub fn write_file_content(path: String, content: String) -> Result<(), LokusError> {
+    let _lock = crate::file_locking::FileLockGuard::for_write(&path)?;
+    write_note(&path, &content)?;
+    Ok(())
+}
+
+/// Save like `write_file_content`, but only if the file on disk still has
+/// `base_hash` (from `file_lock_status`); otherwise the save is refused and a
+/// `file-externally-modified` event lets the editor offer to merge. Returns
+/// the hash of the saved file.
+#[tauri::command]
+pub fn write_file_content_checked(app: AppHandle, path: String, content: String, base_hash: String) -> Result<String, LokusError> {
+    let _lock = crate::file_locking::FileLockGuard::for_write(&path)?;
+    if let Some(current_hash) = crate::file_locking::disk_hash(&path).filter(|hash| *hash != base_hash) {
+        let _ = app.emit("file-externally-modified", ExternalModification {
+            path: path.clone(),
+            expected_hash: base_hash,
+            current_hash,
+        });
+        return Err(LokusError::Conflict {
+            message: format!("{} was changed elsewhere since it was opened", path),
+            files: vec![path],
+        });
+    }
+    Ok(write_note(&path, &content)?)
+}
+
+/// Whether writes to `path` must go through [`write_note`] to be encrypted
+/// and indexed, rather than stored as raw bytes
+pub(super) fn is_note_write(path: &Path) -> bool {
+    crate::encryption::stores_encrypted(path) || crate::search::index::is_indexable(path)
+}
+
+/// Store a note and tell the indexes; the caller holds the write lock.
+/// Returns the hash of the stored bytes.
+pub(super) fn write_note(path: &str, content: &str) -> Result<String, String> {
+    super::file_stream::check_size(Path::new(path), content.len() as u64)?;
+    // Encrypted notes are stored (and indexed) as ciphertext only
+    let stored = crate::encryption::encrypt_for_write(path, content)?;
+    atomic_write_file(path, &stored)?;
+    crate::search::index::notify_file_saved(path, &stored);
+    crate::links::notify_file_saved(path, &stored);
+    crate::metadata_cache::notify_file_saved(path, &stored);
+    #[cfg(desktop)]
+    crate::embeddings::notify_file_changed(path);
+    // Snapshots would hold either ciphertext or leaked plaintext of encrypted notes
+    if stored == content {
+        super::version_policy::notify_file_saved(path, content);
+    }
+    Ok(crate::file_locking::content_hash(stored.as_bytes()))
+}
+
+/// Write `bytes` to `path` so that a crash leaves either the old or the new
+/// file, never a truncated one: temp file in the same directory, fsync,
+/// rename over the original, fsync the directory
+pub(crate) fn durable_write(path: &Path, bytes: &[u8]) -> Result<(), String> {
     use std::io::Write;
 
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut temp = tempfile::Builder::new()
+        .prefix(".lokus-save-")
+        .suffix(".tmp")
+        .tempfile_in(parent)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    temp.write_all(bytes).map_err(|e| format!("Failed to write to temp file: {}", e))?;
+    // Keep the original's permissions rather than the temp file's 0600
+    if let Ok(meta) = fs::metadata(path) {
+        let _ = fs::set_permissions(temp.path(), meta.permissions());
+    }
+    temp.as_file().sync_all().map_err(|e| format!("Failed to flush temp file: {}", e))?;
+    temp.persist(path).map_err(|e| format!("Failed to rename temp file: {}", e.error))?;
+
+    // Make the rename itself durable; directories can't be opened for this on Windows
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+// Atomic write implementation: journal, then write durably
+fn atomic_write_file(path: &str, content: &str) -> Result<(), String> {
     let target_path = Path::new(path);
+    crate::vaults::ensure_writable(target_path)?;
 
     // Pre-write validation: check parent directory exists
     if let Some(parent) = target_path.parent() {
@@ -116,60 +393,17 @@ fn atomic_write_file(path: &str, content: &str) -> Result<(), String> {
         }
     }
 
-    // Create backup if file exists (for rollback)
-    let backup_path = if target_path.exists() {
-        let backup = format!("{}.backup", path);
-        fs::copy(target_path, &backup).ok(); // Best effort - don't fail if backup fails
-        Some(backup)
-    } else {
-        None
-    };
-
-    // Write to temporary file first
-    let temp_path = format!("{}.tmp", path);
-    let write_result = (|| -> Result<(), std::io::Error> {
-        let mut file = fs::File::create(&temp_path)?;
-        file.write_all(content.as_bytes())?;
-        file.sync_all()?; // Ensure data is flushed to disk
-        Ok(())
-    })();
-
-    match write_result {
-        Ok(_) => {
-            // Atomic rename: this is the critical operation
-            match fs::rename(&temp_path, target_path) {
-                Ok(_) => {
-                    // Success! Clean up backup
-                    if let Some(backup) = backup_path {
-                        let _ = fs::remove_file(backup); // Best effort cleanup
-                    }
-                    Ok(())
-                }
-                Err(e) => {
-                    // Rename failed - clean up temp file and restore backup
-                    let _ = fs::remove_file(&temp_path);
-                    if let Some(backup) = backup_path {
-                        let _ = fs::rename(&backup, target_path); // Attempt rollback
-                    }
-                    Err(format!("Failed to rename temp file: {}", e))
-                }
-            }
-        }
-        Err(e) => {
-            // Write to temp failed - clean up
-            let _ = fs::remove_file(&temp_path);
-            if let Some(backup) = backup_path {
-                let _ = fs::remove_file(backup);
-            }
-            Err(format!("Failed to write to temp file: {}", e))
-        }
-    }
+    // If this fails the note is untouched, so don't save without the journal
+    let entry = super::write_journal::record(target_path, content)?;
+    durable_write(target_path, content.as_bytes())?;
+    super::write_journal::complete(entry);
+    Ok(())
 }
 
 // Separate command for saving versions - only called when needed
 #[tauri::command]
-pub fn save_file_version_manual(path: String, content: String) -> Result<(), String> {
-    save_file_version(&path, &content)
+pub fn save_file_version_manual(path: String, content: String) -> Result<(), LokusError> {
+    Ok(save_file_version(&path, &content)?)
 }
 
 // Helper function to save file version
@@ -196,7 +430,7 @@ fn save_file_version(file_path: &str, content: &str) -> Result<(), String> {
 }
 
 // Helper function to find workspace root containing .lokus directory
-fn find_workspace_root(start_path: &Path) -> Result<PathBuf, String> {
+pub(crate) fn find_workspace_root(start_path: &Path) -> Result<PathBuf, String> {
     let mut current = start_path;
 
     // If the path is a file, start from its parent
@@ -223,18 +457,19 @@ fn find_workspace_root(start_path: &Path) -> Result<PathBuf, String> {
 }
 
 #[tauri::command]
-pub fn rename_file(path: String, new_name: String) -> Result<String, String> {
+pub fn rename_file(path: String, new_name: String) -> Result<String, LokusError> {
 
     let path = PathBuf::from(&path);
 
     // Validate that the source file exists
     if !path.exists() {
-        return Err(format!("File or folder '{}' does not exist", path.display()));
+        return Err(LokusError::not_found(&path));
     }
+    crate::vaults::ensure_writable(&path)?;
 
     // Validate new name is not empty
     if new_name.trim().is_empty() {
-        return Err("New name cannot be empty".to_string());
+        return Err(LokusError::invalid("New name cannot be empty"));
     }
 
     let mut new_path = path.clone();
@@ -242,30 +477,34 @@ pub fn rename_file(path: String, new_name: String) -> Result<String, String> {
 
     // Check if destination already exists
     if new_path.exists() {
-        let file_name = new_path.file_name()
-            .ok_or_else(|| "Invalid file path: no filename".to_string())?;
-        return Err(format!("A file or folder named '{}' already exists", file_name.to_string_lossy()));
+        return Err(LokusError::already_exists(&new_path));
     }
 
-    fs::rename(&path, &new_path).map_err(|e| {
-        format!("Failed to rename: {}", e)
-    })?;
+    fs::rename(&path, &new_path).map_err(|e| LokusError::io("Failed to rename", &path, e))?;
 
-    Ok(new_path.to_string_lossy().to_string())
+    let new_path_str = new_path.to_string_lossy().to_string();
+    crate::links::notify_file_renamed(&path.to_string_lossy(), &new_path_str);
+    crate::metadata_cache::notify_file_renamed(&path.to_string_lossy(), &new_path_str);
+    crate::search::index::notify_file_renamed(&path.to_string_lossy(), &new_path_str);
+
+    Ok(new_path_str)
 }
 
 #[tauri::command]
-pub fn create_file_in_workspace(workspace_path: String, name: String) -> Result<String, String> {
+pub fn create_file_in_workspace(workspace_path: String, name: String) -> Result<String, LokusError> {
     let path = Path::new(&workspace_path).join(&name);
     let path_str = path.to_string_lossy().to_string();
     atomic_write_file(&path_str, "")?;
+    crate::metadata_cache::notify_file_saved(&path_str, "");
     Ok(path_str)
 }
 
 #[tauri::command]
-pub fn create_folder_in_workspace(workspace_path: String, name: String) -> Result<(), String> {
+pub fn create_folder_in_workspace(workspace_path: String, name: String) -> Result<(), LokusError> {
     let path = Path::new(&workspace_path).join(name);
-    fs::create_dir(path).map_err(|e| e.to_string())?;
+    crate::vaults::ensure_writable(&path)?;
+    fs::create_dir(&path).map_err(|e| LokusError::io("Failed to create folder", &path, e))?;
+    crate::metadata_cache::notify_folder_created(&path.to_string_lossy());
     Ok(())
 }
 
@@ -274,6 +513,9 @@ pub struct CopyFilesResult {
     success: Vec<String>,
     failed: Vec<String>,
     skipped: Vec<String>,
+    /// Imported images optimized under the workspace's `auto_optimize_images`
+    #[serde(default, skip_deserializing)]
+    optimized: Vec<crate::images::ImageOptimizeResult>,
 }
 
 // Helper: Recursive directory copy
@@ -301,12 +543,14 @@ pub async fn copy_external_files_to_workspace(
     file_paths: Vec<String>,
     workspace_path: String,
     target_folder: Option<String>,
-) -> Result<CopyFilesResult, String> {
+) -> Result<CopyFilesResult, LokusError> {
     let mut result = CopyFilesResult {
         success: vec![],
         failed: vec![],
         skipped: vec![],
+        optimized: vec![],
     };
+    let image_options = crate::images::import_options(Path::new(&workspace_path));
 
     let destination = if let Some(folder) = target_folder {
         PathBuf::from(&folder)
@@ -316,8 +560,9 @@ pub async fn copy_external_files_to_workspace(
 
     // Ensure destination exists
     if !destination.exists() {
-        return Err(format!("Destination folder does not exist: {:?}", destination));
+        return Err(LokusError::not_found(&destination));
     }
+    crate::vaults::ensure_writable(&destination)?;
 
     for file_path in file_paths {
         let source = Path::new(&file_path);
@@ -366,7 +611,22 @@ pub async fn copy_external_files_to_workspace(
         } else {
             tokio::fs::copy(source, &target_path).await.map(|_| ())
         } {
-            Ok(_) => result.success.push(target_path.to_string_lossy().to_string()),
+            Ok(_) => {
+                let mut imported = target_path.to_string_lossy().to_string();
+                if let Some(options) = image_options.clone().filter(|_| source.is_file() && crate::images::is_optimizable(&target_path)) {
+                    let image = target_path.clone();
+                    match tokio::task::spawn_blocking(move || crate::images::optimize(&image, &options)).await {
+                        Ok(Ok(optimized)) => {
+                            imported = optimized.path.clone();
+                            result.optimized.push(optimized);
+                        }
+                        // The copy itself succeeded; keep it as it is
+                        Ok(Err(e)) => eprintln!("[Images] Failed to optimize {}: {}", target_path.display(), e),
+                        Err(e) => eprintln!("[Images] Failed to optimize {}: {}", target_path.display(), e),
+                    }
+                }
+                result.success.push(imported);
+            }
             Err(_) => {
                 result.failed.push(file_path);
             }
@@ -377,48 +637,70 @@ pub async fn copy_external_files_to_workspace(
 }
 
 #[tauri::command]
-pub fn move_file(source_path: String, destination_dir: String) -> Result<(), String> {
+pub fn move_file(source_path: String, destination_dir: String) -> Result<(), LokusError> {
     let source = PathBuf::from(&source_path);
     let dest_dir = PathBuf::from(&destination_dir);
+    crate::vaults::ensure_writable(&source)?;
+    crate::vaults::ensure_writable(&dest_dir)?;
 
-    let file_name = source.file_name().ok_or("Invalid source path")?;
+    let file_name = source.file_name().ok_or_else(|| LokusError::invalid("Invalid source path"))?;
     let final_dest = dest_dir.join(file_name);
 
     // Check if the destination already exists
     if final_dest.exists() {
-        return Err("A file with that name already exists in the destination folder.".to_string());
+        return Err(LokusError::AlreadyExists {
+            path: final_dest.to_string_lossy().to_string(),
+            message: "A file with that name already exists in the destination folder.".to_string(),
+        });
     }
 
-    fs::rename(&source, &final_dest).map_err(|e| e.to_string())?;
+    fs::rename(&source, &final_dest).map_err(|e| LokusError::io("Failed to move", &source, e))?;
+    crate::links::notify_file_renamed(&source_path, &final_dest.to_string_lossy());
+    crate::metadata_cache::notify_file_renamed(&source_path, &final_dest.to_string_lossy());
+    crate::search::index::notify_file_renamed(&source_path, &final_dest.to_string_lossy());
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_file(path: String) -> Result<(), String> {
-    let path = PathBuf::from(path);
-    if path.is_dir() {
-        fs::remove_dir_all(path).map_err(|e| e.to_string())
-    } else {
-        fs::remove_file(path).map_err(|e| e.to_string())
+pub fn delete_file(path: String) -> Result<(), LokusError> {
+    let path_buf = PathBuf::from(&path);
+    crate::vaults::ensure_writable(&path_buf)?;
+    // Inside a workspace, deletes go to the trash so they can be restored
+    match find_workspace_root(&path_buf) {
+        Ok(root) if root != path_buf => {
+            crate::trash::move_to_trash(&root, &path_buf)?;
+        }
+        _ if path_buf.is_dir() => {
+            fs::remove_dir_all(&path_buf).map_err(|e| LokusError::io("Failed to delete", &path_buf, e))?;
+        }
+        _ => {
+            fs::remove_file(&path_buf).map_err(|e| LokusError::io("Failed to delete", &path_buf, e))?;
+        }
     }
+    crate::search::index::notify_file_removed(&path);
+    crate::links::notify_file_removed(&path);
+    crate::metadata_cache::notify_file_removed(&path);
+    #[cfg(desktop)]
+    crate::embeddings::notify_file_removed(&path);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn reveal_in_finder(path: String) -> Result<(), String> {
+pub fn reveal_in_finder(path: String) -> Result<(), LokusError> {
     // Use platform abstraction for better error handling and consistency
-    super::platform_files::platform_reveal_in_file_manager(path)
+    Ok(super::platform_files::platform_reveal_in_file_manager(path)?)
 }
 
 #[tauri::command]
-pub fn open_terminal(path: String) -> Result<(), String> {
+pub fn open_terminal(path: String) -> Result<(), LokusError> {
     // Use platform abstraction for better error handling and consistency
-    super::platform_files::platform_open_terminal(path)
+    Ok(super::platform_files::platform_open_terminal(path)?)
 }
 
 #[tauri::command]
-pub fn read_image_file(path: String) -> Result<String, String> {
+pub fn read_image_file(path: String) -> Result<String, LokusError> {
     // Read the file as binary
-    let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+    let bytes = fs::read(&path).map_err(|e| LokusError::io("Failed to read", &path, e))?;
 
     // Convert to base64
     use base64::{Engine as _, engine::general_purpose};
@@ -459,12 +741,12 @@ pub struct DirectoryEntry {
 }
 
 #[tauri::command]
-pub fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String> {
-    let entries = fs::read_dir(&path).map_err(|e| e.to_string())?;
+pub fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, LokusError> {
+    let entries = fs::read_dir(&path).map_err(|e| LokusError::io("Failed to read", &path, e))?;
     let mut result = vec![];
 
     for entry in entries {
-        let entry = entry.map_err(|e| e.to_string())?;
+        let entry = entry.map_err(|e| LokusError::io("Failed to read", &path, e))?;
         let path = entry.path();
         let name = path.file_name()
             .unwrap_or_default()
@@ -479,23 +761,26 @@ pub fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String> {
 }
 
 #[tauri::command]
-pub fn write_file(path: String, content: String) -> Result<(), String> {
+pub fn write_file(path: String, content: String) -> Result<(), LokusError> {
     // Alias for write_file_content for consistency with importers
-    atomic_write_file(&path, &content)
+    let _lock = crate::file_locking::FileLockGuard::for_write(&path)?;
+    write_note(&path, &content)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn create_directory(path: String, recursive: bool) -> Result<(), String> {
+pub fn create_directory(path: String, recursive: bool) -> Result<(), LokusError> {
     let path = Path::new(&path);
+    crate::vaults::ensure_writable(path)?;
     if recursive {
-        fs::create_dir_all(path).map_err(|e| e.to_string())
+        fs::create_dir_all(path).map_err(|e| LokusError::io("Failed to create folder", path, e))
     } else {
-        fs::create_dir(path).map_err(|e| e.to_string())
+        fs::create_dir(path).map_err(|e| LokusError::io("Failed to create folder", path, e))
     }
 }
 
 #[tauri::command]
-pub async fn read_all_files(paths: Vec<String>) -> Result<std::collections::HashMap<String, String>, String> {
+pub async fn read_all_files(paths: Vec<String>) -> Result<std::collections::HashMap<String, String>, LokusError> {
     use futures::future::join_all;
     use tokio::fs;
 
@@ -525,40 +810,34 @@ pub async fn read_all_files(paths: Vec<String>) -> Result<std::collections::Hash
 }
 
 #[tauri::command]
-pub async fn write_binary_file(path: String, content: Vec<u8>) -> Result<(), String> {
-    use std::io::Write;
-
+pub async fn write_binary_file(path: String, content: Vec<u8>) -> Result<(), LokusError> {
     let file_path = std::path::Path::new(&path);
+    crate::vaults::ensure_writable(file_path)?;
 
     // Ensure parent directory exists
     if let Some(parent) = file_path.parent() {
         std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+            .map_err(|e| LokusError::io("Failed to create directory", parent, e))?;
     }
 
-    // Atomic write: temp file + rename
-    let temp_path = file_path.with_extension("tmp_sync");
-    let mut file = std::fs::File::create(&temp_path)
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
-    file.write_all(&content)
-        .map_err(|e| format!("Failed to write content: {}", e))?;
-    file.sync_all()
-        .map_err(|e| format!("Failed to sync file: {}", e))?;
-
-    std::fs::rename(&temp_path, file_path)
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
-
+    super::file_stream::check_size(file_path, content.len() as u64)?;
+    let _lock = crate::file_locking::FileLockGuard::for_write(&path)?;
+    if !is_note_write(file_path) {
+        return Ok(durable_write(file_path, &content)?);
+    }
+    let text = String::from_utf8(content).map_err(|_| LokusError::invalid(format!("{} can only be saved as UTF-8 text", path)))?;
+    write_note(&path, &text)?;
     Ok(())
 }
 
 #[tauri::command]
-pub async fn find_workspace_images(workspace_path: String) -> Result<Vec<String>, String> {
+pub async fn find_workspace_images(workspace_path: String) -> Result<Vec<String>, LokusError> {
     const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "svg", "bmp", "ico"];
 
     let workspace = Path::new(&workspace_path);
 
     if !workspace.exists() {
-        return Err("Workspace path does not exist".to_string());
+        return Err(LokusError::not_found(workspace));
     }
 
     let mut image_files = Vec::new();
@@ -596,3 +875,31 @@ pub async fn find_workspace_images(workspace_path: String) -> Result<Vec<String>
 
     Ok(image_files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_pages_and_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("zeta")).unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        for name in ["b.md", "A.md", "c.md"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+
+        let first = directory_page(dir.path(), 0, 2, DirectorySort::Name, false).unwrap();
+        let names: Vec<&str> = first.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["zeta", "A.md"]);
+        assert_eq!((first.total, first.next_cursor), (4, Some(2)));
+
+        let last = directory_page(dir.path(), 2, 2, DirectorySort::Name, true).unwrap();
+        let names: Vec<&str> = last.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["b.md", "A.md"]);
+        assert_eq!(last.next_cursor, None);
+
+        let summary = folder_summary(dir.path()).unwrap();
+        assert_eq!(summary, FolderSummary { files: 3, folders: 1, has_subdirs: true });
+    }
+}
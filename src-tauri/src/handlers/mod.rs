@@ -1,3 +1,8 @@
+pub mod autosave;
 pub mod files;
+pub mod file_stream;
 pub mod platform_files;
+pub mod version_diff;
 pub mod version_history;
+pub mod version_policy;
+pub mod write_journal;
@@ -13,7 +13,7 @@ use std::sync::OnceLock;
 static PLATFORM_PROVIDER: OnceLock<Box<dyn PlatformProvider>> = OnceLock::new();
 
 /// Initialize the platform provider
-fn get_or_init_platform_provider() -> &'static Box<dyn PlatformProvider> {
+pub(crate) fn get_or_init_platform_provider() -> &'static Box<dyn PlatformProvider> {
     PLATFORM_PROVIDER.get_or_init(|| {
         let mut provider = get_platform_provider();
         if let Err(_err) = provider.initialize() {
@@ -81,6 +81,7 @@ impl PlatformFileOperations {
             "custom_terminal" => PlatformFeature::CustomTerminal,
             "quick_look" => PlatformFeature::QuickLook,
             "context_menus" => PlatformFeature::ContextMenus,
+            "os_search_index" => PlatformFeature::OsSearchIndex,
             _ => return false,
         };
         
@@ -174,6 +175,7 @@ pub fn get_platform_capabilities() -> Vec<String> {
         ("custom_terminal", PlatformFeature::CustomTerminal),
         ("quick_look", PlatformFeature::QuickLook),
         ("context_menus", PlatformFeature::ContextMenus),
+        ("os_search_index", PlatformFeature::OsSearchIndex),
     ];
     
     features
@@ -0,0 +1,564 @@
+//! Structured diffs between note versions for rich inline rendering.
+//!
+//! Text is compared paragraph by paragraph (blocks separated by blank lines);
+//! paragraphs that changed in place are then compared word by word. Ranges
+//! are in UTF-16 code units so they index JavaScript strings directly.
+
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+
+use super::version_history::{get_file_versions, get_version_content};
+
+/// Above this many token pairs the changed middle is treated as one block
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Equal,
+    Insert,
+    Delete,
+    Modify,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TextRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordChange {
+    /// `equal`, `insert` or `delete`
+    pub kind: ChangeKind,
+    pub text: String,
+    pub old_range: Option<TextRange>,
+    pub new_range: Option<TextRange>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParagraphChange {
+    pub kind: ChangeKind,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+    pub old_range: Option<TextRange>,
+    pub new_range: Option<TextRange>,
+    /// Word-level changes, for `modify` paragraphs only
+    pub words: Vec<WordChange>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffStats {
+    pub paragraphs_inserted: usize,
+    pub paragraphs_deleted: usize,
+    pub paragraphs_modified: usize,
+    pub words_inserted: usize,
+    pub words_deleted: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredDiff {
+    pub paragraphs: Vec<ParagraphChange>,
+    pub stats: DiffStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ThreeWayKind {
+    Unchanged,
+    /// Edited in the working copy only
+    Working,
+    /// Changed by the version being restored only
+    Version,
+    /// Both made the same change
+    Both,
+    Conflict,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreeWayBlock {
+    pub kind: ThreeWayKind,
+    pub base: String,
+    pub working: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreeWayDiff {
+    /// Timestamp of the saved version used as the common base
+    pub base_timestamp: String,
+    pub blocks: Vec<ThreeWayBlock>,
+    pub conflicts: usize,
+    /// The working copy with the restored version's changes applied, when
+    /// nothing conflicts
+    pub merged: Option<String>,
+}
+
+/// A slice of the text with its UTF-16 range
+#[derive(Debug, Clone, Copy)]
+struct Segment<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Hunk {
+    Equal(Range<usize>, Range<usize>),
+    /// Replace `a[range]` with `b[range]`; either side may be empty
+    Change(Range<usize>, Range<usize>),
+}
+
+fn push_hunk(hunks: &mut Vec<Hunk>, hunk: Hunk) {
+    match (hunks.last_mut(), &hunk) {
+        (Some(Hunk::Equal(a, b)), Hunk::Equal(na, nb)) | (Some(Hunk::Change(a, b)), Hunk::Change(na, nb))
+            if a.end == na.start && b.end == nb.start =>
+        {
+            a.end = na.end;
+            b.end = nb.end;
+        }
+        _ => hunks.push(hunk),
+    }
+}
+
+/// Equal and changed runs turning `a` into `b`
+fn diff_hunks<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Hunk> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (n, m) = (a.len() - prefix - suffix, b.len() - prefix - suffix);
+
+    let mut hunks = Vec::new();
+    if prefix > 0 {
+        hunks.push(Hunk::Equal(0..prefix, 0..prefix));
+    }
+    if n == 0 || m == 0 || n * m > MAX_LCS_CELLS {
+        if n > 0 || m > 0 {
+            push_hunk(&mut hunks, Hunk::Change(prefix..prefix + n, prefix..prefix + m));
+        }
+    } else {
+        let (x, y) = (&a[prefix..prefix + n], &b[prefix..prefix + m]);
+        // lcs[i][j] covers x[i..] and y[j..]
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if x[i] == y[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            let (ai, bj) = (prefix + i, prefix + j);
+            if i < n && j < m && x[i] == y[j] {
+                push_hunk(&mut hunks, Hunk::Equal(ai..ai + 1, bj..bj + 1));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                push_hunk(&mut hunks, Hunk::Change(ai..ai + 1, bj..bj));
+                i += 1;
+            } else {
+                push_hunk(&mut hunks, Hunk::Change(ai..ai, bj..bj + 1));
+                j += 1;
+            }
+        }
+    }
+    if suffix > 0 {
+        push_hunk(&mut hunks, Hunk::Equal(a.len() - suffix..a.len(), b.len() - suffix..b.len()));
+    }
+    hunks
+}
+
+/// Paragraphs including the blank lines that follow them, so they concatenate
+/// back to the original text
+fn paragraphs(text: &str) -> Vec<Segment<'_>> {
+    let mut out = Vec::new();
+    let (mut start, mut start16, mut pos16) = (0, 0, 0);
+    let mut in_gap = false;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let blank = line.trim().is_empty();
+        if !blank && in_gap {
+            out.push(Segment { text: &text[start..offset], start: start16, end: pos16 });
+            start = offset;
+            start16 = pos16;
+        }
+        in_gap = blank && offset > start;
+        offset += line.len();
+        pos16 += line.encode_utf16().count();
+    }
+    if offset > start {
+        out.push(Segment { text: &text[start..], start: start16, end: pos16 });
+    }
+    out
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum CharClass {
+    Word,
+    Space,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else if c.is_whitespace() {
+        CharClass::Space
+    } else {
+        CharClass::Other
+    }
+}
+
+/// Words, whitespace runs and single punctuation characters
+fn tokenize(segment: Segment<'_>) -> Vec<Segment<'_>> {
+    let text = segment.text;
+    let mut tokens = Vec::new();
+    let mut pos16 = segment.start;
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let class = char_class(c);
+        let mut end = start + c.len_utf8();
+        let mut len16 = c.len_utf16();
+        if class != CharClass::Other {
+            while let Some(&(i, next)) = chars.peek() {
+                if char_class(next) != class {
+                    break;
+                }
+                end = i + next.len_utf8();
+                len16 += next.len_utf16();
+                chars.next();
+            }
+        }
+        tokens.push(Segment { text: &text[start..end], start: pos16, end: pos16 + len16 });
+        pos16 += len16;
+    }
+    tokens
+}
+
+fn span(segments: &[Segment]) -> Option<TextRange> {
+    Some(TextRange { start: segments.first()?.start, end: segments.last()?.end })
+}
+
+fn concat(segments: &[Segment]) -> String {
+    segments.iter().map(|s| s.text).collect()
+}
+
+fn counts_as_word(segments: &[Segment]) -> usize {
+    segments.iter().filter(|s| !s.text.trim().is_empty()).count()
+}
+
+/// The paragraph without the blank lines after it
+fn content(segment: Segment<'_>) -> Segment<'_> {
+    let text = segment.text.trim_end();
+    Segment { text, start: segment.start, end: segment.start + text.encode_utf16().count() }
+}
+
+fn word_changes(old: Segment, new: Segment, stats: &mut DiffStats) -> Vec<WordChange> {
+    let old_tokens = tokenize(content(old));
+    let new_tokens = tokenize(content(new));
+    let old_keys: Vec<&str> = old_tokens.iter().map(|t| t.text).collect();
+    let new_keys: Vec<&str> = new_tokens.iter().map(|t| t.text).collect();
+
+    let mut words = Vec::new();
+    for hunk in diff_hunks(&old_keys, &new_keys) {
+        match hunk {
+            Hunk::Equal(a, b) => words.push(WordChange {
+                kind: ChangeKind::Equal,
+                text: concat(&old_tokens[a.clone()]),
+                old_range: span(&old_tokens[a]),
+                new_range: span(&new_tokens[b]),
+            }),
+            Hunk::Change(a, b) => {
+                if !a.is_empty() {
+                    stats.words_deleted += counts_as_word(&old_tokens[a.clone()]);
+                    words.push(WordChange {
+                        kind: ChangeKind::Delete,
+                        text: concat(&old_tokens[a.clone()]),
+                        old_range: span(&old_tokens[a]),
+                        new_range: None,
+                    });
+                }
+                if !b.is_empty() {
+                    stats.words_inserted += counts_as_word(&new_tokens[b.clone()]);
+                    words.push(WordChange {
+                        kind: ChangeKind::Insert,
+                        text: concat(&new_tokens[b.clone()]),
+                        old_range: None,
+                        new_range: span(&new_tokens[b]),
+                    });
+                }
+            }
+        }
+    }
+    words
+}
+
+fn paragraph_keys<'a>(paragraphs: &[Segment<'a>]) -> Vec<&'a str> {
+    paragraphs.iter().map(|p| p.text.trim_end()).collect()
+}
+
+pub fn structured_diff(old: &str, new: &str) -> StructuredDiff {
+    let old_paragraphs = paragraphs(old);
+    let new_paragraphs = paragraphs(new);
+    let mut stats = DiffStats::default();
+    let mut out = Vec::new();
+
+    let whole = |kind, old: Option<Segment>, new: Option<Segment>| ParagraphChange {
+        kind,
+        old_text: old.map(|s| s.text.to_string()),
+        new_text: new.map(|s| s.text.to_string()),
+        old_range: old.map(|s| TextRange { start: s.start, end: s.end }),
+        new_range: new.map(|s| TextRange { start: s.start, end: s.end }),
+        words: Vec::new(),
+    };
+
+    for hunk in diff_hunks(&paragraph_keys(&old_paragraphs), &paragraph_keys(&new_paragraphs)) {
+        match hunk {
+            Hunk::Equal(a, b) => {
+                for (old, new) in old_paragraphs[a].iter().zip(&new_paragraphs[b]) {
+                    out.push(whole(ChangeKind::Equal, Some(*old), Some(*new)));
+                }
+            }
+            Hunk::Change(a, b) => {
+                // Pair paragraphs edited in place; the remainder were added or removed
+                let paired = a.len().min(b.len());
+                for k in 0..paired {
+                    let (old, new) = (old_paragraphs[a.start + k], new_paragraphs[b.start + k]);
+                    let mut change = whole(ChangeKind::Modify, Some(old), Some(new));
+                    change.words = word_changes(old, new, &mut stats);
+                    stats.paragraphs_modified += 1;
+                    out.push(change);
+                }
+                for old in &old_paragraphs[a.start + paired..a.end] {
+                    stats.paragraphs_deleted += 1;
+                    out.push(whole(ChangeKind::Delete, Some(*old), None));
+                }
+                for new in &new_paragraphs[b.start + paired..b.end] {
+                    stats.paragraphs_inserted += 1;
+                    out.push(whole(ChangeKind::Insert, None, Some(*new)));
+                }
+            }
+        }
+    }
+    StructuredDiff { paragraphs: out, stats }
+}
+
+/// Changed regions only, as (base range, side range)
+fn changes(hunks: Vec<Hunk>) -> Vec<(Range<usize>, Range<usize>)> {
+    hunks
+        .into_iter()
+        .filter_map(|hunk| match hunk {
+            Hunk::Change(a, b) => Some((a, b)),
+            Hunk::Equal(..) => None,
+        })
+        .collect()
+}
+
+/// One side's text for base paragraphs `start..end` with its changes applied
+fn apply_side(
+    base: &[Segment],
+    side: &[Segment],
+    start: usize,
+    end: usize,
+    changes: &[(Range<usize>, Range<usize>)],
+) -> String {
+    let mut out = String::new();
+    let mut pos = start;
+    for (a, b) in changes {
+        out.push_str(&concat(&base[pos..a.start]));
+        out.push_str(&concat(&side[b.clone()]));
+        pos = a.end;
+    }
+    out.push_str(&concat(&base[pos..end]));
+    out
+}
+
+/// Compare the working copy and a version against their common base, by paragraph
+pub fn three_way_diff(base: &str, working: &str, version: &str) -> (Vec<ThreeWayBlock>, usize, Option<String>) {
+    let base_paragraphs = paragraphs(base);
+    let working_paragraphs = paragraphs(working);
+    let version_paragraphs = paragraphs(version);
+    let base_keys = paragraph_keys(&base_paragraphs);
+    let working_changes = changes(diff_hunks(&base_keys, &paragraph_keys(&working_paragraphs)));
+    let version_changes = changes(diff_hunks(&base_keys, &paragraph_keys(&version_paragraphs)));
+
+    let mut blocks = Vec::new();
+    let (mut i, mut j, mut pos) = (0, 0, 0);
+    let unchanged = |range: Range<usize>| {
+        let text = concat(&base_paragraphs[range]);
+        ThreeWayBlock { kind: ThreeWayKind::Unchanged, base: text.clone(), working: text.clone(), version: text }
+    };
+
+    loop {
+        let start = match (working_changes.get(i), version_changes.get(j)) {
+            (Some(w), Some(v)) => w.0.start.min(v.0.start),
+            (Some(w), None) => w.0.start,
+            (None, Some(v)) => v.0.start,
+            (None, None) => break,
+        };
+        if pos < start {
+            blocks.push(unchanged(pos..start));
+        }
+
+        // Grow the region until no change from either side touches it
+        let (first_w, first_v) = (i, j);
+        let mut end = start;
+        let overlaps = |a: &Range<usize>, end: usize| a.start < end || a.start == start;
+        loop {
+            let before = (i, j);
+            while working_changes.get(i).map_or(false, |c| overlaps(&c.0, end)) {
+                end = end.max(working_changes[i].0.end);
+                i += 1;
+            }
+            while version_changes.get(j).map_or(false, |c| overlaps(&c.0, end)) {
+                end = end.max(version_changes[j].0.end);
+                j += 1;
+            }
+            if (i, j) == before {
+                break;
+            }
+        }
+
+        let working_text = apply_side(&base_paragraphs, &working_paragraphs, start, end, &working_changes[first_w..i]);
+        let version_text = apply_side(&base_paragraphs, &version_paragraphs, start, end, &version_changes[first_v..j]);
+        let kind = if first_w == i {
+            ThreeWayKind::Version
+        } else if first_v == j {
+            ThreeWayKind::Working
+        } else if working_text == version_text {
+            ThreeWayKind::Both
+        } else {
+            ThreeWayKind::Conflict
+        };
+        blocks.push(ThreeWayBlock {
+            kind,
+            base: concat(&base_paragraphs[start..end]),
+            working: working_text,
+            version: version_text,
+        });
+        pos = end;
+    }
+    if pos < base_paragraphs.len() {
+        blocks.push(unchanged(pos..base_paragraphs.len()));
+    }
+
+    let conflicts = blocks.iter().filter(|b| b.kind == ThreeWayKind::Conflict).count();
+    let merged = (conflicts == 0).then(|| {
+        blocks
+            .iter()
+            .map(|b| if b.kind == ThreeWayKind::Version { b.version.as_str() } else { b.working.as_str() })
+            .collect()
+    });
+    (blocks, conflicts, merged)
+}
+
+fn read_working_copy(workspace_path: &str, file_path: &str) -> Result<String, String> {
+    let path = Path::new(workspace_path).join(file_path);
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    crate::encryption::decrypt_for_read(&path.to_string_lossy(), content)
+}
+
+// --- Tauri Commands ---
+
+/// Paragraph and word-level changes from one version to another, or to the
+/// current file when `timestamp2` is omitted
+#[tauri::command]
+pub fn get_diff_structured(
+    workspace_path: String,
+    file_path: String,
+    timestamp1: String,
+    timestamp2: Option<String>,
+) -> Result<StructuredDiff, String> {
+    let old = get_version_content(workspace_path.clone(), file_path.clone(), timestamp1)?;
+    let new = match timestamp2 {
+        Some(timestamp) => get_version_content(workspace_path, file_path, timestamp)?,
+        None => read_working_copy(&workspace_path, &file_path)?,
+    };
+    Ok(structured_diff(&old, &new))
+}
+
+/// Preview restoring `timestamp` over a working copy that has changed since
+/// `base_timestamp` (the latest saved version by default). `working_content`
+/// is the editor's buffer; the file on disk is used when it's omitted.
+#[tauri::command]
+pub fn get_diff_three_way(
+    workspace_path: String,
+    file_path: String,
+    timestamp: String,
+    base_timestamp: Option<String>,
+    working_content: Option<String>,
+) -> Result<ThreeWayDiff, String> {
+    let base_timestamp = match base_timestamp {
+        Some(base) => base,
+        None => get_file_versions(workspace_path.clone(), file_path.clone())?
+            .into_iter()
+            .map(|v| v.timestamp)
+            .max()
+            .ok_or("No saved version to compare against")?,
+    };
+    let base = get_version_content(workspace_path.clone(), file_path.clone(), base_timestamp.clone())?;
+    let version = get_version_content(workspace_path.clone(), file_path.clone(), timestamp)?;
+    let working = match working_content {
+        Some(content) => content,
+        None => read_working_copy(&workspace_path, &file_path)?,
+    };
+
+    let (blocks, conflicts, merged) = three_way_diff(&base, &working, &version);
+    Ok(ThreeWayDiff { base_timestamp, blocks, conflicts, merged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_diff_words_and_ranges() {
+        let old = "# Title\n\nThe quick brown fox.\n\nGone paragraph\n";
+        let new = "# Title\n\nThe quick red fox jumps.\n";
+        let diff = structured_diff(old, new);
+
+        let kinds: Vec<ChangeKind> = diff.paragraphs.iter().map(|p| p.kind).collect();
+        assert_eq!(kinds, vec![ChangeKind::Equal, ChangeKind::Modify, ChangeKind::Delete]);
+        assert_eq!(diff.stats.paragraphs_modified, 1);
+        assert_eq!(diff.stats.paragraphs_deleted, 1);
+
+        let modified = &diff.paragraphs[1];
+        let deleted: Vec<&str> = modified.words.iter().filter(|w| w.kind == ChangeKind::Delete).map(|w| w.text.as_str()).collect();
+        let inserted: Vec<&str> = modified.words.iter().filter(|w| w.kind == ChangeKind::Insert).map(|w| w.text.as_str()).collect();
+        assert_eq!(deleted, vec!["brown"]);
+        assert_eq!(inserted, vec!["red", " jumps"]);
+
+        // Ranges index the full text in UTF-16 units
+        let red = modified.words.iter().find(|w| w.text == "red").unwrap();
+        let range = red.new_range.unwrap();
+        let units: Vec<u16> = new.encode_utf16().collect();
+        assert_eq!(String::from_utf16(&units[range.start..range.end]).unwrap(), "red");
+    }
+
+    #[test]
+    fn test_three_way_diff() {
+        let base = "one\n\ntwo\n\nthree\n";
+        let working = "one edited\n\ntwo\n\nthree\n";
+        let version = "one\n\ntwo restored\n\nthree\n";
+        let (blocks, conflicts, merged) = three_way_diff(base, working, version);
+        assert_eq!(conflicts, 0);
+        let kinds: Vec<ThreeWayKind> = blocks.iter().map(|b| b.kind).collect();
+        assert_eq!(kinds, vec![ThreeWayKind::Working, ThreeWayKind::Version, ThreeWayKind::Unchanged]);
+        assert_eq!(merged.as_deref(), Some("one edited\n\ntwo restored\n\nthree\n"));
+
+        let (blocks, conflicts, merged) = three_way_diff(base, "one\n\ntwo mine\n\nthree\n", version);
+        assert_eq!(conflicts, 1);
+        assert!(merged.is_none());
+        assert_eq!(blocks[1].kind, ThreeWayKind::Conflict);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::io::{Read, Write};
@@ -9,6 +10,10 @@ use flate2::read::GzDecoder;
 use rand::Rng;
 use crate::file_locking::FileLock;
 
+/// Deltas per keyframe before a full copy is stored again
+const KEYFRAME_INTERVAL: usize = 20;
+const DELTA_LEVEL: i32 = 9;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileVersion {
     pub timestamp: String,
@@ -16,6 +21,12 @@ pub struct FileVersion {
     pub lines: usize,
     pub action: String,
     pub preview: String,
+    /// Storage file in the backups folder; missing for versions saved before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Keyframe this version is stored as a zstd delta against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -72,7 +83,7 @@ fn get_metadata_path(backups_dir: &Path) -> PathBuf {
     backups_dir.join("metadata.json")
 }
 
-fn load_metadata(backups_dir: &Path) -> VersionMetadata {
+pub(crate) fn load_metadata(backups_dir: &Path) -> VersionMetadata {
     let metadata_path = get_metadata_path(backups_dir);
 
     if metadata_path.exists() {
@@ -131,6 +142,211 @@ fn decompress_content(compressed: &[u8]) -> Result<String, String> {
     Ok(decompressed)
 }
 
+// Compress content using the previous keyframe as a zstd dictionary
+fn compress_delta(content: &str, base: &str) -> Result<Vec<u8>, String> {
+    zstd::bulk::Compressor::with_dictionary(DELTA_LEVEL, base.as_bytes())
+        .and_then(|mut compressor| compressor.compress(content.as_bytes()))
+        .map_err(|e| format!("Failed to compress: {}", e))
+}
+
+fn decompress_delta(compressed: &[u8], base: &str, size: usize) -> Result<String, String> {
+    let bytes = zstd::bulk::Decompressor::with_dictionary(base.as_bytes())
+        .and_then(|mut decompressor| decompressor.decompress(compressed, size))
+        .map_err(|e| format!("Failed to decompress: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("Failed to decompress: {}", e))
+}
+
+fn version_path(backups_dir: &Path, version: &FileVersion) -> Option<PathBuf> {
+    if let Some(file) = &version.file {
+        let path = backups_dir.join(file);
+        return path.exists().then_some(path);
+    }
+    // Older versions only have their timestamp; the file name adds a random suffix
+    let prefix = DateTime::parse_from_rfc3339(&version.timestamp)
+        .ok()?
+        .format("%Y-%m-%dT%H-%M-%S%.3f")
+        .to_string();
+    fs::read_dir(backups_dir).ok()?.flatten().map(|e| e.path()).find(|path| {
+        path.file_name().map_or(false, |name| {
+            let name = name.to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".md.gz")
+        })
+    })
+}
+
+fn read_keyframe(backups_dir: &Path, file: &str) -> Result<String, String> {
+    let compressed = fs::read(backups_dir.join(file))
+        .map_err(|e| format!("Failed to read version: {}", e))?;
+    decompress_content(&compressed)
+}
+
+fn read_version(backups_dir: &Path, version: &FileVersion) -> Result<String, String> {
+    let path = version_path(backups_dir, version).ok_or("Version file not found")?;
+    let compressed = fs::read(&path)
+        .map_err(|e| format!("Failed to read version: {}", e))?;
+    match &version.base {
+        Some(base) => decompress_delta(&compressed, &read_keyframe(backups_dir, base)?, version.size as usize),
+        None => decompress_content(&compressed),
+    }
+}
+
+fn latest_keyframe(versions: &[FileVersion]) -> Option<&FileVersion> {
+    versions
+        .iter()
+        .filter(|v| v.base.is_none() && v.file.is_some())
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+}
+
+// Store as a delta against the newest keyframe, or as a new keyframe when
+// there is none yet or its chain is full. Returns (file, base).
+fn write_version_blob(
+    backups_dir: &Path,
+    versions: &[FileVersion],
+    stem: &str,
+    content: &str,
+    delta: bool,
+) -> Result<(String, Option<String>), String> {
+    if let Some(key) = latest_keyframe(versions).filter(|_| delta).and_then(|v| v.file.clone()) {
+        let chain = versions.iter().filter(|v| v.base.as_deref() == Some(key.as_str())).count();
+        if chain < KEYFRAME_INTERVAL {
+            if let Ok(base_content) = read_keyframe(backups_dir, &key) {
+                let file = format!("{}.md.zst", stem);
+                fs::write(backups_dir.join(&file), compress_delta(content, &base_content)?)
+                    .map_err(|e| format!("Failed to save version: {}", e))?;
+                return Ok((file, Some(key)));
+            }
+        }
+    }
+
+    let file = format!("{}.md.gz", stem);
+    fs::write(backups_dir.join(&file), compress_content(content)?)
+        .map_err(|e| format!("Failed to save version: {}", e))?;
+    Ok((file, None))
+}
+
+// Rewrite the deltas based on a keyframe that is about to go: the oldest
+// becomes the new keyframe and the rest are re-encoded against it
+fn rebase_dependents(backups_dir: &Path, versions: &mut [FileVersion], keyframe: &str) -> Result<(), String> {
+    let mut dependents: Vec<usize> = (0..versions.len())
+        .filter(|&i| versions[i].base.as_deref() == Some(keyframe))
+        .collect();
+    if dependents.is_empty() {
+        return Ok(());
+    }
+    dependents.sort_by(|&a, &b| versions[a].timestamp.cmp(&versions[b].timestamp));
+    let contents = dependents
+        .iter()
+        .map(|&i| read_version(backups_dir, &versions[i]))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let first = dependents[0];
+    let old_file = versions[first].file.clone().unwrap_or_default();
+    let new_key = format!("{}.md.gz", old_file.trim_end_matches(".md.zst"));
+    fs::write(backups_dir.join(&new_key), compress_content(&contents[0])?)
+        .map_err(|e| format!("Failed to save version: {}", e))?;
+    let _ = fs::remove_file(backups_dir.join(&old_file));
+    versions[first].file = Some(new_key.clone());
+    versions[first].base = None;
+
+    for (&i, content) in dependents.iter().zip(&contents).skip(1) {
+        let file = versions[i].file.clone().unwrap_or_default();
+        fs::write(backups_dir.join(&file), compress_delta(content, &contents[0])?)
+            .map_err(|e| format!("Failed to save version: {}", e))?;
+        versions[i].base = Some(new_key.clone());
+    }
+    Ok(())
+}
+
+fn remove_versions(
+    backups_dir: &Path,
+    metadata: &mut VersionMetadata,
+    doomed: Vec<FileVersion>,
+) -> Result<usize, String> {
+    let doomed_timestamps: HashSet<&str> = doomed.iter().map(|v| v.timestamp.as_str()).collect();
+    metadata.versions.retain(|v| !doomed_timestamps.contains(v.timestamp.as_str()));
+
+    let mut removed = 0;
+    for version in &doomed {
+        if let (None, Some(file)) = (&version.base, &version.file) {
+            rebase_dependents(backups_dir, &mut metadata.versions, file)?;
+        }
+        if let Some(path) = version_path(backups_dir, version) {
+            let _ = fs::remove_file(path);
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn with_metadata_lock<T>(
+    backups_dir: &Path,
+    op_id: &str,
+    f: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let metadata_path = get_metadata_path(backups_dir).to_string_lossy().to_string();
+    FileLock::acquire_write_lock(&metadata_path, op_id)
+        .map_err(|e| format!("Failed to acquire metadata lock: {}", e))?;
+    let result = f();
+    let _ = FileLock::release_write_lock(&metadata_path, op_id);
+    result
+}
+
+/// Apply retention to one file's history and move versions saved before
+/// delta storage into keyframe chains. Returns how many versions were removed.
+pub(crate) fn compact(workspace: &Path, backups_dir: &Path) -> Result<usize, String> {
+    let delta = super::version_policy::load_policy(workspace).delta_compression;
+    let op_id = format!("compact_versions_{}", Utc::now().timestamp_millis());
+    with_metadata_lock(backups_dir, &op_id, || {
+        let mut metadata = load_metadata(backups_dir);
+        let removed = cleanup_old_versions_internal(workspace, &mut metadata, backups_dir)?;
+
+        metadata.versions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        let mut keyframe: Option<(String, String)> = None;
+        let mut chain = 0;
+        for version in metadata.versions.iter_mut().filter(|v| v.file.is_none()) {
+            let Some(path) = version_path(backups_dir, version) else {
+                continue;
+            };
+            let file = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let content = read_version(backups_dir, version)?;
+            match &keyframe {
+                Some((key, base)) if delta && chain < KEYFRAME_INTERVAL => {
+                    let delta_file = format!("{}.md.zst", file.trim_end_matches(".md.gz"));
+                    fs::write(backups_dir.join(&delta_file), compress_delta(&content, base)?)
+                        .map_err(|e| format!("Failed to save version: {}", e))?;
+                    let _ = fs::remove_file(&path);
+                    version.file = Some(delta_file);
+                    version.base = Some(key.clone());
+                    chain += 1;
+                }
+                _ => {
+                    version.file = Some(file.clone());
+                    keyframe = Some((file, content));
+                    chain = 0;
+                }
+            }
+        }
+        metadata.versions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        save_metadata(backups_dir, &metadata)?;
+        Ok(removed)
+    })
+}
+
+/// Delete the stored versions of `file_path`, so an encrypted note leaves no
+/// plaintext history behind. History is kept per file name, so same-named
+/// notes elsewhere in the workspace lose theirs too. Returns how many
+/// versions were deleted.
+pub(crate) fn purge_history(workspace: &Path, file_path: &str) -> Result<usize, String> {
+    let file_name = Path::new(file_path).file_name().ok_or("Invalid file path")?;
+    let backups_dir = workspace.join(".lokus").join("backups").join(file_name);
+    if !backups_dir.is_dir() {
+        return Ok(0);
+    }
+    let versions = load_metadata(&backups_dir).versions.len();
+    fs::remove_dir_all(&backups_dir).map_err(|e| format!("Failed to delete version history: {}", e))?;
+    Ok(versions)
+}
+
 // --- Tauri Commands ---
 
 #[tauri::command]
@@ -142,47 +358,40 @@ pub fn save_version(
 ) -> Result<FileVersion, String> {
 
     let workspace = Path::new(&workspace_path);
+    crate::vaults::ensure_writable(&workspace.join(&file_path))?;
     let backups_dir = get_backups_dir(workspace, &file_path)?;
 
     // Generate timestamp-based filename with random suffix to prevent collisions
     let timestamp = Utc::now();
     let timestamp_str = timestamp.format("%Y-%m-%dT%H-%M-%S%.3f").to_string();
     let random_suffix: u16 = rand::thread_rng().gen();
-    let version_path = backups_dir.join(format!("{}-{:04x}.md.gz", timestamp_str, random_suffix));
-
-    // Compress and save version file
-    let compressed = compress_content(&content)?;
-    fs::write(&version_path, &compressed)
-        .map_err(|e| format!("Failed to save version: {}", e))?;
-
+    let stem = format!("{}-{:04x}", timestamp_str, random_suffix);
+    let delta = super::version_policy::load_policy(workspace).delta_compression;
 
     // Create version info
-    let version = FileVersion {
+    let mut version = FileVersion {
         timestamp: timestamp.to_rfc3339(),
         size: content.len() as u64,
         lines: content.lines().count(),
         action: action.unwrap_or_else(|| "auto_save".to_string()),
         preview: create_preview(&content, 200),
+        file: None,
+        base: None,
     };
 
-    // Load metadata and add version (protected by file lock)
-    let metadata_path = backups_dir.join("metadata.json").to_string_lossy().to_string();
+    // Save the version file and add it to metadata (protected by file lock),
+    // since picking the delta base depends on the versions already stored
     let op_id = format!("save_version_{}", timestamp_str);
-
-    FileLock::acquire_write_lock(&metadata_path, &op_id)
-        .map_err(|e| format!("Failed to acquire metadata lock: {}", e))?;
-
-    let result = (|| -> Result<(), String> {
+    with_metadata_lock(&backups_dir, &op_id, || {
         let mut metadata = load_metadata(&backups_dir);
         metadata.file = file_path.clone();
+        let (file, base) = write_version_blob(&backups_dir, &metadata.versions, &stem, &content, delta)?;
+        version.file = Some(file);
+        version.base = base;
         metadata.versions.push(version.clone());
-        cleanup_old_versions_internal(&mut metadata, &backups_dir)?;
-        save_metadata(&backups_dir, &metadata)?;
-        Ok(())
-    })();
-
-    let _ = FileLock::release_write_lock(&metadata_path, &op_id);
-    result?;
+        cleanup_old_versions_internal(workspace, &mut metadata, &backups_dir)?;
+        save_metadata(&backups_dir, &metadata)
+    })?;
 
     Ok(version)
 }
@@ -210,20 +419,17 @@ pub fn get_version_content(
     let workspace = Path::new(&workspace_path);
     let backups_dir = get_backups_dir(workspace, &file_path)?;
 
-    // Parse timestamp and find file
+    // Parse timestamp and find the version
     let dt = DateTime::parse_from_rfc3339(&timestamp)
         .map_err(|e| format!("Invalid timestamp: {}", e))?;
-    let formatted = dt.format("%Y-%m-%dT%H-%M-%S%.3f").to_string();
-    let version_path = backups_dir.join(format!("{}.md.gz", formatted));
-
-    if !version_path.exists() {
-        return Err("Version file not found".to_string());
-    }
+    let metadata = load_metadata(&backups_dir);
+    let version = metadata
+        .versions
+        .iter()
+        .find(|v| DateTime::parse_from_rfc3339(&v.timestamp).map_or(false, |t| t == dt))
+        .ok_or("Version file not found")?;
 
-    // Read and decompress version file
-    let compressed = fs::read(&version_path)
-        .map_err(|e| format!("Failed to read version: {}", e))?;
-    decompress_content(&compressed)
+    read_version(&backups_dir, version)
 }
 
 #[tauri::command]
@@ -291,6 +497,7 @@ pub fn restore_version(
 
     // Write content back to original file
     let full_path = Path::new(&workspace_path).join(&file_path);
+    crate::vaults::ensure_writable(&full_path)?;
     fs::write(&full_path, &content)
         .map_err(|e| format!("Failed to restore version: {}", e))?;
 
@@ -314,62 +521,57 @@ pub fn cleanup_old_versions(
     let workspace = Path::new(&workspace_path);
     let backups_dir = get_backups_dir(workspace, &file_path)?;
 
-    let mut metadata = load_metadata(&backups_dir);
-    let removed = cleanup_old_versions_internal(&mut metadata, &backups_dir)?;
-    save_metadata(&backups_dir, &metadata)?;
-
-    Ok(removed)
+    let op_id = format!("cleanup_versions_{}", Utc::now().timestamp_millis());
+    with_metadata_lock(&backups_dir, &op_id, || {
+        let mut metadata = load_metadata(&backups_dir);
+        let removed = cleanup_old_versions_internal(workspace, &mut metadata, &backups_dir)?;
+        save_metadata(&backups_dir, &metadata)?;
+        Ok(removed)
+    })
 }
 
 fn cleanup_old_versions_internal(
+    workspace: &Path,
     metadata: &mut VersionMetadata,
     backups_dir: &Path,
 ) -> Result<usize, String> {
     let max_versions = metadata.settings.max_versions;
     let retention_days = metadata.settings.retention_days;
     let now = Utc::now();
-
-    let mut removed = 0;
+    let policy = super::version_policy::load_policy(workspace);
 
     // Sort versions by timestamp (newest first)
     metadata.versions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
 
+    // With a policy enabled, automatic snapshots follow its retention tiers
+    // and the limits below only apply to the rest
+    let mut doomed = if policy.enabled {
+        super::version_policy::expired_snapshots(&metadata.versions, &policy, now)
+    } else {
+        Vec::new()
+    };
+    let limited: Vec<&FileVersion> = metadata
+        .versions
+        .iter()
+        .filter(|v| !(policy.enabled && super::version_policy::is_automatic(v)))
+        .collect();
+
     // Keep only max_versions most recent
-    if metadata.versions.len() > max_versions {
-        let to_remove = metadata.versions.split_off(max_versions);
-        for version in &to_remove {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(&version.timestamp) {
-                let formatted = dt.format("%Y-%m-%dT%H-%M-%S%.3f").to_string();
-                let version_path = backups_dir.join(format!("{}.md.gz", formatted));
-                if version_path.exists() {
-                    let _ = fs::remove_file(version_path);
-                    removed += 1;
-                }
-            }
-        }
-    }
+    doomed.extend(limited.iter().skip(max_versions).map(|v| (*v).clone()));
 
     // Remove versions older than retention_days
-    metadata.versions.retain(|version| {
-        if let Ok(dt) = DateTime::parse_from_rfc3339(&version.timestamp) {
-            let age_days = (now - dt.with_timezone(&Utc)).num_days();
-            if age_days > retention_days {
-                let formatted = dt.format("%Y-%m-%dT%H-%M-%S%.3f").to_string();
-                let version_path = backups_dir.join(format!("{}.md.gz", formatted));
-                if version_path.exists() {
-                    let _ = fs::remove_file(version_path);
-                    removed += 1;
-                }
-                false
-            } else {
-                true
-            }
-        } else {
-            true
-        }
-    });
-
-    Ok(removed)
+    doomed.extend(
+        limited
+            .iter()
+            .take(max_versions)
+            .filter(|version| {
+                DateTime::parse_from_rfc3339(&version.timestamp)
+                    .map_or(false, |dt| (now - dt.with_timezone(&Utc)).num_days() > retention_days)
+            })
+            .map(|v| (*v).clone()),
+    );
+
+    remove_versions(backups_dir, metadata, doomed)
 }
 
 #[cfg(test)]
@@ -391,4 +593,45 @@ mod tests {
         // (birthday paradox threshold is ~256 for 65536 space)
         assert_eq!(filenames.len(), 100, "Generated duplicate filenames!");
     }
+
+    #[test]
+    fn test_delta_versions_survive_keyframe_removal() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().to_string_lossy().to_string();
+        let texts = ["# Note\n\nfirst draft\n", "# Note\n\nsecond draft\n", "# Note\n\nthird draft\n"];
+        for text in texts {
+            save_version(workspace.clone(), "note.md".to_string(), text.to_string(), Some("manual".to_string())).unwrap();
+        }
+
+        let backups_dir = get_backups_dir(dir.path(), "note.md").unwrap();
+        let mut metadata = load_metadata(&backups_dir);
+        metadata.versions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        assert!(metadata.versions[0].base.is_none());
+        assert_eq!(metadata.versions[2].base, metadata.versions[0].file);
+        for (version, text) in metadata.versions.iter().zip(texts) {
+            assert_eq!(read_version(&backups_dir, version).unwrap(), text);
+        }
+
+        let keyframe = metadata.versions[0].clone();
+        assert_eq!(remove_versions(&backups_dir, &mut metadata, vec![keyframe]).unwrap(), 1);
+        assert!(metadata.versions[0].base.is_none());
+        assert_eq!(metadata.versions[1].base, metadata.versions[0].file);
+        for (version, text) in metadata.versions.iter().zip(&texts[1..]) {
+            assert_eq!(read_version(&backups_dir, version).unwrap(), *text);
+        }
+    }
+
+    #[test]
+    fn test_purge_history_deletes_every_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().to_string_lossy().to_string();
+        for text in ["secret one", "secret two"] {
+            save_version(workspace.clone(), "diary.md".to_string(), text.to_string(), None).unwrap();
+        }
+
+        let note = dir.path().join("diary.md").to_string_lossy().to_string();
+        assert_eq!(purge_history(dir.path(), &note).unwrap(), 2);
+        assert!(!dir.path().join(".lokus").join("backups").join("diary.md").exists());
+        assert_eq!(purge_history(dir.path(), &note).unwrap(), 0);
+    }
 }
@@ -0,0 +1,295 @@
+//! Automatic snapshot policy for version history.
+//!
+//! While a note is being edited, saves are counted towards active editing
+//! time; once `interval_minutes` of it has accumulated, a snapshot is taken.
+//! Automatic snapshots are then thinned by retention tiers: everything from
+//! the last hour, one per hour for `hourly_retention_hours`, one per day for
+//! `daily_retention_days`, nothing older. Manual saves and restores keep the
+//! per-file limits in `VersionSettings`.
+
+use chrono::{DateTime, Duration, Local, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::version_history::{self, FileVersion};
+
+const POLICY_FILE: &str = "version-policy.json";
+pub const AUTO_SNAPSHOT_ACTION: &str = "auto_snapshot";
+/// A longer pause between saves counts as idle time, not editing
+const IDLE_GAP_SECS: i64 = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VersionPolicy {
+    /// Take automatic snapshots and apply the retention tiers
+    pub enabled: bool,
+    /// Minutes of active editing between automatic snapshots
+    pub interval_minutes: u32,
+    pub hourly_retention_hours: u32,
+    pub daily_retention_days: u32,
+    /// Store versions as zstd deltas against a keyframe instead of full copies
+    pub delta_compression: bool,
+}
+
+impl Default for VersionPolicy {
+    fn default() -> Self {
+        VersionPolicy {
+            enabled: false,
+            interval_minutes: 10,
+            hourly_retention_hours: 24,
+            daily_retention_days: 30,
+            delta_compression: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionStorageStats {
+    pub files: usize,
+    pub versions: usize,
+    pub keyframes: usize,
+    pub deltas: usize,
+    /// Bytes used under `.lokus/backups`
+    pub stored_bytes: u64,
+    /// Size of every version uncompressed
+    pub original_bytes: u64,
+    /// `original_bytes / stored_bytes`
+    pub compression_ratio: f64,
+}
+
+struct Activity {
+    last_save: DateTime<Utc>,
+    active_secs: i64,
+}
+
+static POLICIES: Lazy<Mutex<HashMap<PathBuf, VersionPolicy>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static ACTIVITY: Lazy<Mutex<HashMap<String, Activity>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn policy_path(workspace: &Path) -> PathBuf {
+    workspace.join(".lokus").join(POLICY_FILE)
+}
+
+/// The workspace's policy, cached since it's consulted on every save
+pub fn load_policy(workspace: &Path) -> VersionPolicy {
+    let mut policies = POLICIES.lock().unwrap_or_else(|e| e.into_inner());
+    policies
+        .entry(workspace.to_path_buf())
+        .or_insert_with(|| {
+            fs::read_to_string(policy_path(workspace))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+fn save_policy(workspace: &Path, policy: &VersionPolicy) -> Result<(), String> {
+    let path = policy_path(workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(policy).map_err(|e| format!("Failed to serialize version policy: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write version policy: {}", e))?;
+    POLICIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(workspace.to_path_buf(), policy.clone());
+    Ok(())
+}
+
+pub fn is_automatic(version: &FileVersion) -> bool {
+    version.action.starts_with("auto")
+}
+
+/// Automatic snapshots the retention tiers no longer keep. Within each hour
+/// or day bucket the newest snapshot survives.
+pub fn expired_snapshots(versions: &[FileVersion], policy: &VersionPolicy, now: DateTime<Utc>) -> Vec<FileVersion> {
+    let mut snapshots: Vec<&FileVersion> = versions.iter().filter(|v| is_automatic(v)).collect();
+    snapshots.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    let mut buckets = HashSet::new();
+    let mut expired = Vec::new();
+    for version in snapshots {
+        let Ok(at) = DateTime::parse_from_rfc3339(&version.timestamp) else {
+            continue;
+        };
+        let age = now - at.with_timezone(&Utc);
+        let local = at.with_timezone(&Local);
+        let keep = if age < Duration::hours(1) {
+            true
+        } else if age < Duration::hours(i64::from(policy.hourly_retention_hours)) {
+            buckets.insert(local.format("hour %Y-%m-%d %H").to_string())
+        } else if age < Duration::days(i64::from(policy.daily_retention_days)) {
+            buckets.insert(local.format("day %Y-%m-%d").to_string())
+        } else {
+            false
+        };
+        if !keep {
+            expired.push(version.clone());
+        }
+    }
+    expired
+}
+
+/// Count a save of `path` towards active editing and snapshot it once the
+/// policy interval is reached. Only markdown notes are versioned.
+pub fn notify_file_saved(path: &str, content: &str) {
+    if !path.ends_with(".md") {
+        return;
+    }
+    let Ok(workspace) = super::files::find_workspace_root(Path::new(path)) else {
+        return;
+    };
+    let policy = load_policy(&workspace);
+    if !policy.enabled {
+        return;
+    }
+
+    let now = Utc::now();
+    let due = {
+        let mut activity = ACTIVITY.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = activity
+            .entry(path.to_string())
+            .or_insert(Activity { last_save: now, active_secs: 0 });
+        entry.active_secs += (now - entry.last_save).num_seconds().clamp(0, IDLE_GAP_SECS);
+        entry.last_save = now;
+        let due = entry.active_secs >= i64::from(policy.interval_minutes.max(1)) * 60;
+        if due {
+            entry.active_secs = 0;
+        }
+        due
+    };
+    if !due {
+        return;
+    }
+
+    let Some(relative) = crate::links::relative_path(&workspace, Path::new(path)) else {
+        return;
+    };
+    if let Err(e) = version_history::save_version(
+        workspace.to_string_lossy().to_string(),
+        relative,
+        content.to_string(),
+        Some(AUTO_SNAPSHOT_ACTION.to_string()),
+    ) {
+        tracing::warn!("Automatic snapshot of {} failed: {}", path, e);
+    }
+}
+
+fn backup_dirs(workspace: &Path) -> Vec<PathBuf> {
+    fs::read_dir(workspace.join(".lokus").join("backups"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn storage_stats(workspace: &Path) -> VersionStorageStats {
+    let mut stats = VersionStorageStats::default();
+    for dir in backup_dirs(workspace) {
+        let metadata = version_history::load_metadata(&dir);
+        stats.files += 1;
+        stats.versions += metadata.versions.len();
+        for version in &metadata.versions {
+            if version.base.is_some() {
+                stats.deltas += 1;
+            } else {
+                stats.keyframes += 1;
+            }
+            stats.original_bytes += version.size;
+        }
+        stats.stored_bytes += fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(|meta| meta.is_file())
+            .map(|meta| meta.len())
+            .sum::<u64>();
+    }
+    if stats.stored_bytes > 0 {
+        stats.compression_ratio = stats.original_bytes as f64 / stats.stored_bytes as f64;
+    }
+    stats
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn version_policy_get(workspace_path: String) -> Result<VersionPolicy, String> {
+    Ok(load_policy(Path::new(&workspace_path)))
+}
+
+/// Save the policy and compact existing history under it
+#[tauri::command]
+pub async fn version_policy_set(workspace_path: String, config: VersionPolicy) -> Result<VersionStorageStats, String> {
+    let workspace = PathBuf::from(&workspace_path);
+    if !workspace.is_dir() {
+        return Err(format!("Workspace not found: {}", workspace_path));
+    }
+    save_policy(&workspace, &config)?;
+
+    tokio::task::spawn_blocking(move || {
+        for dir in backup_dirs(&workspace) {
+            if let Err(e) = version_history::compact(&workspace, &dir) {
+                tracing::warn!("Failed to compact versions in {}: {}", dir.display(), e);
+            }
+        }
+        storage_stats(&workspace)
+    })
+    .await
+    .map_err(|e| format!("Version compaction failed: {}", e))
+}
+
+#[tauri::command]
+pub async fn version_storage_stats(workspace_path: String) -> Result<VersionStorageStats, String> {
+    let workspace = PathBuf::from(&workspace_path);
+    tokio::task::spawn_blocking(move || storage_stats(&workspace))
+        .await
+        .map_err(|e| format!("Failed to read version storage: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn snapshot(at: DateTime<Utc>) -> FileVersion {
+        FileVersion {
+            timestamp: at.to_rfc3339(),
+            size: 1,
+            lines: 1,
+            action: AUTO_SNAPSHOT_ACTION.to_string(),
+            preview: String::new(),
+            file: None,
+            base: None,
+        }
+    }
+
+    #[test]
+    fn test_expired_snapshots_follow_tiers() {
+        let policy = VersionPolicy { enabled: true, hourly_retention_hours: 24, daily_retention_days: 30, ..Default::default() };
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let mut versions: Vec<FileVersion> = (0..6).map(|i| snapshot(now - Duration::minutes(i * 10))).collect();
+        // Two on the same day, past the hourly tier; one beyond the daily tier
+        versions.push(snapshot(now - Duration::hours(30) + Duration::seconds(20)));
+        versions.push(snapshot(now - Duration::hours(30) + Duration::seconds(10)));
+        versions.push(snapshot(now - Duration::days(40)));
+        let mut manual = snapshot(now - Duration::days(40));
+        manual.action = "manual".to_string();
+        versions.push(manual);
+
+        let expired = expired_snapshots(&versions, &policy, now);
+        let expired: HashSet<&str> = expired.iter().map(|v| v.timestamp.as_str()).collect();
+        assert_eq!(expired.len(), 2);
+        assert!(expired.contains(versions[7].timestamp.as_str()));
+        assert!(expired.contains(versions[8].timestamp.as_str()));
+    }
+}
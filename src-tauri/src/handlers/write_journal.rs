@@ -0,0 +1,198 @@
+//! Write-ahead journal for note saves.
+//!
+//! Before a file in a workspace is replaced, the new content is recorded in
+//! `.lokus/journal`; the entry is removed once the file itself is durably on
+//! disk. An entry that is still there on the next start is a save that may
+//! not have finished, and `recover_unsaved_changes` writes it again. The
+//! journal is on unless the workspace sets `write_journal` to false.
+
+use super::files::{durable_write, find_workspace_root};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+const JOURNAL_DIR: &str = "journal";
+/// Workspace setting; missing means on
+const SETTINGS_KEY: &str = "write_journal";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalEntry {
+    /// Relative to the workspace root
+    path: String,
+    content: String,
+    written_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredFile {
+    pub path: String,
+    pub written_at: i64,
+}
+
+fn journal_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join(JOURNAL_DIR)
+}
+
+fn entry_path(root: &Path, rel: &str) -> PathBuf {
+    let digest = blake3::hash(rel.as_bytes()).to_hex();
+    journal_dir(root).join(format!("{}.json", &digest[..16]))
+}
+
+fn enabled(root: &Path) -> bool {
+    crate::settings::workspace_settings(root)
+        .get(SETTINGS_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
+
+/// Journal `content` as the next version of `path`. Returns the entry to
+/// pass to `complete` once the file is written, or None for files outside a
+/// workspace (or when the journal is off).
+pub(crate) fn record(path: &Path, content: &str) -> Result<Option<PathBuf>, String> {
+    let Ok(root) = find_workspace_root(path) else {
+        return Ok(None);
+    };
+    if !enabled(&root) {
+        return Ok(None);
+    }
+    let Some(rel) = crate::links::relative_path(&root, path) else {
+        return Ok(None);
+    };
+
+    let entry = JournalEntry {
+        path: rel.clone(),
+        content: content.to_string(),
+        written_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let json = serde_json::to_vec(&entry).map_err(|e| format!("Failed to serialize journal entry: {}", e))?;
+    let dir = journal_dir(&root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create journal directory: {}", e))?;
+    let entry_path = entry_path(&root, &rel);
+    durable_write(&entry_path, &json)?;
+    Ok(Some(entry_path))
+}
+
+/// The save an entry was recorded for has finished
+pub(crate) fn complete(entry: Option<PathBuf>) {
+    if let Some(entry) = entry {
+        let _ = fs::remove_file(entry);
+    }
+}
+
+/// Finish every save left in `root`'s journal. Files whose content already
+/// matches are just cleared; the others are written again after snapshotting
+/// what is on disk now.
+fn replay(root: &Path) -> Result<Vec<RecoveredFile>, String> {
+    let dir = journal_dir(root);
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(vec![]);
+    };
+
+    let mut recovered = vec![];
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        if entry_path.extension().and_then(|e| e.to_str()) != Some("json") {
+            // Leftover temp file from a crash while journalling
+            let _ = fs::remove_file(&entry_path);
+            continue;
+        }
+        // A torn entry means the crash came before the note was touched
+        let Some(journalled) = fs::read(&entry_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<JournalEntry>(&bytes).ok())
+        else {
+            let _ = fs::remove_file(&entry_path);
+            continue;
+        };
+
+        let target = root.join(&journalled.path);
+        let on_disk = fs::read_to_string(&target).ok();
+        if on_disk.as_deref() != Some(journalled.content.as_str()) {
+            if let Some(on_disk) = on_disk.filter(|c| !c.is_empty()) {
+                let _ = super::version_history::save_version(
+                    root.to_string_lossy().to_string(),
+                    journalled.path.clone(),
+                    on_disk,
+                    Some("Before save recovery".to_string()),
+                );
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to recreate {}: {}", parent.display(), e))?;
+            }
+            durable_write(&target, journalled.content.as_bytes())?;
+            recovered.push(RecoveredFile {
+                path: target.to_string_lossy().to_string(),
+                written_at: journalled.written_at,
+            });
+        }
+        let _ = fs::remove_file(&entry_path);
+    }
+
+    recovered.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(recovered)
+}
+
+// --- Tauri Commands ---
+
+/// Replay saves interrupted by a crash in `workspace` (the open workspace
+/// when not given) and report the files that were written again. Meant to
+/// be called once on startup.
+#[tauri::command]
+pub async fn recover_unsaved_changes(app: AppHandle, workspace: Option<String>) -> Result<Vec<RecoveredFile>, String> {
+    let root = workspace
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(&app))
+        .filter(|root| root.is_dir())
+        .ok_or_else(|| "No workspace is open".to_string())?;
+    let recovered = tokio::task::spawn_blocking(move || replay(&root))
+        .await
+        .map_err(|e| format!("Recovery failed: {}", e))??;
+    if !recovered.is_empty() {
+        eprintln!("[Journal] Recovered {} interrupted save(s)", recovered.len());
+    }
+    Ok(recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_finishes_interrupted_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join(".lokus")).unwrap();
+        let done = root.join("done.md");
+        let torn = root.join("torn.md");
+        fs::write(&done, "new").unwrap();
+        fs::write(&torn, "").unwrap();
+
+        // Both saves journalled; only the first reached the file
+        record(&done, "new").unwrap().unwrap();
+        record(&torn, "second draft").unwrap().unwrap();
+
+        let recovered = replay(root).unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].path, torn.to_string_lossy());
+        assert_eq!(fs::read_to_string(&torn).unwrap(), "second draft");
+        assert_eq!(fs::read_dir(journal_dir(root)).unwrap().count(), 0);
+        assert!(replay(root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_completed_save_leaves_no_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        let note = dir.path().join("note.md");
+        let entry = record(&note, "text").unwrap();
+        assert!(entry.as_ref().is_some_and(|e| e.exists()));
+        complete(entry);
+        assert!(replay(dir.path()).unwrap().is_empty());
+
+        let outside = tempfile::tempdir().unwrap();
+        assert!(record(&outside.path().join("x.md"), "text").unwrap().is_none());
+    }
+}
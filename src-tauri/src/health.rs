@@ -0,0 +1,486 @@
+//! Vault health checks.
+//!
+//! `vault_health_check` scans every note for links and embeds that point
+//! nowhere, frontmatter that doesn't parse, text that isn't clean UTF-8 and
+//! notes with nothing in them. Each issue gets an id derived from what it is
+//! and where, so `vault_health_fix` can rescan and apply the repairs that are
+//! safe to automate: relinking to the one note or file a broken target most
+//! likely became, dropping a byte-order mark, and trashing empty notes.
+
+use crate::links::{self, LinkKind, Resolver, MARKDOWN_LINK_RE, WIKILINK_RE};
+use regex::Captures;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const BOM: char = '\u{feff}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    BrokenLink,
+    MissingEmbed,
+    InvalidFrontmatter,
+    Encoding,
+    EmptyNote,
+}
+
+/// A repair `vault_health_fix` can apply
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum HealthFix {
+    /// Point the link at `to` (workspace-relative) instead
+    Relink { to: String },
+    RemoveBom,
+    MoveToTrash,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthIssue {
+    pub id: String,
+    pub kind: IssueKind,
+    /// Absolute path of the note
+    pub path: String,
+    pub line: Option<usize>,
+    /// Link target as written
+    pub target: Option<String>,
+    pub message: String,
+    pub suggestion: Option<String>,
+    pub fix: Option<HealthFix>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub workspace: String,
+    pub scanned_notes: usize,
+    /// Encrypted notes, which can't be checked without the key
+    pub skipped_notes: usize,
+    pub counts: BTreeMap<String, usize>,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixFailure {
+    pub id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthFixResult {
+    pub fixed: Vec<String>,
+    pub failed: Vec<FixFailure>,
+    /// Notes that were rewritten or trashed
+    pub changed_notes: Vec<String>,
+}
+
+/// Lowercased letters and digits only, so "Project Plan" matches "project-plan"
+fn loose(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn issue_id(kind: IssueKind, rel: &str, line: Option<usize>, target: Option<&str>) -> String {
+    let key = format!("{:?}\n{}\n{}\n{}", kind, rel, line.unwrap_or(0), target.unwrap_or(""));
+    blake3::hash(key.as_bytes()).to_hex()[..16].to_string()
+}
+
+/// Every note and other file of a workspace, with what is needed to resolve
+/// and guess link targets
+struct Vault {
+    root: PathBuf,
+    notes: Vec<String>,
+    resolver: Resolver,
+    /// Lowercased relative paths of files that aren't notes
+    files: HashSet<String>,
+    /// Files that aren't notes, by lowercased file name
+    files_by_name: HashMap<String, Vec<String>>,
+    /// Notes by loose stem and by loose title
+    notes_by_name: HashMap<String, Vec<String>>,
+}
+
+impl Vault {
+    fn scan(root: &Path) -> Self {
+        let mut notes = Vec::new();
+        let mut files = HashSet::new();
+        let mut files_by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for entry in WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| {
+                e.depth() == 0 || !(e.file_type().is_dir() && e.file_name().to_str().map_or(false, |n| links::EXCLUDED_DIRS.contains(&n)))
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let Some(rel) = links::relative_path(root, entry.path()) else { continue };
+            if links::is_note(&rel) {
+                notes.push(rel);
+            } else {
+                let name = rel.rsplit('/').next().unwrap_or(&rel).to_lowercase();
+                files_by_name.entry(name).or_default().push(rel.clone());
+                files.insert(rel.to_lowercase());
+            }
+        }
+        notes.sort();
+
+        let mut notes_by_name: HashMap<String, Vec<String>> = HashMap::new();
+        let titles: HashMap<String, String> = crate::metadata_cache::query(root, &Default::default())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|m| Some((links::relative_path(root, Path::new(&m.path))?, m.title?)))
+            .collect();
+        for rel in &notes {
+            let stem = rel.rsplit('/').next().unwrap_or(rel);
+            let mut keys = vec![loose(&stem[..stem.len() - 3])];
+            keys.extend(titles.get(rel).map(|t| loose(t)));
+            keys.dedup();
+            for key in keys.into_iter().filter(|k| !k.is_empty()) {
+                notes_by_name.entry(key).or_default().push(rel.clone());
+            }
+        }
+
+        Self { root: root.to_path_buf(), resolver: Resolver::new(notes.iter()), notes, files, files_by_name, notes_by_name }
+    }
+
+    /// Whether a link that isn't to a note reaches an existing file
+    fn file_exists(&self, note: &str, kind: LinkKind, target: &str) -> bool {
+        let target = match kind {
+            LinkKind::Markdown => {
+                let target = target.split(['#', '?']).next().unwrap_or("");
+                urlencoding::decode(target).map(|s| s.into_owned()).unwrap_or_else(|_| target.to_string())
+            }
+            LinkKind::Wikilink | LinkKind::Embed => target.trim().to_string(),
+        };
+        if let Some(absolute) = target.strip_prefix('/') {
+            return self.files.contains(&absolute.to_lowercase());
+        }
+        if kind != LinkKind::Markdown && !target.contains('/') {
+            return self.files_by_name.contains_key(&target.to_lowercase());
+        }
+        let joined = match links::parent_dir(note) {
+            "" => target.clone(),
+            dir => format!("{}/{}", dir, target),
+        };
+        links::normalize(&joined).map_or(false, |rel| self.files.contains(&rel.to_lowercase()))
+            || (kind != LinkKind::Markdown && self.files.contains(&target.to_lowercase()))
+    }
+
+    /// Where a broken target most likely went: notes (or, for files, files of
+    /// the same name) matching it loosely
+    fn candidates(&self, target: &str, is_file: bool) -> Vec<String> {
+        let target = target.split(['#', '?']).next().unwrap_or("");
+        let decoded = urlencoding::decode(target).map(|s| s.into_owned()).unwrap_or_else(|_| target.to_string());
+        let name = decoded.rsplit('/').next().unwrap_or(&decoded);
+        if is_file {
+            return self.files_by_name.get(&name.to_lowercase()).cloned().unwrap_or_default();
+        }
+        let stem = name.strip_suffix(".md").unwrap_or(name);
+        self.notes_by_name.get(&loose(stem)).cloned().unwrap_or_default()
+    }
+
+    /// Link text for `to` from `note`, in the same style as the original
+    fn link_target(&self, note: &str, kind: LinkKind, original: &str, to: &str) -> String {
+        if kind == LinkKind::Markdown {
+            return links::relative_link(links::parent_dir(note), to).replace(' ', "%20");
+        }
+        let name = to.rsplit('/').next().unwrap_or(to);
+        let short = if links::is_note(to) && !links::is_note(original) { &name[..name.len() - 3] } else { name };
+        let full = if links::is_note(to) && !links::is_note(original) { &to[..to.len() - 3] } else { to };
+        let reaches = |candidate: &str| match self.resolver.resolve(note, kind, candidate) {
+            Some(found) => found == to,
+            None => !links::is_note(to) && self.candidates(candidate, true) == [to.to_string()],
+        };
+        if !original.contains('/') && reaches(short) {
+            short.to_string()
+        } else {
+            full.to_string()
+        }
+    }
+
+    fn check_links(&self, note: &str, content: &str, issues: &mut Vec<HealthIssue>) {
+        for link in links::parse_links(content) {
+            let target = link.target.as_str();
+            if self.resolver.resolve(note, link.kind, target).is_some() || self.file_exists(note, link.kind, target) {
+                continue;
+            }
+            let name = target.split(['#', '?']).next().unwrap_or("").rsplit('/').next().unwrap_or("");
+            let is_file = links::has_foreign_extension(name);
+            let kind = if link.kind == LinkKind::Embed || is_file { IssueKind::MissingEmbed } else { IssueKind::BrokenLink };
+
+            let candidates = self.candidates(target, is_file);
+            let (suggestion, fix) = match candidates.as_slice() {
+                [only] => (Some(format!("Link to {} instead", only)), Some(HealthFix::Relink { to: only.clone() })),
+                [] if kind == IssueKind::BrokenLink => (Some("Create the note, or remove the link".to_string()), None),
+                [] => (Some("Restore the file, or remove the embed".to_string()), None),
+                many => (Some(format!("Possible targets: {}", many.join(", "))), None),
+            };
+            let what = if kind == IssueKind::BrokenLink { "Link to" } else { "Embedded file" };
+            issues.push(HealthIssue {
+                id: issue_id(kind, note, Some(link.line), Some(target)),
+                kind,
+                path: self.root.join(note).to_string_lossy().to_string(),
+                line: Some(link.line),
+                target: Some(target.to_string()),
+                message: format!("{} '{}' not found", what, target),
+                suggestion,
+                fix,
+            });
+        }
+    }
+
+    fn check_note(&self, note: &str, bytes: Vec<u8>, issues: &mut Vec<HealthIssue>) {
+        let path = self.root.join(note).to_string_lossy().to_string();
+        let issue = |kind: IssueKind, message: String, suggestion: &str, fix: Option<HealthFix>| HealthIssue {
+            id: issue_id(kind, note, None, None),
+            kind,
+            path: path.clone(),
+            line: None,
+            target: None,
+            message,
+            suggestion: Some(suggestion.to_string()),
+            fix,
+        };
+
+        let content = match String::from_utf8(bytes) {
+            Ok(content) => content,
+            Err(e) => {
+                let message = format!("Not valid UTF-8 (first bad byte at offset {})", e.utf8_error().valid_up_to());
+                issues.push(issue(IssueKind::Encoding, message, "Re-save the note as UTF-8 in the editor it came from", None));
+                return;
+            }
+        };
+        if content.starts_with(BOM) {
+            let message = "Starts with a byte-order mark".to_string();
+            issues.push(issue(IssueKind::Encoding, message, "Remove the byte-order mark", Some(HealthFix::RemoveBom)));
+        } else if content.contains('\0') || content.contains('\u{fffd}') {
+            let message = "Contains NUL or replacement characters, usually left by a bad conversion".to_string();
+            issues.push(issue(IssueKind::Encoding, message, "Check the note for garbled text", None));
+        }
+        let content = content.trim_start_matches(BOM);
+
+        if let Err(e) = crate::frontmatter::check(content) {
+            issues.push(issue(IssueKind::InvalidFrontmatter, e, "Fix the YAML between the '---' lines", None));
+        }
+        if crate::export::strip_frontmatter(content).trim().is_empty() {
+            let message = "Note is empty".to_string();
+            issues.push(issue(IssueKind::EmptyNote, message, "Write something, or move the note to the trash", Some(HealthFix::MoveToTrash)));
+        }
+        self.check_links(note, content, issues);
+    }
+}
+
+fn run_check(root: &Path) -> Result<(Vault, HealthReport), String> {
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", root.display()));
+    }
+    let vault = Vault::scan(root);
+    let mut issues = Vec::new();
+    let mut skipped_notes = 0;
+    for note in &vault.notes {
+        let Ok(bytes) = fs::read(root.join(note)) else { continue };
+        if std::str::from_utf8(&bytes).map_or(false, crate::encryption::is_encrypted) {
+            skipped_notes += 1;
+            continue;
+        }
+        vault.check_note(note, bytes, &mut issues);
+    }
+
+    let mut counts = BTreeMap::new();
+    for issue in &issues {
+        let kind = serde_json::to_value(issue.kind).ok().and_then(|v| v.as_str().map(String::from)).unwrap_or_default();
+        *counts.entry(kind).or_insert(0) += 1;
+    }
+    let report = HealthReport {
+        workspace: root.to_string_lossy().to_string(),
+        scanned_notes: vault.notes.len() - skipped_notes,
+        skipped_notes,
+        counts,
+        issues,
+    };
+    Ok((vault, report))
+}
+
+/// Point the link to `target` on `line` at `to`; `None` if it isn't there
+fn relink(vault: &Vault, note: &str, content: &str, line: usize, target: &str, to: &str) -> Option<String> {
+    let mut changed = false;
+    let output: String = content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, text)| {
+            if i + 1 != line {
+                return text.to_string();
+            }
+            let text = WIKILINK_RE.replace_all(text, |caps: &Captures| {
+                if caps[2].trim() != target {
+                    return caps[0].to_string();
+                }
+                changed = true;
+                let kind = if &caps[1] == "!" { LinkKind::Embed } else { LinkKind::Wikilink };
+                format!(
+                    "{}[[{}{}{}]]",
+                    &caps[1],
+                    vault.link_target(note, kind, target, to),
+                    caps.get(3).map_or("", |m| m.as_str()),
+                    caps.get(4).map_or("", |m| m.as_str())
+                )
+            });
+            MARKDOWN_LINK_RE
+                .replace_all(&text, |caps: &Captures| {
+                    if &caps[3] != target {
+                        return caps[0].to_string();
+                    }
+                    changed = true;
+                    let fragment = target.find('#').map_or("", |i| &target[i..]);
+                    let new_target = vault.link_target(note, LinkKind::Markdown, target, to);
+                    format!("{}[{}]({}{}{})", &caps[1], &caps[2], new_target, fragment, &caps[4])
+                })
+                .into_owned()
+        })
+        .collect();
+    changed.then_some(output)
+}
+
+fn apply_fix(vault: &Vault, issue: &HealthIssue) -> Result<(), String> {
+    let path = PathBuf::from(&issue.path);
+    let note = links::relative_path(&vault.root, &path).ok_or_else(|| format!("{} is outside the workspace", issue.path))?;
+    match &issue.fix {
+        Some(HealthFix::Relink { to }) => {
+            let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", issue.path, e))?;
+            let (line, target) = (issue.line.unwrap_or(0), issue.target.as_deref().unwrap_or(""));
+            let rewritten = relink(vault, &note, &content, line, target, to).ok_or_else(|| "The link has changed since the check".to_string())?;
+            crate::handlers::files::write_file_content(issue.path.clone(), rewritten).map_err(String::from)
+        }
+        Some(HealthFix::RemoveBom) => {
+            let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", issue.path, e))?;
+            crate::handlers::files::write_file_content(issue.path.clone(), content.trim_start_matches(BOM).to_string()).map_err(String::from)
+        }
+        Some(HealthFix::MoveToTrash) => crate::trash::move_to_trash(&vault.root, &path).map(|_| ()),
+        None => Err("This issue has to be fixed by hand".to_string()),
+    }
+}
+
+fn run_fix(root: &Path, issue_ids: &[String]) -> Result<HealthFixResult, String> {
+    let (vault, report) = run_check(root)?;
+    let by_id: HashMap<&str, &HealthIssue> = report.issues.iter().map(|i| (i.id.as_str(), i)).collect();
+    let mut result = HealthFixResult::default();
+
+    // Trash empty notes last so their other fixes don't recreate them
+    let mut wanted: Vec<&HealthIssue> = Vec::new();
+    for id in issue_ids {
+        match by_id.get(id.as_str()) {
+            Some(issue) => wanted.push(issue),
+            None => result.failed.push(FixFailure { id: id.clone(), error: "Issue no longer present".to_string() }),
+        }
+    }
+    wanted.sort_by_key(|i| i.fix == Some(HealthFix::MoveToTrash));
+
+    for issue in wanted {
+        match apply_fix(&vault, issue) {
+            Ok(()) => {
+                result.fixed.push(issue.id.clone());
+                if !result.changed_notes.contains(&issue.path) {
+                    result.changed_notes.push(issue.path.clone());
+                }
+            }
+            Err(error) => result.failed.push(FixFailure { id: issue.id.clone(), error }),
+        }
+    }
+    Ok(result)
+}
+
+// --- Tauri Commands ---
+
+/// Scan a workspace for broken links, missing embeds, bad frontmatter,
+/// encoding problems and empty notes
+#[tauri::command]
+pub async fn vault_health_check(workspace_path: String) -> Result<HealthReport, String> {
+    let root = PathBuf::from(workspace_path);
+    tokio::task::spawn_blocking(move || run_check(&root).map(|(_, report)| report))
+        .await
+        .map_err(|e| format!("Health check failed: {}", e))?
+}
+
+/// Apply the automatic fixes of the given issues, found by a fresh scan
+#[tauri::command]
+pub async fn vault_health_fix(workspace_path: String, issue_ids: Vec<String>) -> Result<HealthFixResult, String> {
+    let root = PathBuf::from(workspace_path);
+    tokio::task::spawn_blocking(move || run_fix(&root, &issue_ids))
+        .await
+        .map_err(|e| format!("Health fix failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(files: &[(&str, &[u8])]) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for (rel, content) in files {
+            let path = root.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        (dir, root)
+    }
+
+    fn issue<'a>(report: &'a HealthReport, kind: IssueKind) -> Vec<&'a HealthIssue> {
+        report.issues.iter().filter(|i| i.kind == kind).collect()
+    }
+
+    #[test]
+    fn test_check_finds_each_kind() {
+        let (_dir, root) = workspace(&[
+            ("Index.md", b"See [[Project Plan]], [[Missing]], [b](sub/B.md) and ![[gone.png]]\n![](img/pic.png)"),
+            ("Projects/project-plan.md", b"# Plan"),
+            ("sub/B.md", b"\xef\xbb\xbf# B"),
+            ("assets/pic.png", b"png"),
+            ("Bad.md", b"---\ntitle: [unclosed\n---\nText"),
+            ("Latin.md", b"caf\xe9"),
+            ("Empty.md", b"---\ntitle: Empty\n---\n\n"),
+        ]);
+        let (_, report) = run_check(&root).unwrap();
+
+        let broken = issue(&report, IssueKind::BrokenLink);
+        assert_eq!(broken.len(), 2);
+        let plan = broken.iter().find(|i| i.target.as_deref() == Some("Project Plan")).unwrap();
+        assert_eq!(plan.fix, Some(HealthFix::Relink { to: "Projects/project-plan.md".into() }));
+        assert!(broken.iter().any(|i| i.target.as_deref() == Some("Missing") && i.fix.is_none()));
+
+        let embeds = issue(&report, IssueKind::MissingEmbed);
+        assert_eq!(embeds.len(), 2);
+        assert!(embeds.iter().any(|i| i.fix == Some(HealthFix::Relink { to: "assets/pic.png".into() })));
+
+        assert_eq!(issue(&report, IssueKind::InvalidFrontmatter).len(), 1);
+        assert_eq!(issue(&report, IssueKind::Encoding).len(), 2);
+        assert_eq!(issue(&report, IssueKind::EmptyNote).len(), 1);
+        assert_eq!(report.counts["brokenLink"], 2);
+    }
+
+    #[test]
+    fn test_fix_relinks_and_cleans_up() {
+        let (_dir, root) = workspace(&[
+            ("Index.md", b"See [[Project Plan|the plan]]\n![pic](img/pic.png)\n"),
+            ("Projects/project-plan.md", b"# Plan"),
+            ("assets/pic.png", b"png"),
+            ("BOM.md", b"\xef\xbb\xbf# Title"),
+        ]);
+        let (_, report) = run_check(&root).unwrap();
+        let ids: Vec<String> = report.issues.iter().filter(|i| i.fix.is_some()).map(|i| i.id.clone()).collect();
+        assert_eq!(ids.len(), 3);
+
+        let result = run_fix(&root, &[ids.clone(), vec!["nope".into()]].concat()).unwrap();
+        assert_eq!(result.fixed.len(), 3);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(
+            fs::read_to_string(root.join("Index.md")).unwrap(),
+            "See [[project-plan|the plan]]\n![pic](assets/pic.png)\n"
+        );
+        assert_eq!(fs::read_to_string(root.join("BOM.md")).unwrap(), "# Title");
+        assert!(run_check(&root).unwrap().1.issues.is_empty());
+    }
+}
@@ -0,0 +1,421 @@
+//! Shrinking and converting images that end up in the vault.
+//!
+//! `image_optimize` resizes an image to fit a maximum dimension, converts it
+//! (HEIC, which the webview can't show, becomes JPEG unless another format
+//! is asked for) and removes GPS coordinates. Re-encoded images carry no
+//! metadata at all, with the EXIF orientation applied to the pixels first;
+//! JPEGs that are otherwise left alone have only their GPS block blanked, in
+//! place. WebP output is lossless.
+//!
+//! Workspaces can set `auto_optimize_images` to `true` (default options) or
+//! to an options object, and `copy_external_files_to_workspace` then runs
+//! every imported image through this. With `keepOriginal`, the untouched
+//! file is kept under `.lokus/cache/originals`.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::codecs::webp::WebPEncoder;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Workspace setting: `true` or an `ImageOptions` object
+const SETTINGS_KEY: &str = "auto_optimize_images";
+const ORIGINALS_DIR: &str = "originals";
+const DEFAULT_MAX_DIMENSION: u32 = 2560;
+const DEFAULT_QUALITY: u8 = 82;
+const EXIF_GPS_IFD: u16 = 0x8825;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "png" => Some(ImageFormat::Png),
+            "webp" => Some(ImageFormat::Webp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ImageOptions {
+    /// Longest side after resizing; smaller images are never enlarged
+    pub max_dimension: Option<u32>,
+    /// Output format; the source format when not given (JPEG for HEIC)
+    pub format: Option<ImageFormat>,
+    /// JPEG quality, 1-100
+    pub quality: u8,
+    pub strip_gps: bool,
+    pub keep_original: bool,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            max_dimension: Some(DEFAULT_MAX_DIMENSION),
+            format: None,
+            quality: DEFAULT_QUALITY,
+            strip_gps: true,
+            keep_original: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageOptimizeResult {
+    /// The optimized image; differs from `original_path` when converted
+    pub path: String,
+    pub original_path: String,
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+    pub width: u32,
+    pub height: u32,
+    pub resized: bool,
+    pub converted: bool,
+    pub gps_removed: bool,
+    /// Copy of the untouched file, when kept
+    pub original_backup: Option<String>,
+}
+
+pub(crate) fn is_heic(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_lowercase().as_str(), "heic" | "heif"))
+}
+
+/// Whether `copy_external_files_to_workspace` should optimize this file
+pub fn is_optimizable(path: &Path) -> bool {
+    is_heic(path)
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(ImageFormat::from_extension)
+            .is_some()
+}
+
+/// Decode with the EXIF orientation applied
+pub(crate) fn decode(path: &Path) -> Result<DynamicImage, String> {
+    let mut decoder = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?
+        .into_decoder()
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    let orientation = decoder.orientation().map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// HEIC goes through the system converter: `sips` on macOS, libheif's
+/// `heif-convert` elsewhere
+pub(crate) fn decode_heic(path: &Path) -> Result<DynamicImage, String> {
+    let temp = tempfile::Builder::new()
+        .suffix(".jpg")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("sips");
+        command.args(["-s", "format", "jpeg"]).arg(path).arg("--out").arg(temp.path());
+        command
+    } else {
+        let mut command = std::process::Command::new("heif-convert");
+        command.args(["-q", "95"]).arg(path).arg(temp.path());
+        command
+    };
+    match command.output() {
+        Ok(output) if output.status.success() => decode(temp.path()),
+        Ok(output) => Err(format!("Failed to convert {}: {}", path.display(), String::from_utf8_lossy(&output.stderr).trim())),
+        Err(_) => Err("Converting HEIC images needs heif-convert (libheif) installed".to_string()),
+    }
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let result = match format {
+        // JPEG has no alpha channel
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut out, quality.clamp(1, 100))),
+        ImageFormat::Png => {
+            image.write_with_encoder(PngEncoder::new_with_quality(&mut out, CompressionType::Best, FilterType::Adaptive))
+        }
+        ImageFormat::Webp if image.color().has_alpha() => {
+            DynamicImage::ImageRgba8(image.to_rgba8()).write_with_encoder(WebPEncoder::new_lossless(&mut out))
+        }
+        ImageFormat::Webp => DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+    };
+    result.map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(out)
+}
+
+/// Blank the GPS block of a JPEG's EXIF data in place, keeping the rest of
+/// the metadata and the image data byte for byte. Returns whether there was
+/// GPS data to remove.
+fn strip_jpeg_gps(bytes: &mut [u8]) -> bool {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+    let mut pos = 2;
+    while pos + 4 <= bytes.len() && bytes[pos] == 0xFF {
+        let marker = bytes[pos + 1];
+        // Start of scan or end of image: no metadata past this point
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let len = u16::from_be_bytes([bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let end = pos + 2 + len;
+        if len < 2 || end > bytes.len() {
+            break;
+        }
+        if marker == 0xE1 && bytes[pos + 4..end].starts_with(b"Exif\0\0") {
+            return strip_tiff_gps(&mut bytes[pos + 10..end]).unwrap_or(false);
+        }
+        pos = end;
+    }
+    false
+}
+
+fn strip_tiff_gps(tiff: &mut [u8]) -> Option<bool> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read16 = |t: &[u8], at: usize| -> Option<usize> {
+        let b: [u8; 2] = t.get(at..at + 2)?.try_into().ok()?;
+        Some((if little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) }) as usize)
+    };
+    let read32 = |t: &[u8], at: usize| -> Option<usize> {
+        let b: [u8; 4] = t.get(at..at + 4)?.try_into().ok()?;
+        Some((if little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) }) as usize)
+    };
+
+    let ifd0 = read32(tiff, 4)?;
+    let gps = (0..read16(tiff, ifd0)?)
+        .map(|i| ifd0 + 2 + i * 12)
+        .find(|&entry| read16(tiff, entry) == Some(EXIF_GPS_IFD as usize))
+        .and_then(|entry| read32(tiff, entry + 8))?;
+
+    let count = read16(tiff, gps)?;
+    for i in 0..count {
+        let entry = gps + 2 + i * 12;
+        let unit = match read16(tiff, entry + 2)? {
+            3 => 2,
+            4 | 9 => 4,
+            5 | 10 => 8,
+            _ => 1,
+        };
+        let size = unit * read32(tiff, entry + 4)?;
+        // Values over four bytes live elsewhere in the block
+        if size > 4 {
+            let offset = read32(tiff, entry + 8)?;
+            if let Some(value) = tiff.get_mut(offset..offset + size) {
+                value.fill(0);
+            }
+        }
+        tiff.get_mut(entry..entry + 12)?.fill(0);
+    }
+    // Zero entries, in either byte order
+    tiff.get_mut(gps..gps + 2)?.fill(0);
+    Some(count > 0)
+}
+
+/// A free path next to `path` with the given extension
+fn converted_path(path: &Path, ext: &str) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "image".to_string());
+    let mut candidate = path.with_file_name(format!("{}.{}", stem, ext));
+    let mut counter = 1;
+    while candidate.exists() && counter < 1000 {
+        candidate = path.with_file_name(format!("{}-{}.{}", stem, counter, ext));
+        counter += 1;
+    }
+    candidate
+}
+
+fn backup_original(path: &Path, bytes: &[u8]) -> Result<String, String> {
+    let dir = match crate::handlers::files::find_workspace_root(path.parent().unwrap_or(path)) {
+        Ok(root) => root.join(".lokus").join("cache").join(ORIGINALS_DIR),
+        Err(_) => std::env::temp_dir().join("lokus-originals"),
+    };
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let digest = blake3::hash(bytes).to_hex();
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let backup = dir.join(format!("{}-{}", &digest[..16], name));
+    fs::write(&backup, bytes).map_err(|e| format!("Failed to keep original: {}", e))?;
+    Ok(backup.to_string_lossy().to_string())
+}
+
+pub fn optimize(path: &Path, options: &ImageOptions) -> Result<ImageOptimizeResult, String> {
+    let original = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let heic = is_heic(path);
+    let source_format = path.extension().and_then(|e| e.to_str()).and_then(ImageFormat::from_extension);
+    if source_format.is_none() && !heic {
+        return Err(format!("Unsupported image type: {}", path.display()));
+    }
+    let target = options.format.or(source_format).unwrap_or(ImageFormat::Jpeg);
+    let converted = heic || source_format != Some(target);
+
+    let mut gps_probe = original.clone();
+    let had_gps = source_format == Some(ImageFormat::Jpeg) && strip_jpeg_gps(&mut gps_probe);
+
+    let image = if heic { decode_heic(path)? } else { decode(path)? };
+    let (width, height) = (image.width(), image.height());
+    let max = options.max_dimension.filter(|&m| m > 0).unwrap_or(u32::MAX);
+    let resized = width.max(height) > max;
+
+    let (bytes, width, height) = if resized || converted {
+        let image = if resized { image.resize(max, max, image::imageops::FilterType::Lanczos3) } else { image };
+        (encode(&image, target, options.quality)?, image.width(), image.height())
+    } else if options.strip_gps && had_gps {
+        (gps_probe, width, height)
+    } else {
+        // Same format and size: only worth rewriting if it gets smaller
+        let reencoded = match target {
+            ImageFormat::Png => encode(&image, target, options.quality)?,
+            _ => original.clone(),
+        };
+        (if reencoded.len() < original.len() { reencoded } else { original.clone() }, width, height)
+    };
+
+    let original_backup = if options.keep_original && (bytes != original || converted) {
+        Some(backup_original(path, &original)?)
+    } else {
+        None
+    };
+
+    let output = if converted { converted_path(path, target.extension()) } else { path.to_path_buf() };
+    if bytes != original || converted {
+        let output_str = output.to_string_lossy().to_string();
+        let _lock = crate::file_locking::FileLockGuard::for_write(&output_str)?;
+        crate::handlers::files::durable_write(&output, &bytes)?;
+        if converted {
+            fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(ImageOptimizeResult {
+        path: output.to_string_lossy().to_string(),
+        original_path: path.to_string_lossy().to_string(),
+        before_bytes: original.len() as u64,
+        after_bytes: bytes.len() as u64,
+        width,
+        height,
+        resized,
+        converted,
+        gps_removed: had_gps && (options.strip_gps || resized || converted),
+        original_backup,
+    })
+}
+
+/// Options from `auto_optimize_images`, or None when imports aren't optimized
+pub fn import_options(workspace: &Path) -> Option<ImageOptions> {
+    match crate::settings::workspace_settings(workspace).remove(SETTINGS_KEY)? {
+        Value::Bool(true) => Some(ImageOptions::default()),
+        value @ Value::Object(_) => serde_json::from_value(value).ok(),
+        _ => None,
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Resize, convert and strip location data from the image at `path`
+#[tauri::command]
+pub async fn image_optimize(path: String, options: Option<ImageOptions>) -> Result<ImageOptimizeResult, String> {
+    tokio::task::spawn_blocking(move || optimize(Path::new(&path), &options.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Image optimization failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A JPEG header whose EXIF has a GPS latitude (three rationals)
+    fn jpeg_with_gps() -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+        // IFD0: one entry pointing at the GPS IFD at 26
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(EXIF_GPS_IFD.to_le_bytes());
+        tiff.extend(4u16.to_le_bytes());
+        tiff.extend(1u32.to_le_bytes());
+        tiff.extend(26u32.to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        // GPS IFD: GPSLatitude, RATIONAL x3 at 44
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(2u16.to_le_bytes());
+        tiff.extend(5u16.to_le_bytes());
+        tiff.extend(3u32.to_le_bytes());
+        tiff.extend(44u32.to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        for value in [52u32, 1, 31, 1, 12, 1] {
+            tiff.extend(value.to_le_bytes());
+        }
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(&tiff);
+        jpeg.extend([0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn test_strip_jpeg_gps_keeps_the_rest() {
+        let mut jpeg = jpeg_with_gps();
+        let before = jpeg.clone();
+        assert!(strip_jpeg_gps(&mut jpeg));
+        assert_eq!(jpeg.len(), before.len());
+        let tiff = &jpeg[12..];
+        // IFD0 still points at the (now empty) GPS IFD
+        assert_eq!(&tiff[8..22], &before[20..34]);
+        assert_eq!(&tiff[26..28], &[0, 0]);
+        assert!(tiff[44..68].iter().all(|&b| b == 0));
+        assert!(!strip_jpeg_gps(&mut jpeg));
+    }
+
+    #[test]
+    fn test_optimize_resizes_and_converts() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        let path = dir.path().join("shot.png");
+        DynamicImage::new_rgb8(400, 200).save(&path).unwrap();
+
+        let options = ImageOptions {
+            max_dimension: Some(100),
+            format: Some(ImageFormat::Jpeg),
+            keep_original: true,
+            ..Default::default()
+        };
+        let result = optimize(&path, &options).unwrap();
+        assert!(result.resized && result.converted);
+        assert_eq!((result.width, result.height), (100, 50));
+        assert_eq!(result.path, dir.path().join("shot.jpg").to_string_lossy());
+        assert!(!path.exists());
+        assert!(result.original_backup.is_some_and(|b| b.contains(".lokus/cache/originals")));
+        assert_eq!(image::image_dimensions(dir.path().join("shot.jpg")).unwrap(), (100, 50));
+
+        fs::write(dir.path().join(".lokus/settings.json"), r#"{"auto_optimize_images": {"maxDimension": 800}}"#).unwrap();
+        let options = import_options(dir.path()).unwrap();
+        assert_eq!((options.max_dimension, options.quality), (Some(800), DEFAULT_QUALITY));
+    }
+}
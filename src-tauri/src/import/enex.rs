@@ -0,0 +1,251 @@
+//! Evernote `.enex` import.
+//!
+//! An ENEX file is an XML list of notes whose bodies are ENML (XHTML) and
+//! whose attachments are base64 resources referenced from the body by their
+//! MD5 hash. Each note becomes a markdown file with its title, dates, tags and
+//! source URL as frontmatter; resources are decoded into an `attachments`
+//! folder and linked from where they appeared.
+
+use super::html::{html_to_markdown, Reference};
+use super::ImportSink;
+use base64::{engine::general_purpose, Engine as _};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+struct EnexResource {
+    data: String,
+    mime: String,
+    file_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: Option<String>,
+    updated: Option<String>,
+    tags: Vec<String>,
+    source_url: Option<String>,
+    resources: Vec<EnexResource>,
+}
+
+fn parse_enex(xml: &str) -> Result<Vec<EnexNote>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<String> = Vec::new();
+    let mut notes = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut resource: Option<EnexResource> = None;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match name.as_str() {
+                    "note" => note = Some(EnexNote::default()),
+                    "resource" => resource = Some(EnexResource::default()),
+                    _ => {}
+                }
+                stack.push(name);
+                text.clear();
+            }
+            Ok(Event::End(_)) => {
+                let name = stack.pop().unwrap_or_default();
+                let value = std::mem::take(&mut text);
+                let value = value.trim();
+                match name.as_str() {
+                    "resource" => {
+                        if let (Some(n), Some(res)) = (note.as_mut(), resource.take()) {
+                            n.resources.push(res);
+                        }
+                        continue;
+                    }
+                    "note" => {
+                        notes.extend(note.take());
+                        continue;
+                    }
+                    _ => {}
+                }
+                match (name.as_str(), note.as_mut(), resource.as_mut()) {
+                    ("data", _, Some(res)) => res.data = value.to_string(),
+                    ("mime", _, Some(res)) => res.mime = value.to_string(),
+                    ("file-name", _, Some(res)) => res.file_name = Some(value.to_string()),
+                    ("title", Some(n), None) => n.title = value.to_string(),
+                    ("content", Some(n), None) => n.content = value.to_string(),
+                    ("created", Some(n), None) => n.created = Some(value.to_string()),
+                    ("updated", Some(n), None) => n.updated = Some(value.to_string()),
+                    ("tag", Some(n), None) => n.tags.push(value.to_string()),
+                    ("source-url", Some(n), None) => n.source_url = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let unescaped = e.unescape().map_err(|e| format!("Invalid ENEX text: {}", e))?;
+                text.push_str(&unescaped);
+            }
+            Ok(Event::CData(e)) => text.push_str(&String::from_utf8_lossy(&e.into_inner())),
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid ENEX file at byte {}: {}", reader.buffer_position(), e)),
+            _ => {}
+        }
+    }
+    Ok(notes)
+}
+
+/// ENEX timestamps look like `20240131T235959Z`
+fn format_timestamp(value: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "image/webp" => "webp",
+        "application/pdf" => "pdf",
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/mpeg" => "mp3",
+        "audio/mp4" | "audio/m4a" => "m4a",
+        "text/plain" => "txt",
+        _ => "bin",
+    }
+}
+
+fn frontmatter(note: &EnexNote) -> String {
+    let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let mut lines = vec!["---".to_string(), format!("title: {}", quote(&note.title))];
+    if let Some(created) = &note.created {
+        lines.push(format!("created: {}", format_timestamp(created)));
+    }
+    if let Some(updated) = &note.updated {
+        lines.push(format!("updated: {}", format_timestamp(updated)));
+    }
+    if !note.tags.is_empty() {
+        let tags: Vec<String> = note.tags.iter().map(|t| quote(t)).collect();
+        lines.push(format!("tags: [{}]", tags.join(", ")));
+    }
+    if let Some(source) = &note.source_url {
+        lines.push(format!("source: {}", quote(source)));
+    }
+    lines.push("---".to_string());
+    lines.join("\n") + "\n\n"
+}
+
+pub fn import(
+    enex_path: &Path,
+    target: &Path,
+    dry_run: bool,
+    mut progress: impl FnMut(usize, usize, Option<&str>),
+) -> Result<super::ImportReport, String> {
+    let xml = fs::read_to_string(enex_path).map_err(|e| format!("Failed to read {}: {}", enex_path.display(), e))?;
+    let notes = parse_enex(&xml)?;
+    let name = enex_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Evernote".to_string());
+    let mut sink = ImportSink::new(target, &name, dry_run)?;
+
+    for (index, note) in notes.iter().enumerate() {
+        let title = if note.title.is_empty() { "Untitled" } else { note.title.as_str() };
+        progress(index + 1, notes.len(), Some(title));
+        let note_rel = sink.reserve(&format!("{}.md", super::sanitize_file_name(title)));
+
+        // Decode resources first so the body can link to them by hash
+        let mut by_hash: HashMap<String, String> = HashMap::new();
+        for resource in &note.resources {
+            let compact: String = resource.data.split_whitespace().collect();
+            let data = match general_purpose::STANDARD.decode(compact) {
+                Ok(data) => data,
+                Err(e) => {
+                    sink.issue(title, format!("Attachment could not be decoded: {}", e));
+                    continue;
+                }
+            };
+            let hash = format!("{:x}", md5::compute(&data));
+            let file_name = resource
+                .file_name
+                .as_deref()
+                .map(super::sanitize_file_name)
+                .unwrap_or_else(|| format!("{}.{}", hash, extension_for_mime(&resource.mime)));
+            let rel = sink.reserve(&format!("attachments/{}", file_name));
+            sink.write_attachment(&rel, &data)?;
+            by_hash.insert(hash, rel);
+        }
+
+        let conversion = html_to_markdown(&note.content, &mut |reference| match reference {
+            Reference::Media { hash, .. } => by_hash.get(&hash.to_lowercase()).map(|rel| {
+                crate::links::relative_link(crate::links::parent_dir(&note_rel), rel)
+            }),
+            _ => None,
+        });
+        for tag in &conversion.unsupported {
+            sink.issue(title, format!("Unsupported content dropped: {}", tag));
+        }
+
+        let markdown = frontmatter(note) + &conversion.markdown;
+        sink.write_note(&note_rel, &markdown)?;
+    }
+
+    Ok(sink.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export3.dtd">
+<en-export>
+  <note>
+    <title>Trip &amp; plans</title>
+    <content><![CDATA[<?xml version="1.0" encoding="UTF-8"?><!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd"><en-note><div>Packing <b>list</b></div><div><en-todo checked="true"/>Passport</div><en-media hash="5d41402abc4b2a76b9719d911017c592" type="image/png"/></en-note>]]></content>
+    <created>20240131T235959Z</created>
+    <tag>travel</tag>
+    <note-attributes><source-url>https://example.com</source-url></note-attributes>
+    <resource>
+      <data encoding="base64">aGVs
+bG8=</data>
+      <mime>image/png</mime>
+      <resource-attributes><file-name>photo.png</file-name></resource-attributes>
+    </resource>
+  </note>
+</en-export>"#;
+
+    #[test]
+    fn test_parse_enex() {
+        let notes = parse_enex(SAMPLE).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].title, "Trip & plans");
+        assert_eq!(notes[0].tags, vec!["travel"]);
+        assert_eq!(notes[0].resources.len(), 1);
+        assert_eq!(notes[0].resources[0].file_name.as_deref(), Some("photo.png"));
+    }
+
+    #[test]
+    fn test_import_writes_note_and_attachment() {
+        let dir = tempfile::tempdir().unwrap();
+        let enex = dir.path().join("Travel.enex");
+        fs::write(&enex, SAMPLE).unwrap();
+        let target = tempfile::tempdir().unwrap();
+
+        let report = import(&enex, target.path(), false, |_, _, _| {}).unwrap();
+        assert_eq!(report.notes, 1);
+        assert_eq!(report.attachments, 1);
+
+        let out = Path::new(&report.output_folder);
+        let note = fs::read_to_string(out.join("Trip & plans.md")).unwrap();
+        assert!(note.starts_with("---\ntitle: \"Trip & plans\"\ncreated: 2024-01-31T23:59:59Z\ntags: [\"travel\"]\nsource: \"https://example.com\"\n---\n"));
+        assert!(note.contains("Packing **list**"));
+        assert!(note.contains("[x] Passport"));
+        assert!(note.contains("![](attachments/photo.png)"));
+        assert_eq!(fs::read(out.join("attachments/photo.png")).unwrap(), b"hello");
+    }
+}
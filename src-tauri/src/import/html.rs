@@ -0,0 +1,408 @@
+//! HTML to Markdown conversion for imported content.
+//!
+//! Handles the markup produced by Notion's HTML export, Evernote's ENML and
+//! pages cleaned up by the web clipper:
+//! headings, emphasis, lists (including to-dos), quotes and callouts, code,
+//! tables, equations and media. Links and media sources are passed through a
+//! caller-supplied rewriter so importers can point them at converted notes and
+//! copied attachments. Tags with no Markdown equivalent are reported back.
+
+use scraper::{ElementRef, Html, Node};
+use std::collections::BTreeSet;
+
+/// A reference found in the HTML that the importer may want to rewrite
+pub enum Reference<'a> {
+    Link(&'a str),
+    Image(&'a str),
+    /// Evernote `<en-media>`: the MD5 hash of a resource and its MIME type
+    Media { hash: &'a str, mime: &'a str },
+}
+
+pub struct Conversion {
+    pub markdown: String,
+    /// Elements that were dropped or only partly converted
+    pub unsupported: BTreeSet<String>,
+}
+
+struct Writer<'a> {
+    rewrite: &'a mut dyn FnMut(Reference) -> Option<String>,
+    unsupported: BTreeSet<String>,
+    list_depth: usize,
+}
+
+/// Collapse runs of whitespace the way a browser would
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+    out
+}
+
+/// Wrap inline content in a marker, keeping surrounding spaces outside it
+fn wrap(inner: &str, marker: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+    let lead = if inner.starts_with(' ') { " " } else { "" };
+    let trail = if inner.ends_with(' ') { " " } else { "" };
+    format!("{}{}{}{}{}", lead, marker, trimmed, marker, trail)
+}
+
+/// Squash block output into tight lines, for list items and table cells
+fn tighten(block: &str) -> String {
+    block
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn has_class(el: &ElementRef, class: &str) -> bool {
+    el.value().classes().any(|c| c == class)
+}
+
+impl<'a> Writer<'a> {
+    fn children(&mut self, el: ElementRef) -> String {
+        let mut out = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(&collapse_whitespace(text)),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        out.push_str(&self.element(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn block(&mut self, el: ElementRef) -> String {
+        format!("\n\n{}\n\n", self.children(el).trim())
+    }
+
+    fn element(&mut self, el: ElementRef) -> String {
+        let name = el.value().name();
+        match name {
+            "script" | "style" | "head" | "title" | "meta" | "link" | "noscript" => String::new(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = self.children(el);
+                format!("\n\n{} {}\n\n", "#".repeat(level), text.trim())
+            }
+            "p" => self.block(el),
+            "div" if has_class(&el, "checkbox") => String::new(),
+            "div" | "section" | "article" | "header" | "footer" | "main" | "body" | "html" | "en-note"
+            | "details" | "summary" | "figcaption" | "center" => {
+                let inner = self.children(el);
+                format!("\n{}\n", inner)
+            }
+            "figure" if has_class(&el, "equation") => {
+                let tex = el
+                    .select(&scraper::Selector::parse("annotation").expect("valid selector"))
+                    .next()
+                    .map(|a| a.text().collect::<String>())
+                    .unwrap_or_else(|| el.text().collect());
+                format!("\n\n$$\n{}\n$$\n\n", tex.trim())
+            }
+            "figure" if has_class(&el, "callout") => self.quote(el),
+            "figure" => self.block(el),
+            "br" => "\\\n".to_string(),
+            "hr" => "\n\n---\n\n".to_string(),
+            "strong" | "b" => wrap(&self.children(el), "**"),
+            "em" | "i" => wrap(&self.children(el), "*"),
+            "s" | "del" | "strike" => wrap(&self.children(el), "~~"),
+            "mark" => wrap(&self.children(el), "=="),
+            "code" => {
+                let text: String = el.text().collect();
+                if text.contains('`') {
+                    format!("`` {} ``", text)
+                } else {
+                    format!("`{}`", text)
+                }
+            }
+            "pre" => self.pre(el),
+            "blockquote" => self.quote(el),
+            "a" => self.link(el),
+            "img" => self.image(el),
+            "en-media" => self.media(el),
+            "en-todo" => {
+                // HTML parsing ignores `<en-todo/>` self-closing, so the rest of
+                // the line ends up as its children
+                let checked = el.value().attr("checked").map_or(false, |v| v == "true");
+                let marker = if checked { "[x] " } else { "[ ] " };
+                format!("{}{}", marker, self.children(el))
+            }
+            "en-crypt" => {
+                self.unsupported.insert("en-crypt (encrypted text)".to_string());
+                String::new()
+            }
+            "ul" | "ol" => self.list(el, name == "ol"),
+            "li" => self.list_item(el, "- "),
+            "table" => self.table(el),
+            "iframe" | "video" | "audio" | "source" | "embed" | "object" => {
+                let src = el.value().attr("src").or_else(|| el.value().attr("data"));
+                match src {
+                    Some(src) => {
+                        let href = (self.rewrite)(Reference::Link(src)).unwrap_or_else(|| src.to_string());
+                        format!("\n\n[{}]({})\n\n", name, href)
+                    }
+                    None => {
+                        self.unsupported.insert(name.to_string());
+                        self.children(el)
+                    }
+                }
+            }
+            "svg" | "canvas" | "form" | "input" | "button" | "select" => {
+                self.unsupported.insert(name.to_string());
+                String::new()
+            }
+            _ => self.children(el),
+        }
+    }
+
+    fn pre(&mut self, el: ElementRef) -> String {
+        let language = el
+            .select(&scraper::Selector::parse("code").expect("valid selector"))
+            .next()
+            .and_then(|code| code.value().classes().find_map(|c| c.strip_prefix("language-")))
+            .or_else(|| el.value().classes().find_map(|c| c.strip_prefix("language-")))
+            .unwrap_or("")
+            .to_string();
+        let text: String = el.text().collect();
+        format!("\n\n```{}\n{}\n```\n\n", language, text.trim_end_matches('\n'))
+    }
+
+    fn quote(&mut self, el: ElementRef) -> String {
+        let inner = self.children(el);
+        let quoted: Vec<String> = inner
+            .trim()
+            .lines()
+            .map(|line| if line.trim().is_empty() { ">".to_string() } else { format!("> {}", line.trim_end()) })
+            .collect();
+        format!("\n\n{}\n\n", quoted.join("\n"))
+    }
+
+    fn link(&mut self, el: ElementRef) -> String {
+        let text = self.children(el);
+        let Some(href) = el.value().attr("href") else {
+            return text;
+        };
+        let href = (self.rewrite)(Reference::Link(href)).unwrap_or_else(|| href.to_string());
+        let label = if text.trim().is_empty() { href.clone() } else { text.trim().to_string() };
+        format!("[{}]({})", label, href.replace(' ', "%20"))
+    }
+
+    fn image(&mut self, el: ElementRef) -> String {
+        let Some(src) = el.value().attr("src") else {
+            return String::new();
+        };
+        let alt = el.value().attr("alt").unwrap_or("");
+        let src = (self.rewrite)(Reference::Image(src)).unwrap_or_else(|| src.to_string());
+        format!("![{}]({})", alt, src.replace(' ', "%20"))
+    }
+
+    fn media(&mut self, el: ElementRef) -> String {
+        let hash = el.value().attr("hash").unwrap_or("");
+        let mime = el.value().attr("type").unwrap_or("application/octet-stream");
+        // Like en-todo, anything after a self-closed en-media is parsed as its children
+        let trailing = self.children(el);
+        let media = match (self.rewrite)(Reference::Media { hash, mime }) {
+            Some(path) if mime.starts_with("image/") => format!("![]({})", path.replace(' ', "%20")),
+            Some(path) => {
+                let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                format!("[{}]({})", name, path.replace(' ', "%20"))
+            }
+            None => {
+                self.unsupported.insert(format!("en-media ({})", mime));
+                String::new()
+            }
+        };
+        media + &trailing
+    }
+
+    fn list(&mut self, el: ElementRef, ordered: bool) -> String {
+        let todo_list = has_class(&el, "to-do-list");
+        self.list_depth += 1;
+        let mut items = Vec::new();
+        let mut number = el.value().attr("start").and_then(|s| s.parse::<usize>().ok()).unwrap_or(1);
+        for child in el.children().filter_map(ElementRef::wrap) {
+            if child.value().name() != "li" {
+                let other = tighten(&self.element(child));
+                if !other.is_empty() {
+                    items.push(other);
+                }
+                continue;
+            }
+            let marker = if todo_list {
+                let checked = child
+                    .select(&scraper::Selector::parse(".checkbox-on").expect("valid selector"))
+                    .next()
+                    .is_some();
+                if checked { "- [x] ".to_string() } else { "- [ ] ".to_string() }
+            } else if ordered {
+                let marker = format!("{}. ", number);
+                number += 1;
+                marker
+            } else {
+                "- ".to_string()
+            };
+            items.push(self.list_item(child, &marker));
+        }
+        self.list_depth -= 1;
+
+        let list = items.join("\n");
+        if self.list_depth == 0 {
+            format!("\n\n{}\n\n", list)
+        } else {
+            format!("\n{}\n", list)
+        }
+    }
+
+    fn list_item(&mut self, el: ElementRef, marker: &str) -> String {
+        let content = tighten(&self.children(el));
+        let indent = " ".repeat(marker.len());
+        let mut lines = content.lines();
+        let mut item = format!("{}{}", marker, lines.next().unwrap_or("").trim_start());
+        for line in lines {
+            item.push('\n');
+            item.push_str(&indent);
+            item.push_str(line);
+        }
+        item
+    }
+
+    fn table(&mut self, el: ElementRef) -> String {
+        let row_selector = scraper::Selector::parse("tr").expect("valid selector");
+        let rows: Vec<Vec<String>> = el
+            .select(&row_selector)
+            .map(|row| {
+                row.children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                    .map(|cell| tighten(&self.children(cell)).replace('\n', "<br>").replace('|', "\\|"))
+                    .collect()
+            })
+            .collect();
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+
+        let mut out = String::from("\n\n");
+        for (i, row) in rows.iter().enumerate() {
+            let mut cells = row.clone();
+            cells.resize(columns, String::new());
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            if i == 0 {
+                out.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+            }
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Normalize spacing: no trailing whitespace, at most one blank line in a row
+fn clean_up(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut blank_run = 0;
+    let mut in_fence = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let line = if in_fence { line } else { line.trim_end() };
+        if line.trim().is_empty() && !in_fence {
+            blank_run += 1;
+            if blank_run > 1 || out.is_empty() {
+                continue;
+            }
+            out.push('\n');
+            continue;
+        }
+        blank_run = 0;
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim_end().to_string() + "\n"
+}
+
+/// Convert an HTML document or fragment to Markdown
+pub fn html_to_markdown(html: &str, rewrite: &mut dyn FnMut(Reference) -> Option<String>) -> Conversion {
+    let document = Html::parse_document(html);
+    let mut writer = Writer {
+        rewrite,
+        unsupported: BTreeSet::new(),
+        list_depth: 0,
+    };
+
+    // Notion wraps the page in <article>, with its title in a <header>
+    let body_selector = scraper::Selector::parse("body").expect("valid selector");
+    let root = document.select(&body_selector).next().unwrap_or_else(|| document.root_element());
+    let markdown = writer.element(root);
+
+    Conversion {
+        markdown: clean_up(&markdown),
+        unsupported: writer.unsupported,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(html: &str) -> String {
+        html_to_markdown(html, &mut |_| None).markdown
+    }
+
+    #[test]
+    fn test_basic_blocks() {
+        let md = convert("<h1>Title</h1><p>Some <strong>bold</strong> and <em>italic </em>text.</p><hr><pre><code class=\"language-rust\">fn main() {}\n</code></pre>");
+        assert_eq!(md, "# Title\n\nSome **bold** and *italic* text.\n\n---\n\n```rust\nfn main() {}\n```\n");
+    }
+
+    #[test]
+    fn test_nested_lists_and_todos() {
+        let md = convert("<ul><li>One<ul><li>Nested</li></ul></li><li>Two</li></ul><ol><li>First</li><li>Second</li></ol><div><en-todo checked=\"true\"/>Done</div>");
+        assert_eq!(md, "- One\n  - Nested\n- Two\n\n1. First\n2. Second\n\n[x] Done\n");
+    }
+
+    #[test]
+    fn test_table_and_quote() {
+        let md = convert("<table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>x|y</td></tr></table><blockquote><p>Quoted</p></blockquote>");
+        assert_eq!(md, "| A | B |\n| --- | --- |\n| 1 | x\\|y |\n\n> Quoted\n");
+    }
+
+    #[test]
+    fn test_references_are_rewritten() {
+        let mut seen = Vec::new();
+        let conversion = html_to_markdown(
+            "<p><a href=\"Other%20abc.html\">Other</a> <img src=\"pic.png\" alt=\"pic\"><en-media hash=\"ff\" type=\"image/png\"/></p><svg></svg>",
+            &mut |reference| match reference {
+                Reference::Link(href) => {
+                    seen.push(href.to_string());
+                    Some("Other.md".to_string())
+                }
+                Reference::Image(_) => Some("assets/pic.png".to_string()),
+                Reference::Media { hash, .. } => Some(format!("attachments/{}.png", hash)),
+            },
+        );
+        assert_eq!(conversion.markdown, "[Other](Other.md) ![pic](assets/pic.png)![](attachments/ff.png)\n");
+        assert_eq!(seen, vec!["Other%20abc.html"]);
+        assert!(conversion.unsupported.contains("svg"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Importers for other note apps.
+//!
+//! Each importer converts a foreign export into workspace markdown inside a new
+//! folder under the chosen target, keeping the source's folder structure and
+//! copying attachments alongside. Everything goes through an `ImportSink`, so a
+//! dry run follows exactly the same path and only skips the writes; the
+//! resulting report lists what would be created and what could not be
+//! converted.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+pub mod enex;
+pub mod html;
+pub mod notion;
+pub mod obsidian;
+
+const PROGRESS_EVENT: &str = "import-progress";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    /// "obsidian", "notion" or "enex"
+    pub source: String,
+    pub current: usize,
+    pub total: usize,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportIssue {
+    /// Source path (or note title) the issue relates to
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Folder the import was (or would be) written to
+    pub output_folder: String,
+    pub notes: usize,
+    pub attachments: usize,
+    /// Workspace-relative paths of everything created, relative to `output_folder`
+    pub created: Vec<String>,
+    /// Content that was dropped or only partly converted
+    pub issues: Vec<ImportIssue>,
+}
+
+/// Make a name safe to use as a file or folder name on every platform
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let trimmed = cleaned.trim().trim_matches('.').trim();
+    if trimmed.is_empty() {
+        "Untitled".to_string()
+    } else {
+        trimmed.chars().take(120).collect()
+    }
+}
+
+/// Collects the output of an import, writing it unless this is a dry run
+pub(crate) struct ImportSink {
+    root: PathBuf,
+    dry_run: bool,
+    planned: HashSet<String>,
+    report: ImportReport,
+}
+
+impl ImportSink {
+    /// Create a sink writing into a new folder called `name` under `target`
+    pub(crate) fn new(target: &Path, name: &str, dry_run: bool) -> Result<Self, String> {
+        if !target.is_dir() {
+            return Err(format!("Target folder does not exist: {}", target.display()));
+        }
+        let base = sanitize_file_name(name);
+        let root = (1..)
+            .map(|n| if n == 1 { target.join(&base) } else { target.join(format!("{} {}", base, n)) })
+            .find(|candidate| !candidate.exists())
+            .expect("unbounded candidates");
+        Ok(Self {
+            report: ImportReport {
+                dry_run,
+                output_folder: root.to_string_lossy().to_string(),
+                ..Default::default()
+            },
+            root,
+            dry_run,
+            planned: HashSet::new(),
+        })
+    }
+
+    /// Reserve `rel` (a '/'-separated path), adding a number if it is taken
+    pub(crate) fn reserve(&mut self, rel: &str) -> String {
+        let (dir, file) = match rel.rsplit_once('/') {
+            Some((dir, file)) => (format!("{}/", dir), file),
+            None => (String::new(), rel),
+        };
+        let (stem, ext) = match file.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+            _ => (file, String::new()),
+        };
+        let unique = (1..)
+            .map(|n| {
+                if n == 1 {
+                    format!("{}{}{}", dir, stem, ext)
+                } else {
+                    format!("{}{} {}{}", dir, stem, n, ext)
+                }
+            })
+            .find(|candidate| !self.planned.contains(&candidate.to_lowercase()))
+            .expect("unbounded candidates");
+        self.planned.insert(unique.to_lowercase());
+        unique
+    }
+
+    fn write(&mut self, rel: &str, data: &[u8]) -> Result<(), String> {
+        self.report.created.push(rel.to_string());
+        if self.dry_run {
+            return Ok(());
+        }
+        let path = self.root.join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Write a note at a path previously returned by `reserve`
+    pub(crate) fn write_note(&mut self, rel: &str, markdown: &str) -> Result<(), String> {
+        self.report.notes += 1;
+        self.write(rel, markdown.as_bytes())?;
+        if !self.dry_run {
+            let path = self.root.join(rel).to_string_lossy().to_string();
+            crate::search::index::notify_file_saved(&path, markdown);
+            crate::links::notify_file_saved(&path, markdown);
+            crate::metadata_cache::notify_file_saved(&path, markdown);
+        }
+        Ok(())
+    }
+
+    /// Write an attachment at a path previously returned by `reserve`
+    pub(crate) fn write_attachment(&mut self, rel: &str, data: &[u8]) -> Result<(), String> {
+        self.report.attachments += 1;
+        self.write(rel, data)
+    }
+
+    pub(crate) fn issue(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.report.issues.push(ImportIssue {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    pub(crate) fn finish(self) -> ImportReport {
+        self.report
+    }
+}
+
+fn progress_reporter(app: AppHandle, source: &'static str) -> impl FnMut(usize, usize, Option<&str>) {
+    move |current, total, path| {
+        let _ = app.emit(
+            PROGRESS_EVENT,
+            ImportProgress {
+                source: source.to_string(),
+                current,
+                total,
+                path: path.map(|p| p.to_string()),
+            },
+        );
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Import an Obsidian vault folder into `target_folder`
+#[tauri::command]
+pub async fn import_obsidian_vault(
+    app: AppHandle,
+    path: String,
+    target_folder: String,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    let progress = progress_reporter(app, "obsidian");
+    tokio::task::spawn_blocking(move || {
+        obsidian::import(Path::new(&path), Path::new(&target_folder), dry_run.unwrap_or(false), progress)
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+/// Import a Notion "Markdown & CSV" or "HTML" export zip into `target_folder`
+#[tauri::command]
+pub async fn import_notion_export(
+    app: AppHandle,
+    zip: String,
+    target_folder: String,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    let progress = progress_reporter(app, "notion");
+    tokio::task::spawn_blocking(move || {
+        notion::import(Path::new(&zip), Path::new(&target_folder), dry_run.unwrap_or(false), progress)
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+/// Import an Evernote `.enex` export into `target_folder`
+#[tauri::command]
+pub async fn import_enex(
+    app: AppHandle,
+    file: String,
+    target_folder: String,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    let progress = progress_reporter(app, "enex");
+    tokio::task::spawn_blocking(move || {
+        enex::import(Path::new(&file), Path::new(&target_folder), dry_run.unwrap_or(false), progress)
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("a/b: c?"), "a-b- c-");
+        assert_eq!(sanitize_file_name("  ..  "), "Untitled");
+    }
+
+    #[test]
+    fn test_reserve_deduplicates_case_insensitively() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = ImportSink::new(dir.path(), "Import", true).unwrap();
+        assert_eq!(sink.reserve("Notes/Todo.md"), "Notes/Todo.md");
+        assert_eq!(sink.reserve("Notes/todo.md"), "Notes/todo 2.md");
+        assert_eq!(sink.reserve("README"), "README");
+    }
+
+    #[test]
+    fn test_dry_run_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = ImportSink::new(dir.path(), "Import", true).unwrap();
+        let rel = sink.reserve("a.md");
+        sink.write_note(&rel, "# A").unwrap();
+        let report = sink.finish();
+        assert_eq!(report.notes, 1);
+        assert_eq!(report.created, vec!["a.md"]);
+        assert!(!dir.path().join("Import").exists());
+    }
+}
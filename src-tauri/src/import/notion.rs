@@ -0,0 +1,261 @@
+//! Notion export import.
+//!
+//! Handles both of Notion's export flavours: "Markdown & CSV" and "HTML".
+//! Notion appends a 32-character id to every page and folder name; those are
+//! stripped, and links between pages (which point at the id'd names) are
+//! rewritten to the new paths. HTML pages are converted to markdown. Database
+//! CSVs are kept as files, with their rows already exported as pages.
+//! Large exports arrive as a zip of zips, which is unpacked in memory.
+
+use super::html::{html_to_markdown, Reference};
+use super::ImportSink;
+use crate::links;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+static NOTION_ID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+[0-9a-f]{32}$").unwrap());
+
+// ![alt](src) and [label](href)
+static MD_LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(!?)\[([^\]]*)\]\(([^)]+)\)").unwrap());
+
+/// Strip the Notion id from one path component, keeping any extension
+fn clean_component(component: &str, is_file: bool) -> String {
+    let (stem, ext) = match component.rsplit_once('.') {
+        Some((stem, ext)) if is_file && !stem.is_empty() => (stem, Some(ext)),
+        _ => (component, None),
+    };
+    let stem = super::sanitize_file_name(&NOTION_ID_RE.replace(stem, ""));
+    match ext {
+        Some("html") => format!("{}.md", stem),
+        Some(ext) => format!("{}.{}", stem, ext),
+        None => stem,
+    }
+}
+
+fn clean_path(path: &str) -> String {
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| clean_component(part, i == parts.len() - 1))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Maps paths inside the export to paths in the imported folder
+struct PathMap {
+    paths: HashMap<String, String>,
+}
+
+impl PathMap {
+    /// Resolve a link found in `source` (an export path) to its new path
+    fn resolve(&self, source: &str, href: &str) -> Option<&String> {
+        if href.contains("://") || href.starts_with('#') || href.starts_with("mailto:") {
+            return None;
+        }
+        let target = href.split('#').next().unwrap_or(href);
+        let decoded = urlencoding::decode(target).map(|s| s.into_owned()).unwrap_or_else(|_| target.to_string());
+        let joined = match links::parent_dir(source) {
+            "" => decoded,
+            dir => format!("{}/{}", dir, decoded),
+        };
+        self.paths.get(&links::normalize(&joined)?)
+    }
+
+    /// The link text to use from `new_source` to `target`
+    fn href(new_source: &str, target: &str) -> String {
+        links::relative_link(links::parent_dir(new_source), target)
+            .split('/')
+            .map(|segment| if segment == ".." { segment.to_string() } else { urlencoding::encode(segment).into_owned() })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+fn rewrite_markdown(content: &str, source: &str, new_source: &str, map: &PathMap, sink: &mut ImportSink) -> String {
+    let mut unresolved = 0;
+    let rewritten = MD_LINK_RE.replace_all(content, |caps: &Captures| {
+        let href = caps[3].trim();
+        if href.contains("://") || href.starts_with('#') || href.starts_with("mailto:") {
+            return caps[0].to_string();
+        }
+        match map.resolve(source, href) {
+            Some(target) => format!("{}[{}]({})", &caps[1], &caps[2], PathMap::href(new_source, target)),
+            None => {
+                unresolved += 1;
+                caps[0].to_string()
+            }
+        }
+    });
+    if unresolved > 0 {
+        sink.issue(new_source, format!("{} link(s) point outside the export and were left unchanged", unresolved));
+    }
+    rewritten.into_owned()
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, index: usize) -> Result<Vec<u8>, String> {
+    let mut file = archive.by_index(index).map_err(|e| format!("Failed to read zip entry: {}", e))?;
+    let mut data = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut data).map_err(|e| format!("Failed to read zip entry: {}", e))?;
+    Ok(data)
+}
+
+fn import_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    sink: &mut ImportSink,
+    progress: &mut impl FnMut(usize, usize, Option<&str>),
+) -> Result<(), String> {
+    let names: Vec<(usize, String)> = (0..archive.len())
+        .filter_map(|i| archive.name_for_index(i).map(|name| (i, name.to_string())))
+        .filter(|(_, name)| !name.ends_with('/') && !name.starts_with("__MACOSX/"))
+        .collect();
+
+    // Zip of zips: import each part into the same folder
+    if !names.is_empty() && names.iter().all(|(_, name)| name.to_lowercase().ends_with(".zip")) {
+        for (index, name) in &names {
+            let data = read_entry(archive, *index)?;
+            let mut inner = ZipArchive::new(Cursor::new(data)).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+            import_archive(&mut inner, sink, progress)?;
+        }
+        return Ok(());
+    }
+
+    let mut map = PathMap { paths: HashMap::new() };
+    for (_, name) in &names {
+        let new_path = sink.reserve(&clean_path(name));
+        map.paths.insert(name.clone(), new_path);
+    }
+
+    for (position, (index, name)) in names.iter().enumerate() {
+        progress(position + 1, names.len(), Some(name));
+        let new_path = map.paths[name].clone();
+        let data = read_entry(archive, *index)?;
+        let lower = name.to_lowercase();
+
+        if lower.ends_with(".md") {
+            let content = String::from_utf8_lossy(&data);
+            let markdown = rewrite_markdown(&content, name, &new_path, &map, sink);
+            sink.write_note(&new_path, &markdown)?;
+        } else if lower.ends_with(".html") {
+            let html = String::from_utf8_lossy(&data);
+            let mut unresolved = 0;
+            let conversion = html_to_markdown(&html, &mut |reference| {
+                let href = match reference {
+                    Reference::Link(href) | Reference::Image(href) => href,
+                    Reference::Media { .. } => return None,
+                };
+                if href.contains("://") || href.starts_with('#') || href.starts_with("mailto:") {
+                    return None;
+                }
+                let resolved = map.resolve(name, href).map(|target| PathMap::href(&new_path, target));
+                if resolved.is_none() {
+                    unresolved += 1;
+                }
+                resolved
+            });
+            if unresolved > 0 {
+                sink.issue(&new_path, format!("{} link(s) point outside the export and were left unchanged", unresolved));
+            }
+            for tag in &conversion.unsupported {
+                sink.issue(&new_path, format!("Unsupported content dropped: <{}>", tag));
+            }
+            sink.write_note(&new_path, &conversion.markdown)?;
+        } else {
+            if lower.ends_with(".csv") {
+                sink.issue(&new_path, "Database kept as CSV; its rows were imported as separate pages");
+            }
+            sink.write_attachment(&new_path, &data)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn import(
+    zip_path: &Path,
+    target: &Path,
+    dry_run: bool,
+    mut progress: impl FnMut(usize, usize, Option<&str>),
+) -> Result<super::ImportReport, String> {
+    let file = File::open(zip_path).map_err(|e| format!("Failed to open {}: {}", zip_path.display(), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a valid Notion export: {}", e))?;
+    let name = zip_path
+        .file_stem()
+        .map(|s| clean_component(&s.to_string_lossy(), false))
+        .unwrap_or_else(|| "Notion".to_string());
+
+    let mut sink = ImportSink::new(target, &name, dry_run)?;
+    import_archive(&mut archive, &mut sink, &mut progress)?;
+    Ok(sink.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn build_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_clean_path_strips_ids() {
+        assert_eq!(
+            clean_path("Projects 0123456789abcdef0123456789abcdef/Plan 0123456789abcdef0123456789abcdef.html"),
+            "Projects/Plan.md"
+        );
+        assert_eq!(clean_path("Tasks 0123456789abcdef0123456789abcdef.csv"), "Tasks.csv");
+    }
+
+    #[test]
+    fn test_markdown_export_links_are_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("Export.zip");
+        let id = "0123456789abcdef0123456789abcdef";
+        build_zip(
+            &zip_path,
+            &[
+                (&format!("Home {}.md", id), &format!("# Home\n\n[Plan](Home%20{id}/Plan%20{id}.md) ![](Home%20{id}/pic.png) [web](https://notion.so)", id = id)),
+                (&format!("Home {}/Plan {}.md", id, id), "# Plan"),
+                (&format!("Home {}/pic.png", id), "png"),
+            ],
+        );
+
+        let target = tempfile::tempdir().unwrap();
+        let report = import(&zip_path, target.path(), false, |_, _, _| {}).unwrap();
+        assert_eq!(report.notes, 2);
+        assert_eq!(report.attachments, 1);
+
+        let home = fs::read_to_string(Path::new(&report.output_folder).join("Home.md")).unwrap();
+        assert_eq!(home, "# Home\n\n[Plan](Home/Plan.md) ![](Home/pic.png) [web](https://notion.so)");
+    }
+
+    #[test]
+    fn test_html_export_is_converted() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("Export.zip");
+        let id = "0123456789abcdef0123456789abcdef";
+        build_zip(
+            &zip_path,
+            &[
+                (&format!("Home {}.html", id), &format!("<html><body><article><h1>Home</h1><p><a href=\"Other%20{}.html\">Other</a></p></article></body></html>", id)),
+                (&format!("Other {}.html", id), "<p>Hi</p>"),
+            ],
+        );
+
+        let target = tempfile::tempdir().unwrap();
+        let report = import(&zip_path, target.path(), true, |_, _, _| {}).unwrap();
+        assert_eq!(report.created, vec!["Home.md", "Other.md"]);
+        assert!(report.issues.is_empty());
+    }
+}
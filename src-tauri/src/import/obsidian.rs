@@ -0,0 +1,133 @@
+//! Obsidian vault import.
+//!
+//! Obsidian notes are already markdown with wikilinks that Lokus resolves the
+//! same way, so the vault is copied as-is apart from a few syntax conversions:
+//! `%% comments %%` become HTML comments, and plugin-specific content that
+//! Lokus cannot run (Dataview queries, Canvas and Excalidraw files) is
+//! reported. The `.obsidian` config folder is not imported.
+
+use super::ImportSink;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+const SKIPPED_DIRS: &[&str] = &[".obsidian", ".trash", ".git", ".lokus", "node_modules"];
+
+static COMMENT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)%%(.*?)%%").unwrap());
+
+/// Convert Obsidian-only syntax; returns the note and any issues found
+fn convert_note(content: &str) -> (String, Vec<String>) {
+    let mut issues = Vec::new();
+    let mut output = String::with_capacity(content.len());
+    let mut in_fence = false;
+    let mut outside = String::new();
+
+    let flush = |outside: &mut String, output: &mut String| {
+        output.push_str(&COMMENT_RE.replace_all(outside, "<!--$1-->"));
+        outside.clear();
+    };
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if !in_fence {
+                flush(&mut outside, &mut output);
+                let language = trimmed.trim_start_matches(&['`', '~'][..]).trim();
+                if matches!(language, "dataview" | "dataviewjs" | "tasks" | "query") {
+                    issues.push(format!("`{}` block kept as a code block; queries are not evaluated", language));
+                }
+            }
+            in_fence = !in_fence;
+            output.push_str(line);
+        } else if in_fence {
+            output.push_str(line);
+        } else {
+            outside.push_str(line);
+        }
+    }
+    flush(&mut outside, &mut output);
+    (output, issues)
+}
+
+pub fn import(
+    vault: &Path,
+    target: &Path,
+    dry_run: bool,
+    mut progress: impl FnMut(usize, usize, Option<&str>),
+) -> Result<super::ImportReport, String> {
+    if !vault.is_dir() {
+        return Err(format!("Vault folder does not exist: {}", vault.display()));
+    }
+    let vault_name = vault
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Obsidian".to_string());
+    let mut sink = ImportSink::new(target, &vault_name, dry_run)?;
+
+    let files: Vec<_> = WalkDir::new(vault)
+        .into_iter()
+        .filter_entry(|e| e.file_name().to_str().map_or(true, |n| !SKIPPED_DIRS.contains(&n)))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .collect();
+
+    for (index, entry) in files.iter().enumerate() {
+        let Some(rel) = crate::links::relative_path(vault, entry.path()) else { continue };
+        progress(index + 1, files.len(), Some(&rel));
+        let rel = sink.reserve(&rel);
+
+        if crate::links::is_note(&rel) {
+            let content = fs::read_to_string(entry.path()).map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+            let (converted, issues) = convert_note(&content);
+            if rel.ends_with(".excalidraw.md") {
+                sink.issue(&rel, "Excalidraw drawing imported as its markdown source");
+            }
+            for issue in issues {
+                sink.issue(&rel, issue);
+            }
+            sink.write_note(&rel, &converted)?;
+        } else {
+            if rel.ends_with(".canvas") {
+                sink.issue(&rel, "Canvas file copied but not converted");
+            }
+            let data = fs::read(entry.path()).map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+            sink.write_attachment(&rel, &data)?;
+        }
+    }
+
+    Ok(sink.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_comments_and_queries() {
+        let (converted, issues) = convert_note("Text %%hidden%% more\n```dataview\nLIST %%x%%\n```\n");
+        assert_eq!(converted, "Text <!--hidden--> more\n```dataview\nLIST %%x%%\n```\n");
+        assert_eq!(issues.len(), 1);
+    }
+
+    #[test]
+    fn test_import_copies_structure_and_skips_config() {
+        let vault = tempfile::tempdir().unwrap();
+        fs::create_dir_all(vault.path().join(".obsidian")).unwrap();
+        fs::write(vault.path().join(".obsidian/app.json"), "{}").unwrap();
+        fs::create_dir_all(vault.path().join("Daily")).unwrap();
+        fs::write(vault.path().join("Daily/today.md"), "See [[Home]]").unwrap();
+        fs::write(vault.path().join("board.canvas"), "{}").unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let report = import(vault.path(), target.path(), false, |_, _, _| {}).unwrap();
+
+        assert_eq!(report.notes, 1);
+        assert_eq!(report.attachments, 1);
+        assert_eq!(report.issues.len(), 1);
+        let out = Path::new(&report.output_folder);
+        assert_eq!(fs::read_to_string(out.join("Daily/today.md")).unwrap(), "See [[Home]]");
+        assert!(!out.join(".obsidian").exists());
+    }
+}
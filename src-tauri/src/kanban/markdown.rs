@@ -0,0 +1,372 @@
+//! Markdown board format, compatible with the Obsidian Kanban plugin.
+//!
+//! ```markdown
+//! ---
+//!
+//! kanban-plugin: basic
+//!
+//! ---
+//!
+//! ## To Do
+//!
+//! - [ ] Write docs #writing @{2024-05-01}
+//! 	Longer description
+//! 	- [ ] checklist item
+//! 	[[Linked Note]]
+//! 	%% lokus: {"id":"…","priority":"high",…} %%
+//!
+//! %% kanban:settings
+//! ```
+//! {"kanban-plugin":"basic","lokus":{…}}
+//! ```
+//! %%
+//! ```
+//!
+//! Columns are `##` headings and cards are checklist items whose indented
+//! lines hold the description, checklist and linked notes. Anything the
+//! readable form can't carry exactly (card ids, priority, timestamps, column
+//! ids, board settings) goes into hidden `%% … %%` comments, so a board saved
+//! here reloads identical to what was saved.
+
+use super::{BoardMetadata, BoardSettings, ChecklistItem, KanbanBoard, KanbanCard, KanbanColumn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+const SETTINGS_START: &str = "%% kanban:settings";
+const CARD_META_PREFIX: &str = "%% lokus:";
+
+/// Whether file content is a markdown board rather than the JSON format
+pub fn is_markdown_board(content: &str) -> bool {
+    !content.trim_start().starts_with('{')
+}
+
+/// Board-level data that has no place in the visible markdown
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BoardExtras {
+    version: Option<String>,
+    name: Option<String>,
+    /// Column ids and orders, in heading order
+    columns: Vec<ColumnExtras>,
+    settings: Option<BoardSettings>,
+    metadata: Option<BoardMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ColumnExtras {
+    id: String,
+    order: i32,
+}
+
+fn column_id_for(name: &str) -> String {
+    name.to_lowercase().replace(' ', "-")
+}
+
+/// Readable parts of a card line: `title #tag @{date}`
+fn card_line(card: &KanbanCard) -> String {
+    let mut line = card.title.clone();
+    for tag in &card.tags {
+        line.push_str(&format!(" #{}", tag));
+    }
+    if let Some(date) = card.due_date.as_deref().and_then(|d| d.get(..10)) {
+        line.push_str(&format!(" @{{{}}}", date));
+    }
+    line
+}
+
+/// Split a card line back into title, tags and due date
+fn parse_card_line(line: &str) -> (String, Vec<String>, Option<String>) {
+    let mut rest = line.trim_end().to_string();
+    let mut due = None;
+    if rest.ends_with('}') {
+        if let Some(start) = rest.rfind(" @{") {
+            due = Some(rest[start + 3..rest.len() - 1].to_string());
+            rest.truncate(start);
+        }
+    }
+
+    let mut tags = Vec::new();
+    loop {
+        let Some(start) = rest.rfind(" #") else { break };
+        let tag = &rest[start + 2..];
+        if tag.is_empty() || tag.contains(char::is_whitespace) {
+            break;
+        }
+        tags.insert(0, tag.to_string());
+        rest.truncate(start);
+    }
+    (rest.trim().to_string(), tags, due)
+}
+
+fn is_checklist_line(line: &str) -> Option<ChecklistItem> {
+    let rest = line.strip_prefix("- [")?;
+    let mut chars = rest.chars();
+    let mark = chars.next()?;
+    let text = chars.as_str().strip_prefix("] ")?;
+    Some(ChecklistItem {
+        text: text.to_string(),
+        completed: mark == 'x' || mark == 'X',
+    })
+}
+
+fn is_link_line(line: &str) -> Option<String> {
+    let inner = line.strip_prefix("[[")?.strip_suffix("]]")?;
+    (!inner.is_empty() && !inner.contains("]]")).then(|| inner.to_string())
+}
+
+/// Lines nested under a card
+fn card_body(card: &KanbanCard) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(description) = &card.description {
+        lines.extend(description.lines().map(str::to_string));
+    }
+    for item in &card.checklist {
+        lines.push(format!("- [{}] {}", if item.completed { "x" } else { " " }, item.text));
+    }
+    for note in &card.linked_notes {
+        lines.push(format!("[[{}]]", note));
+    }
+    lines
+}
+
+fn parse_card(line: &str, body: &[String]) -> Result<KanbanCard, String> {
+    let text = line
+        .get(6..)
+        .ok_or_else(|| format!("Invalid card line: {}", line))?;
+    let (title, tags, due) = parse_card_line(text);
+
+    let mut card = KanbanCard {
+        id: String::new(),
+        title,
+        description: None,
+        tags,
+        assignee: None,
+        priority: String::from("normal"),
+        due_date: due,
+        linked_notes: Vec::new(),
+        checklist: Vec::new(),
+        created: String::new(),
+        modified: String::new(),
+        completed_at: None,
+    };
+
+    let mut description: Vec<&str> = Vec::new();
+    let mut meta: Option<Map<String, Value>> = None;
+    for line in body {
+        if let Some(raw) = line.strip_prefix(CARD_META_PREFIX).and_then(|r| r.strip_suffix("%%")) {
+            meta = serde_json::from_str(raw.trim()).map_err(|e| format!("Invalid card metadata: {}", e))?;
+        } else if let Some(item) = is_checklist_line(line) {
+            card.checklist.push(item);
+        } else if let Some(note) = is_link_line(line) {
+            card.linked_notes.push(note);
+        } else {
+            description.push(line);
+        }
+    }
+    if !description.is_empty() {
+        card.description = Some(description.join("\n"));
+    }
+
+    // Metadata overrides whatever the readable form could not express exactly
+    if let Some(meta) = meta {
+        let mut value = serde_json::to_value(&card).map_err(|e| e.to_string())?;
+        if let Some(object) = value.as_object_mut() {
+            object.extend(meta);
+        }
+        card = serde_json::from_value(value).map_err(|e| format!("Invalid card metadata: {}", e))?;
+    }
+    if card.id.is_empty() {
+        card.id = uuid::Uuid::new_v4().to_string();
+    }
+    if card.created.is_empty() {
+        card.created = chrono::Utc::now().to_rfc3339();
+    }
+    if card.modified.is_empty() {
+        card.modified = card.created.clone();
+    }
+    Ok(card)
+}
+
+/// Fields of `card` that don't survive the readable form
+fn card_meta(card: &KanbanCard) -> Result<Map<String, Value>, String> {
+    let body = card_body(card);
+    let reparsed = parse_card(&format!("- [ ] {}", card_line(card)), &body)?;
+    let original = serde_json::to_value(card).map_err(|e| e.to_string())?;
+    let reparsed = serde_json::to_value(&reparsed).map_err(|e| e.to_string())?;
+
+    let mut meta = Map::new();
+    meta.insert("id".to_string(), json!(card.id));
+    if let (Some(original), Some(reparsed)) = (original.as_object(), reparsed.as_object()) {
+        for (key, value) in original {
+            if key != "id" && reparsed.get(key) != Some(value) {
+                meta.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    Ok(meta)
+}
+
+fn sorted_columns(board: &KanbanBoard) -> Vec<(&String, &KanbanColumn)> {
+    let mut columns: Vec<_> = board.columns.iter().collect();
+    columns.sort_by(|a, b| a.1.order.cmp(&b.1.order).then_with(|| a.0.cmp(b.0)));
+    columns
+}
+
+pub fn to_markdown(board: &KanbanBoard) -> Result<String, String> {
+    let mut out = String::from("---\n\nkanban-plugin: basic\n\n---\n\n");
+    let columns = sorted_columns(board);
+
+    for (_, column) in &columns {
+        out.push_str(&format!("## {}\n\n", column.name));
+        for card in &column.cards {
+            out.push_str(&format!("- [ ] {}\n", card_line(card)));
+            for line in card_body(card) {
+                out.push_str(&format!("\t{}\n", line));
+            }
+            let meta = serde_json::to_string(&card_meta(card)?).map_err(|e| e.to_string())?;
+            out.push_str(&format!("\t{} {} %%\n", CARD_META_PREFIX, meta));
+        }
+        out.push('\n');
+    }
+
+    let extras = BoardExtras {
+        version: Some(board.version.clone()),
+        name: Some(board.name.clone()),
+        columns: columns
+            .iter()
+            .map(|(id, column)| ColumnExtras { id: (*id).clone(), order: column.order })
+            .collect(),
+        settings: Some(board.settings.clone()),
+        metadata: Some(board.metadata.clone()),
+    };
+    let settings = json!({ "kanban-plugin": "basic", "lokus": extras });
+    out.push_str(&format!(
+        "\n{}\n```\n{}\n```\n%%\n",
+        SETTINGS_START,
+        serde_json::to_string(&settings).map_err(|e| e.to_string())?
+    ));
+    Ok(out)
+}
+
+/// Parse a markdown board. `fallback_name` (usually the file stem) is used when
+/// the file carries no Lokus settings, e.g. boards created in Obsidian.
+pub fn from_markdown(content: &str, fallback_name: &str) -> Result<KanbanBoard, String> {
+    let mut extras = BoardExtras::default();
+    let mut body = content;
+
+    // Settings block at the end
+    if let Some(start) = content.rfind(SETTINGS_START) {
+        let block = &content[start + SETTINGS_START.len()..];
+        if let Some(json_start) = block.find('{') {
+            let json_end = block.rfind('}').unwrap_or(block.len() - 1);
+            if let Ok(settings) = serde_json::from_str::<Value>(&block[json_start..=json_end]) {
+                if let Some(lokus) = settings.get("lokus") {
+                    extras = serde_json::from_value(lokus.clone()).unwrap_or_default();
+                }
+            }
+        }
+        body = &content[..start];
+    }
+
+    // Frontmatter
+    let trimmed = body.trim_start();
+    if let Some(rest) = trimmed.strip_prefix("---") {
+        if let Some(end) = rest.find("\n---") {
+            body = &rest[end + 4..];
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut columns: Vec<(String, Vec<KanbanCard>)> = Vec::new();
+    let lines: Vec<&str> = body.lines().collect();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(name) = line.strip_prefix("## ") {
+            columns.push((name.trim().to_string(), Vec::new()));
+        } else if line.starts_with("- [") && line.len() >= 6 {
+            let mut card_lines = Vec::new();
+            while i + 1 < lines.len() && (lines[i + 1].starts_with('\t') || lines[i + 1].starts_with("    ")) {
+                i += 1;
+                let nested = lines[i];
+                card_lines.push(nested.strip_prefix('\t').unwrap_or_else(|| &nested[4..]).to_string());
+            }
+            let card = parse_card(line, &card_lines)?;
+            match columns.last_mut() {
+                Some((_, cards)) => cards.push(card),
+                None => return Err("Card found before any column heading".to_string()),
+            }
+        }
+        // Blank lines and Obsidian lane markers like **Complete** are ignored
+        i += 1;
+    }
+
+    let mut column_map = HashMap::new();
+    for (index, (name, cards)) in columns.into_iter().enumerate() {
+        let (id, order) = match extras.columns.get(index) {
+            Some(c) => (c.id.clone(), c.order),
+            None => (column_id_for(&name), index as i32),
+        };
+        column_map.insert(id, KanbanColumn { name, order, cards });
+    }
+
+    Ok(KanbanBoard {
+        version: extras.version.unwrap_or_else(|| String::from("1.0.0")),
+        name: extras.name.unwrap_or_else(|| fallback_name.to_string()),
+        columns: column_map,
+        settings: extras.settings.unwrap_or_default(),
+        metadata: extras.metadata.unwrap_or(BoardMetadata {
+            created: now.clone(),
+            modified: now,
+            created_with: String::from("Lokus"),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_board() -> KanbanBoard {
+        let mut board = KanbanBoard::new("Roadmap".into(), vec!["To Do".into(), "In Progress".into(), "Done".into()]);
+        let mut card = KanbanCard::new("Write docs".into());
+        card.tags = vec!["writing".into(), "q2".into()];
+        card.priority = "high".into();
+        card.assignee = Some("sam".into());
+        card.due_date = Some("2024-05-01T10:00:00Z".into());
+        card.description = Some("First line\n- [ ] looks like a checklist".into());
+        card.checklist = vec![ChecklistItem { text: "outline".into(), completed: true }];
+        card.linked_notes = vec!["Specs/API".into()];
+        board.add_card("to-do", card).unwrap();
+        board.add_card("done", KanbanCard::new("Title ending in #hash".into())).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_round_trip_is_lossless() {
+        let board = sample_board();
+        let markdown = to_markdown(&board).unwrap();
+        assert!(markdown.contains("## To Do\n\n- [ ] Write docs #writing #q2 @{2024-05-01}\n"));
+        assert!(markdown.contains("\t[[Specs/API]]\n"));
+
+        let parsed = from_markdown(&markdown, "ignored").unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&board).unwrap());
+    }
+
+    #[test]
+    fn test_parses_obsidian_board_without_settings() {
+        let markdown = "---\n\nkanban-plugin: basic\n\n---\n\n## Backlog\n\n- [ ] Fix login #bug @{2024-06-01}\n\t[[Auth]]\n\n## Done\n\n**Complete**\n- [x] Ship v1\n";
+        let board = from_markdown(markdown, "Work").unwrap();
+        assert_eq!(board.name, "Work");
+        let backlog = &board.columns["backlog"];
+        assert_eq!(backlog.order, 0);
+        assert_eq!(backlog.cards[0].title, "Fix login");
+        assert_eq!(backlog.cards[0].tags, vec!["bug"]);
+        assert_eq!(backlog.cards[0].due_date.as_deref(), Some("2024-06-01"));
+        assert_eq!(backlog.cards[0].linked_notes, vec!["Auth"]);
+        assert_eq!(board.columns["done"].cards[0].title, "Ship v1");
+        assert!(is_markdown_board(markdown));
+        assert!(!is_markdown_board("  {\"version\": \"1.0.0\"}"));
+    }
+}
@@ -1,3 +1,6 @@
+pub mod markdown;
+pub mod rules;
+
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -15,6 +18,8 @@ pub struct KanbanCard {
     pub checklist: Vec<ChecklistItem>,
     pub created: String,
     pub modified: String,
+    #[serde(default)]
+    pub completed_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +52,8 @@ pub struct BoardSettings {
     pub automations: Vec<String>,
     #[serde(default)]
     pub custom_fields: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<rules::AutomationRule>,
 }
 
 impl Default for BoardSettings {
@@ -55,6 +62,7 @@ impl Default for BoardSettings {
             card_template: HashMap::new(),
             automations: Vec::new(),
             custom_fields: Vec::new(),
+            rules: Vec::new(),
         }
     }
 }
@@ -90,6 +98,7 @@ impl KanbanCard {
             checklist: Vec::new(),
             created: now.clone(),
             modified: now,
+            completed_at: None,
         }
     }
 }
@@ -181,19 +190,77 @@ impl KanbanBoard {
     }
 }
 
+fn is_markdown_path(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("md"))
+}
+
+fn board_display_name(file_path: &Path) -> String {
+    file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Board")
+        .to_string()
+}
+
+/// Whether a markdown note is an (Obsidian-compatible) kanban board
+async fn is_markdown_board_file(file_path: &Path) -> bool {
+    match tokio::fs::read_to_string(file_path).await {
+        Ok(content) => {
+            let head: String = content.chars().take(200).collect();
+            head.trim_start().starts_with("---") && head.contains("kanban-plugin")
+        }
+        Err(_) => false,
+    }
+}
+
 // File I/O operations
 pub async fn load_board_from_file(file_path: &Path) -> Result<KanbanBoard, String> {
     let content = tokio::fs::read_to_string(file_path)
         .await
         .map_err(|e| format!("Failed to read board file: {}", e))?;
 
+    if markdown::is_markdown_board(&content) {
+        return markdown::from_markdown(&content, &board_display_name(file_path));
+    }
     serde_json::from_str(&content)
         .map_err(|e| format!("Failed to parse board JSON: {}", e))
 }
 
+/// Save a board, keeping the file's format: `.md` files and `.kanban` files that
+/// already hold markdown are written as markdown, everything else as JSON
 pub async fn save_board_to_file(file_path: &Path, board: &KanbanBoard) -> Result<(), String> {
-    let content = serde_json::to_string_pretty(board)
-        .map_err(|e| format!("Failed to serialize board: {}", e))?;
+    let as_markdown = is_markdown_path(file_path)
+        || tokio::fs::read_to_string(file_path)
+            .await
+            .map_or(false, |existing| markdown::is_markdown_board(&existing));
+    write_board(file_path, board, as_markdown).await
+}
+
+/// Save a board after running its automation rules against `previous`
+/// (the version on disk), then carry out the rules' side effects
+async fn save_with_rules(
+    app: &tauri::AppHandle,
+    file_path: &Path,
+    previous: Option<&KanbanBoard>,
+    board: &mut KanbanBoard,
+) -> Result<(), String> {
+    let board_path = file_path.to_string_lossy().to_string();
+    let outcome = rules::evaluate(&board_path, previous, board, chrono::Utc::now());
+    save_board_to_file(file_path, board).await?;
+    rules::dispatch(app, &board_path, outcome);
+    Ok(())
+}
+
+async fn write_board(file_path: &Path, board: &KanbanBoard, as_markdown: bool) -> Result<(), String> {
+    let content = if as_markdown {
+        markdown::to_markdown(board)?
+    } else {
+        serde_json::to_string_pretty(board)
+            .map_err(|e| format!("Failed to serialize board: {}", e))?
+    };
 
     tokio::fs::write(file_path, content)
         .await
@@ -214,7 +281,9 @@ fn scan_directory_for_boards<'a>(
             let path = entry.path();
 
             if path.is_file() {
-                if path.extension().and_then(|s| s.to_str()) == Some("kanban") {
+                let is_board = path.extension().and_then(|s| s.to_str()) == Some("kanban")
+                    || (is_markdown_path(&path) && is_markdown_board_file(&path).await);
+                if is_board {
                     if let Ok(board) = load_board_from_file(&path).await {
                         // Use filename (without extension) as the display name
                         let display_name = path
@@ -231,7 +300,7 @@ fn scan_directory_for_boards<'a>(
                         });
                     }
                 }
-            } else if path.is_dir() {
+            } else if path.is_dir() && path.file_name().and_then(|n| n.to_str()).map_or(true, |n| !n.starts_with('.')) {
                 // Recursively scan subdirectories
                 let _ = scan_directory_for_boards(&path, boards).await;
             }
@@ -263,11 +332,21 @@ pub async fn create_kanban_board(
     workspace_path: String,
     name: String,
     columns: Vec<String>,
+    format: Option<String>,
 ) -> Result<KanbanBoard, String> {
     let board = KanbanBoard::new(name.clone(), columns);
     let sanitized_name = name.replace(|c: char| !c.is_alphanumeric() && c != ' ', "");
-    let file_name = format!("{}.kanban", sanitized_name);
+    // Markdown boards are plain notes, so they use the .md extension like Obsidian Kanban
+    let extension = match format.as_deref() {
+        Some("markdown") => "md",
+        None | Some("json") => "kanban",
+        Some(other) => return Err(format!("Unknown board format '{}'", other)),
+    };
+    let file_name = format!("{}.{}", sanitized_name, extension);
     let file_path = Path::new(&workspace_path).join(file_name);
+    if file_path.exists() {
+        return Err(format!("A file named '{}' already exists", file_path.display()));
+    }
 
     save_board_to_file(&file_path, &board).await?;
     Ok(board)
@@ -279,10 +358,39 @@ pub async fn open_kanban_board(file_path: String) -> Result<KanbanBoard, String>
     load_board_from_file(path).await
 }
 
+/// Save a board, returning it as stored (automation rules may have changed it)
 #[tauri::command]
-pub async fn save_kanban_board(file_path: String, board: KanbanBoard) -> Result<(), String> {
+pub async fn save_kanban_board(app: tauri::AppHandle, file_path: String, board: KanbanBoard) -> Result<KanbanBoard, String> {
     let path = Path::new(&file_path);
-    save_board_to_file(path, &board).await
+    let previous = load_board_from_file(path).await.ok();
+    let mut board = board;
+    save_with_rules(&app, path, previous.as_ref(), &mut board).await?;
+    Ok(board)
+}
+
+/// Convert a board between the JSON (`.kanban`) and markdown (`.md`) formats.
+/// Returns the new file path; the old file is removed.
+#[tauri::command]
+pub async fn convert_kanban_board(file_path: String, format: String) -> Result<String, String> {
+    let old_path = PathBuf::from(&file_path);
+    let board = load_board_from_file(&old_path).await?;
+    let (extension, as_markdown) = match format.as_str() {
+        "markdown" => ("md", true),
+        "json" => ("kanban", false),
+        other => return Err(format!("Unknown board format '{}'", other)),
+    };
+
+    let new_path = old_path.with_extension(extension);
+    if new_path != old_path && new_path.exists() {
+        return Err(format!("A file named '{}' already exists", new_path.display()));
+    }
+    write_board(&new_path, &board, as_markdown).await?;
+    if new_path != old_path {
+        tokio::fs::remove_file(&old_path)
+            .await
+            .map_err(|e| format!("Failed to delete old board file: {}", e))?;
+    }
+    Ok(new_path.to_string_lossy().to_string())
 }
 
 #[tauri::command]
@@ -299,7 +407,8 @@ pub async fn rename_kanban_board(old_path: String, new_name: String) -> Result<S
         .ok_or_else(|| String::from("Invalid file path"))?;
 
     let sanitized_name = new_name.replace(|c: char| !c.is_alphanumeric() && c != ' ', "");
-    let new_file_name = format!("{}.kanban", sanitized_name);
+    let extension = old_path_buf.extension().and_then(|e| e.to_str()).unwrap_or("kanban");
+    let new_file_name = format!("{}.{}", sanitized_name, extension);
     let new_path = parent.join(new_file_name);
 
     // Load board and update name
@@ -307,8 +416,11 @@ pub async fn rename_kanban_board(old_path: String, new_name: String) -> Result<S
     board.name = new_name;
     board.metadata.modified = chrono::Utc::now().to_rfc3339();
 
-    // Save to new path and delete old file
-    save_board_to_file(&new_path, &board).await?;
+    // Save to new path (in the same format) and delete old file
+    let as_markdown = tokio::fs::read_to_string(&old_path_buf)
+        .await
+        .map_or(is_markdown_path(&old_path_buf), |content| markdown::is_markdown_board(&content));
+    write_board(&new_path, &board, as_markdown).await?;
     tokio::fs::remove_file(&old_path)
         .await
         .map_err(|e| format!("Failed to delete old board file: {}", e))?;
@@ -318,6 +430,7 @@ pub async fn rename_kanban_board(old_path: String, new_name: String) -> Result<S
 
 #[tauri::command]
 pub async fn add_card_to_board(
+    app: tauri::AppHandle,
     board_path: String,
     column_id: String,
     title: String,
@@ -333,14 +446,16 @@ pub async fn add_card_to_board(
     card.tags = tags;
     card.priority = priority;
 
+    let previous = board.clone();
     board.add_card(&column_id, card.clone())?;
-    save_board_to_file(path, &board).await?;
+    save_with_rules(&app, path, Some(&previous), &mut board).await?;
 
     Ok(card)
 }
 
 #[tauri::command]
 pub async fn move_card_between_columns(
+    app: tauri::AppHandle,
     board_path: String,
     card_id: String,
     from_column: String,
@@ -349,25 +464,73 @@ pub async fn move_card_between_columns(
     let path = Path::new(&board_path);
     let mut board = load_board_from_file(path).await?;
 
+    let previous = board.clone();
     board.move_card(&card_id, &from_column, &to_column)?;
-    save_board_to_file(path, &board).await
+    save_with_rules(&app, path, Some(&previous), &mut board).await?;
+
+    // Moving a card changes the status of its linked tasks (and their checkboxes)
+    let column_name = board.columns.get(&to_column).map_or(to_column.as_str(), |c| c.name.as_str());
+    if let Err(e) = crate::tasks::sync_kanban_move(&app, &board_path, &card_id, column_name) {
+        tracing::warn!("Failed to sync tasks for card {}: {}", card_id, e);
+    }
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn update_card_in_board(
+    app: tauri::AppHandle,
     board_path: String,
     card: KanbanCard,
 ) -> Result<KanbanCard, String> {
     let path = Path::new(&board_path);
     let mut board = load_board_from_file(path).await?;
 
+    let previous = board.clone();
     let card_id = card.id.clone();
     let updated_card = board.update_card(&card_id, card)?;
-    save_board_to_file(path, &board).await?;
+    save_with_rules(&app, path, Some(&previous), &mut board).await?;
 
     Ok(updated_card)
 }
 
+#[tauri::command]
+pub async fn kanban_list_rules(board_path: String) -> Result<Vec<rules::AutomationRule>, String> {
+    Ok(load_board_from_file(Path::new(&board_path)).await?.settings.rules)
+}
+
+/// Add a rule to a board, or replace the rule with the same id
+#[tauri::command]
+pub async fn kanban_add_rule(board_path: String, rule: rules::AutomationRule) -> Result<rules::AutomationRule, String> {
+    let path = Path::new(&board_path);
+    let mut board = load_board_from_file(path).await?;
+    rules::validate(&board, &rule)?;
+
+    let mut rule = rule;
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+    match board.settings.rules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => board.settings.rules.push(rule.clone()),
+    }
+    board.metadata.modified = chrono::Utc::now().to_rfc3339();
+    save_board_to_file(path, &board).await?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn kanban_remove_rule(board_path: String, rule_id: String) -> Result<(), String> {
+    let path = Path::new(&board_path);
+    let mut board = load_board_from_file(path).await?;
+    let before = board.settings.rules.len();
+    board.settings.rules.retain(|r| r.id != rule_id);
+    if board.settings.rules.len() == before {
+        return Err(format!("Rule '{}' not found", rule_id));
+    }
+    board.metadata.modified = chrono::Utc::now().to_rfc3339();
+    save_board_to_file(path, &board).await
+}
+
 #[tauri::command]
 pub async fn delete_card_from_board(
     board_path: String,
@@ -0,0 +1,409 @@
+//! Board automation rules.
+//!
+//! Rules live in the board's settings (`settings.rules`) so they travel with
+//! the board file. They are evaluated whenever a board is saved through the
+//! backend and, for time-based triggers, periodically by a background timer.
+//! Every rule that changes something emits `kanban-rule-fired`.
+
+use super::{KanbanBoard, KanbanCard};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter};
+
+pub const RULE_FIRED_EVENT: &str = "kanban-rule-fired";
+
+#[cfg(desktop)]
+const TIMER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleTrigger {
+    /// A card arrives in a column (moved there, or created there)
+    CardMovedTo { column: String },
+    /// A card's due date is in the past, unless it sits in one of `except`
+    DueDatePassed {
+        #[serde(default)]
+        except: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleAction {
+    MoveToColumn { column: String },
+    /// Mark tasks linked to the card as completed
+    CompleteLinkedTask,
+    /// Stamp the card's completion date (cleared again by `ClearCompletionDate`)
+    SetCompletionDate,
+    ClearCompletionDate,
+    AddTag { tag: String },
+    RemoveTag { tag: String },
+    SetPriority { priority: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub trigger: RuleTrigger,
+    pub actions: Vec<RuleAction>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleFired {
+    pub board_path: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub card_id: String,
+    pub card_title: String,
+}
+
+/// Side effects outside the board file, carried out by the caller
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleEffect {
+    CompleteTasks { card_id: String },
+}
+
+#[derive(Debug, Default)]
+pub struct RuleOutcome {
+    pub fired: Vec<RuleFired>,
+    pub effects: Vec<RuleEffect>,
+}
+
+impl RuleOutcome {
+    pub fn changed(&self) -> bool {
+        !self.fired.is_empty()
+    }
+}
+
+/// Resolve a column given by id or (case-insensitive) name
+fn column_id(board: &KanbanBoard, reference: &str) -> Option<String> {
+    if board.columns.contains_key(reference) {
+        return Some(reference.to_string());
+    }
+    board
+        .columns
+        .iter()
+        .find(|(_, c)| c.name.eq_ignore_ascii_case(reference))
+        .map(|(id, _)| id.clone())
+}
+
+fn card_column(board: &KanbanBoard, card_id: &str) -> Option<String> {
+    board
+        .columns
+        .iter()
+        .find(|(_, c)| c.cards.iter().any(|card| card.id == card_id))
+        .map(|(id, _)| id.clone())
+}
+
+fn card_mut<'a>(board: &'a mut KanbanBoard, card_id: &str) -> Option<&'a mut KanbanCard> {
+    board
+        .columns
+        .values_mut()
+        .flat_map(|c| c.cards.iter_mut())
+        .find(|card| card.id == card_id)
+}
+
+/// End of the due day for date-only values, the exact instant otherwise
+fn due_passed(due: &str, now: DateTime<Utc>) -> bool {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(due) {
+        return instant.with_timezone(&Utc) < now;
+    }
+    NaiveDate::parse_from_str(due.get(..10).unwrap_or(due), "%Y-%m-%d")
+        .map_or(false, |date| date < now.with_timezone(&Local).date_naive())
+}
+
+pub fn validate(board: &KanbanBoard, rule: &AutomationRule) -> Result<(), String> {
+    if rule.name.trim().is_empty() {
+        return Err("Rule name cannot be empty".to_string());
+    }
+    if rule.actions.is_empty() {
+        return Err("Rule needs at least one action".to_string());
+    }
+    let mut columns: Vec<&str> = Vec::new();
+    match &rule.trigger {
+        RuleTrigger::CardMovedTo { column } => columns.push(column),
+        RuleTrigger::DueDatePassed { except } => columns.extend(except.iter().map(String::as_str)),
+    }
+    for action in &rule.actions {
+        if let RuleAction::MoveToColumn { column } = action {
+            columns.push(column);
+        }
+    }
+    for column in columns {
+        if column_id(board, column).is_none() {
+            return Err(format!("Column '{}' not found on this board", column));
+        }
+    }
+    Ok(())
+}
+
+/// Apply a rule's actions to one card. Returns whether anything changed.
+fn apply_actions(board: &mut KanbanBoard, card_id: &str, actions: &[RuleAction], effects: &mut Vec<RuleEffect>) -> bool {
+    let mut changed = false;
+    let now = Utc::now().to_rfc3339();
+    for action in actions {
+        match action {
+            RuleAction::MoveToColumn { column } => {
+                let (Some(to), Some(from)) = (column_id(board, column), card_column(board, card_id)) else {
+                    continue;
+                };
+                if to != from && board.move_card(card_id, &from, &to).is_ok() {
+                    changed = true;
+                }
+            }
+            RuleAction::CompleteLinkedTask => {
+                let effect = RuleEffect::CompleteTasks { card_id: card_id.to_string() };
+                if !effects.contains(&effect) {
+                    effects.push(effect);
+                }
+            }
+            other => {
+                let Some(card) = card_mut(board, card_id) else { continue };
+                let card_changed = match other {
+                    RuleAction::SetCompletionDate if card.completed_at.is_none() => {
+                        card.completed_at = Some(now.clone());
+                        true
+                    }
+                    RuleAction::ClearCompletionDate if card.completed_at.is_some() => {
+                        card.completed_at = None;
+                        true
+                    }
+                    RuleAction::AddTag { tag } if !card.tags.contains(tag) => {
+                        card.tags.push(tag.clone());
+                        true
+                    }
+                    RuleAction::RemoveTag { tag } if card.tags.contains(tag) => {
+                        card.tags.retain(|t| t != tag);
+                        true
+                    }
+                    RuleAction::SetPriority { priority } if &card.priority != priority => {
+                        card.priority = priority.clone();
+                        true
+                    }
+                    _ => false,
+                };
+                if card_changed {
+                    card.modified = now.clone();
+                    changed = true;
+                }
+            }
+        }
+    }
+    if changed {
+        board.metadata.modified = now;
+    }
+    changed
+}
+
+fn fired(board_path: &str, rule: &AutomationRule, board: &KanbanBoard, card_id: &str) -> RuleFired {
+    let card_title = board
+        .columns
+        .values()
+        .flat_map(|c| c.cards.iter())
+        .find(|c| c.id == card_id)
+        .map(|c| c.title.clone())
+        .unwrap_or_default();
+    RuleFired {
+        board_path: board_path.to_string(),
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        card_id: card_id.to_string(),
+        card_title,
+    }
+}
+
+/// Evaluate rules against `board` as it is about to be saved. Cards whose
+/// column differs from `previous` (or that are new) trigger `CardMovedTo`;
+/// `DueDatePassed` is checked for every card.
+pub fn evaluate(board_path: &str, previous: Option<&KanbanBoard>, board: &mut KanbanBoard, now: DateTime<Utc>) -> RuleOutcome {
+    let mut outcome = RuleOutcome::default();
+    let rules: Vec<AutomationRule> = board.settings.rules.iter().filter(|r| r.enabled).cloned().collect();
+    if rules.is_empty() {
+        return outcome;
+    }
+
+    let before: HashMap<String, String> = previous
+        .map(|prev| {
+            prev.columns
+                .iter()
+                .flat_map(|(col, c)| c.cards.iter().map(move |card| (card.id.clone(), col.clone())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let arrivals: Vec<(String, String)> = board
+        .columns
+        .iter()
+        .flat_map(|(col, c)| c.cards.iter().map(move |card| (card.id.clone(), col.clone())))
+        .filter(|(card, col)| previous.is_some() && before.get(card) != Some(col))
+        .collect();
+
+    for rule in &rules {
+        let targets: Vec<String> = match &rule.trigger {
+            RuleTrigger::CardMovedTo { column } => match column_id(board, column) {
+                Some(target) => arrivals.iter().filter(|(_, col)| *col == target).map(|(card, _)| card.clone()).collect(),
+                None => Vec::new(),
+            },
+            RuleTrigger::DueDatePassed { except } => {
+                let excluded: Vec<String> = except.iter().filter_map(|c| column_id(board, c)).collect();
+                board
+                    .columns
+                    .iter()
+                    .filter(|(col, _)| !excluded.contains(col))
+                    .flat_map(|(_, c)| c.cards.iter())
+                    .filter(|card| card.due_date.as_deref().map_or(false, |due| due_passed(due, now)))
+                    .map(|card| card.id.clone())
+                    .collect()
+            }
+        };
+
+        for card_id in targets {
+            let effects_before = outcome.effects.len();
+            let changed = apply_actions(board, &card_id, &rule.actions, &mut outcome.effects);
+            if changed || outcome.effects.len() > effects_before {
+                outcome.fired.push(fired(board_path, rule, board, &card_id));
+            }
+        }
+    }
+    outcome
+}
+
+/// Carry out side effects and notify the UI
+pub fn dispatch(app: &AppHandle, board_path: &str, outcome: RuleOutcome) {
+    for effect in outcome.effects {
+        match effect {
+            RuleEffect::CompleteTasks { card_id } => {
+                if let Err(e) = crate::tasks::set_card_tasks_status(app, board_path, &card_id, crate::tasks::TaskStatus::Completed, None) {
+                    tracing::warn!("Kanban rule failed to complete tasks for card {}: {}", card_id, e);
+                }
+            }
+        }
+    }
+    for fired in outcome.fired {
+        if let Err(e) = app.emit(RULE_FIRED_EVENT, &fired) {
+            tracing::warn!("Failed to emit {}: {}", RULE_FIRED_EVENT, e);
+        }
+    }
+}
+
+/// Re-check time-based rules on every board of the open workspace
+#[cfg(desktop)]
+pub fn start_timer(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TIMER_INTERVAL).await;
+            let Some(root) = crate::watcher::active_root() else {
+                continue;
+            };
+            let Ok(boards) = super::list_boards_in_workspace(&root).await else {
+                continue;
+            };
+            for info in boards {
+                let path = std::path::Path::new(&info.path);
+                let Ok(mut board) = super::load_board_from_file(path).await else {
+                    continue;
+                };
+                let has_timed_rules = board
+                    .settings
+                    .rules
+                    .iter()
+                    .any(|r| r.enabled && matches!(r.trigger, RuleTrigger::DueDatePassed { .. }));
+                if !has_timed_rules {
+                    continue;
+                }
+                let previous = board.clone();
+                let outcome = evaluate(&info.path, Some(&previous), &mut board, Utc::now());
+                if outcome.changed() {
+                    // Moves made here don't cascade into CardMovedTo rules
+                    if let Err(e) = super::save_board_to_file(path, &board).await {
+                        tracing::warn!("Failed to save board {} after rules ran: {}", info.path, e);
+                        continue;
+                    }
+                    dispatch(&app, &info.path, outcome);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn board_with_rules(rules: Vec<AutomationRule>) -> KanbanBoard {
+        let mut board = KanbanBoard::new("Work".into(), vec!["To Do".into(), "Done".into(), "Overdue".into()]);
+        board.settings.rules = rules;
+        board
+    }
+
+    fn rule(name: &str, trigger: RuleTrigger, actions: Vec<RuleAction>) -> AutomationRule {
+        AutomationRule { id: name.to_string(), name: name.to_string(), enabled: true, trigger, actions }
+    }
+
+    #[test]
+    fn test_move_to_done_completes_and_stamps() {
+        let mut board = board_with_rules(vec![rule(
+            "done",
+            RuleTrigger::CardMovedTo { column: "Done".into() },
+            vec![RuleAction::CompleteLinkedTask, RuleAction::SetCompletionDate],
+        )]);
+        let card = KanbanCard::new("Ship".into());
+        let card_id = card.id.clone();
+        board.add_card("to-do", card).unwrap();
+
+        let previous = board.clone();
+        board.move_card(&card_id, "to-do", "done").unwrap();
+        let outcome = evaluate("b.kanban", Some(&previous), &mut board, Utc::now());
+        assert_eq!(outcome.fired.len(), 1);
+        assert_eq!(outcome.effects, vec![RuleEffect::CompleteTasks { card_id: card_id.clone() }]);
+        assert!(board.columns["done"].cards[0].completed_at.is_some());
+
+        // Saving again without a move fires nothing
+        let previous = board.clone();
+        assert!(!evaluate("b.kanban", Some(&previous), &mut board, Utc::now()).changed());
+    }
+
+    #[test]
+    fn test_overdue_cards_move_once() {
+        let mut board = board_with_rules(vec![rule(
+            "overdue",
+            RuleTrigger::DueDatePassed { except: vec!["Done".into()] },
+            vec![RuleAction::MoveToColumn { column: "Overdue".into() }],
+        )]);
+        let mut late = KanbanCard::new("Late".into());
+        late.due_date = Some((Utc::now() - Duration::hours(1)).to_rfc3339());
+        let mut finished = KanbanCard::new("Finished".into());
+        finished.due_date = Some("2000-01-01".into());
+        board.add_card("to-do", late).unwrap();
+        board.add_card("done", finished).unwrap();
+
+        let previous = board.clone();
+        let outcome = evaluate("b.kanban", Some(&previous), &mut board, Utc::now());
+        assert_eq!(outcome.fired.len(), 1);
+        assert_eq!(board.columns["overdue"].cards.len(), 1);
+        assert_eq!(board.columns["done"].cards.len(), 1);
+
+        let previous = board.clone();
+        assert!(!evaluate("b.kanban", Some(&previous), &mut board, Utc::now()).changed());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_columns() {
+        let board = board_with_rules(Vec::new());
+        let bad = rule("x", RuleTrigger::CardMovedTo { column: "Nope".into() }, vec![RuleAction::CompleteLinkedTask]);
+        assert!(validate(&board, &bad).is_err());
+        let good = rule("y", RuleTrigger::CardMovedTo { column: "done".into() }, vec![RuleAction::CompleteLinkedTask]);
+        assert!(validate(&board, &good).is_ok());
+    }
+}
@@ -3,6 +3,7 @@ mod window_manager;
 #[cfg(desktop)]
 mod menu;
 mod theme;
+mod settings;
 mod handlers;
 mod clipboard;
 mod clipboard_platform;
@@ -10,7 +11,47 @@ mod tasks;
 mod schedule_blocks;
 mod kanban;
 mod search;
+mod ocr;
+mod pdf;
+mod links;
+mod traversal;
+mod filetypes;
+mod tabular;
+mod canvas;
+mod diagrams;
+mod images;
+mod thumbnails;
+#[cfg(desktop)]
+mod media;
+mod vaults;
+mod path_policy;
+mod sanitize;
+mod errors;
+mod export;
+mod encryption;
+mod trash;
+mod backup;
+mod import;
+mod templates;
+mod daily_notes;
+mod attachments;
+mod health;
+mod bulk_replace;
+mod metadata_cache;
+mod stats;
+mod frontmatter;
+mod tags;
+mod smart_folders;
+mod citations;
+mod task_sync;
+mod recurrence;
+mod reminders;
+#[cfg(desktop)]
+mod watcher;
+#[cfg(desktop)]
+mod sync;
 mod plugins;
+mod plugin_broker;
 mod platform;
 #[cfg(desktop)]
 mod mcp;
@@ -29,6 +70,28 @@ mod oauth_server;
 mod secure_storage;
 #[cfg(desktop)]
 mod api_server;
+#[cfg(desktop)]
+mod embeddings;
+#[cfg(desktop)]
+mod ai;
+#[cfg(desktop)]
+mod clipper;
+#[cfg(desktop)]
+mod feeds;
+#[cfg(desktop)]
+pub mod cli;
+#[cfg(desktop)]
+mod capture;
+#[cfg(desktop)]
+mod deep_link;
+#[cfg(desktop)]
+mod tray;
+#[cfg(desktop)]
+mod os_search;
+#[cfg(desktop)]
+mod diagnostics;
+#[cfg(desktop)]
+mod telemetry;
 mod logging;
 pub(crate) mod file_locking;
 #[cfg(target_os = "macos")]
@@ -41,11 +104,6 @@ mod notifications;
 #[cfg(desktop)]
 use window_manager::{open_workspace_window, open_preferences_window, open_launcher_window};
 use tauri::{Manager, Listener, Emitter, RunEvent, WindowEvent};
-#[cfg(desktop)]
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-};
 use tauri_plugin_store::{StoreBuilder, JsonValue};
 use std::path::PathBuf;
 
@@ -78,6 +136,19 @@ fn save_last_workspace(app: tauri::AppHandle, path: String) -> Result<(), String
         .map_err(|e| format!("Store error: {}", e))?;
     let _ = store.reload();
 
+    // Keep the vault registry in step with whichever workspace was opened last;
+    // only a real workspace, which `validate_workspace_path` gave a `.lokus` folder
+    let registered = vaults::check_vault_folder(std::path::Path::new(&path)).and_then(|()| {
+        if std::path::Path::new(&path).join(".lokus").is_dir() {
+            vaults::register_vault(&path, None)
+        } else {
+            Err(format!("{} is not a workspace", path))
+        }
+    });
+    if let Err(e) = registered {
+        tracing::warn!("Failed to register vault: {}", e);
+    }
+
     #[cfg(target_os = "macos")]
     {
         // Create security-scoped bookmark for macOS
@@ -358,6 +429,9 @@ fn save_session_state(
 
     let _ = store.set(workspace_key, serde_json::to_value(session).map_err(|e| e.to_string())?);
     let _ = store.save();
+
+    #[cfg(desktop)]
+    tray::refresh_recent(&app);
     Ok(())
 }
 
@@ -411,10 +485,15 @@ fn get_all_workspaces(app: tauri::AppHandle) -> Vec<WorkspaceItem> {
         }
     }
 
-    // Get all session states to find unique workspace paths
-    // Note: Currently we only return the last workspace because workspace paths
-    // are stored as hashed keys (session_state_<hash>). To support multiple
-    // workspaces, we would need to store workspace metadata separately.
+    // Session keys are hashed, so every other workspace comes from the vault registry
+    for vault in vaults::list_vaults() {
+        if !workspaces.iter().any(|w| w.path == vault.path) {
+            workspaces.push(WorkspaceItem {
+                path: vault.path,
+                name: vault.name,
+            });
+        }
+    }
 
     workspaces
 }
@@ -624,51 +703,6 @@ async fn llm_stream_request(
     Ok(serde_json::json!({ "done": true }))
 }
 
-/// Set up the system tray icon with a context menu.
-///
-/// Left-click on the tray icon shows and focuses the main window.
-/// Right-click reveals the context menu with "Show Window" and "Quit Lokus" items.
-#[cfg(desktop)]
-fn setup_tray(app: &mut tauri::App) -> tauri::Result<()> {
-    let show_item = MenuItem::with_id(app, "show_window", "Show Window", true, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "Quit Lokus", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
-
-    let _tray = TrayIconBuilder::new()
-        .icon(app.default_window_icon().cloned().unwrap())
-        .menu(&menu)
-        .show_menu_on_left_click(false)
-        .on_tray_icon_event(|tray, event| {
-            if let TrayIconEvent::Click {
-                button: MouseButton::Left,
-                button_state: MouseButtonState::Up,
-                ..
-            } = event
-            {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
-            }
-        })
-        .on_menu_event(|app, event| match event.id.as_ref() {
-            "show_window" => {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
-            }
-            "quit" => {
-                app.exit(0);
-            }
-            _ => {}
-        })
-        .build(app)?;
-
-    Ok(())
-}
-
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   // Load environment variables from .env file if it exists
@@ -735,7 +769,8 @@ pub fn run() {
         }
       }
 
-      // Report panic to Sentry
+      // Report panic to Sentry, with the last resource snapshot
+      diagnostics::add_crash_breadcrumb();
       sentry::integrations::panic::panic_handler(panic_info);
 
       // Also print the normal panic message
@@ -748,6 +783,7 @@ pub fn run() {
   let mut builder = tauri::Builder::default()
     .plugin(tauri_plugin_updater::Builder::new().build())
     .plugin(tauri_plugin_store::Builder::new().build())
+    .plugin(tauri_plugin_notification::init())
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_fs::init())
     .plugin(tauri_plugin_opener::init())
@@ -762,7 +798,7 @@ pub fn run() {
   }
 
   builder
-    .invoke_handler(tauri::generate_handler![
+    .invoke_handler(path_policy::guarded(tauri::generate_handler![
       greet,
       #[cfg(desktop)]
       open_workspace_window,
@@ -791,12 +827,37 @@ pub fn run() {
       theme::list_custom_themes,
       theme::get_theme_tokens,
       theme::save_theme_tokens,
+      theme::theme_preview,
+      settings::settings_get_effective,
+      settings::settings_get_workspace_overrides,
+      settings::settings_set,
+      settings::profile::settings_export_profile,
+      settings::profile::settings_import_profile,
+      #[cfg(desktop)]
+      theme::install_theme_from_url,
+      #[cfg(desktop)]
+      theme::install_theme_from_registry,
+      #[cfg(desktop)]
+      theme::check_theme_updates,
+      #[cfg(desktop)]
+      theme::update_theme,
       handlers::files::read_workspace_files,
+      handlers::files::read_directory_page,
+      handlers::files::get_folder_summary,
       handlers::files::create_file_in_workspace,
       handlers::files::create_folder_in_workspace,
       handlers::files::read_file_content,
       handlers::files::read_binary_file,
+      handlers::file_stream::read_file_range,
+      handlers::file_stream::read_file_stream_start,
+      handlers::file_stream::file_stream_cancel,
+      handlers::file_stream::write_file_stream_start,
+      handlers::file_stream::write_file_stream_chunk,
+      handlers::file_stream::write_file_stream_finish,
+      handlers::file_stream::write_file_stream_abort,
       handlers::files::write_file_content,
+      handlers::files::write_file_content_checked,
+      file_locking::file_lock_status,
       handlers::files::write_binary_file,
       handlers::files::save_file_version_manual,
       handlers::files::rename_file,
@@ -812,7 +873,23 @@ pub fn run() {
       handlers::files::create_directory,
       handlers::files::read_all_files,
       handlers::files::copy_external_files_to_workspace,
+      images::image_optimize,
+      thumbnails::get_thumbnail,
+      #[cfg(desktop)]
+      media::media_get_metadata,
+      #[cfg(desktop)]
+      media::media_extract_audio_waveform,
       handlers::files::find_workspace_images,
+      handlers::write_journal::recover_unsaved_changes,
+      handlers::autosave::autosave_store,
+      handlers::autosave::autosave_recover,
+      handlers::autosave::autosave_clear,
+      filetypes::filetypes_get_registry,
+      filetypes::filetypes_set_handler,
+      filetypes::filetypes_open,
+      tabular::csv_read,
+      tabular::csv_query,
+      tabular::csv_write,
       handlers::platform_files::platform_reveal_in_file_manager,
       handlers::platform_files::platform_open_terminal,
       handlers::platform_files::get_platform_information,
@@ -824,6 +901,15 @@ pub fn run() {
       handlers::version_history::get_diff,
       handlers::version_history::restore_version,
       handlers::version_history::cleanup_old_versions,
+      handlers::version_policy::version_policy_get,
+      handlers::version_policy::version_policy_set,
+      handlers::version_policy::version_storage_stats,
+      handlers::version_diff::get_diff_structured,
+      handlers::version_diff::get_diff_three_way,
+      backup::backup_workspace,
+      backup::backup_restore,
+      backup::schedule::backup_schedule,
+      backup::schedule::backup_schedule_get,
       clipboard::clipboard_write_text,
       clipboard::clipboard_read_text,
       clipboard::clipboard_write_html,
@@ -837,6 +923,7 @@ pub fn run() {
       clipboard_platform::clipboard_get_platform_info,
       clipboard_platform::clipboard_get_usage_tips,
       clipboard_platform::clipboard_clear_enhanced,
+      clipboard_platform::clipboard_paste_into_workspace,
       platform::system_info::get_system_information,
       platform::system_info::check_system_capability,
       platform::examples::run_platform_examples,
@@ -851,6 +938,65 @@ pub fn run() {
       tasks::extract_tasks_from_content,
       tasks::link_task_to_kanban,
       tasks::get_tasks_by_kanban_board,
+      tasks::tasks_get_upcoming,
+      reminders::reminder_set,
+      reminders::reminder_list,
+      reminders::reminder_snooze,
+      reminders::reminder_cancel,
+      #[cfg(desktop)]
+      sync::git::git_status,
+      #[cfg(desktop)]
+      sync::git::git_init,
+      #[cfg(desktop)]
+      sync::git::git_commit,
+      #[cfg(desktop)]
+      sync::git::git_push,
+      #[cfg(desktop)]
+      sync::git::git_pull,
+      #[cfg(desktop)]
+      sync::git::git_set_credentials,
+      #[cfg(desktop)]
+      sync::git::git_clear_credentials,
+      #[cfg(desktop)]
+      sync::git::git_file_log,
+      #[cfg(desktop)]
+      sync::git::git_show_file_at,
+      #[cfg(desktop)]
+      sync::git::git_diff_commits,
+      #[cfg(desktop)]
+      sync::merge::git_get_conflict_regions,
+      #[cfg(desktop)]
+      sync::merge::git_resolve_conflict,
+      #[cfg(desktop)]
+      sync::merge::git_abort_merge,
+      #[cfg(desktop)]
+      sync::autosync::git_autosync_configure,
+      #[cfg(desktop)]
+      sync::autosync::git_autosync_status,
+      #[cfg(desktop)]
+      sync::autosync::git_autosync_retry,
+      #[cfg(desktop)]
+      sync::selective::sync_set_roots,
+      #[cfg(desktop)]
+      sync::selective::sync_get_selection,
+      #[cfg(desktop)]
+      sync::selective::sync_scan_metrics,
+      #[cfg(desktop)]
+      sync::chunks::sync_delta_metrics,
+      #[cfg(desktop)]
+      sync::state::sync_gc_tombstones,
+      #[cfg(desktop)]
+      sync::conflicts::sync_list_conflicts,
+      #[cfg(desktop)]
+      sync::conflicts::sync_preview_conflict,
+      #[cfg(desktop)]
+      sync::conflicts::sync_resolve_conflict,
+      #[cfg(desktop)]
+      sync::remote::remote_sync_configure,
+      #[cfg(desktop)]
+      sync::remote::remote_sync_now,
+      #[cfg(desktop)]
+      sync::remote::remote_sync_status,
       schedule_blocks::create_schedule_block,
       schedule_blocks::update_schedule_block,
       schedule_blocks::delete_schedule_block,
@@ -864,15 +1010,155 @@ pub fn run() {
       kanban::save_kanban_board,
       kanban::delete_kanban_board,
       kanban::rename_kanban_board,
+      kanban::convert_kanban_board,
       kanban::add_card_to_board,
       kanban::move_card_between_columns,
       kanban::update_card_in_board,
       kanban::delete_card_from_board,
+      kanban::kanban_list_rules,
+      kanban::kanban_add_rule,
+      kanban::kanban_remove_rule,
       kanban::initialize_workspace_kanban,
       search::search_in_files,
+      search::search_in_files_stream,
+      search::search_cancel,
       search::search_in_file,
       search::get_file_content_with_lines,
       search::build_search_index,
+      search::search_index_rebuild,
+      search::search_index_update_file,
+      search::search_query_ranked,
+      ocr::ocr_process_image,
+      ocr::check_ocr_availability,
+      ocr::sidecar::ocr_index_image,
+      ocr::sidecar::ocr_index_workspace,
+      pdf::pdf_page_count,
+      pdf::pdf_extract_text,
+      pdf::pdf_extract_page_text,
+      pdf::pdf_extract_images,
+      pdf::render_pdf_page_to_png,
+      pdf::index::pdf_index_settings_get,
+      pdf::index::pdf_index_settings_set,
+      pdf::index::reindex_pdfs,
+      canvas::canvas_create,
+      canvas::canvas_load,
+      canvas::canvas_save,
+      canvas::canvas_add_node,
+      canvas::canvas_export_to_image,
+      diagrams::diagram_render_preview,
+      diagrams::diagram_validate,
+      diagrams::diagram_save,
+      links::get_backlinks,
+      links::get_outgoing_links,
+      links::get_orphan_notes,
+      links::get_link_graph,
+      links::rebuild_link_graph,
+      vaults::vault_register,
+      vaults::vault_list,
+      vaults::vault_rename,
+      vaults::vault_remove,
+      vaults::vault_set_settings,
+      vaults::vault_get_settings,
+      vaults::vault_open,
+      vaults::vault_set_readonly,
+      vaults::get_workspace_capabilities,
+      path_policy::path_policy_get,
+      path_policy::path_policy_allow,
+      path_policy::path_policy_revoke,
+      path_policy::path_policy_denials,
+      sanitize::sanitize_html_command,
+      sanitize::sanitize_get_policies,
+      sanitize::sanitize_set_policy,
+      errors::error_catalog,
+      export::export_note_to_pdf,
+      export::export_folder_to_pdf,
+      export::workspace::export_workspace,
+      #[cfg(desktop)]
+      export::print::print_note,
+      encryption::encryption_unlock,
+      encryption::encryption_lock,
+      encryption::encryption_status,
+      encryption::encrypt_note,
+      encryption::decrypt_note,
+      encryption::set_folder_encryption,
+      trash::trash_list,
+      trash::trash_restore,
+      trash::trash_empty,
+      trash::trash_purge_older_than,
+      import::import_obsidian_vault,
+      import::import_notion_export,
+      import::import_enex,
+      templates::template_list,
+      templates::template_render,
+      templates::template_create_note_from,
+      daily_notes::daily_note_open,
+      daily_notes::daily_note_exists,
+      daily_notes::daily_note_range,
+      #[cfg(desktop)]
+      capture::open_capture_window,
+      #[cfg(desktop)]
+      capture::capture_append,
+      #[cfg(desktop)]
+      capture::capture_set_shortcut,
+      #[cfg(desktop)]
+      deep_link::deep_link_take_pending,
+      #[cfg(desktop)]
+      os_search::os_search_index_rebuild,
+      #[cfg(desktop)]
+      os_search::os_search_set_enabled,
+      #[cfg(desktop)]
+      diagnostics::diagnostics_snapshot,
+      #[cfg(desktop)]
+      diagnostics::diagnostics_get_config,
+      #[cfg(desktop)]
+      diagnostics::diagnostics_set_config,
+      #[cfg(desktop)]
+      diagnostics::diagnostics_mitigate,
+      #[cfg(desktop)]
+      telemetry::telemetry_get_config,
+      #[cfg(desktop)]
+      telemetry::telemetry_set_enabled,
+      #[cfg(desktop)]
+      telemetry::telemetry_record,
+      #[cfg(desktop)]
+      telemetry::telemetry_get_pending,
+      #[cfg(desktop)]
+      telemetry::telemetry_purge,
+      attachments::find_orphaned_attachments,
+      attachments::move_attachment,
+      attachments::dedupe_attachments,
+      health::vault_health_check,
+      health::vault_health_fix,
+      bulk_replace::bulk_replace_preview,
+      bulk_replace::bulk_replace_apply,
+      bulk_replace::bulk_replace_revert,
+      metadata_cache::metadata_query,
+      metadata_cache::metadata_rebuild,
+      stats::stats_get_vault_summary,
+      stats::stats_get_note,
+      stats::stats_get_writing_history,
+      frontmatter::get_frontmatter,
+      frontmatter::set_frontmatter_field,
+      frontmatter::query_notes_by_frontmatter,
+      tags::tags_list,
+      tags::tags_get_notes,
+      tags::tags_rename,
+      tags::tags_merge,
+      smart_folders::smart_folder_create,
+      smart_folders::smart_folder_list,
+      smart_folders::smart_folder_delete,
+      smart_folders::smart_folder_evaluate,
+      citations::citations_search,
+      citations::citations_get,
+      citations::citations_format,
+      citations::citations_note_bibliography,
+      task_sync::task_sync_note,
+      task_sync::task_sync_conflicts,
+      task_sync::task_sync_resolve,
+      #[cfg(desktop)]
+      watcher::watch_workspace_start,
+      #[cfg(desktop)]
+      watcher::watch_workspace_stop,
       plugins::list_plugins,
       plugins::install_plugin,
       plugins::uninstall_plugin,
@@ -890,6 +1176,28 @@ pub fn run() {
       plugins::read_plugin_file,
       plugins::get_plugin_manifest,
       #[cfg(desktop)]
+      plugins::registry_search,
+      #[cfg(desktop)]
+      plugins::registry_get_plugin,
+      #[cfg(desktop)]
+      plugins::registry_install,
+      #[cfg(desktop)]
+      plugins::plugins_check_updates,
+      #[cfg(desktop)]
+      plugins::plugins_get_available_updates,
+      #[cfg(desktop)]
+      plugins::plugins_update,
+      #[cfg(desktop)]
+      plugins::plugins_set_auto_update,
+      #[cfg(desktop)]
+      plugins::wasm_host::plugin_wasm_invoke,
+      plugin_broker::plugin_issue_token,
+      plugin_broker::plugin_read_file,
+      plugin_broker::plugin_write_file,
+      #[cfg(desktop)]
+      plugin_broker::plugin_fetch,
+      plugin_broker::get_plugin_audit_log,
+      #[cfg(desktop)]
       mcp::mcp_start,
       #[cfg(desktop)]
       mcp::mcp_stop,
@@ -900,6 +1208,44 @@ pub fn run() {
       #[cfg(desktop)]
       mcp::mcp_health_check,
       #[cfg(desktop)]
+      mcp::tools::mcp_tools_list,
+      #[cfg(desktop)]
+      mcp::tools::mcp_tool_set_enabled,
+      #[cfg(desktop)]
+      mcp::resources::mcp_get_max_resource_size,
+      #[cfg(desktop)]
+      mcp::resources::mcp_set_max_resource_size,
+      #[cfg(desktop)]
+      embeddings::embeddings_settings_get,
+      #[cfg(desktop)]
+      embeddings::embeddings_settings_set,
+      #[cfg(desktop)]
+      embeddings::embeddings_status,
+      #[cfg(desktop)]
+      embeddings::embeddings_reindex,
+      #[cfg(desktop)]
+      embeddings::semantic_search,
+      #[cfg(desktop)]
+      embeddings::find_similar_notes,
+      #[cfg(desktop)]
+      ai::ai_list_providers,
+      #[cfg(desktop)]
+      ai::ai_configure_provider,
+      #[cfg(desktop)]
+      ai::ai_complete,
+      #[cfg(desktop)]
+      ai::ai_cancel,
+      #[cfg(desktop)]
+      clipper::clip_url,
+      #[cfg(desktop)]
+      feeds::feed_add,
+      #[cfg(desktop)]
+      feeds::feed_list,
+      #[cfg(desktop)]
+      feeds::feed_remove,
+      #[cfg(desktop)]
+      feeds::feed_refresh_all,
+      #[cfg(desktop)]
       auth::initiate_oauth_flow,
       #[cfg(desktop)]
       auth::handle_oauth_callback,
@@ -960,6 +1306,114 @@ pub fn run() {
       #[cfg(desktop)]
       connections::gmail_clear_queue,
       #[cfg(desktop)]
+      connections::gmail::notes::gmail_save_email_as_note,
+      #[cfg(desktop)]
+      connections::gmail::notes::gmail_watch_label,
+      #[cfg(desktop)]
+      connections::gmail::notes::gmail_list_label_watches,
+      #[cfg(desktop)]
+      connections::gmail::notes::gmail_unwatch_label,
+      #[cfg(desktop)]
+      connections::gmail::attachments::gmail_save_attachment,
+      #[cfg(desktop)]
+      connections::gmail::attachments::gmail_save_all_attachments,
+      #[cfg(desktop)]
+      connections::imap::imap_connect,
+      #[cfg(desktop)]
+      connections::imap::imap_disconnect,
+      #[cfg(desktop)]
+      connections::imap::imap_is_connected,
+      #[cfg(desktop)]
+      connections::imap::imap_get_profile,
+      #[cfg(desktop)]
+      connections::imap::imap_list_folders,
+      #[cfg(desktop)]
+      connections::imap::imap_list_emails,
+      #[cfg(desktop)]
+      connections::imap::imap_get_email,
+      #[cfg(desktop)]
+      connections::imap::imap_send_email,
+      #[cfg(desktop)]
+      connections::imap::imap_get_queue_stats,
+      #[cfg(desktop)]
+      connections::imap::imap_clear_queue,
+      #[cfg(desktop)]
+      connections::contacts::contacts_list,
+      #[cfg(desktop)]
+      connections::contacts::contacts_search,
+      #[cfg(desktop)]
+      connections::contacts::contacts_get,
+      #[cfg(desktop)]
+      connections::contacts::contacts_connect_carddav,
+      #[cfg(desktop)]
+      connections::contacts::contacts_disconnect_carddav,
+      #[cfg(desktop)]
+      connections::readwise::readwise_configure,
+      #[cfg(desktop)]
+      connections::readwise::readwise_status,
+      #[cfg(desktop)]
+      connections::readwise::readwise_sync,
+      #[cfg(desktop)]
+      connections::readwise::readwise_disconnect,
+      #[cfg(desktop)]
+      connections::github::github_auth_start,
+      #[cfg(desktop)]
+      connections::github::github_check_auth_callback,
+      #[cfg(desktop)]
+      connections::github::github_connect_token,
+      #[cfg(desktop)]
+      connections::github::github_get_account,
+      #[cfg(desktop)]
+      connections::github::github_disconnect,
+      #[cfg(desktop)]
+      connections::github::github_list_issues,
+      #[cfg(desktop)]
+      connections::github::github_create_issue,
+      #[cfg(desktop)]
+      connections::github::github_link_issue_to_note,
+      #[cfg(desktop)]
+      connections::github::github_refresh_badges,
+      #[cfg(desktop)]
+      connections::messaging::messaging_add_channel,
+      #[cfg(desktop)]
+      connections::messaging::messaging_list_channels,
+      #[cfg(desktop)]
+      connections::messaging::messaging_remove_channel,
+      #[cfg(desktop)]
+      connections::messaging::send_to_channel,
+      #[cfg(desktop)]
+      connections::issues::issues_connect_jira,
+      #[cfg(desktop)]
+      connections::issues::issues_connect_linear,
+      #[cfg(desktop)]
+      connections::issues::issues_disconnect,
+      #[cfg(desktop)]
+      connections::issues::issues_status,
+      #[cfg(desktop)]
+      connections::issues::issues_list,
+      #[cfg(desktop)]
+      connections::issues::issues_import_as_tasks,
+      #[cfg(desktop)]
+      connections::issues::issues_reconcile,
+      #[cfg(desktop)]
+      connections::oauth_manager::connections_list_status,
+      #[cfg(desktop)]
+      connections::oauth_manager::connections_revoke,
+      #[cfg(desktop)]
+      connections::request_queue::connections_get_queue_stats,
+      #[cfg(desktop)]
+      connections::gmail::outbox::gmail_outbox_list,
+      #[cfg(desktop)]
+      connections::gmail::outbox::gmail_outbox_cancel,
+      #[cfg(desktop)]
+      connections::inbox::inbox_list,
+      #[cfg(desktop)]
+      connections::inbox::inbox_set_read,
+      #[cfg(desktop)]
+      connections::inbox::inbox_set_starred,
+      #[cfg(desktop)]
+      connections::inbox::inbox_archive,
+      #[cfg(desktop)]
       mcp_setup::setup_mcp_integration,
       #[cfg(desktop)]
       mcp_setup::check_mcp_status,
@@ -971,6 +1425,18 @@ pub fn run() {
       api_server::api_clear_workspace,
       #[cfg(desktop)]
       api_server::api_get_current_workspace,
+      #[cfg(desktop)]
+      api_server::publish::publish_note,
+      #[cfg(desktop)]
+      api_server::publish::unpublish_note,
+      #[cfg(desktop)]
+      api_server::publish::list_published,
+      #[cfg(desktop)]
+      api_server::rest::api_keys_create,
+      #[cfg(desktop)]
+      api_server::rest::api_keys_revoke,
+      #[cfg(desktop)]
+      api_server::rest::api_keys_list,
       // Calendar commands
       #[cfg(desktop)]
       calendar::google_calendar_auth_start,
@@ -983,6 +1449,14 @@ pub fn run() {
       #[cfg(desktop)]
       calendar::google_calendar_get_account,
       #[cfg(desktop)]
+      calendar::outlook_calendar_auth_start,
+      #[cfg(desktop)]
+      calendar::outlook_calendar_check_auth_callback,
+      #[cfg(desktop)]
+      calendar::outlook_calendar_auth_status,
+      #[cfg(desktop)]
+      calendar::outlook_calendar_get_account,
+      #[cfg(desktop)]
       calendar::calendar_disconnect,
       #[cfg(desktop)]
       calendar::get_calendars,
@@ -1003,6 +1477,8 @@ pub fn run() {
       #[cfg(desktop)]
       calendar::sync_calendars,
       #[cfg(desktop)]
+      calendar::get_cached_events,
+      #[cfg(desktop)]
       calendar::update_calendar_visibility,
       // iCal commands
       #[cfg(desktop)]
@@ -1051,6 +1527,24 @@ pub fn run() {
       calendar::set_sync_config,
       #[cfg(desktop)]
       calendar::get_sync_state,
+      #[cfg(desktop)]
+      calendar::calendar_link_event_to_note,
+      #[cfg(desktop)]
+      calendar::calendar_unlink_event_from_note,
+      #[cfg(desktop)]
+      calendar::calendar_get_events_for_note,
+      #[cfg(desktop)]
+      calendar::calendar_get_notes_for_event,
+      #[cfg(desktop)]
+      calendar::offline::calendar_pending_operations,
+      #[cfg(desktop)]
+      calendar::offline::calendar_retry_pending_operation,
+      #[cfg(desktop)]
+      calendar::offline::calendar_discard_pending_operation,
+      #[cfg(desktop)]
+      calendar::timeblocking::calendar_schedule_task,
+      #[cfg(desktop)]
+      calendar::timeblocking::calendar_get_unscheduled_tasks,
       // Audio capture commands
       audio::get_audio_devices,
       audio::start_audio_capture,
@@ -1066,6 +1560,12 @@ pub fn run() {
       // Transcription commands
       transcription::start_transcription,
       transcription::stop_transcription,
+      #[cfg(desktop)]
+      transcription::memos::transcribe_audio,
+      #[cfg(desktop)]
+      transcription::memos::voice_memo_settings_get,
+      #[cfg(desktop)]
+      transcription::memos::voice_memo_settings_set,
       // Native notification commands
       notifications::request_notification_permission_cmd,
       notifications::send_native_notification,
@@ -1073,13 +1573,13 @@ pub fn run() {
       validate_api_key,
       #[cfg(desktop)]
       llm_stream_request
-    ])
+    ]))
     .setup(|app| {
       #[cfg(desktop)]
       menu::init(&app.handle())?;
 
       #[cfg(desktop)]
-      setup_tray(app)?;
+      tray::init(app)?;
 
       // Install native macOS notification delegate and register categories.
       // Permission request is non-blocking; the OS shows a dialog at most once.
@@ -1100,9 +1600,67 @@ pub fn run() {
         }
       }
 
+      // Fire persisted and newly due reminders for tasks and calendar events
+      reminders::start(app.handle().clone());
+
+      // Purge expired trash in the background so startup isn't delayed
+      let trash_app = app.handle().clone();
+      std::thread::spawn(move || trash::purge_on_startup(&trash_app));
+
       // Desktop-only initialization
       #[cfg(desktop)]
       {
+        // Re-check time-based kanban automation rules (e.g. overdue cards)
+        kanban::rules::start_timer(app.handle().clone());
+
+        // Commit and push workspaces that have git auto-sync enabled
+        sync::autosync::start(app.handle().clone());
+
+        // Run scheduled workspace backups when they come due
+        backup::schedule::start(app.handle().clone());
+
+        // Pull new items from subscribed RSS/Atom feeds
+        feeds::start(app.handle().clone());
+
+        // Keep GitHub issue badges in notes up to date
+        connections::github::start(app.handle().clone());
+
+        // Follow Jira and Linear status changes on imported tasks
+        connections::issues::start(app.handle().clone());
+
+        // Refresh OAuth tokens before they expire
+        connections::oauth_manager::start(app.handle().clone());
+
+        // Import new messages from watched Gmail labels
+        connections::gmail::notes::start(app.handle().clone());
+
+        // Deliver Gmail sends that were queued while offline
+        connections::gmail::outbox::start(app.handle().clone());
+
+        // Retry queued IMAP/SMTP sends
+        connections::imap::start();
+
+        // Sync calendar events in the background
+        calendar::sync::incremental::start(app.handle().clone());
+
+        // Look for plugin updates, installing them if auto-update is on
+        #[cfg(desktop)]
+        plugins::start_update_checker(app.handle().clone());
+
+        // Global shortcut for quick capture
+        #[cfg(desktop)]
+        capture::start(app.handle().clone());
+
+        // Notes in Spotlight / Windows Search
+        #[cfg(desktop)]
+        os_search::start(app.handle().clone());
+
+        // Watch memory and file handles, shedding caches when limits are crossed
+        diagnostics::start(app.handle().clone());
+
+        // Queue and send opt-in usage counters
+        telemetry::start(app.handle().clone());
+
         // Initialize MCP Server Manager
         let mcp_manager = mcp::MCPServerManager::new(app.handle().clone());
         app.manage(mcp_manager.clone());
@@ -1202,11 +1760,13 @@ pub fn run() {
         auth::register_deep_link_handler(&app.handle());
       }
 
-      // Register generic deep link handler for plugin dev
+      // Register generic deep link handler: lokus:// actions and plugin dev
       let app_handle_deep_link = app.handle().clone();
       app.listen("deep-link://new-url", move |event| {
         let payload = event.payload();
         let _ = app_handle_deep_link.emit("deep-link-received", payload);
+        #[cfg(desktop)]
+        deep_link::handle_event_payload(&app_handle_deep_link, payload);
         
         // If this is a plugin-dev link, try to open devtools (debug only)
         #[cfg(debug_assertions)]
@@ -1217,6 +1777,17 @@ pub fn run() {
         }
       });
 
+      // A link that launched the app arrives before the listener above
+      #[cfg(desktop)]
+      {
+        use tauri_plugin_deep_link::DeepLinkExt;
+        if let Ok(Some(urls)) = app.deep_link().get_current() {
+          for url in urls {
+            deep_link::handle(app.handle(), url.as_str());
+          }
+        }
+      }
+
       // Auto-setup MCP integration on first launch (desktop only)
       #[cfg(desktop)]
       {
@@ -1277,15 +1848,31 @@ pub fn run() {
         if window.label() == "prefs" {
           return;
         }
+        // Without the tray, closing windows really closes them
+        #[cfg(desktop)]
+        if !tray::keep_running(window.app_handle()) {
+          return;
+        }
         let _ = window.hide();
         api.prevent_close();
       }
     })
     .build(tauri::generate_context!())
     .expect("error while building tauri application")
-    .run(|_app, event| {
-      if let RunEvent::ExitRequested { api, .. } = event {
-        api.prevent_exit();
+    .run(|_app, event| match event {
+      // Keep running in the tray when the last window closes; an explicit
+      // exit (Quit Lokus) carries a code and goes ahead
+      RunEvent::ExitRequested { api, code, .. } => {
+        #[cfg(desktop)]
+        let keep_running = code.is_none() && tray::keep_running(_app);
+        #[cfg(not(desktop))]
+        let keep_running = code.is_none();
+        if keep_running {
+          api.prevent_exit();
+        }
       }
+      #[cfg(desktop)]
+      RunEvent::Exit => tray::cleanup(_app),
+      _ => {}
     });
 }
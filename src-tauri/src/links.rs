@@ -0,0 +1,982 @@
+//! Workspace link graph.
+//!
+//! Parses `[[wikilinks]]`, `![[embeds]]` and relative markdown links from every
+//! note and keeps the graph in memory per workspace. The graph is persisted to
+//! `.lokus/links.db`, so reopening a vault only re-parses notes whose mtime
+//! changed. Links are stored as written and resolved against the current set
+//! of notes on demand, which means creating a note immediately resolves any
+//! dangling links pointing at it. Canvases are link sources too: their file
+//! cards count as embeds of the note they show.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+const GRAPH_VERSION: u32 = 1;
+const GRAPH_FILE: &str = "links.db";
+pub(crate) const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", "node_modules", ".trash"];
+const MAX_CONTEXT_CHARS: usize = 200;
+
+/// Loaded graphs keyed by workspace root
+static GRAPHS: Lazy<Mutex<HashMap<PathBuf, LinkGraph>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// [[target#heading|alias]] and ![[target]]
+pub(crate) static WIKILINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(!?)\[\[([^\[\]|#]*)(#[^\[\]|]*)?(\|[^\[\]]*)?\]\]").unwrap()
+});
+
+// [text](target "title") and ![alt](target)
+pub(crate) static MARKDOWN_LINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(!?)\[([^\]]*)\]\(([^)\s]+)((?:\s+"[^"]*")?)\)"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Wikilink,
+    Embed,
+    Markdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RawLink {
+    /// Target exactly as written in the note
+    pub target: String,
+    pub kind: LinkKind,
+    pub line: usize,
+    /// Trimmed source line, used for backlink previews
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NoteEntry {
+    modified: i64,
+    links: Vec<RawLink>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LinkGraph {
+    version: u32,
+    /// Notes keyed by path relative to the workspace root
+    notes: HashMap<String, NoteEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutgoingLink {
+    pub target: String,
+    /// Absolute path of the linked note, if it exists
+    pub resolved: Option<String>,
+    pub kind: LinkKind,
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backlink {
+    pub source: String,
+    pub kind: LinkKind,
+    pub line: usize,
+    pub context: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub name: String,
+    pub outgoing: usize,
+    pub incoming: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkGraphData {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+// --- Parsing ---
+
+fn is_external(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:") || target.starts_with('#')
+}
+
+/// Extract links from note content, skipping fenced code blocks
+pub(crate) fn parse_links(content: &str) -> Vec<RawLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let context: String = line.trim().chars().take(MAX_CONTEXT_CHARS).collect();
+
+        for caps in WIKILINK_RE.captures_iter(line) {
+            let target = caps[2].trim();
+            // [[#heading]] points inside the current note
+            if target.is_empty() {
+                continue;
+            }
+            links.push(RawLink {
+                target: target.to_string(),
+                kind: if &caps[1] == "!" { LinkKind::Embed } else { LinkKind::Wikilink },
+                line: index + 1,
+                context: context.clone(),
+            });
+        }
+
+        for caps in MARKDOWN_LINK_RE.captures_iter(line) {
+            let target = &caps[3];
+            if is_external(target) {
+                continue;
+            }
+            links.push(RawLink {
+                target: target.to_string(),
+                kind: LinkKind::Markdown,
+                line: index + 1,
+                context: context.clone(),
+            });
+        }
+    }
+
+    links
+}
+
+// --- Path helpers (all paths are '/'-separated and workspace-relative) ---
+
+pub(crate) fn is_note(rel: &str) -> bool {
+    rel.get(rel.len().saturating_sub(3)..)
+        .map_or(false, |ext| ext.eq_ignore_ascii_case(".md"))
+}
+
+/// Files whose links are tracked: notes, canvases and diagrams
+fn is_link_source(rel: &str) -> bool {
+    is_note(rel) || crate::canvas::is_canvas(Path::new(rel)) || crate::diagrams::is_diagram(rel)
+}
+
+fn strip_md(path: &str) -> &str {
+    if is_note(path) {
+        &path[..path.len() - 3]
+    } else {
+        path
+    }
+}
+
+/// True for targets like `image.png`; note names containing dots are not affected
+pub(crate) fn has_foreign_extension(target: &str) -> bool {
+    match target.rsplit_once('.') {
+        Some((_, ext)) => {
+            !ext.is_empty()
+                && ext.len() <= 5
+                && ext.chars().all(|c| c.is_ascii_alphanumeric())
+                && !ext.eq_ignore_ascii_case("md")
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn parent_dir(rel: &str) -> &str {
+    rel.rfind('/').map_or("", |i| &rel[..i])
+}
+
+fn join(dir: &str, rel: &str) -> String {
+    if dir.is_empty() {
+        rel.to_string()
+    } else {
+        format!("{}/{}", dir, rel)
+    }
+}
+
+/// Collapse `.` and `..` segments; `None` if the path escapes the workspace
+pub(crate) fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            other => parts.push(other),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Relative path from directory `from_dir` to file `to`
+pub(crate) fn relative_link(from_dir: &str, to: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|s| !s.is_empty()).collect();
+    let target: Vec<&str> = to.split('/').filter(|s| !s.is_empty()).collect();
+    let common = from.iter().zip(target.iter()).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<&str> = vec![".."; from.len() - common];
+    parts.extend(&target[common..]);
+    parts.join("/")
+}
+
+pub(crate) fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(root)
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+fn file_stem(rel: &str) -> &str {
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    strip_md(name)
+}
+
+// --- Resolution ---
+
+/// Maps link targets onto notes using Obsidian-style rules: wikilinks match
+/// by note name (preferring the linking note's folder, then the shallowest
+/// path) or by vault path; markdown links are relative to the linking note.
+pub(crate) struct Resolver {
+    by_path: HashMap<String, String>,
+    by_stem: HashMap<String, Vec<String>>,
+}
+
+impl Resolver {
+    pub(crate) fn new<'a>(notes: impl Iterator<Item = &'a String>) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_stem: HashMap<String, Vec<String>> = HashMap::new();
+        for rel in notes {
+            by_path.insert(strip_md(rel).to_lowercase(), rel.clone());
+            by_stem
+                .entry(file_stem(rel).to_lowercase())
+                .or_default()
+                .push(rel.clone());
+        }
+        Self { by_path, by_stem }
+    }
+
+    pub(crate) fn resolve(&self, source: &str, kind: LinkKind, target: &str) -> Option<String> {
+        match kind {
+            LinkKind::Markdown => self.resolve_markdown(source, target),
+            LinkKind::Wikilink | LinkKind::Embed => self.resolve_wikilink(source, target),
+        }
+    }
+
+    fn resolve_wikilink(&self, source: &str, target: &str) -> Option<String> {
+        // A leading slash means a vault path, never a note name
+        let vault_path = target.trim().starts_with('/');
+        let target = target.trim().trim_start_matches('/');
+        if has_foreign_extension(target) {
+            return None;
+        }
+        let key = strip_md(target).to_lowercase();
+
+        if vault_path {
+            return self.by_path.get(&key).cloned();
+        }
+        if key.contains('/') {
+            if let Some(rel) = self.by_path.get(&key) {
+                return Some(rel.clone());
+            }
+            let joined = normalize(&join(&parent_dir(source).to_lowercase(), &key))?;
+            return self.by_path.get(&joined).cloned();
+        }
+
+        let candidates = self.by_stem.get(&key)?;
+        let source_dir = parent_dir(source);
+        candidates
+            .iter()
+            .find(|c| parent_dir(c).eq_ignore_ascii_case(source_dir))
+            .or_else(|| candidates.iter().min_by_key(|c| (c.matches('/').count(), c.len())))
+            .cloned()
+    }
+
+    fn resolve_markdown(&self, source: &str, target: &str) -> Option<String> {
+        let target = target.split('#').next().unwrap_or("");
+        let decoded = urlencoding::decode(target)
+            .map(|s| s.into_owned())
+            .unwrap_or_else(|_| target.to_string());
+        if decoded.is_empty() || has_foreign_extension(&decoded) {
+            return None;
+        }
+
+        let path = match decoded.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => join(parent_dir(source), &decoded),
+        };
+        let key = strip_md(&normalize(&path)?).to_lowercase();
+        self.by_path.get(&key).cloned()
+    }
+
+    fn is_ambiguous_stem(&self, stem: &str, excluding: &str) -> bool {
+        self.by_stem
+            .get(&stem.to_lowercase())
+            .map_or(false, |notes| notes.iter().any(|n| n != excluding))
+    }
+}
+
+// --- Rewriting ---
+
+/// Rewrite the links in one note. `retarget` receives each link's kind and the
+/// note it currently resolves to, and returns the note it should point at
+/// instead (or `None` to leave it alone). Returns `None` if nothing changed.
+fn rewrite_note(
+    content: &str,
+    source_before: &str,
+    source_after: &str,
+    resolver: &Resolver,
+    prefer_path_form: bool,
+    retarget: impl Fn(LinkKind, &str) -> Option<String>,
+) -> Option<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        let is_fence = line.trim_start().starts_with("```");
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        if is_fence || in_fence {
+            output.push_str(line);
+            continue;
+        }
+
+        let line = WIKILINK_RE.replace_all(line, |caps: &Captures| {
+            let kind = if &caps[1] == "!" { LinkKind::Embed } else { LinkKind::Wikilink };
+            let target = caps[2].trim();
+            let new_rel = resolver
+                .resolve(source_before, kind, target)
+                .and_then(|current| retarget(kind, &current).filter(|new| *new != current));
+            let Some(new_rel) = new_rel else {
+                return caps[0].to_string();
+            };
+
+            let keep_ext = is_note(target);
+            let mut new_target = if target.contains('/') || prefer_path_form {
+                strip_md(&new_rel).to_string()
+            } else {
+                file_stem(&new_rel).to_string()
+            };
+            if keep_ext {
+                new_target.push_str(".md");
+            }
+            format!(
+                "{}[[{}{}{}]]",
+                &caps[1],
+                new_target,
+                caps.get(3).map_or("", |m| m.as_str()),
+                caps.get(4).map_or("", |m| m.as_str())
+            )
+        });
+
+        let line = MARKDOWN_LINK_RE.replace_all(&line, |caps: &Captures| {
+            let target = &caps[3];
+            if is_external(target) {
+                return caps[0].to_string();
+            }
+            let Some(current) = resolver.resolve(source_before, LinkKind::Markdown, target) else {
+                return caps[0].to_string();
+            };
+            let Some(new_rel) = retarget(LinkKind::Markdown, &current) else {
+                return caps[0].to_string();
+            };
+
+            let fragment = target.find('#').map_or("", |i| &target[i..]);
+            let new_target = format!(
+                "{}{}",
+                relative_link(parent_dir(source_after), &new_rel).replace(' ', "%20"),
+                fragment
+            );
+            if new_target == target {
+                return caps[0].to_string();
+            }
+            format!("{}[{}]({}{})", &caps[1], &caps[2], new_target, &caps[4])
+        });
+
+        output.push_str(&line);
+    }
+
+    if output != content {
+        Some(output)
+    } else {
+        None
+    }
+}
+
+/// `rewrite_note` for a canvas: file cards pointing at `old_rel` move to
+/// `new_rel`, and links inside text cards are rewritten like note links
+fn rewrite_canvas(
+    content: &str,
+    source_before: &str,
+    source_after: &str,
+    resolver: &Resolver,
+    prefer_path_form: bool,
+    old_rel: &str,
+    new_rel: &str,
+) -> Option<String> {
+    let mut canvas = crate::canvas::parse(content).ok()?;
+    let mut changed = false;
+    for node in &mut canvas.nodes {
+        if node.file.as_deref() == Some(old_rel) {
+            node.file = Some(new_rel.to_string());
+            changed = true;
+        }
+        let Some(text) = &node.text else { continue };
+        let retarget = |_: LinkKind, current: &str| (current == old_rel).then(|| new_rel.to_string());
+        if let Some(updated) = rewrite_note(text, source_before, source_after, resolver, prefer_path_form, retarget) {
+            node.text = Some(updated);
+            changed = true;
+        }
+    }
+    if !changed {
+        return None;
+    }
+    crate::canvas::to_json(&canvas).ok()
+}
+
+// --- Graph maintenance ---
+
+fn modified_ms(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// List notes as (relative path, absolute path)
+fn scan_notes(root: &Path) -> Vec<(String, PathBuf)> {
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !(e.file_type().is_dir()
+                    && e.file_name().to_str().map_or(false, |n| EXCLUDED_DIRS.contains(&n)))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = relative_path(root, e.path())?;
+            is_link_source(&rel).then(|| (rel, e.path().to_path_buf()))
+        })
+        .collect()
+}
+
+impl LinkGraph {
+    fn new() -> Self {
+        Self {
+            version: GRAPH_VERSION,
+            notes: HashMap::new(),
+        }
+    }
+
+    fn resolver(&self) -> Resolver {
+        // Diagrams are link targets too; their extensions are too long to be
+        // taken for attachments, so `[[Flow.drawio]]` resolves by name
+        Resolver::new(self.notes.keys().filter(|rel| is_note(rel) || crate::diagrams::is_diagram(rel)))
+    }
+
+    fn update_note(&mut self, rel: &str, content: &str, modified: i64) {
+        let links = if is_note(rel) {
+            parse_links(content)
+        } else if crate::diagrams::is_diagram(rel) {
+            crate::diagrams::parse_links(rel, content)
+        } else {
+            crate::canvas::parse_links(content)
+        };
+        self.notes.insert(rel.to_string(), NoteEntry { modified, links });
+    }
+
+    /// Bring the graph in line with the notes on disk, returning the number of changes
+    fn reconcile(&mut self, root: &Path) -> usize {
+        let on_disk = scan_notes(root);
+        let present: HashSet<&str> = on_disk.iter().map(|(rel, _)| rel.as_str()).collect();
+        let before = self.notes.len();
+        self.notes.retain(|rel, _| present.contains(rel.as_str()));
+        let mut changes = before - self.notes.len();
+
+        for (rel, abs) in &on_disk {
+            let modified = modified_ms(abs);
+            if self.notes.get(rel).map_or(false, |n| n.modified == modified) {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(abs) {
+                self.update_note(rel, &content, modified);
+                changes += 1;
+            }
+        }
+
+        changes
+    }
+
+    /// Resolved (source, target) pairs, ignoring self links and duplicates
+    fn edges(&self, resolver: &Resolver) -> Vec<(String, String)> {
+        let mut edges = HashSet::new();
+        for (source, entry) in &self.notes {
+            for link in &entry.links {
+                if let Some(target) = resolver.resolve(source, link.kind, &link.target) {
+                    if target != *source {
+                        edges.insert((source.clone(), target));
+                    }
+                }
+            }
+        }
+        edges.into_iter().collect()
+    }
+
+    fn backlinks(&self, target: &str) -> Vec<(String, RawLink)> {
+        let resolver = self.resolver();
+        let mut backlinks = Vec::new();
+        for (source, entry) in &self.notes {
+            for link in &entry.links {
+                if resolver.resolve(source, link.kind, &link.target).as_deref() == Some(target) {
+                    backlinks.push((source.clone(), link.clone()));
+                }
+            }
+        }
+        backlinks.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.line.cmp(&b.1.line)));
+        backlinks
+    }
+
+    /// Move `old_rel` to `new_rel` in the graph and rewrite every link that
+    /// pointed at it. The file itself must already have been moved on disk.
+    /// Returns the rewritten files for the caller to save once the graph lock
+    /// is released, since saving re-enters the graph.
+    fn apply_rename(&mut self, root: &Path, old_rel: &str, new_rel: &str) -> Vec<(PathBuf, String)> {
+        // Resolve against the pre-rename state so existing links still match
+        let resolver = self.resolver();
+        let prefer_path_form = resolver.is_ambiguous_stem(file_stem(new_rel), old_rel);
+        let moved_folder = parent_dir(old_rel) != parent_dir(new_rel);
+
+        let mut sources: Vec<String> = self
+            .notes
+            .iter()
+            .filter(|(source, entry)| {
+                entry.links.iter().any(|l| {
+                    resolver.resolve(source, l.kind, &l.target).as_deref() == Some(old_rel)
+                })
+            })
+            .map(|(source, _)| source.clone())
+            .collect();
+        // The moved note's own relative markdown links need new `../` prefixes
+        if moved_folder && !sources.iter().any(|s| s == old_rel) {
+            sources.push(old_rel.to_string());
+        }
+
+        if let Some(entry) = self.notes.remove(old_rel) {
+            self.notes.insert(new_rel.to_string(), entry);
+        }
+
+        let mut rewrites = Vec::new();
+        for source_before in sources {
+            let is_moved_note = source_before == old_rel;
+            let source_after = if is_moved_note { new_rel.to_string() } else { source_before.clone() };
+            let abs = root.join(&source_after);
+            let Ok(content) = fs::read_to_string(&abs) else { continue };
+
+            // Links drawn into diagrams are left as they are
+            if crate::diagrams::is_diagram(&source_after) {
+                continue;
+            }
+            if !is_note(&source_after) {
+                let rewritten = rewrite_canvas(
+                    &content,
+                    &source_before,
+                    &source_after,
+                    &resolver,
+                    prefer_path_form,
+                    old_rel,
+                    new_rel,
+                );
+                if let Some(updated) = rewritten {
+                    self.update_note(&source_after, &updated, modified_ms(&abs));
+                    rewrites.push((abs, updated));
+                }
+                continue;
+            }
+
+            let rewritten = rewrite_note(
+                &content,
+                &source_before,
+                &source_after,
+                &resolver,
+                prefer_path_form,
+                |kind, current| {
+                    if current == old_rel {
+                        Some(new_rel.to_string())
+                    } else if is_moved_note && moved_folder && kind == LinkKind::Markdown {
+                        Some(current.to_string())
+                    } else {
+                        None
+                    }
+                },
+            );
+
+            if let Some(updated) = rewritten {
+                self.update_note(&source_after, &updated, modified_ms(&abs));
+                rewrites.push((abs, updated));
+            }
+        }
+        rewrites
+    }
+}
+
+// --- Persistence ---
+
+fn graph_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(GRAPH_FILE)
+}
+
+fn load_from_disk(root: &Path) -> Option<LinkGraph> {
+    let bytes = fs::read(graph_path(root)).ok()?;
+    let graph: LinkGraph = bincode::deserialize(&bytes).ok()?;
+    (graph.version == GRAPH_VERSION).then_some(graph)
+}
+
+fn save_to_disk(root: &Path, graph: &LinkGraph) -> Result<(), String> {
+    let path = graph_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create link graph directory: {}", e))?;
+    }
+
+    let bytes = bincode::serialize(graph)
+        .map_err(|e| format!("Failed to serialize link graph: {}", e))?;
+    let temp_path = path.with_extension("db.tmp");
+    fs::write(&temp_path, bytes).map_err(|e| format!("Failed to write link graph: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to replace link graph: {}", e))
+}
+
+/// Run `f` against the in-memory graph for `root`, loading or building it first
+fn with_graph<T>(root: &Path, f: impl FnOnce(&mut LinkGraph) -> T) -> Result<T, String> {
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", root.display()));
+    }
+    let mut graphs = GRAPHS.lock().map_err(|e| format!("Link graph lock poisoned: {}", e))?;
+
+    if !graphs.contains_key(root) {
+        let mut graph = load_from_disk(root).unwrap_or_else(LinkGraph::new);
+        if graph.reconcile(root) > 0 {
+            save_to_disk(root, &graph)?;
+        }
+        graphs.insert(root.to_path_buf(), graph);
+    }
+
+    let graph = graphs.get_mut(root).expect("graph inserted above");
+    Ok(f(graph))
+}
+
+fn note_rel(root: &Path, path: &str) -> Result<String, String> {
+    relative_path(root, Path::new(path)).ok_or_else(|| format!("Note is outside the workspace: {}", path))
+}
+
+fn absolute(root: &Path, rel: &str) -> String {
+    root.join(rel).to_string_lossy().to_string()
+}
+
+// --- Hooks for file operations ---
+
+/// Re-parse a saved note in whichever loaded graph contains it
+pub fn notify_file_saved(file_path: &str, content: &str) {
+    let path = Path::new(file_path);
+    if let Ok(mut graphs) = GRAPHS.lock() {
+        for (root, graph) in graphs.iter_mut() {
+            if let Some(rel) = relative_path(root, path).filter(|r| is_link_source(r)) {
+                graph.update_note(&rel, content, modified_ms(path));
+            }
+        }
+    }
+}
+
+/// Drop a deleted note (or every note under a deleted folder)
+pub fn notify_file_removed(file_path: &str) {
+    let path = Path::new(file_path);
+    if let Ok(mut graphs) = GRAPHS.lock() {
+        for (root, graph) in graphs.iter_mut() {
+            if let Some(rel) = relative_path(root, path) {
+                let prefix = format!("{}/", rel);
+                graph.notes.retain(|note, _| *note != rel && !note.starts_with(&prefix));
+            }
+        }
+    }
+}
+
+/// Update the graph after a rename/move and rewrite links pointing at the note
+pub fn notify_file_renamed(old_path: &str, new_path: &str) {
+    let Ok(mut graphs) = GRAPHS.lock() else { return };
+    let mut rewrites = Vec::new();
+    for (root, graph) in graphs.iter_mut() {
+        let (Some(old_rel), Some(new_rel)) = (
+            relative_path(root, Path::new(old_path)),
+            relative_path(root, Path::new(new_path)),
+        ) else {
+            continue;
+        };
+
+        if is_note(&old_rel) && is_note(&new_rel) && Path::new(new_path).is_file() {
+            rewrites.extend(graph.apply_rename(root, &old_rel, &new_rel));
+        } else {
+            // Folder moves and extension changes: re-scan rather than rewrite
+            graph.reconcile(root);
+        }
+    }
+    drop(graphs);
+
+    // Through the central writer so locks, encryption and the other indexes
+    // see the rewritten links like any other save
+    for (path, content) in rewrites {
+        if let Err(e) = crate::handlers::files::write_file_content(path.to_string_lossy().to_string(), content) {
+            tracing::warn!("Failed to update links in {}: {}", path.display(), e);
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_backlinks(workspace_path: String, path: String) -> Result<Vec<Backlink>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let rel = note_rel(&root, &path)?;
+    with_graph(&root, |graph| {
+        graph
+            .backlinks(&rel)
+            .into_iter()
+            .map(|(source, link)| Backlink {
+                source: absolute(&root, &source),
+                kind: link.kind,
+                line: link.line,
+                context: link.context,
+            })
+            .collect()
+    })
+}
+
+#[tauri::command]
+pub fn get_outgoing_links(workspace_path: String, path: String) -> Result<Vec<OutgoingLink>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let rel = note_rel(&root, &path)?;
+    with_graph(&root, |graph| {
+        let resolver = graph.resolver();
+        graph
+            .notes
+            .get(&rel)
+            .map(|entry| {
+                entry
+                    .links
+                    .iter()
+                    .map(|link| OutgoingLink {
+                        target: link.target.clone(),
+                        resolved: resolver
+                            .resolve(&rel, link.kind, &link.target)
+                            .map(|r| absolute(&root, &r)),
+                        kind: link.kind,
+                        line: link.line,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+#[tauri::command]
+pub fn get_orphan_notes(workspace_path: String) -> Result<Vec<String>, String> {
+    let root = PathBuf::from(&workspace_path);
+    with_graph(&root, |graph| {
+        let resolver = graph.resolver();
+        let linked: HashSet<String> = graph
+            .edges(&resolver)
+            .into_iter()
+            .flat_map(|(source, target)| [source, target])
+            .collect();
+
+        let mut orphans: Vec<String> = graph
+            .notes
+            .keys()
+            .filter(|rel| is_note(rel) && !linked.contains(*rel))
+            .map(|rel| absolute(&root, rel))
+            .collect();
+        orphans.sort();
+        orphans
+    })
+}
+
+#[tauri::command]
+pub fn get_link_graph(workspace_path: String) -> Result<LinkGraphData, String> {
+    let root = PathBuf::from(&workspace_path);
+    with_graph(&root, |graph| {
+        let resolver = graph.resolver();
+        let edges = graph.edges(&resolver);
+
+        let mut outgoing: HashMap<&str, usize> = HashMap::new();
+        let mut incoming: HashMap<&str, usize> = HashMap::new();
+        for (source, target) in &edges {
+            *outgoing.entry(source.as_str()).or_insert(0) += 1;
+            *incoming.entry(target.as_str()).or_insert(0) += 1;
+        }
+
+        let nodes = graph
+            .notes
+            .keys()
+            .map(|rel| GraphNode {
+                id: absolute(&root, rel),
+                name: file_stem(rel).to_string(),
+                outgoing: outgoing.get(rel.as_str()).copied().unwrap_or(0),
+                incoming: incoming.get(rel.as_str()).copied().unwrap_or(0),
+            })
+            .collect();
+
+        let edges = edges
+            .iter()
+            .map(|(source, target)| GraphEdge {
+                source: absolute(&root, source),
+                target: absolute(&root, target),
+            })
+            .collect();
+
+        LinkGraphData { nodes, edges }
+    })
+}
+
+/// Discard the cached graph and re-parse every note
+#[tauri::command]
+pub fn rebuild_link_graph(workspace_path: String) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", workspace_path));
+    }
+    let mut graph = LinkGraph::new();
+    graph.reconcile(&root);
+    save_to_disk(&root, &graph)?;
+    let count = graph.notes.len();
+
+    GRAPHS
+        .lock()
+        .map_err(|e| format!("Link graph lock poisoned: {}", e))?
+        .insert(root, graph);
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(notes: &[&str]) -> Resolver {
+        let owned: Vec<String> = notes.iter().map(|s| s.to_string()).collect();
+        Resolver::new(owned.iter())
+    }
+
+    #[test]
+    fn test_parse_links_kinds_and_fences() {
+        let content = "See [[Note A#Intro|alias]] and ![[diagram.png]]\n```\n[[ignored]]\n```\n[doc](sub/Doc%20B.md) [web](https://x.y)";
+        let links = parse_links(content);
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].target, "Note A");
+        assert_eq!(links[0].kind, LinkKind::Wikilink);
+        assert_eq!(links[1].kind, LinkKind::Embed);
+        assert_eq!(links[2].target, "sub/Doc%20B.md");
+        assert_eq!(links[2].line, 5);
+    }
+
+    #[test]
+    fn test_resolve_wikilink_prefers_same_folder() {
+        let r = resolver(&["a/Note.md", "b/Note.md", "b/Other.md"]);
+        assert_eq!(r.resolve("b/Other.md", LinkKind::Wikilink, "note"), Some("b/Note.md".to_string()));
+        assert_eq!(r.resolve("root.md", LinkKind::Wikilink, "a/Note"), Some("a/Note.md".to_string()));
+        assert_eq!(r.resolve("root.md", LinkKind::Wikilink, "image.png"), None);
+    }
+
+    #[test]
+    fn test_resolve_markdown_relative() {
+        let r = resolver(&["docs/Doc B.md", "index.md"]);
+        assert_eq!(r.resolve("docs/x.md", LinkKind::Markdown, "../index.md#top"), Some("index.md".to_string()));
+        assert_eq!(r.resolve("index.md", LinkKind::Markdown, "docs/Doc%20B.md"), Some("docs/Doc B.md".to_string()));
+        assert_eq!(r.resolve("index.md", LinkKind::Markdown, "../outside.md"), None);
+    }
+
+    #[test]
+    fn test_rename_rewrites_incoming_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("a.md"), "Link to [[Old]] and [md](sub/Old.md#h)\n").unwrap();
+        fs::write(root.join("sub").join("Old.md"), "Back to [a](../a.md)\n").unwrap();
+
+        let mut graph = LinkGraph::new();
+        graph.reconcile(root);
+
+        fs::rename(root.join("sub").join("Old.md"), root.join("New.md")).unwrap();
+        for (path, content) in graph.apply_rename(root, "sub/Old.md", "New.md") {
+            fs::write(path, content).unwrap();
+        }
+
+        let a = fs::read_to_string(root.join("a.md")).unwrap();
+        assert_eq!(a, "Link to [[New]] and [md](New.md#h)\n");
+        let moved = fs::read_to_string(root.join("New.md")).unwrap();
+        assert_eq!(moved, "Back to [a](a.md)\n");
+        assert_eq!(graph.backlinks("New.md").len(), 2);
+    }
+
+    #[test]
+    fn test_canvas_file_cards_are_backlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("Plan.md"), "# Plan\n").unwrap();
+        let canvas = r#"{"nodes":[{"id":"a","type":"file","file":"Plan.md","x":0,"y":0,"width":400,"height":300},{"id":"b","type":"text","text":"See [[Plan]]","x":500,"y":0,"width":200,"height":100}],"edges":[]}"#;
+        fs::write(root.join("Board.canvas"), canvas).unwrap();
+
+        let mut graph = LinkGraph::new();
+        graph.reconcile(root);
+        assert_eq!(graph.backlinks("Plan.md").len(), 2);
+
+        fs::rename(root.join("Plan.md"), root.join("Roadmap.md")).unwrap();
+        for (path, content) in graph.apply_rename(root, "Plan.md", "Roadmap.md") {
+            fs::write(path, content).unwrap();
+        }
+        let board = crate::canvas::parse(&fs::read_to_string(root.join("Board.canvas")).unwrap()).unwrap();
+        assert_eq!(board.nodes[0].file.as_deref(), Some("Roadmap.md"));
+        assert_eq!(board.nodes[1].text.as_deref(), Some("See [[Roadmap]]"));
+    }
+
+    #[test]
+    fn test_diagrams_are_graph_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("Plan.md"), "Overview: ![[Flow.drawio]] and [[Sketch.excalidraw]]\n").unwrap();
+        fs::write(
+            root.join("Sketch.excalidraw"),
+            r#"{"type":"excalidraw","elements":[{"type":"rectangle","x":0,"y":0,"width":10,"height":10,"link":"[[Plan]]"}]}"#,
+        )
+        .unwrap();
+        fs::write(root.join("Flow.drawio"), "<mxfile><diagram></diagram></mxfile>").unwrap();
+
+        let mut graph = LinkGraph::new();
+        graph.reconcile(root);
+        let mut edges = graph.edges(&graph.resolver());
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![
+                ("Plan.md".to_string(), "Flow.drawio".to_string()),
+                ("Plan.md".to_string(), "Sketch.excalidraw".to_string()),
+                ("Sketch.excalidraw".to_string(), "Plan.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relative_link() {
+        assert_eq!(relative_link("a/b", "a/c/d.md"), "../c/d.md");
+        assert_eq!(relative_link("", "x.md"), "x.md");
+    }
+}
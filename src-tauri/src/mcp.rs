@@ -7,6 +7,9 @@
  * - Provides IPC commands for frontend communication
  */
 
+pub mod resources;
+pub mod tools;
+
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, State};
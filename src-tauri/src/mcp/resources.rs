@@ -0,0 +1,359 @@
+//! Streamed resource reads for MCP clients.
+//!
+//! Notes and attachments are served by the API server without loading them
+//! into memory:
+//!
+//! - `GET /api/mcp/resources?path=<rel>` streams the raw bytes, honouring a
+//!   single `Range: bytes=...` header (206 with `Content-Range`).
+//! - `GET /api/mcp/resources/read?path=<rel>&offset=&length=` returns one
+//!   chunk as MCP resource contents (`text` for text types, base64 `blob`
+//!   otherwise) plus the offset of the next chunk.
+//!
+//! Whole-file reads larger than the configured limit (`mcp_max_resource_mb`
+//! in `.settings.dat`) are refused with 413; ranged and chunked reads of a
+//! large file still work as long as each piece fits the limit.
+
+use crate::api_server::ApiState;
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreBuilder;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+const SETTINGS_FILE: &str = ".settings.dat";
+const MAX_SIZE_KEY: &str = "mcp_max_resource_mb";
+const DEFAULT_MAX_SIZE_MB: u64 = 50;
+const DEFAULT_CHUNK_SIZE: u64 = 1024 * 1024;
+/// Bytes sniffed for content-type detection
+const SNIFF_LEN: usize = 512;
+
+#[derive(Debug, Deserialize)]
+pub struct ResourceQuery {
+    /// Path relative to the workspace
+    pub path: String,
+    pub offset: Option<u64>,
+    pub length: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceChunk {
+    pub path: String,
+    pub mime_type: String,
+    /// Total size of the resource in bytes
+    pub size: u64,
+    pub offset: u64,
+    pub length: u64,
+    /// Offset to request next, `None` once the end is reached
+    pub next_offset: Option<u64>,
+    pub text: Option<String>,
+    /// Base64 bytes for binary resources
+    pub blob: Option<String>,
+}
+
+// --- Settings ---
+
+pub fn max_resource_bytes(app: &AppHandle) -> u64 {
+    let mb = StoreBuilder::new(app, PathBuf::from(SETTINGS_FILE))
+        .build()
+        .ok()
+        .and_then(|store| {
+            let _ = store.reload();
+            store.get(MAX_SIZE_KEY).and_then(|v| v.as_u64())
+        })
+        .filter(|mb| *mb > 0)
+        .unwrap_or(DEFAULT_MAX_SIZE_MB);
+    mb * 1024 * 1024
+}
+
+// --- Detection ---
+
+/// Content type from magic bytes, falling back to the extension
+pub fn detect_content_type(path: &Path, head: &[u8]) -> &'static str {
+    let sniffed = match head {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some("audio/wav"),
+        [b'%', b'P', b'D', b'F', ..] => Some("application/pdf"),
+        [b'P', b'K', 0x03, 0x04, ..] => Some("application/zip"),
+        [b'I', b'D', b'3', ..] => Some("audio/mpeg"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("video/mp4"),
+        _ => None,
+    };
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let by_extension = match ext.as_str() {
+        "md" | "markdown" => Some("text/markdown"),
+        "txt" => Some("text/plain"),
+        "json" | "canvas" | "kanban" => Some("application/json"),
+        "csv" => Some("text/csv"),
+        "html" | "htm" => Some("text/html"),
+        "svg" => Some("image/svg+xml"),
+        "docx" => Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+        "xlsx" => Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        "m4a" => Some("audio/mp4"),
+        _ => None,
+    };
+    match (sniffed, by_extension) {
+        // Office files and m4a are zip/mp4 containers; the extension is more specific
+        (Some("application/zip" | "video/mp4"), Some(specific)) => specific,
+        (Some(sniffed), _) => sniffed,
+        (None, Some(by_extension)) => by_extension,
+        // The sniffed prefix may end partway through a character
+        (None, None) if !head.is_empty() && std::str::from_utf8(head).map_or_else(|e| e.error_len().is_none(), |_| true) => {
+            "text/plain"
+        }
+        (None, None) => "application/octet-stream",
+    }
+}
+
+fn is_text(mime: &str) -> bool {
+    mime.starts_with("text/") || mime == "application/json" || mime == "image/svg+xml"
+}
+
+// --- Ranges ---
+
+/// Parse a single `bytes=` range against a resource of `size` bytes into
+/// an inclusive `(start, end)`. `Err` means the range can't be satisfied.
+fn parse_range(header: &str, size: u64) -> Result<(u64, u64), ()> {
+    let spec = header.trim().strip_prefix("bytes=").ok_or(())?;
+    if spec.contains(',') || size == 0 {
+        return Err(());
+    }
+    let (start, end) = spec.split_once('-').ok_or(())?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| ())?;
+            if suffix == 0 {
+                return Err(());
+            }
+            (size.saturating_sub(suffix), size - 1)
+        }
+        (start, "") => (start.parse().map_err(|_| ())?, size - 1),
+        (start, end) => (start.parse().map_err(|_| ())?, end.parse::<u64>().map_err(|_| ())?.min(size - 1)),
+    };
+    if start > end || start >= size {
+        return Err(());
+    }
+    Ok((start, end))
+}
+
+// --- Handlers ---
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    let body = crate::api_server::ApiResponse::<()> { success: false, data: None, error: Some(message.into()) };
+    (status, Json(body)).into_response()
+}
+
+fn too_large(length: u64, limit: u64) -> Response {
+    error(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "Resource is {:.1} MB, over the {} MB limit for MCP reads. Request it in ranges or raise mcp_max_resource_mb.",
+            length as f64 / (1024.0 * 1024.0),
+            limit / (1024 * 1024)
+        ),
+    )
+}
+
+/// Resolve a workspace-relative path, refusing escapes and Lokus' own data
+async fn resolve(state: &ApiState, rel: &str) -> Result<PathBuf, Response> {
+    let workspace = state.current_workspace.read().await.clone();
+    let root = workspace.ok_or_else(|| error(StatusCode::SERVICE_UNAVAILABLE, "No workspace open"))?;
+    let rel = rel.trim_start_matches('/');
+    let relative = Path::new(rel);
+    let safe = !rel.is_empty()
+        && relative.components().all(|c| matches!(c, Component::Normal(_)))
+        && !relative.starts_with(".lokus");
+    if !safe {
+        return Err(error(StatusCode::BAD_REQUEST, format!("Invalid resource path: {}", rel)));
+    }
+    let path = Path::new(&root).join(relative);
+    if !path.is_file() {
+        return Err(error(StatusCode::NOT_FOUND, format!("Resource not found: {}", rel)));
+    }
+    Ok(path)
+}
+
+async fn sniff(file: &mut tokio::fs::File) -> std::io::Result<Vec<u8>> {
+    let mut head = vec![0; SNIFF_LEN];
+    let mut filled = 0;
+    while filled < SNIFF_LEN {
+        let read = file.read(&mut head[filled..]).await?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    head.truncate(filled);
+    file.rewind().await?;
+    Ok(head)
+}
+
+/// Stream a resource's bytes, optionally one range of them
+pub async fn stream_resource(State(state): State<ApiState>, Query(query): Query<ResourceQuery>, headers: HeaderMap) -> Response {
+    let path = match resolve(&state, &query.path).await {
+        Ok(path) => path,
+        Err(response) => return response,
+    };
+    let opened = async {
+        let mut file = tokio::fs::File::open(&path).await?;
+        let size = file.metadata().await?.len();
+        let head = sniff(&mut file).await?;
+        Ok::<_, std::io::Error>((file, size, head))
+    };
+    let (mut file, size, head) = match opened.await {
+        Ok(opened) => opened,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to open resource: {}", e)),
+    };
+    let mime = detect_content_type(&path, &head);
+
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let (start, end) = match range.map(|r| parse_range(r, size)) {
+        None => (0, size.saturating_sub(1)),
+        Some(Ok(range)) => range,
+        Some(Err(())) => {
+            let mut response = error(StatusCode::RANGE_NOT_SATISFIABLE, "Requested range is not satisfiable");
+            if let Ok(value) = HeaderValue::from_str(&format!("bytes */{}", size)) {
+                response.headers_mut().insert(header::CONTENT_RANGE, value);
+            }
+            return response;
+        }
+    };
+    let length = if size == 0 { 0 } else { end - start + 1 };
+    let limit = max_resource_bytes(&state.app_handle);
+    if length > limit {
+        return too_large(length, limit);
+    }
+
+    if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read resource: {}", e));
+    }
+    let body = Body::from_stream(ReaderStream::new(file.take(length)));
+    let mut response = Response::new(body);
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(mime));
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    if range.is_some() {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        if let Ok(value) = HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, size)) {
+            response.headers_mut().insert(header::CONTENT_RANGE, value);
+        }
+    }
+    response
+}
+
+/// Read one chunk of a resource as MCP resource contents
+pub async fn read_resource_chunk(State(state): State<ApiState>, Query(query): Query<ResourceQuery>) -> Response {
+    let path = match resolve(&state, &query.path).await {
+        Ok(path) => path,
+        Err(response) => return response,
+    };
+    let limit = max_resource_bytes(&state.app_handle);
+    let requested = query.length.unwrap_or(DEFAULT_CHUNK_SIZE).max(1);
+    if requested > limit {
+        return too_large(requested, limit);
+    }
+
+    let read = async {
+        let mut file = tokio::fs::File::open(&path).await?;
+        let size = file.metadata().await?.len();
+        let head = sniff(&mut file).await?;
+        let offset = query.offset.unwrap_or(0).min(size);
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        let mut bytes = Vec::with_capacity(requested.min(size - offset) as usize);
+        file.take(requested).read_to_end(&mut bytes).await?;
+        Ok::<_, std::io::Error>((size, head, offset, bytes))
+    };
+    let (size, head, offset, mut bytes) = match read.await {
+        Ok(read) => read,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read resource: {}", e)),
+    };
+    let mime = detect_content_type(&path, &head);
+
+    let (text, blob) = if is_text(mime) {
+        // Never split a UTF-8 sequence; the next chunk starts where this one stops
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => bytes.len(),
+        };
+        bytes.truncate(valid.max(1).min(bytes.len()));
+        (Some(String::from_utf8_lossy(&bytes).into_owned()), None)
+    } else {
+        (None, Some(general_purpose::STANDARD.encode(&bytes)))
+    };
+    let length = bytes.len() as u64;
+    let next = offset + length;
+    Json(ResourceChunk {
+        path: query.path,
+        mime_type: mime.to_string(),
+        size,
+        offset,
+        length,
+        next_offset: (next < size).then_some(next),
+        text,
+        blob,
+    })
+    .into_response()
+}
+
+// --- Tauri Commands ---
+
+/// Largest whole resource MCP clients may read at once, in megabytes
+#[tauri::command]
+pub fn mcp_get_max_resource_size(app: AppHandle) -> u64 {
+    max_resource_bytes(&app) / (1024 * 1024)
+}
+
+#[tauri::command]
+pub fn mcp_set_max_resource_size(app: AppHandle, megabytes: u64) -> Result<(), String> {
+    if megabytes == 0 {
+        return Err("The resource size limit must be at least 1 MB".to_string());
+    }
+    let store = StoreBuilder::new(&app, PathBuf::from(SETTINGS_FILE))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    store.set(MAX_SIZE_KEY.to_string(), serde_json::json!(megabytes));
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Ok((0, 99)));
+        assert_eq!(parse_range("bytes=900-", 1000), Ok((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Ok((900, 999)));
+        assert_eq!(parse_range("bytes=500-5000", 1000), Ok((500, 999)));
+        assert!(parse_range("bytes=1000-", 1000).is_err());
+        assert!(parse_range("bytes=0-1,5-6", 1000).is_err());
+        assert!(parse_range("items=0-1", 1000).is_err());
+    }
+
+    #[test]
+    fn test_detect_content_type() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A];
+        assert_eq!(detect_content_type(Path::new("wrong.jpg"), &png), "image/png");
+        assert_eq!(detect_content_type(Path::new("a.md"), b"# Title"), "text/markdown");
+        assert_eq!(
+            detect_content_type(Path::new("doc.docx"), b"PK\x03\x04rest"),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        );
+        assert_eq!(detect_content_type(Path::new("notes"), b"plain words"), "text/plain");
+        assert_eq!(detect_content_type(Path::new("blob"), &[0xC3, 0x28]), "application/octet-stream");
+    }
+}
@@ -0,0 +1,323 @@
+//! Vault tools for MCP clients.
+//!
+//! The Node MCP server lists these through `GET /api/mcp/tools` on the API
+//! server and runs them with `POST /api/mcp/tools/<name>`, so assistants can
+//! search, manage tasks and boards, and write to the daily note through the
+//! same code paths the app uses. Each tool can be switched on or off in
+//! settings (`mcp_tool_permissions` in `.settings.dat`); tools that change
+//! the vault start off. Disabled tools are neither listed nor callable.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreBuilder;
+
+const SETTINGS_FILE: &str = ".settings.dat";
+const PERMISSIONS_KEY: &str = "mcp_tool_permissions";
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: Value,
+    /// Whether the tool changes the vault
+    pub writes: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolPermission {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub writes: bool,
+    pub enabled: bool,
+}
+
+pub fn definitions() -> Vec<ToolDefinition> {
+    vec![
+        ToolDefinition {
+            name: "search_notes",
+            description: "Full-text search across the notes in the open workspace, best matches first.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to search for" },
+                    "limit": { "type": "integer", "description": "Maximum number of results (default 10)" }
+                },
+                "required": ["query"]
+            }),
+            writes: false,
+        },
+        ToolDefinition {
+            name: "create_task",
+            description: "Create a task. Natural due dates in the title (e.g. \"tomorrow 3pm\") are understood.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "description": { "type": "string" },
+                    "notePath": { "type": "string", "description": "Note the task belongs to" },
+                    "dueDate": { "type": "string", "description": "RFC 3339 due date" }
+                },
+                "required": ["title"]
+            }),
+            writes: true,
+        },
+        ToolDefinition {
+            name: "list_tasks",
+            description: "List tasks, optionally only those with one status.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "status": {
+                        "type": "string",
+                        "enum": ["todo", "in-progress", "urgent", "needs-info", "completed", "cancelled", "delegated"]
+                    }
+                }
+            }),
+            writes: false,
+        },
+        ToolDefinition {
+            name: "move_kanban_card",
+            description: "Move a card to another column of a kanban board. Linked tasks follow the card's new status.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "boardPath": { "type": "string", "description": "Board file, relative to the workspace" },
+                    "cardId": { "type": "string" },
+                    "toColumn": { "type": "string", "description": "Column id or name" }
+                },
+                "required": ["boardPath", "cardId", "toColumn"]
+            }),
+            writes: true,
+        },
+        ToolDefinition {
+            name: "get_backlinks",
+            description: "Notes and canvases that link to or embed a note.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Note path, relative to the workspace" }
+                },
+                "required": ["path"]
+            }),
+            writes: false,
+        },
+        ToolDefinition {
+            name: "append_to_daily_note",
+            description: "Append markdown to a daily note, creating the note from its template if needed.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "content": { "type": "string" },
+                    "date": { "type": "string", "description": "YYYY-MM-DD, default today" }
+                },
+                "required": ["content"]
+            }),
+            writes: true,
+        },
+    ]
+}
+
+// --- Permissions ---
+
+fn load_permissions(app: &AppHandle) -> HashMap<String, bool> {
+    let Ok(store) = StoreBuilder::new(app, PathBuf::from(SETTINGS_FILE)).build() else {
+        return HashMap::new();
+    };
+    let _ = store.reload();
+    store
+        .get(PERMISSIONS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_permissions(app: &AppHandle, permissions: &HashMap<String, bool>) -> Result<(), String> {
+    let store = StoreBuilder::new(app, PathBuf::from(SETTINGS_FILE))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    let value = serde_json::to_value(permissions).map_err(|e| e.to_string())?;
+    store.set(PERMISSIONS_KEY.to_string(), value);
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Read-only tools are enabled until switched off; tools that write need
+/// switching on first
+fn is_enabled(permissions: &HashMap<String, bool>, tool: &ToolDefinition) -> bool {
+    permissions.get(tool.name).copied().unwrap_or(!tool.writes)
+}
+
+pub fn enabled_definitions(app: &AppHandle) -> Vec<ToolDefinition> {
+    let permissions = load_permissions(app);
+    definitions().into_iter().filter(|tool| is_enabled(&permissions, tool)).collect()
+}
+
+// --- Execution ---
+
+fn string_arg(args: &Value, key: &str) -> Option<String> {
+    args.get(key).and_then(Value::as_str).map(str::to_string).filter(|s| !s.trim().is_empty())
+}
+
+fn required(args: &Value, key: &str) -> Result<String, String> {
+    string_arg(args, key).ok_or_else(|| format!("Missing required argument: {}", key))
+}
+
+/// Resolve a workspace-relative path, refusing ones that leave the workspace
+fn workspace_file(root: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel = rel.trim_start_matches('/');
+    if Path::new(rel).components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+        return Err(format!("Invalid path: {}", rel));
+    }
+    Ok(root.join(rel))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+async fn move_kanban_card(app: &AppHandle, root: &Path, args: &Value) -> Result<Value, String> {
+    let board_path = workspace_file(root, &required(args, "boardPath")?)?;
+    let card_id = required(args, "cardId")?;
+    let to_column = required(args, "toColumn")?;
+
+    let board = crate::kanban::load_board_from_file(&board_path).await?;
+    let from = board
+        .columns
+        .iter()
+        .find(|(_, column)| column.cards.iter().any(|card| card.id == card_id))
+        .map(|(id, _)| id.clone())
+        .ok_or_else(|| format!("Card '{}' not found", card_id))?;
+    let to = board
+        .columns
+        .iter()
+        .find(|(id, column)| **id == to_column || column.name.eq_ignore_ascii_case(&to_column))
+        .map(|(id, _)| id.clone())
+        .ok_or_else(|| format!("Column '{}' not found", to_column))?;
+
+    let board_path = board_path.to_string_lossy().to_string();
+    crate::kanban::move_card_between_columns(app.clone(), board_path, card_id.clone(), from.clone(), to.clone()).await?;
+    Ok(json!({ "cardId": card_id, "fromColumn": from, "toColumn": to }))
+}
+
+async fn append_to_daily_note(app: &AppHandle, root: &Path, args: &Value) -> Result<Value, String> {
+    let content = required(args, "content")?;
+    let workspace = root.to_string_lossy().to_string();
+    let note = crate::daily_notes::daily_note_open(app.clone(), workspace, string_arg(args, "date")).await?;
+
+    let existing = crate::handlers::files::read_file_content(note.path.clone()).await?;
+    let mut updated = existing.trim_end_matches('\n').to_string();
+    if !updated.is_empty() {
+        updated.push_str("\n\n");
+    }
+    updated.push_str(content.trim_end());
+    updated.push('\n');
+    crate::handlers::files::write_file_content(note.path.clone(), updated)?;
+    Ok(json!({ "path": note.path, "date": note.date, "created": note.created }))
+}
+
+/// Run a tool against the workspace at `root`
+pub async fn call(app: &AppHandle, root: &Path, name: &str, args: Value) -> Result<Value, String> {
+    let Some(tool) = definitions().into_iter().find(|tool| tool.name == name) else {
+        return Err(format!("Unknown tool: {}", name));
+    };
+    if !is_enabled(&load_permissions(app), &tool) {
+        return Err(format!("The {} tool is disabled in Lokus settings", name));
+    }
+
+    match name {
+        "search_notes" => {
+            let query = required(&args, "query")?;
+            let limit = args.get("limit").and_then(Value::as_u64).map_or(DEFAULT_SEARCH_LIMIT, |l| l as usize);
+            let workspace = root.to_string_lossy().to_string();
+            to_value(crate::search::search_query_ranked(workspace, query, Some(limit)).await?)
+        }
+        "create_task" => {
+            let note_path = string_arg(&args, "notePath")
+                .map(|rel| workspace_file(root, &rel).map(|p| p.to_string_lossy().to_string()))
+                .transpose()?;
+            let task = crate::tasks::create_task(
+                app.clone(),
+                required(&args, "title")?,
+                string_arg(&args, "description"),
+                note_path,
+                None,
+                string_arg(&args, "dueDate"),
+                None,
+                None,
+            )
+            .await?;
+            to_value(task)
+        }
+        "list_tasks" => match args.get("status").filter(|s| !s.is_null()) {
+            Some(status) => {
+                let status = serde_json::from_value(status.clone()).map_err(|e| format!("Invalid status: {}", e))?;
+                to_value(crate::tasks::get_tasks_by_status(app.clone(), status, None).await?)
+            }
+            None => to_value(crate::tasks::get_all_tasks(app.clone()).await?),
+        },
+        "move_kanban_card" => move_kanban_card(app, root, &args).await,
+        "get_backlinks" => {
+            let path = workspace_file(root, &required(&args, "path")?)?;
+            let workspace = root.to_string_lossy().to_string();
+            to_value(crate::links::get_backlinks(workspace, path.to_string_lossy().to_string())?)
+        }
+        "append_to_daily_note" => append_to_daily_note(app, root, &args).await,
+        _ => Err(format!("Unknown tool: {}", name)),
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Every MCP tool with its permission toggle, for the settings screen
+#[tauri::command]
+pub fn mcp_tools_list(app: AppHandle) -> Vec<ToolPermission> {
+    let permissions = load_permissions(&app);
+    definitions()
+        .into_iter()
+        .map(|tool| ToolPermission {
+            enabled: is_enabled(&permissions, &tool),
+            name: tool.name,
+            description: tool.description,
+            writes: tool.writes,
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn mcp_tool_set_enabled(app: AppHandle, name: String, enabled: bool) -> Result<(), String> {
+    if !definitions().iter().any(|tool| tool.name == name) {
+        return Err(format!("Unknown tool: {}", name));
+    }
+    let mut permissions = load_permissions(&app);
+    permissions.insert(name, enabled);
+    save_permissions(&app, &permissions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permissions_default_to_read_only_and_paths_stay_inside() {
+        let tool = |name: &str| definitions().into_iter().find(|t| t.name == name).unwrap();
+        let permissions = HashMap::new();
+        assert!(is_enabled(&permissions, &tool("search_notes")));
+        assert!(!is_enabled(&permissions, &tool("create_task")));
+        let permissions = HashMap::from([("create_task".to_string(), true), ("search_notes".to_string(), false)]);
+        assert!(is_enabled(&permissions, &tool("create_task")));
+        assert!(!is_enabled(&permissions, &tool("search_notes")));
+
+        let root = Path::new("/vault");
+        assert_eq!(workspace_file(root, "/boards/a.kanban").unwrap(), root.join("boards/a.kanban"));
+        assert!(workspace_file(root, "../etc/passwd").is_err());
+
+        let names: Vec<&str> = definitions().iter().map(|t| t.name).collect();
+        assert_eq!(names.len(), 6);
+        assert!(names.contains(&"append_to_daily_note"));
+    }
+}
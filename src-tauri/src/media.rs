@@ -0,0 +1,365 @@
+//! Audio and video attachment metadata.
+//!
+//! Audio is probed with symphonia, which reads MP3, AAC, MP4/M4A, ALAC, WAV,
+//! FLAC and Ogg without any system tools. Video (and audio symphonia can't
+//! read) goes through `ffprobe` when it is installed; without it videos only
+//! get a duration if symphonia can read their audio track.
+//!
+//! Durations are also stored in the metadata cache, so smart folders can
+//! filter attachments by length. Desktop only, like transcription.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+use std::process::Command;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "aac", "wav", "flac", "ogg", "oga", "opus", "alac", "aiff"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "m4v", "mov", "webm", "mkv", "avi"];
+const DEFAULT_BUCKETS: usize = 200;
+const MAX_BUCKETS: usize = 10_000;
+/// Frames folded into one peak while decoding, before bucketing
+const PEAK_WINDOW: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Audio,
+    Video,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaMetadata {
+    pub duration_ms: Option<i64>,
+    /// Codec of the main stream (video stream for videos)
+    pub codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    /// Bits per second, over the whole file
+    pub bitrate: Option<u64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaInfo {
+    pub path: String,
+    pub kind: MediaKind,
+    #[serde(flatten)]
+    pub metadata: MediaMetadata,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Waveform {
+    /// Peak amplitude per bucket, 0.0 to 1.0
+    pub peaks: Vec<f32>,
+    pub duration_ms: i64,
+    pub sample_rate: u32,
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase()
+}
+
+pub fn kind_of(path: &Path) -> Option<MediaKind> {
+    let ext = extension(path);
+    if AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        Some(MediaKind::Audio)
+    } else if VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+        Some(MediaKind::Video)
+    } else {
+        None
+    }
+}
+
+fn open_format(path: &Path) -> Result<Box<dyn FormatReader>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    hint.with_extension(&extension(path));
+    symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map(|probed| probed.format)
+        .map_err(|e| format!("Unsupported media format: {}", e))
+}
+
+/// Stream details from the container headers, without decoding
+fn probe_symphonia(path: &Path) -> Result<MediaMetadata, String> {
+    let format = open_format(path)?;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("The file has no audio track")?;
+    let params = &track.codec_params;
+    let duration_ms = match (params.n_frames, params.time_base, params.sample_rate) {
+        (Some(frames), Some(time_base), _) => {
+            let time = time_base.calc_time(frames);
+            Some((time.seconds as f64 * 1000.0 + time.frac * 1000.0).round() as i64)
+        }
+        (Some(frames), None, Some(rate)) if rate > 0 => Some((frames as f64 * 1000.0 / rate as f64).round() as i64),
+        _ => None,
+    };
+    let codec = symphonia::default::get_codecs().get_codec(params.codec).map(|c| c.short_name.to_string());
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Ok(MediaMetadata {
+        duration_ms,
+        codec: codec.clone(),
+        audio_codec: codec,
+        sample_rate: params.sample_rate,
+        channels: params.channels.map(|c| c.count() as u32),
+        bitrate: duration_ms.filter(|&ms| ms > 0).map(|ms| size * 8 * 1000 / ms as u64),
+        ..Default::default()
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FfprobeOutput {
+    #[serde(default)]
+    streams: Vec<FfprobeStream>,
+    format: Option<FfprobeFormat>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FfprobeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    avg_frame_rate: Option<String>,
+    sample_rate: Option<String>,
+    channels: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FfprobeFormat {
+    duration: Option<String>,
+    bit_rate: Option<String>,
+}
+
+/// ffprobe rates look like `30000/1001`
+fn parse_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/').unwrap_or((rate, "1"));
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    (den != 0.0 && num > 0.0).then(|| num / den)
+}
+
+fn parse_ffprobe(json: &str) -> Result<MediaMetadata, String> {
+    let output: FfprobeOutput = serde_json::from_str(json).map_err(|e| format!("Unexpected ffprobe output: {}", e))?;
+    let stream = |kind: &str| output.streams.iter().find(|s| s.codec_type.as_deref() == Some(kind));
+    let video = stream("video");
+    let audio = stream("audio");
+    let format = output.format.unwrap_or_default();
+    Ok(MediaMetadata {
+        duration_ms: format
+            .duration
+            .and_then(|d| d.parse::<f64>().ok())
+            .map(|seconds| (seconds * 1000.0).round() as i64),
+        codec: video.or(audio).and_then(|s| s.codec_name.clone()),
+        audio_codec: audio.and_then(|s| s.codec_name.clone()),
+        width: video.and_then(|s| s.width),
+        height: video.and_then(|s| s.height),
+        frame_rate: video.and_then(|s| s.avg_frame_rate.as_deref()).and_then(parse_rate),
+        bitrate: format.bit_rate.and_then(|b| b.parse().ok()),
+        sample_rate: audio.and_then(|s| s.sample_rate.as_deref()).and_then(|r| r.parse().ok()),
+        channels: audio.and_then(|s| s.channels),
+    })
+}
+
+fn probe_ffprobe(path: &Path) -> Result<MediaMetadata, String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path)
+        .output()
+        .map_err(|_| "Reading video metadata needs ffprobe (ffmpeg) installed".to_string())?;
+    if !output.status.success() {
+        return Err(format!("ffprobe failed for {}: {}", path.display(), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    parse_ffprobe(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub fn metadata(path: &Path) -> Result<MediaInfo, String> {
+    let kind = kind_of(path).ok_or_else(|| format!("Not an audio or video file: {}", path.display()))?;
+    let metadata = match kind {
+        MediaKind::Audio => probe_symphonia(path).or_else(|e| probe_ffprobe(path).map_err(|_| e))?,
+        MediaKind::Video => probe_ffprobe(path).or_else(|e| probe_symphonia(path).map_err(|_| e))?,
+    };
+    Ok(MediaInfo { path: path.to_string_lossy().to_string(), kind, metadata })
+}
+
+/// Duration for the metadata cache; headers only, never a full decode
+pub(crate) fn duration_ms(path: &Path) -> Option<i64> {
+    match kind_of(path)? {
+        MediaKind::Audio => probe_symphonia(path).ok().and_then(|m| m.duration_ms),
+        MediaKind::Video => probe_symphonia(path)
+            .ok()
+            .and_then(|m| m.duration_ms)
+            .or_else(|| probe_ffprobe(path).ok().and_then(|m| m.duration_ms)),
+    }
+}
+
+/// Fold window peaks into `buckets` peaks, normalized so the loudest is 1.0
+fn bucket_peaks(windows: &[f32], buckets: usize) -> Vec<f32> {
+    if windows.is_empty() || buckets == 0 {
+        return Vec::new();
+    }
+    let peaks: Vec<f32> = (0..buckets)
+        .map(|i| {
+            let start = i * windows.len() / buckets;
+            let end = ((i + 1) * windows.len() / buckets).max(start + 1).min(windows.len());
+            windows.get(start..end).map_or(0.0, |w| w.iter().copied().fold(0.0, f32::max))
+        })
+        .collect();
+    let loudest = peaks.iter().copied().fold(0.0, f32::max);
+    if loudest > 0.0 {
+        peaks.iter().map(|p| p / loudest).collect()
+    } else {
+        peaks
+    }
+}
+
+fn waveform(path: &Path, buckets: usize) -> Result<Waveform, String> {
+    let mut format = open_format(path)?;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("The file has no audio track")?;
+    let track_id = track.id;
+    let sample_rate = track.codec_params.sample_rate.ok_or("Unknown sample rate")?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported audio codec: {}", e))?;
+
+    let mut windows = Vec::new();
+    let (mut peak, mut in_window, mut frames) = (0.0f32, 0usize, 0u64);
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(format!("Failed to read audio: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+        };
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        samples.copy_interleaved_ref(decoded);
+        for frame in samples.samples().chunks(channels) {
+            peak = frame.iter().fold(peak, |p, s| p.max(s.abs()));
+            in_window += 1;
+            frames += 1;
+            if in_window == PEAK_WINDOW {
+                windows.push(peak);
+                (peak, in_window) = (0.0, 0);
+            }
+        }
+    }
+    if in_window > 0 {
+        windows.push(peak);
+    }
+
+    Ok(Waveform {
+        peaks: bucket_peaks(&windows, buckets),
+        duration_ms: (frames as f64 * 1000.0 / sample_rate as f64).round() as i64,
+        sample_rate,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Duration, codecs, dimensions and bitrate of an audio or video file
+#[tauri::command]
+pub async fn media_get_metadata(path: String) -> Result<MediaInfo, String> {
+    tokio::task::spawn_blocking(move || metadata(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Media probe failed: {}", e))?
+}
+
+/// Peak levels across the file's audio, for drawing a player's waveform
+#[tauri::command]
+pub async fn media_extract_audio_waveform(path: String, buckets: Option<usize>) -> Result<Waveform, String> {
+    let buckets = buckets.unwrap_or(DEFAULT_BUCKETS).clamp(1, MAX_BUCKETS);
+    tokio::task::spawn_blocking(move || waveform(Path::new(&path), buckets))
+        .await
+        .map_err(|e| format!("Waveform extraction failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One second of 8 kHz mono 16-bit PCM: silence, then a loud half
+    fn write_wav(path: &Path) {
+        let rate: u32 = 8000;
+        let samples: Vec<i16> = (0..rate).map(|i| if i < rate / 2 { 0 } else { 16000 }).collect();
+        let data_len = samples.len() as u32 * 2;
+        let mut wav = Vec::new();
+        wav.extend(b"RIFF");
+        wav.extend((36 + data_len).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(rate.to_le_bytes());
+        wav.extend((rate * 2).to_le_bytes());
+        wav.extend(2u16.to_le_bytes());
+        wav.extend(16u16.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend(data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend(sample.to_le_bytes());
+        }
+        std::fs::write(path, wav).unwrap();
+    }
+
+    #[test]
+    fn test_wav_metadata_and_waveform() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.wav");
+        write_wav(&path);
+
+        let info = metadata(&path).unwrap();
+        assert_eq!(info.kind, MediaKind::Audio);
+        assert_eq!(info.metadata.duration_ms, Some(1000));
+        assert_eq!((info.metadata.sample_rate, info.metadata.channels), (Some(8000), Some(1)));
+        assert_eq!(duration_ms(&path), Some(1000));
+
+        let waveform = waveform(&path, 4).unwrap();
+        assert_eq!(waveform.duration_ms, 1000);
+        assert_eq!(waveform.peaks, vec![0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_parse_ffprobe() {
+        let json = r#"{"streams":[
+            {"codec_type":"video","codec_name":"h264","width":1920,"height":1080,"avg_frame_rate":"30000/1001"},
+            {"codec_type":"audio","codec_name":"aac","sample_rate":"48000","channels":2}
+        ],"format":{"duration":"12.345","bit_rate":"5000000"}}"#;
+        let metadata = parse_ffprobe(json).unwrap();
+        assert_eq!(metadata.duration_ms, Some(12345));
+        assert_eq!(metadata.codec.as_deref(), Some("h264"));
+        assert_eq!(metadata.audio_codec.as_deref(), Some("aac"));
+        assert_eq!((metadata.width, metadata.height), (Some(1920), Some(1080)));
+        assert!((metadata.frame_rate.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(metadata.sample_rate, Some(48000));
+    }
+}
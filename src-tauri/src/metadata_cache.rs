@@ -0,0 +1,678 @@
+//! Persistent workspace metadata cache.
+//!
+//! Stores every file's stats plus each note's title, frontmatter and tags in
+//! `.lokus/cache.db` (SQLite), so the file tree and property views don't have
+//! to walk and read a large vault on every call. The first use in a session
+//! reconciles the cache against the disk by mtime and size; after that file
+//! handlers and the watcher write through via the `notify_*` hooks, like the
+//! search index and link graph.
+//!
+//! Notes also carry their word, character and link counts, and every change
+//! to a note's word count is logged per day for writing statistics. The log
+//! is kept when the rest of the cache is rebuilt.
+//!
+//! Audio and video files carry their duration, so they can be filtered by
+//! length.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use crate::traversal::{VaultWalk, Visit};
+
+const CACHE_FILE: &str = "cache.db";
+const SCHEMA_VERSION: i32 = 4;
+const EXCLUDED_NAMES: &[&str] = &[".lokus", "node_modules", ".git", ".DS_Store"];
+
+/// Bumped whenever any cached entry changes, so derived results know they are stale
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Open caches keyed by workspace root
+static CACHES: Lazy<Mutex<HashMap<PathBuf, Connection>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+static HEADING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^#\s+(.+?)\s*#*\s*$").unwrap());
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetadata {
+    pub path: String,
+    pub is_directory: bool,
+    pub size: u64,
+    pub created: Option<i64>,
+    pub modified: Option<i64>,
+    pub title: Option<String>,
+    pub frontmatter: Option<JsonValue>,
+    pub tags: Vec<String>,
+    /// A cloud file that isn't downloaded; listed without its content
+    pub placeholder: bool,
+    /// Length of an audio or video file
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetadataFilter {
+    /// Only entries under this folder (absolute or workspace-relative)
+    pub folder: Option<String>,
+    /// Notes carrying this tag or one nested below it
+    pub tag: Option<String>,
+    /// Notes whose frontmatter has this field...
+    pub field: Option<String>,
+    /// ...with this value (any value if omitted); arrays match if they contain it
+    pub value: Option<JsonValue>,
+    /// Modification time bounds, in milliseconds since the epoch
+    pub modified_after: Option<i64>,
+    pub modified_before: Option<i64>,
+    /// Media length bounds; files without a duration don't match either
+    pub min_duration_ms: Option<i64>,
+    pub max_duration_ms: Option<i64>,
+    pub include_directories: bool,
+    pub limit: Option<usize>,
+}
+
+/// What is known about a note's content
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct NoteMetadata {
+    pub title: Option<String>,
+    pub frontmatter: Option<JsonValue>,
+    pub tags: Vec<String>,
+    /// `None` for encrypted notes, whose content can't be measured
+    pub text: Option<crate::stats::TextStats>,
+}
+
+/// Cached size and counts of one note
+#[derive(Debug, Clone)]
+pub(crate) struct NoteCounts {
+    pub path: String,
+    pub title: Option<String>,
+    pub size: u64,
+    pub created: Option<i64>,
+    pub modified: Option<i64>,
+    pub text: Option<crate::stats::TextStats>,
+}
+
+/// Words written on one day, summed over notes
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WritingDay {
+    /// Local date, `YYYY-MM-DD`
+    pub day: String,
+    pub added: i64,
+    pub removed: i64,
+    pub notes: i64,
+}
+
+// --- Extraction ---
+
+pub(crate) fn extract(rel: &str, content: &str) -> NoteMetadata {
+    if crate::encryption::is_encrypted(content) {
+        return NoteMetadata::default();
+    }
+    let frontmatter = crate::frontmatter::parse(content);
+    let body = crate::export::strip_frontmatter(content);
+
+    let title = frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("title"))
+        .and_then(|t| t.as_str())
+        .map(String::from)
+        .or_else(|| HEADING_RE.captures(body).map(|c| c[1].to_string()))
+        .or_else(|| {
+            let name = rel.rsplit('/').next().unwrap_or(rel);
+            Some(name.strip_suffix(".md").unwrap_or(name).to_string())
+        });
+
+    let tags = crate::tags::extract(frontmatter.as_ref(), body);
+    let text = Some(crate::stats::measure(body));
+    NoteMetadata { title, frontmatter, tags, text }
+}
+
+#[cfg(desktop)]
+fn media_duration(path: &Path) -> Option<i64> {
+    crate::media::duration_ms(path)
+}
+
+#[cfg(not(desktop))]
+fn media_duration(_path: &Path) -> Option<i64> {
+    None
+}
+
+// --- Storage ---
+
+pub(crate) fn to_ms(time: std::io::Result<std::time::SystemTime>) -> Option<i64> {
+    time.ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+}
+
+fn open(root: &Path) -> Result<Connection, String> {
+    let dir = root.join(".lokus");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let conn = Connection::open(dir.join(CACHE_FILE)).map_err(|e| format!("Failed to open metadata cache: {}", e))?;
+
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap_or(0);
+    if version != SCHEMA_VERSION {
+        conn.execute_batch("DROP TABLE IF EXISTS tags; DROP TABLE IF EXISTS files;")
+            .map_err(|e| format!("Failed to reset metadata cache: {}", e))?;
+    }
+    // journal_mode answers with the resulting mode, which execute can't accept
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+        .map_err(|e| format!("Failed to initialise metadata cache: {}", e))?;
+    conn.execute_batch(&format!(
+        "PRAGMA case_sensitive_like = ON;
+         CREATE TABLE IF NOT EXISTS files (
+             path TEXT PRIMARY KEY,
+             is_dir INTEGER NOT NULL,
+             size INTEGER NOT NULL,
+             created INTEGER,
+             modified INTEGER,
+             title TEXT,
+             frontmatter TEXT,
+             words INTEGER,
+             characters INTEGER,
+             links INTEGER,
+             placeholder INTEGER NOT NULL DEFAULT 0,
+             duration_ms INTEGER
+         );
+         CREATE TABLE IF NOT EXISTS tags (
+             path TEXT NOT NULL,
+             tag TEXT NOT NULL,
+             PRIMARY KEY (path, tag)
+         );
+         CREATE INDEX IF NOT EXISTS tags_by_tag ON tags (tag COLLATE NOCASE);
+         CREATE TABLE IF NOT EXISTS writing (
+             day TEXT NOT NULL,
+             path TEXT NOT NULL,
+             added INTEGER NOT NULL,
+             removed INTEGER NOT NULL,
+             PRIMARY KEY (day, path)
+         );
+         PRAGMA user_version = {};",
+        SCHEMA_VERSION
+    ))
+    .map_err(|e| format!("Failed to initialise metadata cache: {}", e))?;
+    Ok(conn)
+}
+
+fn sql_err(e: rusqlite::Error) -> String {
+    format!("Metadata cache error: {}", e)
+}
+
+fn remove_entry(conn: &Connection, rel: &str) -> rusqlite::Result<()> {
+    let prefix = format!("{}/%", rel.replace('%', "\\%").replace('_', "\\_"));
+    conn.execute("DELETE FROM tags WHERE path = ?1 OR path LIKE ?2 ESCAPE '\\'", params![rel, prefix])?;
+    conn.execute("DELETE FROM files WHERE path = ?1 OR path LIKE ?2 ESCAPE '\\'", params![rel, prefix])?;
+    Ok(())
+}
+
+/// Log the change in a note's word count on the day it was modified
+fn log_writing(conn: &Connection, rel: &str, before: i64, after: i64, modified: Option<i64>) -> rusqlite::Result<()> {
+    if before == after {
+        return Ok(());
+    }
+    let day = crate::stats::local_day(modified.unwrap_or_else(|| chrono::Utc::now().timestamp_millis())).to_string();
+    let (added, removed) = if after > before { (after - before, 0) } else { (0, before - after) };
+    conn.execute(
+        "INSERT INTO writing (day, path, added, removed) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (day, path) DO UPDATE SET added = added + ?3, removed = removed + ?4",
+        params![day, rel, added, removed],
+    )?;
+    Ok(())
+}
+
+/// Insert or replace one entry; `content` is read from disk if not supplied.
+/// With `track`, a change in the note's word count is logged as writing.
+fn upsert(conn: &Connection, root: &Path, rel: &str, content: Option<&str>, track: bool) -> rusqlite::Result<()> {
+    let path = root.join(rel);
+    let Ok(meta) = fs::metadata(&path) else {
+        return remove_entry(conn, rel);
+    };
+
+    // Reading a cloud placeholder would fail or start a download
+    let placeholder = crate::traversal::is_placeholder(&path, &meta);
+    let note = if meta.is_file() && !placeholder && crate::links::is_note(rel) {
+        let read;
+        let content = match content {
+            Some(content) => Some(content),
+            None => {
+                read = fs::read_to_string(&path).ok();
+                read.as_deref()
+            }
+        };
+        content.map(|c| extract(rel, c)).unwrap_or_default()
+    } else {
+        NoteMetadata::default()
+    };
+
+    let duration_ms = if meta.is_file() && !placeholder { media_duration(&path) } else { None };
+
+    let modified = to_ms(meta.modified());
+    if let (true, Some(text)) = (track, note.text) {
+        // A note seen for the first time counts from zero; one that couldn't
+        // be measured before (encrypted) isn't counted at all
+        let before: Option<Option<i64>> = conn
+            .query_row("SELECT words FROM files WHERE path = ?1", params![rel], |row| row.get(0))
+            .optional()?;
+        if let Some(before) = before.unwrap_or(Some(0)) {
+            log_writing(conn, rel, before, text.words as i64, modified)?;
+        }
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO files (path, is_dir, size, created, modified, title, frontmatter, words, characters, links, placeholder, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            rel,
+            meta.is_dir(),
+            if meta.is_dir() { 0 } else { meta.len() as i64 },
+            to_ms(meta.created()),
+            modified,
+            note.title,
+            note.frontmatter.map(|fm| fm.to_string()),
+            note.text.map(|t| t.words as i64),
+            note.text.map(|t| t.characters as i64),
+            note.text.map(|t| t.links as i64),
+            placeholder,
+            duration_ms,
+        ],
+    )?;
+    conn.execute("DELETE FROM tags WHERE path = ?1", params![rel])?;
+    for tag in &note.tags {
+        conn.execute("INSERT OR IGNORE INTO tags (path, tag) VALUES (?1, ?2)", params![rel, tag])?;
+    }
+    Ok(())
+}
+
+/// Bring the cache in line with the disk; returns the number of entries
+/// changed. With `track`, notes edited outside Lokus count as writing.
+fn reconcile(conn: &mut Connection, root: &Path, track: bool) -> Result<usize, String> {
+    let mut known: HashMap<String, (Option<i64>, i64)> = HashMap::new();
+    {
+        let mut stmt = conn.prepare("SELECT path, modified, size FROM files").map_err(sql_err)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(sql_err)?;
+        for row in rows.flatten() {
+            known.insert(row.0, row.1);
+        }
+    }
+
+    let tx = conn.transaction().map_err(sql_err)?;
+    let mut changes = 0;
+    let mut walk = VaultWalk::new(root);
+    for entry in VaultWalk::walker(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.file_name().to_str().map_or(true, |n| !EXCLUDED_NAMES.contains(&n)) && walk.visit(e) != Visit::Skip)
+        .filter_map(|e| e.ok())
+    {
+        let Some(rel) = crate::links::relative_path(root, entry.path()) else { continue };
+        let Ok(meta) = entry.metadata() else { continue };
+        let current = (to_ms(meta.modified()), if meta.is_dir() { 0 } else { meta.len() as i64 });
+        if known.remove(&rel) == Some(current) {
+            continue;
+        }
+        upsert(&tx, root, &rel, None, track).map_err(sql_err)?;
+        changes += 1;
+    }
+    for rel in known.keys() {
+        remove_entry(&tx, rel).map_err(sql_err)?;
+        changes += 1;
+    }
+    tx.commit().map_err(sql_err)?;
+    if changes > 0 {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+    Ok(changes)
+}
+
+/// Run `f` against the cache for `root`, opening and reconciling it first
+fn with_cache<T>(root: &Path, f: impl FnOnce(&mut Connection) -> Result<T, String>) -> Result<T, String> {
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", root.display()));
+    }
+    let mut caches = CACHES.lock().map_err(|e| format!("Metadata cache lock poisoned: {}", e))?;
+    if !caches.contains_key(root) {
+        let mut conn = open(root)?;
+        // Building a new cache finds every note; none of that is new writing
+        let populated = conn
+            .query_row("SELECT EXISTS (SELECT 1 FROM files)", [], |row| row.get::<_, bool>(0))
+            .map_err(sql_err)?;
+        reconcile(&mut conn, root, populated)?;
+        caches.insert(root.to_path_buf(), conn);
+    }
+    f(caches.get_mut(root).expect("cache inserted above"))
+}
+
+fn load_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut stmt = conn.prepare("SELECT path, tag FROM tags ORDER BY rowid").map_err(sql_err)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(sql_err)?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (path, tag) in rows.flatten() {
+        tags.entry(path).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+fn matches_value(actual: &JsonValue, wanted: &JsonValue) -> bool {
+    match (actual, wanted) {
+        (JsonValue::Array(items), wanted) if !wanted.is_array() => items.iter().any(|item| matches_value(item, wanted)),
+        (JsonValue::String(a), JsonValue::String(b)) => a.eq_ignore_ascii_case(b),
+        // Frontmatter numbers and booleans are often typed as strings in filters
+        (actual, JsonValue::String(b)) if !actual.is_string() => actual.to_string() == *b,
+        (actual, wanted) => actual == wanted,
+    }
+}
+
+pub fn query(root: &Path, filter: &MetadataFilter) -> Result<Vec<FileMetadata>, String> {
+    let folder = match filter.folder.as_deref().filter(|f| !f.is_empty()) {
+        Some(folder) if Path::new(folder).is_absolute() => {
+            Some(crate::links::relative_path(root, Path::new(folder)).ok_or_else(|| format!("Folder is outside the workspace: {}", folder))?)
+        }
+        Some(folder) => Some(folder.trim_matches('/').to_string()),
+        None => None,
+    };
+
+    with_cache(root, |conn| {
+        let tags = load_tags(conn)?;
+        let mut stmt = conn
+            .prepare("SELECT path, is_dir, size, created, modified, title, frontmatter, placeholder, duration_ms FROM files ORDER BY path")
+            .map_err(sql_err)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, bool>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                    row.get::<_, bool>(7)?,
+                    row.get::<_, Option<i64>>(8)?,
+                ))
+            })
+            .map_err(sql_err)?;
+
+        let wanted_tag = filter.tag.as_deref().map(|t| t.trim_start_matches('#').to_lowercase());
+        let mut results = Vec::new();
+        for (rel, is_dir, size, created, modified, title, frontmatter, placeholder, duration_ms) in rows.flatten() {
+            if is_dir && !filter.include_directories {
+                continue;
+            }
+            if let Some(folder) = &folder {
+                if !rel.starts_with(&format!("{}/", folder)) {
+                    continue;
+                }
+            }
+            if filter.modified_after.map_or(false, |after| modified.map_or(true, |m| m < after))
+                || filter.modified_before.map_or(false, |before| modified.map_or(true, |m| m > before))
+                || filter.min_duration_ms.map_or(false, |min| duration_ms.map_or(true, |d| d < min))
+                || filter.max_duration_ms.map_or(false, |max| duration_ms.map_or(true, |d| d > max))
+            {
+                continue;
+            }
+            let note_tags = tags.get(&rel).cloned().unwrap_or_default();
+            if let Some(wanted) = &wanted_tag {
+                let nested = format!("{}/", wanted);
+                if !note_tags.iter().map(|t| t.to_lowercase()).any(|t| t == *wanted || t.starts_with(&nested)) {
+                    continue;
+                }
+            }
+            let frontmatter: Option<JsonValue> = frontmatter.and_then(|fm| serde_json::from_str(&fm).ok());
+            if let Some(field) = &filter.field {
+                let actual = frontmatter.as_ref().and_then(|fm| fm.get(field));
+                let keep = match (actual, &filter.value) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(actual), Some(wanted)) => matches_value(actual, wanted),
+                };
+                if !keep {
+                    continue;
+                }
+            }
+
+            results.push(FileMetadata {
+                path: root.join(&rel).to_string_lossy().to_string(),
+                is_directory: is_dir,
+                size: size as u64,
+                created,
+                modified,
+                title,
+                frontmatter,
+                tags: note_tags,
+                placeholder,
+                duration_ms,
+            });
+            if filter.limit.map_or(false, |limit| results.len() >= limit) {
+                break;
+            }
+        }
+        Ok(results)
+    })
+}
+
+/// Every cached file and folder of an open workspace, for building the file tree
+pub fn cached_entries(root: &Path) -> Result<Vec<FileMetadata>, String> {
+    query(
+        root,
+        &MetadataFilter {
+            include_directories: true,
+            ..Default::default()
+        },
+    )
+}
+
+fn lookup(conn: &Connection, rel: &str) -> Option<bool> {
+    conn.query_row("SELECT is_dir FROM files WHERE path = ?1", params![rel], |row| row.get(0))
+        .optional()
+        .ok()
+        .flatten()
+}
+
+/// Size and counts of every cached note
+pub(crate) fn note_counts(root: &Path) -> Result<Vec<NoteCounts>, String> {
+    with_cache(root, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, title, size, created, modified, words, characters, links FROM files
+                 WHERE is_dir = 0 AND LOWER(path) LIKE '%.md' ORDER BY path",
+            )
+            .map_err(sql_err)?;
+        let rows = stmt
+            .query_map([], |row| {
+                let counts = (row.get::<_, Option<i64>>(5)?, row.get::<_, Option<i64>>(6)?, row.get::<_, Option<i64>>(7)?);
+                Ok(NoteCounts {
+                    path: root.join(row.get::<_, String>(0)?).to_string_lossy().to_string(),
+                    title: row.get(1)?,
+                    size: row.get::<_, i64>(2)? as u64,
+                    created: row.get(3)?,
+                    modified: row.get(4)?,
+                    text: match counts {
+                        (Some(words), Some(characters), Some(links)) => Some(crate::stats::TextStats {
+                            words: words as usize,
+                            characters: characters as usize,
+                            links: links as usize,
+                        }),
+                        _ => None,
+                    },
+                })
+            })
+            .map_err(sql_err)?;
+        Ok(rows.flatten().collect())
+    })
+}
+
+/// Logged writing per day between `from` and `to` (inclusive, `YYYY-MM-DD`)
+pub(crate) fn writing_days(root: &Path, from: &str, to: &str) -> Result<Vec<WritingDay>, String> {
+    with_cache(root, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT day, SUM(added), SUM(removed), COUNT(*) FROM writing
+                 WHERE day >= ?1 AND day <= ?2 GROUP BY day ORDER BY day",
+            )
+            .map_err(sql_err)?;
+        let rows = stmt
+            .query_map(params![from, to], |row| {
+                Ok(WritingDay { day: row.get(0)?, added: row.get(1)?, removed: row.get(2)?, notes: row.get(3)? })
+            })
+            .map_err(sql_err)?;
+        Ok(rows.flatten().collect())
+    })
+}
+
+/// Changes whenever the cached metadata of any workspace changes
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+// --- Hooks for file operations ---
+
+/// Update a saved file (and its parent folders) in whichever open cache contains it
+pub fn notify_file_saved(file_path: &str, content: &str) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let path = Path::new(file_path);
+    let Ok(caches) = CACHES.lock() else { return };
+    for (root, conn) in caches.iter() {
+        let Some(rel) = crate::links::relative_path(root, path) else { continue };
+        // New files can come with new folders the tree needs to show
+        let mut dir = crate::links::parent_dir(&rel);
+        while !dir.is_empty() && lookup(conn, dir).is_none() {
+            let _ = upsert(conn, root, dir, None, false);
+            dir = crate::links::parent_dir(dir);
+        }
+        if let Err(e) = upsert(conn, root, &rel, Some(content), true) {
+            tracing::warn!("Failed to update metadata cache for {}: {}", rel, e);
+        }
+    }
+}
+
+/// Record a new folder, and any missing parents
+pub fn notify_folder_created(folder_path: &str) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let path = Path::new(folder_path);
+    let Ok(caches) = CACHES.lock() else { return };
+    for (root, conn) in caches.iter() {
+        let Some(rel) = crate::links::relative_path(root, path) else { continue };
+        let mut dir = rel.as_str();
+        while !dir.is_empty() && lookup(conn, dir).is_none() {
+            if let Err(e) = upsert(conn, root, dir, None, false) {
+                tracing::warn!("Failed to update metadata cache for {}: {}", dir, e);
+            }
+            dir = crate::links::parent_dir(dir);
+        }
+    }
+}
+
+/// Drop a deleted file, or a folder and everything under it
+pub fn notify_file_removed(file_path: &str) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let path = Path::new(file_path);
+    let Ok(caches) = CACHES.lock() else { return };
+    for (root, conn) in caches.iter() {
+        if let Some(rel) = crate::links::relative_path(root, path) {
+            let _ = remove_entry(conn, &rel);
+        }
+    }
+}
+
+/// Move the entries for a renamed file or folder
+pub fn notify_file_renamed(old_path: &str, new_path: &str) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let Ok(mut caches) = CACHES.lock() else { return };
+    for (root, conn) in caches.iter_mut() {
+        let Some(old_rel) = crate::links::relative_path(root, Path::new(old_path)) else { continue };
+        let _ = remove_entry(conn, &old_rel);
+        let Some(new_rel) = crate::links::relative_path(root, Path::new(new_path)) else { continue };
+        if Path::new(new_path).is_dir() {
+            // Re-scan so everything under the moved folder is picked up
+            let _ = reconcile(conn, root, false);
+        } else {
+            let _ = upsert(conn, root, &new_rel, None, false);
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn metadata_query(workspace_path: String, filter: Option<MetadataFilter>) -> Result<Vec<FileMetadata>, String> {
+    query(Path::new(&workspace_path), &filter.unwrap_or_default())
+}
+
+/// Discard and rebuild the cache; returns the number of entries
+#[tauri::command]
+pub fn metadata_rebuild(workspace_path: String) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    with_cache(&root, |conn| {
+        conn.execute_batch("DELETE FROM tags; DELETE FROM files;").map_err(sql_err)?;
+        reconcile(conn, &root, false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_title_frontmatter_and_tags() {
+        let content = "---\ntitle: Plan\ntags: [work, \"#project/alpha\"]\nstatus: draft\n---\n# Heading\nText #idea and #123, see http://x.y/#anchor\n```\n#not-a-tag\n```\n";
+        let meta = extract("notes/plan.md", content);
+        assert_eq!(meta.title.as_deref(), Some("Plan"));
+        assert_eq!(meta.tags, vec!["work", "project/alpha", "idea"]);
+        assert_eq!(meta.frontmatter.unwrap()["status"], "draft");
+
+        let meta = extract("notes/plain.md", "No heading here");
+        assert_eq!(meta.title.as_deref(), Some("plain"));
+        assert!(meta.frontmatter.is_none());
+    }
+
+    #[test]
+    fn test_query_and_write_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("Projects/Alpha")).unwrap();
+        fs::write(root.join("Projects/Alpha/spec.md"), "---\nstatus: active\npriority: 2\n---\n#project/alpha").unwrap();
+        fs::write(root.join("inbox.md"), "#project").unwrap();
+
+        let by_tag = query(&root, &MetadataFilter { tag: Some("project".into()), ..Default::default() }).unwrap();
+        assert_eq!(by_tag.len(), 2);
+
+        let by_field = query(
+            &root,
+            &MetadataFilter {
+                field: Some("priority".into()),
+                value: Some(JsonValue::String("2".into())),
+                folder: Some("Projects".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(by_field.len(), 1);
+        assert_eq!(by_field[0].frontmatter.as_ref().unwrap()["status"], "active");
+
+        let new_note = root.join("Projects/Beta/new.md");
+        fs::create_dir_all(new_note.parent().unwrap()).unwrap();
+        fs::write(&new_note, "# Beta").unwrap();
+        notify_file_saved(&new_note.to_string_lossy(), "# Beta");
+        let entries = cached_entries(&root).unwrap();
+        assert!(entries.iter().any(|e| e.is_directory && e.path.ends_with("Beta")));
+        assert!(entries.iter().any(|e| e.title.as_deref() == Some("Beta")));
+
+        fs::create_dir(root.join("Projects/Empty")).unwrap();
+        notify_folder_created(&root.join("Projects/Empty").to_string_lossy());
+        assert!(cached_entries(&root).unwrap().iter().any(|e| e.is_directory && e.path.ends_with("Empty")));
+
+        notify_file_removed(&root.join("Projects").to_string_lossy());
+        assert_eq!(cached_entries(&root).unwrap().len(), 1);
+        assert_eq!(metadata_rebuild(root.to_string_lossy().to_string()).unwrap(), 7);
+    }
+}
@@ -294,6 +294,15 @@ mod macos_impl {
     /// A new UUID is used for each call so notifications do not overwrite
     /// each other.
     pub fn send_meeting_notification(title: &str, body: &str) {
+        deliver(title, body, Some("MEETING_ALERT"));
+    }
+
+    /// Show a plain notification (no action buttons), e.g. for reminders.
+    pub fn send_plain_notification(title: &str, body: &str) {
+        deliver(title, body, None);
+    }
+
+    fn deliver(title: &str, body: &str, category: Option<&str>) {
         if !has_bundle_id() {
             tracing::warn!("Skipping notification — no bundle identifier (dev mode)");
             return;
@@ -301,7 +310,9 @@ mod macos_impl {
         let content = UNMutableNotificationContent::new();
         content.setTitle(&NSString::from_str(title));
         content.setBody(&NSString::from_str(body));
-        content.setCategoryIdentifier(&NSString::from_str("MEETING_ALERT"));
+        if let Some(category) = category {
+            content.setCategoryIdentifier(&NSString::from_str(category));
+        }
 
         let trigger =
             UNTimeIntervalNotificationTrigger::triggerWithTimeInterval_repeats(0.1, false);
@@ -327,9 +338,9 @@ mod macos_impl {
 
         let completion = RcBlock::new(|error: *mut objc2_foundation::NSError| {
             if error.is_null() {
-                tracing::info!("Meeting notification scheduled successfully");
+                tracing::info!("Notification scheduled successfully");
             } else {
-                tracing::warn!("Failed to schedule meeting notification");
+                tracing::warn!("Failed to schedule notification");
             }
         });
 
@@ -344,7 +355,7 @@ mod macos_impl {
 #[cfg(target_os = "macos")]
 pub use macos_impl::{
     install_notification_delegate, register_notification_categories,
-    request_notification_permission, send_meeting_notification,
+    request_notification_permission, send_meeting_notification, send_plain_notification,
 };
 
 // ---------------------------------------------------------------------------
@@ -363,6 +374,31 @@ pub fn install_notification_delegate(_app_handle: tauri::AppHandle) {}
 #[cfg(not(target_os = "macos"))]
 pub fn send_meeting_notification(_title: &str, _body: &str) {}
 
+// ---------------------------------------------------------------------------
+// Cross-platform delivery
+// ---------------------------------------------------------------------------
+
+/// Show a plain OS notification.
+///
+/// macOS goes through `UNUserNotificationCenter` like meeting alerts; other
+/// platforms use `tauri-plugin-notification`, which is adequate when no click
+/// handling is needed.
+pub fn send_notification(app: &tauri::AppHandle, title: &str, body: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = app;
+        send_plain_notification(title, body);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            tracing::warn!(error = %e, "Failed to show notification");
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tauri commands
 // ---------------------------------------------------------------------------
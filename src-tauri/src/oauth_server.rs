@@ -100,7 +100,9 @@ async fn handle_request(req: Request<Incoming>) -> Result<HyperResponse, Box<dyn
 
     match (method, path) {
         (&Method::GET, "/gmail-callback") => handle_gmail_callback(req).await,
-        (&Method::GET, "/calendar-callback") => handle_calendar_callback(req).await,
+        (&Method::GET, "/calendar-callback") => handle_provider_callback(req, "Google Calendar", "calendar_auth_callback.json").await,
+        (&Method::GET, "/outlook-callback") => handle_provider_callback(req, "Outlook Calendar", "outlook_auth_callback.json").await,
+        (&Method::GET, "/github-callback") => handle_provider_callback(req, "GitHub", "github_auth_callback.json").await,
         (&Method::GET, "/auth-callback") => handle_supabase_auth_callback(req).await,
         (&Method::POST, "/complete-auth") => handle_complete_auth(req).await,
         (&Method::GET, "/health") => handle_health_check().await,
@@ -184,7 +186,13 @@ async fn handle_gmail_callback(req: Request<Incoming>) -> Result<HyperResponse,
         ))))?)
 }
 
-async fn handle_calendar_callback(req: Request<Incoming>) -> Result<HyperResponse, Box<dyn std::error::Error + Send + Sync>> {
+/// Authorization-code callback for providers whose flow is finished by the
+/// app polling `~/.lokus/temp/<callback_file>`
+async fn handle_provider_callback(
+    req: Request<Incoming>,
+    provider_name: &str,
+    callback_file: &str,
+) -> Result<HyperResponse, Box<dyn std::error::Error + Send + Sync>> {
     let uri = req.uri();
     let query_params = parse_query_params(uri.query().unwrap_or(""));
 
@@ -200,13 +208,13 @@ async fn handle_calendar_callback(req: Request<Incoming>) -> Result<HyperRespons
                 r#"
                 <html>
                   <body style="font-family: Arial, sans-serif; text-align: center; padding: 50px;">
-                    <h1 style="color: #dc3545;">Calendar Authentication Failed</h1>
+                    <h1 style="color: #dc3545;">{} Authentication Failed</h1>
                     <p>Error: {}</p>
                     <p>You can close this window and try again.</p>
                   </body>
                 </html>
                 "#,
-                error
+                provider_name, error
             ))))?);
     }
 
@@ -216,22 +224,23 @@ async fn handle_calendar_callback(req: Request<Incoming>) -> Result<HyperRespons
             return Ok(hyper::Response::builder()
                 .status(StatusCode::BAD_REQUEST)
                 .header("Content-Type", "text/html")
-                .body(Full::new(Bytes::from(
+                .body(Full::new(Bytes::from(format!(
                     r#"
                     <html>
                       <body style="font-family: Arial, sans-serif; text-align: center; padding: 50px;">
-                        <h1 style="color: #dc3545;">Calendar Authentication Failed</h1>
+                        <h1 style="color: #dc3545;">{} Authentication Failed</h1>
                         <p>Missing authorization code or state parameter.</p>
                         <p>You can close this window and try again.</p>
                       </body>
                     </html>
-                    "#
-                )))?);
+                    "#,
+                    provider_name
+                ))))?);
         }
     };
 
     // Write the auth data to a temporary file for the Tauri app to pick up
-    if let Err(_e) = write_calendar_auth_callback(code, state) {
+    if let Err(_e) = write_provider_auth_callback(callback_file, code, state) {
     }
 
     Ok(hyper::Response::builder()
@@ -241,8 +250,8 @@ async fn handle_calendar_callback(req: Request<Incoming>) -> Result<HyperRespons
             r#"
             <html>
               <body style="font-family: Arial, sans-serif; text-align: center; padding: 50px;">
-                <h1 style="color: #28a745;">Calendar Connected Successfully!</h1>
-                <p>Google Calendar connection completed successfully.</p>
+                <h1 style="color: #28a745;">{} Connected Successfully!</h1>
+                <p>{} connection completed successfully.</p>
                 <p>You can close this window and return to Lokus.</p>
                 <script>
                   // Auto-close after 3 seconds
@@ -252,7 +261,8 @@ async fn handle_calendar_callback(req: Request<Incoming>) -> Result<HyperRespons
                 </script>
               </body>
             </html>
-            "#
+            "#,
+            provider_name, provider_name
         ))))?)
 }
 
@@ -354,7 +364,7 @@ fn write_supabase_auth_callback(code: &str) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
-fn write_calendar_auth_callback(code: &str, state: &str) -> Result<(), Box<dyn std::error::Error>> {
+fn write_provider_auth_callback(file_name: &str, code: &str, state: &str) -> Result<(), Box<dyn std::error::Error>> {
     let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
     let temp_dir = home_dir.join(".lokus").join("temp");
 
@@ -363,7 +373,7 @@ fn write_calendar_auth_callback(code: &str, state: &str) -> Result<(), Box<dyn s
         fs::create_dir_all(&temp_dir)?;
     }
 
-    let auth_file = temp_dir.join("calendar_auth_callback.json");
+    let auth_file = temp_dir.join(file_name);
     let auth_data = serde_json::json!({
         "code": code,
         "state": state,
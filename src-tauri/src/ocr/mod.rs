@@ -0,0 +1,278 @@
+//! Text recognition for images in the workspace.
+//!
+//! Tesseract is used when its CLI is installed; otherwise recognition falls
+//! back to the platform engine (Apple Vision on macOS, `Windows.Media.Ocr`
+//! on Windows). Results are persisted as sidecar files and fed into the
+//! search index by `sidecar`.
+
+pub mod sidecar;
+#[cfg(target_os = "macos")]
+mod vision;
+#[cfg(target_os = "windows")]
+mod winrt;
+
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+const DEFAULT_LANGUAGE: &str = "eng";
+/// Formats Tesseract's image loader understands
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "webp", "gif"];
+
+/// Tesseract language codes and the BCP 47 tags platform engines use
+const LANGUAGE_TAGS: &[(&str, &str)] = &[
+    ("eng", "en-US"),
+    ("deu", "de-DE"),
+    ("fra", "fr-FR"),
+    ("spa", "es-ES"),
+    ("ita", "it-IT"),
+    ("por", "pt-BR"),
+    ("nld", "nl-NL"),
+    ("rus", "ru-RU"),
+    ("ukr", "uk-UA"),
+    ("pol", "pl-PL"),
+    ("jpn", "ja-JP"),
+    ("kor", "ko-KR"),
+    ("chi_sim", "zh-Hans"),
+    ("chi_tra", "zh-Hant"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Tesseract,
+    Vision,
+    Windows,
+}
+
+impl Engine {
+    const ALL: [Engine; 3] = [Engine::Tesseract, Engine::Vision, Engine::Windows];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Engine::Tesseract => "tesseract",
+            Engine::Vision => "vision",
+            Engine::Windows => "windows",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Engine::Tesseract => "Tesseract",
+            Engine::Vision => "Apple Vision",
+            Engine::Windows => "Windows OCR",
+        }
+    }
+
+    /// `None` or "auto" picks the first available engine
+    fn parse(engine: Option<&str>) -> Result<Option<Engine>, String> {
+        match engine.filter(|e| !e.is_empty() && *e != "auto") {
+            None => Ok(None),
+            Some(id) => Engine::ALL
+                .into_iter()
+                .find(|e| e.id() == id)
+                .map(Some)
+                .ok_or_else(|| format!("Unknown OCR engine: {}", id)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineAvailability {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub available: bool,
+    pub version: Option<String>,
+    pub languages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrAvailability {
+    pub available: bool,
+    /// Engine used when none is requested
+    pub default_engine: Option<&'static str>,
+    pub engines: Vec<EngineAvailability>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrResult {
+    pub text: String,
+    pub engine: String,
+    pub language: String,
+}
+
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Platform engines take BCP 47 tags; Tesseract codes are translated
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn language_tags(language: &str) -> Vec<String> {
+    language
+        .split('+')
+        .filter(|code| !code.is_empty())
+        .map(|code| {
+            LANGUAGE_TAGS
+                .iter()
+                .find(|(tesseract, _)| *tesseract == code)
+                .map_or(code, |(_, tag)| tag)
+                .to_string()
+        })
+        .collect()
+}
+
+fn tesseract_version() -> Option<String> {
+    Command::new("tesseract")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| {
+            // Older releases print the version to stderr
+            let text = if o.stdout.is_empty() { o.stderr } else { o.stdout };
+            String::from_utf8_lossy(&text).lines().next().map(|l| l.trim().to_string())
+        })
+}
+
+fn tesseract_languages() -> Vec<String> {
+    Command::new("tesseract")
+        .arg("--list-langs")
+        .output()
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .skip(1) // "List of available languages ..."
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty() && l != "osd")
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn tesseract(path: &Path, language: &str) -> Result<String, String> {
+    let output = Command::new("tesseract")
+        .arg(path)
+        .arg("stdout")
+        .args(["-l", language])
+        .output()
+        .map_err(|e| format!("Failed to run tesseract (is it installed?): {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("OCR failed for {}: {}", path.display(), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn engine_available(engine: Engine) -> bool {
+    match engine {
+        Engine::Tesseract => tesseract_version().is_some(),
+        #[cfg(target_os = "macos")]
+        Engine::Vision => vision::is_available(),
+        #[cfg(target_os = "windows")]
+        Engine::Windows => winrt::is_available(),
+        #[allow(unreachable_patterns)]
+        _ => false,
+    }
+}
+
+fn engine_availability(engine: Engine) -> EngineAvailability {
+    let (version, languages) = match engine {
+        Engine::Tesseract => match tesseract_version() {
+            Some(version) => (Some(version), tesseract_languages()),
+            None => (None, Vec::new()),
+        },
+        #[cfg(target_os = "macos")]
+        Engine::Vision if vision::is_available() => (None, vision::languages()),
+        #[cfg(target_os = "windows")]
+        Engine::Windows => (None, winrt::languages()),
+        _ => (None, Vec::new()),
+    };
+    let available = match engine {
+        Engine::Tesseract => version.is_some(),
+        _ => !languages.is_empty(),
+    };
+    EngineAvailability { id: engine.id(), name: engine.name(), available, version, languages }
+}
+
+fn run_engine(engine: Engine, path: &Path, language: &str) -> Result<String, String> {
+    match engine {
+        Engine::Tesseract => tesseract(path, language),
+        #[cfg(target_os = "macos")]
+        Engine::Vision => vision::recognize(path, &language_tags(language)),
+        #[cfg(target_os = "windows")]
+        // Windows OCR recognizes one language at a time
+        Engine::Windows => winrt::recognize(path, language_tags(language).first().map(String::as_str)),
+        #[allow(unreachable_patterns)]
+        _ => Err(format!("{} is not available on this platform", engine.name())),
+    }
+}
+
+/// Recognize text in one image. `language` uses Tesseract codes, joined with
+/// `+` for several (e.g. `eng+deu`); platform engines get the matching BCP 47
+/// tags. Without an `engine`, Tesseract is preferred when installed.
+pub fn recognize_with(path: &Path, language: Option<&str>, engine: Option<Engine>) -> Result<OcrResult, String> {
+    if !path.is_file() {
+        return Err(format!("Image not found: {}", path.display()));
+    }
+    let language = language.filter(|l| !l.is_empty()).unwrap_or(DEFAULT_LANGUAGE);
+    let engine = match engine {
+        Some(engine) => engine,
+        None => Engine::ALL
+            .into_iter()
+            .find(|e| engine_available(*e))
+            .ok_or("No OCR engine is available: install Tesseract to recognize text")?,
+    };
+    Ok(OcrResult {
+        text: run_engine(engine, path, language)?.trim().to_string(),
+        engine: engine.id().to_string(),
+        language: language.to_string(),
+    })
+}
+
+pub fn recognize(path: &Path, language: Option<&str>) -> Result<OcrResult, String> {
+    recognize_with(path, language, None)
+}
+
+pub fn availability() -> OcrAvailability {
+    let engines: Vec<EngineAvailability> = Engine::ALL.into_iter().map(engine_availability).collect();
+    OcrAvailability {
+        available: engines.iter().any(|e| e.available),
+        default_engine: engines.iter().find(|e| e.available).map(|e| e.id),
+        engines,
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn ocr_process_image(path: String, language: Option<String>, engine: Option<String>) -> Result<OcrResult, String> {
+    let engine = Engine::parse(engine.as_deref())?;
+    tokio::task::spawn_blocking(move || recognize_with(Path::new(&path), language.as_deref(), engine))
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn check_ocr_availability() -> Result<OcrAvailability, String> {
+    tokio::task::spawn_blocking(availability)
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_tags_and_engine_ids() {
+        assert_eq!(language_tags("eng+chi_sim"), vec!["en-US", "zh-Hans"]);
+        assert_eq!(language_tags("fr-CA"), vec!["fr-CA"]);
+        assert_eq!(Engine::parse(Some("auto")), Ok(None));
+        assert_eq!(Engine::parse(Some("vision")), Ok(Some(Engine::Vision)));
+        assert!(Engine::parse(Some("abbyy")).is_err());
+    }
+}
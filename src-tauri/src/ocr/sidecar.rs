@@ -0,0 +1,342 @@
+//! Persisted OCR results.
+//!
+//! Recognized text is stored in `.lokus/ocr/<hash>.txt`, keyed by the BLAKE3
+//! hash of the image so copies of the same screenshot are only recognized
+//! once. `.lokus/ocr/index.json` maps each image path to its sidecar and the
+//! image mtime it was made from; the search index reads that map to index
+//! images under their own path.
+
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+const MAP_FILE: &str = "index.json";
+const PROGRESS_EVENT: &str = "ocr-index-progress";
+const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", "node_modules", ".trash"];
+const DEFAULT_CONCURRENCY: usize = 2;
+/// Each Tesseract process is itself multi-threaded
+const MAX_CONCURRENCY: usize = 8;
+
+/// Serializes read-modify-write cycles of the sidecar map
+static MAP_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarEntry {
+    /// BLAKE3 of the image, which names the sidecar
+    pub hash: String,
+    /// Image mtime (ms) when it was recognized
+    pub modified: i64,
+    pub engine: String,
+    pub language: String,
+    pub indexed_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SidecarMap {
+    files: HashMap<String, SidecarEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrSidecar {
+    pub path: String,
+    pub sidecar: String,
+    pub characters: usize,
+    /// The sidecar was already current and no recognition ran
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrIndexProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub failed: usize,
+    pub current: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrIndexSummary {
+    pub total: usize,
+    pub indexed: usize,
+    pub unchanged: usize,
+    pub failed: Vec<OcrFailure>,
+    pub duration_ms: u64,
+}
+
+fn ocr_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join("ocr")
+}
+
+fn sidecar_path(root: &Path, hash: &str) -> PathBuf {
+    ocr_dir(root).join(format!("{}.txt", hash))
+}
+
+fn load_map(root: &Path) -> SidecarMap {
+    fs::read_to_string(ocr_dir(root).join(MAP_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_map(root: &Path, map: &SidecarMap) -> Result<(), String> {
+    let dir = ocr_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create OCR directory: {}", e))?;
+    let json = serde_json::to_string_pretty(map).map_err(|e| format!("Failed to serialize OCR index: {}", e))?;
+    let temp = dir.join(format!("{}.tmp", MAP_FILE));
+    fs::write(&temp, json).map_err(|e| format!("Failed to write OCR index: {}", e))?;
+    fs::rename(&temp, dir.join(MAP_FILE)).map_err(|e| format!("Failed to replace OCR index: {}", e))
+}
+
+fn modified_ms(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Images with a sidecar, as (relative image path, sidecar path, image mtime
+/// at recognition). Images that have since been deleted are left out.
+pub(crate) fn indexed_sidecars(root: &Path) -> Vec<(String, PathBuf, i64)> {
+    load_map(root)
+        .files
+        .into_iter()
+        .filter(|(rel, _)| root.join(rel).is_file())
+        .map(|(rel, entry)| (rel, sidecar_path(root, &entry.hash), entry.modified))
+        .filter(|(_, sidecar, _)| sidecar.is_file())
+        .collect()
+}
+
+/// Recognized text for an image, if it has been indexed
+pub(crate) fn read_text(root: &Path, rel: &str) -> Option<String> {
+    let entry = load_map(root).files.remove(rel)?;
+    fs::read_to_string(sidecar_path(root, &entry.hash)).ok()
+}
+
+/// Recognize `abs` unless `existing` is still current, write its sidecar and
+/// index the text. Returns `None` when nothing changed.
+fn process(
+    root: &Path,
+    rel: &str,
+    abs: &Path,
+    existing: Option<&SidecarEntry>,
+    language: Option<&str>,
+    force: bool,
+) -> Result<Option<(SidecarEntry, String)>, String> {
+    let modified = modified_ms(abs);
+    if !force {
+        if let Some(entry) = existing {
+            if entry.modified == modified && sidecar_path(root, &entry.hash).is_file() {
+                return Ok(None);
+            }
+        }
+    }
+
+    let bytes = fs::read(abs).map_err(|e| format!("Failed to read {}: {}", abs.display(), e))?;
+    let hash = blake3::hash(&bytes).to_hex().to_string();
+    let sidecar = sidecar_path(root, &hash);
+    let (text, engine, language) = match fs::read_to_string(&sidecar) {
+        // An identical image was already recognized
+        Ok(text) if !force => (
+            text,
+            existing.map_or_else(|| "cached".to_string(), |e| e.engine.clone()),
+            language.unwrap_or(super::DEFAULT_LANGUAGE).to_string(),
+        ),
+        _ => {
+            let result = super::recognize(abs, language)?;
+            fs::create_dir_all(ocr_dir(root)).map_err(|e| format!("Failed to create OCR directory: {}", e))?;
+            fs::write(&sidecar, &result.text).map_err(|e| format!("Failed to write OCR sidecar: {}", e))?;
+            (result.text, result.engine, result.language)
+        }
+    };
+
+    crate::search::index::index_extracted_text(root, rel, &text, modified)?;
+    let entry = SidecarEntry {
+        hash,
+        modified,
+        engine,
+        language,
+        indexed_at: chrono::Utc::now().timestamp_millis(),
+    };
+    Ok(Some((entry, text)))
+}
+
+/// Merge new entries into the map and drop entries and sidecars whose image is gone
+fn record(root: &Path, updates: Vec<(String, SidecarEntry)>) -> Result<(), String> {
+    let _guard = MAP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut map = load_map(root);
+    map.files.extend(updates);
+    map.files.retain(|rel, _| root.join(rel).is_file());
+
+    let referenced: HashSet<String> = map.files.values().map(|e| format!("{}.txt", e.hash)).collect();
+    for entry in fs::read_dir(ocr_dir(root)).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".txt") && !referenced.contains(&name) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    save_map(root, &map)
+}
+
+fn workspace_images(root: &Path) -> Vec<(String, PathBuf)> {
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !(e.file_type().is_dir() && e.file_name().to_str().map_or(false, |n| EXCLUDED_DIRS.contains(&n)))
+        })
+        .flatten()
+        .filter(|e| e.file_type().is_file() && super::is_image(e.path()))
+        .filter_map(|e| Some((crate::links::relative_path(root, e.path())?, e.path().to_path_buf())))
+        .collect()
+}
+
+// --- Tauri Commands ---
+
+/// Recognize one image and make its text searchable
+#[tauri::command]
+pub async fn ocr_index_image(path: String, language: Option<String>, force: Option<bool>) -> Result<OcrSidecar, String> {
+    tokio::task::spawn_blocking(move || {
+        let abs = PathBuf::from(&path);
+        if !super::is_image(&abs) {
+            return Err(format!("Not a supported image: {}", path));
+        }
+        let root = crate::handlers::files::find_workspace_root(&abs)?;
+        let rel = crate::links::relative_path(&root, &abs).ok_or_else(|| format!("Image is outside the workspace: {}", path))?;
+        let existing = load_map(&root).files.remove(&rel);
+
+        match process(&root, &rel, &abs, existing.as_ref(), language.as_deref(), force.unwrap_or(false))? {
+            Some((entry, text)) => {
+                let sidecar = sidecar_path(&root, &entry.hash);
+                record(&root, vec![(rel, entry)])?;
+                Ok(OcrSidecar {
+                    path,
+                    sidecar: sidecar.to_string_lossy().to_string(),
+                    characters: text.chars().count(),
+                    cached: false,
+                })
+            }
+            None => {
+                let entry = existing.expect("unchanged images have an entry");
+                let sidecar = sidecar_path(&root, &entry.hash);
+                let characters = fs::read_to_string(&sidecar).map(|t| t.chars().count()).unwrap_or(0);
+                Ok(OcrSidecar { path, sidecar: sidecar.to_string_lossy().to_string(), characters, cached: true })
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("OCR task failed: {}", e))?
+}
+
+/// Recognize every image in the workspace that changed since it was last
+/// indexed, running at most `concurrency` recognitions at once
+#[tauri::command]
+pub async fn ocr_index_workspace(
+    app: AppHandle,
+    workspace_path: String,
+    language: Option<String>,
+    concurrency: Option<usize>,
+    force: Option<bool>,
+) -> Result<OcrIndexSummary, String> {
+    let started = Instant::now();
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(format!("Workspace not found: {}", workspace_path));
+    }
+    let concurrency = concurrency.unwrap_or(DEFAULT_CONCURRENCY).clamp(1, MAX_CONCURRENCY);
+    let force = force.unwrap_or(false);
+
+    let scan_root = root.clone();
+    let images = tokio::task::spawn_blocking(move || workspace_images(&scan_root))
+        .await
+        .map_err(|e| format!("Failed to scan workspace: {}", e))?;
+    let existing = Arc::new(load_map(&root).files);
+
+    let mut summary = OcrIndexSummary { total: images.len(), ..Default::default() };
+    let mut updates = Vec::new();
+    let jobs = images.into_iter().map(|(rel, abs)| {
+        let root = root.clone();
+        let existing = existing.clone();
+        let language = language.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = process(&root, &rel, &abs, existing.get(&rel), language.as_deref(), force);
+            (rel, result)
+        })
+    });
+    let mut results = futures::stream::iter(jobs).buffer_unordered(concurrency);
+    let mut processed = 0;
+    while let Some(joined) = results.next().await {
+        let (rel, result) = joined.map_err(|e| format!("OCR task failed: {}", e))?;
+        processed += 1;
+        match result {
+            Ok(Some((entry, _))) => {
+                summary.indexed += 1;
+                updates.push((rel.clone(), entry));
+            }
+            Ok(None) => summary.unchanged += 1,
+            Err(error) => summary.failed.push(OcrFailure { path: rel.clone(), error }),
+        }
+        let progress = OcrIndexProgress { processed, total: summary.total, failed: summary.failed.len(), current: rel };
+        if let Err(e) = app.emit(PROGRESS_EVENT, &progress) {
+            tracing::warn!("Failed to emit {}: {}", PROGRESS_EVENT, e);
+        }
+    }
+
+    tokio::task::spawn_blocking(move || record(&root, updates))
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))??;
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str) -> SidecarEntry {
+        SidecarEntry {
+            hash: hash.to_string(),
+            modified: 1,
+            engine: "tesseract".to_string(),
+            language: "eng".to_string(),
+            indexed_at: 1,
+        }
+    }
+
+    #[test]
+    fn test_record_prunes_missing_images_and_orphaned_sidecars() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(ocr_dir(root)).unwrap();
+        fs::write(root.join("scan.png"), b"png").unwrap();
+        fs::write(sidecar_path(root, "aaa"), "invoice total").unwrap();
+        fs::write(sidecar_path(root, "bbb"), "deleted image").unwrap();
+
+        record(root, vec![("scan.png".to_string(), entry("aaa")), ("gone.png".to_string(), entry("bbb"))]).unwrap();
+
+        assert_eq!(read_text(root, "scan.png").as_deref(), Some("invoice total"));
+        assert!(read_text(root, "gone.png").is_none());
+        assert!(!sidecar_path(root, "bbb").exists());
+        let sidecars = indexed_sidecars(root);
+        assert_eq!(sidecars.len(), 1);
+        assert_eq!(sidecars[0].0, "scan.png");
+    }
+}
@@ -0,0 +1,81 @@
+//! Text recognition with Apple's Vision framework (macOS 10.15+).
+
+use objc2::rc::{autoreleasepool, Allocated, Retained};
+use objc2::runtime::{AnyClass, AnyObject};
+use objc2::{msg_send, sel};
+use objc2_foundation::{NSArray, NSDictionary, NSError, NSString, NSURL};
+use std::path::Path;
+
+#[link(name = "Vision", kind = "framework")]
+extern "C" {}
+
+/// `VNRequestTextRecognitionLevelAccurate`
+const RECOGNITION_LEVEL_ACCURATE: isize = 0;
+
+fn request_class() -> Option<&'static AnyClass> {
+    AnyClass::get(c"VNRecognizeTextRequest")
+}
+
+fn new_request() -> Result<Retained<AnyObject>, String> {
+    let class = request_class().ok_or("Vision text recognition requires macOS 10.15 or later")?;
+    Ok(unsafe { msg_send![class, new] })
+}
+
+pub fn is_available() -> bool {
+    request_class().is_some()
+}
+
+/// BCP 47 codes the accurate recognizer supports (macOS 12+ can list them)
+pub fn languages() -> Vec<String> {
+    autoreleasepool(|_| {
+        let Ok(request) = new_request() else {
+            return Vec::new();
+        };
+        let can_list: bool = unsafe { msg_send![&*request, respondsToSelector: sel!(supportedRecognitionLanguagesAndReturnError:)] };
+        if !can_list {
+            return vec!["en-US".to_string()];
+        }
+        let languages: Result<Retained<NSArray<NSString>>, Retained<NSError>> =
+            unsafe { msg_send![&*request, supportedRecognitionLanguagesAndReturnError: _] };
+        languages.map(|list| list.iter().map(|l| l.to_string()).collect()).unwrap_or_default()
+    })
+}
+
+pub fn recognize(path: &Path, languages: &[String]) -> Result<String, String> {
+    autoreleasepool(|_| {
+        let request = new_request()?;
+        unsafe {
+            let _: () = msg_send![&*request, setRecognitionLevel: RECOGNITION_LEVEL_ACCURATE];
+            let _: () = msg_send![&*request, setUsesLanguageCorrection: true];
+            if !languages.is_empty() {
+                let names: Vec<Retained<NSString>> = languages.iter().map(|l| NSString::from_str(l)).collect();
+                let names = NSArray::from_retained_slice(&names);
+                let _: () = msg_send![&*request, setRecognitionLanguages: &*names];
+            }
+        }
+
+        let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+        let options = NSDictionary::<AnyObject, AnyObject>::new();
+        let handler_class = AnyClass::get(c"VNImageRequestHandler").ok_or("Vision is not available")?;
+        let handler: Retained<AnyObject> = unsafe {
+            let handler: Allocated<AnyObject> = msg_send![handler_class, alloc];
+            msg_send![handler, initWithURL: &*url, options: &*options]
+        };
+        let requests = NSArray::from_retained_slice(&[request.clone()]);
+        let performed: Result<(), Retained<NSError>> =
+            unsafe { msg_send![&*handler, performRequests: &*requests, error: _] };
+        performed.map_err(|e| format!("Vision OCR failed: {}", e.localizedDescription()))?;
+
+        // One observation per detected line, best candidate first
+        let observations: Option<Retained<NSArray<AnyObject>>> = unsafe { msg_send![&*request, results] };
+        let mut lines = Vec::new();
+        for observation in observations.iter().flat_map(|list| list.iter()) {
+            let candidates: Retained<NSArray<AnyObject>> = unsafe { msg_send![&*observation, topCandidates: 1usize] };
+            if let Some(candidate) = candidates.firstObject() {
+                let text: Retained<NSString> = unsafe { msg_send![&*candidate, string] };
+                lines.push(text.to_string());
+            }
+        }
+        Ok(lines.join("\n"))
+    })
+}
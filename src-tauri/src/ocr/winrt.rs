@@ -0,0 +1,63 @@
+//! Text recognition with `Windows.Media.Ocr` (Windows 10+).
+
+use std::path::Path;
+use ::windows::core::HSTRING;
+use ::windows::Globalization::Language;
+use ::windows::Graphics::Imaging::BitmapDecoder;
+use ::windows::Media::Ocr::OcrEngine;
+use ::windows::Storage::{FileAccessMode, StorageFile};
+use ::windows::Win32::System::WinRT::{RoInitialize, RO_INIT_MULTITHREADED};
+
+/// Worker threads aren't initialized for WinRT; repeated calls are harmless
+fn init() {
+    let _ = unsafe { RoInitialize(RO_INIT_MULTITHREADED) };
+}
+
+/// Tags of the recognizer languages installed on this machine
+pub fn languages() -> Vec<String> {
+    init();
+    OcrEngine::AvailableRecognizerLanguages()
+        .map(|languages| {
+            languages
+                .into_iter()
+                .filter_map(|language| language.LanguageTag().ok())
+                .map(|tag| tag.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn is_available() -> bool {
+    !languages().is_empty()
+}
+
+pub fn recognize(path: &Path, language: Option<&str>) -> Result<String, String> {
+    init();
+    let err = |e: ::windows::core::Error| format!("Windows OCR failed: {}", e.message());
+    let engine = match language {
+        Some(tag) => {
+            let language = Language::CreateLanguage(&HSTRING::from(tag)).map_err(err)?;
+            OcrEngine::TryCreateFromLanguage(&language)
+                .map_err(|_| format!("The Windows OCR language pack for {} is not installed", tag))?
+        }
+        None => OcrEngine::TryCreateFromUserProfileLanguages().map_err(err)?,
+    };
+
+    // StorageFile only accepts absolute paths
+    let path = path.canonicalize().map_err(|e| format!("Image not found: {}", e))?;
+    let path = path.to_string_lossy();
+    let path = path.strip_prefix(r"\\?\").unwrap_or(&path);
+    let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(path)).and_then(|op| op.get()).map_err(err)?;
+    let stream = file.OpenAsync(FileAccessMode::Read).and_then(|op| op.get()).map_err(err)?;
+    let decoder = BitmapDecoder::CreateAsync(&stream).and_then(|op| op.get()).map_err(err)?;
+    let bitmap = decoder.GetSoftwareBitmapAsync().and_then(|op| op.get()).map_err(err)?;
+    let result = engine.RecognizeAsync(&bitmap).and_then(|op| op.get()).map_err(err)?;
+
+    let lines = result.Lines().map_err(err)?;
+    Ok(lines
+        .into_iter()
+        .filter_map(|line| line.Text().ok())
+        .map(|text| text.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
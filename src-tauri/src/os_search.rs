@@ -0,0 +1,203 @@
+//! Notes in OS-level search (Spotlight, Windows Search).
+//!
+//! Off by default; the `os_search_index` setting turns it on. The index is
+//! rebuilt from the metadata cache for the open vault at startup and on
+//! request, and each result opens its note through a `lokus://` link. The
+//! platform side lives behind `platform::SearchIndexing`.
+
+use crate::platform::{PlatformFeature, SearchableNote};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Settings key; missing means off
+const SETTINGS_KEY: &str = "os_search_index";
+/// Text handed to the index per note
+const MAX_TEXT_CHARS: usize = 10_000;
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OsSearchIndexReport {
+    pub workspace: String,
+    pub indexed: usize,
+    /// Encrypted or unreadable notes
+    pub skipped: usize,
+}
+
+fn enabled(app: &AppHandle) -> bool {
+    crate::settings::effective(app, None, SETTINGS_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Stable per-vault identifier for the OS index
+fn vault_id(root: &Path) -> String {
+    let digest = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+    format!("lokus-{}", &digest[..16])
+}
+
+/// Markdown reduced to searchable text
+fn plain_text(content: &str) -> String {
+    let body = crate::export::strip_frontmatter(content);
+    let mut text = String::with_capacity(body.len().min(MAX_TEXT_CHARS));
+    for line in body.lines() {
+        let line = line.trim_start_matches(|c| matches!(c, '#' | '>' | '-' | '*' | ' ')).trim();
+        if line.is_empty() || line.starts_with("```") {
+            continue;
+        }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+        if text.len() >= MAX_TEXT_CHARS {
+            break;
+        }
+    }
+    text.chars().take(MAX_TEXT_CHARS).collect()
+}
+
+fn collect_notes(root: &Path) -> Result<(Vec<SearchableNote>, usize), String> {
+    let mut notes = Vec::new();
+    let mut skipped = 0;
+    for entry in crate::metadata_cache::query(root, &Default::default())? {
+        if !crate::links::is_note(&entry.path) {
+            continue;
+        }
+        let content = match std::fs::read_to_string(&entry.path) {
+            Ok(content) if !crate::encryption::is_encrypted(&content) => content,
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let path = PathBuf::from(&entry.path);
+        let title = entry
+            .title
+            .clone()
+            .unwrap_or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default());
+        notes.push(SearchableNote {
+            link: crate::deep_link::note_link(&entry.path),
+            title,
+            text: plain_text(&content),
+            keywords: entry.tags,
+            modified: entry.modified,
+            path,
+        });
+    }
+    Ok((notes, skipped))
+}
+
+fn rebuild(root: &Path) -> Result<OsSearchIndexReport, String> {
+    let provider = crate::handlers::platform_files::get_or_init_platform_provider();
+    if !provider.supports_feature(PlatformFeature::OsSearchIndex) {
+        return Err("OS search indexing is not supported on this platform".to_string());
+    }
+    let (notes, skipped) = collect_notes(root)?;
+    provider
+        .replace_search_index(&vault_id(root), &notes)
+        .map_err(|e| e.user_message())?;
+    Ok(OsSearchIndexReport {
+        workspace: root.to_string_lossy().to_string(),
+        indexed: notes.len(),
+        skipped,
+    })
+}
+
+fn clear(root: &Path) -> Result<(), String> {
+    crate::handlers::platform_files::get_or_init_platform_provider()
+        .clear_search_index(&vault_id(root))
+        .map_err(|e| e.user_message())
+}
+
+fn workspace_or_current(app: &AppHandle, workspace: Option<String>) -> Result<PathBuf, String> {
+    workspace
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(app))
+        .filter(|root| root.is_dir())
+        .ok_or_else(|| "No workspace is open".to_string())
+}
+
+/// Route opened OS search results to the deep link handler, and refresh the
+/// open vault's entries a little after startup
+pub fn start(app: AppHandle) {
+    #[cfg(target_os = "macos")]
+    {
+        let handle = app.clone();
+        crate::platform::macos::spotlight::on_open(move |link| crate::deep_link::handle(&handle, &link));
+    }
+
+    if !enabled(&app) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        let Some(root) = crate::settings::current_workspace(&app) else {
+            return;
+        };
+        match tauri::async_runtime::spawn_blocking(move || rebuild(&root)).await {
+            Ok(Err(e)) => eprintln!("[OsSearch] Rebuilding the index failed: {}", e),
+            Err(e) => eprintln!("[OsSearch] Rebuilding the index failed: {}", e),
+            Ok(Ok(_)) => {}
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Re-index the notes of `workspace` (the open vault by default)
+#[tauri::command]
+pub async fn os_search_index_rebuild(app: AppHandle, workspace: Option<String>) -> Result<OsSearchIndexReport, String> {
+    if !enabled(&app) {
+        return Err("OS search indexing is turned off".to_string());
+    }
+    let root = workspace_or_current(&app, workspace)?;
+    tauri::async_runtime::spawn_blocking(move || rebuild(&root))
+        .await
+        .map_err(|e| format!("Indexing failed: {}", e))?
+}
+
+/// Turn OS search indexing on (indexing the open vault) or off (removing
+/// every known vault from the index)
+#[tauri::command]
+pub async fn os_search_set_enabled(
+    app: AppHandle,
+    enabled: bool,
+    workspace: Option<String>,
+) -> Result<Option<OsSearchIndexReport>, String> {
+    crate::settings::settings_set(
+        app.clone(),
+        crate::settings::SettingsScope::Global,
+        SETTINGS_KEY.to_string(),
+        serde_json::Value::Bool(enabled),
+        None,
+    )?;
+
+    if enabled {
+        let root = workspace_or_current(&app, workspace)?;
+        let report = tauri::async_runtime::spawn_blocking(move || rebuild(&root))
+            .await
+            .map_err(|e| format!("Indexing failed: {}", e))??;
+        return Ok(Some(report));
+    }
+
+    let mut roots: Vec<PathBuf> = crate::vaults::list_vaults().into_iter().map(|v| PathBuf::from(v.path)).collect();
+    roots.extend(workspace.map(PathBuf::from).or_else(|| crate::settings::current_workspace(&app)));
+    tauri::async_runtime::spawn_blocking(move || roots.iter().try_for_each(|root| clear(root)))
+        .await
+        .map_err(|e| format!("Clearing the index failed: {}", e))??;
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let content = "---\ntags: [a]\n---\n# Plan\n\n- ship it\n> quoted\n```\ncode\n```\n";
+        assert_eq!(plain_text(content), "Plan ship it quoted code");
+        assert_ne!(vault_id(Path::new("/a")), vault_id(Path::new("/b")));
+    }
+}
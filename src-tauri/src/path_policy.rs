@@ -0,0 +1,619 @@
+//! Path policy for the file commands.
+//!
+//! Every invoke passes through [`guarded`] before reaching its command. Each
+//! path argument is resolved — made absolute, `..` and symlinks followed — and
+//! must land inside a registered vault or a directory the user allowed
+//! explicitly (kept in `~/.lokus/path-policy.json`). The commands in [`RULES`]
+//! name their path arguments; for any other command every argument that looks
+//! like a path is checked, so a new command is covered without being listed.
+//! Anything else is rejected before the command runs, and the attempt is
+//! appended to `~/.lokus/logs/path-denials.jsonl`. Calls made by plugins are
+//! further limited to the files their granted permissions cover.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Manager;
+
+const POLICY_FILE: &str = "path-policy.json";
+/// Invoke headers a plugin's calls carry: its id and capability token
+const PLUGIN_ID_HEADER: &str = "lokus-plugin-id";
+const PLUGIN_TOKEN_HEADER: &str = "lokus-plugin-token";
+/// Commands that check a plugin's permissions themselves; all but
+/// `plugin_issue_token` must carry the plugin headers from any webview
+const PLUGIN_BROKER_COMMANDS: &[&str] = &["plugin_issue_token", "plugin_read_file", "plugin_write_file", "plugin_fetch"];
+const DENIAL_LIMIT: usize = 500;
+/// The denial log is cut back to its newer half past this size
+const DENIAL_LOG_MAX_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Canonical roots that may be accessed; `None` until computed, and again
+/// after the vault registry or the allow list changes
+static ROOTS: Lazy<Mutex<Option<Vec<PathBuf>>>> = Lazy::new(|| Mutex::new(None));
+static POLICY_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static DENIAL_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// A path argument of a command, by its name in the invoke payload
+#[derive(Debug, Clone, Copy)]
+enum Arg {
+    Path(&'static str),
+    /// An array of paths
+    Paths(&'static str),
+    /// A path relative to another argument, such as a file in a workspace
+    Join(&'static str, &'static str),
+    /// Like `Join`, but the path may also be absolute
+    Within(&'static str, &'static str),
+    /// A path inside an object argument, such as an options struct
+    Field(&'static str, &'static str),
+    /// A new name for the file at another argument
+    Sibling(&'static str, &'static str),
+}
+
+/// Whether a command reads or changes the files it names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+}
+
+/// Commands that touch files, and the arguments to check. The sources of
+/// [`EXTERNAL_SOURCES`] aren't listed, as they may be read from anywhere.
+const RULES: &[(&str, Access, &[Arg])] = &[
+    ("read_workspace_files", Access::Read, &[Arg::Path("workspacePath")]),
+    ("read_directory_page", Access::Read, &[Arg::Path("path")]),
+    ("get_folder_summary", Access::Read, &[Arg::Path("path")]),
+    ("create_file_in_workspace", Access::Write, &[Arg::Join("workspacePath", "name")]),
+    ("create_folder_in_workspace", Access::Write, &[Arg::Join("workspacePath", "name")]),
+    ("read_file_content", Access::Read, &[Arg::Path("path")]),
+    ("read_binary_file", Access::Read, &[Arg::Path("path")]),
+    ("read_file_range", Access::Read, &[Arg::Path("path")]),
+    ("read_file_stream_start", Access::Read, &[Arg::Path("path")]),
+    ("write_file_stream_start", Access::Write, &[Arg::Path("path")]),
+    ("write_file_content", Access::Write, &[Arg::Path("path")]),
+    ("write_file_content_checked", Access::Write, &[Arg::Path("path")]),
+    ("write_binary_file", Access::Write, &[Arg::Path("path")]),
+    ("save_file_version_manual", Access::Write, &[Arg::Path("path")]),
+    ("rename_file", Access::Write, &[Arg::Path("path"), Arg::Sibling("path", "newName")]),
+    ("move_file", Access::Write, &[Arg::Path("sourcePath"), Arg::Path("destinationDir")]),
+    ("delete_file", Access::Write, &[Arg::Path("path")]),
+    ("read_image_file", Access::Read, &[Arg::Path("path")]),
+    ("read_directory", Access::Read, &[Arg::Path("path")]),
+    ("write_file", Access::Write, &[Arg::Path("path")]),
+    ("create_directory", Access::Write, &[Arg::Path("path")]),
+    ("read_all_files", Access::Read, &[Arg::Paths("paths")]),
+    ("copy_external_files_to_workspace", Access::Write, &[Arg::Path("workspacePath"), Arg::Path("targetFolder")]),
+    ("find_workspace_images", Access::Read, &[Arg::Path("workspacePath")]),
+    ("save_version", Access::Write, &[Arg::Join("workspacePath", "filePath")]),
+    ("get_file_versions", Access::Read, &[Arg::Join("workspacePath", "filePath")]),
+    ("get_version_content", Access::Read, &[Arg::Join("workspacePath", "filePath")]),
+    ("get_diff", Access::Read, &[Arg::Join("workspacePath", "filePath")]),
+    ("restore_version", Access::Write, &[Arg::Join("workspacePath", "filePath")]),
+    ("cleanup_old_versions", Access::Write, &[Arg::Join("workspacePath", "filePath")]),
+    ("autosave_store", Access::Write, &[Arg::Path("path")]),
+    ("autosave_clear", Access::Write, &[Arg::Path("path")]),
+    ("csv_read", Access::Read, &[Arg::Path("path")]),
+    ("csv_query", Access::Read, &[Arg::Path("path")]),
+    ("csv_write", Access::Write, &[Arg::Path("path")]),
+    ("canvas_create", Access::Write, &[Arg::Path("path")]),
+    ("canvas_load", Access::Read, &[Arg::Path("path")]),
+    ("canvas_save", Access::Write, &[Arg::Path("path")]),
+    ("canvas_add_node", Access::Write, &[Arg::Path("path")]),
+    ("canvas_export_to_image", Access::Write, &[Arg::Path("path"), Arg::Path("outputPath")]),
+    ("diagram_render_preview", Access::Read, &[Arg::Path("path")]),
+    ("diagram_validate", Access::Read, &[Arg::Path("path")]),
+    ("diagram_save", Access::Write, &[Arg::Path("path")]),
+    ("encrypt_note", Access::Write, &[Arg::Path("path")]),
+    ("decrypt_note", Access::Write, &[Arg::Path("path")]),
+    ("set_folder_encryption", Access::Write, &[Arg::Path("workspacePath"), Arg::Path("folder")]),
+    ("get_frontmatter", Access::Read, &[Arg::Path("path")]),
+    ("set_frontmatter_field", Access::Write, &[Arg::Path("path")]),
+    ("query_notes_by_frontmatter", Access::Read, &[Arg::Path("workspacePath")]),
+    ("tags_list", Access::Read, &[Arg::Path("workspacePath")]),
+    ("tags_get_notes", Access::Read, &[Arg::Path("workspacePath")]),
+    ("tags_rename", Access::Write, &[Arg::Path("workspacePath")]),
+    ("tags_merge", Access::Write, &[Arg::Path("workspacePath")]),
+    ("trash_list", Access::Read, &[Arg::Path("workspacePath")]),
+    ("trash_restore", Access::Write, &[Arg::Path("workspacePath")]),
+    ("trash_empty", Access::Write, &[Arg::Path("workspacePath")]),
+    ("trash_purge_older_than", Access::Write, &[Arg::Path("workspacePath")]),
+    ("template_list", Access::Read, &[Arg::Path("workspacePath")]),
+    ("template_render", Access::Read, &[Arg::Path("workspacePath")]),
+    ("template_create_note_from", Access::Write, &[Arg::Within("workspacePath", "targetFolder")]),
+    ("export_note_to_pdf", Access::Write, &[Arg::Path("path"), Arg::Field("options", "outputPath")]),
+    ("export_folder_to_pdf", Access::Write, &[Arg::Path("folder"), Arg::Path("outputDir")]),
+    ("export_workspace", Access::Write, &[Arg::Path("workspacePath"), Arg::Field("options", "outputPath")]),
+    ("plugin_read_file", Access::Read, &[Arg::Path("path")]),
+    ("plugin_write_file", Access::Write, &[Arg::Path("path")]),
+    ("gmail_save_attachment", Access::Write, &[Arg::Join("workspacePath", "folder")]),
+    ("gmail_save_all_attachments", Access::Write, &[Arg::Join("workspacePath", "folder")]),
+    ("gmail_save_email_as_note", Access::Write, &[Arg::Join("workspacePath", "folder")]),
+    ("import_obsidian_vault", Access::Write, &[Arg::Path("targetFolder")]),
+    ("import_notion_export", Access::Write, &[Arg::Path("targetFolder")]),
+    ("import_enex", Access::Write, &[Arg::Path("targetFolder")]),
+    ("backup_workspace", Access::Write, &[Arg::Path("workspacePath"), Arg::Path("dest")]),
+    ("backup_restore", Access::Write, &[Arg::Path("targetDir")]),
+];
+
+/// Commands in [`RULES`] that also read files the user picked from anywhere,
+/// such as an import source; plugins can't call them
+const EXTERNAL_SOURCES: &[&str] = &[
+    "copy_external_files_to_workspace",
+    "import_obsidian_vault",
+    "import_notion_export",
+    "import_enex",
+    "backup_restore",
+];
+
+/// Commands whose path arguments are left alone: probes used while choosing a
+/// workspace, opening one, the policy's own commands, and imports of a single
+/// file the user picked. Plugins can't call them.
+const UNRESTRICTED: &[&str] = &[
+    "path_exists",
+    "is_directory",
+    "validate_workspace_path",
+    "save_last_workspace",
+    "get_workspace_capabilities",
+    "vault_register",
+    "open_workspace_window",
+    "path_policy_allow",
+    "path_policy_revoke",
+    "install_plugin",
+    "read_plugin_file",
+    "ical_import_file",
+    "validate_theme_file",
+    "import_theme_file",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathPolicy {
+    /// Registered vaults; always allowed
+    #[serde(skip_deserializing)]
+    pub workspaces: Vec<String>,
+    /// Directories allowed in addition to the vaults
+    pub allowed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathDenial {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub path: String,
+    pub reason: String,
+}
+
+fn lokus_dir() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join(".lokus"))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn load_policy() -> PathPolicy {
+    let mut policy: PathPolicy = lokus_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(POLICY_FILE)).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    policy.workspaces = crate::vaults::list_vaults().into_iter().map(|v| v.path).collect();
+    policy
+}
+
+fn save_policy(policy: &PathPolicy) -> Result<(), String> {
+    let dir = lokus_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    let json = serde_json::json!({ "allowed": policy.allowed });
+    let json = serde_json::to_string_pretty(&json).map_err(|e| format!("Failed to serialize path policy: {}", e))?;
+    std::fs::write(dir.join(POLICY_FILE), json).map_err(|e| format!("Failed to save path policy: {}", e))
+}
+
+/// Forget the cached roots; called when the vault registry changes
+pub(crate) fn invalidate() {
+    *ROOTS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// `path` made absolute and free of `..` and symlinks. The part that doesn't
+/// exist yet is resolved through the nearest existing ancestor and may not
+/// climb out of it.
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err("Path must be absolute".to_string());
+    }
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            let mut resolved = resolved;
+            for component in missing.iter().rev() {
+                match component {
+                    Component::Normal(name) => resolved.push(name),
+                    Component::CurDir => {}
+                    _ => return Err("Path leaves its folder".to_string()),
+                }
+            }
+            return Ok(resolved);
+        }
+        let (Some(parent), Some(last)) = (existing.parent(), existing.components().next_back()) else {
+            return Err("Path does not exist".to_string());
+        };
+        missing.push(last);
+        existing = parent;
+    }
+}
+
+fn roots() -> Vec<PathBuf> {
+    if let Some(roots) = ROOTS.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        return roots;
+    }
+    // Computed without holding the lock: the registry takes its own lock and then ours
+    let policy = load_policy();
+    let roots: Vec<PathBuf> = policy
+        .workspaces
+        .iter()
+        .chain(&policy.allowed)
+        .filter_map(|root| Path::new(root).canonicalize().ok())
+        .collect();
+    *ROOTS.lock().unwrap_or_else(|e| e.into_inner()) = Some(roots.clone());
+    roots
+}
+
+fn check_path(roots: &[PathBuf], path: &Path) -> Result<(), String> {
+    let resolved = resolve(path)?;
+    if roots.iter().any(|root| resolved.starts_with(root)) {
+        Ok(())
+    } else {
+        Err("Outside the open workspaces and allowed folders".to_string())
+    }
+}
+
+/// The paths named by `arg` in the invoke arguments; absent optional
+/// arguments yield none. `Err` holds the bad value and why.
+fn arg_paths(args: &Value, arg: Arg) -> Result<Vec<PathBuf>, (String, String)> {
+    let text = |key: &str| args.get(key).and_then(Value::as_str);
+    Ok(match arg {
+        Arg::Path(key) => text(key).map(PathBuf::from).into_iter().collect(),
+        Arg::Paths(key) => args
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|paths| paths.iter().filter_map(Value::as_str).map(PathBuf::from).collect())
+            .unwrap_or_default(),
+        Arg::Join(base, child) => match (text(base), text(child)) {
+            (Some(base), Some(child)) => {
+                let child = Path::new(child);
+                if child.is_absolute() {
+                    return Err((child.display().to_string(), "Must be relative to the workspace".to_string()));
+                }
+                vec![Path::new(base).join(child)]
+            }
+            (Some(base), None) => vec![PathBuf::from(base)],
+            _ => Vec::new(),
+        },
+        Arg::Within(base, child) => match (text(base), text(child)) {
+            (Some(base), Some(child)) => vec![Path::new(base).join(child)],
+            (Some(base), None) => vec![PathBuf::from(base)],
+            _ => Vec::new(),
+        },
+        Arg::Field(object, key) => args
+            .get(object)
+            .and_then(|object| object.get(key))
+            .and_then(Value::as_str)
+            .map(PathBuf::from)
+            .into_iter()
+            .collect(),
+        Arg::Sibling(path, name) => match (text(path), text(name)) {
+            (Some(path), Some(name)) => {
+                let name = name.trim();
+                if name.is_empty() || name.contains(['/', '\\']) || name == ".." || name == "." {
+                    return Err((name.to_string(), "Not a valid file name".to_string()));
+                }
+                vec![Path::new(path).with_file_name(name)]
+            }
+            _ => Vec::new(),
+        },
+    })
+}
+
+/// The arguments of a command without a rule that look like paths: `path`,
+/// `paths`, `dest`, `destination`, `workspace` and names ending in `Path`,
+/// `Paths` or `Dir`, plus folders (`folder`, `...Folder`) when there's a
+/// workspace. Relative ones are taken against `workspacePath`.
+fn default_paths(args: &Value) -> Vec<PathBuf> {
+    let Some(args) = args.as_object() else {
+        return Vec::new();
+    };
+    let workspace = ["workspacePath", "workspace"]
+        .iter()
+        .find_map(|key| args.get(*key).and_then(Value::as_str))
+        .map(PathBuf::from);
+    let mut paths = Vec::new();
+    for (key, value) in args {
+        let is_path = matches!(key.as_str(), "path" | "paths" | "dest" | "destination" | "workspace")
+            || key.ends_with("Path")
+            || key.ends_with("Paths")
+            || key.ends_with("Dir");
+        let is_folder = key == "folder" || key.ends_with("Folder");
+        let values: Vec<&str> = match value {
+            _ if !is_path && !is_folder => continue,
+            Value::String(value) => vec![value],
+            Value::Array(values) => values.iter().filter_map(Value::as_str).collect(),
+            _ => continue,
+        };
+        for value in values {
+            let path = Path::new(value);
+            match &workspace {
+                _ if path.is_absolute() => paths.push(path.to_path_buf()),
+                Some(root) => paths.push(root.join(path)),
+                // Without a workspace a folder is a name, such as a mail folder
+                None if is_folder => {}
+                None => paths.push(path.to_path_buf()),
+            }
+        }
+    }
+    paths
+}
+
+/// Check the invoke arguments of `command` against the roots; `Err` holds the
+/// offending path and why
+fn check(command: &str, args: &Value, roots: &[PathBuf]) -> Result<(), (String, String)> {
+    let paths = match RULES.iter().find(|(name, _, _)| *name == command) {
+        Some((_, _, rules)) => {
+            let mut paths = Vec::new();
+            for &arg in rules.iter() {
+                paths.extend(arg_paths(args, arg)?);
+            }
+            paths
+        }
+        None if UNRESTRICTED.contains(&command) => return Ok(()),
+        None => default_paths(args),
+    };
+    for path in paths {
+        check_path(roots, &path).map_err(|reason| (path.display().to_string(), reason))?;
+    }
+    Ok(())
+}
+
+fn denial_log_path() -> Result<PathBuf, String> {
+    let dir = lokus_dir()?.join("logs");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    Ok(dir.join("path-denials.jsonl"))
+}
+
+fn record_denial(entry: &PathDenial) -> Result<(), String> {
+    let _guard = DENIAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = denial_log_path()?;
+
+    if std::fs::metadata(&path).map(|m| m.len() > DENIAL_LOG_MAX_BYTES).unwrap_or(false) {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let lines: Vec<&str> = content.lines().collect();
+        let kept = lines[lines.len() / 2..].join("\n");
+        std::fs::write(&path, kept + "\n").map_err(|e| format!("Failed to trim denial log: {}", e))?;
+    }
+
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open denial log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write denial log: {}", e))
+}
+
+/// Checks for calls a plugin makes. A call is a plugin's when it carries the
+/// plugin headers, and every call from a plugin webview must, as must every
+/// broker call whichever webview it comes from. Plugins may use the broker
+/// commands, which authorize themselves against the same identity, and the
+/// file commands in [`RULES`] on paths their granted permissions cover;
+/// nothing else. In particular `plugin_wasm_invoke` is for the host window
+/// only.
+fn check_plugin(invoke: &Invoke, command: &str) -> Result<(), String> {
+    if command == "plugin_issue_token" {
+        return Ok(());
+    }
+    let webview = invoke.message.webview_ref();
+    let label = webview.label();
+    let header = |name: &str| invoke.message.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from);
+    let is_broker = PLUGIN_BROKER_COMMANDS.contains(&command);
+    let caller = match (header(PLUGIN_ID_HEADER), header(PLUGIN_TOKEN_HEADER)) {
+        (Some(plugin_id), Some(token)) => crate::plugin_broker::PluginCaller { plugin_id, token },
+        _ if is_broker || label.starts_with("plugin-") => {
+            return Err(format!("Plugin calls to {} must identify the plugin", command))
+        }
+        _ => return Ok(()),
+    };
+    if is_broker {
+        // The broker authorizes the caller in the arguments; it must be the one in the headers
+        let named = match invoke.message.payload() {
+            InvokeBody::Json(args) => args.get("caller").cloned(),
+            _ => None,
+        };
+        let named = named.and_then(|caller| serde_json::from_value::<crate::plugin_broker::PluginCaller>(caller).ok());
+        return match named {
+            Some(named) if named.plugin_id == caller.plugin_id && named.token == caller.token => Ok(()),
+            _ => Err(format!("Plugin calls to {} must identify the plugin", command)),
+        };
+    }
+
+    // Their source files may lie anywhere, so the rules don't cover them
+    let rule = RULES.iter().find(|(name, _, _)| *name == command && !EXTERNAL_SOURCES.contains(name));
+    let Some((_, access, rules)) = rule else {
+        return Err(format!("Plugins can't call {}", command));
+    };
+    let InvokeBody::Json(args) = invoke.message.payload() else {
+        return Err(format!("Plugins can't call {}", command));
+    };
+    let app = webview.app_handle();
+    for &arg in rules.iter() {
+        for path in arg_paths(args, arg).map_err(|(path, reason)| format!("Access denied to {}: {}", path, reason))? {
+            let capability = match access {
+                Access::Read => crate::plugin_broker::Capability::ReadFile(path),
+                Access::Write => crate::plugin_broker::Capability::WriteFile(path),
+            };
+            crate::plugin_broker::authorize_path(app, &caller, label, command, &capability)?;
+        }
+    }
+    Ok(())
+}
+
+/// Wrap the app's invoke handler so file commands are checked before they run
+pub fn guarded(handler: impl Fn(Invoke) -> bool + Send + Sync + 'static) -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
+    move |invoke: Invoke| {
+        #[cfg(desktop)]
+        let started = std::time::Instant::now();
+        let command = invoke.message.command().to_string();
+        let result = match invoke.message.payload() {
+            InvokeBody::Json(args) => check(&command, args, &roots()),
+            _ => Ok(()),
+        };
+        // Whether a command took the call, and whether it ran rather than being refused
+        let (handled, ran) = match result {
+            Ok(()) => match check_plugin(&invoke, &command) {
+                Ok(()) => {
+                    let handled = handler(invoke);
+                    (handled, handled)
+                }
+                Err(message) => {
+                    invoke.resolver.reject(message);
+                    (true, false)
+                }
+            },
+            Err((path, reason)) => {
+                tracing::warn!(command = %command, path = %path, "Denied file access: {}", reason);
+                let entry = PathDenial { timestamp: Utc::now(), command: command.clone(), path: path.clone(), reason: reason.clone() };
+                if let Err(e) = record_denial(&entry) {
+                    tracing::warn!("Failed to record path denial: {}", e);
+                }
+                invoke.resolver.reject(format!("Access denied to {}: {}", path, reason));
+                (true, false)
+            }
+        };
+        #[cfg(desktop)]
+        crate::telemetry::record_command(&command, started.elapsed(), ran);
+        handled
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn path_policy_get() -> PathPolicy {
+    load_policy()
+}
+
+/// Allow file commands to use `path` and everything below it
+#[tauri::command]
+pub fn path_policy_allow(path: String) -> Result<PathPolicy, String> {
+    let resolved = Path::new(&path).canonicalize().map_err(|e| format!("Folder is not accessible: {}", e))?;
+    if !resolved.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    // Allowing `/` or the home folder would switch the policy off
+    crate::vaults::check_vault_folder(&resolved)?;
+    let _guard = POLICY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut policy = load_policy();
+    let resolved = resolved.to_string_lossy().to_string();
+    if !policy.allowed.contains(&resolved) {
+        policy.allowed.push(resolved);
+        save_policy(&policy)?;
+        invalidate();
+    }
+    Ok(policy)
+}
+
+#[tauri::command]
+pub fn path_policy_revoke(path: String) -> Result<PathPolicy, String> {
+    let _guard = POLICY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut policy = load_policy();
+    let resolved = Path::new(&path).canonicalize().map(|p| p.to_string_lossy().to_string()).unwrap_or(path.clone());
+    let before = policy.allowed.len();
+    policy.allowed.retain(|allowed| *allowed != path && *allowed != resolved);
+    if policy.allowed.len() == before {
+        return Err(format!("{} is not an allowed folder", path));
+    }
+    save_policy(&policy)?;
+    invalidate();
+    Ok(policy)
+}
+
+/// Denied file accesses, newest first
+#[tauri::command]
+pub fn path_policy_denials(limit: Option<usize>) -> Result<Vec<PathDenial>, String> {
+    let path = denial_log_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read denial log: {}", e))?;
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<PathDenial>(line).ok())
+        .take(limit.unwrap_or(DENIAL_LIMIT).min(DENIAL_LIMIT))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn paths_must_resolve_inside_a_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = dir.path().join("vault");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(vault.join("notes")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        let roots = vec![vault.canonicalize().unwrap()];
+
+        assert!(check_path(&roots, &vault.join("notes/a.md")).is_ok());
+        // Files and folders that don't exist yet
+        assert!(check_path(&roots, &vault.join("new/deeper/b.md")).is_ok());
+        assert!(check_path(&roots, &vault.join("notes/../../outside/c.md")).is_err());
+        assert!(check_path(&roots, &vault.join("missing/../../outside")).is_err());
+        assert!(check_path(&roots, &outside.join("d.md")).is_err());
+        assert!(check_path(&roots, Path::new("relative.md")).is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, vault.join("escape")).unwrap();
+            assert!(check_path(&roots, &vault.join("escape/e.md")).is_err());
+        }
+    }
+
+    #[test]
+    fn only_listed_commands_and_arguments_are_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots = vec![dir.path().canonicalize().unwrap()];
+        let inside = dir.path().join("a.md").to_string_lossy().to_string();
+
+        assert!(check("read_file_content", &json!({ "path": inside }), &roots).is_ok());
+        assert!(check("read_file_content", &json!({ "path": "/etc/passwd" }), &roots).is_err());
+        assert!(check("path_exists", &json!({ "path": "/etc/passwd" }), &roots).is_ok());
+        assert!(check("read_all_files", &json!({ "paths": [inside, "/etc/hosts"] }), &roots).is_err());
+        assert!(check("rename_file", &json!({ "path": inside, "newName": "../b.md" }), &roots).is_err());
+        assert!(check("rename_file", &json!({ "path": inside, "newName": "b.md" }), &roots).is_ok());
+        let workspace = dir.path().to_string_lossy().to_string();
+        assert!(check("get_file_versions", &json!({ "workspacePath": workspace, "filePath": "../x.md" }), &roots).is_err());
+        assert!(check("copy_external_files_to_workspace", &json!({ "workspacePath": workspace, "filePaths": ["/etc/hosts"] }), &roots).is_ok());
+    }
+
+    #[test]
+    fn unlisted_commands_are_checked_by_argument_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots = vec![dir.path().canonicalize().unwrap()];
+        let workspace = dir.path().to_string_lossy().to_string();
+
+        assert!(check("pdf_page_count", &json!({ "path": "/etc/passwd" }), &roots).is_err());
+        assert!(check("settings_export_profile", &json!({ "dest": "/tmp/profile.zip" }), &roots).is_err());
+        assert!(check("sync_preview_conflict", &json!({ "workspacePath": workspace, "path": "notes/a.md" }), &roots).is_ok());
+        assert!(check("sync_preview_conflict", &json!({ "workspacePath": workspace, "path": "../a.md" }), &roots).is_err());
+        assert!(check("sync_set_roots", &json!({ "workspacePath": workspace, "paths": ["a", "/etc"] }), &roots).is_err());
+        assert!(check("feed_add", &json!({ "workspacePath": "/etc", "folder": "Feeds" }), &roots).is_err());
+        // A mail folder isn't a path
+        assert!(check("imap_list_emails", &json!({ "folder": "INBOX" }), &roots).is_ok());
+        assert!(check("export_workspace", &json!({ "workspacePath": workspace, "options": { "outputPath": "/etc/site" } }), &roots).is_err());
+    }
+}
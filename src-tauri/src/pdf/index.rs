@@ -0,0 +1,347 @@
+//! Full-text indexing of PDFs in the workspace.
+//!
+//! Extracted text is cached in `.lokus/pdf-text/<hash>.txt` (pages separated
+//! by form feeds, as `pdftotext` writes them), keyed by the BLAKE3 hash of the
+//! PDF so unchanged or duplicated files are never extracted twice.
+//! `.lokus/pdf-text/index.json` maps each PDF to its cache entry; the search
+//! index reads it to index PDFs under their own path.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+use super::{PDFProcessor, PdfPage};
+
+const SETTINGS_FILE: &str = "pdf-index.json";
+const MAP_FILE: &str = "index.json";
+const PROGRESS_EVENT: &str = "pdf-index-progress";
+const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", "node_modules", ".trash"];
+
+static MAP_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfIndexSettings {
+    pub enabled: bool,
+    /// PDFs larger than this are not indexed
+    pub max_file_size_mb: u64,
+}
+
+impl Default for PdfIndexSettings {
+    fn default() -> Self {
+        PdfIndexSettings { enabled: true, max_file_size_mb: 50 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    hash: String,
+    /// PDF mtime (ms) when it was extracted
+    modified: i64,
+    pages: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheMap {
+    files: HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfIndexProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub current: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfIndexFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfIndexSummary {
+    pub total: usize,
+    pub indexed: usize,
+    pub unchanged: usize,
+    /// Over the size limit
+    pub skipped: usize,
+    pub failed: Vec<PdfIndexFailure>,
+    pub duration_ms: u64,
+}
+
+enum Outcome {
+    Indexed(CacheEntry),
+    Unchanged,
+    Skipped,
+}
+
+fn cache_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join("pdf-text")
+}
+
+fn cache_path(root: &Path, hash: &str) -> PathBuf {
+    cache_dir(root).join(format!("{}.txt", hash))
+}
+
+pub fn load_settings(root: &Path) -> PdfIndexSettings {
+    fs::read_to_string(root.join(".lokus").join(SETTINGS_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(root: &Path, settings: &PdfIndexSettings) -> Result<(), String> {
+    let dir = root.join(".lokus");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to serialize PDF settings: {}", e))?;
+    fs::write(dir.join(SETTINGS_FILE), json).map_err(|e| format!("Failed to write PDF settings: {}", e))
+}
+
+fn load_map(root: &Path) -> CacheMap {
+    fs::read_to_string(cache_dir(root).join(MAP_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_map(root: &Path, map: &CacheMap) -> Result<(), String> {
+    let dir = cache_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create PDF text cache: {}", e))?;
+    let json = serde_json::to_string_pretty(map).map_err(|e| format!("Failed to serialize PDF index: {}", e))?;
+    let temp = dir.join(format!("{}.tmp", MAP_FILE));
+    fs::write(&temp, json).map_err(|e| format!("Failed to write PDF index: {}", e))?;
+    fs::rename(&temp, dir.join(MAP_FILE)).map_err(|e| format!("Failed to replace PDF index: {}", e))
+}
+
+fn modified_ms(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("pdf"))
+}
+
+/// Indexed PDFs as (relative path, cached text path, PDF mtime at extraction).
+/// PDFs that have since been deleted are left out.
+pub(crate) fn indexed_pdfs(root: &Path) -> Vec<(String, PathBuf, i64)> {
+    load_map(root)
+        .files
+        .into_iter()
+        .filter(|(rel, _)| root.join(rel).is_file())
+        .map(|(rel, entry)| (rel, cache_path(root, &entry.hash), entry.modified))
+        .filter(|(_, cache, _)| cache.is_file())
+        .collect()
+}
+
+/// Cached text of an indexed PDF, pages separated by form feeds
+pub(crate) fn read_text(root: &Path, rel: &str) -> Option<String> {
+    let entry = load_map(root).files.remove(rel)?;
+    fs::read_to_string(cache_path(root, &entry.hash)).ok()
+}
+
+/// Cached pages of an indexed PDF
+pub(crate) fn cached_pages(root: &Path, rel: &str) -> Option<Vec<PdfPage>> {
+    read_text(root, rel).map(|text| super::split_pages(&text))
+}
+
+fn process(
+    root: &Path,
+    rel: &str,
+    abs: &Path,
+    existing: Option<&CacheEntry>,
+    settings: &PdfIndexSettings,
+    force: bool,
+) -> Result<Outcome, String> {
+    let metadata = fs::metadata(abs).map_err(|e| format!("Failed to read {}: {}", abs.display(), e))?;
+    if metadata.len() > settings.max_file_size_mb.saturating_mul(1024 * 1024) {
+        return Ok(Outcome::Skipped);
+    }
+    let modified = modified_ms(&metadata);
+    if !force {
+        if let Some(entry) = existing {
+            if entry.modified == modified && cache_path(root, &entry.hash).is_file() {
+                return Ok(Outcome::Unchanged);
+            }
+        }
+    }
+
+    let hash = blake3::Hasher::new()
+        .update_reader(fs::File::open(abs).map_err(|e| format!("Failed to read {}: {}", abs.display(), e))?)
+        .map_err(|e| format!("Failed to read {}: {}", abs.display(), e))?
+        .finalize()
+        .to_hex()
+        .to_string();
+    let cache = cache_path(root, &hash);
+    let text = match fs::read_to_string(&cache) {
+        Ok(text) if !force => text,
+        _ => {
+            let pages = PDFProcessor::new(abs)?.extract_text()?;
+            let text = pages.iter().map(|p| p.text.as_str()).collect::<Vec<_>>().join("\u{c}");
+            fs::create_dir_all(cache_dir(root)).map_err(|e| format!("Failed to create PDF text cache: {}", e))?;
+            fs::write(&cache, &text).map_err(|e| format!("Failed to write PDF text cache: {}", e))?;
+            text
+        }
+    };
+
+    crate::search::index::index_extracted_text(root, rel, &text, modified)?;
+    let pages = text.split('\u{c}').count() as u32;
+    Ok(Outcome::Indexed(CacheEntry { hash, modified, pages }))
+}
+
+/// Merge new entries and drop entries and cached text for deleted PDFs
+fn record(root: &Path, updates: Vec<(String, CacheEntry)>) -> Result<(), String> {
+    let _guard = MAP_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut map = load_map(root);
+    map.files.extend(updates);
+    map.files.retain(|rel, _| root.join(rel).is_file());
+
+    let referenced: HashSet<String> = map.files.values().map(|e| format!("{}.txt", e.hash)).collect();
+    for entry in fs::read_dir(cache_dir(root)).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".txt") && !referenced.contains(&name) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    save_map(root, &map)
+}
+
+/// Index one PDF if the workspace allows it
+fn index_file(root: &Path, abs: &Path) -> Result<(), String> {
+    let settings = load_settings(root);
+    if !settings.enabled {
+        return Ok(());
+    }
+    let rel = crate::links::relative_path(root, abs).ok_or_else(|| format!("PDF is outside the workspace: {}", abs.display()))?;
+    let existing = load_map(root).files.remove(&rel);
+    if let Outcome::Indexed(entry) = process(root, &rel, abs, existing.as_ref(), &settings, false)? {
+        record(root, vec![(rel, entry)])?;
+    }
+    Ok(())
+}
+
+/// Hook for PDFs added or changed on disk. Extraction runs in the
+/// background so the watcher isn't held up.
+pub fn notify_file_changed(file_path: &str) {
+    let path = PathBuf::from(file_path);
+    if !is_pdf(&path) {
+        return;
+    }
+    let Ok(root) = crate::handlers::files::find_workspace_root(&path) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        if let Err(e) = index_file(&root, &path) {
+            tracing::warn!("Failed to index {}: {}", path.display(), e);
+        }
+    });
+}
+
+fn workspace_pdfs(root: &Path) -> Vec<(String, PathBuf)> {
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !(e.file_type().is_dir() && e.file_name().to_str().map_or(false, |n| EXCLUDED_DIRS.contains(&n)))
+        })
+        .flatten()
+        .filter(|e| e.file_type().is_file() && is_pdf(e.path()))
+        .filter_map(|e| Some((crate::links::relative_path(root, e.path())?, e.path().to_path_buf())))
+        .collect()
+}
+
+fn reindex(root: &Path, force: bool, mut progress: impl FnMut(PdfIndexProgress)) -> Result<PdfIndexSummary, String> {
+    let started = Instant::now();
+    let settings = load_settings(root);
+    let pdfs = if settings.enabled { workspace_pdfs(root) } else { Vec::new() };
+    let mut existing = load_map(root).files;
+    let mut summary = PdfIndexSummary { total: pdfs.len(), ..Default::default() };
+    let mut updates = Vec::new();
+
+    for (processed, (rel, abs)) in pdfs.into_iter().enumerate() {
+        progress(PdfIndexProgress { processed, total: summary.total, current: rel.clone() });
+        match process(root, &rel, &abs, existing.remove(&rel).as_ref(), &settings, force) {
+            Ok(Outcome::Indexed(entry)) => {
+                summary.indexed += 1;
+                updates.push((rel, entry));
+            }
+            Ok(Outcome::Unchanged) => summary.unchanged += 1,
+            Ok(Outcome::Skipped) => summary.skipped += 1,
+            Err(error) => summary.failed.push(PdfIndexFailure { path: rel, error }),
+        }
+    }
+    record(root, updates)?;
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(summary)
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn pdf_index_settings_get(workspace_path: String) -> Result<PdfIndexSettings, String> {
+    Ok(load_settings(Path::new(&workspace_path)))
+}
+
+#[tauri::command]
+pub fn pdf_index_settings_set(workspace_path: String, settings: PdfIndexSettings) -> Result<(), String> {
+    save_settings(Path::new(&workspace_path), &settings)
+}
+
+/// Extract and index every PDF that changed since it was last indexed, or
+/// all of them with `force`
+#[tauri::command]
+pub async fn reindex_pdfs(app: AppHandle, workspace_path: String, force: Option<bool>) -> Result<PdfIndexSummary, String> {
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(format!("Workspace not found: {}", workspace_path));
+    }
+    tokio::task::spawn_blocking(move || {
+        reindex(&root, force.unwrap_or(false), |progress| {
+            if let Err(e) = app.emit(PROGRESS_EVENT, &progress) {
+                tracing::warn!("Failed to emit {}: {}", PROGRESS_EVENT, e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("PDF indexing failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_limit_and_cached_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("big.pdf"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+        let settings = PdfIndexSettings { enabled: true, max_file_size_mb: 1 };
+        assert!(matches!(
+            process(root, "big.pdf", &root.join("big.pdf"), None, &settings, false),
+            Ok(Outcome::Skipped)
+        ));
+
+        fs::create_dir_all(cache_dir(root)).unwrap();
+        fs::write(cache_path(root, "abc"), "first page\u{c}second page").unwrap();
+        record(root, vec![("big.pdf".to_string(), CacheEntry { hash: "abc".to_string(), modified: 1, pages: 2 })]).unwrap();
+        let pages = cached_pages(root, "big.pdf").unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].page, 2);
+        assert_eq!(pages[1].text, "second page");
+    }
+}
@@ -0,0 +1,211 @@
+//! PDF text, image and page extraction.
+//!
+//! Backed by the poppler command-line tools (`pdfinfo`, `pdftotext`,
+//! `pdfimages`, `pdftoppm`), which ship with most Linux distributions and
+//! are available through Homebrew and the poppler Windows builds.
+
+pub mod index;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const DEFAULT_DPI: u32 = 110;
+const MAX_DPI: u32 = 600;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfPage {
+    /// 1-based page number
+    pub page: u32,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedImage {
+    pub page: u32,
+    pub path: String,
+}
+
+pub struct PDFProcessor {
+    path: PathBuf,
+}
+
+/// Run a poppler tool as `program <args> <pdf> <trailing>`
+fn run(program: &str, args: &[&str], path: &Path, trailing: &[&Path]) -> Result<Output, String> {
+    let output = Command::new(program)
+        .args(args)
+        .arg(path)
+        .args(trailing)
+        .output()
+        .map_err(|e| format!("Failed to run {} (is poppler installed?): {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed for {}: {}", program, path.display(), stderr.trim()));
+    }
+    Ok(output)
+}
+
+/// Page count from `pdfinfo` output
+fn parse_page_count(info: &str) -> Option<u32> {
+    info.lines()
+        .find_map(|line| line.strip_prefix("Pages:"))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+/// Split `pdftotext` output at form feeds, one entry per page
+fn split_pages(text: &str) -> Vec<PdfPage> {
+    let mut pages: Vec<&str> = text.split('\u{c}').collect();
+    // pdftotext ends the last page with a form feed too
+    if pages.len() > 1 && pages.last().map_or(false, |p| p.trim().is_empty()) {
+        pages.pop();
+    }
+    pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| PdfPage { page: i as u32 + 1, text: text.trim_end().to_string() })
+        .collect()
+}
+
+/// Page number from a `pdfimages -p` file name (`<prefix>-<page>-<index>.<ext>`)
+fn image_page(file_name: &str) -> Option<u32> {
+    let stem = file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem);
+    let mut parts = stem.rsplit('-');
+    parts.next()?;
+    parts.next()?.parse().ok()
+}
+
+impl PDFProcessor {
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        if !path.is_file() {
+            return Err(format!("PDF not found: {}", path.display()));
+        }
+        Ok(PDFProcessor { path })
+    }
+
+    pub fn page_count(&self) -> Result<u32, String> {
+        let output = run("pdfinfo", &[], &self.path, &[])?;
+        parse_page_count(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| format!("Could not read the page count of {}", self.path.display()))
+    }
+
+    fn check_page(&self, page: u32) -> Result<(), String> {
+        let count = self.page_count()?;
+        if page == 0 || page > count {
+            return Err(format!("Page {} is out of range (1-{})", page, count));
+        }
+        Ok(())
+    }
+
+    /// Text of one page, with columns and indentation kept
+    pub fn extract_page_text(&self, page: u32) -> Result<String, String> {
+        self.check_page(page)?;
+        let page = page.to_string();
+        // Text goes to stdout when the output file is "-"
+        let output = run("pdftotext", &["-layout", "-enc", "UTF-8", "-f", &page, "-l", &page], &self.path, &[Path::new("-")])?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches(['\u{c}', '\n']).to_string())
+    }
+
+    /// Text of every page
+    pub fn extract_text(&self) -> Result<Vec<PdfPage>, String> {
+        let output = run("pdftotext", &["-layout", "-enc", "UTF-8"], &self.path, &[Path::new("-")])?;
+        Ok(split_pages(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Write every embedded image XObject to `output_dir` as PNG (JPEGs are
+    /// kept as-is)
+    pub fn extract_images(&self, output_dir: &Path) -> Result<Vec<ExtractedImage>, String> {
+        fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+        let stem = self.path.file_stem().map_or("pdf".into(), |s| s.to_string_lossy());
+        let prefix = output_dir.join(stem.as_ref());
+        run("pdfimages", &["-png", "-j", "-p"], &self.path, &[prefix.as_path()])?;
+
+        let stem_prefix = format!("{}-", stem);
+        let mut images: Vec<ExtractedImage> = fs::read_dir(output_dir)
+            .map_err(|e| format!("Failed to read {}: {}", output_dir.display(), e))?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let page = image_page(name.strip_prefix(&stem_prefix)?)?;
+                Some(ExtractedImage { page, path: entry.path().to_string_lossy().to_string() })
+            })
+            .collect();
+        images.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(images)
+    }
+
+    /// Render one page to PNG bytes
+    pub fn render_page_png(&self, page: u32, dpi: u32) -> Result<Vec<u8>, String> {
+        self.check_page(page)?;
+        let page = page.to_string();
+        let dpi = dpi.clamp(18, MAX_DPI).to_string();
+        // With -singlefile and no output root, pdftoppm writes to stdout
+        let output = run("pdftoppm", &["-png", "-singlefile", "-r", &dpi, "-f", &page, "-l", &page], &self.path, &[])?;
+        if output.stdout.is_empty() {
+            return Err(format!("pdftoppm produced no image for {}", self.path.display()));
+        }
+        Ok(output.stdout)
+    }
+}
+
+// --- Tauri Commands ---
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("PDF task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn pdf_page_count(path: String) -> Result<u32, String> {
+    blocking(move || PDFProcessor::new(path)?.page_count()).await
+}
+
+#[tauri::command]
+pub async fn pdf_extract_text(path: String) -> Result<Vec<PdfPage>, String> {
+    blocking(move || PDFProcessor::new(path)?.extract_text()).await
+}
+
+#[tauri::command]
+pub async fn pdf_extract_page_text(path: String, page: u32) -> Result<String, String> {
+    blocking(move || PDFProcessor::new(path)?.extract_page_text(page)).await
+}
+
+#[tauri::command]
+pub async fn pdf_extract_images(path: String, output_dir: String) -> Result<Vec<ExtractedImage>, String> {
+    blocking(move || PDFProcessor::new(path)?.extract_images(Path::new(&output_dir))).await
+}
+
+/// Render a page for preview, as a `data:image/png;base64,...` URL
+#[tauri::command]
+pub async fn render_pdf_page_to_png(path: String, page: u32, dpi: Option<u32>) -> Result<String, String> {
+    blocking(move || {
+        let png = PDFProcessor::new(path)?.render_page_png(page, dpi.unwrap_or(DEFAULT_DPI))?;
+        Ok(format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(png)))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_poppler_output() {
+        let info = "Title:          Report\nPages:          12\nEncrypted:      no\n";
+        assert_eq!(parse_page_count(info), Some(12));
+        assert_eq!(parse_page_count("Title: x"), None);
+
+        let pages = split_pages("First page\n   indented\n\u{c}Second page\n\u{c}");
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].text, "First page\n   indented");
+        assert_eq!(pages[1].page, 2);
+
+        assert_eq!(image_page("report-003-001.png"), Some(3));
+        assert_eq!(image_page("001.png"), None);
+    }
+}
@@ -2,7 +2,7 @@
 
 use super::{
     errors::PlatformError, FileSystemOperations, PlatformConfig, PlatformFeature,
-    PlatformProvider, SystemIntegration,
+    PlatformProvider, SearchIndexing, SearchableNote, SystemIntegration,
 };
 use std::path::Path;
 
@@ -40,6 +40,7 @@ impl SystemIntegration for AndroidPlatform {
             PlatformFeature::CustomTerminal => false,
             PlatformFeature::QuickLook => false,
             PlatformFeature::ContextMenus => true, // Android supports context menus
+            PlatformFeature::OsSearchIndex => false,
         }
     }
 
@@ -53,6 +54,16 @@ impl SystemIntegration for AndroidPlatform {
     }
 }
 
+impl SearchIndexing for AndroidPlatform {
+    fn replace_search_index(&self, _vault_id: &str, _notes: &[SearchableNote]) -> Result<(), PlatformError> {
+        Err(PlatformError::unsupported("OS search indexing"))
+    }
+
+    fn clear_search_index(&self, _vault_id: &str) -> Result<(), PlatformError> {
+        Ok(())
+    }
+}
+
 impl PlatformProvider for AndroidPlatform {
     fn initialize(&mut self) -> Result<(), PlatformError> {
         self.initialized = true;
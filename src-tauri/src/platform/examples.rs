@@ -30,6 +30,7 @@ pub fn example_platform_detection() -> Result<(), PlatformError> {
         ("Custom Terminal", PlatformFeature::CustomTerminal),
         ("Quick Look", PlatformFeature::QuickLook),
         ("Context Menus", PlatformFeature::ContextMenus),
+        ("OS Search Index", PlatformFeature::OsSearchIndex),
     ];
     
     for (name, feature) in features {
@@ -2,7 +2,7 @@
 
 use super::{
     errors::PlatformError, FileSystemOperations, PlatformConfig, PlatformFeature,
-    PlatformProvider, SystemIntegration,
+    PlatformProvider, SearchIndexing, SearchableNote, SystemIntegration,
 };
 use std::path::Path;
 
@@ -40,6 +40,7 @@ impl SystemIntegration for IosPlatform {
             PlatformFeature::CustomTerminal => false,
             PlatformFeature::QuickLook => true, // iOS supports Quick Look
             PlatformFeature::ContextMenus => true, // iOS supports context menus
+            PlatformFeature::OsSearchIndex => false,
         }
     }
 
@@ -53,6 +54,17 @@ impl SystemIntegration for IosPlatform {
     }
 }
 
+impl SearchIndexing for IosPlatform {
+    fn replace_search_index(&self, _vault_id: &str, _notes: &[SearchableNote]) -> Result<(), PlatformError> {
+        // Core Spotlight on iOS would need the app extension Lokus doesn't ship
+        Err(PlatformError::unsupported("OS search indexing"))
+    }
+
+    fn clear_search_index(&self, _vault_id: &str) -> Result<(), PlatformError> {
+        Ok(())
+    }
+}
+
 impl PlatformProvider for IosPlatform {
     fn initialize(&mut self) -> Result<(), PlatformError> {
         self.initialized = true;
@@ -5,7 +5,7 @@
 /// and tools.
 
 use super::{
-    FileSystemOperations, SystemIntegration, PlatformProvider, PlatformFeature, PlatformConfig,
+    FileSystemOperations, SystemIntegration, SearchIndexing, SearchableNote, PlatformProvider, PlatformFeature, PlatformConfig,
     errors::{PlatformError, PlatformErrorKind, ErrorMessages}
 };
 use std::path::Path;
@@ -362,6 +362,7 @@ impl SystemIntegration for LinuxPlatform {
             }
             PlatformFeature::QuickLook => false, // Linux doesn't have built-in QuickLook
             PlatformFeature::ContextMenus => true, // Linux DEs support context menus
+            PlatformFeature::OsSearchIndex => false,
         }
     }
     
@@ -375,6 +376,17 @@ impl SystemIntegration for LinuxPlatform {
     }
 }
 
+impl SearchIndexing for LinuxPlatform {
+    fn replace_search_index(&self, _vault_id: &str, _notes: &[SearchableNote]) -> Result<(), PlatformError> {
+        // Desktop search tools (Tracker, Baloo) index the vault folder themselves
+        Err(PlatformError::unsupported("OS search indexing"))
+    }
+
+    fn clear_search_index(&self, _vault_id: &str) -> Result<(), PlatformError> {
+        Ok(())
+    }
+}
+
 impl PlatformProvider for LinuxPlatform {
     fn initialize(&mut self) -> Result<(), PlatformError> {
         if self.initialized {
@@ -4,7 +4,7 @@
 /// system integration, and error handling using native macOS tools and conventions.
 
 use super::{
-    FileSystemOperations, SystemIntegration, PlatformProvider, PlatformFeature, PlatformConfig,
+    FileSystemOperations, SystemIntegration, SearchIndexing, SearchableNote, PlatformProvider, PlatformFeature, PlatformConfig,
     errors::{PlatformError, ErrorMessages}
 };
 use std::path::Path;
@@ -183,6 +183,7 @@ impl SystemIntegration for MacOsPlatform {
             PlatformFeature::CustomTerminal => self.is_iterm_available() || self.is_warp_available(),
             PlatformFeature::QuickLook => true, // macOS has built-in QuickLook
             PlatformFeature::ContextMenus => true, // macOS supports context menus
+            PlatformFeature::OsSearchIndex => true, // Core Spotlight
         }
     }
     
@@ -196,6 +197,157 @@ impl SystemIntegration for MacOsPlatform {
     }
 }
 
+impl SearchIndexing for MacOsPlatform {
+    fn replace_search_index(&self, vault_id: &str, notes: &[SearchableNote]) -> Result<(), PlatformError> {
+        spotlight::clear(vault_id)?;
+        spotlight::index(vault_id, notes)
+    }
+
+    fn clear_search_index(&self, vault_id: &str) -> Result<(), PlatformError> {
+        spotlight::clear(vault_id)
+    }
+}
+
+/// Core Spotlight items, one per note. Each vault is a Spotlight domain so it
+/// can be replaced or removed as a whole; an item's identifier is the note's
+/// `lokus://` link.
+pub mod spotlight {
+    use super::{PlatformError, SearchableNote};
+    use block2::{DynBlock, RcBlock};
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, Bool, Sel};
+    use objc2::{msg_send, sel, AnyThread};
+    use objc2_core_spotlight::{CSSearchableIndex, CSSearchableItem, CSSearchableItemAttributeSet};
+    use objc2_foundation::{NSArray, NSDate, NSError, NSString, NSURL};
+    use std::sync::{mpsc, OnceLock};
+    use std::time::Duration;
+
+    const MARKDOWN_UTI: &str = "net.daringfireball.markdown";
+    /// Items per indexing call
+    const BATCH: usize = 500;
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const DESCRIPTION_CHARS: usize = 300;
+
+    static ON_OPEN: OnceLock<Box<dyn Fn(String) + Send + Sync>> = OnceLock::new();
+
+    /// Run a Spotlight call and wait for its completion handler
+    fn wait(operation: &str, call: impl FnOnce(&DynBlock<dyn Fn(*mut NSError)>)) -> Result<(), PlatformError> {
+        let (tx, rx) = mpsc::channel();
+        let done = RcBlock::new(move |error: *mut NSError| {
+            let message = unsafe { error.as_ref() }.map(|e| e.localizedDescription().to_string());
+            let _ = tx.send(message);
+        });
+        call(&done);
+        match rx.recv_timeout(TIMEOUT) {
+            Ok(None) => Ok(()),
+            Ok(Some(message)) => Err(PlatformError::system(message, operation)),
+            Err(_) => Err(PlatformError::system("Spotlight did not respond", operation)),
+        }
+    }
+
+    fn item(vault_id: &str, note: &SearchableNote) -> Retained<CSSearchableItem> {
+        unsafe {
+            let attributes = CSSearchableItemAttributeSet::initWithItemContentType(
+                CSSearchableItemAttributeSet::alloc(),
+                &NSString::from_str(MARKDOWN_UTI),
+            );
+            attributes.setTitle(Some(&NSString::from_str(&note.title)));
+            attributes.setDisplayName(Some(&NSString::from_str(&note.title)));
+            attributes.setTextContent(Some(&NSString::from_str(&note.text)));
+            let description: String = note.text.chars().take(DESCRIPTION_CHARS).collect();
+            attributes.setContentDescription(Some(&NSString::from_str(&description)));
+            let keywords: Vec<Retained<NSString>> = note.keywords.iter().map(|k| NSString::from_str(k)).collect();
+            attributes.setKeywords(Some(&NSArray::from_retained_slice(&keywords)));
+            if let Some(url) = NSURL::URLWithString(&NSString::from_str(&note.link)) {
+                attributes.setContentURL(Some(&url));
+            }
+            if let Some(modified) = note.modified {
+                let date = NSDate::dateWithTimeIntervalSince1970(modified as f64 / 1000.0);
+                attributes.setContentModificationDate(Some(&date));
+            }
+            CSSearchableItem::initWithUniqueIdentifier_domainIdentifier_attributeSet(
+                CSSearchableItem::alloc(),
+                Some(&NSString::from_str(&note.link)),
+                Some(&NSString::from_str(vault_id)),
+                &attributes,
+            )
+        }
+    }
+
+    pub(super) fn index(vault_id: &str, notes: &[SearchableNote]) -> Result<(), PlatformError> {
+        let index = unsafe { CSSearchableIndex::defaultSearchableIndex() };
+        for batch in notes.chunks(BATCH) {
+            let items: Vec<_> = batch.iter().map(|note| item(vault_id, note)).collect();
+            let items = NSArray::from_retained_slice(&items);
+            wait("replace_search_index", |done| unsafe {
+                index.indexSearchableItems_completionHandler(&items, Some(done))
+            })?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn clear(vault_id: &str) -> Result<(), PlatformError> {
+        let index = unsafe { CSSearchableIndex::defaultSearchableIndex() };
+        let domains = NSArray::from_retained_slice(&[NSString::from_str(vault_id)]);
+        wait("clear_search_index", |done| unsafe {
+            index.deleteSearchableItemsWithDomainIdentifiers_completionHandler(&domains, Some(done))
+        })
+    }
+
+    /// `application:continueUserActivity:restorationHandler:`, which AppKit
+    /// calls when a Spotlight result is opened
+    extern "C-unwind" fn continue_user_activity(
+        _this: &AnyObject,
+        _cmd: Sel,
+        _app: &AnyObject,
+        activity: &AnyObject,
+        _restoration_handler: *mut AnyObject,
+    ) -> Bool {
+        let link = unsafe {
+            let info: *mut AnyObject = msg_send![activity, userInfo];
+            info.as_ref().and_then(|info| {
+                let key = NSString::from_str("kCSSearchableItemActivityIdentifier");
+                let value: *mut NSString = msg_send![info, objectForKey: &*key];
+                value.as_ref().map(|value| value.to_string())
+            })
+        };
+        match (link, ON_OPEN.get()) {
+            (Some(link), Some(open)) => {
+                open(link);
+                Bool::YES
+            }
+            _ => Bool::NO,
+        }
+    }
+
+    /// Call `open` with the `lokus://` link of each Spotlight result the user
+    /// opens. The app delegate doesn't handle user activities, so the method
+    /// is added to its class. Must run on the main thread after launch.
+    pub fn on_open(open: impl Fn(String) + Send + Sync + 'static) {
+        if ON_OPEN.set(Box::new(open)).is_err() {
+            return;
+        }
+        unsafe {
+            let Some(ns_application) = AnyClass::get(c"NSApplication") else { return };
+            let app: *mut AnyObject = msg_send![ns_application, sharedApplication];
+            let Some(app) = app.as_ref() else { return };
+            let delegate: *mut AnyObject = msg_send![app, delegate];
+            let Some(delegate) = delegate.as_ref() else { return };
+
+            let class = delegate.class() as *const AnyClass as *mut AnyClass;
+            let handler: extern "C-unwind" fn(&AnyObject, Sel, &AnyObject, &AnyObject, *mut AnyObject) -> Bool =
+                continue_user_activity;
+            let types = if cfg!(target_arch = "aarch64") { c"B@:@@@" } else { c"c@:@@@" };
+            objc2::ffi::class_addMethod(
+                class,
+                sel!(application:continueUserActivity:restorationHandler:),
+                std::mem::transmute::<_, objc2::runtime::Imp>(handler),
+                types.as_ptr(),
+            );
+        }
+    }
+}
+
 impl PlatformProvider for MacOsPlatform {
     fn initialize(&mut self) -> Result<(), PlatformError> {
         if self.initialized {
@@ -20,7 +20,7 @@ pub mod ios;
 pub mod android;
 
 use errors::PlatformError;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Trait for file system operations that vary by platform
 pub trait FileSystemOperations {
@@ -57,6 +57,33 @@ pub trait SystemIntegration {
     fn get_platform_config(&self) -> PlatformConfig;
 }
 
+/// A note as handed to the OS-level search index
+#[derive(Debug, Clone)]
+pub struct SearchableNote {
+    /// `lokus://` link that opens the note; also its identifier in the index
+    pub link: String,
+    pub path: PathBuf,
+    pub title: String,
+    /// Plain text for full-text search, trimmed to a size the index accepts
+    pub text: String,
+    pub keywords: Vec<String>,
+    /// Unix ms
+    pub modified: Option<i64>,
+}
+
+/// Trait for putting notes into OS-level search (Spotlight, Windows Search)
+pub trait SearchIndexing {
+    /// Replace everything indexed for a vault with `notes`
+    ///
+    /// # Arguments
+    /// * `vault_id` - Stable identifier of the vault the notes belong to
+    /// * `notes` - The vault's notes
+    fn replace_search_index(&self, vault_id: &str, notes: &[SearchableNote]) -> Result<(), PlatformError>;
+
+    /// Remove everything indexed for a vault
+    fn clear_search_index(&self, vault_id: &str) -> Result<(), PlatformError>;
+}
+
 /// Features that may or may not be available on different platforms
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlatformFeature {
@@ -65,6 +92,7 @@ pub enum PlatformFeature {
     CustomTerminal,
     QuickLook,
     ContextMenus,
+    OsSearchIndex,
 }
 
 /// Platform-specific configuration and behavior hints
@@ -102,7 +130,7 @@ pub fn get_platform_provider() -> Box<dyn PlatformProvider> {
 }
 
 /// Combined trait that all platform implementations must provide
-pub trait PlatformProvider: FileSystemOperations + SystemIntegration + Send + Sync {
+pub trait PlatformProvider: FileSystemOperations + SystemIntegration + SearchIndexing + Send + Sync {
     /// Initialize any platform-specific resources
     fn initialize(&mut self) -> Result<(), PlatformError>;
     
@@ -4,10 +4,10 @@
 /// system integration, and error handling.
 
 use super::{
-    FileSystemOperations, SystemIntegration, PlatformProvider, PlatformFeature, PlatformConfig,
+    FileSystemOperations, SystemIntegration, SearchIndexing, SearchableNote, PlatformProvider, PlatformFeature, PlatformConfig,
     errors::{PlatformError, ErrorMessages}
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Windows platform implementation
@@ -198,6 +198,7 @@ impl SystemIntegration for WindowsPlatform {
             PlatformFeature::CustomTerminal => self.is_windows_terminal_available() || self.is_powershell_available(),
             PlatformFeature::QuickLook => false, // Windows doesn't have QuickLook
             PlatformFeature::ContextMenus => true, // Windows supports context menus
+            PlatformFeature::OsSearchIndex => true, // Windows Search over note shortcuts
         }
     }
     
@@ -211,6 +212,77 @@ impl SystemIntegration for WindowsPlatform {
     }
 }
 
+/// Windows Search can only index files, so each note gets an Internet
+/// Shortcut (`.url`) pointing at its `lokus://` link, under
+/// `%LOCALAPPDATA%\Lokus\SearchIndex\<vault>`. That folder is added to the
+/// indexer's crawl scope; opening a result launches the link in Lokus.
+fn search_index_dir(vault_id: &str) -> Result<PathBuf, PlatformError> {
+    dirs::data_local_dir()
+        .map(|dir| dir.join("Lokus").join("SearchIndex").join(vault_id))
+        .ok_or_else(|| PlatformError::system("Local app data folder not found", "search_index"))
+}
+
+/// A file name for `title` that Windows accepts
+fn shortcut_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| if matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') || c.is_control() { '_' } else { c })
+        .take(120)
+        .collect();
+    let name = name.trim().trim_end_matches('.');
+    if name.is_empty() { "Untitled".to_string() } else { name.to_string() }
+}
+
+/// Add `dir` to the Windows Search crawl scope, if it isn't already
+fn include_in_crawl_scope(dir: &Path) -> Result<(), PlatformError> {
+    use windows::core::{w, HSTRING};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_LOCAL_SERVER, COINIT_MULTITHREADED};
+    use windows::Win32::System::Search::{CSearchManager, ISearchManager, FF_INDEXCOMPLEXURLS};
+
+    let fail = |e: windows::core::Error| PlatformError::system(format!("Windows Search: {}", e.message()), "search_index");
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let manager: ISearchManager = CoCreateInstance(&CSearchManager, None, CLSCTX_LOCAL_SERVER).map_err(fail)?;
+        let scope = manager.GetCatalog(w!("SystemIndex")).and_then(|c| c.GetCrawlScopeManager()).map_err(fail)?;
+        let url = HSTRING::from(format!("file:///{}\\", dir.display()));
+        if scope.IncludedInCrawlScope(&url).map_err(fail)?.as_bool() {
+            return Ok(());
+        }
+        scope.AddUserScopeRule(&url, true.into(), true.into(), FF_INDEXCOMPLEXURLS.0 as u32).map_err(fail)?;
+        scope.SaveAll().map_err(fail)
+    }
+}
+
+impl SearchIndexing for WindowsPlatform {
+    fn replace_search_index(&self, vault_id: &str, notes: &[SearchableNote]) -> Result<(), PlatformError> {
+        self.clear_search_index(vault_id)?;
+        let dir = search_index_dir(vault_id)?;
+        std::fs::create_dir_all(&dir).map_err(PlatformError::from)?;
+
+        let mut used = std::collections::HashSet::new();
+        for note in notes {
+            let base = shortcut_name(&note.title);
+            let name = (1..)
+                .map(|n| if n == 1 { base.clone() } else { format!("{} ({})", base, n) })
+                .find(|name| used.insert(name.to_lowercase()))
+                .expect("unbounded candidates");
+            let shortcut = format!("[InternetShortcut]\r\nURL={}\r\n", note.link);
+            std::fs::write(dir.join(format!("{}.url", name)), shortcut).map_err(PlatformError::from)?;
+        }
+
+        let root = dir.parent().unwrap_or(&dir);
+        include_in_crawl_scope(root)
+    }
+
+    fn clear_search_index(&self, vault_id: &str) -> Result<(), PlatformError> {
+        let dir = search_index_dir(vault_id)?;
+        match std::fs::remove_dir_all(&dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(PlatformError::from(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl PlatformProvider for WindowsPlatform {
     fn initialize(&mut self) -> Result<(), PlatformError> {
         if self.initialized {
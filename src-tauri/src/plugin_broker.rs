@@ -0,0 +1,448 @@
+//! Permission broker for plugin-originated calls.
+//!
+//! The plugin host asks for a capability token when it loads an enabled
+//! plugin and attaches it, with the plugin's id, to every call the plugin
+//! makes through the broker commands below. A token only works from the
+//! webview it was issued to: the app window hosting plugins, or the plugin's
+//! own `plugin-<folder>` webview. Each call is checked against the
+//! permissions the user granted the plugin before it runs:
+//!
+//! - `read:files` / `write:files`: files inside the open workspace
+//! - `read:search`: searching the workspace, also allowed by file access
+//! - `network:fetch`: any host; `network:<host>` that host and its subdomains
+//!
+//! Denied calls are logged to `~/.lokus/logs/plugin-audit.jsonl`.
+
+use crate::plugins::{get_plugin_settings, get_plugins_directory, resolve_plugin_name};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreBuilder;
+
+/// Audit entries returned per plugin
+const AUDIT_LIMIT: usize = 500;
+/// The audit log is cut back to its newer half past this size
+const AUDIT_MAX_BYTES: u64 = 2 * 1024 * 1024;
+#[cfg(desktop)]
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Label of the app window that hosts plugins running in the page
+const HOST_WEBVIEW: &str = "main";
+
+/// Tokens of this session, by plugin folder name
+static TOKENS: Lazy<Mutex<HashMap<String, IssuedToken>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static AUDIT_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+struct IssuedToken {
+    token: String,
+    /// Label of the webview the token was issued to
+    webview: String,
+}
+
+/// Identity attached to every plugin-originated call
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginCaller {
+    pub plugin_id: String,
+    pub token: String,
+}
+
+/// What a call needs to be allowed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Capability {
+    ReadFile(PathBuf),
+    WriteFile(PathBuf),
+    Network(String),
+    Search(String),
+}
+
+impl Capability {
+    fn permission(&self) -> String {
+        match self {
+            Capability::ReadFile(_) => "read:files".to_string(),
+            Capability::WriteFile(_) => "write:files".to_string(),
+            Capability::Network(host) => format!("network:{}", host),
+            Capability::Search(_) => "read:search".to_string(),
+        }
+    }
+
+    fn target(&self) -> String {
+        match self {
+            Capability::ReadFile(path) | Capability::WriteFile(path) => path.to_string_lossy().to_string(),
+            Capability::Network(host) => host.clone(),
+            Capability::Search(query) => query.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub plugin_id: String,
+    pub command: String,
+    pub permission: String,
+    pub target: String,
+    pub reason: String,
+}
+
+/// Whether `granted` covers `capability`; file paths are checked separately
+pub fn grants(granted: &[String], capability: &Capability) -> bool {
+    match capability {
+        Capability::ReadFile(_) => granted.iter().any(|p| p == "read:files" || p == "write:files"),
+        // Reading the files covers searching them
+        Capability::Search(_) => granted.iter().any(|p| p == "read:search" || p == "read:files" || p == "write:files"),
+        Capability::WriteFile(_) => granted.iter().any(|p| p == "write:files"),
+        Capability::Network(host) => granted.iter().any(|p| {
+            let Some(allowed) = p.strip_prefix("network:") else {
+                return false;
+            };
+            allowed == "fetch"
+                || host.eq_ignore_ascii_case(allowed)
+                || host.to_ascii_lowercase().ends_with(&format!(".{}", allowed.to_ascii_lowercase()))
+        }),
+    }
+}
+
+/// `path` made absolute and free of `..` and symlinks. Files that don't
+/// exist yet are resolved through their parent directory.
+fn resolve_path(path: &Path) -> Result<PathBuf, String> {
+    if let Ok(resolved) = path.canonicalize() {
+        return Ok(resolved);
+    }
+    let parent = path.parent().ok_or_else(|| "Invalid path".to_string())?;
+    let name = path.file_name().ok_or_else(|| "Invalid path".to_string())?;
+    parent
+        .canonicalize()
+        .map(|parent| parent.join(name))
+        .map_err(|_| format!("Folder does not exist: {}", parent.display()))
+}
+
+pub(crate) fn workspace_root(app: &AppHandle) -> Result<PathBuf, String> {
+    let store = StoreBuilder::new(app, PathBuf::from(".settings.dat"))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    let root = store
+        .get("last_workspace_path")
+        .and_then(|v| v.as_str().map(String::from))
+        .ok_or_else(|| "No workspace is open".to_string())?;
+    PathBuf::from(root)
+        .canonicalize()
+        .map_err(|e| format!("Workspace is not accessible: {}", e))
+}
+
+fn audit_log_path() -> Result<PathBuf, String> {
+    let dir = dirs::home_dir()
+        .ok_or_else(|| "Unable to determine home directory".to_string())?
+        .join(".lokus")
+        .join("logs");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    Ok(dir.join("plugin-audit.jsonl"))
+}
+
+fn record_denial(entry: &AuditEntry) -> Result<(), String> {
+    let _guard = AUDIT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = audit_log_path()?;
+
+    if std::fs::metadata(&path).map(|m| m.len() > AUDIT_MAX_BYTES).unwrap_or(false) {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let lines: Vec<&str> = content.lines().collect();
+        let kept = lines[lines.len() / 2..].join("\n");
+        std::fs::write(&path, kept + "\n").map_err(|e| format!("Failed to trim audit log: {}", e))?;
+    }
+
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))
+}
+
+fn deny(caller: &PluginCaller, command: &str, capability: &Capability, reason: String) -> String {
+    tracing::warn!(
+        plugin = %caller.plugin_id,
+        command,
+        target = %capability.target(),
+        "Denied plugin call: {}",
+        reason
+    );
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        plugin_id: caller.plugin_id.clone(),
+        command: command.to_string(),
+        permission: capability.permission(),
+        target: capability.target(),
+        reason: reason.clone(),
+    };
+    if let Err(e) = record_denial(&entry) {
+        tracing::warn!("Failed to record plugin audit entry: {}", e);
+    }
+    format!("Permission denied: {}", reason)
+}
+
+/// Label of the webview a plugin gets when it runs in its own
+pub(crate) fn plugin_webview_label(folder: &str) -> String {
+    format!("plugin-{}", folder)
+}
+
+fn plugin_folder(plugin_id: &str) -> Result<String, String> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    resolve_plugin_name(&plugins_dir, plugin_id)
+}
+
+/// Forget a plugin's token; its calls are refused until the host asks again
+pub fn revoke(plugin_folder: &str) {
+    TOKENS.lock().unwrap_or_else(|e| e.into_inner()).remove(plugin_folder);
+}
+
+/// Check `caller`, calling from the webview labelled `webview`, may do
+/// `capability` for `command`, logging refusals
+pub fn authorize(app: &AppHandle, caller: &PluginCaller, webview: &str, command: &str, capability: &Capability) -> Result<(), String> {
+    let folder = match plugin_folder(&caller.plugin_id) {
+        Ok(folder) => folder,
+        Err(_) => return Err(deny(caller, command, capability, "unknown plugin".to_string())),
+    };
+
+    let token_valid = TOKENS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&folder)
+        .is_some_and(|issued| issued.token == caller.token && issued.webview == webview);
+    if !token_valid {
+        return Err(deny(caller, command, capability, "invalid capability token".to_string()));
+    }
+    authorize_granted(app, caller, &folder, command, capability)
+}
+
+/// `authorize` without the token, for calls the backend makes on a plugin's
+/// behalf (its WASM module) rather than ones arriving from the webview
+pub(crate) fn authorize_host(app: &AppHandle, plugin_id: &str, command: &str, capability: &Capability) -> Result<(), String> {
+    let caller = PluginCaller { plugin_id: plugin_id.to_string(), token: String::new() };
+    match plugin_folder(plugin_id) {
+        Ok(folder) => authorize_granted(app, &caller, &folder, command, capability),
+        Err(_) => Err(deny(&caller, command, capability, "unknown plugin".to_string())),
+    }
+}
+
+fn authorize_granted(app: &AppHandle, caller: &PluginCaller, folder: &str, command: &str, capability: &Capability) -> Result<(), String> {
+    let settings = get_plugin_settings(app)?;
+    if !settings.enabled_plugins.iter().any(|p| p == folder) {
+        revoke(folder);
+        return Err(deny(caller, command, capability, "plugin is disabled".to_string()));
+    }
+    // Permissions may have been granted under the manifest id or the folder
+    let granted: Vec<String> = [caller.plugin_id.as_str(), folder]
+        .iter()
+        .filter_map(|key| settings.plugin_permissions.get(*key))
+        .flatten()
+        .cloned()
+        .collect();
+    if !grants(&granted, capability) {
+        let reason = format!("'{}' not granted", capability.permission());
+        return Err(deny(caller, command, capability, reason));
+    }
+    Ok(())
+}
+
+/// `authorize` for a file capability, which must also lie inside the open
+/// workspace. Returns the resolved path to use.
+pub fn authorize_path(
+    app: &AppHandle,
+    caller: &PluginCaller,
+    webview: &str,
+    command: &str,
+    capability: &Capability,
+) -> Result<PathBuf, String> {
+    authorize(app, caller, webview, command, capability)?;
+    workspace_path(app, caller, command, capability)
+}
+
+/// `authorize_path` for calls made on a plugin's behalf, see `authorize_host`
+pub(crate) fn authorize_host_path(app: &AppHandle, plugin_id: &str, command: &str, capability: &Capability) -> Result<PathBuf, String> {
+    authorize_host(app, plugin_id, command, capability)?;
+    let caller = PluginCaller { plugin_id: plugin_id.to_string(), token: String::new() };
+    workspace_path(app, &caller, command, capability)
+}
+
+fn workspace_path(app: &AppHandle, caller: &PluginCaller, command: &str, capability: &Capability) -> Result<PathBuf, String> {
+    let (Capability::ReadFile(path) | Capability::WriteFile(path)) = capability else {
+        return Err("Not a file capability".to_string());
+    };
+
+    let root = workspace_root(app)?;
+    // Relative paths are taken from the workspace root
+    let path = root.join(path);
+    let resolved = resolve_path(&path).map_err(|e| deny(caller, command, capability, e))?;
+    if !resolved.starts_with(&root) {
+        return Err(deny(caller, command, capability, "path is outside the workspace".to_string()));
+    }
+    Ok(resolved)
+}
+
+#[cfg(desktop)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginFetchRequest {
+    pub url: String,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+#[cfg(desktop)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginFetchResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+// --- Tauri Commands ---
+
+/// Issue the capability token for an enabled plugin, usable only from the
+/// requesting webview. Each call rotates the token, so only the host that
+/// loaded the plugin last holds a working one.
+#[tauri::command]
+pub fn plugin_issue_token(app: AppHandle, webview: tauri::Webview, plugin_id: String) -> Result<String, String> {
+    let folder = plugin_folder(&plugin_id)?;
+    if !get_plugin_settings(&app)?.enabled_plugins.contains(&folder) {
+        return Err(format!("Plugin '{}' is not enabled", plugin_id));
+    }
+    // Another plugin's webview can't obtain this plugin's token
+    let label = webview.label().to_string();
+    if label != HOST_WEBVIEW && label != plugin_webview_label(&folder) {
+        return Err(format!("Plugin '{}' can't be loaded in this window", plugin_id));
+    }
+    let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    TOKENS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(folder, IssuedToken { token: token.clone(), webview: label });
+    Ok(token)
+}
+
+#[tauri::command]
+pub async fn plugin_read_file(app: AppHandle, webview: tauri::Webview, caller: PluginCaller, path: String) -> Result<String, String> {
+    let capability = Capability::ReadFile(PathBuf::from(path));
+    let path = authorize_path(&app, &caller, webview.label(), "plugin_read_file", &capability)?;
+    Ok(crate::handlers::files::read_file_content(path.to_string_lossy().to_string()).await?)
+}
+
+#[tauri::command]
+pub fn plugin_write_file(
+    app: AppHandle,
+    webview: tauri::Webview,
+    caller: PluginCaller,
+    path: String,
+    content: String,
+) -> Result<(), String> {
+    let capability = Capability::WriteFile(PathBuf::from(path));
+    let path = authorize_path(&app, &caller, webview.label(), "plugin_write_file", &capability)?;
+    Ok(crate::handlers::files::write_file_content(path.to_string_lossy().to_string(), content)?)
+}
+
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn plugin_fetch(
+    app: AppHandle,
+    webview: tauri::Webview,
+    caller: PluginCaller,
+    request: PluginFetchRequest,
+) -> Result<PluginFetchResponse, String> {
+    let url = url::Url::parse(&request.url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Only http and https URLs can be fetched".to_string());
+    }
+    let host = url.host_str().ok_or_else(|| "URL has no host".to_string())?.to_string();
+    authorize(&app, &caller, webview.label(), "plugin_fetch", &Capability::Network(host))?;
+
+    let method = reqwest::Method::from_bytes(request.method.as_deref().unwrap_or("GET").to_uppercase().as_bytes())
+        .map_err(|_| "Invalid HTTP method".to_string())?;
+    // A redirect could lead to a host the plugin wasn't granted; the plugin
+    // gets the 3xx and has to fetch the new location itself
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut builder = client.request(method, url);
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+
+    let mut response = builder.send().await.map_err(|e| format!("Request failed: {}", e))?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| value.to_str().ok().map(|v| (name.to_string(), v.to_string())))
+        .collect();
+    if response.content_length().is_some_and(|len| len > MAX_RESPONSE_BYTES as u64) {
+        return Err("Response is too large".to_string());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read response: {}", e))? {
+        if bytes.len() + chunk.len() > MAX_RESPONSE_BYTES {
+            return Err("Response is too large".to_string());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(PluginFetchResponse {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&bytes).to_string(),
+    })
+}
+
+/// Denied calls of a plugin, newest first
+#[tauri::command]
+pub fn get_plugin_audit_log(plugin_id: String) -> Result<Vec<AuditEntry>, String> {
+    let path = audit_log_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read audit log: {}", e))?;
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+        .filter(|entry| entry.plugin_id == plugin_id)
+        .take(AUDIT_LIMIT)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grants() {
+        let granted = vec!["read:files".to_string(), "network:example.com".to_string()];
+
+        assert!(grants(&granted, &Capability::ReadFile(PathBuf::from("/vault/a.md"))));
+        assert!(!grants(&granted, &Capability::WriteFile(PathBuf::from("/vault/a.md"))));
+        assert!(grants(&granted, &Capability::Network("api.Example.com".to_string())));
+        assert!(grants(&granted, &Capability::Network("example.com".to_string())));
+        assert!(!grants(&granted, &Capability::Network("badexample.com".to_string())));
+        assert!(grants(&granted, &Capability::Search("todo".to_string())));
+        assert!(!grants(&["network:fetch".to_string()], &Capability::Search("todo".to_string())));
+
+        let everything = vec!["write:files".to_string(), "network:fetch".to_string()];
+        assert!(grants(&everything, &Capability::ReadFile(PathBuf::from("/vault/a.md"))));
+        assert!(grants(&everything, &Capability::Network("anywhere.org".to_string())));
+    }
+}
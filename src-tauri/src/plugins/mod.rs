@@ -0,0 +1,1882 @@
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::io::Read;
+// Removed unused imports for performance optimization
+use tauri_plugin_store::{StoreBuilder, JsonValue};
+use tauri::AppHandle;
+use zip::ZipArchive;
+use semver::Version;
+use crate::errors::LokusError;
+use chrono;
+use walkdir;
+
+#[cfg(desktop)]
+pub mod wasm_host;
+
+// === Core Data Structures ===
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>, // Plugin identifier (defaults to name if not provided)
+    pub version: String,
+    pub description: String,
+    pub author: String,
+    pub main: String, // Entry point file
+    pub permissions: Vec<String>,
+    pub dependencies: Option<HashMap<String, String>>,
+    pub keywords: Option<Vec<String>>,
+    pub repository: Option<serde_json::Value>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+    pub contributes: Option<serde_json::Value>,
+    /// WASM module with backend functions, relative to the plugin folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PluginInfo {
+    pub manifest: PluginManifest,
+    pub path: String,
+    pub enabled: bool,
+    pub installed_at: String, // ISO timestamp
+    pub size: u64, // Size in bytes
+    // Cached marketplace metadata (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_from: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PluginSettings {
+    pub enabled_plugins: Vec<String>,
+    pub plugin_permissions: HashMap<String, Vec<String>>,
+    pub plugin_settings: HashMap<String, JsonValue>,
+}
+
+/// Cached marketplace metadata (saved alongside plugin)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct MarketplaceMetadata {
+    pub slug: Option<String>,
+    pub icon_url: Option<String>,
+    pub description: Option<String>,
+    pub readme: Option<String>,
+    pub changelog: Option<String>,
+    pub author: Option<String>,
+    pub downloads: Option<u64>,
+    pub rating: Option<f64>,
+    pub homepage: Option<String>,
+    pub repository: Option<String>,
+    pub license: Option<String>,
+    pub installed_at: Option<String>,
+    pub installed_from: Option<String>,
+}
+
+// Removed cache for simpler implementation
+
+#[derive(Serialize, Debug)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ValidationResult {
+    pub valid: bool,
+    pub errors: Vec<ValidationError>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstallationLog {
+    pub plugin_name: String,
+    pub version: String,
+    pub installed_at: String,
+    pub install_method: String,
+    pub source_url: Option<String>,
+    pub checksum: Option<String>,
+    #[serde(default)]
+    pub registry_id: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[allow(dead_code)]
+pub struct GitHubRepoInfo {
+    pub owner: String,
+    pub repo: String,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub download_url: String,
+}
+
+#[derive(Serialize, Debug)]
+#[allow(dead_code)]
+pub struct InstallResult {
+    pub success: bool,
+    pub message: String,
+    pub plugin_name: Option<String>,
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+    pub installation_log: Option<InstallationLog>,
+}
+
+// === Plugin Directory Management ===
+
+fn get_home_dir() -> Result<PathBuf, String> {
+    dirs::home_dir().ok_or_else(|| "Unable to determine home directory".to_string())
+}
+
+#[tauri::command]
+pub fn get_plugins_directory() -> Result<String, LokusError> {
+    let home = get_home_dir()?;
+    let plugins_dir = home.join(".lokus").join("plugins");
+    Ok(plugins_dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn create_plugins_directory() -> Result<String, LokusError> {
+    let home = get_home_dir()?;
+    let lokus_dir = home.join(".lokus");
+    let plugins_dir = lokus_dir.join("plugins");
+    
+    // Create .lokus directory if it doesn't exist
+    if !lokus_dir.exists() {
+        fs::create_dir_all(&lokus_dir).map_err(|e| LokusError::io("Failed to create", &lokus_dir, e))?;
+    }
+    
+    // Create plugins directory if it doesn't exist
+    if !plugins_dir.exists() {
+        fs::create_dir_all(&plugins_dir).map_err(|e| LokusError::io("Failed to create", &plugins_dir, e))?;
+    }
+    
+    Ok(plugins_dir.to_string_lossy().to_string())
+}
+
+// === Plugin Discovery ===
+
+#[tauri::command]
+pub fn list_plugins(app: AppHandle) -> Result<Vec<PluginInfo>, LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    
+    if !plugins_dir.exists() {
+        return Ok(vec![]);
+    }
+    
+    // Load enabled plugins from storage
+    let enabled_plugins = match get_enabled_plugins(app.clone()) {
+        Ok(enabled) => enabled.into_iter().collect::<std::collections::HashSet<_>>(),
+        Err(_e) => {
+            std::collections::HashSet::new()
+        }
+    };
+    
+    let mut plugins = Vec::new();
+    
+    for entry in fs::read_dir(&plugins_dir).map_err(|e| LokusError::io("Failed to read", &plugins_dir, e))? {
+        let entry = entry.map_err(|e| LokusError::io("Failed to read", &plugins_dir, e))?;
+        let plugin_path = entry.path();
+        
+        if plugin_path.is_dir() {
+            if let Ok(mut plugin_info) = load_plugin_info(&plugin_path) {
+                // Set correct enabled state from storage
+                plugin_info.enabled = enabled_plugins.contains(&plugin_info.manifest.name);
+                plugins.push(plugin_info);
+            }
+        }
+    }
+    
+    // Sort plugins by name
+    plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    
+    Ok(plugins)
+}
+
+fn load_plugin_info(plugin_path: &Path) -> Result<PluginInfo, String> {
+    let manifest_path = plugin_path.join("plugin.json");
+
+    if !manifest_path.exists() {
+        return Err("plugin.json not found".to_string());
+    }
+
+    let manifest_content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read plugin.json: {}", e))?;
+
+    let manifest: PluginManifest = serde_json::from_str(&manifest_content)
+        .map_err(|e| format!("Failed to parse plugin.json: {}", e))?;
+
+    // Calculate plugin directory size
+    let size = calculate_directory_size(plugin_path)?;
+
+    // Get installation timestamp from directory metadata
+    let metadata = fs::metadata(plugin_path)
+        .map_err(|e| format!("Failed to read plugin metadata: {}", e))?;
+
+    let installed_at = metadata.created()
+        .or_else(|_| metadata.modified())
+        .map(|time| {
+            let duration = time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+            chrono::DateTime::<chrono::Utc>::from_timestamp(duration.as_secs() as i64, 0)
+                .unwrap_or_default()
+                .to_rfc3339()
+        })
+        .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
+
+    // Read local plugin files for metadata
+    // Icon: check for icon.png, icon.svg, or icon field in manifest
+    let icon_url = find_plugin_icon(plugin_path);
+
+    // README: read README.md if exists
+    let readme = read_plugin_metadata_file(plugin_path, "README.md");
+
+    // Changelog: read CHANGELOG.md if exists
+    let changelog = read_plugin_metadata_file(plugin_path, "CHANGELOG.md");
+
+    let install_log = read_install_log(plugin_path);
+
+    Ok(PluginInfo {
+        manifest,
+        path: plugin_path.to_string_lossy().to_string(),
+        enabled: false, // Will be updated from settings
+        installed_at,
+        size,
+        icon_url,
+        slug: install_log.as_ref().and_then(|log| log.registry_id.clone()),
+        readme,
+        changelog,
+        downloads: None,
+        rating: None,
+        homepage: None,
+        installed_from: install_log.map(|log| log.install_method),
+    })
+}
+
+/// Find plugin icon file and return as file:// URL or data URL
+fn find_plugin_icon(plugin_path: &Path) -> Option<String> {
+    // Check for common icon files
+    let icon_files = ["icon.png", "icon.svg", "icon.jpg", "icon.jpeg", "logo.png", "logo.svg"];
+
+    // Folders to check for icons
+    let icon_folders = ["", "assets", "dist", "images", "img"];
+
+    for folder in &icon_folders {
+        for icon_file in &icon_files {
+            let icon_path = if folder.is_empty() {
+                plugin_path.join(icon_file)
+            } else {
+                plugin_path.join(folder).join(icon_file)
+            };
+
+            if icon_path.exists() {
+                // Return as file:// URL for local access
+                return Some(format!("file://{}", icon_path.to_string_lossy()));
+            }
+        }
+    }
+
+    None
+}
+
+/// Read a text file from plugin directory (internal helper)
+fn read_plugin_metadata_file(plugin_path: &Path, filename: &str) -> Option<String> {
+    let file_path = plugin_path.join(filename);
+    if file_path.exists() {
+        fs::read_to_string(&file_path).ok()
+    } else {
+        None
+    }
+}
+
+fn calculate_directory_size(dir: &Path) -> Result<u64, String> {
+    let mut size = 0;
+
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry.map_err(|e| format!("Failed to walk directory: {}", e))?;
+        if entry.file_type().is_file() {
+            let metadata = entry.metadata().map_err(|e| format!("Failed to read file metadata: {}", e))?;
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
+
+/// Resolve plugin name/id to actual folder name
+/// Checks: 1) exact folder match, 2) manifest.id match, 3) manifest.name match
+pub(crate) fn resolve_plugin_name(plugins_dir: &Path, name_or_id: &str) -> Result<String, String> {
+    // 1. Try exact folder name match
+    let direct_path = plugins_dir.join(name_or_id);
+    if direct_path.exists() && direct_path.is_dir() {
+        return Ok(name_or_id.to_string());
+    }
+
+    // 2. Search through plugin folders for matching manifest.id or manifest.name
+    if let Ok(entries) = fs::read_dir(plugins_dir) {
+        for entry in entries.flatten() {
+            let folder_path = entry.path();
+            if !folder_path.is_dir() { continue; }
+
+            let manifest_path = folder_path.join("plugin.json");
+            if let Ok(content) = fs::read_to_string(&manifest_path) {
+                if let Ok(manifest) = serde_json::from_str::<PluginManifest>(&content) {
+                    // Match by id or name
+                    if manifest.id.as_deref() == Some(name_or_id) || manifest.name == name_or_id {
+                        if let Some(folder_name) = folder_path.file_name() {
+                            return Ok(folder_name.to_string_lossy().to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Err(format!("Plugin '{}' not found", name_or_id))
+}
+
+// === Plugin Information ===
+
+#[tauri::command]
+pub fn get_plugin_info(name: String) -> Result<PluginInfo, LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    let plugin_path = plugins_dir.join(&name);
+    
+    if !plugin_path.exists() {
+        return Err(LokusError::NotFound {
+            path: plugin_path.to_string_lossy().to_string(),
+            message: format!("Plugin '{}' not found", name),
+        });
+    }
+    
+    load_plugin_info(&plugin_path).map_err(|e| LokusError::plugin(&name, e))
+}
+
+// === Plugin Installation ===
+
+#[tauri::command]
+pub async fn install_plugin(path: String) -> Result<String, LokusError> {
+    let plugins_dir = PathBuf::from(create_plugins_directory()?);
+
+    // Check if it's a URL (desktop only - requires reqwest)
+    #[cfg(desktop)]
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return Ok(install_plugin_from_url(&path, &plugins_dir).await?);
+    }
+
+    #[cfg(not(desktop))]
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return Err(LokusError::Unsupported("Installing plugins from URLs is not supported on mobile".to_string()));
+    }
+
+    let source_path = PathBuf::from(&path);
+
+    if !source_path.exists() {
+        return Err(LokusError::not_found(&source_path));
+    }
+
+    // If it's a file, assume it's a zip archive
+    if source_path.is_file() {
+        Ok(install_plugin_from_zip(&source_path, &plugins_dir).await?)
+    } else if source_path.is_dir() {
+        Ok(install_plugin_from_directory(&source_path, &plugins_dir).await?)
+    } else {
+        Err(LokusError::invalid("Invalid plugin source path"))
+    }
+}
+
+#[cfg(desktop)]
+async fn install_plugin_from_url(url: &str, plugins_dir: &std::path::Path) -> Result<String, String> {
+    // Download the file
+    let response = reqwest::get(url)
+        .await
+        .map_err(|e| format!("Failed to download plugin: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to download plugin: HTTP {}", response.status()));
+    }
+
+    let content = response.bytes()
+        .await
+        .map_err(|e| format!("Failed to read download content: {}", e))?;
+
+    // Save to temporary file
+    let temp_dir = tempfile::tempdir()
+        .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+    let temp_path = temp_dir.path().join("plugin.zip");
+
+    fs::write(&temp_path, content)
+        .map_err(|e| format!("Failed to write temporary file: {}", e))?;
+
+    // Install from the downloaded zip
+    install_plugin_from_zip(&temp_path, plugins_dir).await
+}
+
+async fn install_plugin_from_directory(source_dir: &Path, plugins_dir: &Path) -> Result<String, String> {
+    // Validate plugin manifest first
+    let manifest_path = source_dir.join("plugin.json");
+    if !manifest_path.exists() {
+        return Err("plugin.json not found in source directory".to_string());
+    }
+    
+    let manifest_content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read plugin.json: {}", e))?;
+    
+    let validation_result = validate_plugin_manifest(manifest_content.clone())?;
+    if !validation_result.valid {
+        return Err(format!("Plugin manifest validation failed: {:?}", validation_result.errors));
+    }
+    
+    let manifest: PluginManifest = serde_json::from_str(&manifest_content)
+        .map_err(|e| format!("Failed to parse plugin.json: {}", e))?;
+    
+    // Check if plugin already exists
+    let dest_dir = plugins_dir.join(&manifest.name);
+    if dest_dir.exists() {
+        return Err(format!("Plugin '{}' is already installed", manifest.name));
+    }
+    
+    // Copy plugin directory
+    copy_directory(source_dir, &dest_dir)
+        .map_err(|e| format!("Failed to copy plugin: {}", e))?;
+    
+    Ok(manifest.name)
+}
+
+async fn install_plugin_from_zip(zip_path: &Path, plugins_dir: &Path) -> Result<String, String> {
+    let file = fs::File::open(zip_path)
+        .map_err(|e| format!("Failed to open ZIP file: {}", e))?;
+    
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read ZIP archive: {}", e))?;
+    
+    // Find the plugin.json file to determine the plugin structure
+    let mut plugin_manifest_index = None;
+    let mut root_folder = None;
+    
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)
+            .map_err(|e| format!("Failed to read ZIP entry: {}", e))?;
+        
+        if file.name().ends_with("plugin.json") {
+            plugin_manifest_index = Some(i);
+            // Extract the root folder name from the path
+            let path_parts: Vec<&str> = file.name().split('/').collect();
+            if path_parts.len() > 1 {
+                root_folder = Some(path_parts[0].to_string());
+            }
+            break;
+        }
+    }
+    
+    let manifest_index = plugin_manifest_index
+        .ok_or_else(|| "No plugin.json found in ZIP archive".to_string())?;
+    
+    // Read and validate the manifest
+    let mut manifest_file = archive.by_index(manifest_index)
+        .map_err(|e| format!("Failed to read plugin.json: {}", e))?;
+    
+    let mut manifest_content = String::new();
+    manifest_file.read_to_string(&mut manifest_content)
+        .map_err(|e| format!("Failed to read plugin.json content: {}", e))?;
+    
+    let validation_result = validate_plugin_manifest(manifest_content.clone())?;
+    if !validation_result.valid {
+        return Err(format!("Plugin manifest validation failed: {:?}", validation_result.errors));
+    }
+    
+    let manifest: PluginManifest = serde_json::from_str(&manifest_content)
+        .map_err(|e| format!("Failed to parse plugin.json: {}", e))?;
+    
+    // Check if plugin already exists
+    let dest_dir = plugins_dir.join(&manifest.name);
+    if dest_dir.exists() {
+        return Err(format!("Plugin '{}' is already installed", manifest.name));
+    }
+    
+    // Extract the ZIP archive
+    let temp_dir = tempfile::tempdir()
+        .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+    
+    // Reset the archive reader
+    drop(manifest_file);
+    let file = fs::File::open(zip_path)
+        .map_err(|e| format!("Failed to reopen ZIP file: {}", e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to reread ZIP archive: {}", e))?;
+    
+    // Extract all files
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)
+            .map_err(|e| format!("Failed to read ZIP entry: {}", e))?;
+        
+            let outpath = temp_dir.path().join(file.name());
+        
+        // Prevent Zip Slip vulnerability
+        if !outpath.starts_with(temp_dir.path()) {
+            return Err(format!("Invalid file path in ZIP: {}", file.name()));
+        }
+        
+        if file.name().ends_with('/') {
+            fs::create_dir_all(&outpath)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+            }
+            
+            let mut outfile = fs::File::create(&outpath)
+                .map_err(|e| format!("Failed to create file: {}", e))?;
+            std::io::copy(&mut file, &mut outfile)
+                .map_err(|e| format!("Failed to extract file: {}", e))?;
+        }
+    }
+    
+    // Determine the source directory for copying
+    let source_dir = if let Some(folder) = root_folder {
+        temp_dir.path().join(folder)
+    } else {
+        temp_dir.path().to_path_buf()
+    };
+    
+    // Copy to the final destination
+    copy_directory(&source_dir, &dest_dir)
+        .map_err(|e| format!("Failed to copy extracted plugin: {}", e))?;
+    
+    Ok(manifest.name)
+}
+
+fn copy_directory(src: &Path, dest: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dest)?;
+    
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+        
+        if src_path.is_dir() {
+            copy_directory(&src_path, &dest_path)?;
+        } else {
+            fs::copy(&src_path, &dest_path)?;
+        }
+    }
+    
+    Ok(())
+}
+
+// === Plugin Registry ===
+
+const DEFAULT_REGISTRY_URL: &str = "https://lokusmd.com/api/v1/registry";
+const REGISTRY_CACHE_SECS: u64 = 300;
+/// Written next to plugin.json by registry installs
+const INSTALL_LOG_FILE: &str = ".lokus-install.json";
+
+/// Where plugins are looked up: a static `index.json` or the registry API.
+/// With a public key every download must carry a valid signature.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryConfig {
+    pub url: String,
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self { url: DEFAULT_REGISTRY_URL.to_string(), public_key: None }
+    }
+}
+
+#[cfg(desktop)]
+impl RegistryConfig {
+    pub(crate) fn is_static(&self) -> bool {
+        self.url.ends_with(".json")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegistryVersion {
+    pub version: String,
+    #[serde(default, alias = "downloadUrl")]
+    pub download_url: Option<String>,
+    /// Hex SHA-256 of the archive
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Base64 Ed25519 signature of the archive
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Semver requirement on the Lokus version, e.g. `>=1.1`
+    #[serde(default, alias = "lokusVersion")]
+    pub lokus: Option<String>,
+    #[serde(default)]
+    pub changelog: Option<String>,
+    #[serde(default, alias = "publishedAt")]
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegistryPlugin {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default, alias = "iconUrl")]
+    pub icon_url: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub downloads: Option<u64>,
+    #[serde(default)]
+    pub rating: Option<f64>,
+    #[serde(default, alias = "ratingCount")]
+    pub rating_count: Option<u64>,
+    #[serde(default, alias = "latestVersion")]
+    pub latest_version: Option<String>,
+    #[serde(default)]
+    pub versions: Vec<RegistryVersion>,
+    /// Newest version that runs on this Lokus; filled in by the client
+    #[serde(default)]
+    pub compatible_version: Option<String>,
+}
+
+#[cfg(desktop)]
+static REGISTRY_CACHE: once_cell::sync::Lazy<std::sync::Mutex<Option<(std::time::Instant, String, Vec<RegistryPlugin>)>>> =
+    once_cell::sync::Lazy::new(|| std::sync::Mutex::new(None));
+
+#[cfg(desktop)]
+fn lokus_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("package version is semver")
+}
+
+/// Whether a release runs on `lokus`; releases without a requirement run anywhere
+#[cfg(desktop)]
+fn is_compatible(release: &RegistryVersion, lokus: &Version) -> bool {
+    match &release.lokus {
+        Some(requirement) => semver::VersionReq::parse(requirement)
+            .map(|req| req.matches(lokus))
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+/// The newest release of `plugin` matching `requested` (an exact version, a
+/// semver requirement, or `latest`/`None`) that runs on `lokus`
+#[cfg(desktop)]
+pub fn resolve_version<'a>(
+    plugin: &'a RegistryPlugin,
+    requested: Option<&str>,
+    lokus: &Version,
+) -> Result<&'a RegistryVersion, String> {
+    let requirement = match requested.map(str::trim).filter(|r| !r.is_empty() && *r != "latest") {
+        // A bare version means exactly that version, not `^version`
+        Some(exact) if Version::parse(exact).is_ok() => semver::VersionReq::parse(&format!("={}", exact)),
+        Some(range) => semver::VersionReq::parse(range),
+        None => Ok(semver::VersionReq::STAR),
+    }
+    .map_err(|e| format!("Invalid version requirement: {}", e))?;
+
+    let mut candidates: Vec<(Version, &RegistryVersion)> = plugin
+        .versions
+        .iter()
+        .filter(|release| !release.yanked)
+        .filter_map(|release| Version::parse(&release.version).ok().map(|v| (v, release)))
+        .filter(|(version, _)| requirement.matches(version))
+        .collect();
+    if candidates.is_empty() {
+        return Err(format!("No release of '{}' matches {}", plugin.id, requirement));
+    }
+    candidates.retain(|(_, release)| is_compatible(release, lokus));
+    candidates
+        .into_iter()
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, release)| release)
+        .ok_or_else(|| format!("No matching release of '{}' supports Lokus {}", plugin.id, lokus))
+}
+
+/// Check a downloaded archive against the registry's checksum and, when the
+/// registry has a public key, its signature
+#[cfg(desktop)]
+pub fn verify_archive(bytes: &[u8], release: &RegistryVersion, public_key: Option<&str>) -> Result<String, String> {
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    let checksum = hex::encode(Sha256::digest(bytes));
+    let expected = release
+        .sha256
+        .as_deref()
+        .ok_or_else(|| "Registry did not provide a checksum for this release".to_string())?;
+    if !checksum.eq_ignore_ascii_case(expected.trim()) {
+        return Err("Checksum mismatch: the download does not match the registry".to_string());
+    }
+
+    if let Some(public_key) = public_key {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let key_bytes: [u8; 32] = engine
+            .decode(public_key.trim())
+            .ok()
+            .and_then(|k| k.try_into().ok())
+            .ok_or_else(|| "Invalid registry public key".to_string())?;
+        let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| format!("Invalid registry public key: {}", e))?;
+        let signature = release
+            .signature
+            .as_deref()
+            .ok_or_else(|| "Release is not signed".to_string())?;
+        let signature = engine
+            .decode(signature.trim())
+            .ok()
+            .and_then(|s| Signature::from_slice(&s).ok())
+            .ok_or_else(|| "Invalid release signature".to_string())?;
+        key.verify(bytes, &signature)
+            .map_err(|_| "Signature verification failed".to_string())?;
+    }
+    Ok(checksum)
+}
+
+#[cfg(desktop)]
+fn matches_query(plugin: &RegistryPlugin, query: &str) -> bool {
+    let query = query.to_lowercase();
+    plugin.id.to_lowercase().contains(&query)
+        || plugin.name.to_lowercase().contains(&query)
+        || plugin.description.to_lowercase().contains(&query)
+        || plugin.keywords.iter().any(|k| k.to_lowercase().contains(&query))
+}
+
+#[cfg(desktop)]
+fn with_compatibility(mut plugin: RegistryPlugin, lokus: &Version) -> RegistryPlugin {
+    plugin.compatible_version = resolve_version(&plugin, None, lokus).ok().map(|r| r.version.clone());
+    plugin
+}
+
+#[cfg(desktop)]
+pub(crate) fn get_registry_config(app: &AppHandle) -> RegistryConfig {
+    StoreBuilder::new(app, PathBuf::from(".settings.dat"))
+        .build()
+        .ok()
+        .and_then(|store| {
+            let _ = store.reload();
+            store.get("plugin_registry")
+        })
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(desktop)]
+pub(crate) async fn registry_get_json(url: &str) -> Result<serde_json::Value, String> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", "Lokus-Plugin-Client/1.0")
+        .send()
+        .await
+        .map_err(|e| format!("Registry unreachable: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err("Not found in the plugin registry".to_string());
+    }
+    if !response.status().is_success() {
+        return Err(format!("Registry request failed: HTTP {}", response.status()));
+    }
+    response.json().await.map_err(|e| format!("Invalid registry response: {}", e))
+}
+
+/// Plugins in a static index or API search response
+#[cfg(desktop)]
+fn parse_plugin_list(value: serde_json::Value) -> Result<Vec<RegistryPlugin>, String> {
+    let list = match value {
+        serde_json::Value::Array(_) => value,
+        mut object => object
+            .get_mut("plugins")
+            .map(serde_json::Value::take)
+            .unwrap_or_else(|| serde_json::Value::Array(Vec::new())),
+    };
+    serde_json::from_value(list).map_err(|e| format!("Invalid registry index: {}", e))
+}
+
+/// The whole static index, cached for a few minutes
+#[cfg(desktop)]
+async fn static_index(config: &RegistryConfig) -> Result<Vec<RegistryPlugin>, String> {
+    if let Some((fetched, url, plugins)) = REGISTRY_CACHE.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        if *url == config.url && fetched.elapsed().as_secs() < REGISTRY_CACHE_SECS {
+            return Ok(plugins.clone());
+        }
+    }
+    let plugins = parse_plugin_list(registry_get_json(&config.url).await?)?;
+    *REGISTRY_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some((std::time::Instant::now(), config.url.clone(), plugins.clone()));
+    Ok(plugins)
+}
+
+#[cfg(desktop)]
+async fn fetch_registry_plugin(config: &RegistryConfig, id: &str) -> Result<RegistryPlugin, String> {
+    if config.is_static() {
+        return static_index(config)
+            .await?
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Plugin '{}' is not in the registry", id));
+    }
+    let url = format!("{}/plugin/{}", config.url.trim_end_matches('/'), urlencoding::encode(id));
+    serde_json::from_value(registry_get_json(&url).await?).map_err(|e| format!("Invalid registry response: {}", e))
+}
+
+/// Download a release; the API may answer with a redirect or a JSON `{ url }`
+#[cfg(desktop)]
+async fn download_release(config: &RegistryConfig, plugin_id: &str, release: &RegistryVersion) -> Result<Vec<u8>, String> {
+    let url = match &release.download_url {
+        Some(url) => url.clone(),
+        None if !config.is_static() => format!(
+            "{}/download/{}/{}",
+            config.url.trim_end_matches('/'),
+            urlencoding::encode(plugin_id),
+            urlencoding::encode(&release.version)
+        ),
+        None => return Err("Release has no download URL".to_string()),
+    };
+
+    let client = reqwest::Client::new();
+    let mut response = client.get(&url).send().await.map_err(|e| format!("Failed to download plugin: {}", e))?;
+    let is_json = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("application/json"));
+    if is_json {
+        let body: serde_json::Value = response.json().await.map_err(|e| format!("Invalid download response: {}", e))?;
+        let signed_url = body["url"].as_str().ok_or_else(|| "Invalid download response: missing URL".to_string())?;
+        response = client.get(signed_url).send().await.map_err(|e| format!("Failed to download plugin: {}", e))?;
+    }
+    if !response.status().is_success() {
+        return Err(format!("Failed to download plugin: HTTP {}", response.status()));
+    }
+    response
+        .bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| format!("Failed to read download content: {}", e))
+}
+
+pub(crate) fn read_install_log(plugin_path: &Path) -> Option<InstallationLog> {
+    let content = fs::read_to_string(plugin_path.join(INSTALL_LOG_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Search the registry; each result says which version would install here
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn registry_search(app: AppHandle, query: String) -> Result<Vec<RegistryPlugin>, LokusError> {
+    let config = get_registry_config(&app);
+    let lokus = lokus_version();
+    let query = query.trim();
+
+    let plugins = if config.is_static() {
+        let mut found: Vec<RegistryPlugin> = static_index(&config)
+            .await?
+            .into_iter()
+            .filter(|p| query.is_empty() || matches_query(p, query))
+            .collect();
+        found.sort_by(|a, b| b.downloads.unwrap_or(0).cmp(&a.downloads.unwrap_or(0)));
+        found
+    } else {
+        let url = format!("{}/search?q={}", config.url.trim_end_matches('/'), urlencoding::encode(query));
+        parse_plugin_list(registry_get_json(&url).await?)?
+    };
+    Ok(plugins.into_iter().map(|p| with_compatibility(p, &lokus)).collect())
+}
+
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn registry_get_plugin(app: AppHandle, id: String) -> Result<RegistryPlugin, LokusError> {
+    let config = get_registry_config(&app);
+    Ok(with_compatibility(fetch_registry_plugin(&config, &id).await?, &lokus_version()))
+}
+
+/// Download, verify and install one release, recording where it came from
+#[cfg(desktop)]
+async fn install_release(config: &RegistryConfig, plugin: &RegistryPlugin, release: &RegistryVersion) -> Result<String, String> {
+    let bytes = download_release(config, &plugin.id, release).await?;
+    let checksum = verify_archive(&bytes, release, config.public_key.as_deref())?;
+
+    let temp_dir = tempfile::tempdir().map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+    let temp_path = temp_dir.path().join("plugin.zip");
+    fs::write(&temp_path, &bytes).map_err(|e| format!("Failed to write temporary file: {}", e))?;
+
+    let plugins_dir = PathBuf::from(create_plugins_directory()?);
+    let name = install_plugin_from_zip(&temp_path, &plugins_dir).await?;
+
+    let log = InstallationLog {
+        plugin_name: name.clone(),
+        version: release.version.clone(),
+        installed_at: chrono::Utc::now().to_rfc3339(),
+        install_method: "registry".to_string(),
+        source_url: Some(config.url.clone()),
+        checksum: Some(checksum),
+        registry_id: Some(plugin.id.clone()),
+    };
+    let log_json = serde_json::to_string_pretty(&log).map_err(|e| e.to_string())?;
+    if let Err(e) = fs::write(plugins_dir.join(&name).join(INSTALL_LOG_FILE), log_json) {
+        tracing::warn!("Failed to record installation of {}: {}", name, e);
+    }
+    Ok(name)
+}
+
+/// Install a plugin from the registry. `version` is an exact version, a
+/// semver requirement, or empty for the newest compatible release.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn registry_install(app: AppHandle, id: String, version: Option<String>) -> Result<String, LokusError> {
+    let config = get_registry_config(&app);
+    let plugin = fetch_registry_plugin(&config, &id).await?;
+    let release = resolve_version(&plugin, version.as_deref(), &lokus_version()).map_err(|e| LokusError::plugin(&id, e))?;
+    install_release(&config, &plugin, release).await.map_err(|e| LokusError::plugin(&id, e))
+}
+
+// === Plugin Updates ===
+
+#[cfg(desktop)]
+const UPDATE_CACHE_FILE: &str = "plugin-updates.json";
+#[cfg(desktop)]
+const AUTO_UPDATE_SETTING: &str = "plugin_auto_update";
+#[cfg(desktop)]
+const UPDATE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PluginUpdate {
+    /// Folder the plugin is installed in
+    pub plugin_name: String,
+    pub current_version: String,
+    pub latest_version: String,
+    /// Release notes of every version since the installed one, newest first
+    pub changelog: Option<String>,
+    /// `registry` or `github`
+    pub source: String,
+    pub download_url: Option<String>,
+    pub published_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PluginUpdateCache {
+    pub checked_at: Option<String>,
+    pub updates: Vec<PluginUpdate>,
+}
+
+#[cfg(desktop)]
+fn update_cache_path() -> Result<PathBuf, String> {
+    Ok(get_home_dir()?.join(".lokus").join(UPDATE_CACHE_FILE))
+}
+
+#[cfg(desktop)]
+fn load_update_cache() -> PluginUpdateCache {
+    update_cache_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(desktop)]
+fn save_update_cache(cache: &PluginUpdateCache) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(cache).map_err(|e| e.to_string())?;
+    fs::write(update_cache_path()?, json).map_err(|e| format!("Failed to save plugin update cache: {}", e))
+}
+
+#[cfg(desktop)]
+/// `(owner, repo)` of a GitHub `repository` manifest entry, a URL string or
+/// an npm-style `{ "url": ... }` object
+fn github_repo(repository: &serde_json::Value) -> Option<(String, String)> {
+    let url = repository.as_str().or_else(|| repository["url"].as_str())?;
+    let (_, path) = url.split_once("github.com")?;
+    let mut parts = path.trim_start_matches([':', '/']).split('/');
+    let owner = parts.next().filter(|o| !o.is_empty())?;
+    let repo = parts.next()?.trim_end_matches(".git");
+    (!repo.is_empty()).then(|| (owner.to_string(), repo.to_string()))
+}
+
+/// Release notes of the versions after `current` up to `latest`, newest first
+#[cfg(desktop)]
+fn changelog_between(plugin: &RegistryPlugin, current: &Version, latest: &Version) -> Option<String> {
+    let mut releases: Vec<(Version, &str)> = plugin
+        .versions
+        .iter()
+        .filter(|release| !release.yanked)
+        .filter_map(|release| {
+            let version = Version::parse(&release.version).ok()?;
+            let notes = release.changelog.as_deref()?.trim();
+            (version > *current && version <= *latest && !notes.is_empty()).then_some((version, notes))
+        })
+        .collect();
+    releases.sort_by(|a, b| b.0.cmp(&a.0));
+    let sections: Vec<String> = releases
+        .into_iter()
+        .map(|(version, notes)| format!("## {}\n\n{}", version, notes))
+        .collect();
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
+#[cfg(desktop)]
+async fn check_registry_update(
+    config: &RegistryConfig,
+    folder: &str,
+    registry_id: &str,
+    current: &Version,
+) -> Result<Option<PluginUpdate>, String> {
+    let plugin = fetch_registry_plugin(config, registry_id).await?;
+    let release = resolve_version(&plugin, None, &lokus_version())?;
+    let latest = Version::parse(&release.version).map_err(|e| e.to_string())?;
+    if latest <= *current {
+        return Ok(None);
+    }
+    Ok(Some(PluginUpdate {
+        plugin_name: folder.to_string(),
+        current_version: current.to_string(),
+        latest_version: latest.to_string(),
+        changelog: changelog_between(&plugin, current, &latest),
+        source: "registry".to_string(),
+        download_url: None,
+        published_at: release.published_at.clone(),
+    }))
+}
+
+#[cfg(desktop)]
+async fn check_github_update(folder: &str, owner: &str, repo: &str, current: &Version) -> Result<Option<PluginUpdate>, String> {
+    let url = format!("https://api.github.com/repos/{}/{}/releases/latest", owner, repo);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("User-Agent", "Lokus-Plugin-Client/1.0")
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("GitHub unreachable: {}", e))?;
+    // No releases published
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("GitHub request failed: HTTP {}", response.status()));
+    }
+    let release: serde_json::Value = response.json().await.map_err(|e| format!("Invalid GitHub response: {}", e))?;
+
+    let tag = release["tag_name"].as_str().unwrap_or_default();
+    let Ok(latest) = Version::parse(tag.trim_start_matches('v')) else {
+        return Ok(None);
+    };
+    if latest <= *current {
+        return Ok(None);
+    }
+    // Prefer a packaged zip over the source archive
+    let download_url = release["assets"]
+        .as_array()
+        .and_then(|assets| assets.iter().find(|a| a["name"].as_str().is_some_and(|n| n.ends_with(".zip"))))
+        .and_then(|asset| asset["browser_download_url"].as_str())
+        .or_else(|| release["zipball_url"].as_str())
+        .map(|url| url.to_string());
+
+    Ok(Some(PluginUpdate {
+        plugin_name: folder.to_string(),
+        current_version: current.to_string(),
+        latest_version: latest.to_string(),
+        changelog: release["body"].as_str().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+        source: "github".to_string(),
+        download_url,
+        published_at: release["published_at"].as_str().map(|s| s.to_string()),
+    }))
+}
+
+/// Look for newer versions of every installed plugin and cache the result
+#[cfg(desktop)]
+async fn check_updates(app: &AppHandle) -> Result<Vec<PluginUpdate>, String> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    let config = get_registry_config(app);
+    let mut updates = Vec::new();
+
+    if plugins_dir.exists() {
+        for entry in fs::read_dir(&plugins_dir).map_err(|e| format!("Failed to read plugins directory: {}", e))?.flatten() {
+            let path = entry.path();
+            let Ok(info) = load_plugin_info(&path) else {
+                continue;
+            };
+            let folder = entry.file_name().to_string_lossy().to_string();
+            let Ok(current) = Version::parse(&info.manifest.version) else {
+                continue;
+            };
+
+            let registry_id = read_install_log(&path).and_then(|log| log.registry_id);
+            let checked = match (registry_id, info.manifest.repository.as_ref().and_then(github_repo)) {
+                (Some(id), _) => check_registry_update(&config, &folder, &id, &current).await,
+                (None, Some((owner, repo))) => check_github_update(&folder, &owner, &repo, &current).await,
+                (None, None) => Ok(None),
+            };
+            match checked {
+                Ok(Some(update)) => updates.push(update),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to check {} for updates: {}", folder, e),
+            }
+        }
+    }
+
+    save_update_cache(&PluginUpdateCache {
+        checked_at: Some(chrono::Utc::now().to_rfc3339()),
+        updates: updates.clone(),
+    })?;
+    Ok(updates)
+}
+
+#[cfg(desktop)]
+fn plugin_backup_dir(folder: &str) -> Result<PathBuf, String> {
+    Ok(get_home_dir()?.join(".lokus").join("plugin-backups").join(folder))
+}
+
+#[cfg(desktop)]
+/// Move a directory, copying when a rename isn't possible
+fn move_directory(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_directory(from, to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+    fs::remove_dir_all(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+#[cfg(desktop)]
+async fn install_update(app: &AppHandle, update: &PluginUpdate) -> Result<String, String> {
+    let plugins_dir = PathBuf::from(create_plugins_directory()?);
+    match update.source.as_str() {
+        "registry" => {
+            let registry_id = read_install_log(&plugin_backup_dir(&update.plugin_name)?)
+                .and_then(|log| log.registry_id)
+                .ok_or_else(|| "Plugin was not installed from the registry".to_string())?;
+            let config = get_registry_config(app);
+            let plugin = fetch_registry_plugin(&config, &registry_id).await?;
+            let release = resolve_version(&plugin, Some(&update.latest_version), &lokus_version())?;
+            install_release(&config, &plugin, release).await
+        }
+        _ => {
+            let url = update.download_url.as_deref().ok_or_else(|| "Release has no download".to_string())?;
+            let name = install_plugin_from_url(url, &plugins_dir).await?;
+            let log = InstallationLog {
+                plugin_name: name.clone(),
+                version: update.latest_version.clone(),
+                installed_at: chrono::Utc::now().to_rfc3339(),
+                install_method: "github".to_string(),
+                source_url: Some(url.to_string()),
+                checksum: None,
+                registry_id: None,
+            };
+            let log_json = serde_json::to_string_pretty(&log).map_err(|e| e.to_string())?;
+            let _ = fs::write(plugins_dir.join(&name).join(INSTALL_LOG_FILE), log_json);
+            Ok(name)
+        }
+    }
+}
+
+/// Replace a plugin with its update. The installed version is kept under
+/// `~/.lokus/plugin-backups/` and put back if anything goes wrong.
+#[cfg(desktop)]
+async fn apply_update(app: &AppHandle, update: &PluginUpdate) -> Result<(), String> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    let installed = plugins_dir.join(&update.plugin_name);
+    if !installed.exists() {
+        return Err(format!("Plugin '{}' is not installed", update.plugin_name));
+    }
+
+    let backup = plugin_backup_dir(&update.plugin_name)?;
+    if backup.exists() {
+        fs::remove_dir_all(&backup).map_err(|e| format!("Failed to clear old backup: {}", e))?;
+    }
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    }
+    move_directory(&installed, &backup)?;
+
+    let result = async {
+        let name = install_update(app, update).await?;
+        // Keep the folder name so enabled state and permissions still apply
+        if name != update.plugin_name {
+            move_directory(&plugins_dir.join(&name), &installed)?;
+        }
+        let info = load_plugin_info(&installed)?;
+        if info.manifest.version != update.latest_version {
+            return Err(format!("Expected version {}, got {}", update.latest_version, info.manifest.version));
+        }
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        if installed.exists() {
+            let _ = fs::remove_dir_all(&installed);
+        }
+        move_directory(&backup, &installed)
+            .map_err(|restore| format!("Update failed ({}) and restoring the backup failed: {}", e, restore))?;
+        return Err(format!("Update failed, previous version restored: {}", e));
+    }
+    Ok(())
+}
+
+#[cfg(desktop)]
+fn auto_update_enabled(app: &AppHandle) -> bool {
+    StoreBuilder::new(app, PathBuf::from(".settings.dat"))
+        .build()
+        .ok()
+        .and_then(|store| {
+            let _ = store.reload();
+            store.get(AUTO_UPDATE_SETTING)
+        })
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// Check for plugin updates periodically, installing them when auto-update is on
+#[cfg(desktop)]
+pub fn start_update_checker(app: AppHandle) {
+    use tauri::Emitter;
+
+    tauri::async_runtime::spawn(async move {
+        // Leave startup alone
+        tokio::time::sleep(std::time::Duration::from_secs(5 * 60)).await;
+        loop {
+            match check_updates(&app).await {
+                Ok(updates) if !updates.is_empty() => {
+                    if auto_update_enabled(&app) {
+                        let mut updated = false;
+                        for update in &updates {
+                            match apply_update(&app, update).await {
+                                Ok(()) => updated = true,
+                                Err(e) => tracing::warn!("Auto-update of {} failed: {}", update.plugin_name, e),
+                            }
+                        }
+                        if updated {
+                            let _ = check_updates(&app).await;
+                            let _ = app.emit("plugins:updated", ());
+                        }
+                    } else {
+                        let _ = app.emit("plugins:updates-available", &updates);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Plugin update check failed: {}", e),
+            }
+            tokio::time::sleep(UPDATE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Check every installed plugin for a newer version now
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn plugins_check_updates(app: AppHandle) -> Result<Vec<PluginUpdate>, LokusError> {
+    Ok(check_updates(&app).await?)
+}
+
+/// Updates found by the last check, checking first if there never was one
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn plugins_get_available_updates(app: AppHandle) -> Result<PluginUpdateCache, LokusError> {
+    let cache = load_update_cache();
+    if cache.checked_at.is_some() {
+        return Ok(cache);
+    }
+    let updates = check_updates(&app).await?;
+    Ok(PluginUpdateCache { checked_at: Some(chrono::Utc::now().to_rfc3339()), updates })
+}
+
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn plugins_update(app: AppHandle, name: String) -> Result<(), LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    let folder = resolve_plugin_name(&plugins_dir, &name)?;
+    let mut cache = load_update_cache();
+    let update = cache
+        .updates
+        .iter()
+        .find(|u| u.plugin_name == folder)
+        .cloned()
+        .ok_or_else(|| LokusError::plugin(&name, format!("No update available for '{}'", name)))?;
+
+    apply_update(&app, &update).await.map_err(|e| LokusError::plugin(&folder, e))?;
+    cache.updates.retain(|u| u.plugin_name != folder);
+    Ok(save_update_cache(&cache)?)
+}
+
+#[cfg(desktop)]
+#[tauri::command]
+pub fn plugins_set_auto_update(app: AppHandle, enabled: bool) -> Result<(), LokusError> {
+    let store = StoreBuilder::new(&app, PathBuf::from(".settings.dat"))
+        .build()
+        .map_err(|e| format!("Failed to create store: {}", e))?;
+    let _ = store.reload();
+    store.set(AUTO_UPDATE_SETTING.to_string(), JsonValue::Bool(enabled));
+    store.save().map_err(|e| LokusError::Io(format!("Failed to save settings: {}", e)))
+}
+
+// === Plugin Uninstallation ===
+
+#[tauri::command]
+pub fn uninstall_plugin(app: AppHandle, name: String) -> Result<(), LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+
+    // Resolve name/id to actual folder name
+    let resolved_name = resolve_plugin_name(&plugins_dir, &name)?;
+    let plugin_path = plugins_dir.join(&resolved_name);
+
+    // Remove plugin from enabled list and clean up settings
+    cleanup_plugin_settings(&app, &resolved_name)?;
+    crate::plugin_broker::revoke(&resolved_name);
+
+    // Remove plugin directory
+    fs::remove_dir_all(&plugin_path)
+        .map_err(|e| LokusError::io("Failed to remove", &plugin_path, e))?;
+
+    Ok(())
+}
+
+fn cleanup_plugin_settings(app: &AppHandle, plugin_name: &str) -> Result<(), String> {
+    let store = StoreBuilder::new(app, PathBuf::from(".settings.dat")).build()
+        .map_err(|e| format!("Failed to create store: {}", e))?;
+    
+    let _ = store.reload();
+    
+    // Get current plugin settings
+    let mut plugin_settings = if let Some(settings_value) = store.get("plugin_settings") {
+        serde_json::from_value::<PluginSettings>(settings_value.clone())
+            .unwrap_or_default()
+    } else {
+        PluginSettings {
+            enabled_plugins: vec![],
+            plugin_permissions: HashMap::new(),
+            plugin_settings: HashMap::new(),
+        }
+    };
+    
+    // Remove plugin from enabled list
+    plugin_settings.enabled_plugins.retain(|p| p != plugin_name);
+    
+    // Remove plugin permissions
+    plugin_settings.plugin_permissions.remove(plugin_name);
+    
+    // Remove plugin-specific settings
+    plugin_settings.plugin_settings.remove(plugin_name);
+    
+    // Save updated settings
+    let _ = store.set("plugin_settings".to_string(), serde_json::to_value(plugin_settings).unwrap());
+    let _ = store.save();
+    
+    Ok(())
+}
+
+// === Plugin Manifest Validation ===
+
+#[tauri::command]
+pub fn validate_plugin_manifest(manifest: String) -> Result<ValidationResult, LokusError> {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    
+    // Try to parse the JSON
+    let parsed_manifest: Result<PluginManifest, _> = serde_json::from_str(&manifest);
+    
+    match parsed_manifest {
+        Ok(manifest) => {
+            // Validate required fields
+            if manifest.name.trim().is_empty() {
+                errors.push(ValidationError {
+                    field: "name".to_string(),
+                    message: "Plugin name cannot be empty".to_string(),
+                });
+            } else if !is_valid_plugin_name(&manifest.name) {
+                errors.push(ValidationError {
+                    field: "name".to_string(),
+                    message: "Plugin name contains invalid characters".to_string(),
+                });
+            }
+            
+            if manifest.version.trim().is_empty() {
+                errors.push(ValidationError {
+                    field: "version".to_string(),
+                    message: "Plugin version cannot be empty".to_string(),
+                });
+            } else {
+                match validate_version_format(&manifest.version) {
+                    Ok(_version) => {
+                        // Version is valid
+                    }
+                    Err(e) => {
+                        errors.push(ValidationError {
+                            field: "version".to_string(),
+                            message: e,
+                        });
+                    }
+                }
+            }
+            
+            if manifest.description.trim().is_empty() {
+                warnings.push("Plugin description is empty".to_string());
+            }
+            
+            if manifest.author.trim().is_empty() {
+                warnings.push("Plugin author is empty".to_string());
+            }
+            
+            if manifest.main.trim().is_empty() {
+                errors.push(ValidationError {
+                    field: "main".to_string(),
+                    message: "Main entry point cannot be empty".to_string(),
+                });
+            }
+            
+            // Validate permissions
+            for permission in &manifest.permissions {
+                if !is_valid_permission(permission) {
+                    errors.push(ValidationError {
+                        field: "permissions".to_string(),
+                        message: format!("Invalid permission: {}", permission),
+                    });
+                }
+            }
+        }
+        Err(e) => {
+            errors.push(ValidationError {
+                field: "json".to_string(),
+                message: format!("Invalid JSON: {}", e),
+            });
+        }
+    }
+    
+    Ok(ValidationResult {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    })
+}
+
+fn is_valid_plugin_name(name: &str) -> bool {
+    // Plugin name should be alphanumeric with hyphens, underscores, and spaces
+    name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ') && !name.trim().is_empty()
+}
+
+// Enhanced validation functions
+fn validate_version_format(version: &str) -> Result<Version, String> {
+    Version::parse(version)
+        .map_err(|e| format!("Invalid semantic version format: {}", e))
+}
+
+fn is_valid_permission(permission: &str) -> bool {
+    // Define valid permission patterns for the plugin system
+    // Format: category:action or category:subcategory:action
+    const VALID_PREFIXES: &[&str] = &[
+        "read:", "write:", "execute:", "network:", "ui:", "storage:", "clipboard:",
+        "filesystem:", "files:", "workspace:", "editor:", "commands:", "events:",
+        "notifications:", "settings:", "themes:", "sidebar:", "toolbar:", "statusbar:",
+    ];
+
+    // Check if permission matches any valid prefix pattern
+    VALID_PREFIXES.iter().any(|prefix| permission.starts_with(prefix))
+}
+
+// === Plugin Storage Operations ===
+
+impl Default for PluginSettings {
+    fn default() -> Self {
+        Self {
+            enabled_plugins: Vec::new(),
+            plugin_permissions: HashMap::new(),
+            plugin_settings: HashMap::new(),
+        }
+    }
+}
+
+pub(crate) fn get_plugin_settings(app: &AppHandle) -> Result<PluginSettings, String> {
+
+    let store = StoreBuilder::new(app, PathBuf::from(".settings.dat")).build()
+        .map_err(|e| {
+            let error = format!("Failed to create store: {}", e);
+            error
+        })?;
+
+    store.reload()
+        .map_err(|e| {
+            let error = format!("Failed to reload store: {}", e);
+            error
+        })?;
+
+    if let Some(settings_value) = store.get("plugin_settings") {
+
+        let settings: PluginSettings = serde_json::from_value(settings_value.clone())
+            .map_err(|e| {
+                let error = format!("Failed to parse plugin settings: {}", e);
+                error
+            })?;
+
+        Ok(settings)
+    } else {
+        Ok(PluginSettings::default())
+    }
+}
+
+pub(crate) fn save_plugin_settings_internal(app: &AppHandle, settings: &PluginSettings) -> Result<(), String> {
+    
+    let store = StoreBuilder::new(app, PathBuf::from(".settings.dat")).build()
+        .map_err(|e| {
+            let error = format!("Failed to create store: {}", e);
+            error
+        })?;
+    
+    // Reload store before saving
+    store.reload()
+        .map_err(|e| {
+            let error = format!("Failed to reload store: {}", e);
+            error
+        })?;
+    
+    // Serialize settings with detailed logging
+    let settings_value = serde_json::to_value(settings)
+        .map_err(|e| {
+            let error = format!("Failed to serialize plugin settings: {}", e);
+            error
+        })?;
+    
+    
+    // Set the value in store
+    store.set("plugin_settings".to_string(), settings_value.clone());
+    
+    // Verify the value was set correctly
+    if let Some(stored_value) = store.get("plugin_settings") {
+        if stored_value != settings_value {
+        }
+    } else {
+        return Err("Failed to set value in store".to_string());
+    }
+    
+    // Save store with error handling
+    store.save()
+        .map_err(|e| {
+            let error = format!("Failed to save plugin settings to disk: {}", e);
+            error
+        })?;
+    
+    // Remove unnecessary delay for better performance
+    
+    // Force reload the store to verify the save was successful
+    store.reload()
+        .map_err(|e| {
+            let error = format!("Failed to reload store after save: {}", e);
+            error
+        })?;
+    
+    // Verify the save was successful
+    if let Some(stored_value) = store.get("plugin_settings") {
+        if let Ok(stored_settings) = serde_json::from_value::<PluginSettings>(stored_value) {
+            if stored_settings.enabled_plugins != settings.enabled_plugins {
+            }
+        } else {
+        }
+    } else {
+        return Err("Save operation failed - no data found after save".to_string());
+    }
+    
+
+    Ok(())
+}
+
+// === Atomic Plugin State Operations ===
+
+/// Atomically update a plugin's enabled state with proper error handling and verification
+fn update_plugin_enabled_state(app: &AppHandle, plugin_name: &str, enabled: bool) -> Result<(), String> {
+    
+    // Load current settings with retry logic
+    let mut settings = get_plugin_settings(app)?;
+    
+    // Verify the plugin exists before enabling
+    if enabled {
+        let plugins_dir = PathBuf::from(get_plugins_directory()?);
+        let plugin_path = plugins_dir.join(plugin_name);
+        if !plugin_path.exists() {
+            return Err(format!("Cannot enable plugin '{}': plugin directory not found", plugin_name));
+        }
+    }
+    
+    // Update enabled state atomically
+    let was_enabled = settings.enabled_plugins.contains(&plugin_name.to_string());
+    
+    if enabled && !was_enabled {
+        // Add to enabled plugins
+        settings.enabled_plugins.push(plugin_name.to_string());
+    } else if !enabled && was_enabled {
+        // Remove from enabled plugins
+        settings.enabled_plugins.retain(|p| p != plugin_name);
+    } else {
+        // No change needed
+        return Ok(());
+    }
+    
+    // Validate the settings before saving
+    if let Err(validation_error) = validate_plugin_settings(&settings) {
+        return Err(format!("Settings validation failed: {}", validation_error));
+    }
+    
+    // Save settings atomically
+    save_plugin_settings_internal(app, &settings)?;
+    
+    Ok(())
+}
+
+/// Validate plugin settings before saving
+fn validate_plugin_settings(settings: &PluginSettings) -> Result<(), String> {
+    // Check for duplicates in enabled plugins
+    let mut seen = std::collections::HashSet::new();
+    for plugin_name in &settings.enabled_plugins {
+        if !seen.insert(plugin_name.clone()) {
+            return Err(format!("Duplicate plugin in enabled list: {}", plugin_name));
+        }
+    }
+    
+    // Validate plugin names (basic validation)
+    for plugin_name in &settings.enabled_plugins {
+        if plugin_name.trim().is_empty() {
+            return Err("Empty plugin name found in enabled list".to_string());
+        }
+        if plugin_name.contains("..") || plugin_name.contains("/") || plugin_name.contains("\\") {
+            return Err(format!("Invalid plugin name: {}", plugin_name));
+        }
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+pub fn enable_plugin(app: AppHandle, name: String) -> Result<(), LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+
+    // Resolve name/id to actual folder name
+    let resolved_name = resolve_plugin_name(&plugins_dir, &name)?;
+
+    // Use atomic operation for enabling plugin
+    update_plugin_enabled_state(&app, &resolved_name, true).map_err(|e| LokusError::plugin(&resolved_name, e))
+}
+
+#[tauri::command]
+pub fn disable_plugin(app: AppHandle, name: String) -> Result<(), LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+
+    // Resolve name/id to actual folder name
+    let resolved_name = resolve_plugin_name(&plugins_dir, &name)?;
+
+    crate::plugin_broker::revoke(&resolved_name);
+
+    // Use atomic operation for disabling plugin
+    update_plugin_enabled_state(&app, &resolved_name, false).map_err(|e| LokusError::plugin(&resolved_name, e))
+}
+
+#[tauri::command]
+pub fn get_enabled_plugins(app: AppHandle) -> Result<Vec<String>, LokusError> {
+    let settings = get_plugin_settings(&app)?;
+    Ok(settings.enabled_plugins)
+}
+
+#[tauri::command]
+pub fn set_plugin_permission(app: AppHandle, plugin_name: String, permissions: Vec<String>) -> Result<(), LokusError> {
+    let mut settings = get_plugin_settings(&app)?;
+    settings.plugin_permissions.insert(plugin_name, permissions);
+    save_plugin_settings_internal(&app, &settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_plugin_permissions(app: AppHandle, plugin_name: String) -> Result<Vec<String>, LokusError> {
+    let settings = get_plugin_settings(&app)?;
+    Ok(settings.plugin_permissions.get(&plugin_name).cloned().unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn set_plugin_setting(app: AppHandle, plugin_name: String, key: String, value: JsonValue) -> Result<(), LokusError> {
+    let mut settings = get_plugin_settings(&app)?;
+    
+    let plugin_settings = settings.plugin_settings
+        .entry(plugin_name)
+        .or_insert_with(|| JsonValue::Object(serde_json::Map::new()));
+    
+    if let JsonValue::Object(map) = plugin_settings {
+        map.insert(key, value);
+    }
+    
+    save_plugin_settings_internal(&app, &settings)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_plugin_setting(app: AppHandle, plugin_name: String, key: String) -> Result<Option<JsonValue>, LokusError> {
+    let settings = get_plugin_settings(&app)?;
+    
+    if let Some(plugin_settings) = settings.plugin_settings.get(&plugin_name) {
+        if let JsonValue::Object(map) = plugin_settings {
+            Ok(map.get(&key).cloned())
+        } else {
+            Ok(None)
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+#[allow(dead_code)]
+#[tauri::command]
+pub fn save_plugin_settings(app: AppHandle, plugin_id: String, settings: JsonValue) -> Result<(), LokusError> {
+    let mut current_settings = get_plugin_settings(&app)?;
+    
+    current_settings.plugin_settings.insert(plugin_id, settings);
+    
+    save_plugin_settings_internal(&app, &current_settings)?;
+    Ok(())
+}
+
+// === Plugin File Operations ===
+
+#[tauri::command]
+pub fn read_plugin_file(path: String) -> Result<String, LokusError> {
+    let file_path = PathBuf::from(&path);
+    
+    // Security check: ensure the path is within the plugins directory
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    if !file_path.starts_with(&plugins_dir) {
+        return Err(LokusError::PermissionDenied {
+            path,
+            message: "Access denied: path must be within plugins directory".to_string(),
+        });
+    }
+    
+    // Check if file exists
+    if !file_path.exists() {
+        return Err(LokusError::not_found(&file_path));
+    }
+    
+    // Read file content
+    fs::read_to_string(&file_path)
+        .map_err(|e| LokusError::io("Failed to read", &file_path, e))
+}
+
+#[tauri::command]
+pub fn get_plugin_manifest(plugin_name: String) -> Result<PluginManifest, LokusError> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    let plugin_path = plugins_dir.join(&plugin_name);
+    let manifest_path = plugin_path.join("plugin.json");
+    
+    if !manifest_path.exists() {
+        return Err(LokusError::plugin(&plugin_name, format!("Manifest not found for plugin: {}", plugin_name)));
+    }
+    
+    let manifest_content = fs::read_to_string(&manifest_path)
+        .map_err(|e| LokusError::io("Failed to read", &manifest_path, e))?;
+    
+    let manifest: PluginManifest = serde_json::from_str(&manifest_content)
+        .map_err(|e| LokusError::plugin(&plugin_name, format!("Failed to parse manifest: {}", e)))?;
+    
+    Ok(manifest)
+}
+#[cfg(all(test, desktop))]
+mod tests {
+    use super::*;
+
+    fn release(version: &str, lokus: Option<&str>) -> RegistryVersion {
+        RegistryVersion {
+            version: version.to_string(),
+            download_url: None,
+            sha256: None,
+            signature: None,
+            lokus: lokus.map(|l| l.to_string()),
+            changelog: None,
+            published_at: None,
+            yanked: false,
+        }
+    }
+
+    fn plugin(versions: Vec<RegistryVersion>) -> RegistryPlugin {
+        RegistryPlugin {
+            id: "word-count".to_string(),
+            name: "Word Count".to_string(),
+            description: String::new(),
+            author: None,
+            keywords: Vec::new(),
+            icon_url: None,
+            homepage: None,
+            repository: None,
+            downloads: None,
+            rating: None,
+            rating_count: None,
+            latest_version: versions.last().map(|v| v.version.clone()),
+            versions,
+            compatible_version: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_version_skips_incompatible_releases() {
+        let mut yanked = release("1.3.0", None);
+        yanked.yanked = true;
+        let plugin = plugin(vec![
+            release("1.1.0", Some(">=1.0")),
+            release("1.2.0", Some(">=1.0")),
+            yanked,
+            release("2.0.0", Some(">=2.0")),
+            release("2.1.0-beta.1", None),
+        ]);
+        let lokus = Version::parse("1.1.0").unwrap();
+
+        assert_eq!(resolve_version(&plugin, None, &lokus).unwrap().version, "1.2.0");
+        assert_eq!(resolve_version(&plugin, Some("1.1.0"), &lokus).unwrap().version, "1.1.0");
+        assert_eq!(resolve_version(&plugin, Some("~1.1"), &lokus).unwrap().version, "1.1.0");
+        assert!(resolve_version(&plugin, Some("2.0.0"), &lokus).unwrap_err().contains("supports Lokus"));
+        assert!(resolve_version(&plugin, Some("1.3.0"), &lokus).is_err());
+    }
+
+    #[test]
+    fn test_verify_archive_checksum() {
+        let mut signed = release("1.0.0", None);
+        signed.sha256 = Some("2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824".to_string());
+
+        assert!(verify_archive(b"hello", &signed, None).is_ok());
+        assert!(verify_archive(b"tampered", &signed, None).is_err());
+        assert!(verify_archive(b"hello", &release("1.0.0", None), None).is_err());
+        // A registry with a key refuses unsigned releases
+        let key = "11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=";
+        assert_eq!(verify_archive(b"hello", &signed, Some(key)).unwrap_err(), "Release is not signed");
+    }
+
+    #[test]
+    fn test_update_changelog_and_github_repo() {
+        let notes = |version: &str, text: &str| {
+            let mut r = release(version, None);
+            r.changelog = Some(text.to_string());
+            r
+        };
+        let plugin = plugin(vec![notes("1.0.0", "First"), notes("1.1.0", "Fixes"), release("1.2.0", None), notes("1.3.0", "Tables")]);
+        let changelog = changelog_between(&plugin, &Version::parse("1.0.0").unwrap(), &Version::parse("1.3.0").unwrap());
+        assert_eq!(changelog.as_deref(), Some("## 1.3.0\n\nTables\n\n## 1.1.0\n\nFixes"));
+
+        let repo = |value: serde_json::Value| github_repo(&value);
+        let expected = Some(("lokus-ai".to_string(), "word-count".to_string()));
+        assert_eq!(repo(serde_json::json!("https://github.com/lokus-ai/word-count")), expected);
+        assert_eq!(repo(serde_json::json!({ "type": "git", "url": "git+https://github.com/lokus-ai/word-count.git" })), expected);
+        assert_eq!(repo(serde_json::json!("git@github.com:lokus-ai/word-count.git")), expected);
+        assert_eq!(repo(serde_json::json!("https://gitlab.com/lokus-ai/word-count")), None);
+    }
+}
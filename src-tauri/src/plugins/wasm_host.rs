@@ -0,0 +1,321 @@
+//! Backend functions for plugins, compiled to WebAssembly and run in a
+//! wasmtime sandbox instead of the webview.
+//!
+//! A plugin names its module with `"wasm"` in plugin.json. Modules get no
+//! WASI and no ambient access; the only way out is the `lokus` imports
+//! below, each checked by the permission broker against what the user
+//! granted the plugin. Every call gets a fresh instance with a fuel and
+//! memory budget, so nothing carries over and a runaway loop is cut off.
+//!
+//! Strings cross the boundary as UTF-8 in the module's exported `memory`.
+//! The module exports `alloc(len) -> ptr`, and each function it exposes
+//! takes the `(ptr, len)` of its JSON arguments and returns its JSON result
+//! packed into an `i64` as `ptr << 32 | len`. Imports return strings packed
+//! the same way, or a negative error code:
+//!
+//! - `lokus.read_note(path_ptr, path_len) -> i64`
+//! - `lokus.write_note(path_ptr, path_len, text_ptr, text_len) -> i32`
+//! - `lokus.search(query_ptr, query_len) -> i64`, a JSON array of hits
+//! - `lokus.log(ptr, len)`
+
+use super::{get_plugin_settings, get_plugins_directory, load_plugin_info, resolve_plugin_name};
+use crate::errors::LokusError;
+use crate::plugin_broker::{authorize_host, authorize_host_path, workspace_root, Capability};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::AppHandle;
+use wasmtime::{
+    AsContext, AsContextMut, Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    Trap, TypedFunc,
+};
+
+/// Roughly one unit per instruction executed
+const FUEL_PER_CALL: u64 = 2_000_000_000;
+const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+/// Longest string read out of a module, checked before anything is allocated
+const MAX_STRING_BYTES: u32 = 16 * 1024 * 1024;
+const SEARCH_LIMIT: usize = 50;
+
+/// Import refused by the plugin's permissions
+const ERR_DENIED: i64 = -1;
+/// Import failed for any other reason
+const ERR_FAILED: i64 = -2;
+
+static ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("wasmtime engine configuration is valid")
+});
+
+/// Compiled modules by path, with the modification time they were built from
+static MODULES: Lazy<Mutex<HashMap<PathBuf, (SystemTime, Module)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct HostState {
+    app: AppHandle,
+    plugin_id: String,
+    limits: StoreLimits,
+}
+
+enum HostError {
+    /// Refused by the broker, which has logged it
+    Denied,
+    Failed(String),
+}
+
+fn pack(ptr: u32, len: u32) -> i64 {
+    (((ptr as u64) << 32) | len as u64) as i64
+}
+
+fn unpack(value: i64) -> (u32, u32) {
+    ((value as u64 >> 32) as u32, value as u32)
+}
+
+fn module(path: &Path) -> Result<Module, String> {
+    let modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read WASM module: {}", e))?;
+
+    let mut modules = MODULES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((built, module)) = modules.get(path) {
+        if *built == modified {
+            return Ok(module.clone());
+        }
+    }
+    let module = Module::from_file(&ENGINE, path).map_err(|e| format!("Invalid WASM module: {}", e))?;
+    modules.insert(path.to_path_buf(), (modified, module.clone()));
+    Ok(module)
+}
+
+fn read_string(store: impl AsContext, memory: &Memory, ptr: u32, len: u32) -> Result<String, String> {
+    if len > MAX_STRING_BYTES {
+        return Err("String is too large".to_string());
+    }
+    if u64::from(ptr) + u64::from(len) > memory.data_size(&store) as u64 {
+        return Err("String is outside the module's memory".to_string());
+    }
+    let mut buffer = vec![0u8; len as usize];
+    memory
+        .read(&store, ptr as usize, &mut buffer)
+        .map_err(|_| "String is outside the module's memory".to_string())?;
+    String::from_utf8(buffer).map_err(|_| "String is not UTF-8".to_string())
+}
+
+/// Copy `text` into memory the module allocated for it
+fn write_string(mut store: impl AsContextMut, memory: &Memory, alloc: &TypedFunc<i32, i32>, text: &str) -> Result<i64, String> {
+    let len = u32::try_from(text.len()).map_err(|_| "String is too large".to_string())?;
+    let ptr = alloc
+        .call(&mut store, len as i32)
+        .map_err(|e| format!("Module failed to allocate: {}", e))? as u32;
+    memory
+        .write(&mut store, ptr as usize, text.as_bytes())
+        .map_err(|_| "Module allocated outside its memory".to_string())?;
+    Ok(pack(ptr, len))
+}
+
+/// The module's memory and allocator, from inside an import
+fn guest_exports(caller: &mut Caller<'_, HostState>) -> Result<(Memory, TypedFunc<i32, i32>), HostError> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(|e| e.into_memory())
+        .ok_or_else(|| HostError::Failed("Module exports no memory".to_string()))?;
+    let alloc = caller
+        .get_export("alloc")
+        .and_then(|e| e.into_func())
+        .ok_or_else(|| HostError::Failed("Module exports no alloc".to_string()))?
+        .typed::<i32, i32>(&caller)
+        .map_err(|e| HostError::Failed(format!("alloc has the wrong signature: {}", e)))?;
+    Ok((memory, alloc))
+}
+
+fn read_arg(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Result<String, HostError> {
+    let (Ok(ptr), Ok(len)) = (u32::try_from(ptr), u32::try_from(len)) else {
+        return Err(HostError::Failed("Negative string pointer or length".to_string()));
+    };
+    let (memory, _) = guest_exports(caller)?;
+    read_string(&caller, &memory, ptr, len).map_err(HostError::Failed)
+}
+
+fn return_string(caller: &mut Caller<'_, HostState>, text: &str) -> Result<i64, HostError> {
+    let (memory, alloc) = guest_exports(caller)?;
+    write_string(caller, &memory, &alloc, text).map_err(HostError::Failed)
+}
+
+/// The import's result as the module sees it, logging failures
+fn to_code(caller: &Caller<'_, HostState>, import: &str, result: Result<i64, HostError>) -> i64 {
+    match result {
+        Ok(value) => value,
+        Err(HostError::Denied) => ERR_DENIED,
+        Err(HostError::Failed(e)) => {
+            tracing::warn!(plugin = %caller.data().plugin_id, import, "WASM import failed: {}", e);
+            ERR_FAILED
+        }
+    }
+}
+
+fn read_note(caller: &mut Caller<'_, HostState>, path_ptr: i32, path_len: i32) -> Result<i64, HostError> {
+    let path = read_arg(caller, path_ptr, path_len)?;
+    let state = caller.data();
+    let capability = Capability::ReadFile(PathBuf::from(path));
+    let path = authorize_host_path(&state.app, &state.plugin_id, "wasm:read_note", &capability).map_err(|_| HostError::Denied)?;
+    // Imports run on a blocking thread (see `plugin_wasm_invoke`), so waiting here is fine
+    let text = tauri::async_runtime::block_on(crate::handlers::files::read_file_content(path.to_string_lossy().to_string()))
+        .map_err(|e| HostError::Failed(e.to_string()))?;
+    return_string(caller, &text)
+}
+
+fn write_note(caller: &mut Caller<'_, HostState>, path_ptr: i32, path_len: i32, text_ptr: i32, text_len: i32) -> Result<i64, HostError> {
+    let path = read_arg(caller, path_ptr, path_len)?;
+    let text = read_arg(caller, text_ptr, text_len)?;
+    let state = caller.data();
+    let capability = Capability::WriteFile(PathBuf::from(path));
+    let path = authorize_host_path(&state.app, &state.plugin_id, "wasm:write_note", &capability).map_err(|_| HostError::Denied)?;
+    crate::handlers::files::write_file_content(path.to_string_lossy().to_string(), text)
+        .map_err(|e| HostError::Failed(e.to_string()))?;
+    Ok(0)
+}
+
+fn search(caller: &mut Caller<'_, HostState>, query_ptr: i32, query_len: i32) -> Result<i64, HostError> {
+    let query = read_arg(caller, query_ptr, query_len)?;
+    let state = caller.data();
+    authorize_host(&state.app, &state.plugin_id, "wasm:search", &Capability::Search(query.clone())).map_err(|_| HostError::Denied)?;
+    let root = workspace_root(&state.app).map_err(HostError::Failed)?;
+    let hits = crate::search::index::query(&root.to_string_lossy(), &query, SEARCH_LIMIT).map_err(HostError::Failed)?;
+    let json = serde_json::to_string(&hits).map_err(|e| HostError::Failed(e.to_string()))?;
+    return_string(caller, &json)
+}
+
+fn linker() -> wasmtime::Result<Linker<HostState>> {
+    let mut linker = Linker::new(&ENGINE);
+    linker.func_wrap("lokus", "read_note", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+        let result = read_note(&mut caller, ptr, len);
+        to_code(&caller, "read_note", result)
+    })?;
+    linker.func_wrap(
+        "lokus",
+        "write_note",
+        |mut caller: Caller<'_, HostState>, path_ptr: i32, path_len: i32, text_ptr: i32, text_len: i32| -> i32 {
+            let result = write_note(&mut caller, path_ptr, path_len, text_ptr, text_len);
+            to_code(&caller, "write_note", result) as i32
+        },
+    )?;
+    linker.func_wrap("lokus", "search", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+        let result = search(&mut caller, ptr, len);
+        to_code(&caller, "search", result)
+    })?;
+    linker.func_wrap("lokus", "log", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+        if let Ok(message) = read_arg(&mut caller, ptr, len) {
+            tracing::info!(plugin = %caller.data().plugin_id, "{}", message);
+        }
+    })?;
+    Ok(linker)
+}
+
+/// The module file of an enabled plugin, which must stay inside its folder
+fn module_path(app: &AppHandle, plugin_id: &str) -> Result<PathBuf, String> {
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    let folder = resolve_plugin_name(&plugins_dir, plugin_id)?;
+    if !get_plugin_settings(app)?.enabled_plugins.contains(&folder) {
+        return Err(format!("Plugin '{}' is not enabled", plugin_id));
+    }
+
+    let plugin_path = plugins_dir.join(&folder);
+    let wasm = load_plugin_info(&plugin_path)?
+        .manifest
+        .wasm
+        .ok_or_else(|| format!("Plugin '{}' has no WASM module", plugin_id))?;
+    let root = plugin_path.canonicalize().map_err(|e| format!("Plugin folder is not accessible: {}", e))?;
+    let path = root
+        .join(&wasm)
+        .canonicalize()
+        .map_err(|e| format!("WASM module {} not found: {}", wasm, e))?;
+    if !path.starts_with(&root) {
+        return Err("WASM module is outside the plugin folder".to_string());
+    }
+    Ok(path)
+}
+
+fn invoke(app: &AppHandle, plugin_id: &str, function: &str, args: &Value) -> Result<Value, String> {
+    let module = module(&module_path(app, plugin_id)?)?;
+    let state = HostState {
+        app: app.clone(),
+        plugin_id: plugin_id.to_string(),
+        limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).build(),
+    };
+    let mut store = Store::new(&ENGINE, state);
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+
+    let instance = linker()
+        .and_then(|linker| linker.instantiate(&mut store, &module))
+        .map_err(|e| format!("Failed to start WASM module: {}", e))?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| "WASM module exports no memory".to_string())?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .map_err(|_| "WASM module exports no alloc(len) -> ptr".to_string())?;
+    let func = instance
+        .get_typed_func::<(i32, i32), i64>(&mut store, function)
+        .map_err(|_| format!("WASM module exports no function '{}'", function))?;
+
+    let input = serde_json::to_string(args).map_err(|e| e.to_string())?;
+    let (ptr, len) = unpack(write_string(&mut store, &memory, &alloc, &input)?);
+    let output = func.call(&mut store, (ptr as i32, len as i32)).map_err(|e| {
+        if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+            format!("'{}' took too long and was stopped", function)
+        } else {
+            format!("'{}' failed: {}", function, e)
+        }
+    })?;
+
+    let (ptr, len) = unpack(output);
+    if len > MAX_STRING_BYTES {
+        return Err(format!("'{}' returned too much data", function));
+    }
+    let text = read_string(&store, &memory, ptr, len)?;
+    // Plain text results are passed through as a string
+    Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+}
+
+/// Call `function` in an enabled plugin's WASM module with JSON `args`. Only
+/// the host window may call this, on a plugin's behalf: calls from plugin
+/// webviews or carrying plugin headers are refused by
+/// `path_policy::check_plugin`, as the module's imports are checked against
+/// the plugin's permissions instead.
+#[tauri::command]
+pub async fn plugin_wasm_invoke(app: AppHandle, plugin_id: String, function: String, args: Option<Value>) -> Result<Value, LokusError> {
+    let id = plugin_id.clone();
+    tauri::async_runtime::spawn_blocking(move || invoke(&app, &id, &function, &args.unwrap_or(Value::Null)))
+        .await
+        .map_err(|e| LokusError::plugin(&plugin_id, format!("WASM call panicked: {}", e)))?
+        .map_err(|e| LokusError::plugin(&plugin_id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_round_trip() {
+        assert_eq!(unpack(pack(0x0010_0000, 42)), (0x0010_0000, 42));
+        assert_eq!(unpack(pack(u32::MAX, u32::MAX)), (u32::MAX, u32::MAX));
+        // Anything inside the memory budget can't be mistaken for an error code
+        assert!(pack(MAX_MEMORY_BYTES as u32 - 1, MAX_STRING_BYTES) > 0);
+    }
+
+    #[test]
+    fn test_read_string_checks_bounds_before_allocating() {
+        let mut store = Store::new(&Engine::default(), ());
+        let memory = Memory::new(&mut store, wasmtime::MemoryType::new(1, None)).unwrap();
+        memory.write(&mut store, 0, b"hi").unwrap();
+
+        assert_eq!(read_string(&store, &memory, 0, 2).unwrap(), "hi");
+        assert!(read_string(&store, &memory, 0, u32::MAX).is_err());
+        assert!(read_string(&store, &memory, 65_530, 10).is_err());
+        assert!(read_string(&store, &memory, u32::MAX, 1).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! iCalendar recurrence rules (RFC 5545 `RRULE`) evaluated on whole days.
+//!
+//! Supports FREQ (DAILY/WEEKLY/MONTHLY/YEARLY), INTERVAL, COUNT, UNTIL, BYDAY
+//! (with ordinals such as `1MO` or `-1FR`), BYMONTHDAY, BYMONTH and WKST.
+//! Time-of-day is left to the caller, which keeps the start's time.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Safety net for rules that can never match (e.g. `BYMONTHDAY=31;BYMONTH=2`)
+const MAX_SCAN_DAYS: i64 = 366 * 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+    /// Weekday with an optional ordinal within the month (or year, without BYMONTH)
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_list<T: std::str::FromStr>(key: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|v| v.trim().parse::<T>().map_err(|_| format!("Invalid {} value '{}'", key, v)))
+        .collect()
+}
+
+impl RRule {
+    /// Parse `FREQ=WEEKLY;BYDAY=MO,WE`; a leading `RRULE:` is accepted
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+        let mut freq = None;
+        let mut parsed = RRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+
+        for part in rule.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid RRULE part '{}'", part))?;
+            let key = key.trim().to_uppercase();
+            let value = value.trim().to_uppercase();
+            match key.as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(format!("Unsupported FREQ '{}'", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    parsed.interval = value.parse().ok().filter(|i| *i > 0).ok_or_else(|| format!("Invalid INTERVAL '{}'", value))?
+                }
+                "COUNT" => parsed.count = Some(value.parse().map_err(|_| format!("Invalid COUNT '{}'", value))?),
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(&value);
+                    parsed.until = Some(NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| format!("Invalid UNTIL '{}'", value))?);
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let day = day.trim();
+                        let split = day.len().saturating_sub(2);
+                        let weekday = day.get(split..).and_then(parse_weekday).ok_or_else(|| format!("Invalid BYDAY '{}'", day))?;
+                        let ordinal = match day.get(..split).unwrap_or_default() {
+                            "" => None,
+                            n => Some(n.trim_start_matches('+').parse::<i32>().map_err(|_| format!("Invalid BYDAY '{}'", day))?),
+                        };
+                        parsed.by_day.push((ordinal, weekday));
+                    }
+                }
+                "BYMONTHDAY" => parsed.by_month_day = parse_list("BYMONTHDAY", &value)?,
+                "BYMONTH" => parsed.by_month = parse_list("BYMONTH", &value)?,
+                // Weeks always start on Monday here; WKST only matters for exotic rules
+                "WKST" => {}
+                other => return Err(format!("Unsupported RRULE part '{}'", other)),
+            }
+        }
+
+        parsed.freq = freq.ok_or("RRULE is missing FREQ")?;
+        Ok(parsed)
+    }
+
+    /// Number of whole periods between the start and `date`
+    fn period_index(&self, start: NaiveDate, date: NaiveDate) -> i64 {
+        match self.freq {
+            Frequency::Daily => (date - start).num_days(),
+            Frequency::Weekly => {
+                let week_start = |d: NaiveDate| d - Duration::days(i64::from(d.weekday().num_days_from_monday()));
+                (week_start(date) - week_start(start)).num_days() / 7
+            }
+            Frequency::Monthly => {
+                i64::from(date.year() - start.year()) * 12 + i64::from(date.month()) - i64::from(start.month())
+            }
+            Frequency::Yearly => i64::from(date.year() - start.year()),
+        }
+    }
+
+    fn matches_day(&self, start: NaiveDate, date: NaiveDate) -> bool {
+        if !self.by_month.is_empty() && !self.by_month.contains(&date.month()) {
+            return false;
+        }
+
+        if !self.by_month_day.is_empty() {
+            let days_in_month = days_in_month(date);
+            let hit = self.by_month_day.iter().any(|&d| {
+                let day = if d < 0 { days_in_month as i32 + d + 1 } else { d };
+                day == date.day() as i32
+            });
+            if !hit {
+                return false;
+            }
+        }
+
+        if !self.by_day.is_empty() {
+            let in_year = self.freq == Frequency::Yearly && self.by_month.is_empty();
+            let hit = self.by_day.iter().any(|&(ordinal, weekday)| {
+                date.weekday() == weekday && ordinal.map_or(true, |n| weekday_ordinal_matches(date, n, in_year))
+            });
+            if !hit {
+                return false;
+            }
+        }
+
+        // Without BYxxx parts the start date supplies the missing fields
+        let no_day_parts = self.by_day.is_empty() && self.by_month_day.is_empty();
+        match self.freq {
+            Frequency::Daily => true,
+            Frequency::Weekly => !no_day_parts || date.weekday() == start.weekday(),
+            Frequency::Monthly => !no_day_parts || date.day() == start.day(),
+            Frequency::Yearly => {
+                if no_day_parts && self.by_month.is_empty() {
+                    date.month() == start.month() && date.day() == start.day()
+                } else {
+                    !no_day_parts || date.day() == start.day()
+                }
+            }
+        }
+    }
+
+    fn is_occurrence(&self, start: NaiveDate, date: NaiveDate) -> bool {
+        date == start
+            || (date > start
+                && self.period_index(start, date) % i64::from(self.interval) == 0
+                && self.matches_day(start, date))
+    }
+
+    /// Occurrences on or after `from` (the start always counts as the first), up to `limit` of them
+    pub fn occurrences(&self, start: NaiveDate, from: NaiveDate, until: NaiveDate, limit: usize) -> Vec<NaiveDate> {
+        let last = match self.until {
+            Some(rule_until) => rule_until.min(until),
+            None => until,
+        };
+        let mut found = Vec::new();
+        let mut seen = 0u32;
+        let mut date = start;
+        while date <= last && found.len() < limit && (date - start).num_days() <= MAX_SCAN_DAYS + (from - start).num_days().max(0) {
+            if self.is_occurrence(start, date) {
+                seen += 1;
+                if self.count.map_or(false, |count| seen > count) {
+                    break;
+                }
+                if date >= from {
+                    found.push(date);
+                }
+            }
+            date += Duration::days(1);
+        }
+        found
+    }
+
+    /// First occurrence strictly after `after`
+    pub fn next_after(&self, start: NaiveDate, after: NaiveDate) -> Option<NaiveDate> {
+        let from = after + Duration::days(1);
+        self.occurrences(start, from, from + Duration::days(MAX_SCAN_DAYS), 1).into_iter().next()
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .map(|next| (next - Duration::days(1)).day())
+        .unwrap_or(31)
+}
+
+/// Whether `date` is the `n`th (negative: from the end) such weekday of its month or year
+fn weekday_ordinal_matches(date: NaiveDate, n: i32, in_year: bool) -> bool {
+    let (index_from_start, days_left) = if in_year {
+        let year_end = NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date);
+        (date.ordinal0() as i32 / 7 + 1, (year_end - date).num_days() as i32)
+    } else {
+        ((date.day() as i32 - 1) / 7 + 1, days_in_month(date) as i32 - date.day() as i32)
+    };
+    if n > 0 {
+        index_from_start == n
+    } else {
+        days_left / 7 + 1 == -n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn test_weekly_byday() {
+        let rule = RRule::parse("FREQ=WEEKLY;BYDAY=MO,WE").unwrap();
+        // 2024-01-01 is a Monday
+        let dates = rule.occurrences(d(2024, 1, 1), d(2024, 1, 1), d(2024, 1, 14), 10);
+        assert_eq!(dates, vec![d(2024, 1, 1), d(2024, 1, 3), d(2024, 1, 8), d(2024, 1, 10)]);
+
+        let biweekly = RRule::parse("FREQ=WEEKLY;INTERVAL=2").unwrap();
+        assert_eq!(biweekly.next_after(d(2024, 1, 1), d(2024, 1, 1)), Some(d(2024, 1, 15)));
+    }
+
+    #[test]
+    fn test_monthly_ordinals_and_month_days() {
+        let last_friday = RRule::parse("FREQ=MONTHLY;BYDAY=-1FR").unwrap();
+        assert_eq!(last_friday.next_after(d(2024, 1, 26), d(2024, 1, 26)), Some(d(2024, 2, 23)));
+
+        let last_day = RRule::parse("FREQ=MONTHLY;BYMONTHDAY=-1").unwrap();
+        assert_eq!(last_day.next_after(d(2024, 1, 31), d(2024, 1, 31)), Some(d(2024, 2, 29)));
+
+        // Plain monthly skips months without that day
+        let monthly = RRule::parse("FREQ=MONTHLY").unwrap();
+        assert_eq!(monthly.next_after(d(2024, 1, 31), d(2024, 1, 31)), Some(d(2024, 3, 31)));
+    }
+
+    #[test]
+    fn test_count_until_and_errors() {
+        let rule = RRule::parse("RRULE:FREQ=DAILY;COUNT=3").unwrap();
+        assert_eq!(rule.occurrences(d(2024, 1, 1), d(2024, 1, 1), d(2024, 12, 31), 10).len(), 3);
+        assert_eq!(rule.next_after(d(2024, 1, 1), d(2024, 1, 3)), None);
+
+        let rule = RRule::parse("FREQ=YEARLY;UNTIL=20260101T000000Z").unwrap();
+        assert_eq!(rule.occurrences(d(2024, 3, 1), d(2024, 1, 1), d(2030, 1, 1), 10).len(), 2);
+
+        assert!(RRule::parse("BYDAY=MO").is_err());
+        assert!(RRule::parse("FREQ=HOURLY").is_err());
+        assert!(RRule::parse("FREQ=DAILY;BYSETPOS=1").is_err());
+    }
+}
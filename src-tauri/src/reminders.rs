@@ -0,0 +1,404 @@
+//! Reminder scheduler.
+//!
+//! Reminders are persisted in `.reminders.dat` so they survive restarts. A
+//! background loop started at launch fires due reminders as native
+//! notifications (see `notifications`) and emits `reminder-fired` for the UI.
+//! Besides reminders set explicitly with `reminder_set`, the loop schedules
+//! one for every open task with a due date and every upcoming calendar event.
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreBuilder;
+
+pub const REMINDER_FIRED_EVENT: &str = "reminder-fired";
+
+const STORE_FILE: &str = ".reminders.dat";
+const TICK: std::time::Duration = std::time::Duration::from_secs(30);
+/// Calendar providers are queried over the network, so events are polled less often
+const EVENT_POLL_TICKS: u32 = 20;
+const EVENT_LEAD_MINUTES: i64 = 10;
+/// All-day tasks are announced at this local time on their due day
+const ALL_DAY_REMINDER_HOUR: u32 = 9;
+/// Reminders missed by more than this while the app was closed are dropped silently
+const MISSED_GRACE_HOURS: i64 = 12;
+/// Fired and cancelled reminders are kept this long so auto-scheduling doesn't recreate them
+const RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderKind {
+    Task,
+    Event,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderState {
+    Pending,
+    Fired,
+    Missed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reminder {
+    pub id: String,
+    pub kind: ReminderKind,
+    pub target_id: String,
+    pub title: String,
+    pub body: Option<String>,
+    /// Milliseconds since the epoch
+    pub at: i64,
+    pub state: ReminderState,
+    /// Set by the scheduler rather than the user
+    pub automatic: bool,
+    pub snooze_count: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReminderStore {
+    reminders: Vec<Reminder>,
+}
+
+impl ReminderStore {
+    /// Insert unless a reminder with this id already exists (in any state)
+    fn schedule(&mut self, reminder: Reminder) -> bool {
+        if self.reminders.iter().any(|r| r.id == reminder.id) {
+            return false;
+        }
+        self.reminders.push(reminder);
+        true
+    }
+
+    /// Reminders that should fire at `now`. Ones missed for too long (the app
+    /// was closed) are marked missed instead.
+    fn take_due(&mut self, now: i64) -> Vec<Reminder> {
+        let grace = Duration::hours(MISSED_GRACE_HOURS).num_milliseconds();
+        let mut due = Vec::new();
+        for reminder in self.reminders.iter_mut().filter(|r| r.state == ReminderState::Pending && r.at <= now) {
+            if now - reminder.at > grace {
+                reminder.state = ReminderState::Missed;
+            } else {
+                reminder.state = ReminderState::Fired;
+                due.push(reminder.clone());
+            }
+        }
+        due
+    }
+
+    fn prune(&mut self, now: i64) {
+        let cutoff = now - Duration::days(RETENTION_DAYS).num_milliseconds();
+        self.reminders.retain(|r| r.state == ReminderState::Pending || r.at >= cutoff);
+    }
+}
+
+fn load(app: &AppHandle) -> Result<ReminderStore, String> {
+    let store = StoreBuilder::new(app, PathBuf::from(STORE_FILE))
+        .build()
+        .map_err(|e| format!("Failed to build reminder store: {}", e))?;
+    let _ = store.reload();
+
+    match store.get("reminders") {
+        Some(value) => serde_json::from_value(value.clone()).map_err(|e| format!("Failed to deserialize reminders: {}", e)),
+        None => Ok(ReminderStore::default()),
+    }
+}
+
+fn save(app: &AppHandle, reminders: &ReminderStore) -> Result<(), String> {
+    let store = StoreBuilder::new(app, PathBuf::from(STORE_FILE))
+        .build()
+        .map_err(|e| format!("Failed to build reminder store: {}", e))?;
+    let _ = store.reload();
+
+    let value = serde_json::to_value(reminders).map_err(|e| format!("Failed to serialize reminders: {}", e))?;
+    store.set("reminders".to_string(), value);
+    store.save().map_err(|e| format!("Failed to save reminder store: {}", e))
+}
+
+/// When to remind about a task: at its due time, or in the morning for all-day tasks
+fn task_reminder_time(task: &crate::tasks::Task) -> Option<i64> {
+    let due = DateTime::parse_from_rfc3339(task.due_date.as_deref()?).ok()?.with_timezone(&Local);
+    if !task.due_date_is_all_day {
+        return Some(due.timestamp_millis());
+    }
+    let morning = due.date_naive().and_time(NaiveTime::from_hms_opt(ALL_DAY_REMINDER_HOUR, 0, 0)?);
+    Local.from_local_datetime(&morning).earliest().map(|dt| dt.timestamp_millis())
+}
+
+/// Automatic reminders for open tasks; ids include the due time so moving the
+/// due date schedules a fresh reminder
+fn schedule_task_reminders(store: &mut ReminderStore, tasks: &[crate::tasks::Task], now: i64) -> bool {
+    use crate::tasks::TaskStatus;
+    let mut changed = false;
+    for task in tasks {
+        if matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled) {
+            continue;
+        }
+        let Some(at) = task_reminder_time(task).filter(|at| *at > now) else {
+            continue;
+        };
+        changed |= store.schedule(Reminder {
+            id: format!("task:{}:{}", task.id, at),
+            kind: ReminderKind::Task,
+            target_id: task.id.clone(),
+            title: task.title.clone(),
+            body: Some("Task due".to_string()),
+            at,
+            state: ReminderState::Pending,
+            automatic: true,
+            snooze_count: 0,
+        });
+    }
+
+    // Drop automatic reminders for tasks that are done, deleted or rescheduled
+    let open_ids: std::collections::HashMap<&str, Option<i64>> = tasks
+        .iter()
+        .filter(|t| !matches!(t.status, TaskStatus::Completed | TaskStatus::Cancelled))
+        .map(|t| (t.id.as_str(), task_reminder_time(t)))
+        .collect();
+    for reminder in store.reminders.iter_mut().filter(|r| {
+        r.kind == ReminderKind::Task && r.automatic && r.state == ReminderState::Pending && r.snooze_count == 0
+    }) {
+        let current = open_ids.get(reminder.target_id.as_str()).copied().flatten();
+        if current != Some(reminder.at) {
+            reminder.state = ReminderState::Cancelled;
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(desktop)]
+async fn schedule_event_reminders(store: &mut ReminderStore, now: i64) -> bool {
+    let start = Utc::now();
+    let end = start + Duration::hours(24);
+    let events = match crate::calendar::get_all_events(start.to_rfc3339(), end.to_rfc3339()).await {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::debug!("Skipping event reminders: {}", e);
+            return false;
+        }
+    };
+
+    let mut changed = false;
+    for event in events {
+        if event.all_day || event.status == crate::calendar::models::EventStatus::Cancelled {
+            continue;
+        }
+        let at = (event.start - Duration::minutes(EVENT_LEAD_MINUTES)).timestamp_millis();
+        if at <= now {
+            continue;
+        }
+        let local_start = event.start.with_timezone(&Local);
+        changed |= store.schedule(Reminder {
+            id: format!("event:{}:{}", event.id, event.start.timestamp_millis()),
+            kind: ReminderKind::Event,
+            target_id: event.id.clone(),
+            title: event.title.clone(),
+            body: Some(match &event.location {
+                Some(location) if !location.is_empty() => format!("{} · {}", local_start.format("%H:%M"), location),
+                _ => format!("Starts at {}", local_start.format("%H:%M")),
+            }),
+            at,
+            state: ReminderState::Pending,
+            automatic: true,
+            snooze_count: 0,
+        });
+    }
+    changed
+}
+
+async fn tick(app: &AppHandle, poll_events: bool) -> Result<(), String> {
+    let now = Utc::now().timestamp_millis();
+    let mut store = load(app)?;
+    let mut changed = false;
+
+    if let Ok(tasks) = crate::tasks::get_task_store(app) {
+        let tasks: Vec<crate::tasks::Task> = tasks.tasks.into_values().collect();
+        changed |= schedule_task_reminders(&mut store, &tasks, now);
+    }
+    #[cfg(desktop)]
+    {
+        if poll_events {
+            changed |= schedule_event_reminders(&mut store, now).await;
+        }
+    }
+    #[cfg(not(desktop))]
+    let _ = poll_events;
+
+    let due = store.take_due(now);
+    let before = store.reminders.len();
+    store.prune(now);
+    if changed || !due.is_empty() || store.reminders.len() != before {
+        save(app, &store)?;
+    }
+
+    for reminder in due {
+        crate::notifications::send_notification(app, &reminder.title, reminder.body.as_deref().unwrap_or(""));
+        if let Err(e) = app.emit(REMINDER_FIRED_EVENT, &reminder) {
+            tracing::warn!("Failed to emit {}: {}", REMINDER_FIRED_EVENT, e);
+        }
+    }
+    Ok(())
+}
+
+/// Run the scheduler for the lifetime of the app
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticks: u32 = 0;
+        loop {
+            if let Err(e) = tick(&app, ticks % EVENT_POLL_TICKS == 0).await {
+                tracing::warn!("Reminder scheduler tick failed: {}", e);
+            }
+            ticks = ticks.wrapping_add(1);
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Remind about a task at `at` (RFC 3339), replacing any earlier manual reminder for it
+#[tauri::command]
+pub async fn reminder_set(app: AppHandle, task_id: String, at: String) -> Result<Reminder, String> {
+    let at = DateTime::parse_from_rfc3339(&at)
+        .map_err(|e| format!("Invalid reminder time: {}", e))?
+        .timestamp_millis();
+    let task = crate::tasks::get_task_store(&app)?
+        .get_task(&task_id)
+        .cloned()
+        .ok_or_else(|| format!("Task with id {} not found", task_id))?;
+
+    let mut store = load(&app)?;
+    for reminder in store
+        .reminders
+        .iter_mut()
+        .filter(|r| r.target_id == task_id && !r.automatic && r.state == ReminderState::Pending)
+    {
+        reminder.state = ReminderState::Cancelled;
+    }
+    let reminder = Reminder {
+        id: format!("manual:{}:{}", task_id, at),
+        kind: ReminderKind::Task,
+        target_id: task_id,
+        title: task.title,
+        body: Some("Reminder".to_string()),
+        at,
+        state: ReminderState::Pending,
+        automatic: false,
+        snooze_count: 0,
+    };
+    store.reminders.retain(|r| r.id != reminder.id);
+    store.reminders.push(reminder.clone());
+    save(&app, &store)?;
+    Ok(reminder)
+}
+
+/// Pending reminders, soonest first
+#[tauri::command]
+pub async fn reminder_list(app: AppHandle) -> Result<Vec<Reminder>, String> {
+    let mut pending: Vec<Reminder> = load(&app)?
+        .reminders
+        .into_iter()
+        .filter(|r| r.state == ReminderState::Pending)
+        .collect();
+    pending.sort_by_key(|r| r.at);
+    Ok(pending)
+}
+
+#[tauri::command]
+pub async fn reminder_snooze(app: AppHandle, id: String, minutes: u32) -> Result<Reminder, String> {
+    if minutes == 0 {
+        return Err("Snooze duration must be at least one minute".to_string());
+    }
+    let mut store = load(&app)?;
+    let reminder = store
+        .reminders
+        .iter_mut()
+        .find(|r| r.id == id)
+        .ok_or_else(|| format!("Reminder {} not found", id))?;
+    reminder.at = Utc::now().timestamp_millis() + i64::from(minutes) * 60_000;
+    reminder.state = ReminderState::Pending;
+    reminder.snooze_count += 1;
+    let reminder = reminder.clone();
+    save(&app, &store)?;
+    Ok(reminder)
+}
+
+#[tauri::command]
+pub async fn reminder_cancel(app: AppHandle, id: String) -> Result<(), String> {
+    let mut store = load(&app)?;
+    let reminder = store
+        .reminders
+        .iter_mut()
+        .find(|r| r.id == id)
+        .ok_or_else(|| format!("Reminder {} not found", id))?;
+    reminder.state = ReminderState::Cancelled;
+    save(&app, &store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::{Task, TaskStatus};
+
+    fn timed_task(title: &str, due: DateTime<Local>) -> Task {
+        let mut task = Task::new(title.to_string());
+        task.due_date = Some(due.to_rfc3339());
+        task
+    }
+
+    #[test]
+    fn test_task_reminders_follow_due_dates() {
+        let now = Local::now();
+        let mut store = ReminderStore::default();
+        let mut task = timed_task("Send invoice", now + Duration::hours(2));
+        let done = {
+            let mut t = timed_task("Old", now + Duration::hours(1));
+            t.status = TaskStatus::Completed;
+            t
+        };
+
+        assert!(schedule_task_reminders(&mut store, &[task.clone(), done], now.timestamp_millis()));
+        assert_eq!(store.reminders.len(), 1);
+        // Re-running is idempotent
+        assert!(!schedule_task_reminders(&mut store, &[task.clone()], now.timestamp_millis()));
+
+        // Rescheduling cancels the old reminder and creates a new one
+        task.due_date = Some((now + Duration::hours(5)).to_rfc3339());
+        schedule_task_reminders(&mut store, &[task], now.timestamp_millis());
+        let pending: Vec<_> = store.reminders.iter().filter(|r| r.state == ReminderState::Pending).collect();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].at, (now + Duration::hours(5)).timestamp_millis());
+    }
+
+    #[test]
+    fn test_due_and_missed_reminders() {
+        let now = Utc::now().timestamp_millis();
+        let reminder = |id: &str, at: i64| Reminder {
+            id: id.to_string(),
+            kind: ReminderKind::Task,
+            target_id: "t".to_string(),
+            title: id.to_string(),
+            body: None,
+            at,
+            state: ReminderState::Pending,
+            automatic: false,
+            snooze_count: 0,
+        };
+        let mut store = ReminderStore::default();
+        store.schedule(reminder("due", now - 1_000));
+        store.schedule(reminder("stale", now - Duration::days(2).num_milliseconds()));
+        store.schedule(reminder("later", now + 60_000));
+
+        let fired = store.take_due(now);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, "due");
+        assert_eq!(store.reminders[1].state, ReminderState::Missed);
+        assert_eq!(store.reminders[2].state, ReminderState::Pending);
+        assert!(store.take_due(now).is_empty());
+    }
+}
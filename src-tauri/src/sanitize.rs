@@ -0,0 +1,250 @@
+//! HTML sanitization for content from outside the app.
+//!
+//! Clipboard pastes, email bodies and web clips all end up rendered in the
+//! webview, so they go through one allow-list sanitizer
+//! (ammonia) here rather than each surface cleaning HTML its own way.
+//! Scripts, event handlers, `javascript:` URLs and embedded frames never
+//! survive; what else is kept depends on the [`Policy`] for the source, whose
+//! defaults can be overridden in `~/.lokus/sanitize-policies.json`.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const POLICIES_FILE: &str = "sanitize-policies.json";
+
+/// Tags a policy can't add back, whatever its `extra_tags` say
+const FORBIDDEN_TAGS: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "base", "link", "meta", "form", "input",
+    "button", "textarea", "select", "option", "svg", "math", "template", "noscript",
+];
+
+/// Overrides from the policies file; `None` until read
+static OVERRIDES: Lazy<Mutex<Option<BTreeMap<Policy, PolicyConfig>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Where the HTML came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Policy {
+    Clipboard,
+    Email,
+    WebClip,
+}
+
+impl Policy {
+    const ALL: [Policy; 3] = [Policy::Clipboard, Policy::Email, Policy::WebClip];
+
+    fn defaults(self) -> PolicyConfig {
+        let base = PolicyConfig {
+            allow_links: true,
+            allow_images: true,
+            allow_remote_images: true,
+            allow_styles: false,
+            allow_classes: false,
+            extra_tags: Vec::new(),
+        };
+        match self {
+            // Pasted images often arrive inline as data URLs
+            Policy::Clipboard => base,
+            // Newsletters are laid out with inline styles
+            Policy::Email => PolicyConfig { allow_styles: true, ..base },
+            // Classes carry code block languages into the markdown
+            Policy::WebClip => PolicyConfig { allow_classes: true, ..base },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PolicyConfig {
+    pub allow_links: bool,
+    pub allow_images: bool,
+    /// Images fetched over http(s), which also lets senders see the message was opened
+    pub allow_remote_images: bool,
+    /// `style` attributes; `<style>` elements are never kept
+    pub allow_styles: bool,
+    pub allow_classes: bool,
+    /// Tags to keep beyond the default allow list
+    pub extra_tags: Vec<String>,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Policy::Clipboard.defaults()
+    }
+}
+
+fn policies_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join(".lokus").join(POLICIES_FILE))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn overrides() -> BTreeMap<Policy, PolicyConfig> {
+    OVERRIDES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| {
+            policies_path()
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+/// The configuration in effect for `policy`
+pub fn config(policy: Policy) -> PolicyConfig {
+    overrides().remove(&policy).unwrap_or_else(|| policy.defaults())
+}
+
+/// Clean `html` with an explicit configuration
+pub fn clean(html: &str, config: &PolicyConfig) -> String {
+    let mut builder = ammonia::Builder::default();
+    let mut schemes: HashSet<&str> = ["http", "https", "mailto", "tel"].into_iter().collect();
+    // Inline email parts are referenced by Content-ID
+    schemes.insert("cid");
+
+    let mut removed = Vec::new();
+    if !config.allow_links {
+        removed.push("a");
+    }
+    if !config.allow_images {
+        removed.extend(["img", "picture", "source"]);
+    } else {
+        schemes.insert("data");
+    }
+    builder.rm_tags(removed);
+    builder.url_schemes(schemes);
+
+    let extra: Vec<&str> = config
+        .extra_tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty() && !FORBIDDEN_TAGS.iter().any(|f| f.eq_ignore_ascii_case(tag)))
+        .collect();
+    builder.add_tags(extra);
+    if config.allow_styles {
+        builder.add_generic_attributes(["style"]);
+    }
+    if config.allow_classes {
+        builder.add_generic_attributes(["class"]);
+    }
+
+    let allow_remote_images = config.allow_remote_images;
+    builder.attribute_filter(move |element, attribute, value| {
+        // Values without a colon have no scheme, such as relative URLs and titles
+        let (scheme, rest) = value.trim_start().split_once(':').unwrap_or_default();
+        let is_image_source = element == "img" && attribute == "src";
+        match scheme.to_ascii_lowercase().as_str() {
+            // Data URLs only as image sources, and only images
+            "data" if !(is_image_source && rest.starts_with("image/")) => None,
+            "http" | "https" if is_image_source && !allow_remote_images => None,
+            _ => Some(value.into()),
+        }
+    });
+
+    builder.clean(html).to_string()
+}
+
+/// Whether `text` has markup in it: a tag, comment or doctype. Text without
+/// any can't produce elements, so it needn't be cleaned (which would only
+/// escape it).
+pub fn looks_like_html(text: &str) -> bool {
+    text.as_bytes()
+        .windows(2)
+        .any(|pair| pair[0] == b'<' && (pair[1].is_ascii_alphabetic() || matches!(pair[1], b'/' | b'!' | b'?')))
+}
+
+/// Clean `html` from the given source
+pub fn sanitize_html(html: &str, policy: Policy) -> String {
+    clean(html, &config(policy))
+}
+
+// --- Tauri Commands ---
+
+/// Sanitize HTML before the frontend renders it
+#[tauri::command(rename = "sanitize_html")]
+pub fn sanitize_html_command(html: String, policy: Policy) -> String {
+    sanitize_html(&html, policy)
+}
+
+/// The configuration in effect for each policy
+#[tauri::command]
+pub fn sanitize_get_policies() -> BTreeMap<Policy, PolicyConfig> {
+    Policy::ALL.into_iter().map(|policy| (policy, config(policy))).collect()
+}
+
+/// Override a policy, or go back to its defaults with `None`
+#[tauri::command]
+pub fn sanitize_set_policy(policy: Policy, config: Option<PolicyConfig>) -> Result<PolicyConfig, String> {
+    let mut overrides = overrides();
+    match config {
+        Some(config) => overrides.insert(policy, config),
+        None => overrides.remove(&policy),
+    };
+    let path = policies_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&overrides).map_err(|e| format!("Failed to serialize policies: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to save policies: {}", e))?;
+    *OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()) = Some(overrides);
+    Ok(self::config(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_and_handlers_never_survive() {
+        let html = r#"<p onclick="steal()">Hi <script>alert(1)</script><a href="javascript:alert(1)">x</a><iframe src="https://evil"></iframe></p>"#;
+        for policy in Policy::ALL {
+            let mut config = policy.defaults();
+            config.extra_tags = vec!["script".to_string(), "iframe".to_string()];
+            let cleaned = clean(html, &config);
+            assert!(!cleaned.contains("script") && !cleaned.contains("onclick"), "{:?}: {}", policy, cleaned);
+            assert!(!cleaned.contains("javascript:") && !cleaned.contains("iframe"), "{:?}: {}", policy, cleaned);
+        }
+    }
+
+    #[test]
+    fn policies_decide_images_styles_and_links() {
+        let html = r#"<p style="color:red"><img src="https://tracker/x.png"><img src="data:image/png;base64,AA=="><img src="cid:logo@mail"><a href="data:text/html,hi">a</a></p>"#;
+
+        let email = clean(html, &Policy::Email.defaults());
+        assert!(email.contains(r#"style="color:red""#));
+        assert!(email.contains("https://tracker/x.png") && email.contains("cid:logo@mail"));
+        assert!(!email.contains("data:text/html"));
+
+        let private = PolicyConfig { allow_remote_images: false, ..Policy::Email.defaults() };
+        let cleaned = clean(html, &private);
+        assert!(!cleaned.contains("tracker") && cleaned.contains("data:image/png"));
+
+        let clipboard = clean(html, &Policy::Clipboard.defaults());
+        assert!(!clipboard.contains("style="));
+
+        let text_only = PolicyConfig { allow_links: false, allow_images: false, ..Default::default() };
+        assert_eq!(clean(html, &text_only), "<p>a</p>");
+    }
+
+    #[test]
+    fn values_without_a_scheme_are_kept() {
+        let config = PolicyConfig::default();
+        let cleaned = clean(r#"<p title="data">x</p><img src="DATA:image/png;base64,AA=="><img src="data:text/html,x">"#, &config);
+        assert!(cleaned.contains(r#"title="data""#));
+        assert!(cleaned.contains("DATA:image/png") && !cleaned.contains("text/html"));
+    }
+
+    #[test]
+    fn only_markup_looks_like_html() {
+        assert!(looks_like_html("<p>hi</p>"));
+        assert!(looks_like_html("text <!-- note -->"));
+        assert!(!looks_like_html("a < b && c > d"));
+        assert!(!looks_like_html("Tom & Jerry"));
+    }
+}
@@ -104,7 +104,7 @@ impl ScheduleBlockStore {
     }
 }
 
-fn get_schedule_block_store(app: &AppHandle) -> Result<ScheduleBlockStore, String> {
+pub(crate) fn get_schedule_block_store(app: &AppHandle) -> Result<ScheduleBlockStore, String> {
     let store = StoreBuilder::new(app, PathBuf::from(".schedule-blocks.dat"))
         .build()
         .map_err(|e| format!("Failed to build schedule block store: {}", e))?;
@@ -118,7 +118,7 @@ fn get_schedule_block_store(app: &AppHandle) -> Result<ScheduleBlockStore, Strin
     }
 }
 
-fn save_schedule_block_store(app: &AppHandle, store_data: &ScheduleBlockStore) -> Result<(), String> {
+pub(crate) fn save_schedule_block_store(app: &AppHandle, store_data: &ScheduleBlockStore) -> Result<(), String> {
     let store = StoreBuilder::new(app, PathBuf::from(".schedule-blocks.dat"))
         .build()
         .map_err(|e| format!("Failed to build schedule block store: {}", e))?;
@@ -0,0 +1,721 @@
+//! Persistent inverted index for workspace full-text search.
+//!
+//! Each workspace gets one index, stored in `.lokus/search-index.bin` and kept
+//! in memory once loaded. Saved files are re-tokenized individually, and
+//! entries that drifted while the app was closed are reconciled by mtime when
+//! the index is loaded from disk, so a full rebuild is only needed once.
+//! Images with OCR sidecars and PDFs with extracted text are indexed under
+//! their own path.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, UNIX_EPOCH};
+use crate::traversal::{VaultWalk, Visit};
+
+const INDEX_VERSION: u32 = 1;
+const INDEX_FILE: &str = "search-index.bin";
+const INDEXED_EXTENSIONS: &[&str] = &["md", "txt"];
+const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", "node_modules", ".trash"];
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const MAX_TOKEN_LEN: usize = 64;
+
+// Standard Okapi BM25 parameters
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// Characters of context kept on each side of the first hit in a snippet
+const SNIPPET_RADIUS: usize = 80;
+
+/// Loaded indexes keyed by workspace root
+static INDEXES: Lazy<Mutex<HashMap<PathBuf, SearchIndex>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedDoc {
+    /// Path relative to the workspace root
+    path: String,
+    /// Number of tokens in the document
+    length: u32,
+    /// Modification time (ms since epoch) at the time of indexing
+    modified: i64,
+    /// Distinct terms, kept so the document can be removed from postings
+    terms: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Posting {
+    doc: u32,
+    tf: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    next_doc: u32,
+    docs: HashMap<u32, IndexedDoc>,
+    by_path: HashMap<String, u32>,
+    postings: HashMap<String, Vec<Posting>>,
+    total_length: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexStats {
+    pub documents: usize,
+    pub terms: usize,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnippetHighlight {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankedResult {
+    pub file: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub score: f64,
+    pub snippet: String,
+    /// Character ranges inside `snippet` that matched a query term
+    pub highlights: Vec<SnippetHighlight>,
+}
+
+// --- Tokenization ---
+
+/// Split text into lowercase alphanumeric tokens with their character spans
+fn tokens_with_spans(text: &str) -> Vec<(String, usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    let mut position = 0;
+
+    for (i, c) in text.chars().enumerate() {
+        if c.is_alphanumeric() {
+            if current.is_empty() {
+                start = i;
+            }
+            current.extend(c.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push((std::mem::take(&mut current), start, i));
+        }
+        position = i + 1;
+    }
+    if !current.is_empty() {
+        tokens.push((current, start, position));
+    }
+
+    tokens.retain(|(token, _, _)| token.chars().count() <= MAX_TOKEN_LEN);
+    tokens
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    tokens_with_spans(text).into_iter().map(|(token, _, _)| token).collect()
+}
+
+// --- Index maintenance ---
+
+impl SearchIndex {
+    fn new() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            ..Default::default()
+        }
+    }
+
+    /// Rough heap size: strings, posting lists and map entries
+    fn approx_bytes(&self) -> usize {
+        let docs: usize = self
+            .docs
+            .values()
+            .map(|doc| 64 + doc.path.len() * 2 + doc.terms.iter().map(|t| t.len() + 24).sum::<usize>())
+            .sum();
+        let postings: usize = self
+            .postings
+            .iter()
+            .map(|(term, list)| 48 + term.len() + list.len() * std::mem::size_of::<Posting>())
+            .sum();
+        docs + postings
+    }
+
+    /// Add or replace a document
+    fn upsert(&mut self, rel_path: &str, content: &str, modified: i64) {
+        self.remove(rel_path);
+
+        let tokens = tokenize(content);
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *frequencies.entry(token.clone()).or_insert(0) += 1;
+        }
+
+        let doc_id = self.next_doc;
+        self.next_doc = self.next_doc.wrapping_add(1);
+
+        for (term, tf) in &frequencies {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .push(Posting { doc: doc_id, tf: *tf });
+        }
+
+        self.total_length += tokens.len() as u64;
+        self.by_path.insert(rel_path.to_string(), doc_id);
+        self.docs.insert(doc_id, IndexedDoc {
+            path: rel_path.to_string(),
+            length: tokens.len() as u32,
+            modified,
+            terms: frequencies.into_keys().collect(),
+        });
+    }
+
+    /// Remove a document, returning whether it was indexed
+    fn remove(&mut self, rel_path: &str) -> bool {
+        let doc_id = match self.by_path.remove(rel_path) {
+            Some(id) => id,
+            None => return false,
+        };
+
+        if let Some(doc) = self.docs.remove(&doc_id) {
+            self.total_length = self.total_length.saturating_sub(doc.length as u64);
+            for term in doc.terms {
+                if let Some(list) = self.postings.get_mut(&term) {
+                    list.retain(|p| p.doc != doc_id);
+                    if list.is_empty() {
+                        self.postings.remove(&term);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Move a document, or every document under a folder, to a new path
+    /// without re-reading it. Returns how many documents moved.
+    fn rename(&mut self, old_rel: &str, new_rel: &str) -> usize {
+        if old_rel == new_rel {
+            return 0;
+        }
+        let prefix = format!("{}/", old_rel);
+        let moved: Vec<(String, String)> = self
+            .by_path
+            .keys()
+            .filter_map(|p| match p.strip_prefix(&prefix) {
+                Some(rest) => Some((p.clone(), format!("{}/{}", new_rel, rest))),
+                None => (p == old_rel).then(|| (p.clone(), new_rel.to_string())),
+            })
+            .collect();
+
+        for (from, to) in &moved {
+            // A file that was overwritten by the move
+            self.remove(to);
+            if let Some(id) = self.by_path.remove(from) {
+                if let Some(doc) = self.docs.get_mut(&id) {
+                    doc.path = to.clone();
+                }
+                self.by_path.insert(to.clone(), id);
+            }
+        }
+        moved.len()
+    }
+
+    /// Bring the index in line with the files currently on disk.
+    /// Returns the number of documents added, updated or removed.
+    fn reconcile(&mut self, root: &Path) -> usize {
+        let on_disk = scan_workspace(root);
+        let mut changes = 0;
+
+        let present: HashSet<&str> = on_disk.iter().map(|(rel, _, _)| rel.as_str()).collect();
+        let stale: Vec<String> = self
+            .by_path
+            .keys()
+            .filter(|rel| !present.contains(rel.as_str()))
+            .cloned()
+            .collect();
+        for rel in stale {
+            self.remove(&rel);
+            changes += 1;
+        }
+
+        for (rel, abs, modified) in &on_disk {
+            let up_to_date = self
+                .by_path
+                .get(rel)
+                .and_then(|id| self.docs.get(id))
+                .map_or(false, |doc| doc.modified == *modified);
+            if up_to_date {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(abs) {
+                self.upsert(rel, &content, *modified);
+                changes += 1;
+            }
+        }
+
+        changes
+    }
+
+    /// Score documents against a query using BM25
+    fn rank(&self, query: &str, limit: usize) -> Vec<(u32, f64)> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+
+        let doc_count = self.docs.len() as f64;
+        if doc_count == 0.0 || terms.is_empty() {
+            return Vec::new();
+        }
+        let avg_length = (self.total_length as f64 / doc_count).max(1.0);
+
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+        for term in &terms {
+            let Some(list) = self.postings.get(term) else { continue };
+            let df = list.len() as f64;
+            let idf = (1.0 + (doc_count - df + 0.5) / (df + 0.5)).ln();
+
+            for posting in list {
+                let length = self.docs.get(&posting.doc).map_or(0, |d| d.length) as f64;
+                let tf = posting.tf as f64;
+                let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length / avg_length);
+                *scores.entry(posting.doc).or_insert(0.0) += idf * tf * (BM25_K1 + 1.0) / (tf + norm);
+            }
+        }
+
+        let mut ranked: Vec<(u32, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(limit);
+        ranked
+    }
+
+    fn stats(&self, started: Instant) -> IndexStats {
+        IndexStats {
+            documents: self.docs.len(),
+            terms: self.postings.len(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+// --- Persistence ---
+
+fn index_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(INDEX_FILE)
+}
+
+fn load_from_disk(root: &Path) -> Option<SearchIndex> {
+    let bytes = fs::read(index_path(root)).ok()?;
+    let index: SearchIndex = bincode::deserialize(&bytes).ok()?;
+    if index.version != INDEX_VERSION {
+        return None;
+    }
+    Some(index)
+}
+
+fn save_to_disk(root: &Path, index: &SearchIndex) -> Result<(), String> {
+    let path = index_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create index directory: {}", e))?;
+    }
+
+    let bytes = bincode::serialize(index)
+        .map_err(|e| format!("Failed to serialize search index: {}", e))?;
+    let temp_path = path.with_extension("bin.tmp");
+    fs::write(&temp_path, bytes)
+        .map_err(|e| format!("Failed to write search index: {}", e))?;
+    fs::rename(&temp_path, &path)
+        .map_err(|e| format!("Failed to replace search index: {}", e))
+}
+
+// --- Workspace scanning ---
+
+pub(crate) fn is_indexable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |ext| INDEXED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn modified_ms(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// List indexable files as (relative path, path to read the text from, mtime)
+fn scan_workspace(root: &Path) -> Vec<(String, PathBuf, i64)> {
+    // Placeholders are never indexed: their text isn't on disk
+    let mut walk = VaultWalk::new(root);
+    let mut files: Vec<(String, PathBuf, i64)> = VaultWalk::walker(root)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || (!(e.file_type().is_dir()
+                    && e.file_name().to_str().map_or(false, |n| EXCLUDED_DIRS.contains(&n)))
+                    && walk.visit(e) == Visit::Read)
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_indexable(e.path()))
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            if metadata.len() > MAX_FILE_SIZE {
+                return None;
+            }
+            let rel = relative_path(root, e.path())?;
+            Some((rel, e.path().to_path_buf(), modified_ms(&metadata)))
+        })
+        .collect();
+    files.extend(crate::ocr::sidecar::indexed_sidecars(root));
+    files.extend(crate::pdf::index::indexed_pdfs(root));
+    files
+}
+
+/// Text of an indexed document: the file itself, a PDF's extracted text or
+/// an image's OCR sidecar
+fn document_text(root: &Path, rel: &str) -> Option<String> {
+    let path = Path::new(rel);
+    if is_indexable(path) {
+        fs::read_to_string(root.join(rel)).ok()
+    } else if crate::pdf::index::is_pdf(path) {
+        crate::pdf::index::read_text(root, rel)
+    } else {
+        crate::ocr::sidecar::read_text(root, rel)
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(root)
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+/// Run `f` against the in-memory index for `root`, loading or building it first
+fn with_index<T>(root: &Path, f: impl FnOnce(&mut SearchIndex) -> T) -> Result<T, String> {
+    let mut indexes = INDEXES.lock().map_err(|e| format!("Search index lock poisoned: {}", e))?;
+
+    if !indexes.contains_key(root) {
+        let mut index = load_from_disk(root).unwrap_or_else(SearchIndex::new);
+        if index.reconcile(root) > 0 {
+            save_to_disk(root, &index)?;
+        }
+        indexes.insert(root.to_path_buf(), index);
+    }
+
+    let index = indexes.get_mut(root).expect("index inserted above");
+    Ok(f(index))
+}
+
+// --- Public API ---
+
+/// Discard any existing index for the workspace and index every file again
+pub fn rebuild(workspace_path: &str) -> Result<IndexStats, String> {
+    let started = Instant::now();
+    let root = PathBuf::from(workspace_path);
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", workspace_path));
+    }
+
+    let mut index = SearchIndex::new();
+    index.reconcile(&root);
+    save_to_disk(&root, &index)?;
+    let stats = index.stats(started);
+
+    INDEXES
+        .lock()
+        .map_err(|e| format!("Search index lock poisoned: {}", e))?
+        .insert(root, index);
+
+    Ok(stats)
+}
+
+/// Approximate memory held by the loaded indexes
+pub(crate) fn memory_usage() -> usize {
+    INDEXES
+        .lock()
+        .map(|indexes| indexes.values().map(SearchIndex::approx_bytes).sum())
+        .unwrap_or(0)
+}
+
+/// Write the loaded indexes to disk and drop them from memory; they are
+/// loaded again on the next search. Returns the approximate bytes freed.
+pub(crate) fn unload_all() -> usize {
+    let Ok(mut indexes) = INDEXES.lock() else {
+        return 0;
+    };
+    let mut freed = 0;
+    for (root, index) in indexes.drain() {
+        if let Err(e) = save_to_disk(&root, &index) {
+            tracing::warn!("Failed to save search index for {}: {}", root.display(), e);
+        }
+        freed += index.approx_bytes();
+    }
+    freed
+}
+
+/// Re-index a single file, or drop it from the index if it no longer exists
+pub fn update_file(workspace_path: &str, file_path: &str) -> Result<(), String> {
+    let root = PathBuf::from(workspace_path);
+    let path = Path::new(file_path);
+    let rel = relative_path(&root, path)
+        .ok_or_else(|| format!("File is outside the workspace: {}", file_path))?;
+
+    with_index(&root, |index| {
+        let content = if path.is_file() { document_text(&root, &rel) } else { None };
+        let Some(content) = content else {
+            index.remove(&rel);
+            return Ok(());
+        };
+        let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+        index.upsert(&rel, &content, modified_ms(&metadata));
+        Ok(())
+    })?
+}
+
+/// Index text extracted from a non-text file (e.g. by OCR) under that file's path
+pub(crate) fn index_extracted_text(root: &Path, rel: &str, text: &str, modified: i64) -> Result<(), String> {
+    with_index(root, |index| index.upsert(rel, text, modified))
+}
+
+/// Hook for file saves: updates whichever loaded index contains the file.
+/// Workspaces whose index has not been loaded yet are reconciled on load.
+pub fn notify_file_saved(file_path: &str, content: &str) {
+    let path = Path::new(file_path);
+    if !is_indexable(path) {
+        return;
+    }
+    let modified = fs::metadata(path).map(|m| modified_ms(&m)).unwrap_or(0);
+
+    if let Ok(mut indexes) = INDEXES.lock() {
+        for (root, index) in indexes.iter_mut() {
+            if let Some(rel) = relative_path(root, path) {
+                index.upsert(&rel, content, modified);
+            }
+        }
+    }
+}
+
+/// Hook for file deletions: drops the file from any loaded index
+pub fn notify_file_removed(file_path: &str) {
+    let path = Path::new(file_path);
+    if let Ok(mut indexes) = INDEXES.lock() {
+        for (root, index) in indexes.iter_mut() {
+            if let Some(rel) = relative_path(root, path) {
+                let prefix = format!("{}/", rel);
+                let removed: Vec<String> = index
+                    .by_path
+                    .keys()
+                    .filter(|p| **p == rel || p.starts_with(&prefix))
+                    .cloned()
+                    .collect();
+                for p in removed {
+                    index.remove(&p);
+                }
+            }
+        }
+    }
+}
+
+/// Hook for renames and moves: re-keys the file, or a folder's files, in any
+/// loaded index. Moving out of a workspace drops the files from its index.
+pub fn notify_file_renamed(old_path: &str, new_path: &str) {
+    let (old, new) = (Path::new(old_path), Path::new(new_path));
+    if let Ok(mut indexes) = INDEXES.lock() {
+        for (root, index) in indexes.iter_mut() {
+            let Some(old_rel) = relative_path(root, old) else { continue };
+            match relative_path(root, new) {
+                Some(new_rel) => {
+                    index.rename(&old_rel, &new_rel);
+                }
+                None => {
+                    let prefix = format!("{}/", old_rel);
+                    let removed: Vec<String> =
+                        index.by_path.keys().filter(|p| **p == old_rel || p.starts_with(&prefix)).cloned().collect();
+                    for p in removed {
+                        index.remove(&p);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Workspace-relative paths of documents containing every query term, best first
+pub(crate) fn matching_all_terms(root: &Path, query: &str) -> Result<Vec<(String, f64)>, String> {
+    with_index(root, |index| {
+        let terms: HashSet<String> = tokenize(query).into_iter().collect();
+        let mut required: Option<HashSet<u32>> = None;
+        for term in &terms {
+            let docs: HashSet<u32> = index.postings.get(term).map_or_else(HashSet::new, |list| list.iter().map(|p| p.doc).collect());
+            required = Some(match required {
+                Some(current) => current.intersection(&docs).copied().collect(),
+                None => docs,
+            });
+        }
+        let required = required.unwrap_or_default();
+        index
+            .rank(query, usize::MAX)
+            .into_iter()
+            .filter(|(id, _)| required.contains(id))
+            .filter_map(|(id, score)| index.docs.get(&id).map(|d| (d.path.clone(), score)))
+            .collect()
+    })
+}
+
+/// Run a BM25-ranked query and attach a snippet to each hit
+pub fn query(workspace_path: &str, query: &str, limit: usize) -> Result<Vec<RankedResult>, String> {
+    let root = PathBuf::from(workspace_path);
+    let ranked: Vec<(String, f64)> = with_index(&root, |index| {
+        index
+            .rank(query, limit)
+            .into_iter()
+            .filter_map(|(id, score)| index.docs.get(&id).map(|d| (d.path.clone(), score)))
+            .collect()
+    })?;
+
+    let query_terms: HashSet<String> = tokenize(query).into_iter().collect();
+    let mut results = Vec::with_capacity(ranked.len());
+
+    for (rel, score) in ranked {
+        let abs = root.join(&rel);
+        // The file may have been removed by another app since it was indexed
+        let Some(content) = document_text(&root, &rel) else { continue };
+        let (snippet, highlights) = build_snippet(&content, &query_terms);
+        let file_name = abs
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+
+        results.push(RankedResult {
+            file: abs.to_string_lossy().to_string(),
+            file_name,
+            score,
+            snippet,
+            highlights,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Cut a window around the first query hit, with highlight ranges in chars
+fn build_snippet(content: &str, terms: &HashSet<String>) -> (String, Vec<SnippetHighlight>) {
+    let chars: Vec<char> = content.chars().collect();
+    let spans = tokens_with_spans(content);
+    let hits: Vec<(usize, usize)> = spans
+        .iter()
+        .filter(|(token, _, _)| terms.contains(token))
+        .map(|(_, start, end)| (*start, *end))
+        .collect();
+
+    let (window_start, window_end) = match hits.first() {
+        Some(&(start, end)) => (
+            start.saturating_sub(SNIPPET_RADIUS),
+            (end + SNIPPET_RADIUS).min(chars.len()),
+        ),
+        None => (0, (SNIPPET_RADIUS * 2).min(chars.len())),
+    };
+
+    let lead = if window_start > 0 { "…" } else { "" };
+    let tail = if window_end < chars.len() { "…" } else { "" };
+    let offset = lead.chars().count();
+
+    let body: String = chars[window_start..window_end]
+        .iter()
+        .map(|c| if c.is_whitespace() { ' ' } else { *c })
+        .collect();
+
+    let highlights = hits
+        .into_iter()
+        .filter(|&(start, end)| start >= window_start && end <= window_end)
+        .map(|(start, end)| SnippetHighlight {
+            start: start - window_start + offset,
+            end: end - window_start + offset,
+        })
+        .collect();
+
+    (format!("{}{}{}", lead, body, tail), highlights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_lowercases_and_splits() {
+        assert_eq!(tokenize("Hello, World! foo_bar 42"), vec!["hello", "world", "foo", "bar", "42"]);
+    }
+
+    #[test]
+    fn test_rank_prefers_higher_term_frequency() {
+        let mut index = SearchIndex::new();
+        index.upsert("a.md", "rust rust rust and more rust", 1);
+        index.upsert("b.md", "a single mention of rust among many other words here", 1);
+        index.upsert("c.md", "nothing relevant", 1);
+
+        let ranked = index.rank("Rust", 10);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(index.docs[&ranked[0].0].path, "a.md");
+    }
+
+    #[test]
+    fn test_remove_clears_postings() {
+        let mut index = SearchIndex::new();
+        index.upsert("a.md", "unique term", 1);
+        assert!(index.remove("a.md"));
+        assert!(index.postings.is_empty());
+        assert_eq!(index.total_length, 0);
+        assert!(index.rank("unique", 10).is_empty());
+    }
+
+    #[test]
+    fn test_rename_moves_files_and_folders() {
+        let mut index = SearchIndex::new();
+        index.upsert("a.md", "alpha", 1);
+        index.upsert("dir/b.md", "beta", 1);
+        index.upsert("dir/sub/c.md", "gamma", 1);
+        index.upsert("dirt.md", "delta", 1);
+
+        assert_eq!(index.rename("a.md", "renamed.md"), 1);
+        assert_eq!(index.docs[&index.rank("alpha", 10)[0].0].path, "renamed.md");
+
+        assert_eq!(index.rename("dir", "moved"), 2);
+        let mut paths: Vec<&str> = index.by_path.keys().map(String::as_str).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dirt.md", "moved/b.md", "moved/sub/c.md", "renamed.md"]);
+        assert_eq!(index.docs[&index.rank("gamma", 10)[0].0].path, "moved/sub/c.md");
+    }
+
+    #[test]
+    fn test_reconcile_picks_up_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("one.md"), "alpha beta").unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        fs::write(dir.path().join(".lokus").join("hidden.md"), "alpha").unwrap();
+
+        let mut index = SearchIndex::new();
+        assert_eq!(index.reconcile(dir.path()), 1);
+        assert_eq!(index.reconcile(dir.path()), 0);
+
+        fs::remove_file(dir.path().join("one.md")).unwrap();
+        assert_eq!(index.reconcile(dir.path()), 1);
+        assert!(index.docs.is_empty());
+    }
+
+    #[test]
+    fn test_snippet_highlights_match() {
+        let terms: HashSet<String> = ["world".to_string()].into_iter().collect();
+        let (snippet, highlights) = build_snippet("Hello\nworld", &terms);
+        assert_eq!(snippet, "Hello world");
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(highlights[0].start, 6);
+        assert_eq!(highlights[0].end, 11);
+    }
+}
@@ -0,0 +1,539 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use walkdir::WalkDir;
+use tauri::{command, AppHandle, Emitter};
+
+pub mod index;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchOptions {
+    #[serde(rename = "caseSensitive")]
+    pub case_sensitive: Option<bool>,
+    #[serde(rename = "wholeWord")]
+    pub whole_word: Option<bool>,
+    pub regex: Option<bool>,
+    #[serde(rename = "fileTypes")]
+    pub file_types: Option<Vec<String>>,
+    #[serde(rename = "maxResults")]
+    pub max_results: Option<usize>,
+    #[serde(rename = "contextLines")]
+    pub context_lines: Option<usize>,
+    /// Also search the extracted text of indexed PDFs
+    #[serde(rename = "includePdfs")]
+    pub include_pdfs: Option<bool>,
+    /// Only files matching one of these globs (relative to the searched folder)
+    #[serde(default)]
+    pub include: Option<Vec<String>>,
+    /// Skip files matching any of these globs; a folder's name skips all of it
+    #[serde(default)]
+    pub exclude: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    #[serde(rename = "match")]
+    pub match_text: String,
+    pub context: Vec<ContextLine>,
+    /// PDF page the match is on; `line` is then relative to the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextLine {
+    #[serde(rename = "lineNumber")]
+    pub line_number: usize,
+    pub text: String,
+    #[serde(rename = "isMatch")]
+    pub is_match: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub file: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub matches: Vec<SearchMatch>,
+    #[serde(rename = "matchCount")]
+    pub match_count: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: Some(false),
+            whole_word: Some(false),
+            regex: Some(false),
+            file_types: Some(vec!["md".to_string(), "txt".to_string()]),
+            max_results: Some(100),
+            context_lines: Some(2),
+            include_pdfs: Some(false),
+            include: None,
+            exclude: None,
+        }
+    }
+}
+
+/// Files searched per `search:results` event
+const STREAM_BATCH_FILES: usize = 20;
+const STREAM_RESULTS_EVENT: &str = "search:results";
+const STREAM_DONE_EVENT: &str = "search:done";
+
+/// Streaming searches the frontend has asked to stop
+static CANCELLED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchBatch {
+    pub search_id: String,
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSummary {
+    pub search_id: String,
+    pub files: usize,
+    pub matches: usize,
+    /// Stopped at `maxResults` files
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
+/// Build the search regex. Whole-word applies to regex queries too.
+fn build_regex(query: &str, opts: &SearchOptions) -> Result<Regex, String> {
+    let pattern = if opts.regex.unwrap_or(false) { query.to_string() } else { regex::escape(query) };
+    let pattern = if opts.whole_word.unwrap_or(false) { format!(r"\b(?:{})\b", pattern) } else { pattern };
+    regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!opts.case_sensitive.unwrap_or(false))
+        .build()
+        .map_err(|e| format!("Invalid regex pattern: {}", e))
+}
+
+fn build_globs(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        // A bare name like `drafts` or `*.txt` matches at any depth
+        let pattern = if pattern.contains('/') { pattern.trim_start_matches('/').to_string() } else { format!("**/{}", pattern) };
+        let glob = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+        // Excluding or including a folder covers what is inside it
+        if let Ok(glob) = GlobBuilder::new(&format!("{}/**", pattern)).literal_separator(true).build() {
+            builder.add(glob);
+        }
+    }
+    builder.build().map(Some).map_err(|e| format!("Invalid globs: {}", e))
+}
+
+/// Walk `path` and call `on_result` for each file with matches, until it
+/// returns false or `max_results` files have matched. Returns whether the
+/// walk stopped early.
+fn search_tree(
+    path: &Path,
+    regex: &Regex,
+    query: &str,
+    opts: &SearchOptions,
+    mut on_result: impl FnMut(SearchResult) -> bool,
+) -> Result<bool, String> {
+    let file_types = opts.file_types.clone().unwrap_or_else(|| vec!["md".to_string(), "txt".to_string()]);
+    let max_results = opts.max_results.unwrap_or(100);
+    let context_lines = opts.context_lines.unwrap_or(2);
+    let include_pdfs = opts.include_pdfs.unwrap_or(false);
+    let include = build_globs(opts.include.as_deref().unwrap_or_default())?;
+    let exclude = build_globs(opts.exclude.as_deref().unwrap_or_default())?;
+
+    let mut total_results = 0;
+
+    // Walk through directory
+    for entry in WalkDir::new(path)
+        .follow_links(false)
+        .max_depth(10)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if total_results >= max_results {
+            return Ok(true);
+        }
+
+        let file_path = entry.path();
+        
+        // Skip directories
+        if file_path.is_dir() {
+            continue;
+        }
+
+        // Skip build and cache directories
+        let path_str = file_path.to_string_lossy();
+        if path_str.contains("target/") || 
+           path_str.contains("node_modules/") || 
+           path_str.contains(".git/") ||
+           path_str.contains("dist/") ||
+           path_str.contains("build/") ||
+           path_str.contains(".cache/") ||
+           path_str.contains(".next/") ||
+           path_str.contains(".vscode/") ||
+           path_str.contains("__pycache__/") {
+            continue;
+        }
+
+        if include.is_some() || exclude.is_some() {
+            let rel = crate::links::relative_path(path, file_path).unwrap_or_default();
+            if include.as_ref().is_some_and(|globs| !globs.is_match(&rel)) || exclude.as_ref().is_some_and(|globs| globs.is_match(&rel)) {
+                continue;
+            }
+        }
+
+        // PDFs are searched through their cached text, page by page
+        if include_pdfs && crate::pdf::index::is_pdf(file_path) {
+            let pages = crate::links::relative_path(path, file_path)
+                .and_then(|rel| crate::pdf::index::cached_pages(path, &rel))
+                .unwrap_or_default();
+            let file_matches: Vec<SearchMatch> = pages
+                .iter()
+                .flat_map(|page| search_in_text(&page.text, regex, context_lines, Some(page.page)))
+                .collect();
+            if !file_matches.is_empty() {
+                total_results += 1;
+                let keep_going = on_result(SearchResult {
+                    file: file_path.to_string_lossy().to_string(),
+                    file_name: file_path.file_name().and_then(|n| n.to_str()).unwrap_or("Unknown").to_string(),
+                    match_count: file_matches.len(),
+                    matches: file_matches,
+                });
+                if !keep_going {
+                    return Ok(true);
+                }
+            }
+            continue;
+        }
+
+        // Check file extension
+        if let Some(extension) = file_path.extension() {
+            if let Some(ext_str) = extension.to_str() {
+                if !file_types.contains(&ext_str.to_lowercase()) {
+                    continue;
+                }
+            }
+        } else {
+            // Skip files without extensions unless txt is in allowed types
+            if !file_types.contains(&"txt".to_string()) {
+                continue;
+            }
+        }
+
+        // Skip binary files and large files
+        if let Ok(metadata) = file_path.metadata() {
+            // Skip files larger than 10MB
+            if metadata.len() > 10 * 1024 * 1024 {
+                continue;
+            }
+        }
+
+        // Search in file; unreadable files are skipped instead of failing the search
+        if let Ok(file_matches) = search_in_single_file(file_path, regex, query, context_lines) {
+            if !file_matches.is_empty() {
+                let file_name = file_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Unknown")
+                    .to_string();
+
+                total_results += 1;
+                let keep_going = on_result(SearchResult {
+                    file: file_path.to_string_lossy().to_string(),
+                    file_name,
+                    match_count: file_matches.len(),
+                    matches: file_matches,
+                });
+                if !keep_going {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Search for a query across multiple files in a directory
+#[command]
+pub async fn search_in_files(
+    query: String,
+    workspace_path: Option<String>,
+    options: Option<SearchOptions>,
+) -> Result<Vec<SearchResult>, String> {
+    if query.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let opts = options.unwrap_or_default();
+    let search_path = workspace_path.unwrap_or_else(|| ".".to_string());
+    let path = Path::new(&search_path);
+
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", search_path));
+    }
+
+    let regex = build_regex(&query, &opts)?;
+    let mut results = Vec::new();
+    search_tree(path, &regex, &query, &opts, |result| {
+        results.push(result);
+        true
+    })?;
+    Ok(results)
+}
+
+/// Like `search_in_files`, but results are sent as `search:results` events
+/// in batches while the search runs, followed by one `search:done` event
+/// with the summary (which is also returned). `search_cancel` stops it.
+#[command]
+pub async fn search_in_files_stream(
+    app: AppHandle,
+    search_id: String,
+    query: String,
+    workspace_path: String,
+    options: Option<SearchOptions>,
+) -> Result<SearchSummary, String> {
+    let opts = options.unwrap_or_default();
+    let path = PathBuf::from(&workspace_path);
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", workspace_path));
+    }
+    let regex = build_regex(&query, &opts)?;
+
+    let summary = tokio::task::spawn_blocking(move || -> Result<SearchSummary, String> {
+        let mut summary = SearchSummary { search_id: search_id.clone(), files: 0, matches: 0, truncated: false, cancelled: false };
+        let mut batch = Vec::new();
+        let flush = |batch: &mut Vec<SearchResult>| {
+            if !batch.is_empty() {
+                let _ = app.emit(STREAM_RESULTS_EVENT, SearchBatch { search_id: search_id.clone(), results: std::mem::take(batch) });
+            }
+        };
+
+        let is_cancelled = || CANCELLED.lock().is_ok_and(|c| c.contains(&search_id));
+        let stopped = if query.trim().is_empty() {
+            false
+        } else {
+            search_tree(&path, &regex, &query, &opts, |result| {
+                summary.files += 1;
+                summary.matches += result.match_count;
+                batch.push(result);
+                if batch.len() >= STREAM_BATCH_FILES {
+                    flush(&mut batch);
+                }
+                !is_cancelled()
+            })?
+        };
+        flush(&mut batch);
+
+        summary.cancelled = CANCELLED.lock().is_ok_and(|mut c| c.remove(&search_id));
+        summary.truncated = stopped && !summary.cancelled;
+        let _ = app.emit(STREAM_DONE_EVENT, &summary);
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Search failed: {}", e))??;
+    Ok(summary)
+}
+
+/// Stop a streaming search after the file it is on
+#[command]
+pub fn search_cancel(search_id: String) {
+    if let Ok(mut cancelled) = CANCELLED.lock() {
+        cancelled.insert(search_id);
+    }
+}
+
+/// Search within a single file
+fn search_in_single_file(
+    file_path: &Path,
+    regex: &Regex,
+    _query: &str,
+    context_lines: usize,
+) -> Result<Vec<SearchMatch>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(file_path)?;
+    Ok(search_in_text(&content, regex, context_lines, None))
+}
+
+/// Match `regex` line by line in `content`
+fn search_in_text(content: &str, regex: &Regex, context_lines: usize, page: Option<u32>) -> Vec<SearchMatch> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut matches = Vec::new();
+
+    for (line_index, line) in lines.iter().enumerate() {
+        if let Some(regex_match) = regex.find(line) {
+            let line_number = line_index + 1;
+            let column = regex_match.start();
+            let match_text = regex_match.as_str().to_string();
+            
+            // Get context lines
+            let context = get_context_lines(&lines, line_index, context_lines);
+            
+            matches.push(SearchMatch {
+                line: line_number,
+                column,
+                text: line.to_string(),
+                match_text,
+                context,
+                page,
+            });
+        }
+    }
+
+    matches
+}
+
+/// Get context lines around a match
+fn get_context_lines(lines: &[&str], target_line: usize, context_lines: usize) -> Vec<ContextLine> {
+    let start = target_line.saturating_sub(context_lines);
+    let end = std::cmp::min(lines.len() - 1, target_line + context_lines);
+    
+    let mut context = Vec::new();
+    
+    for i in start..=end {
+        if i < lines.len() {
+            context.push(ContextLine {
+                line_number: i + 1,
+                text: lines[i].to_string(),
+                is_match: i == target_line,
+            });
+        }
+    }
+    
+    context
+}
+
+/// Search within a specific file (for single file search)
+#[command]
+pub async fn search_in_file(
+    file_path: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<SearchMatch>, String> {
+    if query.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let opts = options.unwrap_or_default();
+    let path = Path::new(&file_path);
+
+    if !path.exists() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    let context_lines = opts.context_lines.unwrap_or(2);
+    let regex = build_regex(&query, &opts)?;
+
+    search_in_single_file(path, &regex, &query, context_lines)
+        .map_err(|e| format!("Error searching file: {}", e))
+}
+
+/// Get file content with line numbers (utility for editor integration)
+#[command]
+pub async fn get_file_content_with_lines(file_path: String) -> Result<Vec<String>, String> {
+    let path = Path::new(&file_path);
+    
+    if !path.exists() {
+        return Err(format!("File does not exist: {}", file_path));
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            let lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
+            Ok(lines)
+        }
+        Err(e) => Err(format!("Error reading file: {}", e))
+    }
+}
+
+/// Build search index for faster searching
+#[command]
+pub async fn build_search_index(workspace_path: String) -> Result<String, String> {
+    let stats = index::rebuild(&workspace_path)?;
+    Ok(format!(
+        "Search index built for workspace: {} ({} documents)",
+        workspace_path, stats.documents
+    ))
+}
+
+/// Rebuild the persistent full-text index from scratch
+#[command]
+pub async fn search_index_rebuild(workspace_path: String) -> Result<index::IndexStats, String> {
+    index::rebuild(&workspace_path)
+}
+
+/// Re-index a single file (or drop it if it was deleted)
+#[command]
+pub async fn search_index_update_file(workspace_path: String, file_path: String) -> Result<(), String> {
+    index::update_file(&workspace_path, &file_path)
+}
+
+/// BM25-ranked full-text query against the workspace index
+#[command]
+pub async fn search_query_ranked(
+    workspace_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<index::RankedResult>, String> {
+    if query.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    index::query(&workspace_path, &query, limit.unwrap_or(50))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_tree_with_globs_and_whole_word_regex() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("drafts")).unwrap();
+        fs::write(dir.path().join("a.md"), "cat catalog\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "cat\n").unwrap();
+        fs::write(dir.path().join("drafts/c.md"), "cat\n").unwrap();
+
+        let opts = SearchOptions {
+            regex: Some(true),
+            whole_word: Some(true),
+            exclude: Some(vec!["drafts".to_string()]),
+            include: Some(vec!["*.md".to_string()]),
+            ..Default::default()
+        };
+        let regex = build_regex("ca.", &opts).unwrap();
+        let mut found = Vec::new();
+        let stopped = search_tree(dir.path(), &regex, "ca.", &opts, |result| {
+            found.push(result);
+            true
+        })
+        .unwrap();
+        assert!(!stopped);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].file_name, "a.md");
+        assert_eq!(found[0].match_count, 1);
+
+        let all = SearchOptions::default();
+        let mut files = 0;
+        let stopped = search_tree(dir.path(), &build_regex("cat", &all).unwrap(), "cat", &all, |_| {
+            files += 1;
+            false
+        })
+        .unwrap();
+        assert!(stopped && files == 1);
+    }
+}
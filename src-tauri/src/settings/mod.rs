@@ -0,0 +1,199 @@
+//! Layered settings. Each vault can override global settings (the
+//! `.settings.dat` store) in `<workspace>/.lokus/settings.json`, key by key.
+//! Object values are merged field by field, so a workspace can change one
+//! part of a global object and inherit the rest. Every change is sent to all
+//! windows as `settings:changed`.
+
+pub mod profile;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Wry};
+use tauri_plugin_store::{Store, StoreBuilder};
+
+const WORKSPACE_SETTINGS_FILE: &str = "settings.json";
+/// App state rather than preferences; a workspace can't override these
+const GLOBAL_ONLY: &[&str] = &["last_workspace_path", "plugin_registry"];
+
+/// Serializes read-modify-write of workspace files
+static WRITE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsScope {
+    Global,
+    Workspace,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChange {
+    pub scope: SettingsScope,
+    pub key: String,
+    /// The key's effective value for `workspace`, or the global value
+    pub value: Option<Value>,
+    pub workspace: Option<String>,
+}
+
+/// `overlay` on top of `base`: objects merge recursively, anything else in
+/// `overlay` replaces `base`
+pub fn merge(base: &Value, overlay: &Value) -> Value {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            let mut merged = base.clone();
+            for (key, value) in overlay {
+                let value = match base.get(key) {
+                    Some(existing) => merge(existing, value),
+                    None => value.clone(),
+                };
+                merged.insert(key.clone(), value);
+            }
+            Value::Object(merged)
+        }
+        _ => overlay.clone(),
+    }
+}
+
+fn workspace_settings_path(workspace: &Path) -> PathBuf {
+    workspace.join(".lokus").join(WORKSPACE_SETTINGS_FILE)
+}
+
+/// A workspace's overrides; a missing or broken file overrides nothing
+pub fn workspace_settings(workspace: &Path) -> Map<String, Value> {
+    fs::read_to_string(workspace_settings_path(workspace))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_workspace_settings(workspace: &Path, settings: &Map<String, Value>) -> Result<(), String> {
+    let path = workspace_settings_path(workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write workspace settings: {}", e))
+}
+
+fn global_store(app: &AppHandle) -> Result<Arc<Store<Wry>>, String> {
+    let store = StoreBuilder::new(app, PathBuf::from(".settings.dat"))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    Ok(store)
+}
+
+/// The open workspace when the caller didn't name one
+pub(crate) fn current_workspace(app: &AppHandle) -> Option<PathBuf> {
+    global_store(app)
+        .ok()?
+        .get("last_workspace_path")
+        .and_then(|v| v.as_str().map(PathBuf::from))
+}
+
+/// `key` as it applies in `workspace`: the global value with the
+/// workspace's override merged over it
+pub fn effective(app: &AppHandle, workspace: Option<&Path>, key: &str) -> Option<Value> {
+    let global = global_store(app).ok().and_then(|store| store.get(key));
+    if GLOBAL_ONLY.contains(&key) {
+        return global;
+    }
+    let overlay = workspace.and_then(|workspace| workspace_settings(workspace).remove(key));
+    match (global, overlay) {
+        (Some(global), Some(overlay)) => Some(merge(&global, &overlay)),
+        (global, overlay) => overlay.or(global),
+    }
+}
+
+// --- Tauri Commands ---
+
+/// A setting's value in `workspace`, or in the open workspace when not given
+#[tauri::command]
+pub fn settings_get_effective(app: AppHandle, key: String, workspace: Option<String>) -> Result<Option<Value>, String> {
+    let workspace = workspace.map(PathBuf::from).or_else(|| current_workspace(&app));
+    Ok(effective(&app, workspace.as_deref(), &key))
+}
+
+/// What a workspace overrides, for showing which settings differ per vault
+#[tauri::command]
+pub fn settings_get_workspace_overrides(app: AppHandle, workspace: Option<String>) -> Result<Map<String, Value>, String> {
+    let workspace = workspace
+        .map(PathBuf::from)
+        .or_else(|| current_workspace(&app))
+        .ok_or_else(|| "No workspace is open".to_string())?;
+    Ok(workspace_settings(&workspace))
+}
+
+/// Set `key` at `scope`; a `null` value removes it, so a workspace falls
+/// back to the global value
+#[tauri::command]
+pub fn settings_set(
+    app: AppHandle,
+    scope: SettingsScope,
+    key: String,
+    value: Value,
+    workspace: Option<String>,
+) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("Setting key cannot be empty".to_string());
+    }
+    let workspace = workspace.map(PathBuf::from).or_else(|| current_workspace(&app));
+
+    match scope {
+        SettingsScope::Global => {
+            let store = global_store(&app)?;
+            if value.is_null() {
+                store.delete(&key);
+            } else {
+                store.set(key.clone(), value);
+            }
+            store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+        }
+        SettingsScope::Workspace => {
+            if GLOBAL_ONLY.contains(&key.as_str()) {
+                return Err(format!("'{}' can only be set globally", key));
+            }
+            let workspace = workspace.as_deref().ok_or_else(|| "No workspace is open".to_string())?;
+            let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let mut settings = workspace_settings(workspace);
+            if value.is_null() {
+                settings.remove(&key);
+            } else {
+                settings.insert(key.clone(), value);
+            }
+            save_workspace_settings(workspace, &settings)?;
+        }
+    }
+
+    let change = SettingsChange {
+        scope,
+        value: effective(&app, workspace.as_deref(), &key),
+        key,
+        workspace: workspace.map(|w| w.to_string_lossy().to_string()),
+    };
+    app.emit("settings:changed", change).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_overrides_nested_fields() {
+        let global = json!({ "theme": "dark", "editor": { "font": "Inter", "size": 14 } });
+        let workspace = json!({ "editor": { "size": 16 }, "attachments": "assets" });
+
+        assert_eq!(
+            merge(&global, &workspace),
+            json!({ "theme": "dark", "editor": { "font": "Inter", "size": 16 }, "attachments": "assets" })
+        );
+        // Non-objects replace outright
+        assert_eq!(merge(&json!(["a", "b"]), &json!(["c"])), json!(["c"]));
+        assert_eq!(merge(&json!({ "a": 1 }), &json!("flat")), json!("flat"));
+    }
+}
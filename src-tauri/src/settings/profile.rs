@@ -0,0 +1,325 @@
+//! Settings profiles: the user's setup in one zip, for a new machine or a
+//! dotfiles repo.
+//!
+//! - `profile.json`: what the archive holds
+//! - `settings.json`: global preferences from `.settings.dat`
+//! - `config.json`: the app config, keybindings included
+//! - `themes/`: custom themes
+//! - `plugins.json`: enabled plugins, their permissions and where they were
+//!   installed from, plus their settings if asked for
+//!
+//! Plugins themselves aren't bundled; import reports the ones to reinstall.
+
+use super::merge;
+use crate::plugins::{get_plugin_settings, get_plugins_directory, read_install_log, save_plugin_settings_internal};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreBuilder;
+
+const FORMAT_VERSION: u32 = 1;
+/// Per-machine state that doesn't belong in a profile
+const MACHINE_KEYS: &[&str] = &["last_workspace_path", "plugin_settings"];
+const MACHINE_KEY_PREFIXES: &[&str] = &["session_state_"];
+const MAX_ENTRY_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Imported values replace existing ones
+    Overwrite,
+    /// Imported values are merged over existing ones, objects field by field
+    Merge,
+    /// Only settings and themes missing here are added
+    KeepExisting,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileManifest {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub app_version: String,
+    pub settings: usize,
+    pub themes: usize,
+    pub plugins: usize,
+    pub includes_plugin_settings: bool,
+}
+
+/// A plugin of the profile, with enough to find it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePlugin {
+    pub name: String,
+    pub version: Option<String>,
+    pub registry_id: Option<String>,
+    pub source_url: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileImportReport {
+    pub settings: usize,
+    pub themes: usize,
+    pub enabled_plugins: usize,
+    /// Enabled in the profile but not installed here
+    pub missing_plugins: Vec<ProfilePlugin>,
+}
+
+fn is_machine_key(key: &str) -> bool {
+    MACHINE_KEYS.contains(&key) || MACHINE_KEY_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// The value to store for an imported key, or `None` to leave it alone
+pub fn resolve(existing: Option<&Value>, imported: &Value, strategy: MergeStrategy) -> Option<Value> {
+    let resolved = match (existing, strategy) {
+        (None, _) => imported.clone(),
+        (Some(_), MergeStrategy::KeepExisting) => return None,
+        (Some(_), MergeStrategy::Overwrite) => imported.clone(),
+        (Some(existing), MergeStrategy::Merge) => merge(existing, imported),
+    };
+    (Some(&resolved) != existing).then_some(resolved)
+}
+
+fn app_config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to find app data directory: {}", e))?;
+    Ok(dir.join("Lokus").join("config.json"))
+}
+
+fn themes_dir() -> Result<PathBuf, String> {
+    crate::theme::get_themes_directory().map_err(|e| format!("Failed to access themes directory: {}", e))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize profile: {}", e))
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<String>, String> {
+    let entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", name, e)),
+    };
+    let mut text = String::new();
+    entry
+        .take(MAX_ENTRY_BYTES)
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(Some(text))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<T>, String> {
+    match read_entry(archive, name)? {
+        Some(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| format!("Invalid {} in profile: {}", name, e)),
+        None => Ok(None),
+    }
+}
+
+fn profile_plugins(include_settings: bool, app: &AppHandle) -> Result<Vec<ProfilePlugin>, String> {
+    let settings = get_plugin_settings(app)?;
+    let plugins_dir = PathBuf::from(get_plugins_directory()?);
+    Ok(settings
+        .enabled_plugins
+        .iter()
+        .map(|name| {
+            let log = read_install_log(&plugins_dir.join(name));
+            ProfilePlugin {
+                name: name.clone(),
+                version: log.as_ref().map(|l| l.version.clone()),
+                registry_id: log.as_ref().and_then(|l| l.registry_id.clone()),
+                source_url: log.as_ref().and_then(|l| l.source_url.clone()),
+                permissions: settings.plugin_permissions.get(name).cloned().unwrap_or_default(),
+                settings: include_settings.then(|| settings.plugin_settings.get(name).cloned()).flatten(),
+            }
+        })
+        .collect())
+}
+
+// --- Tauri Commands ---
+
+/// Write the global settings, themes, keybindings and enabled plugins to a
+/// zip at `dest`; plugin settings only with `include_plugin_settings`
+#[tauri::command]
+pub fn settings_export_profile(app: AppHandle, dest: String, include_plugin_settings: Option<bool>) -> Result<ProfileManifest, String> {
+    use zip::write::SimpleFileOptions;
+
+    let include_plugin_settings = include_plugin_settings.unwrap_or(false);
+    let store = StoreBuilder::new(&app, PathBuf::from(".settings.dat"))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    let settings: Map<String, Value> = store.entries().into_iter().filter(|(key, _)| !is_machine_key(key)).collect();
+
+    let config = fs::read_to_string(app_config_path(&app)?).ok();
+    let themes: Vec<PathBuf> = fs::read_dir(themes_dir()?)
+        .map_err(|e| format!("Failed to read themes directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect();
+    let plugins = profile_plugins(include_plugin_settings, &app)?;
+
+    let manifest = ProfileManifest {
+        format_version: FORMAT_VERSION,
+        exported_at: Utc::now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        settings: settings.len(),
+        themes: themes.len(),
+        plugins: plugins.len(),
+        includes_plugin_settings: include_plugin_settings,
+    };
+
+    let dest = PathBuf::from(dest);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file = File::create(&dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: &str, content: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| format!("Failed to write profile: {}", e))?;
+        zip.write_all(content).map_err(|e| format!("Failed to write profile: {}", e))
+    };
+    add("profile.json", to_json(&manifest)?.as_bytes())?;
+    add("settings.json", to_json(&settings)?.as_bytes())?;
+    add("plugins.json", to_json(&plugins)?.as_bytes())?;
+    if let Some(config) = config {
+        add("config.json", config.as_bytes())?;
+    }
+    for path in &themes {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let content = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        add(&format!("themes/{}", name), &content)?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write profile: {}", e))?;
+    Ok(manifest)
+}
+
+/// Apply a profile from `src` with `merge_strategy`
+#[tauri::command]
+pub fn settings_import_profile(app: AppHandle, src: String, merge_strategy: MergeStrategy) -> Result<ProfileImportReport, String> {
+    let file = File::open(&src).map_err(|e| format!("Failed to open {}: {}", src, e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Not a settings profile: {}", e))?;
+    let manifest: ProfileManifest = read_json(&mut archive, "profile.json")?.ok_or_else(|| "Not a settings profile".to_string())?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err("This profile was exported by a newer version of Lokus".to_string());
+    }
+    let mut report = ProfileImportReport::default();
+
+    // Global settings
+    if let Some(settings) = read_json::<Map<String, Value>>(&mut archive, "settings.json")? {
+        let store = StoreBuilder::new(&app, PathBuf::from(".settings.dat"))
+            .build()
+            .map_err(|e| format!("Failed to open settings: {}", e))?;
+        let _ = store.reload();
+        for (key, value) in settings.into_iter().filter(|(key, _)| !is_machine_key(key)) {
+            if let Some(resolved) = resolve(store.get(&key).as_ref(), &value, merge_strategy) {
+                store.set(key, resolved);
+                report.settings += 1;
+            }
+        }
+        store.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+
+    // App config, keybindings included
+    if let Some(imported) = read_json::<Value>(&mut archive, "config.json")? {
+        let path = app_config_path(&app)?;
+        let existing = fs::read_to_string(&path).ok().and_then(|c| serde_json::from_str::<Value>(&c).ok());
+        if let Some(config) = resolve(existing.as_ref(), &imported, merge_strategy) {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            fs::write(&path, to_json(&config)?).map_err(|e| format!("Failed to write app config: {}", e))?;
+            report.settings += 1;
+        }
+    }
+
+    // Themes, by file name only so entries can't point outside the folder
+    let themes_dir = themes_dir()?;
+    let theme_entries: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.starts_with("themes/") && name.ends_with(".json"))
+        .map(|name| name.to_string())
+        .collect();
+    for entry in theme_entries {
+        let Some(file_name) = Path::new(&entry).file_name() else {
+            continue;
+        };
+        let target = themes_dir.join(file_name);
+        if target.exists() && merge_strategy == MergeStrategy::KeepExisting {
+            continue;
+        }
+        if let Some(content) = read_entry(&mut archive, &entry)? {
+            fs::write(&target, content).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+            report.themes += 1;
+        }
+    }
+
+    // Plugins: enable the ones installed here, report the rest
+    if let Some(plugins) = read_json::<Vec<ProfilePlugin>>(&mut archive, "plugins.json")? {
+        let plugins_dir = PathBuf::from(get_plugins_directory()?);
+        let mut settings = get_plugin_settings(&app)?;
+        if merge_strategy == MergeStrategy::Overwrite {
+            settings.enabled_plugins.clear();
+        }
+        let existing_settings = settings.plugin_settings.clone();
+        for plugin in plugins {
+            if !plugins_dir.join(&plugin.name).is_dir() {
+                report.missing_plugins.push(plugin);
+                continue;
+            }
+            if !settings.enabled_plugins.contains(&plugin.name) {
+                settings.enabled_plugins.push(plugin.name.clone());
+                report.enabled_plugins += 1;
+            }
+            if merge_strategy != MergeStrategy::KeepExisting || !settings.plugin_permissions.contains_key(&plugin.name) {
+                settings.plugin_permissions.insert(plugin.name.clone(), plugin.permissions.clone());
+            }
+            if let Some(imported) = &plugin.settings {
+                if let Some(resolved) = resolve(existing_settings.get(&plugin.name), imported, merge_strategy) {
+                    settings.plugin_settings.insert(plugin.name.clone(), resolved);
+                }
+            }
+        }
+        save_plugin_settings_internal(&app, &settings)?;
+    }
+
+    let _ = app.emit("settings:profile-imported", &report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_strategies() {
+        let existing = json!({ "font": "Inter", "size": 14 });
+        let imported = json!({ "size": 16 });
+
+        assert_eq!(resolve(Some(&existing), &imported, MergeStrategy::Overwrite), Some(json!({ "size": 16 })));
+        assert_eq!(
+            resolve(Some(&existing), &imported, MergeStrategy::Merge),
+            Some(json!({ "font": "Inter", "size": 16 }))
+        );
+        assert_eq!(resolve(Some(&existing), &imported, MergeStrategy::KeepExisting), None);
+        assert_eq!(resolve(None, &imported, MergeStrategy::KeepExisting), Some(imported.clone()));
+        // Nothing to write when the value is already there
+        assert_eq!(resolve(Some(&imported), &imported, MergeStrategy::Overwrite), None);
+        assert!(is_machine_key("session_state_abc") && !is_machine_key("theme"));
+    }
+}
@@ -0,0 +1,308 @@
+//! Smart folders (saved searches).
+//!
+//! A smart folder is a named query stored in `.lokus/smart-folders.json`,
+//! combining full-text terms (search index), tags, frontmatter conditions and
+//! date ranges (metadata cache). A duration range turns it into a folder of
+//! audio and video attachments instead of notes. Results are cached per folder and reused until
+//! the metadata cache changes; the file watcher emits `smart-folders-changed`
+//! after each batch of changes so the sidebar knows to re-evaluate.
+
+use crate::frontmatter::{self, Condition};
+use crate::metadata_cache::{self, MetadataFilter};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const CHANGED_EVENT: &str = "smart-folders-changed";
+const STORE_FILE: &str = "smart-folders.json";
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Evaluated results keyed by (workspace, folder name), with the cache generation they were computed at
+static RESULTS: Lazy<Mutex<HashMap<(PathBuf, String), (u64, Vec<SmartFolderItem>)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    /// Best full-text match first; falls back to most recently modified
+    Relevance,
+    #[default]
+    Modified,
+    Created,
+    Title,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SmartFolderQuery {
+    /// Full-text terms; every term must appear
+    pub text: Option<String>,
+    /// Every tag must be present (nested tags count)
+    pub tags: Vec<String>,
+    /// None of these tags may be present
+    pub exclude_tags: Vec<String>,
+    pub conditions: Vec<Condition>,
+    pub folder: Option<String>,
+    /// Milliseconds since the epoch
+    pub modified_after: Option<i64>,
+    pub modified_before: Option<i64>,
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+    /// Relative window, e.g. 7 for "modified in the last week"
+    pub modified_within_days: Option<u32>,
+    /// Media length bounds; with either set, the folder lists audio and video
+    /// files of that length instead of notes
+    pub min_duration_ms: Option<i64>,
+    pub max_duration_ms: Option<i64>,
+    pub sort: SortOrder,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartFolder {
+    pub name: String,
+    pub query: SmartFolderQuery,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartFolderItem {
+    pub path: String,
+    pub title: Option<String>,
+    pub modified: Option<i64>,
+    pub tags: Vec<String>,
+    pub duration_ms: Option<i64>,
+}
+
+fn store_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(STORE_FILE)
+}
+
+fn load(root: &Path) -> Vec<SmartFolder> {
+    fs::read_to_string(store_path(root))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(root: &Path, folders: &[SmartFolder]) -> Result<(), String> {
+    let path = store_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(folders).map_err(|e| format!("Failed to serialize smart folders: {}", e))?;
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write smart folders: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Failed to save smart folders: {}", e))
+}
+
+fn has_tag(tags: &[String], wanted: &str) -> bool {
+    let wanted = wanted.trim_start_matches('#').to_lowercase();
+    let nested = format!("{}/", wanted);
+    tags.iter().map(|t| t.to_lowercase()).any(|t| t == wanted || t.starts_with(&nested))
+}
+
+pub fn evaluate(root: &Path, query: &SmartFolderQuery) -> Result<Vec<SmartFolderItem>, String> {
+    let modified_after = match query.modified_within_days {
+        Some(days) => {
+            let window = chrono::Utc::now().timestamp_millis() - i64::from(days) * DAY_MS;
+            Some(query.modified_after.map_or(window, |after| after.max(window)))
+        }
+        None => query.modified_after,
+    };
+    let notes = metadata_cache::query(
+        root,
+        &MetadataFilter {
+            folder: query.folder.clone(),
+            tag: query.tags.first().cloned(),
+            modified_after,
+            modified_before: query.modified_before,
+            min_duration_ms: query.min_duration_ms,
+            max_duration_ms: query.max_duration_ms,
+            ..Default::default()
+        },
+    )?;
+
+    // Full-text hits as absolute path -> score
+    let text_scores: Option<HashMap<String, f64>> = match query.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        Some(text) => Some(
+            crate::search::index::matching_all_terms(root, text)?
+                .into_iter()
+                .map(|(rel, score)| (root.join(rel).to_string_lossy().to_string(), score))
+                .collect(),
+        ),
+        None => None,
+    };
+
+    let media = query.min_duration_ms.is_some() || query.max_duration_ms.is_some();
+    let mut matched: Vec<(f64, Option<i64>, SmartFolderItem)> = notes
+        .into_iter()
+        .filter(|note| if media { note.duration_ms.is_some() } else { crate::links::is_note(&note.path) })
+        .filter(|note| query.tags.iter().all(|t| has_tag(&note.tags, t)))
+        .filter(|note| !query.exclude_tags.iter().any(|t| has_tag(&note.tags, t)))
+        .filter(|note| query.created_after.map_or(true, |after| note.created.map_or(false, |c| c >= after)))
+        .filter(|note| query.created_before.map_or(true, |before| note.created.map_or(false, |c| c <= before)))
+        .filter(|note| {
+            query.conditions.is_empty()
+                || note
+                    .frontmatter
+                    .as_ref()
+                    .map_or(false, |fm| query.conditions.iter().all(|c| frontmatter::matches(fm, c)))
+        })
+        .filter_map(|note| {
+            let score = match &text_scores {
+                Some(scores) => *scores.get(&note.path)?,
+                None => 0.0,
+            };
+            Some((
+                score,
+                note.created,
+                SmartFolderItem {
+                    path: note.path,
+                    title: note.title,
+                    modified: note.modified,
+                    tags: note.tags,
+                    duration_ms: note.duration_ms,
+                },
+            ))
+        })
+        .collect();
+
+    match query.sort {
+        SortOrder::Relevance if text_scores.is_some() => {
+            matched.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal))
+        }
+        SortOrder::Relevance | SortOrder::Modified => matched.sort_by(|a, b| b.2.modified.cmp(&a.2.modified)),
+        SortOrder::Created => matched.sort_by(|a, b| b.1.cmp(&a.1)),
+        SortOrder::Title => matched.sort_by(|a, b| {
+            let title = |item: &SmartFolderItem| item.title.clone().unwrap_or_default().to_lowercase();
+            title(&a.2).cmp(&title(&b.2))
+        }),
+    }
+
+    let mut items: Vec<SmartFolderItem> = matched.into_iter().map(|(_, _, item)| item).collect();
+    if let Some(limit) = query.limit {
+        items.truncate(limit);
+    }
+    Ok(items)
+}
+
+/// Drop every cached result; folders are evaluated again when next opened
+pub(crate) fn clear_cache() -> usize {
+    RESULTS.lock().map(|mut results| results.drain().count()).unwrap_or(0)
+}
+
+fn forget(root: &Path, name: &str) {
+    if let Ok(mut results) = RESULTS.lock() {
+        results.retain(|(r, n), _| !(r == root && n.eq_ignore_ascii_case(name)));
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Create or replace a smart folder
+#[tauri::command]
+pub fn smart_folder_create(workspace_path: String, name: String, query: SmartFolderQuery) -> Result<SmartFolder, String> {
+    let root = PathBuf::from(&workspace_path);
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Smart folder name cannot be empty".to_string());
+    }
+
+    let mut folders = load(&root);
+    folders.retain(|f| !f.name.eq_ignore_ascii_case(&name));
+    let folder = SmartFolder {
+        name: name.clone(),
+        query,
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    folders.push(folder.clone());
+    save(&root, &folders)?;
+    forget(&root, &name);
+    Ok(folder)
+}
+
+#[tauri::command]
+pub fn smart_folder_list(workspace_path: String) -> Result<Vec<SmartFolder>, String> {
+    Ok(load(Path::new(&workspace_path)))
+}
+
+#[tauri::command]
+pub fn smart_folder_delete(workspace_path: String, name: String) -> Result<(), String> {
+    let root = PathBuf::from(&workspace_path);
+    let mut folders = load(&root);
+    let before = folders.len();
+    folders.retain(|f| !f.name.eq_ignore_ascii_case(&name));
+    if folders.len() == before {
+        return Err(format!("Smart folder '{}' not found", name));
+    }
+    save(&root, &folders)?;
+    forget(&root, &name);
+    Ok(())
+}
+
+/// Notes currently matching a smart folder, served from cache while nothing changed
+#[tauri::command]
+pub fn smart_folder_evaluate(workspace_path: String, name: String) -> Result<Vec<SmartFolderItem>, String> {
+    let root = PathBuf::from(&workspace_path);
+    let folder = load(&root)
+        .into_iter()
+        .find(|f| f.name.eq_ignore_ascii_case(&name))
+        .ok_or_else(|| format!("Smart folder '{}' not found", name))?;
+
+    let key = (root.clone(), folder.name.to_lowercase());
+    let generation = metadata_cache::generation();
+    if let Some((cached_at, items)) = RESULTS.lock().ok().and_then(|r| r.get(&key).cloned()) {
+        // Relative date windows move with the clock, so they are always re-run
+        if cached_at == generation && folder.query.modified_within_days.is_none() {
+            return Ok(items);
+        }
+    }
+
+    let items = evaluate(&root, &folder.query)?;
+    // Evaluation may itself reconcile the cache; record the generation it saw
+    let generation = metadata_cache::generation();
+    if let Ok(mut results) = RESULTS.lock() {
+        results.insert(key, (generation, items.clone()));
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::Operator;
+    use serde_json::json;
+
+    #[test]
+    fn test_evaluate_combines_text_tags_and_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join("a.md"), "---\nstatus: open\n---\nRust borrow checker notes #project/lokus").unwrap();
+        fs::write(root.join("b.md"), "---\nstatus: done\n---\nRust borrow notes #project/lokus").unwrap();
+        fs::write(root.join("c.md"), "---\nstatus: open\n---\nPython notes #project/lokus").unwrap();
+        fs::write(root.join("d.md"), "---\nstatus: open\n---\nRust borrow checker #archive").unwrap();
+
+        let query = SmartFolderQuery {
+            text: Some("rust borrow".into()),
+            tags: vec!["project".into()],
+            conditions: vec![Condition { field: "status".into(), op: Operator::Eq, value: json!("open") }],
+            sort: SortOrder::Relevance,
+            ..Default::default()
+        };
+        let items = evaluate(&root, &query).unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].path.ends_with("a.md"));
+
+        let workspace = root.to_string_lossy().to_string();
+        smart_folder_create(workspace.clone(), "Open Rust".into(), query).unwrap();
+        assert_eq!(smart_folder_list(workspace.clone()).unwrap().len(), 1);
+        assert_eq!(smart_folder_evaluate(workspace.clone(), "open rust".into()).unwrap().len(), 1);
+        smart_folder_delete(workspace.clone(), "Open Rust".into()).unwrap();
+        assert!(smart_folder_evaluate(workspace, "Open Rust".into()).is_err());
+    }
+}
@@ -0,0 +1,422 @@
+//! Workspace statistics and writing analytics.
+//!
+//! Word, character and link counts are measured whenever the metadata cache
+//! stores a note, and the cache logs each change in a note's word count under
+//! the day it happened. Summaries, per-note stats and writing history are
+//! computed from the cache, so dashboards and heatmaps don't read every note.
+
+use crate::metadata_cache;
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+const TOP_LIMIT: usize = 10;
+const WORDS_PER_MINUTE: usize = 230;
+const DEFAULT_HISTORY_DAYS: i64 = 365;
+const MAX_HISTORY_DAYS: i64 = 3660;
+
+/// What is measured from a note's body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct TextStats {
+    pub words: usize,
+    /// Characters other than whitespace
+    pub characters: usize,
+    /// Links to other notes and attachments
+    pub links: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteStats {
+    pub path: String,
+    pub title: Option<String>,
+    pub words: usize,
+    pub characters: usize,
+    pub links: usize,
+    pub backlinks: usize,
+    pub links_per_thousand_words: f64,
+    pub reading_minutes: usize,
+    pub size: u64,
+    pub created: Option<i64>,
+    pub modified: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSize {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteWords {
+    pub path: String,
+    pub title: Option<String>,
+    pub words: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultSummary {
+    pub workspace: String,
+    pub notes: usize,
+    /// Files other than notes
+    pub attachments: usize,
+    pub folders: usize,
+    /// Encrypted notes, which aren't included in the counts
+    pub encrypted_notes: usize,
+    pub total_words: usize,
+    pub total_characters: usize,
+    pub total_links: usize,
+    pub total_size: u64,
+    pub average_words: usize,
+    pub links_per_thousand_words: f64,
+    pub words_today: i64,
+    pub notes_created_today: usize,
+    /// Consecutive days with words written, up to today (or yesterday)
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub largest_files: Vec<FileSize>,
+    pub longest_notes: Vec<NoteWords>,
+}
+
+/// Dates are `YYYY-MM-DD`, inclusive; the default is the last year
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatsRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryDay {
+    pub date: NaiveDate,
+    pub words_added: i64,
+    pub words_removed: i64,
+    pub notes_edited: i64,
+    pub notes_created: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritingHistory {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Every day of the range, oldest first
+    pub days: Vec<HistoryDay>,
+    pub total_words_added: i64,
+    pub total_words_removed: i64,
+    pub notes_created: usize,
+    pub active_days: usize,
+}
+
+// --- Measuring ---
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF)
+}
+
+/// Words in `text`. Markdown punctuation on its own isn't a word, and CJK
+/// characters count one word each since they aren't separated by spaces.
+pub(crate) fn count_words(text: &str) -> usize {
+    let mut words = 0;
+    for token in text.split_whitespace() {
+        let mut in_word = false;
+        for c in token.chars() {
+            if is_cjk(c) {
+                words += 1;
+                in_word = false;
+            } else if c.is_alphanumeric() && !in_word {
+                words += 1;
+                in_word = true;
+            }
+        }
+    }
+    words
+}
+
+/// Counts for a note body (without frontmatter)
+pub(crate) fn measure(body: &str) -> TextStats {
+    TextStats {
+        words: count_words(body),
+        characters: body.chars().filter(|c| !c.is_whitespace()).count(),
+        links: crate::links::parse_links(body).len(),
+    }
+}
+
+/// Local calendar day of a timestamp in milliseconds
+pub(crate) fn local_day(ms: i64) -> NaiveDate {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.with_timezone(&Local).date_naive())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
+
+/// (current, longest) runs of consecutive days. The current run may end
+/// yesterday, so it isn't lost before the first words of the day.
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days.iter().filter(|d| **d <= today) {
+        run = match previous {
+            Some(p) if *day - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let mut current = 0;
+    let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+    (current, longest)
+}
+
+fn per_thousand(links: usize, words: usize) -> f64 {
+    if words == 0 {
+        return 0.0;
+    }
+    (links as f64 * 1000.0 / words as f64 * 10.0).round() / 10.0
+}
+
+fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+// --- Computing ---
+
+fn workspace_or_current(app: &AppHandle, workspace: Option<String>) -> Result<PathBuf, String> {
+    workspace
+        .map(PathBuf::from)
+        .or_else(|| crate::settings::current_workspace(app))
+        .filter(|root| root.is_dir())
+        .ok_or_else(|| "No workspace is open".to_string())
+}
+
+fn note_stats(root: &Path, path: &Path) -> Result<NoteStats, String> {
+    let rel = crate::links::relative_path(root, path).ok_or_else(|| format!("{} is outside the workspace", path.display()))?;
+    let absolute = root.join(&rel).to_string_lossy().to_string();
+    let cached = metadata_cache::note_counts(root)?.into_iter().find(|n| n.path == absolute);
+
+    // Notes the cache can't measure (encrypted, or not cached yet) are read,
+    // which works for encrypted notes while the vault is unlocked
+    let (title, text) = match cached.as_ref().and_then(|n| n.text.map(|t| (n.title.clone(), t))) {
+        Some(found) => found,
+        None => {
+            let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let content = crate::encryption::decrypt_for_read(&absolute, content)?;
+            let meta = metadata_cache::extract(&rel, &content);
+            (meta.title, meta.text.unwrap_or_default())
+        }
+    };
+    let backlinks = crate::links::get_backlinks(root.to_string_lossy().to_string(), absolute.clone())
+        .map(|b| b.len())
+        .unwrap_or(0);
+    let file = std::fs::metadata(path).ok();
+
+    Ok(NoteStats {
+        path: absolute,
+        title,
+        words: text.words,
+        characters: text.characters,
+        links: text.links,
+        backlinks,
+        links_per_thousand_words: per_thousand(text.links, text.words),
+        reading_minutes: reading_minutes(text.words),
+        size: cached.as_ref().map(|n| n.size).or(file.map(|m| m.len())).unwrap_or(0),
+        created: cached.as_ref().and_then(|n| n.created),
+        modified: cached.as_ref().and_then(|n| n.modified),
+    })
+}
+
+fn writing_dates(root: &Path) -> Result<BTreeSet<NaiveDate>, String> {
+    Ok(metadata_cache::writing_days(root, "0000-01-01", "9999-12-31")?
+        .into_iter()
+        .filter(|d| d.added > 0)
+        .filter_map(|d| NaiveDate::parse_from_str(&d.day, "%Y-%m-%d").ok())
+        .collect())
+}
+
+fn vault_summary(root: &Path) -> Result<VaultSummary, String> {
+    let today = Local::now().date_naive();
+    let entries = metadata_cache::cached_entries(root)?;
+    let notes = metadata_cache::note_counts(root)?;
+
+    let folders = entries.iter().filter(|e| e.is_directory).count();
+    let files: Vec<_> = entries.iter().filter(|e| !e.is_directory).collect();
+    let measured: Vec<_> = notes.iter().filter_map(|n| n.text.map(|t| (n, t))).collect();
+    let total_words: usize = measured.iter().map(|(_, t)| t.words).sum();
+    let total_links: usize = measured.iter().map(|(_, t)| t.links).sum();
+
+    let mut largest_files: Vec<FileSize> = files.iter().map(|e| FileSize { path: e.path.clone(), size: e.size }).collect();
+    largest_files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    largest_files.truncate(TOP_LIMIT);
+
+    let mut longest_notes: Vec<NoteWords> = measured
+        .iter()
+        .map(|(n, t)| NoteWords { path: n.path.clone(), title: n.title.clone(), words: t.words })
+        .collect();
+    longest_notes.sort_by(|a, b| b.words.cmp(&a.words).then_with(|| a.path.cmp(&b.path)));
+    longest_notes.truncate(TOP_LIMIT);
+
+    let day = today.to_string();
+    let words_today = metadata_cache::writing_days(root, &day, &day)?.first().map_or(0, |d| d.added);
+    let (current_streak, longest_streak) = streaks(&writing_dates(root)?, today);
+
+    Ok(VaultSummary {
+        workspace: root.to_string_lossy().to_string(),
+        notes: notes.len(),
+        attachments: files.len() - notes.len(),
+        folders,
+        encrypted_notes: notes.len() - measured.len(),
+        total_words,
+        total_characters: measured.iter().map(|(_, t)| t.characters).sum(),
+        total_links,
+        total_size: files.iter().map(|e| e.size).sum(),
+        average_words: if measured.is_empty() { 0 } else { total_words / measured.len() },
+        links_per_thousand_words: per_thousand(total_links, total_words),
+        words_today,
+        notes_created_today: notes.iter().filter(|n| n.created.map(local_day) == Some(today)).count(),
+        current_streak,
+        longest_streak,
+        largest_files,
+        longest_notes,
+    })
+}
+
+fn writing_history(root: &Path, range: &StatsRange) -> Result<WritingHistory, String> {
+    let to = range.to.unwrap_or_else(|| Local::now().date_naive());
+    let from = range.from.unwrap_or(to - Duration::days(DEFAULT_HISTORY_DAYS - 1));
+    if from > to {
+        return Err(format!("Range starts after it ends: {} to {}", from, to));
+    }
+    if (to - from).num_days() >= MAX_HISTORY_DAYS {
+        return Err(format!("Range is longer than {} days", MAX_HISTORY_DAYS));
+    }
+
+    let mut days: Vec<HistoryDay> = from
+        .iter_days()
+        .take_while(|d| *d <= to)
+        .map(|date| HistoryDay { date, ..Default::default() })
+        .collect();
+    let index: HashMap<NaiveDate, usize> = days.iter().enumerate().map(|(i, d)| (d.date, i)).collect();
+
+    for logged in metadata_cache::writing_days(root, &from.to_string(), &to.to_string())? {
+        let Some(&i) = NaiveDate::parse_from_str(&logged.day, "%Y-%m-%d").ok().and_then(|d| index.get(&d)) else {
+            continue;
+        };
+        days[i].words_added = logged.added;
+        days[i].words_removed = logged.removed;
+        days[i].notes_edited = logged.notes;
+    }
+    for created in metadata_cache::note_counts(root)?.into_iter().filter_map(|n| n.created) {
+        if let Some(&i) = index.get(&local_day(created)) {
+            days[i].notes_created += 1;
+        }
+    }
+
+    Ok(WritingHistory {
+        from,
+        to,
+        total_words_added: days.iter().map(|d| d.words_added).sum(),
+        total_words_removed: days.iter().map(|d| d.words_removed).sum(),
+        notes_created: days.iter().map(|d| d.notes_created).sum(),
+        active_days: days.iter().filter(|d| d.words_added > 0 || d.words_removed > 0).count(),
+        days,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Totals, streaks and the largest files of `workspace` (the open vault by
+/// default)
+#[tauri::command]
+pub async fn stats_get_vault_summary(app: AppHandle, workspace: Option<String>) -> Result<VaultSummary, String> {
+    let root = workspace_or_current(&app, workspace)?;
+    tokio::task::spawn_blocking(move || vault_summary(&root))
+        .await
+        .map_err(|e| format!("Statistics task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn stats_get_note(path: String) -> Result<NoteStats, String> {
+    let path = PathBuf::from(path);
+    let root = crate::handlers::files::find_workspace_root(&path)?;
+    tokio::task::spawn_blocking(move || note_stats(&root, &path))
+        .await
+        .map_err(|e| format!("Statistics task failed: {}", e))?
+}
+
+/// Words written and notes created per day, for heatmaps
+#[tauri::command]
+pub async fn stats_get_writing_history(
+    app: AppHandle,
+    range: Option<StatsRange>,
+    workspace: Option<String>,
+) -> Result<WritingHistory, String> {
+    let root = workspace_or_current(&app, workspace)?;
+    let range = range.unwrap_or_default();
+    tokio::task::spawn_blocking(move || writing_history(&root, &range))
+        .await
+        .map_err(|e| format!("Statistics task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_measure() {
+        let stats = measure("# Plan\n\nShip it -- don't wait. See [[Roadmap]] and ![](img.png), [web](https://x.y).\n日本語 text");
+        assert_eq!(stats.words, 14);
+        assert_eq!(stats.links, 2);
+        assert_eq!(count_words("- [ ] **bold** * _"), 1);
+        assert_eq!(measure("a b\n c").characters, 3);
+    }
+
+    #[test]
+    fn test_streaks() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let days: BTreeSet<NaiveDate> = [1, 2, 3, 4, 7, 8, 9].into_iter().map(day).collect();
+        assert_eq!(streaks(&days, day(9)), (3, 4));
+        assert_eq!(streaks(&days, day(10)), (3, 4));
+        assert_eq!(streaks(&days, day(11)), (0, 4));
+        assert_eq!(streaks(&BTreeSet::new(), day(1)), (0, 0));
+    }
+
+    #[test]
+    fn test_writing_is_logged_from_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let note = root.join("Journal.md");
+        fs::write(&note, "# Journal\n\nOne two three").unwrap();
+        fs::write(root.join("pic.png"), [0u8; 64]).unwrap();
+
+        // Building the cache isn't writing
+        let summary = vault_summary(&root).unwrap();
+        assert_eq!((summary.notes, summary.attachments, summary.total_words), (1, 1, 4));
+        assert_eq!(summary.words_today, 0);
+
+        let content = "# Journal\n\nOne two three four five, see [[Other]]";
+        fs::write(&note, content).unwrap();
+        metadata_cache::notify_file_saved(&note.to_string_lossy(), content);
+
+        let history = writing_history(&root, &StatsRange::default()).unwrap();
+        assert_eq!(history.days.len(), DEFAULT_HISTORY_DAYS as usize);
+        assert_eq!(history.total_words_added, 4);
+        assert_eq!(history.days.last().unwrap().notes_edited, 1);
+
+        let stats = note_stats(&root, &note).unwrap();
+        assert_eq!((stats.words, stats.links, stats.reading_minutes), (8, 1, 1));
+        assert_eq!(vault_summary(&root).unwrap().current_streak, 1);
+        assert!(writing_history(&root, &StatsRange { from: Some(Local::now().date_naive() + Duration::days(1)), to: None }).is_err());
+    }
+}
@@ -0,0 +1,536 @@
+//! Background git auto-sync.
+//!
+//! File changes reported by the workspace watcher mark the repository dirty.
+//! Once edits have been quiet for the idle period (or the interval has passed
+//! since the first unsaved change) everything is committed, then pushed with
+//! exponential backoff. A rejected push pulls with a merge first; if that
+//! merge conflicts, auto-sync pauses until the conflict is resolved.
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use super::git::{self, RemoteError};
+use crate::errors::LokusError;
+
+pub const STATUS_EVENT: &str = "git-autosync-status";
+
+const CONFIG_FILE: &str = "git-sync.json";
+const TICK: Duration = Duration::from_secs(5);
+const BACKOFF_BASE_SECS: u64 = 15;
+const BACKOFF_MAX_SECS: u64 = 30 * 60;
+/// Files listed by `{files}` before it switches to "and N more"
+const TEMPLATE_MAX_FILES: usize = 3;
+
+fn default_interval() -> u64 {
+    300
+}
+
+fn default_idle() -> u64 {
+    30
+}
+
+fn default_template() -> String {
+    "Auto-sync: {count} file(s) changed at {datetime}".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSyncConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Longest a change may wait before being committed, in seconds
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// Commit once edits have been quiet this long, in seconds
+    #[serde(default = "default_idle")]
+    pub idle_secs: u64,
+    /// Supports `{date}`, `{time}`, `{datetime}`, `{count}` and `{files}`
+    #[serde(default = "default_template")]
+    pub commit_message_template: String,
+    #[serde(default)]
+    pub push: bool,
+    #[serde(default)]
+    pub remote: Option<String>,
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+impl Default for AutoSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_interval(),
+            idle_secs: default_idle(),
+            commit_message_template: default_template(),
+            push: false,
+            remote: None,
+            branch: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AutoSyncState {
+    Disabled,
+    Idle,
+    Pending,
+    Committing,
+    Pushing,
+    /// Push failed; retrying after a backoff
+    Retrying,
+    /// Stopped because of a merge conflict
+    Paused,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoSyncStatus {
+    pub workspace_path: String,
+    pub state: AutoSyncState,
+    pub config: AutoSyncConfig,
+    pub is_repo: bool,
+    pub last_commit: Option<String>,
+    /// Unix ms
+    pub last_commit_at: Option<i64>,
+    pub last_push_at: Option<i64>,
+    pub push_attempts: u32,
+    /// Unix ms of the next push attempt while retrying
+    pub next_push_at: Option<i64>,
+    pub conflicted_files: Vec<String>,
+    pub last_error: Option<String>,
+}
+
+struct Runtime {
+    config: AutoSyncConfig,
+    state: AutoSyncState,
+    dirty_since: Option<Instant>,
+    last_change: Option<Instant>,
+    last_check: Instant,
+    needs_push: bool,
+    push_attempts: u32,
+    next_push: Option<Instant>,
+    last_commit: Option<String>,
+    last_commit_at: Option<i64>,
+    last_push_at: Option<i64>,
+    conflicted_files: Vec<String>,
+    last_error: Option<String>,
+}
+
+impl Runtime {
+    fn new(config: AutoSyncConfig) -> Self {
+        Self {
+            state: if config.enabled { AutoSyncState::Idle } else { AutoSyncState::Disabled },
+            config,
+            dirty_since: None,
+            last_change: None,
+            last_check: Instant::now(),
+            // Push anything committed while the app was closed
+            needs_push: true,
+            push_attempts: 0,
+            next_push: None,
+            last_commit: None,
+            last_commit_at: None,
+            last_push_at: None,
+            conflicted_files: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    /// The parts of the status worth telling the UI about when they change
+    fn status_key(&self) -> (AutoSyncState, Option<String>, Option<i64>, u32, Option<String>) {
+        (self.state, self.last_commit.clone(), self.last_push_at, self.push_attempts, self.last_error.clone())
+    }
+
+    fn status(&self, root: &Path) -> AutoSyncStatus {
+        let now = Instant::now();
+        AutoSyncStatus {
+            workspace_path: root.to_string_lossy().to_string(),
+            state: self.state,
+            config: self.config.clone(),
+            is_repo: git::is_repo(root),
+            last_commit: self.last_commit.clone(),
+            last_commit_at: self.last_commit_at,
+            last_push_at: self.last_push_at,
+            push_attempts: self.push_attempts,
+            next_push_at: self.next_push.filter(|at| *at > now).map(|at| {
+                chrono::Utc::now().timestamp_millis() + (at - now).as_millis() as i64
+            }),
+            conflicted_files: self.conflicted_files.clone(),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+static RUNTIMES: Lazy<Mutex<HashMap<PathBuf, Runtime>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(CONFIG_FILE)
+}
+
+fn load_config(root: &Path) -> AutoSyncConfig {
+    fs::read_to_string(config_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(root: &Path, config: &AutoSyncConfig) -> Result<(), String> {
+    let path = config_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write git sync config: {}", e))
+}
+
+fn with_runtime<R>(root: &Path, f: impl FnOnce(&mut Runtime) -> R) -> R {
+    let mut runtimes = RUNTIMES.lock().unwrap_or_else(|e| e.into_inner());
+    let runtime = runtimes
+        .entry(root.to_path_buf())
+        .or_insert_with(|| Runtime::new(load_config(root)));
+    f(runtime)
+}
+
+/// Called by the workspace watcher after a batch of file changes
+pub fn notify_changed(root: &Path) {
+    with_runtime(root, |rt| {
+        if !rt.config.enabled {
+            return;
+        }
+        let now = Instant::now();
+        rt.last_change = Some(now);
+        rt.dirty_since.get_or_insert(now);
+        if rt.state == AutoSyncState::Idle {
+            rt.state = AutoSyncState::Pending;
+        }
+    });
+}
+
+fn backoff(attempts: u32) -> Duration {
+    let secs = BACKOFF_BASE_SECS.saturating_mul(1u64 << attempts.saturating_sub(1).min(16));
+    Duration::from_secs(secs.min(BACKOFF_MAX_SECS))
+}
+
+fn render_template(template: &str, files: &[String]) -> String {
+    let now = Local::now();
+    let listed = if files.len() > TEMPLATE_MAX_FILES {
+        format!("{} and {} more", files[..TEMPLATE_MAX_FILES].join(", "), files.len() - TEMPLATE_MAX_FILES)
+    } else {
+        files.join(", ")
+    };
+    let message = template
+        .replace("{datetime}", &now.format("%Y-%m-%d %H:%M").to_string())
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M").to_string())
+        .replace("{count}", &files.len().to_string())
+        .replace("{files}", &listed);
+    if message.trim().is_empty() {
+        render_template(&default_template(), files)
+    } else {
+        message
+    }
+}
+
+fn pause_for_conflict(rt: &mut Runtime, files: Vec<String>) {
+    rt.state = AutoSyncState::Paused;
+    rt.last_error = Some(format!("Merge conflict in {} file(s); resolve it to resume auto-sync", files.len()));
+    rt.conflicted_files = files;
+    rt.next_push = None;
+}
+
+/// One pass of the state machine; returns whether the status changed
+fn step(root: &Path) -> bool {
+    let now = Instant::now();
+    let (config, before) = with_runtime(root, |rt| (rt.config.clone(), rt.status_key()));
+    if !config.enabled || !git::is_repo(root) {
+        return false;
+    }
+
+    // Conflicts (ours or left over from a manual pull) always pause; clearing them resumes
+    let conflicts = git::conflicted_files(root);
+    if !conflicts.is_empty() || git::is_merging(root) {
+        let conflicts = if conflicts.is_empty() { vec!["(unfinished merge)".to_string()] } else { conflicts };
+        with_runtime(root, |rt| pause_for_conflict(rt, conflicts));
+        return with_runtime(root, |rt| rt.status_key()) != before;
+    }
+    with_runtime(root, |rt| {
+        if rt.state == AutoSyncState::Paused {
+            rt.state = AutoSyncState::Idle;
+            rt.conflicted_files.clear();
+            rt.last_error = None;
+        }
+    });
+
+    let commit_due = with_runtime(root, |rt| {
+        let idle = rt.last_change.map_or(false, |t| now.duration_since(t) >= Duration::from_secs(config.idle_secs));
+        let overdue = rt.dirty_since.map_or(false, |t| now.duration_since(t) >= Duration::from_secs(config.interval_secs));
+        // Also catch edits the watcher can't see (e.g. while the app was closed)
+        let periodic = now.duration_since(rt.last_check) >= Duration::from_secs(config.interval_secs);
+        if periodic {
+            rt.last_check = now;
+        }
+        idle || overdue || periodic
+    });
+
+    if commit_due {
+        with_runtime(root, |rt| rt.state = AutoSyncState::Committing);
+        let files = git::changed_files(root).unwrap_or_default();
+        let result = if files.is_empty() {
+            Ok(None)
+        } else {
+            git::commit_all(root, &render_template(&config.commit_message_template, &files))
+        };
+        with_runtime(root, |rt| {
+            rt.dirty_since = None;
+            rt.last_change = None;
+            rt.state = AutoSyncState::Idle;
+            match result {
+                Ok(Some(sha)) => {
+                    rt.last_commit = Some(sha);
+                    rt.last_commit_at = Some(chrono::Utc::now().timestamp_millis());
+                    rt.needs_push = true;
+                    rt.last_error = None;
+                }
+                Ok(None) => {}
+                Err(e) => rt.last_error = Some(e),
+            }
+        });
+    }
+
+    let push_due = with_runtime(root, |rt| {
+        config.push && rt.needs_push && rt.next_push.map_or(true, |at| now >= at)
+    });
+    if push_due {
+        with_runtime(root, |rt| rt.state = AutoSyncState::Pushing);
+        let remote = config.remote.as_deref();
+        let branch = config.branch.as_deref();
+        let mut result = git::push(root, remote, branch);
+        if let Err(RemoteError::Rejected(_)) = result {
+            result = git::pull(root, remote, branch).and_then(|_| git::push(root, remote, branch));
+        }
+        with_runtime(root, |rt| match result {
+            Ok(()) => {
+                rt.state = AutoSyncState::Idle;
+                rt.needs_push = false;
+                rt.push_attempts = 0;
+                rt.next_push = None;
+                rt.last_push_at = Some(chrono::Utc::now().timestamp_millis());
+                rt.last_error = None;
+            }
+            Err(RemoteError::Conflict(files)) => pause_for_conflict(rt, files),
+            Err(e) => {
+                rt.push_attempts += 1;
+                rt.next_push = Some(now + backoff(rt.push_attempts));
+                rt.state = AutoSyncState::Retrying;
+                rt.last_error = Some(e.to_string());
+            }
+        });
+    }
+
+    with_runtime(root, |rt| {
+        if rt.state == AutoSyncState::Idle && rt.dirty_since.is_some() {
+            rt.state = AutoSyncState::Pending;
+        }
+        rt.status_key()
+    }) != before
+}
+
+fn emit_status(app: &AppHandle, root: &Path) {
+    let status = with_runtime(root, |rt| rt.status(root));
+    if let Err(e) = app.emit(STATUS_EVENT, &status) {
+        tracing::warn!("Failed to emit {}: {}", STATUS_EVENT, e);
+    }
+}
+
+/// Commit and push right away ("Sync now"), whether or not auto-sync is on.
+/// A rejected push pulls with a merge first, like the background loop.
+pub(crate) fn sync_now(app: &AppHandle, root: &Path) -> Result<(), String> {
+    if !git::is_repo(root) {
+        return Err("Workspace is not a git repository".to_string());
+    }
+    let config = with_runtime(root, |rt| rt.config.clone());
+    let files = git::changed_files(root)?;
+    if !files.is_empty() {
+        if let Some(sha) = git::commit_all(root, &render_template(&config.commit_message_template, &files))? {
+            with_runtime(root, |rt| {
+                rt.last_commit = Some(sha);
+                rt.last_commit_at = Some(chrono::Utc::now().timestamp_millis());
+                rt.dirty_since = None;
+                rt.last_change = None;
+            });
+        }
+    }
+
+    let remote = config.remote.as_deref();
+    let branch = config.branch.as_deref();
+    let mut result = git::push(root, remote, branch);
+    if let Err(RemoteError::Rejected(_)) = result {
+        result = git::pull(root, remote, branch).and_then(|_| git::push(root, remote, branch));
+    }
+    let outcome = with_runtime(root, |rt| match result {
+        Ok(()) => {
+            rt.needs_push = false;
+            rt.push_attempts = 0;
+            rt.next_push = None;
+            rt.last_push_at = Some(chrono::Utc::now().timestamp_millis());
+            rt.last_error = None;
+            Ok(())
+        }
+        Err(RemoteError::Conflict(files)) => {
+            pause_for_conflict(rt, files);
+            Err(rt.last_error.clone().unwrap_or_default())
+        }
+        Err(e) => Err(e.to_string()),
+    });
+    emit_status(app, root);
+    outcome
+}
+
+/// Start the auto-sync loop for the active workspace and any configured ones
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            if crate::diagnostics::background_paused() {
+                continue;
+            }
+            let mut roots: Vec<PathBuf> = {
+                let runtimes = RUNTIMES.lock().unwrap_or_else(|e| e.into_inner());
+                runtimes.iter().filter(|(_, rt)| rt.config.enabled).map(|(root, _)| root.clone()).collect()
+            };
+            if let Some(active) = crate::watcher::active_root() {
+                if !roots.contains(&active) {
+                    roots.push(active);
+                }
+            }
+            for root in roots {
+                let step_root = root.clone();
+                match tokio::task::spawn_blocking(move || step(&step_root)).await {
+                    Ok(true) => emit_status(&app, &root),
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Git auto-sync step panicked for {}: {}", root.display(), e),
+                }
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Enable (or update) auto-sync for a workspace; `interval` is in seconds
+#[tauri::command]
+pub async fn git_autosync_configure(
+    app: AppHandle,
+    workspace_path: String,
+    interval: Option<u64>,
+    commit_message_template: Option<String>,
+    push: Option<bool>,
+    enabled: Option<bool>,
+    idle_secs: Option<u64>,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> Result<AutoSyncStatus, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(LokusError::not_found(&root));
+    }
+
+    let mut config = load_config(&root);
+    config.enabled = enabled.unwrap_or(true);
+    if let Some(interval) = interval {
+        if interval < 10 {
+            return Err(LokusError::invalid("Auto-sync interval must be at least 10 seconds"));
+        }
+        config.interval_secs = interval;
+    }
+    if let Some(idle) = idle_secs {
+        config.idle_secs = idle;
+    }
+    if let Some(template) = commit_message_template {
+        config.commit_message_template = if template.trim().is_empty() { default_template() } else { template };
+    }
+    if let Some(push) = push {
+        config.push = push;
+    }
+    if remote.is_some() {
+        config.remote = remote.filter(|r| !r.trim().is_empty());
+    }
+    if branch.is_some() {
+        config.branch = branch.filter(|b| !b.trim().is_empty());
+    }
+    if config.enabled && !git::is_repo(&root) {
+        return Err(LokusError::Unsupported("Workspace is not a git repository".to_string()));
+    }
+    save_config(&root, &config)?;
+
+    with_runtime(&root, |rt| {
+        rt.state = match (config.enabled, rt.state) {
+            (false, _) => AutoSyncState::Disabled,
+            (true, AutoSyncState::Disabled) => AutoSyncState::Idle,
+            (true, state) => state,
+        };
+        rt.config = config;
+    });
+    emit_status(&app, &root);
+    Ok(with_runtime(&root, |rt| rt.status(&root)))
+}
+
+#[tauri::command]
+pub async fn git_autosync_status(workspace_path: String) -> Result<AutoSyncStatus, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    Ok(with_runtime(&root, |rt| rt.status(&root)))
+}
+
+/// Retry a pending push right away instead of waiting out the backoff
+#[tauri::command]
+pub async fn git_autosync_retry(workspace_path: String) -> Result<(), LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    with_runtime(&root, |rt| {
+        if rt.state == AutoSyncState::Paused {
+            return Err(LokusError::Conflict {
+                files: rt.conflicted_files.clone(),
+                message: "Auto-sync is paused until the merge conflict is resolved".to_string(),
+            });
+        }
+        rt.next_push = None;
+        rt.needs_push = true;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(15));
+        assert_eq!(backoff(2), Duration::from_secs(30));
+        assert_eq!(backoff(4), Duration::from_secs(120));
+        assert_eq!(backoff(40), Duration::from_secs(BACKOFF_MAX_SECS));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let files: Vec<String> = ["a.md", "b.md", "c.md", "d.md"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(render_template("{count}: {files}", &files), "4: a.md, b.md, c.md and 1 more");
+        assert_eq!(render_template("{files}", &files[..1]), "a.md");
+        assert!(render_template("  ", &files).starts_with("Auto-sync: 4 file(s)"));
+    }
+
+    #[test]
+    fn test_config_defaults_from_partial_json() {
+        let config: AutoSyncConfig = serde_json::from_str(r#"{"enabled":true,"push":true}"#).unwrap();
+        assert_eq!(config.interval_secs, 300);
+        assert_eq!(config.idle_secs, 30);
+        assert!(config.push);
+    }
+}
@@ -0,0 +1,248 @@
+//! Content-defined chunking for delta sync.
+//!
+//! Files are cut where a rolling gear hash hits a boundary mask, so an edit
+//! only changes the chunks around it; everything before and after keeps its
+//! hash. A `FileManifest` lists the chunk hashes in order. Providers upload
+//! the manifest plus only the chunks the remote doesn't have yet, and rebuild
+//! files on download from chunks already present locally plus fetched ones.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const MIN_CHUNK: usize = 8 * 1024;
+const MAX_CHUNK: usize = 128 * 1024;
+/// Top 15 bits: they mix in the last 64 bytes, and give ~32 KiB past the minimum on average
+const BOUNDARY_MASK: u64 = 0xfffe_0000_0000_0000;
+
+/// Files smaller than this are always sent whole; chunking wouldn't save anything
+pub const DELTA_THRESHOLD: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkRef {
+    /// blake3, hex
+    pub hash: String,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileManifest {
+    /// blake3 of the whole file, hex
+    pub hash: String,
+    pub size: u64,
+    pub chunks: Vec<ChunkRef>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaMetrics {
+    /// Transfers that reused at least one chunk
+    pub delta_syncs: u64,
+    pub full_syncs: u64,
+    pub bytes_transferred: u64,
+    /// Bytes that didn't need sending thanks to chunks already on the other side
+    pub bytes_saved: u64,
+}
+
+static METRICS: Lazy<Mutex<HashMap<PathBuf, DeltaMetrics>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Pseudo-random gear table (splitmix64), fixed so every device cuts identically
+static GEAR: Lazy<[u64; 256]> = Lazy::new(|| {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x4c6f_6b75_7353_796e; // "LokusSyn"
+    for slot in table.iter_mut() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        *slot = z ^ (z >> 31);
+    }
+    table
+});
+
+/// Chunk boundaries (end offsets) for `data`
+fn cut_points(data: &[u8]) -> Vec<usize> {
+    let mut cuts = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let end = (start + MAX_CHUNK).min(data.len());
+        let mut cut = end;
+        let mut hash: u64 = 0;
+        let mut i = start + MIN_CHUNK;
+        // Warm the rolling hash over the bytes the minimum size skips
+        for &byte in &data[start..i.min(end)] {
+            hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        }
+        while i < end {
+            hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+            i += 1;
+            if hash & BOUNDARY_MASK == 0 {
+                cut = i;
+                break;
+            }
+        }
+        cuts.push(cut);
+        start = cut;
+    }
+    cuts
+}
+
+fn hash_hex(data: &[u8]) -> String {
+    blake3::hash(data).to_hex().to_string()
+}
+
+/// Split `data` into chunks, returning the manifest and each chunk's bytes
+pub fn chunk_bytes(data: &[u8]) -> (FileManifest, Vec<&[u8]>) {
+    let mut pieces = Vec::new();
+    let mut refs = Vec::new();
+    let mut start = 0;
+    for cut in cut_points(data) {
+        let piece = &data[start..cut];
+        refs.push(ChunkRef { hash: hash_hex(piece), size: piece.len() as u32 });
+        pieces.push(piece);
+        start = cut;
+    }
+    let manifest = FileManifest { hash: hash_hex(data), size: data.len() as u64, chunks: refs };
+    (manifest, pieces)
+}
+
+/// Index of the chunks in an existing local copy, for reuse while rebuilding
+pub fn local_chunks(data: &[u8]) -> HashMap<String, Vec<u8>> {
+    let (manifest, pieces) = chunk_bytes(data);
+    manifest
+        .chunks
+        .into_iter()
+        .zip(pieces)
+        .map(|(chunk, bytes)| (chunk.hash, bytes.to_vec()))
+        .collect()
+}
+
+/// Chunks of `manifest` the other side lacks, deduplicated, in file order
+pub fn missing_chunks<'a>(manifest: &'a FileManifest, known: &HashSet<String>) -> Vec<&'a ChunkRef> {
+    let mut seen = HashSet::new();
+    manifest
+        .chunks
+        .iter()
+        .filter(|c| !known.contains(&c.hash) && seen.insert(c.hash.as_str()))
+        .collect()
+}
+
+/// Rebuild a file from its manifest, taking chunks from `local` when possible and
+/// calling `fetch` for the rest. Every chunk and the final file are verified.
+pub fn reconstruct(
+    manifest: &FileManifest,
+    local: &HashMap<String, Vec<u8>>,
+    mut fetch: impl FnMut(&ChunkRef) -> Result<Vec<u8>, String>,
+) -> Result<(Vec<u8>, u64), String> {
+    let mut out = Vec::with_capacity(manifest.size as usize);
+    let mut fetched: HashMap<&str, Vec<u8>> = HashMap::new();
+    let mut transferred = 0u64;
+    for chunk in &manifest.chunks {
+        if let Some(bytes) = local.get(&chunk.hash).or_else(|| fetched.get(chunk.hash.as_str())) {
+            out.extend_from_slice(bytes);
+            continue;
+        }
+        let bytes = fetch(chunk)?;
+        if hash_hex(&bytes) != chunk.hash {
+            return Err(format!("Chunk {} failed verification", chunk.hash));
+        }
+        transferred += bytes.len() as u64;
+        out.extend_from_slice(&bytes);
+        fetched.insert(chunk.hash.as_str(), bytes);
+    }
+    if hash_hex(&out) != manifest.hash {
+        return Err("Reconstructed file does not match its manifest".to_string());
+    }
+    Ok((out, transferred))
+}
+
+/// Record one file transfer for the workspace's delta metrics
+pub fn record_transfer(root: &Path, total: u64, transferred: u64) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = metrics.entry(root.to_path_buf()).or_default();
+    if transferred < total {
+        entry.delta_syncs += 1;
+    } else {
+        entry.full_syncs += 1;
+    }
+    entry.bytes_transferred += transferred;
+    entry.bytes_saved += total.saturating_sub(transferred);
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn sync_delta_metrics(workspace_path: String) -> Result<DeltaMetrics, crate::errors::LokusError> {
+    Ok(METRICS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(Path::new(&workspace_path))
+        .cloned()
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic, incompressible-looking test data
+    fn sample(len: usize) -> Vec<u8> {
+        let mut state: u32 = 12345;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_sizes_are_bounded() {
+        let data = sample(2 * 1024 * 1024);
+        let (manifest, pieces) = chunk_bytes(&data);
+        assert_eq!(pieces.concat(), data);
+        assert_eq!(manifest.chunks.iter().map(|c| c.size as u64).sum::<u64>(), data.len() as u64);
+        let (last, rest) = manifest.chunks.split_last().unwrap();
+        assert!(rest.iter().all(|c| (MIN_CHUNK..=MAX_CHUNK).contains(&(c.size as usize))));
+        assert!(last.size as usize <= MAX_CHUNK);
+    }
+
+    #[test]
+    fn test_small_edit_only_touches_nearby_chunks() {
+        let original = sample(8 * 1024 * 1024);
+        let mut edited = original.clone();
+        // Insert a line in the middle, shifting everything after it
+        let middle = edited.len() / 2;
+        edited.splice(middle..middle, b"one more line\n".iter().copied());
+
+        let (old_manifest, _) = chunk_bytes(&original);
+        let (new_manifest, _) = chunk_bytes(&edited);
+        let known: HashSet<String> = old_manifest.chunks.iter().map(|c| c.hash.clone()).collect();
+        let missing = missing_chunks(&new_manifest, &known);
+        let missing_bytes: u64 = missing.iter().map(|c| c.size as u64).sum();
+        assert!(missing.len() <= 3, "{} chunks changed", missing.len());
+        assert!(missing_bytes <= 3 * MAX_CHUNK as u64);
+
+        let local = local_chunks(&original);
+        let (rebuilt, transferred) = reconstruct(&new_manifest, &local, |chunk| {
+            let (_, pieces) = chunk_bytes(&edited);
+            let index = new_manifest.chunks.iter().position(|c| c.hash == chunk.hash).unwrap();
+            Ok(pieces[index].to_vec())
+        })
+        .unwrap();
+        assert_eq!(rebuilt, edited);
+        assert_eq!(transferred, missing_bytes);
+    }
+
+    #[test]
+    fn test_reconstruct_rejects_bad_chunks() {
+        let data = sample(600 * 1024);
+        let (manifest, _) = chunk_bytes(&data);
+        let result = reconstruct(&manifest, &HashMap::new(), |chunk| Ok(vec![0; chunk.size as usize]));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! Conflict center shared by the file sync providers.
+//!
+//! When a provider finds a file changed both locally and remotely since the
+//! last sync, it doesn't pick a winner. It parks the remote version under
+//! `.lokus/sync/conflicts/`, records a `ConflictInfo` and emits
+//! `sync-conflict-detected`, so the user can compare the two versions and
+//! choose. Until they do, the provider leaves that path alone.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+use crate::errors::LokusError;
+use crate::handlers::version_history::DiffLine;
+
+pub const CONFLICT_DETECTED_EVENT: &str = "sync-conflict-detected";
+pub const CONFLICT_RESOLVED_EVENT: &str = "sync-conflict-resolved";
+
+const STORE_FILE: &str = "conflicts.json";
+/// Larger files are previewed without a line diff
+const MAX_DIFF_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictInfo {
+    pub id: String,
+    /// Workspace-relative path
+    pub path: String,
+    /// Which provider found it, e.g. `webdav` or `s3`
+    pub provider: String,
+    pub local_hash: String,
+    pub remote_hash: String,
+    /// Provider's version marker for the remote copy (ETag)
+    pub remote_version: String,
+    /// Unix ms
+    pub detected_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConflictStore {
+    #[serde(default)]
+    open: Vec<ConflictInfo>,
+    /// Remote versions the user has already ruled on, by path
+    #[serde(default)]
+    acknowledged: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionStrategy {
+    KeepLocal,
+    KeepRemote,
+    /// Keep local at the path and save the remote version next to it
+    KeepBoth,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictPreview {
+    pub conflict: ConflictInfo,
+    pub binary: bool,
+    /// Text contents; empty for binary files
+    pub local: String,
+    pub remote: String,
+    pub diff: Vec<DiffLine>,
+}
+
+fn sync_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join("sync")
+}
+
+fn blob_path(root: &Path, id: &str) -> PathBuf {
+    sync_dir(root).join("conflicts").join(format!("{}.remote", id))
+}
+
+fn load_store(root: &Path) -> ConflictStore {
+    fs::read_to_string(sync_dir(root).join(STORE_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(root: &Path, store: &ConflictStore) -> Result<(), String> {
+    let dir = sync_dir(root);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create sync directory: {}", e))?;
+    let json = serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize conflicts: {}", e))?;
+    fs::write(dir.join(STORE_FILE), json).map_err(|e| format!("Failed to write conflicts: {}", e))
+}
+
+/// Whether a path has an unresolved conflict; providers skip such paths
+pub fn is_conflicted(root: &Path, path: &str) -> bool {
+    load_store(root).open.iter().any(|c| c.path == path)
+}
+
+/// Remote version the user already chose over (or merged with), if any
+pub fn acknowledged_version(root: &Path, path: &str) -> Option<String> {
+    load_store(root).acknowledged.get(path).cloned()
+}
+
+/// Forget an acknowledgement once the provider has synced past it
+pub fn clear_acknowledged(root: &Path, path: &str) -> Result<(), String> {
+    let mut store = load_store(root);
+    if store.acknowledged.remove(path).is_some() {
+        save_store(root, &store)?;
+    }
+    Ok(())
+}
+
+/// Park the remote version and tell the UI. Re-reporting the same remote
+/// version for a path that's already open is a no-op.
+pub fn record_conflict(
+    app: &AppHandle,
+    root: &Path,
+    path: &str,
+    provider: &str,
+    local_hash: &str,
+    remote_version: &str,
+    remote_bytes: &[u8],
+) -> Result<ConflictInfo, String> {
+    let mut store = load_store(root);
+    if let Some(existing) = store.open.iter().find(|c| c.path == path && c.remote_version == remote_version) {
+        return Ok(existing.clone());
+    }
+
+    let conflict = ConflictInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        path: path.to_string(),
+        provider: provider.to_string(),
+        local_hash: local_hash.to_string(),
+        remote_hash: blake3::hash(remote_bytes).to_hex().to_string(),
+        remote_version: remote_version.to_string(),
+        detected_at: Utc::now().timestamp_millis(),
+    };
+    let blob = blob_path(root, &conflict.id);
+    if let Some(parent) = blob.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create conflicts directory: {}", e))?;
+    }
+    fs::write(&blob, remote_bytes).map_err(|e| format!("Failed to save remote version: {}", e))?;
+
+    // A newer remote version replaces the one the user hasn't looked at yet
+    for stale in store.open.iter().filter(|c| c.path == path) {
+        let _ = fs::remove_file(blob_path(root, &stale.id));
+    }
+    store.open.retain(|c| c.path != path);
+    store.open.push(conflict.clone());
+    save_store(root, &store)?;
+
+    if let Err(e) = app.emit(CONFLICT_DETECTED_EVENT, &conflict) {
+        tracing::warn!("Failed to emit {}: {}", CONFLICT_DETECTED_EVENT, e);
+    }
+    Ok(conflict)
+}
+
+/// `notes/a.md` -> `notes/a (conflict 2024-05-01).md`, numbered if taken
+fn conflict_copy_path(root: &Path, path: &str) -> String {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), path),
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let date = Utc::now().format("%Y-%m-%d");
+    let mut candidate = format!("{}{} (conflict {}){}", dir, stem, date, ext);
+    let mut n = 2;
+    while root.join(&candidate).exists() {
+        candidate = format!("{}{} (conflict {} {}){}", dir, stem, date, n, ext);
+        n += 1;
+    }
+    candidate
+}
+
+fn decode_text(root: &Path, path: &str, bytes: Vec<u8>) -> Option<String> {
+    let text = String::from_utf8(bytes).ok()?;
+    crate::encryption::decrypt_for_read(&root.join(path).to_string_lossy(), text).ok()
+}
+
+fn no_conflict(path: &str) -> LokusError {
+    LokusError::NotFound { path: path.to_string(), message: format!("No sync conflict for {}", path) }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn sync_list_conflicts(workspace_path: String) -> Result<Vec<ConflictInfo>, LokusError> {
+    Ok(load_store(Path::new(&workspace_path)).open)
+}
+
+/// Both versions of a conflicted file and a line diff from local to remote
+#[tauri::command]
+pub async fn sync_preview_conflict(workspace_path: String, path: String) -> Result<ConflictPreview, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let conflict = load_store(&root).open.into_iter().find(|c| c.path == path).ok_or_else(|| no_conflict(&path))?;
+
+    let local_bytes = fs::read(root.join(&path)).unwrap_or_default();
+    let blob = blob_path(&root, &conflict.id);
+    let remote_bytes = fs::read(&blob).map_err(|e| LokusError::io("Remote version is missing", &blob, e))?;
+
+    let texts = (local_bytes.len() <= MAX_DIFF_BYTES && remote_bytes.len() <= MAX_DIFF_BYTES)
+        .then(|| Some((decode_text(&root, &path, local_bytes)?, decode_text(&root, &path, remote_bytes)?)))
+        .flatten();
+    Ok(match texts {
+        Some((local, remote)) => {
+            let diff = super::merge::line_diff(&local, &remote);
+            ConflictPreview { conflict, binary: false, local, remote, diff }
+        }
+        None => ConflictPreview { conflict, binary: true, local: String::new(), remote: String::new(), diff: Vec::new() },
+    })
+}
+
+#[tauri::command]
+pub async fn sync_resolve_conflict(
+    app: AppHandle,
+    workspace_path: String,
+    path: String,
+    strategy: ResolutionStrategy,
+) -> Result<Option<String>, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let mut store = load_store(&root);
+    let position = store.open.iter().position(|c| c.path == path).ok_or_else(|| no_conflict(&path))?;
+    let conflict = store.open[position].clone();
+    let blob = blob_path(&root, &conflict.id);
+
+    let mut copy_path = None;
+    match strategy {
+        ResolutionStrategy::KeepLocal => {}
+        ResolutionStrategy::KeepRemote => {
+            let target = root.join(&path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| LokusError::io("Failed to create folder", parent, e))?;
+            }
+            fs::copy(&blob, &target).map_err(|e| LokusError::io("Failed to restore remote version of", &target, e))?;
+        }
+        ResolutionStrategy::KeepBoth => {
+            let copy = conflict_copy_path(&root, &path);
+            let target = root.join(&copy);
+            fs::copy(&blob, &target).map_err(|e| LokusError::io("Failed to save remote copy", &target, e))?;
+            copy_path = Some(copy);
+        }
+    }
+
+    store.open.remove(position);
+    store.acknowledged.insert(path.clone(), conflict.remote_version.clone());
+    save_store(&root, &store)?;
+    let _ = fs::remove_file(&blob);
+
+    if let Err(e) = app.emit(CONFLICT_RESOLVED_EVENT, &conflict) {
+        tracing::warn!("Failed to emit {}: {}", CONFLICT_RESOLVED_EVENT, e);
+    }
+    Ok(copy_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_copy_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let date = Utc::now().format("%Y-%m-%d").to_string();
+        let first = conflict_copy_path(dir.path(), "notes/a.md");
+        assert_eq!(first, format!("notes/a (conflict {}).md", date));
+
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+        fs::write(dir.path().join(&first), "x").unwrap();
+        assert_eq!(conflict_copy_path(dir.path(), "notes/a.md"), format!("notes/a (conflict {} 2).md", date));
+        assert_eq!(conflict_copy_path(dir.path(), ".hidden"), format!(".hidden (conflict {})", date));
+    }
+}
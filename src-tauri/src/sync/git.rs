@@ -0,0 +1,584 @@
+//! Thin wrapper around the `git` command line for workspace sync.
+//!
+//! Shelling out keeps us compatible with whatever the user already has set up
+//! (SSH agents, credential helpers, hooks). HTTPS tokens saved through
+//! `git_set_credentials` are injected per invocation and never written to
+//! the repository config.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::errors::LokusError;
+use crate::handlers::version_history::DiffLine;
+use crate::secure_storage::SecureStorage;
+
+const CREDENTIALS_KEY: &str = "git_credentials";
+/// Field and record separators for `git log --format`, unlikely to appear in messages
+const FIELD_SEP: char = '\x1f';
+const RECORD_SEP: char = '\x1e';
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCredentials {
+    pub username: String,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatus {
+    pub is_repo: bool,
+    pub branch: Option<String>,
+    pub changed_files: Vec<String>,
+    pub conflicted_files: Vec<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub merging: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitInfo {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub author_email: String,
+    /// Unix seconds
+    pub timestamp: i64,
+    pub message: String,
+    /// Path of the file in this commit, which differs from the current one across renames
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Trailing context git prints after the `@@` range, usually the nearest heading
+    pub section: String,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub path: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// How a push or pull ended, so callers can decide between retrying and pausing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteError {
+    /// Remote has commits we don't; pull first
+    Rejected(String),
+    /// Merge stopped with conflicts in the working tree
+    Conflict(Vec<String>),
+    /// Network, auth or anything else worth retrying later
+    Failed(String),
+}
+
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteError::Rejected(msg) | RemoteError::Failed(msg) => write!(f, "{}", msg),
+            RemoteError::Conflict(files) => write!(f, "Merge conflict in {}", files.join(", ")),
+        }
+    }
+}
+
+impl From<RemoteError> for LokusError {
+    fn from(err: RemoteError) -> Self {
+        match err {
+            RemoteError::Rejected(message) => LokusError::Conflict { files: Vec::new(), message },
+            RemoteError::Conflict(files) => LokusError::conflict(files),
+            RemoteError::Failed(message) => LokusError::Network(message),
+        }
+    }
+}
+
+pub(crate) fn load_credentials() -> Option<GitCredentials> {
+    SecureStorage::new().ok()?.retrieve(CREDENTIALS_KEY).ok().flatten()
+}
+
+/// Git with stored credentials sent only to `credentials_url`. The header goes
+/// through GIT_CONFIG_* variables so the token never shows up in argv.
+fn git_command(repo: &Path, credentials_url: Option<&str>) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo)
+        // Never block a background job on an interactive prompt
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_ASKPASS", "")
+        .args(["-c", "core.quotepath=false"]);
+    if let Some(url) = credentials_url {
+        if let Some(creds) = load_credentials() {
+            let basic = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", creds.username, creds.token));
+            cmd.env("GIT_CONFIG_COUNT", "1")
+                .env("GIT_CONFIG_KEY_0", format!("http.{}.extraHeader", url))
+                .env("GIT_CONFIG_VALUE_0", format!("Authorization: Basic {}", basic));
+        }
+    }
+    cmd
+}
+
+/// URL of `remote`, or of the remote git would use by default, if it is HTTP(S)
+fn remote_url(repo: &Path, remote: Option<&str>) -> Option<String> {
+    let name = match remote {
+        Some(remote) => remote.to_string(),
+        None => current_branch(repo)
+            .and_then(|branch| run_git(repo, &["config", &format!("branch.{}.remote", branch)]).ok())
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| "origin".to_string()),
+    };
+    let url = run_git(repo, &["remote", "get-url", &name]).ok()?.trim().to_string();
+    (url.starts_with("https://") || url.starts_with("http://")).then_some(url)
+}
+
+fn output_to_result(output: std::process::Output) -> Result<String, String> {
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Err(if stderr.is_empty() { stdout } else { stderr })
+    }
+}
+
+/// Run git in `repo` and return stdout, or stderr as the error
+pub(crate) fn run_git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = git_command(repo, None)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    output_to_result(output)
+}
+
+fn run_git_remote(repo: &Path, remote: Option<&str>, args: &[&str]) -> Result<String, String> {
+    let output = git_command(repo, remote_url(repo, remote).as_deref())
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    output_to_result(output)
+}
+
+pub(crate) fn is_repo(path: &Path) -> bool {
+    run_git(path, &["rev-parse", "--is-inside-work-tree"])
+        .map(|out| out.trim() == "true")
+        .unwrap_or(false)
+}
+
+pub(crate) fn current_branch(repo: &Path) -> Option<String> {
+    run_git(repo, &["symbolic-ref", "--short", "HEAD"])
+        .ok()
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+}
+
+/// Paths with unmerged index entries
+pub(crate) fn conflicted_files(repo: &Path) -> Vec<String> {
+    let mut files: Vec<String> = run_git(repo, &["diff", "--name-only", "--diff-filter=U"])
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    files.dedup();
+    files
+}
+
+pub(crate) fn is_merging(repo: &Path) -> bool {
+    run_git(repo, &["rev-parse", "-q", "--verify", "MERGE_HEAD"]).is_ok()
+}
+
+fn parse_porcelain(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.len() > 3)
+        .map(|line| {
+            let path = &line[3..];
+            // Renames are reported as "old -> new"
+            path.rsplit(" -> ").next().unwrap_or(path).trim_matches('"').to_string()
+        })
+        .collect()
+}
+
+pub(crate) fn changed_files(repo: &Path) -> Result<Vec<String>, String> {
+    Ok(parse_porcelain(&run_git(repo, &["status", "--porcelain"])?))
+}
+
+fn ahead_behind(repo: &Path) -> (u32, u32) {
+    run_git(repo, &["rev-list", "--left-right", "--count", "HEAD...@{upstream}"])
+        .ok()
+        .and_then(|out| {
+            let mut parts = out.split_whitespace().map(|n| n.parse::<u32>().unwrap_or(0));
+            Some((parts.next()?, parts.next()?))
+        })
+        .unwrap_or((0, 0))
+}
+
+pub(crate) fn status(repo: &Path) -> Result<GitStatus, String> {
+    if !is_repo(repo) {
+        return Ok(GitStatus {
+            is_repo: false,
+            branch: None,
+            changed_files: Vec::new(),
+            conflicted_files: Vec::new(),
+            ahead: 0,
+            behind: 0,
+            merging: false,
+        });
+    }
+    let (ahead, behind) = ahead_behind(repo);
+    Ok(GitStatus {
+        is_repo: true,
+        branch: current_branch(repo),
+        changed_files: changed_files(repo)?,
+        conflicted_files: conflicted_files(repo),
+        ahead,
+        behind,
+        merging: is_merging(repo),
+    })
+}
+
+/// Stage everything and commit; `Ok(None)` when there was nothing to commit
+pub(crate) fn commit_all(repo: &Path, message: &str) -> Result<Option<String>, String> {
+    run_git(repo, &["add", "-A"])?;
+    if run_git(repo, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(None);
+    }
+    run_git(repo, &["commit", "-q", "-m", message])?;
+    Ok(Some(run_git(repo, &["rev-parse", "HEAD"])?.trim().to_string()))
+}
+
+fn remote_args<'a>(base: &[&'a str], remote: Option<&'a str>, branch: Option<&'a str>) -> Vec<&'a str> {
+    let mut args = base.to_vec();
+    if let Some(remote) = remote {
+        args.push(remote);
+        if let Some(branch) = branch {
+            args.push(branch);
+        }
+    }
+    args
+}
+
+pub(crate) fn push(repo: &Path, remote: Option<&str>, branch: Option<&str>) -> Result<(), RemoteError> {
+    run_git_remote(repo, remote, &remote_args(&["push"], remote, branch)).map(|_| ()).map_err(|e| {
+        let lower = e.to_lowercase();
+        if lower.contains("rejected") || lower.contains("fetch first") || lower.contains("non-fast-forward") {
+            RemoteError::Rejected(e)
+        } else {
+            RemoteError::Failed(e)
+        }
+    })
+}
+
+/// Fetch and merge (never rebase, so local history stays as committed), auto-resolving
+/// markdown conflicts where possible
+pub(crate) fn pull(repo: &Path, remote: Option<&str>, branch: Option<&str>) -> Result<(), RemoteError> {
+    match run_git_remote(repo, remote, &remote_args(&["pull", "--no-rebase", "--no-edit"], remote, branch)) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            if conflicted_files(repo).is_empty() {
+                return Err(RemoteError::Failed(e));
+            }
+            // Markdown edits that merely sit next to each other shouldn't need the user
+            let remaining = super::merge::auto_resolve(repo);
+            if remaining.is_empty() {
+                Ok(())
+            } else {
+                Err(RemoteError::Conflict(remaining))
+            }
+        }
+    }
+}
+
+/// Repository root and the file's path relative to it, with `/` separators
+pub(crate) fn locate_file(path: &Path) -> Result<(PathBuf, String), String> {
+    let dir = if path.is_dir() { path } else { path.parent().ok_or("Invalid file path")? };
+    let top = run_git(dir, &["rev-parse", "--show-toplevel"]).map_err(|_| "File is not inside a git repository".to_string())?;
+    let root = PathBuf::from(top.trim());
+    // Canonicalize both sides so symlinked workspaces (e.g. /var vs /private/var) still line up
+    let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+    let canonical_path = match path.canonicalize() {
+        Ok(p) => p,
+        // Deleted files: resolve the parent and re-attach the name
+        Err(_) => dir
+            .canonicalize()
+            .map_err(|e| format!("Invalid file path: {}", e))?
+            .join(path.file_name().ok_or("Invalid file path")?),
+    };
+    let relative = canonical_path
+        .strip_prefix(&canonical_root)
+        .map_err(|_| "File is not inside the repository".to_string())?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+    Ok((root, relative))
+}
+
+fn log_format() -> String {
+    format!("--format={}%H{}%an{}%ae{}%at{}%s", RECORD_SEP, FIELD_SEP, FIELD_SEP, FIELD_SEP, FIELD_SEP)
+}
+
+/// Parse `git log --name-only` output produced with `log_format()`
+fn parse_log(output: &str) -> Vec<GitCommitInfo> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let record = record.trim_start_matches('\n');
+            let (header, names) = record.split_once('\n').unwrap_or((record, ""));
+            let mut fields = header.split(FIELD_SEP);
+            let hash = fields.next()?.trim().to_string();
+            if hash.is_empty() {
+                return None;
+            }
+            let author = fields.next()?.to_string();
+            let author_email = fields.next()?.to_string();
+            let timestamp = fields.next()?.parse().ok()?;
+            let message = fields.next().unwrap_or_default().trim_end().to_string();
+            // `--name-only` lists the file's path in that commit after the header
+            let path = names.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default().to_string();
+            Some(GitCommitInfo {
+                short_hash: hash.chars().take(7).collect(),
+                hash,
+                author,
+                author_email,
+                timestamp,
+                message,
+                path,
+            })
+        })
+        .collect()
+}
+
+fn parse_range(range: &str) -> (usize, usize) {
+    let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+    (start.parse().unwrap_or(0), len.parse().unwrap_or(0))
+}
+
+/// Turn unified diff output into hunks; returns `None` for binary files
+fn parse_unified_diff(patch: &str) -> Option<Vec<DiffHunk>> {
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+    let (mut old_line, mut new_line) = (0, 0);
+
+    for line in patch.lines() {
+        if line.starts_with("Binary files ") {
+            return None;
+        }
+        if let Some(rest) = line.strip_prefix("@@ -") {
+            let Some((ranges, section)) = rest.split_once(" @@") else {
+                continue;
+            };
+            let (old, new) = ranges.split_once(" +").unwrap_or((ranges, "0,0"));
+            let (old_start, old_lines) = parse_range(old);
+            let (new_start, new_lines) = parse_range(new);
+            old_line = old_start;
+            new_line = new_start;
+            hunks.push(DiffHunk {
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                section: section.trim().to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let Some(hunk) = hunks.last_mut() else {
+            // Still in the file header (diff --git, index, ---/+++)
+            continue;
+        };
+        let (change_type, content) = match line.chars().next() {
+            Some('+') => ("add", &line[1..]),
+            Some('-') => ("delete", &line[1..]),
+            Some(' ') => ("unchanged", &line[1..]),
+            Some('\\') => continue, // "\ No newline at end of file"
+            _ => ("unchanged", line),
+        };
+        let (number_old, number_new) = match change_type {
+            "add" => (None, Some(new_line)),
+            "delete" => (Some(old_line), None),
+            _ => (Some(old_line), Some(new_line)),
+        };
+        if number_old.is_some() {
+            old_line += 1;
+        }
+        if number_new.is_some() {
+            new_line += 1;
+        }
+        hunk.lines.push(DiffLine {
+            line_number_old: number_old,
+            line_number_new: number_new,
+            content: content.to_string(),
+            change_type: change_type.to_string(),
+        });
+    }
+    Some(hunks)
+}
+
+/// Contents of `relative` at `commit`, decrypted when the note is encrypted
+pub(crate) fn show_file_at(root: &Path, relative: &str, commit: &str) -> Result<String, String> {
+    if commit.starts_with('-') {
+        return Err(format!("Invalid commit '{}'", commit));
+    }
+    let content = run_git(root, &["show", &format!("{}:{}", commit, relative)])?;
+    crate::encryption::decrypt_for_read(&root.join(relative).to_string_lossy(), content)
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn git_status(workspace_path: String) -> Result<GitStatus, LokusError> {
+    Ok(status(Path::new(&workspace_path))?)
+}
+
+#[tauri::command]
+pub async fn git_init(workspace_path: String) -> Result<(), LokusError> {
+    let repo = Path::new(&workspace_path);
+    if is_repo(repo) {
+        return Ok(());
+    }
+    run_git(repo, &["init", "-q"])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn git_commit(workspace_path: String, message: String) -> Result<Option<String>, LokusError> {
+    if message.trim().is_empty() {
+        return Err(LokusError::invalid("Commit message cannot be empty"));
+    }
+    Ok(commit_all(Path::new(&workspace_path), &message)?)
+}
+
+#[tauri::command]
+pub async fn git_push(workspace_path: String, remote: Option<String>, branch: Option<String>) -> Result<(), LokusError> {
+    Ok(push(Path::new(&workspace_path), remote.as_deref(), branch.as_deref())?)
+}
+
+#[tauri::command]
+pub async fn git_pull(workspace_path: String, remote: Option<String>, branch: Option<String>) -> Result<(), LokusError> {
+    Ok(pull(Path::new(&workspace_path), remote.as_deref(), branch.as_deref())?)
+}
+
+/// Save an HTTPS username/token used for pushes and pulls
+#[tauri::command]
+pub async fn git_set_credentials(username: String, token: String) -> Result<(), LokusError> {
+    let storage = SecureStorage::new().map_err(|e| e.to_string())?;
+    storage
+        .store(CREDENTIALS_KEY, &GitCredentials { username, token })
+        .map_err(|e| LokusError::Other(format!("Failed to store git credentials: {}", e)))
+}
+
+#[tauri::command]
+pub async fn git_clear_credentials() -> Result<(), LokusError> {
+    let storage = SecureStorage::new().map_err(|e| e.to_string())?;
+    Ok(storage.delete(CREDENTIALS_KEY).map_err(|e| e.to_string())?)
+}
+
+/// Commits touching `path`, newest first, following renames
+#[tauri::command]
+pub async fn git_file_log(path: String, limit: Option<usize>) -> Result<Vec<GitCommitInfo>, LokusError> {
+    let (root, relative) = locate_file(Path::new(&path))?;
+    let format = log_format();
+    let limit = format!("-n{}", limit.unwrap_or(100).max(1));
+    let output = run_git(&root, &["log", "--follow", "--name-only", &format, &limit, "--", &relative])?;
+    Ok(parse_log(&output))
+}
+
+/// File contents as of `commit`
+#[tauri::command]
+pub async fn git_show_file_at(path: String, commit: String) -> Result<String, LokusError> {
+    let (root, relative) = locate_file(Path::new(&path))?;
+    // Renamed files live under their old name in older commits
+    if commit.starts_with('-') {
+        return Err(LokusError::invalid(format!("Invalid commit '{}'", commit)));
+    }
+    let format = log_format();
+    let output = run_git(&root, &["log", "--follow", "--name-only", &format, "-n1", &commit, "--", &relative])?;
+    let historical = parse_log(&output)
+        .into_iter()
+        .next()
+        .map(|c| c.path)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(relative);
+    Ok(show_file_at(&root, &historical, &commit)?)
+}
+
+/// Structured diff of `path` between commits `a` and `b`; without `b`, against the working tree
+#[tauri::command]
+pub async fn git_diff_commits(path: String, a: String, b: Option<String>) -> Result<FileDiff, LokusError> {
+    let (root, relative) = locate_file(Path::new(&path))?;
+    if a.starts_with('-') || b.as_deref().map_or(false, |b| b.starts_with('-')) {
+        return Err(LokusError::invalid("Invalid commit"));
+    }
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-M", "-U3", a.as_str()];
+    if let Some(b) = b.as_deref() {
+        args.push(b);
+    }
+    args.extend(["--", relative.as_str()]);
+    let output = run_git(&root, &args)?;
+    let hunks = parse_unified_diff(&output);
+    Ok(FileDiff {
+        path: relative,
+        from: a,
+        to: b,
+        binary: hunks.is_none(),
+        hunks: hunks.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain() {
+        let out = " M notes/a.md\n?? new file.md\nR  old.md -> renamed.md\nUU \"conflict.md\"\n";
+        assert_eq!(
+            parse_porcelain(out),
+            vec!["notes/a.md", "new file.md", "renamed.md", "conflict.md"]
+        );
+    }
+
+    #[test]
+    fn test_remote_args() {
+        assert_eq!(remote_args(&["push"], None, Some("main")), vec!["push"]);
+        assert_eq!(remote_args(&["push"], Some("origin"), Some("main")), vec!["push", "origin", "main"]);
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let out = "\x1eabc1234567\x1fAda\x1fada@example.com\x1f1700000000\x1fEdit notes\n\nnotes/a.md\n\x1edef7654321\x1fBob\x1fbob@example.com\x1f1690000000\x1fCreate\n\nold.md\n";
+        let log = parse_log(out);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].short_hash, "abc1234");
+        assert_eq!(log[0].author, "Ada");
+        assert_eq!(log[0].timestamp, 1_700_000_000);
+        assert_eq!(log[0].message, "Edit notes");
+        assert_eq!(log[0].path, "notes/a.md");
+        assert_eq!(log[1].path, "old.md");
+    }
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let patch = "diff --git a/a.md b/a.md\nindex 1..2 100644\n--- a/a.md\n+++ b/a.md\n@@ -1,3 +1,3 @@ # Title\n one\n-two\n+deux\n three\n\\ No newline at end of file\n";
+        let hunks = parse_unified_diff(patch).unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].section, "# Title");
+        let lines = &hunks[0].lines;
+        assert_eq!(lines.len(), 4);
+        assert_eq!((lines[1].change_type.as_str(), lines[1].line_number_old, lines[1].line_number_new), ("delete", Some(2), None));
+        assert_eq!((lines[2].change_type.as_str(), lines[2].line_number_old, lines[2].line_number_new), ("add", None, Some(2)));
+        assert_eq!((lines[3].line_number_old, lines[3].line_number_new), (Some(3), Some(3)));
+
+        assert!(parse_unified_diff("Binary files a/x.png and b/x.png differ\n").is_none());
+    }
+}
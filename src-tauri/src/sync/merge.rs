@@ -0,0 +1,517 @@
+//! Three-way merge for files left conflicted by a git pull.
+//!
+//! The base/ours/theirs versions come from the index stages (`:1:`, `:2:`,
+//! `:3:`) and are merged line by line here rather than by parsing conflict
+//! markers, so regions are stable between listing and resolving. Markdown gets
+//! a lenient strategy: edits that only touch adjacent lines, and insertions
+//! both sides made at the same spot, merge without asking the user.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::git::{self, run_git};
+use crate::errors::LokusError;
+use crate::handlers::version_history::DiffLine;
+
+/// Above this many line pairs the changed middle is treated as one block
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Ours,
+    Theirs,
+}
+
+/// Replacement of base lines `start..end`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Chunk {
+    Merged(String),
+    Conflict { base: String, ours: String, theirs: String },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictRegion {
+    pub index: usize,
+    /// 1-based line in the merged file where the region starts
+    pub start_line: usize,
+    pub base: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileConflicts {
+    pub path: String,
+    /// False for add/add conflicts, where both sides created the file
+    pub has_base: bool,
+    pub regions: Vec<ConflictRegion>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolutionChoice {
+    Ours,
+    Theirs,
+    Base,
+    /// Ours followed by theirs
+    Both,
+    Custom,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    pub index: usize,
+    pub choice: ResolutionChoice,
+    /// Replacement text for `custom`
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveOutcome {
+    pub remaining_conflicts: Vec<String>,
+    /// Whether resolving the last file also concluded the merge commit
+    pub merge_committed: bool,
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// Line changes turning `base` into `other`, in base order
+fn diff<'a>(base: &[&str], other: &[&'a str]) -> Vec<Change<'a>> {
+    let prefix = base.iter().zip(other).take_while(|(a, b)| a == b).count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(other[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &base[prefix..base.len() - suffix];
+    let b = &other[prefix..other.len() - suffix];
+    if a.is_empty() && b.is_empty() {
+        return Vec::new();
+    }
+    if a.is_empty() || b.is_empty() || a.len() * b.len() > MAX_LCS_CELLS {
+        return vec![Change { start: prefix, end: prefix + a.len(), lines: b.to_vec() }];
+    }
+
+    // LCS table over the changed middle; lcs[i][j] covers a[i..] and b[j..]
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut pending: Option<Change> = None;
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            changes.extend(pending.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let change = pending.get_or_insert(Change { start: prefix + i, end: prefix + i, lines: Vec::new() });
+        if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            change.lines.push(b[j]);
+            j += 1;
+        } else {
+            i += 1;
+            change.end = prefix + i;
+        }
+    }
+    changes.extend(pending);
+    changes
+}
+
+/// Full line diff in the shape the version history view renders
+pub(crate) fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    let line = |kind: &str, text: &str, old_no: Option<usize>, new_no: Option<usize>| DiffLine {
+        line_number_old: old_no,
+        line_number_new: new_no,
+        content: text.trim_end_matches(['\n', '\r']).to_string(),
+        change_type: kind.to_string(),
+    };
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for change in diff(&old_lines, &new_lines) {
+        while i < change.start {
+            out.push(line("unchanged", old_lines[i], Some(i + 1), Some(j + 1)));
+            i += 1;
+            j += 1;
+        }
+        for removed in &old_lines[change.start..change.end] {
+            out.push(line("delete", removed, Some(i + 1), None));
+            i += 1;
+        }
+        for added in &change.lines {
+            out.push(line("add", added, None, Some(j + 1)));
+            j += 1;
+        }
+    }
+    while i < old_lines.len() {
+        out.push(line("unchanged", old_lines[i], Some(i + 1), Some(j + 1)));
+        i += 1;
+        j += 1;
+    }
+    out
+}
+
+fn overlaps(a: &Change, b: &Change, lenient: bool) -> bool {
+    if !lenient {
+        // Like git: touching edits conflict too
+        return a.start <= b.end && b.start <= a.end;
+    }
+    match (a.start == a.end, b.start == b.end) {
+        (false, false) => a.start < b.end && b.start < a.end,
+        (true, false) => b.start < a.start && a.start < b.end,
+        (false, true) => a.start < b.start && b.start < a.end,
+        (true, true) => a.start == b.start,
+    }
+}
+
+/// One side's text for base lines `start..end` with its changes applied
+fn apply_side(base: &[&str], start: usize, end: usize, changes: &[&Change]) -> String {
+    let mut out = String::new();
+    let mut pos = start;
+    for change in changes {
+        out.extend(base[pos..change.start.max(pos)].iter().copied());
+        out.extend(change.lines.iter().copied());
+        pos = change.end.max(pos);
+    }
+    out.extend(base[pos.min(end)..end].iter().copied());
+    out
+}
+
+fn push_merged(chunks: &mut Vec<Chunk>, text: String) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(Chunk::Merged(prev)) = chunks.last_mut() {
+        prev.push_str(&text);
+    } else {
+        chunks.push(Chunk::Merged(text));
+    }
+}
+
+/// Merge three versions into agreed text and conflicting regions
+pub(crate) fn merge3(base: &str, ours: &str, theirs: &str, lenient: bool) -> Vec<Chunk> {
+    let base_lines = split_lines(base);
+    let ours_lines = split_lines(ours);
+    let theirs_lines = split_lines(theirs);
+
+    let mut changes: Vec<(Side, Change)> = diff(&base_lines, &ours_lines)
+        .into_iter()
+        .map(|c| (Side::Ours, c))
+        .chain(diff(&base_lines, &theirs_lines).into_iter().map(|c| (Side::Theirs, c)))
+        .collect();
+    changes.sort_by_key(|(side, c)| (c.start, c.end, *side == Side::Theirs));
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    let mut pos = 0;
+    let mut idx = 0;
+    while idx < changes.len() {
+        // Grow a group while the next change overlaps anything already in it
+        let mut group = vec![&changes[idx]];
+        idx += 1;
+        while idx < changes.len() && group.iter().any(|(_, g)| overlaps(g, &changes[idx].1, lenient)) {
+            group.push(&changes[idx]);
+            idx += 1;
+        }
+        let start = group.iter().map(|(_, c)| c.start).min().unwrap_or(pos);
+        let end = group.iter().map(|(_, c)| c.end).max().unwrap_or(pos);
+        push_merged(&mut chunks, base_lines[pos..start].concat());
+        pos = end;
+
+        let side_changes = |side: Side| group.iter().filter(|(s, _)| *s == side).map(|(_, c)| c).collect::<Vec<_>>();
+        let ours_changes = side_changes(Side::Ours);
+        let theirs_changes = side_changes(Side::Theirs);
+        let ours_text = apply_side(&base_lines, start, end, &ours_changes);
+        let theirs_text = apply_side(&base_lines, start, end, &theirs_changes);
+
+        if theirs_changes.is_empty() {
+            push_merged(&mut chunks, ours_text);
+        } else if ours_changes.is_empty() || ours_text == theirs_text {
+            push_merged(&mut chunks, theirs_text);
+        } else if lenient && start == end {
+            // Both sides inserted at the same spot (e.g. appended to a list): keep both
+            push_merged(&mut chunks, ours_text);
+            push_merged(&mut chunks, theirs_text);
+        } else {
+            chunks.push(Chunk::Conflict { base: base_lines[start..end].concat(), ours: ours_text, theirs: theirs_text });
+        }
+    }
+    push_merged(&mut chunks, base_lines[pos..].concat());
+    chunks
+}
+
+fn is_markdown(relative: &str) -> bool {
+    let lower = relative.to_lowercase();
+    lower.ends_with(".md") || lower.ends_with(".markdown")
+}
+
+/// Stage `n` of a conflicted file, `None` when that side doesn't have it
+fn stage(root: &Path, relative: &str, n: u8) -> Option<String> {
+    let content = run_git(root, &["show", &format!(":{}:{}", n, relative)]).ok()?;
+    crate::encryption::decrypt_for_read(&root.join(relative).to_string_lossy(), content).ok()
+}
+
+struct Stages {
+    base: Option<String>,
+    ours: String,
+    theirs: String,
+}
+
+fn load_stages(root: &Path, relative: &str) -> Result<Stages, String> {
+    let ours = stage(root, relative, 2);
+    let theirs = stage(root, relative, 3);
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) => Ok(Stages { base: stage(root, relative, 1), ours, theirs }),
+        // modify/delete conflicts have nothing to merge line by line
+        _ => Err(format!("{} was deleted on one side; keep or remove it manually", relative)),
+    }
+}
+
+fn merge_stages(relative: &str, stages: &Stages) -> Vec<Chunk> {
+    merge3(stages.base.as_deref().unwrap_or(""), &stages.ours, &stages.theirs, is_markdown(relative))
+}
+
+fn write_and_stage(root: &Path, relative: &str, content: &str) -> Result<(), String> {
+    let path = root.join(relative);
+    let content = crate::encryption::encrypt_for_write(&path.to_string_lossy(), content)?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", relative, e))?;
+    run_git(root, &["add", "--", relative]).map(|_| ())
+}
+
+/// Conclude the merge once nothing is left unmerged
+fn commit_merge_if_done(root: &Path) -> Result<bool, String> {
+    if !git::conflicted_files(root).is_empty() || !git::is_merging(root) {
+        return Ok(false);
+    }
+    run_git(root, &["commit", "-q", "--no-edit"])?;
+    Ok(true)
+}
+
+/// Resolve conflicted markdown files that merge cleanly under the lenient strategy.
+/// Returns the files that still need the user.
+pub(crate) fn auto_resolve(root: &Path) -> Vec<String> {
+    let mut remaining = Vec::new();
+    for relative in git::conflicted_files(root) {
+        let merged = is_markdown(&relative)
+            .then(|| load_stages(root, &relative).ok())
+            .flatten()
+            .map(|stages| merge_stages(&relative, &stages))
+            .and_then(|chunks| {
+                chunks
+                    .into_iter()
+                    .map(|chunk| match chunk {
+                        Chunk::Merged(text) => Some(text),
+                        Chunk::Conflict { .. } => None,
+                    })
+                    .collect::<Option<String>>()
+            });
+        match merged {
+            Some(text) => {
+                if let Err(e) = write_and_stage(root, &relative, &text) {
+                    tracing::warn!("Failed to auto-resolve {}: {}", relative, e);
+                    remaining.push(relative);
+                }
+            }
+            None => remaining.push(relative),
+        }
+    }
+    if remaining.is_empty() {
+        if let Err(e) = commit_merge_if_done(root) {
+            tracing::warn!("Failed to commit auto-resolved merge: {}", e);
+            remaining = git::conflicted_files(root);
+        }
+    }
+    remaining
+}
+
+fn conflict_regions(chunks: &[Chunk]) -> Vec<ConflictRegion> {
+    let mut regions = Vec::new();
+    let mut line = 1;
+    for chunk in chunks {
+        match chunk {
+            Chunk::Merged(text) => line += text.matches('\n').count(),
+            Chunk::Conflict { base, ours, theirs } => {
+                regions.push(ConflictRegion {
+                    index: regions.len(),
+                    start_line: line,
+                    base: base.clone(),
+                    ours: ours.clone(),
+                    theirs: theirs.clone(),
+                });
+                // The UI shows our side in place until it's resolved
+                line += ours.matches('\n').count();
+            }
+        }
+    }
+    regions
+}
+
+fn apply_resolutions(chunks: Vec<Chunk>, resolutions: &[ConflictResolution]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut index = 0;
+    for chunk in chunks {
+        match chunk {
+            Chunk::Merged(text) => out.push_str(&text),
+            Chunk::Conflict { base, ours, theirs } => {
+                let resolution = resolutions
+                    .iter()
+                    .find(|r| r.index == index)
+                    .ok_or_else(|| format!("Conflict region {} has no resolution", index))?;
+                match resolution.choice {
+                    ResolutionChoice::Ours => out.push_str(&ours),
+                    ResolutionChoice::Theirs => out.push_str(&theirs),
+                    ResolutionChoice::Base => out.push_str(&base),
+                    ResolutionChoice::Both => {
+                        out.push_str(&ours);
+                        if !ours.is_empty() && !ours.ends_with('\n') {
+                            out.push('\n');
+                        }
+                        out.push_str(&theirs);
+                    }
+                    ResolutionChoice::Custom => out.push_str(
+                        resolution
+                            .content
+                            .as_deref()
+                            .ok_or_else(|| format!("Custom resolution for region {} has no content", index))?,
+                    ),
+                }
+                index += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
+// --- Tauri Commands ---
+
+/// Conflicting base/ours/theirs regions of a file left unmerged by a pull
+#[tauri::command]
+pub async fn git_get_conflict_regions(path: String) -> Result<FileConflicts, LokusError> {
+    let (root, relative) = git::locate_file(Path::new(&path))?;
+    let stages = load_stages(&root, &relative)?;
+    let regions = conflict_regions(&merge_stages(&relative, &stages));
+    Ok(FileConflicts { path: relative, has_base: stages.base.is_some(), regions })
+}
+
+/// Write the file with every region resolved, stage it, and finish the merge if it was the last one
+#[tauri::command]
+pub async fn git_resolve_conflict(path: String, resolutions: Vec<ConflictResolution>) -> Result<ResolveOutcome, LokusError> {
+    let (root, relative) = git::locate_file(Path::new(&path))?;
+    let stages = load_stages(&root, &relative)?;
+    let merged = apply_resolutions(merge_stages(&relative, &stages), &resolutions)?;
+    write_and_stage(&root, &relative, &merged)?;
+    let merge_committed = commit_merge_if_done(&root)?;
+    Ok(ResolveOutcome { remaining_conflicts: git::conflicted_files(&root), merge_committed })
+}
+
+#[tauri::command]
+pub async fn git_abort_merge(workspace_path: String) -> Result<(), LokusError> {
+    let root = Path::new(&workspace_path);
+    if !git::is_merging(root) {
+        return Err(LokusError::invalid("No merge in progress"));
+    }
+    run_git(root, &["merge", "--abort"])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(chunks: &[Chunk]) -> Option<String> {
+        chunks
+            .iter()
+            .map(|c| match c {
+                Chunk::Merged(t) => Some(t.as_str()),
+                Chunk::Conflict { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_non_overlapping_edits_merge() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "A\nb\nc\nd\ne\n";
+        let theirs = "a\nb\nc\nd\nE\n";
+        assert_eq!(merged(&merge3(base, ours, theirs, false)).unwrap(), "A\nb\nc\nd\nE\n");
+    }
+
+    #[test]
+    fn test_adjacent_edits_only_merge_leniently() {
+        let base = "a\nb\nc\n";
+        let ours = "A\nb\nc\n";
+        let theirs = "a\nB\nc\n";
+        assert!(merged(&merge3(base, ours, theirs, false)).is_none());
+        assert_eq!(merged(&merge3(base, ours, theirs, true)).unwrap(), "A\nB\nc\n");
+    }
+
+    #[test]
+    fn test_same_point_insertions_keep_both_for_markdown() {
+        let base = "- one\n";
+        let ours = "- one\n- two\n";
+        let theirs = "- one\n- three\n";
+        assert_eq!(merged(&merge3(base, ours, theirs, true)).unwrap(), "- one\n- two\n- three\n");
+        // Identical edits on both sides are never a conflict
+        assert_eq!(merged(&merge3(base, ours, ours, false)).unwrap(), ours);
+    }
+
+    #[test]
+    fn test_line_diff_numbers_lines() {
+        let lines = line_diff("a\nb\nc\n", "a\nB\nc\nd\n");
+        let summary: Vec<_> = lines
+            .iter()
+            .map(|l| (l.change_type.as_str(), l.content.as_str(), l.line_number_old, l.line_number_new))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("unchanged", "a", Some(1), Some(1)),
+                ("delete", "b", Some(2), None),
+                ("add", "B", None, Some(2)),
+                ("unchanged", "c", Some(3), Some(3)),
+                ("add", "d", None, Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_edits_conflict_and_resolve() {
+        let base = "title\nbody\nend\n";
+        let ours = "title\nmine\nend\n";
+        let theirs = "title\nyours\nend\n";
+        let chunks = merge3(base, ours, theirs, true);
+        let regions = conflict_regions(&chunks);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].start_line, 2);
+        assert_eq!((regions[0].base.as_str(), regions[0].ours.as_str(), regions[0].theirs.as_str()), ("body\n", "mine\n", "yours\n"));
+
+        let both = [ConflictResolution { index: 0, choice: ResolutionChoice::Both, content: None }];
+        assert_eq!(apply_resolutions(chunks.clone(), &both).unwrap(), "title\nmine\nyours\nend\n");
+        assert!(apply_resolutions(chunks, &[]).is_err());
+    }
+}
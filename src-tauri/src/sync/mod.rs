@@ -0,0 +1,11 @@
+//! Workspace synchronisation backends.
+
+pub mod autosync;
+pub mod chunks;
+pub mod conflicts;
+pub mod git;
+pub mod merge;
+pub mod provider;
+pub mod remote;
+pub mod selective;
+pub mod state;
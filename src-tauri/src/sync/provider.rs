@@ -0,0 +1,38 @@
+//! Storage backend interface for file-based sync providers.
+//!
+//! A backend is a flat key/value object store with version markers (ETags):
+//! WebDAV collections and S3 buckets both fit. The sync engine in
+//! `sync::remote` handles everything else on top of it (change detection,
+//! chunking, tombstones, conflicts).
+
+use async_trait::async_trait;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteObject {
+    /// `/`-separated key relative to the backend's root
+    pub key: String,
+    pub etag: String,
+    pub size: u64,
+}
+
+#[async_trait]
+pub trait SyncProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Every object below the root, recursively
+    async fn list(&self) -> Result<Vec<RemoteObject>, String>;
+
+    /// Contents and current ETag; `Ok(None)` if the object doesn't exist
+    async fn get(&self, key: &str) -> Result<Option<(Vec<u8>, String)>, String>;
+
+    /// Store the object, creating parent folders as needed, and return its new ETag
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<String, String>;
+
+    /// Remove the object; missing objects are not an error
+    async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// ETags arrive quoted and sometimes weak (`W/"abc"`); compare them bare
+pub fn normalize_etag(etag: &str) -> String {
+    etag.trim().trim_start_matches("W/").trim_matches('"').to_string()
+}
@@ -0,0 +1,676 @@
+//! Remote file sync against WebDAV or S3-compatible storage.
+//!
+//! The remote mirrors the workspace layout so it stays browsable: small
+//! files are stored as-is, while files above `chunks::DELTA_THRESHOLD` are
+//! stored as a `<path>.lokus-manifest` plus content-addressed chunks under
+//! `.lokus-sync/chunks/`, so editing a large attachment only uploads the
+//! chunks that changed. Change detection compares each object's ETag with
+//! the one recorded at the last sync. Deletions and renames travel as
+//! tombstones in `.lokus-sync/tombstones.json`. Files changed on both sides
+//! go to the conflict center instead of being overwritten.
+
+pub mod s3;
+pub mod webdav;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use super::chunks::{self, FileManifest};
+use super::conflicts;
+use super::provider::SyncProvider;
+use super::selective::{self, ScanMetrics};
+use super::state::{self, IndexEntry, LocalChange, SyncIndex, Tombstone};
+use crate::errors::LokusError;
+use crate::secure_storage::SecureStorage;
+
+pub const STATUS_EVENT: &str = "remote-sync-status";
+
+const CONFIG_FILE: &str = "remote.json";
+const META_PREFIX: &str = ".lokus-sync/";
+const CHUNK_PREFIX: &str = ".lokus-sync/chunks/";
+const TOMBSTONES_KEY: &str = ".lokus-sync/tombstones.json";
+const MANIFEST_SUFFIX: &str = ".lokus-manifest";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Webdav,
+    S3,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSyncConfig {
+    pub backend: Backend,
+    /// WebDAV collection URL, or the S3 endpoint (e.g. `https://s3.eu-west-1.amazonaws.com`)
+    pub endpoint: String,
+    #[serde(default)]
+    pub bucket: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Folder inside the bucket
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteCredentials {
+    /// WebDAV
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// S3
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+}
+
+/// Saved next to the sync index: config plus the ETags seen at the last sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteState {
+    #[serde(default)]
+    config: Option<RemoteSyncConfig>,
+    #[serde(default)]
+    etags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub deleted_remote: usize,
+    pub deleted_local: usize,
+    pub renamed: usize,
+    pub conflicts: usize,
+    pub errors: Vec<String>,
+    pub scan: ScanMetrics,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteSyncStatus {
+    pub workspace_path: String,
+    pub configured: bool,
+    pub backend: Option<Backend>,
+    pub endpoint: Option<String>,
+    pub syncing: bool,
+    /// Unix ms
+    pub last_sync_at: Option<i64>,
+    pub last_report: Option<SyncReport>,
+    pub last_error: Option<String>,
+}
+
+static STATUS: Lazy<Mutex<HashMap<PathBuf, RemoteSyncStatus>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+struct RemoteFile {
+    key: String,
+    etag: String,
+    chunked: bool,
+}
+
+fn state_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join("sync").join(CONFIG_FILE)
+}
+
+fn load_state(root: &Path) -> RemoteState {
+    fs::read_to_string(state_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(root: &Path, state: &RemoteState) -> Result<(), String> {
+    let path = state_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create sync directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize remote sync state: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write remote sync state: {}", e))
+}
+
+/// Secure storage key, one per workspace
+fn credentials_key(root: &Path) -> String {
+    let digest = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+    format!("remote_sync_{}", &digest[..16])
+}
+
+fn load_credentials(root: &Path) -> Result<RemoteCredentials, String> {
+    SecureStorage::new()
+        .map_err(|e| e.to_string())?
+        .retrieve(&credentials_key(root))
+        .map_err(|e| format!("Failed to read remote sync credentials: {}", e))?
+        .ok_or_else(|| "Remote sync credentials are missing; configure sync again".to_string())
+}
+
+fn build_provider(config: &RemoteSyncConfig, creds: &RemoteCredentials) -> Result<Box<dyn SyncProvider>, String> {
+    let require = |value: &Option<String>, name: &str| {
+        value.clone().filter(|v| !v.is_empty()).ok_or_else(|| format!("{} is required", name))
+    };
+    let provider: Box<dyn SyncProvider> = match config.backend {
+        Backend::Webdav => Box::new(webdav::WebDavProvider::new(
+            &config.endpoint,
+            &require(&creds.username, "Username")?,
+            &require(&creds.password, "Password")?,
+        )?),
+        Backend::S3 => Box::new(s3::S3Provider::new(
+            &config.endpoint,
+            &require(&config.bucket, "Bucket")?,
+            config.region.as_deref().unwrap_or_default(),
+            config.prefix.as_deref().unwrap_or_default(),
+            &require(&creds.access_key_id, "Access key ID")?,
+            &require(&creds.secret_access_key, "Secret access key")?,
+        )?),
+    };
+    Ok(provider)
+}
+
+fn update_status(app: &AppHandle, root: &Path, f: impl FnOnce(&mut RemoteSyncStatus)) {
+    let status = {
+        let mut statuses = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+        let status = statuses.entry(root.to_path_buf()).or_insert_with(|| initial_status(root));
+        f(status);
+        status.clone()
+    };
+    if let Err(e) = app.emit(STATUS_EVENT, &status) {
+        tracing::warn!("Failed to emit {}: {}", STATUS_EVENT, e);
+    }
+}
+
+fn initial_status(root: &Path) -> RemoteSyncStatus {
+    let config = load_state(root).config;
+    RemoteSyncStatus {
+        workspace_path: root.to_string_lossy().to_string(),
+        configured: config.is_some(),
+        backend: config.as_ref().map(|c| c.backend),
+        endpoint: config.map(|c| c.endpoint),
+        ..Default::default()
+    }
+}
+
+/// Split a listing into workspace files and the set of stored chunk hashes
+/// Whether a remote key names a file inside the workspace: plain relative
+/// segments only, so a hostile remote can't write through `..` or an absolute path
+fn is_safe_key(path: &str) -> bool {
+    !path.contains('\\')
+        && path.split('/').all(|segment| !matches!(segment, "" | "." | ".."))
+        && Path::new(path).components().all(|c| matches!(c, std::path::Component::Normal(_)))
+}
+
+fn split_listing(objects: Vec<super::provider::RemoteObject>) -> (HashMap<String, RemoteFile>, HashSet<String>) {
+    let mut files = HashMap::new();
+    let mut known_chunks = HashSet::new();
+    for object in objects {
+        if let Some(hash) = object.key.strip_prefix(CHUNK_PREFIX) {
+            known_chunks.insert(hash.to_string());
+        } else if object.key.starts_with(META_PREFIX) {
+            continue;
+        } else if !is_safe_key(object.key.strip_suffix(MANIFEST_SUFFIX).unwrap_or(&object.key)) {
+            tracing::warn!("Ignoring remote file with an unsafe path: {}", object.key);
+        } else if let Some(path) = object.key.strip_suffix(MANIFEST_SUFFIX) {
+            files.insert(path.to_string(), RemoteFile { key: object.key.clone(), etag: object.etag, chunked: true });
+        } else {
+            // A manifest wins over a stale plain copy of the same path
+            files
+                .entry(object.key.clone())
+                .or_insert(RemoteFile { key: object.key, etag: object.etag, chunked: false });
+        }
+    }
+    (files, known_chunks)
+}
+
+struct Session<'a> {
+    app: &'a AppHandle,
+    root: &'a Path,
+    provider: &'a dyn SyncProvider,
+    remote_files: HashMap<String, RemoteFile>,
+    known_chunks: HashSet<String>,
+    tombstones: BTreeMap<String, Tombstone>,
+    tombstones_dirty: bool,
+    etags: BTreeMap<String, String>,
+    report: SyncReport,
+}
+
+impl Session<'_> {
+    fn remote_changed(&self, path: &str) -> bool {
+        match (self.remote_files.get(path), self.etags.get(path)) {
+            (Some(remote), Some(known)) => remote.etag != *known,
+            (Some(_), None) | (None, Some(_)) => true,
+            (None, None) => false,
+        }
+    }
+
+    async fn upload(&mut self, path: &str) -> Result<(), String> {
+        let data = fs::read(self.root.join(path)).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let total = data.len() as u64;
+        let stale = self.remote_files.get(path).cloned();
+
+        let (key, etag, chunked) = if total >= chunks::DELTA_THRESHOLD {
+            let (manifest, pieces) = chunks::chunk_bytes(&data);
+            let mut transferred = 0u64;
+            for (chunk, bytes) in manifest.chunks.iter().zip(pieces) {
+                if self.known_chunks.contains(&chunk.hash) {
+                    continue;
+                }
+                self.provider.put(&format!("{}{}", CHUNK_PREFIX, chunk.hash), bytes.to_vec()).await?;
+                self.known_chunks.insert(chunk.hash.clone());
+                transferred += bytes.len() as u64;
+            }
+            let json = serde_json::to_vec(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+            transferred += json.len() as u64;
+            let key = format!("{}{}", path, MANIFEST_SUFFIX);
+            let etag = self.provider.put(&key, json).await?;
+            chunks::record_transfer(self.root, total, transferred.min(total));
+            (key, etag, true)
+        } else {
+            let etag = self.provider.put(path, data).await?;
+            chunks::record_transfer(self.root, total, total);
+            (path.to_string(), etag, false)
+        };
+
+        // The file crossed the chunking threshold; drop the other representation
+        if let Some(stale) = stale.filter(|s| s.key != key) {
+            self.provider.delete(&stale.key).await?;
+        }
+        self.etags.insert(path.to_string(), etag.clone());
+        self.remote_files.insert(path.to_string(), RemoteFile { key, etag, chunked });
+        if self.tombstones.remove(path).is_some() {
+            self.tombstones_dirty = true;
+        }
+        self.report.uploaded += 1;
+        Ok(())
+    }
+
+    /// Remote contents of `path` and the ETag they were read at
+    async fn fetch(&self, path: &str, remote: &RemoteFile) -> Result<(Vec<u8>, String), String> {
+        let (bytes, etag) = self
+            .provider
+            .get(&remote.key)
+            .await?
+            .ok_or_else(|| format!("{} disappeared from the remote", path))?;
+        let etag = if etag.is_empty() { remote.etag.clone() } else { etag };
+        if !remote.chunked {
+            chunks::record_transfer(self.root, bytes.len() as u64, bytes.len() as u64);
+            return Ok((bytes, etag));
+        }
+
+        let manifest: FileManifest =
+            serde_json::from_slice(&bytes).map_err(|e| format!("Invalid manifest for {}: {}", path, e))?;
+        let local = fs::read(self.root.join(path)).map(|d| chunks::local_chunks(&d)).unwrap_or_default();
+        let mut fetched: HashMap<String, Vec<u8>> = HashMap::new();
+        for chunk in chunks::missing_chunks(&manifest, &local.keys().cloned().collect()) {
+            let (data, _) = self
+                .provider
+                .get(&format!("{}{}", CHUNK_PREFIX, chunk.hash))
+                .await?
+                .ok_or_else(|| format!("Chunk {} of {} is missing on the remote", chunk.hash, path))?;
+            fetched.insert(chunk.hash.clone(), data);
+        }
+        let (data, transferred) = chunks::reconstruct(&manifest, &local, |chunk| {
+            fetched.get(&chunk.hash).cloned().ok_or_else(|| format!("Chunk {} was not downloaded", chunk.hash))
+        })?;
+        chunks::record_transfer(self.root, manifest.size, transferred + bytes.len() as u64);
+        Ok((data, etag))
+    }
+
+    fn write_local(&self, path: &str, data: &[u8]) -> Result<IndexEntry, String> {
+        if !is_safe_key(path) {
+            return Err(format!("Refusing to write outside the workspace: {}", path));
+        }
+        let target = self.root.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder for {}: {}", path, e))?;
+        }
+        fs::write(&target, data).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        Ok(entry_for(&target, blake3::hash(data).to_hex().to_string()))
+    }
+
+    async fn delete_remote(&mut self, path: &str, hash: String, renamed_to: Option<String>) -> Result<(), String> {
+        if let Some(remote) = self.remote_files.remove(path) {
+            self.provider.delete(&remote.key).await?;
+        }
+        self.etags.remove(path);
+        self.tombstones.insert(
+            path.to_string(),
+            Tombstone { hash, deleted_at: Utc::now().timestamp_millis(), renamed_to },
+        );
+        self.tombstones_dirty = true;
+        self.report.deleted_remote += 1;
+        Ok(())
+    }
+
+    /// Upload a locally added or edited file unless the remote changed too
+    async fn push_local(&mut self, index: &mut SyncIndex, change: &LocalChange, path: &str, entry: &IndexEntry) -> Result<(), String> {
+        let remote = self.remote_files.get(path).cloned();
+        if let Some(remote) = remote.filter(|_| self.remote_changed(path)) {
+            let acknowledged = conflicts::acknowledged_version(self.root, path);
+            if acknowledged.as_deref() != Some(remote.etag.as_str()) {
+                let (bytes, etag) = self.fetch(path, &remote).await?;
+                if blake3::hash(&bytes).to_hex().as_str() == entry.hash {
+                    // Same edit made on both sides
+                    self.etags.insert(path.to_string(), etag);
+                    index.apply(std::slice::from_ref(change));
+                } else {
+                    conflicts::record_conflict(self.app, self.root, path, self.provider.name(), &entry.hash, &etag, &bytes)?;
+                    self.report.conflicts += 1;
+                }
+                return Ok(());
+            }
+        }
+        self.upload(path).await?;
+        conflicts::clear_acknowledged(self.root, path)?;
+        index.apply(std::slice::from_ref(change));
+        Ok(())
+    }
+}
+
+fn entry_for(path: &Path, hash: String) -> IndexEntry {
+    let meta = fs::metadata(path).ok();
+    IndexEntry {
+        hash,
+        size: meta.as_ref().map_or(0, |m| m.len()),
+        modified: meta
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as i64),
+    }
+}
+
+async fn run_sync(app: &AppHandle, root: &Path, provider: &dyn SyncProvider) -> Result<SyncReport, String> {
+    let scan_root = root.to_path_buf();
+    let (scanned, scan) = tokio::task::spawn_blocking(move || selective::scan_workspace(&scan_root))
+        .await
+        .map_err(|e| format!("Workspace scan failed: {}", e))?;
+    let mut index = state::load_index(root);
+    let mut remote_state = load_state(root);
+    let local_changes = index.detect_changes(root, &scanned);
+
+    let (remote_files, known_chunks) = split_listing(provider.list().await?);
+    let tombstones = match provider.get(TOMBSTONES_KEY).await? {
+        Some((bytes, _)) => serde_json::from_slice(&bytes).unwrap_or_default(),
+        None => BTreeMap::new(),
+    };
+    let mut session = Session {
+        app,
+        root,
+        provider,
+        remote_files,
+        known_chunks,
+        tombstones,
+        tombstones_dirty: false,
+        etags: std::mem::take(&mut remote_state.etags),
+        report: SyncReport { scan, ..Default::default() },
+    };
+    let mut handled: HashSet<String> = HashSet::new();
+
+    // 1. Push local additions, edits, deletions and renames
+    for change in &local_changes {
+        let result = match change {
+            LocalChange::Added { path, entry } | LocalChange::Modified { path, entry } => {
+                handled.insert(path.clone());
+                if conflicts::is_conflicted(root, path) {
+                    continue;
+                }
+                session.push_local(&mut index, change, path, entry).await
+            }
+            LocalChange::Deleted { path } => {
+                // A remote edit after our delete wins; it's downloaded below
+                if session.remote_files.contains_key(path) && session.remote_changed(path) {
+                    continue;
+                }
+                handled.insert(path.clone());
+                let hash = index.entries.get(path).map(|e| e.hash.clone()).unwrap_or_default();
+                let result = session.delete_remote(path, hash, None).await;
+                if result.is_ok() {
+                    index.apply(std::slice::from_ref(change));
+                }
+                result
+            }
+            LocalChange::Renamed { from, to, entry } => {
+                handled.insert(from.clone());
+                handled.insert(to.clone());
+                let mut result = session.upload(to).await;
+                if result.is_ok() && !session.remote_changed(from) {
+                    result = session.delete_remote(from, entry.hash.clone(), Some(to.clone())).await;
+                }
+                if result.is_ok() {
+                    index.apply(std::slice::from_ref(change));
+                    session.report.renamed += 1;
+                }
+                result
+            }
+        };
+        if let Err(e) = result {
+            session.report.errors.push(e);
+        }
+    }
+
+    // 2. Apply remote deletions and renames to files we haven't touched
+    let vanished: Vec<String> = session
+        .etags
+        .keys()
+        .filter(|path| !session.remote_files.contains_key(*path) && !handled.contains(*path))
+        .cloned()
+        .collect();
+    for path in vanished {
+        handled.insert(path.clone());
+        let tombstone = session.tombstones.get(&path).cloned();
+        if let Some(tombstone) = &tombstone {
+            if !index.tombstone_applies(&path, tombstone) {
+                continue;
+            }
+        }
+        let local_path = root.join(&path);
+        let rename_target = tombstone
+            .as_ref()
+            .and_then(|t| t.renamed_to.clone())
+            .filter(|to| session.remote_files.contains_key(to) && !root.join(to).exists() && !handled.contains(to));
+
+        let result = match rename_target {
+            Some(to) if local_path.exists() => {
+                let target = root.join(&to);
+                let moved = target
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::rename(&local_path, &target))
+                    .map_err(|e| format!("Failed to rename {} to {}: {}", path, to, e));
+                if moved.is_ok() {
+                    if let Some(entry) = index.entries.get(&path).cloned() {
+                        index.apply(&[LocalChange::Renamed { from: path.clone(), to: to.clone(), entry }]);
+                    }
+                    if let Some(remote) = session.remote_files.get(&to) {
+                        session.etags.insert(to.clone(), remote.etag.clone());
+                    }
+                    handled.insert(to);
+                    session.report.renamed += 1;
+                }
+                moved
+            }
+            _ => {
+                let trashed = if local_path.exists() {
+                    crate::trash::move_to_trash(root, &local_path).map(|_| ())
+                } else {
+                    Ok(())
+                };
+                if trashed.is_ok() {
+                    index.apply(&[LocalChange::Deleted { path: path.clone() }]);
+                    session.report.deleted_local += 1;
+                }
+                trashed
+            }
+        };
+        session.etags.remove(&path);
+        if let Err(e) = result {
+            session.report.errors.push(e);
+        }
+    }
+
+    // 3. Download remote additions and edits
+    let incoming: Vec<(String, RemoteFile)> = session
+        .remote_files
+        .iter()
+        .filter(|(path, _)| !handled.contains(*path) && session.remote_changed(path))
+        .filter(|(path, _)| selective::is_selected(root, path) && !conflicts::is_conflicted(root, path))
+        .map(|(path, remote)| (path.clone(), remote.clone()))
+        .collect();
+    for (path, remote) in incoming {
+        let result = async {
+            let (bytes, etag) = session.fetch(&path, &remote).await?;
+            let entry = session.write_local(&path, &bytes)?;
+            index.tombstones.remove(&path);
+            index.entries.insert(path.clone(), entry);
+            session.etags.insert(path.clone(), etag);
+            session.report.downloaded += 1;
+            Ok::<(), String>(())
+        }
+        .await;
+        if let Err(e) = result {
+            session.report.errors.push(e);
+        }
+    }
+
+    if session.tombstones_dirty {
+        let json = serde_json::to_vec(&session.tombstones).map_err(|e| format!("Failed to serialize tombstones: {}", e))?;
+        provider.put(TOMBSTONES_KEY, json).await?;
+    }
+    remote_state.etags = session.etags;
+    save_state(root, &remote_state)?;
+    state::save_index(root, &index)?;
+    Ok(session.report)
+}
+
+// --- Tauri Commands ---
+
+/// Point the workspace at a WebDAV or S3 remote; the connection is tested before saving
+#[tauri::command]
+pub async fn remote_sync_configure(
+    app: AppHandle,
+    workspace_path: String,
+    backend: Backend,
+    endpoint: String,
+    credentials: RemoteCredentials,
+    bucket: Option<String>,
+    region: Option<String>,
+    prefix: Option<String>,
+) -> Result<RemoteSyncStatus, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    if !root.is_dir() {
+        return Err(LokusError::not_found(&root));
+    }
+    let config = RemoteSyncConfig { backend, endpoint: endpoint.trim().to_string(), bucket, region, prefix };
+    let provider = build_provider(&config, &credentials).map_err(LokusError::InvalidInput)?;
+    provider.list().await.map_err(|e| LokusError::Network(format!("Could not connect to remote: {}", e)))?;
+
+    SecureStorage::new()
+        .map_err(|e| e.to_string())?
+        .store(&credentials_key(&root), &credentials)
+        .map_err(|e| LokusError::Other(format!("Failed to store remote sync credentials: {}", e)))?;
+    let mut remote_state = load_state(&root);
+    // A different remote starts from a clean slate
+    let same_remote = matches!(&remote_state.config, Some(old)
+        if old.backend == config.backend && old.endpoint == config.endpoint && old.bucket == config.bucket && old.prefix == config.prefix);
+    if !same_remote {
+        remote_state.etags.clear();
+    }
+    remote_state.config = Some(config.clone());
+    save_state(&root, &remote_state)?;
+
+    update_status(&app, &root, |status| {
+        status.configured = true;
+        status.backend = Some(config.backend);
+        status.endpoint = Some(config.endpoint.clone());
+        status.last_error = None;
+    });
+    remote_sync_status(workspace_path).await
+}
+
+#[tauri::command]
+pub async fn remote_sync_now(app: AppHandle, workspace_path: String) -> Result<SyncReport, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let config = load_state(&root)
+        .config
+        .ok_or_else(|| LokusError::Unsupported("Remote sync is not configured for this workspace".to_string()))?;
+    let provider = build_provider(&config, &load_credentials(&root).map_err(LokusError::AuthRequired)?)?;
+
+    let already_running = {
+        let mut statuses = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+        let status = statuses.entry(root.clone()).or_insert_with(|| initial_status(&root));
+        std::mem::replace(&mut status.syncing, true)
+    };
+    if already_running {
+        return Err(LokusError::invalid("A sync is already running for this workspace"));
+    }
+    update_status(&app, &root, |_| {});
+
+    let result = run_sync(&app, &root, provider.as_ref()).await;
+    update_status(&app, &root, |status| {
+        status.syncing = false;
+        match &result {
+            Ok(report) => {
+                status.last_sync_at = Some(Utc::now().timestamp_millis());
+                status.last_report = Some(report.clone());
+                status.last_error = report.errors.first().cloned();
+            }
+            Err(e) => status.last_error = Some(e.clone()),
+        }
+    });
+    Ok(result?)
+}
+
+#[tauri::command]
+pub async fn remote_sync_status(workspace_path: String) -> Result<RemoteSyncStatus, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let mut statuses = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(statuses.entry(root.clone()).or_insert_with(|| initial_status(&root)).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::provider::RemoteObject;
+
+    fn object(key: &str, etag: &str) -> RemoteObject {
+        RemoteObject { key: key.to_string(), etag: etag.to_string(), size: 1 }
+    }
+
+    #[test]
+    fn test_split_listing() {
+        let (files, chunks) = split_listing(vec![
+            object("notes/a.md", "1"),
+            object("video.mp4", "old"),
+            object("video.mp4.lokus-manifest", "2"),
+            object(".lokus-sync/chunks/abc", "3"),
+            object(".lokus-sync/tombstones.json", "4"),
+        ]);
+        assert_eq!(files.len(), 2);
+        assert!(!files["notes/a.md"].chunked);
+        assert!(files["video.mp4"].chunked);
+        assert_eq!(files["video.mp4"].key, "video.mp4.lokus-manifest");
+        assert!(chunks.contains("abc"));
+    }
+
+    #[test]
+    fn test_split_listing_drops_unsafe_keys() {
+        let (files, _) = split_listing(vec![
+            object("../outside.md", "1"),
+            object("/etc/passwd", "2"),
+            object("notes/../../x.md", "3"),
+            object("a\\..\\b.md", "4"),
+            object("../big.bin.lokus-manifest", "5"),
+            object("notes/./b.md", "6"),
+            object("notes/ok.md", "7"),
+        ]);
+        assert_eq!(files.keys().collect::<Vec<_>>(), vec!["notes/ok.md"]);
+    }
+}
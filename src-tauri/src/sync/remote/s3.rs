@@ -0,0 +1,324 @@
+//! S3-compatible backend (AWS, MinIO, R2, Backblaze B2...) with SigV4 signing.
+//!
+//! Requests use path-style addressing (`endpoint/bucket/key`), which every
+//! S3-compatible server accepts.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::{Client, Method, StatusCode};
+use sha2::{Digest, Sha256};
+
+use crate::sync::provider::{normalize_etag, RemoteObject, SyncProvider};
+
+pub struct S3Provider {
+    client: Client,
+    endpoint: url::Url,
+    bucket: String,
+    region: String,
+    /// Key prefix inside the bucket, empty or ending in `/`
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = 64;
+    let mut block_key = if key.len() > BLOCK { Sha256::digest(key).to_vec() } else { key.to_vec() };
+    block_key.resize(BLOCK, 0);
+    let inner_pad: Vec<u8> = block_key.iter().map(|b| b ^ 0x36).collect();
+    let outer_pad: Vec<u8> = block_key.iter().map(|b| b ^ 0x5c).collect();
+    let inner = Sha256::new().chain_update(&inner_pad).chain_update(message).finalize();
+    Sha256::new().chain_update(&outer_pad).chain_update(inner).finalize().to_vec()
+}
+
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// RFC 3986 encoding as SigV4 expects; `/` kept in paths
+fn aws_encode(value: &str, keep_slash: bool) -> String {
+    if keep_slash {
+        value.split('/').map(|s| urlencoding::encode(s).into_owned()).collect::<Vec<_>>().join("/")
+    } else {
+        urlencoding::encode(value).into_owned()
+    }
+}
+
+#[derive(Default)]
+struct ListPage {
+    objects: Vec<RemoteObject>,
+    next_token: Option<String>,
+}
+
+fn parse_list(xml: &str) -> Result<ListPage, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut page = ListPage::default();
+    let mut current: Option<RemoteObject> = None;
+    let mut truncated = false;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                if e.local_name().as_ref() == b"Contents" {
+                    current = Some(RemoteObject { key: String::new(), etag: String::new(), size: 0 });
+                }
+                text.clear();
+            }
+            Ok(Event::Text(e)) => text.push_str(&e.unescape().map_err(|e| format!("Invalid S3 response: {}", e))?),
+            Ok(Event::End(e)) => {
+                let value = std::mem::take(&mut text);
+                match (e.local_name().as_ref(), current.as_mut()) {
+                    (b"Contents", _) => page.objects.extend(current.take()),
+                    (b"Key", Some(object)) => object.key = value,
+                    (b"ETag", Some(object)) => object.etag = normalize_etag(&value),
+                    (b"Size", Some(object)) => object.size = value.trim().parse().unwrap_or(0),
+                    (b"IsTruncated", None) => truncated = value.trim() == "true",
+                    (b"NextContinuationToken", None) => page.next_token = Some(value),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid S3 response at byte {}: {}", reader.buffer_position(), e)),
+            _ => {}
+        }
+    }
+    if !truncated {
+        page.next_token = None;
+    }
+    Ok(page)
+}
+
+impl S3Provider {
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        prefix: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Result<Self, String> {
+        let endpoint = url::Url::parse(endpoint).map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+        if bucket.trim().is_empty() {
+            return Err("S3 bucket is required".to_string());
+        }
+        let prefix = prefix.trim_matches('/');
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(120))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        Ok(Self {
+            client,
+            endpoint,
+            bucket: bucket.trim().to_string(),
+            region: if region.trim().is_empty() { "us-east-1".to_string() } else { region.trim().to_string() },
+            prefix: if prefix.is_empty() { String::new() } else { format!("{}/", prefix) },
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        })
+    }
+
+    fn host(&self) -> String {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
+    }
+
+    /// Build a signed request for `/<bucket>/<object>` with the given query parameters
+    fn signed(&self, method: Method, object: &str, query: &[(&str, &str)], body: &[u8]) -> reqwest::RequestBuilder {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256_hex(body);
+
+        let base_path = self.endpoint.path().trim_end_matches('/');
+        let path = if object.is_empty() {
+            format!("{}/{}", base_path, aws_encode(&self.bucket, false))
+        } else {
+            format!("{}/{}/{}", base_path, aws_encode(&self.bucket, false), aws_encode(object, true))
+        };
+        let mut params: Vec<(String, String)> =
+            query.iter().map(|(k, v)| (aws_encode(k, false), aws_encode(v, false))).collect();
+        params.sort();
+        let canonical_query = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let host = self.host();
+        let canonical_headers = format!("host:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n", host, payload_hash, amz_date);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method.as_str(),
+            path,
+            canonical_query,
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign =
+            format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_hex(canonical_request.as_bytes()));
+        let signature = hex::encode(hmac_sha256(
+            &signing_key(&self.secret_access_key, &date, &self.region, "s3"),
+            string_to_sign.as_bytes(),
+        ));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, path);
+        if !canonical_query.is_empty() {
+            url = format!("{}?{}", url, canonical_query);
+        }
+        self.client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("Authorization", authorization)
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+fn etag_header(response: &reqwest::Response) -> String {
+    response
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(normalize_etag)
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl SyncProvider for S3Provider {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn list(&self) -> Result<Vec<RemoteObject>, String> {
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2")];
+            if !self.prefix.is_empty() {
+                query.push(("prefix", self.prefix.as_str()));
+            }
+            if let Some(token) = token.as_deref() {
+                query.push(("continuation-token", token));
+            }
+            let response = self
+                .signed(Method::GET, "", &query, b"")
+                .send()
+                .await
+                .map_err(|e| format!("S3 request failed: {}", e))?;
+            let status = response.status();
+            let body = response.text().await.map_err(|e| format!("S3 request failed: {}", e))?;
+            if !status.is_success() {
+                return Err(format!("S3 list failed: {} {}", status, body));
+            }
+            let page = parse_list(&body)?;
+            objects.extend(page.objects.into_iter().filter_map(|mut object| {
+                object.key = object.key.strip_prefix(&self.prefix)?.to_string();
+                // Zero-byte "folder" markers created by some clients
+                (!object.key.is_empty() && !object.key.ends_with('/')).then_some(object)
+            }));
+            match page.next_token {
+                Some(next) => token = Some(next),
+                None => break,
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<(Vec<u8>, String)>, String> {
+        let response = self
+            .signed(Method::GET, &self.object_key(key), &[], b"")
+            .send()
+            .await
+            .map_err(|e| format!("S3 request failed: {}", e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let etag = etag_header(&response);
+                let bytes = response.bytes().await.map_err(|e| format!("S3 download failed: {}", e))?;
+                Ok(Some((bytes.to_vec(), etag)))
+            }
+            status => Err(format!("S3 GET {} failed: {}", key, status)),
+        }
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<String, String> {
+        let response = self
+            .signed(Method::PUT, &self.object_key(key), &[], &data)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| format!("S3 upload failed: {}", e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("S3 PUT {} failed: {}", key, status));
+        }
+        Ok(etag_header(&response))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let response = self
+            .signed(Method::DELETE, &self.object_key(key), &[], b"")
+            .send()
+            .await
+            .map_err(|e| format!("S3 request failed: {}", e))?;
+        let status = response.status();
+        if status.is_success() || status == StatusCode::NOT_FOUND {
+            Ok(())
+        } else {
+            Err(format!("S3 DELETE {} failed: {}", key, status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_and_signing_key() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Example from the AWS SigV4 documentation
+        assert_eq!(
+            hex::encode(signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam")),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let xml = r#"<ListBucketResult>
+  <IsTruncated>true</IsTruncated>
+  <Contents><Key>vault/a.md</Key><ETag>"abc"</ETag><Size>12</Size></Contents>
+  <Contents><Key>vault/dir/</Key><ETag>"d41d"</ETag><Size>0</Size></Contents>
+  <NextContinuationToken>tok==</NextContinuationToken>
+</ListBucketResult>"#;
+        let page = parse_list(xml).unwrap();
+        assert_eq!(page.objects.len(), 2);
+        assert_eq!(page.objects[0], RemoteObject { key: "vault/a.md".into(), etag: "abc".into(), size: 12 });
+        assert_eq!(page.next_token.as_deref(), Some("tok=="));
+        assert_eq!(aws_encode("my notes/a+b.md", true), "my%20notes/a%2Bb.md");
+    }
+}
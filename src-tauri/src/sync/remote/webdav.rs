@@ -0,0 +1,296 @@
+//! WebDAV backend (Nextcloud, ownCloud, any RFC 4918 server).
+
+use async_trait::async_trait;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::{Client, Method, StatusCode};
+
+use crate::sync::provider::{normalize_etag, RemoteObject, SyncProvider};
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:getetag/><d:getcontentlength/><d:resourcetype/></d:prop>
+</d:propfind>"#;
+
+pub struct WebDavProvider {
+    client: Client,
+    /// Collection URL with a trailing slash
+    base_url: String,
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct PropfindEntry {
+    href: String,
+    etag: String,
+    size: u64,
+    is_collection: bool,
+}
+
+fn method(name: &[u8]) -> Method {
+    Method::from_bytes(name).unwrap_or(Method::GET)
+}
+
+/// Percent-encode each key segment for use in a URL path
+fn encode_key(key: &str) -> String {
+    key.split('/').map(|segment| urlencoding::encode(segment).into_owned()).collect::<Vec<_>>().join("/")
+}
+
+fn parse_multistatus(xml: &str) -> Result<Vec<PropfindEntry>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut current: Option<PropfindEntry> = None;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                if e.local_name().as_ref() == b"response" {
+                    current = Some(PropfindEntry::default());
+                }
+                text.clear();
+            }
+            Ok(Event::Empty(e)) => {
+                if e.local_name().as_ref() == b"collection" {
+                    if let Some(entry) = current.as_mut() {
+                        entry.is_collection = true;
+                    }
+                }
+            }
+            Ok(Event::Text(e)) => {
+                text.push_str(&e.unescape().map_err(|e| format!("Invalid WebDAV response: {}", e))?);
+            }
+            Ok(Event::End(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                let value = std::mem::take(&mut text);
+                match (name.as_str(), current.as_mut()) {
+                    ("response", _) => entries.extend(current.take()),
+                    ("href", Some(entry)) => entry.href = value.trim().to_string(),
+                    ("getetag", Some(entry)) => entry.etag = normalize_etag(&value),
+                    ("getcontentlength", Some(entry)) => entry.size = value.trim().parse().unwrap_or(0),
+                    ("collection", Some(entry)) => entry.is_collection = true,
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid WebDAV response at byte {}: {}", reader.buffer_position(), e)),
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+impl WebDavProvider {
+    pub fn new(endpoint: &str, username: &str, password: &str) -> Result<Self, String> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(120))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let base_url = format!("{}/", endpoint.trim_end_matches('/'));
+        url::Url::parse(&base_url).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        Ok(Self { client, base_url, username: username.to_string(), password: password.to_string() })
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}{}", self.base_url, encode_key(key))
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth(&self.username, Some(&self.password))
+            .header("User-Agent", "Lokus/1.0 (WebDAV Sync)")
+    }
+
+    /// Key for an href from a PROPFIND response, if it lies under our root
+    fn key_for_href(&self, href: &str) -> Option<String> {
+        let base_path = url::Url::parse(&self.base_url).ok()?.path().to_string();
+        let path = match url::Url::parse(href) {
+            Ok(absolute) => absolute.path().to_string(),
+            Err(_) => href.to_string(),
+        };
+        let relative = path.strip_prefix(&base_path)?;
+        let decoded = urlencoding::decode(relative).ok()?.into_owned();
+        Some(decoded.trim_end_matches('/').to_string())
+    }
+
+    async fn propfind(&self, key: &str) -> Result<Vec<PropfindEntry>, String> {
+        let url = if key.is_empty() { self.base_url.clone() } else { format!("{}/", self.url(key)) };
+        let response = self
+            .request(method(b"PROPFIND"), &url)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(PROPFIND_BODY)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(Vec::new()),
+            status if status.is_success() => {
+                parse_multistatus(&response.text().await.map_err(|e| format!("WebDAV request failed: {}", e))?)
+            }
+            status => Err(format!("WebDAV PROPFIND {} failed: {}", url, status)),
+        }
+    }
+
+    /// Create every missing collection on the way to `key`
+    async fn ensure_parents(&self, key: &str) -> Result<(), String> {
+        let segments: Vec<&str> = key.split('/').collect();
+        for depth in 1..segments.len() {
+            let url = format!("{}/", self.url(&segments[..depth].join("/")));
+            let response = self
+                .request(method(b"MKCOL"), &url)
+                .send()
+                .await
+                .map_err(|e| format!("WebDAV request failed: {}", e))?;
+            // 405 means it already exists
+            let status = response.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(format!("WebDAV MKCOL {} failed: {}", url, status));
+            }
+        }
+        Ok(())
+    }
+
+    async fn current_etag(&self, key: &str) -> Result<String, String> {
+        let response = self
+            .request(Method::HEAD, &self.url(key))
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        Ok(response
+            .headers()
+            .get("etag")
+            .and_then(|v| v.to_str().ok())
+            .map(normalize_etag)
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl SyncProvider for WebDavProvider {
+    fn name(&self) -> &'static str {
+        "webdav"
+    }
+
+    async fn list(&self) -> Result<Vec<RemoteObject>, String> {
+        // Depth: infinity is disabled on most servers, so walk collection by collection
+        let mut objects = Vec::new();
+        let mut pending = vec![String::new()];
+        while let Some(dir) = pending.pop() {
+            for entry in self.propfind(&dir).await? {
+                let Some(key) = self.key_for_href(&entry.href) else {
+                    continue;
+                };
+                if key == dir {
+                    continue;
+                }
+                if entry.is_collection {
+                    pending.push(key);
+                } else {
+                    objects.push(RemoteObject { key, etag: entry.etag, size: entry.size });
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<(Vec<u8>, String)>, String> {
+        let response = self
+            .request(Method::GET, &self.url(key))
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let etag = response
+                    .headers()
+                    .get("etag")
+                    .and_then(|v| v.to_str().ok())
+                    .map(normalize_etag)
+                    .unwrap_or_default();
+                let bytes = response.bytes().await.map_err(|e| format!("WebDAV download failed: {}", e))?;
+                Ok(Some((bytes.to_vec(), etag)))
+            }
+            status => Err(format!("WebDAV GET {} failed: {}", key, status)),
+        }
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<String, String> {
+        let mut created_parents = false;
+        loop {
+            let response = self
+                .request(Method::PUT, &self.url(key))
+                .body(data.clone())
+                .send()
+                .await
+                .map_err(|e| format!("WebDAV upload failed: {}", e))?;
+            let status = response.status();
+            // 409 Conflict: a parent collection is missing
+            if status == StatusCode::CONFLICT && !created_parents {
+                self.ensure_parents(key).await?;
+                created_parents = true;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(format!("WebDAV PUT {} failed: {}", key, status));
+            }
+            let etag = response.headers().get("etag").and_then(|v| v.to_str().ok()).map(normalize_etag);
+            return match etag {
+                Some(etag) if !etag.is_empty() => Ok(etag),
+                // Not every server returns the ETag on PUT
+                _ => self.current_etag(key).await,
+            };
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        let response = self
+            .request(Method::DELETE, &self.url(key))
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        let status = response.status();
+        if status.is_success() || status == StatusCode::NOT_FOUND {
+            Ok(())
+        } else {
+            Err(format!("WebDAV DELETE {} failed: {}", key, status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/remote.php/dav/files/me/Lokus/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/me/Lokus/My%20Note.md</d:href>
+    <d:propstat><d:prop>
+      <d:getetag>&quot;5f2a&quot;</d:getetag>
+      <d:getcontentlength>42</d:getcontentlength>
+      <d:resourcetype/>
+    </d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let entries = parse_multistatus(xml).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_collection);
+        assert_eq!(entries[1].etag, "5f2a");
+        assert_eq!(entries[1].size, 42);
+
+        let provider = WebDavProvider::new("https://cloud.example.com/remote.php/dav/files/me/Lokus", "me", "pw").unwrap();
+        assert_eq!(provider.key_for_href(&entries[1].href).as_deref(), Some("My Note.md"));
+        assert_eq!(provider.key_for_href(&entries[0].href).as_deref(), Some(""));
+        assert_eq!(provider.url("a b/c.md"), "https://cloud.example.com/remote.php/dav/files/me/Lokus/a%20b/c.md");
+    }
+}
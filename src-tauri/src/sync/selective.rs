@@ -0,0 +1,356 @@
+//! Selective sync: which workspace files a sync provider should consider.
+//!
+//! Two mechanisms narrow the set. Sync roots (saved in `.lokus/sync.json`)
+//! limit syncing to chosen folders. A `.lokussyncignore` file at the
+//! workspace root holds gitignore-style patterns: `*`, `**`, `?`, a trailing
+//! `/` for folders only, a leading `/` to anchor at the root, and `!` to
+//! re-include. Providers call `scan_workspace`, which applies both and counts
+//! what it skipped.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+use crate::errors::LokusError;
+use crate::traversal::{VaultWalk, Visit};
+
+pub const IGNORE_FILE: &str = ".lokussyncignore";
+const CONFIG_FILE: &str = "sync.json";
+
+/// Never synced regardless of rules: app metadata, VCS data and dependencies
+const ALWAYS_EXCLUDED: &[&str] = &[".lokus", ".git", "node_modules", ".DS_Store"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSelectionConfig {
+    /// Workspace-relative folders to sync; empty means the whole workspace
+    #[serde(default)]
+    pub roots: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    segments: Vec<String>,
+    negated: bool,
+    dir_only: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanMetrics {
+    pub files: usize,
+    pub bytes: u64,
+    pub skipped_ignored: usize,
+    pub skipped_outside_roots: usize,
+    /// Cloud files that aren't downloaded, so there is nothing to sync yet
+    pub skipped_placeholders: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannedFile {
+    /// Workspace-relative with `/` separators
+    pub path: String,
+    pub size: u64,
+    /// Unix ms
+    pub modified: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSelection {
+    pub roots: Vec<String>,
+    pub ignore_patterns: Vec<String>,
+    /// Counts from the most recent scan, if one ran this session
+    pub last_scan: Option<ScanMetrics>,
+}
+
+static LAST_SCAN: Lazy<Mutex<HashMap<PathBuf, ScanMetrics>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Glob match of one path segment; `*` and `?` never cross a `/`
+fn segment_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| segment_matches(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && segment_matches(&pattern[1..], &text[1..]),
+        Some(c) => text.first() == Some(c) && segment_matches(&pattern[1..], &text[1..]),
+    }
+}
+
+fn segments_match(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(p) if p == "**" => (0..=path.len()).any(|skip| segments_match(&pattern[1..], &path[skip..])),
+        Some(p) => {
+            !path.is_empty()
+                && segment_matches(&p.chars().collect::<Vec<_>>(), &path[0].chars().collect::<Vec<_>>())
+                && segments_match(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+impl IgnoreRules {
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let dir_only = line.ends_with('/');
+                let line = line.trim_end_matches('/');
+                // Patterns without a slash match at any depth
+                let anchored = line.contains('/');
+                let mut segments: Vec<String> =
+                    line.trim_start_matches('/').split('/').filter(|s| !s.is_empty()).map(str::to_string).collect();
+                if !anchored {
+                    segments.insert(0, "**".to_string());
+                }
+                IgnoreRule { segments, negated, dir_only }
+            })
+            .filter(|rule| !rule.segments.is_empty())
+            .collect();
+        Self { rules }
+    }
+
+    pub fn load(root: &Path) -> Self {
+        Self::parse(&fs::read_to_string(root.join(IGNORE_FILE)).unwrap_or_default())
+    }
+
+    /// Whether a workspace-relative path is ignored; the last matching rule wins
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let parts: Vec<&str> = relative.split('/').filter(|s| !s.is_empty()).collect();
+        let mut ignored = false;
+        for rule in &self.rules {
+            // A rule for a folder also covers everything inside it
+            let hit = (1..=parts.len()).any(|len| {
+                let is_prefix_dir = len < parts.len() || is_dir;
+                (!rule.dir_only || is_prefix_dir) && segments_match(&rule.segments, &parts[..len])
+            });
+            if hit {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join(CONFIG_FILE)
+}
+
+pub fn load_config(root: &Path) -> SyncSelectionConfig {
+    fs::read_to_string(config_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(root: &Path, config: &SyncSelectionConfig) -> Result<(), String> {
+    let path = config_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create .lokus directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize sync config: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write sync config: {}", e))
+}
+
+fn in_roots(roots: &[String], relative: &str) -> bool {
+    roots.is_empty()
+        || roots.iter().any(|root| relative == root || relative.starts_with(&format!("{}/", root)))
+}
+
+/// Whether a single path would be picked up by `scan_workspace`; used for
+/// files that only exist on the remote so far
+pub fn is_selected(root: &Path, relative: &str) -> bool {
+    let segments: Vec<&str> = relative.split('/').collect();
+    if segments.iter().any(|s| ALWAYS_EXCLUDED.contains(s)) || !in_roots(&load_config(root).roots, relative) {
+        return false;
+    }
+    let rules = IgnoreRules::load(root);
+    (1..=segments.len()).all(|depth| !rules.is_ignored(&segments[..depth].join("/"), depth < segments.len()))
+}
+
+/// Every syncable file in the workspace, honouring sync roots and ignore rules
+pub fn scan_workspace(root: &Path) -> (Vec<ScannedFile>, ScanMetrics) {
+    let roots = load_config(root).roots;
+    let rules = IgnoreRules::load(root);
+    let mut metrics = ScanMetrics::default();
+    let mut files = Vec::new();
+
+    let mut walk = VaultWalk::new(root);
+    let mut walker = VaultWalk::walker(root).min_depth(1).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        match walk.visit(&entry) {
+            Visit::Read => {}
+            Visit::Stub => {
+                metrics.skipped_placeholders += 1;
+                continue;
+            }
+            Visit::Skip => {
+                if entry.file_type().is_dir() {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+        }
+        let Some(relative) = crate::links::relative_path(root, entry.path()) else {
+            continue;
+        };
+        let is_dir = entry.file_type().is_dir();
+        let name = entry.file_name().to_string_lossy();
+        if ALWAYS_EXCLUDED.contains(&name.as_ref()) {
+            if is_dir {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+
+        if rules.is_ignored(&relative, is_dir) {
+            if is_dir {
+                metrics.skipped_ignored += WalkDir::new(entry.path()).into_iter().flatten().filter(|e| e.file_type().is_file()).count();
+                walker.skip_current_dir();
+            } else {
+                metrics.skipped_ignored += 1;
+            }
+            continue;
+        }
+        if is_dir {
+            continue;
+        }
+        if !in_roots(&roots, &relative) {
+            metrics.skipped_outside_roots += 1;
+            continue;
+        }
+
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as i64);
+        metrics.files += 1;
+        metrics.bytes += meta.len();
+        files.push(ScannedFile { path: relative, size: meta.len(), modified });
+    }
+
+    LAST_SCAN
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(root.to_path_buf(), metrics.clone());
+    (files, metrics)
+}
+
+// --- Tauri Commands ---
+
+/// Limit syncing to these workspace-relative folders; an empty list syncs everything
+#[tauri::command]
+pub async fn sync_set_roots(workspace_path: String, paths: Vec<String>) -> Result<Vec<String>, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let mut roots = Vec::new();
+    for path in paths {
+        // Accept absolute paths from the file tree as well as relative ones
+        let relative = if Path::new(&path).is_absolute() {
+            crate::links::relative_path(&root, Path::new(&path))
+                .ok_or_else(|| LokusError::invalid(format!("{} is outside the workspace", path)))?
+        } else {
+            crate::links::normalize(&path).ok_or_else(|| LokusError::invalid(format!("Invalid sync root '{}'", path)))?
+        };
+        let relative = relative.trim_matches('/').to_string();
+        if !root.join(&relative).is_dir() {
+            return Err(LokusError::invalid(format!("Sync root '{}' is not a folder", relative)));
+        }
+        if !relative.is_empty() && !roots.contains(&relative) {
+            roots.push(relative);
+        }
+    }
+    roots.sort();
+    save_config(&root, &SyncSelectionConfig { roots: roots.clone() })?;
+    Ok(roots)
+}
+
+#[tauri::command]
+pub async fn sync_get_selection(workspace_path: String) -> Result<SyncSelection, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let ignore_patterns = fs::read_to_string(root.join(IGNORE_FILE))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    let last_scan = LAST_SCAN.lock().unwrap_or_else(|e| e.into_inner()).get(&root).cloned();
+    Ok(SyncSelection { roots: load_config(&root).roots, ignore_patterns, last_scan })
+}
+
+/// Re-scan now so the settings page can preview what the rules exclude
+#[tauri::command]
+pub async fn sync_scan_metrics(workspace_path: String) -> Result<ScanMetrics, LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    tokio::task::spawn_blocking(move || scan_workspace(&root).1)
+        .await
+        .map_err(|e| LokusError::Other(format!("Scan failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_patterns() {
+        let rules = IgnoreRules::parse("# comment\n*.mp4\nattachments/\n/private\n!attachments/keep.png\nnotes/**/draft-?.md\n");
+        assert!(rules.is_ignored("video.mp4", false));
+        assert!(rules.is_ignored("deep/dir/video.mp4", false));
+        assert!(rules.is_ignored("attachments/big.zip", false));
+        assert!(rules.is_ignored("sub/attachments/big.zip", false));
+        assert!(!rules.is_ignored("attachments/keep.png", false));
+        assert!(rules.is_ignored("private/diary.md", false));
+        assert!(!rules.is_ignored("other/private/diary.md", false));
+        assert!(rules.is_ignored("notes/a/b/draft-1.md", false));
+        assert!(!rules.is_ignored("notes/draft-12.md", false));
+        // Folder-only rules don't hit files of the same name
+        assert!(!rules.is_ignored("attachments", false));
+    }
+
+    #[test]
+    fn test_scan_respects_roots_and_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for path in ["Projects/a.md", "Projects/big.mp4", "Journal/b.md", ".lokus/x.json"] {
+            let file = root.join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, "x").unwrap();
+        }
+        fs::write(root.join(IGNORE_FILE), "*.mp4\n").unwrap();
+        save_config(root, &SyncSelectionConfig { roots: vec!["Projects".to_string()] }).unwrap();
+
+        let (files, metrics) = scan_workspace(root);
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["Projects/a.md"]);
+        assert_eq!(metrics.skipped_ignored, 1);
+        // Journal/b.md and the ignore file itself
+        assert_eq!(metrics.skipped_outside_roots, 2);
+
+        assert!(is_selected(root, "Projects/new.md"));
+        assert!(!is_selected(root, "Projects/clip.mp4"));
+        assert!(!is_selected(root, "Journal/c.md"));
+        assert!(!is_selected(root, "Projects/.lokus/x.json"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Per-workspace sync index with tombstones.
+//!
+//! The index remembers every file as of the last successful sync. Comparing a
+//! fresh scan against it tells deletions apart from files that were never
+//! synced. Deleted paths leave a tombstone so peers remove their copy instead
+//! of re-uploading it. A deletion plus an addition with the same content hash
+//! is a rename, so peers move the file rather than ending up with a duplicate.
+//! Tombstones are compacted after they've had time to reach every device.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::selective::ScannedFile;
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexEntry {
+    /// blake3, hex
+    pub hash: String,
+    pub size: u64,
+    /// Unix ms
+    pub modified: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tombstone {
+    /// Hash of the content that was deleted, so stale copies can be recognised
+    pub hash: String,
+    /// Unix ms
+    pub deleted_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncIndex {
+    #[serde(default)]
+    pub entries: BTreeMap<String, IndexEntry>,
+    #[serde(default)]
+    pub tombstones: BTreeMap<String, Tombstone>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LocalChange {
+    Added { path: String, entry: IndexEntry },
+    Modified { path: String, entry: IndexEntry },
+    Deleted { path: String },
+    Renamed { from: String, to: String, entry: IndexEntry },
+}
+
+fn index_path(root: &Path) -> PathBuf {
+    root.join(".lokus").join("sync").join(INDEX_FILE)
+}
+
+pub fn load_index(root: &Path) -> SyncIndex {
+    fs::read_to_string(index_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_index(root: &Path, index: &SyncIndex) -> Result<(), String> {
+    let path = index_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create sync directory: {}", e))?;
+    }
+    let json = serde_json::to_string(index).map_err(|e| format!("Failed to serialize sync index: {}", e))?;
+    // Write-then-rename so a crash never leaves a truncated index behind
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("Failed to write sync index: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write sync index: {}", e))
+}
+
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(blake3::hash(&data).to_hex().to_string())
+}
+
+impl SyncIndex {
+    /// Compare a scan with the index. Unchanged size and mtime reuse the stored hash.
+    pub fn detect_changes(&self, root: &Path, scanned: &[ScannedFile]) -> Vec<LocalChange> {
+        let mut changes = Vec::new();
+        let mut added: Vec<(String, IndexEntry)> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for file in scanned {
+            seen.insert(file.path.as_str());
+            let previous = self.entries.get(&file.path);
+            if let Some(prev) = previous {
+                if prev.size == file.size && prev.modified == file.modified {
+                    continue;
+                }
+            }
+            let Ok(hash) = hash_file(&root.join(&file.path)) else {
+                continue;
+            };
+            let entry = IndexEntry { hash, size: file.size, modified: file.modified };
+            match previous {
+                // Touched but identical content isn't worth syncing
+                Some(prev) if prev.hash == entry.hash => {}
+                Some(_) => changes.push(LocalChange::Modified { path: file.path.clone(), entry }),
+                None => added.push((file.path.clone(), entry)),
+            }
+        }
+
+        let mut deleted: Vec<&String> = self.entries.keys().filter(|path| !seen.contains(path.as_str())).collect();
+
+        // Pair each new file with a vanished one holding the same content
+        for (path, entry) in added {
+            let rename_from = deleted
+                .iter()
+                .position(|old| self.entries.get(*old).map_or(false, |e| e.hash == entry.hash));
+            match rename_from {
+                Some(i) => {
+                    let from = deleted.remove(i).clone();
+                    changes.push(LocalChange::Renamed { from, to: path, entry });
+                }
+                None => changes.push(LocalChange::Added { path, entry }),
+            }
+        }
+        changes.extend(deleted.into_iter().map(|path| LocalChange::Deleted { path: path.clone() }));
+        changes
+    }
+
+    /// Record changes as synced, leaving tombstones for removed paths
+    pub fn apply(&mut self, changes: &[LocalChange]) {
+        let now = Utc::now().timestamp_millis();
+        for change in changes {
+            match change {
+                LocalChange::Added { path, entry } | LocalChange::Modified { path, entry } => {
+                    self.tombstones.remove(path);
+                    self.entries.insert(path.clone(), entry.clone());
+                }
+                LocalChange::Deleted { path } => {
+                    if let Some(old) = self.entries.remove(path) {
+                        self.tombstones
+                            .insert(path.clone(), Tombstone { hash: old.hash, deleted_at: now, renamed_to: None });
+                    }
+                }
+                LocalChange::Renamed { from, to, entry } => {
+                    self.entries.remove(from);
+                    self.tombstones.remove(to);
+                    self.entries.insert(to.clone(), entry.clone());
+                    self.tombstones.insert(
+                        from.clone(),
+                        Tombstone { hash: entry.hash.clone(), deleted_at: now, renamed_to: Some(to.clone()) },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Whether a peer's tombstone should delete our copy: only if we haven't
+    /// edited the file since, otherwise the edit wins and is re-uploaded
+    pub fn tombstone_applies(&self, path: &str, tombstone: &Tombstone) -> bool {
+        match self.entries.get(path) {
+            Some(entry) => entry.hash == tombstone.hash || entry.modified <= tombstone.deleted_at,
+            None => true,
+        }
+    }
+
+    /// Drop tombstones older than `days`; returns how many were removed
+    pub fn gc_tombstones(&mut self, days: u32) -> usize {
+        let cutoff = Utc::now().timestamp_millis() - i64::from(days) * 24 * 60 * 60 * 1000;
+        let before = self.tombstones.len();
+        self.tombstones.retain(|_, t| t.deleted_at >= cutoff);
+        before - self.tombstones.len()
+    }
+
+    /// Old path to new path for every rename still covered by a tombstone
+    pub fn renamed_paths(&self) -> HashMap<&str, &str> {
+        self.tombstones
+            .iter()
+            .filter_map(|(from, t)| t.renamed_to.as_deref().map(|to| (from.as_str(), to)))
+            .collect()
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Compact tombstones older than `days` (default 30)
+#[tauri::command]
+pub async fn sync_gc_tombstones(workspace_path: String, days: Option<u32>) -> Result<usize, crate::errors::LokusError> {
+    let root = PathBuf::from(&workspace_path);
+    let mut index = load_index(&root);
+    let removed = index.gc_tombstones(days.unwrap_or(30));
+    if removed > 0 {
+        save_index(&root, &index)?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) -> ScannedFile {
+        let file = root.join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, content).unwrap();
+        ScannedFile { path: path.to_string(), size: content.len() as u64, modified: 1 }
+    }
+
+    #[test]
+    fn test_detects_deletes_and_renames() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let a = write(root, "a.md", "alpha");
+        let b = write(root, "b.md", "beta");
+        let mut index = SyncIndex::default();
+        let initial = index.detect_changes(root, &[a, b]);
+        assert_eq!(initial.len(), 2);
+        index.apply(&initial);
+
+        // a.md moved into a folder, b.md deleted
+        fs::remove_file(root.join("a.md")).unwrap();
+        let moved = write(root, "notes/a.md", "alpha");
+        let changes = index.detect_changes(root, &[moved]);
+        assert!(changes.contains(&LocalChange::Deleted { path: "b.md".to_string() }));
+        assert!(changes.iter().any(|c| matches!(c, LocalChange::Renamed { from, to, .. } if from == "a.md" && to == "notes/a.md")));
+        assert_eq!(changes.len(), 2);
+
+        index.apply(&changes);
+        assert_eq!(index.entries.keys().collect::<Vec<_>>(), vec!["notes/a.md"]);
+        assert_eq!(index.tombstones["a.md"].renamed_to.as_deref(), Some("notes/a.md"));
+        assert!(index.tombstones.contains_key("b.md"));
+        assert_eq!(index.renamed_paths().get("a.md"), Some(&"notes/a.md"));
+    }
+
+    #[test]
+    fn test_tombstone_gc_and_edit_wins() {
+        let mut index = SyncIndex::default();
+        let now = Utc::now().timestamp_millis();
+        index.tombstones.insert("old.md".into(), Tombstone { hash: "h1".into(), deleted_at: now - 40 * 86_400_000, renamed_to: None });
+        index.tombstones.insert("new.md".into(), Tombstone { hash: "h2".into(), deleted_at: now, renamed_to: None });
+        assert_eq!(index.gc_tombstones(30), 1);
+        assert!(index.tombstones.contains_key("new.md"));
+
+        index.entries.insert("new.md".into(), IndexEntry { hash: "h3".into(), size: 1, modified: now + 1000 });
+        assert!(!index.tombstone_applies("new.md", &index.tombstones["new.md"].clone()));
+    }
+}
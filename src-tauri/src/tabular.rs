@@ -0,0 +1,515 @@
+//! CSV and TSV files as typed, pageable tables.
+//!
+//! A file is decoded (BOM, then UTF-8, then Windows-1252 unless an encoding
+//! is given), its delimiter sniffed from the first lines, and each column
+//! typed from its values. Parsed tables are kept for the few most recently
+//! read files, keyed by size and mtime, so paging and querying a large file
+//! doesn't parse it again each time.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Parsed tables kept around
+const CACHE_SIZE: usize = 4;
+/// Lines looked at when sniffing the delimiter
+const SNIFF_LINES: usize = 20;
+/// Rows looked at when typing columns
+const TYPE_SAMPLE_ROWS: usize = 1000;
+const DEFAULT_PAGE_SIZE: usize = 200;
+const DELIMITERS: &[u8] = b",\t;|";
+
+static CACHE: Lazy<Mutex<Vec<(CacheKey, Arc<Table>)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+struct CacheKey {
+    path: PathBuf,
+    size: u64,
+    modified: Option<i64>,
+    options: ParseOptions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ParseOptions {
+    /// Sniffed when not given
+    pub delimiter: Option<char>,
+    /// Encoding label such as `utf-8`, `windows-1252` or `shift_jis`; detected when not given
+    pub encoding: Option<String>,
+    /// Whether the first row holds column names (default true)
+    pub has_header: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvReadOptions {
+    #[serde(flatten)]
+    pub parse: ParseOptions,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Integer,
+    Number,
+    Boolean,
+    Date,
+    Text,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Column {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ColumnType,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvPage {
+    pub columns: Vec<Column>,
+    /// Typed cells: numbers and booleans as JSON values, empty cells as null
+    pub rows: Vec<Vec<Value>>,
+    pub offset: usize,
+    /// Rows in the file, or matching the query
+    pub total_rows: usize,
+    pub delimiter: char,
+    pub encoding: String,
+    pub has_header: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Contains,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Empty,
+    NotEmpty,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvFilter {
+    /// Column name or index
+    pub column: Value,
+    pub op: FilterOp,
+    #[serde(default)]
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvSort {
+    /// Column name or index
+    pub column: Value,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvWriteOptions {
+    /// Defaults to the file's current delimiter, or by extension for new files
+    pub delimiter: Option<char>,
+    /// Written as the first row
+    pub header: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Value>>,
+    delimiter: u8,
+    encoding: &'static encoding_rs::Encoding,
+    has_header: bool,
+}
+
+/// Decode file bytes: an explicit label wins, then a BOM, then UTF-8 if the
+/// bytes are valid, else Windows-1252 (which accepts anything)
+fn decode(bytes: &[u8], label: Option<&str>) -> Result<(String, &'static encoding_rs::Encoding), String> {
+    let encoding = match label {
+        Some(label) => encoding_rs::Encoding::for_label(label.as_bytes()).ok_or_else(|| format!("Unknown encoding: {}", label))?,
+        None => match encoding_rs::Encoding::for_bom(bytes) {
+            Some((encoding, _)) => encoding,
+            None if std::str::from_utf8(bytes).is_ok() => encoding_rs::UTF_8,
+            None => encoding_rs::WINDOWS_1252,
+        },
+    };
+    let (text, encoding, _) = encoding.decode(bytes);
+    Ok((text.into_owned(), encoding))
+}
+
+/// Fields per line for a delimiter, ignoring delimiters inside quotes
+fn field_counts(sample: &str, delimiter: u8) -> Vec<usize> {
+    sample
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .map(|line| {
+            let mut quoted = false;
+            1 + line
+                .bytes()
+                .filter(|&b| {
+                    if b == b'"' {
+                        quoted = !quoted;
+                    }
+                    b == delimiter && !quoted
+                })
+                .count()
+        })
+        .collect()
+}
+
+/// The delimiter that splits the first lines into the same number of
+/// fields most often, preferring more fields on ties
+fn sniff_delimiter(text: &str, path: &Path) -> u8 {
+    let fallback = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tsv")) { b'\t' } else { b',' };
+    DELIMITERS
+        .iter()
+        .filter_map(|&delimiter| {
+            let counts = field_counts(text, delimiter);
+            let first = *counts.first()?;
+            if first < 2 {
+                return None;
+            }
+            let consistent = counts.iter().filter(|&&c| c == first).count();
+            Some((delimiter, consistent, first))
+        })
+        .max_by(|a, b| a.1.cmp(&b.1).then(a.2.cmp(&b.2)).then((a.0 == fallback).cmp(&(b.0 == fallback))))
+        .map_or(fallback, |(delimiter, _, _)| delimiter)
+}
+
+fn is_date(value: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        || chrono::DateTime::parse_from_rfc3339(value).is_ok()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// The narrowest type every non-empty sampled value fits
+fn infer_type<'a>(values: impl Iterator<Item = &'a str>) -> ColumnType {
+    let mut kind: Option<ColumnType> = None;
+    for value in values.map(str::trim).filter(|v| !v.is_empty()) {
+        let fits = |k: ColumnType| match k {
+            ColumnType::Integer => value.parse::<i64>().is_ok(),
+            ColumnType::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            ColumnType::Boolean => parse_bool(value).is_some(),
+            ColumnType::Date => is_date(value),
+            ColumnType::Text => true,
+        };
+        kind = Some(match kind {
+            None => [ColumnType::Integer, ColumnType::Number, ColumnType::Boolean, ColumnType::Date]
+                .into_iter()
+                .find(|&k| fits(k))
+                .unwrap_or(ColumnType::Text),
+            Some(ColumnType::Integer) if !fits(ColumnType::Integer) && fits(ColumnType::Number) => ColumnType::Number,
+            Some(k) if fits(k) => k,
+            Some(_) => ColumnType::Text,
+        });
+        if kind == Some(ColumnType::Text) {
+            break;
+        }
+    }
+    kind.unwrap_or(ColumnType::Text)
+}
+
+fn typed_cell(value: &str, kind: ColumnType) -> Value {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Value::Null;
+    }
+    match kind {
+        ColumnType::Integer => trimmed.parse::<i64>().map(Value::from).unwrap_or_else(|_| Value::from(value)),
+        ColumnType::Number => trimmed.parse::<f64>().map(Value::from).unwrap_or_else(|_| Value::from(value)),
+        ColumnType::Boolean => parse_bool(trimmed).map(Value::from).unwrap_or_else(|| Value::from(value)),
+        ColumnType::Date | ColumnType::Text => Value::from(value),
+    }
+}
+
+fn parse_table(text: &str, path: &Path, options: &ParseOptions, encoding: &'static encoding_rs::Encoding) -> Result<Table, String> {
+    let delimiter = match options.delimiter {
+        Some(c) if c.is_ascii() => c as u8,
+        Some(c) => return Err(format!("Delimiter must be a single ASCII character, not '{}'", c)),
+        None => sniff_delimiter(text, path),
+    };
+    let has_header = options.has_header.unwrap_or(true);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut records: Vec<Vec<String>> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        records.push(record.iter().map(String::from).collect());
+    }
+
+    let header = if has_header && !records.is_empty() { Some(records.remove(0)) } else { None };
+    let width = records.iter().map(Vec::len).chain(header.as_ref().map(Vec::len)).max().unwrap_or(0);
+    let columns: Vec<Column> = (0..width)
+        .map(|i| {
+            let name = header
+                .as_ref()
+                .and_then(|h| h.get(i))
+                .filter(|n| !n.trim().is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("Column {}", i + 1));
+            let kind = infer_type(records.iter().take(TYPE_SAMPLE_ROWS).filter_map(|r| r.get(i).map(String::as_str)));
+            Column { name, kind }
+        })
+        .collect();
+    let rows = records
+        .iter()
+        .map(|record| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| record.get(i).map_or(Value::Null, |v| typed_cell(v, column.kind)))
+                .collect()
+        })
+        .collect();
+
+    Ok(Table { columns, rows, delimiter, encoding, has_header })
+}
+
+/// The parsed table for `path`, from the cache when the file hasn't changed
+fn load(path: &Path, options: &ParseOptions) -> Result<Arc<Table>, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let key = CacheKey {
+        path: path.to_path_buf(),
+        size: meta.len(),
+        modified: crate::metadata_cache::to_ms(meta.modified()),
+        options: options.clone(),
+    };
+    if let Some((_, table)) = CACHE.lock().unwrap_or_else(|e| e.into_inner()).iter().find(|(k, _)| *k == key) {
+        return Ok(table.clone());
+    }
+
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let (text, encoding) = decode(&bytes, options.encoding.as_deref())?;
+    let table = Arc::new(parse_table(&text, path, options, encoding)?);
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.retain(|(k, _)| k.path != key.path);
+    cache.push((key, table.clone()));
+    if cache.len() > CACHE_SIZE {
+        cache.remove(0);
+    }
+    Ok(table)
+}
+
+fn column_index(table: &Table, column: &Value) -> Result<usize, String> {
+    let index = match column {
+        Value::Number(n) => n.as_u64().map(|i| i as usize),
+        Value::String(name) => table.columns.iter().position(|c| c.name == *name),
+        _ => None,
+    };
+    index.filter(|&i| i < table.columns.len()).ok_or_else(|| format!("Unknown column: {}", column))
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Numbers compare numerically, everything else as case-insensitive text;
+/// nulls sort first
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Less,
+        (_, Value::Null) => Ordering::Greater,
+        (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()).unwrap_or(Ordering::Equal),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ => as_text(a).to_lowercase().cmp(&as_text(b).to_lowercase()),
+    }
+}
+
+fn matches(cell: &Value, filter: &CsvFilter) -> bool {
+    // Compare a typed cell with the filter value, coercing text filter values
+    let wanted = match (cell, &filter.value) {
+        (Value::Number(_), Value::String(s)) => s.trim().parse::<f64>().map(Value::from).unwrap_or_else(|_| filter.value.clone()),
+        (Value::Bool(_), Value::String(s)) => parse_bool(s.trim()).map(Value::from).unwrap_or_else(|| filter.value.clone()),
+        _ => filter.value.clone(),
+    };
+    match filter.op {
+        FilterOp::Empty => cell.is_null(),
+        FilterOp::NotEmpty => !cell.is_null(),
+        FilterOp::Contains => as_text(cell).to_lowercase().contains(&as_text(&wanted).to_lowercase()),
+        FilterOp::Eq => compare(cell, &wanted) == Ordering::Equal,
+        FilterOp::Ne => compare(cell, &wanted) != Ordering::Equal,
+        _ if cell.is_null() => false,
+        FilterOp::Gt => compare(cell, &wanted) == Ordering::Greater,
+        FilterOp::Gte => compare(cell, &wanted) != Ordering::Less,
+        FilterOp::Lt => compare(cell, &wanted) == Ordering::Less,
+        FilterOp::Lte => compare(cell, &wanted) != Ordering::Greater,
+    }
+}
+
+fn page(table: &Table, rows: &[Vec<Value>], offset: usize, limit: usize) -> CsvPage {
+    CsvPage {
+        columns: table.columns.clone(),
+        rows: rows.iter().skip(offset).take(limit).cloned().collect(),
+        offset,
+        total_rows: rows.len(),
+        delimiter: table.delimiter as char,
+        encoding: table.encoding.name().to_string(),
+        has_header: table.has_header,
+    }
+}
+
+fn query(table: &Table, filters: &[CsvFilter], sort: Option<&CsvSort>) -> Result<Vec<Vec<Value>>, String> {
+    let filters: Vec<(usize, &CsvFilter)> = filters
+        .iter()
+        .map(|f| column_index(table, &f.column).map(|i| (i, f)))
+        .collect::<Result<_, _>>()?;
+    let mut rows: Vec<Vec<Value>> = table
+        .rows
+        .iter()
+        .filter(|row| filters.iter().all(|(i, f)| matches(&row[*i], f)))
+        .cloned()
+        .collect();
+    if let Some(sort) = sort {
+        let i = column_index(table, &sort.column)?;
+        rows.sort_by(|a, b| {
+            let order = compare(&a[i], &b[i]);
+            if sort.descending { order.reverse() } else { order }
+        });
+    }
+    Ok(rows)
+}
+
+fn write_table(path: &Path, rows: &[Vec<String>], options: &CsvWriteOptions) -> Result<(), String> {
+    crate::vaults::ensure_writable(path)?;
+    let delimiter = match options.delimiter {
+        Some(c) if c.is_ascii() => c as u8,
+        Some(c) => return Err(format!("Delimiter must be a single ASCII character, not '{}'", c)),
+        None if path.exists() => load(path, &ParseOptions::default())?.delimiter,
+        None => sniff_delimiter("", path),
+    };
+
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).flexible(true).from_writer(Vec::new());
+    for row in options.header.iter().chain(rows) {
+        writer.write_record(row).map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+    let bytes = writer.into_inner().map_err(|e| format!("Failed to write rows: {}", e))?;
+
+    let target = path.to_string_lossy().to_string();
+    let _lock = crate::file_locking::FileLockGuard::for_write(&target)?;
+    crate::handlers::files::durable_write(path, &bytes)
+}
+
+// --- Tauri Commands ---
+
+/// A page of rows from a CSV/TSV file, with typed columns
+#[tauri::command]
+pub async fn csv_read(path: String, options: Option<CsvReadOptions>) -> Result<CsvPage, String> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let table = load(Path::new(&path), &options.parse)?;
+        let limit = options.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        Ok(page(&table, &table.rows, options.offset, limit))
+    })
+    .await
+    .map_err(|e| format!("CSV read failed: {}", e))?
+}
+
+/// Rows matching every filter, optionally sorted by one column
+#[tauri::command]
+pub async fn csv_query(
+    path: String,
+    filter: Option<Vec<CsvFilter>>,
+    sort: Option<CsvSort>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    options: Option<ParseOptions>,
+) -> Result<CsvPage, String> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let table = load(Path::new(&path), &options)?;
+        let rows = query(&table, &filter.unwrap_or_default(), sort.as_ref())?;
+        Ok(page(&table, &rows, offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PAGE_SIZE)))
+    })
+    .await
+    .map_err(|e| format!("CSV query failed: {}", e))?
+}
+
+/// Replace a CSV/TSV file with `rows` (UTF-8), keeping its delimiter
+#[tauri::command]
+pub async fn csv_write(path: String, rows: Vec<Vec<String>>, options: Option<CsvWriteOptions>) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || write_table(Path::new(&path), &rows, &options))
+        .await
+        .map_err(|e| format!("CSV write failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sniffing_and_typed_columns() {
+        let text = "name;age;score;active;joined\n\"Smith; J\";42;3.5;yes;2024-01-02\nLee;7;;no;2023-12-31\n";
+        let table = parse_table(text, Path::new("people.csv"), &ParseOptions::default(), encoding_rs::UTF_8).unwrap();
+        assert_eq!(table.delimiter, b';');
+        let kinds: Vec<ColumnType> = table.columns.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [ColumnType::Text, ColumnType::Integer, ColumnType::Number, ColumnType::Boolean, ColumnType::Date]);
+        assert_eq!(table.rows[0], vec![json!("Smith; J"), json!(42), json!(3.5), json!(true), json!("2024-01-02")]);
+        assert_eq!(table.rows[1][2], Value::Null);
+
+        assert_eq!(sniff_delimiter("a\tb\tc\n1\t2\t3\n", Path::new("x.txt")), b'\t');
+        assert_eq!(sniff_delimiter("single\n", Path::new("x.tsv")), b'\t');
+    }
+
+    #[test]
+    fn test_decoding() {
+        let (text, encoding) = decode(b"caf\xe9", None).unwrap();
+        assert_eq!((text.as_str(), encoding.name()), ("café", "windows-1252"));
+        let (text, encoding) = decode(b"\xef\xbb\xbfa,b", None).unwrap();
+        assert_eq!((text.as_str(), encoding.name()), ("a,b", "UTF-8"));
+    }
+
+    #[test]
+    fn test_query_and_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.tsv");
+        fs::write(&path, "city\tpop\nOslo\t700\nBergen\t285\nAsker\t\n").unwrap();
+        let table = load(&path, &ParseOptions::default()).unwrap();
+
+        let filters = vec![CsvFilter { column: json!("pop"), op: FilterOp::Gt, value: json!("300") }];
+        let rows = query(&table, &filters, None).unwrap();
+        assert_eq!(rows, vec![vec![json!("Oslo"), json!(700)]]);
+
+        let sort = CsvSort { column: json!(1), descending: true };
+        let rows = query(&table, &[], Some(&sort)).unwrap();
+        assert_eq!(rows[0][0], json!("Oslo"));
+        assert_eq!(rows[2][1], Value::Null);
+
+        let options = CsvWriteOptions { header: Some(vec!["city".into(), "pop".into()]), ..Default::default() };
+        write_table(&path, &[vec!["Tromsø".into(), "77".into()]], &options).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "city\tpop\nTromsø\t77\n");
+    }
+}
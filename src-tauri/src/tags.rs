@@ -0,0 +1,367 @@
+//! Tags.
+//!
+//! Tags come from `#inline` tags in note bodies and the `tags` frontmatter
+//! field; `#project/alpha` nests under `project`. Extraction lives here and the
+//! metadata cache stores the result, which is the persistent index the
+//! commands read from. Renames and merges rewrite every affected note and are
+//! all-or-nothing: if any write fails, notes already rewritten are restored.
+
+use crate::metadata_cache::{self, MetadataFilter};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+// #tag or #nested/tag, not inside words, URLs or headings
+static INLINE_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|[\s(])#([\p{L}\p{N}_][\p{L}\p{N}_/-]*)").unwrap());
+// One item of a YAML tag value: a quoted string, a comment, or a plain scalar
+static YAML_ITEM_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#""(?:[^"\\]|\\.)*"|'(?:[^']|'')*'|(?:^|\s)#.*|[^\s\[\],]+"#).unwrap());
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagInfo {
+    /// Full tag, e.g. `project/alpha`
+    pub tag: String,
+    /// Last segment, e.g. `alpha`
+    pub name: String,
+    /// Notes carrying exactly this tag
+    pub count: usize,
+    /// Notes carrying this tag or any tag nested below it
+    pub total: usize,
+    pub children: Vec<TagInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRenameResult {
+    pub updated: Vec<String>,
+    /// Encrypted notes that could not be rewritten
+    pub skipped: Vec<String>,
+}
+
+// --- Extraction ---
+
+fn clean(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').trim_end_matches(['/', '-']);
+    // Pure numbers are issue references like #123, not tags
+    (!tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit())).then(|| tag.to_string())
+}
+
+fn frontmatter_key(frontmatter: &JsonValue) -> Option<&'static str> {
+    ["tags", "tag"].into_iter().find(|key| frontmatter.get(key).is_some())
+}
+
+fn frontmatter_tags(frontmatter: Option<&JsonValue>) -> Vec<String> {
+    let Some(value) = frontmatter.and_then(|fm| fm.get(frontmatter_key(fm)?)) else {
+        return Vec::new();
+    };
+    match value {
+        JsonValue::String(s) => s.split([',', ' ']).filter_map(clean).collect(),
+        JsonValue::Array(items) => items.iter().filter_map(|v| v.as_str()).filter_map(clean).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Apply `f` to every inline tag outside code fences; `f` returns a replacement
+fn map_inline_tags(body: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut in_fence = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.trim_start().starts_with("```") {
+            output.push_str(line);
+            continue;
+        }
+        let mut last = 0;
+        for caps in INLINE_TAG_RE.captures_iter(line) {
+            let m = caps.get(1).expect("tag group");
+            let Some(tag) = clean(m.as_str()) else { continue };
+            if let Some(replacement) = f(&tag) {
+                output.push_str(&line[last..m.start()]);
+                output.push_str(&replacement);
+                last = m.start() + tag.len();
+            }
+        }
+        output.push_str(&line[last..]);
+    }
+    output
+}
+
+/// Tags of a note, frontmatter first, without case-insensitive duplicates
+pub(crate) fn extract(frontmatter: Option<&JsonValue>, body: &str) -> Vec<String> {
+    let mut tags = frontmatter_tags(frontmatter);
+    map_inline_tags(body, |tag| {
+        tags.push(tag.to_string());
+        None
+    });
+    let mut seen = HashSet::new();
+    tags.retain(|t| seen.insert(t.to_lowercase()));
+    tags
+}
+
+// --- Rewriting ---
+
+/// `tag` with the `old` prefix replaced by `new`, if `tag` is `old` or nested below it
+fn renamed(tag: &str, old: &str, new: &str) -> Option<String> {
+    let prefix = tag.get(..old.len())?;
+    let rest = &tag[old.len()..];
+    (prefix.to_lowercase() == old.to_lowercase() && (rest.is_empty() || rest.starts_with('/'))).then(|| format!("{}{}", new, rest))
+}
+
+/// Rename the tags in one line of a YAML tag value, leaving quoting,
+/// separators and comments as they are. With `split`, the value is a single
+/// string listing several tags, as `frontmatter_tags` reads it.
+fn rename_yaml_items(line: &str, old: &str, new: &str, split: bool) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut last = 0;
+    for m in YAML_ITEM_RE.find_iter(line) {
+        let item = m.as_str();
+        if item.trim_start().starts_with('#') && (m.start() == 0 || item.starts_with(char::is_whitespace)) {
+            break;
+        }
+        let quote = item.starts_with(['"', '\'']) as usize;
+        let inner = &item[quote..item.len() - quote];
+        let mut start = m.start() + quote;
+        for piece in inner.split(|c| split && (c == ',' || c == ' ')) {
+            let piece_start = start;
+            start += piece.len() + 1;
+            // `clean` drops leading space and `#`s; the tag starts after them
+            let lead = piece.len() - piece.trim_start().trim_start_matches('#').len();
+            let Some(tag) = clean(piece) else { continue };
+            let Some(replacement) = renamed(&tag, old, new) else { continue };
+            let tag_start = piece_start + lead;
+            output.push_str(&line[last..tag_start]);
+            output.push_str(&replacement);
+            last = tag_start + tag.len();
+        }
+    }
+    output.push_str(&line[last..]);
+    output
+}
+
+/// Rename `old` (and tags nested under it) to `new` in one note. Only the
+/// tag values change; the rest of the frontmatter keeps its formatting.
+pub(crate) fn rename_in_note(content: &str, old: &str, new: &str) -> Result<Option<String>, String> {
+    let body = crate::export::strip_frontmatter(content);
+    let header = &content[..content.len() - body.len()];
+    let frontmatter = crate::frontmatter::parse(content);
+    let key = frontmatter.as_ref().and_then(frontmatter_key);
+    let split = key.and_then(|key| frontmatter.as_ref()?.get(key)).map_or(false, JsonValue::is_string);
+
+    let mut updated = String::with_capacity(content.len());
+    let mut in_value = false;
+    for line in header.split_inclusive('\n') {
+        let is_key = key.map_or(false, |key| crate::frontmatter::is_key_line(line, key));
+        if in_value && !is_key && (line.starts_with([' ', '\t']) || line.starts_with('-')) {
+            updated.push_str(&rename_yaml_items(line, old, new, split));
+            continue;
+        }
+        in_value = is_key;
+        match key.filter(|_| is_key) {
+            Some(key) => {
+                updated.push_str(&line[..key.len() + 1]);
+                updated.push_str(&rename_yaml_items(&line[key.len() + 1..], old, new, split));
+            }
+            None => updated.push_str(line),
+        }
+    }
+    updated.push_str(&map_inline_tags(body, |tag| renamed(tag, old, new)));
+
+    Ok((updated != content).then_some(updated))
+}
+
+fn validate(tag: &str) -> Result<String, String> {
+    let cleaned = clean(tag).ok_or_else(|| format!("Invalid tag '{}'", tag))?;
+    let valid = cleaned
+        .split('/')
+        .all(|segment| !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-'));
+    if !valid {
+        return Err(format!("Invalid tag '{}': use letters, numbers, '_', '-' and '/'", tag));
+    }
+    Ok(cleaned)
+}
+
+/// Rename a tag across the workspace, restoring every note if any write fails
+pub fn rename(root: &Path, old: &str, new: &str) -> Result<TagRenameResult, String> {
+    let (old, new) = (validate(old)?, validate(new)?);
+    crate::vaults::ensure_writable(root)?;
+    let notes = metadata_cache::query(
+        root,
+        &MetadataFilter {
+            tag: Some(old.clone()),
+            ..Default::default()
+        },
+    )?;
+
+    // Plan every change before writing anything
+    let mut result = TagRenameResult::default();
+    let mut plan: Vec<(String, String, String)> = Vec::new();
+    for note in notes {
+        let original = fs::read_to_string(&note.path).map_err(|e| format!("Failed to read {}: {}", note.path, e))?;
+        if crate::encryption::is_encrypted(&original) {
+            result.skipped.push(note.path);
+            continue;
+        }
+        if let Some(updated) = rename_in_note(&original, &old, &new)? {
+            plan.push((note.path, original, updated));
+        }
+    }
+
+    for (index, (path, _, updated)) in plan.iter().enumerate() {
+        if let Err(e) = crate::handlers::files::write_file_content(path.clone(), updated.clone()) {
+            for (path, original, _) in &plan[..index] {
+                if let Err(restore_error) = crate::handlers::files::write_file_content(path.clone(), original.clone()) {
+                    tracing::error!("Failed to restore {} after a failed tag rename: {}", path, restore_error);
+                }
+            }
+            return Err(format!("Tag rename rolled back: failed to write {}: {}", path, e));
+        }
+    }
+
+    result.updated = plan.into_iter().map(|(path, _, _)| path).collect();
+    Ok(result)
+}
+
+/// Nest flat (tag, count) pairs into a tree, adding parents that only exist as prefixes
+fn build_tree(counts: BTreeMap<String, (String, HashSet<String>)>) -> Vec<TagInfo> {
+    fn children_of(parent: &str, counts: &BTreeMap<String, (String, HashSet<String>)>) -> Vec<TagInfo> {
+        let mut names: Vec<&String> = counts
+            .keys()
+            .filter(|key| match parent {
+                "" => !key.contains('/'),
+                parent => key.strip_prefix(parent).and_then(|r| r.strip_prefix('/')).map_or(false, |r| !r.contains('/')),
+            })
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|key| {
+                let (display, notes) = &counts[key];
+                let nested = format!("{}/", key);
+                let total: HashSet<&String> = counts
+                    .iter()
+                    .filter(|(k, _)| *k == key || k.starts_with(&nested))
+                    .flat_map(|(_, (_, notes))| notes)
+                    .collect();
+                TagInfo {
+                    name: display.rsplit('/').next().unwrap_or(display).to_string(),
+                    tag: display.clone(),
+                    count: notes.len(),
+                    total: total.len(),
+                    children: children_of(key, counts),
+                }
+            })
+            .collect()
+    }
+    children_of("", &counts)
+}
+
+pub fn list(root: &Path) -> Result<Vec<TagInfo>, String> {
+    // Keyed by lowercase tag; the first spelling seen is the one shown
+    let mut counts: BTreeMap<String, (String, HashSet<String>)> = BTreeMap::new();
+    for note in metadata_cache::query(root, &MetadataFilter::default())? {
+        for tag in &note.tags {
+            let segments: Vec<&str> = tag.split('/').collect();
+            for depth in 1..=segments.len() {
+                let prefix = segments[..depth].join("/");
+                let entry = counts.entry(prefix.to_lowercase()).or_insert_with(|| (prefix, HashSet::new()));
+                if depth == segments.len() {
+                    entry.1.insert(note.path.clone());
+                }
+            }
+        }
+    }
+    Ok(build_tree(counts))
+}
+
+// --- Tauri Commands ---
+
+/// All tags as a tree with note counts
+#[tauri::command]
+pub fn tags_list(workspace_path: String) -> Result<Vec<TagInfo>, String> {
+    list(Path::new(&workspace_path))
+}
+
+/// Paths of notes tagged `tag` or a tag nested below it
+#[tauri::command]
+pub fn tags_get_notes(workspace_path: String, tag: String) -> Result<Vec<String>, String> {
+    let notes = metadata_cache::query(
+        Path::new(&workspace_path),
+        &MetadataFilter {
+            tag: Some(tag),
+            ..Default::default()
+        },
+    )?;
+    Ok(notes.into_iter().map(|n| n.path).collect())
+}
+
+#[tauri::command]
+pub fn tags_rename(workspace_path: String, old: String, new: String) -> Result<TagRenameResult, String> {
+    rename(Path::new(&workspace_path), &old, &new)
+}
+
+/// Merge tag `a` into tag `b`; notes carrying both end up with `b` once
+#[tauri::command]
+pub fn tags_merge(workspace_path: String, a: String, b: String) -> Result<TagRenameResult, String> {
+    rename(Path::new(&workspace_path), &a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_inline_and_frontmatter() {
+        let fm = json!({"tags": ["work", "#Project/Alpha"]});
+        let tags = extract(Some(&fm), "Text #idea #project/alpha, #123 and url/#x\n```\n#code\n```\n");
+        assert_eq!(tags, vec!["work", "Project/Alpha", "idea"]);
+    }
+
+    #[test]
+    fn test_rename_in_note_handles_nested_and_frontmatter() {
+        let content = "---\ntags: [project, other]\n---\n\nSee #project/alpha and #projects and #Project.\n";
+        let updated = rename_in_note(content, "project", "work").unwrap().unwrap();
+        assert_eq!(updated, "---\ntags: [work, other]\n---\n\nSee #work/alpha and #projects and #work.\n");
+        assert!(rename_in_note("#other", "project", "work").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rename_in_note_keeps_frontmatter_formatting() {
+        let content = "---\ntitle: Plan # project\ntags: # project tags\n  - \"project/alpha\"\n  - 'projects'\n  - other # project\nstatus: project\n---\nbody\n";
+        let updated = rename_in_note(content, "project", "work").unwrap().unwrap();
+        assert_eq!(
+            updated,
+            "---\ntitle: Plan # project\ntags: # project tags\n  - \"work/alpha\"\n  - 'projects'\n  - other # project\nstatus: project\n---\nbody\n"
+        );
+
+        let content = "---\ntag: \"#project, misc\"\n---\n";
+        assert_eq!(rename_in_note(content, "project", "work").unwrap().unwrap(), "---\ntag: \"#work, misc\"\n---\n");
+    }
+
+    #[test]
+    fn test_list_and_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join("a.md"), "#project/alpha #todo").unwrap();
+        fs::write(root.join("b.md"), "#project/beta #task").unwrap();
+        fs::write(root.join("c.md"), "#todo #task").unwrap();
+
+        let tags = list(&root).unwrap();
+        let project = tags.iter().find(|t| t.tag == "project").unwrap();
+        assert_eq!((project.count, project.total, project.children.len()), (0, 2, 2));
+
+        let result = rename(&root, "todo", "task").unwrap();
+        assert_eq!(result.updated.len(), 2);
+        assert_eq!(fs::read_to_string(root.join("c.md")).unwrap(), "#task #task");
+        let notes = tags_get_notes(root.to_string_lossy().to_string(), "task".into()).unwrap();
+        assert_eq!(notes.len(), 3);
+        assert!(rename(&root, "todo", "bad tag").is_err());
+    }
+}
@@ -0,0 +1,457 @@
+//! Two-way sync between checkbox lines in notes and the task store.
+//!
+//! A synced task is anchored to its `- [ ]` line by a block ID (`^task-1a2b3c4d`)
+//! appended to the line the first time the store writes to it. The task also
+//! remembers the line's state at the last sync, which lets us tell which side
+//! changed: store-side edits (task panel, kanban moves) are written into the
+//! note, note edits picked up on save are applied to the store, and when both
+//! changed the task is left alone and a conflict is recorded for the user.
+
+use crate::tasks::{self, Task, TaskStatus, TaskStore};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// `- [ ] title ^task-id`, with any list marker and indentation
+static CHECKBOX_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\s*[-*+]\s+\[)([ xX])(\]\s*)(.*?)(?:\s+\^(task-[A-Za-z0-9]+))?\s*$").unwrap()
+});
+
+/// Line state at the last successful sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteAnchor {
+    pub block_id: String,
+    pub checked: bool,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub task_id: String,
+    pub note_path: String,
+    pub line: usize,
+    pub note_checked: bool,
+    pub note_title: String,
+    pub task_status: TaskStatus,
+    pub task_title: String,
+    pub detected_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSyncReport {
+    pub updated_tasks: Vec<String>,
+    pub unlinked_tasks: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub note_rewritten: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Checkbox {
+    checked: bool,
+    raw_title: String,
+    title: String,
+    block_id: Option<String>,
+}
+
+fn parse_checkbox(line: &str) -> Option<Checkbox> {
+    let caps = CHECKBOX_RE.captures(line)?;
+    let raw_title = caps.get(4).map_or("", |m| m.as_str()).trim().to_string();
+    Some(Checkbox {
+        checked: !caps[2].trim().is_empty(),
+        title: tasks::normalize_title(&raw_title),
+        raw_title,
+        block_id: caps.get(5).map(|m| m.as_str().to_string()),
+    })
+}
+
+/// Rewrite a checkbox line in place, keeping its indentation and list marker
+fn render_line(line: &str, checked: bool, raw_title: &str, block_id: &str) -> String {
+    match CHECKBOX_RE.captures(line) {
+        Some(caps) => format!(
+            "{}{}{}{} ^{}",
+            &caps[1],
+            if checked { "x" } else { " " },
+            &caps[3],
+            raw_title,
+            block_id
+        ),
+        None => line.to_string(),
+    }
+}
+
+fn is_checked(status: &TaskStatus) -> bool {
+    matches!(status, TaskStatus::Completed | TaskStatus::Cancelled)
+}
+
+/// Status implied by a checkbox, keeping richer statuses that agree with it
+fn status_from_checkbox(current: &TaskStatus, checked: bool) -> TaskStatus {
+    match (checked, is_checked(current)) {
+        (true, true) | (false, false) => current.clone(),
+        (true, false) => TaskStatus::Completed,
+        (false, true) => TaskStatus::Todo,
+    }
+}
+
+fn new_block_id(task_id: &str) -> String {
+    let short: String = task_id.chars().filter(|c| c.is_ascii_alphanumeric()).take(8).collect();
+    format!("task-{}", short)
+}
+
+fn same_note(a: &str, b: &str) -> bool {
+    a == b || crate::links::normalize(Path::new(a)) == crate::links::normalize(Path::new(b))
+}
+
+fn read_note(path: &str) -> Result<String, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    crate::encryption::decrypt_for_read(path, content)
+}
+
+fn write_note(path: &str, lines: &[String], trailing_newline: bool) -> Result<(), String> {
+    let mut content = lines.join("\n");
+    if trailing_newline {
+        content.push('\n');
+    }
+    crate::handlers::files::write_file_content(path.to_string(), content).map_err(String::from)
+}
+
+/// Find a task's line: by block ID, then (for tasks not yet anchored) by its
+/// recorded position, then by title
+fn locate(lines: &[String], task: &Task) -> Option<(usize, Checkbox)> {
+    if let Some(anchor) = &task.note_anchor {
+        return lines.iter().enumerate().find_map(|(i, line)| {
+            parse_checkbox(line)
+                .filter(|c| c.block_id.as_deref() == Some(anchor.block_id.as_str()))
+                .map(|c| (i, c))
+        });
+    }
+
+    let unanchored = |i: usize| {
+        lines.get(i).and_then(|l| parse_checkbox(l)).filter(|c| c.block_id.is_none() && c.title == task.title)
+    };
+    if let Some(pos) = task.note_position.filter(|p| *p >= 0).map(|p| p as usize) {
+        if let Some(c) = unanchored(pos) {
+            return Some((pos, c));
+        }
+    }
+    (0..lines.len()).find_map(|i| unanchored(i).map(|c| (i, c)))
+}
+
+fn record_conflict(store: &mut TaskStore, task: &Task, note_path: &str, line: usize, checkbox: &Checkbox) {
+    store.conflicts.retain(|c| c.task_id != task.id);
+    store.conflicts.push(SyncConflict {
+        task_id: task.id.clone(),
+        note_path: note_path.to_string(),
+        line,
+        note_checked: checkbox.checked,
+        note_title: checkbox.title.clone(),
+        task_status: task.status.clone(),
+        task_title: task.title.clone(),
+        detected_at: chrono::Utc::now().timestamp_millis(),
+    });
+}
+
+/// Write a task's current state into its source note.
+///
+/// Returns `Ok(false)` when nothing was written: the task has no note, its line
+/// is gone, or the line was also edited since the last sync (a conflict is
+/// recorded in that case). `force` overwrites the note regardless.
+pub fn push_to_note(store: &mut TaskStore, task_id: &str, force: bool) -> Result<bool, String> {
+    let Some(mut task) = store.get_task(task_id).cloned() else {
+        return Err(format!("Task with id {} not found", task_id));
+    };
+    let Some(note_path) = task.note_path.clone() else {
+        return Ok(false);
+    };
+    if !Path::new(&note_path).is_file() {
+        return Ok(false);
+    }
+
+    let content = read_note(&note_path)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let Some((index, checkbox)) = locate(&lines, &task) else {
+        return Ok(false);
+    };
+
+    if !force {
+        if let Some(anchor) = &task.note_anchor {
+            let note_changed = checkbox.checked != anchor.checked || checkbox.title != anchor.title;
+            let task_changed = is_checked(&task.status) != anchor.checked || task.title != anchor.title;
+            if note_changed && task_changed && (checkbox.checked != is_checked(&task.status) || checkbox.title != task.title) {
+                record_conflict(store, &task, &note_path, index, &checkbox);
+                return Ok(false);
+            }
+        }
+    }
+
+    // Keep the line's own wording (due phrases etc.) unless the title itself changed
+    let raw_title = if checkbox.title == task.title { checkbox.raw_title.clone() } else { task.title.clone() };
+    let block_id = checkbox
+        .block_id
+        .clone()
+        .unwrap_or_else(|| new_block_id(&task.id));
+    let checked = is_checked(&task.status);
+    let line = render_line(&lines[index], checked, &raw_title, &block_id);
+    if line != lines[index] {
+        lines[index] = line;
+        write_note(&note_path, &lines, content.ends_with('\n'))?;
+    }
+
+    task.note_position = Some(index as i32);
+    task.note_anchor = Some(NoteAnchor { block_id, checked, title: task.title.clone() });
+    store.conflicts.retain(|c| c.task_id != task.id);
+    store.update_task(task_id, task)?;
+    Ok(true)
+}
+
+/// Apply a saved note's checkbox lines to the tasks linked to it
+pub fn reconcile_note(store: &mut TaskStore, note_path: &str, content: &str) -> Result<NoteSyncReport, String> {
+    let mut report = NoteSyncReport::default();
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let linked: Vec<Task> = store
+        .get_all_tasks()
+        .into_iter()
+        .filter(|t| t.note_path.as_deref().map_or(false, |p| same_note(p, note_path)))
+        .cloned()
+        .collect();
+
+    for mut task in linked {
+        let Some((index, checkbox)) = locate(&lines, &task) else {
+            // The line was removed; keep the task but drop its link to the note
+            if task.note_anchor.is_some() {
+                task.note_path = None;
+                task.note_position = None;
+                task.note_anchor = None;
+                report.unlinked_tasks.push(task.id.clone());
+                store.update_task(&task.id.clone(), task)?;
+            }
+            continue;
+        };
+
+        let task_checked = is_checked(&task.status);
+        let in_sync = checkbox.checked == task_checked && checkbox.title == task.title;
+        let (note_changed, task_changed) = match &task.note_anchor {
+            Some(anchor) => (
+                checkbox.checked != anchor.checked || checkbox.title != anchor.title,
+                task_checked != anchor.checked || task.title != anchor.title,
+            ),
+            // Never synced: the note was just edited, so it wins
+            None => (!in_sync, false),
+        };
+
+        task.note_position = Some(index as i32);
+        if in_sync {
+            if let Some(anchor) = task.note_anchor.as_mut() {
+                anchor.checked = checkbox.checked;
+                anchor.title = checkbox.title.clone();
+            }
+        } else if note_changed && task_changed {
+            record_conflict(store, &task, note_path, index, &checkbox);
+            report.conflicts.push(task.id.clone());
+        } else if note_changed {
+            task.update_status(status_from_checkbox(&task.status, checkbox.checked));
+            task.title = checkbox.title.clone();
+            if let Some(anchor) = task.note_anchor.as_mut() {
+                anchor.checked = checkbox.checked;
+                anchor.title = checkbox.title.clone();
+            }
+            report.updated_tasks.push(task.id.clone());
+        } else if let Some(anchor) = task.note_anchor.as_mut() {
+            // A store-side change never reached the note (e.g. it was locked); write it now
+            let raw_title = if checkbox.title == task.title { checkbox.raw_title.clone() } else { task.title.clone() };
+            lines[index] = render_line(&lines[index], task_checked, &raw_title, &anchor.block_id);
+            anchor.checked = task_checked;
+            anchor.title = task.title.clone();
+            report.note_rewritten = true;
+        }
+
+        if !report.conflicts.contains(&task.id) {
+            store.conflicts.retain(|c| c.task_id != task.id);
+        }
+        let task_id = task.id.clone();
+        store.update_task(&task_id, task)?;
+        store.spawn_next_occurrence(&task_id);
+    }
+
+    if report.note_rewritten {
+        write_note(note_path, &lines, content.ends_with('\n'))?;
+    }
+    Ok(report)
+}
+
+/// Status for a card dropped into a kanban column, if the column name implies one
+pub fn status_for_column(column_name: &str) -> Option<TaskStatus> {
+    let name = column_name.to_lowercase();
+    if name.contains("done") || name.contains("complete") {
+        Some(TaskStatus::Completed)
+    } else if name.contains("cancel") {
+        Some(TaskStatus::Cancelled)
+    } else if name.contains("progress") || name.contains("doing") {
+        Some(TaskStatus::InProgress)
+    } else if name.contains("todo") || name.contains("to do") || name.contains("backlog") {
+        Some(TaskStatus::Todo)
+    } else {
+        None
+    }
+}
+
+/// Hook for the file watcher: re-extract linked tasks from a saved note
+pub fn notify_note_saved(app: &AppHandle, path: &str) {
+    let Ok(content) = read_note(path) else {
+        return;
+    };
+    let result = tasks::get_task_store(app).and_then(|mut store| {
+        let has_linked = store
+            .get_all_tasks()
+            .iter()
+            .any(|t| t.note_path.as_deref().map_or(false, |p| same_note(p, path)));
+        if !has_linked {
+            return Ok(());
+        }
+        reconcile_note(&mut store, path, &content)?;
+        tasks::save_task_store(app, &store)
+    });
+    if let Err(e) = result {
+        tracing::warn!("Task sync failed for {}: {}", path, e);
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn task_sync_note(app: AppHandle, note_path: String) -> Result<NoteSyncReport, String> {
+    let content = read_note(&note_path)?;
+    let mut store = tasks::get_task_store(&app)?;
+    let report = reconcile_note(&mut store, &note_path, &content)?;
+    tasks::save_task_store(&app, &store)?;
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn task_sync_conflicts(app: AppHandle) -> Result<Vec<SyncConflict>, String> {
+    Ok(tasks::get_task_store(&app)?.conflicts)
+}
+
+/// Resolve a conflict by keeping either the note's line (`"note"`) or the task store's state (`"task"`)
+#[tauri::command]
+pub async fn task_sync_resolve(app: AppHandle, task_id: String, keep: String) -> Result<Task, String> {
+    let mut store = tasks::get_task_store(&app)?;
+    let conflict = store
+        .conflicts
+        .iter()
+        .find(|c| c.task_id == task_id)
+        .cloned()
+        .ok_or_else(|| format!("No sync conflict for task {}", task_id))?;
+
+    match keep.as_str() {
+        "note" => {
+            let mut task = store
+                .get_task(&task_id)
+                .cloned()
+                .ok_or_else(|| format!("Task with id {} not found", task_id))?;
+            task.update_status(status_from_checkbox(&task.status, conflict.note_checked));
+            task.title = conflict.note_title.clone();
+            if let Some(anchor) = task.note_anchor.as_mut() {
+                anchor.checked = conflict.note_checked;
+                anchor.title = conflict.note_title.clone();
+            }
+            store.update_task(&task_id, task)?;
+            store.spawn_next_occurrence(&task_id);
+            store.conflicts.retain(|c| c.task_id != task_id);
+        }
+        "task" => {
+            push_to_note(&mut store, &task_id, true)?;
+        }
+        other => return Err(format!("Unknown resolution '{}', expected 'note' or 'task'", other)),
+    }
+
+    tasks::save_task_store(&app, &store)?;
+    store
+        .get_task(&task_id)
+        .cloned()
+        .ok_or_else(|| format!("Task with id {} not found", task_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linked_task(store: &mut TaskStore, note: &str, title: &str, line: i32) -> String {
+        let mut task = Task::new(title.to_string());
+        task.note_path = Some(note.to_string());
+        task.note_position = Some(line);
+        let id = task.id.clone();
+        store.add_task(task);
+        id
+    }
+
+    #[test]
+    fn test_parse_and_render_checkbox() {
+        let c = parse_checkbox("  * [x] Ship it ^task-abc123").unwrap();
+        assert!(c.checked);
+        assert_eq!(c.title, "Ship it");
+        assert_eq!(c.block_id.as_deref(), Some("task-abc123"));
+        assert!(parse_checkbox("plain text").is_none());
+        assert_eq!(render_line("  - [ ] Ship it", true, "Ship it", "task-1"), "  - [x] Ship it ^task-1");
+    }
+
+    #[test]
+    fn test_push_anchors_line_and_note_edits_flow_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("todo.md");
+        fs::write(&note, "# List\n- [ ] Buy milk\n- [ ] Call mom\n").unwrap();
+        let note = note.to_string_lossy().to_string();
+
+        let mut store = TaskStore::default();
+        let id = linked_task(&mut store, &note, "Buy milk", 1);
+        let mut task = store.get_task(&id).cloned().unwrap();
+        task.update_status(TaskStatus::Completed);
+        store.update_task(&id, task).unwrap();
+
+        assert!(push_to_note(&mut store, &id, false).unwrap());
+        let content = fs::read_to_string(&note).unwrap();
+        let block = store.get_task(&id).unwrap().note_anchor.clone().unwrap().block_id;
+        assert!(content.contains(&format!("- [x] Buy milk ^{}", block)));
+        assert!(content.contains("- [ ] Call mom\n"));
+
+        // Unchecking in the note (and moving the line) reopens the task
+        let edited = format!("# List\n- [ ] Call mom\n- [ ] Buy milk ^{}\n", block);
+        let report = reconcile_note(&mut store, &note, &edited).unwrap();
+        assert_eq!(report.updated_tasks, vec![id.clone()]);
+        let task = store.get_task(&id).unwrap();
+        assert_eq!(task.status, TaskStatus::Todo);
+        assert_eq!(task.note_position, Some(2));
+    }
+
+    #[test]
+    fn test_both_sides_changed_is_a_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("todo.md");
+        fs::write(&note, "- [ ] Write report\n").unwrap();
+        let note = note.to_string_lossy().to_string();
+
+        let mut store = TaskStore::default();
+        let id = linked_task(&mut store, &note, "Write report", 0);
+        push_to_note(&mut store, &id, false).unwrap();
+        let block = store.get_task(&id).unwrap().note_anchor.clone().unwrap().block_id;
+
+        // Store marks it done while the note renames it
+        let mut task = store.get_task(&id).cloned().unwrap();
+        task.update_status(TaskStatus::Completed);
+        store.update_task(&id, task).unwrap();
+        fs::write(&note, format!("- [ ] Write final report ^{}\n", block)).unwrap();
+
+        assert!(!push_to_note(&mut store, &id, false).unwrap());
+        assert_eq!(store.conflicts.len(), 1);
+        assert!(fs::read_to_string(&note).unwrap().contains("- [ ] Write final report"));
+
+        assert!(push_to_note(&mut store, &id, true).unwrap());
+        assert!(store.conflicts.is_empty());
+        assert!(fs::read_to_string(&note).unwrap().starts_with("- [x] Write report ^"));
+    }
+}
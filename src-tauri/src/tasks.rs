@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use tauri::AppHandle;
 use tauri_plugin_store::StoreBuilder;
+use crate::task_sync::{NoteAnchor, SyncConflict};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -47,6 +48,35 @@ pub struct Task {
     pub kanban_board: Option<String>,  // Path to .kanban file
     pub kanban_column: Option<String>, // Column ID in the board
     pub kanban_card_id: Option<String>, // ID of the card in kanban
+    // Note sync: block ID of the source checkbox line and its state when last synced
+    #[serde(default)]
+    pub note_anchor: Option<NoteAnchor>,
+    // Recurrence: iCal RRULE, the DTSTART it is anchored to, and the instance
+    // created when this one was completed
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub recurrence_start: Option<String>,
+    #[serde(default)]
+    pub next_occurrence_id: Option<String>,
+    // Jira/Linear issue the task was imported from
+    #[serde(default)]
+    pub remote_issue: Option<RemoteIssueLink>,
+}
+
+/// A task's remote issue, with both sides' state when last reconciled so the
+/// reconciler can tell which one changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteIssueLink {
+    /// `jira` or `linear`
+    pub provider: String,
+    /// Issue key, e.g. `ENG-42`
+    pub key: String,
+    pub url: String,
+    /// Status name on the remote side
+    pub remote_status: String,
+    pub synced_status: TaskStatus,
 }
 
 fn current_timestamp_ms() -> i64 {
@@ -76,6 +106,11 @@ impl Task {
             kanban_board: None,
             kanban_column: None,
             kanban_card_id: None,
+            note_anchor: None,
+            recurrence: None,
+            recurrence_start: None,
+            next_occurrence_id: None,
+            remote_issue: None,
         }
     }
 
@@ -88,12 +123,15 @@ impl Task {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskStore {
     pub tasks: HashMap<String, Task>,
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
 }
 
 impl Default for TaskStore {
     fn default() -> Self {
         Self {
             tasks: HashMap::new(),
+            conflicts: Vec::new(),
         }
     }
 }
@@ -135,6 +173,20 @@ impl TaskStore {
             .collect()
     }
 
+    /// Completing a recurring task creates its next instance (once); returns it
+    pub fn spawn_next_occurrence(&mut self, task_id: &str) -> Option<Task> {
+        let task = self.tasks.get(task_id)?;
+        if task.status != TaskStatus::Completed || task.next_occurrence_id.is_some() {
+            return None;
+        }
+        let next = next_occurrence(task)?;
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.next_occurrence_id = Some(next.id.clone());
+        }
+        self.add_task(next.clone());
+        Some(next)
+    }
+
     pub fn get_tasks_by_note(&self, note_path: &str) -> Vec<&Task> {
         self.tasks
             .values()
@@ -147,7 +199,7 @@ impl TaskStore {
     }
 }
 
-fn get_task_store(app: &AppHandle) -> Result<TaskStore, String> {
+pub(crate) fn get_task_store(app: &AppHandle) -> Result<TaskStore, String> {
     let store = StoreBuilder::new(app, PathBuf::from(".tasks.dat"))
         .build()
         .map_err(|e| format!("Failed to build task store: {}", e))?;
@@ -161,7 +213,7 @@ fn get_task_store(app: &AppHandle) -> Result<TaskStore, String> {
     }
 }
 
-fn save_task_store(app: &AppHandle, task_store: &TaskStore) -> Result<(), String> {
+pub(crate) fn save_task_store(app: &AppHandle, task_store: &TaskStore) -> Result<(), String> {
     let store = StoreBuilder::new(app, PathBuf::from(".tasks.dat"))
         .build()
         .map_err(|e| format!("Failed to build task store: {}", e))?;
@@ -346,6 +398,105 @@ fn parse_task_title_and_due(raw_title: &str) -> (String, Option<String>, bool) {
     (normalize_task_title(raw_title), None, false)
 }
 
+/// Parse a range bound given as `YYYY-MM-DD` or RFC 3339
+pub(crate) fn parse_day(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .or_else(|_| chrono::DateTime::parse_from_rfc3339(value.trim()).map(|dt| dt.with_timezone(&Local).date_naive()))
+        .map_err(|_| format!("Invalid date '{}'", value))
+}
+
+pub(crate) fn due_local(task: &Task) -> Option<chrono::DateTime<Local>> {
+    task.due_date
+        .as_deref()
+        .and_then(|due| chrono::DateTime::parse_from_rfc3339(due).ok())
+        .map(|due| due.with_timezone(&Local))
+}
+
+/// `date` at the time of day of `template` (midnight for all-day tasks)
+fn due_on(date: NaiveDate, template: Option<chrono::DateTime<Local>>, all_day: bool) -> Option<String> {
+    let time = match template {
+        Some(due) if !all_day => due.time(),
+        _ => NaiveTime::MIN,
+    };
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|dt| dt.to_rfc3339())
+}
+
+fn validate_recurrence(rule: &str) -> Result<String, String> {
+    crate::recurrence::RRule::parse(rule)?;
+    Ok(rule.trim().trim_start_matches("RRULE:").to_string())
+}
+
+fn recurrence_of(task: &Task) -> Option<(crate::recurrence::RRule, NaiveDate)> {
+    let rule = crate::recurrence::RRule::parse(task.recurrence.as_deref()?).ok()?;
+    let start = task
+        .recurrence_start
+        .as_deref()
+        .and_then(|s| parse_day(s).ok())
+        .or_else(|| due_local(task).map(|due| due.date_naive()))
+        .unwrap_or_else(|| Local::now().date_naive());
+    Some((rule, start))
+}
+
+/// The instance following `task`: due at the rule's next date after the
+/// current due date (or after yesterday, so late completions don't schedule
+/// into the past)
+fn next_occurrence(task: &Task) -> Option<Task> {
+    let (rule, start) = recurrence_of(task)?;
+    let due = due_local(task);
+    let yesterday = Local::now().date_naive() - Duration::days(1);
+    let after = due.map_or(yesterday, |d| d.date_naive().max(yesterday));
+    let next_date = rule.next_after(start, after)?;
+
+    let mut next = Task::new(task.title.clone());
+    next.description = task.description.clone();
+    next.priority = task.priority;
+    next.tags = task.tags.clone();
+    next.note_path = task.note_path.clone();
+    next.kanban_board = task.kanban_board.clone();
+    next.due_date = due_on(next_date, due, task.due_date_is_all_day || due.is_none());
+    next.due_date_is_all_day = task.due_date_is_all_day || due.is_none();
+    next.recurrence = task.recurrence.clone();
+    next.recurrence_start = task.recurrence_start.clone();
+    Some(next)
+}
+
+/// Future instances of an open recurring task within `from..=to`, excluding the
+/// task's own due date. Projected tasks are not stored; their id is
+/// `<task id>@<YYYY-MM-DD>`.
+fn projected_occurrences(task: &Task, from: NaiveDate, to: NaiveDate) -> Vec<Task> {
+    if task.next_occurrence_id.is_some() || matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled) {
+        return Vec::new();
+    }
+    let Some((rule, start)) = recurrence_of(task) else {
+        return Vec::new();
+    };
+    let due = due_local(task);
+    let own_date = due.map(|d| d.date_naive());
+    let after_own = own_date.map_or(from, |d| from.max(d + Duration::days(1)));
+
+    rule.occurrences(start, after_own, to, 366)
+        .into_iter()
+        .map(|date| {
+            let mut projected = task.clone();
+            projected.id = format!("{}@{}", task.id, date.format("%Y-%m-%d"));
+            projected.status = TaskStatus::Todo;
+            projected.due_date = due_on(date, due, task.due_date_is_all_day || due.is_none());
+            projected.note_anchor = None;
+            projected.kanban_card_id = None;
+            projected.remote_issue = None;
+            projected
+        })
+        .collect()
+}
+
+/// Task title as stored for a raw line, with any due-date phrase removed
+pub(crate) fn normalize_title(raw_title: &str) -> String {
+    parse_task_title_and_due(raw_title).0
+}
+
 fn build_extracted_task(raw_title: &str, note_path: &str, line_num: usize) -> Option<Task> {
     let (title, due_date, due_date_is_all_day) = parse_task_title_and_due(raw_title);
     if title.is_empty() {
@@ -370,6 +521,7 @@ pub async fn create_task(
     note_position: Option<i32>,
     due_date: Option<String>,
     due_date_is_all_day: Option<bool>,
+    recurrence: Option<String>,
 ) -> Result<Task, String> {
     let (normalized_title, parsed_due_date, parsed_due_date_is_all_day) = parse_task_title_and_due(&title);
     let mut task = Task::new(normalized_title);
@@ -378,6 +530,10 @@ pub async fn create_task(
     task.note_position = note_position;
     task.due_date = due_date.or(parsed_due_date);
     task.due_date_is_all_day = due_date_is_all_day.unwrap_or(parsed_due_date_is_all_day);
+    if let Some(rule) = recurrence.filter(|r| !r.trim().is_empty()) {
+        task.recurrence = Some(validate_recurrence(&rule)?);
+        task.recurrence_start = Some(task.due_date.clone().unwrap_or_else(|| Local::now().to_rfc3339()));
+    }
     
     let mut task_store = get_task_store(&app)?;
     task_store.add_task(task.clone());
@@ -408,6 +564,7 @@ pub async fn update_task(
     priority: Option<i32>,
     due_date: Option<Option<String>>,
     due_date_is_all_day: Option<bool>,
+    recurrence: Option<Option<String>>,
 ) -> Result<Task, String> {
     let mut task_store = get_task_store(&app)?;
     
@@ -417,6 +574,7 @@ pub async fn update_task(
         .clone();
 
     let mut touched = false;
+    let sync_note = status.is_some() || title.is_some();
     
     if let Some(new_title) = title {
         let (normalized_title, parsed_due_date, parsed_due_date_is_all_day) = parse_task_title_and_due(&new_title);
@@ -449,17 +607,80 @@ pub async fn update_task(
         task.due_date_is_all_day = new_due_date_is_all_day;
         touched = true;
     }
+    if let Some(new_recurrence) = recurrence {
+        task.recurrence = new_recurrence.filter(|r| !r.trim().is_empty()).map(|r| validate_recurrence(&r)).transpose()?;
+        task.recurrence_start = task
+            .recurrence
+            .as_ref()
+            .map(|_| task.due_date.clone().unwrap_or_else(|| Local::now().to_rfc3339()));
+        touched = true;
+    }
 
     if touched {
         task.updated_at = current_timestamp_ms();
     }
     
     task_store.update_task(&task_id, task.clone())?;
+    task_store.spawn_next_occurrence(&task_id);
+    if sync_note {
+        task = push_task_to_note(&mut task_store, &task_id).unwrap_or(task);
+    }
     save_task_store(&app, &task_store)?;
     
     Ok(task)
 }
 
+/// Mirror a task's status/title into its source note; a failure here must not
+/// lose the store update, so it is only logged
+fn push_task_to_note(task_store: &mut TaskStore, task_id: &str) -> Option<Task> {
+    if let Err(e) = crate::task_sync::push_to_note(task_store, task_id, false) {
+        tracing::warn!("Failed to sync task {} to its note: {}", task_id, e);
+    }
+    task_store.get_task(task_id).cloned()
+}
+
+/// Apply a kanban card move to the tasks linked to that card
+pub(crate) fn sync_kanban_move(app: &AppHandle, board_path: &str, card_id: &str, column_name: &str) -> Result<(), String> {
+    match crate::task_sync::status_for_column(column_name) {
+        Some(status) => set_card_tasks_status(app, board_path, card_id, status, Some(column_name)),
+        None => Ok(()),
+    }
+}
+
+/// Set the status of every task linked to a kanban card
+pub(crate) fn set_card_tasks_status(
+    app: &AppHandle,
+    board_path: &str,
+    card_id: &str,
+    status: TaskStatus,
+    column_name: Option<&str>,
+) -> Result<(), String> {
+    let mut task_store = get_task_store(app)?;
+    let linked: Vec<String> = task_store
+        .get_all_tasks()
+        .into_iter()
+        .filter(|task| task.kanban_card_id.as_deref() == Some(card_id))
+        .filter(|task| task.kanban_board.as_deref().map_or(true, |board| board == board_path))
+        .map(|task| task.id.clone())
+        .collect();
+    if linked.is_empty() {
+        return Ok(());
+    }
+
+    for task_id in linked {
+        if let Some(mut task) = task_store.get_task(&task_id).cloned() {
+            task.update_status(status.clone());
+            if let Some(column_name) = column_name {
+                task.kanban_column = Some(column_name.to_string());
+            }
+            task_store.update_task(&task_id, task)?;
+            task_store.spawn_next_occurrence(&task_id);
+            push_task_to_note(&mut task_store, &task_id);
+        }
+    }
+    save_task_store(app, &task_store)
+}
+
 #[tauri::command]
 pub async fn delete_task(app: AppHandle, task_id: String) -> Result<(), String> {
     let mut task_store = get_task_store(&app)?;
@@ -468,14 +689,58 @@ pub async fn delete_task(app: AppHandle, task_id: String) -> Result<(), String>
     Ok(())
 }
 
+/// Tasks with a status. With `until`, upcoming instances of recurring tasks up
+/// to that date are included as projected `todo` tasks for agenda views.
 #[tauri::command]
-pub async fn get_tasks_by_status(app: AppHandle, status: TaskStatus) -> Result<Vec<Task>, String> {
+pub async fn get_tasks_by_status(app: AppHandle, status: TaskStatus, until: Option<String>) -> Result<Vec<Task>, String> {
     let task_store = get_task_store(&app)?;
-    Ok(task_store
+    let mut tasks: Vec<Task> = task_store
         .get_tasks_by_status(&status)
         .into_iter()
         .cloned()
-        .collect())
+        .collect();
+
+    if let (Some(until), TaskStatus::Todo) = (until, &status) {
+        let today = Local::now().date_naive();
+        let until = parse_day(&until)?;
+        for task in task_store.get_all_tasks() {
+            tasks.extend(projected_occurrences(task, today, until));
+        }
+    }
+    Ok(tasks)
+}
+
+/// What `tasks_get_upcoming` returns, from an already loaded store
+pub(crate) fn upcoming(task_store: &TaskStore, start: NaiveDate, end: NaiveDate) -> Vec<Task> {
+    let mut upcoming: Vec<(chrono::DateTime<Local>, Task)> = Vec::new();
+
+    for task in task_store.get_all_tasks() {
+        if matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled) {
+            continue;
+        }
+        if let Some(due) = due_local(task).filter(|due| (start..=end).contains(&due.date_naive())) {
+            upcoming.push((due, task.clone()));
+        }
+        for projected in projected_occurrences(task, start, end) {
+            if let Some(due) = due_local(&projected) {
+                upcoming.push((due, projected));
+            }
+        }
+    }
+
+    upcoming.sort_by(|a, b| a.0.cmp(&b.0));
+    upcoming.into_iter().map(|(_, task)| task).collect()
+}
+
+/// Open tasks due within `start..=end` (dates or RFC 3339), including projected
+/// instances of recurring tasks, ordered by due date
+#[tauri::command]
+pub async fn tasks_get_upcoming(app: AppHandle, start: String, end: String) -> Result<Vec<Task>, String> {
+    let (start, end) = (parse_day(&start)?, parse_day(&end)?);
+    if end < start {
+        return Err("Range end is before its start".to_string());
+    }
+    Ok(upcoming(&get_task_store(&app)?, start, end))
 }
 
 #[tauri::command]
@@ -497,7 +762,8 @@ pub async fn bulk_update_task_status(app: AppHandle, task_ids: Vec<String>, stat
         if let Some(mut task) = task_store.get_task(&task_id).cloned() {
             task.update_status(status.clone());
             task_store.update_task(&task_id, task.clone())?;
-            updated_tasks.push(task);
+            task_store.spawn_next_occurrence(&task_id);
+            updated_tasks.push(push_task_to_note(&mut task_store, &task_id).unwrap_or(task));
         }
     }
     
@@ -846,6 +1112,35 @@ mod tests {
         assert!(store.get_task(&task_id).is_none());
     }
 
+    #[test]
+    fn test_completing_recurring_task_spawns_next_instance() {
+        let mut store = TaskStore::default();
+        let mut task = Task::new("Water plants".to_string());
+        let due = Local::now().date_naive() + Duration::days(1);
+        task.due_date = due_on(due, None, true);
+        task.due_date_is_all_day = true;
+        task.recurrence = Some("FREQ=WEEKLY".to_string());
+        task.recurrence_start = task.due_date.clone();
+        let task_id = task.id.clone();
+        store.add_task(task.clone());
+
+        // Open instances project forward without being stored
+        let projected = projected_occurrences(&task, due, due + Duration::days(21));
+        assert_eq!(projected.len(), 3);
+        assert!(projected[0].id.starts_with(&format!("{}@", task_id)));
+
+        task.update_status(TaskStatus::Completed);
+        store.update_task(&task_id, task).unwrap();
+        let next = store.spawn_next_occurrence(&task_id).unwrap();
+        assert_eq!(due_local(&next).unwrap().date_naive(), due + Duration::days(7));
+        assert_eq!(next.status, TaskStatus::Todo);
+        assert_eq!(next.recurrence.as_deref(), Some("FREQ=WEEKLY"));
+
+        // Only once, even if completed again
+        assert!(store.spawn_next_occurrence(&task_id).is_none());
+        assert_eq!(store.get_all_tasks().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_task_extraction() {
         let content = r#"
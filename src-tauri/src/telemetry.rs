@@ -0,0 +1,358 @@
+//! Opt-in usage telemetry.
+//!
+//! Off until the user turns it on. While on, the app counts how often each
+//! command is invoked, how long it took and whether it was refused, and the
+//! frontend may report features it uses with their round-trip latency and
+//! whether they failed. Only these counters are
+//! kept: names are identifiers checked by [`valid_name`], never note content,
+//! titles or paths, and a batch carries no device or user id. Counters are
+//! rolled into a batch every hour, kept in `~/.lokus/telemetry-pending.json`
+//! and sent together to the configured endpoint; without one nothing leaves
+//! the machine. `telemetry_get_pending` shows exactly what would be sent and
+//! `telemetry_purge` (or turning telemetry off) deletes it.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const CONFIG_FILE: &str = "telemetry.json";
+const PENDING_FILE: &str = "telemetry-pending.json";
+const ENDPOINT_ENV: &str = "LOKUS_TELEMETRY_ENDPOINT";
+const TICK: Duration = Duration::from_secs(60 * 60);
+const SCHEMA_VERSION: u32 = 1;
+/// Unsent batches kept; the oldest are dropped past this
+const MAX_PENDING_BATCHES: usize = 48;
+/// Distinct names counted per batch, so a misbehaving caller can't grow it unbounded
+const MAX_NAMES: usize = 500;
+
+static ENABLED: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(load_config().enabled));
+static CURRENT: Lazy<Mutex<Aggregate>> = Lazy::new(|| Mutex::new(Aggregate::new()));
+static PENDING_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// Where batches are sent; falls back to `LOKUS_TELEMETRY_ENDPOINT`
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Counter {
+    pub count: u64,
+    pub errors: u64,
+    /// Calls that reported a duration
+    pub timed: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+impl Counter {
+    fn add(&mut self, duration_ms: Option<u64>, ok: bool) {
+        self.count += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        if let Some(ms) = duration_ms {
+            self.timed += 1;
+            self.total_ms = self.total_ms.saturating_add(ms);
+            self.max_ms = self.max_ms.max(ms);
+        }
+    }
+}
+
+/// One period of counters, as it would be sent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryBatch {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub os: String,
+    pub period_start: i64,
+    pub period_end: i64,
+    /// Invocations per command
+    pub commands: BTreeMap<String, Counter>,
+    /// Features reported by the frontend
+    pub features: BTreeMap<String, Counter>,
+}
+
+struct Aggregate {
+    started_at: i64,
+    commands: BTreeMap<String, Counter>,
+    features: BTreeMap<String, Counter>,
+}
+
+impl Aggregate {
+    fn new() -> Self {
+        Self { started_at: chrono::Utc::now().timestamp_millis(), commands: BTreeMap::new(), features: BTreeMap::new() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.features.is_empty()
+    }
+
+    fn batch(&self, app_version: &str) -> TelemetryBatch {
+        TelemetryBatch {
+            schema_version: SCHEMA_VERSION,
+            app_version: app_version.to_string(),
+            os: std::env::consts::OS.to_string(),
+            period_start: self.started_at,
+            period_end: chrono::Utc::now().timestamp_millis(),
+            commands: self.commands.clone(),
+            features: self.features.clone(),
+        }
+    }
+}
+
+fn count(counters: &mut BTreeMap<String, Counter>, name: &str, duration_ms: Option<u64>, ok: bool) {
+    if !counters.contains_key(name) && counters.len() >= MAX_NAMES {
+        return;
+    }
+    counters.entry(name.to_string()).or_default().add(duration_ms, ok);
+}
+
+/// Names are identifiers like `editor.table_insert`: lowercase letters,
+/// digits, `_`, `.`, `:` and `-`, starting with a letter. Anything that could
+/// hold a path or free text fails this.
+fn valid_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | ':' | '-'))
+}
+
+fn lokus_dir() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join(".lokus"))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn load_config() -> TelemetryConfig {
+    lokus_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(CONFIG_FILE)).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(config: &TelemetryConfig) -> Result<(), String> {
+    let dir = lokus_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    let json = serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    std::fs::write(dir.join(CONFIG_FILE), json).map_err(|e| format!("Failed to save telemetry config: {}", e))
+}
+
+fn endpoint(config: &TelemetryConfig) -> Option<String> {
+    config
+        .endpoint
+        .clone()
+        .or_else(|| std::env::var(ENDPOINT_ENV).ok())
+        .filter(|url| url.starts_with("https://"))
+}
+
+fn load_pending() -> Vec<TelemetryBatch> {
+    lokus_dir()
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(PENDING_FILE)).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_pending(batches: &[TelemetryBatch]) -> Result<(), String> {
+    let dir = lokus_dir()?;
+    let path = dir.join(PENDING_FILE);
+    if batches.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove pending telemetry: {}", e)),
+            _ => Ok(()),
+        };
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    let json = serde_json::to_string(batches).map_err(|e| format!("Failed to serialize telemetry: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to save pending telemetry: {}", e))
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Count an invoke of `command` that took `duration` to handle, as an error
+/// when it was refused or no such command exists; a no-op unless telemetry is
+/// on. Async commands are timed until they were started. Names that fail
+/// [`valid_name`] aren't counted.
+pub(crate) fn record_command(command: &str, duration: Duration, ok: bool) {
+    if !enabled() || !valid_name(command) {
+        return;
+    }
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    count(&mut current.commands, command, Some(duration.as_millis() as u64), ok);
+}
+
+/// Close the current period and queue it with the unsent batches
+fn roll(app_version: &str) -> Result<(), String> {
+    let batch = {
+        let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_empty() {
+            return Ok(());
+        }
+        let batch = current.batch(app_version);
+        *current = Aggregate::new();
+        batch
+    };
+    let _guard = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut pending = load_pending();
+    pending.push(batch);
+    let excess = pending.len().saturating_sub(MAX_PENDING_BATCHES);
+    pending.drain(..excess);
+    save_pending(&pending)
+}
+
+/// Send the unsent batches in one request, dropping them once accepted
+async fn flush(endpoint: &str) -> Result<usize, String> {
+    let batches = {
+        let _guard = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load_pending()
+    };
+    if batches.is_empty() {
+        return Ok(0);
+    }
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .timeout(Duration::from_secs(30))
+        .json(&batches)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send telemetry: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Telemetry endpoint returned HTTP {}", response.status()));
+    }
+
+    // Batches purged or added while sending are left as they are
+    let _guard = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut pending = load_pending();
+    pending.retain(|batch| !batches.contains(batch));
+    save_pending(&pending)?;
+    Ok(batches.len())
+}
+
+/// Roll and send batches every hour while telemetry is on
+pub fn start(app: AppHandle) {
+    let app_version = app.package_info().version.to_string();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK).await;
+            if !enabled() {
+                continue;
+            }
+            let version = app_version.clone();
+            let rolled = tokio::task::spawn_blocking(move || roll(&version))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|result| result);
+            if let Err(e) = rolled {
+                tracing::warn!("Failed to queue telemetry: {}", e);
+            }
+            let Some(endpoint) = endpoint(&load_config()) else {
+                continue;
+            };
+            match flush(&endpoint).await {
+                Ok(0) => {}
+                Ok(sent) => tracing::debug!("Sent {} telemetry batches", sent),
+                Err(e) => tracing::debug!("Telemetry not sent, will retry: {}", e),
+            }
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn telemetry_get_config() -> TelemetryConfig {
+    load_config()
+}
+
+/// Opt in or out; opting out deletes everything not yet sent
+#[tauri::command]
+pub fn telemetry_set_enabled(enabled: bool) -> Result<TelemetryConfig, String> {
+    let config = TelemetryConfig { enabled, ..load_config() };
+    save_config(&config)?;
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        telemetry_purge()?;
+    }
+    Ok(config)
+}
+
+/// Count a use of a frontend feature, with its latency and outcome when known
+#[tauri::command]
+pub fn telemetry_record(name: String, duration_ms: Option<u64>, ok: Option<bool>) -> Result<(), String> {
+    if !valid_name(&name) {
+        return Err(format!("Invalid telemetry name '{}'", name));
+    }
+    if !enabled() {
+        return Ok(());
+    }
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    count(&mut current.features, &name, duration_ms, ok.unwrap_or(true));
+    Ok(())
+}
+
+/// Everything that would be sent next: the queued batches and the period in progress
+#[tauri::command]
+pub fn telemetry_get_pending(app: AppHandle) -> Vec<TelemetryBatch> {
+    let mut batches = {
+        let _guard = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load_pending()
+    };
+    let current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    if !current.is_empty() {
+        batches.push(current.batch(&app.package_info().version.to_string()));
+    }
+    batches
+}
+
+/// Delete unsent telemetry; returns how many batches were dropped
+#[tauri::command]
+pub fn telemetry_purge() -> Result<usize, String> {
+    let in_progress = {
+        let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+        let had_data = !current.is_empty();
+        *current = Aggregate::new();
+        had_data
+    };
+    let _guard = PENDING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let queued = load_pending().len();
+    save_pending(&[])?;
+    Ok(queued + usize::from(in_progress))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_identifiers_and_counters_aggregate() {
+        assert!(valid_name("editor.table_insert"));
+        assert!(valid_name("search:query-run2"));
+        for name in ["", "Editor", "notes/secret.md", "/home/me", "has space", "1st", "a".repeat(65).as_str()] {
+            assert!(!valid_name(name), "{:?}", name);
+        }
+
+        let mut counters = BTreeMap::new();
+        count(&mut counters, "search", Some(40), true);
+        count(&mut counters, "search", Some(120), false);
+        count(&mut counters, "search", None, true);
+        assert_eq!(counters["search"], Counter { count: 3, errors: 1, timed: 2, total_ms: 160, max_ms: 120 });
+
+        for i in 0..MAX_NAMES + 5 {
+            count(&mut counters, &format!("f{}", i), None, true);
+        }
+        assert_eq!(counters.len(), MAX_NAMES);
+        assert_eq!(counters["search"].count, 3);
+    }
+}
@@ -0,0 +1,534 @@
+//! Note templates.
+//!
+//! Templates are markdown files under `<workspace>/.lokus/templates/`, with
+//! optional frontmatter (`name`, `description`, `filename`) describing the
+//! template itself. Rendering happens here rather than in each window so every
+//! caller — editor, daily notes, plugins, the CLI — gets identical output.
+//!
+//! Supported placeholders:
+//! - `{{date}}`, `{{time}}`, `{{datetime}}`, `{{timestamp}}`, with optional date
+//!   math and a moment-style format: `{{date:+1d}}`, `{{date:-1w:dddd}}`,
+//!   `{{time:HH:mm}}`
+//! - `{{title}}` and `{{cursor}}` (removed; its offset is returned)
+//! - prompts, filled from the supplied variables or their default:
+//!   `{{prompt:var:Question:default}}`, `{{suggest:var:Question:a,b:default}}`,
+//!   `{{checkbox:var:Question:false}}`
+//! - `{{name}}` for any other supplied variable
+//!
+//! Anything else is left untouched.
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap());
+static OFFSET_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([+-]\d+)([hdwmy])(?::(.*))?$").unwrap());
+
+/// Stands in for `{{cursor}}` until the rest of the template is rendered
+const CURSOR_MARK: char = '\u{E000}';
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePrompt {
+    pub var: String,
+    /// "text", "suggest" or "checkbox"
+    pub kind: String,
+    pub question: String,
+    pub default: Option<String>,
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    /// Path below `.lokus/templates` without the extension, e.g. `meetings/standup`
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub path: String,
+    pub prompts: Vec<TemplatePrompt>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedTemplate {
+    pub content: String,
+    /// Character offset of `{{cursor}}`, if the template has one
+    pub cursor: Option<usize>,
+    /// Prompts that had neither a supplied value nor a default
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedNote {
+    pub path: String,
+    pub cursor: Option<usize>,
+}
+
+/// Everything a render depends on besides the template text
+pub struct RenderContext {
+    pub now: DateTime<Local>,
+    pub title: Option<String>,
+    pub vars: HashMap<String, String>,
+}
+
+impl RenderContext {
+    pub fn new(title: Option<String>, vars: HashMap<String, String>) -> Self {
+        Self { now: Local::now(), title, vars }
+    }
+
+    /// Render relative to `date` instead of today, e.g. for a daily note
+    pub fn for_date(date: NaiveDate, title: Option<String>, vars: HashMap<String, String>) -> Self {
+        let now = Local::now();
+        let at = date
+            .and_time(now.time())
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap_or(now);
+        Self { now: at, title, vars }
+    }
+}
+
+// --- Dates ---
+
+/// Translate a moment/date-fns style format (`YYYY-MM-DD`) into chrono's syntax.
+/// Text in `[brackets]` is copied literally. Tokens chrono has no equivalent
+/// for (`Do`, `dd`) are filled in from `date`, the day being formatted.
+pub fn convert_date_format(format: &str, date: NaiveDate) -> String {
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "%Y"),
+        ("yyyy", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DDDD", "%j"),
+        ("DD", "%d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+        ("EEEE", "%A"),
+        ("EEE", "%a"),
+        ("dd", ""),
+        ("Do", ""),
+        ("D", "%-d"),
+        ("d", "%w"),
+        ("HH", "%H"),
+        ("H", "%-H"),
+        ("hh", "%I"),
+        ("h", "%-I"),
+        ("mm", "%M"),
+        ("ss", "%S"),
+        ("A", "%p"),
+        ("a", "%P"),
+        ("ww", "%V"),
+        ("W", "%-V"),
+        ("X", "%s"),
+    ];
+
+    let mut out = String::with_capacity(format.len() * 2);
+    let mut rest = format;
+    'outer: while !rest.is_empty() {
+        if let Some(literal) = rest.strip_prefix('[') {
+            if let Some(end) = literal.find(']') {
+                out.push_str(&literal[..end].replace('%', "%%"));
+                rest = &literal[end + 1..];
+                continue;
+            }
+        }
+        for (token, replacement) in TOKENS {
+            if let Some(after) = rest.strip_prefix(token) {
+                match *token {
+                    "Do" => out.push_str(&ordinal(date.day())),
+                    // Two-letter weekday: "Mo", "Tu", ...
+                    "dd" => out.extend(date.format("%a").to_string().chars().take(2)),
+                    _ => out.push_str(replacement),
+                }
+                rest = after;
+                continue 'outer;
+            }
+        }
+        let c = rest.chars().next().expect("rest is non-empty");
+        if c == '%' {
+            out.push_str("%%");
+        } else {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// `1st`, `2nd`, `11th`, `22nd`, ...
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", day, suffix)
+}
+
+/// Apply an offset like `+1d`, `-2w`, `+1m` (months) or `+1y`
+pub fn shift_date(base: DateTime<Local>, amount: i64, unit: &str) -> Option<DateTime<Local>> {
+    let months = |n: i64| Months::new(n.unsigned_abs() as u32);
+    match unit {
+        "h" => base.checked_add_signed(Duration::hours(amount)),
+        "d" => base.checked_add_signed(Duration::days(amount)),
+        "w" => base.checked_add_signed(Duration::weeks(amount)),
+        "m" if amount >= 0 => base.checked_add_months(months(amount)),
+        "m" => base.checked_sub_months(months(amount)),
+        "y" if amount >= 0 => base.checked_add_months(months(amount * 12)),
+        "y" => base.checked_sub_months(months(amount * 12)),
+        _ => None,
+    }
+}
+
+/// `spec` is what follows `date:`: an optional offset, then an optional format
+fn render_date(now: DateTime<Local>, spec: Option<&str>, default_format: &str) -> Option<String> {
+    let (date, format) = match spec {
+        None => (now, default_format.to_string()),
+        Some(spec) => match OFFSET_RE.captures(spec) {
+            Some(caps) => {
+                let amount: i64 = caps[1].parse().ok()?;
+                let shifted = shift_date(now, amount, &caps[2])?;
+                let format = caps
+                    .get(3)
+                    .map(|m| convert_date_format(m.as_str(), shifted.date_naive()))
+                    .unwrap_or_else(|| default_format.to_string());
+                (shifted, format)
+            }
+            None => (now, convert_date_format(spec, now.date_naive())),
+        },
+    };
+    Some(date.format(&format).to_string())
+}
+
+// --- Rendering ---
+
+fn prompt_from_placeholder(expr: &str) -> Option<TemplatePrompt> {
+    let (kind, rest) = expr.split_once(':')?;
+    let parts: Vec<&str> = rest.splitn(4, ':').collect();
+    let non_empty = |s: Option<&&str>| s.map(|s| s.to_string()).filter(|s| !s.is_empty());
+    match kind {
+        "prompt" | "checkbox" if parts.len() >= 2 => Some(TemplatePrompt {
+            var: parts[0].to_string(),
+            kind: if kind == "prompt" { "text".to_string() } else { kind.to_string() },
+            question: parts[1].to_string(),
+            default: non_empty(parts.get(2)).map(|d| if parts.len() > 3 { format!("{}:{}", d, parts[3]) } else { d }),
+            options: Vec::new(),
+        }),
+        "suggest" if parts.len() >= 3 => Some(TemplatePrompt {
+            var: parts[0].to_string(),
+            kind: kind.to_string(),
+            question: parts[1].to_string(),
+            default: non_empty(parts.get(3)),
+            options: parts[2].split(',').map(|o| o.trim().to_string()).collect(),
+        }),
+        _ => None,
+    }
+}
+
+/// Prompts declared in a template, in order of first appearance
+pub fn parse_prompts(template: &str) -> Vec<TemplatePrompt> {
+    let mut prompts: Vec<TemplatePrompt> = Vec::new();
+    for caps in PLACEHOLDER_RE.captures_iter(template) {
+        if let Some(prompt) = prompt_from_placeholder(&caps[1]) {
+            if !prompts.iter().any(|p| p.var == prompt.var) {
+                prompts.push(prompt);
+            }
+        }
+    }
+    prompts
+}
+
+pub fn render(template: &str, ctx: &RenderContext) -> RenderedTemplate {
+    let mut missing = Vec::new();
+    let rendered = PLACEHOLDER_RE.replace_all(template, |caps: &Captures| {
+        let expr = &caps[1];
+        let (name, spec) = match expr.split_once(':') {
+            Some((name, spec)) => (name, Some(spec)),
+            None => (expr, None),
+        };
+
+        let value = match name {
+            "date" => render_date(ctx.now, spec, "%Y-%m-%d"),
+            "time" => render_date(ctx.now, spec, "%H:%M"),
+            "datetime" => render_date(ctx.now, spec, "%Y-%m-%d %H:%M"),
+            "timestamp" => Some(ctx.now.timestamp_millis().to_string()),
+            "date.tomorrow" => render_date(ctx.now, Some("+1d"), "%Y-%m-%d"),
+            "date.yesterday" => render_date(ctx.now, Some("-1d"), "%Y-%m-%d"),
+            "date.weekday" | "weekday" => Some(ctx.now.format("%A").to_string()),
+            "date.week" => Some(format!("Week {}", ctx.now.iso_week().week())),
+            "title" => ctx.title.clone(),
+            "cursor" => Some(CURSOR_MARK.to_string()),
+            "prompt" | "suggest" | "checkbox" => prompt_from_placeholder(expr).and_then(|prompt| {
+                let value = ctx.vars.get(&prompt.var).cloned().or(prompt.default);
+                if value.is_none() {
+                    missing.push(prompt.var);
+                }
+                value
+            }),
+            _ => ctx.vars.get(expr).cloned(),
+        };
+        value.unwrap_or_else(|| caps[0].to_string())
+    });
+
+    // Only the first cursor counts; any others are dropped
+    let cursor = rendered.chars().position(|c| c == CURSOR_MARK);
+    RenderedTemplate {
+        content: rendered.replace(CURSOR_MARK, ""),
+        cursor,
+        missing,
+    }
+}
+
+// --- Template files ---
+
+fn templates_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join("templates")
+}
+
+/// Split a template into its metadata (simple `key: value` frontmatter) and body
+fn split_template(content: &str) -> (HashMap<String, String>, &str) {
+    let body = crate::export::strip_frontmatter(content);
+    let header = &content[..content.len() - body.len()];
+    let meta = header
+        .lines()
+        .filter(|line| *line != "---")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().trim_matches('"').to_string()))
+        .collect();
+    (meta, body)
+}
+
+fn template_id(dir: &Path, path: &Path) -> Option<String> {
+    let rel = crate::links::relative_path(dir, path)?;
+    Some(rel.strip_suffix(".md").unwrap_or(&rel).to_string())
+}
+
+fn load_templates(root: &Path) -> Vec<(TemplateInfo, String)> {
+    let dir = templates_dir(root);
+    let mut templates: Vec<(TemplateInfo, String)> = WalkDir::new(&dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && crate::links::is_note(&e.file_name().to_string_lossy()))
+        .filter_map(|e| {
+            let content = fs::read_to_string(e.path()).ok()?;
+            let id = template_id(&dir, e.path())?;
+            let (meta, body) = split_template(&content);
+            let info = TemplateInfo {
+                name: meta.get("name").cloned().unwrap_or_else(|| id.rsplit('/').next().unwrap_or(&id).to_string()),
+                description: meta.get("description").cloned(),
+                path: e.path().to_string_lossy().to_string(),
+                prompts: parse_prompts(body),
+                id,
+            };
+            Some((info, content))
+        })
+        .collect();
+    templates.sort_by(|a, b| a.0.id.to_lowercase().cmp(&b.0.id.to_lowercase()));
+    templates
+}
+
+/// Find a template by id, display name or frontmatter id (case-insensitive)
+fn find_template(root: &Path, name: &str) -> Result<(TemplateInfo, String), String> {
+    let wanted = name.trim().trim_end_matches(".md").to_lowercase();
+    load_templates(root)
+        .into_iter()
+        .find(|(info, content)| {
+            let (meta, _) = split_template(content);
+            info.id.to_lowercase() == wanted
+                || info.name.to_lowercase() == wanted
+                || meta.get("id").map_or(false, |id| id.to_lowercase() == wanted)
+        })
+        .ok_or_else(|| format!("Template '{}' not found", name))
+}
+
+/// The text of a workspace template, without its metadata
+pub fn template_body(root: &Path, name: &str) -> Option<String> {
+    let (_, content) = find_template(root, name).ok()?;
+    Some(split_template(&content).1.to_string())
+}
+
+/// Render a workspace template by name
+pub fn render_named(root: &Path, name: &str, ctx: &RenderContext) -> Result<RenderedTemplate, String> {
+    let (_, content) = find_template(root, name)?;
+    let (_, body) = split_template(&content);
+    Ok(render(body, ctx))
+}
+
+/// `<folder>/<title>.md`, or the first free `<title> N.md`
+pub(crate) fn unused_note_path(folder: &Path, title: &str) -> PathBuf {
+    let file_stem = crate::import::sanitize_file_name(title);
+    (1..)
+        .map(|n| {
+            if n == 1 {
+                folder.join(format!("{}.md", file_stem))
+            } else {
+                folder.join(format!("{} {}.md", file_stem, n))
+            }
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded candidates")
+}
+
+/// Create a note from a template in `folder`. The file name comes from the
+/// explicit title, the template's `filename` pattern, or "Untitled".
+pub fn create_note(root: &Path, name: &str, folder: &Path, ctx: RenderContext) -> Result<CreatedNote, String> {
+    let (_, content) = find_template(root, name)?;
+    let (meta, body) = split_template(&content);
+
+    let title = match (&ctx.title, meta.get("filename")) {
+        (Some(title), _) => title.clone(),
+        (None, Some(pattern)) => render(pattern, &ctx).content,
+        (None, None) => "Untitled".to_string(),
+    };
+    let ctx = RenderContext { title: Some(title), ..ctx };
+    let rendered = render(body, &ctx);
+
+    fs::create_dir_all(folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+    let path = unused_note_path(folder, &title).to_string_lossy().to_string();
+    crate::handlers::files::write_file_content(path.clone(), rendered.content)?;
+    Ok(CreatedNote { path, cursor: rendered.cursor })
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn template_list(workspace_path: String) -> Result<Vec<TemplateInfo>, String> {
+    Ok(load_templates(Path::new(&workspace_path)).into_iter().map(|(info, _)| info).collect())
+}
+
+/// Render a template. `date` (YYYY-MM-DD) renders relative to that day instead of today.
+#[tauri::command]
+pub fn template_render(
+    workspace_path: String,
+    name: String,
+    vars: Option<HashMap<String, String>>,
+    title: Option<String>,
+    date: Option<String>,
+) -> Result<RenderedTemplate, String> {
+    let vars = vars.unwrap_or_default();
+    let ctx = match date {
+        Some(date) => {
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", date, e))?;
+            RenderContext::for_date(date, title, vars)
+        }
+        None => RenderContext::new(title, vars),
+    };
+    render_named(Path::new(&workspace_path), &name, &ctx)
+}
+
+#[tauri::command]
+pub fn template_create_note_from(
+    workspace_path: String,
+    name: String,
+    target_folder: String,
+    title: Option<String>,
+    vars: Option<HashMap<String, String>>,
+) -> Result<CreatedNote, String> {
+    let ctx = RenderContext::new(title, vars.unwrap_or_default());
+    create_note(Path::new(&workspace_path), &name, Path::new(&target_folder), ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn ctx(vars: &[(&str, &str)]) -> RenderContext {
+        RenderContext {
+            now: Local.with_ymd_and_hms(2024, 1, 31, 9, 5, 0).unwrap(),
+            title: Some("Standup".to_string()),
+            vars: vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_date_math_and_formats() {
+        let out = render("{{date}} {{date:+1d}} {{date:+1m}} {{date:-1y:YYYY}} {{date:dddd, MMMM D}} {{time:HH:mm}}", &ctx(&[]));
+        assert_eq!(out.content, "2024-01-31 2024-02-01 2024-02-29 2023 Wednesday, January 31 09:05");
+    }
+
+    #[test]
+    fn test_prompts_vars_and_cursor() {
+        let template = "# {{title}}\n{{prompt:owner:Who owns this?:me}} / {{suggest:status:Status:todo,done}}\n{{cursor}}{{project}} {{unknown}}";
+        let out = render(template, &ctx(&[("project", "Lokus")]));
+        assert_eq!(out.content, "# Standup\nme / {{suggest:status:Status:todo,done}}\nLokus {{unknown}}");
+        assert_eq!(out.cursor, Some("# Standup\nme / {{suggest:status:Status:todo,done}}\n".chars().count()));
+        assert_eq!(out.missing, vec!["status"]);
+
+        let prompts = parse_prompts(template);
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(prompts[1].options, vec!["todo", "done"]);
+    }
+
+    #[test]
+    fn test_convert_date_format() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(convert_date_format("YYYY-MM-DD", date), "%Y-%m-%d");
+        assert_eq!(convert_date_format("[Week] ww, ddd", date), "Week %V, %a");
+    }
+
+    #[test]
+    fn test_ordinal_day_token() {
+        let format = |d: u32| {
+            let date = NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+            date.format(&convert_date_format("MMMM Do", date)).to_string()
+        };
+        assert_eq!(format(1), "January 1st");
+        assert_eq!(format(2), "January 2nd");
+        assert_eq!(format(3), "January 3rd");
+        assert_eq!(format(11), "January 11th");
+        assert_eq!(format(13), "January 13th");
+        assert_eq!(format(22), "January 22nd");
+        assert_eq!(format(31), "January 31st");
+    }
+
+    #[test]
+    fn test_two_letter_weekday_token() {
+        // 2024-01-29 is a Monday
+        let date = NaiveDate::from_ymd_opt(2024, 1, 29).unwrap();
+        assert_eq!(date.format(&convert_date_format("dd", date)).to_string(), "Mo");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(date.format(&convert_date_format("dd DD", date)).to_string(), "We 31");
+    }
+
+    #[test]
+    fn test_weekday_number_token() {
+        // Sunday is 0, as in moment
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 28).unwrap();
+        assert_eq!(sunday.format(&convert_date_format("d", sunday)).to_string(), "0");
+        let saturday = NaiveDate::from_ymd_opt(2024, 2, 3).unwrap();
+        assert_eq!(saturday.format(&convert_date_format("d", saturday)).to_string(), "6");
+    }
+
+    #[test]
+    fn test_create_note_from_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".lokus/templates")).unwrap();
+        fs::write(
+            root.join(".lokus/templates/meeting.md"),
+            "---\nname: Meeting\nfilename: {{date}} Meeting\n---\n# {{title}}\n",
+        )
+        .unwrap();
+
+        let list = template_list(root.to_string_lossy().to_string()).unwrap();
+        assert_eq!(list[0].name, "Meeting");
+
+        let created = create_note(root, "meeting", &root.join("Notes"), ctx(&[])).unwrap();
+        assert!(created.path.ends_with("2024-01-31 Meeting.md"));
+        assert_eq!(fs::read_to_string(&created.path).unwrap(), "# 2024-01-31 Meeting\n");
+    }
+}
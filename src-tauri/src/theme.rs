@@ -43,6 +43,28 @@ pub struct ThemeManifest {
     pub description: Option<String>,
     #[serde(default)]
     pub version: Option<String>,
+    /// Set for themes installed from a URL or the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ThemeSource>,
+}
+
+/// Where an installed theme was downloaded from, used to check for updates
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThemeSource {
+    pub url: String,
+    #[serde(default)]
+    pub registry_id: Option<String>,
+    /// Hex SHA-256 of the downloaded file
+    pub sha256: String,
+    pub installed_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThemeUpdate {
+    pub theme_id: String,
+    pub name: String,
+    pub current_version: Option<String>,
+    pub latest_version: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -189,27 +211,16 @@ pub fn validate_theme_file(file_path: String) -> Result<ThemeValidationResult, S
     Ok(validate_theme_manifest(&manifest))
 }
 
-#[tauri::command]
-pub fn import_theme_file(file_path: String, overwrite: bool) -> Result<String, String> {
-    // Read and validate the theme file
-    let content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read theme file: {}", e))?;
-
-    let manifest: ThemeManifest = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse theme JSON: {}", e))?;
-
+/// Validate a theme and write it to the themes directory, returning its id
+fn install_manifest(manifest: &ThemeManifest, overwrite: bool) -> Result<String, String> {
     // Validate the theme
-    let validation = validate_theme_manifest(&manifest);
+    let validation = validate_theme_manifest(manifest);
     if !validation.valid {
         return Err(format!("Theme validation failed: {}", validation.errors.join(", ")));
     }
 
     // Generate safe filename from theme name
-    let safe_name = manifest.name
-        .chars()
-        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
-        .collect::<String>()
-        .to_lowercase();
+    let safe_name = theme_id(&manifest.name);
 
     if safe_name.is_empty() {
         return Err("Theme name contains no valid characters".to_string());
@@ -227,7 +238,7 @@ pub fn import_theme_file(file_path: String, overwrite: bool) -> Result<String, S
     }
 
     // Write the theme file
-    fs::write(&theme_file, serde_json::to_string_pretty(&manifest).unwrap())
+    fs::write(&theme_file, serde_json::to_string_pretty(manifest).unwrap())
         .map_err(|e| format!("Failed to write theme file: {}", e))?;
 
     // Set file permissions
@@ -240,6 +251,29 @@ pub fn import_theme_file(file_path: String, overwrite: bool) -> Result<String, S
     Ok(safe_name)
 }
 
+fn theme_id(name: &str) -> String {
+    name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[tauri::command]
+pub fn import_theme_file(file_path: String, overwrite: bool) -> Result<String, String> {
+    // Read and validate the theme file
+    let content = fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read theme file: {}", e))?;
+
+    let mut manifest: ThemeManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse theme JSON: {}", e))?;
+
+    // A local copy is no longer tied to where it was downloaded from
+    manifest.source = None;
+
+    install_manifest(&manifest, overwrite)
+}
+
 #[tauri::command]
 pub fn export_theme(theme_id: String, export_path: String) -> Result<(), String> {
     let themes_dir = get_themes_directory()
@@ -355,3 +389,284 @@ pub fn save_theme_tokens(theme_id: String, tokens: HashMap<String, String>) -> R
 
     Ok(())
 }
+
+/// Apply tokens to every window without saving them. Re-broadcasting the
+/// active theme ends the preview.
+#[tauri::command]
+pub fn theme_preview(app: AppHandle, tokens: HashMap<String, String>) -> Result<(), String> {
+    let payload = ThemePayload {
+        tokens: Some(tokens),
+        mode: None,
+        accent: None,
+        scope: Some("preview".to_string()),
+    };
+    app.emit("theme:apply", payload).map_err(|e| e.to_string())
+}
+
+// === Installing from the web ===
+
+#[cfg(desktop)]
+const MAX_THEME_BYTES: usize = 1024 * 1024;
+
+#[cfg(desktop)]
+#[derive(Debug, PartialEq)]
+enum GitHubTarget {
+    /// Raw URL of a file in a repository
+    File(String),
+    Repo { owner: String, repo: String },
+}
+
+/// What a github.com link points at; `blob` links are turned into raw ones
+#[cfg(desktop)]
+fn github_target(url: &str) -> Option<GitHubTarget> {
+    let rest = url
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.strip_prefix("http://github.com/"))?;
+    let parts: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+    match parts.as_slice() {
+        [owner, repo] => Some(GitHubTarget::Repo {
+            owner: owner.to_string(),
+            repo: repo.trim_end_matches(".git").to_string(),
+        }),
+        [owner, repo, "blob" | "raw", path @ ..] if path.len() >= 2 => Some(GitHubTarget::File(format!(
+            "https://raw.githubusercontent.com/{}/{}/{}",
+            owner,
+            repo,
+            path.join("/")
+        ))),
+        _ => None,
+    }
+}
+
+#[cfg(desktop)]
+async fn fetch_theme_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", "Lokus-Theme-Client/1.0")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download theme: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download theme: HTTP {}", response.status()));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_THEME_BYTES) {
+        return Err("Theme file is too large".to_string());
+    }
+    let bytes = response.bytes().await.map_err(|e| format!("Failed to download theme: {}", e))?;
+    if bytes.len() > MAX_THEME_BYTES {
+        return Err("Theme file is too large".to_string());
+    }
+    Ok(bytes.to_vec())
+}
+
+/// The file to download for `url`. A repository link means the `.json`
+/// asset of its latest release, or `theme.json` on the default branch.
+#[cfg(desktop)]
+async fn resolve_theme_url(url: &str) -> Result<String, String> {
+    match github_target(url) {
+        Some(GitHubTarget::File(raw)) => Ok(raw),
+        Some(GitHubTarget::Repo { owner, repo }) => {
+            let api = format!("https://api.github.com/repos/{}/{}/releases/latest", owner, repo);
+            let release: Option<serde_json::Value> = match reqwest::Client::new()
+                .get(&api)
+                .header("User-Agent", "Lokus-Theme-Client/1.0")
+                .header("Accept", "application/vnd.github+json")
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => response.json().await.ok(),
+                _ => None,
+            };
+            let asset = release.as_ref().and_then(|release| {
+                release["assets"]
+                    .as_array()?
+                    .iter()
+                    .find(|a| a["name"].as_str().is_some_and(|n| n.ends_with(".json")))?["browser_download_url"]
+                    .as_str()
+                    .map(|u| u.to_string())
+            });
+            Ok(asset.unwrap_or_else(|| format!("https://raw.githubusercontent.com/{}/{}/HEAD/theme.json", owner, repo)))
+        }
+        None if url.starts_with("https://") || url.starts_with("http://") => Ok(url.to_string()),
+        None => Err("Theme URL must be http(s)".to_string()),
+    }
+}
+
+#[cfg(desktop)]
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Check, parse and install a downloaded theme file
+#[cfg(desktop)]
+fn install_downloaded(
+    bytes: &[u8],
+    url: &str,
+    registry_id: Option<String>,
+    expected_sha256: Option<&str>,
+    overwrite: bool,
+) -> Result<String, String> {
+    let checksum = sha256_hex(bytes);
+    if let Some(expected) = expected_sha256 {
+        if !expected.trim().eq_ignore_ascii_case(&checksum) {
+            return Err(format!("Checksum mismatch: expected {}, got {}", expected.trim(), checksum));
+        }
+    }
+
+    let mut manifest: ThemeManifest = serde_json::from_slice(bytes)
+        .map_err(|e| format!("Failed to parse theme JSON: {}", e))?;
+    manifest.source = Some(ThemeSource {
+        url: url.to_string(),
+        registry_id,
+        sha256: checksum,
+        installed_at: chrono::Utc::now().to_rfc3339(),
+    });
+    install_manifest(&manifest, overwrite)
+}
+
+#[cfg(desktop)]
+#[derive(Deserialize, Debug, Clone)]
+struct RegistryTheme {
+    id: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(alias = "downloadUrl")]
+    download_url: String,
+    #[serde(default)]
+    sha256: Option<String>,
+}
+
+/// A theme's registry entry; static indexes list themes under `themes`
+#[cfg(desktop)]
+async fn fetch_registry_theme(app: &AppHandle, id: &str) -> Result<RegistryTheme, String> {
+    let config = crate::plugins::get_registry_config(app);
+    let entry = if config.is_static() {
+        crate::plugins::registry_get_json(&config.url)
+            .await?
+            .get("themes")
+            .and_then(|themes| themes.as_array())
+            .and_then(|themes| themes.iter().find(|t| t["id"].as_str() == Some(id)).cloned())
+            .ok_or_else(|| format!("Theme '{}' is not in the registry", id))?
+    } else {
+        let url = format!("{}/theme/{}", config.url.trim_end_matches('/'), urlencoding::encode(id));
+        crate::plugins::registry_get_json(&url)
+            .await
+            .map_err(|e| format!("Theme '{}' is not available: {}", id, e))?
+    };
+    serde_json::from_value(entry).map_err(|e| format!("Invalid registry response: {}", e))
+}
+
+/// Install a theme from a direct link or a GitHub file, repository or
+/// release. With `sha256` the download must match it.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn install_theme_from_url(url: String, sha256: Option<String>, overwrite: Option<bool>) -> Result<String, String> {
+    let download = resolve_theme_url(url.trim()).await?;
+    let bytes = fetch_theme_bytes(&download).await?;
+    install_downloaded(&bytes, url.trim(), None, sha256.as_deref(), overwrite.unwrap_or(false))
+}
+
+/// Install a theme from the registry, checked against its published checksum
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn install_theme_from_registry(app: AppHandle, theme_id: String, overwrite: Option<bool>) -> Result<String, String> {
+    let entry = fetch_registry_theme(&app, &theme_id).await?;
+    let sha256 = entry.sha256.as_deref().ok_or_else(|| "Registry theme has no checksum".to_string())?;
+    let bytes = fetch_theme_bytes(&entry.download_url).await?;
+    install_downloaded(&bytes, &entry.download_url, Some(entry.id.clone()), Some(sha256), overwrite.unwrap_or(false))
+}
+
+/// Installed themes with a newer version upstream: a higher registry version,
+/// or different content behind the URL a theme came from
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn check_theme_updates(app: AppHandle) -> Result<Vec<ThemeUpdate>, String> {
+    let mut updates = Vec::new();
+    for manifest in list_custom_themes()? {
+        let Some(source) = manifest.source.clone() else {
+            continue;
+        };
+        let latest_version = match &source.registry_id {
+            Some(id) => match fetch_registry_theme(&app, id).await {
+                Ok(entry) => {
+                    let newer = match (entry.version.as_deref(), manifest.version.as_deref()) {
+                        (Some(latest), Some(current)) => match (semver::Version::parse(latest), semver::Version::parse(current)) {
+                            (Ok(latest), Ok(current)) => latest > current,
+                            _ => latest != current,
+                        },
+                        _ => entry.sha256.as_deref().is_some_and(|sha| !sha.eq_ignore_ascii_case(&source.sha256)),
+                    };
+                    if !newer {
+                        continue;
+                    }
+                    entry.version
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check theme {} for updates: {}", manifest.name, e);
+                    continue;
+                }
+            },
+            None => {
+                let fetched = match resolve_theme_url(&source.url).await {
+                    Ok(download) => fetch_theme_bytes(&download).await,
+                    Err(e) => Err(e),
+                };
+                match fetched {
+                    Ok(bytes) if sha256_hex(&bytes) != source.sha256 => serde_json::from_slice::<ThemeManifest>(&bytes)
+                        .ok()
+                        .and_then(|latest| latest.version),
+                    Ok(_) => continue,
+                    Err(e) => {
+                        tracing::warn!("Failed to check theme {} for updates: {}", manifest.name, e);
+                        continue;
+                    }
+                }
+            }
+        };
+        updates.push(ThemeUpdate {
+            theme_id: theme_id(&manifest.name),
+            name: manifest.name.clone(),
+            current_version: manifest.version.clone(),
+            latest_version,
+        });
+    }
+    Ok(updates)
+}
+
+/// Reinstall a theme from where it was downloaded
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn update_theme(app: AppHandle, theme_id: String) -> Result<String, String> {
+    let themes_dir = get_themes_directory()
+        .map_err(|e| format!("Failed to access themes directory: {}", e))?;
+    let content = fs::read_to_string(themes_dir.join(format!("{}.json", theme_id)))
+        .map_err(|_| format!("Theme '{}' not found", theme_id))?;
+    let manifest: ThemeManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse theme JSON: {}", e))?;
+    let source = manifest.source.ok_or_else(|| format!("Theme '{}' was not installed from the web", theme_id))?;
+
+    match source.registry_id {
+        Some(id) => install_theme_from_registry(app, id, Some(true)).await,
+        None => install_theme_from_url(source.url, None, Some(true)).await,
+    }
+}
+
+#[cfg(all(test, desktop))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_target() {
+        assert_eq!(
+            github_target("https://github.com/lokus-ai/themes/blob/main/nord/theme.json"),
+            Some(GitHubTarget::File("https://raw.githubusercontent.com/lokus-ai/themes/main/nord/theme.json".to_string()))
+        );
+        assert_eq!(
+            github_target("https://github.com/lokus-ai/nord-theme.git"),
+            Some(GitHubTarget::Repo { owner: "lokus-ai".to_string(), repo: "nord-theme".to_string() })
+        );
+        assert_eq!(github_target("https://github.com/lokus-ai/themes/tree/main"), None);
+        assert_eq!(github_target("https://example.com/theme.json"), None);
+    }
+}
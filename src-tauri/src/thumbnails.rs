@@ -0,0 +1,274 @@
+//! Small previews for the file explorer.
+//!
+//! Thumbnails are PNGs in `.lokus/thumbnails`, named by the content hash of
+//! the file they show and their size, so a moved or copied file reuses its
+//! preview. Images are decoded directly, PDFs show their first page (through
+//! poppler), videos their first second (through ffmpeg, when installed) and
+//! diagrams their rendered drawing.
+//!
+//! Hashes are remembered per path, size and modification time; the watcher
+//! drops them when a file changes. The folder is kept under a size budget by
+//! removing the least recently used thumbnails, which are touched on every
+//! hit.
+
+use base64::{engine::general_purpose, Engine as _};
+use image::DynamicImage;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const THUMBNAILS_DIR: &str = "thumbnails";
+const DEFAULT_SIZE: u32 = 256;
+const MIN_SIZE: u32 = 32;
+const MAX_SIZE: u32 = 1024;
+/// Total size of a thumbnail folder before the least recently used go
+const CACHE_BUDGET: u64 = 64 * 1024 * 1024;
+/// Resolution PDF pages are rendered at before scaling down
+const PDF_DPI: u32 = 72;
+
+/// Content hash per (path), valid while size and mtime match
+static HASHES: Lazy<Mutex<HashMap<PathBuf, (u64, SystemTime, String)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailSource {
+    Image,
+    Pdf,
+    Video,
+    Diagram,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailEncoding {
+    /// Path of the cached PNG
+    #[default]
+    Path,
+    /// A `data:image/png;base64,...` URL
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnail {
+    pub path: Option<String>,
+    pub data_url: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub content_hash: String,
+    pub source: ThumbnailSource,
+}
+
+pub fn source_of(path: &Path) -> Option<ThumbnailSource> {
+    if crate::diagrams::is_diagram(&path.to_string_lossy()) {
+        return Some(ThumbnailSource::Diagram);
+    }
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "tif" | "tiff" | "heic" | "heif" | "svg" => {
+            Some(ThumbnailSource::Image)
+        }
+        "pdf" => Some(ThumbnailSource::Pdf),
+        "mp4" | "mov" | "webm" | "mkv" | "m4v" | "avi" => Some(ThumbnailSource::Video),
+        _ => None,
+    }
+}
+
+fn content_hash(path: &Path) -> Result<String, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    if let Some((size, mtime, hash)) = HASHES.lock().unwrap_or_else(|e| e.into_inner()).get(path) {
+        if *size == meta.len() && *mtime == modified {
+            return Ok(hash.clone());
+        }
+    }
+    let file = fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(file).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let hash = hasher.finalize().to_hex()[..32].to_string();
+    HASHES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path.to_path_buf(), (meta.len(), modified, hash.clone()));
+    Ok(hash)
+}
+
+fn cache_dir(path: &Path) -> PathBuf {
+    match crate::handlers::files::find_workspace_root(path.parent().unwrap_or(path)) {
+        Ok(root) => root.join(".lokus").join(THUMBNAILS_DIR),
+        Err(_) => std::env::temp_dir().join("lokus-thumbnails"),
+    }
+}
+
+fn decode_png(bytes: &[u8]) -> Result<DynamicImage, String> {
+    image::load_from_memory(bytes).map_err(|e| format!("Failed to decode preview: {}", e))
+}
+
+/// First frame after a second in (or the very first, for shorter clips)
+fn video_frame(path: &Path, size: u32) -> Result<DynamicImage, String> {
+    let scale = format!("scale={}:-2", size);
+    for seek in ["1", "0"] {
+        let output = Command::new("ffmpeg")
+            .args(["-v", "error", "-ss", seek, "-i"])
+            .arg(path)
+            .args(["-frames:v", "1", "-vf", &scale, "-f", "image2pipe", "-c:v", "png", "-"])
+            .output()
+            .map_err(|_| "Video thumbnails need ffmpeg installed".to_string())?;
+        if output.status.success() && !output.stdout.is_empty() {
+            return decode_png(&output.stdout);
+        }
+    }
+    Err(format!("ffmpeg could not read a frame from {}", path.display()))
+}
+
+fn render(path: &Path, source: ThumbnailSource, size: u32) -> Result<DynamicImage, String> {
+    match source {
+        ThumbnailSource::Image if crate::images::is_heic(path) => crate::images::decode_heic(path),
+        ThumbnailSource::Image if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg")) => {
+            let svg = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            decode_png(&crate::canvas::svg_to_png(&svg, 1.0)?)
+        }
+        ThumbnailSource::Image => crate::images::decode(path),
+        ThumbnailSource::Pdf => decode_png(&crate::pdf::PDFProcessor::new(path)?.render_page_png(1, PDF_DPI)?),
+        ThumbnailSource::Video => video_frame(path, size),
+        ThumbnailSource::Diagram => {
+            let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            decode_png(&crate::canvas::svg_to_png(&crate::diagrams::render(path, &content)?.svg, 1.0)?)
+        }
+    }
+}
+
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Remove the least recently used thumbnails until `dir` fits the budget
+fn evict(dir: &Path, budget: u64) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((meta.modified().ok()?, meta.len(), e.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort();
+    for (_, len, path) in files {
+        if total <= budget {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+fn thumbnail(path: &Path, size: u32, encoding: ThumbnailEncoding) -> Result<Thumbnail, String> {
+    let source = source_of(path).ok_or_else(|| format!("No preview available for {}", path.display()))?;
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let content_hash = content_hash(path)?;
+    let dir = cache_dir(path);
+    let cached = dir.join(format!("{}-{}.png", content_hash, size));
+
+    let png = match fs::read(&cached) {
+        Ok(png) => {
+            touch(&cached);
+            png
+        }
+        Err(_) => {
+            let preview = render(path, source, size)?.thumbnail(size, size);
+            let mut png = Vec::new();
+            preview
+                .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+            fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            // Written aside first so a concurrent reader never sees half a file
+            let temp = dir.join(format!("{}-{}.png.tmp", content_hash, size));
+            fs::write(&temp, &png).map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+            fs::rename(&temp, &cached).map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+            evict(&dir, CACHE_BUDGET);
+            png
+        }
+    };
+
+    let (width, height) = image::load_from_memory(&png).map(|i| (i.width(), i.height())).unwrap_or((0, 0));
+    let (path, data_url) = match encoding {
+        ThumbnailEncoding::Path => (Some(cached.to_string_lossy().to_string()), None),
+        ThumbnailEncoding::Base64 => (None, Some(format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(&png)))),
+    };
+    Ok(Thumbnail { path, data_url, width, height, content_hash, source })
+}
+
+// --- Hooks for file operations ---
+
+/// Forget the remembered hash of a changed, moved or deleted file
+pub fn notify_file_changed(file_path: &str) {
+    let path = Path::new(file_path);
+    HASHES.lock().unwrap_or_else(|e| e.into_inner()).retain(|p, _| !p.starts_with(path));
+}
+
+// --- Tauri Commands ---
+
+/// Preview of `path` no larger than `size` pixels on either side
+#[tauri::command]
+pub async fn get_thumbnail(path: String, size: Option<u32>, encoding: Option<ThumbnailEncoding>) -> Result<Thumbnail, String> {
+    tokio::task::spawn_blocking(move || {
+        thumbnail(Path::new(&path), size.unwrap_or(DEFAULT_SIZE), encoding.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Thumbnail failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnails_are_cached_by_content() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        let photo = dir.path().join("photo.png");
+        DynamicImage::new_rgb8(600, 300).save(&photo).unwrap();
+
+        let first = thumbnail(&photo, 100, ThumbnailEncoding::Path).unwrap();
+        assert_eq!((first.width, first.height), (100, 50));
+        let cached = PathBuf::from(first.path.unwrap());
+        assert!(cached.starts_with(dir.path().join(".lokus/thumbnails")));
+
+        // A copy shares the preview
+        let copy = dir.path().join("copy.png");
+        fs::copy(&photo, &copy).unwrap();
+        let second = thumbnail(&copy, 100, ThumbnailEncoding::Base64).unwrap();
+        assert_eq!(second.content_hash, first.content_hash);
+        assert!(second.data_url.unwrap().starts_with("data:image/png;base64,"));
+        assert_eq!(fs::read_dir(cached.parent().unwrap()).unwrap().count(), 1);
+
+        DynamicImage::new_rgb8(50, 80).save(&photo).unwrap();
+        notify_file_changed(&photo.to_string_lossy());
+        assert_ne!(thumbnail(&photo, 100, ThumbnailEncoding::Path).unwrap().content_hash, first.content_hash);
+        assert!(thumbnail(&dir.path().join("notes.md"), 100, ThumbnailEncoding::Path).is_err());
+    }
+
+    #[test]
+    fn test_eviction_drops_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        for (i, name) in ["old.png", "mid.png", "new.png"].iter().enumerate() {
+            let path = dir.path().join(name);
+            fs::write(&path, [0u8; 100]).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000 * (i as u64 + 1))).unwrap();
+        }
+        evict(dir.path(), 250);
+        assert!(!dir.path().join("old.png").exists());
+        assert!(dir.path().join("mid.png").exists() && dir.path().join("new.png").exists());
+    }
+}
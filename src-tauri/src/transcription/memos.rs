@@ -0,0 +1,414 @@
+//! Voice memo transcription.
+//!
+//! Audio files in the workspace are transcribed either locally with
+//! whisper.cpp (built with the `whisper` feature, using a ggml model file)
+//! or by an OpenAI-compatible `/audio/transcriptions` endpoint. The result
+//! is written next to the audio as `<name> (transcript).md`, with one
+//! timestamped line per segment, and goes through the normal save path so it
+//! is searchable straight away.
+//!
+//! With `autoTranscribe` on, audio files that appear in the workspace are
+//! transcribed in the background.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreBuilder;
+
+const SETTINGS_FILE: &str = ".settings.dat";
+const SETTINGS_KEY: &str = "voice_memo_transcription";
+const API_KEY_STORAGE: &str = "voice-memo-api-key";
+const PROGRESS_EVENT: &str = "voice-memo-progress";
+const TRANSCRIBED_EVENT: &str = "voice-memo-transcribed";
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "aac", "wav", "ogg", "oga", "opus", "flac", "webm"];
+/// Upload limit of the OpenAI transcription API
+const REMOTE_MAX_BYTES: u64 = 25 * 1024 * 1024;
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MemoBackend {
+    /// whisper.cpp with a ggml model such as `ggml-base.bin`
+    #[serde(rename_all = "camelCase")]
+    Local { model_path: String },
+    /// OpenAI-compatible API; the key is kept in secure storage
+    #[serde(rename_all = "camelCase")]
+    Remote { endpoint: String, model: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MemoSettings {
+    pub backend: Option<MemoBackend>,
+    /// ISO 639-1 code; detected from the audio when unset
+    pub language: Option<String>,
+    pub auto_transcribe: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TranscribeOptions {
+    /// Overrides the configured language
+    pub language: Option<String>,
+    /// Where to write the note; defaults to `<name> (transcript).md` beside the audio
+    pub output_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Seconds from the start of the recording
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Transcript {
+    language: Option<String>,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptNote {
+    pub audio_path: String,
+    pub note_path: String,
+    pub language: Option<String>,
+    /// Seconds
+    pub duration: f64,
+    pub segments: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress<'a> {
+    path: &'a str,
+    /// `decoding`, `transcribing`, `uploading` or `writing`
+    stage: &'a str,
+    /// 0.0 to 1.0 within the stage, when known
+    progress: Option<f32>,
+}
+
+/// Audio files currently being transcribed
+static IN_PROGRESS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+pub(crate) fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |ext| AUDIO_EXTENSIONS.iter().any(|a| ext.eq_ignore_ascii_case(a)))
+}
+
+fn default_note_path(audio: &Path) -> PathBuf {
+    let stem = audio.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    audio.with_file_name(format!("{} (transcript).md", stem))
+}
+
+fn emit_progress(app: &AppHandle, path: &str, stage: &str, progress: Option<f32>) {
+    if let Err(e) = app.emit(PROGRESS_EVENT, &Progress { path, stage, progress }) {
+        tracing::warn!("Failed to emit transcription progress: {}", e);
+    }
+}
+
+fn load_settings(app: &AppHandle) -> MemoSettings {
+    let Ok(store) = StoreBuilder::new(app, PathBuf::from(SETTINGS_FILE)).build() else {
+        return MemoSettings::default();
+    };
+    let _ = store.reload();
+    store
+        .get(SETTINGS_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+// --- Output ---
+
+fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}", minutes, secs)
+    }
+}
+
+fn render_note(audio_name: &str, transcript: &Transcript, duration: f64) -> String {
+    let title = audio_name.rsplit_once('.').map_or(audio_name, |(stem, _)| stem);
+    let mut note = String::from("---\n");
+    note.push_str(&format!("audio: \"[[{}]]\"\n", audio_name));
+    if let Some(language) = &transcript.language {
+        note.push_str(&format!("language: {}\n", language));
+    }
+    note.push_str(&format!("duration: \"{}\"\n", format_timestamp(duration)));
+    note.push_str(&format!("transcribed: {}\n", chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)));
+    note.push_str("tags: [voice-memo]\n---\n\n");
+    note.push_str(&format!("# {}\n\n![[{}]]\n\n", title, audio_name));
+    for segment in &transcript.segments {
+        let text = segment.text.trim();
+        if !text.is_empty() {
+            note.push_str(&format!("**[{}]** {}\n\n", format_timestamp(segment.start), text));
+        }
+    }
+    format!("{}\n", note.trim_end())
+}
+
+// --- Backends ---
+
+#[cfg(feature = "whisper")]
+fn transcribe_local(
+    model_path: &Path,
+    samples: &[f32],
+    language: Option<&str>,
+    progress: impl FnMut(i32) + Send + Sync + 'static,
+) -> Result<Transcript, String> {
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    let err = |e: whisper_rs::WhisperError| format!("Transcription failed: {}", e);
+    let context = WhisperContext::new_with_params(&model_path.to_string_lossy(), WhisperContextParameters::default())
+        .map_err(|e| format!("Failed to load whisper model: {}", e))?;
+    let mut state = context.create_state().map_err(err)?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language.unwrap_or("auto")));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+    params.set_progress_callback_safe(progress);
+    state.full(params, samples).map_err(err)?;
+
+    let language = state
+        .full_lang_id_from_state()
+        .ok()
+        .and_then(whisper_rs::get_lang_str)
+        .map(str::to_string);
+    let count = state.full_n_segments().map_err(err)?;
+    let segments = (0..count)
+        .map(|i| {
+            Ok(Segment {
+                // whisper timestamps are in centiseconds
+                start: state.full_get_segment_t0(i).map_err(err)? as f64 / 100.0,
+                end: state.full_get_segment_t1(i).map_err(err)? as f64 / 100.0,
+                text: state.full_get_segment_text(i).map_err(err)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Transcript { language, segments })
+}
+
+#[cfg(not(feature = "whisper"))]
+fn transcribe_local(
+    _model_path: &Path,
+    _samples: &[f32],
+    _language: Option<&str>,
+    _progress: impl FnMut(i32) + Send + Sync + 'static,
+) -> Result<Transcript, String> {
+    Err("This build doesn't include local transcription; configure a remote endpoint instead".to_string())
+}
+
+#[derive(Deserialize)]
+struct RemoteTranscript {
+    language: Option<String>,
+    duration: Option<f64>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    segments: Vec<Segment>,
+}
+
+async fn transcribe_remote(endpoint: &str, model: &str, audio: &Path, language: Option<&str>) -> Result<(Transcript, f64), String> {
+    let size = tokio::fs::metadata(audio).await.map_err(|e| format!("Failed to read audio: {}", e))?.len();
+    if size > REMOTE_MAX_BYTES {
+        return Err(format!(
+            "The recording is {} MB; the transcription endpoint accepts up to {} MB",
+            size / (1024 * 1024),
+            REMOTE_MAX_BYTES / (1024 * 1024)
+        ));
+    }
+    let bytes = tokio::fs::read(audio).await.map_err(|e| format!("Failed to read audio: {}", e))?;
+    let name = audio.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "audio".to_string());
+
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", reqwest::multipart::Part::bytes(bytes).file_name(name))
+        .text("model", model.to_string())
+        .text("response_format", "verbose_json");
+    if let Some(language) = language {
+        form = form.text("language", language.to_string());
+    }
+    let api_key = crate::secure_storage::SecureStorage::new()
+        .ok()
+        .and_then(|storage| storage.retrieve::<String>(API_KEY_STORAGE).ok().flatten());
+
+    let client = reqwest::Client::builder()
+        .timeout(REMOTE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut request = client.post(format!("{}/audio/transcriptions", endpoint.trim_end_matches('/'))).multipart(form);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+    let response = request.send().await.map_err(|e| format!("Transcription request failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Transcription endpoint returned {}: {}", status, body.trim()));
+    }
+    let parsed: RemoteTranscript = response.json().await.map_err(|e| format!("Invalid transcription response: {}", e))?;
+
+    let duration = parsed.duration.or_else(|| parsed.segments.last().map(|s| s.end)).unwrap_or(0.0);
+    let segments = if parsed.segments.is_empty() && !parsed.text.trim().is_empty() {
+        vec![Segment { start: 0.0, end: duration, text: parsed.text }]
+    } else {
+        parsed.segments
+    };
+    Ok((Transcript { language: parsed.language, segments }, duration))
+}
+
+async fn run(app: &AppHandle, audio: &Path, options: TranscribeOptions) -> Result<TranscriptNote, String> {
+    let settings = load_settings(app);
+    let backend = settings.backend.ok_or("Choose a transcription engine in settings first")?;
+    let language = options.language.or(settings.language).filter(|l| !l.trim().is_empty() && l != "auto");
+    let audio_str = audio.to_string_lossy().to_string();
+
+    let (transcript, duration) = match backend {
+        MemoBackend::Local { model_path } => {
+            emit_progress(app, &audio_str, "decoding", None);
+            let path = audio.to_path_buf();
+            let samples = tokio::task::spawn_blocking(move || super::decode::decode_mono_16k(&path))
+                .await
+                .map_err(|e| format!("Decoding failed: {}", e))??;
+            let duration = samples.len() as f64 / super::decode::SAMPLE_RATE as f64;
+
+            emit_progress(app, &audio_str, "transcribing", Some(0.0));
+            let progress_app = app.clone();
+            let progress_path = audio_str.clone();
+            tokio::task::spawn_blocking(move || {
+                transcribe_local(Path::new(&model_path), &samples, language.as_deref(), move |percent| {
+                    emit_progress(&progress_app, &progress_path, "transcribing", Some(percent as f32 / 100.0));
+                })
+                .map(|transcript| (transcript, duration))
+            })
+            .await
+            .map_err(|e| format!("Transcription failed: {}", e))??
+        }
+        MemoBackend::Remote { endpoint, model } => {
+            emit_progress(app, &audio_str, "uploading", None);
+            transcribe_remote(&endpoint, &model, audio, language.as_deref()).await?
+        }
+    };
+
+    emit_progress(app, &audio_str, "writing", None);
+    let note_path = options.output_path.map(PathBuf::from).unwrap_or_else(|| default_note_path(audio));
+    let audio_name = audio.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let note = render_note(&audio_name, &transcript, duration);
+    let note_path = note_path.to_string_lossy().to_string();
+    crate::handlers::files::write_file_content(note_path.clone(), note)?;
+
+    Ok(TranscriptNote {
+        audio_path: audio_str,
+        note_path,
+        language: transcript.language,
+        duration,
+        segments: transcript.segments.len(),
+    })
+}
+
+/// Transcribe unless the same file is already being transcribed
+async fn transcribe_once(app: &AppHandle, audio: &Path, options: TranscribeOptions) -> Result<TranscriptNote, String> {
+    if !IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).insert(audio.to_path_buf()) {
+        return Err("This recording is already being transcribed".to_string());
+    }
+    let result = run(app, audio, options).await;
+    IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner()).remove(audio);
+    result
+}
+
+/// Called by the workspace watcher when a file appears: transcribes new
+/// recordings in the background when auto-transcription is on
+pub fn notify_audio_added(app: &AppHandle, path: &Path) {
+    if !is_audio(path) || default_note_path(path).exists() {
+        return;
+    }
+    let settings = load_settings(app);
+    if !settings.auto_transcribe || settings.backend.is_none() {
+        return;
+    }
+    let app = app.clone();
+    let path = path.to_path_buf();
+    tauri::async_runtime::spawn(async move {
+        match transcribe_once(&app, &path, TranscribeOptions::default()).await {
+            Ok(note) => {
+                if let Err(e) = app.emit(TRANSCRIBED_EVENT, &note) {
+                    tracing::warn!("Failed to emit {}: {}", TRANSCRIBED_EVENT, e);
+                }
+            }
+            Err(e) => tracing::warn!("Automatic transcription of {} failed: {}", path.display(), e),
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+/// Transcribe an audio file into a markdown note and return where it was written
+#[tauri::command]
+pub async fn transcribe_audio(app: AppHandle, path: String, options: Option<TranscribeOptions>) -> Result<TranscriptNote, String> {
+    let audio = PathBuf::from(&path);
+    if !audio.is_file() {
+        return Err(format!("Audio file not found: {}", path));
+    }
+    if !is_audio(&audio) {
+        return Err("Not a supported audio file".to_string());
+    }
+    transcribe_once(&app, &audio, options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub fn voice_memo_settings_get(app: AppHandle) -> MemoSettings {
+    load_settings(&app)
+}
+
+/// Save settings. `api_key` replaces the stored key for the remote endpoint;
+/// an empty string removes it.
+#[tauri::command]
+pub fn voice_memo_settings_set(app: AppHandle, settings: MemoSettings, api_key: Option<String>) -> Result<(), String> {
+    if let Some(key) = api_key {
+        let storage = crate::secure_storage::SecureStorage::new().map_err(|e| format!("Secure storage unavailable: {}", e))?;
+        let saved = if key.is_empty() { storage.delete(API_KEY_STORAGE) } else { storage.store(API_KEY_STORAGE, &key) };
+        saved.map_err(|e| format!("Failed to save API key: {}", e))?;
+    }
+    let store = StoreBuilder::new(&app, PathBuf::from(SETTINGS_FILE))
+        .build()
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    let _ = store.reload();
+    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    store.set(SETTINGS_KEY.to_string(), value);
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_note_with_timestamps() {
+        let transcript = Transcript {
+            language: Some("en".to_string()),
+            segments: vec![
+                Segment { start: 0.0, end: 4.2, text: " Buy milk.".to_string() },
+                Segment { start: 3725.0, end: 3730.0, text: "  ".to_string() },
+                Segment { start: 65.5, end: 70.0, text: "Call Sam back.".to_string() },
+            ],
+        };
+        let note = render_note("memo 1.m4a", &transcript, 3730.0);
+        assert!(note.starts_with("---\naudio: \"[[memo 1.m4a]]\"\nlanguage: en\nduration: \"1:02:10\"\n"));
+        assert!(note.contains("# memo 1\n\n![[memo 1.m4a]]\n\n**[00:00]** Buy milk.\n\n**[01:05]** Call Sam back.\n"));
+        assert_eq!(note.matches("**[").count(), 2);
+
+        assert_eq!(default_note_path(Path::new("/v/a/memo.m4a")), PathBuf::from("/v/a/memo (transcript).md"));
+        assert!(is_audio(Path::new("x.MP3")) && !is_audio(Path::new("x.md")));
+    }
+}
@@ -43,6 +43,13 @@
 //! [`MAX_RETRIES`] times with exponential back-off (1 s → 2 s → 4 s).
 //! After all retries are exhausted a [`lokus:transcription-error`] event is
 //! emitted and the state machine resets to `Idle`.
+//!
+//! Transcription of recorded audio files (voice memos) lives in [`memos`].
+
+#[cfg(desktop)]
+mod decode;
+#[cfg(desktop)]
+pub mod memos;
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
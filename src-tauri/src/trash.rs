@@ -0,0 +1,393 @@
+//! Workspace trash.
+//!
+//! Deleted files and folders are moved into `.lokus/trash/<id>/` and recorded
+//! in `.lokus/trash/index.json` with their original location, so they can be
+//! restored until they are purged. Items older than the retention setting
+//! (`trash_retention_days` in `.settings.dat`, 30 days by default) are purged
+//! when the app starts.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+pub const DEFAULT_RETENTION_DAYS: u64 = 30;
+pub const RETENTION_SETTING: &str = "trash_retention_days";
+
+/// Serializes updates to trash indexes
+static TRASH_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    pub id: String,
+    pub name: String,
+    /// Original location relative to the workspace root
+    pub original_path: String,
+    pub is_directory: bool,
+    /// Unix timestamp in milliseconds
+    pub deleted_at: i64,
+    pub size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrashIndex {
+    items: Vec<TrashItem>,
+}
+
+fn trash_dir(root: &Path) -> PathBuf {
+    root.join(".lokus").join("trash")
+}
+
+fn index_path(root: &Path) -> PathBuf {
+    trash_dir(root).join("index.json")
+}
+
+fn load_index(root: &Path) -> TrashIndex {
+    let mut index: TrashIndex = fs::read_to_string(index_path(root))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    // The index is a plain file in the workspace, so a synced or edited copy
+    // must not be able to point deletes or restores outside the trash
+    index.items.retain(|item| {
+        let valid = is_valid(item);
+        if !valid {
+            tracing::warn!("Ignoring invalid trash entry '{}' in {}", item.id, root.display());
+        }
+        valid
+    });
+    index
+}
+
+/// Only relative paths made of plain names, outside `.lokus`
+fn is_workspace_relative(rel: &str) -> bool {
+    let mut components = Path::new(rel).components().peekable();
+    components.peek().map_or(false, |first| *first != Component::Normal(".lokus".as_ref()))
+        && components.all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn is_valid(item: &TrashItem) -> bool {
+    // Ids are always written as hyphenated v4 UUIDs
+    let id_ok = uuid::Uuid::parse_str(&item.id).map_or(false, |id| id.hyphenated().to_string() == item.id);
+    let name_ok = matches!(Path::new(&item.name).components().collect::<Vec<_>>().as_slice(), [Component::Normal(_)]);
+    id_ok && name_ok && is_workspace_relative(&item.original_path)
+}
+
+fn save_index(root: &Path, index: &TrashIndex) -> Result<(), String> {
+    fs::create_dir_all(trash_dir(root)).map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    let json = serde_json::to_string_pretty(index).map_err(|e| format!("Failed to serialize trash index: {}", e))?;
+    let temp_path = index_path(root).with_extension("json.tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Failed to write trash index: {}", e))?;
+    fs::rename(&temp_path, index_path(root)).map_err(|e| format!("Failed to replace trash index: {}", e))
+}
+
+fn with_index<T>(root: &Path, f: impl FnOnce(&mut TrashIndex) -> Result<T, String>) -> Result<T, String> {
+    let _guard = TRASH_LOCK.lock().map_err(|e| format!("Trash lock poisoned: {}", e))?;
+    let mut index = load_index(root);
+    let result = f(&mut index)?;
+    save_index(root, &index)?;
+    Ok(result)
+}
+
+fn item_location(root: &Path, item: &TrashItem) -> PathBuf {
+    trash_dir(root).join(&item.id).join(&item.name)
+}
+
+fn total_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Rename, falling back to copy + delete across filesystems
+fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        for entry in WalkDir::new(from).into_iter().filter_map(|e| e.ok()) {
+            let rel = entry.path().strip_prefix(from).map_err(|e| e.to_string())?;
+            let dest = to.join(rel);
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+            } else {
+                fs::copy(entry.path(), &dest).map_err(|e| format!("Failed to copy {}: {}", entry.path().display(), e))?;
+            }
+        }
+        fs::remove_dir_all(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+    } else {
+        fs::copy(from, to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+        fs::remove_file(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+    }
+}
+
+/// Move `path` into the trash of workspace `root`
+pub fn move_to_trash(root: &Path, path: &Path) -> Result<TrashItem, String> {
+    let original_path = crate::links::relative_path(root, path)
+        .filter(|rel| !rel.is_empty())
+        .ok_or_else(|| format!("Path is outside the workspace: {}", path.display()))?;
+    if original_path == ".lokus" || original_path.starts_with(".lokus/") {
+        return Err("Cannot move workspace metadata to the trash".to_string());
+    }
+
+    let item = TrashItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Invalid path: {}", path.display()))?,
+        original_path,
+        is_directory: path.is_dir(),
+        deleted_at: chrono::Utc::now().timestamp_millis(),
+        size: total_size(path),
+    };
+
+    with_index(root, |index| {
+        let destination = item_location(root, &item);
+        fs::create_dir_all(destination.parent().expect("trash item has a parent"))
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+        move_path(path, &destination)?;
+        index.items.push(item.clone());
+        Ok(item)
+    })
+}
+
+/// A free path for a restored item: the original, or `name (restored N).ext`
+fn restore_destination(original: &Path) -> PathBuf {
+    if !original.exists() {
+        return original.to_path_buf();
+    }
+    let stem = original.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = original.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 { String::new() } else { format!(" {}", n) };
+            original.with_file_name(format!("{} (restored{}){}", stem, suffix, extension))
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded candidates")
+}
+
+/// Feed restored notes back into the search index and link graph
+fn reindex(path: &Path) {
+    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let file = entry.path().to_string_lossy();
+        if let Ok(content) = fs::read_to_string(entry.path()) {
+            crate::search::index::notify_file_saved(&file, &content);
+            crate::links::notify_file_saved(&file, &content);
+        }
+    }
+}
+
+/// Permanently delete items deleted more than `days` days ago; returns how many
+pub fn purge_older_than(root: &Path, days: u64) -> Result<usize, String> {
+    if !trash_dir(root).exists() {
+        return Ok(0);
+    }
+    let cutoff = chrono::Utc::now().timestamp_millis() - (days as i64) * 24 * 60 * 60 * 1000;
+    with_index(root, |index| {
+        let (expired, kept): (Vec<TrashItem>, Vec<TrashItem>) =
+            index.items.drain(..).partition(|item| item.deleted_at < cutoff);
+        index.items = kept;
+        for item in &expired {
+            let _ = fs::remove_dir_all(trash_dir(root).join(&item.id));
+        }
+        Ok(expired.len())
+    })
+}
+
+/// Purge expired trash in every known workspace, honoring the retention setting
+pub fn purge_on_startup(app: &tauri::AppHandle) {
+    use tauri_plugin_store::StoreBuilder;
+
+    let mut retention_days = DEFAULT_RETENTION_DAYS;
+    let mut workspaces: Vec<String> = crate::vaults::list_vaults().into_iter().map(|v| v.path).collect();
+    if let Ok(store) = StoreBuilder::new(app, PathBuf::from(".settings.dat")).build() {
+        let _ = store.reload();
+        if let Some(days) = store.get(RETENTION_SETTING).and_then(|v| v.as_u64()) {
+            retention_days = days;
+        }
+        if let Some(last) = store.get("last_workspace_path").and_then(|v| v.as_str().map(String::from)) {
+            if !workspaces.contains(&last) {
+                workspaces.push(last);
+            }
+        }
+    }
+
+    // Zero means keep items until the trash is emptied by hand
+    if retention_days == 0 {
+        return;
+    }
+    for workspace in workspaces {
+        match purge_older_than(Path::new(&workspace), retention_days) {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Purged {} expired trash items from {}", count, workspace),
+            Err(e) => tracing::warn!("Failed to purge trash in {}: {}", workspace, e),
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Items in the workspace trash, most recently deleted first
+#[tauri::command]
+pub fn trash_list(workspace_path: String) -> Result<Vec<TrashItem>, String> {
+    let mut items = load_index(Path::new(&workspace_path)).items;
+    items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(items)
+}
+
+/// Restore an item to its original location (or next to it if that path is
+/// taken again). Returns the restored path.
+#[tauri::command]
+pub fn trash_restore(workspace_path: String, id: String) -> Result<String, String> {
+    let root = PathBuf::from(&workspace_path);
+    let restored = with_index(&root, |index| {
+        let position = index
+            .items
+            .iter()
+            .position(|item| item.id == id)
+            .ok_or_else(|| format!("Trash item '{}' not found", id))?;
+        let item = &index.items[position];
+
+        let destination = restore_destination(&root.join(&item.original_path));
+        crate::vaults::ensure_writable(&destination)?;
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to recreate {}: {}", parent.display(), e))?;
+            // A folder on the way may have been replaced by a symlink since
+            let inside = match (parent.canonicalize(), root.canonicalize()) {
+                (Ok(parent), Ok(root)) => parent.starts_with(root),
+                _ => false,
+            };
+            if !inside {
+                return Err(format!("Cannot restore outside the workspace: {}", destination.display()));
+            }
+        }
+        move_path(&item_location(&root, item), &destination)?;
+        let _ = fs::remove_dir_all(trash_dir(&root).join(&item.id));
+        index.items.remove(position);
+        Ok(destination)
+    })?;
+
+    reindex(&restored);
+    Ok(restored.to_string_lossy().to_string())
+}
+
+/// Permanently delete everything in the trash; returns the number of items
+#[tauri::command]
+pub fn trash_empty(workspace_path: String) -> Result<usize, String> {
+    let root = PathBuf::from(&workspace_path);
+    with_index(&root, |index| {
+        for item in &index.items {
+            let _ = fs::remove_dir_all(trash_dir(&root).join(&item.id));
+        }
+        let count = index.items.len();
+        index.items.clear();
+        Ok(count)
+    })
+}
+
+#[tauri::command]
+pub fn trash_purge_older_than(workspace_path: String, days: u64) -> Result<usize, String> {
+    purge_older_than(Path::new(&workspace_path), days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".lokus")).unwrap();
+        fs::create_dir_all(dir.path().join("Notes")).unwrap();
+        fs::write(dir.path().join("Notes/a.md"), "alpha").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_trash_and_restore_round_trip() {
+        let dir = workspace();
+        let root = dir.path();
+        let note = root.join("Notes/a.md");
+
+        let item = move_to_trash(root, &note).unwrap();
+        assert!(!note.exists());
+        assert_eq!(item.original_path, "Notes/a.md");
+        assert_eq!(item.size, 5);
+
+        let listed = trash_list(root.to_string_lossy().to_string()).unwrap();
+        assert_eq!(listed.len(), 1);
+
+        let restored = trash_restore(root.to_string_lossy().to_string(), item.id).unwrap();
+        assert_eq!(PathBuf::from(restored), note);
+        assert_eq!(fs::read_to_string(&note).unwrap(), "alpha");
+        assert!(load_index(root).items.is_empty());
+    }
+
+    #[test]
+    fn test_restore_does_not_overwrite() {
+        let dir = workspace();
+        let root = dir.path();
+        let note = root.join("Notes/a.md");
+
+        let item = move_to_trash(root, &note).unwrap();
+        fs::write(&note, "replacement").unwrap();
+        let restored = trash_restore(root.to_string_lossy().to_string(), item.id).unwrap();
+
+        assert!(restored.ends_with("a (restored).md"));
+        assert_eq!(fs::read_to_string(&note).unwrap(), "replacement");
+    }
+
+    #[test]
+    fn test_invalid_entries_are_ignored() {
+        let dir = workspace();
+        let root = dir.path();
+        let item = move_to_trash(root, &root.join("Notes/a.md")).unwrap();
+        let tampered = [
+            TrashItem { id: "../../Notes".to_string(), ..item.clone() },
+            TrashItem { id: uuid::Uuid::new_v4().to_string(), original_path: "../outside.md".to_string(), ..item.clone() },
+            TrashItem { id: uuid::Uuid::new_v4().to_string(), original_path: "/etc/passwd".to_string(), ..item.clone() },
+            TrashItem { id: uuid::Uuid::new_v4().to_string(), original_path: ".lokus/x.md".to_string(), ..item.clone() },
+            TrashItem { id: uuid::Uuid::new_v4().to_string(), name: "../a.md".to_string(), ..item.clone() },
+        ];
+        let mut index = TrashIndex { items: tampered.to_vec() };
+        index.items.push(item.clone());
+        save_index(root, &index).unwrap();
+
+        let listed = load_index(root).items;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, item.id);
+        assert!(trash_restore(root.to_string_lossy().to_string(), "../../Notes".to_string()).is_err());
+        assert_eq!(trash_empty(root.to_string_lossy().to_string()).unwrap(), 1);
+        assert!(root.join("Notes").is_dir());
+    }
+
+    #[test]
+    fn test_purge_respects_age() {
+        let dir = workspace();
+        let root = dir.path();
+        move_to_trash(root, &root.join("Notes")).unwrap();
+
+        assert_eq!(purge_older_than(root, 1).unwrap(), 0);
+        with_index(root, |index| {
+            index.items[0].deleted_at -= 2 * 24 * 60 * 60 * 1000;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(purge_older_than(root, 1).unwrap(), 1);
+        assert_eq!(fs::read_dir(trash_dir(root)).unwrap().count(), 1); // only index.json
+    }
+
+    #[test]
+    fn test_metadata_cannot_be_trashed() {
+        let dir = workspace();
+        assert!(move_to_trash(dir.path(), &dir.path().join(".lokus")).is_err());
+    }
+}
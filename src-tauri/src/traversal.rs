@@ -0,0 +1,224 @@
+//! Walking vaults that live in cloud-synced folders or contain symlinks.
+//!
+//! Dropbox, OneDrive and iCloud can leave files on disk as placeholders
+//! whose content hasn't been downloaded; reading one either fails or blocks
+//! on a download. The `cloud_placeholders` workspace setting decides what a
+//! walk does with them: leave them out, ask the provider to download them
+//! (they show up once the watcher sees the real file), or list them as stubs
+//! without reading them, which is the default.
+//!
+//! Symlinks are followed. A link into the vault itself is skipped, since the
+//! walk reaches its target anyway, and so is a second link to a target
+//! already walked; loops are reported by walkdir as errors, which walks skip.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::{DirEntry, WalkDir};
+
+/// Workspace setting holding the `PlaceholderPolicy`
+const SETTINGS_KEY: &str = "cloud_placeholders";
+/// Suffix of the stand-in files older iCloud Drive versions leave for evicted files
+const ICLOUD_SUFFIX: &str = ".icloud";
+
+/// Placeholders a download was already asked for in this session
+static REQUESTED: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaceholderPolicy {
+    /// Leave placeholders out
+    Ignore,
+    /// Ask the provider to download them, and leave them out until it has
+    Download,
+    /// List them without reading their content
+    #[default]
+    Stub,
+}
+
+pub fn policy(root: &Path) -> PlaceholderPolicy {
+    crate::settings::workspace_settings(root)
+        .get(SETTINGS_KEY)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// The file an iCloud stub like `.Note.md.icloud` stands for
+pub fn icloud_stub_target(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let target = name.strip_prefix('.')?.strip_suffix(ICLOUD_SUFFIX)?;
+    (!target.is_empty()).then(|| path.with_file_name(target))
+}
+
+/// Name to show for a listed file: an iCloud stub goes by the file it stands for
+pub fn listed_name(path: &Path, name: String) -> String {
+    icloud_stub_target(path)
+        .and_then(|target| target.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or(name)
+}
+
+/// Whether `path` is a cloud file whose content isn't on disk
+pub fn is_placeholder(path: &Path, meta: &fs::Metadata) -> bool {
+    if !meta.is_file() {
+        return false;
+    }
+    icloud_stub_target(path).is_some() || has_placeholder_attributes(meta)
+}
+
+#[cfg(windows)]
+fn has_placeholder_attributes(meta: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+    meta.file_attributes() & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS) != 0
+}
+
+/// File Provider files (iCloud, Dropbox, OneDrive) that were evicted
+#[cfg(target_os = "macos")]
+fn has_placeholder_attributes(meta: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x4000_0000;
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn has_placeholder_attributes(_meta: &fs::Metadata) -> bool {
+    false
+}
+
+/// Ask the cloud provider to download a placeholder, once per session.
+/// Returns without waiting for the download.
+pub fn request_download(path: &Path) {
+    let target = icloud_stub_target(path).unwrap_or_else(|| path.to_path_buf());
+    if !REQUESTED.lock().unwrap_or_else(|e| e.into_inner()).insert(target.clone()) {
+        return;
+    }
+    std::thread::spawn(move || {
+        #[cfg(target_os = "macos")]
+        if std::process::Command::new("brctl").arg("download").arg(&target).status().is_ok_and(|s| s.success()) {
+            return;
+        }
+        // Other providers hydrate a placeholder when its content is read
+        if let Ok(mut file) = fs::File::open(&target) {
+            let _ = std::io::Read::read(&mut file, &mut [0u8; 1]);
+        }
+    });
+}
+
+/// What a walk should do with an entry. Placeholders are only read with
+/// `Read`; `Stub` entries are listed by metadata alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Read,
+    Stub,
+    Skip,
+}
+
+/// Applies the placeholder policy and symlink rules to a vault walk
+pub struct VaultWalk {
+    root: PathBuf,
+    policy: PlaceholderPolicy,
+    /// Targets of symlinks followed so far
+    followed: HashSet<PathBuf>,
+}
+
+impl VaultWalk {
+    pub fn new(root: &Path) -> Self {
+        let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        Self { policy: policy(&root), root, followed: HashSet::new() }
+    }
+
+    /// A walker over `root` that follows symlinks; pair it with `visit`
+    pub fn walker(root: &Path) -> WalkDir {
+        WalkDir::new(root).follow_links(true)
+    }
+
+    /// Whether a symlink at `path` leads somewhere not walked yet
+    pub fn follow_link(&mut self, path: &Path) -> bool {
+        let Ok(target) = fs::canonicalize(path) else {
+            return false;
+        };
+        !target.starts_with(&self.root) && self.followed.insert(target)
+    }
+
+    /// Decide on a walked entry. Call it from `filter_entry` so skipped
+    /// symlinked folders aren't descended into.
+    pub fn visit(&mut self, entry: &DirEntry) -> Visit {
+        if entry.depth() > 0 && entry.path_is_symlink() && !self.follow_link(entry.path()) {
+            return Visit::Skip;
+        }
+        match entry.metadata() {
+            Ok(meta) => self.visit_path(entry.path(), &meta),
+            Err(_) => Visit::Skip,
+        }
+    }
+
+    /// Decide on a path by its (followed) metadata
+    pub fn visit_path(&self, path: &Path, meta: &fs::Metadata) -> Visit {
+        if !is_placeholder(path, meta) {
+            return Visit::Read;
+        }
+        match self.policy {
+            PlaceholderPolicy::Stub => Visit::Stub,
+            PlaceholderPolicy::Ignore => Visit::Skip,
+            PlaceholderPolicy::Download => {
+                request_download(path);
+                Visit::Skip
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icloud_stub_detection_and_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let stub = dir.path().join(".Note.md.icloud");
+        fs::write(&stub, "plist").unwrap();
+        assert_eq!(icloud_stub_target(&stub), Some(dir.path().join("Note.md")));
+        assert_eq!(icloud_stub_target(&dir.path().join("Note.md")), None);
+        assert!(is_placeholder(&stub, &fs::metadata(&stub).unwrap()));
+
+        assert_eq!(VaultWalk::new(dir.path()).visit_path(&stub, &fs::metadata(&stub).unwrap()), Visit::Stub);
+        fs::create_dir(dir.path().join(".lokus")).unwrap();
+        fs::write(dir.path().join(".lokus/settings.json"), r#"{"cloud_placeholders": "ignore"}"#).unwrap();
+        assert_eq!(policy(dir.path()), PlaceholderPolicy::Ignore);
+        assert_eq!(VaultWalk::new(dir.path()).visit_path(&stub, &fs::metadata(&stub).unwrap()), Visit::Skip);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_walked_once_without_loops() {
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("shared.md"), "x").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir(root.join("notes")).unwrap();
+        fs::write(root.join("notes/a.md"), "a").unwrap();
+        std::os::unix::fs::symlink(root.join("notes"), root.join("notes/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("notes"), root.join("alias")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("ext")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("ext2")).unwrap();
+
+        let mut walk = VaultWalk::new(root);
+        let mut files: Vec<String> = VaultWalk::walker(root)
+            .into_iter()
+            .filter_entry(|e| walk.visit(e) != Visit::Skip)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| crate::links::relative_path(root, e.path()))
+            .collect();
+        files.sort();
+        // Only one of the two links to the outside folder is walked
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0], "notes/a.md");
+        assert!(files[1] == "ext/shared.md" || files[1] == "ext2/shared.md");
+    }
+}
@@ -0,0 +1,227 @@
+//! System tray / menubar icon.
+//!
+//! Left-click shows the main window; the menu has quick capture, today's
+//! daily note, sync now, the open vault's recent files and a sync status
+//! line that follows git auto-sync and remote sync status events. With the
+//! `keep_in_tray` setting on (the default), closing windows hides them and
+//! Lokus keeps running here until "Quit Lokus".
+
+use std::sync::Mutex;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Listener, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+/// Settings key; missing means keep running
+const KEEP_IN_TRAY_KEY: &str = "keep_in_tray";
+const RECENT_LIMIT: usize = 10;
+const RECENT_PREFIX: &str = "recent:";
+
+struct TrayState {
+    status: MenuItem<Wry>,
+    recent: Submenu<Wry>,
+    /// Paths behind the recent file items, by index
+    recent_paths: Mutex<Vec<String>>,
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn set_status(app: &AppHandle, text: &str) {
+    let label = format!("Sync: {}", text);
+    if let Some(state) = app.try_state::<TrayState>() {
+        let _ = state.status.set_text(&label);
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("Lokus — {}", label)));
+    }
+}
+
+/// Status line for a `git-autosync-status` payload
+fn git_status_text(status: &serde_json::Value) -> &'static str {
+    match status["state"].as_str().unwrap_or_default() {
+        "idle" => "Up to date",
+        "pending" => "Changes pending",
+        "committing" => "Committing…",
+        "pushing" => "Pushing…",
+        "retrying" => "Retrying push",
+        "paused" => "Paused (merge conflict)",
+        _ => "Off",
+    }
+}
+
+/// Status line for a `remote-sync-status` payload
+fn remote_status_text(status: &serde_json::Value) -> &'static str {
+    if status["syncing"].as_bool() == Some(true) {
+        "Syncing…"
+    } else if !status["lastError"].is_null() {
+        "Error"
+    } else if !status["configured"].as_bool().unwrap_or(false) {
+        "Off"
+    } else {
+        "Up to date"
+    }
+}
+
+fn file_label(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Rebuild the recent files submenu from the open vault's session
+pub fn refresh_recent(app: &AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let files: Vec<String> = crate::settings::current_workspace(app)
+        .and_then(|workspace| crate::load_session_state(app.clone(), workspace.to_string_lossy().to_string()))
+        .map(|session| session.recent_files)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| std::path::Path::new(path).is_file())
+        .take(RECENT_LIMIT)
+        .collect();
+
+    while let Ok(Some(_)) = state.recent.remove_at(0) {}
+    if files.is_empty() {
+        if let Ok(item) = MenuItem::with_id(app, "recent:none", "No Recent Files", false, None::<&str>) {
+            let _ = state.recent.append(&item);
+        }
+    }
+    for (i, path) in files.iter().enumerate() {
+        if let Ok(item) = MenuItem::with_id(app, format!("{}{}", RECENT_PREFIX, i), file_label(path), true, None::<&str>) {
+            let _ = state.recent.append(&item);
+        }
+    }
+    *state.recent_paths.lock().unwrap_or_else(|e| e.into_inner()) = files;
+}
+
+fn sync_now(app: &AppHandle) {
+    let Some(root) = crate::settings::current_workspace(app) else {
+        crate::notifications::send_notification(app, "Sync", "Open a vault first");
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let workspace = root.to_string_lossy().to_string();
+        let remote = crate::sync::remote::remote_sync_status(workspace.clone()).await.map(|s| s.configured);
+        let result = if remote.unwrap_or(false) {
+            crate::sync::remote::remote_sync_now(app.clone(), workspace).await.map(|_| ()).map_err(String::from)
+        } else {
+            let task_app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || crate::sync::autosync::sync_now(&task_app, &root))
+                .await
+                .unwrap_or_else(|e| Err(format!("Sync failed: {}", e)))
+        };
+        if let Err(e) = result {
+            crate::notifications::send_notification(&app, "Sync failed", &e);
+        }
+    });
+}
+
+fn on_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "show_window" => show_main_window(app),
+        "quick_capture" => {
+            if let Err(e) = crate::capture::open_capture_window(app.clone()) {
+                eprintln!("[Tray] {}", e);
+            }
+        }
+        "daily_note" => crate::deep_link::handle(app, "lokus://daily"),
+        "sync_now" => sync_now(app),
+        "quit" => app.exit(0),
+        _ => {
+            let Some(index) = id.strip_prefix(RECENT_PREFIX).and_then(|i| i.parse::<usize>().ok()) else {
+                return;
+            };
+            let path = app
+                .try_state::<TrayState>()
+                .and_then(|state| state.recent_paths.lock().unwrap_or_else(|e| e.into_inner()).get(index).cloned());
+            if let Some(path) = path {
+                crate::deep_link::handle(app, &crate::deep_link::note_link(&path));
+            }
+        }
+    }
+}
+
+/// Create the tray icon and keep its status line current
+pub fn init(app: &mut tauri::App) -> tauri::Result<()> {
+    let show_item = MenuItem::with_id(app, "show_window", "Show Window", true, None::<&str>)?;
+    let capture_item = MenuItem::with_id(app, "quick_capture", "Quick Capture", true, None::<&str>)?;
+    let daily_item = MenuItem::with_id(app, "daily_note", "Open Daily Note", true, None::<&str>)?;
+    let recent = Submenu::with_id(app, "recent", "Recent Files", true)?;
+    let sync_item = MenuItem::with_id(app, "sync_now", "Sync Now", true, None::<&str>)?;
+    let status = MenuItem::with_id(app, "sync_status", "Sync: Off", false, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit Lokus", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &show_item,
+            &PredefinedMenuItem::separator(app)?,
+            &capture_item,
+            &daily_item,
+            &recent,
+            &PredefinedMenuItem::separator(app)?,
+            &sync_item,
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &quit_item,
+        ],
+    )?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(app.default_window_icon().cloned().unwrap())
+        .tooltip("Lokus")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        })
+        .on_menu_event(|app, event| on_menu_event(app, event.id.as_ref()))
+        .build(app)?;
+
+    app.manage(TrayState { status, recent, recent_paths: Mutex::new(Vec::new()) });
+    refresh_recent(app.handle());
+
+    let handle = app.handle().clone();
+    app.listen(crate::sync::autosync::STATUS_EVENT, move |event| {
+        if let Ok(status) = serde_json::from_str::<serde_json::Value>(event.payload()) {
+            set_status(&handle, git_status_text(&status));
+        }
+    });
+    let handle = app.handle().clone();
+    app.listen(crate::sync::remote::STATUS_EVENT, move |event| {
+        if let Ok(status) = serde_json::from_str::<serde_json::Value>(event.payload()) {
+            set_status(&handle, remote_status_text(&status));
+        }
+    });
+    Ok(())
+}
+
+/// Whether closing every window should leave Lokus running in the tray
+pub fn keep_running(app: &AppHandle) -> bool {
+    crate::settings::effective(app, None, KEEP_IN_TRAY_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
+
+/// Release the tray icon and global shortcuts before the process exits
+pub fn cleanup(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+    let _ = app.global_shortcut().unregister_all();
+    let _ = app.remove_tray_by_id(TRAY_ID);
+}
+
@@ -0,0 +1,531 @@
+//! Multi-vault registry.
+//!
+//! Every workspace the user opens is recorded in `~/.lokus/vaults.json` with a
+//! stable id, display name and its own settings object, so the workspace
+//! switcher can list all vaults and per-vault preferences never leak between
+//! them. On macOS each vault also keeps its own security-scoped bookmark.
+//!
+//! A vault can be marked read-only (a shared drive, published reference
+//! material); the file handlers, and every other command that changes notes
+//! (canvas, CSV, tags, frontmatter, link rewrites, trash, encryption), then
+//! refuse to write, rename or delete anything inside it, failing with an error
+//! starting with [`READ_ONLY_ERROR`].
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const REGISTRY_VERSION: u32 = 1;
+/// Prefix of the error returned for changes to a read-only vault
+pub const READ_ONLY_ERROR: &str = "VAULT_READ_ONLY";
+
+/// Serializes read-modify-write cycles on the registry file
+static REGISTRY_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+/// Roots of read-only vaults; `None` until read from the registry, and again after it changes
+static READ_ONLY_ROOTS: Lazy<Mutex<Option<Vec<PathBuf>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vault {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub created_at: i64,
+    #[serde(default)]
+    pub last_opened_at: Option<i64>,
+    #[serde(default = "empty_settings")]
+    pub settings: serde_json::Value,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+fn empty_settings() -> serde_json::Value {
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultRegistry {
+    version: u32,
+    vaults: Vec<Vault>,
+    /// Base64 security-scoped bookmarks keyed by vault id (macOS only)
+    #[serde(default)]
+    bookmarks: HashMap<String, String>,
+}
+
+impl Default for VaultRegistry {
+    fn default() -> Self {
+        Self {
+            version: REGISTRY_VERSION,
+            vaults: Vec::new(),
+            bookmarks: HashMap::new(),
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn registry_path() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join(".lokus").join("vaults.json"))
+        .ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+fn vault_name_from_path(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Compare paths ignoring trailing separators
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(&['/', '\\'][..]) == b.trim_end_matches(&['/', '\\'][..])
+}
+
+impl VaultRegistry {
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create registry directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize vault registry: {}", e))?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json).map_err(|e| format!("Failed to write vault registry: {}", e))?;
+        fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace vault registry: {}", e))
+    }
+
+    fn find_mut(&mut self, id: &str) -> Result<&mut Vault, String> {
+        self.vaults
+            .iter_mut()
+            .find(|v| v.id == id)
+            .ok_or_else(|| format!("Vault '{}' not found", id))
+    }
+
+    /// Add a vault for `path`, or refresh the existing entry's last-opened time
+    fn register(&mut self, path: &str, name: Option<String>) -> Vault {
+        if let Some(existing) = self.vaults.iter_mut().find(|v| same_path(&v.path, path)) {
+            existing.last_opened_at = Some(now_ms());
+            if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+                existing.name = name.trim().to_string();
+            }
+            return existing.clone();
+        }
+
+        let now = now_ms();
+        let vault = Vault {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name
+                .filter(|n| !n.trim().is_empty())
+                .map(|n| n.trim().to_string())
+                .unwrap_or_else(|| vault_name_from_path(path)),
+            path: path.to_string(),
+            created_at: now,
+            last_opened_at: Some(now),
+            settings: empty_settings(),
+            read_only: false,
+        };
+        self.vaults.push(vault.clone());
+        vault
+    }
+
+    /// Shallow-merge `settings` into the vault's settings; `null` removes a key
+    fn merge_settings(&mut self, id: &str, settings: serde_json::Value) -> Result<Vault, String> {
+        let serde_json::Value::Object(updates) = settings else {
+            return Err("Vault settings must be a JSON object".to_string());
+        };
+
+        let vault = self.find_mut(id)?;
+        if !vault.settings.is_object() {
+            vault.settings = empty_settings();
+        }
+        let current = vault.settings.as_object_mut().expect("settings is an object");
+        for (key, value) in updates {
+            if value.is_null() {
+                current.remove(&key);
+            } else {
+                current.insert(key, value);
+            }
+        }
+        Ok(vault.clone())
+    }
+
+    fn read_only_roots(&self) -> Vec<PathBuf> {
+        self.vaults
+            .iter()
+            .filter(|v| v.read_only)
+            .map(|v| match v.path.trim_end_matches(&['/', '\\'][..]) {
+                "" => PathBuf::from(&v.path),
+                trimmed => PathBuf::from(trimmed),
+            })
+            .collect()
+    }
+
+    fn sorted(&self) -> Vec<Vault> {
+        let mut vaults = self.vaults.clone();
+        vaults.sort_by(|a, b| b.last_opened_at.cmp(&a.last_opened_at).then_with(|| a.name.cmp(&b.name)));
+        vaults
+    }
+}
+
+/// Load the registry, apply `f`, and save it back under the registry lock
+fn with_registry<T>(f: impl FnOnce(&mut VaultRegistry) -> Result<T, String>) -> Result<T, String> {
+    let _guard = REGISTRY_LOCK
+        .lock()
+        .map_err(|e| format!("Vault registry lock poisoned: {}", e))?;
+    let path = registry_path()?;
+    let mut registry = VaultRegistry::load_from(&path);
+    let result = f(&mut registry)?;
+    registry.save_to(&path)?;
+    *READ_ONLY_ROOTS.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    crate::path_policy::invalidate();
+    Ok(result)
+}
+
+fn read_registry() -> Result<VaultRegistry, String> {
+    let _guard = REGISTRY_LOCK
+        .lock()
+        .map_err(|e| format!("Vault registry lock poisoned: {}", e))?;
+    Ok(VaultRegistry::load_from(&registry_path()?))
+}
+
+#[cfg(target_os = "macos")]
+fn store_bookmark(registry: &mut VaultRegistry, vault: &Vault) {
+    use base64::{engine::general_purpose, Engine as _};
+    match crate::macos::bookmarks::create_bookmark(&vault.path) {
+        Ok(data) => {
+            registry.bookmarks.insert(vault.id.clone(), general_purpose::STANDARD.encode(data));
+        }
+        Err(e) => tracing::warn!("Failed to create bookmark for vault {}: {}", vault.name, e),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn store_bookmark(_registry: &mut VaultRegistry, _vault: &Vault) {}
+
+/// Record a vault for `path`; used by the command and when a workspace is opened
+pub fn register_vault(path: &str, name: Option<String>) -> Result<Vault, String> {
+    with_registry(|registry| {
+        let vault = registry.register(path, name);
+        store_bookmark(registry, &vault);
+        Ok(vault)
+    })
+}
+
+/// Refuse folders that would open up every file to the file commands once
+/// registered: the filesystem root, the home folder and anything above it
+pub(crate) fn check_vault_folder(path: &Path) -> Result<(), String> {
+    let resolved = path
+        .canonicalize()
+        .ok()
+        .filter(|resolved| resolved.is_dir())
+        .ok_or_else(|| format!("Vault folder does not exist: {}", path.display()))?;
+    let home = dirs::home_dir().and_then(|home| home.canonicalize().ok());
+    if resolved.parent().is_none() || home.is_some_and(|home| home.starts_with(&resolved)) {
+        return Err(format!("{} is too broad to be a vault", path.display()));
+    }
+    Ok(())
+}
+
+/// The read-only vault containing `path`, if any
+fn read_only_root(roots: &[PathBuf], path: &Path) -> Option<PathBuf> {
+    roots.iter().find(|root| path.starts_with(root)).cloned()
+}
+
+/// `path` with symlinks and `..` resolved, for paths that may not exist yet:
+/// the longest existing ancestor is canonicalized and the rest appended
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    let mut resolved = loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            break canonical;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => break existing.to_path_buf(),
+        }
+    };
+    for component in rest.iter().rev() {
+        if component == ".." {
+            resolved.pop();
+        } else if component != "." {
+            resolved.push(component);
+        }
+    }
+    resolved
+}
+
+/// Fail with [`READ_ONLY_ERROR`] if `path` is inside a read-only vault
+pub(crate) fn ensure_writable(path: &Path) -> Result<(), String> {
+    let cached = READ_ONLY_ROOTS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    // Read outside the lock, which `with_registry` takes while holding the registry lock
+    let roots = cached.unwrap_or_else(|| {
+        let roots: Vec<PathBuf> = read_registry()
+            .map(|r| r.read_only_roots())
+            .unwrap_or_default()
+            .iter()
+            .map(|root| resolve(root))
+            .collect();
+        *READ_ONLY_ROOTS.lock().unwrap_or_else(|e| e.into_inner()) = Some(roots.clone());
+        roots
+    });
+    match read_only_root(&roots, &resolve(path)) {
+        Some(root) => Err(format!(
+            "{}: {} is in a read-only vault ({})",
+            READ_ONLY_ERROR,
+            path.display(),
+            root.display()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// All registered vaults, most recently opened first
+pub fn list_vaults() -> Vec<Vault> {
+    read_registry().map(|r| r.sorted()).unwrap_or_default()
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn vault_register(path: String, name: Option<String>) -> Result<Vault, String> {
+    check_vault_folder(Path::new(&path))?;
+    register_vault(&path, name)
+}
+
+#[tauri::command]
+pub fn vault_list() -> Result<Vec<Vault>, String> {
+    Ok(read_registry()?.sorted())
+}
+
+#[tauri::command]
+pub fn vault_rename(id: String, name: String) -> Result<Vault, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Vault name cannot be empty".to_string());
+    }
+    with_registry(|registry| {
+        let vault = registry.find_mut(&id)?;
+        vault.name = name;
+        Ok(vault.clone())
+    })
+}
+
+/// Forget a vault. The folder on disk is left untouched.
+#[tauri::command]
+pub fn vault_remove(id: String) -> Result<(), String> {
+    with_registry(|registry| {
+        let before = registry.vaults.len();
+        registry.vaults.retain(|v| v.id != id);
+        if registry.vaults.len() == before {
+            return Err(format!("Vault '{}' not found", id));
+        }
+        registry.bookmarks.remove(&id);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn vault_set_settings(id: String, settings: serde_json::Value) -> Result<Vault, String> {
+    with_registry(|registry| registry.merge_settings(&id, settings))
+}
+
+#[tauri::command]
+pub fn vault_get_settings(id: String) -> Result<serde_json::Value, String> {
+    let mut registry = read_registry()?;
+    Ok(registry.find_mut(&id)?.settings.clone())
+}
+
+/// Mark the vault at `path` read-only or writable, registering it if needed
+#[tauri::command]
+pub fn vault_set_readonly(path: String, readonly: bool) -> Result<Vault, String> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Vault folder does not exist: {}", path));
+    }
+    with_registry(|registry| {
+        let id = match registry.vaults.iter().find(|v| same_path(&v.path, &path)) {
+            Some(vault) => vault.id.clone(),
+            None => registry.register(&path, None).id,
+        };
+        let vault = registry.find_mut(&id)?;
+        vault.read_only = readonly;
+        Ok(vault.clone())
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadOnlyReason {
+    /// Marked with `vault_set_readonly`
+    Vault,
+    /// The folder itself can't be written to
+    Filesystem,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceCapabilities {
+    pub read_only: bool,
+    pub reason: Option<ReadOnlyReason>,
+    pub can_write: bool,
+    pub can_rename: bool,
+    pub can_delete: bool,
+}
+
+/// What the frontend may do in the workspace at `path`
+#[tauri::command]
+pub fn get_workspace_capabilities(path: String) -> Result<WorkspaceCapabilities, String> {
+    let metadata = fs::metadata(&path).map_err(|e| format!("Workspace is not accessible: {}", e))?;
+    let reason = if ensure_writable(Path::new(&path)).is_err() {
+        Some(ReadOnlyReason::Vault)
+    } else if metadata.permissions().readonly() {
+        Some(ReadOnlyReason::Filesystem)
+    } else {
+        None
+    };
+    let writable = reason.is_none();
+    Ok(WorkspaceCapabilities {
+        read_only: !writable,
+        reason,
+        can_write: writable,
+        can_rename: writable,
+        can_delete: writable,
+    })
+}
+
+/// Resolve a vault's path for opening, restoring security-scoped access from
+/// its bookmark on macOS, and mark it as most recently opened.
+#[tauri::command]
+pub fn vault_open(id: String) -> Result<String, String> {
+    with_registry(|registry| {
+        #[allow(unused_mut)]
+        let mut path = registry.find_mut(&id)?.path.clone();
+
+        #[cfg(target_os = "macos")]
+        if let Some(encoded) = registry.bookmarks.get(&id) {
+            use base64::{engine::general_purpose, Engine as _};
+            if let Ok(data) = general_purpose::STANDARD.decode(encoded) {
+                match crate::macos::bookmarks::resolve_bookmark(&data) {
+                    // Access stays open for the session, like the last-workspace bookmark
+                    Ok(resolved) => path = resolved,
+                    Err(e) => tracing::warn!("Failed to resolve bookmark for vault {}: {}", id, e),
+                }
+            }
+        }
+
+        if !crate::validate_path_internal(&path) {
+            return Err(format!("Vault folder is not accessible: {}", path));
+        }
+
+        let vault = registry.find_mut(&id)?;
+        vault.path = path.clone();
+        vault.last_opened_at = Some(now_ms());
+        Ok(path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_is_idempotent_per_path() {
+        let mut registry = VaultRegistry::default();
+        let first = registry.register("/notes/work", None);
+        let second = registry.register("/notes/work/", Some("Work".to_string()));
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.name, "Work");
+        assert_eq!(registry.vaults.len(), 1);
+        assert_eq!(first.name, "work");
+    }
+
+    #[test]
+    fn test_settings_are_isolated_and_merged() {
+        let mut registry = VaultRegistry::default();
+        let a = registry.register("/a", None);
+        let b = registry.register("/b", None);
+
+        registry.merge_settings(&a.id, serde_json::json!({ "theme": "dark", "font": 14 })).unwrap();
+        let a = registry.merge_settings(&a.id, serde_json::json!({ "font": null })).unwrap();
+        assert_eq!(a.settings, serde_json::json!({ "theme": "dark" }));
+
+        let b = registry.find_mut(&b.id).unwrap().clone();
+        assert_eq!(b.settings, serde_json::json!({}));
+        assert!(registry.merge_settings(&b.id, serde_json::json!(["x"])).is_err());
+    }
+
+    #[test]
+    fn test_read_only_vaults_cover_their_contents() {
+        let mut registry = VaultRegistry::default();
+        let shared = registry.register("/mnt/shared/", None);
+        registry.register("/notes", None);
+        registry.find_mut(&shared.id).unwrap().read_only = true;
+
+        let roots = registry.read_only_roots();
+        assert_eq!(roots, vec![PathBuf::from("/mnt/shared")]);
+        assert!(read_only_root(&roots, Path::new("/mnt/shared/a/note.md")).is_some());
+        assert!(read_only_root(&roots, Path::new("/mnt/shared")).is_some());
+        assert!(read_only_root(&roots, Path::new("/mnt/shared-other/note.md")).is_none());
+        assert!(read_only_root(&roots, Path::new("/notes/note.md")).is_none());
+    }
+
+    #[test]
+    fn test_broad_folders_cannot_be_vaults() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(check_vault_folder(dir.path()).is_ok());
+        assert!(check_vault_folder(&dir.path().join("missing")).is_err());
+        assert!(check_vault_folder(Path::new("/")).is_err());
+        if let Some(home) = dirs::home_dir() {
+            assert!(check_vault_folder(&home).is_err());
+        }
+    }
+
+    #[test]
+    fn test_resolve_follows_dot_dot_and_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared = dir.path().join("shared");
+        fs::create_dir_all(shared.join("a")).unwrap();
+        let shared = shared.canonicalize().unwrap();
+        let roots = vec![shared.clone()];
+
+        let sneaky = dir.path().join("other/../shared/a/new.md");
+        assert_eq!(resolve(&sneaky), shared.join("a/new.md"));
+        assert!(read_only_root(&roots, &resolve(&sneaky)).is_some());
+
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&shared, &link).unwrap();
+            assert!(read_only_root(&roots, &resolve(&link.join("a/new.md"))).is_some());
+        }
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vaults.json");
+        let mut registry = VaultRegistry::default();
+        registry.register("/a", Some("Alpha".to_string()));
+        registry.save_to(&path).unwrap();
+
+        let loaded = VaultRegistry::load_from(&path);
+        assert_eq!(loaded.vaults.len(), 1);
+        assert_eq!(loaded.vaults[0].name, "Alpha");
+    }
+}
@@ -0,0 +1,406 @@
+//! Workspace file watcher.
+//!
+//! Watches the open workspace recursively and emits debounced
+//! `workspace-file-changed` events so the file tree and editor can react to
+//! edits made by other apps. External changes are also fed into the search
+//! index and link graph so they never go stale while the app is running.
+
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const FILE_CHANGED_EVENT: &str = "workspace-file-changed";
+
+/// Quiet period after the last event before a batch is emitted
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// Upper bound on how long a change can be held back during a burst
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+const EXCLUDED_DIRS: &[&str] = &[".lokus", ".git", "node_modules"];
+/// Editor swap files and the temp/backup files written by atomic saves
+const IGNORED_SUFFIXES: &[&str] = &[".tmp", ".tmp_sync", ".backup", ".swp", "~", ".DS_Store"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    pub old_path: Option<String>,
+    pub is_directory: bool,
+}
+
+struct PendingChange {
+    kind: ChangeKind,
+    old_path: Option<PathBuf>,
+}
+
+struct ActiveWatcher {
+    root: PathBuf,
+    // Dropping the watcher closes the event channel, which stops the debounce thread
+    _watcher: RecommendedWatcher,
+}
+
+static ACTIVE_WATCHER: Lazy<Mutex<Option<ActiveWatcher>>> = Lazy::new(|| Mutex::new(None));
+
+// --- Filtering ---
+
+fn is_ignored(root: &Path, path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if IGNORED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        return true;
+    }
+
+    path.strip_prefix(root)
+        .map(|rel| {
+            rel.components().any(|c| {
+                c.as_os_str()
+                    .to_str()
+                    .map_or(false, |s| EXCLUDED_DIRS.contains(&s))
+            })
+        })
+        .unwrap_or(true)
+}
+
+// --- Debouncing ---
+
+/// Fold a new change for `path` into whatever is already pending for it
+fn merge(pending: &mut HashMap<PathBuf, PendingChange>, path: PathBuf, kind: ChangeKind) {
+    let previous = pending.remove(&path);
+    let merged = match (previous.as_ref().map(|p| p.kind), kind) {
+        // Created and removed within one window: nothing to report
+        (Some(ChangeKind::Created), ChangeKind::Deleted) => None,
+        (Some(ChangeKind::Created), ChangeKind::Modified) => Some(ChangeKind::Created),
+        // Delete + create is how many editors save; report it as a modification
+        (Some(ChangeKind::Deleted), ChangeKind::Created) => Some(ChangeKind::Modified),
+        (Some(ChangeKind::Renamed), ChangeKind::Modified) => Some(ChangeKind::Renamed),
+        (Some(ChangeKind::Renamed), ChangeKind::Deleted) => {
+            // The renamed file is gone again; report the original path as deleted
+            if let Some(old_path) = previous.and_then(|p| p.old_path) {
+                pending.insert(old_path, PendingChange { kind: ChangeKind::Deleted, old_path: None });
+            }
+            return;
+        }
+        (_, kind) => Some(kind),
+    };
+
+    if let Some(kind) = merged {
+        let old_path = if kind == ChangeKind::Renamed {
+            previous.and_then(|p| p.old_path)
+        } else {
+            None
+        };
+        pending.insert(path, PendingChange { kind, old_path });
+    }
+}
+
+fn merge_rename(root: &Path, pending: &mut HashMap<PathBuf, PendingChange>, from: PathBuf, to: PathBuf) {
+    // Atomic save by another app: temp file renamed over the real one
+    if is_ignored(root, &from) {
+        merge(pending, to, ChangeKind::Modified);
+        return;
+    }
+
+    match pending.remove(&from) {
+        Some(change) if change.kind == ChangeKind::Created => {
+            merge(pending, to, ChangeKind::Created);
+        }
+        Some(change) if change.kind == ChangeKind::Renamed => {
+            pending.insert(to, PendingChange { kind: ChangeKind::Renamed, old_path: change.old_path });
+        }
+        _ => {
+            pending.insert(to, PendingChange { kind: ChangeKind::Renamed, old_path: Some(from) });
+        }
+    }
+}
+
+/// Report `path` under the workspace path as it was opened. Events carry the
+/// resolved path, which differs when the root is reached through a symlink
+/// (macOS `/var` is `/private/var`).
+fn rebase(watched: &Path, root: &Path, path: PathBuf) -> PathBuf {
+    match path.strip_prefix(watched) {
+        Ok(rel) if watched != root => root.join(rel),
+        _ => path,
+    }
+}
+
+fn record(root: &Path, pending: &mut HashMap<PathBuf, PendingChange>, event: Event) {
+    let mut paths = event.paths.into_iter();
+    match event.kind {
+        EventKind::Create(_) => paths.for_each(|p| merge(pending, p, ChangeKind::Created)),
+        EventKind::Remove(_) => paths.for_each(|p| merge(pending, p, ChangeKind::Deleted)),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+                merge_rename(root, pending, from, to);
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            paths.for_each(|p| merge(pending, p, ChangeKind::Deleted))
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            paths.for_each(|p| merge(pending, p, ChangeKind::Created))
+        }
+        // Backends that can't pair rename halves (e.g. FSEvents): infer from existence
+        EventKind::Modify(ModifyKind::Name(_)) => paths.for_each(|p| {
+            let kind = if p.exists() { ChangeKind::Created } else { ChangeKind::Deleted };
+            merge(pending, p, kind)
+        }),
+        // Permission and access-time updates don't change content
+        EventKind::Modify(ModifyKind::Metadata(_)) => {}
+        EventKind::Modify(_) => paths.for_each(|p| merge(pending, p, ChangeKind::Modified)),
+        _ => {}
+    }
+}
+
+fn is_text_note(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("txt"))
+}
+
+/// Keep the search index and link graph in sync with an external change.
+/// External renames are treated as delete + create: rewriting links is only
+/// done for renames initiated inside Lokus.
+fn update_indexes(change: &WorkspaceFileChange) {
+    if let Some(old_path) = &change.old_path {
+        crate::search::index::notify_file_removed(old_path);
+        crate::links::notify_file_removed(old_path);
+        crate::metadata_cache::notify_file_removed(old_path);
+        crate::embeddings::notify_file_removed(old_path);
+        crate::thumbnails::notify_file_changed(old_path);
+    }
+
+    match change.kind {
+        ChangeKind::Deleted => {
+            crate::search::index::notify_file_removed(&change.path);
+            crate::links::notify_file_removed(&change.path);
+            crate::metadata_cache::notify_file_removed(&change.path);
+            crate::embeddings::notify_file_removed(&change.path);
+            crate::thumbnails::notify_file_changed(&change.path);
+        }
+        ChangeKind::Created | ChangeKind::Modified | ChangeKind::Renamed => {
+            let files: Vec<PathBuf> = if change.is_directory {
+                walkdir::WalkDir::new(&change.path)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .map(|e| e.into_path())
+                    .collect()
+            } else {
+                vec![PathBuf::from(&change.path)]
+            };
+
+            if change.is_directory {
+                crate::metadata_cache::notify_file_saved(&change.path, "");
+            }
+            for file in &files {
+                if !is_text_note(file) {
+                    crate::metadata_cache::notify_file_saved(&file.to_string_lossy(), "");
+                    crate::pdf::index::notify_file_changed(&file.to_string_lossy());
+                    crate::thumbnails::notify_file_changed(&file.to_string_lossy());
+                    if crate::canvas::is_canvas(file) {
+                        if let Ok(content) = std::fs::read_to_string(file) {
+                            crate::links::notify_file_saved(&file.to_string_lossy(), &content);
+                        }
+                    }
+                    continue;
+                }
+                if let Ok(content) = std::fs::read_to_string(file) {
+                    let file = file.to_string_lossy();
+                    crate::search::index::notify_file_saved(&file, &content);
+                    crate::links::notify_file_saved(&file, &content);
+                    crate::metadata_cache::notify_file_saved(&file, &content);
+                    crate::embeddings::notify_file_changed(&file);
+                }
+            }
+        }
+    }
+}
+
+fn flush(app: &AppHandle, root: &Path, pending: &mut HashMap<PathBuf, PendingChange>) {
+    let mut changed = false;
+    for (path, change) in pending.drain() {
+        if is_ignored(root, &path) {
+            continue;
+        }
+
+        let payload = WorkspaceFileChange {
+            kind: change.kind,
+            path: path.to_string_lossy().to_string(),
+            old_path: change.old_path.map(|p| p.to_string_lossy().to_string()),
+            is_directory: path.is_dir(),
+        };
+
+        update_indexes(&payload);
+        if payload.kind != ChangeKind::Deleted && !payload.is_directory && is_text_note(&path) {
+            crate::task_sync::notify_note_saved(app, &payload.path);
+        }
+        if payload.kind == ChangeKind::Created && !payload.is_directory {
+            crate::transcription::memos::notify_audio_added(app, &path);
+        }
+        changed = true;
+        if let Err(e) = app.emit(FILE_CHANGED_EVENT, &payload) {
+            tracing::warn!("Failed to emit {}: {}", FILE_CHANGED_EVENT, e);
+        }
+    }
+
+    // Cached smart folder results are stale now; let the sidebar re-evaluate
+    if changed {
+        crate::sync::autosync::notify_changed(root);
+        if let Err(e) = app.emit(crate::smart_folders::CHANGED_EVENT, root.to_string_lossy().to_string()) {
+            tracing::warn!("Failed to emit {}: {}", crate::smart_folders::CHANGED_EVENT, e);
+        }
+    }
+}
+
+fn run_debouncer(app: AppHandle, root: PathBuf, watched: PathBuf, rx: Receiver<notify::Result<Event>>) {
+    let mut pending: HashMap<PathBuf, PendingChange> = HashMap::new();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        match rx.recv_timeout(DEBOUNCE) {
+            Ok(Ok(mut event)) => {
+                event.paths = event.paths.into_iter().map(|p| rebase(&watched, &root, p)).collect();
+                record(&root, &mut pending, event);
+                let started = *batch_started.get_or_insert_with(Instant::now);
+                if started.elapsed() >= MAX_DELAY {
+                    flush(&app, &root, &mut pending);
+                    batch_started = None;
+                }
+            }
+            Ok(Err(e)) => tracing::warn!("Workspace watcher error: {}", e),
+            Err(RecvTimeoutError::Timeout) => {
+                if !pending.is_empty() {
+                    flush(&app, &root, &mut pending);
+                }
+                batch_started = None;
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                flush(&app, &root, &mut pending);
+                break;
+            }
+        }
+    }
+
+    tracing::debug!("Workspace watcher stopped for {}", root.display());
+}
+
+/// Root of the workspace currently being watched, if any
+pub fn active_root() -> Option<PathBuf> {
+    ACTIVE_WATCHER.lock().ok()?.as_ref().map(|w| w.root.clone())
+}
+
+// --- Tauri Commands ---
+
+/// Start watching a workspace, replacing any watcher that is already running
+#[tauri::command]
+pub fn watch_workspace_start(app: AppHandle, path: String) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("Workspace does not exist: {}", path));
+    }
+
+    let mut active = ACTIVE_WATCHER
+        .lock()
+        .map_err(|e| format!("Watcher lock poisoned: {}", e))?;
+    if active.as_ref().map_or(false, |w| w.root == root) {
+        return Ok(());
+    }
+    // Drop the previous watcher first so its thread flushes and exits
+    *active = None;
+    let watched = root.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", path, e))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    watcher
+        .watch(&watched, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", path, e))?;
+
+    let thread_root = root.clone();
+    std::thread::Builder::new()
+        .name("workspace-watcher".to_string())
+        .spawn(move || run_debouncer(app, thread_root, watched, rx))
+        .map_err(|e| format!("Failed to start watcher thread: {}", e))?;
+
+    tracing::info!("Watching workspace {}", root.display());
+    *active = Some(ActiveWatcher { root, _watcher: watcher });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn watch_workspace_stop() -> Result<(), String> {
+    let mut active = ACTIVE_WATCHER
+        .lock()
+        .map_err(|e| format!("Watcher lock poisoned: {}", e))?;
+    *active = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_collapses_transient_and_atomic_saves() {
+        let mut pending = HashMap::new();
+        let path = PathBuf::from("/ws/a.md");
+
+        merge(&mut pending, path.clone(), ChangeKind::Created);
+        merge(&mut pending, path.clone(), ChangeKind::Modified);
+        assert_eq!(pending[&path].kind, ChangeKind::Created);
+        merge(&mut pending, path.clone(), ChangeKind::Deleted);
+        assert!(pending.is_empty());
+
+        merge(&mut pending, path.clone(), ChangeKind::Deleted);
+        merge(&mut pending, path.clone(), ChangeKind::Created);
+        assert_eq!(pending[&path].kind, ChangeKind::Modified);
+    }
+
+    #[test]
+    fn test_rename_from_temp_file_is_modification() {
+        let root = PathBuf::from("/ws");
+        let mut pending = HashMap::new();
+        merge_rename(&root, &mut pending, root.join("a.md.tmp"), root.join("a.md"));
+        assert_eq!(pending[&root.join("a.md")].kind, ChangeKind::Modified);
+
+        merge_rename(&root, &mut pending, root.join("b.md"), root.join("c.md"));
+        let change = &pending[&root.join("c.md")];
+        assert_eq!(change.kind, ChangeKind::Renamed);
+        assert_eq!(change.old_path, Some(root.join("b.md")));
+    }
+
+    #[test]
+    fn test_ignored_paths() {
+        let root = Path::new("/ws");
+        assert!(is_ignored(root, Path::new("/ws/.lokus/search-index.bin")));
+        assert!(is_ignored(root, Path::new("/ws/notes/a.md.backup")));
+        assert!(is_ignored(root, Path::new("/elsewhere/a.md")));
+        assert!(!is_ignored(root, Path::new("/ws/notes/a.md")));
+    }
+
+    #[test]
+    fn test_rebase_maps_resolved_paths_to_the_workspace() {
+        let (watched, root) = (Path::new("/private/var/ws"), Path::new("/var/ws"));
+        assert_eq!(rebase(watched, root, PathBuf::from("/private/var/ws/notes/a.md")), PathBuf::from("/var/ws/notes/a.md"));
+        assert_eq!(rebase(root, root, PathBuf::from("/var/ws/a.md")), PathBuf::from("/var/ws/a.md"));
+        assert_eq!(rebase(watched, root, PathBuf::from("/elsewhere/a.md")), PathBuf::from("/elsewhere/a.md"));
+    }
+}